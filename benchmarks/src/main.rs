@@ -61,10 +61,136 @@ fn main() {
 
 	let glsp = Runtime::new();
 	glsp.run(|| {
+		glsp::bind_global("bench-label", "Glsp")?;
+		glsp::bind_rfn("rfn-add3", rfn!(|a: f32, b: f32, c: f32| a + b + c))?;
+		glsp::bind_global("loops-only?", false)?;
 		glsp::load("src/benchmarks.glsp")?;
 		Ok(())
 	}).unwrap();
 
+	//measures the overhead of glsp::set_fuel. the limit is never reached
+	let glsp = Runtime::new();
+	glsp.run(|| {
+		glsp::set_fuel(Some(u64::MAX));
+		glsp::bind_global("bench-label", "Glsp (metered)")?;
+		glsp::bind_rfn("rfn-add3", rfn!(|a: f32, b: f32, c: f32| a + b + c))?;
+		glsp::bind_global("loops-only?", false)?;
+		glsp::load("src/benchmarks.glsp")?;
+		Ok(())
+	}).unwrap();
+
+	let glsp = RuntimeBuilder::new().opt_level(0).build();
+	glsp.run(|| {
+		glsp::bind_global("bench-label", "Glsp (opt-level 0)")?;
+		glsp::bind_global("loops-only?", true)?;
+		glsp::load("src/benchmarks.glsp")?;
+		Ok(())
+	}).unwrap();
+
+	//interns one million new symbols, then looks them all up again, one at a time and in bulk
+	let glsp = Runtime::new();
+	glsp.run(|| {
+		let names: Vec<String> = (0 .. 1000_000).map(|i| format!("archetype-{}-stat", i)).collect();
+		let names: Vec<&str> = names.iter().map(|name| &name[..]).collect();
+
+		let start = Instant::now();
+		for name in &names {
+			black_box(glsp::sym(name)?);
+		}
+		let elapsed = start.elapsed().as_secs_f64() * 1000.0;
+		println!("Glsp sym_intern: {:.1}ms", elapsed);
+
+		let start = Instant::now();
+		for name in &names {
+			black_box(glsp::sym(name)?);
+		}
+		let elapsed = start.elapsed().as_secs_f64() * 1000.0;
+		println!("Glsp sym_lookup: {:.1}ms", elapsed);
+
+		let start = Instant::now();
+		black_box(glsp::syms(&names)?);
+		let elapsed = start.elapsed().as_secs_f64() * 1000.0;
+		println!("Glsp syms_lookup: {:.1}ms", elapsed);
+
+		Ok(())
+	}).unwrap();
+
+	//compares the cost of re-initializing a Runtime with a large prelude against the cost of
+	//restoring a RuntimeSnapshot which was taken just after the prelude was loaded
+	let mut prelude = String::new();
+	for i in 0 .. 3000 {
+		prelude.push_str(&format!("(defn f{0} (a b) (let ar (arr a b {0})) (+ (len ar) a b))\n", i));
+		prelude.push_str(&format!("(defclass C{0} (field n {0}) (meth inc () (inc! @n)))\n", i));
+		prelude.push_str(&format!("(def t{0} (tab ('k {0}) ('ar (arr 1 2 3))))\n", i));
+	}
+
+	let start = Instant::now();
+	let glsp = Runtime::new();
+	glsp.run(|| {
+		glsp::load_str(&prelude, "prelude.glsp")?;
+		Ok(())
+	}).unwrap();
+	let elapsed = start.elapsed().as_secs_f64() * 1000.0;
+	println!("Glsp runtime_init: {:.1}ms", elapsed);
+
+	glsp.run(|| {
+		let snapshot = glsp::runtime_snapshot()?;
+		let mutate = glsp::parse_all("(push! [t0 'ar] 4) (.inc (C1)) (def extra 1)", None)?;
+
+		let mut total = 0.0;
+		for _ in 0 .. 100 {
+			glsp::eval_multi(&mutate, None)?;
+
+			let start = Instant::now();
+			glsp::restore_runtime(&snapshot)?;
+			total += start.elapsed().as_secs_f64() * 1000.0;
+		}
+		println!("Glsp runtime_restore: {:.2}ms", total / 100.0);
+
+		Ok(())
+	}).unwrap();
+
+	//simulates 600 frames of a script which allocates lots of short-lived arrs and tabs, with
+	//a call to glsp::gc at the end of each frame. the second pass wraps each frame in a scratch
+	//scope. we report the total time and the peak size of the young generation.
+	let glsp = Runtime::new();
+	glsp.run(|| {
+		glsp::load_str(r#"
+			(def entities (arr ..(map (fn1 (tab ('x _) ('y 0))) (rn 1000))))
+
+			(defn frame ()
+			  (for e in entities
+			    (let (dx dy) (arr 1 2))
+			    (let neighbours (arr e e e))
+			    (inc! [e 'x] (* dx (len neighbours)))
+			    (inc! [e 'y] dy)))
+		"#, "scratch.glsp")?;
+
+		let frame: Root<GFn> = glsp::global("frame")?;
+
+		for &scratch in &[false, true] {
+			let mut peak = 0;
+
+			let start = Instant::now();
+			for _ in 0 .. 600 {
+				if scratch {
+					glsp::scratch_scope(|| glsp::call::<_, _, Val>(&frame, &()))?;
+				} else {
+					glsp::call::<_, _, Val>(&frame, &())?;
+				}
+
+				peak = peak.max(glsp::gc_young_bytes());
+				glsp::gc();
+			}
+			let elapsed = start.elapsed().as_secs_f64() * 1000.0;
+
+			let label = if scratch { "frames_scratch" } else { "frames" };
+			println!("Glsp {}: {:.1}ms, peak young bytes {}", label, elapsed, peak);
+		}
+
+		Ok(())
+	}).unwrap();
+
 	println!();
 
 
@@ -0,0 +1,167 @@
+/*
+A C API for the GameLisp scripting language. See https://gamelisp.rs/ for details.
+
+Each glsp_runtime must only be used by the thread which created it. None of these functions
+may be called with a null glsp_runtime.
+
+Functions which return a bool return false when an error occurs. The error's message and its
+GameLisp stack trace can then be retrieved using glsp_error_message and glsp_error_stack_trace.
+*/
+
+#ifndef GLSP_H
+#define GLSP_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* A GameLisp Runtime, including the standard library. */
+typedef struct glsp_runtime glsp_runtime;
+
+/*
+A reference-counted reference to any GameLisp value. While a handle exists, the value will not
+be garbage-collected. Every handle must be released using glsp_handle_release or
+glsp_value_release before its runtime is freed.
+*/
+typedef struct glsp_handle glsp_handle;
+
+typedef enum glsp_tag {
+	GLSP_NIL = 0,
+	GLSP_INT = 1,
+	GLSP_FLO = 2,
+	GLSP_BOOL = 3,
+	GLSP_STR = 4,
+	GLSP_HANDLE = 5
+} glsp_tag;
+
+/*
+A GameLisp value. Nil, ints, flos, bools and strs are copied; every other type of value is
+stored in a glsp_handle.
+
+A glsp_value passed to the API is only borrowed. Its string must be valid UTF-8, and an int
+must be within the range of an int32_t.
+
+A glsp_value returned by the API is owned by the caller. It must be passed to
+glsp_value_release, which frees its string or releases its handle.
+*/
+typedef struct glsp_value {
+	glsp_tag tag;
+	union {
+		int64_t integer;
+		double real;
+		bool boolean;
+		const char* string;
+		glsp_handle* handle;
+	} data;
+} glsp_value;
+
+/*
+A C function which can be called from GameLisp, registered using glsp_bind_callback.
+
+The arguments are owned by the runtime, and they're released when the callback returns. Use
+glsp_handle_clone to keep a reference to a handle argument.
+
+To return a value, write it to *result, which is initialized to nil. A string in the result is
+copied, and still belongs to the callback. A handle in the result is consumed: to return one
+of the arguments, clone it first.
+
+To report an error, return false. If *result is a string, it's used as the error message.
+*/
+typedef bool (*glsp_callback)(
+	glsp_runtime* rt,
+	void* userdata,
+	const glsp_value* args,
+	size_t arg_count,
+	glsp_value* result
+);
+
+/* Creates a new runtime. */
+glsp_runtime* glsp_runtime_new(void);
+
+/*
+Destroys a runtime. Must not be called from within a glsp_callback. Passing a null pointer is
+a no-op.
+*/
+void glsp_runtime_free(glsp_runtime* rt);
+
+/*
+The message for the error reported by the most recent failed call, or NULL if the most recent
+call succeeded. The string is valid until the next call which receives this runtime.
+*/
+const char* glsp_error_message(const glsp_runtime* rt);
+
+/*
+The GameLisp stack trace for the error reported by the most recent failed call, or NULL if the
+most recent call succeeded or no stack trace was recorded. The string is valid until the next
+call which receives this runtime.
+*/
+const char* glsp_error_stack_trace(const glsp_runtime* rt);
+
+/*
+Parses and evaluates some GameLisp source code, returning the result of its last form.
+`filename` is used for error messages, and it may be NULL. `out` may be NULL.
+*/
+bool glsp_eval(glsp_runtime* rt, const char* text, const char* filename, glsp_value* out);
+
+/*
+Loads a file which was compiled using glsp::load_and_compile or the compile! macro, returning
+the result of its last form. `out` may be NULL.
+*/
+bool glsp_load_compiled(glsp_runtime* rt, const uint8_t* bytes, size_t len, glsp_value* out);
+
+/* Reads a global variable. Fails if the global is unbound. */
+bool glsp_get_global(glsp_runtime* rt, const char* name, glsp_value* out);
+
+/* Assigns to a global variable, binding it first if it's unbound. */
+bool glsp_set_global(glsp_runtime* rt, const char* name, const glsp_value* value);
+
+/*
+Calls the function, or other callable value, which is bound to a global variable. `args` may be
+NULL when `arg_count` is 0. `out` may be NULL.
+*/
+bool glsp_call(
+	glsp_runtime* rt,
+	const char* name,
+	const glsp_value* args,
+	size_t arg_count,
+	glsp_value* out
+);
+
+/*
+Binds a C function to a global variable, as an rfn. `userdata` is passed to each call, and it
+must remain valid for as long as the runtime exists.
+*/
+bool glsp_bind_callback(
+	glsp_runtime* rt,
+	const char* name,
+	glsp_callback callback,
+	void* userdata
+);
+
+/*
+Frees a value's string, or releases its handle, and then sets it to nil. Values which were
+returned by the API must be released exactly once.
+*/
+void glsp_value_release(glsp_runtime* rt, glsp_value* value);
+
+/* Creates another reference to a handle's value, which must be released separately. */
+glsp_handle* glsp_handle_clone(glsp_runtime* rt, const glsp_handle* handle);
+
+/* Releases a handle. Passing a null pointer is a no-op. */
+void glsp_handle_release(glsp_runtime* rt, glsp_handle* handle);
+
+/*
+The name of the GameLisp type of a handle's value, such as "arr" or "obj". The string is owned
+by the runtime, and it remains valid for as long as the runtime exists.
+*/
+const char* glsp_handle_type(glsp_runtime* rt, const glsp_handle* handle);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
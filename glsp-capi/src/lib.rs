@@ -0,0 +1,514 @@
+/*!
+A C API for embedding [GameLisp](https://gamelisp.rs) in programs which aren't written in Rust.
+
+The API is declared in `include/glsp.h`, which documents each function in detail. This crate
+builds both a static library and a dynamic library; a C or C++ program can link against either.
+
+```c
+glsp_runtime* rt = glsp_runtime_new();
+
+glsp_value result;
+if (glsp_eval(rt, "(+ 1 2 3)", NULL, &result)) {
+    printf("%lld\n", (long long)result.data.integer);
+    glsp_value_release(rt, &result);
+} else {
+    printf("error: %s\n", glsp_error_message(rt));
+}
+
+glsp_runtime_free(rt);
+```
+
+Values cross the API as a tagged `glsp_value` struct. Nil, integers, floats, bools and strings are
+copied. Every other type of value is stored in an opaque, reference-counted `glsp_handle`, which
+keeps the value alive until the handle is released.
+
+Functions which can fail return `false`. The error's message and its stack trace can then be
+retrieved using `glsp_error_message` and `glsp_error_stack_trace`.
+*/
+
+//the safety requirements for each function are documented in glsp.h
+#![allow(clippy::missing_safety_doc)]
+
+use glsp::{bail, Callable, error, Flo, GError, GResult, GSend, Runtime, Sym, Val};
+use std::{ptr, slice};
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap};
+use std::convert::{TryFrom};
+use std::error::{Error};
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int, c_void};
+use std::panic::{self, AssertUnwindSafe};
+
+//-------------------------------------------------------------------------------------------------
+// types
+//-------------------------------------------------------------------------------------------------
+
+pub struct GlspRuntime {
+	runtime: Runtime,
+	active: Cell<bool>,
+	error_message: RefCell<Option<CString>>,
+	stack_trace: RefCell<Option<CString>>,
+	type_names: RefCell<HashMap<&'static str, CString>>
+}
+
+pub struct GlspHandle(Val);
+
+//a handle's Val is only accessed while its runtime is active, within GlspRuntime::enter(). these
+//impls allow pointers to runtimes, handles and values to be captured by the closure passed to
+//enter(). the c code is responsible for only using each runtime from a single thread.
+impl GSend for GlspRuntime { }
+impl GSend for GlspHandle { }
+
+pub const GLSP_NIL: c_int = 0;
+pub const GLSP_INT: c_int = 1;
+pub const GLSP_FLO: c_int = 2;
+pub const GLSP_BOOL: c_int = 3;
+pub const GLSP_STR: c_int = 4;
+pub const GLSP_HANDLE: c_int = 5;
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct GlspValue {
+	pub tag: c_int,
+	pub data: GlspData
+}
+
+//`boolean` is a u8 rather than a bool, because c code could store any byte there
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub union GlspData {
+	pub integer: i64,
+	pub real: f64,
+	pub boolean: u8,
+	pub string: *const c_char,
+	pub handle: *mut GlspHandle
+}
+
+pub type GlspCallback = Option<unsafe extern "C" fn(
+	rt: *mut GlspRuntime,
+	userdata: *mut c_void,
+	args: *const GlspValue,
+	arg_count: usize,
+	result: *mut GlspValue
+) -> bool>;
+
+impl GlspValue {
+	const NIL: GlspValue = GlspValue { tag: GLSP_NIL, data: GlspData { integer: 0 } };
+}
+
+//-------------------------------------------------------------------------------------------------
+// conversions
+//-------------------------------------------------------------------------------------------------
+
+//converts a Val into an owned GlspValue, which must be passed to release_value() later
+fn to_c_value(val: &Val) -> GResult<GlspValue> {
+	let (tag, data) = match *val {
+		Val::Nil => (GLSP_NIL, GlspData { integer: 0 }),
+		Val::Int(i) => (GLSP_INT, GlspData { integer: i as i64 }),
+		Val::Flo(f) => (GLSP_FLO, GlspData { real: f as f64 }),
+		Val::Bool(b) => (GLSP_BOOL, GlspData { boolean: b as u8 }),
+		Val::Str(ref st) => {
+			let c_string = match CString::new(st.to_string()) {
+				Ok(c_string) => c_string,
+				Err(_) => bail!("the str {:?} contains a nul character", st)
+			};
+
+			(GLSP_STR, GlspData { string: c_string.into_raw() })
+		}
+		ref val => {
+			let handle = Box::into_raw(Box::new(GlspHandle(val.clone())));
+			(GLSP_HANDLE, GlspData { handle })
+		}
+	};
+
+	Ok(GlspValue { tag, data })
+}
+
+//converts a GlspValue into a Val, without taking ownership of its string or handle
+unsafe fn from_c_value(value: &GlspValue) -> GResult<Val> {
+	match value.tag {
+		GLSP_NIL => Ok(Val::Nil),
+		GLSP_INT => {
+			let integer = value.data.integer;
+			match i32::try_from(integer) {
+				Ok(i) => Ok(Val::Int(i)),
+				Err(_) => bail!("the integer {} is outside the range of a glsp int", integer)
+			}
+		}
+		GLSP_FLO => Ok(Val::Flo(value.data.real as Flo)),
+		GLSP_BOOL => Ok(Val::Bool(value.data.boolean != 0)),
+		GLSP_STR => {
+			let st = c_str(value.data.string)?;
+			Ok(Val::Str(glsp::str_from_rust_str(st)))
+		}
+		GLSP_HANDLE => {
+			let handle = value.data.handle;
+			if handle.is_null() {
+				bail!("received a null glsp_handle")
+			}
+
+			Ok((*handle).0.clone())
+		}
+		tag => bail!("received a glsp_value with the invalid tag {}", tag)
+	}
+}
+
+//frees the string or handle owned by a GlspValue. must be called while the runtime is active.
+unsafe fn release_value(value: &mut GlspValue) {
+	match value.tag {
+		GLSP_STR if !value.data.string.is_null() => {
+			drop(CString::from_raw(value.data.string as *mut c_char));
+		}
+		GLSP_HANDLE if !value.data.handle.is_null() => {
+			drop(Box::from_raw(value.data.handle));
+		}
+		_ => ()
+	}
+
+	*value = GlspValue::NIL;
+}
+
+unsafe fn c_str<'a>(st: *const c_char) -> GResult<&'a str> {
+	if st.is_null() {
+		bail!("received a null string")
+	}
+
+	match CStr::from_ptr(st).to_str() {
+		Ok(st) => Ok(st),
+		Err(_) => bail!("received a string which is not valid utf-8")
+	}
+}
+
+unsafe fn c_values(values: *const GlspValue, count: usize) -> GResult<Vec<Val>> {
+	if count == 0 {
+		return Ok(Vec::new())
+	}
+
+	if values.is_null() {
+		bail!("received a null glsp_value array")
+	}
+
+	slice::from_raw_parts(values, count).iter().map(|value| from_c_value(value)).collect()
+}
+
+//-------------------------------------------------------------------------------------------------
+// entering the runtime
+//-------------------------------------------------------------------------------------------------
+
+impl GlspRuntime {
+	//runs `f` with this runtime active. if `f` fails or panics, the error is stored so that
+	//it can be retrieved by glsp_error_message(), and we return false. panics must not unwind
+	//into c code.
+	//
+	//when the c code calls back into the api from a glsp_callback, the runtime is already active,
+	//and Runtime::run() can't be nested within an rfn call, so we just call `f` directly.
+	fn enter<F: FnOnce() -> GResult<()> + GSend>(&self, f: F) -> bool {
+		*self.error_message.borrow_mut() = None;
+		*self.stack_trace.borrow_mut() = None;
+
+		let was_active = self.active.replace(true);
+		let result = panic::catch_unwind(AssertUnwindSafe(|| {
+			let f = || f().map_err(|err| (describe(&err), err.stack_trace().map(str::to_string)));
+			if was_active {
+				Some(f())
+			} else {
+				self.runtime.run(|| Ok(f()))
+			}
+		}));
+		self.active.set(was_active);
+
+		let (message, stack_trace) = match result {
+			Ok(Some(Ok(()))) => return true,
+			Ok(Some(Err(err))) => err,
+			Ok(None) => unreachable!(),
+			Err(_) => ("a rust panic occurred".to_string(), None)
+		};
+
+		*self.error_message.borrow_mut() = Some(to_c_string(message));
+		*self.stack_trace.borrow_mut() = stack_trace.map(to_c_string);
+
+		false
+	}
+}
+
+//the error's value, followed by each of its sources, e.g. "unable to load file 'a.glsp': No
+//such file or directory (os error 2)"
+fn describe(err: &GError) -> String {
+	let mut description = err.val().to_string();
+
+	let mut source = err.source();
+	while let Some(source_err) = source {
+		match source_err.downcast_ref::<GError>() {
+			Some(gerror) => description.push_str(&format!(": {}", gerror.val())),
+			None => description.push_str(&format!(": {}", source_err))
+		}
+		source = source_err.source();
+	}
+
+	description
+}
+
+fn to_c_string(st: String) -> CString {
+	CString::new(st.replace('\0', "\\0")).unwrap()
+}
+
+unsafe fn write_result(out: *mut GlspValue, val: &Val) -> GResult<()> {
+	if !out.is_null() {
+		*out = to_c_value(val)?;
+	}
+
+	Ok(())
+}
+
+//-------------------------------------------------------------------------------------------------
+// exported functions
+//-------------------------------------------------------------------------------------------------
+
+#[no_mangle]
+pub extern "C" fn glsp_runtime_new() -> *mut GlspRuntime {
+	Box::into_raw(Box::new(GlspRuntime {
+		runtime: Runtime::new(),
+		active: Cell::new(false),
+		error_message: RefCell::new(None),
+		stack_trace: RefCell::new(None),
+		type_names: RefCell::new(HashMap::new())
+	}))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn glsp_runtime_free(rt: *mut GlspRuntime) {
+	if !rt.is_null() {
+		drop(Box::from_raw(rt));
+	}
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn glsp_error_message(rt: *const GlspRuntime) -> *const c_char {
+	match *(*rt).error_message.borrow() {
+		Some(ref message) => message.as_ptr(),
+		None => ptr::null()
+	}
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn glsp_error_stack_trace(rt: *const GlspRuntime) -> *const c_char {
+	match *(*rt).stack_trace.borrow() {
+		Some(ref stack_trace) => stack_trace.as_ptr(),
+		None => ptr::null()
+	}
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn glsp_eval(
+	rt: *mut GlspRuntime,
+	text: *const c_char,
+	filename: *const c_char,
+	out: *mut GlspValue
+) -> bool {
+	(*rt).enter(|| {
+		let text = c_str(text)?;
+		let filename = if filename.is_null() { None } else { Some(c_str(filename)?) };
+
+		let vals = glsp::parse_all(text, filename)?;
+		let result = glsp::eval_multi(&vals, None)?;
+		write_result(out, &result)
+	})
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn glsp_load_compiled(
+	rt: *mut GlspRuntime,
+	bytes: *const u8,
+	len: usize,
+	out: *mut GlspValue
+) -> bool {
+	(*rt).enter(|| {
+		if bytes.is_null() {
+			bail!("received a null byte array")
+		}
+
+		let result = glsp::load_compiled(slice::from_raw_parts(bytes, len))?;
+		write_result(out, &result)
+	})
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn glsp_get_global(
+	rt: *mut GlspRuntime,
+	name: *const c_char,
+	out: *mut GlspValue
+) -> bool {
+	(*rt).enter(|| {
+		let name = glsp::sym(c_str(name)?)?;
+		let val: Val = glsp::global(name)?;
+		write_result(out, &val)
+	})
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn glsp_set_global(
+	rt: *mut GlspRuntime,
+	name: *const c_char,
+	value: *const GlspValue
+) -> bool {
+	(*rt).enter(|| {
+		let name = glsp::sym(c_str(name)?)?;
+		if value.is_null() {
+			bail!("received a null glsp_value")
+		}
+
+		let val = from_c_value(&*value)?;
+		if glsp::has_global(name)? {
+			glsp::set_global(name, val)
+		} else {
+			glsp::bind_global(name, val)
+		}
+	})
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn glsp_call(
+	rt: *mut GlspRuntime,
+	name: *const c_char,
+	args: *const GlspValue,
+	arg_count: usize,
+	out: *mut GlspValue
+) -> bool {
+	(*rt).enter(|| {
+		let name = glsp::sym(c_str(name)?)?;
+		let callee: Callable = match glsp::global(name) {
+			Ok(callee) => callee,
+			Err(err) => return Err(error!("the global {} is not callable", name).with_source(err))
+		};
+
+		let args = c_values(args, arg_count)?;
+		let result: Val = glsp::call(&callee, &args[..])?;
+		write_result(out, &result)
+	})
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn glsp_bind_callback(
+	rt: *mut GlspRuntime,
+	name: *const c_char,
+	callback: GlspCallback,
+	userdata: *mut c_void
+) -> bool {
+	(*rt).enter(|| {
+		let name = glsp::sym(c_str(name)?)?;
+		let callback = match callback {
+			Some(callback) => callback,
+			None => bail!("received a null glsp_callback")
+		};
+
+		glsp::bind_rfn_boxed(name, glsp::rfn_boxed!(move |args: &[Val]| -> GResult<Val> {
+			invoke_callback(rt, name, callback, userdata, args)
+		}))?;
+
+		Ok(())
+	})
+}
+
+unsafe fn invoke_callback(
+	rt: *mut GlspRuntime,
+	name: Sym,
+	callback: unsafe extern "C" fn(
+		*mut GlspRuntime, *mut c_void, *const GlspValue, usize, *mut GlspValue
+	) -> bool,
+	userdata: *mut c_void,
+	args: &[Val]
+) -> GResult<Val> {
+	let mut c_args = Vec::with_capacity(args.len());
+	for arg in args {
+		match to_c_value(arg) {
+			Ok(c_arg) => c_args.push(c_arg),
+			Err(err) => {
+				for c_arg in &mut c_args {
+					release_value(c_arg);
+				}
+				return Err(err)
+			}
+		}
+	}
+
+	let mut c_result = GlspValue::NIL;
+	let succeeded = callback(rt, userdata, c_args.as_ptr(), c_args.len(), &mut c_result);
+
+	for c_arg in &mut c_args {
+		release_value(c_arg);
+	}
+
+	//the callback keeps ownership of any string which it returns, but it passes ownership
+	//of any handle to us
+	let result = from_c_value(&c_result);
+	if c_result.tag == GLSP_HANDLE {
+		release_value(&mut c_result);
+	}
+
+	if succeeded {
+		result
+	} else {
+		match result {
+			Ok(Val::Str(message)) => bail!("{}", message),
+			_ => bail!("the C callback {} failed", name)
+		}
+	}
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn glsp_value_release(rt: *mut GlspRuntime, value: *mut GlspValue) {
+	if !value.is_null() {
+		(*rt).enter(|| {
+			release_value(&mut *value);
+			Ok(())
+		});
+	}
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn glsp_handle_clone(
+	rt: *mut GlspRuntime,
+	handle: *const GlspHandle
+) -> *mut GlspHandle {
+	if handle.is_null() {
+		return ptr::null_mut()
+	}
+
+	let mut cloned = ptr::null_mut();
+	(*rt).enter(|| {
+		cloned = Box::into_raw(Box::new(GlspHandle((*handle).0.clone())));
+		Ok(())
+	});
+
+	cloned
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn glsp_handle_release(rt: *mut GlspRuntime, handle: *mut GlspHandle) {
+	if !handle.is_null() {
+		(*rt).enter(|| {
+			drop(Box::from_raw(handle));
+			Ok(())
+		});
+	}
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn glsp_handle_type(
+	rt: *mut GlspRuntime,
+	handle: *const GlspHandle
+) -> *const c_char {
+	if handle.is_null() {
+		return ptr::null()
+	}
+
+	let mut type_name = "";
+	(*rt).enter(|| {
+		type_name = (*handle).0.type_name();
+		Ok(())
+	});
+
+	//type names are 'static, so we only need to allocate each c string once
+	let mut type_names = (*rt).type_names.borrow_mut();
+	type_names.entry(type_name).or_insert_with(|| CString::new(type_name).unwrap()).as_ptr()
+}
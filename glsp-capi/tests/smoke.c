@@ -0,0 +1,310 @@
+/*
+Exercises the C API. Built and run by tests/smoke.rs; the first argument is the path to a file
+which was compiled using glsp::load_and_compile_str.
+*/
+
+#include "glsp.h"
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+static int failures = 0;
+
+#define CHECK(cond) \
+	do { \
+		if (!(cond)) { \
+			fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, #cond); \
+			failures += 1; \
+		} \
+	} while (0)
+
+static void print_error(glsp_runtime* rt) {
+	const char* message = glsp_error_message(rt);
+	fprintf(stderr, "glsp error: %s\n", message ? message : "(none)");
+}
+
+static bool add_ints(
+	glsp_runtime* rt,
+	void* userdata,
+	const glsp_value* args,
+	size_t arg_count,
+	glsp_value* result
+) {
+	(void)rt;
+
+	int* call_count = (int*)userdata;
+	*call_count += 1;
+
+	int64_t sum = 0;
+	for (size_t i = 0; i < arg_count; i++) {
+		if (args[i].tag != GLSP_INT) {
+			result->tag = GLSP_STR;
+			result->data.string = "c-add expects ints";
+			return false;
+		}
+
+		sum += args[i].data.integer;
+	}
+
+	result->tag = GLSP_INT;
+	result->data.integer = sum;
+	return true;
+}
+
+static bool first_arg(
+	glsp_runtime* rt,
+	void* userdata,
+	const glsp_value* args,
+	size_t arg_count,
+	glsp_value* result
+) {
+	(void)userdata;
+
+	if (arg_count != 1 || args[0].tag != GLSP_HANDLE) {
+		return false;
+	}
+
+	result->tag = GLSP_HANDLE;
+	result->data.handle = glsp_handle_clone(rt, args[0].data.handle);
+	return true;
+}
+
+static void test_eval(glsp_runtime* rt) {
+	glsp_value result;
+
+	if (glsp_eval(rt, "(+ 1 2)", NULL, &result)) {
+		CHECK(result.tag == GLSP_INT && result.data.integer == 3);
+		glsp_value_release(rt, &result);
+	} else {
+		print_error(rt);
+		CHECK(false);
+	}
+
+	if (glsp_eval(rt, "(/ 1.0 4)", "flo.glsp", &result)) {
+		CHECK(result.tag == GLSP_FLO && result.data.real == 0.25);
+		glsp_value_release(rt, &result);
+	} else {
+		print_error(rt);
+		CHECK(false);
+	}
+
+	if (glsp_eval(rt, "(str \"a\" \"b\")", NULL, &result)) {
+		CHECK(result.tag == GLSP_STR && strcmp(result.data.string, "ab") == 0);
+		glsp_value_release(rt, &result);
+		CHECK(result.tag == GLSP_NIL);
+	} else {
+		print_error(rt);
+		CHECK(false);
+	}
+
+	CHECK(glsp_eval(rt, "(def ignored 1)", NULL, NULL));
+	CHECK(glsp_error_message(rt) == NULL);
+}
+
+static void test_globals(glsp_runtime* rt) {
+	glsp_value value;
+
+	value.tag = GLSP_INT;
+	value.data.integer = 42;
+	CHECK(glsp_set_global(rt, "answer", &value));
+
+	value.tag = GLSP_STR;
+	value.data.string = "hello";
+	CHECK(glsp_set_global(rt, "greeting", &value));
+
+	value.tag = GLSP_BOOL;
+	value.data.boolean = true;
+	CHECK(glsp_set_global(rt, "flag", &value));
+
+	glsp_value result;
+	if (glsp_eval(rt, "(if flag (+ answer (len greeting)) 0)", NULL, &result)) {
+		CHECK(result.tag == GLSP_INT && result.data.integer == 47);
+		glsp_value_release(rt, &result);
+	} else {
+		print_error(rt);
+		CHECK(false);
+	}
+
+	if (glsp_get_global(rt, "greeting", &result)) {
+		CHECK(result.tag == GLSP_STR && strcmp(result.data.string, "hello") == 0);
+		glsp_value_release(rt, &result);
+	} else {
+		print_error(rt);
+		CHECK(false);
+	}
+
+	CHECK(!glsp_get_global(rt, "no-such-global", &result));
+	CHECK(glsp_error_message(rt) != NULL);
+
+	value.tag = GLSP_INT;
+	value.data.integer = INT64_MAX;
+	CHECK(!glsp_set_global(rt, "answer", &value));
+}
+
+static void test_call(glsp_runtime* rt) {
+	CHECK(glsp_eval(rt, "(defn scale (x factor) (* x factor))", NULL, NULL));
+
+	glsp_value args[2];
+	args[0].tag = GLSP_INT;
+	args[0].data.integer = 6;
+	args[1].tag = GLSP_INT;
+	args[1].data.integer = 7;
+
+	glsp_value result;
+	if (glsp_call(rt, "scale", args, 2, &result)) {
+		CHECK(result.tag == GLSP_INT && result.data.integer == 42);
+		glsp_value_release(rt, &result);
+	} else {
+		print_error(rt);
+		CHECK(false);
+	}
+
+	CHECK(!glsp_call(rt, "scale", args, 1, &result));
+	CHECK(glsp_error_message(rt) != NULL);
+}
+
+static void test_callbacks(glsp_runtime* rt) {
+	int call_count = 0;
+	CHECK(glsp_bind_callback(rt, "c-add", add_ints, &call_count));
+	CHECK(glsp_bind_callback(rt, "c-first", first_arg, NULL));
+
+	glsp_value result;
+	if (glsp_eval(rt, "(c-add 1 2 (c-add 3 4))", NULL, &result)) {
+		CHECK(result.tag == GLSP_INT && result.data.integer == 10);
+		glsp_value_release(rt, &result);
+	} else {
+		print_error(rt);
+		CHECK(false);
+	}
+
+	CHECK(call_count == 2);
+
+	CHECK(!glsp_eval(rt, "(c-add 1 'two)", NULL, NULL));
+	const char* message = glsp_error_message(rt);
+	CHECK(message != NULL && strstr(message, "c-add expects ints") != NULL);
+
+	if (glsp_eval(rt, "(let a (arr 1 2)) (same? a (c-first a))", NULL, &result)) {
+		CHECK(result.tag == GLSP_BOOL && result.data.boolean);
+		glsp_value_release(rt, &result);
+	} else {
+		print_error(rt);
+		CHECK(false);
+	}
+
+	CHECK(!glsp_eval(rt, "(c-first 1)", NULL, NULL));
+	message = glsp_error_message(rt);
+	CHECK(message != NULL && strstr(message, "c-first") != NULL);
+}
+
+static void test_handles(glsp_runtime* rt) {
+	glsp_value result;
+	if (!glsp_eval(rt, "(arr 10 20 30)", NULL, &result)) {
+		print_error(rt);
+		CHECK(false);
+		return;
+	}
+
+	CHECK(result.tag == GLSP_HANDLE);
+	CHECK(strcmp(glsp_handle_type(rt, result.data.handle), "arr") == 0);
+
+	glsp_handle* kept = glsp_handle_clone(rt, result.data.handle);
+	glsp_value_release(rt, &result);
+
+	//the arr must survive a collection while the clone is still held
+	CHECK(glsp_eval(rt, "(gc) (gc) (gc)", NULL, NULL));
+
+	glsp_value arg;
+	arg.tag = GLSP_HANDLE;
+	arg.data.handle = kept;
+	CHECK(glsp_set_global(rt, "kept", &arg));
+
+	glsp_value sum;
+	if (glsp_eval(rt, "(+ ..kept)", NULL, &sum)) {
+		CHECK(sum.tag == GLSP_INT && sum.data.integer == 60);
+		glsp_value_release(rt, &sum);
+	} else {
+		print_error(rt);
+		CHECK(false);
+	}
+
+	glsp_handle_release(rt, kept);
+	glsp_handle_release(rt, NULL);
+}
+
+static void test_errors(glsp_runtime* rt) {
+	CHECK(glsp_eval(rt, "(defn fails () (bail \"deliberate failure\"))", NULL, NULL));
+
+	CHECK(!glsp_eval(rt, "(fails)", "errors.glsp", NULL));
+	const char* message = glsp_error_message(rt);
+	const char* stack_trace = glsp_error_stack_trace(rt);
+	CHECK(message != NULL && strstr(message, "deliberate failure") != NULL);
+	CHECK(stack_trace != NULL && strstr(stack_trace, "(bail)") != NULL);
+
+	CHECK(!glsp_eval(rt, "(+ 1", NULL, NULL));
+	CHECK(glsp_error_message(rt) != NULL);
+
+	CHECK(glsp_eval(rt, "()", NULL, NULL));
+	CHECK(glsp_error_message(rt) == NULL);
+	CHECK(glsp_error_stack_trace(rt) == NULL);
+}
+
+static void test_load_compiled(glsp_runtime* rt, const char* path) {
+	FILE* file = fopen(path, "rb");
+	if (file == NULL) {
+		fprintf(stderr, "unable to open %s\n", path);
+		CHECK(false);
+		return;
+	}
+
+	fseek(file, 0, SEEK_END);
+	long len = ftell(file);
+	fseek(file, 0, SEEK_SET);
+
+	uint8_t* bytes = malloc((size_t)len);
+	CHECK(fread(bytes, 1, (size_t)len, file) == (size_t)len);
+	fclose(file);
+
+	glsp_value result;
+	if (glsp_load_compiled(rt, bytes, (size_t)len, &result)) {
+		CHECK(result.tag == GLSP_INT && result.data.integer == 120);
+		glsp_value_release(rt, &result);
+	} else {
+		print_error(rt);
+		CHECK(false);
+	}
+
+	CHECK(!glsp_load_compiled(rt, bytes, (size_t)len / 2, NULL));
+	CHECK(glsp_error_message(rt) != NULL);
+
+	free(bytes);
+}
+
+int main(int argc, char** argv) {
+	if (argc != 2) {
+		fprintf(stderr, "usage: %s <compiled file>\n", argv[0]);
+		return 2;
+	}
+
+	glsp_runtime* rt = glsp_runtime_new();
+	CHECK(rt != NULL);
+
+	test_eval(rt);
+	test_globals(rt);
+	test_call(rt);
+	test_callbacks(rt);
+	test_handles(rt);
+	test_errors(rt);
+	test_load_compiled(rt, argv[1]);
+
+	glsp_runtime_free(rt);
+	glsp_runtime_free(NULL);
+
+	if (failures > 0) {
+		fprintf(stderr, "%d checks failed\n", failures);
+		return 1;
+	}
+
+	printf("all checks passed\n");
+	return 0;
+}
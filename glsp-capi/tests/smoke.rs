@@ -0,0 +1,83 @@
+//builds the static library, links tests/smoke.c against it using the system's `cc`, then runs
+//it. the native libraries which the static library depends on are platform-specific, so we ask
+//rustc for them using `--print native-static-libs`.
+#![cfg(unix)]
+
+use glsp::{Flo, Runtime};
+use std::{env, fs, mem};
+use std::path::{Path, PathBuf};
+use std::process::{Command};
+
+//the static library is built by a nested `cargo rustc`, with its own target directory so that
+//it doesn't wait for the lock held by the cargo process which is running this test. returns
+//the path to the library, and the linker arguments for its native dependencies.
+fn build_staticlib(tmp_dir: &Path) -> (PathBuf, Vec<String>) {
+	let target_dir = tmp_dir.join("staticlib");
+
+	//features passed to the outer cargo aren't inherited, but the library needs to use the same
+	//flo width as this test, or it would refuse to load the compiled bytes which we pass to it
+	let mut features = Vec::new();
+	if mem::size_of::<Flo>() == mem::size_of::<f64>() {
+		features.push("--features=glsp/f64-flos");
+	}
+
+	let output = Command::new(env::var("CARGO").unwrap_or_else(|_| "cargo".to_string()))
+		.args(["rustc", "-p", "glsp-capi", "--lib", "--crate-type", "staticlib"])
+		.args(&features)
+		.arg("--target-dir")
+		.arg(&target_dir)
+		.args(["--", "--print", "native-static-libs"])
+		.output()
+		.expect("unable to run cargo");
+	let stderr = String::from_utf8_lossy(&output.stderr);
+	assert!(output.status.success(), "unable to build the static library:\n{}", stderr);
+
+	//cargo replays rustc's diagnostics when the library is already up to date, so this note is
+	//printed even when nothing is rebuilt
+	let native_libs = stderr
+		.lines()
+		.find_map(|line| line.split("native-static-libs: ").nth(1))
+		.unwrap_or_else(|| panic!("rustc didn't print native-static-libs:\n{}", stderr))
+		.split_whitespace()
+		.map(|arg| arg.to_string())
+		.collect();
+
+	(target_dir.join("debug").join("libglsp_capi.a"), native_libs)
+}
+
+#[test]
+fn smoke() {
+	let tmp_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+	let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+	let (lib, native_libs) = build_staticlib(&tmp_dir);
+
+	let compiled = Runtime::new().run(|| {
+		let (_, bytes) = glsp::load_and_compile_str("(let n 5) (* n 4 3 2)", "smoke.glsp")?;
+		Ok(bytes)
+	}).unwrap();
+
+	let compiled_path = tmp_dir.join("smoke.glsp.compiled");
+	fs::write(&compiled_path, compiled).unwrap();
+
+	let smoke_path = tmp_dir.join("smoke");
+	let status = Command::new(env::var("CC").unwrap_or_else(|_| "cc".to_string()))
+		.arg("-std=c99")
+		.arg("-Wall")
+		.arg("-Werror")
+		.arg(manifest_dir.join("tests/smoke.c"))
+		.arg("-I")
+		.arg(manifest_dir.join("include"))
+		.arg(&lib)
+		.args(&native_libs)
+		.arg("-o")
+		.arg(&smoke_path)
+		.status()
+		.expect("unable to run the C compiler");
+	assert!(status.success(), "unable to compile tests/smoke.c");
+
+	let output = Command::new(&smoke_path).arg(&compiled_path).output().unwrap();
+	print!("{}", String::from_utf8_lossy(&output.stdout));
+	eprint!("{}", String::from_utf8_lossy(&output.stderr));
+	assert!(output.status.success(), "tests/smoke.c failed");
+}
@@ -0,0 +1,174 @@
+use std::fmt::{Write};
+use std::iter::{FromIterator};
+use super::engine::{Sym};
+
+/**
+A description of a Rust function or macro, recorded when it was bound to a name.
+
+Returned by [`glsp::api_manifest`](glsp/fn.api_manifest.html). An entry is recorded by
+[`glsp::bind_rfn`](glsp/fn.bind_rfn.html), [`glsp::bind_rfn_macro`](glsp/fn.bind_rfn_macro.html),
+[`glsp::bind_rfn_boxed`](glsp/fn.bind_rfn_boxed.html),
+[`glsp::bind_deprecated`](glsp/fn.bind_deprecated.html) and [`glsp::install`](glsp/fn.install.html).
+*/
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ApiEntry {
+	///The name of the global or macro.
+	pub name: Sym,
+
+	///`true` if the function is bound to a global macro, rather than a global variable.
+	pub is_macro: bool,
+
+	/**
+	The library which bound the function, if any.
+
+	This is the name of the [`Library`](struct.Library.html) which was installed, or the name
+	passed to [`glsp::with_api_library`](glsp/fn.with_api_library.html). The standard library's
+	functions belong to the library `"std"`.
+	*/
+	pub library: Option<String>,
+
+	///The function's [argument limits](trait.CallableOps.html#tymethod.arg_limits).
+	pub arg_limits: (usize, Option<usize>),
+
+	/**
+	The name of each parameter, if they're known.
+
+	Names are recorded by the [`rfn_boxed!`](macro.rfn_boxed.html) macro when it's passed a
+	closure whose parameters are all simple identifiers with a type annotation. Otherwise, they
+	can be provided using [`glsp::document_api`](glsp/fn.document_api.html).
+	*/
+	pub param_names: Option<Vec<String>>,
+
+	/**
+	The Rust type of each parameter, if they're known, like `"i32"` or `"Option<&str>"`.
+
+	Types are recorded by the [`rfn!`](macro.rfn.html) and [`rfn_boxed!`](macro.rfn_boxed.html)
+	macros. Parameters which borrow a [`Lib`](trait.Lib.html) aren't listed, because they don't
+	receive an argument.
+	*/
+	pub param_types: Option<Vec<String>>,
+
+	///The documentation provided using [`glsp::document_api`](glsp/fn.document_api.html).
+	pub doc: Option<String>,
+
+	/**
+	The capability which must be granted for this function to be bound, if any.
+
+	For the standard library, this is the name of one of the constants defined by `Caps`, like
+	`"FS"`.
+	*/
+	pub capability: Option<String>,
+
+	///If this name is [deprecated](glsp/fn.bind_deprecated.html), the name which replaced it.
+	pub replacement: Option<Sym>,
+
+	///The note passed to `glsp::bind_deprecated`, if it wasn't empty.
+	pub deprecation_note: Option<String>
+}
+
+//strips the module path from each segment of a type name, so that
+//"core::option::Option<glsp_engine::val::Val>" becomes "Option<Val>"
+pub(crate) fn short_type_name(type_name: &str) -> String {
+	let mut result = String::with_capacity(type_name.len());
+	let mut segment = String::new();
+
+	for ch in type_name.chars() {
+		if ch.is_alphanumeric() || ch == '_' {
+			segment.push(ch);
+		} else if ch == ':' {
+			segment.clear();
+		} else {
+			result.push_str(&segment);
+			segment.clear();
+			result.push(ch);
+		}
+	}
+
+	result.push_str(&segment);
+	result
+}
+
+//the entries should already be sorted by library, then by name
+pub(crate) fn render_markdown(entries: &[ApiEntry]) -> String {
+	let mut text = String::from("# API Reference\n");
+
+	let mut current_library = None;
+	for (i, entry) in entries.iter().enumerate() {
+		if i == 0 || entry.library != current_library {
+			current_library = entry.library.clone();
+			match current_library {
+				Some(ref library) => write!(text, "\n## Library `{}`\n", library).unwrap(),
+				None => text.push_str("\n## Other Functions\n")
+			}
+		}
+
+		render_entry(&mut text, entry);
+	}
+
+	text
+}
+
+fn render_entry(text: &mut String, entry: &ApiEntry) {
+	let kind = if entry.is_macro { "macro" } else { "fn" };
+	write!(text, "\n### `{}` ({})\n\n", entry.name, kind).unwrap();
+
+	//each parameter's name and type, when either of them is known
+	let param_count = match (&entry.param_names, &entry.param_types) {
+		(Some(names), _) => names.len(),
+		(None, Some(types)) => types.len(),
+		(None, None) => 0
+	};
+
+	let params = Vec::from_iter((0 .. param_count).map(|i| {
+		let name = entry.param_names.as_ref().map(|names| &names[i][..]);
+		let ty = entry.param_types.as_ref().and_then(|types| types.get(i)).map(|ty| &ty[..]);
+		(name, ty)
+	}));
+
+	//the signature lists each parameter's name, or its type if the name is unknown
+	let mut signature = format!("({}", entry.name);
+	for &(name, ty) in &params {
+		write!(signature, " {}", name.or(ty).unwrap()).unwrap();
+	}
+
+	if params.is_empty() && entry.arg_limits != (0, Some(0)) {
+		signature.push_str(" ..");
+	}
+
+	writeln!(text, "`{})`\n", signature).unwrap();
+
+	let arg_count = match entry.arg_limits {
+		(min, Some(max)) if min == max => format!("exactly {}", min),
+		(min, Some(max)) => format!("{} to {}", min, max),
+		(min, None) => format!("at least {}", min)
+	};
+	writeln!(text, "- Arguments: {}", arg_count).unwrap();
+
+	if !params.is_empty() {
+		let params = Vec::from_iter(params.iter().map(|&(name, ty)| {
+			match (name, ty) {
+				(Some(name), Some(ty)) => format!("`{}: {}`", name, ty),
+				(name, ty) => format!("`{}`", name.or(ty).unwrap())
+			}
+		}));
+
+		writeln!(text, "- Parameters: {}", params.join(", ")).unwrap();
+	}
+
+	if let Some(ref capability) = entry.capability {
+		writeln!(text, "- Requires the `{}` capability", capability).unwrap();
+	}
+
+	if let Some(replacement) = entry.replacement {
+		write!(text, "- **Deprecated:** use `{}` instead", replacement).unwrap();
+		if let Some(ref note) = entry.deprecation_note {
+			write!(text, ": {}", note).unwrap();
+		}
+		text.push('\n');
+	}
+
+	if let Some(ref doc) = entry.doc {
+		write!(text, "\n{}\n", doc.trim()).unwrap();
+	}
+}
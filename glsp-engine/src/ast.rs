@@ -3,6 +3,8 @@ use std::iter::{DoubleEndedIterator, ExactSizeIterator, FromIterator, FusedItera
 use std::marker::{PhantomData};
 use std::num::{NonZeroU32};
 use std::ops::{Index, IndexMut};
+use std::rc::{Rc};
+use super::code::{ParamName};
 use super::collections::{Arr, DequeAccess, DequeOps};
 use super::gc::{Root};
 use super::engine::{Span, stock_syms::*, Sym, SymKind};
@@ -58,12 +60,20 @@ impl Ast {
 
 					Ok(())
 				}
+				Val::Rec(ref rec) => {
+					for val in rec.field_vals() {
+						check_type(&val, span)?;
+					}
+
+					Ok(())
+				}
 				Val::Nil | Val::Int(..) | Val::Char(..) | Val::Flo(..) |
 				Val::Bool(..) | Val::Sym(..) | Val::Str(..) => {
 					Ok(())
 				}
 				Val::GIter(..) | Val::Obj(..) | Val::Class(..) | Val::GFn(..) | 
-				Val::RFn(..) | Val::Coro(..) | Val::RData(..) => {
+				Val::RFn(..) | Val::Coro(..) | Val::RData(..) | Val::PArr(..) | Val::PTab(..) |
+				Val::View(..) | Val::Vec2(..) | Val::Vec3(..) | Val::Mat3(..) => {
 					bail_at!(span, "{} literals cannot be evaluated", val.type_name())
 				}
 			}
@@ -362,8 +372,12 @@ pub(crate) enum Expr {
 	RestartBlock(Sym),
 	Fn {
 		name: Option<Sym>,
+		doc: Option<Rc<str>>,
 		arg_limits: Option<(usize, Option<usize>)>,
 		param_list: ParamList,
+		param_names: Option<Box<[ParamName]>>,
+		param_types: Option<Box<[Option<Sym>]>>,
+		return_type: Option<Sym>,
 		body: Range<Node>,
 		yields: bool
 	},
@@ -478,8 +492,20 @@ fn val_to_node(ast: &mut Ast, val: &Val, span: Span) -> GResult<Node> {
 
 			Ok(Node(span, Expr::Literal(Val::Tab(tab))))
 		}
+		Val::Rec(ref root) => {
+			let rec = if val.is_deep_frozen() {
+				root.clone()
+			} else {
+				let rec = root.deep_clone()?;
+				rec.deep_freeze();
+				rec
+			};
+
+			Ok(Node(span, Expr::Literal(Val::Rec(rec))))
+		}
 		Val::GIter(_) | Val::RFn(_) | Val::Obj(_) | Val::Class(_) | 
-		Val::GFn(_) | Val::Coro(_) | Val::RData(_) => {
+		Val::GFn(_) | Val::Coro(_) | Val::RData(_) | Val::PArr(_) | Val::PTab(_) | Val::View(_) |
+		Val::Vec2(_) | Val::Vec3(_) | Val::Mat3(_) => {
 			//already checked above, in Ast::node_from_val
 			unreachable!()
 		}
@@ -705,7 +731,11 @@ fn restart_block_to_node(_ast: &mut Ast, args: &[Val], span: Span) -> GResult<No
 
 fn fn_to_node(ast: &mut Ast, args: &[Val], span: Span) -> GResult<Node> {
 	let mut name = None;
+	let mut doc = None;
 	let mut arg_limits = None;
+	let mut param_names = None;
+	let mut param_types = None;
+	let mut return_type = None;
 
 	let mut i = 0;
 	let param_list = loop {
@@ -726,6 +756,17 @@ fn fn_to_node(ast: &mut Ast, args: &[Val], span: Span) -> GResult<Node> {
 
 				i += 2;
 			}
+			Val::Sym(FLAG_DOC_SYM) => {
+				ensure_at!(span, args.len() >= i + 2, "invalid &doc flag in (fn)");
+				ensure_at!(span, doc.is_none(), "duplicate &doc flag in (fn)");
+
+				doc = match args[i + 1] {
+					Val::Str(ref st) => Some(Rc::from(st.to_string())),
+					ref arg => bail_at!(span, "{} is not a valid doc string", arg)
+				};
+
+				i += 2;
+			}
 			Val::Sym(FLAG_ARG_LIMITS_SYM) => {
 				ensure_at!(span, args.len() >= i + 2, "invalid &arg-limits flag in (fn)");
 				ensure_at!(span, arg_limits.is_none(), "duplicate &arg-limits flag in (fn)");
@@ -749,19 +790,97 @@ fn fn_to_node(ast: &mut Ast, args: &[Val], span: Span) -> GResult<Node> {
 
 				i += 3;
 			}
+			Val::Sym(FLAG_PARAM_NAMES_SYM) => {
+				ensure_at!(span, args.len() >= i + 1, "invalid &param-names flag in (fn)");
+				ensure_at!(span, param_names.is_none(), "duplicate &param-names flag in (fn)");
+
+				param_names = match args[i + 1] {
+					Val::Arr(ref arr) => Some(param_names_from_arr(arr, span)?),
+					ref arg => bail_at!(span, "{} is not a valid &param-names list", arg)
+				};
+
+				i += 2;
+			}
+			Val::Sym(FLAG_PARAM_TYPES_SYM) => {
+				ensure_at!(span, args.len() >= i + 2, "invalid &param-types flag in (fn)");
+				ensure_at!(span, param_types.is_none(), "duplicate &param-types flag in (fn)");
+
+				param_types = match args[i + 1] {
+					Val::Arr(ref arr) => Some(param_types_from_arr(arr, span)?),
+					ref arg => bail_at!(span, "{} is not a valid &param-types list", arg)
+				};
+
+				i += 2;
+			}
+			Val::Sym(FLAG_RETURN_TYPE_SYM) => {
+				ensure_at!(span, args.len() >= i + 2, "invalid &return-type flag in (fn)");
+				ensure_at!(span, return_type.is_none(), "duplicate &return-type flag in (fn)");
+
+				return_type = match args[i + 1] {
+					Val::Sym(return_type) => Some(return_type),
+					ref arg => bail_at!(span, "{} is not a valid return type", arg)
+				};
+
+				i += 2;
+			}
 			ref arg => bail_at!(span, "(fn) form received unexpected argument {}", arg)
 		}
 	};
 
 	Ok(Node(span, Expr::Fn {
 		name,
+		doc,
 		arg_limits,
 		param_list,
+		param_names,
+		param_types,
+		return_type,
 		body: vals_to_nodes(ast, &args[i + 1..], span)?,
 		yields: false
 	}))
 }
 
+//the argument to the &param-names flag is a list of names, (? name) forms and ..name forms, in
+//any order
+fn param_names_from_arr(arr: &Arr, span: Span) -> GResult<Box<[ParamName]>> {
+	let mut param_names = Vec::with_capacity(arr.len());
+	for val in arr.iter() {
+		let param_name = match val {
+			Val::Sym(name) => Some(ParamName::Basic(name)),
+			Val::Arr(ref form) if form.len() == 2 => {
+				match (form.get::<Val>(0)?, form.get::<Val>(1)?) {
+					(Val::Sym(QUESTION_MARK_SYM), Val::Sym(name)) => Some(ParamName::Opt(name)),
+					(Val::Sym(SPLAY_SYM), Val::Sym(name)) => Some(ParamName::Rest(name)),
+					_ => None
+				}
+			}
+			_ => None
+		};
+
+		match param_name {
+			Some(param_name) => param_names.push(param_name),
+			None => bail_at!(span, "{} is not a valid parameter name", val)
+		}
+	}
+
+	Ok(param_names.into_boxed_slice())
+}
+
+//the argument to the &param-types flag has one element for each parameter, in the same order as
+//the &param-names flag. each element is either a type name, or #n for an unannotated parameter.
+fn param_types_from_arr(arr: &Arr, span: Span) -> GResult<Box<[Option<Sym>]>> {
+	let mut param_types = Vec::with_capacity(arr.len());
+	for val in arr.iter() {
+		match val {
+			Val::Sym(type_name) => param_types.push(Some(type_name)),
+			Val::Nil => param_types.push(None),
+			val => bail_at!(span, "{} is not a valid parameter type", val)
+		}
+	}
+
+	Ok(param_types.into_boxed_slice())
+}
+
 fn return_to_node(ast: &mut Ast, args: &[Val], span: Span) -> GResult<Node> {
 	let result = if args.is_empty() {
 		val_to_node(ast, &Val::Nil, span)?
@@ -0,0 +1,379 @@
+#![cfg(feature = "compiler")]
+
+use fnv::{FnvHashMap, FnvHashSet, FnvHasher};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::hash::{Hasher};
+use std::path::{Path, PathBuf};
+use super::code::{GFn};
+use super::compile::{FLO_WIDTH};
+use super::engine::{glsp, Sym};
+use super::eval::{Expander};
+use super::gc::{Root};
+use super::val::{Val};
+
+/*
+this module is only present when the "compiler" crate feature is enabled.
+
+it provides the bookkeeping for glsp::set_compile_cache. the loading itself happens in engine.rs:
+when a file misses the cache, it's loaded normally while its toplevel forms are recorded into a
+Recording (see compile.rs). when it hits, the Recording is played back instead, just like
+glsp::load_compiled. each (load) has its own cache entry, so a nested (load) sets aside the
+enclosing file's Recording until it's finished.
+
+a cache entry is named after a hash of the file's name and source text, the engine version, the
+opt level and the flo width. that isn't enough on its own, because macro-expansion can consult
+things which are defined elsewhere. while a file is being compiled, we collect a Dep for each
+global macro lookup, each inlined constant, each file read using glsp::read_source (e.g. by the
+(include) macro), and each file which it loads, directly or indirectly. the Deps are stored in
+the entry, and the entry is only reused if each Dep is still current.
+
+to describe a macro Dep, we need to know which file bound the macro. bind_macro and set_macro
+report each binding which happens during a cached load, and we store the expander alongside the
+file, so that a binding which has since been replaced by some other route (say, by
+glsp::restore_globals) is detected. a GFn macro with no known provider might have come from
+anywhere, so when one is consulted, the file is still loaded but its entry isn't stored. a macro
+implemented by an rfn is assumed to belong to the host, which isn't tracked. constants work the
+same way, except that their value is also recorded.
+
+the Deps are checked before playback begins, but a file will often (require) the file which
+provides its macros. in that case, the macro is unbound when we check it. that's fine, as long
+as the provider is one of the files which this file loads, with the same source text: it will
+have been loaded again by the time the macro would have been expanded. the nested files are
+also what stops us from missing a new macro which shadows a global: a NoMacro Dep might only be
+invalidated after one of the nested files has been loaded, but that file's text will have
+changed.
+
+each file's own macros aren't recorded as Deps, because when the entry is reused, they'll be
+rebound by the playback itself.
+*/
+
+/**
+Statistics for the compile cache.
+
+Returned by [`glsp::compile_cache_stats`](glsp/fn.compile_cache_stats.html).
+*/
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct CompileCacheStats {
+	hits: usize,
+	misses: usize,
+	uncacheable: usize
+}
+
+impl CompileCacheStats {
+	/** Returns the number of files which were played back from the cache. */
+	pub fn hits(&self) -> usize {
+		self.hits
+	}
+
+	/** Returns the number of files which were compiled, and then stored in the cache. */
+	pub fn misses(&self) -> usize {
+		self.misses
+	}
+
+	/**
+	Returns the number of files which were compiled, but which couldn't be stored in the cache.
+
+	This happens when a file uses a macro whose origin is unknown, or when its compiled code
+	contains a value which can't be serialized.
+	*/
+	pub fn uncacheable(&self) -> usize {
+		self.uncacheable
+	}
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub(crate) enum Dep {
+	//the named global had no macro binding
+	NoMacro(String),
+
+	//the named macro was implemented by an rfn which didn't come from a script
+	HostMacro(String),
+
+	//the named macro was bound by the given file, whose source text had the given hash
+	FileMacro(String, String, u64),
+
+	//the named constant was inlined, and its value had the given representation. it was bound by
+	//the given file, if known
+	Const(String, String, Option<(String, u64)>),
+
+	//the given file was loaded, or read using glsp::read_source, and its text had the given hash
+	Source(String, u64)
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct CacheEntry {
+	filename: String,
+	pub(crate) deps: Vec<Dep>,
+	pub(crate) recording: Vec<u8>
+}
+
+struct Frame {
+	filename: String,
+	hash: u64,
+	recording: bool,
+	deps: FnvHashSet<Dep>,
+	established: bool
+}
+
+pub(crate) struct CompileCache {
+	dir: Option<PathBuf>,
+	providers: FnvHashMap<Sym, (Expander, String, u64)>,
+	const_providers: FnvHashMap<Sym, (String, u64)>,
+	frames: Vec<Frame>,
+	stats: CompileCacheStats
+}
+
+impl CompileCache {
+	pub(crate) fn new() -> CompileCache {
+		CompileCache {
+			dir: None,
+			providers: FnvHashMap::default(),
+			const_providers: FnvHashMap::default(),
+			frames: Vec::new(),
+			stats: CompileCacheStats::default()
+		}
+	}
+
+	pub(crate) fn dir(&self) -> Option<&PathBuf> {
+		self.dir.as_ref()
+	}
+
+	pub(crate) fn set_dir(&mut self, dir: Option<PathBuf>) {
+		self.dir = dir;
+	}
+
+	pub(crate) fn stats(&self) -> CompileCacheStats {
+		self.stats
+	}
+
+	pub(crate) fn is_loading(&self) -> bool {
+		self.frames.len() > 0
+	}
+
+	pub(crate) fn is_recording(&self) -> bool {
+		self.frames.last().map_or(false, |frame| frame.recording)
+	}
+
+	pub(crate) fn push_frame(&mut self, filename: &str, hash: u64) {
+		self.frames.push(Frame {
+			filename: filename.to_string(),
+			hash,
+			recording: false,
+			deps: FnvHashSet::default(),
+			established: true
+		});
+	}
+
+	pub(crate) fn pop_frame(&mut self) {
+		self.frames.pop().unwrap();
+	}
+
+	//the Roots in `providers` need to be dropped before the heap
+	pub(crate) fn clear(&mut self) {
+		self.providers.clear();
+		self.const_providers.clear();
+		self.frames.clear();
+	}
+
+	pub(crate) fn start_recording(&mut self) {
+		self.frames.last_mut().unwrap().recording = true;
+	}
+
+	//returns the current frame's Deps, or None if they couldn't all be established
+	pub(crate) fn take_deps(&mut self) -> Option<Vec<Dep>> {
+		let frame = self.frames.last_mut().unwrap();
+		if frame.established {
+			Some(frame.deps.drain().collect())
+		} else {
+			None
+		}
+	}
+
+	pub(crate) fn record_hit(&mut self) {
+		self.stats.hits += 1;
+	}
+
+	pub(crate) fn record_miss(&mut self, stored: bool) {
+		if stored {
+			self.stats.misses += 1;
+		} else {
+			self.stats.uncacheable += 1;
+		}
+	}
+
+	//called when a global macro is bound or reassigned
+	pub(crate) fn note_provider(&mut self, sym: Sym, expander: &Expander) {
+		match self.frames.last() {
+			Some(frame) => {
+				let provider = (expander.clone(), frame.filename.clone(), frame.hash);
+				self.providers.insert(sym, provider);
+			}
+			None => {
+				self.providers.remove(&sym);
+			}
+		}
+	}
+
+	//called when a constant is bound or redefined
+	pub(crate) fn note_const_provider(&mut self, sym: Sym) {
+		match self.frames.last() {
+			Some(frame) => {
+				self.const_providers.insert(sym, (frame.filename.clone(), frame.hash));
+			}
+			None => {
+				self.const_providers.remove(&sym);
+			}
+		}
+	}
+
+	//returns None when the origin of the macro bound to `sym` is unknown
+	fn macro_dep(&self, sym: Sym, expander: Option<&Expander>) -> Option<Dep> {
+		let name = sym.name().to_string();
+		match (expander, self.providers.get(&sym)) {
+			(None, _) => Some(Dep::NoMacro(name)),
+			(Some(expander), Some(&(ref provider, ref filename, hash)))
+				if same_expander(expander, provider) => {
+
+				Some(Dep::FileMacro(name, filename.clone(), hash))
+			}
+			(Some(&Expander::RFn(_)), _) => Some(Dep::HostMacro(name)),
+			(Some(&Expander::GFn(_)), _) => None
+		}
+	}
+
+	pub(crate) fn note_macro(&mut self, sym: Sym, expander: Option<&Expander>) {
+		let dep = self.macro_dep(sym, expander);
+		let frame = self.frames.last_mut().unwrap();
+		match dep {
+			Some(Dep::FileMacro(_, ref filename, hash))
+				if *filename == frame.filename && hash == frame.hash => (),
+			Some(dep) => {
+				frame.deps.insert(dep);
+			}
+			None => frame.established = false
+		}
+	}
+
+	pub(crate) fn note_const(&mut self, sym: Sym, val: &Val) {
+		let provider = self.const_providers.get(&sym).cloned();
+		let frame = self.frames.last_mut().unwrap();
+		let dep = Dep::Const(sym.name().to_string(), format!("{:?}", val), provider);
+		frame.deps.insert(dep);
+	}
+
+	pub(crate) fn note_source(&mut self, filename: &str, text: &str) {
+		let dep = Dep::Source(filename.to_string(), hash_source(text));
+		self.frames.last_mut().unwrap().deps.insert(dep);
+	}
+
+	//a file which is loaded during a recording is a Dep of every file which is being recorded
+	pub(crate) fn note_load(&mut self, filename: &str, hash: u64) {
+		for frame in &mut self.frames {
+			if frame.recording {
+				frame.deps.insert(Dep::Source(filename.to_string(), hash));
+			}
+		}
+	}
+
+	//checks a macro or constant Dep against the current global bindings. Source deps are checked
+	//by the caller, since reading a file may call back into glsp. `loads` lists the files which 
+	//the entry will load itself, which have already been checked.
+	pub(crate) fn is_current(&self, dep: &Dep, loads: &FnvHashSet<(&str, u64)>) -> bool {
+		let sym = |name: &str| glsp::sym(name).ok();
+
+		match *dep {
+			Dep::NoMacro(ref name) | Dep::HostMacro(ref name) | Dep::FileMacro(ref name, _, _) => {
+				let sym = match sym(name) {
+					Some(sym) => sym,
+					None => return false
+				};
+
+				match (glsp::get_macro(sym).ok(), dep) {
+					(None, &Dep::FileMacro(_, ref filename, hash)) => {
+						loads.contains(&(&filename[..], hash))
+					}
+					(expander, dep) => self.macro_dep(sym, expander.as_ref()).as_ref() == Some(dep)
+				}
+			}
+			Dep::Const(ref name, ref repr, ref provider) => {
+				match (sym(name).and_then(glsp::inlined_const), provider) {
+					(Some(val), _) => format!("{:?}", val) == *repr,
+					(None, Some((filename, hash))) => loads.contains(&(&filename[..], *hash)),
+					(None, None) => false
+				}
+			}
+			Dep::Source(..) => unreachable!()
+		}
+	}
+}
+
+fn same_expander(a: &Expander, b: &Expander) -> bool {
+	match (a, b) {
+		(&Expander::GFn(ref a), &Expander::GFn(ref b)) => Root::<GFn>::ptr_eq(a, b),
+		(&Expander::RFn(a), &Expander::RFn(b)) => a == b,
+		_ => false
+	}
+}
+
+pub(crate) fn hash_source(text: &str) -> u64 {
+	let mut hasher = FnvHasher::default();
+	hasher.write(text.as_bytes());
+	hasher.finish()
+}
+
+pub(crate) fn entry_key(filename: &str, text: &str) -> u64 {
+	let mut hasher = FnvHasher::default();
+	hasher.write(env!("CARGO_PKG_VERSION").as_bytes());
+	hasher.write_u8(0);
+	hasher.write_u8(glsp::opt_level());
+	hasher.write_u8(FLO_WIDTH);
+	hasher.write(filename.as_bytes());
+	hasher.write_u8(0);
+	hasher.write(text.as_bytes());
+	hasher.finish()
+}
+
+fn entry_path(dir: &Path, key: u64) -> PathBuf {
+	dir.join(format!("{:016x}.glspc", key))
+}
+
+//a missing, unreadable or corrupt entry is just a cache miss
+pub(crate) fn read_entry(dir: &Path, key: u64, filename: &str) -> Option<CacheEntry> {
+	let bytes = fs::read(entry_path(dir, key)).ok()?;
+	let entry: CacheEntry = bincode::deserialize(&bytes).ok()?;
+
+	if entry.filename == filename {
+		Some(entry)
+	} else {
+		None
+	}
+}
+
+//the entry is written to a temporary file and then renamed, so that an interrupted write can't
+//leave a truncated entry behind. failing to write an entry isn't an error.
+pub(crate) fn write_entry(
+	dir: &Path,
+	key: u64,
+	filename: &str,
+	deps: Vec<Dep>,
+	recording: Vec<u8>
+) {
+	let entry = CacheEntry {
+		filename: filename.to_string(),
+		deps,
+		recording
+	};
+
+	let bytes = match bincode::serialize(&entry) {
+		Ok(bytes) => bytes,
+		Err(_) => return
+	};
+
+	let path = entry_path(dir, key);
+	let temp_path = path.with_extension("tmp");
+	if fs::write(&temp_path, &bytes).is_ok() && fs::rename(&temp_path, &path).is_err() {
+		let _ = fs::remove_file(&temp_path);
+	}
+}
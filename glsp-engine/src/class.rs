@@ -3,17 +3,19 @@ use smallvec::{SmallVec};
 use super::code::{GFn};
 use super::collections::{Arr, DequeAccess, DequeOps, Tab};
 use super::engine::{glsp, Guard, Sym, stock_syms::*, ToSym, with_heap, with_vm};
-use super::error::{GResult};
+use super::error::{GError, GResult, nonexistent_field_error, nonexistent_meth_error};
 use super::gc::{Allocate, Gc, GcHeader, Slot, Root, Visitor};
 use super::iter::{GIter, GIterState};
+use super::suggest;
 use super::val::{Val};
 use super::wrap::{CallableOps, FromVal, ToCallArgs, ToVal};
 use std::{u16, str};
-use std::cell::{RefCell, RefMut};
+use std::cell::{Cell, Ref, RefCell, RefMut};
 use std::cmp::{Ord};
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::iter::{FromIterator};
 use std::mem::{forget, size_of};
+use std::rc::{Rc};
 
 /*
 
@@ -41,10 +43,15 @@ Note that [`CallableOps`](trait.CallableOps.html) is implemented for `Root<Class
 
 pub struct Obj {
 	header: GcHeader,
-	class: Gc<Class>,
-	storage: RefCell<Option<ObjStorage>> //None for a killed obj
+	class: RefCell<Gc<Class>>, //replaced by glsp::reload when the class is redefined
+	storage: RefCell<Option<ObjStorage>>, //None for a killed obj
+
+	//set by glsp::reload when the obj couldn't be migrated to a redefined class. it's the 
+	//version of the class which the migration targeted.
+	stale: Cell<Option<u32>>
 }
 
+#[derive(Clone)]
 struct ObjStorage {
 	//there is a one-to-one relationship between (field ...) or (prop ...) declarations, and Slots 
 	//in this vec. while a state is disabled, all of its fields are set to Slot::Nil.
@@ -59,6 +66,12 @@ struct ObjStorage {
 	gc_self: Gc<Obj>
 }
 
+//an Obj's mutable state, saved by glsp::runtime_snapshot
+pub(crate) struct ObjContents {
+	class: Gc<Class>,
+	storage: Option<ObjStorage>
+}
+
 /**
 The `class` primitive type.
 
@@ -70,6 +83,7 @@ Note that [`CallableOps`](trait.CallableOps.html) is implemented for `Root<Class
 pub struct Class {
 	header: GcHeader,
 	name: Option<Sym>,
+	doc: Option<Rc<str>>,
 	is_mixin: bool,
 
 	//when the user attempts to access a field, const or method from outside the class, it's 
@@ -94,7 +108,11 @@ pub struct Class {
 	states: FnvHashMap<Sym, State>,
 
 	//see above
-	raw_class: Option<Box<RawClass>>
+	raw_class: Option<Box<RawClass>>,
+
+	//starts at 1. when glsp::reload redefines a class, the new class' version is one greater
+	//than the old class' version. only used for error messages.
+	version: Cell<u32>
 }
 
 //states are an illusion! classes are actually just a flat collection of bindings, where each
@@ -232,6 +250,7 @@ impl Class {
 			Ok(Class {
 				header: GcHeader::new(),
 				name: raw_class.name,
+				doc: raw_class.doc.clone(),
 				is_mixin: true,
 
 				bindings: FnvHashMap::default(),
@@ -241,7 +260,8 @@ impl Class {
 				is: FnvHashSet::default(),
 				states: FnvHashMap::default(),
 
-				raw_class: Some(Box::new(raw_class))
+				raw_class: Some(Box::new(raw_class)),
+				version: Cell::new(1)
 			})
 		} else {
 			raw_class.mix()?;
@@ -318,6 +338,12 @@ impl Class {
 		}
 	}
 
+	//the consts whose names are most similar to `name`, for "did you mean" suggestions
+	pub(crate) fn similar_names(&self, name: Sym) -> Vec<Sym> {
+		let candidates = self.bindings.keys().copied().filter(|&key| self.lookup(key).is_some());
+		suggest::suggest_names(&name.name(), candidates)
+	}
+
 	/**
 	Invokes a callable value stored in a constant.
 	
@@ -333,7 +359,7 @@ impl Class {
 
 		match self.call_if_present(sym, args)? {
 			Some(r) => Ok(r),
-			None => bail!("attempted to call nonexistent method '{}'", sym)
+			None => Err(nonexistent_meth_error(sym, None, self.similar_names(sym)))
 		}
 	}
 
@@ -405,6 +431,46 @@ impl Class {
 		self.name
 	}
 
+	/**
+	Returns the class' doc string, if any.
+
+	Equivalent to [`(doc cls)`](https://gamelisp.rs/std/doc).
+	*/
+	pub fn doc(&self) -> Option<Rc<str>> {
+		self.doc.clone()
+	}
+
+	/**
+	Returns the doc string of the method or callable constant bound to the given name, if any.
+
+	Methods are checked regardless of which states are currently enabled. Returns an `Err` if
+	no method or callable constant is bound to the name.
+
+	Equivalent to [`(doc cls key)`](https://gamelisp.rs/std/doc).
+	*/
+	pub fn meth_doc<S: ToSym>(&self, key: S) -> GResult<Option<Rc<str>>> {
+		let sym = key.to_sym()?;
+
+		let gfn = match self.bindings.get(&sym) {
+			Some(&Binding::Meth(MethBinding::Simple(_, ref gfn, _))) => Some(gfn.clone()),
+			Some(&Binding::Meth(MethBinding::Stackable(_, stack_index))) => {
+				match self.meth_stack[stack_index as usize] {
+					MethStackEntry::Meth(_, ref gfn, _) => Some(gfn.clone()),
+					MethStackEntry::End => None
+				}
+			}
+			_ => None
+		};
+
+		match (gfn, self.lookup(sym)) {
+			(Some(gfn), _) => Ok(gfn.doc()),
+			(None, Some(Slot::GFn(gfn))) => Ok(gfn.doc()),
+			(None, Some(Slot::RFn(_))) => Ok(None),
+			(None, Some(Slot::Class(class))) => Ok(class.doc()),
+			(None, _) => bail!("{} is not bound to a method", sym)
+		}
+	}
+
 	/**
 	Returns `true` if the class defines a state with the given name.
 
@@ -424,6 +490,84 @@ impl Class {
 		self.is.contains(mixin.as_gc())
 	}
 
+	//used by glsp::reload. two classes have the same layout if an obj's storage could be
+	//reinterpreted from one class to the other: the same state names at the same flag bits, and
+	//the same field names at the same ObjStorage indices.
+	pub(crate) fn has_same_layout(&self, other: &Class) -> bool {
+		fn layout(class: &Class) -> (Vec<(u32, u8)>, Vec<(u32, Vec<(u8, u16)>)>) {
+			let mut states = Vec::from_iter(class.states.iter().map(|(name, state)| {
+				(name.0, state.index)
+			}));
+			states.sort_unstable();
+
+			let mut fields = Vec::new();
+			for (name, binding) in &class.bindings {
+				let entries = match *binding {
+					Binding::SimpleField(state_i, field_i) => vec![(state_i, field_i)],
+					Binding::StackableField(stack_i) => {
+						class.field_stack[stack_i as usize..].iter()
+							.take_while(|entry| !matches!(entry, FieldStackEntry::End))
+							.filter_map(|entry| match *entry {
+								FieldStackEntry::Field(state_i, field_i) => {
+									Some((state_i, field_i))
+								}
+								_ => None
+							})
+							.collect()
+					}
+					_ => continue
+				};
+
+				fields.push((name.0, entries));
+			}
+			fields.sort_unstable();
+
+			(states, fields)
+		}
+
+		self.field_count == other.field_count && layout(self) == layout(other)
+	}
+
+	//maps each (field name, state name) pair to its index in the ObjStorage's `fields` vec. 
+	//used by Obj::migrate to match up the fields of an old and a new class.
+	fn field_indices(&self) -> FnvHashMap<(Sym, Sym), u16> {
+		let mut state_names = vec![MAIN_SYM; self.states.len()];
+		for (&name, state) in &self.states {
+			state_names[state.index as usize] = name;
+		}
+
+		let mut indices = FnvHashMap::default();
+		for (&name, binding) in &self.bindings {
+			match *binding {
+				Binding::SimpleField(state_i, field_i) => {
+					indices.insert((name, state_names[state_i as usize]), field_i);
+				}
+				Binding::StackableField(stack_i) => {
+					for entry in &self.field_stack[stack_i as usize..] {
+						match *entry {
+							FieldStackEntry::Field(state_i, field_i) => {
+								indices.insert((name, state_names[state_i as usize]), field_i);
+							}
+							FieldStackEntry::Const(..) => (),
+							FieldStackEntry::End => break
+						}
+					}
+				}
+				_ => ()
+			}
+		}
+
+		indices
+	}
+
+	pub(crate) fn version(&self) -> u32 {
+		self.version.get()
+	}
+
+	pub(crate) fn set_version(&self, version: u32) {
+		self.version.set(version)
+	}
+
 	/**
 	Returns `true` if the class is a mixin.
 
@@ -529,8 +673,9 @@ impl Obj {
 
 		let obj = Obj {
 			header: GcHeader::new(),
-			class: Gc::from_root(class),
-			storage: RefCell::new(None)
+			class: RefCell::new(Gc::from_root(class)),
+			storage: RefCell::new(None),
+			stale: Cell::new(None)
 		};
 
 		let root = glsp::alloc(obj);
@@ -594,18 +739,173 @@ impl Obj {
 
 	/**
 	Returns `true` if the object has been frozen.
+
+	A frozen object's fields can't be assigned to, and its states can't be enabled or disabled.
+	This means that it can't be killed.
 	*/
 	pub fn is_frozen(&self) -> bool {
 		self.header.frozen()
 	}
 
+	//used by val::visit_reachable. a killed obj has no fields.
+	pub(crate) fn field_vals(&self) -> Vec<Val> {
+		match *self.storage.borrow() {
+			Some(ref storage) => storage.fields.iter().map(|slot| slot.root()).collect(),
+			None => Vec::new()
+		}
+	}
+
 	/**
 	Returns the object's class.
 	
 	Equivalent to [`(class-of ob)`](https://gamelisp.rs/std/class-of).
 	*/
 	pub fn class(&self) -> Root<Class> {
-		self.class.root()
+		self.class.borrow().root()
+	}
+
+	pub(crate) fn has_class(&self, class: &Gc<Class>) -> bool {
+		Gc::ptr_eq(&self.class.borrow(), class)
+	}
+
+	pub(crate) fn save_contents(&self) -> ObjContents {
+		ObjContents {
+			class: self.class.borrow().clone(),
+			storage: self.storage.borrow().clone()
+		}
+	}
+
+	pub(crate) fn restore_contents(&self, saved: &ObjContents) {
+		*self.class.borrow_mut() = saved.class.clone();
+		self.storage.borrow_mut().clone_from(&saved.storage);
+	}
+
+	//used by glsp::reload. the caller is responsible for checking that the new class has the
+	//same layout as the old one. we never hold a borrow of `class` while running any glsp code,
+	//so this can't panic.
+	pub(crate) fn replace_class(&self, new_class: &Gc<Class>) {
+		with_heap(|heap| heap.write_barrier(self, new_class));
+		*self.class.borrow_mut() = new_class.clone();
+	}
+
+	//used by glsp::reload when the obj's class has been redefined with a different layout. if
+	//the new class has a `migrate` const, it's called with this obj, which is still an instance
+	//of its old class. it may return a new instance of the new class, in which case we move that
+	//instance's fields and states into this obj. if it returns #n or this obj, we copy each field
+	//to the new class' field with the same name and state, and fields with no counterpart are #n.
+	pub(crate) fn migrate(&self, new_class: &Root<Class>) -> GResult<()> {
+		let root = match *self.storage.borrow() {
+			Some(ref storage) => storage.gc_self.root(),
+			None => {
+				self.replace_class(&new_class.to_gc());
+				return Ok(())
+			}
+		};
+
+		let migrated = match new_class.call_if_present(MIGRATE_SYM, &[Val::Obj(root.clone())])? {
+			Some(val) => val,
+			None => Val::Nil
+		};
+
+		if self.is_killed() {
+			self.replace_class(&new_class.to_gc());
+			return Ok(())
+		}
+
+		match migrated {
+			Val::Nil => self.copy_fields_to(&new_class.to_gc()),
+			Val::Obj(ref obj) if Root::ptr_eq(obj, &root) => self.copy_fields_to(&new_class.to_gc()),
+			Val::Obj(ref obj) if Root::ptr_eq(&obj.class(), new_class) => {
+				//the returned obj is killed, without running its finalizers
+				let prev_usage = obj.memory_usage();
+				let storage = obj.storage.borrow_mut().take();
+				with_heap(|heap| heap.memory_usage_barrier(&**obj, prev_usage, obj.memory_usage()));
+
+				match storage {
+					Some(storage) => self.transplant(&new_class.to_gc(), storage),
+					None => bail!("the migrate function returned a killed obj")
+				}
+			}
+			val => {
+				bail!("the migrate function should return #n or an instance of the new class, \
+				       but it returned {}", val.a_type_name())
+			}
+		}
+
+		Ok(())
+	}
+
+	fn copy_fields_to(&self, new_class: &Gc<Class>) {
+		let old_class = self.class.borrow().clone();
+		let old_indices = old_class.field_indices();
+		let old_storage = self.storage.borrow().clone().unwrap();
+
+		let mut states_enabled = 0;
+		for (name, state) in &new_class.states {
+			if let Some(old_state) = old_class.states.get(name) {
+				if old_storage.states_enabled & (1 << old_state.index as u32) != 0 {
+					states_enabled |= 1 << state.index as u32;
+				}
+			}
+		}
+
+		let mut fields = vec![Slot::Nil; new_class.field_count];
+		for (key, new_i) in new_class.field_indices() {
+			if let Some(&old_i) = old_indices.get(&key) {
+				fields[new_i as usize] = old_storage.fields[old_i as usize].clone();
+			}
+		}
+
+		self.transplant(new_class, ObjStorage {
+			fields,
+			states_enabled,
+			gc_self: old_storage.gc_self
+		});
+	}
+
+	fn transplant(&self, new_class: &Gc<Class>, mut storage: ObjStorage) {
+		let prev_usage = self.memory_usage();
+
+		storage.gc_self = self.storage.borrow().as_ref().unwrap().gc_self.clone();
+		with_heap(|heap| {
+			for field in &storage.fields {
+				heap.write_barrier_slot(self, field);
+			}
+		});
+
+		*self.storage.borrow_mut() = Some(storage);
+		self.replace_class(new_class);
+
+		with_heap(|heap| heap.memory_usage_barrier(self, prev_usage, self.memory_usage()));
+	}
+
+	pub(crate) fn mark_stale(&self, version: u32) {
+		self.stale.set(Some(version));
+	}
+
+	/**
+	Returns `true` if this object's class was redefined by [`glsp::reload`](fn.reload.html), 
+	but the object couldn't be migrated to the new class.
+
+	A stale object is still an instance of its old class. Its fields can be accessed, but any 
+	attempt to call one of its methods will fail with a `stale-instance` error.
+	*/
+	pub fn is_stale(&self) -> bool {
+		self.stale.get().is_some()
+	}
+
+	fn stale_error(&self, method_name: Sym) -> Option<GError> {
+		let version = self.stale.get()?;
+		let class = self.class.borrow();
+		let class_name = match class.name {
+			Some(name) => name.to_string(),
+			None => "an anonymous class".to_string()
+		};
+
+		let message = format!("attempted to call the method '{}' on a stale instance of {}, \
+		                       which couldn't be migrated from version {} to version {}",
+		                       method_name, class_name, class.version(), version);
+		Some(GError::from_val(STALE_INSTANCE_SYM).with_source(GError::from_str(&message)))
 	}
 
 	/**
@@ -614,7 +914,22 @@ impl Obj {
 	Equivalent to [`(is? ob cls)`](https://gamelisp.rs/std/is-p).
 	*/
 	pub fn is(&self, class: &Root<Class>) -> bool {
-		Root::ptr_eq(&self.class.root(), class) || self.class.has_mixin(class)
+		Root::ptr_eq(&self.class.borrow().root(), class) || self.class.borrow().has_mixin(class)
+	}
+
+	//the fields (or methods) whose names are most similar to `name`, for "did you mean"
+	//suggestions. names which aren't bound in the obj's current states are ignored.
+	pub(crate) fn similar_names(&self, name: Sym, meths: bool) -> Vec<Sym> {
+		let keys: Vec<Sym> = self.class.borrow().bindings.keys().copied().collect();
+		let candidates = keys.into_iter().filter(|&key| {
+			match self.lookup(key) {
+				Lookup::FieldOrConst(_) | Lookup::PropGetter(_) => !meths,
+				Lookup::Meth(_) => meths,
+				Lookup::NotBound => false
+			}
+		});
+
+		suggest::suggest_names(&name.name(), candidates)
 	}
 
 	//the common backing function for get(), call(), has(), etc. 
@@ -631,7 +946,7 @@ impl Obj {
 			states_enabled & (1 << state_index as u32) != 0
 		};
 
-		if let Some(binding) = self.class.bindings.get(&key) {
+		if let Some(binding) = self.class.borrow().bindings.get(&key) {
 			match *binding {
 				Binding::SimpleField(state_index, field_index) => {
 					if state_is_accessible(state_index) {
@@ -649,7 +964,7 @@ impl Obj {
 				}
 				Binding::StackableField(mut stack_index) => {
 					loop {
-						match self.class.field_stack[stack_index as usize] {
+						match self.class.borrow().field_stack[stack_index as usize] {
 							FieldStackEntry::Field(state_index, field_index) => {
 								if state_is_accessible(state_index) {
 									let i = field_index as usize;
@@ -713,11 +1028,11 @@ impl Obj {
 			MethBinding::Stackable(state_index, mut stack_index) => {
 				if state_is_accessible(state_index) {
 					loop {
-						match self.class.meth_stack[stack_index as usize] {
+						match self.class.borrow().meth_stack[stack_index as usize] {
 							MethStackEntry::Meth(state_index, ref gfn, requires_next_index) => {
 								if state_is_accessible(state_index) {
 									let i = (stack_index + 1) as usize;
-									let next_index = match self.class.meth_stack[i] {
+									let next_index = match self.class.borrow().meth_stack[i] {
 										MethStackEntry::Meth(..) => Some((stack_index + 1) as u16),
 										MethStackEntry::End => None
 									};
@@ -754,7 +1069,7 @@ impl Obj {
 			states_enabled & (1 << state_index as u32) != 0
 		};
 
-		if let Some(binding) = self.class.bindings.get(&key) {
+		if let Some(binding) = self.class.borrow().bindings.get(&key) {
 			match *binding {
 				Binding::SimpleField(state_index, field_index) => {
 					if state_is_accessible(state_index) {
@@ -768,7 +1083,7 @@ impl Obj {
 				}
 				Binding::StackableField(mut stack_index) => {
 					loop {
-						match self.class.field_stack[stack_index as usize] {
+						match self.class.borrow().field_stack[stack_index as usize] {
 							FieldStackEntry::Field(state_index, field_index) => {
 								if state_is_accessible(state_index) {
 									let rm = RefMut::map(storage_ref, |s| {
@@ -862,7 +1177,7 @@ impl Obj {
 				self.invoke_method(&getter, &())
 			}
 			Lookup::Meth(..) => bail!("attempted to access method '{}' as a field", sym),
-			Lookup::NotBound => bail!("attempted to access nonexistent field '{}'", sym)
+			Lookup::NotBound => Err(nonexistent_field_error(sym, self.similar_names(sym, false)))
 		}
 	}
 
@@ -902,6 +1217,10 @@ impl Obj {
 		method_name: Sym
 	) -> GResult<Option<(Slot, bool, bool, Slot)>> 
 	{
+		if let Some(err) = self.stale_error(method_name) {
+			return Err(err)
+		}
+
 		Ok(match self.lookup(method_name) {
 			Lookup::FieldOrConst(slot) => {
 				match slot {
@@ -932,18 +1251,29 @@ impl Obj {
 		})
 	}
 
+	//the receiver's class and enabled states, which together determine the result of any method
+	//lookup. used by the inline cache in vm.rs. returns None for a killed or stale obj.
+	pub(crate) fn meth_cache_key(&self) -> Option<(Ref<Gc<Class>>, u32)> {
+		if self.stale.get().is_some() {
+			return None
+		}
+
+		let storage = self.storage.borrow();
+		storage.as_ref().map(|storage| (self.class.borrow(), storage.states_enabled))
+	}
+
 	//used by OpCallBaseRaw
 	pub(crate) fn get_base_raw_method(&self, mut index: usize) -> Option<MethLookup> {
 		let states_enabled = self.storage.borrow().as_ref().unwrap().states_enabled;
 
 		loop {
-			match self.class.meth_stack[index] {
+			match self.class.borrow().meth_stack[index] {
 				MethStackEntry::End => {
 					return None
 				}
 				MethStackEntry::Meth(state_id, ref gfn, requires_next_index) => { 
 					if states_enabled & (1 << state_id as u32) != 0 {
-						let next_index = match self.class.meth_stack[index + 1] {
+						let next_index = match self.class.borrow().meth_stack[index + 1] {
 							MethStackEntry::End => None,
 							_ => Some((index + 1) as u16)
 						};
@@ -987,7 +1317,7 @@ impl Obj {
 		let sym = key.to_sym()?;
 		match self.call_if_present(sym, args)? {
 			Some(r) => Ok(r),
-			None => bail!("attempted to call nonexistent method '{}'", sym)
+			None => Err(nonexistent_meth_error(sym, None, self.similar_names(sym, true)))
 		}
 	}
 
@@ -1004,6 +1334,10 @@ impl Obj {
 	{
 		let sym = key.to_sym()?;
 
+		if let Some(err) = self.stale_error(sym) {
+			return Err(err)
+		}
+
 		if self.storage.borrow().is_some() {
 			let slot = match self.lookup(sym) {
 				Lookup::Meth(meth_lookup) => {
@@ -1056,10 +1390,10 @@ impl Obj {
 	}
 
 	fn set_impl<S: ToSym, V: ToVal>(&self, key: S, value: V) -> GResult<Option<&'static str>> {
-		ensure!(!self.header.frozen(), "attempted to mutate a frozen obj");
+		let sym = key.to_sym()?;
+		ensure!(!self.header.frozen(), "attempted to set the field '{}' on a frozen obj", sym);
 		ensure!(self.storage.borrow().is_some(), "attempted to mutate a field on a killed obj");
 
-		let sym = key.to_sym()?;
 		match self.lookup_mut(sym) {
 			LookupMut::Field(mut field) => {
 				let slot = value.to_slot()?;
@@ -1106,7 +1440,7 @@ impl Obj {
 	Equivalent to [`(has-state? ob state-name)`](https://gamelisp.rs/std/has-state-p).
 	*/
 	pub fn has_state<S: ToSym>(&self, state_name: S) -> GResult<bool> {
-		Ok(self.class.states.contains_key(&state_name.to_sym()?))
+		Ok(self.class.borrow().states.contains_key(&state_name.to_sym()?))
 	}
 
 	/**
@@ -1123,7 +1457,7 @@ impl Obj {
 			None => bail!("attempted to query the state '{}' on a killed obj", sym)
 		};
 
-		match self.class.states.get(&sym) {
+		match self.class.borrow().states.get(&sym) {
 			Some(state_ref) => Ok(storage.states_enabled & (1 << state_ref.index as u32) != 0),
 			None => bail!("attempted to query a nonexistent state '{}'", sym)
 		}
@@ -1141,7 +1475,7 @@ impl Obj {
 	{
 		let sym = state_name.to_sym()?;
 
-		ensure!(self.class.states.contains_key(&sym),
+		ensure!(self.class.borrow().states.contains_key(&sym),
 		        "attempted to enable the nonexistent state '{}'", sym);
 		ensure!(!self.header.frozen(),
 		        "attempted to enable the state '{}' on a frozen obj", sym);
@@ -1176,11 +1510,12 @@ impl Obj {
 
 		let guard = Guard::new(|| self.kill_impl());
 
-		let state_ref = self.class.states.get(&state_name).unwrap();
+		let class = self.class.borrow().clone();
+		let state_ref = class.states.get(&state_name).unwrap();
 		let mut states_enabled = self.storage.borrow().as_ref().unwrap().states_enabled;
 
 		if let Some(parent_name) = state_ref.parent {
-			let parent_ref = self.class.states.get(&parent_name).unwrap();
+			let parent_ref = class.states.get(&parent_name).unwrap();
 			if states_enabled & (1 << parent_ref.index as u32) == 0 {
 				self.recursively_enable_state(parent_name, args)?;
 
@@ -1194,7 +1529,7 @@ impl Obj {
 		}
 
 		for sibling_name in &state_ref.fsm_siblings {
-			let sibling_ref = self.class.states.get(&sibling_name).unwrap();
+			let sibling_ref = class.states.get(&sibling_name).unwrap();
 			if states_enabled & (1 << sibling_ref.index as u32) != 0 {
 				self.disab(*sibling_name)?;
 				//states_enabled = self.storage.borrow().as_ref().unwrap().states_enabled;
@@ -1206,7 +1541,7 @@ impl Obj {
 		states_enabled = self.storage.borrow().as_ref().unwrap().states_enabled;
 
 		for child_name in &state_ref.children {
-			let child_ref = self.class.states.get(child_name).unwrap();
+			let child_ref = class.states.get(child_name).unwrap();
 			
 			if child_ref.enabled_by_default {
 				//we can't assume that the child state is disabled here, because this state's
@@ -1233,7 +1568,8 @@ impl Obj {
 		let mut storage_ref = self.storage.borrow_mut();
 		let storage = storage_ref.as_mut().unwrap();
 
-		let state_ref = self.class.states.get(&state_name).unwrap();
+		let class = self.class.borrow().clone();
+		let state_ref = class.states.get(&state_name).unwrap();
 		let state_bit = 1 << state_ref.index as u32;
 
 		ensure!(storage.states_enabled & state_bit == 0,
@@ -1288,13 +1624,14 @@ impl Obj {
 
 		//disable all of the immediate child states.
 		let states_enabled = self.storage.borrow().as_ref().unwrap().states_enabled;
-		let state_ref = match self.class.states.get(&state_name_sym) {
+		let class = self.class.borrow().clone();
+		let state_ref = match class.states.get(&state_name_sym) {
 			Some(state_ref) => state_ref,
 			None => bail!("attempted to disable the nonexistent state '{}'", state_name_sym)
 		};
 
 		for child_name in state_ref.children.iter().rev() {
-			let child_ref = self.class.states.get(&child_name).unwrap();
+			let child_ref = class.states.get(&child_name).unwrap();
 			if states_enabled & (1 << child_ref.index as u32) != 0 {
 				self.disab(*child_name)?;
 			}
@@ -1324,7 +1661,8 @@ impl Obj {
 		let mut storage_ref = self.storage.borrow_mut();
 		let storage = storage_ref.as_mut().unwrap();
 
-		let state_ref = self.class.states.get(&state_name).unwrap();
+		let class = self.class.borrow().clone();
+		let state_ref = class.states.get(&state_name).unwrap();
 		let state_bit = 1 << state_ref.index as u32;
 
 		ensure!(storage.states_enabled & state_bit != 0,
@@ -1336,7 +1674,7 @@ impl Obj {
 
 		//we need to replace any fields which belong to this state with #n, so that they
 		//don't live too long for gc purposes, or reappear when the state is re-enabled
-		for binding in self.class.bindings.values() {
+		for binding in class.bindings.values() {
 			if let Binding::SimpleField(field_state_index, field_index) = *binding {
 				if field_state_index == state_ref.index as u8 {
 					storage.fields[field_index as usize] = Slot::Nil;
@@ -1344,7 +1682,7 @@ impl Obj {
 			}
 		}
 
-		for entry in &self.class.field_stack {
+		for entry in &class.field_stack {
 			if let FieldStackEntry::Field(field_state_index, field_index) = *entry {
 				if field_state_index == state_ref.index as u8 {
 					storage.fields[field_index as usize] = Slot::Nil;
@@ -1395,7 +1733,7 @@ impl Allocate for Obj {
 	}
 
 	fn visit_gcs<V: Visitor>(&self, visitor: &mut V) {
-		visitor.visit_gc(&self.class);
+		visitor.visit_gc(&self.class.borrow());
 		
 		let storage_ref = self.storage.borrow();
 		if let Some(ref storage) = *storage_ref {
@@ -1560,6 +1898,7 @@ impl Allocate for Class {
 
 struct RawClass {
 	name: Option<Sym>,
+	doc: Option<Rc<str>>,
 	is_mixin: bool,
 	mixins: Vec<Gc<Class>>,
 	states: Vec<State>,
@@ -1647,6 +1986,7 @@ impl RawClass {
 
 	fn from_tab(tab: &Tab) -> GResult<RawClass> {
 		let name = tab.get_if_present::<_, Sym>(NAME_SYM)?;
+		let doc = tab.get_if_present::<_, String>(DOC_SYM)?.map(Rc::from);
 		let is_mixin = tab.get::<_, bool>(MIXINP_SYM)?;
 		let mixins = tab.get::<_, Vec<Gc<Class>>>(MIXIN_SYM)?;
 
@@ -1750,6 +2090,7 @@ impl RawClass {
 
 		Ok(RawClass {
 			name,
+			doc,
 			is_mixin,
 			mixins,
 			states,
@@ -2497,6 +2838,7 @@ impl ClassBuilder {
 		Class {
 			header: GcHeader::new(),
 			name: self.raw_class.name,
+			doc: self.raw_class.doc.clone(),
 			is_mixin: self.raw_class.is_mixin,
 
 			bindings: self.bindings,
@@ -2510,7 +2852,8 @@ impl ClassBuilder {
 				Some(self.raw_class)
 			} else {
 				None
-			}
+			},
+			version: Cell::new(1)
 		}
 	}
 }
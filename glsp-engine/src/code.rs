@@ -2,14 +2,19 @@ use std::{isize};
 use std::cell::{Cell, RefCell};
 use std::convert::{TryFrom};
 use std::fmt::{self, Debug, Formatter};
+use std::marker::{PhantomData};
 use std::mem::{size_of};
-use super::ast::{ParamList};
-use super::engine::{glsp, Span, Sym, with_heap};
-use super::error::{GError, GResult};
+use std::rc::{Rc};
+use super::ast::{Ast, ParamList};
+use super::class::{Class};
+use super::collections::{Arr, DequeOps};
+use super::corosave;
+use super::engine::{glsp, Span, Sym, stock_syms::*, with_heap};
+use super::error::{GError, GResult, MessageArgs, MessageId};
 use super::gc::{Allocate, Gc, GcHeader, Root, Slot, Visitor};
 use super::transform::{Predicate};
 use super::val::{Val};
-use super::wrap::{CallableOps};
+use super::wrap::{CallableOps, FromVal, ToCallArgs};
 
 #[cfg(feature = "compiler")]
 use serde::{Deserialize, Serialize};
@@ -25,7 +30,66 @@ pub struct Bytecode {
 	pub(crate) scratch_count: u8,
 	pub(crate) literal_count: u8,
 	pub(crate) lambdas: Vec<Gc<Lambda>>,
-	pub(crate) defers: Vec<usize>
+	pub(crate) defers: Vec<usize>,
+	pub(crate) inline_caches: RefCell<Vec<InlineCache>>
+}
+
+//each LoadGlobal, OpCallMeth and OpCallMethOpt instr stores an index into its Bytecode's
+//inline_caches, or NO_INLINE_CACHE if the Bytecode has run out of indexes. the vm uses these to
+//skip repeated global lookups and method lookups at the same call site.
+//
+//a Global entry is only valid while the vm's global generation is unchanged. the generation is 
+//bumped whenever a global which might be cached is mutated, rebound or unbound (see the `cached` 
+//flag in engine.rs). a Meth entry never goes stale, because classes are immutable: a redefined 
+//class is a distinct Class with a distinct pointer. the entry holds onto its Gc<Class>, so that 
+//pointer can't be recycled for some other class while the entry exists. Meth entries are also 
+//keyed on the method name, since call-meth's method name could be any expression.
+pub(crate) const NO_INLINE_CACHE: u16 = u16::MAX;
+
+pub(crate) enum InlineCache {
+	Empty,
+	Global(u64, Slot),
+	Meth {
+		class: Gc<Class>,
+		states_enabled: u32,
+		name: Sym,
+		gfn: Gc<GFn>,
+		requires_next_index: bool,
+		next_index: Option<u16>
+	}
+}
+
+impl Bytecode {
+	//assigns a cache index to each instr which can use one. called by encoder.rs
+	pub(crate) fn assign_inline_caches(instrs: &mut [Instr]) {
+		let mut next_id = 0u16;
+		for instr in instrs {
+			let id = match instr {
+				Instr::LoadGlobal(_, _, ref mut id) => id,
+				Instr::OpCallMeth(_, _, _, ref mut id) => id,
+				Instr::OpCallMethOpt(_, _, _, ref mut id) => id,
+				_ => continue
+			};
+
+			*id = next_id;
+			if next_id != NO_INLINE_CACHE {
+				next_id += 1;
+			}
+		}
+	}
+
+	pub(crate) fn empty_inline_caches(instrs: &[Instr]) -> RefCell<Vec<InlineCache>> {
+		let count = instrs.iter().filter_map(|instr| {
+			match *instr {
+				Instr::LoadGlobal(_, _, id) |
+				Instr::OpCallMeth(_, _, _, id) |
+				Instr::OpCallMethOpt(_, _, _, id) if id != NO_INLINE_CACHE => Some(id as usize + 1),
+				_ => None
+			}
+		}).max().unwrap_or(0);
+
+		RefCell::new((0 .. count).map(|_| InlineCache::Empty).collect())
+	}
 }
 
 #[derive(PartialEq)]
@@ -62,6 +126,7 @@ pub struct Lambda {
 	pub(crate) bytecode: Gc<Bytecode>,
 	pub(crate) param_map: ParamMap,
 	pub(crate) name: Option<Sym>,
+	pub(crate) doc: Option<Rc<str>>,
 	pub(crate) yields: bool,
 	
 	//the stay references which are cloned from the immediately-enclosing stack frame when we 
@@ -85,7 +150,10 @@ the [`CallableOps`](trait.CallableOps.html) trait. To invoke a function, use
 pub struct GFn {
 	header: GcHeader,
 	pub(crate) lambda: Gc<Lambda>,
-	pub(crate) captured_stays: Vec<Gc<Stay>>
+	pub(crate) captured_stays: Vec<Gc<Stay>>,
+
+	//shared with every WeakCallback for this function, and cleared when the function is freed
+	alive: RefCell<Option<Rc<Cell<bool>>>>
 }
 
 impl GFn {
@@ -93,7 +161,19 @@ impl GFn {
 		GFn {
 			header: GcHeader::new(),
 			lambda: lambda.clone(),
-			captured_stays
+			captured_stays,
+			alive: RefCell::new(None)
+		}
+	}
+
+	fn alive_flag(&self) -> Rc<Cell<bool>> {
+		let mut alive = self.alive.borrow_mut();
+		Rc::clone(alive.get_or_insert_with(|| Rc::new(Cell::new(true))))
+	}
+
+	fn mark_freed(&self) {
+		if let Some(ref alive) = *self.alive.borrow() {
+			alive.set(false);
 		}
 	}
 
@@ -105,6 +185,103 @@ impl GFn {
 	pub fn yields(&self) -> bool {
 		self.lambda.yields
 	}
+
+	/**
+	Returns this function's doc string, if any.
+
+	Equivalent to [`(doc f)`](https://gamelisp.rs/std/doc).
+	*/
+	pub fn doc(&self) -> Option<Rc<str>> {
+		self.lambda.doc.clone()
+	}
+
+	/**
+	Returns the minimum and maximum number of arguments which this function accepts.
+
+	Equivalent to [`(arity f)`](https://gamelisp.rs/std/arity).
+	*/
+	pub fn arity(&self) -> (usize, Option<usize>) {
+		let param_map = &self.lambda.param_map;
+		(param_map.min_args, param_map.max_args)
+	}
+
+	/**
+	Returns the name of each parameter, in the order they were written.
+
+	A parameter which was written as a pattern, like `[x y]` or `_`, is named `_`.
+
+	Returns `None` if this function was compiled while [`glsp::retain_param_names`][0] was 
+	disabled.
+
+	[0]: fn.retain_param_names.html
+	*/
+	pub fn param_names(&self) -> Option<Vec<Sym>> {
+		self.lambda.param_map.param_names.as_ref().map(|names| {
+			names.iter().map(|param_name| param_name.name()).collect()
+		})
+	}
+
+	/**
+	Returns this function's parameter list, with each pattern replaced by its name.
+
+	For example, the parameter list `(a [b c] (? d 10) ..e)` would be returned as 
+	`(a _ (? d) ..e)`. A parameter with a type annotation is returned with its annotation, so
+	`((a : int) (? (b : str) "") ..(c : sym))` would be returned unchanged, apart from the 
+	initializer. Returns `None` if this function was compiled while
+	[`glsp::retain_param_names`][0] was disabled.
+
+	Equivalent to [`(params f)`](https://gamelisp.rs/std/params).
+
+	[0]: fn.retain_param_names.html
+	*/
+	pub fn params(&self) -> Option<Root<Arr>> {
+		let param_map = &self.lambda.param_map;
+		let param_names = param_map.param_names.as_ref()?;
+
+		let params = glsp::arr();
+		for (i, param_name) in param_names.iter().enumerate() {
+			let param_type = param_map.param_types.as_ref().and_then(|types| types[i]);
+			let name = match param_type {
+				Some(param_type) => Val::Arr(arr![param_name.name(), COLON_SYM, param_type]),
+				None => Val::Sym(param_name.name())
+			};
+
+			match *param_name {
+				ParamName::Basic(_) => params.push(name).unwrap(),
+				ParamName::Opt(_) => params.push(arr![QUESTION_MARK_SYM, name]).unwrap(),
+				ParamName::Rest(_) => params.push(arr![SPLAY_SYM, name]).unwrap()
+			}
+		}
+
+		Some(params)
+	}
+
+	/**
+	Returns the type annotation of each parameter, in the order they were written.
+
+	An unannotated parameter is represented by `None`. Returns `None` if no parameter was 
+	annotated, or if this function was compiled while [`glsp::retain_param_names`][0] was
+	disabled.
+
+	[0]: fn.retain_param_names.html
+	*/
+	pub fn param_types(&self) -> Option<Vec<Option<Sym>>> {
+		self.lambda.param_map.param_types.as_ref().map(|types| types.to_vec())
+	}
+
+	/**
+	Returns this function's return-type annotation, if any.
+
+	Returns `None` if this function was compiled while [`glsp::retain_param_names`][0] was
+	disabled.
+
+	Equivalent to [`(return-type f)`](https://gamelisp.rs/std/return-type).
+
+	[0]: fn.retain_param_names.html
+	*/
+	pub fn return_type(&self) -> Option<Sym> {
+		self.lambda.param_map.return_type
+	}
 }
 
 //vm.rs currently needs to be able to copy its callee to a Slot, so we can't implement CallableOps
@@ -139,6 +316,200 @@ impl CallableOps for Gc<GFn> {
 	}
 }
 
+//with "unsafe-internals" enabled, a GFn is dropped when it's freed. otherwise, it's freed by
+//clear_gcs(), but it may not be dropped until its last WeakCallback is dropped.
+impl Drop for GFn {
+	fn drop(&mut self) {
+		self.mark_freed();
+	}
+}
+
+/**
+A GameLisp function with a statically-typed signature.
+
+`Callback` is a convenient way to store a GameLisp callback in a Rust struct. `A` is the type 
+of the arguments, which should usually be a tuple, and `R` is the return type.
+
+	struct Button {
+		on_click: Callback<(i32, i32), bool>
+	}
+
+	fn set_on_click(button: &mut Button, gfn: Root<GFn>) -> GResult<()> {
+		button.on_click = Callback::new(gfn)?;
+		Ok(())
+	}
+
+	let handled = button.on_click.call(&(x, y))?;
+
+A `Callback` keeps its function alive, in the same way as a `Root<GFn>`. If you'd prefer to let
+the function be garbage-collected, use a [`WeakCallback`](struct.WeakCallback.html) instead.
+
+Cloning a `Callback` is cheap.
+*/
+
+pub struct Callback<A: ToCallArgs + ?Sized, R: FromVal> {
+	gfn: Root<GFn>,
+	bound_at: Option<Rc<str>>,
+	phantom: PhantomData<fn(&A) -> R>
+}
+
+impl<A: ToCallArgs + ?Sized, R: FromVal> Callback<A, R> {
+	/**
+	Wraps a GameLisp function.
+
+	Returns an `Err` if the function can't accept the number of arguments in `A`. When `A` is 
+	a slice, the number of arguments isn't checked until the callback is called.
+
+	The current [file location](fn.file_location.html), if any, is recorded, so that errors
+	can describe where the callback was bound.
+	*/
+	pub fn new(gfn: Root<GFn>) -> GResult<Callback<A, R>> {
+		let callback = Callback {
+			gfn,
+			bound_at: glsp::file_location().map(Rc::from),
+			phantom: PhantomData
+		};
+
+		if let Some(arg_count) = A::ARG_COUNT {
+			let (min_args, max_args) = callback.gfn.arg_limits();
+			if arg_count < min_args || max_args.map_or(false, |max_args| arg_count > max_args) {
+				let (expected, plural) = match max_args {
+					Some(max_args) if max_args == min_args => {
+						(format!("exactly {}", min_args), max_args != 1)
+					}
+					Some(max_args) => (format!("{} to {}", min_args, max_args), true),
+					None => (format!("at least {}", min_args), min_args != 1)
+				};
+
+				bail!("{} expects {} argument{}, but its signature passes {}",
+				      callback.description(), expected, if plural { "s" } else { "" }, arg_count)
+			}
+		}
+
+		Ok(callback)
+	}
+
+	/**
+	Calls the function, converting its arguments using [`ToVal`](trait.ToVal.html) and its
+	return value using [`FromVal`](trait.FromVal.html).
+
+	If the return value can't be converted to `R`, the error describes where the callback was
+	bound.
+	*/
+	pub fn call(&self, args: &A) -> GResult<R> {
+		let val: Val = glsp::call(&self.gfn, args)?;
+		R::from_val(&val).map_err(|err| {
+			error!("{} returned {}, which is invalid", self.description(), val.a_type_name())
+				.with_source(err)
+		})
+	}
+
+	///Returns the wrapped function.
+	pub fn gfn(&self) -> &Root<GFn> {
+		&self.gfn
+	}
+
+	///Creates a [`WeakCallback`](struct.WeakCallback.html) for the same function.
+	pub fn downgrade(&self) -> WeakCallback<A, R> {
+		WeakCallback {
+			gfn: self.gfn.to_gc(),
+			alive: self.gfn.alive_flag(),
+			bound_at: self.bound_at.clone(),
+			phantom: PhantomData
+		}
+	}
+
+	fn description(&self) -> String {
+		describe_callback(self.gfn.lambda.name, &self.bound_at)
+	}
+}
+
+impl<A: ToCallArgs + ?Sized, R: FromVal> Clone for Callback<A, R> {
+	fn clone(&self) -> Callback<A, R> {
+		Callback {
+			gfn: self.gfn.clone(),
+			bound_at: self.bound_at.clone(),
+			phantom: PhantomData
+		}
+	}
+}
+
+fn describe_callback(name: Option<Sym>, bound_at: &Option<Rc<str>>) -> String {
+	let mut description = String::from("callback");
+	if let Some(name) = name {
+		description.push_str(&format!(" ({})", name));
+	}
+
+	if let Some(ref bound_at) = *bound_at {
+		description.push_str(&format!(" bound at {}", bound_at));
+	}
+
+	description
+}
+
+/**
+A [`Callback`](struct.Callback.html) which doesn't keep its function alive.
+
+Once the function has been garbage-collected, [`upgrade`](#method.upgrade) will return `None`, 
+and [`call`](#method.call) will return `Ok(None)`. This is useful when a Rust struct holds a 
+callback which belongs to a GameLisp object, so that the callback's captured environment 
+doesn't outlive the object.
+
+Created using [`Callback::downgrade`](struct.Callback.html#method.downgrade).
+*/
+
+pub struct WeakCallback<A: ToCallArgs + ?Sized, R: FromVal> {
+	//this Gc isn't traced, and it may have been freed. we only dereference it while `alive` is set
+	gfn: Gc<GFn>,
+	alive: Rc<Cell<bool>>,
+	bound_at: Option<Rc<str>>,
+	phantom: PhantomData<fn(&A) -> R>
+}
+
+impl<A: ToCallArgs + ?Sized, R: FromVal> WeakCallback<A, R> {
+	/**
+	Returns a strong `Callback` for the same function, or `None` if the function has been
+	garbage-collected.
+	*/
+	pub fn upgrade(&self) -> Option<Callback<A, R>> {
+		if !self.alive.get() {
+			return None
+		}
+
+		with_heap(|heap| heap.root_untraced(&self.gfn)).map(|gfn| {
+			Callback {
+				gfn,
+				bound_at: self.bound_at.clone(),
+				phantom: PhantomData
+			}
+		})
+	}
+
+	/**
+	Calls the function, or returns `Ok(None)` if the function has been garbage-collected.
+
+	Equivalent to calling [`Callback::call`](struct.Callback.html#method.call) on the result
+	of [`upgrade`](#method.upgrade).
+	*/
+	pub fn call(&self, args: &A) -> GResult<Option<R>> {
+		match self.upgrade() {
+			Some(callback) => Ok(Some(callback.call(args)?)),
+			None => Ok(None)
+		}
+	}
+}
+
+impl<A: ToCallArgs + ?Sized, R: FromVal> Clone for WeakCallback<A, R> {
+	fn clone(&self) -> WeakCallback<A, R> {
+		WeakCallback {
+			gfn: self.gfn.clone(),
+			alive: Rc::clone(&self.alive),
+			bound_at: self.bound_at.clone(),
+			phantom: PhantomData
+		}
+	}
+}
+
 //using Cell rather than RefCell here shrinks Stay by one word. it requires a small amount of
 //juggling when accessing or tracing the stored Slot, but i'm hopeful that should be optimised 
 //away, at least in "unsafe-internals" mode.
@@ -202,12 +573,13 @@ pub enum CoroState {
 pub(crate) enum PrivCoroState {
 	Newborn,
 	Running,
-	Paused(u8), //the u8 is the resume_reg
+	Paused(Option<u8>), //the resume_reg, or None if the coro was paused by a safepoint
 	Finished,
 	Poisoned,
 	Recycling
 }
 
+#[derive(Clone)]
 pub(crate) struct CoroStorage {
 	//this is only None in the Recycling state
 	pub(crate) gfn: Option<Gc<GFn>>,
@@ -283,6 +655,31 @@ impl Coro {
 			PrivCoroState::Recycling => unreachable!()
 		}
 	}
+
+	/**
+	Returns `Ok` if this coroutine can be saved using [`glsp::coro_save`](fn.coro_save.html).
+
+	Equivalent to [`(coro-saveable? co)`](https://gamelisp.rs/std/coro-saveable-p), except
+	that the error describes why the coroutine can't be saved.
+
+	The coroutine must be `newborn`, or `paused` by a [`yield`](https://gamelisp.rs/std/yield)
+	form. Its function must be bound to a global with the same name, and it can't capture any
+	local variables. Each of the coroutine's local variables, and each of its temporary
+	values, must be [representable](struct.Val.html#method.check_representability) - except
+	that a function or class which is bound to a global with the same name is saved by name.
+	*/
+	pub fn check_saveability(&self) -> GResult<()> {
+		corosave::check_saveability(self)
+	}
+
+	//true if the coro is paused within the scope of a (defer). the gc won't free such a coro
+	//until its defers have been run by glsp::gc.
+	pub(crate) fn has_pending_defers(&self) -> bool {
+		match self.state.get() {
+			PrivCoroState::Paused(_) => !self.storage.borrow().defers.is_empty(),
+			_ => false
+		}
+	}
 }
 
 impl Allocate for Bytecode {
@@ -314,6 +711,17 @@ impl Allocate for Bytecode {
 		for lambda in &self.lambdas {
 			visitor.visit_gc(lambda);
 		}
+
+		for cache in self.inline_caches.borrow().iter() {
+			match *cache {
+				InlineCache::Empty => (),
+				InlineCache::Global(_, ref slot) => visitor.visit_slot(slot),
+				InlineCache::Meth { ref class, ref gfn, .. } => {
+					visitor.visit_gc(class);
+					visitor.visit_gc(gfn);
+				}
+			}
+		}
 	}
 
 	fn clear_gcs(&self) {
@@ -327,6 +735,7 @@ impl Allocate for Bytecode {
 		+ self.start_stays.capacity() * size_of::<StaySource>()
 		+ self.lambdas.capacity() * size_of::<Gc<Lambda>>()
 		+ self.defers.capacity() * size_of::<usize>()
+		+ self.inline_caches.borrow().capacity() * size_of::<InlineCache>()
 	}
 }
 
@@ -345,6 +754,12 @@ impl Allocate for Lambda {
 
 	fn owned_memory_usage(&self) -> usize {
 		self.captures.capacity()
+		+ self.param_map.param_names.as_ref().map_or(0, |names| {
+			names.len() * size_of::<ParamName>()
+		})
+		+ self.param_map.param_types.as_ref().map_or(0, |types| {
+			types.len() * size_of::<Option<Sym>>()
+		})
 	}
 }
 
@@ -361,7 +776,9 @@ impl Allocate for GFn {
 	}
 
 	fn clear_gcs(&self) {
-		//deliberate no-op
+		//we don't need to clear any Gcs, but this is our only notification that we've been 
+		//freed when "unsafe-internals" is disabled
+		self.mark_freed();
 	}
 
 	fn owned_memory_usage(&self) -> usize {
@@ -443,13 +860,43 @@ pub(crate) struct ParamMap {
 	//None for no param, Some(false) for .._, Some(true) for ..name
 	pub(crate) rest_param: Option<bool>,
 
+	//None when glsp::retain_param_names() was disabled during compilation
+	pub(crate) param_names: Option<Box<[ParamName]>>,
+
+	//type annotations, from the &param-types and &return-type flags. param_types is parallel to
+	//param_names, and it's None when no parameter was annotated.
+	pub(crate) param_types: Option<Box<[Option<Sym>]>>,
+	pub(crate) return_type: Option<Sym>,
+
 	min_args: usize,
 	max_args: Option<usize>
 }
 
+//a parameter as it was written in the source code, before the (fn) macro replaced it with a 
+//gensym. a parameter which isn't a plain symbol is named `_`.
+#[derive(Copy, Clone)]
+#[cfg_attr(feature = "compiler", derive(Deserialize, Serialize))]
+pub(crate) enum ParamName {
+	Basic(Sym),
+	Opt(Sym),
+	Rest(Sym)
+}
+
+impl ParamName {
+	pub(crate) fn name(&self) -> Sym {
+		match *self {
+			ParamName::Basic(name) | ParamName::Opt(name) | ParamName::Rest(name) => name
+		}
+	}
+}
+
 impl ParamMap {
 	pub(crate) fn from_param_list(
+		ast: &Ast,
 		param_list: &ParamList,
+		param_names: &Option<Box<[ParamName]>>,
+		param_types: &Option<Box<[Option<Sym>]>>,
+		return_type: Option<Sym>,
 		arg_limits: &Option<(usize, Option<usize>)>,
 		span: Span,
 	) -> GResult<ParamMap> {
@@ -459,6 +906,53 @@ impl ParamMap {
 		let opt_param_count = param_list.opt_params.len();
 		let rest_param = param_list.rest_param.map(|o| o.is_some());
 
+		//the (fn) macro uses the &param-names flag to preserve the names of any parameters 
+		//which it replaced with gensyms
+		let param_names = if !glsp::retain_param_names() {
+			None
+		} else if let Some(ref param_names) = *param_names {
+			Some(param_names.clone())
+		} else {
+			let mut names = Vec::with_capacity(param_count);
+			for binding in &ast[param_list.basic_params] {
+				names.push(ParamName::Basic(binding.name));
+			}
+			for binding in &ast[param_list.opt_params] {
+				names.push(ParamName::Opt(binding.name));
+			}
+			match param_list.rest_param {
+				Some(Some(binding)) => names.push(ParamName::Rest(ast[binding].name)),
+				Some(None) => names.push(ParamName::Rest(UNDERSCORE_SYM)),
+				None => ()
+			}
+
+			Some(names.into_boxed_slice())
+		};
+
+		//annotations are stored alongside the parameter names, so they're discarded along with
+		//them. a list with no annotations isn't worth storing.
+		let (param_types, return_type) = match param_names {
+			None => (None, None),
+			Some(ref param_names) => {
+				let param_types = match *param_types {
+					Some(ref param_types) => {
+						ensure_at!(span, param_types.len() == param_names.len(), 
+						           "the &param-types flag has {} elements, but the fn has {} \
+						           parameters", param_types.len(), param_names.len());
+
+						if param_types.iter().any(|param_type| param_type.is_some()) {
+							Some(param_types.clone())
+						} else {
+							None
+						}
+					}
+					None => None
+				};
+
+				(param_types, return_type)
+			}
+		};
+
 		let (min_args, max_args) = match arg_limits {
 			Some(pair) => {
 				ensure_at!(span, basic_param_count == 0 && opt_param_count == 0 && 
@@ -483,6 +977,9 @@ impl ParamMap {
 			basic_param_count,
 			opt_param_count,
 			rest_param,
+			param_names,
+			param_types,
+			return_type,
 
 			min_args,
 			max_args
@@ -512,18 +1009,34 @@ impl ParamMap {
 		start_regs: &[Slot],
 		arg_count: usize, 
 		callsite: Option<Span>
-	) -> Result<(), String> {
+	) -> Result<(), (MessageId, MessageArgs)> {
 
 		if arg_count < self.min_args {
-			return Err(format!("received {} argument{}, but expected at least {}",
-			                   arg_count, if arg_count == 1 { "" } else { "s" }, self.min_args))
+			let mut args = MessageArgs::new()
+				.with("received", arg_count)
+				.with("min-args", self.min_args);
+
+			if let Some(ref param_names) = self.param_names {
+				let missing_end = self.basic_param_count.min(param_names.len());
+				if arg_count < missing_end {
+					let missing: Vec<String> = param_names[arg_count .. missing_end]
+						.iter()
+						.map(|p| format!("`{}`", p.name()))
+						.collect();
+
+					args = args.with("missing", missing.join(", "));
+				}
+			}
+
+			return Err((MessageId::TooFewArgs, args))
 		}
 
 		if let Some(max_args) = self.max_args {
 			if arg_count > max_args {
-				return Err(format!("received {} argument{}, but expected no more than {}",
-				                   arg_count, if arg_count == 1 { "" } else { "s" },
-				                   max_args))
+				let args = MessageArgs::new()
+					.with("received", arg_count)
+					.with("max-args", max_args);
+				return Err((MessageId::TooManyArgs, args))
 			}
 		}
 
@@ -572,7 +1085,7 @@ pub(crate) enum Instr {
 	//if you add or remove registers from any variant, don't forget to change the register_mut
 	//method at the bottom of this file! you'll also need to amend print.rs, vm.rs and encoder.rs.
 	CopyRegister(u8, u8),
-	LoadGlobal(u8, SymBytes),
+	LoadGlobal(u8, SymBytes, u16),
 	SetGlobal(u8, SymBytes),
 	LoadStay(u8, u8),
 	SetStay(u8, u8),
@@ -582,12 +1095,25 @@ pub(crate) enum Instr {
 	Call1(u8, u8, u8),
 	Call2(u8, u8, u8, u8),
 	CallN(u8, u8, u8),
+	TailCall0(u8),
+	TailCall1(u8, u8),
+	TailCall2(u8, u8, u8),
+	TailCallN(u8, u8),
 	Splay([u8; 4]),
 	Return(u8),
 	Yield(u8, u8),
 	Jump(JumpBytes),
 	JumpIfTrue(u8, JumpBytes),
 	JumpIfFalse(u8, JumpBytes),
+
+	//superinstructions, produced by the peephole pass in encoder.rs. each of these is equivalent 
+	//to a comparison op which writes to its dst_reg, followed by a JumpIfFalse of that dst_reg.
+	NumEqJumpIfFalse(u8, u8, u8, JumpBytes),
+	LtJumpIfFalse(u8, u8, u8, JumpBytes),
+	LteJumpIfFalse(u8, u8, u8, JumpBytes),
+	GtJumpIfFalse(u8, u8, u8, JumpBytes),
+	GteJumpIfFalse(u8, u8, u8, JumpBytes),
+
 	PushDefer(u8),
 	RunAndPopDefers(u8),
 	RunDefer(u8),
@@ -601,6 +1127,13 @@ pub(crate) enum Instr {
 	OpAbs(u8, u8),
 	OpNeg(u8, u8),
 	OpSign(u8, u8),
+	OpBitAnd(u8, u8, u8),
+	OpBitOr(u8, u8, u8),
+	OpBitXor(u8, u8, u8),
+	OpBitNot(u8, u8),
+	OpShl(u8, u8, u8),
+	OpShr(u8, u8, u8),
+	OpSar(u8, u8, u8),
 	OpMin(u8, u8, u8),
 	OpMax(u8, u8, u8),
 	OpPredicate(u8, u8, Predicate),
@@ -622,8 +1155,8 @@ pub(crate) enum Instr {
 	OpAccess(u8, u8, u8),
 	OpSetAccess(u8, u8, u8, u8),
 	OpArr(u8, u8, u8),
-	OpCallMeth(u8, u8, u8),
-	OpCallMethOpt(u8, u8, u8),
+	OpCallMeth(u8, u8, u8, u16),
+	OpCallMethOpt(u8, u8, u8, u16),
 	OpCallBaseRaw(u8, u8, u8),
 	OpGlobal(u8, u8),
 	OpSetGlobal(u8, u8, u8)
@@ -721,12 +1254,21 @@ impl Instr {
 			Call1(a 0, b 1, c 2),
 			Call2(a 0, b 1, c 2, d 3),
 			CallN(a 0, b 1, c 2),
+			TailCall0(a 0),
+			TailCall1(a 0, b 1),
+			TailCall2(a 0, b 1, c 2),
+			TailCallN(a 0, b 1),
 			Splay(),
 			Return(a 0),
 			Yield(a 0, b 1),
 			Jump(),
 			JumpIfTrue(a 0),
 			JumpIfFalse(a 0),
+			NumEqJumpIfFalse(a 0, b 1, c 2),
+			LtJumpIfFalse(a 0, b 1, c 2),
+			LteJumpIfFalse(a 0, b 1, c 2),
+			GtJumpIfFalse(a 0, b 1, c 2),
+			GteJumpIfFalse(a 0, b 1, c 2),
 			PushDefer(),
 			RunAndPopDefers(),
 			RunDefer(),
@@ -740,6 +1282,13 @@ impl Instr {
 			OpAbs(a 0, b 1),
 			OpNeg(a 0, b 1),
 			OpSign(a 0, b 1),
+			OpBitAnd(a 0, b 1, c 2),
+			OpBitOr(a 0, b 1, c 2),
+			OpBitXor(a 0, b 1, c 2),
+			OpBitNot(a 0, b 1),
+			OpShl(a 0, b 1, c 2),
+			OpShr(a 0, b 1, c 2),
+			OpSar(a 0, b 1, c 2),
 			OpMin(a 0, b 1, c 2),
 			OpMax(a 0, b 1, c 2),
 			OpPredicate(a 0, b 1),
@@ -776,7 +1325,28 @@ impl Instr {
 			Jump(ref mut bytes) => *bytes = new_bytes,
 			JumpIfTrue(_, ref mut bytes) => *bytes = new_bytes,
 			JumpIfFalse(_, ref mut bytes) => *bytes = new_bytes,
+			NumEqJumpIfFalse(_, _, _, ref mut bytes) => *bytes = new_bytes,
+			LtJumpIfFalse(_, _, _, ref mut bytes) => *bytes = new_bytes,
+			LteJumpIfFalse(_, _, _, ref mut bytes) => *bytes = new_bytes,
+			GtJumpIfFalse(_, _, _, ref mut bytes) => *bytes = new_bytes,
+			GteJumpIfFalse(_, _, _, ref mut bytes) => *bytes = new_bytes,
 			_ => panic!()
 		}
 	}
+
+	pub fn jump_bytes(&self) -> Option<JumpBytes> {
+		use Instr::*;
+
+		match *self {
+			Jump(bytes) => Some(bytes),
+			JumpIfTrue(_, bytes) => Some(bytes),
+			JumpIfFalse(_, bytes) => Some(bytes),
+			NumEqJumpIfFalse(_, _, _, bytes) => Some(bytes),
+			LtJumpIfFalse(_, _, _, bytes) => Some(bytes),
+			LteJumpIfFalse(_, _, _, bytes) => Some(bytes),
+			GtJumpIfFalse(_, _, _, bytes) => Some(bytes),
+			GteJumpIfFalse(_, _, _, bytes) => Some(bytes),
+			_ => None
+		}
+	}
 }
@@ -10,11 +10,12 @@ use std::iter::{FromIterator, FusedIterator, repeat};
 use std::marker::{PhantomData};
 use std::mem::{size_of};
 use std::ops::{Bound, RangeBounds};
+use std::ptr;
 use super::engine::{glsp, Guard, Span, with_heap};
-use super::error::{GResult};
+use super::error::{GResult, out_of_bounds_error};
 use super::gc::{Allocate, GcHeader, Slot, Root, Visitor};
-use super::iter::{GIter, GIterState};
-use super::val::{Val};
+use super::iter::{GIter, GIterState, TabPart};
+use super::val::{Val, visit_reachable};
 use super::wrap::{FromVal, ToVal};
 
 
@@ -67,17 +68,17 @@ macro_rules! impl_arr_index_unsigned {
 		impl DequeIndex for $type {
 			#[inline(always)]
 			fn as_usize<A>(&self, arr: &A) -> GResult<usize> where A: DequeOps {
-				ensure!((*self as usize) < arr.len(), 
-				        "out-of-bounds arr access: len is {}, index is {}",
-				        arr.len(), *self);
+				if (*self as usize) >= arr.len() {
+					return Err(out_of_bounds_error("arr", arr.len(), *self))
+				}
 				Ok(*self as usize)
 			}
 
 			#[inline(always)]
 			fn as_usize_excluded<A>(&self, arr: &A) -> GResult<usize> where A: DequeOps {
-				ensure!((*self as usize) <= arr.len(), 
-				        "out-of-bounds arr access: len is {}, index is {}",
-				        arr.len(), *self);
+				if (*self as usize) > arr.len() {
+					return Err(out_of_bounds_error("arr", arr.len(), *self))
+				}
 				Ok(*self as usize)
 			}
 		}
@@ -97,13 +98,15 @@ macro_rules! impl_arr_index_signed {
 			#[inline(always)]
 			fn as_usize<A>(&self, arr: &A) -> GResult<usize> where A: DequeOps {
 				if *self >= 0 {
-					ensure!((*self as usize) < arr.len(), 
-					        "out-of-bounds arr access: len is {}, index is {}", arr.len(), *self);
+					if (*self as usize) >= arr.len() {
+						return Err(out_of_bounds_error("arr", arr.len(), *self))
+					}
 					Ok(*self as usize)
 				} else {
 					let index = arr.len() as isize + (*self as isize);
-					ensure!(index >= 0 && (index as usize) < arr.len(), 
-					        "out-of-bounds arr access: len is {}, index is {}", arr.len(), *self);
+					if index < 0 || (index as usize) >= arr.len() {
+						return Err(out_of_bounds_error("arr", arr.len(), *self))
+					}
 
 					Ok(index as usize)
 				}
@@ -112,13 +115,15 @@ macro_rules! impl_arr_index_signed {
 			#[inline(always)]
 			fn as_usize_excluded<A>(&self, arr: &A) -> GResult<usize> where A: DequeOps {
 				if *self >= 0 {
-					ensure!(*self as usize <= arr.len(), 
-					        "out-of-bounds arr access: len is {}, index is {}", arr.len(), *self);
+					if *self as usize > arr.len() {
+						return Err(out_of_bounds_error("arr", arr.len(), *self))
+					}
 					Ok(*self as usize)
 				} else {
 					let index = arr.len() as isize + (*self as isize);
-					ensure!(index >= 0 && index as usize <= arr.len(), 
-					        "out-of-bounds arr access: len is {}, index is {}", arr.len(), *self);
+					if index < 0 || index as usize > arr.len() {
+						return Err(out_of_bounds_error("arr", arr.len(), *self))
+					}
 
 					Ok(index as usize)
 				}
@@ -928,8 +933,8 @@ impl Arr {
 		self.vec.borrow()
 	}
 
-	pub(crate) fn borrow_mut(&self) -> GResult<RefMut<VecDeque<Slot>>> {
-		ensure!(!self.header.frozen(), "attempted to mutate a frozen arr");
+	pub(crate) fn borrow_mut(&self, op: &str) -> GResult<RefMut<VecDeque<Slot>>> {
+		ensure!(!self.header.frozen(), "attempted to {} a frozen arr", op);
 		match self.vec.try_borrow_mut() {
 			Ok(ref_mut) => Ok(ref_mut),
 			Err(_) => bail!("attempted to mutate a borrowed arr")
@@ -949,7 +954,7 @@ impl Arr {
 		with_heap(|heap| heap.memory_usage_barrier(self, prev_usage, cur_usage));
 	}
 
-	fn borrow_mut_with_capacity_guard<R, F>(&self, f: F) -> GResult<R> 
+	fn borrow_mut_with_capacity_guard<R, F>(&self, op: &str, f: F) -> GResult<R> 
 	where
 		F: FnOnce(&mut RefMut<VecDeque<Slot>>) -> GResult<R>
 	{
@@ -961,10 +966,19 @@ impl Arr {
 			}
 		});
 
-		let mut ref_mut = self.borrow_mut()?;
+		let mut ref_mut = self.borrow_mut(op)?;
 
 		f(&mut ref_mut)
 	}
+
+	//used by glsp::runtime_snapshot and glsp::restore_runtime
+	pub(crate) fn save_contents(&self) -> VecDeque<Slot> {
+		self.vec.borrow().clone()
+	}
+
+	pub(crate) fn restore_contents(&self, saved: &VecDeque<Slot>) {
+		self.vec.borrow_mut().clone_from(saved);
+	}
 }
 
 impl PartialEq<Arr> for Arr {
@@ -987,21 +1001,20 @@ impl DequeOps for Arr {
 		self.header.freeze()
 	}
 
-	//todo: handle reference cycles
 	fn deep_freeze(&self) {
 		self.freeze();
-		for element in self.iter() {
-			element.deep_freeze();
-		}
+		visit_reachable(self.iter(), |val| {
+			val.freeze();
+			true
+		});
 	}
 
 	fn is_frozen(&self) -> bool {
 		self.header.frozen()
 	}
 
-	//todo: handle reference cycles
 	fn is_deep_frozen(&self) -> bool {
-		self.header.frozen() && self.iter().all(|val| val.is_deep_frozen())
+		self.header.frozen() && visit_reachable(self.iter(), |val| val.is_frozen())
 	}
 
 	fn can_mutate(&self) -> bool {
@@ -1009,7 +1022,7 @@ impl DequeOps for Arr {
 	}
 
 	fn push<V: IntoElement<Slot>>(&self, val: V) -> GResult<()> {
-		self.borrow_mut_with_capacity_guard(|vec| {
+		self.borrow_mut_with_capacity_guard("push to", |vec| {
 			let val = val.into_item()?;
 			self.write_barrier_slot(&val);
 			vec.push_back(val);
@@ -1020,14 +1033,14 @@ impl DequeOps for Arr {
 	//methods which just delete elements (rather than updating or adding them) don't need to be
 	//write-barriered, since they cannot create new references into the gc heap.
 	fn pop<R: FromElement<Slot>>(&self) -> GResult<R> {
-		match self.borrow_mut()?.pop_back() {
+		match self.borrow_mut("pop from")?.pop_back() {
 			Some(val) => R::from_item(&val),
 			None => bail!("attempted to pop from an arr of length 0")
 		}
 	}
 
 	fn push_start<V: IntoElement<Slot>>(&self, val: V) -> GResult<()> {
-		self.borrow_mut_with_capacity_guard(|vec| {
+		self.borrow_mut_with_capacity_guard("push to", |vec| {
 			let val = val.into_item()?;
 			self.write_barrier_slot(&val);
 			vec.push_front(val);
@@ -1036,7 +1049,7 @@ impl DequeOps for Arr {
 	}
 
 	fn pop_start<R: FromElement<Slot>>(&self) -> GResult<R> {
-		match self.borrow_mut()?.pop_front() {
+		match self.borrow_mut("pop from")?.pop_front() {
 			Some(val) => R::from_item(&val),
 			None => bail!("attempted to pop from the start of an arr of length 0")
 		}
@@ -1045,7 +1058,7 @@ impl DequeOps for Arr {
 	fn grow<V: IntoElement<Slot>>(&self, start_to_add: usize, 
 	                          end_to_add: usize, fill: V) -> GResult<()> {
 		if start_to_add + end_to_add > 0 {
-			self.borrow_mut_with_capacity_guard(|vec| {
+			self.borrow_mut_with_capacity_guard("grow", |vec| {
 				let fill = fill.into_item()?;
 				self.write_barrier_slot(&fill);
 				generic_grow(vec, start_to_add, end_to_add, fill);
@@ -1057,11 +1070,11 @@ impl DequeOps for Arr {
 	}
 
 	fn shrink(&self, start_to_remove: usize, end_to_remove: usize) -> GResult<()> {
-		generic_shrink(&mut *(self.borrow_mut()?), start_to_remove, end_to_remove)
+		generic_shrink(&mut *(self.borrow_mut("shrink")?), start_to_remove, end_to_remove)
 	}
 
 	fn append(&self, other: &Arr) -> GResult<()> {
-		self.borrow_mut_with_capacity_guard(|self_vec| {
+		self.borrow_mut_with_capacity_guard("append to", |self_vec| {
 			let other_vec = other.borrow();
 
 			self_vec.reserve(other_vec.len());
@@ -1076,7 +1089,7 @@ impl DequeOps for Arr {
 	}
 
 	fn prepend(&self, other: &Arr) -> GResult<()> {
-		self.borrow_mut_with_capacity_guard(|self_vec| {
+		self.borrow_mut_with_capacity_guard("prepend to", |self_vec| {
 			let other_vec = other.borrow();
 
 			self_vec.reserve(other_vec.len());
@@ -1091,7 +1104,7 @@ impl DequeOps for Arr {
 	}
 
 	fn resize<V: IntoElement<Slot>>(&self, new_len: usize, fill: V) -> GResult<()> {
-		self.borrow_mut_with_capacity_guard(|self_vec| {
+		self.borrow_mut_with_capacity_guard("resize", |self_vec| {
 			let fill = fill.into_item()?;
 
 			let old_len = self_vec.len();
@@ -1106,12 +1119,12 @@ impl DequeOps for Arr {
 	}
 
 	fn rotate_left(&self, mid: usize) -> GResult<()> {
-		self.borrow_mut()?.rotate_left(mid);
+		self.borrow_mut("rotate")?.rotate_left(mid);
 		Ok(())
 	}
 
 	fn rotate_right(&self, k: usize) -> GResult<()> {
-		self.borrow_mut()?.rotate_right(k);
+		self.borrow_mut("rotate")?.rotate_right(k);
 		Ok(())
 	}
 
@@ -1122,7 +1135,7 @@ impl DequeOps for Arr {
 	{
 		let i1 = index1.as_usize(self)?;
 		let i2 = index2.as_usize(self)?;
-		self.borrow_mut()?.swap(i1, i2);
+		self.borrow_mut("swap the elements of")?.swap(i1, i2);
 		Ok(())
 	}
 
@@ -1135,33 +1148,33 @@ impl DequeOps for Arr {
 	}
 
 	fn reserve_exact(&self, additional: usize) -> GResult<()> {
-		self.borrow_mut_with_capacity_guard(|vec| {
+		self.borrow_mut_with_capacity_guard("reserve capacity for", |vec| {
 			vec.reserve_exact(additional);
 			Ok(())
 		})
 	}
 
 	fn reserve(&self, additional: usize) -> GResult<()> {
-		self.borrow_mut_with_capacity_guard(|vec| {
+		self.borrow_mut_with_capacity_guard("reserve capacity for", |vec| {
 			vec.reserve(additional);
 			Ok(())
 		})
 	}
 
 	fn shrink_to_fit(&self) -> GResult<()> {
-		self.borrow_mut_with_capacity_guard(|vec| {
+		self.borrow_mut_with_capacity_guard("shrink the capacity of", |vec| {
 			vec.shrink_to_fit();
 			Ok(())
 		})
 	}
 
 	fn truncate(&self, len: usize) -> GResult<()> {
-		self.borrow_mut()?.truncate(len);
+		self.borrow_mut("truncate")?.truncate(len);
 		Ok(())
 	}
 
 	fn clear(&self) -> GResult<()> {
-		self.borrow_mut()?.clear();
+		self.borrow_mut("clear")?.clear();
 		Ok(())
 	}
 
@@ -1178,7 +1191,7 @@ impl DequeOps for Arr {
 		//do something weird which needs immutable access to the Arr, e.g. a garbage collection.
 		let iter = source.into_iter();
 
-		self.borrow_mut_with_capacity_guard(|vec| {
+		self.borrow_mut_with_capacity_guard("push to", |vec| {
 			vec.reserve(iter.size_hint().0);
 			Ok(())
 		})?;
@@ -1187,7 +1200,7 @@ impl DequeOps for Arr {
 			let val = val.into_item()?;
 
 			self.write_barrier_slot(&val);
-			self.borrow_mut_with_capacity_guard(|vec| {
+			self.borrow_mut_with_capacity_guard("push to", |vec| {
 				vec.push_back(val);
 				Ok(())
 			})?;
@@ -1213,14 +1226,14 @@ impl<I: DequeIndex> DequeAccess<I> for Arr {
 
 		let val = val.into_item()?;
 		self.write_barrier_slot(&val);
-		self.borrow_mut()?[i] = val;
+		self.borrow_mut("set an element of")?[i] = val;
 		Ok(())
 	}
 
 	fn insert<V: IntoElement<Slot>>(&self, index: I, val: V) -> GResult<()> {
 		let i = index.as_usize_excluded(self)?;
 
-		self.borrow_mut_with_capacity_guard(|vec| {
+		self.borrow_mut_with_capacity_guard("insert into", |vec| {
 			let val = val.into_item()?;
 			self.write_barrier_slot(&val);
 			vec.insert(i, val);
@@ -1230,25 +1243,25 @@ impl<I: DequeIndex> DequeAccess<I> for Arr {
 
 	fn del(&self, index: I) -> GResult<()> {
 		let i = index.as_usize(self)?;
-		self.borrow_mut()?.remove(i).unwrap();
+		self.borrow_mut("delete from")?.remove(i).unwrap();
 		Ok(())
 	}
 
 	fn remove<R: FromElement<Slot>>(&self, index: I) -> GResult<R> {
 		let i = index.as_usize(self)?;
-		let slot = self.borrow_mut()?.remove(i).unwrap();
+		let slot = self.borrow_mut("remove from")?.remove(i).unwrap();
 		R::from_item(&slot)
 	}
 
 	fn swap_remove<R: FromElement<Slot>>(&self, index: I) -> GResult<R> {
 		let i = index.as_usize(self)?;
-		let slot = self.borrow_mut()?.swap_remove_back(i).unwrap();
+		let slot = self.borrow_mut("remove from")?.swap_remove_back(i).unwrap();
 		R::from_item(&slot)
 	}
 
 	fn swap_remove_start<R: FromElement<Slot>>(&self, index: I) -> GResult<R> {
 		let i = index.as_usize(self)?;
-		let slot = self.borrow_mut()?.swap_remove_front(i).unwrap();
+		let slot = self.borrow_mut("remove from")?.swap_remove_front(i).unwrap();
 		R::from_item(&slot)
 	}
 }
@@ -1256,7 +1269,7 @@ impl<I: DequeIndex> DequeAccess<I> for Arr {
 impl<I: DequeIndex, R: DequeRange<I> + Debug> DequeAccessRange<I, R> for Arr {
 	fn del_slice(&self, range: R) -> GResult<()> {
 		let r = range.as_range(self)?;
-		self.borrow_mut()?.drain(r);
+		self.borrow_mut("delete from")?.drain(r);
 		Ok(())
 	}
 }
@@ -1905,6 +1918,74 @@ impl Str {
 		glsp::str_from_iter(self.iter()).unwrap()
 	}
 
+	/**
+	Returns `true` if this string was returned by [`glsp::intern`](fn.intern.html).
+
+	Interned strings are always frozen, and two interned strings are equal if and only if they
+	are the same string.
+	*/
+	pub fn is_interned(&self) -> bool {
+		self.header.interned()
+	}
+
+	/**
+	Returns the index of the first occurrence of `needle` within this string.
+
+	The index counts characters, not bytes. An empty `needle` is found at index `0`.
+	*/
+	pub fn find(&self, needle: &Str) -> Option<usize> {
+		let (len, needle_len) = (self.len(), needle.len());
+		if needle_len > len {
+			return None
+		}
+
+		(0 ..= len - needle_len).find(|&start| {
+			(0 .. needle_len).all(|i| {
+				self.get::<char>(start + i).unwrap() == needle.get::<char>(i).unwrap()
+			})
+		})
+	}
+
+	/**
+	Returns `true` if the substring `needle` occurs anywhere within this string.
+
+	Equivalent to [`(contains? st needle)`](https://gamelisp.rs/std/contains-p). To search for
+	a single character, use [`DequeOps::contains`](trait.DequeOps.html#tymethod.contains).
+	*/
+	pub fn contains_str(&self, needle: &Str) -> bool {
+		self.find(needle).is_some()
+	}
+
+	/**
+	Creates a new string, replacing each non-overlapping occurrence of `before` with `after`.
+
+	Equivalent to [`(replace st before after)`](https://gamelisp.rs/std/replace).
+	*/
+	pub fn replace(&self, before: &Str, after: &Str) -> GResult<Root<Str>> {
+		ensure!(before.len() > 0, "'before' string must not be empty");
+
+		let result = glsp::str_with_capacity(self.len());
+
+		let mut start = 0;
+		while start < self.len() {
+			let matches = start + before.len() <= self.len() && (0 .. before.len()).all(|i| {
+				self.get::<char>(start + i).unwrap() == before.get::<char>(i).unwrap()
+			});
+
+			if matches {
+				for ch in after.iter() {
+					result.push(ch)?;
+				}
+				start += before.len();
+			} else {
+				result.push(self.get::<char>(start).unwrap())?;
+				start += 1;
+			}
+		}
+
+		Ok(result)
+	}
+
 	/**
 	Creates an indexing iterator for this collection.
 
@@ -1925,7 +2006,7 @@ impl Str {
 			ch_count += 1;
 		}
 
-		self.borrow_mut_with_capacity_guard(|storage| {
+		self.borrow_mut_with_capacity_guard("push to", |storage| {
 			storage.prepare_for_char(max_ch);
 			with_str_storage_mut!(storage, vec, (), {
 				vec.reserve(ch_count);
@@ -1942,8 +2023,8 @@ impl Str {
 		self.storage.borrow()
 	}
 
-	fn borrow_mut(&self) -> GResult<RefMut<StrStorage>> {
-		ensure!(!self.header.frozen(), "attempted to mutate a frozen str");
+	fn borrow_mut(&self, op: &str) -> GResult<RefMut<StrStorage>> {
+		ensure!(!self.header.frozen(), "attempted to {} a frozen str", op);
 		match self.storage.try_borrow_mut() {
 			Ok(ref_mut) => Ok(ref_mut),
 			Err(_) => bail!("attempted to mutate a borrowed str")
@@ -1954,7 +2035,7 @@ impl Str {
 		with_heap(|heap| heap.memory_usage_barrier(self, prev_usage, cur_usage));
 	}
 
-	fn borrow_mut_with_capacity_guard<R, F>(&self, f: F) -> GResult<R> 
+	fn borrow_mut_with_capacity_guard<R, F>(&self, op: &str, f: F) -> GResult<R> 
 	where
 		F: FnOnce(&mut RefMut<StrStorage>) -> GResult<R>
 	{
@@ -1966,10 +2047,22 @@ impl Str {
 			}
 		});
 
-		let mut ref_mut = self.borrow_mut()?;
+		let mut ref_mut = self.borrow_mut(op)?;
 
 		f(&mut ref_mut)
 	}
+
+	//used by glsp::runtime_snapshot and glsp::restore_runtime
+	pub(crate) fn save_contents(&self) -> StrStorage {
+		self.storage.borrow().clone()
+	}
+
+	pub(crate) fn restore_contents(&self, saved: &StrStorage) {
+		//the intern table is keyed by an interned string's contents, so they must never change
+		if !self.is_interned() {
+			self.storage.borrow_mut().clone_from(saved);
+		}
+	}
 }
 
 //if we were to implement fmt::Write for Str directly, it would require a &mut Str receiver, and
@@ -1987,6 +2080,10 @@ impl fmt::Write for Root<Str> {
 
 impl PartialEq<Str> for Str {
 	fn eq(&self, other: &Str) -> bool {
+		if self.is_interned() && other.is_interned() {
+			return ptr::eq(self, other)
+		}
+
 		self.len() == other.len() && self.iter().zip(other.iter()).all(|(c0, c1)| c0 == c1)
 	}
 }
@@ -2029,7 +2126,7 @@ impl DequeOps for Str {
 		let ch = ch.into_item()?;
 		let ch = &ch;
 
-		self.borrow_mut_with_capacity_guard(|storage| {
+		self.borrow_mut_with_capacity_guard("push to", |storage| {
 			with_str_storage_mut!(storage, vec, (ch), {
 				vec.push_back(ch);
 			});
@@ -2039,7 +2136,7 @@ impl DequeOps for Str {
 	}
 	
 	fn pop<R: FromElement<char>>(&self) -> GResult<R> {
-		with_str_storage_mut!(&mut *self.borrow_mut()?, vec, (), {
+		with_str_storage_mut!(&mut *self.borrow_mut("pop from")?, vec, (), {
 			match vec.pop_back() {
 				Some(char_storage) => R::from_item(&char_storage.into_char()),
 				None => bail!("attempted to pop a char from an empty str")
@@ -2051,7 +2148,7 @@ impl DequeOps for Str {
 		let ch = ch.into_item()?;
 		let ch = &ch;
 
-		self.borrow_mut_with_capacity_guard(|storage| {
+		self.borrow_mut_with_capacity_guard("push to", |storage| {
 			with_str_storage_mut!(storage, vec, (ch), {
 				vec.push_front(ch);
 			});
@@ -2061,7 +2158,7 @@ impl DequeOps for Str {
 	}
 	
 	fn pop_start<R: FromElement<char>>(&self) -> GResult<R> {
-		with_str_storage_mut!(&mut *self.borrow_mut()?, vec, (), {
+		with_str_storage_mut!(&mut *self.borrow_mut("pop from")?, vec, (), {
 			match vec.pop_front() {
 				Some(char_storage) => R::from_item(&char_storage.into_char()),
 				None => bail!("attempted to pop from the start of an empty str")
@@ -2074,7 +2171,7 @@ impl DequeOps for Str {
 		let fill = fill.into_item()?;
 		let fill = &fill;
 
-		self.borrow_mut_with_capacity_guard(|storage| {
+		self.borrow_mut_with_capacity_guard("grow", |storage| {
 			with_str_storage_mut!(storage, vec, (fill), {
 				generic_grow(vec, start_to_add, end_to_add, fill);
 			});
@@ -2084,20 +2181,20 @@ impl DequeOps for Str {
 	}
 	
 	fn shrink(&self, start_to_remove: usize, end_to_remove: usize) -> GResult<()> {
-		with_str_storage_mut!(&mut *self.borrow_mut()?, vec, (), {
+		with_str_storage_mut!(&mut *self.borrow_mut("shrink")?, vec, (), {
 			generic_shrink(vec, start_to_remove, end_to_remove)
 		})
 	}
 	
 	fn append(&self, other: &Str) -> GResult<()> {
-		self.borrow_mut_with_capacity_guard(|self_storage| {
+		self.borrow_mut_with_capacity_guard("append to", |self_storage| {
 			self_storage.append(&other.borrow());
 			Ok(())
 		})
 	}
 	
 	fn prepend(&self, other: &Str) -> GResult<()> {
-		self.borrow_mut_with_capacity_guard(|self_storage| {
+		self.borrow_mut_with_capacity_guard("prepend to", |self_storage| {
 			self_storage.prepend(&other.borrow());
 			Ok(())
 		})
@@ -2107,7 +2204,7 @@ impl DequeOps for Str {
 		let ch = ch.into_item()?;
 		let ch = &ch;
 
-		self.borrow_mut_with_capacity_guard(|storage| {
+		self.borrow_mut_with_capacity_guard("resize", |storage| {
 			with_str_storage_mut!(storage, vec, (ch), {
 				vec.resize(new_len, ch);
 			});
@@ -2117,14 +2214,14 @@ impl DequeOps for Str {
 	}
 
 	fn rotate_left(&self, mid: usize) -> GResult<()> {
-		with_str_storage_mut!(&mut *self.borrow_mut()?, vec, (), {
+		with_str_storage_mut!(&mut *self.borrow_mut("rotate")?, vec, (), {
 			vec.rotate_left(mid);
 			Ok(())
 		})
 	}
 
 	fn rotate_right(&self, k: usize) -> GResult<()> {
-		with_str_storage_mut!(&mut *self.borrow_mut()?, vec, (), {
+		with_str_storage_mut!(&mut *self.borrow_mut("rotate")?, vec, (), {
 			vec.rotate_right(k);
 			Ok(())
 		})
@@ -2137,7 +2234,7 @@ impl DequeOps for Str {
 	{
 		let i = i_index.as_usize(self)?;
 		let j = j_index.as_usize(self)?;
-		with_str_storage_mut!(&mut *self.borrow_mut()?, vec, (), {
+		with_str_storage_mut!(&mut *self.borrow_mut("swap the elements of")?, vec, (), {
 			vec.swap(i, j);
 			Ok(())
 		})
@@ -2156,14 +2253,14 @@ impl DequeOps for Str {
 	}
 
 	fn reserve_exact(&self, additional: usize) -> GResult<()> {
-		with_str_storage_mut!(&mut *self.borrow_mut()?, vec, (), {
+		with_str_storage_mut!(&mut *self.borrow_mut("reserve capacity for")?, vec, (), {
 			vec.reserve_exact(additional);
 			Ok(())
 		})
 	}
 
 	fn reserve(&self, additional: usize) -> GResult<()> {
-		self.borrow_mut_with_capacity_guard(|storage| {
+		self.borrow_mut_with_capacity_guard("reserve capacity for", |storage| {
 			with_str_storage_mut!(storage, vec, (), {
 				vec.reserve(additional);
 			});
@@ -2173,7 +2270,7 @@ impl DequeOps for Str {
 	}
 
 	fn shrink_to_fit(&self) -> GResult<()> {
-		self.borrow_mut_with_capacity_guard(|storage| {
+		self.borrow_mut_with_capacity_guard("shrink the capacity of", |storage| {
 			with_str_storage_mut!(storage, vec, (), {
 				vec.shrink_to_fit();
 			});
@@ -2183,14 +2280,14 @@ impl DequeOps for Str {
 	}
 
 	fn truncate(&self, len: usize) -> GResult<()> {
-		with_str_storage_mut!(&mut *self.borrow_mut()?, vec, (), {
+		with_str_storage_mut!(&mut *self.borrow_mut("truncate")?, vec, (), {
 			vec.truncate(len);
 			Ok(())
 		})
 	}
 
 	fn clear(&self) -> GResult<()> {
-		with_str_storage_mut!(&mut *self.borrow_mut()?, vec, (), {
+		with_str_storage_mut!(&mut *self.borrow_mut("clear")?, vec, (), {
 			vec.clear();
 			Ok(())
 		})
@@ -2198,12 +2295,23 @@ impl DequeOps for Str {
 
 	fn contains<C: IntoElement<char>>(&self, ch: C) -> GResult<bool> {
 		let ch = ch.into_item()?;
-		let ch = &ch;
 
-		self.borrow_mut_with_capacity_guard(|storage| {
-			with_str_storage_mut!(storage, vec, (ch), {
-				Ok(vec.contains(&ch))
-			})
+		//a char which is too wide for the storage can't be present. we check for that, rather 
+		//than widening the storage, so that this method doesn't fail for a frozen str
+		let storage = self.borrow();
+		let fits = match (&*storage, ch as u32) {
+			(&StrStorage::Str1(_), ch) => ch <= u8::MAX as u32,
+			(&StrStorage::Str2(_), ch) => ch <= u16::MAX as u32,
+			(&StrStorage::Str4(_), _) => true
+		};
+
+		if !fits {
+			return Ok(false)
+		}
+
+		let ch = &ch;
+		with_str_storage!(&*storage, vec, (ch), {
+			Ok(vec.contains(&ch))
 		})
 	}
 
@@ -2223,7 +2331,7 @@ impl DequeOps for Str {
 			let ch = ch.into_item()?;
 			let ch = &ch;
 
-			self.borrow_mut_with_capacity_guard(|storage| {
+			self.borrow_mut_with_capacity_guard("push to", |storage| {
 				with_str_storage_mut!(storage, vec, (ch), {
 					vec.push_back(ch);
 				});
@@ -2249,7 +2357,7 @@ impl<I: DequeIndex> DequeAccess<I> for Str {
 		let ch = ch.into_item()?;
 		let ch = &ch;
 		
-		self.borrow_mut_with_capacity_guard(|storage| {
+		self.borrow_mut_with_capacity_guard("set an element of", |storage| {
 			with_str_storage_mut!(storage, vec, (ch), {
 				vec[i] = ch;
 			});
@@ -2263,7 +2371,7 @@ impl<I: DequeIndex> DequeAccess<I> for Str {
 		let ch = ch.into_item()?;
 		let ch = &ch;
 
-		self.borrow_mut_with_capacity_guard(|storage| {
+		self.borrow_mut_with_capacity_guard("insert into", |storage| {
 			with_str_storage_mut!(storage, vec, (ch), {
 				vec.insert(i, ch);
 			});
@@ -2274,7 +2382,7 @@ impl<I: DequeIndex> DequeAccess<I> for Str {
 
 	fn del(&self, index: I) -> GResult<()> {
 		let i = index.as_usize(self)?;
-		with_str_storage_mut!(&mut *self.borrow_mut()?, vec, (), {
+		with_str_storage_mut!(&mut *self.borrow_mut("delete from")?, vec, (), {
 			vec.remove(i).unwrap();
 		});
 		Ok(())
@@ -2282,21 +2390,21 @@ impl<I: DequeIndex> DequeAccess<I> for Str {
 	
 	fn remove<R: FromElement<char>>(&self, index: I) -> GResult<R> {
 		let i = index.as_usize(self)?;
-		with_str_storage_mut!(&mut *self.borrow_mut()?, vec, (), {
+		with_str_storage_mut!(&mut *self.borrow_mut("remove from")?, vec, (), {
 			R::from_item(&vec.remove(i).unwrap().into_char())
 		})
 	}
 
 	fn swap_remove<R: FromElement<char>>(&self, index: I) -> GResult<R> {
 		let i = index.as_usize(self)?;
-		with_str_storage_mut!(&mut *self.borrow_mut()?, vec, (), {
+		with_str_storage_mut!(&mut *self.borrow_mut("remove from")?, vec, (), {
 			R::from_item(&vec.swap_remove_back(i).unwrap().into_char())
 		})
 	}
 
 	fn swap_remove_start<R: FromElement<char>>(&self, index: I) -> GResult<R> {
 		let i = index.as_usize(self)?;
-		with_str_storage_mut!(&mut *self.borrow_mut()?, vec, (), {
+		with_str_storage_mut!(&mut *self.borrow_mut("remove from")?, vec, (), {
 			R::from_item(&vec.swap_remove_front(i).unwrap().into_char())
 		})
 	}
@@ -2305,7 +2413,7 @@ impl<I: DequeIndex> DequeAccess<I> for Str {
 impl<I: DequeIndex, R: DequeRange<I> + Debug> DequeAccessRange<I, R> for Str {
 	fn del_slice(&self, range: R) -> GResult<()> {
 		let r = range.as_range(self)?;
-		with_str_storage_mut!(&mut *self.borrow_mut()?, vec, (), {
+		with_str_storage_mut!(&mut *self.borrow_mut("delete from")?, vec, (), {
 			vec.drain(r);
 		});
 
@@ -2889,11 +2997,27 @@ The `tab` primitive type.
 
 New tables can be constructed using the [`tab!` macro](macro.tab.html) or various toplevel 
 functions, such as [`glsp::tab`](fn.tab.html) and [`glsp::tab_from_iter`](fn.tab_from_iter.html).
+
+A table constructed by [`glsp::weak_key_tab`](fn.weak_key_tab.html) or 
+[`glsp::weak_value_tab`](fn.weak_value_tab.html) holds its keys or values weakly. Its entries
+are removed when their weakly-held key or value is garbage-collected.
 */
 
 pub struct Tab {
 	header: GcHeader,
-	map: RefCell<FnvHashMap<Slot, Slot>>
+	map: RefCell<FnvHashMap<Slot, Slot>>,
+	default: RefCell<Option<Slot>>,
+	weakness: Weakness
+}
+
+//a weak tab is registered with the Heap, which removes its entries when their weakly-held key
+//or value is about to be freed. only identity-compared types are held weakly: see
+//Slot::weak_referent(). weak referents aren't traced or write-barriered.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Weakness {
+	Strong,
+	WeakKeys,
+	WeakValues
 }
 
 impl Allocate for Tab {
@@ -2903,13 +3027,30 @@ impl Allocate for Tab {
 
 	fn visit_gcs<V: Visitor>(&self, visitor: &mut V) {
 		for (internal_key, internal_value) in self.map.borrow().iter() {
-			visitor.visit_slot(internal_key);
-			visitor.visit_slot(internal_value);
+			match self.weakness {
+				Weakness::Strong => {
+					visitor.visit_slot(internal_key);
+					visitor.visit_slot(internal_value);
+				}
+				Weakness::WeakKeys => {
+					visitor.visit_weak_slot(internal_key);
+					visitor.visit_slot(internal_value);
+				}
+				Weakness::WeakValues => {
+					visitor.visit_slot(internal_key);
+					visitor.visit_weak_slot(internal_value);
+				}
+			}
+		}
+
+		if let Some(ref default) = *self.default.borrow() {
+			visitor.visit_slot(default);
 		}
 	}
 
 	fn clear_gcs(&self) {
-		self.map.borrow_mut().clear()
+		self.map.borrow_mut().clear();
+		*self.default.borrow_mut() = None;
 	}
 
 	fn owned_memory_usage(&self) -> usize {
@@ -3023,12 +3164,34 @@ macro_rules! tab {
 
 impl Tab {
 	pub(crate) fn new() -> Tab {
+		Tab::with_weakness(Weakness::Strong)
+	}
+
+	//the caller must register the tab using Heap::register_weak_tab, unless it's Strong
+	pub(crate) fn with_weakness(weakness: Weakness) -> Tab {
 		Tab {
 			header: GcHeader::new(),
-			map: RefCell::new(FnvHashMap::default())
+			map: RefCell::new(FnvHashMap::default()),
+			default: RefCell::new(None),
+			weakness
 		}
 	}
 
+	//a default is shared by every missing key, so if it could be mutated, (push! [t k] x) would
+	//silently mutate the default rather than the table. we require it to be deep-frozen instead.
+	pub(crate) fn with_default(default: Slot) -> GResult<Tab> {
+		ensure!(default.root().is_deep_frozen(), 
+		        "a tab's default value must be deep-frozen, but received {}", 
+		        default.a_type_name());
+
+		Ok(Tab {
+			header: GcHeader::new(),
+			map: RefCell::new(FnvHashMap::default()),
+			default: RefCell::new(Some(default)),
+			weakness: Weakness::Strong
+		})
+	}
+
 	pub(crate) fn from_iter<T, K, V>(t: T) -> GResult<Tab> 
 	where
 		T: IntoIterator<Item = (K, V)>,
@@ -3044,14 +3207,18 @@ impl Tab {
 
 		Ok(Tab {
 			header: GcHeader::new(),
-			map: RefCell::new(map)
+			map: RefCell::new(map),
+			default: RefCell::new(None),
+			weakness: Weakness::Strong
 		})
 	}
 
 	pub(crate) fn with_capacity(capacity: usize) -> Tab {
 		Tab {
 			header: GcHeader::new(),
-			map: RefCell::new(FnvHashMap::with_capacity_and_hasher(capacity, Default::default()))
+			map: RefCell::new(FnvHashMap::with_capacity_and_hasher(capacity, Default::default())),
+			default: RefCell::new(None),
+			weakness: Weakness::Strong
 		}
 	}
 
@@ -3059,21 +3226,88 @@ impl Tab {
 		self.map.borrow()
 	}
 
-	pub(crate) fn borrow_mut(&self) -> GResult<RefMut<FnvHashMap<Slot, Slot>>> {
-		ensure!(!self.header.frozen(), "attempted to mutate a frozen tab");
+	pub(crate) fn borrow_mut(&self, op: &str) -> GResult<RefMut<FnvHashMap<Slot, Slot>>> {
+		ensure!(!self.header.frozen(), "attempted to {} a frozen tab", op);
 		match self.map.try_borrow_mut() {
 			Ok(ref_mut) => Ok(ref_mut),
 			Err(_) => bail!("attempted to mutate a borrowed tab")
 		}
 	}
 
+	pub(crate) fn weakness(&self) -> Weakness {
+		self.weakness
+	}
+
+	///Returns `true` if this table was constructed by [`glsp::weak_key_tab`](fn.weak_key_tab.html).
+	pub fn has_weak_keys(&self) -> bool {
+		self.weakness == Weakness::WeakKeys
+	}
+
+	/**
+	Returns `true` if this table was constructed by
+	[`glsp::weak_value_tab`](fn.weak_value_tab.html).
+	*/
+	pub fn has_weak_values(&self) -> bool {
+		self.weakness == Weakness::WeakValues
+	}
+
+	//used by the Heap. while a weak tab is borrowed, it can't be purged.
+	pub(crate) fn is_borrowed(&self) -> bool {
+		self.map.try_borrow_mut().is_err()
+	}
+
+	//visits each weakly-held key or value as though it were a strong reference
+	pub(crate) fn visit_weak_referents<V: Visitor>(&self, visitor: &mut V) {
+		for (key, value) in self.map.borrow().iter() {
+			match self.weakness {
+				Weakness::Strong => (),
+				Weakness::WeakKeys => visitor.visit_slot(key),
+				Weakness::WeakValues => visitor.visit_slot(value)
+			}
+		}
+	}
+
+	//removes each entry whose weakly-held key or value is about to be freed, unless the table is
+	//currently borrowed. `is_dead` receives the referent's header and address.
+	pub(crate) fn purge_weak<F: Fn(&GcHeader, usize) -> bool>(&self, is_dead: F) {
+		let weakness = self.weakness;
+		if let Ok(mut map) = self.map.try_borrow_mut() {
+			map.retain(|key, value| {
+				let weak_slot = match weakness {
+					Weakness::Strong => return true,
+					Weakness::WeakKeys => key,
+					Weakness::WeakValues => value
+				};
+
+				match weak_slot.weak_referent() {
+					Some((header, addr)) => !is_dead(header, addr),
+					None => true
+				}
+			});
+		}
+	}
+
+	//an empty table with the same weakness as this one
+	fn new_like(&self, capacity: usize) -> Root<Tab> {
+		match self.weakness {
+			Weakness::Strong => glsp::tab_with_capacity(capacity),
+			weakness => glsp::weak_tab(weakness)
+		}
+	}
+
 	/**
 	Creates a shallow copy of the table.
 
 	Equivalent to [`(clone t)`](https://gamelisp.rs/std/clone).
 	*/
 	pub fn shallow_clone(&self) -> Root<Tab> {
-		glsp::tab_from_iter(self.entries().iter()).unwrap()
+		let tab = self.new_like(self.len());
+		for (key, value) in self.entries().iter() {
+			tab.set(key, value).unwrap();
+		}
+
+		*tab.default.borrow_mut() = self.default.borrow().clone();
+		tab
 	}
 
 	/**
@@ -3083,10 +3317,13 @@ impl Tab {
 	*/
 	//todo: check for reference cycles
 	pub fn deep_clone(&self) -> GResult<Root<Tab>> {
-		let tab = glsp::tab_with_capacity(self.len());
+		let tab = self.new_like(self.len());
 		for (k, v) in self.entries().iter() {
 			tab.set(k.deep_clone()?, v.deep_clone()?)?;
 		}
+
+		//the default is deep-frozen, so it can be shared
+		*tab.default.borrow_mut() = self.default.borrow().clone();
 		Ok(tab)
 	}
 
@@ -3127,7 +3364,18 @@ impl Tab {
 		with_heap(|heap| heap.memory_usage_barrier(self, prev_usage, cur_usage));
 	}
 
-	fn borrow_mut_with_capacity_guard<R, F>(&self, f: F) -> GResult<R> 
+	//a weakly-held key or value isn't kept alive by the table, so it's not write-barriered
+	fn write_barrier_entry(&self, key: &Slot, value: &Slot) {
+		if self.weakness != Weakness::WeakKeys || key.weak_referent().is_none() {
+			self.write_barrier_slot(key);
+		}
+
+		if self.weakness != Weakness::WeakValues || value.weak_referent().is_none() {
+			self.write_barrier_slot(value);
+		}
+	}
+
+	fn borrow_mut_with_capacity_guard<R, F>(&self, op: &str, f: F) -> GResult<R> 
 	where
 		F: FnOnce(&mut RefMut<FnvHashMap<Slot, Slot>>) -> GResult<R>
 	{
@@ -3139,21 +3387,46 @@ impl Tab {
 			}
 		});
 
-		let mut ref_mut = self.borrow_mut()?;
+		let mut ref_mut = self.borrow_mut(op)?;
 
 		f(&mut ref_mut)
 	}
 
+	//used by glsp::runtime_snapshot and glsp::restore_runtime
+	pub(crate) fn save_contents(&self) -> FnvHashMap<Slot, Slot> {
+		self.map.borrow().clone()
+	}
+
+	pub(crate) fn restore_contents(&self, saved: &FnvHashMap<Slot, Slot>) {
+		self.map.borrow_mut().clone_from(saved);
+	}
+
+	/**
+	Returns the table's default value, if it has one.
+
+	Equivalent to [`(tab-default t)`](https://gamelisp.rs/std/tab-default).
+	*/
+	pub fn default_value(&self) -> Option<Val> {
+		self.default.borrow().as_ref().map(|slot| slot.root())
+	}
+
 	/**
 	Indexes the table.
 
+	If the key is missing, returns the table's [default value](#method.default_value), if any.
+
 	Equivalent to [`[t key]`](https://gamelisp.rs/std/access).
 	*/
 	pub fn get<K: ToVal, V: FromVal>(&self, key: K) -> GResult<V> {
 		let key = key.to_slot()?;
 		match self.borrow().get(&key) {
 			Some(value) => V::from_slot(value),
-			None => bail!("missing tab field {:?}", key)
+			None => {
+				match *self.default.borrow() {
+					Some(ref default) => V::from_slot(default),
+					None => bail!("missing tab field {:?}", key)
+				}
+			}
 		}
 	}
 
@@ -3181,10 +3454,10 @@ impl Tab {
 	{
 		let key = key.to_slot()?;
 		let value = value.to_slot()?;
+		ensure!(!self.header.frozen(), "attempted to set the key {} in a frozen tab", key);
 
-		self.borrow_mut_with_capacity_guard(|map| {
-			self.write_barrier_slot(&key);
-			self.write_barrier_slot(&value);
+		self.borrow_mut_with_capacity_guard("set an element of", |map| {
+			self.write_barrier_entry(&key, &value);
 			map.insert(key, value);
 			Ok(())
 		})
@@ -3205,11 +3478,10 @@ impl Tab {
 		let key = key.to_slot()?;
 		let value = value.to_slot()?;
 
-		self.borrow_mut_with_capacity_guard(|map| {
+		self.borrow_mut_with_capacity_guard("set an element of", |map| {
 			match map.get_mut(&key) {
 				Some(dst_value) => {
-					self.write_barrier_slot(&key);
-					self.write_barrier_slot(&value);
+					self.write_barrier_entry(&key, &value);
 					*dst_value = value;
 					Ok(true)
 				}
@@ -3235,7 +3507,7 @@ impl Tab {
 	*/
 	pub fn del<K: ToVal>(&self, key: K) -> GResult<()> {
 		let key = key.to_slot()?;
-		match self.borrow_mut()?.remove(&key) {
+		match self.borrow_mut("delete from")?.remove(&key) {
 			Some(_) => Ok(()),
 			None => bail!("attempted to delete nonexistent tab field {}", key)
 		}
@@ -3250,7 +3522,7 @@ impl Tab {
 	*/
 	pub fn del_if_present<K: ToVal>(&self, key: K) -> GResult<bool> {
 		let key = key.to_slot()?;
-		Ok(self.borrow_mut()?.remove(&key).is_some())
+		Ok(self.borrow_mut("delete from")?.remove(&key).is_some())
 	}
 
 	/**
@@ -3260,7 +3532,7 @@ impl Tab {
 	*/
 	pub fn remove<K: ToVal, V: FromVal>(&self, key: K) -> GResult<V> {
 		let key = key.to_slot()?;
-		match self.borrow_mut()?.remove(&key) {
+		match self.borrow_mut("remove from")?.remove(&key) {
 			Some(value) => V::from_slot(&value),
 			None => bail!("attempted to remove nonexistent tab field {}", key)
 		}
@@ -3273,7 +3545,7 @@ impl Tab {
 	*/
 	pub fn remove_if_present<K: ToVal, V: FromVal>(&self, key: K) -> GResult<Option<V>> {
 		let key = key.to_slot()?;
-		match self.borrow_mut()?.remove(&key) {
+		match self.borrow_mut("remove from")?.remove(&key) {
 			Some(value) => Ok(Some(V::from_slot(&value)?)),
 			None => Ok(None)
 		}
@@ -3307,7 +3579,7 @@ impl Tab {
 	[0]: https://doc.rust-lang.org/std/collections/struct.HashMap.html#method.reserve
 	*/
 	pub fn reserve(&self, additional: usize) -> GResult<()> {
-		self.borrow_mut_with_capacity_guard(|map| {
+		self.borrow_mut_with_capacity_guard("reserve capacity for", |map| {
 			map.reserve(additional);
 			Ok(())
 		})
@@ -3321,7 +3593,7 @@ impl Tab {
 	[0]: https://doc.rust-lang.org/std/collections/struct.HashMap.html#method.shrink_to_fit
 	*/
 	pub fn shrink_to_fit(&self) -> GResult<()> {
-		self.borrow_mut_with_capacity_guard(|map| {
+		self.borrow_mut_with_capacity_guard("shrink the capacity of", |map| {
 			map.shrink_to_fit();
 			Ok(())
 		})
@@ -3335,7 +3607,7 @@ impl Tab {
 	[0]: https://doc.rust-lang.org/std/collections/struct.HashMap.html#method.clear
 	*/
 	pub fn clear(&self) -> GResult<()> {
-		self.borrow_mut()?.clear();
+		self.borrow_mut("clear")?.clear();
 		Ok(())
 	}
 
@@ -3357,6 +3629,10 @@ impl Tab {
 	Equivalent to [`(keys t)`](https://gamelisp.rs/std/keys).
 	*/
 	pub fn gkeys(&self) -> Root<GIter> {
+		if self.weakness != Weakness::Strong {
+			return self.weak_giter(TabPart::Keys)
+		}
+
 		let arr = glsp::arr_with_capacity(self.len());
 		for key in self.entries().keys_to::<Slot>() {
 			arr.push(key.unwrap()).unwrap();
@@ -3371,6 +3647,10 @@ impl Tab {
 	Equivalent to [`(values t)`](https://gamelisp.rs/std/values).
 	*/
 	pub fn gvalues(&self) -> Root<GIter> {
+		if self.weakness != Weakness::Strong {
+			return self.weak_giter(TabPart::Values)
+		}
+
 		let arr = glsp::arr_with_capacity(self.len());
 		for value in self.entries().values_to::<Slot>() {
 			arr.push(value.unwrap()).unwrap();
@@ -3379,6 +3659,44 @@ impl Tab {
 		glsp::giter(GIterState::TabValues(arr.to_gc()))
 	}
 
+	/*
+	an iterator over a weak tab mustn't keep its weak referents alive. instead of copying the
+	entries into an arr, we copy them into a tab with the same weakness, and we record their
+	order in a tab which maps indexes to keys, holding the keys weakly. entries which are 
+	collected before they're reached are skipped.
+	*/
+	pub(crate) fn weak_giter(&self, part: TabPart) -> Root<GIter> {
+		let pending = self.new_like(self.len());
+		let order = glsp::weak_tab(Weakness::WeakValues);
+
+		for (i, (key, value)) in self.entries().iter().enumerate() {
+			order.set(i as i32, &key).unwrap();
+			pending.set(key, value).unwrap();
+		}
+
+		glsp::giter(GIterState::WeakTab(pending.to_gc(), order.to_gc(), 0, part))
+	}
+
+	//used by GIter::next() for a weak tab iterator. pops the entry which is next in `order`,
+	//skipping any which have been collected.
+	pub(crate) fn pop_weak_entry(&self, order: &Tab, next_i: &mut u32) -> Option<(Slot, Slot)> {
+		let mut order_map = order.map.borrow_mut();
+		let mut map = self.map.borrow_mut();
+
+		while !order_map.is_empty() {
+			let i = *next_i as i32;
+			*next_i += 1;
+
+			if let Some(key) = order_map.remove(&Slot::Int(i)) {
+				if let Some(value) = map.remove(&key) {
+					return Some((key, value))
+				}
+			}
+		}
+
+		None
+	}
+
 	/**
 	Inserts the contents of an iterator as table entries.
 
@@ -3392,7 +3710,7 @@ impl Tab {
 	{
 		let iter = iter_source.into_iter();
 
-		self.borrow_mut_with_capacity_guard(|map| {
+		self.borrow_mut_with_capacity_guard("extend", |map| {
 			map.reserve(iter.size_hint().0);
 			Ok(())
 		})?;
@@ -3401,10 +3719,8 @@ impl Tab {
 			let key = key.to_slot()?;
 			let value = value.to_slot()?;
 
-			self.write_barrier_slot(&key);
-			self.write_barrier_slot(&value);
-
-			self.borrow_mut()?.insert(key, value);
+			self.write_barrier_entry(&key, &value);
+			self.borrow_mut("extend")?.insert(key, value);
 		}
 
 		Ok(())
@@ -3425,13 +3741,12 @@ impl Tab {
 	Equivalent to [`(deep-freeze! t)`](https://gamelisp.rs/std/deep-freeze-mut).
 	*/
 
-	//todo: handle reference cycles
 	pub fn deep_freeze(&self) {
 		self.freeze();
-		for (key, value) in self.entries().iter() {
-			key.deep_freeze();
-			value.deep_freeze();
-		}
+		visit_reachable(self.entry_vals(), |val| {
+			val.freeze();
+			true
+		});
 	}
 
 	///Returns `true` if the table has been frozen.
@@ -3439,12 +3754,19 @@ impl Tab {
 		self.header.frozen()
 	}
 
-	//todo: handle reference cycles
 	///Returns `true` if the table and all of its contents have been frozen.
 	pub fn is_deep_frozen(&self) -> bool {
-		self.is_frozen() && self.entries().iter().all(|(key, value)| {
-			key.is_deep_frozen() && value.is_deep_frozen()
-		})
+		self.is_frozen() && visit_reachable(self.entry_vals(), |val| val.is_frozen())
+	}
+
+	fn entry_vals(&self) -> Vec<Val> {
+		let mut vals = Vec::with_capacity(self.len() * 2);
+		for (key, value) in self.entries().iter() {
+			vals.push(key);
+			vals.push(value);
+		}
+
+		vals
 	}
 
 	/**
@@ -7,11 +7,13 @@ use std::collections::{hash_map::Entry::{Occupied, Vacant}, VecDeque};
 use std::convert::{TryInto};
 use std::io::{Write};
 use std::iter::{repeat_with};
+use std::rc::{Rc};
 use super::code::{Bytecode, Instr, Lambda, ParamMap, Stay, StaySource};
+use super::collections::{Arr, DequeOps};
 use super::engine::{Filename, glsp, Span, SpanStorage, Sym};
 use super::error::{GResult};
 use super::gc::{GcHeader, Slot, Root};
-use super::val::{Val};
+use super::val::{Flo, Val};
 
 /*
 this module is only present when the "compiler" crate feature is enabled.
@@ -68,13 +70,21 @@ pub(crate) enum Action {
 
 	//start or finish loading the given file
 	StartLoad(Filename),
-	EndLoad
+	EndLoad,
+
+	//register this Bytecode as one of the current file's toplevel (defer) forms
+	Defer(Root<Bytecode>)
 }
 
 //we don't perform deflate compression for small inputs (e.g. those generated by an eval!() macro), 
 //since the decompression is surprisingly expensive: about 80us for 122 compressed bytes!
 const DEFLATE_LIMIT: usize = 8 * 1024;
 
+//the size of a flo in bytes: either 4, or 8 when the "f64-flos" feature is enabled. it's written
+//into the header of each serialized Recording, because a Recording produced by one flo width
+//can't be deserialized using the other.
+pub(crate) const FLO_WIDTH: u8 = std::mem::size_of::<Flo>() as u8;
+
 impl Recording {
 	pub(crate) fn new() -> Recording {
 		Recording {
@@ -104,7 +114,22 @@ impl Recording {
 		self.actions.push_back(action)
 	}
 
+	//each toplevel form's Bytecode, including toplevel (defer) forms. used by deadcode.rs.
+	pub(crate) fn bytecodes(&self) -> impl Iterator<Item = &Root<Bytecode>> + '_ {
+		self.actions.iter().filter_map(|action| {
+			match action {
+				Action::Execute(bytecode) | Action::Defer(bytecode) => Some(bytecode),
+				_ => None
+			}
+		})
+	}
+
 	pub(crate) fn into_bytes(self) -> Vec<u8> {
+		self.try_into_bytes().unwrap()
+	}
+
+	//fails if the Recording contains a literal which can't be serialized, like an rdata
+	pub(crate) fn try_into_bytes(self) -> GResult<Vec<u8>> {
 		let mut conv = DenseConverter::default();
 
 		let actions = self.actions.iter().map(|action| {
@@ -120,13 +145,17 @@ impl Recording {
 
 		//we use `bincode` because `serde_cbor` produces a larger output (even when using
 		//`to_packed_vec` followed by deflate compression) which is also slower to read back in. 
-		let raw_bytes = bincode::serialize(&chunk).unwrap();
+		let raw_bytes = match bincode::serialize(&chunk) {
+			Ok(raw_bytes) => raw_bytes,
+			Err(err) => return Err(error!("unable to serialize compiled code").with_source(err))
+		};
 
-		//we store a u64 uncompressed length, followed by the deflated payload. using
-		//Compression::default rather than Compression::best only increases the payload size
+		//we store a u64 uncompressed length and a u8 flo width, followed by the deflated payload.
+		//using Compression::default rather than Compression::best only increases the payload size
 		//by 3%, and it doubles the compression speed.
 		let mut compressed = Vec::<u8>::new();
 		compressed.extend_from_slice(&(raw_bytes.len() as u64).to_le_bytes());
+		compressed.push(FLO_WIDTH);
 
 		if raw_bytes.len() < DEFLATE_LIMIT {
 			compressed.extend_from_slice(&raw_bytes[..]);
@@ -135,22 +164,28 @@ impl Recording {
 			encoder.write_all(&raw_bytes[..]).unwrap();
 		}
 
-		compressed
+		Ok(compressed)
 	}
 
 	pub(crate) fn from_bytes(bytes: &[u8]) -> GResult<Recording> {
 		//decompress the payload
-		ensure!(bytes.len() >= 8);
+		ensure!(bytes.len() >= 9);
 		let decompressed_len = u64::from_le_bytes((&bytes[..8]).try_into().unwrap());
 
+		let flo_width = bytes[8];
+		if flo_width != FLO_WIDTH {
+			bail!("compiled bytes were produced with {}-bit flos, but this Runtime uses {}-bit \
+			       flos; the \"f64-flos\" feature must match", flo_width as u32 * 8, FLO_WIDTH * 8)
+		}
+
 		let mut inflate_storage: Option<Vec<u8>>;
 		let decompressed = if decompressed_len < DEFLATE_LIMIT as u64 {
-			&bytes[8..]
+			&bytes[9..]
 		} else {
 			inflate_storage = Some(Vec::<u8>::with_capacity(decompressed_len as usize));
 
 			let mut decoder = DeflateDecoder::new(inflate_storage.as_mut().unwrap());
-			decoder.write_all(&bytes[8..]).unwrap();
+			decoder.write_all(&bytes[9..]).unwrap();
 			drop(decoder);
 
 			&inflate_storage.as_ref().unwrap()[..]
@@ -227,7 +262,8 @@ enum DenseAction {
 	Execute(Box<DenseBytecode>),
 	ToplevelLet(DenseStay),
 	StartLoad(DenseFilename),
-	EndLoad
+	EndLoad,
+	Defer(Box<DenseBytecode>)
 }
 
 impl DenseAction {
@@ -246,6 +282,10 @@ impl DenseAction {
 			Action::EndLoad => {
 				DenseAction::EndLoad
 			}
+			Action::Defer(ref bytecode) => {
+				let dense_bytecode = DenseBytecode::from_bytecode(bytecode, conv);
+				DenseAction::Defer(Box::new(dense_bytecode))
+			}
 		}
 	}
 
@@ -263,6 +303,9 @@ impl DenseAction {
 			DenseAction::EndLoad => {
 				Action::EndLoad
 			}
+			DenseAction::Defer(dense_bytecode) => {
+				Action::Defer(dense_bytecode.into_bytecode(conv))
+			}
 		}
 	}
 }
@@ -424,7 +467,15 @@ struct DenseBytecode {
 	scratch_count: u8,
 	literal_count: u8,
 	lambdas: Vec<Box<DenseLambda>>,
-	defers: Vec<usize>
+	defers: Vec<usize>,
+
+	//indexes into start_regs
+	interned_literals: Vec<u8>,
+
+	//serde doesn't preserve the Spans of arrs, so for each literal in start_regs which contains
+	//an arr with a known Span, we store its index and the Span of every arr which it contains,
+	//in depth-first order
+	literal_spans: Vec<(u8, Vec<DenseSpan>)>
 }
 
 impl DenseBytecode {
@@ -442,7 +493,28 @@ impl DenseBytecode {
 			lambdas: src.lambdas.iter().map(|lambda| {
 				Box::new(DenseLambda::from_lambda(lambda, conv))
 			}).collect(),
-			defers: src.defers.clone()
+			defers: src.defers.clone(),
+			interned_literals: src.start_regs.iter().enumerate().filter_map(|(i, slot)| {
+				match *slot {
+					Slot::Str(ref st) if st.is_interned() => Some(i as u8),
+					_ => None
+				}
+			}).collect(),
+			literal_spans: src.start_regs.iter().enumerate().filter_map(|(i, slot)| {
+				match *slot {
+					Slot::Arr(ref arr) => {
+						let mut spans = Vec::new();
+						collect_arr_spans(arr, &mut spans);
+						if spans.iter().any(|&span| span != Span::default()) {
+							let spans = spans.iter().map(|span| DenseSpan::from_span(*span, conv));
+							Some((i as u8, spans.collect()))
+						} else {
+							None
+						}
+					}
+					_ => None
+				}
+			}).collect()
 		}
 	}
 
@@ -456,9 +528,27 @@ impl DenseBytecode {
 			scratch_count,
 			literal_count,
 			lambdas,
-			defers
+			defers,
+			interned_literals,
+			literal_spans
 		} = self;
 
+		let mut start_regs = start_regs;
+		for i in interned_literals {
+			if let Val::Str(ref st) = start_regs[i as usize] {
+				start_regs[i as usize] = Val::Str(glsp::intern(st));
+			}
+		}
+
+		for (i, dense_spans) in literal_spans {
+			if let Val::Arr(ref arr) = start_regs[i as usize] {
+				let mut spans = dense_spans.iter().map(|dense_span| dense_span.to_span(conv));
+				restore_arr_spans(arr, &mut spans);
+			}
+		}
+
+		let inline_caches = Bytecode::empty_inline_caches(&instrs);
+
 		glsp::alloc(Bytecode {
 			header: GcHeader::new(),
 			instrs,
@@ -471,16 +561,39 @@ impl DenseBytecode {
 			lambdas: lambdas.into_iter().map(|dense_lambda| {
 				dense_lambda.into_lambda(conv).into_gc()
 			}).collect(),
-			defers
+			defers,
+			inline_caches
 		})
 	}
 }
 
+fn collect_arr_spans(arr: &Arr, spans: &mut Vec<Span>) {
+	spans.push(arr.span());
+	for val in arr.iter() {
+		if let Val::Arr(ref nested) = val {
+			collect_arr_spans(nested, spans);
+		}
+	}
+}
+
+fn restore_arr_spans<I: Iterator<Item = Span>>(arr: &Arr, spans: &mut I) {
+	if let Some(span) = spans.next() {
+		arr.set_span(span);
+	}
+
+	for val in arr.iter() {
+		if let Val::Arr(ref nested) = val {
+			restore_arr_spans(nested, spans);
+		}
+	}
+}
+
 #[derive(Deserialize, Serialize)]
 struct DenseLambda {
 	bytecode: Box<DenseBytecode>,
 	param_map: ParamMap,
 	name: Option<Sym>,
+	doc: Option<String>,
 	captures: Vec<u8>,
 	yields: bool
 }
@@ -491,6 +604,7 @@ impl DenseLambda {
 			bytecode: Box::new(DenseBytecode::from_bytecode(&src.bytecode, conv)),
 			param_map: src.param_map.clone(),
 			name: src.name.clone(),
+			doc: src.doc.as_ref().map(|doc| doc.to_string()),
 			captures: src.captures.clone(),
 			yields: src.yields
 		}
@@ -501,16 +615,19 @@ impl DenseLambda {
 			bytecode: dense_bytecode,
 			param_map,
 			name,
+			doc,
 			captures,
 			yields
 		} = self;
 
+		glsp::add_fn_name(name);
 		glsp::alloc(Lambda {
 			header: GcHeader::new(),
 
 			bytecode: dense_bytecode.into_bytecode(conv).into_gc(),
 			param_map,
 			name,
+			doc: doc.map(Rc::from),
 			captures,
 			yields
 		})
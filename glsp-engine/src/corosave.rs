@@ -0,0 +1,303 @@
+use super::code::{Bytecode, Coro, GFn, PrivCoroState, Stay, StaySource};
+use super::collections::{DequeOps, Tab};
+use super::engine::{glsp, Sym};
+use super::error::{GResult};
+use super::gc::{Gc, Root, Slot};
+use super::print::{instr_to_string};
+use super::val::{Val};
+use super::wrap::{CallableOps, FromVal};
+
+/*
+saving and restoring coroutines, so that a game can be saved while a long-running script is
+paused at a (yield).
+
+we only support the subset of coroutines which can be described as data. the coroutine's fn must
+be a named global which doesn't capture any local variables, so that it can be looked up by name
+when the coroutine is restored. its local and scratch registers, and its stays, must hold
+representable data (or a fn, rfn or class which is bound to a global with the same name, since
+the callee of a pending call is often stored in a scratch register).
+
+the saved tab records the fn's name, the index of the instr where the coroutine will resume, its
+registers and stays, and its pending defers. literal registers aren't saved, because they're
+copied from the bytecode when the coroutine is restored. stays which are shared with something
+outside the coroutine (toplevel (let) forms, or the fn's own captures) are also relinked rather
+than saved.
+
+instr indexes and register numbers are only meaningful for identical bytecode, so we also save
+the number of instrs and a hash of their text. the hash uses sym names rather than sym ids,
+so it's stable between runs of the same program.
+*/
+
+struct SaveSyms {
+	fn_: Sym,
+	state: Sym,
+	instr: Sym,
+	instr_count: Sym,
+	instr_hash: Sym,
+	resume_reg: Sym,
+	regs: Sym,
+	globals: Sym,
+	stays: Sym,
+	defers: Sym,
+	newborn: Sym,
+	paused: Sym
+}
+
+impl SaveSyms {
+	fn new() -> GResult<SaveSyms> {
+		Ok(SaveSyms {
+			fn_: glsp::sym("fn")?,
+			state: glsp::sym("state")?,
+			instr: glsp::sym("instr")?,
+			instr_count: glsp::sym("instr-count")?,
+			instr_hash: glsp::sym("instr-hash")?,
+			resume_reg: glsp::sym("resume-reg")?,
+			regs: glsp::sym("regs")?,
+			globals: glsp::sym("globals")?,
+			stays: glsp::sym("stays")?,
+			defers: glsp::sym("defers")?,
+			newborn: glsp::sym("newborn")?,
+			paused: glsp::sym("paused")?
+		})
+	}
+}
+
+//32-bit fnv-1a over the text of each instr. glsp ints are 32 bits wide, so the result is
+//reinterpreted as an i32.
+fn bytecode_hash(bytecode: &Bytecode) -> i32 {
+	let mut hash: u32 = 0x811c_9dc5;
+	for instr in &bytecode.instrs {
+		let text = instr_to_string(*instr, bytecode.local_count, bytecode.scratch_count);
+		for byte in text.bytes().chain(Some(b'\n')) {
+			hash ^= byte as u32;
+			hash = hash.wrapping_mul(0x0100_0193);
+		}
+	}
+
+	hash as i32
+}
+
+//returns the name of the global which `gfn` is bound to
+fn global_name(gfn: &Gc<GFn>) -> GResult<Sym> {
+	let name = match gfn.lambda.name {
+		Some(name) => name,
+		None => bail!("the coroutine's fn is anonymous, so it can't be looked up by name")
+	};
+
+	ensure!(gfn.captured_stays.is_empty(),
+	        "the coroutine's fn {} captures local variables", name);
+
+	let bound = glsp::has_global(name)? && match glsp::global::<_, Val>(name)? {
+		Val::GFn(ref global) => Gc::ptr_eq(&global.to_gc(), gfn),
+		_ => false
+	};
+
+	ensure!(bound, "the coroutine's fn {} isn't bound to the global {}", name, name);
+	Ok(name)
+}
+
+//a register which holds a fn, rfn or class is saved as the name of a global, if it's bound to
+//that global. returns None for any other value.
+fn callable_global(val: &Val) -> GResult<Option<Sym>> {
+	let name = match *val {
+		Val::GFn(ref gfn) => gfn.lambda.name,
+		Val::RFn(ref rfn) => rfn.name(),
+		Val::Class(ref class) => class.name(),
+		_ => None
+	};
+
+	match name {
+		Some(name) if glsp::has_global(name)? => {
+			let global: Val = glsp::global(name)?;
+			Ok(if global.same(val) { Some(name) } else { None })
+		}
+		_ => Ok(None)
+	}
+}
+
+fn check_val(val: &Val, what: &str) -> GResult<()> {
+	if let Err(msg) = val.check_representability() {
+		bail!("{} can't be saved: {}", what, msg)
+	}
+
+	Ok(())
+}
+
+pub(crate) fn coro_save(coro: &Coro) -> GResult<Root<Tab>> {
+	let syms = SaveSyms::new()?;
+
+	let (state, resume_reg) = match coro.state.get() {
+		PrivCoroState::Newborn => (syms.newborn, None),
+		PrivCoroState::Paused(Some(resume_reg)) => (syms.paused, Some(resume_reg)),
+		PrivCoroState::Paused(None) => {
+			bail!("the coroutine was paused by a safepoint callback, rather than a yield")
+		}
+		PrivCoroState::Running => bail!("the coroutine is currently running"),
+		PrivCoroState::Finished => bail!("the coroutine has already finished"),
+		PrivCoroState::Poisoned => bail!("the coroutine has been poisoned"),
+		PrivCoroState::Recycling => unreachable!()
+	};
+
+	let storage = coro.storage.borrow();
+	let gfn = storage.gfn.as_ref().unwrap();
+	let name = global_name(gfn)?;
+	let bytecode = &gfn.lambda.bytecode;
+
+	assert!(storage.regs.len() == bytecode.start_regs.len() &&
+	        storage.stays.len() == bytecode.start_stays.len());
+
+	//local and scratch registers. the resume_reg will be overwritten by the next (coro-run), so
+	//its current value is discarded.
+	let reg_count = (bytecode.local_count + bytecode.scratch_count) as usize;
+	let regs = glsp::arr_with_capacity(reg_count);
+	let globals = glsp::tab();
+
+	for (i, reg) in storage.regs[..reg_count].iter().enumerate() {
+		let val = reg.root();
+		if Some(i as u8) == resume_reg {
+			regs.push(Val::Nil)?;
+		} else if let Some(global) = callable_global(&val)? {
+			regs.push(Val::Nil)?;
+			globals.set(i, global)?;
+		} else {
+			check_val(&val, &format!("register {} of {}", i, name))?;
+			regs.push(val.deep_clone()?)?;
+		}
+	}
+
+	//stays which belong to the coroutine itself. an Empty stay is still None if its (let) form
+	//hasn't been reached yet.
+	let stays = glsp::tab();
+	for (i, (stay, source)) in storage.stays.iter().zip(&bytecode.start_stays).enumerate() {
+		match (stay, source) {
+			(Some(stay), StaySource::Empty) | (Some(stay), StaySource::Param(_)) => {
+				let val = stay.get().root();
+				check_val(&val, &format!("captured variable {} of {}", i, name))?;
+				stays.set(i, val.deep_clone()?)?;
+			}
+			_ => ()
+		}
+	}
+
+	let defers = glsp::arr_from_iter(storage.defers.iter().map(|&defer| defer as i32))?;
+
+	let tab = glsp::tab();
+	tab.set(syms.fn_, name)?;
+	tab.set(syms.state, state)?;
+	tab.set(syms.instr, storage.instr as i32)?;
+	tab.set(syms.instr_count, bytecode.instrs.len() as i32)?;
+	tab.set(syms.instr_hash, bytecode_hash(bytecode))?;
+	tab.set(syms.resume_reg, resume_reg.map(|reg| reg as i32))?;
+	tab.set(syms.regs, regs)?;
+	tab.set(syms.globals, globals)?;
+	tab.set(syms.stays, stays)?;
+	tab.set(syms.defers, defers)?;
+
+	Ok(tab)
+}
+
+pub(crate) fn check_saveability(coro: &Coro) -> GResult<()> {
+	coro_save(coro).map(|_| ())
+}
+
+pub(crate) fn coro_restore(data: &Tab) -> GResult<Gc<Coro>> {
+	let syms = SaveSyms::new()?;
+
+	let name: Sym = data.get(syms.fn_)?;
+	let gfn = match glsp::global::<_, Val>(name) {
+		Ok(Val::GFn(gfn)) if gfn.yields() => gfn,
+		Ok(val) => bail!("the global {} is {}, rather than a coroutine fn", name, val.a_type_name()),
+		Err(_) => bail!("the coroutine's fn {} is no longer bound to a global", name)
+	};
+
+	ensure!(gfn.captured_stays.is_empty(), "the coroutine's fn {} captures local variables", name);
+
+	//check that the bytecode hasn't changed since the coroutine was saved
+	let bytecode = gfn.lambda.bytecode.clone();
+
+	let saved_count: i32 = data.get(syms.instr_count)?;
+	let saved_hash: i32 = data.get(syms.instr_hash)?;
+
+	if saved_count as usize != bytecode.instrs.len() {
+		bail!("the coroutine's fn {} has been modified: it had {} instrs when the coroutine was \
+		       saved, but it now has {}", name, saved_count, bytecode.instrs.len())
+	}
+
+	if saved_hash != bytecode_hash(&bytecode) {
+		bail!("the coroutine's fn {} has been modified: its instrs no longer match the \
+		       instrs which were saved", name)
+	}
+
+	let state: Sym = data.get(syms.state)?;
+	let instr: usize = data.get(syms.instr)?;
+	let resume_reg = match data.get::<_, Val>(syms.resume_reg)? {
+		Val::Nil => None,
+		val => Some(u8::from_val(&val)?)
+	};
+
+	let state = match (state, resume_reg) {
+		(state, None) if state == syms.newborn && instr == 0 => PrivCoroState::Newborn,
+		(state, Some(reg)) if state == syms.paused => PrivCoroState::Paused(Some(reg)),
+		_ => bail!("invalid coroutine state {} for a saved coroutine", state)
+	};
+
+	ensure!(instr < bytecode.instrs.len(), "invalid instr index {} for the fn {}", instr, name);
+
+	//local and scratch registers, followed by the bytecode's literals
+	let reg_count = (bytecode.local_count + bytecode.scratch_count) as usize;
+	let saved_regs: Vec<Val> = data.get(syms.regs)?;
+	let globals: Root<Tab> = data.get(syms.globals)?;
+
+	ensure!(saved_regs.len() == reg_count, "the saved coroutine has {} registers, but the fn {} \
+	        has {}", saved_regs.len(), name, reg_count);
+
+	let mut regs = Vec::with_capacity(bytecode.start_regs.len());
+	for (i, val) in saved_regs.iter().enumerate() {
+		let val = match globals.get_if_present::<_, Sym>(i)? {
+			Some(global) => glsp::global(global)?,
+			None => val.deep_clone()?
+		};
+
+		regs.push(Slot::from_val(&val));
+	}
+
+	regs.extend(bytecode.start_regs[reg_count..].iter().cloned());
+
+	//stays
+	let saved_stays: Root<Tab> = data.get(syms.stays)?;
+	let mut stays = Vec::with_capacity(bytecode.start_stays.len());
+
+	for (i, source) in bytecode.start_stays.iter().enumerate() {
+		let saved: Option<Val> = saved_stays.get_if_present(i)?;
+		let stay = match (source, saved) {
+			(StaySource::Empty, None) => None,
+			(StaySource::Empty, Some(val)) | (StaySource::Param(_), Some(val)) => {
+				Some(glsp::alloc_gc(Stay::new(Slot::from_val(&val.deep_clone()?))))
+			}
+			(StaySource::Param(_), None) => {
+				bail!("the saved coroutine is missing captured variable {} of {}", i, name)
+			}
+			(StaySource::Captured(_), _) => unreachable!(),
+			(StaySource::PreExisting(ref stay), _) => Some(stay.clone())
+		};
+
+		stays.push(stay);
+	}
+
+	let defers: Vec<usize> = data.get(syms.defers)?;
+	ensure!(defers.iter().all(|&defer| defer < bytecode.instrs.len()),
+	        "invalid defer index for the fn {}", name);
+
+	let coro = Coro::new(gfn.to_gc(), regs, stays);
+	coro.state.set(state);
+
+	let mut storage = coro.storage.borrow_mut();
+	storage.instr = instr;
+	storage.defers = defers;
+	drop(storage);
+
+	let coro = glsp::alloc_gc(coro);
+	coro.write_barrier();
+
+	Ok(coro)
+}
@@ -0,0 +1,375 @@
+#![cfg(feature = "compiler")]
+
+use fnv::{FnvHashMap, FnvHashSet};
+use std::fmt::{Write};
+use std::iter::{FromIterator};
+use super::code::{Bytecode, Instr};
+use super::collections::{DequeOps};
+use super::compile::{Recording};
+use super::engine::{glsp, Span, SpanInfo, SpanStorage, stock_syms::*, Sym};
+use super::val::{Val};
+
+/*
+this module is only present when the "compiler" crate feature is enabled.
+
+it finds the defn, defmacro and defclass forms in a set of Recordings which can't be reached from
+the host's entry points. we build a graph with one node per toplevel form: each Action::Execute
+or Action::Defer bytecode. a node which performs one or more reportable definitions is only live
+when one of its names is referred to by a live node. any other node runs when its file is loaded,
+so it's a root.
+
+a node refers to...
+	- each global which is accessed by a LoadGlobal or SetGlobal instr
+	- each sym which appears in a literal, since it might be passed to (global), (call-meth) or
+	  some other function which treats it as a global's name
+	- each macro which took part in generating the code, recovered from its instrs' Spans. macros
+	  are expanded before compilation, so this is the only record of their use. a quoted form
+	  which a macro returns as-is keeps the span of the macro's own source, so it isn't counted.
+
+definitions share a single namespace, so a macro and a global with the same name are treated as
+the same node. this can only cause false negatives, never false positives.
+*/
+
+/**
+A definition which can't be reached from any entry point, found by
+[`glsp::dead_code_report`](glsp/fn.dead_code_report.html).
+*/
+
+#[derive(Clone, Debug)]
+pub struct DeadDef {
+	kind: Sym,
+	name: Sym,
+	location: Option<SpanInfo>,
+	certain: bool,
+	dynamic_accesses: Vec<SpanInfo>
+}
+
+impl DeadDef {
+	/**
+	Returns the kind of definition: `defn`, `defmacro` or `defclass`.
+
+	Mixins defined by `defmixin` are reported as `defclass`.
+	*/
+	pub fn kind(&self) -> Sym {
+		self.kind
+	}
+
+	/** Returns the name being defined. */
+	pub fn name(&self) -> Sym {
+		self.name
+	}
+
+	/** Returns the location of the definition, when it's known. */
+	pub fn location(&self) -> Option<&SpanInfo> {
+		self.location.as_ref()
+	}
+
+	/**
+	Returns `false` if reachable code looks up globals using a name which isn't a literal,
+	like `(global (sym name))`.
+
+	In that case, this definition might still be used. The locations of those lookups are
+	returned by [`dynamic_accesses`](#method.dynamic_accesses).
+	*/
+	pub fn is_certain(&self) -> bool {
+		self.certain
+	}
+
+	/**
+	Returns the location of each lookup which prevents this definition from being
+	[certainly dead](#method.is_certain).
+
+	Lookups which can't be traced back to a source file are left out.
+	*/
+	pub fn dynamic_accesses(&self) -> &[SpanInfo] {
+		&self.dynamic_accesses[..]
+	}
+}
+
+//a single toplevel form
+struct Node {
+	//each (kind, name, span) defined by this form. empty for a root.
+	defs: Vec<(Sym, Sym, Span)>,
+	refs: FnvHashSet<Sym>,
+	dynamic_accesses: Vec<Span>
+}
+
+//what's known about the value stored in a register by the toplevel code which precedes an instr
+#[derive(Copy, Clone)]
+enum Known {
+	Global(Sym),
+	Literal(Sym),
+	Lambda,
+	CallTo(Sym)
+}
+
+pub(crate) fn dead_code_report(recordings: &[Recording], entry_points: &[Sym]) -> Vec<DeadDef> {
+	let nodes = Vec::from_iter(recordings.iter().flat_map(|recording| {
+		recording.bytecodes().map(|bytecode| Node::from_bytecode(bytecode))
+	}));
+
+	let mut by_name = FnvHashMap::<Sym, Vec<usize>>::default();
+	let mut live_nodes = vec![false; nodes.len()];
+	let mut pending = entry_points.to_vec();
+
+	for (i, node) in nodes.iter().enumerate() {
+		if node.defs.is_empty() {
+			live_nodes[i] = true;
+			pending.extend(node.refs.iter().copied());
+		} else {
+			for &(_, name, _) in &node.defs {
+				by_name.entry(name).or_default().push(i);
+			}
+		}
+	}
+
+	let mut live_names = FnvHashSet::<Sym>::default();
+	while let Some(name) = pending.pop() {
+		if live_names.insert(name) {
+			for &i in by_name.get(&name).into_iter().flatten() {
+				if !live_nodes[i] {
+					live_nodes[i] = true;
+					pending.extend(nodes[i].refs.iter().copied());
+				}
+			}
+		}
+	}
+
+	let mut certain = true;
+	let mut dynamic_accesses = Vec::new();
+	for (node, _) in nodes.iter().zip(&live_nodes).filter(|(_, &live)| live) {
+		for &span in &node.dynamic_accesses {
+			certain = false;
+			if let Some(info) = glsp::span_info(span) {
+				if !dynamic_accesses.contains(&info) {
+					dynamic_accesses.push(info);
+				}
+			}
+		}
+	}
+
+	let mut dead = Vec::new();
+	for (node, _) in nodes.iter().zip(&live_nodes).filter(|(_, &live)| !live) {
+		for &(kind, name, span) in &node.defs {
+			dead.push(DeadDef {
+				kind,
+				name,
+				location: glsp::span_info(span),
+				certain,
+				dynamic_accesses: dynamic_accesses.clone()
+			});
+		}
+	}
+
+	dead
+}
+
+//the text produced by glsp::dead_code_text
+pub(crate) fn render_text(dead: &[DeadDef]) -> String {
+	let mut text = String::new();
+
+	for def in dead {
+		match def.location.as_ref().and_then(|info| Some((info.filename()?, info.line()?))) {
+			Some((filename, line)) => write!(text, "{}:{}: ", filename, line).unwrap(),
+			None => text.push_str("generated code: ")
+		}
+
+		writeln!(text, "({} {})", def.kind, def.name).unwrap();
+	}
+
+	if let Some(def) = dead.iter().find(|def| !def.certain) {
+		text.push_str("\nnote: some globals are looked up using a name which isn't a literal, \
+		               so these definitions might still be used");
+
+		if def.dynamic_accesses.is_empty() {
+			text.push_str(".\n");
+		} else {
+			text.push_str(". the lookups are at:\n");
+			for info in &def.dynamic_accesses {
+				writeln!(text, "\t{}", info).unwrap();
+			}
+		}
+	}
+
+	text
+}
+
+impl Node {
+	fn from_bytecode(bytecode: &Bytecode) -> Node {
+		let mut node = Node {
+			defs: Vec::new(),
+			refs: FnvHashSet::default(),
+			dynamic_accesses: Vec::new()
+		};
+
+		//a plain (def), or a definition which is performed conditionally, makes the whole form
+		//a root. we don't attempt to track register values across jumps.
+		let mut is_root = false;
+		let mut known = FnvHashMap::<u8, Known>::default();
+
+		for (&instr, &span) in bytecode.instrs.iter().zip(&bytecode.spans) {
+			let (dst_reg, call) = match instr {
+				Instr::LoadGlobal(dst_reg, global_name, _) => {
+					known.insert(dst_reg, Known::Global(global_name.into()));
+					continue
+				}
+				Instr::MakeGFn(dst_reg, _) => {
+					known.insert(dst_reg, Known::Lambda);
+					continue
+				}
+				Instr::CopyRegister(dst_reg, src_reg) => {
+					match lookup(bytecode, &known, src_reg) {
+						Some(value) => known.insert(dst_reg, value),
+						None => known.remove(&dst_reg)
+					};
+					continue
+				}
+				Instr::Call0(dst_reg, callee_reg) |
+				Instr::Call1(dst_reg, callee_reg, _) |
+				Instr::CallN(dst_reg, callee_reg, _) => (Some(dst_reg), (callee_reg, None)),
+				Instr::Call2(dst_reg, callee_reg, arg0_reg, arg1_reg) => {
+					(Some(dst_reg), (callee_reg, Some((arg0_reg, arg1_reg))))
+				}
+				Instr::TailCall2(callee_reg, arg0_reg, arg1_reg) => {
+					(None, (callee_reg, Some((arg0_reg, arg1_reg))))
+				}
+				Instr::Jump(..) => {
+					is_root = true;
+					continue
+				}
+				Instr::Splay(..) | Instr::PushDefer(..) | Instr::RunAndPopDefers(..) |
+				Instr::RunDefer(..) | Instr::EndDefer() => continue,
+				mut instr => {
+					if instr.jump_bytes().is_some() {
+						is_root = true;
+					}
+
+					known.remove(instr.register_mut(0));
+					continue
+				}
+			};
+
+			let (callee_reg, args) = call;
+			let callee = match lookup(bytecode, &known, callee_reg) {
+				Some(Known::Global(callee)) => Some(callee),
+				_ => None
+			};
+
+			if let (Some(callee), Some((name_reg, value_reg))) = (callee, args) {
+				if callee == BIND_GLOBAL_SYM || callee == BIND_MACRO_SYM {
+					let name = match lookup(bytecode, &known, name_reg) {
+						Some(Known::Literal(name)) => Some(name),
+						_ => None
+					};
+
+					let kind = match (callee, lookup(bytecode, &known, value_reg)) {
+						(BIND_MACRO_SYM, Some(Known::Lambda)) => Some(DEFMACRO_SYM),
+						(BIND_GLOBAL_SYM, Some(Known::Lambda)) => Some(DEFN_SYM),
+						(BIND_GLOBAL_SYM, Some(Known::CallTo(MAKE_CLASS_SYM))) => Some(DEFCLASS_SYM),
+						_ => None
+					};
+
+					match (kind, name) {
+						(Some(kind), Some(name)) => node.defs.push((kind, name, span)),
+						_ => is_root = true
+					}
+				}
+			}
+
+			if let Some(dst_reg) = dst_reg {
+				match callee {
+					Some(callee) => known.insert(dst_reg, Known::CallTo(callee)),
+					None => known.remove(&dst_reg)
+				};
+			}
+		}
+
+		if is_root {
+			node.defs.clear();
+		}
+
+		let mut visited_spans = FnvHashSet::<Span>::default();
+		node.add_refs(bytecode, &mut visited_spans);
+
+		node
+	}
+
+	//records the references made by a Bytecode and all of its nested Lambdas
+	fn add_refs(&mut self, bytecode: &Bytecode, visited_spans: &mut FnvHashSet<Span>) {
+		let literal_start = (bytecode.local_count + bytecode.scratch_count) as usize;
+		for literal in &bytecode.start_regs[literal_start..] {
+			add_literal_refs(&mut self.refs, &literal.root());
+		}
+
+		for (&instr, &span) in bytecode.instrs.iter().zip(&bytecode.spans) {
+			match instr {
+				Instr::LoadGlobal(_, global_name, _) => {
+					let global_name = Sym::from(global_name);
+					self.refs.insert(global_name);
+
+					//these functions can access any global, unless they're called directly
+					//with a literal argument, in which case they'd be compiled to an op
+					match global_name {
+						GLOBAL_SYM | SET_GLOBAL_SYM | HAS_GLOBALP_SYM | EVAL_SYM |
+						EVAL_MULTI_SYM => self.dynamic_accesses.push(span),
+						_ => ()
+					}
+				}
+				Instr::SetGlobal(_, global_name) => {
+					self.refs.insert(global_name.into());
+				}
+				Instr::OpGlobal(_, name_reg) | Instr::OpSetGlobal(_, name_reg, _) => {
+					if (name_reg as usize) < literal_start {
+						self.dynamic_accesses.push(span);
+					}
+				}
+				_ => ()
+			}
+
+			//each macro which produced an instr refers to that macro
+			let mut span = span;
+			while visited_spans.insert(span) {
+				match glsp::span_storage(span) {
+					SpanStorage::Expanded(macro_name, callsite, _) => {
+						if let Some(macro_name) = macro_name {
+							self.refs.insert(macro_name);
+						}
+
+						span = callsite;
+					}
+					SpanStorage::Loaded(..) | SpanStorage::Generated => break
+				}
+			}
+		}
+
+		for lambda in &bytecode.lambdas {
+			self.add_refs(&lambda.bytecode, visited_spans);
+		}
+	}
+}
+
+fn lookup(bytecode: &Bytecode, known: &FnvHashMap<u8, Known>, reg: u8) -> Option<Known> {
+	let literal_start = (bytecode.local_count + bytecode.scratch_count) as usize;
+	if reg as usize >= literal_start {
+		match bytecode.start_regs[reg as usize].root() {
+			Val::Sym(sym) => Some(Known::Literal(sym)),
+			_ => None
+		}
+	} else {
+		known.get(&reg).copied()
+	}
+}
+
+fn add_literal_refs(refs: &mut FnvHashSet<Sym>, literal: &Val) {
+	match *literal {
+		Val::Sym(sym) => {
+			refs.insert(sym);
+		}
+		Val::Arr(ref arr) => {
+			for item in arr.iter() {
+				add_literal_refs(refs, &item);
+			}
+		}
+		_ => ()
+	}
+}
@@ -7,6 +7,7 @@ use super::ast::{self, Alias, Ast, Expr, Id, Node, ParamList, Range};
 use super::code::{
 	Lambda, Bytecode, Instr, ParamMap, Stay, StaySource, SymBytes, JumpBytes
 };
+use super::collections::{DequeOps};
 use super::error::{GResult};
 use super::engine::{glsp, Span, Sym};
 use super::gc::{Gc, GcHeader, Slot, Root};
@@ -15,6 +16,10 @@ use super::transform::{
 };
 use super::val::{Val};
 
+//frozen str literals with this many characters or fewer are passed to glsp::intern, so that
+//identical literals throughout a program share a single allocation
+const INTERN_LITERAL_MAX_LEN: usize = 64;
+
 //the borrow checker doesn't yet support e.emit(CopyRegister(e.nr(dst), e.nr(src)), span); it
 //complains about simultaneous mutable borrows. so until nll gets smarter, we provide this macro
 //to hack around it. emit!(e, Fizz(dst, src; sym_id), span) roughly expands to:
@@ -559,8 +564,15 @@ impl Frame {
 		if self.literals.len() > 255 {
 			bail_at!(span, "stack frame requires more than 255 literals")
 		}
+
+		let val = match *val {
+			Val::Str(ref st) if st.is_frozen() && st.len() <= INTERN_LITERAL_MAX_LEN => {
+				Val::Str(glsp::intern(st))
+			}
+			ref val => val.clone()
+		};
 		
-		self.literals.push(val.clone());
+		self.literals.push(val);
 		Ok((self.literals.len() - 1) as u8)
 	}
 
@@ -728,6 +740,16 @@ impl Frame {
 		
 		self.reify_scratch_placeholders();
 		self.reify_literal_placeholders();
+
+		if !self.yields {
+			self.mark_tail_calls();
+		}
+
+		if glsp::opt_level() > 0 {
+			self.peephole();
+		}
+
+		Bytecode::assign_inline_caches(&mut self.instrs);
 		
 		let mut start_regs = Vec::<Slot>::with_capacity(reg_count);
 		start_regs.extend(self.local_inits.iter().map(|val| Slot::from_val(val)));
@@ -737,6 +759,178 @@ impl Frame {
 		Ok(start_regs)
 	}
 
+	//a call is in tail position when the only instrs between it and a Return of its result are
+	//Jumps. we replace each such call with a TailCall instr, which vm.rs can execute by reusing
+	//the current interpreter. if any (defer) were in scope at the call, a RunAndPopDefers instr 
+	//would appear before the Return, so tail calls never skip a pending (defer).
+	//
+	//yielding frames are excluded, because a coro's frame can't be discarded from under it.
+	fn mark_tail_calls(&mut self) {
+		for i in 0 .. self.instrs.len() {
+			let dst_reg = match self.instrs[i] {
+				Instr::Call0(dst_reg, ..) | Instr::Call1(dst_reg, ..) |
+				Instr::Call2(dst_reg, ..) | Instr::CallN(dst_reg, ..) => dst_reg,
+				_ => continue
+			};
+
+			//follow a short chain of Jumps. bounding its length protects us from cycles.
+			let mut next_instr = i + 1;
+			let mut jumps = 0;
+			let is_tail = loop {
+				match self.instrs.get(next_instr) {
+					Some(&Instr::Jump(jump_bytes)) if jumps < 8 => {
+						let target = (next_instr + 1) as isize + isize::from(jump_bytes);
+						next_instr = target as usize;
+						jumps += 1;
+					}
+					Some(&Instr::Return(src_reg)) => break src_reg == dst_reg,
+					_ => break false
+				}
+			};
+
+			if is_tail {
+				self.instrs[i] = match self.instrs[i] {
+					Instr::Call0(_, callee) => Instr::TailCall0(callee),
+					Instr::Call1(_, callee, arg) => Instr::TailCall1(callee, arg),
+					Instr::Call2(_, callee, arg0, arg1) => Instr::TailCall2(callee, arg0, arg1),
+					Instr::CallN(_, base, arg_count) => Instr::TailCallN(base, arg_count),
+					_ => unreachable!()
+				};
+			}
+		}
+	}
+
+	//the peephole pass...
+	//- a Jump to a Jump is redirected to the final target, and a Jump to a Return is replaced
+	//  with a copy of that Return
+	//- a comparison op followed by a JumpIfFalse of its result is fused into a single 
+	//  superinstruction, such as LtJumpIfFalse
+	//- a JumpIfFalse which only skips over a Jump is fused with that Jump into a JumpIfTrue
+	//
+	//an instr is never fused into its predecessor when it's the target of a jump or the start 
+	//of a (defer). fusing shrinks the bytecode, so once we're finished, we recalculate every
+	//jump offset and (defer) start instr.
+	fn peephole(&mut self) {
+		fn target_of(instrs: &[Instr], i: usize) -> Option<usize> {
+			instrs[i].jump_bytes().map(|bytes| ((i + 1) as isize + isize::from(bytes)) as usize)
+		}
+
+		fn offset_bytes(from: usize, to: usize) -> JumpBytes {
+			JumpBytes::try_from(to as isize - (from + 1) as isize).unwrap()
+		}
+
+		//jump threading. as in mark_tail_calls, we bound the length of the chain
+		for i in 0 .. self.instrs.len() {
+			if let Instr::Jump(_) = self.instrs[i] {
+				let mut target = target_of(&self.instrs, i).unwrap();
+				let mut jumps = 0;
+				while let Some(&Instr::Jump(_)) = self.instrs.get(target) {
+					if jumps == 8 {
+						break
+					}
+
+					target = target_of(&self.instrs, target).unwrap();
+					jumps += 1;
+				}
+
+				match self.instrs.get(target) {
+					Some(&Instr::Return(src_reg)) => self.instrs[i] = Instr::Return(src_reg),
+					_ => self.instrs[i].replace_jump_bytes(offset_bytes(i, target))
+				}
+			}
+		}
+
+		//find the jump targets, storing each jump's absolute target so that it can be 
+		//recalculated after fusing
+		let len = self.instrs.len();
+		let mut targets = Vec::<Option<usize>>::with_capacity(len);
+		targets.extend((0 .. len).map(|i| target_of(&self.instrs, i)));
+
+		let mut is_target = vec![false; len + 1];
+		for &target in targets.iter().flatten().chain(self.defers.iter()) {
+			if target <= len {
+				is_target[target] = true;
+			}
+		}
+
+		//fusing
+		let mut removed = vec![false; len];
+		let mut i = 0;
+		while i + 1 < len {
+			use Instr::*;
+
+			let fused = if is_target[i + 1] {
+				None
+			} else {
+				match (self.instrs[i], self.instrs[i + 1]) {
+					(OpNumEq(dst, arg0, arg1), JumpIfFalse(src, bytes)) if src == dst => {
+						Some(NumEqJumpIfFalse(dst, arg0, arg1, bytes))
+					}
+					(OpLt(dst, arg0, arg1), JumpIfFalse(src, bytes)) if src == dst => {
+						Some(LtJumpIfFalse(dst, arg0, arg1, bytes))
+					}
+					(OpLte(dst, arg0, arg1), JumpIfFalse(src, bytes)) if src == dst => {
+						Some(LteJumpIfFalse(dst, arg0, arg1, bytes))
+					}
+					(OpGt(dst, arg0, arg1), JumpIfFalse(src, bytes)) if src == dst => {
+						Some(GtJumpIfFalse(dst, arg0, arg1, bytes))
+					}
+					(OpGte(dst, arg0, arg1), JumpIfFalse(src, bytes)) if src == dst => {
+						Some(GteJumpIfFalse(dst, arg0, arg1, bytes))
+					}
+					(JumpIfFalse(src, skip), Jump(bytes)) if isize::from(skip) == 1 => {
+						Some(JumpIfTrue(src, bytes))
+					}
+					_ => None
+				}
+			};
+
+			if let Some(fused) = fused {
+				self.instrs[i] = fused;
+				targets[i] = targets[i + 1];
+				removed[i + 1] = true;
+				i += 2;
+			} else {
+				i += 1;
+			}
+		}
+
+		if removed.iter().all(|&removed| !removed) {
+			return
+		}
+
+		//compaction
+		let mut new_index = Vec::<usize>::with_capacity(len + 1);
+		let mut next_index = 0;
+		for i in 0 ..= len {
+			new_index.push(next_index);
+			if i < len && !removed[i] {
+				next_index += 1;
+			}
+		}
+
+		let mut new_instrs = Vec::<Instr>::with_capacity(next_index);
+		let mut new_spans = Vec::<Span>::with_capacity(next_index);
+		for i in 0 .. len {
+			if !removed[i] {
+				let mut instr = self.instrs[i];
+				if let Some(target) = targets[i] {
+					instr.replace_jump_bytes(offset_bytes(new_index[i], new_index[target]));
+				}
+
+				new_instrs.push(instr);
+				new_spans.push(self.spans[i]);
+			}
+		}
+
+		self.instrs = new_instrs;
+		self.spans = new_spans;
+
+		for defer_start in &mut self.defers {
+			*defer_start = new_index[*defer_start];
+		}
+	}
+
 	fn add_lambda(&mut self, lambda: Root<Lambda>, span: Span) -> GResult<u8> {
 		let lambda_id = self.lambdas.len();
 		self.lambdas.push(lambda);
@@ -770,6 +964,8 @@ pub(crate) fn encode_fragment(
 		defers,
 		..
 	} = enc.frames.pop().unwrap();
+
+	let inline_caches = Bytecode::empty_inline_caches(&instrs);
 	
 	Ok(glsp::alloc(Bytecode {
 		header: GcHeader::new(),
@@ -781,7 +977,8 @@ pub(crate) fn encode_fragment(
 		literal_count: literals.len() as u8,
 		start_stays: stay_sources,
 		lambdas: lambdas.iter().map(|root| Gc::from_root(root)).collect(),
-		defers
+		defers,
+		inline_caches
 	}))
 }
 
@@ -878,6 +1075,18 @@ fn encode_splay(enc: &mut Encoder, splay_bits: u32, span: Span) {
 	}
 }
 
+//a name which isn't bound to a local variable refers to a global. we resolve it relative to the
+//current module at compile time, so modules have no runtime cost.
+fn resolve_global(name: Sym, span: Span) -> GResult<Sym> {
+	match glsp::resolve_global(name) {
+		Ok(global_name) => Ok(global_name),
+		Err(source) => {
+			let msg = error_at!(span, "unable to resolve the global '{}'", name);
+			Err(msg.with_source(source))
+		}
+	}
+}
+
 fn encode_node(enc: &mut Encoder, ast: &Ast, node: Id<Node>, dst: Reg) -> GResult<Reg> {
 	let mut starting_scratch_height = enc.frame().next_scratch();
 	
@@ -912,9 +1121,19 @@ fn encode_node(enc: &mut Encoder, ast: &Ast, node: Id<Node>, dst: Reg) -> GResul
 					}
 				}
 			} else {
-				let dst_reg = reify_dst(enc, dst, node_span)?;
-				emit!(enc.frame_mut(), LoadGlobal(dst_reg; SymBytes::from(name)), node_span);
-				dst_reg
+				let global_name = resolve_global(name, node_span)?;
+
+				if let Some(const_val) = glsp::inlined_const(global_name) {
+					#[cfg(feature = "compiler")]
+					glsp::note_inlined_const(global_name, &const_val);
+
+					Reg::Literal(enc.frame_mut().alloc_literal(&const_val, node_span)?)
+				} else {
+					let dst_reg = reify_dst(enc, dst, node_span)?;
+					emit!(enc.frame_mut(), LoadGlobal(dst_reg; SymBytes::from(global_name), 0), 
+					      node_span);
+					dst_reg
+				}
 			}
 		}
 		Expr::Call { callee, args, splay_bits } => {
@@ -1165,8 +1384,13 @@ fn encode_node(enc: &mut Encoder, ast: &Ast, node: Id<Node>, dst: Reg) -> GResul
 					}
 				}
 			} else {
+				let global_name = resolve_global(target, node_span)?;
+				if glsp::is_const(global_name)? {
+					bail_at!(node_span, "attempted to assign to the constant '{}'", global_name)
+				}
+
 				let src_reg = encode_node(enc, ast, src_node, Reg::Unspecified)?;
-				emit!(enc.frame_mut(), SetGlobal(src_reg; SymBytes::from(target)), node_span);
+				emit!(enc.frame_mut(), SetGlobal(src_reg; SymBytes::from(global_name)), node_span);
 				src_reg
 			}
 		}
@@ -1214,7 +1438,10 @@ fn encode_node(enc: &mut Encoder, ast: &Ast, node: Id<Node>, dst: Reg) -> GResul
 			//evaluates to #n
 			Reg::Literal(enc.frame_mut().alloc_literal(&Val::Nil, node_span)?)
 		}
-		Expr::Fn { name, arg_limits, ref param_list, body, yields } => {
+		Expr::Fn {
+			name, ref doc, arg_limits, ref param_list, ref param_names, ref param_types, 
+			return_type, body, yields
+		} => {
 			//initialize the lambda's frame
 			let mut frame = Frame::new();
 			frame.yields = yields;
@@ -1246,6 +1473,8 @@ fn encode_node(enc: &mut Encoder, ast: &Ast, node: Id<Node>, dst: Reg) -> GResul
 				..
 			} = enc.frames.pop().unwrap();
 
+			let inline_caches = Bytecode::empty_inline_caches(&instrs);
+
 			let new_lambda = glsp::alloc(Lambda {
 				header: GcHeader::new(),
 				bytecode: Gc::from_root(&glsp::alloc(Bytecode {
@@ -1258,13 +1487,18 @@ fn encode_node(enc: &mut Encoder, ast: &Ast, node: Id<Node>, dst: Reg) -> GResul
 					scratch_count: scratch_used as u8,
 					literal_count: literals.len() as u8,
 					lambdas: lambdas.iter().map(|root| Gc::from_root(root)).collect(),
-					defers
+					defers,
+					inline_caches
 				})),
-				param_map: ParamMap::from_param_list(param_list, &arg_limits, node_span)?,
+				param_map: ParamMap::from_param_list(
+					ast, param_list, param_names, param_types, return_type, &arg_limits, node_span
+				)?,
 				captures: stay_captures,
 				name,
+				doc: doc.clone(),
 				yields
 			});
+			glsp::add_fn_name(name);
 			
 			//emit the code to create a new closure for the just-created fn
 			let lambda_id = enc.frame_mut().add_lambda(new_lambda, node_span)?;
@@ -1378,6 +1612,16 @@ fn encode_do(
 		let mut result = None; //this will always be overwritten in the loop
 		let base_defer = enc.frame().active_defers.len();
 
+		//a (defer) body is executed after the last form, but it's encoded using the scratch regs 
+		//which were free at the point of the (defer) form itself. if the last form were to store 
+		//its result in a newly-allocated scratch reg, the (defer) body could clobber it.
+		let has_defer = nodes.clone().any(|node| matches!(ast[node].1, Expr::Defer(_)));
+		let dst = if dst == Reg::Unspecified && has_defer {
+			reify_dst(enc, dst, span)?
+		} else {
+			dst
+		};
+
 		for (i, node) in nodes.enumerate() {
 			let node_span = ast[node].0;
 			let is_last = i == nodes.len() - 1;
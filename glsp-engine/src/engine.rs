@@ -2,41 +2,75 @@ use fnv::{FnvHashMap};
 use owning_ref::{OwningHandle};
 use self::stock_syms::*;
 use smallvec::{SmallVec};
-use std::{fmt, fs, str, u32};
+use std::{fmt, str, u32};
 use std::any::{Any, TypeId, type_name};
+use std::borrow::{Cow};
 use std::cell::{Cell, Ref, RefCell, RefMut};
-use std::collections::{HashMap, hash_map::Entry::{Occupied, Vacant}, HashSet};
+use std::collections::{HashMap, hash_map::Entry::{Occupied, Vacant}, HashSet, VecDeque};
 use std::convert::{TryFrom};
 use std::fmt::{Debug, Display, Formatter, Pointer};
 use std::io::{self, stderr, stdout, Write};
 use std::iter::{FromIterator};
 use std::marker::{PhantomData};
+use std::mem::{size_of};
 use std::num::{NonZeroU32};
 use std::ops::{Deref, DerefMut};
 use std::panic::{self, AssertUnwindSafe};
-use std::path::{PathBuf};
+use std::panic::{Location};
+use std::path::{Path};
 use std::rc::{Rc};
-use std::time::{SystemTime, UNIX_EPOCH};
-use super::{eval, lex};
-use super::class::{Class, Obj};
-use super::code::{Coro, GFn};
-use super::collections::{Arr, DequeAccess, DequeOps, IntoElement, Str, Tab};
-use super::error::{GResult};
+use std::sync::{Arc, Mutex, Weak};
+use std::sync::atomic::{AtomicU64, Ordering::{Acquire, Release}};
+use std::thread;
+#[cfg(any(feature = "watch", not(target_arch = "wasm32")))]
+use std::{fs, time::SystemTime};
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{UNIX_EPOCH};
+
+use super::{eval, lex, print};
+use super::api::{ApiEntry, render_markdown, short_type_name};
+use super::class::{Class, Obj, ObjContents};
+use super::code::{Bytecode, Coro, CoroState, CoroStorage, GFn, PrivCoroState, Stay};
+use super::collections::{
+	Arr, DequeAccess, DequeOps, IntoElement, Str, StrStorage, Tab, Weakness
+};
+use super::corosave;
+use super::error::{
+	frozen_global_error, GError, GResult, MessageArgs, MessageId, nonexistent_meth_error,
+	unbound_global_error
+};
 use super::eval::{Env, EnvMode, Expander, Expansion};
-use super::gc::{Allocate, Heap, Gc, GcHeader, Slot, Root, Visitor};
+use super::gc::{Allocate, ErasedGc, Heap, Gc, GcHeader, Slot, Root, Visitor};
 use super::iter::{GcCallable, GIter, GIterState, Iterable, IterableOps};
-use super::parse::{Parser};
+use super::library::{Library, LibraryItem};
+use super::parse::{Definition, Parser, ParsedForm};
+use super::persist::{PArr, PTab};
+use super::record::{Rec, RecType};
+use super::suggest;
 use super::transform::{KnownOp, known_ops};
-use super::val::{Num, Val};
-use super::vm::{Frame, GlspApiName, Vm};
-use super::wrap::{FromVal, ToCallArgs, Callable, CallableOps, ToVal, WrappedFn};
+use super::val::{Flo, Num, Val};
+use super::vecmath::{self, Mat3, Vec2, Vec3, VectorOp};
+use super::view::{View, ViewField, ViewLayout, ViewLend};
+use super::vm::{DEFAULT_MAX_CALL_DEPTH, Frame, GlspApiName, Vm};
+use super::wrap::{BoxedFn, FromVal, ToCallArgs, Callable, CallableOps, ToVal, WrappedFn};
 
 #[cfg(feature = "compiler")]
 use std::mem::forget;
 
 #[cfg(feature = "compiler")]
-use super::{code::Stay, compile::{Action, Recording}};
+use fnv::{FnvHashSet};
 
+#[cfg(feature = "compiler")]
+use super::cache::{self, CompileCache, CompileCacheStats, Dep};
+
+#[cfg(feature = "compiler")]
+use super::compile::{Action, Recording};
+
+#[cfg(feature = "compiler")]
+use super::deadcode::{self, DeadDef};
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Instant};
 
 //-------------------------------------------------------------------------------------------------
 // ACTIVE_ENGINE
@@ -90,9 +124,11 @@ fn free_engine_id(id: u8) {
 #[inline(always)]
 fn with_engine<R, F: FnOnce(&EngineStorage) -> R>(f: F) -> R {
 	ACTIVE_ENGINE.with(|ref_cell| {
-		let borrow = ref_cell.borrow();
-		let rc = borrow.as_ref().unwrap();
-		f(&rc)
+		let opt_rc = ref_cell.borrow();
+		match &*opt_rc {
+			Some(rc) => f(&rc),
+			None => panic!("attempted to call a glsp function when no Runtime is active")
+		}
 	})
 }
 
@@ -118,6 +154,17 @@ pub(crate) fn with_vm<R, F: FnOnce(&Vm) -> R>(f: F) -> R {
 	})
 }
 
+//like with_vm, but returns None rather than panicking when no glsp engine is active. this is
+//used by destructors which might run while a panic is unwinding, when a second panic would abort
+pub(crate) fn try_with_vm<R, F: FnOnce(&Vm) -> R>(f: F) -> Option<R> {
+	ACTIVE_ENGINE.try_with(|ref_cell| {
+		match ref_cell.try_borrow() {
+			Ok(opt_rc) => opt_rc.as_ref().map(|rc| f(&rc.vm)),
+			Err(_) => None
+		}
+	}).ok().flatten()
+}
+
 #[inline(always)]
 pub(crate) fn with_known_ops<R, F: FnOnce(&HashMap<Sym, KnownOp>) -> R>(f: F) -> R {
 	ACTIVE_ENGINE.with(|ref_cell| {
@@ -160,6 +207,106 @@ where
 // pr!(), prn!(), epr!(), eprn!()
 //-------------------------------------------------------------------------------------------------
 
+/**
+The severity of a line of text which is passed to the 
+[print sink](glsp/fn.set_print_sink.html).
+
+Levels are ordered from least to most severe: `Trace < Print < Warn < Error`. Text below the
+[log level](glsp/fn.set_log_level.html) is discarded.
+*/
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum PrintLevel {
+	/**
+	Diagnostic messages, such as the statistics reported by [`glsp::gc`](glsp/fn.gc.html).
+	*/
+	Trace,
+
+	/**
+	Output from [`pr`](https://gamelisp.rs/std/pr), [`prn`](https://gamelisp.rs/std/prn),
+	[`pr!`](macro.pr.html) and [`prn!`](macro.prn.html).
+	*/
+	Print,
+
+	/**
+	Warnings, such as [`(log-msg 'warn ...)`](https://gamelisp.rs/std/log-msg).
+	*/
+	Warn,
+
+	/**
+	Output from [`epr`](https://gamelisp.rs/std/epr), [`eprn`](https://gamelisp.rs/std/eprn),
+	[`epr!`](macro.epr.html) and [`eprn!`](macro.eprn.html), including uncaught errors.
+	*/
+	Error
+}
+
+type PrintSink = dyn Fn(PrintLevel, &str);
+
+type MessageFormatter = dyn Fn(MessageId, &MessageArgs) -> String;
+
+//every piece of printed text passes through this function. without a print sink, it's written to
+//the pr_writer or epr_writer as-is. with a print sink, it's split into lines: an incomplete line
+//is held back until it's completed, or until some text is printed at a different level.
+fn print_output(level: PrintLevel, buf: &[u8]) -> io::Result<usize> {
+	with_engine(|engine| {
+		if level < engine.log_level.get() {
+			return Ok(buf.len())
+		}
+
+		let sink = match *engine.print_sink.borrow() {
+			Some(ref sink) => sink.clone(),
+			None => {
+				return match level {
+					PrintLevel::Print => engine.pr_writer.borrow_mut().write(buf),
+					_ => engine.epr_writer.borrow_mut().write(buf)
+				}
+			}
+		};
+
+		let mut lines = Vec::new();
+		{
+			let mut pending = engine.print_pending.borrow_mut();
+			let (ref mut pending_level, ref mut pending_text) = *pending;
+
+			if *pending_level != level && pending_text.len() > 0 {
+				lines.push((*pending_level, std::mem::take(pending_text)));
+			}
+
+			*pending_level = level;
+			pending_text.push_str(&String::from_utf8_lossy(buf));
+
+			while let Some(i) = pending_text.find('\n') {
+				let rest = pending_text.split_off(i + 1);
+				let mut line = std::mem::replace(pending_text, rest);
+				line.pop();
+				lines.push((level, line));
+			}
+		}
+
+		//the sink is invoked while no part of the engine is borrowed
+		for (level, line) in lines {
+			sink(level, &line);
+		}
+
+		Ok(buf.len())
+	})
+}
+
+//passes any incomplete line to the print sink
+fn flush_print_sink() {
+	let (sink, pending) = with_engine(|engine| {
+		let sink = engine.print_sink.borrow().clone();
+		let (level, ref mut text) = *engine.print_pending.borrow_mut();
+		(sink, (level, std::mem::take(text)))
+	});
+
+	if let Some(sink) = sink {
+		if pending.1.len() > 0 {
+			sink(pending.0, &pending.1);
+		}
+	}
+}
+
 //we can't have the macros call rt::with_pr_writer directly, because their arguments might use
 //the ? operator. we use PrWriter and EprWriter as (slightly inefficient) adapters instead.
 #[doc(hidden)]
@@ -167,7 +314,7 @@ pub struct PrWriter;
 
 impl Write for PrWriter {
 	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-		with_engine(|engine| engine.pr_writer.borrow_mut().write(buf))
+		print_output(PrintLevel::Print, buf)
 	}
 
 	fn flush(&mut self) -> io::Result<()> {
@@ -180,7 +327,7 @@ pub struct EprWriter;
 
 impl Write for EprWriter {
 	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-		with_engine(|engine| engine.epr_writer.borrow_mut().write(buf))
+		print_output(PrintLevel::Error, buf)
 	}
 
 	fn flush(&mut self) -> io::Result<()> {
@@ -330,16 +477,162 @@ impl<T> !Send for Root<T> { }
 impl !Send for Sym { }
 impl !Send for RFn { }
 
+//the engine's half of RuntimeBuilder. options which belong to the stdlib, like the clock and the
+//filesystem policy, are stored in the RuntimeBuilder itself.
 #[doc(hidden)]
-pub struct EngineBuilder;
+pub struct EngineBuilder {
+	opt_level: u8,
+	retain_param_names: bool,
+	type_checks: bool,
+
+	tail_calls: bool,
+	max_call_depth: u32,
+	stack_budget: Option<usize>,
+	fuel: Option<u64>,
+	gc_ratio: Option<f32>,
+	log_level: PrintLevel,
+	pr_writer: Option<Box<dyn Write>>,
+	epr_writer: Option<Box<dyn Write>>,
+	print_sink: Option<Box<PrintSink>>,
+	loader: Option<Box<Loader>>,
+	file_resolution: FileResolution
+}
 
 impl EngineBuilder {
 	pub fn new() -> EngineBuilder {
-		EngineBuilder
+		EngineBuilder {
+			opt_level: 1,
+			retain_param_names: true,
+			type_checks: false,
+
+			tail_calls: true,
+			max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+			stack_budget: None,
+			fuel: None,
+			gc_ratio: None,
+			log_level: PrintLevel::Print,
+			pr_writer: None,
+			epr_writer: None,
+			print_sink: None,
+			loader: None,
+			file_resolution: FileResolution::Root
+		}
+	}
+
+	pub fn opt_level(self, opt_level: u8) -> EngineBuilder {
+		EngineBuilder { opt_level, ..self }
+	}
+
+	pub fn retain_param_names(self, retain_param_names: bool) -> EngineBuilder {
+		EngineBuilder { retain_param_names, ..self }
+	}
+
+	pub fn type_checks(self, type_checks: bool) -> EngineBuilder {
+		EngineBuilder { type_checks, ..self }
 	}
 
-	pub fn build(self) -> Engine {
-		Engine::new()
+	pub fn tail_calls(self, tail_calls: bool) -> EngineBuilder {
+		EngineBuilder { tail_calls, ..self }
+	}
+
+	pub fn max_call_depth(self, max_call_depth: u32) -> EngineBuilder {
+		EngineBuilder { max_call_depth, ..self }
+	}
+
+	pub fn stack_budget(self, stack_budget: Option<usize>) -> EngineBuilder {
+		EngineBuilder { stack_budget, ..self }
+	}
+
+	pub fn fuel(self, fuel: Option<u64>) -> EngineBuilder {
+		EngineBuilder { fuel, ..self }
+	}
+
+	pub fn gc_ratio(self, gc_ratio: f32) -> EngineBuilder {
+		EngineBuilder { gc_ratio: Some(gc_ratio), ..self }
+	}
+
+	pub fn log_level(self, log_level: PrintLevel) -> EngineBuilder {
+		EngineBuilder { log_level, ..self }
+	}
+
+	pub fn pr_writer(self, pr_writer: Box<dyn Write>) -> EngineBuilder {
+		EngineBuilder { pr_writer: Some(pr_writer), ..self }
+	}
+
+	pub fn epr_writer(self, epr_writer: Box<dyn Write>) -> EngineBuilder {
+		EngineBuilder { epr_writer: Some(epr_writer), ..self }
+	}
+
+	pub fn print_sink(self, print_sink: Box<PrintSink>) -> EngineBuilder {
+		EngineBuilder { print_sink: Some(print_sink), ..self }
+	}
+
+	pub fn loader(self, loader: Box<Loader>) -> EngineBuilder {
+		EngineBuilder { loader: Some(loader), ..self }
+	}
+
+	pub fn file_resolution(self, file_resolution: FileResolution) -> EngineBuilder {
+		EngineBuilder { file_resolution, ..self }
+	}
+
+	//checks for combinations of options which can't be meaningfully used together. we can't
+	//return a GError, because there's no active Runtime yet.
+	pub fn validate(&self) -> Result<(), String> {
+		if self.print_sink.is_some() {
+			if self.pr_writer.is_some() {
+				return Err("the pr_writer and print_sink options are mutually exclusive: \
+				            the pr_writer is never used while a print sink is set".into())
+			}
+			if self.epr_writer.is_some() {
+				return Err("the epr_writer and print_sink options are mutually exclusive: \
+				            the epr_writer is never used while a print sink is set".into())
+			}
+		}
+
+		if self.max_call_depth == 0 {
+			return Err("the max_call_depth option must be at least 1".into())
+		}
+
+		if let Some(ratio) = self.gc_ratio {
+			if !ratio.is_finite() {
+				return Err(format!("the gc_ratio option must be a finite number, not {}", ratio))
+			}
+		}
+
+		Ok(())
+	}
+
+	pub fn build(self) -> Result<Engine, String> {
+		self.validate()?;
+
+		let engine = Engine::new();
+		let storage = &engine.0;
+
+		storage.opt_level.set(self.opt_level);
+		storage.retain_param_names.set(self.retain_param_names);
+		storage.type_checks.set(self.type_checks);
+
+		storage.vm.set_tail_calls(self.tail_calls);
+		storage.vm.set_max_call_depth(self.max_call_depth);
+		storage.vm.set_stack_budget(self.stack_budget);
+		storage.vm.set_fuel(self.fuel);
+		if let Some(ratio) = self.gc_ratio {
+			storage.heap.set_ratio(ratio);
+		}
+
+		storage.log_level.set(self.log_level);
+		if let Some(pr_writer) = self.pr_writer {
+			*storage.pr_writer.borrow_mut() = pr_writer;
+		}
+		if let Some(epr_writer) = self.epr_writer {
+			*storage.epr_writer.borrow_mut() = epr_writer;
+		}
+		*storage.print_sink.borrow_mut() = self.print_sink.map(Rc::from);
+
+		*storage.loader.borrow_mut() = self.loader.map(Rc::from);
+		storage.file_resolution.set(self.file_resolution);
+
+		Ok(engine)
 	}
 }
 
@@ -355,11 +648,36 @@ impl Drop for Engine {
 		//engine.libs_ordering unborrowed while the destructor runs.
 		self.run(|| {
 			with_engine(|engine| {
+				//before that, we run any toplevel (defer) forms which are still pending, in the 
+				//reverse order that their files were loaded. the Runtime is being dropped, so 
+				//there's nowhere to propagate an error to: we just print it.
+				loop {
+					let popped = engine.file_defers.borrow_mut().pop();
+					let (filename, defers) = match popped {
+						Some(popped) => popped,
+						None => break
+					};
+
+					let mut result = Ok(Val::Nil);
+					eval::run_defers(defers, &mut result);
+
+					if let Err(ref error) = result {
+						eprn!("\nunhandled error in a (defer) form from {:?}:\n\n{}", 
+						      filename, error);
+					}
+				}
+
 				while let Some(type_id) = engine.libs_ordering.borrow_mut().pop() {
 					let lib = engine.libs.borrow_mut().remove(&type_id).unwrap();
 					drop(lib);
 				}
 
+				//closures passed to rfn_boxed() can capture arbitrary Rust data, including Roots,
+				//so we drop them next, while the rest of the engine is still intact. we leave
+				//engine.rfns unborrowed while they're dropped.
+				let rfns = std::mem::take(&mut *engine.rfns.borrow_mut());
+				drop(rfns);
+
 				//todo: we should consider free()ing all RData without any ACTIVE_RUNTIME, so
 				//that their destructors can't get up to any mischief like calling add_lib().
 
@@ -367,7 +685,13 @@ impl Drop for Engine {
 				//unsafe-internals flag is disabled, by leaving Rc reference loops intact. as 
 				//such, we first need to clean up anything that holds a Root. (this also helps
 				//us to uphold the invariant that a Root cannot exist when its Heap is dropped.)
+				flush_print_sink();
+				engine.print_sink.borrow_mut().take();
+
 				engine.lazy_storage.borrow_mut().clear();
+				engine.interned.borrow_mut().clear();
+				#[cfg(feature = "watch")] engine.watches.borrow_mut().clear();
+				#[cfg(feature = "compiler")] engine.compile_cache.borrow_mut().clear();
 				engine.syms.borrow_mut().clear();
 				engine.rfns.borrow_mut().clear();
 				engine.vm.clear();
@@ -392,6 +716,9 @@ struct EngineStorage {
 
 	pr_writer: RefCell<Box<dyn Write>>,
 	epr_writer: RefCell<Box<dyn Write>>,
+	print_sink: RefCell<Option<Rc<PrintSink>>>,
+	print_pending: RefCell<(PrintLevel, String)>,
+	log_level: Cell<PrintLevel>,
 
 	syms: RefCell<Vec<SymEntry>>,
 	syms_map: RefCell<HashMap<Rc<str>, Sym>>,
@@ -403,23 +730,49 @@ struct EngineStorage {
 
 	filenames: RefCell<Vec<Rc<str>>>,
 	filenames_map: RefCell<HashMap<Rc<str>, Filename>>,
-	required: RefCell<HashSet<PathBuf>>,
+	required: RefCell<HashSet<String>>,
+	loaded_files: RefCell<Vec<LoadedFile>>,
+	loader: RefCell<Option<Rc<Loader>>>,
+	file_resolution: Cell<FileResolution>,
+	loading: RefCell<Vec<Rc<str>>>,
+	file_defers: RefCell<Vec<(String, Vec<Root<Bytecode>>)>>,
 
 	rfns: RefCell<Vec<RFnEntry>>, 
 	rfns_map: RefCell<HashMap<usize, RFn>>,
 	rclasses: RefCell<HashMap<TypeId, Rc<RClass>>>,
 	rclass_names: RefCell<HashSet<&'static str>>,
+	rec_types: RefCell<HashMap<Sym, Rc<RecType>>>,
+	view_layouts: RefCell<HashMap<TypeId, Rc<dyn Any>>>,
 
 	in_expander: RefCell<Option<(Option<Sym>, Span, Rc<Env>)>>,
 	errors_verbose: Cell<bool>,
+	opt_level: Cell<u8>,
+	retain_param_names: Cell<bool>,
+	type_checks: Cell<bool>,
+	expansion_passes: RefCell<Vec<fn(Val) -> GResult<Val>>>,
+	in_expansion_pass: Cell<bool>,
 
 	libs: RefCell<HashMap<TypeId, Rc<dyn Any>>>,
 	libs_ordering: RefCell<Vec<TypeId>>,
 
 	#[cfg(feature = "compiler")] recording: RefCell<Option<Recording>>,
 	#[cfg(feature = "compiler")] playing_back: RefCell<Option<Recording>>,
+	#[cfg(feature = "compiler")] compile_cache: RefCell<CompileCache>,
+
+	#[cfg(feature = "watch")] watches: RefCell<Vec<Watch>>,
 
 	lazy_storage: RefCell<HashMap<String, Val>>,
+	interned: RefCell<HashMap<String, Root<Str>>>,
+	modules: RefCell<Modules>,
+	reloading: Cell<bool>,
+	let_macro_denied: Cell<bool>,
+	deprecated_callsites: RefCell<HashSet<(Sym, Span)>>,
+	deprecations_hit: RefCell<Vec<Sym>>,
+	api: RefCell<HashMap<Sym, ApiRecord>>,
+	api_library: RefCell<Option<String>>,
+	message_formatter: RefCell<Option<Rc<MessageFormatter>>>,
+	formatting_message: Cell<bool>,
+	poisoned: Cell<bool>,
 
 	known_ops: HashMap<Sym, KnownOp>
 }
@@ -428,1975 +781,6725 @@ struct SymEntry {
 	name: Rc<str>,
 	kind: SymKind,
 	bound_global: Option<GlobalEntry>,
-	bound_macro: Option<Expander>
+	bound_macro: Option<Expander>,
+	meta: Val
 }
 
 struct GlobalEntry {
 	val: Val,
-	frozen: bool
-}
+	frozen: bool,
 
-struct RFnEntry {
-	name: Option<Sym>,
-	wrapped_fn: WrappedFn
+	//true for a global bound by (defconst). constants are always frozen, but unlike other frozen
+	//globals, they can be replaced by glsp::reload or glsp::redefine_const
+	constant: bool,
+
+	//true if this global's value may be stored in a LoadGlobal inline cache. mutating the
+	//global must then invalidate all cached globals. see InlineCache in code.rs
+	cached: bool
 }
 
-impl Engine {
-	pub fn new() -> Engine {
-		//build the initial syms database
-		let syms = Vec::from_iter(STOCK_SYMS.iter().map(|&(name, kind)| {
-			SymEntry {
-				name: name.into(),
-				kind,
-				bound_global: None,
-				bound_macro: None
-			}
-		}));
-		
-		let syms_map = HashMap::from_iter(syms.iter().enumerate().map(|(i, sym_entry)| {
-			(Rc::clone(&sym_entry.name), Sym(i as u32))
-		}));
+/**
+A record of every global and macro binding, produced by
+[`glsp::global_snapshot`](glsp/fn.global_snapshot.html).
 
-		//the initial spans database just contains "Generated", so that it's always Span(0)
-		let spans = vec![SpanStorage::Generated];
-		let mut spans_map = HashMap::new();
-		spans_map.insert(SpanStorage::Generated, Span(0));
+The snapshot is shallow. It records which value each global is bound to, but it doesn't copy
+those values, so it's cheap to produce. Mutating an array which is stored in a global won't be
+undone by [`glsp::restore_globals`](glsp/fn.restore_globals.html).
 
-		//Filenames and RFns store a NonZeroU32 to enable some layout optimizations. therefore,
-		//we need to insert dummy entries for Filename(0) and RFn(0).
-		let rfns = vec![RFnEntry {
-			name: None,
-			wrapped_fn: rfn!(|| panic!())
-		}];
-		let filenames = vec!["".into()];
+When converted to a [`Val`](enum.Val.html), a `GlobalSnapshot` becomes a table with two fields,
+`globals` and `macros`. Each field is a table which maps symbols to their bound values.
+*/
 
-		let engine = Engine(Rc::new(EngineStorage {
-			id: alloc_engine_id().expect("more than 256 simultaneous Runtimes"),
-			heap: Heap::new(),
-			vm: Vm::new(),
+pub struct GlobalSnapshot {
+	pub(crate) engine_id: u8,
 
-			pr_writer: RefCell::new(Box::new(stdout())),
-			epr_writer: RefCell::new(Box::new(stderr())),
+	//sorted by sym. syms which have neither a global nor a macro binding are omitted.
+	pub(crate) bindings: Vec<(Sym, Option<Val>, Option<Expander>)>
+}
 
-			syms: RefCell::new(syms),
-			syms_map: RefCell::new(syms_map),
-			gensym_counter: Cell::new(0),
-			gensym_seed: RefCell::new(None),
+/**
+A record of a `Runtime`'s entire state, produced by
+[`glsp::runtime_snapshot`](glsp/fn.runtime_snapshot.html).
 
-			spans: RefCell::new(spans),
-			spans_map: RefCell::new(spans_map),
+Unlike a [`GlobalSnapshot`](struct.GlobalSnapshot.html), this records the contents of every
+object on the heap, so that [`glsp::restore_runtime`](glsp/fn.restore_runtime.html) can undo
+any mutation. Immutable data, like functions, classes and symbols, is shared with the 
+`Runtime` rather than copied.
 
-			filenames: RefCell::new(filenames),
-			filenames_map: RefCell::new(HashMap::new()),
-			required: RefCell::new(HashSet::new()),
+A `RuntimeSnapshot` keeps every object which existed when it was taken alive. It must be 
+dropped before its `Runtime` is dropped.
+*/
 
-			rfns: RefCell::new(rfns),
-			rfns_map: RefCell::new(HashMap::new()),
-			rclasses: RefCell::new(HashMap::new()),
-			rclass_names: RefCell::new(HashSet::new()),
+pub struct RuntimeSnapshot {
+	engine_id: u8,
+	syms: Vec<SavedSym>,
+	objects: Vec<SavedObject>,
+	required: HashSet<String>,
+	loaded_files: Vec<LoadedFile>,
+	file_defers: Vec<(String, Vec<Root<Bytecode>>)>,
+	modules: Modules,
+	lazy_storage: HashMap<String, Val>
+}
 
-			in_expander: RefCell::new(None),
-			errors_verbose: Cell::new(true),
+struct SavedSym {
+	global: Option<(Val, bool, bool)>, //the value, whether it's frozen, and whether it's constant
+	mac: Option<Expander>,
+	meta: Val
+}
 
-			libs: RefCell::new(HashMap::new()),
-			libs_ordering: RefCell::new(Vec::new()),
+//the gc may free any object which the snapshot doesn't root, so objects without any mutable 
+//state are still rooted, as Vals. bytecode and lambdas are only reachable through a gfn, so they
+//don't need to be rooted separately.
+enum SavedObject {
+	Arr(Root<Arr>, VecDeque<Slot>, bool),
+	Str(Root<Str>, StrStorage, bool),
+	Tab(Root<Tab>, FnvHashMap<Slot, Slot>, bool),
+	Obj(Root<Obj>, ObjContents, bool),
+	Rec(Root<Rec>, Box<[Slot]>, bool),
+	GIter(Root<GIter>, GIterState),
+	Stay(Root<Stay>, Slot),
+	Coro(Root<Coro>, PrivCoroState, CoroStorage),
 
-			#[cfg(feature = "compiler")] recording: RefCell::new(None),
-			#[cfg(feature = "compiler")] playing_back: RefCell::new(None),
+	#[allow(dead_code)]
+	Immutable(Val)
+}
 
-			lazy_storage: RefCell::new(HashMap::new()),
+type Loader = dyn Fn(&str) -> GResult<Cow<'static, str>>;
 
-			known_ops: known_ops()
-		}));
+/**
+The rules for resolving the filenames passed to [`(load)`](https://gamelisp.rs/std/load),
+[`(require)`](https://gamelisp.rs/std/require) and [`(include)`](https://gamelisp.rs/std/include).
 
-		engine
-	}
+Set using [`glsp::set_file_resolution`](glsp/fn.set_file_resolution.html).
+*/
 
-	//once we have specialisation, we could permit the closure to return any value, and only
-	//have the error-reporting behaviour when the return type is GResult<_>.
-	pub fn run<F, R>(&self, f: F) -> Option<R> 
-	where
-		F: FnOnce() -> GResult<R>,
-		F: GSend,
-		R: GSend
-	{
-		let old_active_engine = ACTIVE_ENGINE.with(|ref_cell| {
-			ref_cell.replace(Some(self.0.clone()))
-		});
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum FileResolution {
+	/**
+	Every filename is passed to the [loader](glsp/fn.set_loader.html) exactly as it was written.
+	For the default loader, this means that relative filenames are relative to the process' 
+	working directory. This is the default.
+	*/
+	Root,
 
-		let old_engine_id = ACTIVE_ENGINE_ID.with(|cell| {
-			cell.replace(Some(self.0.id))
-		});
+	/**
+	A relative filename which appears in a file that is being loaded is joined to that file's
+	directory, and any `.` or `..` components are then removed. For example, `(load "../b.glsp")`
+	in `scripts/ai/a.glsp` would load `scripts/b.glsp`.
 
-		let _guard = Guard::new(|| {
-			ACTIVE_ENGINE.with(|ref_cell| {
-				ref_cell.replace(old_active_engine);
-			});
-			ACTIVE_ENGINE_ID.with(|cell| {
-				cell.set(old_engine_id);
-			});
-		});
+	Absolute filenames, and filenames which are loaded while no other file is being loaded, are
+	passed to the loader as-is.
+	*/
+	RelativeToFile
+}
 
-		let result = f();
+/**
+The result of reloading a file using [`glsp::reload`](glsp/fn.reload.html).
 
-		if let Err(ref error) = result {
-			if error.stack_trace().is_some() {
-				eprn!("\nunhandled error in run() call:\n\n{}", error);
-			} else {
-				eprn!("\nunhandled error in run() call: {}", error);
-			}
-		}
+When converted to a [`Val`](enum.Val.html), a `ReloadReport` becomes a table with three
+fields: `result`, `stale-objs` and `migration-errors`. Each migration error is represented by
+its [payload](struct.GError.html#method.val).
+*/
 
-		result.ok()
-	}
+pub struct ReloadReport {
+	result: Val,
+	stale_objs: Vec<Root<Obj>>,
+	migration_errors: Vec<GError>
 }
 
+impl ReloadReport {
+	/** Returns the result of evaluating the file's last toplevel form. */
+	pub fn result(&self) -> &Val {
+		&self.result
+	}
 
-//-------------------------------------------------------------------------------------------------
-// Guard
-//-------------------------------------------------------------------------------------------------
-
-//because rfns are allowed to panic, and because various glsp functions can early-exit when an
-//error is generated, we need to be careful about unwind-safety. we mostly achieve this using the
-//Guard struct, which runs an arbitrary closure when it's dropped.
-pub(crate) struct Guard<F: FnOnce()>(Option<F>);
+	/**
+	Returns every object which couldn't be migrated to its redefined class.
 
-impl<F: FnOnce()> Guard<F> {
-	pub(crate) fn new(f: F) -> Guard<F> {
-		Guard(Some(f))
+	These objects are still instances of the old class, and they're flagged as 
+	[stale](struct.Obj.html#method.is_stale). It's up to the caller to decide whether to 
+	replace them, or to kill them.
+	*/
+	pub fn stale_objs(&self) -> &[Root<Obj>] {
+		&self.stale_objs
 	}
-}
 
-impl<F: FnOnce()> Drop for Guard<F> {
-	fn drop(&mut self) {
-		(self.0.take().unwrap())()
+	/**
+	Returns the error which prevented each of the [`stale_objs`](#method.stale_objs) from
+	being migrated, in the same order.
+	*/
+	pub fn migration_errors(&self) -> &[GError] {
+		&self.migration_errors
 	}
 }
 
-
-//-------------------------------------------------------------------------------------------------
-// Sym, ToSym, RFn, Filename
-//-------------------------------------------------------------------------------------------------
-
 /**
-The `sym` primitive type.
+The budgets for a single call to [`glsp::call_limited`](glsp/fn.call_limited.html).
 
-Symbols are represented by a small `Copy` type (a 32-bit integer id).
+Each field defaults to `None`, which doesn't impose any limit.
 
-To convert a string into a symbol, you should usually call [`glsp::sym`](fn.sym.html).
+	let limits = Limits {
+		fuel: Some(100_000),
+		alloc_bytes: Some(64 * 1024),
+		deadline: Some(Instant::now() + Duration::from_micros(500))
+	};
 */
 
-#[derive(PartialEq, Eq, Hash, Copy, Clone)]
-pub struct Sym(pub(crate) u32);
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Limits {
+	/**
+	The amount of fuel which the call may consume. See [`glsp::set_fuel`](glsp/fn.set_fuel.html).
+	*/
+	pub fuel: Option<u64>,
 
-const MAX_SYM: u32 = 0xffffff;
-
-impl Sym {
 	/**
-	Returns the name of the symbol.
+	The number of bytes which the call may allocate on the garbage-collected heap.
 
-	For gensyms, the name is the same as its printed representation, e.g. `#<gs:tag:8>`.
+	Each object's size is measured when it's allocated, so growing an existing collection
+	doesn't count towards this limit.
 	*/
-	pub fn name(&self) -> Rc<str> {
-		with_engine(|engine| {
-			Rc::clone(&engine.syms.borrow()[self.0 as usize].name)
-		})
-	}
+	pub alloc_bytes: Option<usize>,
 
 	/**
-	Returns `true` if this symbol is a gensym.
+	The time by which the call must return. See 
+	[`glsp::set_deadline`](glsp/fn.set_deadline.html).
 
-	Gensyms are constructed using [`glsp::gensym`](fn.gensym.html) or
-	[`glsp::gensym_with_tag`](fn.gensym_with_tag.html).
+	This field is not available on the `wasm32` target.
 	*/
-	pub fn is_gensym(&self) -> bool {
-		self.kind() == SymKind::Gensym
-	}
+	#[cfg(not(target_arch = "wasm32"))]
+	pub deadline: Option<Instant>
+}
 
-	//only public so that it can be used internally by the backquote!() macro
-	#[doc(hidden)]
-	pub fn kind(&self) -> SymKind {
-		with_engine(|engine| {
-			engine.syms.borrow()[self.0 as usize].kind
-		})
-	}
+/**
+The value returned by a [safepoint callback](fn.set_safepoint_callback.html).
+*/
 
-	//used internally by the encoder and by (bind-global!). we forbid special forms' names from 
-	//being rebound to global or local variables, but not from being bound to a macro. a (let) 
-	//macro has its uses, but a shadowing local variable named something like `defer` would break 
-	//too many macros to be useful.
-	pub(crate) fn is_bindable(&self) -> bool {
-		match self.kind() {
-			SymKind::StockSpecial => false,
-			SymKind::Normal | SymKind::StockKeyword | 
-			SymKind::StockTransform | SymKind::Gensym => true
-		}
-	}
+#[derive(Debug)]
+pub enum SafepointAction {
+	///Continue executing the script.
+	Continue,
 
-	//only public so that it can be used internally by the backquote!() macro
-	#[doc(hidden)]
-	pub fn from_u32(id: u32) -> Sym {
-		assert!(id <= MAX_SYM);
-		Sym(id)
-	}
+	/**
+	Fail with the given error, as though it had been raised by the code which is currently 
+	executing. Scripts can catch the error using [`try`](https://gamelisp.rs/std/try).
+	*/
+	RaiseError(GError),
 
-	//only public so that it can be used internally by the backquote!() macro
-	#[doc(hidden)]
-	pub fn to_u32(&self) -> u32 {
-		self.0
-	}
+	/**
+	Suspend the running coroutine, as though it had evaluated `(yield #n)`. The next call to
+	[`coro-run`](https://gamelisp.rs/std/coro-run) resumes it, and discards its argument.
+
+	This action is ignored unless the safepoint was reached in the body of a running 
+	coroutine's own function. It's never honoured within a nested function call, or within a 
+	[`defer`](https://gamelisp.rs/std/defer) form.
+	*/
+	Yield
 }
 
 /**
-A type which can be converted to a [`Sym`](struct.Sym.html).
+A handle to a `Runtime` which can be sent to other threads, produced by 
+[`Runtime::handle`](struct.Runtime.html#method.handle) or 
+[`glsp::runtime_handle`](glsp/fn.runtime_handle.html).
+
+A watchdog thread can pass the handle to 
+[`glsp::current_execution_point`](glsp/fn.current_execution_point.html) to find out which
+code the `Runtime` is running. The handle doesn't keep its `Runtime` alive; once the `Runtime` 
+has been dropped, `current_execution_point` returns `None`.
+*/
 
-This is mostly used to make APIs more ergonomic. For example, the argument to 
-[`glsp::global`](fn.global.html) is a generic `S: ToSym`, which means that it can 
-receive either a symbol or a string:
-	
-	glsp::global(my_lib.my_sym)?;
-	glsp::global("sym-name")?;
+#[derive(Clone, Debug)]
+pub struct RuntimeHandle(Weak<ExecutionSlot>);
+
+/**
+The code which a `Runtime` is currently executing, produced by
+[`glsp::current_execution_point`](glsp/fn.current_execution_point.html).
+
+Its `Display` implementation produces a brief description, such as 
+`ai/pathing.glsp:211 in fn flood-fill`. Toplevel code and anonymous functions don't have a 
+name, so they're just described as `ai/pathing.glsp:211`.
 */
 
-pub trait ToSym {
-	fn to_sym(&self) -> GResult<Sym>;
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExecutionPoint {
+	location: Option<(String, usize)>,
+	fn_name: Option<String>
 }
 
-impl<'a, T> ToSym for T 
-where
-	T: Deref,
-	T::Target: ToSym
-{
-	fn to_sym(&self) -> GResult<Sym> {
-		(**self).to_sym()
+impl ExecutionPoint {
+	/**
+	Returns the name of the file which is being executed.
+
+	For code which was expanded from a macro, this is the file which contains the outermost 
+	macro invocation. Returns `None` for code which wasn't loaded from a file, such as code 
+	passed to [`glsp::eval`](glsp/fn.eval.html).
+	*/
+	pub fn filename(&self) -> Option<&str> {
+		self.location.as_ref().map(|(filename, _)| &filename[..])
 	}
-}
 
-impl ToSym for Sym {
-	fn to_sym(&self) -> GResult<Sym> {
-		Ok(*self)
+	/** Returns the 1-indexed line number which is being executed, when it's known. */
+	pub fn line(&self) -> Option<usize> {
+		self.location.as_ref().map(|&(_, line)| line)
 	}
-}
 
-impl<'a> ToSym for str {
-	fn to_sym(&self) -> GResult<Sym> {
-		glsp::sym(self)
+	/** Returns the name of the innermost GameLisp function, or `None` if it's anonymous. */
+	pub fn fn_name(&self) -> Option<&str> {
+		self.fn_name.as_ref().map(|name| &name[..])
 	}
 }
 
-impl<'a> ToSym for Str {
-	fn to_sym(&self) -> GResult<Sym> {
-		glsp::sym(&self.to_string())
+impl Display for ExecutionPoint {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		match self.location {
+			Some((ref filename, line)) => write!(f, "{}:{}", filename, line)?,
+			None => write!(f, "generated code")?
+		}
+
+		match self.fn_name {
+			Some(ref fn_name) => write!(f, " in fn {}", fn_name),
+			None => Ok(())
+		}
 	}
 }
 
-#[doc(hidden)]
-#[derive(Clone, Copy, PartialEq)]
-pub enum SymKind {
-	Normal,
-	StockSpecial,
-	StockKeyword,
-	StockTransform,
-	Gensym
+//the vm publishes its position into an ExecutionSlot on entry to each gfn call, and on each jump.
+//the position is packed into a single AtomicU64, with the Span in the upper 32 bits and the 
+//innermost gfn's name in the lower 32 bits. the engine keeps a thread-safe copy of everything
+//which is needed to describe that position, so that it can be read from any thread without
+//interrupting the vm.
+pub(crate) struct ExecutionSlot {
+	point: AtomicU64,
+	table: Mutex<ExecutionTable>
 }
 
-/**
-The `rfn` primitive type.
+//indexed by Span and Filename respectively. gfn names are recorded when their Lambda is created.
+struct ExecutionTable {
+	spans: Vec<Option<(u32, usize)>>,
+	filenames: Vec<Arc<str>>,
+	fn_names: FnvHashMap<u32, Arc<str>>
+}
 
-Rust functions are represented by a small `Copy` type (a 32-bit integer id).
+//IDLE is stored while the vm isn't executing any bytecode. a span of u32::MAX and a name of
+//u32::MAX can't coexist in practice, because we'd run out of memory first.
+const IDLE: u64 = u64::MAX;
+const NO_NAME: u32 = u32::MAX;
+
+impl ExecutionSlot {
+	pub(crate) fn new() -> ExecutionSlot {
+		ExecutionSlot {
+			point: AtomicU64::new(IDLE),
+			table: Mutex::new(ExecutionTable {
+				spans: vec![None],
+				filenames: vec!["".into()],
+				fn_names: FnvHashMap::default()
+			})
+		}
+	}
 
-Most of this type's methods belong to the `callable` abstract type, so they can be found in
-the [`CallableOps`](trait.CallableOps.html) trait. To invoke an `RFn`, use
-[`glsp::call`](fn.call.html).
+	#[inline(always)]
+	pub(crate) fn publish(&self, span: Span, name: Option<Sym>) {
+		let name = name.map(|sym| sym.0).unwrap_or(NO_NAME);
+		self.point.store(((span.0 as u64) << 32) | (name as u64), Release);
+	}
 
-To convert a function pointer or a closure into an `RFn`, you should usually call 
-[`glsp::bind_rfn`](fn.bind_rfn.html) or [`glsp::rfn`](fn.rfn.html).
-*/
+	#[inline(always)]
+	pub(crate) fn save(&self) -> u64 {
+		self.point.load(Acquire)
+	}
 
-#[derive(PartialEq, Eq, Hash, Copy, Clone)]
-pub struct RFn(NonZeroU32);
+	#[inline(always)]
+	pub(crate) fn restore(&self, saved: u64) {
+		self.point.store(saved, Release);
+	}
 
-impl RFn {
-	pub(crate) fn set_name(&self, new_name: Option<Sym>) {
-		with_engine(|engine| {
-			engine.rfns.borrow_mut()[self.0.get() as usize].name = new_name;
-		})
+	fn add_span(&self, location: Option<(u32, usize)>) {
+		self.table.lock().unwrap().spans.push(location);
 	}
-}
 
-impl CallableOps for RFn {
-	fn receive_call(&self, arg_count: usize) -> GResult<Val> {
-		glsp::call_rfn(*self, arg_count).map(|slot| slot.root())
+	fn add_filename(&self, filename: &str) {
+		self.table.lock().unwrap().filenames.push(filename.into());
 	}
 
-	fn name(&self) -> Option<Sym> {
-		with_engine(|engine| {
-			engine.rfns.borrow()[self.0.get() as usize].name
-		})
+	fn add_fn_name(&self, sym: Sym, name: &str) {
+		let mut table = self.table.lock().unwrap();
+		table.fn_names.entry(sym.0).or_insert_with(|| name.into());
 	}
 
-	fn arg_limits(&self) -> (usize, Option<usize>) {
-		with_engine(|engine| {
-			engine.rfns.borrow()[self.0.get() as usize].wrapped_fn.arg_limits
+	fn resolve(&self) -> Option<ExecutionPoint> {
+		let point = self.point.load(Acquire);
+		if point == IDLE {
+			return None
+		}
+
+		let (span, name) = ((point >> 32) as usize, point as u32);
+		let table = self.table.lock().unwrap();
+
+		let location = table.spans.get(span).copied().flatten().map(|(filename, line)| {
+			(table.filenames[filename as usize].to_string(), line)
+		});
+
+		Some(ExecutionPoint {
+			location,
+			fn_name: table.fn_names.get(&name).map(|name| name.to_string())
 		})
 	}
 }
 
-#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
-pub struct Filename(NonZeroU32);
-
 /**
-Define a struct which contains a collection of symbols.
+A file which has been loaded by the active `Runtime`, produced by
+[`glsp::loaded_files`](glsp/fn.loaded_files.html).
+*/
 
-Caching symbols in a struct has better performance than calling [`glsp::sym`](fn.sym.html), and
-it's more convenient than storing `Sym` fields in your own structs directly.
+#[derive(Clone, Debug)]
+pub struct LoadedFile {
+	filename: String,
+	dependencies: Vec<String>
+}
 
-The struct defines a method `fn new() -> GResult<Self>` which initializes each field by passing
-the given string literal to [`glsp::sym`](fn.sym.html).
+impl LoadedFile {
+	/** Returns the file's resolved filename. */
+	pub fn filename(&self) -> &str {
+		&self.filename
+	}
 
-	syms! {
-		#[derive(Clone)]
-		pub struct Syms {
-			pub width: "width",
-			pub set_width: "width=",
-			rectp: "rect?"
-		}
+	/**
+	Returns the resolved filename of each file which this file passed to `load` or `require`,
+	in the order that they were first loaded or required.
+
+	This includes files which were required, but not loaded, because they had already been 
+	loaded by some other file.
+	*/
+	pub fn dependencies(&self) -> &[String] {
+		&self.dependencies
 	}
+}
+
+/**
+A GameLisp source file which has been embedded into the executable, usually by the
+[`include_glsp!`](macro.include_glsp.html) macro.
 */
 
-#[macro_export]
-macro_rules! syms {
-	(
-		$(#[$struct_attr:meta])*
-		$struct_vis:vis struct $struct_name:ident {
-			$($vis:vis $name:ident: $contents:literal),+
-		}
-	) => (
-		$crate::syms! {
-			$(#[$struct_attr])*
-			$struct_vis struct $struct_name {
-				$($vis $name: $contents,)+
-			}
-		}
-	);
+#[derive(Copy, Clone, Debug)]
+pub struct IncludedFile {
+	filename: &'static str,
+	text: &'static str
+}
 
-	(
-		$(#[$struct_attr:meta])*
-		$struct_vis:vis struct $struct_name:ident {
-			$($vis:vis $name:ident: $contents:literal,)*
-		}
-	) => (
-		$struct_vis struct $struct_name {
-			$($vis $name: Sym,)*
-		}
+impl IncludedFile {
+	pub const fn new(filename: &'static str, text: &'static str) -> IncludedFile {
+		IncludedFile { filename, text }
+	}
 
-		impl $struct_name {
-			#[allow(unused_variables)]
-			$struct_vis fn new() -> GResult<Syms> {
-				Ok($struct_name {
-					$($name: glsp::sym($contents)?,)*
-				})
-			}
-		}
-	);
-}
+	/** Returns the filename which was passed to `include_glsp!`, relative to the crate root. */
+	pub fn filename(&self) -> &'static str {
+		self.filename
+	}
 
+	/** Returns the file's source text. */
+	pub fn text(&self) -> &'static str {
+		self.text
+	}
 
-//-------------------------------------------------------------------------------------------------
-// Lib, RData, RRoot
-//-------------------------------------------------------------------------------------------------
+	/**
+	Loads the file, as though it were loaded by [`glsp::load`](glsp/fn.load.html) from the 
+	filesystem.
+
+	Equivalent to [`glsp::load_str(file.text(), file.filename())`](glsp/fn.load_str.html).
+	*/
+	pub fn load(&self) -> GResult<Val> {
+		glsp::load_str(self.text, self.filename)
+	}
+}
 
 /**
-A type which can be passed to [`glsp::add_lib`](fn.add_lib.html).
+A set of GameLisp source files which have been embedded into the executable, usually by the
+[`include_glsp_dir!`](macro.include_glsp_dir.html) macro.
 
-It's possible to implement this trait manually, but using the [`lib!` macro](macro.lib.html) is
-strongly encouraged. Among other things, that macro will automatically implement 
-[`MakeArg`](trait.MakeArg.html) for the library type.
+An `IncludedDir` can be used as a [loader](glsp/fn.set_loader.html), so that 
+[`(load)`](https://gamelisp.rs/std/load), [`(require)`](https://gamelisp.rs/std/require) and
+[`(include)`](https://gamelisp.rs/std/include) read the embedded files rather than the 
+filesystem.
+
+	static SCRIPTS: IncludedDir = include_glsp_dir!("scripts/");
+
+	glsp::set_loader(SCRIPTS.loader());
+	glsp::require("scripts/main.glsp")?;
 */
 
-pub trait Lib: 'static + Sized {
-	fn type_name() -> &'static str;
+#[derive(Copy, Clone, Debug)]
+pub struct IncludedDir {
+	files: &'static [IncludedFile]
+}
 
-	/**
-	Returns a shared reference to the instance of this type owned by the active
-	[`Runtime`](struct.Runtime.html).
+impl IncludedDir {
+	pub const fn new(files: &'static [IncludedFile]) -> IncludedDir {
+		IncludedDir { files }
+	}
 
-	Panics if a library of this type has not been registered with the active `Runtime`, or if 
-	the library is currently mutably borrowed.
-	*/
-	fn borrow() -> LibRef<Self> {
-		glsp::lib::<Self>()
+	/** Returns each of the embedded files, in load order. */
+	pub fn files(&self) -> &'static [IncludedFile] {
+		self.files
 	}
 
 	/**
-	Returns a mutable reference to the instance of this type owned by the active
-	[`Runtime`](struct.Runtime.html).
+	Looks up an embedded file by its filename.
 
-	Panics if a library of this type has not been registered with the active `Runtime`, or if 
-	the library is currently borrowed.
+	Filenames are compared after removing any `.` and `..` components, so `"scripts/a.glsp"` 
+	and `"./scripts/ai/../a.glsp"` both refer to the same file.
 	*/
-	fn borrow_mut() -> LibRefMut<Self> {
-		glsp::lib_mut::<Self>()
+	pub fn get(&self, filename: &str) -> Option<&'static IncludedFile> {
+		let normalized = normalize_filename(filename);
+		self.files.iter().find(|file| normalize_filename(file.filename) == normalized)
 	}
-	
-	/**
-	Returns a shared reference to the instance of this type owned by the active
-	[`Runtime`](struct.Runtime.html).
 
-	Returns an `Err` if a library of this type has not been registered with the active `Runtime`, 
-	or if the library is currently mutably borrowed.
+	/**
+	Loads each of the embedded files in order, using [`IncludedFile::load`](#method.load).
+	Returns the result of loading the last file, or `#n` if the set is empty.
 	*/
-	fn try_borrow() -> GResult<LibRef<Self>> {
-		glsp::try_lib::<Self>()
+	pub fn load(&self) -> GResult<Val> {
+		let mut result = Val::Nil;
+		for file in self.files {
+			result = file.load()?;
+		}
+
+		Ok(result)
 	}
 
 	/**
-	Returns a mutable reference to the instance of this type owned by the active
-	[`Runtime`](struct.Runtime.html).
+	Returns a function which can be passed to [`glsp::set_loader`](glsp/fn.set_loader.html).
 
-	Returns an `Err` if a library of this type has not been registered with the active `Runtime`, 
-	or if the library is currently borrowed.
+	The function fails when it's passed a filename which isn't part of the set. To fall back to
+	some other source, call [`get`](#method.get) from your own loader instead.
 	*/
-	fn try_borrow_mut() -> GResult<LibRefMut<Self>> {
-		glsp::try_lib_mut::<Self>()
+	pub fn loader(&self) -> impl Fn(&str) -> GResult<Cow<'static, str>> + 'static {
+		let dir = *self;
+		move |filename| {
+			match dir.get(filename) {
+				Some(file) => Ok(Cow::Borrowed(file.text)),
+				None => bail!("{} is not one of the embedded files", filename)
+			}
+		}
 	}
 }
 
-/**
-A reference to a [library](trait.Lib.html).
+//a file registered using glsp::watch
+#[cfg(feature = "watch")]
+struct Watch {
+	path: String,
+	modified: Option<SystemTime>,
+	callback: Box<dyn FnMut(&str) -> GResult<()>>
+}
 
-Created using [`Lib::borrow`](trait.Lib.html#method.borrow) or
-[`Lib::try_borrow`](trait.Lib.html#method.try_borrow).
-*/
-pub struct LibRef<T: Lib> {
-	handle: OwningHandle<Rc<RefCell<T>>, Ref<'static, T>>
-}
+//modules are implemented by name-mangling. a global `x` which is defined within the module
+//`combat` is just the global `combat:x`. the compiler resolves unqualified global names relative
+//to the current module and its imports: see glsp::resolve_global.
+#[derive(Clone, Default)]
+struct Modules {
+	current: Option<Sym>,
+	known: HashSet<Sym>,
 
-impl<T: Lib> Deref for LibRef<T> {
-	type Target = T;
-	fn deref(&self) -> &T {
-		&*self.handle
-	}
+	//the imports for each module. the root module's imports are stored under the key `None`.
+	imports: HashMap<Option<Sym>, Vec<Import>>
 }
 
-/**
-A mutable reference to a [library](trait.Lib.html).
+#[derive(Clone, PartialEq)]
+struct Import {
+	module: Sym,
 
-Created using [`Lib::borrow_mut`](trait.Lib.html#method.borrow_mut) or
-[`Lib::try_borrow_mut`](trait.Lib.html#method.try_borrow_mut).
-*/
-pub struct LibRefMut<T: Lib> {
-	handle: OwningHandle<Rc<RefCell<T>>, RefMut<'static, T>>
+	//None for a wildcard import
+	only: Option<Vec<Sym>>
 }
 
-impl<T: Lib> Deref for LibRefMut<T> {
-	type Target = T;
-	fn deref(&self) -> &T {
-		&*self.handle
-	}
-}
+//splits a qualified name like `combat:resolve-hit` into its module name and its local name. names
+//with more than one colon, or an empty module name or local name, aren't qualified.
+pub(crate) fn split_qualified(name: &str) -> Option<(&str, &str)> {
+	let colon = name.find(':')?;
+	let (module, local) = (&name[..colon], &name[colon + 1..]);
 
-impl<T: Lib> DerefMut for LibRefMut<T> {
-	fn deref_mut(&mut self) -> &mut T {
-		&mut *self.handle
+	if module.len() > 0 && local.len() > 0 && !local.contains(':') {
+		Some((module, local))
+	} else {
+		None
 	}
 }
 
-trait RAllocate: GStore + 'static {
-	fn type_name(&self) -> &'static str;
-	fn size_of(&self) -> usize;
-	fn as_any(&self) -> &dyn Any;
-	fn as_rc_any(self: Rc<Self>) -> Rc<dyn Any>;
+struct RFnEntry {
+	name: Option<Sym>,
+	callee: RFnCallee
 }
 
-impl<T: RStore> RAllocate for RefCell<T> {
-	fn type_name(&self) -> &'static str {
-		T::type_name()
-	}
+#[derive(Clone)]
+enum RFnCallee {
+	Wrapped(WrappedFn),
+	Boxed(BoxedFn)
+}
 
-	fn size_of(&self) -> usize {
-		T::size_of()
+impl RFnCallee {
+	fn arg_limits(&self) -> (usize, Option<usize>) {
+		match *self {
+			RFnCallee::Wrapped(ref wrapped_fn) => wrapped_fn.arg_limits,
+			RFnCallee::Boxed(ref boxed_fn) => boxed_fn.arg_limits
+		}
 	}
 
-	fn as_any(&self) -> &dyn Any {
-		self
+	fn param_types(&self) -> Option<Vec<&'static str>> {
+		match *self {
+			RFnCallee::Wrapped(ref wrapped_fn) => wrapped_fn.param_types.map(|f| f()),
+			RFnCallee::Boxed(ref boxed_fn) => boxed_fn.param_types.map(|f| f())
+		}
 	}
 
-	fn as_rc_any(self: Rc<Self>) -> Rc<dyn Any> {
-		self
+	fn param_names(&self) -> Option<&'static [&'static str]> {
+		match *self {
+			RFnCallee::Wrapped(_) => None,
+			RFnCallee::Boxed(ref boxed_fn) => boxed_fn.param_names
+		}
 	}
 }
 
-/**
-A type which can be moved onto the garbage-collected heap as an [`RData`](struct.RData.html).
-
-It's possible to implement this trait manually, but using the [`rdata!` macro](macro.rdata.html) 
-is strongly encouraged. Among other things, that macro will automatically implement 
-[`MakeArg`](trait.MakeArg.html) and [`IntoResult`](trait.IntoResult.html) for your type.
-*/
+//an entry in the api manifest. the entry is only reported while `rfn` is still bound to its name.
+struct ApiRecord {
+	rfn: RFn,
+	entry: ApiEntry
+}
 
-pub trait RStore: GStore + 'static {
-	fn type_name() -> &'static str;
+impl Engine {
+	pub fn new() -> Engine {
+		//build the initial syms database
+		let syms = Vec::from_iter(STOCK_SYMS.iter().map(|&(name, kind)| {
+			SymEntry {
+				name: name.into(),
+				kind,
+				bound_global: None,
+				bound_macro: None,
+				meta: Val::Nil
+			}
+		}));
+		
+		let syms_map = HashMap::from_iter(syms.iter().enumerate().map(|(i, sym_entry)| {
+			(Rc::clone(&sym_entry.name), Sym(i as u32))
+		}));
 
-	//ideally we would provide a `fn owned_memory_usage(&self)` for this trait, but it's not clear
-	//what the syntax would be for implementing it, or how we would document it. for now we just
-	//report the size of the stored struct itself.
-	fn size_of() -> usize;
+		//the initial spans database just contains "Generated", so that it's always Span(0)
+		let spans = vec![SpanStorage::Generated];
+		let mut spans_map = HashMap::new();
+		spans_map.insert(SpanStorage::Generated, Span(0));
 
-	fn rclass() -> GResult<RClass>;
-}
+		//Filenames and RFns store a NonZeroU32 to enable some layout optimizations. therefore,
+		//we need to insert dummy entries for Filename(0) and RFn(0).
+		let rfns = vec![RFnEntry {
+			name: None,
+			callee: RFnCallee::Wrapped(rfn!(|| panic!()))
+		}];
+		let filenames = vec!["".into()];
 
-/**
-An implementation detail of the [`RStore` trait](trait.RStore.html) and the
-[`rdata!` macro](macro.rdata.html).
-*/
+		let engine = Engine(Rc::new(EngineStorage {
+			id: alloc_engine_id().expect("more than 256 simultaneous Runtimes"),
+			heap: Heap::new(),
+			vm: Vm::new(),
 
-pub struct RClass {
-	name: Sym,
-	bindings: FnvHashMap<Sym, RBinding>
-}
+			pr_writer: RefCell::new(Box::new(stdout())),
+			epr_writer: RefCell::new(Box::new(stderr())),
+			print_sink: RefCell::new(None),
+			print_pending: RefCell::new((PrintLevel::Print, String::new())),
+			log_level: Cell::new(PrintLevel::Print),
 
-enum RBinding {
-	Meth(RFn),
-	Prop(Option<RFn>, Option<RFn>)
-}
+			syms: RefCell::new(syms),
+			syms_map: RefCell::new(syms_map),
+			gensym_counter: Cell::new(0),
+			gensym_seed: RefCell::new(None),
 
-impl RClass {
-	pub fn from_vec(
-		class_name: &'static str,
-		raw_bindings: Vec<(&'static str, &'static str, WrappedFn)>
-	) -> GResult<RClass> {
-		let class_name = glsp::sym(class_name)?;
+			spans: RefCell::new(spans),
+			spans_map: RefCell::new(spans_map),
 
-		let mut bindings = FnvHashMap::with_capacity_and_hasher(
-			raw_bindings.len(),
-			Default::default()
-		);
+			filenames: RefCell::new(filenames),
+			filenames_map: RefCell::new(HashMap::new()),
+			required: RefCell::new(HashSet::new()),
+			loaded_files: RefCell::new(Vec::new()),
+			loader: RefCell::new(None),
+			file_resolution: Cell::new(FileResolution::Root),
+			loading: RefCell::new(Vec::new()),
+			file_defers: RefCell::new(Vec::new()),
 
-		for (kind, key, wrapped_fn) in raw_bindings {
-			let name = glsp::sym(key)?;
-			let rfn = glsp::rfn(wrapped_fn);
-			if rfn.name().is_none() {
-				rfn.set_name(Some(name));
-			}
-
-			match (kind, bindings.entry(name)) {
-				("", Vacant(entry)) => { entry.insert(RBinding::Meth(rfn)); }
-				("get", Vacant(entry)) => { entry.insert(RBinding::Prop(Some(rfn), None)); }
-				("set", Vacant(entry)) => { entry.insert(RBinding::Prop(None, Some(rfn))); }
-				("", Occupied(_)) => bail!("duplicate meth name {}", name),
-				("get", Occupied(mut entry)) => {
-					match entry.get_mut() {
-						RBinding::Meth(_) => bail!("{} is bound to both a meth and a prop", name),
-						RBinding::Prop(Some(_), _) => bail!("duplicate getter {}", name),
-						RBinding::Prop(ref mut none, _) => *none = Some(rfn)
-					}
-				}
-				("set", Occupied(mut entry)) => {
-					match entry.get_mut() {
-						RBinding::Meth(_) => bail!("{} is bound to both a meth and a prop", name),
-						RBinding::Prop(_, Some(_)) => bail!("duplicate setter {}", name),
-						RBinding::Prop(_, ref mut none) => *none = Some(rfn)
-					}
-				}
-				(kind, _) => bail!("{} is not a valid tag for an RData meth", kind)
-			}
-		}
+			rfns: RefCell::new(rfns),
+			rfns_map: RefCell::new(HashMap::new()),
+			rclasses: RefCell::new(HashMap::new()),
+			rclass_names: RefCell::new(HashSet::new()),
+			rec_types: RefCell::new(HashMap::new()),
+			view_layouts: RefCell::new(HashMap::new()),
 
-		Ok(RClass {
-			name: class_name,
-			bindings
-		})
-	}
-}
+			in_expander: RefCell::new(None),
+			errors_verbose: Cell::new(true),
+			opt_level: Cell::new(1),
+			retain_param_names: Cell::new(true),
+			type_checks: Cell::new(false),
+			expansion_passes: RefCell::new(Vec::new()),
+			in_expansion_pass: Cell::new(false),
 
-/**
-The `rdata` primitive type.
+			libs: RefCell::new(HashMap::new()),
+			libs_ordering: RefCell::new(Vec::new()),
 
-This is a Rust value which has been moved onto the garbage-collected heap. It can be constructed
-using the [`glsp::rdata`](fn.rdata.html) function.
+			#[cfg(feature = "compiler")] recording: RefCell::new(None),
+			#[cfg(feature = "compiler")] playing_back: RefCell::new(None),
+			#[cfg(feature = "compiler")] compile_cache: RefCell::new(CompileCache::new()),
 
-`RData` has several convenience features:
+			#[cfg(feature = "watch")] watches: RefCell::new(Vec::new()),
 
-- It supports interior mutability, like a 
-  [`RefCell`](https://doc.rust-lang.org/std/cell/struct.RefCell.html).
+			lazy_storage: RefCell::new(HashMap::new()),
+			interned: RefCell::new(HashMap::new()),
+			modules: RefCell::new(Modules::default()),
+			reloading: Cell::new(false),
+			let_macro_denied: Cell::new(false),
+			deprecated_callsites: RefCell::new(HashSet::new()),
+			deprecations_hit: RefCell::new(Vec::new()),
+			api: RefCell::new(HashMap::new()),
+			api_library: RefCell::new(None),
+			message_formatter: RefCell::new(None),
+			formatting_message: Cell::new(false),
+			poisoned: Cell::new(false),
 
-- It can be manually deallocated, or taken back from the garbage-collected heap, using
-  [`free`](#method.free) and [`take`](#method.take). Any attempts to access a deallocated `RData`
-  from GameLisp will trigger an error.
+			known_ops: known_ops()
+		}));
 
-- It's dynamically typed: a `Vec<Root<RData>>` could refer to several different Rust types.
-  (If this is undesirable, consider using [`RRoot`](struct.RRoot.html) instead.)
+		engine
+	}
 
-- The [`rdata!` macro](macro.rdata.html) enables Rust functions to be associated with an `RData` 
-  as its methods and properties. They can be accessed from GameLisp code, or accessed from Rust 
-  code using an API similar to [`Obj`](struct.Obj.html).
-*/
+	//once we have specialisation, we could permit the closure to return any value, and only
+	//have the error-reporting behaviour when the return type is GResult<_>.
+	pub fn run<F, R>(&self, f: F) -> Option<R> 
+	where
+		F: FnOnce() -> GResult<R>,
+		F: GSend,
+		R: GSend
+	{
+		let old_active_engine = ACTIVE_ENGINE.with(|ref_cell| {
+			ref_cell.replace(Some(self.0.clone()))
+		});
 
-pub struct RData {
-	header: GcHeader,
-	storage: RefCell<Option<Rc<dyn RAllocate>>>,
-	pub(crate) class: Rc<RClass>,
+		let old_engine_id = ACTIVE_ENGINE_ID.with(|cell| {
+			cell.replace(Some(self.0.id))
+		});
 
-	//just like Obj, we need this field so that we can generate a `self` argument when 
-	//rdata.call() is invoked from rust code
-	gc_self: Cell<Option<Gc<RData>>>
-}
+		let _guard = Guard::new(|| {
+			ACTIVE_ENGINE.with(|ref_cell| {
+				ref_cell.replace(old_active_engine);
+			});
+			ACTIVE_ENGINE_ID.with(|cell| {
+				cell.set(old_engine_id);
+			});
+		});
 
-impl Allocate for RData {
-	fn header(&self) -> &GcHeader {
-		&self.header
-	}
+		let result = f();
 
-	fn visit_gcs<V: Visitor>(&self, visitor: &mut V) {
-		visitor.visit_gc(&self.gc_self());
-	}
+		if let Err(ref error) = result {
+			if error.stack_trace().is_some() {
+				eprn!("\nunhandled error in run() call:\n\n{}", error);
+			} else {
+				eprn!("\nunhandled error in run() call: {}", error);
+			}
+		}
 
-	fn clear_gcs(&self) {
-		self.gc_self.set(None);
+		result.ok()
 	}
 
-	fn owned_memory_usage(&self) -> usize {
-		match self.storage.borrow().as_ref() {
-			Some(rc_ref) => rc_ref.size_of(),
-			None => 0
-		}
+	pub fn handle(&self) -> RuntimeHandle {
+		RuntimeHandle(Arc::downgrade(&self.0.vm.execution_slot))
 	}
 }
 
-/**
-A shared reference to an [`RData`](struct.RData.html).
 
-Created using [`RData::borrow`](struct.RData.html#method.borrow) or
-[`RData::try_borrow`](struct.RData.html#method.try_borrow).
-*/
-pub struct RRef<T: RStore>(OwningHandle<Rc<RefCell<T>>, Ref<'static, T>>);
+//-------------------------------------------------------------------------------------------------
+// Guard
+//-------------------------------------------------------------------------------------------------
 
-impl<T: RStore> Deref for RRef<T> {
-	type Target = T;
+//because rfns are allowed to panic, and because various glsp functions can early-exit when an
+//error is generated, we need to be careful about unwind-safety. we mostly achieve this using the
+//Guard struct, which runs an arbitrary closure when it's dropped.
+pub(crate) struct Guard<F: FnOnce()>(Option<F>);
 
-	fn deref(&self) -> &T {
-		&*self.0
+impl<F: FnOnce()> Guard<F> {
+	pub(crate) fn new(f: F) -> Guard<F> {
+		Guard(Some(f))
 	}
 }
 
-/**
-A mutable reference to an [`RData`](struct.RData.html).
+impl<F: FnOnce()> Drop for Guard<F> {
+	fn drop(&mut self) {
+		(self.0.take().unwrap())()
+	}
+}
 
-Created using [`RData::borrow_mut`](struct.RData.html#method.borrow_mut) or
-[`RData::try_borrow_mut`](struct.RData.html#method.try_borrow_mut).
-*/
-pub struct RRefMut<T: RStore>(OwningHandle<Rc<RefCell<T>>, RefMut<'static, T>>);
+//gives each generated arr in a transformed form the Span of its innermost enclosing arr, so that
+//code injected by an expansion pass reports a useful source location. the depth limit protects
+//us from self-referential arrs, which the compiler will reject anyway.
+fn inherit_spans(arr: &Root<Arr>, span: Span, depth: usize) {
+	if depth > 1024 {
+		return
+	}
 
-impl<T: RStore> Deref for RRefMut<T> {
-	type Target = T;
+	let span = if arr.span() == Span::default() {
+		arr.set_span(span);
+		span
+	} else {
+		arr.span()
+	};
 
-	fn deref(&self) -> &T {
-		&*self.0
+	for item in arr.iter() {
+		if let Val::Arr(ref child) = item {
+			inherit_spans(child, span, depth + 1);
+		}
 	}
 }
 
-impl<T: RStore> DerefMut for RRefMut<T> {
-	fn deref_mut(&mut self) -> &mut T {
-		&mut *self.0
-	}
+//pushes a resolved filename onto the stack of files which are currently being loaded, popping it
+//when the Guard is dropped. glsp::resolve_filename() resolves relative filenames against the top
+//of this stack.
+fn loading_guard(filename: &str) -> Guard<impl FnOnce()> {
+	with_engine(|engine| engine.loading.borrow_mut().push(Rc::from(filename)));
+	Guard::new(|| {
+		with_engine(|engine| {
+			engine.loading.borrow_mut().pop();
+		})
+	})
 }
 
-impl RData {
-	pub(crate) fn new<T: RStore>(rdata: T, class: Rc<RClass>) -> RData {
-		RData {
-			header: GcHeader::new(),
-			storage: RefCell::new(Some(Rc::new(RefCell::new(rdata)))),
-			class,
-			gc_self: Cell::new(None)
+//records that the file at the top of the loading stack depends on `filename`, which should be
+//normalized. a dependency of a file which is loaded from rust is not recorded.
+fn add_dependency(filename: &str) {
+	with_engine(|engine| {
+		let loading = engine.loading.borrow();
+		let parent = match loading.last() {
+			Some(parent) => normalize_filename(parent),
+			None => return
+		};
+
+		let mut loaded_files = engine.loaded_files.borrow_mut();
+		if let Some(entry) = loaded_files.iter_mut().find(|entry| entry.filename == parent) {
+			if !entry.dependencies.iter().any(|dep| dep == filename) {
+				entry.dependencies.push(filename.to_string());
+			}
 		}
-	}
+	})
+}
 
-	pub fn type_name(&self) -> &'static str {
-		match self.storage.borrow().as_ref() {
-			Some(rc_ref) => rc_ref.type_name(),
-			None => ""
+//records that `filename`, which should be normalized, has started loading. any dependencies from
+//a previous load are discarded, because they might not be loaded again (e.g. by glsp::reload).
+fn start_loaded_file(filename: &str) {
+	with_engine(|engine| {
+		let mut loaded_files = engine.loaded_files.borrow_mut();
+		match loaded_files.iter_mut().find(|entry| entry.filename == filename) {
+			Some(entry) => entry.dependencies.clear(),
+			None => {
+				loaded_files.push(LoadedFile {
+					filename: filename.to_string(),
+					dependencies: Vec::new()
+				})
+			}
 		}
-	}
+	})
+}
 
-	pub fn class_name(&self) -> Sym {
-		self.class.name
-	}
+//splits a glsp::global_path() path into the global's name and a list of keys
+fn parse_global_path(path: &str) -> GResult<(Sym, Vec<Val>)> {
+	let mut segments = path.split('.');
+	let global_sym = glsp::sym(segments.next().unwrap())?;
 
-	/**
-	Returns `true` if this `RData` is currently storing a value of type `T`.
-	*/
-	pub fn is<T: RStore>(&self) -> bool {
-		self.storage.borrow().as_ref().unwrap().as_any().is::<RefCell<T>>()
+	let mut keys = Vec::new();
+	for segment in segments {
+		ensure!(segment.len() > 0, "the path {:?} contains an empty segment", path);
+
+		keys.push(match segment.parse::<i32>() {
+			Ok(i) => Val::Int(i),
+			Err(_) => Val::Sym(glsp::sym(segment)?)
+		});
 	}
 
-	/**
-	Returns a shared reference to the value being stored by this `RData`.
+	Ok((global_sym, keys))
+}
 
-	Panics if the `RData` is not storing a value of type `T`; if its value has been freed; or 
-	if the value is currently mutably borrowed.
-	*/
-	pub fn borrow<T: RStore>(&self) -> RRef<T> {
-		self.try_borrow::<T>().unwrap()
+//the part of a glsp::global_path() path which precedes its key at index `i`
+fn global_path_prefix(path: &str, i: usize) -> &str {
+	match path.match_indices('.').nth(i) {
+		Some((end, _)) => &path[..end],
+		None => path
 	}
+}
 
-	/**
-	Returns a shared reference to the value being stored by this `RData`.
+fn describe_key(key: &Val) -> String {
+	match *key {
+		Val::Int(i) => format!("index {}", i),
+		ref key => format!("key '{}'", key)
+	}
+}
 
-	Returns an `Err` if the `RData` is not storing a value of type `T`; if its value has 
-	been freed; or if the value is currently mutably borrowed.
-	*/
-	pub fn try_borrow<T: RStore>(&self) -> GResult<RRef<T>> {
-		let borrow = match self.storage.try_borrow() {
-			Ok(borrow) => borrow,
-			Err(_) => {
-				//this is unreachable because the only time we *mutably* borrow the
-				//outer RData is when we're about to free it
-				unreachable!()
-			}
-		};
+fn path_to_string(path: &[Val]) -> String {
+	let segments: Vec<String> = path.iter().map(|key| key.to_string()).collect();
+	format!("({})", segments.join(" "))
+}
 
-		if let Some(ref rc) = *borrow {
-			match Rc::downcast::<RefCell<T>>(rc.clone().as_rc_any()) {
-				Ok(rc_ref_cell) => {
-					ensure!(rc_ref_cell.try_borrow().is_ok(),
-					        "try_borrow<{}> failed: value is mutably borrowed", T::type_name());
-					Ok(RRef(OwningHandle::new(rc_ref_cell)))
+//lexically removes `.` and `..` components from a filename, using `/` as the separator
+fn normalize_filename(filename: &str) -> String {
+	let absolute = filename.starts_with(|ch| ch == '/' || ch == '\\');
+
+	let mut components = Vec::<&str>::new();
+	for component in filename.split(|ch| ch == '/' || ch == '\\') {
+		match component {
+			"" | "." => (),
+			".." => {
+				match components.last() {
+					Some(&last) if last != ".." => {
+						components.pop();
+					}
+					_ if absolute => (),
+					_ => components.push("..")
 				}
-				Err(_) => bail!("type mismatch in try_borrow<{}>()", T::type_name())
 			}
-		} else {
-			bail!("try_borrow<{}> failed: attempted to access a freed RData", T::type_name())
+			component => components.push(component)
 		}
 	}
 
-	/**
-	Returns a mutable reference to the value being stored by this `RData`.
-
-	Panics if the `RData` is not storing a value of type `T`; if its value has been freed; or 
-	if the value is currently borrowed.
-	*/
-	pub fn borrow_mut<T: RStore>(&self) -> RRefMut<T> {
-		self.try_borrow_mut::<T>().unwrap()
+	let joined = components.join("/");
+	if absolute {
+		format!("/{}", joined)
+	} else {
+		joined
 	}
+}
 
-	/**
-	Returns a mutable reference to the value being stored by this `RData`.
-
-	Returns an `Err` if the `RData` is not storing a value of type `T`; if its value has 
-	been freed; or if the value is currently borrowed.
-	*/
-	pub fn try_borrow_mut<T: RStore>(&self) -> GResult<RRefMut<T>> {
-		let borrow = match self.storage.try_borrow() {
-			Ok(borrow) => borrow,
-			Err(_) => {
-				//this is unreachable because the only time we *mutably* borrow the
-				//outer RefCell is when we're about to free it
-				unreachable!()
-			}
-		};
 
-		if let Some(ref rc) = *borrow {
-			match Rc::downcast::<RefCell<T>>(rc.clone().as_rc_any()) {
-				Ok(rc_ref_cell) => {
-					ensure!(rc_ref_cell.try_borrow_mut().is_ok(),
-					        "try_borrow_mut<{}> failed: value is currently borrowed", 
-					        T::type_name());
-					Ok(RRefMut(OwningHandle::new_mut(rc_ref_cell)))
-				}
-				Err(_) => bail!("type mismatch in try_borrow<{}>()", T::type_name())
-			}
-		} else {
-			bail!("try_borrow_mut<{}> failed: attempted to access a freed RData", T::type_name())
-		}
-	}
+//-------------------------------------------------------------------------------------------------
+// Sym, ToSym, RFn, Filename
+//-------------------------------------------------------------------------------------------------
 
-	/**
-	Takes the value stored in this `RData` and returns it.
+/**
+The `sym` primitive type.
 
-	Any future attempts to access the value will gracefully fail.
+Symbols are represented by a small `Copy` type (a 32-bit integer id). Each `Runtime` can store
+up to [`glsp::MAX_SYM_COUNT`](constant.MAX_SYM_COUNT.html) symbols.
 
-	Returns an `Err` if the `RData` is not storing a value of type `T`; if its value has already 
-	been freed; or if it's currently borrowed.
-	*/
-	pub fn take<T: RStore>(&self) -> GResult<T> {
-		//freeing the stored data changes our owned_memory_usage(), so we need to write-barrier it.
-		let prev_usage = self.owned_memory_usage();
+To convert a string into a symbol, you should usually call [`glsp::sym`](fn.sym.html).
+*/
 
-		match self.storage.try_borrow_mut() {
-			Ok(mut borrow_mut) => {
-				match *borrow_mut {
-					Some(ref rc) if Rc::strong_count(rc) == 1 => {
-						drop(rc);
-						let rc_any = (*borrow_mut).take().unwrap().as_rc_any();
-						drop(borrow_mut);
+#[derive(PartialEq, Eq, Hash, Copy, Clone)]
+pub struct Sym(pub(crate) u32);
 
-						let rc = match Rc::downcast::<RefCell<T>>(rc_any) {
-							Ok(rc) => rc,
-							Err(_) => {
-								bail!("type mismatch when calling take::<{}>()", T::type_name())
-							}
-						};
+const MAX_SYM: u32 = 0xffffff;
 
-						let cur_usage = self.owned_memory_usage();
-						with_heap(|heap| heap.memory_usage_barrier(self, prev_usage, cur_usage));
+impl Sym {
+	/**
+	Returns the name of the symbol.
 
-						let ref_cell = Rc::try_unwrap(rc).ok().unwrap();
-						Ok(ref_cell.into_inner())
-					}
-					Some(_) => bail!("called take() on an RData which is currently borrowed"),
-					None => bail!("called take() on an RData which has already been freed")
-				}
-			}
-			Err(_) => bail!("called take() on an RData which is currently borrowed")
-		}
+	For gensyms, the name is the same as its printed representation, e.g. `#<gs:tag:8>`.
+	*/
+	pub fn name(&self) -> Rc<str> {
+		with_engine(|engine| {
+			Rc::clone(&engine.syms.borrow()[self.0 as usize].name)
+		})
 	}
 
 	/**
-	Drops the value stored by this `RData`.
-
-	Any future attempts to access the value will gracefully fail.
+	Returns `true` if this symbol is a gensym.
 
-	Returns an `Err` if the `RData` is not storing a value of type `T`; if its value has already 
-	been freed; or if it's currently borrowed.
+	Gensyms are constructed using [`glsp::gensym`](fn.gensym.html) or
+	[`glsp::gensym_with_tag`](fn.gensym_with_tag.html).
 	*/
-	pub fn free(&self) -> GResult<()> {
-		//freeing the stored data changes our owned_memory_usage(), so we need to write-barrier it.
-		let prev_usage = self.owned_memory_usage();
-
-		match self.storage.try_borrow_mut() {
-			Ok(mut borrow_mut) => {
-				match *borrow_mut {
-					Some(ref rc) if Rc::strong_count(rc) == 1 => {
-						drop(rc);
-						*borrow_mut = None;
-						drop(borrow_mut);
-
-						let cur_usage = self.owned_memory_usage();
-						with_heap(|heap| heap.memory_usage_barrier(self, prev_usage, cur_usage));
+	pub fn is_gensym(&self) -> bool {
+		self.kind() == SymKind::Gensym
+	}
 
-						Ok(())
-					}
-					Some(_) => bail!("called free() on an RData which is currently borrowed"),
-					None => bail!("called free() on an RData which has already been freed")
-				}
-			}
-			Err(_) => bail!("called take() on an RData which is currently borrowed")
-		}
+	//only public so that it can be used internally by the backquote!() macro
+	#[doc(hidden)]
+	pub fn kind(&self) -> SymKind {
+		with_engine(|engine| {
+			engine.syms.borrow()[self.0 as usize].kind
+		})
 	}
 
-	///Returns `true` if this `RData`'s value has been taken or freed.
-	pub fn is_freed(&self) -> bool {
-		match self.storage.try_borrow() {
-			Ok(borrow) => borrow.is_none(),
-			Err(_) => unreachable!()
+	//used internally by the encoder and by (bind-global!). we forbid special forms' names from 
+	//being rebound to global or local variables, but not from being bound to a macro. a (let) 
+	//macro has its uses, but a shadowing local variable named something like `defer` would break 
+	//too many macros to be useful.
+	pub(crate) fn is_bindable(&self) -> bool {
+		match self.kind() {
+			SymKind::StockSpecial => false,
+			SymKind::Normal | SymKind::StockKeyword | 
+			SymKind::StockTransform | SymKind::Gensym => true
 		}
 	}
 
-	fn gc_self(&self) -> Gc<RData> {
-		let gc_self = self.gc_self.take();
-		self.gc_self.set(gc_self.clone());
-		gc_self.unwrap()
+	//only public so that it can be used internally by the backquote!() macro
+	#[doc(hidden)]
+	pub fn from_u32(id: u32) -> Sym {
+		assert!(id <= MAX_SYM);
+		Sym(id)
 	}
 
-	/**
-	Creates an indexing iterator for this collection.
-
-	Equivalent to [`[rdata iter]`](https://gamelisp.rs/std/access).
-	*/
-	pub fn access_giter(rdata: &Root<RData>, giter: &Root<GIter>) -> Root<GIter> {
-		glsp::giter(GIterState::AccessRData(rdata.to_gc(), giter.to_gc()))
+	//only public so that it can be used internally by the backquote!() macro
+	#[doc(hidden)]
+	pub fn to_u32(&self) -> u32 {
+		self.0
 	}
+}
 
-	/**
-	Accesses the value of a field or property.
-	
-	Equivalent to [`[rdata key]`](https://gamelisp.rs/std/access).
-	*/
-	pub fn get<S, R>(&self, key: S) -> GResult<R>
-	where
-		S: ToSym,
-		R: FromVal
-	{
-		let sym = key.to_sym()?;
-		match self.get_if_present(sym)? {
-			Some(r) => Ok(r),
-			None => bail!("attempted to access nonexistent prop getter '{}'", sym)
-		}
-	}
+/**
+A type which can be converted to a [`Sym`](struct.Sym.html).
 
-	/**
-	Accesses the value of a field or property, if it exists.
+This is mostly used to make APIs more ergonomic. For example, the argument to 
+[`glsp::global`](fn.global.html) is a generic `S: ToSym`, which means that it can 
+receive either a symbol or a string:
 	
-	Equivalent to [`[rdata (? key)]`](https://gamelisp.rs/std/access).
-	*/
-	pub fn get_if_present<S, R>(&self, key: S) -> GResult<Option<R>>
-	where
-		S: ToSym,
-		R: FromVal
-	{
-		let sym = key.to_sym()?;
+	glsp::global(my_lib.my_sym)?;
+	glsp::global("sym-name")?;
+*/
 
-		match self.class.bindings.get(&sym) {
-			Some(RBinding::Prop(Some(rfn), _)) => {
-				with_vm(|vm| {
-					vm.stacks.borrow_mut().regs.push(Slot::RData(self.gc_self()));
-					Ok(Some(R::from_val(&rfn.receive_call(1)?)?))
+pub trait ToSym {
+	fn to_sym(&self) -> GResult<Sym>;
+}
+
+impl<'a, T> ToSym for T 
+where
+	T: Deref,
+	T::Target: ToSym
+{
+	fn to_sym(&self) -> GResult<Sym> {
+		(**self).to_sym()
+	}
+}
+
+impl ToSym for Sym {
+	fn to_sym(&self) -> GResult<Sym> {
+		Ok(*self)
+	}
+}
+
+impl<'a> ToSym for str {
+	fn to_sym(&self) -> GResult<Sym> {
+		glsp::sym(self)
+	}
+}
+
+impl<'a> ToSym for Str {
+	fn to_sym(&self) -> GResult<Sym> {
+		glsp::sym(&self.to_string())
+	}
+}
+
+#[doc(hidden)]
+#[derive(Clone, Copy, PartialEq)]
+pub enum SymKind {
+	Normal,
+	StockSpecial,
+	StockKeyword,
+	StockTransform,
+	Gensym
+}
+
+/**
+The `rfn` primitive type.
+
+Rust functions are represented by a small `Copy` type (a 32-bit integer id).
+
+Most of this type's methods belong to the `callable` abstract type, so they can be found in
+the [`CallableOps`](trait.CallableOps.html) trait. To invoke an `RFn`, use
+[`glsp::call`](fn.call.html).
+
+To convert a function pointer or a closure into an `RFn`, you should usually call 
+[`glsp::bind_rfn`](fn.bind_rfn.html) or [`glsp::rfn`](fn.rfn.html).
+
+An `RFn` is only meaningful within the `Runtime` which created it. The `Runtime` owns every
+closure passed to [`glsp::rfn_boxed`](fn.rfn_boxed.html), dropping them all when the
+`Runtime` itself is dropped.
+*/
+
+#[derive(PartialEq, Eq, Hash, Copy, Clone)]
+pub struct RFn(NonZeroU32);
+
+impl RFn {
+	pub(crate) fn set_name(&self, new_name: Option<Sym>) {
+		with_engine(|engine| {
+			engine.rfns.borrow_mut()[self.0.get() as usize].name = new_name;
+		})
+	}
+}
+
+impl CallableOps for RFn {
+	fn receive_call(&self, arg_count: usize) -> GResult<Val> {
+		glsp::call_rfn(*self, arg_count).map(|slot| slot.root())
+	}
+
+	fn name(&self) -> Option<Sym> {
+		with_engine(|engine| {
+			engine.rfns.borrow()[self.0.get() as usize].name
+		})
+	}
+
+	fn arg_limits(&self) -> (usize, Option<usize>) {
+		with_engine(|engine| {
+			engine.rfns.borrow()[self.0.get() as usize].callee.arg_limits()
+		})
+	}
+}
+
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub struct Filename(NonZeroU32);
+
+/**
+Define a struct which contains a collection of symbols.
+
+Caching symbols in a struct has better performance than calling [`glsp::sym`](fn.sym.html), and
+it's more convenient than storing `Sym` fields in your own structs directly.
+
+The struct defines a method `fn new() -> GResult<Self>` which initializes each field by passing
+the given string literal to [`glsp::sym`](fn.sym.html).
+
+	syms! {
+		#[derive(Clone)]
+		pub struct Syms {
+			pub width: "width",
+			pub set_width: "width=",
+			rectp: "rect?"
+		}
+	}
+*/
+
+#[macro_export]
+macro_rules! syms {
+	(
+		$(#[$struct_attr:meta])*
+		$struct_vis:vis struct $struct_name:ident {
+			$($vis:vis $name:ident: $contents:literal),+
+		}
+	) => (
+		$crate::syms! {
+			$(#[$struct_attr])*
+			$struct_vis struct $struct_name {
+				$($vis $name: $contents,)+
+			}
+		}
+	);
+
+	(
+		$(#[$struct_attr:meta])*
+		$struct_vis:vis struct $struct_name:ident {
+			$($vis:vis $name:ident: $contents:literal,)*
+		}
+	) => (
+		$struct_vis struct $struct_name {
+			$($vis $name: Sym,)*
+		}
+
+		impl $struct_name {
+			#[allow(unused_variables)]
+			$struct_vis fn new() -> GResult<Syms> {
+				Ok($struct_name {
+					$($name: glsp::sym($contents)?,)*
 				})
 			}
-			_ => Ok(None)
 		}
+	);
+}
+
+
+//-------------------------------------------------------------------------------------------------
+// Lib, RData, RRoot
+//-------------------------------------------------------------------------------------------------
+
+/**
+A type which can be passed to [`glsp::add_lib`](fn.add_lib.html).
+
+It's possible to implement this trait manually, but using the [`lib!` macro](macro.lib.html) is
+strongly encouraged. Among other things, that macro will automatically implement 
+[`MakeArg`](trait.MakeArg.html) for the library type.
+*/
+
+pub trait Lib: 'static + Sized {
+	fn type_name() -> &'static str;
+
+	/**
+	Returns a shared reference to the instance of this type owned by the active
+	[`Runtime`](struct.Runtime.html).
+
+	Panics if a library of this type has not been registered with the active `Runtime`, or if 
+	the library is currently mutably borrowed.
+	*/
+	fn borrow() -> LibRef<Self> {
+		glsp::lib::<Self>()
+	}
+
+	/**
+	Returns a mutable reference to the instance of this type owned by the active
+	[`Runtime`](struct.Runtime.html).
+
+	Panics if a library of this type has not been registered with the active `Runtime`, or if 
+	the library is currently borrowed.
+	*/
+	fn borrow_mut() -> LibRefMut<Self> {
+		glsp::lib_mut::<Self>()
+	}
+	
+	/**
+	Returns a shared reference to the instance of this type owned by the active
+	[`Runtime`](struct.Runtime.html).
+
+	Returns an `Err` if a library of this type has not been registered with the active `Runtime`, 
+	or if the library is currently mutably borrowed.
+	*/
+	fn try_borrow() -> GResult<LibRef<Self>> {
+		glsp::try_lib::<Self>()
+	}
+
+	/**
+	Returns a mutable reference to the instance of this type owned by the active
+	[`Runtime`](struct.Runtime.html).
+
+	Returns an `Err` if a library of this type has not been registered with the active `Runtime`, 
+	or if the library is currently borrowed.
+	*/
+	fn try_borrow_mut() -> GResult<LibRefMut<Self>> {
+		glsp::try_lib_mut::<Self>()
+	}
+}
+
+/**
+A reference to a [library](trait.Lib.html).
+
+Created using [`Lib::borrow`](trait.Lib.html#method.borrow) or
+[`Lib::try_borrow`](trait.Lib.html#method.try_borrow).
+*/
+pub struct LibRef<T: Lib> {
+	handle: OwningHandle<Rc<RefCell<T>>, Ref<'static, T>>
+}
+
+impl<T: Lib> Deref for LibRef<T> {
+	type Target = T;
+	fn deref(&self) -> &T {
+		&*self.handle
+	}
+}
+
+/**
+A mutable reference to a [library](trait.Lib.html).
+
+Created using [`Lib::borrow_mut`](trait.Lib.html#method.borrow_mut) or
+[`Lib::try_borrow_mut`](trait.Lib.html#method.try_borrow_mut).
+*/
+pub struct LibRefMut<T: Lib> {
+	handle: OwningHandle<Rc<RefCell<T>>, RefMut<'static, T>>
+}
+
+impl<T: Lib> Deref for LibRefMut<T> {
+	type Target = T;
+	fn deref(&self) -> &T {
+		&*self.handle
+	}
+}
+
+impl<T: Lib> DerefMut for LibRefMut<T> {
+	fn deref_mut(&mut self) -> &mut T {
+		&mut *self.handle
+	}
+}
+
+trait RAllocate: GStore + 'static {
+	fn type_name(&self) -> &'static str;
+	fn rtype_id(&self) -> TypeId;
+	fn size_of(&self) -> usize;
+	fn as_any(&self) -> &dyn Any;
+	fn as_rc_any(self: Rc<Self>) -> Rc<dyn Any>;
+}
+
+impl<T: RStore> RAllocate for RefCell<T> {
+	fn type_name(&self) -> &'static str {
+		T::type_name()
+	}
+
+	fn rtype_id(&self) -> TypeId {
+		TypeId::of::<T>()
+	}
+
+	fn size_of(&self) -> usize {
+		T::size_of()
+	}
+
+	fn as_any(&self) -> &dyn Any {
+		self
+	}
+
+	fn as_rc_any(self: Rc<Self>) -> Rc<dyn Any> {
+		self
+	}
+}
+
+/**
+A type which can be moved onto the garbage-collected heap as an [`RData`](struct.RData.html).
+
+It's possible to implement this trait manually, but using the [`rdata!` macro](macro.rdata.html) 
+is strongly encouraged. Among other things, that macro will automatically implement 
+[`MakeArg`](trait.MakeArg.html) and [`IntoResult`](trait.IntoResult.html) for your type.
+*/
+
+pub trait RStore: GStore + 'static {
+	fn type_name() -> &'static str;
+
+	//ideally we would provide a `fn owned_memory_usage(&self)` for this trait, but it's not clear
+	//what the syntax would be for implementing it, or how we would document it. for now we just
+	//report the size of the stored struct itself.
+	fn size_of() -> usize;
+
+	fn rclass() -> GResult<RClass>;
+}
+
+/**
+An implementation detail of the [`RStore` trait](trait.RStore.html) and the
+[`rdata!` macro](macro.rdata.html).
+*/
+
+pub struct RClass {
+	name: Sym,
+	bindings: RefCell<FnvHashMap<Sym, RBinding>>
+}
+
+#[derive(Copy, Clone)]
+enum RBinding {
+	Meth(RFn),
+	Prop(Option<RFn>, Option<RFn>)
+}
+
+impl RClass {
+	pub fn from_vec(
+		class_name: &'static str,
+		raw_bindings: Vec<(&'static str, &'static str, WrappedFn)>
+	) -> GResult<RClass> {
+		let class_name = glsp::sym(class_name)?;
+
+		let mut bindings = FnvHashMap::with_capacity_and_hasher(
+			raw_bindings.len(),
+			Default::default()
+		);
+
+		for (kind, key, wrapped_fn) in raw_bindings {
+			insert_rbinding(&mut bindings, kind, glsp::sym(key)?, glsp::rfn(wrapped_fn))?;
+		}
+
+		Ok(RClass {
+			name: class_name,
+			bindings: RefCell::new(bindings)
+		})
+	}
+
+	fn binding(&self, name: Sym) -> Option<RBinding> {
+		self.bindings.borrow().get(&name).copied()
+	}
+
+	//a suffix for "nonexistent method" and "nonexistent prop" errors, listing the alternatives
+	fn describe_bindings(&self, meths: bool) -> String {
+		let mut names: Vec<Sym> = self.bindings.borrow().iter().filter(|(_, binding)| {
+			match binding {
+				RBinding::Meth(_) => meths,
+				RBinding::Prop(getter, _) => !meths && getter.is_some()
+			}
+		}).map(|(&name, _)| name).collect();
+
+		names.sort_by(|a, b| a.name().cmp(&b.name()));
+
+		let kind = if meths { "meths" } else { "props" };
+		if names.is_empty() {
+			return format!("{} has no {}", self.name, kind)
+		}
+
+		let mut description = format!("{}'s {} are ", self.name, kind);
+		for (i, name) in names.iter().enumerate() {
+			if i > 0 {
+				description.push_str(if i == names.len() - 1 { " and " } else { ", " });
+			}
+			description.push_str(&name.name());
+		}
+
+		description
+	}
+}
+
+fn insert_rbinding(
+	bindings: &mut FnvHashMap<Sym, RBinding>,
+	kind: &str,
+	name: Sym,
+	rfn: RFn
+) -> GResult<()> {
+	if rfn.name().is_none() {
+		rfn.set_name(Some(name));
+	}
+
+	match (kind, bindings.entry(name)) {
+		("", Vacant(entry)) => { entry.insert(RBinding::Meth(rfn)); }
+		("get", Vacant(entry)) => { entry.insert(RBinding::Prop(Some(rfn), None)); }
+		("set", Vacant(entry)) => { entry.insert(RBinding::Prop(None, Some(rfn))); }
+		("", Occupied(_)) => bail!("duplicate meth name {}", name),
+		("get", Occupied(mut entry)) => {
+			match entry.get_mut() {
+				RBinding::Meth(_) => bail!("{} is bound to both a meth and a prop", name),
+				RBinding::Prop(Some(_), _) => bail!("duplicate getter {}", name),
+				RBinding::Prop(ref mut none, _) => *none = Some(rfn)
+			}
+		}
+		("set", Occupied(mut entry)) => {
+			match entry.get_mut() {
+				RBinding::Meth(_) => bail!("{} is bound to both a meth and a prop", name),
+				RBinding::Prop(_, Some(_)) => bail!("duplicate setter {}", name),
+				RBinding::Prop(_, ref mut none) => *none = Some(rfn)
+			}
+		}
+		(kind, _) => bail!("{} is not a valid tag for an RData meth", kind)
+	}
+
+	Ok(())
+}
+
+/**
+The `rdata` primitive type.
+
+This is a Rust value which has been moved onto the garbage-collected heap. It can be constructed
+using the [`glsp::rdata`](fn.rdata.html) function.
+
+`RData` has several convenience features:
+
+- It supports interior mutability, like a 
+  [`RefCell`](https://doc.rust-lang.org/std/cell/struct.RefCell.html).
+
+- It can be manually deallocated, or taken back from the garbage-collected heap, using
+  [`free`](#method.free) and [`take`](#method.take). Any attempts to access a deallocated `RData`
+  from GameLisp will trigger an error.
+
+- It's dynamically typed: a `Vec<Root<RData>>` could refer to several different Rust types.
+  (If this is undesirable, consider using [`RRoot`](struct.RRoot.html) instead.)
+
+- The [`rdata!` macro](macro.rdata.html) enables Rust functions to be associated with an `RData` 
+  as its methods and properties. They can be accessed from GameLisp code, or accessed from Rust 
+  code using an API similar to [`Obj`](struct.Obj.html).
+*/
+
+pub struct RData {
+	header: GcHeader,
+	storage: RefCell<Option<Rc<dyn RAllocate>>>,
+	pub(crate) class: Rc<RClass>,
+
+	//just like Obj, we need this field so that we can generate a `self` argument when 
+	//rdata.call() is invoked from rust code
+	gc_self: Cell<Option<Gc<RData>>>,
+
+	//set when the value of a scoped RData is returned to its lender
+	expired: Cell<Option<BorrowSite>>,
+
+	//set when a panic unwinds through a mutable borrow of the value
+	poisoned: Rc<Cell<Option<BorrowSite>>>,
+
+	#[cfg(debug_assertions)]
+	borrows: Rc<BorrowTracker>
+}
+
+impl Allocate for RData {
+	fn header(&self) -> &GcHeader {
+		&self.header
+	}
+
+	fn visit_gcs<V: Visitor>(&self, visitor: &mut V) {
+		visitor.visit_gc(&self.gc_self());
+	}
+
+	fn clear_gcs(&self) {
+		self.gc_self.set(None);
+	}
+
+	fn owned_memory_usage(&self) -> usize {
+		match self.storage.borrow().as_ref() {
+			Some(rc_ref) => rc_ref.size_of(),
+			None => 0
+		}
+	}
+}
+
+/*
+in debug builds, each RData records where its active borrows came from, so that a conflicting
+borrow can report "value is mutably borrowed by the rfn (update-physics), called at 
+world.glsp:88". when there are several shared borrows, we only remember the most recent one.
+
+in all builds, an RData created by glsp::with_scoped_rdata records the BorrowSite which lent it,
+so that an access after it expires can report where the value came from.
+
+also in all builds, a mutable borrow which is dropped while a panic is unwinding poisons the 
+RData, because the panic might have left its value half-updated. the rfn which panicked is 
+looked up when the borrow is dropped, rather than when it's created, so that mutable borrows 
+stay cheap in release builds.
+*/
+
+#[cfg(debug_assertions)]
+#[derive(Default)]
+struct BorrowTracker {
+	shared_count: Cell<usize>,
+	shared_site: Cell<Option<BorrowSite>>,
+	mut_site: Cell<Option<BorrowSite>>
+}
+
+#[derive(Copy, Clone)]
+pub(crate) struct BorrowSite {
+	location: &'static Location<'static>,
+	rfn_callsite: Option<(RFn, Span)>
+}
+
+impl BorrowSite {
+	#[track_caller]
+	pub(crate) fn new() -> BorrowSite {
+		BorrowSite {
+			location: Location::caller(),
+			rfn_callsite: with_vm(|vm| vm.rfn_callsite())
+		}
+	}
+}
+
+impl Display for BorrowSite {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		match self.rfn_callsite {
+			Some((rfn, span)) => {
+				match rfn.name() {
+					Some(name) => write!(f, "the rfn ({})", name)?,
+					None => write!(f, "an anonymous rfn")?
+				}
+
+				let mut file_location = String::new();
+				if glsp::span_file_location(&mut file_location, span)? {
+					write!(f, ", called at {}", file_location)?;
+				}
+
+				Ok(())
+			}
+			None => write!(f, "Rust code at {}", self.location)
+		}
+	}
+}
+
+struct BorrowGuard {
+	#[cfg(debug_assertions)]
+	tracker: Rc<BorrowTracker>,
+	#[cfg(debug_assertions)]
+	is_mut: bool,
+
+	//only present for mutable borrows
+	poison: Option<(Rc<Cell<Option<BorrowSite>>>, &'static Location<'static>)>
+}
+
+impl Drop for BorrowGuard {
+	fn drop(&mut self) {
+		if let Some((ref poisoned, location)) = self.poison {
+			if thread::panicking() {
+				//if this RRefMut is dropped while no Runtime is active, e.g. because it was
+				//stashed outside of Runtime::run, we record the borrow's location without a callsite
+				poisoned.set(Some(BorrowSite {
+					location,
+					rfn_callsite: try_with_vm(|vm| vm.rfn_callsite()).flatten()
+				}));
+			}
+		}
+
+		#[cfg(debug_assertions)] {
+			if self.is_mut {
+				self.tracker.mut_site.set(None);
+			} else {
+				let shared_count = self.tracker.shared_count.get() - 1;
+				self.tracker.shared_count.set(shared_count);
+				if shared_count == 0 {
+					self.tracker.shared_site.set(None);
+				}
+			}
+		}
+	}
+}
+
+/**
+A shared reference to an [`RData`](struct.RData.html).
+
+Created using [`RData::borrow`](struct.RData.html#method.borrow) or
+[`RData::try_borrow`](struct.RData.html#method.try_borrow).
+*/
+pub struct RRef<T: RStore> {
+	handle: OwningHandle<Rc<RefCell<T>>, Ref<'static, T>>,
+	_guard: BorrowGuard //never read; it only exists for its Drop impl
+}
+
+impl<T: RStore> Deref for RRef<T> {
+	type Target = T;
+
+	fn deref(&self) -> &T {
+		&*self.handle
+	}
+}
+
+/**
+A mutable reference to an [`RData`](struct.RData.html).
+
+Created using [`RData::borrow_mut`](struct.RData.html#method.borrow_mut) or
+[`RData::try_borrow_mut`](struct.RData.html#method.try_borrow_mut).
+*/
+pub struct RRefMut<T: RStore> {
+	handle: OwningHandle<Rc<RefCell<T>>, RefMut<'static, T>>,
+	_guard: BorrowGuard //never read; it only exists for its Drop impl
+}
+
+impl<T: RStore> Deref for RRefMut<T> {
+	type Target = T;
+
+	fn deref(&self) -> &T {
+		&*self.handle
+	}
+}
+
+impl<T: RStore> DerefMut for RRefMut<T> {
+	fn deref_mut(&mut self) -> &mut T {
+		&mut *self.handle
+	}
+}
+
+impl RData {
+	pub(crate) fn new<T: RStore>(rdata: T, class: Rc<RClass>) -> RData {
+		RData {
+			header: GcHeader::new(),
+			storage: RefCell::new(Some(Rc::new(RefCell::new(rdata)))),
+			class,
+			gc_self: Cell::new(None),
+			expired: Cell::new(None),
+			poisoned: Rc::new(Cell::new(None)),
+			#[cfg(debug_assertions)]
+			borrows: Rc::new(BorrowTracker::default())
+		}
+	}
+
+	/**
+	Returns the unprefixed name of the Rust type stored by this `RData`, like `"Clip"`.
+
+	Returns `""` if the value has been freed.
+	*/
+	pub fn type_name(&self) -> &'static str {
+		match self.storage.borrow().as_ref() {
+			Some(rc_ref) => rc_ref.type_name(),
+			None => ""
+		}
+	}
+
+	/**
+	Returns the name under which this `RData`'s type was registered with the `Runtime`, as a
+	symbol.
+
+	Unlike [`type_name`](#method.type_name), this is still available after the value has been
+	freed. Equivalent to [`(rdata-type rd)`](https://gamelisp.rs/std/rdata-type).
+	*/
+	pub fn class_name(&self) -> Sym {
+		self.class.name
+	}
+
+	/**
+	Returns the [`TypeId`](https://doc.rust-lang.org/std/any/struct.TypeId.html) of the Rust
+	type stored by this `RData`.
+
+	Returns `None` if the value has been freed.
+	*/
+	pub fn type_id(&self) -> Option<TypeId> {
+		self.storage.borrow().as_ref().map(|rc_ref| rc_ref.rtype_id())
+	}
+
+	/**
+	Returns `true` if a panic occurred while this `RData`'s value was mutably borrowed.
+
+	The panic might have left the value in an inconsistent state, so any attempt to borrow it
+	will fail. The value can still be dropped using [`free`](#method.free), or recovered
+	using [`take`](#method.take).
+	*/
+	pub fn is_poisoned(&self) -> bool {
+		self.poisoned.get().is_some()
+	}
+
+	/**
+	Returns `true` if this `RData` is currently storing a value of type `T`.
+
+	Returns `false` if the value has been freed.
+	*/
+	pub fn is<T: RStore>(&self) -> bool {
+		match self.storage.borrow().as_ref() {
+			Some(rc_ref) => rc_ref.as_any().is::<RefCell<T>>(),
+			None => false
+		}
+	}
+
+	/**
+	Returns a shared reference to the value being stored by this `RData`.
+
+	Panics if the `RData` is not storing a value of type `T`; if its value has been freed or
+	poisoned; or if the value is currently mutably borrowed.
+	*/
+	#[track_caller]
+	pub fn borrow<T: RStore>(&self) -> RRef<T> {
+		self.try_borrow::<T>().unwrap()
+	}
+
+	/**
+	Returns a shared reference to the value being stored by this `RData`.
+
+	Returns an `Err` if the `RData` is not storing a value of type `T`; if its value has 
+	been freed or [poisoned](#method.is_poisoned); or if the value is currently mutably borrowed.
+
+	Any number of shared references can exist at the same time. In debug builds, if the value 
+	is mutably borrowed, the error message will describe where that borrow came from: either
+	the rfn which borrowed it and its callsite, or the location of the Rust code which called 
+	`borrow_mut` or `try_borrow_mut`.
+	*/
+	#[track_caller]
+	pub fn try_borrow<T: RStore>(&self) -> GResult<RRef<T>> {
+		let borrow = match self.storage.try_borrow() {
+			Ok(borrow) => borrow,
+			Err(_) => {
+				//this is unreachable because the only time we *mutably* borrow the
+				//outer RData is when we're about to free it
+				unreachable!()
+			}
+		};
+
+		if self.poisoned.get().is_some() && borrow.is_some() {
+			return Err(self.poisoned_error(&format!("try_borrow<{}>", T::type_name())))
+		}
+
+		if let Some(ref rc) = *borrow {
+			match Rc::downcast::<RefCell<T>>(rc.clone().as_rc_any()) {
+				Ok(rc_ref_cell) => {
+					ensure!(rc_ref_cell.try_borrow().is_ok(),
+					        "try_borrow<{}> failed: value is mutably borrowed{}", 
+					        T::type_name(), self.borrowed_by());
+					Ok(RRef {
+						handle: OwningHandle::new(rc_ref_cell),
+						_guard: self.track_borrow(false)
+					})
+				}
+				Err(_) => bail!("type mismatch in try_borrow<{}>()", T::type_name())
+			}
+		} else {
+			Err(self.freed_error(&format!("try_borrow<{}>", T::type_name())))
+		}
+	}
+
+	/**
+	Returns a mutable reference to the value being stored by this `RData`.
+
+	Panics if the `RData` is not storing a value of type `T`; if its value has been freed or
+	poisoned; or if the value is currently borrowed.
+	*/
+	#[track_caller]
+	pub fn borrow_mut<T: RStore>(&self) -> RRefMut<T> {
+		self.try_borrow_mut::<T>().unwrap()
+	}
+
+	/**
+	Returns a mutable reference to the value being stored by this `RData`.
+
+	Returns an `Err` if the `RData` is not storing a value of type `T`; if its value has 
+	been freed or [poisoned](#method.is_poisoned); or if the value is currently borrowed.
+
+	In debug builds, the error message will describe where the conflicting borrow came from,
+	as for [`try_borrow`](#method.try_borrow).
+	*/
+	#[track_caller]
+	pub fn try_borrow_mut<T: RStore>(&self) -> GResult<RRefMut<T>> {
+		let borrow = match self.storage.try_borrow() {
+			Ok(borrow) => borrow,
+			Err(_) => {
+				//this is unreachable because the only time we *mutably* borrow the
+				//outer RefCell is when we're about to free it
+				unreachable!()
+			}
+		};
+
+		if self.poisoned.get().is_some() && borrow.is_some() {
+			return Err(self.poisoned_error(&format!("try_borrow_mut<{}>", T::type_name())))
+		}
+
+		if let Some(ref rc) = *borrow {
+			match Rc::downcast::<RefCell<T>>(rc.clone().as_rc_any()) {
+				Ok(rc_ref_cell) => {
+					ensure!(rc_ref_cell.try_borrow_mut().is_ok(),
+					        "try_borrow_mut<{}> failed: value is currently borrowed{}", 
+					        T::type_name(), self.borrowed_by());
+					Ok(RRefMut {
+						handle: OwningHandle::new_mut(rc_ref_cell),
+						_guard: self.track_borrow(true)
+					})
+				}
+				Err(_) => bail!("type mismatch in try_borrow<{}>()", T::type_name())
+			}
+		} else {
+			Err(self.freed_error(&format!("try_borrow_mut<{}>", T::type_name())))
+		}
+	}
+
+	/**
+	Takes the value stored in this `RData` and returns it.
+
+	Any future attempts to access the value will gracefully fail.
+
+	Returns an `Err` if the `RData` is not storing a value of type `T`; if its value has already 
+	been freed; or if it's currently borrowed.
+	*/
+	pub fn take<T: RStore>(&self) -> GResult<T> {
+		//freeing the stored data changes our owned_memory_usage(), so we need to write-barrier it.
+		let prev_usage = self.owned_memory_usage();
+
+		match self.storage.try_borrow_mut() {
+			Ok(mut borrow_mut) => {
+				match *borrow_mut {
+					Some(ref rc) if Rc::strong_count(rc) == 1 => {
+						drop(rc);
+						let rc_any = (*borrow_mut).take().unwrap().as_rc_any();
+						drop(borrow_mut);
+
+						let rc = match Rc::downcast::<RefCell<T>>(rc_any) {
+							Ok(rc) => rc,
+							Err(_) => {
+								bail!("type mismatch when calling take::<{}>()", T::type_name())
+							}
+						};
+
+						let cur_usage = self.owned_memory_usage();
+						with_heap(|heap| heap.memory_usage_barrier(self, prev_usage, cur_usage));
+
+						let ref_cell = Rc::try_unwrap(rc).ok().unwrap();
+						Ok(ref_cell.into_inner())
+					}
+					Some(_) => bail!("called take() on an RData which is currently borrowed"),
+					None => bail!("called take() on an RData which has already been freed")
+				}
+			}
+			Err(_) => bail!("called take() on an RData which is currently borrowed")
+		}
+	}
+
+	/**
+	Drops the value stored by this `RData`.
+
+	Any future attempts to access the value will gracefully fail.
+
+	Returns an `Err` if the `RData` is not storing a value of type `T`; if its value has already 
+	been freed; or if it's currently borrowed.
+	*/
+	pub fn free(&self) -> GResult<()> {
+		//freeing the stored data changes our owned_memory_usage(), so we need to write-barrier it.
+		let prev_usage = self.owned_memory_usage();
+
+		match self.storage.try_borrow_mut() {
+			Ok(mut borrow_mut) => {
+				match *borrow_mut {
+					Some(ref rc) if Rc::strong_count(rc) == 1 => {
+						drop(rc);
+						*borrow_mut = None;
+						drop(borrow_mut);
+
+						let cur_usage = self.owned_memory_usage();
+						with_heap(|heap| heap.memory_usage_barrier(self, prev_usage, cur_usage));
+
+						Ok(())
+					}
+					Some(_) => bail!("called free() on an RData which is currently borrowed"),
+					None => bail!("called free() on an RData which has already been freed")
+				}
+			}
+			Err(_) => bail!("called take() on an RData which is currently borrowed")
+		}
+	}
+
+	///Returns `true` if this `RData`'s value has been taken or freed.
+	pub fn is_freed(&self) -> bool {
+		match self.storage.try_borrow() {
+			Ok(borrow) => borrow.is_none(),
+			Err(_) => unreachable!()
+		}
+	}
+
+	/**
+	Returns `true` if this `RData` was created by 
+	[`glsp::with_scoped_rdata`](fn.with_scoped_rdata.html), and its value has been returned to 
+	the lender.
+
+	An expired `RData` is also [freed](#method.is_freed).
+	*/
+	pub fn is_expired(&self) -> bool {
+		self.expired.get().is_some()
+	}
+
+	fn freed_error(&self, attempt: &str) -> GError {
+		match self.expired.get() {
+			Some(site) => {
+				let message = format!("{} failed: attempted to access a scoped {} after it \
+				                       expired. it was lent by {}", attempt, self.class.name, site);
+				GError::from_val(EXPIRED_RDATA_SYM).with_source(GError::from_str(&message))
+			}
+			None => error!("{} failed: attempted to access a freed RData", attempt)
+		}
+	}
+
+	#[cfg(debug_assertions)]
+	#[track_caller]
+	fn track_borrow(&self, is_mut: bool) -> BorrowGuard {
+		let site = Some(BorrowSite::new());
+		if is_mut {
+			self.borrows.mut_site.set(site);
+		} else {
+			self.borrows.shared_count.set(self.borrows.shared_count.get() + 1);
+			self.borrows.shared_site.set(site);
+		}
+
+		BorrowGuard {
+			tracker: Rc::clone(&self.borrows),
+			is_mut,
+			poison: self.poison_guard(is_mut)
+		}
+	}
+
+	#[cfg(not(debug_assertions))]
+	#[inline(always)]
+	#[track_caller]
+	fn track_borrow(&self, is_mut: bool) -> BorrowGuard {
+		BorrowGuard {
+			poison: self.poison_guard(is_mut)
+		}
+	}
+
+	#[inline(always)]
+	#[track_caller]
+	fn poison_guard(&self, is_mut: bool) -> Option<(Rc<Cell<Option<BorrowSite>>>, &'static Location<'static>)> {
+		if is_mut {
+			Some((Rc::clone(&self.poisoned), Location::caller()))
+		} else {
+			None
+		}
+	}
+
+	fn poisoned_error(&self, attempt: &str) -> GError {
+		let site = self.poisoned.get().unwrap();
+		error!("{} failed: value is poisoned: a panic occurred while it was mutably borrowed \
+		        by {}", attempt, site)
+	}
+
+	#[cfg(debug_assertions)]
+	fn borrowed_by(&self) -> String {
+		match self.borrows.mut_site.get().or(self.borrows.shared_site.get()) {
+			Some(site) => format!(" by {}", site),
+			None => String::new()
+		}
+	}
+
+	#[cfg(not(debug_assertions))]
+	#[inline(always)]
+	fn borrowed_by(&self) -> &'static str {
+		""
+	}
+
+	fn gc_self(&self) -> Gc<RData> {
+		let gc_self = self.gc_self.take();
+		self.gc_self.set(gc_self.clone());
+		gc_self.unwrap()
+	}
+
+	/**
+	Creates an indexing iterator for this collection.
+
+	Equivalent to [`[rdata iter]`](https://gamelisp.rs/std/access).
+	*/
+	pub fn access_giter(rdata: &Root<RData>, giter: &Root<GIter>) -> Root<GIter> {
+		glsp::giter(GIterState::AccessRData(rdata.to_gc(), giter.to_gc()))
+	}
+
+	/**
+	Accesses the value of a field or property.
+	
+	Equivalent to [`[rdata key]`](https://gamelisp.rs/std/access).
+	*/
+	pub fn get<S, R>(&self, key: S) -> GResult<R>
+	where
+		S: ToSym,
+		R: FromVal
+	{
+		let sym = key.to_sym()?;
+		match self.get_if_present(sym)? {
+			Some(r) => Ok(r),
+			None => bail!("attempted to access nonexistent prop getter '{}': {}", sym,
+			              self.class.describe_bindings(false))
+		}
+	}
+
+	/**
+	Accesses the value of a field or property, if it exists.
+	
+	Equivalent to [`[rdata (? key)]`](https://gamelisp.rs/std/access).
+	*/
+	pub fn get_if_present<S, R>(&self, key: S) -> GResult<Option<R>>
+	where
+		S: ToSym,
+		R: FromVal
+	{
+		let sym = key.to_sym()?;
+
+		match self.class.binding(sym) {
+			Some(RBinding::Prop(Some(rfn), _)) => {
+				with_vm(|vm| {
+					vm.stacks.borrow_mut().regs.push(Slot::RData(self.gc_self()));
+					Ok(Some(R::from_val(&rfn.receive_call(1)?)?))
+				})
+			}
+			_ => Ok(None)
+		}
+	}
+
+	/**
+	Mutates the field or property bound to the given name.
+	
+	Equivalent to [`(= [rdata key] val)`](https://gamelisp.rs/std/set-access).
+	*/
+	pub fn set<S, V>(&self, key: S, val: V) -> GResult<()>
+	where
+		S: ToSym,
+		V: ToVal
+	{
+		let sym = key.to_sym()?;
+		ensure!(self.set_if_present(sym, val)?, "attempted to assign to nonexistent or \
+		        readonly prop '{}'", sym);
+		Ok(())
+	}
+
+	/**
+	Mutates the field or property bound to the given name, if any. Returns `true` if the
+	field or property exists.
+	
+	Equivalent to [`(= [rdata (? key)] val)`](https://gamelisp.rs/std/set-access).
+	*/
+	pub fn set_if_present<S, V>(&self, key: S, val: V) -> GResult<bool>
+	where
+		S: ToSym,
+		V: ToVal
+	{
+		let sym = key.to_sym()?;
+
+		match self.class.binding(sym) {
+			Some(RBinding::Prop(_, Some(rfn))) => {
+				with_vm(|vm| {
+					let mut stacks = vm.stacks.borrow_mut();
+					stacks.regs.push(Slot::RData(self.gc_self()));
+					stacks.regs.push(val.to_slot()?);
+					drop(stacks);
+
+					rfn.receive_call(2)?;
+					Ok(true)
+				})
+			}
+			_ => Ok(false)
+		}
+	}
+
+	/**
+	Invokes a method.
+	
+	Equivalent to [`(call-meth rdata key ..args)`](https://gamelisp.rs/std/call-meth).
+	*/
+	pub fn call<S, A, R>(&self, key: S, args: &A) -> GResult<R> 
+	where
+		S: ToSym,
+		A: ToCallArgs + ?Sized, 
+		R: FromVal
+	{
+		let sym = key.to_sym()?;
+		match self.call_if_present(sym, args)? {
+			Some(r) => Ok(r),
+			None => {
+				let alternatives = Some(self.class.describe_bindings(true));
+				Err(nonexistent_meth_error(sym, alternatives, Vec::new()))
+			}
+		}
+	}
+
+	/**
+	Invokes a method, if it exists.
+	
+	Equivalent to [`(call-meth rdata (? key) ..args)`](https://gamelisp.rs/std/call-meth).
+	*/
+	pub fn call_if_present<S, A, R>(&self, key: S, args: &A) -> GResult<Option<R>> 
+	where
+		S: ToSym,
+		A: ToCallArgs + ?Sized, 
+		R: FromVal
+	{
+		let sym = key.to_sym()?;
+
+		match self.class.binding(sym) {
+			Some(RBinding::Meth(rfn)) => {
+				with_vm(|vm| {
+					let mut stacks = vm.stacks.borrow_mut();
+					let starting_len = stacks.regs.len();
+
+					stacks.regs.push(Slot::RData(self.gc_self()));
+					args.to_call_args(&mut stacks.regs)?;
+
+					let arg_count = stacks.regs.len() - starting_len;
+					drop(stacks);
+
+					let val = rfn.receive_call(arg_count)?;
+					Ok(Some(R::from_val(&val)?))
+				})
+			}
+			_ => Ok(None)
+		}
+	}
+
+	/**
+	Returns `true` if the given name is bound to a method.
+	
+	Equivalent to [`(has-meth? rdata key)`](https://gamelisp.rs/std/has-meth-p).
+	*/
+	pub fn has_meth<S: ToSym>(&self, key: S) -> GResult<bool> {
+		let sym = key.to_sym()?;
+
+		match self.class.binding(sym) {
+			Some(RBinding::Meth(_)) => Ok(true),
+			_ => Ok(false)
+		}
+	}
+
+	//designed to imitate Obj::get_method(). used in vm.rs
+	pub(crate) fn get_method(&self, key: Sym) -> Option<(Slot, bool, bool, Slot)> {
+		match self.class.binding(key) {
+			Some(RBinding::Meth(rfn)) => Some((Slot::RFn(rfn), true, false, Slot::Nil)),
+			_ => None
+		}
+	}
+
+	//used in vm.rs to describe the alternatives when a method or a prop is missing
+	pub(crate) fn describe_bindings(&self, meths: bool) -> String {
+		self.class.describe_bindings(meths)
+	}
+
+	/**
+	Equivalent to [`(eq? self other)`](https://gamelisp.rs/std/eq-p).
+
+	Note that, because this may invoke an `op-eq?` method, it can potentially fail.
+
+	The same is true for `PartialEq` comparisons between `RData` using Rust's `==` operator.
+	In that case, if an error occurs, the operator will panic.
+	*/
+	pub fn try_eq(&self, other: &Root<RData>) -> GResult<bool> {
+		if !Rc::ptr_eq(&self.class, &other.class) {
+			return Ok(false)
+		}
+
+		let val: Option<Val> = self.call_if_present(OP_EQP_SYM, &[other])?;
+		match val {
+			Some(val) => Ok(val.is_truthy()),
+			None => Ok(false)
+		}
+	}
+}
+
+impl PartialEq<Root<RData>> for RData {
+	fn eq(&self, other: &Root<RData>) -> bool {
+		self.try_eq(other).unwrap()
+	}
+}
+
+/**
+A strongly-typed reference to an [`RData`](struct.RData.html).
+
+Equivalent to a [`Root<RData>`](struct.Root.html), but it enforces that the `RData` must contain
+a value of type `T`.
+
+`RRoot` tends to be more self-documenting than `Root<RData>`, and it has a slightly more
+convenient API.
+	
+	//using Root
+	let mesh = player_mesh.borrow::<Mesh>();
+	let mesh2 = enemy_mesh.take::<Mesh>();
+
+	//using RRoot
+	let mesh = player_mesh.borrow();
+	let mesh2 = enemy_mesh.take();
+*/
+
+pub struct RRoot<T: RStore>(Root<RData>, PhantomData<Rc<RefCell<T>>>);
+
+impl<T: RStore> Clone for RRoot<T> {
+	fn clone(&self) -> RRoot<T> {
+		RRoot(self.0.clone(), PhantomData)
+	}
+}
+
+impl<T: RStore> Debug for RRoot<T> {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		Debug::fmt(&self.0, f)
+	}
+}
+
+impl<T: RStore> Display for RRoot<T> {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		Display::fmt(&self.0, f)
+	}
+}
+
+impl<T: RStore> Pointer for RRoot<T> {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		Pointer::fmt(&self.0, f)
+	}
+}
+
+impl<T: RStore> RRoot<T> {
+	/**
+	Constructs an `RRoot<T>` from a `Root<RData>`.
+
+	Panics if the `RData`'s value does not belong to the type `T`.
+	*/
+	pub fn new(root: Root<RData>) -> RRoot<T> {
+		assert!(root.is::<T>(), "type mismatch when constructing an RRoot<{}>", T::type_name());
+		RRoot(root, PhantomData)
+	}
+
+	///Returns a copy of the underlying `Root<RData>`.
+	pub fn to_root(&self) -> Root<RData> {
+		self.0.clone()
+	}
+
+	///Drops the `RRoot`, returning the wrapped `Root<RData>`.
+	pub fn into_root(self) -> Root<RData> {
+		self.0
+	}
+
+	///Returns `true` if both `RRoots` refer to the same `RData`.
+	pub fn ptr_eq(rr0: &RRoot<T>, rr1: &RRoot<T>) -> bool {
+		Root::ptr_eq(&rr0.0, &rr1.0)
+	}
+
+	pub(crate) fn to_gc(&self) -> Gc<RData> {
+		self.0.to_gc()
+	}
+
+	#[allow(dead_code)]
+	pub(crate) fn into_gc(self) -> Gc<RData> {
+		self.0.into_gc()
+	}
+
+	///Equivalent to [`RData::borrow`](struct.RData.html#method.borrow).
+	#[track_caller]
+	pub fn borrow(&self) -> RRef<T> {
+		self.0.borrow()
+	}
+
+	///Equivalent to [`RData::borrow_mut`](struct.RData.html#method.borrow_mut).
+	#[track_caller]
+	pub fn borrow_mut(&self) -> RRefMut<T> {
+		self.0.borrow_mut()
+	}
+
+	///Equivalent to [`RData::try_borrow`](struct.RData.html#method.try_borrow).
+	#[track_caller]
+	pub fn try_borrow(&self) -> GResult<RRef<T>> {
+		self.0.try_borrow()
+	}
+
+	///Equivalent to [`RData::try_borrow_mut`](struct.RData.html#method.try_borrow_mut).
+	#[track_caller]
+	pub fn try_borrow_mut(&self) -> GResult<RRefMut<T>> {
+		self.0.try_borrow_mut()
+	}
+
+	///Equivalent to [`RData::take`](struct.RData.html#method.take).
+	pub fn take(&self) -> GResult<T> {
+		self.0.take()
+	}
+
+	///Equivalent to [`RData::free`](struct.RData.html#method.free).
+	pub fn free(&self) -> GResult<()> {
+		self.0.free()
+	}
+
+	///Equivalent to [`RData::is_freed`](struct.RData.html#method.is_freed).
+	pub fn is_freed(&self) -> bool {
+		self.0.is_freed()
+	}
+}
+
+
+//-------------------------------------------------------------------------------------------------
+// Span, SpanStorage and Frame
+//-------------------------------------------------------------------------------------------------
+
+//all arrs are tagged with a Span, indicating where they were created. we use this information 
+//for reporting line numbers when an error occurs.
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum SpanStorage {
+
+	//this arr was parsed from the text of a file which was passed to (load) or glsp.load(). 
+	//the Filename identifies the file, and the usize is the 1-indexed line number.
+	Loaded(Filename, usize),
+
+	//this arr was allocated within a macro expander. the Sym is the macro's name, the first Span 
+	//identifies the macro's callsite (i.e. the Span of the arr from which it was expanded), and
+	//the second Span identifies the form's construction site (i.e. the Span of the (arr) or
+	//(clone) call, within a macro expander, which created the form).
+	Expanded(Option<Sym>, Span, Span),
+
+	//a broad category for any other arr. in practice, arrs which aren't loaded from a file or
+	//created within a macro expander are very unlikely to be used as syntax. when they are,
+	//the user is unlikely to care exactly where they were generated.
+	Generated
+}
+
+#[doc(hidden)]
+#[derive(Default, PartialEq, Eq, Hash, Copy, Clone)]
+pub struct Span(u32);
+
+/**
+The source location which has been attached to a form, produced by
+[`glsp::span_of`](glsp/fn.span_of.html).
+
+Its `Display` implementation produces a brief description, such as `scripts/ecs.glsp:120`, 
+followed by the macros which generated the form, such as 
+`scripts/ecs.glsp:120, expanded from (query)`.
+*/
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SpanInfo {
+	location: Option<(String, usize)>,
+	expansions: Vec<Option<Sym>>
+}
+
+impl SpanInfo {
+	/**
+	Returns the name of the file which the form is attributed to.
+
+	For a form which was constructed by a macro, this is the file which contains the outermost
+	macro invocation. Returns `None` when the form can't be traced back to a file.
+	*/
+	pub fn filename(&self) -> Option<&str> {
+		self.location.as_ref().map(|(filename, _)| &filename[..])
+	}
+
+	/** Returns the 1-indexed line number which the form is attributed to, when it's known. */
+	pub fn line(&self) -> Option<usize> {
+		self.location.as_ref().map(|&(_, line)| line)
+	}
+
+	/**
+	Returns the name of each macro which took part in constructing the form, starting with the 
+	innermost.
+
+	Anonymous macros are represented by `None`. The slice is empty when the form was parsed
+	directly from a file, or attributed to a file using [`glsp::spanned`](glsp/fn.spanned.html).
+	*/
+	pub fn expansions(&self) -> &[Option<Sym>] {
+		&self.expansions[..]
+	}
+}
+
+impl Display for SpanInfo {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		match self.location {
+			Some((ref filename, line)) => write!(f, "{}:{}", filename, line)?,
+			None => write!(f, "generated code")?
+		}
+
+		for (i, expansion) in self.expansions.iter().enumerate() {
+			write!(f, "{}", if i == 0 { ", expanded from " } else { " within " })?;
+			match expansion {
+				Some(name) => write!(f, "({})", name)?,
+				None => write!(f, "an anonymous macro")?
+			}
+		}
+
+		Ok(())
+	}
+}
+
+
+//-------------------------------------------------------------------------------------------------
+// glsp:: functions
+//-------------------------------------------------------------------------------------------------
+
+pub mod glsp {
+	use super::*;
+
+	//---------------------------------------------------------------------------------------------
+	// syms
+	//---------------------------------------------------------------------------------------------
+
+	/** Equivalent to [`(sym name)`](https://gamelisp.rs/std/sym). */
+
+	pub fn sym(name: &str) -> GResult<Sym> {
+		ensure!(glsp::is_valid_sym_str(name), "invalid sym '{}'", name);
+		glsp::sym_impl(name, SymKind::Normal)
+	}
+
+	/**
+	Equivalent to [`(sym prefix suffix)`](https://gamelisp.rs/std/sym).
+
+	The symbol's name is `prefix` followed by the `Display` representation of `suffix`.
+
+		let sym = glsp::sym_with_prefix("player-", 3)?;
+		assert!(&*sym.name() == "player-3");
+	*/
+
+	pub fn sym_with_prefix<T: Display>(prefix: &str, suffix: T) -> GResult<Sym> {
+		let mut bytes = SmallVec::<[u8; 64]>::new();
+		write!(&mut bytes, "{}{}", prefix, suffix).unwrap();
+
+		glsp::sym(str::from_utf8(&bytes[..]).unwrap())
+	}
+
+	/**
+	Converts each string into a symbol, as though by calling [`glsp::sym`](fn.sym.html).
+
+	This is faster than calling `glsp::sym` in a loop, because the symbol table is only
+	accessed once.
+
+		let names = ["goblin-hp", "goblin-str", "goblin-dex"];
+		let syms = glsp::syms(&names)?;
+		assert!(&*syms[1].name() == "goblin-str");
+	*/
+
+	pub fn syms(names: &[&str]) -> GResult<Vec<Sym>> {
+		with_engine(|engine| {
+			let mut syms = engine.syms.borrow_mut();
+			let mut syms_map = engine.syms_map.borrow_mut();
+
+			let mut result = Vec::with_capacity(names.len());
+			for name in names {
+				ensure!(glsp::is_valid_sym_str(name), "invalid sym '{}'", name);
+				result.push(intern_sym(&mut syms, &mut syms_map, name, SymKind::Normal)?);
+			}
+
+			Ok(result)
+		})
+	}
+
+	/**
+	Returns the number of symbols which currently exist, including gensyms and the symbols
+	which are built in to GameLisp.
+
+	Symbols are never deallocated. A `Runtime` can store at most
+	[`glsp::MAX_SYM_COUNT`](constant.MAX_SYM_COUNT.html) symbols: beyond that point, functions
+	like [`glsp::sym`](fn.sym.html) will return an error, and
+	[`glsp::gensym`](fn.gensym.html) will panic.
+	*/
+
+	pub fn sym_count() -> usize {
+		with_engine(|engine| {
+			engine.syms.borrow().len()
+		})
+	}
+
+	/**
+	Returns an iterator over every symbol which currently exists, in the order they were
+	created.
+
+	The iterator won't include any symbols which are created after this function returns.
+	*/
+
+	pub fn sym_iter() -> impl Iterator<Item = Sym> {
+		(0 .. glsp::sym_count() as u32).map(Sym)
+	}
+
+	/**
+	The maximum number of symbols which can exist at once in a `Runtime`, including gensyms.
+
+	See [`glsp::sym_count`](fn.sym_count.html).
+	*/
+
+	pub const MAX_SYM_COUNT: usize = MAX_SYM as usize + 1;
+
+	fn sym_impl(name: &str, kind: SymKind) -> GResult<Sym> {
+		with_engine(|engine| {
+			let mut syms = engine.syms.borrow_mut();
+			let mut syms_map = engine.syms_map.borrow_mut();
+			intern_sym(&mut syms, &mut syms_map, name, kind)
+		})
+	}
+
+	//when the name has already been interned, which is the common case, this performs a single
+	//hash lookup without allocating
+	fn intern_sym(
+		syms: &mut Vec<SymEntry>,
+		syms_map: &mut HashMap<Rc<str>, Sym>,
+		name: &str,
+		kind: SymKind
+	) -> GResult<Sym> {
+		if let Some(sym) = syms_map.get(name) {
+			return Ok(*sym)
+		}
+
+		ensure!(syms.len() < MAX_SYM_COUNT, "symbol table full: {} syms", MAX_SYM_COUNT);
+
+		let name = Rc::<str>::from(name);
+		syms.push(SymEntry {
+			name: name.clone(),
+			kind,
+			bound_global: None,
+			bound_macro: None,
+			meta: Val::Nil
+		});
+
+		let sym = Sym((syms.len() - 1) as u32);
+		syms_map.insert(name, sym);
+
+		Ok(sym)
+	}
+
+	/** Equivalent to [`(valid-sym-str? st)`](https://gamelisp.rs/std/valid-sym-str-p). */
+
+	pub fn is_valid_sym_str(st: &str) -> bool {
+		//one or more of the valid sym chars, optionally with a '#' suffix
+		let mut rev_iter = st.chars().rev();
+		match rev_iter.next() {
+			None => false,
+			Some('#') => st.len() > 1 && rev_iter.all(|ch| lex::is_valid_sym_char(ch)),
+			Some(last_char) => {
+				lex::is_valid_sym_char(last_char) && rev_iter.all(|ch| {
+					lex::is_valid_sym_char(ch)
+				})
+			}
+		}
+	}
+
+	/** Equivalent to [`(valid-sym-char? ch)`](https://gamelisp.rs/std/valid-sym-char-p). */
+
+	pub fn is_valid_sym_char(ch: char) -> bool {
+		lex::is_valid_sym_char(ch)
+	}
+
+	/** Equivalent to [`(gensym)`](https://gamelisp.rs/std/gensym). */
+
+	pub fn gensym() -> Sym {
+		glsp::gensym_impl(None)
+	}
+
+	/** Equivalent to [`(gensym tag)`](https://gamelisp.rs/std/gensym). */
+
+	pub fn gensym_with_tag(tag: &str) -> GResult<Sym> {
+		ensure!(glsp::is_valid_sym_str(tag) && !tag.ends_with("#"),
+		        "invalid gensym tag '{}': tags should be a valid sym without a trailing '#'",
+		        tag);
+		Ok(glsp::gensym_impl(Some(tag)))
+	}
+
+	fn gensym_impl(tag: Option<&str>) -> Sym {
+		with_engine(|engine| {
+			let counter = engine.gensym_counter.get();
+			let seed = engine.gensym_seed.borrow();
+
+			let mut bytes = SmallVec::<[u8; 64]>::new();
+			write!(&mut bytes, "#<gs").unwrap();
+			if let Some(tag) = tag {
+				write!(&mut bytes, ":{}", tag).unwrap();
+			}
+			write!(&mut bytes, ":{}", counter).unwrap();
+			if let Some(ref seed) = *seed {
+				write!(&mut bytes, ":{}", seed).unwrap();
+			}
+			write!(&mut bytes, ">").unwrap();
+
+			engine.gensym_counter.set(counter + 1);
+			drop(seed);
+
+			//gensyms are infallible, so running out of symbols is treated as a fatal error, 
+			//similar to running out of memory
+			let name = str::from_utf8(&bytes[..]).unwrap();
+			match glsp::sym_impl(name, SymKind::Gensym) {
+				Ok(sym) => sym,
+				Err(_) => panic!("symbol table full: {} syms", MAX_SYM_COUNT)
+			}
+		})
+	}
+
+	//interns a gensym which was previously printed or serialized, e.g. by load_and_compile
+	#[cfg(feature = "serde")]
+	pub(crate) fn gensym_from_name(name: &str) -> GResult<Sym> {
+		ensure!(name.starts_with("#<gs") && name.ends_with(">"), "invalid gensym '{}'", name);
+		glsp::sym_impl(name, SymKind::Gensym)
+	}
+
+	/** Equivalent to [`(sym-meta s)`](https://gamelisp.rs/std/sym-meta). */
+
+	pub fn sym_meta(sym: Sym) -> Val {
+		with_engine(|engine| {
+			engine.syms.borrow()[sym.0 as usize].meta.clone()
+		})
+	}
+
+	/** Equivalent to [`(set-sym-meta! s val)`](https://gamelisp.rs/std/set-sym-meta-mut). */
+
+	pub fn set_sym_meta<T: ToVal>(sym: Sym, t: T) -> GResult<()> {
+		let val = t.to_val()?;
+
+		//the previous metadata is dropped after the syms database has been unborrowed
+		let prev = with_engine(|engine| {
+			let mut syms = engine.syms.borrow_mut();
+			std::mem::replace(&mut syms[sym.0 as usize].meta, val)
+		});
+
+		drop(prev);
+		Ok(())
+	}
+
+	/**
+	Makes future gensyms globally unique.
+
+	When code is expanded in one Runtime and then evaluated in another (as for the 
+	[`compile!`](macro.compile.html) and [`eval!`](macro.eval.html) macros, and the
+	[`glsp::load_and_compile`](fn.load_and_compile.html) function), gensym collisions may occur,
+	because the gensym counter will have been reset.
+
+	`glsp::seed_gensym` mitigates this by appending a 128-bit ID (derived from the current
+	wall-clock time) to all future gensyms produced by this `Runtime`. We don't switch it on by
+	default, because it makes gensyms' printed representation harder to read. However, it's
+	automatically switched on within [`glsp::load_and_compile`](fn.load_and_compile.html).
+
+	The `wasm32` target has no wall-clock time, so the ID is derived from a counter instead. It's 
+	unique within the current thread, but not across separate runs of the program.
+
+		prn!("{}", glsp::gensym()); //prints #<gs:0>
+		glsp::seed_gensym();
+		prn!("{}", glsp::gensym()); //prints #<gs:1:wTz8iriBJYB>
+	*/
+
+	pub fn seed_gensym() {
+		with_engine(|engine| {
+			//this seems to have at least 100ns resolution
+			#[cfg(not(target_arch = "wasm32"))]
+			let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+
+			//SystemTime::now() panics on wasm32
+			#[cfg(target_arch = "wasm32")]
+			let nanos = {
+				thread_local! {
+					static SEED_COUNTER: Cell<u128> = Cell::new(1);
+				}
+
+				SEED_COUNTER.with(|counter| counter.replace(counter.get() + 1))
+			};
+
+			//to keep it as concise as possible, we use base64
+			static CHARS: [char; 64] = [
+				'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M', 
+				'N', 'O', 'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z', 
+				'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 
+				'n', 'o', 'p', 'q', 'r', 's', 't', 'u', 'v', 'w', 'x', 'y', 'z',
+				'0', '1', '2', '3', '4', '5', '6', '7', '8', '9', '+', '_'
+			];
+
+			//this reverses the order of the characters, but it doesn't matter
+			let mut seed = String::new();
+			let mut bits = nanos;
+			while bits > 0 {
+				seed.push(CHARS[(bits & 0x3f) as usize]);
+				bits >>= 6;
+			}
+
+			*engine.gensym_seed.borrow_mut() = Some(seed);
+		})
+	}
+
+	//---------------------------------------------------------------------------------------------
+	// globals
+	//---------------------------------------------------------------------------------------------
+
+	/** Equivalent to [`(global s)`](https://gamelisp.rs/std/global). */
+
+	pub fn global<S, T>(s: S) -> GResult<T> 
+	where
+		S: ToSym, 
+		T: FromVal
+	{
+		with_engine(|engine| {
+			let sym = s.to_sym()?;
+
+			let syms = engine.syms.borrow();
+			let entry = &syms[sym.0 as usize];
+			match entry.bound_global {
+				Some(ref global) => T::from_val(&global.val),
+				None => Err(unbound_global_error(&entry.name))
+			}
+		})
+	}
+
+	pub(crate) fn try_global<S, T>(s: S) -> GResult<Option<T>> 
+	where
+		S: ToSym, 
+		T: FromVal
+	{
+		with_engine(|engine| {
+			let sym = s.to_sym()?;
+			match engine.syms.borrow()[sym.0 as usize].bound_global {
+				Some(ref global) => T::from_val(&global.val).map(|t| Some(t)),
+				None => Ok(None)
+			}
+		})
+	}
+
+	//used by the LoadGlobal inline cache in vm.rs. returns the global's value, and whether it
+	//may be cached. we only cache callable values: globals which store other data are often 
+	//mutated in a hot loop, and each mutation of a cached global would invalidate every cache.
+	pub(crate) fn try_global_for_cache(sym: Sym) -> Option<(Slot, bool)> {
+		with_engine(|engine| {
+			match engine.syms.borrow_mut()[sym.0 as usize].bound_global {
+				Some(ref mut global) => {
+					let slot = Slot::from_val(&global.val);
+					let cacheable = match slot {
+						Slot::GFn(_) | Slot::RFn(_) | Slot::Class(_) => true,
+						_ => false
+					};
+
+					if cacheable {
+						global.cached = true;
+					}
+
+					Some((slot, cacheable))
+				}
+				None => None
+			}
+		})
+	}
+
+	/** Equivalent to [`(= (global s) val)`](https://gamelisp.rs/std/set-global). */
+
+	pub fn set_global<S, T>(s: S, val: T) -> GResult<()>
+	where
+		S: ToSym,
+		T: ToVal
+	{
+		with_engine(|engine| {
+			let sym = s.to_sym()?;
+			let val = val.to_val()?;
+
+			let mut syms = engine.syms.borrow_mut();
+			let entry = &mut syms[sym.0 as usize];
+
+			match entry.bound_global {
+				Some(ref mut global) => {
+					if global.frozen {
+						let name = entry.name.clone();
+						drop(global);
+						drop(entry);
+						drop(syms);
+						return Err(frozen_global_error(name))
+					}
+
+					if global.cached {
+						global.cached = false;
+						engine.vm.invalidate_global_caches();
+					}
+
+					global.val = val;
+					Ok(())
+				}
+				None => {
+					let name = entry.name.clone();
+					drop(entry);
+					drop(syms);
+					Err(unbound_global_error(name))
+				}
+			}
+		})
+	}
+
+	/**
+	Overrides the value of a global variable while `f` is running.
+
+	The global's previous value is restored when `f` returns, even if it returns an `Err` or 
+	panics. It's an error if the global is unbound or [frozen](fn.freeze_global.html).
+
+		glsp::with_global("*log-level*", glsp::sym("debug")?, || {
+			glsp::call(&update_level, &(level,))
+		})?;
+
+	Scripts can do the same thing using [`with-globals`](https://gamelisp.rs/std/with-globals).
+	*/
+
+	pub fn with_global<S, T, R, F>(s: S, val: T, f: F) -> GResult<R>
+	where
+		S: ToSym,
+		T: ToVal,
+		F: FnOnce() -> GResult<R>
+	{
+		let sym = s.to_sym()?;
+		let outer_value: Val = glsp::global(sym)?;
+		glsp::set_global(sym, val)?;
+
+		let _guard = Guard::new(|| {
+			let _ = glsp::set_global(sym, &outer_value);
+		});
+
+		f()
+	}
+
+	pub(crate) enum TrySetGlobalOutcome {
+		Success,
+		NotBound,
+		Frozen
+	}
+
+	pub(crate) fn try_set_global<S, T>(s: S, t: T) -> GResult<TrySetGlobalOutcome>
+	where
+		S: ToSym, 
+		T: ToVal
+	{
+		with_engine(|engine| {
+			let sym = s.to_sym()?;
+			let val = t.to_val()?;
+
+			let mut syms = engine.syms.borrow_mut();
+			let entry = &mut syms[sym.0 as usize];
+
+			match entry.bound_global {
+				Some(ref mut global) => {
+					if global.frozen {
+						return Ok(TrySetGlobalOutcome::Frozen)
+					}
+
+					if global.cached {
+						global.cached = false;
+						engine.vm.invalidate_global_caches();
+					}
+
+					global.val = val;
+					Ok(TrySetGlobalOutcome::Success)
+				}
+				None => Ok(TrySetGlobalOutcome::NotBound)
+			}
+		})
+	}
+
+	/** Equivalent to [`(freeze-global! s)`](https://gamelisp.rs/std/freeze-global-mut). */
+
+	pub fn freeze_global<S>(s: S) -> GResult<()>
+	where
+		S: ToSym
+	{
+		with_engine(|engine| {
+			let sym = s.to_sym()?;
+
+			let mut syms = engine.syms.borrow_mut();
+			let entry = &mut syms[sym.0 as usize];
+
+			match entry.bound_global {
+				Some(ref mut global) => {
+					global.frozen = true;
+					Ok(())
+				}
+				None => {
+					let name = entry.name.clone();
+					drop(entry);
+					drop(syms);
+					bail!("attempted to freeze the unbound global {}", name)
+				}
+			}
+		})
+	}
+
+	//freezes each global which might be caught by the op-transforms pass, like "+". we can't do
+	//this in the Engine constructor because the stdlib initialization code needs to be able to
+	//bind new rfns to these syms.
+	#[doc(hidden)]
+	pub fn freeze_transform_fns() {
+		with_engine(|engine| {
+			let mut syms = engine.syms.borrow_mut();
+
+			for sym in STOCK_SYMS_BY_KIND[2] {
+				let entry = &mut syms[sym.0 as usize];
+
+				if entry.kind != SymKind::StockTransform {
+					panic!()
+				}
+
+				if let Some(ref mut global) = entry.bound_global {
+					global.frozen = true;
+				} else {
+					panic!("transform fn {} is not bound", entry.name)
+				}
+			}
+		})
+	}
+
+	/**
+	Looks up a value which is nested within a global variable.
+
+	`path` is a list of segments separated by `.` characters. The first segment names the
+	global. Each subsequent segment indexes into the previous value: a tab using a symbol key,
+	an arr using an integer index, or an obj using a field name. A segment which parses as an
+	integer is always used as an integer key.
+
+	Returns an error which names the failing segment if any part of the path is missing, or if
+	the value can't be converted to `T`.
+
+		let msaa: u8 = glsp::global_path("config.graphics.msaa")?;
+		let first_level: Root<Str> = glsp::global_path("levels.0.name")?;
+	*/
+
+	pub fn global_path<T: FromVal>(path: &str) -> GResult<T> {
+		let (global_sym, keys) = parse_global_path(path)?;
+
+		let mut val: Val = match glsp::try_global(global_sym)? {
+			Some(val) => val,
+			None => bail!("the global '{}' is unbound", global_sym)
+		};
+
+		for (i, key) in keys.iter().enumerate() {
+			val = match glsp::get_in(&val, &keys[i .. i + 1]) {
+				Ok(Some(next)) => next,
+				Ok(None) => bail!("{} has no {}", global_path_prefix(path, i), describe_key(key)),
+				Err(err) => {
+					let prefix = global_path_prefix(path, i);
+					return Err(error!("unable to index {} with {}", prefix, key).with_source(err))
+				}
+			};
+		}
+
+		T::from_val(&val).map_err(|err| {
+			error!("{} has an unexpected type", path).with_source(err)
+		})
+	}
+
+	/**
+	Mutates a value which is nested within a global variable.
+
+	The `path` syntax is the same as [`glsp::global_path`](fn.global_path.html). A path with a
+	single segment is equivalent to [`glsp::set_global`](fn.set_global.html). Otherwise, every
+	segment except the last must already exist: a missing tab key is inserted, but an obj 
+	field or arr index must be present.
+
+		glsp::set_global_path("config.graphics.msaa", 4)?;
+	*/
+
+	pub fn set_global_path<T: ToVal>(path: &str, val: T) -> GResult<()> {
+		let (global_sym, keys) = parse_global_path(path)?;
+		if keys.len() == 0 {
+			return glsp::set_global(global_sym, val)
+		}
+
+		let parent_end = path.rfind('.').unwrap();
+		let parent: Val = glsp::global_path(&path[..parent_end])?;
+		let key = keys.last().unwrap();
+
+		glsp::set_in(&parent, &[key.clone()], &val.to_val()?).map_err(|err| {
+			error!("unable to set {}", path).with_source(err)
+		})
+	}
+
+	/**
+	Looks up a value which is nested within a collection.
+
+	Equivalent to [`(get-in coll path)`](https://gamelisp.rs/std/get-in), except that it 
+	returns `None` if a key is missing. Returns an error if something other than a tab, arr,
+	obj, rec, parr, ptab, view, vec2, vec3 or mat3 would be indexed, or if a key has the wrong 
+	type for its collection.
+	*/
+
+	pub fn get_in(coll: &Val, path: &[Val]) -> GResult<Option<Val>> {
+		let mut val = coll.clone();
+		for key in path {
+			let next = match val {
+				Val::Tab(ref tab) => tab.get_if_present(key)?,
+				Val::Arr(ref arr) => {
+					match *key {
+						Val::Int(i) if (i as isize) < arr.len() as isize && 
+						               (i as isize) >= -(arr.len() as isize) => {
+							Some(arr.get(i)?)
+						}
+						Val::Int(_) => None,
+						ref key => bail!("attempted to index an arr with {}", key.a_type_name())
+					}
+				}
+				Val::Obj(ref obj) => {
+					match *key {
+						Val::Sym(sym) => obj.get_if_present(sym)?,
+						ref key => bail!("attempted to index an obj with {}", key.a_type_name())
+					}
+				}
+				Val::Rec(ref rec) => {
+					match *key {
+						Val::Sym(sym) => rec.get_if_present(sym)?,
+						ref key => bail!("attempted to index a rec with {}", key.a_type_name())
+					}
+				}
+				Val::PArr(ref parr) => {
+					match *key {
+						Val::Int(i) if (i as isize) < parr.len() as isize && 
+						               (i as isize) >= -(parr.len() as isize) => {
+							Some(parr.get(i as isize)?)
+						}
+						Val::Int(_) => None,
+						ref key => bail!("attempted to index a parr with {}", key.a_type_name())
+					}
+				}
+				Val::PTab(ref ptab) => ptab.get_if_present(key)?,
+				Val::View(ref view) => {
+					match *key {
+						Val::Int(i) if view.index().is_none() && (i as isize) < view.len() as isize &&
+						               (i as isize) >= -(view.len() as isize) => {
+							Some(Val::View(view.element(i as isize)?))
+						}
+						Val::Int(_) if view.index().is_none() => None,
+						Val::Sym(sym) if view.index().is_some() => {
+							if view.has(sym)? { Some(view.get(sym)?) } else { None }
+						}
+						ref key => bail!("attempted to index a view with {}", key.a_type_name())
+					}
+				}
+				Val::Vec2(ref v) => {
+					match *key {
+						Val::Int(_) | Val::Sym(_) if !v.has(key) => None,
+						ref key => Some(Val::Flo(v.get(key)?))
+					}
+				}
+				Val::Vec3(ref v) => {
+					match *key {
+						Val::Int(_) | Val::Sym(_) if !v.has(key) => None,
+						ref key => Some(Val::Flo(v.get(key)?))
+					}
+				}
+				Val::Mat3(ref m) => {
+					match *key {
+						Val::Int(_) if !m.has(key) => None,
+						ref key => Some(Val::Vec3(m.get(key)?))
+					}
+				}
+				ref val => bail!("attempted to index {} with {}", val.a_type_name(), key)
+			};
+
+			match next {
+				Some(next) => val = next,
+				None => return Ok(None)
+			}
+		}
+
+		Ok(Some(val))
+	}
+
+	/**
+	Mutates a value which is nested within a collection.
+
+	Equivalent to [`(set-in! coll path val)`](https://gamelisp.rs/std/set-in-mut).
+	*/
+
+	pub fn set_in(coll: &Val, path: &[Val], val: &Val) -> GResult<()> {
+		ensure!(path.len() > 0, "set-in! requires a non-empty path");
+
+		let (key, parent_path) = path.split_last().unwrap();
+		let parent = match glsp::get_in(coll, parent_path)? {
+			Some(parent) => parent,
+			None => bail!("the path {} does not exist", path_to_string(parent_path))
+		};
+
+		match parent {
+			Val::Tab(ref tab) => tab.set(key, val),
+			Val::Arr(ref arr) => {
+				match *key {
+					Val::Int(i) => arr.set(i, val),
+					ref key => bail!("attempted to index an arr with {}", key.a_type_name())
+				}
+			}
+			Val::Obj(ref obj) => {
+				match *key {
+					Val::Sym(sym) => obj.set(sym, val),
+					ref key => bail!("attempted to index an obj with {}", key.a_type_name())
+				}
+			}
+			Val::Rec(ref rec) => {
+				match *key {
+					Val::Sym(sym) => rec.set(sym, val),
+					ref key => bail!("attempted to index a rec with {}", key.a_type_name())
+				}
+			}
+			Val::PArr(_) => bail!("attempted to mutate a parr, which is persistent"),
+			Val::PTab(_) => bail!("attempted to mutate a ptab, which is persistent"),
+			Val::View(ref view) => {
+				match *key {
+					Val::Sym(sym) => view.set(sym, val),
+					ref key => bail!("attempted to index a view with {}", key.a_type_name())
+				}
+			}
+			Val::Vec2(_) | Val::Vec3(_) | Val::Mat3(_) => {
+				bail!("attempted to mutate {}, which is immutable", parent.a_type_name())
+			}
+			ref parent => bail!("attempted to index {} with {}", parent.a_type_name(), key)
+		}
+	}
+
+	/** Equivalent to [`(has-global? s)`](https://gamelisp.rs/std/has-global-p). */
+
+	pub fn has_global<S>(s: S) -> GResult<bool>
+	where
+		S: ToSym
+	{
+		with_engine(|engine| {
+			let sym = s.to_sym()?;
+			Ok(engine.syms.borrow()[sym.0 as usize].bound_global.is_some())
+		})
+	}
+
+	/** Equivalent to [`(bind-global! s)`](https://gamelisp.rs/std/bind-global-mut). */
+
+	pub fn bind_global<S, T>(s: S, t: T) -> GResult<()>
+	where
+		S: ToSym, 
+		T: ToVal
+	{
+		with_engine(|engine| {
+			let sym = s.to_sym()?;
+			ensure!(sym.is_bindable(), "unable to bind name '{}' to global", sym);
+
+			let val = t.to_val()?;
+
+			let mut syms = engine.syms.borrow_mut();
+			let entry = &mut syms[sym.0 as usize];
+
+			if entry.bound_global.is_none() {
+				entry.bound_global = Some(GlobalEntry {
+					val,
+					frozen: false,
+					constant: false,
+					cached: false
+				});
+
+				Ok(())
+			} else if engine.reloading.get() && !entry.bound_global.as_ref().unwrap().frozen {
+				//during glsp::reload, definitions replace the existing binding
+				let global = entry.bound_global.as_mut().unwrap();
+				if global.cached {
+					engine.vm.invalidate_global_caches();
+				}
+
+				*global = GlobalEntry {
+					val,
+					frozen: false,
+					constant: false,
+					cached: false
+				};
+
+				Ok(())
+			} else {
+				let name = entry.name.clone();
+				drop(entry);
+				drop(syms);
+				bail!("attempted to bind the global '{}', which is already bound", name)
+			}
+		})
+	}
+
+	/**
+	Binds a constant global.
+
+	Equivalent to [`(defconst s val)`](https://gamelisp.rs/std/defconst), except that the name
+	isn't qualified with the current module's name.
+
+	A constant can't be [mutated](fn.set_global.html), [unbound](fn.del_global.html) or rebound,
+	except by [`glsp::reload`](fn.reload.html) or [`glsp::redefine_const`](fn.redefine_const.html).
+	When the compiler encounters a constant whose value is `#n`, a bool, a number, a char, a 
+	sym or a frozen str, it may inline that value into the code which refers to it.
+	*/
+
+	pub fn bind_const<S, T>(s: S, t: T) -> GResult<()>
+	where
+		S: ToSym, 
+		T: ToVal
+	{
+		with_engine(|engine| {
+			let sym = s.to_sym()?;
+			ensure!(sym.is_bindable(), "unable to bind name '{}' to global", sym);
+
+			let val = t.to_val()?;
+
+			let mut syms = engine.syms.borrow_mut();
+			let entry = &mut syms[sym.0 as usize];
+
+			let replaceable = match entry.bound_global {
+				None => true,
+				Some(ref global) => engine.reloading.get() && (global.constant || !global.frozen)
+			};
+
+			if !replaceable {
+				let name = entry.name.clone();
+				drop(syms);
+				bail!("attempted to bind the global '{}', which is already bound", name)
+			}
+
+			if matches!(entry.bound_global, Some(GlobalEntry { cached: true, .. })) {
+				engine.vm.invalidate_global_caches();
+			}
+
+			entry.bound_global = Some(GlobalEntry {
+				val,
+				frozen: true,
+				constant: true,
+				cached: false
+			});
+
+			#[cfg(feature = "compiler")]
+			engine.compile_cache.borrow_mut().note_const_provider(sym);
+
+			Ok(())
+		})
+	}
+
+	/** Equivalent to [`(const? s)`](https://gamelisp.rs/std/const-p). */
+
+	pub fn is_const<S>(s: S) -> GResult<bool>
+	where
+		S: ToSym
+	{
+		with_engine(|engine| {
+			let sym = s.to_sym()?;
+			let syms = engine.syms.borrow();
+			let global = &syms[sym.0 as usize].bound_global;
+			Ok(matches!(*global, Some(GlobalEntry { constant: true, .. })))
+		})
+	}
+
+	/**
+	Replaces the value of a [constant](fn.bind_const.html).
+
+	Equivalent to [`(redefine-const! s val)`](https://gamelisp.rs/std/redefine-const-mut).
+
+	Code which was compiled while the constant had its previous value may have inlined that
+	value, so it won't observe the change until it's recompiled.
+	*/
+
+	pub fn redefine_const<S, T>(s: S, t: T) -> GResult<()>
+	where
+		S: ToSym, 
+		T: ToVal
+	{
+		with_engine(|engine| {
+			let sym = s.to_sym()?;
+			let val = t.to_val()?;
+
+			let mut syms = engine.syms.borrow_mut();
+			let entry = &mut syms[sym.0 as usize];
+
+			match entry.bound_global {
+				Some(ref mut global) if global.constant => {
+					if global.cached {
+						global.cached = false;
+						engine.vm.invalidate_global_caches();
+					}
+
+					global.val = val;
+
+					#[cfg(feature = "compiler")]
+					engine.compile_cache.borrow_mut().note_const_provider(sym);
+
+					Ok(())
+				}
+				_ => {
+					let name = entry.name.clone();
+						drop(syms);
+					bail!("attempted to redefine '{}', which isn't a constant", name)
+				}
+			}
+		})
+	}
+
+	//the value which the compiler should use in place of a reference to the global `sym`, if any
+	pub(crate) fn inlined_const(sym: Sym) -> Option<Val> {
+		with_engine(|engine| {
+			let syms = engine.syms.borrow();
+			match syms[sym.0 as usize].bound_global {
+				Some(GlobalEntry { constant: true, ref val, .. }) => {
+					match *val {
+						Val::Nil | Val::Bool(_) | Val::Int(_) | Val::Flo(_) | 
+						Val::Char(_) | Val::Sym(_) => Some(val.clone()),
+						Val::Str(ref st) if st.is_frozen() => Some(val.clone()),
+						_ => None
+					}
+				}
+				_ => None
+			}
+		})
+	}
+
+	/** Equivalent to [`(del-global! s)`](https://gamelisp.rs/std/del-global-mut). */
+
+	pub fn del_global<S>(s: S) -> GResult<()>
+	where
+		S: ToSym
+	{
+		with_engine(|engine| {
+			let sym = s.to_sym()?;
+
+			let mut syms = engine.syms.borrow_mut();
+			let entry = &mut syms[sym.0 as usize];
+
+			if let Some(ref mut global) = entry.bound_global {
+				ensure!(!global.frozen, "attempted to unbind '{}', which is frozen", entry.name);
+
+				if global.cached {
+					engine.vm.invalidate_global_caches();
+				}
+
+				entry.bound_global = None;
+				Ok(())
+			} else {
+				let name = entry.name.clone();
+				drop(entry);
+				drop(syms);
+				bail!("attempted to unbind '{}', which is not bound to a global", name)
+			}
+		})
+	}
+
+	/**
+	Records the current binding of every global and macro.
+
+	Equivalent to [`(snapshot-globals)`](https://gamelisp.rs/std/snapshot-globals).
+	*/
+
+	pub fn global_snapshot() -> GlobalSnapshot {
+		with_engine(|engine| {
+			let syms = engine.syms.borrow();
+
+			let mut bindings = Vec::new();
+			for (i, entry) in syms.iter().enumerate() {
+				if entry.bound_global.is_some() || entry.bound_macro.is_some() {
+					bindings.push((
+						Sym(i as u32),
+						entry.bound_global.as_ref().map(|global| global.val.clone()),
+						entry.bound_macro.clone()
+					));
+				}
+			}
+
+			GlobalSnapshot {
+				engine_id: engine.id,
+				bindings
+			}
+		})
+	}
+
+	/**
+	Rebinds every global and macro to the value it had when `snapshot` was taken.
+
+	Globals and macros which were bound after the snapshot are unbound. Frozen globals are left
+	as they are. Symbols remain interned, even if they were created after the snapshot.
+
+	Equivalent to [`(restore-globals! snapshot)`](https://gamelisp.rs/std/restore-globals-mut).
+	*/
+
+	pub fn restore_globals(snapshot: &GlobalSnapshot) -> GResult<()> {
+		with_engine(|engine| {
+			ensure!(snapshot.engine_id == engine.id,
+			        "attempted to restore a GlobalSnapshot from a different Runtime");
+
+			let mut syms = engine.syms.borrow_mut();
+			let mut bindings = snapshot.bindings.iter().peekable();
+
+			for (i, entry) in syms.iter_mut().enumerate() {
+				let (global, mac) = match bindings.peek() {
+					Some(&&(sym, ref global, ref mac)) if sym.0 as usize == i => {
+						bindings.next();
+						(global.clone(), mac.clone())
+					}
+					_ => (None, None)
+				};
+
+				let frozen = entry.bound_global.as_ref().map_or(false, |global| global.frozen);
+				if !frozen {
+					entry.bound_global = global.map(|val| {
+						GlobalEntry {
+							val,
+							frozen: false,
+							constant: false,
+							cached: false
+						}
+					});
+				}
+
+				entry.bound_macro = mac;
+			}
+
+			//any inline cache might refer to a global which has just been rebound
+			engine.vm.invalidate_global_caches();
+
+			Ok(())
+		})
+	}
+
+	/**
+	Records the state of the active `Runtime`, so that it can be restored later using
+	[`glsp::restore_runtime`](fn.restore_runtime.html).
+
+	This is intended for isolating tests from one another. Loading a large prelude once, taking
+	a snapshot, and restoring the snapshot before each test, is much faster than constructing
+	a new `Runtime` for each test.
+
+	The snapshot records every global, macro and symbol metadata binding, the contents of every
+	array, string, table, object, iterator and coroutine on the heap, and the files which have
+	been loaded or required. It doesn't record the state of any Rust data, including
+	[`RData`](struct.RData.html), the [libs](fn.add_lib.html) which have been added to the
+	`Runtime`, or the data captured by an [`RFn`](struct.RFn.html).
+
+	Returns an error if GameLisp code is currently executing.
+	*/
+
+	pub fn runtime_snapshot() -> GResult<RuntimeSnapshot> {
+		with_engine(|engine| {
+			ensure!(engine.vm.is_idle(), "attempted to snapshot a Runtime while it's running");
+
+			let syms = engine.syms.borrow().iter().map(|entry| {
+				SavedSym {
+					global: entry.bound_global.as_ref().map(|global| {
+						(global.val.clone(), global.frozen, global.constant)
+					}),
+					mac: entry.bound_macro.clone(),
+					meta: entry.meta.clone()
+				}
+			}).collect();
+
+			let objects = engine.heap.reachable_objects().iter().filter_map(|erased| {
+				Some(match *erased {
+					ErasedGc::Arr(ref arr) => {
+						SavedObject::Arr(arr.root(), arr.save_contents(), arr.header().frozen())
+					}
+					ErasedGc::Str(ref st) => {
+						SavedObject::Str(st.root(), st.save_contents(), st.header().frozen())
+					}
+					ErasedGc::Tab(ref tab) => {
+						SavedObject::Tab(tab.root(), tab.save_contents(), tab.header().frozen())
+					}
+					ErasedGc::Obj(ref obj) => {
+						SavedObject::Obj(obj.root(), obj.save_contents(), obj.header().frozen())
+					}
+					ErasedGc::Rec(ref rec) => {
+						SavedObject::Rec(rec.root(), rec.save_contents(), rec.header().frozen())
+					}
+					ErasedGc::GIter(ref giter) => {
+						SavedObject::GIter(giter.root(), giter.state.borrow().clone())
+					}
+					ErasedGc::Stay(ref stay) => SavedObject::Stay(stay.root(), stay.get()),
+					ErasedGc::Coro(ref coro) => {
+						let storage = coro.storage.borrow().clone();
+						SavedObject::Coro(coro.root(), coro.state.get(), storage)
+					}
+					ErasedGc::Class(ref class) => SavedObject::Immutable(Val::Class(class.root())),
+					ErasedGc::GFn(ref gfn) => SavedObject::Immutable(Val::GFn(gfn.root())),
+					ErasedGc::RData(ref rdata) => SavedObject::Immutable(Val::RData(rdata.root())),
+					ErasedGc::PArr(ref parr) => SavedObject::Immutable(Val::PArr(parr.root())),
+					ErasedGc::PTab(ref ptab) => SavedObject::Immutable(Val::PTab(ptab.root())),
+					ErasedGc::View(ref view) => SavedObject::Immutable(Val::View(view.root())),
+					ErasedGc::Vec2(ref v) => SavedObject::Immutable(Val::Vec2(v.root())),
+					ErasedGc::Vec3(ref v) => SavedObject::Immutable(Val::Vec3(v.root())),
+					ErasedGc::Mat3(ref m) => SavedObject::Immutable(Val::Mat3(m.root())),
+					ErasedGc::Bytecode(_) | ErasedGc::Lambda(_) => return None
+				})
+			}).collect();
+
+			Ok(RuntimeSnapshot {
+				engine_id: engine.id,
+				syms,
+				objects,
+				required: engine.required.borrow().clone(),
+				loaded_files: engine.loaded_files.borrow().clone(),
+				file_defers: engine.file_defers.borrow().clone(),
+				modules: engine.modules.borrow().clone(),
+				lazy_storage: engine.lazy_storage.borrow().clone()
+			})
+		})
+	}
+
+	/**
+	Returns the active `Runtime` to the state it was in when `snapshot` was taken.
+
+	Every object which existed when the snapshot was taken has its contents restored, including
+	whether it's frozen. Objects which were allocated after the snapshot are not modified, but 
+	once they've been detached from the restored state, they'll be garbage-collected. Frozen
+	globals are restored, too. Symbols remain interned, even if they were created after the
+	snapshot. Any toplevel [`defer`](https://gamelisp.rs/std/defer) forms which were pending 
+	when the snapshot was taken become pending again, and any which have been registered since 
+	then are discarded without being run.
+
+	A snapshot can be restored any number of times.
+
+	Returns an error if GameLisp code is currently executing, or if `snapshot` was taken from
+	a different `Runtime`.
+	*/
+
+	pub fn restore_runtime(snapshot: &RuntimeSnapshot) -> GResult<()> {
+		with_engine(|engine| {
+			ensure!(snapshot.engine_id == engine.id,
+			        "attempted to restore a RuntimeSnapshot from a different Runtime");
+			ensure!(engine.vm.is_idle(), "attempted to restore a Runtime while it's running");
+
+			let heap = &engine.heap;
+			for object in &snapshot.objects {
+				match *object {
+					SavedObject::Arr(ref arr, ref saved, frozen) => {
+						heap.replace_contents(&**arr, || arr.restore_contents(saved));
+						arr.header().set_frozen(frozen);
+					}
+					SavedObject::Str(ref st, ref saved, frozen) => {
+						heap.replace_contents(&**st, || st.restore_contents(saved));
+						st.header().set_frozen(frozen);
+					}
+					SavedObject::Tab(ref tab, ref saved, frozen) => {
+						heap.replace_contents(&**tab, || tab.restore_contents(saved));
+						tab.header().set_frozen(frozen);
+					}
+					SavedObject::Obj(ref obj, ref saved, frozen) => {
+						heap.replace_contents(&**obj, || obj.restore_contents(saved));
+						obj.header().set_frozen(frozen);
+					}
+					SavedObject::Rec(ref rec, ref saved, frozen) => {
+						heap.replace_contents(&**rec, || rec.restore_contents(saved));
+						rec.header().set_frozen(frozen);
+					}
+					SavedObject::GIter(ref giter, ref saved) => {
+						heap.replace_contents(&**giter, || {
+							*giter.state.borrow_mut() = saved.clone();
+						});
+					}
+					SavedObject::Stay(ref stay, ref saved) => stay.set(saved.clone()),
+					SavedObject::Coro(ref coro, state, ref saved) => {
+						heap.replace_contents(&**coro, || {
+							coro.state.set(state);
+							coro.storage.borrow_mut().clone_from(saved);
+						});
+					}
+					SavedObject::Immutable(_) => ()
+				}
+			}
+
+			let mut syms = engine.syms.borrow_mut();
+			for (i, entry) in syms.iter_mut().enumerate() {
+				match snapshot.syms.get(i) {
+					Some(saved) => {
+						entry.bound_global = saved.global.as_ref().map(|&(ref val, frozen, constant)| {
+							GlobalEntry {
+								val: val.clone(),
+								frozen,
+								constant,
+								cached: false
+							}
+						});
+						entry.bound_macro = saved.mac.clone();
+						entry.meta = saved.meta.clone();
+					}
+					None => {
+						entry.bound_global = None;
+						entry.bound_macro = None;
+						entry.meta = Val::Nil;
+					}
+				}
+			}
+			drop(syms);
+
+			*engine.required.borrow_mut() = snapshot.required.clone();
+			*engine.loaded_files.borrow_mut() = snapshot.loaded_files.clone();
+			*engine.file_defers.borrow_mut() = snapshot.file_defers.clone();
+			*engine.modules.borrow_mut() = snapshot.modules.clone();
+			*engine.lazy_storage.borrow_mut() = snapshot.lazy_storage.clone();
+
+			//every global has been rebound, so any inline cache might be stale
+			engine.vm.invalidate_global_caches();
+
+			Ok(())
+		})
+	}
+
+	//---------------------------------------------------------------------------------------------
+	// macros
+	//---------------------------------------------------------------------------------------------
+
+	/** Equivalent to [`(macro s)`](https://gamelisp.rs/std/macro). */
+
+	pub fn get_macro<S: ToSym>(s: S) -> GResult<Expander> {
+		with_engine(|engine| {
+			let sym = s.to_sym()?;
+			match engine.syms.borrow()[sym.0 as usize].bound_macro {
+				Some(ref expander) => Ok(expander.clone()),
+				None => bail!("symbol {} is not bound to a macro", sym)
+			}
+		})
+	}
+
+	/** Equivalent to [`(= (macro s) expander)`](https://gamelisp.rs/std/set-macro). */
+
+	pub fn set_macro<S: ToSym>(s: S, expander: Expander) -> GResult<()> {
+		with_engine(|engine| {
+			let sym = s.to_sym()?;
+			let mut syms = engine.syms.borrow_mut();
+			match syms[sym.0 as usize].bound_macro {
+				Some(ref mut storage) => {
+					#[cfg(feature = "compiler")]
+					engine.compile_cache.borrow_mut().note_provider(sym, &expander);
+
+					*storage = expander;
+					Ok(())
+				}
+				None => {
+					drop(syms);
+					bail!("symbol {} is not bound to a macro", sym)
+				}
+			}
+		})
+	}
+
+	/** Equivalent to [`(has-macro? s)`](https://gamelisp.rs/std/has-macro-p). */
+
+	pub fn has_macro<S: ToSym>(s: S) -> GResult<bool> {
+		with_engine(|engine| {
+			let sym = s.to_sym()?;
+			Ok(engine.syms.borrow()[sym.0 as usize].bound_macro.is_some())
+		})
+	}
+
+	/** Equivalent to [`(bind-macro! s)`](https://gamelisp.rs/std/bind-macro-mut). */
+
+	pub fn bind_macro<S: ToSym>(s: S, expander: Expander) -> GResult<()> {
+		with_engine(|engine| {
+			let sym = s.to_sym()?;
+			let mut syms = engine.syms.borrow_mut();
+			match syms[sym.0 as usize].bound_macro {
+				ref mut storage @ Some(_) if engine.reloading.get() => {
+					#[cfg(feature = "compiler")]
+					engine.compile_cache.borrow_mut().note_provider(sym, &expander);
+
+					*storage = Some(expander);
+					Ok(())
+				}
+				Some(_) => {
+					drop(syms);
+					bail!("attempted to bind the macro {}, which is already bound", sym)
+				}
+				ref mut storage @ None => {
+					#[cfg(feature = "compiler")]
+					engine.compile_cache.borrow_mut().note_provider(sym, &expander);
+
+					*storage = Some(expander);
+					Ok(())
+				}
+			}
+		})
+	}
+
+	//called by glsp-stdlib when a Runtime is built with the MACROS capability denied. the stdlib
+	//takes care of the functions which bind global macros, but (let-macro) is a special form, so
+	//the expander needs to refuse it itself.
+	#[doc(hidden)]
+	pub fn deny_let_macro() {
+		with_engine(|engine| {
+			engine.let_macro_denied.set(true);
+		})
+	}
+
+	pub(crate) fn ensure_let_macro_permitted() -> GResult<()> {
+		if with_engine(|engine| engine.let_macro_denied.get()) {
+			Err(GError::from_val(glsp::sym("capability-denied")?))
+		} else {
+			Ok(())
+		}
+	}
+
+	/** Equivalent to [`(del-macro! s)`](https://gamelisp.rs/std/del-macro-mut). */
+
+	pub fn del_macro<S: ToSym>(s: S) -> GResult<()> {
+		with_engine(|engine| {
+			let sym = s.to_sym()?;
+			let mut syms = engine.syms.borrow_mut();
+			match syms[sym.0 as usize].bound_macro {
+				ref mut storage @ Some(_) => {
+					*storage = None;
+					Ok(())
+				}
+				None => {
+					drop(syms);
+					bail!("attempted to unbind the macro {}, which is not bound", sym)
+				}
+			}
+		})
+	}
+
+	//---------------------------------------------------------------------------------------------
+	// modules
+	//---------------------------------------------------------------------------------------------
+
+	/**
+	Equivalent to [`(in-module name)`](https://gamelisp.rs/std/in-module).
+
+	Pass `None` to return to the root module. Each call to [`glsp::load`](fn.load.html) starts
+	out in the root module, and restores the current module when it returns.
+	*/
+
+	pub fn in_module(name: Option<Sym>) -> GResult<()> {
+		if let Some(name) = name {
+			glsp::ensure_module_name(name)?;
+		}
+
+		with_engine(|engine| {
+			let mut modules = engine.modules.borrow_mut();
+			if let Some(name) = name {
+				modules.known.insert(name);
+			}
+
+			modules.current = name;
+			Ok(())
+		})
+	}
+
+	/** Equivalent to [`(current-module)`](https://gamelisp.rs/std/current-module). */
+
+	pub fn current_module() -> Option<Sym> {
+		with_engine(|engine| {
+			engine.modules.borrow().current
+		})
+	}
+
+	fn set_current_module(name: Option<Sym>) {
+		with_engine(|engine| {
+			engine.modules.borrow_mut().current = name;
+		})
+	}
+
+	/**
+	Equivalent to [`(has-module? name)`](https://gamelisp.rs/std/has-module-p).
+
+	A module exists once it's been entered using [`glsp::in_module`](fn.in_module.html), or once
+	one of its globals or macros has been bound using a function like
+	[`glsp::bind_module_global`](fn.bind_module_global.html).
+	*/
+
+	pub fn has_module(name: Sym) -> bool {
+		with_engine(|engine| {
+			engine.modules.borrow().known.contains(&name)
+		})
+	}
+
+	fn ensure_module_name(name: Sym) -> GResult<()> {
+		ensure!(name.kind() == SymKind::Normal && !name.name().contains(':'),
+		        "'{}' is not a valid module name", name);
+		Ok(())
+	}
+
+	fn register_module(name: Sym) -> GResult<()> {
+		glsp::ensure_module_name(name)?;
+
+		with_engine(|engine| {
+			engine.modules.borrow_mut().known.insert(name);
+			Ok(())
+		})
+	}
+
+	/**
+	Equivalent to [`(import-module! module names)`](https://gamelisp.rs/std/import-module-mut).
+
+	Adds an import to the current module. When `only` is `None`, every global and macro in
+	`module` becomes visible. Otherwise, only the listed names become visible, and it's an error
+	for `module` not to define any of them.
+	*/
+
+	pub fn import_module(module: Sym, only: Option<&[Sym]>) -> GResult<()> {
+		ensure!(glsp::has_module(module), "attempted to import the nonexistent module '{}'",
+		        module);
+
+		let current = glsp::current_module();
+		ensure!(current != Some(module), "the module '{}' attempted to import itself", module);
+
+		if let Some(names) = only {
+			for &name in names {
+				let qualified = glsp::qualify(module, name)?;
+				ensure!(glsp::has_global(qualified)? || glsp::has_macro(qualified)?,
+				        "attempted to import '{}', but the module '{}' has no global or macro \
+				        by that name", name, module);
+			}
+		}
+
+		with_engine(|engine| {
+			let mut modules = engine.modules.borrow_mut();
+			let imports = modules.imports.entry(current).or_insert_with(Vec::new);
+
+			//repeating an import is harmless, which is convenient for glsp::reload
+			let import = Import {
+				module,
+				only: only.map(|names| names.to_vec())
+			};
+
+			if !imports.contains(&import) {
+				imports.push(import);
+			}
+
+			Ok(())
+		})
+	}
+
+	/**
+	Returns the qualified name `module:name`.
+
+	Returns an error if `module` isn't a valid module name, or if `name` is a gensym or contains
+	a colon.
+	*/
+
+	pub fn qualify<M: ToSym, S: ToSym>(module: M, name: S) -> GResult<Sym> {
+		let module = module.to_sym()?;
+		let name = name.to_sym()?;
+
+		glsp::ensure_module_name(module)?;
+		ensure!(name.kind() != SymKind::Gensym && !name.name().contains(':'),
+		        "unable to qualify the name '{}'", name);
+
+		glsp::sym(&format!("{}:{}", module, name))
+	}
+
+	/**
+	Returns the global name which would be bound by a definition of `name`, such as `(def name)`,
+	in the current module.
+
+	Within a module, unqualified names are qualified with the module's name. In the root module,
+	or when `name` is a gensym or contains a colon, `name` is returned unchanged.
+	*/
+
+	pub fn qualify_definition(name: Sym) -> GResult<Sym> {
+		match glsp::current_module() {
+			Some(module) if name.kind() != SymKind::Gensym && !name.name().contains(':') => {
+				glsp::qualify(module, name)
+			}
+			_ => Ok(name)
+		}
+	}
+
+	/**
+	Resolves the name of a global or macro, relative to the current module.
+
+	This is how the compiler decides which global a name refers to, when it isn't bound to a
+	local variable. Gensyms, and names which contain a colon, are never modified. Otherwise, we
+	search for a global or macro binding in this order:
+
+	- The current module's own definitions.
+	- Names which were imported using `:only`.
+	- Wildcard imports. It's an error for two wildcard imports to provide the same name.
+	- The root module. Within the root module itself, this comes first.
+
+	If none of these are bound, within a module, the name is qualified with the current module's
+	name, so that definitions later in the module can be referred to before they're bound.
+	*/
+
+	pub fn resolve_global(name: Sym) -> GResult<Sym> {
+		let resolved = with_engine(|engine| {
+			let modules = engine.modules.borrow();
+			let imports = modules.imports.get(&modules.current).map(|v| &v[..]).unwrap_or(&[]);
+
+			if modules.current.is_none() && imports.is_empty() {
+				return Ok(Some(name))
+			}
+
+			let syms = engine.syms.borrow();
+			let syms_map = engine.syms_map.borrow();
+
+			let entry = &syms[name.0 as usize];
+			match entry.kind {
+				SymKind::Normal | SymKind::StockTransform if !entry.name.contains(':') => (),
+				_ => return Ok(Some(name))
+			}
+
+			let is_bound = |sym: Sym| {
+				let entry = &syms[sym.0 as usize];
+				entry.bound_global.is_some() || entry.bound_macro.is_some()
+			};
+
+			let lookup = |module: Sym| {
+				let qualified = format!("{}:{}", syms[module.0 as usize].name, entry.name);
+				syms_map.get(&*qualified).copied()
+			};
+
+			//the current module's own definitions
+			match modules.current {
+				Some(module) => {
+					if let Some(sym) = lookup(module).filter(|&sym| is_bound(sym)) {
+						return Ok(Some(sym))
+					}
+				}
+				None => {
+					if is_bound(name) {
+						return Ok(Some(name))
+					}
+				}
+			}
+
+			//names imported with :only. these were bound when they were imported, so the lookup
+			//can only fail if the qualified name was never interned
+			for import in imports {
+				if let Some(ref only) = import.only {
+					if only.contains(&name) {
+						if let Some(sym) = lookup(import.module) {
+							return Ok(Some(sym))
+						}
+					}
+				}
+			}
+
+			//wildcard imports
+			let mut found: Option<(Sym, Sym)> = None;
+			for import in imports.iter().filter(|import| import.only.is_none()) {
+				if let Some(sym) = lookup(import.module).filter(|&sym| is_bound(sym)) {
+					if let Some((prev_module, _)) = found {
+						return Err(format!(
+							"the name '{}' is ambiguous: it's imported from both '{}' and '{}'",
+							entry.name, syms[prev_module.0 as usize].name,
+							syms[import.module.0 as usize].name
+						))
+					}
+
+					found = Some((import.module, sym));
+				}
+			}
+
+			if let Some((_, sym)) = found {
+				return Ok(Some(sym))
+			}
+
+			//the root module
+			if modules.current.is_some() && is_bound(name) {
+				return Ok(Some(name))
+			}
+
+			Ok(None)
+		});
+
+		match resolved {
+			Ok(Some(sym)) => Ok(sym),
+			Ok(None) => glsp::qualify_definition(name),
+			Err(msg) => bail!("{}", msg)
+		}
+	}
+
+	//called by the vm when it fails to look up a global. if the global's name is qualified, we
+	//try to explain why it's unbound.
+	pub(crate) fn unbound_global_hint(sym: Sym) -> String {
+		let name = sym.name();
+		match split_qualified(&name) {
+			Some((module, local)) => {
+				let is_known = match glsp::sym(module) {
+					Ok(module_sym) => glsp::has_module(module_sym),
+					Err(_) => false
+				};
+
+				if is_known {
+					format!(" (the module '{}' has no global named '{}')", module, local)
+				} else {
+					format!(" (there is no module named '{}')", module)
+				}
+			}
+			None => String::new()
+		}
+	}
+
+	/**
+	Returns the bound globals and macros whose names are most similar to `name`.
+
+	This is the search which produces the "did you mean" suggestion for an unbound symbol. It's
+	also useful for development tools, like a REPL, which want to correct a misspelled name.
+
+	At most two names are returned, in alphabetical order. Only the nearest matches are
+	returned, and names which are too different from `name` are left out, so the result is
+	often empty.
+	*/
+	pub fn similar_globals(name: &str) -> Vec<Sym> {
+		let candidates: Vec<Sym> = with_engine(|engine| {
+			let syms = engine.syms.borrow();
+			syms.iter().enumerate().filter(|(_, entry)| {
+				entry.kind != SymKind::Gensym &&
+				(entry.bound_global.is_some() || entry.bound_macro.is_some())
+			}).map(|(i, _)| Sym(i as u32)).collect()
+		});
+
+		suggest::suggest_names(name, candidates)
+	}
+
+	/**
+	Returns the names in `candidates` which are most similar to `name`.
+
+	Uses the same rules as [`glsp::similar_globals`](fn.similar_globals.html), but searches an
+	arbitrary list of names, such as the fields of a particular class.
+	*/
+	pub fn similar_names(name: &str, candidates: &[Sym]) -> Vec<Sym> {
+		suggest::suggest_names(name, candidates.iter().copied())
+	}
+
+	/** Equivalent to [`glsp::global(glsp::qualify(module, s)?)`](fn.global.html). */
+
+	pub fn module_global<M, S, T>(module: M, s: S) -> GResult<T>
+	where
+		M: ToSym,
+		S: ToSym,
+		T: FromVal
+	{
+		glsp::global(glsp::qualify(module, s)?)
+	}
+
+	/** Equivalent to [`glsp::set_global(glsp::qualify(module, s)?, val)`](fn.set_global.html). */
+
+	pub fn set_module_global<M, S, T>(module: M, s: S, val: T) -> GResult<()>
+	where
+		M: ToSym,
+		S: ToSym,
+		T: ToVal
+	{
+		glsp::set_global(glsp::qualify(module, s)?, val)
+	}
+
+	/** Equivalent to [`glsp::has_global(glsp::qualify(module, s)?)`](fn.has_global.html). */
+
+	pub fn has_module_global<M: ToSym, S: ToSym>(module: M, s: S) -> GResult<bool> {
+		glsp::has_global(glsp::qualify(module, s)?)
+	}
+
+	/**
+	Equivalent to [`glsp::bind_global(glsp::qualify(module, s)?, val)`](fn.bind_global.html).
+
+	Also creates the module, if it doesn't already exist.
+	*/
+
+	pub fn bind_module_global<M, S, T>(module: M, s: S, val: T) -> GResult<()>
+	where
+		M: ToSym,
+		S: ToSym,
+		T: ToVal
+	{
+		let module = module.to_sym()?;
+		glsp::bind_global(glsp::qualify(module, s)?, val)?;
+		glsp::register_module(module)
+	}
+
+	/** Equivalent to [`glsp::del_global(glsp::qualify(module, s)?)`](fn.del_global.html). */
+
+	pub fn del_module_global<M: ToSym, S: ToSym>(module: M, s: S) -> GResult<()> {
+		glsp::del_global(glsp::qualify(module, s)?)
+	}
+
+	/**
+	Equivalent to [`glsp::bind_macro(glsp::qualify(module, s)?, expander)`](fn.bind_macro.html).
+
+	Also creates the module, if it doesn't already exist.
+	*/
+
+	pub fn bind_module_macro<M, S>(module: M, s: S, expander: Expander) -> GResult<()>
+	where
+		M: ToSym,
+		S: ToSym
+	{
+		let module = module.to_sym()?;
+		glsp::bind_macro(glsp::qualify(module, s)?, expander)?;
+		glsp::register_module(module)
+	}
+
+	/**
+	Binds a Rust function to a global within a module.
+
+	Equivalent to [`glsp::bind_rfn(glsp::qualify(module, name)?, wrapped_fn)`](fn.bind_rfn.html).
+	Also creates the module, if it doesn't already exist.
+	*/
+
+	pub fn bind_module_rfn<M, S>(module: M, name: S, wrapped_fn: WrappedFn) -> GResult<RFn>
+	where
+		M: ToSym,
+		S: ToSym
+	{
+		let module = module.to_sym()?;
+		let rfn = glsp::bind_rfn(glsp::qualify(module, name)?, wrapped_fn)?;
+		glsp::register_module(module)?;
+		Ok(rfn)
+	}
+
+	//---------------------------------------------------------------------------------------------
+	// filenames
+	//---------------------------------------------------------------------------------------------
+
+	#[doc(hidden)]
+	pub fn filename(st: &str) -> Filename {
+		with_engine(|engine| {
+			let mut filenames_map = engine.filenames_map.borrow_mut();
+			if let Some(filename) = filenames_map.get(st) {
+				*filename
+			} else {
+				let mut filenames = engine.filenames.borrow_mut();
+
+				//see glsp::sym() for the rationale behind panicking here
+				assert!(filenames.len() <= u32::MAX as usize,
+				        "cannot load more than {} unique files", (u32::MAX as u64) + 1);
+
+				let rc = Rc::<str>::from(st);
+				filenames.push(Rc::clone(&rc));
+				engine.vm.execution_slot.add_filename(st);
+
+				let id = u32::try_from(filenames.len() - 1).unwrap();
+				let filename = Filename(NonZeroU32::new(id).unwrap());
+				filenames_map.insert(rc, filename);
+				
+				filename
+			}
+		})
+	}
+
+	pub(crate) fn filename_str(filename: Filename) -> Rc<str> {
+		with_engine(|engine| {
+			Rc::clone(&engine.filenames.borrow()[filename.0.get() as usize])
+		})
+	}
+
+	//---------------------------------------------------------------------------------------------
+	// libs, rdata
+	//---------------------------------------------------------------------------------------------
+
+	/**
+	Moves a Rust value onto the garbage-collected heap.
+
+	Rust types are automatically registered with the `Runtime` when `glsp::rdata` is first called
+	for a value of that type. If two types share the same unprefixed name, like
+	`video::Clip` and `audio::Clip`, it's an error.
+	*/
+
+	pub fn rdata<T: RStore>(rdata: T) -> GResult<Root<RData>> {
+		let class_rc = glsp::rclass::<T>()?;
+		let root = glsp::alloc(RData::new(rdata, class_rc));
+		root.gc_self.set(Some(root.to_gc()));
+		Ok(root)
+	}
+
+	/**
+	Lends a Rust value to GameLisp for the duration of a closure.
+
+	The value is moved onto the garbage-collected heap as an [`RData`](struct.RData.html), and
+	`f` is called with that `RData`. Scripts can call the value's methods, and Rust functions
+	can receive it as a `&T` or `&mut T` argument, just like any other `RData`. When `f` returns,
+	the value is moved back into `*value`, and the `RData` expires. Any later attempt to access
+	it triggers an `expired-rdata` error which describes where the value was lent.
+
+	This is useful for immediate-mode APIs, where a per-frame context is passed to a script
+	function. If the script stores the context and tries to use it during a later frame, it
+	receives an error rather than accessing stale state.
+
+		fn draw_debug_ui(ui: &mut DebugUi) -> GResult<()> {
+			let draw: Root<GFn> = glsp::global("draw-debug-ui")?;
+			glsp::with_scoped_rdata(ui, |ui| glsp::call(&draw, (ui,)))?
+		}
+
+	`T::default()` is stored in `*value` while `f` is running; it can't be observed, because
+	`value` is mutably borrowed. Returns an `Err` if `T` can't be registered as an `RData` type,
+	or if the value is still borrowed when `f` returns (for example, because an
+	[`RRef`](struct.RRef.html) was leaked). In that case, `*value` is left as `T::default()`.
+	*/
+
+	#[track_caller]
+	pub fn with_scoped_rdata<T, R, F>(value: &mut T, f: F) -> GResult<R>
+	where
+		T: RStore + Default,
+		F: FnOnce(&Root<RData>) -> R
+	{
+		let site = BorrowSite::new();
+
+		let rdata = glsp::rdata(std::mem::take(value))?;
+		let mut reclaimed = None;
+
+		let result = {
+			//the value is reclaimed even if `f` panics
+			let _guard = Guard::new(|| {
+				reclaimed = Some(rdata.take::<T>());
+				if rdata.is_freed() {
+					rdata.expired.set(Some(site));
+				}
+			});
+
+			f(&rdata)
+		};
+
+		match reclaimed.unwrap() {
+			Ok(reclaimed) => *value = reclaimed,
+			Err(_) => {
+				//a leaked borrow still refers to the value, so we drop our reference instead
+				let prev_usage = rdata.owned_memory_usage();
+				*rdata.storage.borrow_mut() = None;
+				rdata.expired.set(Some(site));
+
+				let cur_usage = rdata.owned_memory_usage();
+				with_heap(|heap| heap.memory_usage_barrier(&*rdata, prev_usage, cur_usage));
+
+				bail!("the scoped {} lent by {} was still borrowed when it expired",
+				      rdata.class.name, site)
+			}
+		}
+
+		Ok(result)
+	}
+
+	fn rclass<T: RStore>() -> GResult<Rc<RClass>> {
+		with_engine(|engine| {
+			match engine.rclasses.borrow_mut().entry(TypeId::of::<T>()) {
+				Vacant(entry) => {
+					let type_name = T::type_name();
+					ensure!(engine.rclass_names.borrow_mut().insert(&type_name),
+					        "multiple distinct RData types share the same name: {}", type_name);
+
+					let class = Rc::new(T::rclass()?);
+					entry.insert(Rc::clone(&class));
+					Ok(class)
+				}
+				Occupied(entry) => {
+					Ok(Rc::clone(&*entry.get()))
+				}
+			}
+		})
+	}
+
+	/**
+	Registers the layout of a Rust type, so that a `Vec` of that type can be lent to GameLisp
+	using [`glsp::with_scoped_views`](fn.with_scoped_views.html).
+
+	Each field is described by its name and a [`ViewField`](enum.ViewField.html), which holds
+	the field's primitive type and a function which borrows it from the struct. Fields which
+	aren't listed can't be accessed by scripts. The names don't need to match the names of the
+	Rust fields.
+
+		struct Transform {
+			x: f32,
+			y: f32,
+			angle: f32,
+			layer: u8,
+			mesh: MeshId
+		}
+
+		glsp::register_view::<Transform>(&[
+			("x", ViewField::F32(|t| &mut t.x)),
+			("y", ViewField::F32(|t| &mut t.y)),
+			("angle", ViewField::F32(|t| &mut t.angle)),
+			("layer", ViewField::U8(|t| &mut t.layer))
+		])?;
+
+	The view's type name is the unprefixed name of `T`, as in `#<view:Transform>`. Returns an
+	`Err` if `T` has already been registered, or if two fields share the same name.
+	*/
+
+	pub fn register_view<T>(fields: &[(&str, ViewField<T>)]) -> GResult<()>
+	where
+		T: GStore + 'static
+	{
+		let full_name = type_name::<T>();
+		let base_name = full_name.split('<').next().unwrap();
+		let type_name = base_name.rsplit("::").next().unwrap();
+
+		let layout = ViewLayout::new(glsp::sym(type_name)?, fields)?;
+
+		with_engine(|engine| {
+			match engine.view_layouts.borrow_mut().entry(TypeId::of::<T>()) {
+				Vacant(entry) => {
+					entry.insert(Rc::new(layout));
+					Ok(())
+				}
+				Occupied(_) => bail!("the view type {} has already been registered", type_name)
+			}
+		})
+	}
+
+	/**
+	Lends a `Vec` of Rust structs to GameLisp for the duration of a closure, without copying
+	its elements.
+
+	`T` must have been registered using [`glsp::register_view`](fn.register_view.html). `f` is
+	called with a [`View`](struct.View.html) which refers to the whole `Vec`. Scripts can index
+	it with an int to receive an element view, and then read and write that element's fields:
+
+		(let v [transforms 3])
+		(= [v 'x] (+ [v 'x] 1.0))
+
+	Each access reads or writes the Rust field directly, with a bounds check and a type check.
+	When `f` returns, every view which refers to the `Vec` expires, even if a script has stored
+	one in a global. Any later attempt to access it triggers an `expired-view` error which
+	describes where the `Vec` was lent.
+
+	This is intended for data which is exchanged with scripts every frame, such as entity
+	transforms, where copying the data into tabs and back again would be too expensive.
+
+		fn update_transforms(transforms: &mut Vec<Transform>) -> GResult<()> {
+			let update: Root<GFn> = glsp::global("update-transforms")?;
+			glsp::with_scoped_views(transforms, |views| glsp::call(&update, &(views.clone(),)))?
+		}
+
+	An empty `Vec` is stored in `*vec` while `f` is running; it can't be observed, because `vec`
+	is mutably borrowed. The `Vec` is returned even if `f` panics. Returns an `Err` if `T`
+	hasn't been registered.
+	*/
+
+	#[track_caller]
+	pub fn with_scoped_views<T, R, F>(vec: &mut Vec<T>, f: F) -> GResult<R>
+	where
+		T: GStore + 'static,
+		F: FnOnce(&Root<View>) -> R
+	{
+		let site = BorrowSite::new();
+
+		let layout = with_engine(|engine| {
+			match engine.view_layouts.borrow().get(&TypeId::of::<T>()) {
+				Some(layout) => Ok(Rc::clone(layout).downcast::<ViewLayout<T>>().unwrap()),
+				None => bail!("the view type {} has not been registered",
+				              type_name::<T>())
+			}
+		})?;
+
+		let lend = Rc::new(ViewLend::new(layout, std::mem::take(vec), site));
+		let views = glsp::alloc(View::new(Rc::clone(&lend), None));
+
+		//the Vec is reclaimed even if `f` panics
+		let _guard = Guard::new(|| *vec = lend.reclaim::<T>());
+		Ok(f(&views))
+	}
+
+	/**
+	Binds additional methods to a Rust type, alongside any methods defined using the
+	[`rdata!` macro](macro.rdata.html).
+
+	Each method receives its `RData` as its first argument, so it will usually be an `rfn` which
+	accepts `&T` or `&mut T` as its first parameter. The `RData` is borrowed for the duration of
+	the call, as usual.
+
+		glsp::bind_rdata_meths::<Texture>(&[
+			("width", glsp::rfn(rfn!(Texture::width))),
+			("resize!", glsp::rfn(rfn!(Texture::resize)))
+		])?;
+
+	<span></span>
+
+		(prn (.width tex))
+		(.resize! tex 256 256)
+
+	Returns an `Err` if any of the names are already bound to a method or property for `T`.
+	The new methods are visible to every `RData` of type `T`, including those which have
+	already been allocated.
+	*/
+	pub fn bind_rdata_meths<T: RStore>(meths: &[(&str, RFn)]) -> GResult<()> {
+		glsp::bind_rdata_bindings::<T, _>(meths.iter().map(|&(name, rfn)| ("", name, rfn)))
+	}
+
+	/**
+	Binds additional properties to a Rust type, alongside any properties defined using the
+	[`rdata!` macro](macro.rdata.html).
+
+	Each property has a getter, which receives the `RData` as its only argument, and an optional
+	setter, which also receives the new value.
+
+		let set_filter = glsp::rfn(rfn!(Texture::set_filter));
+
+		glsp::bind_rdata_props::<Texture>(&[
+			("width", glsp::rfn(rfn!(Texture::width)), None),
+			("filter", glsp::rfn(rfn!(Texture::filter)), Some(set_filter))
+		])?;
+
+	<span></span>
+
+		(prn [tex 'width])
+		(= [tex 'filter] 'nearest)
+
+	Returns an `Err` if any of the names are already bound to a method or property for `T`.
+	*/
+	pub fn bind_rdata_props<T: RStore>(props: &[(&str, RFn, Option<RFn>)]) -> GResult<()> {
+		let getters = props.iter().map(|&(name, getter, _)| ("get", name, getter));
+		let setters = props.iter().filter_map(|&(name, _, setter)| {
+			setter.map(|setter| ("set", name, setter))
+		});
+
+		glsp::bind_rdata_bindings::<T, _>(getters.chain(setters))
+	}
+
+	fn bind_rdata_bindings<'a, T, I>(raw_bindings: I) -> GResult<()>
+	where
+		T: RStore,
+		I: Iterator<Item = (&'static str, &'a str, RFn)>
+	{
+		let class = glsp::rclass::<T>()?;
+
+		//validate every binding before committing any of them
+		let mut bindings = class.bindings.borrow().clone();
+		for (kind, name, rfn) in raw_bindings {
+			insert_rbinding(&mut bindings, kind, glsp::sym(name)?, rfn)?;
+		}
+
+		*class.bindings.borrow_mut() = bindings;
+		Ok(())
+	}
+
+	/**
+	Returns the names of all of the Rust types which have been registered with the active
+	`Runtime`, sorted alphabetically.
+
+	A type is registered when [`glsp::rdata`](fn.rdata.html) is first called for a value of that
+	type, or when methods are bound to it using
+	[`glsp::bind_rdata_meths`](fn.bind_rdata_meths.html). Types which have never been used in
+	either way won't be listed.
+	*/
+	pub fn rdata_types() -> Vec<Sym> {
+		with_engine(|engine| {
+			let mut names: Vec<Sym> = engine.rclasses.borrow().values().map(|class| {
+				class.name
+			}).collect();
+
+			names.sort_by(|a, b| a.name().cmp(&b.name()));
+			names
+		})
+	}
+
+	/**
+	Moves a Rust value onto the garbage-collected heap, returning a typed pointer.
+
+	This function is a shorthand for
+	[`RRoot::<T>::new(glsp::rdata(rdata)?)`](struct.RRoot.html#method.new).
+	*/
+	pub fn rroot<T: RStore>(rdata: T) -> GResult<RRoot<T>> {
+		Ok(RRoot::new(glsp::rdata(rdata)?))
+	}
+
+	/**
+	Registers an instance of a library type.
+
+	Library types are singletons: if the active `Runtime` already contains a library of type
+	`T`, this function will panic.
+
+	When the active `Runtime` is dropped, each of its libraries will be dropped in the
+	reverse order that they were registered.
+
+	Libraries are identified by their concrete type, so generic types like `Assets<Texture>` and
+	`Assets<Sound>` are distinct libraries.
+
+	Once registered, it's possible to remove a library from the `Runtime` with
+	[`glsp::take_lib`](fn.take_lib.html), or borrow it with [`glsp::lib`](fn.lib.html), 
+	[`glsp::lib_mut`](fn.lib_mut.html), [`glsp::try_lib`](fn.try_lib.html) and
+	[`glsp::try_lib_mut`](fn.try_lib_mut.html).
+
+	It can be more convenient to borrow a library by calling 
+	[`T::borrow()`](trait.Lib.html#method.borrow), and other similar methods on the
+	[`Lib` trait](trait.Lib.html).
+	*/
+
+	pub fn add_lib<T: Lib>(lib: T) {
+		with_engine(|engine| {
+			let rc = Rc::new(RefCell::new(lib)) as Rc<dyn Any>;
+			if engine.libs.borrow_mut().insert(TypeId::of::<T>(), rc).is_some() {
+				panic!("glsp.add_lib() called twice for the same type");
+			}
+
+			engine.libs_ordering.borrow_mut().push(TypeId::of::<T>());
+		})
+	}
+
+	/**
+	Unregisters a value previously registered using [`glsp::add_lib`](fn.add_lib.html), and
+	returns it.
+
+	Returns `Err` if no library is registered for the type `T`, or if a library exists but it's
+	currently borrowed.
+
+	This can be used to control the order in which libraries are dropped. Once a library has
+	been taken, any attempt to borrow it will fail, until it's registered again.
+	*/
+
+	pub fn take_lib<T: Lib>() -> GResult<T> {
+		with_engine(|engine| {
+			let mut libs = engine.libs.borrow_mut();
+			let rc = match libs.remove(&TypeId::of::<T>()) {
+				Some(rc) => rc.downcast::<RefCell<T>>().unwrap(),
+				None => bail!("attempted to take nonexistent lib {}", type_name::<T>())
+			};
+
+			match Rc::try_unwrap(rc) {
+				Ok(ref_cell) => {
+					engine.libs_ordering.borrow_mut().retain(|&id| id != TypeId::of::<T>());
+					Ok(ref_cell.into_inner())
+				}
+				Err(rc) => {
+					libs.insert(TypeId::of::<T>(), rc as Rc<dyn Any>);
+					bail!("called take_lib for {}, which is currently borrowed", 
+					      type_name::<T>())
+				}
+			}
+		})
+	}
+
+	/**
+	Returns `true` if a library of type `T` is currently registered with the active `Runtime`.
+	*/
+	pub fn has_lib<T: Lib>() -> bool {
+		with_engine(|engine| {
+			engine.libs.borrow().contains_key(&TypeId::of::<T>())
+		})
+	}
+
+	/**
+	Borrows a library, registering it first if necessary.
+
+	If no library of type `T` is registered with the active `Runtime`, `init` is called to
+	construct one, and it's passed to [`glsp::add_lib`](fn.add_lib.html). This is useful for
+	lazily setting up a library from within an `rfn`.
+
+		fn load_texture(path: &str) -> GResult<RRoot<Texture>> {
+			let cache = glsp::lib_or_init(TextureCache::new)?;
+			cache.load(path)
+		}
+
+	Returns an `Err` under the same circumstances as [`glsp::try_lib`](fn.try_lib.html), or if
+	`init` registers a library of type `T` itself.
+	*/
+	pub fn lib_or_init<T: Lib, F: FnOnce() -> T>(init: F) -> GResult<LibRef<T>> {
+		if !glsp::has_lib::<T>() {
+			let lib = init();
+			ensure!(!glsp::has_lib::<T>(), "lib {} was registered during its own lazy \
+			        initialization", type_name::<T>());
+
+			glsp::add_lib(lib);
+		}
+
+		glsp::try_lib::<T>()
+	}
+
+	///Equivalent to [`Lib::borrow`](trait.Lib.html#method.borrow).
+	pub fn lib<T: Lib>() -> LibRef<T> {
+		match glsp::try_lib::<T>() {
+			Ok(lib_ref) => lib_ref,
+			Err(_) => panic!("attempted to access nonexistent lib {}", type_name::<T>())
+		}
+	}
+
+	///Equivalent to [`Lib::borrow_mut`](trait.Lib.html#method.borrow_mut).
+	pub fn lib_mut<T: Lib>() -> LibRefMut<T> {
+		match glsp::try_lib_mut::<T>() {
+			Ok(lib_ref_mut) => lib_ref_mut,
+			Err(_) => panic!("attempted to access nonexistent lib {}", type_name::<T>())
+		}
+	}
+
+	///Equivalent to [`Lib::try_borrow`](trait.Lib.html#method.try_borrow).
+	pub fn try_lib<T: Lib>() -> GResult<LibRef<T>> {
+		with_engine(|engine| {
+			let libs = engine.libs.borrow();
+
+			let rc = match libs.get(&TypeId::of::<T>()) {
+				Some(rc) => rc.clone(),
+				None => bail!("lib type {} was never registered, or has been \
+				               taken or dropped", type_name::<T>())
+			};
+
+			let rc_ref_cell = rc.downcast::<RefCell<T>>().unwrap();
+
+			if rc_ref_cell.try_borrow().is_err() {
+				bail!("attempted to borrow lib {} during a mutable borrow", type_name::<T>())
+			}
+
+			Ok(LibRef {
+				handle: OwningHandle::new(rc_ref_cell)
+			})
+		})
+	}
+
+	///Equivalent to [`Lib::try_borrow_mut`](trait.Lib.html#method.try_borrow_mut).
+	pub fn try_lib_mut<T: Lib>() -> GResult<LibRefMut<T>> {
+		with_engine(|engine| {
+			let libs = engine.libs.borrow();
+
+			let rc = match libs.get(&TypeId::of::<T>()) {
+				Some(rc) => rc.clone(),
+				None => bail!("lib type {} was never registered, or has been \
+				               taken or dropped", type_name::<T>())
+			};
+
+			let rc_ref_cell = rc.downcast::<RefCell<T>>().unwrap();
+
+			if rc_ref_cell.try_borrow_mut().is_err() {
+				bail!("attempted to mutably borrow {}, which is already borrowed", type_name::<T>())
+			}
+
+			Ok(LibRefMut {
+				handle: OwningHandle::new_mut(rc_ref_cell)
+			})
+		})
+	}
+
+	//---------------------------------------------------------------------------------------------
+	// rfns
+	//---------------------------------------------------------------------------------------------
+
+	/**
+	Creates a GameLisp value which represents a Rust function.
+
+	The `wrapped_fn` parameter should be constructed using the [`rfn!()`](macro.rfn.html) macro.
+
+	When binding a Rust function to a global variable, it's usually more convenient to use 
+	[`glsp::bind_rfn`](fn.bind_rfn.html) instead.
+
+	Functions at the same address are deduplicated. For example, repeated calls to 
+	`glsp::rfn("swap-bytes", i32::swap_bytes)` will return the same `RFn` every time.
+	*/
+
+	pub fn rfn(wrapped_fn: WrappedFn) -> RFn {
+		with_engine(|engine| {
+			let address = wrapped_fn.as_usize();
+
+			let mut rfns_map =  engine.rfns_map.borrow_mut();
+			if let Some(rfn) = rfns_map.get(&address) {
+				*rfn
+			} else {
+				let mut rfns = engine.rfns.borrow_mut();
+				rfns.push(RFnEntry {
+					name: None,
+					callee: RFnCallee::Wrapped(wrapped_fn)
+				});
+
+				let id = u32::try_from(rfns.len() - 1).unwrap();
+				let rfn = RFn(NonZeroU32::new(id).unwrap());
+				rfns_map.insert(address, rfn);
+
+				rfn
+			}
+		})
+	}
+
+	/**
+	Creates a GameLisp value which represents a Rust function, with a name.
+
+	This is equivalent to [`glsp::rfn`](fn.rfn.html), but 
+	[`(fn-name rfn)`](https://gamelisp.rs/std/fn-name) will return the given symbol, 
+	rather than returning `#n`.
+	*/
+
+	pub fn named_rfn(name: Sym, wrapped_fn: WrappedFn) -> RFn {
+		let rfn = glsp::rfn(wrapped_fn);
+		rfn.set_name(Some(name));
+		rfn
+	}
+
+	/**
+	Binds a Rust function to a global variable.
+
+	`glsp::bind_rfn(name, rfn!(f))?` is equivalent to:
+
+		let sym = name.to_sym()?
+		let rfn = glsp::named_rfn(sym, rfn!(f));
+		glsp::bind_global(sym, rfn)?;
+		Ok(rfn)
+	*/
+
+	pub fn bind_rfn<S: ToSym>(name: S, wrapped_fn: WrappedFn) -> GResult<RFn> {
+		let sym = name.to_sym()?;
+
+		let rfn = glsp::named_rfn(sym, wrapped_fn);
+
+		glsp::bind_global(sym, rfn)?;
+		glsp::record_api(sym, rfn, false);
+		Ok(rfn)
+	}
+
+	/**
+	Binds a Rust function to a global macro.
+
+	`glsp::bind_rfn_macro(name, rfn!(f))?` is equivalent to:
+
+		let sym = name.to_sym()?
+		let rfn = glsp::named_rfn(sym, rfn!(f));
+		glsp::bind_macro(sym, Expander::RFn(rfn))?;
+		Ok(rfn)
+	*/
+
+	pub fn bind_rfn_macro<S: ToSym>(name: S, wrapped_fn: WrappedFn) -> GResult<RFn> {
+		let sym = name.to_sym()?;
+
+		let rfn = glsp::named_rfn(sym, wrapped_fn);
+		
+		glsp::bind_macro(sym, Expander::RFn(rfn))?;
+		glsp::record_api(sym, rfn, true);
+		Ok(rfn)
+	}
+
+	/**
+	Installs every binding in a [`Library`](struct.Library.html).
+
+	Installation is atomic. Before anything is bound, each of the library's names is checked
+	against the existing globals and macros, and against the library's other names. If there are
+	any conflicts, this function returns an error which lists all of them, without binding
+	anything. If one of the library's [sources](struct.Library.html#method.source) fails to
+	evaluate, all of the globals and macros which the library defines are unbound again.
+
+	The same `Library` can be installed into any number of `Runtimes`.
+	*/
+
+	pub fn install(library: &Library) -> GResult<()> {
+		//resolve every name and check for conflicts, before binding anything
+		let mut claimed = Vec::<(Sym, bool)>::new();
+		let mut conflicts = Vec::<String>::new();
+		let mut syms = Vec::with_capacity(library.items.len());
+
+		{
+			let mut claim = |sym: Sym, is_macro: bool| -> GResult<()> {
+				let (kind, bound) = if is_macro {
+					("macro", glsp::has_macro(sym)?)
+				} else {
+					("global", glsp::has_global(sym)?)
+				};
+
+				let conflict = if claimed.contains(&(sym, is_macro)) {
+					format!("the {} {} is defined more than once", kind, sym)
+				} else if bound {
+					format!("the {} {} is already bound", kind, sym)
+				} else if !is_macro && !sym.is_bindable() {
+					format!("the name {} can't be bound to a global", sym)
+				} else {
+					claimed.push((sym, is_macro));
+					return Ok(())
+				};
+
+				if !conflicts.contains(&conflict) {
+					conflicts.push(conflict);
+				}
+
+				Ok(())
+			};
+
+			for (name, item) in &library.items {
+				let sym = glsp::sym(name)?;
+				claim(sym, matches!(item, LibraryItem::RFnMacro(_)))?;
+				syms.push(sym);
+			}
+
+			for (_, text) in &library.sources {
+				for def in glsp::definitions(text)? {
+					match &*def.kind().name() {
+						"defonce" => (),
+						"defmacro" => claim(def.name(), true)?,
+						_ => claim(def.name(), false)?
+					}
+				}
+			}
+		}
+
+		for (name, _, _) in &library.docs {
+			let documents_rfn = library.items.iter().any(|(item_name, item)| {
+				item_name == name && !matches!(item, LibraryItem::Const(_))
+			});
+
+			if !documents_rfn {
+				conflicts.push(format!("{} is documented, but it isn't a Rust function", name));
+			}
+		}
+
+		if !conflicts.is_empty() {
+			bail!("unable to install the library: {}", conflicts.join("; "))
+		}
+
+		//convert the bindings to vals, then store them all at once
+		let mut globals = Vec::with_capacity(syms.len());
+		let mut macros = Vec::new();
+		let mut rfns = Vec::new();
+		for (&sym, (_, item)) in syms.iter().zip(&library.items) {
+			match *item {
+				LibraryItem::RFn(wrapped_fn) => {
+					let rfn = glsp::named_rfn(sym, wrapped_fn);
+					globals.push((sym, Val::RFn(rfn), false));
+					rfns.push((sym, rfn, false));
+				}
+				LibraryItem::RFnMacro(wrapped_fn) => {
+					let rfn = glsp::named_rfn(sym, wrapped_fn);
+					macros.push((sym, Expander::RFn(rfn)));
+					rfns.push((sym, rfn, true));
+				}
+				LibraryItem::Const(ref to_val) => {
+					globals.push((sym, to_val()?, true));
+				}
+			}
+		}
+
+		//the api manifest ignores an entry until its rfn is bound, so it's harmless to record 
+		//the library's rfns before we know whether the installation will succeed
+		let record_api = || -> GResult<()> {
+			for &(sym, rfn, is_macro) in &rfns {
+				glsp::record_api(sym, rfn, is_macro);
+			}
+
+			for (name, param_names, doc) in &library.docs {
+				let param_names = Vec::from_iter(param_names.iter().map(|s| &s[..]));
+				glsp::document_api(&name[..], &param_names, doc)?;
+			}
+
+			Ok(())
+		};
+
+		match library.name {
+			Some(ref name) => glsp::with_api_library(name, record_api)?,
+			None => record_api()?
+		}
+
+		with_engine(|engine| {
+			let mut syms = engine.syms.borrow_mut();
+
+			for (sym, val, constant) in globals {
+				syms[sym.0 as usize].bound_global = Some(GlobalEntry {
+					val,
+					frozen: constant,
+					constant,
+					cached: false
+				});
+			}
+
+			for (sym, expander) in macros {
+				syms[sym.0 as usize].bound_macro = Some(expander);
+			}
+		});
+
+		for (filename, text) in &library.sources {
+			if let Err(err) = glsp::load_str(text, filename) {
+				with_engine(|engine| {
+					let mut syms = engine.syms.borrow_mut();
+					for &(sym, is_macro) in &claimed {
+						let entry = &mut syms[sym.0 as usize];
+						if is_macro {
+							entry.bound_macro = None;
+						} else {
+							entry.bound_global = None;
+						}
+					}
+
+					engine.vm.invalidate_global_caches();
+				});
+
+				return Err(err)
+			}
+		}
+
+		Ok(())
+	}
+
+	/**
+	Creates a GameLisp value which represents a Rust closure.
+
+	The `boxed_fn` parameter can be constructed using the [`rfn_boxed!()`](macro.rfn_boxed.html)
+	macro, which converts the closure's arguments and return value in the same way as
+	[`rfn!()`](macro.rfn.html). Alternatively, it can be any
+	`Box<dyn Fn(&[Val]) -> GResult<Val>>`, which receives its arguments unconverted.
+
+		let counter = Rc::new(Cell::new(0));
+
+		let counter_ref = counter.clone();
+		let rfn = glsp::rfn_boxed(Box::new(move |args: &[Val]| {
+			counter_ref.set(counter_ref.get() + args.len());
+			Ok(Val::Nil)
+		}));
+
+	Unlike `glsp::rfn`, this function always allocates a new `RFn`. `RFn`s aren't 
+	garbage-collected, so the closure, and everything which it captures, will remain alive 
+	until the active `Runtime` is dropped. A closure must not capture a reference cycle which
+	leads back to its `Runtime`, or the `Runtime` will be unable to drop it.
+	*/
+
+	pub fn rfn_boxed<F: Into<BoxedFn>>(boxed_fn: F) -> RFn {
+		with_engine(|engine| {
+			let mut rfns = engine.rfns.borrow_mut();
+			rfns.push(RFnEntry {
+				name: None,
+				callee: RFnCallee::Boxed(boxed_fn.into())
+			});
+
+			let id = u32::try_from(rfns.len() - 1).unwrap();
+			RFn(NonZeroU32::new(id).unwrap())
+		})
+	}
+
+	/**
+	Binds a Rust closure to a global variable.
+
+	This is equivalent to calling [`glsp::rfn_boxed`](fn.rfn_boxed.html), giving the resulting
+	`RFn` the name `name`, and then binding it to the global variable `name`.
+
+	For more details, see [`glsp::rfn_boxed`](fn.rfn_boxed.html).
+	*/
+
+	pub fn bind_rfn_boxed<S: ToSym, F: Into<BoxedFn>>(name: S, boxed_fn: F) -> GResult<RFn> {
+		let sym = name.to_sym()?;
+
+		let rfn = glsp::rfn_boxed(boxed_fn);
+		rfn.set_name(Some(sym));
+
+		glsp::bind_global(sym, rfn)?;
+		glsp::record_api(sym, rfn, false);
+		Ok(rfn)
+	}
+
+	/**
+	Binds a global variable which forwards calls to another global, warning that it's deprecated.
+
+	This makes it possible to rename a function without immediately breaking every script which
+	uses its old name. `old_name` is bound to an `RFn` which calls the current value of the
+	global `new_name`, passing its arguments through unchanged. The first time that each callsite
+	calls `old_name`, a warning which names the callsite is printed at
+	[`PrintLevel::Warn`](enum.PrintLevel.html):
+
+		glsp::bind_deprecated("draw-sprite", "sprite-draw", "it will be removed in 0.4")?;
+
+		//scripts/hud.glsp:12: draw-sprite is deprecated, use sprite-draw instead:
+		//it will be removed in 0.4
+		glsp::load("scripts/hud.glsp")?;
+
+	`new_name` must already be bound to a callable value. The `RFn` has the same
+	[argument limits](trait.CallableOps.html#tymethod.arg_limits) as that value, and it can be
+	passed to [`apply`](https://gamelisp.rs/std/apply) or bound as an RData method like any
+	other function. If `old_name` is already bound to a global, its value is replaced.
+
+	To find out whether a deprecated name can be safely removed, use
+	[`glsp::deprecations_hit`](fn.deprecations_hit.html).
+
+	Equivalent to [`(deprecate old-name new-name note)`](https://gamelisp.rs/std/deprecate).
+	*/
+
+	pub fn bind_deprecated<S1, S2>(old_name: S1, new_name: S2, note: &str) -> GResult<RFn>
+	where
+		S1: ToSym,
+		S2: ToSym
+	{
+		let old_name = old_name.to_sym()?;
+		let new_name = new_name.to_sym()?;
+		ensure!(old_name != new_name, "attempted to deprecate {} in favour of itself", old_name);
+
+		let target = glsp::try_global::<_, Val>(new_name)?;
+		let arg_limits = match target.as_ref().map(Callable::from_val) {
+			Some(Ok(callable)) => callable.arg_limits(),
+			_ => bail!("{} is not bound to a callable value", new_name)
+		};
+
+		let deprecation_note = if note.is_empty() { None } else { Some(note.to_string()) };
+
+		let note: Rc<str> = note.into();
+		let rfn = glsp::rfn_boxed(BoxedFn::new(move |vals: Ref<[Slot]>| {
+			let args = SmallVec::<[Val; 8]>::from_iter(vals.iter().map(|slot| slot.root()));
+			drop(vals);
+
+			glsp::warn_deprecated(old_name, new_name, &note);
+
+			let target: Callable = glsp::global(new_name)?;
+			let result: Val = glsp::call(&target, &args[..])?;
+			Ok(Slot::from_val(&result))
+		}, arg_limits));
+		rfn.set_name(Some(old_name));
+
+		match glsp::try_set_global(old_name, rfn)? {
+			TrySetGlobalOutcome::Success => (),
+			TrySetGlobalOutcome::NotBound => glsp::bind_global(old_name, rfn)?,
+			TrySetGlobalOutcome::Frozen => {
+				bail!("attempted to deprecate frozen global {}", old_name)
+			}
+		}
+
+		//the deprecated name inherits its replacement's documentation
+		glsp::record_api(old_name, rfn, false);
+		with_engine(|engine| {
+			let mut api = engine.api.borrow_mut();
+			let target_entry = api.get(&new_name).map(|record| record.entry.clone());
+
+			let entry = &mut api.get_mut(&old_name).unwrap().entry;
+			if let Some(target_entry) = target_entry {
+				entry.param_names = target_entry.param_names;
+				entry.param_types = target_entry.param_types;
+				entry.doc = target_entry.doc;
+			}
+
+			entry.replacement = Some(new_name);
+			entry.deprecation_note = deprecation_note;
+		});
+
+		Ok(rfn)
+	}
+
+	//the warning is printed once for each distinct (name, callsite) pair. calls from rust code,
+	//or from an rfn like (apply), are attributed to the innermost call made by GameLisp code.
+	fn warn_deprecated(old_name: Sym, new_name: Sym, note: &str) {
+		let (callsite, first_call) = with_engine(|engine| {
+			let callsite = engine.vm.callsite();
+
+			let mut hit = engine.deprecations_hit.borrow_mut();
+			if !hit.contains(&old_name) {
+				hit.push(old_name);
+			}
+
+			(callsite, engine.deprecated_callsites.borrow_mut().insert((old_name, callsite)))
+		});
+
+		if first_call {
+			let mut message = String::new();
+			if glsp::span_file_location(&mut message, callsite).unwrap() {
+				message.push_str(": ");
+			}
+
+			let mut args = MessageArgs::new().with("name", old_name).with("replacement", new_name);
+			if !note.is_empty() {
+				args = args.with("note", note);
+			}
+
+			message.push_str(&glsp::format_message(MessageId::Deprecated, &args));
+
+			glsp::log(PrintLevel::Warn, &message);
+		}
+	}
+
+	/**
+	Returns the deprecated names which have been called so far.
+
+	The names are listed in the order that they were first called. A name which was bound by
+	[`glsp::bind_deprecated`](fn.bind_deprecated.html), but which isn't listed here after a
+	typical play session, is probably safe to remove.
+
+	Equivalent to [`(deprecations-hit)`](https://gamelisp.rs/std/deprecations-hit).
+	*/
+
+	pub fn deprecations_hit() -> Vec<Sym> {
+		with_engine(|engine| engine.deprecations_hit.borrow().clone())
+	}
+
+	//records an rfn which has just been bound to the global or macro `name`, replacing any
+	//existing entry for that name
+	fn record_api(name: Sym, rfn: RFn, is_macro: bool) {
+		with_engine(|engine| {
+			let rfns = engine.rfns.borrow();
+			let callee = &rfns[rfn.0.get() as usize].callee;
+
+			let param_names = callee.param_names().map(|names| {
+				Vec::from_iter(names.iter().map(|name| name.to_string()))
+			});
+			let param_types = callee.param_types().map(|types| {
+				Vec::from_iter(types.iter().map(|ty| short_type_name(ty)))
+			});
+
+			let entry = ApiEntry {
+				name,
+				is_macro,
+				library: engine.api_library.borrow().clone(),
+				arg_limits: callee.arg_limits(),
+				param_names,
+				param_types,
+				doc: None,
+				capability: None,
+				replacement: None,
+				deprecation_note: None
+			};
+
+			engine.api.borrow_mut().insert(name, ApiRecord { rfn, entry });
+		})
+	}
+
+	fn edit_api_entry<F>(name: Sym, f: F) -> GResult<()>
+	where
+		F: FnOnce(&mut ApiEntry) -> GResult<()>
+	{
+		with_engine(|engine| {
+			match engine.api.borrow_mut().get_mut(&name) {
+				Some(record) => f(&mut record.entry),
+				None => bail!("{} has not been bound to a Rust function", name)
+			}
+		})
+	}
+
+	/**
+	Returns a description of each Rust function which is bound to a global or a macro.
+
+	Each time that [`glsp::bind_rfn`](fn.bind_rfn.html), 
+	[`glsp::bind_rfn_macro`](fn.bind_rfn_macro.html), 
+	[`glsp::bind_rfn_boxed`](fn.bind_rfn_boxed.html), 
+	[`glsp::bind_deprecated`](fn.bind_deprecated.html) or [`glsp::install`](fn.install.html) 
+	binds a Rust function, it records its name, its argument limits and the Rust types of its 
+	parameters. Names and documentation can be added using 
+	[`glsp::document_api`](fn.document_api.html).
+
+	Functions which have since been unbound, or replaced by a different value, are left out.
+	The entries are sorted by [library](fn.with_api_library.html), and then by name. Functions
+	which don't belong to a library are listed last.
+
+	Equivalent to [`(api-manifest)`](https://gamelisp.rs/std/api-manifest).
+	*/
+
+	pub fn api_manifest() -> Vec<ApiEntry> {
+		let mut entries = with_engine(|engine| {
+			let syms = engine.syms.borrow();
+			let api = engine.api.borrow();
+
+			Vec::from_iter(api.values().filter(|record| {
+				let sym_entry = &syms[record.entry.name.0 as usize];
+				if record.entry.is_macro {
+					matches!(sym_entry.bound_macro, Some(Expander::RFn(rfn)) if rfn == record.rfn)
+				} else {
+					match sym_entry.bound_global {
+						Some(GlobalEntry { val: Val::RFn(rfn), .. }) => rfn == record.rfn,
+						_ => false
+					}
+				}
+			}).map(|record| record.entry.clone()))
+		});
+
+		entries.sort_by_cached_key(|entry| {
+			(entry.library.is_none(), entry.library.clone(), entry.name.name())
+		});
+
+		entries
+	}
+
+	/**
+	Renders the [API manifest](fn.api_manifest.html) as a Markdown document.
+
+	Functions are grouped by library. Each function is listed with its signature, its argument
+	limits, its parameters' names and types, the capability which it requires, whether it's
+	deprecated, and its documentation.
+	*/
+
+	pub fn api_markdown() -> String {
+		render_markdown(&glsp::api_manifest())
+	}
+
+	/**
+	Writes the output of [`glsp::api_markdown`](fn.api_markdown.html) to a file.
+
+	The file is created if it doesn't already exist, and its contents are replaced if it does.
+	*/
+
+	pub fn write_api_markdown<P: AsRef<Path>>(path: P) -> GResult<()> {
+		let path = path.as_ref();
+		std::fs::write(path, glsp::api_markdown()).map_err(|err| {
+			error!("unable to write file '{}'", path.display()).with_source(err)
+		})
+	}
+
+	/**
+	Adds parameter names and documentation to an entry in the [API manifest](fn.api_manifest.html).
+
+	`name` must be bound to a Rust function, using [`glsp::bind_rfn`](fn.bind_rfn.html) or a 
+	similar function. `param_names` lists the function's parameters, in order, leaving out 
+	any parameter which borrows a [`Lib`](trait.Lib.html). `doc` is free-form Markdown text. 
+	If either argument is empty, the existing names or documentation are left unchanged.
+
+		glsp::bind_rfn("spawn-enemy", rfn!(spawn_enemy))?;
+		glsp::document_api("spawn-enemy", &["kind", "x", "y"], "Spawns an enemy on a tile.")?;
+
+	Returns an `Err` if the function's parameter types are known, and `param_names` has a
+	different length.
+	*/
+
+	pub fn document_api<S: ToSym>(name: S, param_names: &[&str], doc: &str) -> GResult<()> {
+		let name = name.to_sym()?;
+		glsp::edit_api_entry(name, |entry| {
+			if !param_names.is_empty() {
+				if let Some(ref param_types) = entry.param_types {
+					ensure!(param_types.len() == param_names.len(),
+					        "{} has {} parameters, but {} parameter names were given",
+					        name, param_types.len(), param_names.len());
+				}
+
+				entry.param_names = Some(Vec::from_iter(param_names.iter().map(|s| s.to_string())));
+			}
+
+			if !doc.is_empty() {
+				entry.doc = Some(doc.to_string());
+			}
+
+			Ok(())
+		})
+	}
+
+	/**
+	Records that a Rust function in the [API manifest](fn.api_manifest.html) is gated behind a
+	capability.
+
+	The capability is an arbitrary name, which is only used for documentation. The standard
+	library uses this function to record the name of the `Caps` constant which each of its 
+	functions requires, like `"FS"`.
+	*/
+
+	pub fn set_api_capability<S: ToSym>(name: S, capability: &str) -> GResult<()> {
+		glsp::edit_api_entry(name.to_sym()?, |entry| {
+			entry.capability = Some(capability.to_string());
+			Ok(())
+		})
+	}
+
+	/**
+	Invokes a closure, grouping any Rust functions which it binds under the given library name.
+
+	The name appears in the [API manifest](fn.api_manifest.html). Functions bound outside of 
+	any call to `with_api_library` don't belong to a library, unless they're installed from a 
+	[named `Library`](struct.Library.html#method.name).
+
+		glsp::with_api_library("combat", || {
+			glsp::bind_rfn("attack", rfn!(attack))?;
+			glsp::bind_rfn("defend", rfn!(defend))?;
+			Ok(())
+		})?;
+	*/
+
+	pub fn with_api_library<R, F: FnOnce() -> R>(library: &str, f: F) -> R {
+		let prev_library = with_engine(|engine| {
+			engine.api_library.replace(Some(library.to_string()))
+		});
+
+		let _guard = Guard::new(|| {
+			with_engine(|engine| *engine.api_library.borrow_mut() = prev_library)
+		});
+
+		f()
+	}
+
+	/**
+	Returns `true` if an `rfn` has panicked since the `Runtime` was created, or since the last
+	call to [`glsp::clear_poison`](fn.clear_poison.html).
+
+	A panic within an `rfn` is caught and converted into an error, with the
+	[message id](enum.MessageId.html) `RustPanic`, which can be handled like any other error.
+	The engine's own state is restored, and any [`RData`](struct.RData.html) which was mutably
+	borrowed by the panicking code is [poisoned](struct.RData.html#method.is_poisoned). However,
+	the `rfn` might have left the host's own data in an inconsistent state, so the `Runtime`
+	is marked as poisoned too.
+
+	The engine doesn't treat a poisoned `Runtime` any differently. It's up to the host to
+	decide whether to keep running scripts, or to shut down the `Runtime`, perhaps after 
+	saving the player's progress.
+
+		runtime.run(|| {
+			glsp::call::<_, Val>(&update_fn, ())?;
+			if glsp::is_poisoned() {
+				report_bug("an rfn panicked during update")?;
+				glsp::clear_poison();
+			}
+
+			Ok(())
+		});
+
+	Panics can only be caught when the crate is compiled with `panic = "unwind"`, which is
+	Rust's default. When it's compiled with `panic = "abort"`, an `rfn` which panics will 
+	terminate the process.
+	*/
+	pub fn is_poisoned() -> bool {
+		with_engine(|engine| engine.poisoned.get())
+	}
+
+	/**
+	Clears the flag returned by [`glsp::is_poisoned`](fn.is_poisoned.html).
+
+	Any poisoned `RData` remain poisoned.
+	*/
+	pub fn clear_poison() {
+		with_engine(|engine| engine.poisoned.set(false))
+	}
+
+	pub(crate) fn call_rfn(rfn: RFn, arg_count: usize) -> GResult<Slot> {
+		with_engine(|engine| {
+
+			/*
+			when invoking a wrapped rfn, we borrow the vm's reg stack, copy the useful parts of
+			it to the Rust callstack (as the Temps type), drop the borrow, and then invoke the 
+			rfn. we only pop the regs after the call returns, so that they remain rooted.
+			*/
+
+			let stacks = engine.vm.stacks.borrow();
+			let base_reg = stacks.regs.len() - arg_count;
+
+			let _guard = Guard::new(|| {
+				let mut stacks = engine.vm.stacks.borrow_mut();
+				stacks.regs.truncate(base_reg);
+			});
+
+			let regs = Ref::map(stacks, |stacks| &stacks.regs[base_reg..]);
+
+			//a BoxedFn is cloned out of the table, so that it can't be dropped while it's running
+			let callee = engine.rfns.borrow()[rfn.0.get() as usize].callee.clone();
+
+			let result = panic::catch_unwind(AssertUnwindSafe(|| {
+				match callee {
+					RFnCallee::Wrapped(wrapped_fn) => wrapped_fn.call(regs),
+					RFnCallee::Boxed(boxed_fn) => boxed_fn.call(regs)
+				}
+			}));
+
+			/*
+			for the time being, we don't go through the rigmarole of trying to set a custom panic
+			hook. it's a global resource, and managing that would be annoying. instead, we allow
+			the normal panic hook to print its usual message, and we convert the caught panic
+			into a RustPanic message, which only includes the panic's payload if it's a string.
+
+			the reg stack is truncated by _guard, and any rdata which were mutably borrowed by
+			the panicking code have poisoned themselves. we can't check the rest of the rfn's 
+			side-effects, so we also poison the Runtime and let the host decide what to do.
+			*/
+			
+			match result {
+				Ok(Ok(slot)) => Ok(slot),
+				Ok(Err(mut error)) => {
+					//the arguments remain on the reg stack until _guard is dropped
+					error.finish_arg_context(rfn.name(), |_| None, |arg_index| {
+						let stacks = engine.vm.stacks.borrow();
+						stacks.regs.get(base_reg + arg_index).map(Slot::root)
+					});
+
+					Err(error)
+				}
+				Err(payload) => {
+					engine.poisoned.set(true);
+
+					let mut args = MessageArgs::new();
+					if let Some(name) = rfn.name() {
+						args = args.with("rfn", name);
+					}
+
+					if let Some(msg) = payload.downcast_ref::<&str>() {
+						args = args.with("payload", msg);
+					} else if let Some(msg) = payload.downcast_ref::<String>() {
+						args = args.with("payload", msg);
+					}
+
+					Err(GError::from_message(MessageId::RustPanic, args))
+				}
+			}
+		})
+	}
+
+	//---------------------------------------------------------------------------------------------
+	// parsing and printing
+	//---------------------------------------------------------------------------------------------
+
+	/** Equivalent to [`(parse text filename)`](https://gamelisp.rs/std/parse). */
+
+	pub fn parse(text: &mut &str, filename: Option<&str>) -> GResult<Option<Val>> {
+		let file_id = filename.map(|path| glsp::filename(path));
+
+		let mut parser = Parser::new(file_id);
+
+		glsp::push_frame(Frame::GlspApi(GlspApiName::Parse, file_id));
+		let _guard = Guard::new(|| glsp::pop_frame());
+
+		while text.len() > 0 {
+			if let Some(form) = parser.parse(text)? {
+				return Ok(Some(form))
+			}
+		}
+
+		parser.ensure_finished()?;
+		Ok(None)
+	}
+
+	/** Equivalent to [`(parse-all text filename)`](https://gamelisp.rs/std/parse-all). */
+
+	pub fn parse_all(mut text: &str, filename: Option<&str>) -> GResult<Vec<Val>> {
+		let file_id = filename.map(|path| glsp::filename(path));
+
+		glsp::push_frame(Frame::GlspApi(GlspApiName::ParseAll, file_id));
+		let _guard = Guard::new(|| glsp::pop_frame());
+
+		let mut parser = Parser::new(file_id);
+		let mut results = Vec::new();
+		while text.len() > 0 {
+			if let Some(form) = parser.parse(&mut text)? {
+				results.push(form);
+			}
+		}
+
+		parser.ensure_finished()?;
+		Ok(results)
 	}
 
-	/**
-	Mutates the field or property bound to the given name.
-	
-	Equivalent to [`(= [rdata key] val)`](https://gamelisp.rs/std/set-access).
-	*/
-	pub fn set<S, V>(&self, key: S, val: V) -> GResult<()>
-	where
-		S: ToSym,
-		V: ToVal
-	{
-		let sym = key.to_sym()?;
-		ensure!(self.set_if_present(sym, val)?, "attempted to assign to nonexistent or \
-		        readonly prop '{}'", sym);
-		Ok(())
+	/** Equivalent to [`(parse-1 text filename)`](https://gamelisp.rs/std/parse-1). */
+
+	pub fn parse_1(mut text: &str, filename: Option<&str>) -> GResult<Val> {
+		let file_id = filename.map(|path| glsp::filename(path));
+
+		glsp::push_frame(Frame::GlspApi(GlspApiName::Parse1, file_id));
+		let _guard = Guard::new(|| glsp::pop_frame());
+
+		let mut parser = Parser::new(file_id);
+		while text.len() > 0 {
+			if let Some(form) = parser.parse(&mut text)? {
+				while text.len() > 0 {
+					if let Some(_) = parser.parse(&mut text)? {
+						bail!("parse-1 produced multiple forms")
+					}
+				}
+
+				return Ok(form)
+			}
+		}
+
+		bail!("parse-1 did not produce a form")
 	}
 
 	/**
-	Mutates the field or property bound to the given name, if any. Returns `true` if the
-	field or property exists.
-	
-	Equivalent to [`(= [rdata (? key)] val)`](https://gamelisp.rs/std/set-access).
+	Parses `text`, recording the byte range of every form.
+
+	This is intended for editor tooling, which needs more precise locations than the line 
+	numbers reported by [`glsp::parse_all`](fn.parse_all.html). Each toplevel form is returned
+	as a [`ParsedForm`](struct.ParsedForm.html), which can be used to look up the range of any 
+	of its nested forms.
+
+		let text = "(defn main ()\n  (prn 'hello))";
+		let forms = glsp::parse_with_offsets(text, None)?;
+
+		let body = &forms[0].children()[3];
+		assert!(&text[body.range()] == "(prn 'hello)");
 	*/
-	pub fn set_if_present<S, V>(&self, key: S, val: V) -> GResult<bool>
-	where
-		S: ToSym,
-		V: ToVal
-	{
-		let sym = key.to_sym()?;
 
-		match self.class.bindings.get(&sym) {
-			Some(RBinding::Prop(_, Some(rfn))) => {
-				with_vm(|vm| {
-					let mut stacks = vm.stacks.borrow_mut();
-					stacks.regs.push(Slot::RData(self.gc_self()));
-					stacks.regs.push(val.to_slot()?);
-					drop(stacks);
+	pub fn parse_with_offsets(mut text: &str, filename: Option<&str>) -> GResult<Vec<ParsedForm>> {
+		let file_id = filename.map(|path| glsp::filename(path));
 
-					rfn.receive_call(2)?;
-					Ok(true)
-				})
+		glsp::push_frame(Frame::GlspApi(GlspApiName::ParseWithOffsets, file_id));
+		let _guard = Guard::new(|| glsp::pop_frame());
+
+		let mut parser = Parser::with_offsets(file_id);
+		let mut results = Vec::new();
+		while text.len() > 0 {
+			if parser.parse(&mut text)?.is_some() {
+				results.push(parser.take_parsed_form().unwrap());
 			}
-			_ => Ok(false)
 		}
+
+		parser.ensure_finished()?;
+		Ok(results)
 	}
 
 	/**
-	Invokes a method.
-	
-	Equivalent to [`(call-meth rdata key ..args)`](https://gamelisp.rs/std/call-meth).
+	Lists the toplevel definitions in `text`, without evaluating it.
+
+	The recognized forms are `def`, `defn`, `defmacro`, `defclass`, `defmixin`, `defstruct`,
+	`defrecord`, `defonce` and `defconst`, when their first argument is a symbol. Definitions nested within other forms,
+	and definitions produced by other macros, aren't detected.
+
+		let defs = glsp::definitions("(defn spawn ())\n(defclass Enemy)")?;
+		assert!(&*defs[1].name().name() == "Enemy");
+		assert!(defs[1].name_range() == (26 .. 31));
 	*/
-	pub fn call<S, A, R>(&self, key: S, args: &A) -> GResult<R> 
-	where
-		S: ToSym,
-		A: ToCallArgs + ?Sized, 
-		R: FromVal
-	{
-		let sym = key.to_sym()?;
-		match self.call_if_present(sym, args)? {
-			Some(r) => Ok(r),
-			None => bail!("attempted to call nonexistent method '{}'", sym)
+
+	pub fn definitions(text: &str) -> GResult<Vec<Definition>> {
+		let mut definitions = Vec::new();
+		for form in &glsp::parse_with_offsets(text, None)? {
+			if let Some(definition) = Definition::from_parsed_form(form)? {
+				definitions.push(definition);
+			}
 		}
+
+		Ok(definitions)
 	}
 
 	/**
-	Invokes a method, if it exists.
-	
-	Equivalent to [`(call-meth rdata (? key) ..args)`](https://gamelisp.rs/std/call-meth).
+	Changes the output writer used by [`pr`](https://gamelisp.rs/std/pr),
+	[`prn`](https://gamelisp.rs/std/prn), [`pr!`](macro.pr.html) and 
+	[`prn!`](macro.prn.html).
+
+	The default writer is [`Stdout`](https://doc.rust-lang.org/std/io/struct.Stdout.html). Note
+	that calling `glsp::set_pr_writer` will not redirect the output of Rust macros like
+	[`println!`](https://doc.rust-lang.org/std/macro.println.html): for that, you would need
+	to use the undocumented feature [`set_print`](https://github.com/rust-lang/rust/issues/31343).
+
+		//silences pr!(), prn!(), pr and prn
+		glsp::set_pr_writer(Box::new(std::io::sink()));
+
+	The writer isn't used while a [print sink](fn.set_print_sink.html) is set. It can be 
+	replaced at any time, or configured up front using 
+	[`RuntimeBuilder::pr_writer`](struct.RuntimeBuilder.html#method.pr_writer).
 	*/
-	pub fn call_if_present<S, A, R>(&self, key: S, args: &A) -> GResult<Option<R>> 
-	where
-		S: ToSym,
-		A: ToCallArgs + ?Sized, 
-		R: FromVal
-	{
-		let sym = key.to_sym()?;
 
-		match self.class.bindings.get(&sym) {
-			Some(RBinding::Meth(rfn)) => {
-				with_vm(|vm| {
-					let mut stacks = vm.stacks.borrow_mut();
-					let starting_len = stacks.regs.len();
+	pub fn set_pr_writer(pr_writer: Box<dyn Write>) {
+		with_engine(|engine| {
+			*engine.pr_writer.borrow_mut() = pr_writer;
+		})
+	}
 
-					stacks.regs.push(Slot::RData(self.gc_self()));
-					args.to_call_args(&mut stacks.regs)?;
+	/**
+	Changes the output writer used by [`epr`](https://gamelisp.rs/std/epr),
+	[`eprn`](https://gamelisp.rs/std/eprn), [`epr!`](macro.epr.html) and 
+	[`eprn!`](macro.eprn.html).
 
-					let arg_count = stacks.regs.len() - starting_len;
-					drop(stacks);
+	That same writer is also used by GameLisp to print a stack trace when an error is 
+	not caught.
 
-					let val = rfn.receive_call(arg_count)?;
-					Ok(Some(R::from_val(&val)?))
-				})
+	The default writer is [`Stderr`](https://doc.rust-lang.org/std/io/struct.Stderr.html). Note
+	that calling `glsp::set_epr_writer` will not redirect the output of Rust macros like
+	[`panic!`](https://doc.rust-lang.org/std/macro.panic.html) or
+	[`eprintln!`](https://doc.rust-lang.org/std/macro.eprintln.html): for that, you would need
+	to use the undocumented feature [`set_panic`](https://github.com/rust-lang/rust/issues/31343).
+
+		//silences error-reporting, epr!(), eprn!(), epr and eprn
+		glsp::set_pr_writer(Box::new(std::io::sink()));
+
+	The writer isn't used while a [print sink](fn.set_print_sink.html) is set. It can be 
+	replaced at any time, or configured up front using 
+	[`RuntimeBuilder::epr_writer`](struct.RuntimeBuilder.html#method.epr_writer).
+	*/
+
+	pub fn set_epr_writer(epr_writer: Box<dyn Write>) {
+		with_engine(|engine| {
+			*engine.epr_writer.borrow_mut() = epr_writer;
+		})
+	}
+
+	/**
+	Redirects all printed text to a callback, rather than the `pr_writer` and `epr_writer`.
+
+	The sink receives the output of [`pr`](https://gamelisp.rs/std/pr), 
+	[`epr`](https://gamelisp.rs/std/epr), [`log-msg`](https://gamelisp.rs/std/log-msg) and related
+	functions and macros, uncaught errors, and the engine's own diagnostic messages. Each call
+	receives one line of text, without its trailing `'\n'`, and its [`PrintLevel`]. Lines are 
+	passed to the sink in the same order that they were printed.
+
+	An incomplete line is held back until it's completed, until text is printed at a different
+	level, until the sink is replaced, or until the `Runtime` is dropped.
+
+	The sink shouldn't print any text itself.
+
+		glsp::set_print_sink(Box::new(|level, line| {
+			my_console.push(level >= PrintLevel::Warn, line);
+		}));
+
+	The sink can be replaced at any time. To capture every line which a `Runtime` prints, 
+	including any output from its initialization, use 
+	[`RuntimeBuilder::print_sink`](struct.RuntimeBuilder.html#method.print_sink) instead.
+
+	[`PrintLevel`]: enum.PrintLevel.html
+	*/
+
+	pub fn set_print_sink(sink: Box<dyn Fn(PrintLevel, &str)>) {
+		flush_print_sink();
+
+		with_engine(|engine| {
+			*engine.print_sink.borrow_mut() = Some(Rc::from(sink));
+		})
+	}
+
+	/**
+	Replaces the text of the engine's [structured messages](enum.MessageId.html).
+
+	The formatter receives each message's id and arguments, and returns its text. This makes
+	it possible to translate the engine's most common error messages, and its warnings, into
+	the player's language:
+
+		glsp::set_message_formatter(Box::new(move |id, args| {
+			match catalog.template(id) {
+				Some(template) => args.substitute(template),
+				None => id.default_text(args)
 			}
-			_ => Ok(None)
-		}
+		}));
+
+	The default formatter is [`MessageId::default_text`](enum.MessageId.html#method.default_text).
+	If the formatter causes another message to be formatted, for example by generating an
+	error, that message uses the default formatter.
+	*/
+
+	pub fn set_message_formatter(formatter: Box<dyn Fn(MessageId, &MessageArgs) -> String>) {
+		with_engine(|engine| {
+			*engine.message_formatter.borrow_mut() = Some(Rc::from(formatter));
+		})
 	}
 
 	/**
-	Returns `true` if the given name is bound to a method.
-	
-	Equivalent to [`(has-meth? rdata key)`](https://gamelisp.rs/std/has-meth-p).
+	Renders a [structured message](enum.MessageId.html) using the current
+	[message formatter](fn.set_message_formatter.html).
 	*/
-	pub fn has_meth<S: ToSym>(&self, key: S) -> GResult<bool> {
-		let sym = key.to_sym()?;
 
-		match self.class.bindings.get(&sym) {
-			Some(RBinding::Meth(_)) => Ok(true),
-			_ => Ok(false)
+	pub fn format_message(id: MessageId, args: &MessageArgs) -> String {
+		let formatter = with_engine(|engine| {
+			if engine.formatting_message.get() {
+				None
+			} else {
+				engine.message_formatter.borrow().clone()
+			}
+		});
+
+		match formatter {
+			Some(formatter) => {
+				with_engine(|engine| engine.formatting_message.set(true));
+				let _guard = Guard::new(|| {
+					with_engine(|engine| engine.formatting_message.set(false))
+				});
+
+				formatter(id, args)
+			}
+			None => id.default_text(args)
 		}
 	}
 
-	//designed to imitate Obj::get_method(). used in vm.rs
-	pub(crate) fn get_method(&self, key: Sym) -> Option<(Slot, bool, bool, Slot)> {
-		match self.class.bindings.get(&key) {
-			Some(&RBinding::Meth(rfn)) => Some((Slot::RFn(rfn), true, false, Slot::Nil)),
-			_ => None
-		}
+	/**
+	Prints a line of text at the given level.
+
+	Equivalent to [`(log-msg level text)`](https://gamelisp.rs/std/log-msg). Without a 
+	[print sink](fn.set_print_sink.html), the text is written to the `pr_writer` when `level`
+	is `PrintLevel::Print`, or the `epr_writer` otherwise.
+	*/
+
+	pub fn log(level: PrintLevel, text: &str) {
+		let mut line = String::with_capacity(text.len() + 1);
+		line.push_str(text);
+		line.push('\n');
+
+		print_output(level, line.as_bytes()).ok();
+	}
+
+	/** Equivalent to [`(log-level)`](https://gamelisp.rs/std/log-level). */
+
+	pub fn log_level() -> PrintLevel {
+		with_engine(|engine| {
+			engine.log_level.get()
+		})
 	}
 
 	/**
-	Equivalent to [`(eq? self other)`](https://gamelisp.rs/std/eq-p).
+	Discards any printed text which is less severe than `level`.
 
-	Note that, because this may invoke an `op-eq?` method, it can potentially fail.
+	Equivalent to [`(= (log-level) level)`](https://gamelisp.rs/std/set-log-level). The default
+	level is `PrintLevel::Print`, so `PrintLevel::Trace` text is discarded. The initial level
+	can be configured using [`RuntimeBuilder::log_level`](struct.RuntimeBuilder.html#method.log_level).
+	*/
 
-	The same is true for `PartialEq` comparisons between `RData` using Rust's `==` operator.
-	In that case, if an error occurs, the operator will panic.
+	pub fn set_log_level(level: PrintLevel) {
+		with_engine(|engine| {
+			engine.log_level.set(level)
+		})
+	}
+
+	/**
+	Enables or disables tail-call elimination.
+
+	When this is enabled (the default), a call to a non-yielding GameLisp function in tail 
+	position will reuse the caller's interpreter, rather than nesting a new one. This means that
+	functions which tail-call themselves, or tail-call one another, can recurse to any depth
+	without exceeding the [maximum call depth](fn.set_max_call_depth.html).
+
+	A call is never in tail position when a [`defer`](https://gamelisp.rs/std/defer) form is 
+	pending, or when the caller is a coroutine.
+
+	Each eliminated caller is missing from stack traces, so you may wish to disable this feature 
+	while debugging. The setting only affects how code is executed, not how it's compiled, so it 
+	can be changed at any time. Its initial value can be configured using
+	[`RuntimeBuilder::tail_calls`](struct.RuntimeBuilder.html#method.tail_calls).
 	*/
-	pub fn try_eq(&self, other: &Root<RData>) -> GResult<bool> {
-		if !Rc::ptr_eq(&self.class, &other.class) {
-			return Ok(false)
-		}
 
-		let val: Option<Val> = self.call_if_present(OP_EQP_SYM, &[other])?;
-		match val {
-			Some(val) => Ok(val.is_truthy()),
-			None => Ok(false)
-		}
+	pub fn set_tail_calls(enabled: bool) {
+		with_engine(|engine| {
+			engine.vm.set_tail_calls(enabled)
+		})
 	}
-}
 
-impl PartialEq<Root<RData>> for RData {
-	fn eq(&self, other: &Root<RData>) -> bool {
-		self.try_eq(other).unwrap()
+	/**
+	Returns `true` if tail-call elimination is enabled.
+
+	See [`glsp::set_tail_calls`](fn.set_tail_calls.html).
+	*/
+
+	pub fn tail_calls() -> bool {
+		with_engine(|engine| {
+			engine.vm.tail_calls()
+		})
 	}
-}
 
-/**
-A strongly-typed reference to an [`RData`](struct.RData.html).
+	/**
+	Limits how deeply GameLisp calls may be nested.
 
-Equivalent to a [`Root<RData>`](struct.Root.html), but it enforces that the `RData` must contain
-a value of type `T`.
+	Each call to a GameLisp function (other than an eliminated [tail call](fn.set_tail_calls.html)),
+	each coroutine resumption, and each level of nested macro expansion consumes some of the 
+	native stack. When more than `depth` of them are nested, the innermost one fails with an 
+	error whose payload is the symbol `stack-overflow`, rather than overflowing the native stack
+	and aborting the process. The error message describes the innermost calls - for runaway
+	recursion, that's usually a short cycle of functions which call one another.
 
-`RRoot` tends to be more self-documenting than `Root<RData>`, and it has a slightly more
-convenient API.
-	
-	//using Root
-	let mesh = player_mesh.borrow::<Mesh>();
-	let mesh2 = enemy_mesh.take::<Mesh>();
+	Unlike `fuel-exhausted`, this error can be usefully caught using 
+	[`try`](https://gamelisp.rs/std/try). By the time it's caught, the stack has unwound, so the
+	`Runtime` can continue to be used as normal.
 
-	//using RRoot
-	let mesh = player_mesh.borrow();
-	let mesh2 = enemy_mesh.take();
-*/
+	The default depth is 256, which is safe for a release build running on a thread with a 1MB 
+	native stack. It can be raised when the `Runtime` is running on a thread with a larger stack,
+	but if it's raised too high, deep recursion will abort the process. 
 
-pub struct RRoot<T: RStore>(Root<RData>, PhantomData<Rc<RefCell<T>>>);
+	In a debug build, each call uses far more native stack, so the default depth may not be 
+	enough to prevent a native stack overflow. In that case, consider also setting a 
+	[stack budget](fn.set_stack_budget.html).
 
-impl<T: RStore> Clone for RRoot<T> {
-	fn clone(&self) -> RRoot<T> {
-		RRoot(self.0.clone(), PhantomData)
-	}
-}
+	The setting can be changed at any time. Its initial value can be configured using
+	[`RuntimeBuilder::max_call_depth`](struct.RuntimeBuilder.html#method.max_call_depth).
+	*/
 
-impl<T: RStore> Debug for RRoot<T> {
-	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-		Debug::fmt(&self.0, f)
+	pub fn set_max_call_depth(depth: u32) {
+		with_engine(|engine| {
+			engine.vm.set_max_call_depth(depth)
+		})
 	}
-}
 
-impl<T: RStore> Display for RRoot<T> {
-	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-		Display::fmt(&self.0, f)
-	}
-}
+	/**
+	Returns the maximum call depth.
 
-impl<T: RStore> Pointer for RRoot<T> {
-	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-		Pointer::fmt(&self.0, f)
+	See [`glsp::set_max_call_depth`](fn.set_max_call_depth.html).
+	*/
+
+	pub fn max_call_depth() -> u32 {
+		with_engine(|engine| {
+			engine.vm.max_call_depth()
+		})
 	}
-}
 
-impl<T: RStore> RRoot<T> {
 	/**
-	Constructs an `RRoot<T>` from a `Root<RData>`.
+	Limits how much native stack may be consumed by nested GameLisp calls.
 
-	Panics if the `RData`'s value does not belong to the type `T`.
+	When this is `Some(bytes)`, GameLisp estimates how much of the native stack has been used
+	since the outermost GameLisp call was entered, each time it nests another call. If the 
+	estimate exceeds `bytes`, the call fails with the same `stack-overflow` error as 
+	[`glsp::set_max_call_depth`](fn.set_max_call_depth.html).
+
+	This is most useful in debug builds, where the stack usage of a single call can be tens of
+	kilobytes. The budget should leave some headroom below the thread's actual stack size: for
+	example, 1MB would be a reasonable budget for a thread with a 2MB stack.
+
+	Defaults to `None`. The setting can be changed at any time. Its initial value can be
+	configured using [`RuntimeBuilder::stack_budget`](struct.RuntimeBuilder.html#method.stack_budget).
 	*/
-	pub fn new(root: Root<RData>) -> RRoot<T> {
-		assert!(root.is::<T>(), "type mismatch when constructing an RRoot<{}>", T::type_name());
-		RRoot(root, PhantomData)
-	}
 
-	///Returns a copy of the underlying `Root<RData>`.
-	pub fn to_root(&self) -> Root<RData> {
-		self.0.clone()
+	pub fn set_stack_budget(bytes: Option<usize>) {
+		with_engine(|engine| {
+			engine.vm.set_stack_budget(bytes)
+		})
 	}
 
-	///Drops the `RRoot`, returning the wrapped `Root<RData>`.
-	pub fn into_root(self) -> Root<RData> {
-		self.0
-	}
+	/**
+	Returns the native stack budget, if any.
 
-	///Returns `true` if both `RRoots` refer to the same `RData`.
-	pub fn ptr_eq(rr0: &RRoot<T>, rr1: &RRoot<T>) -> bool {
-		Root::ptr_eq(&rr0.0, &rr1.0)
-	}
+	See [`glsp::set_stack_budget`](fn.set_stack_budget.html).
+	*/
 
-	pub(crate) fn to_gc(&self) -> Gc<RData> {
-		self.0.to_gc()
+	pub fn stack_budget() -> Option<usize> {
+		with_engine(|engine| {
+			engine.vm.stack_budget()
+		})
 	}
 
-	#[allow(dead_code)]
-	pub(crate) fn into_gc(self) -> Gc<RData> {
-		self.0.into_gc()
-	}
+	/**
+	Limits the number of instructions which the GameLisp interpreter may execute.
 
-	///Equivalent to [`RData::borrow`](struct.RData.html#method.borrow).
-	pub fn borrow(&self) -> RRef<T> {
-		self.0.borrow()
-	}
+	`Some(n)` permits roughly `n` more instructions to be executed. `None`, the default, removes
+	the limit. This can be used to preempt a runaway script:
 
-	///Equivalent to [`RData::borrow_mut`](struct.RData.html#method.borrow_mut).
-	pub fn borrow_mut(&self) -> RRefMut<T> {
-		self.0.borrow_mut()
-	}
+		glsp::set_fuel(Some(1_000_000));
+		let result: GResult<Val> = glsp::call(&update_fn, &(entity,));
+		glsp::set_fuel(None);
 
-	///Equivalent to [`RData::try_borrow`](struct.RData.html#method.try_borrow).
-	pub fn try_borrow(&self) -> GResult<RRef<T>> {
-		self.0.try_borrow()
-	}
+	To keep the overhead low, fuel isn't consumed by every instruction. Instead, each iteration
+	of a loop consumes fuel equal to the length of the loop body, and each call to a GameLisp 
+	function consumes one unit of fuel. Code which doesn't loop or call GameLisp functions can
+	only execute a bounded number of instructions, so it isn't metered.
 
-	///Equivalent to [`RData::try_borrow_mut`](struct.RData.html#method.try_borrow_mut).
-	pub fn try_borrow_mut(&self) -> GResult<RRefMut<T>> {
-		self.0.try_borrow_mut()
-	}
+	When the fuel is exhausted, the interpreter fails with an error whose payload is the symbol
+	`fuel-exhausted`. The fuel remains exhausted, so if a script catches that error using 
+	[`try`](https://gamelisp.rs/std/try), its next loop iteration or function call will fail 
+	again. In practice, the error always propagates to Rust.
 
-	///Equivalent to [`RData::take`](struct.RData.html#method.take).
-	pub fn take(&self) -> GResult<T> {
-		self.0.take()
-	}
+	Time spent inside Rust functions isn't measured, unless they call back into GameLisp.
 
-	///Equivalent to [`RData::free`](struct.RData.html#method.free).
-	pub fn free(&self) -> GResult<()> {
-		self.0.free()
-	}
+	The limit can be changed at any time. A `Runtime` can also be given an initial limit using
+	[`RuntimeBuilder::fuel`](struct.RuntimeBuilder.html#method.fuel).
+	*/
 
-	///Equivalent to [`RData::is_freed`](struct.RData.html#method.is_freed).
-	pub fn is_freed(&self) -> bool {
-		self.0.is_freed()
+	pub fn set_fuel(fuel: Option<u64>) {
+		with_engine(|engine| {
+			engine.vm.set_fuel(fuel)
+		})
 	}
-}
 
+	/**
+	Returns the number of instructions which may still be executed, or `None` if there's no limit.
 
-//-------------------------------------------------------------------------------------------------
-// Span, SpanStorage and Frame
-//-------------------------------------------------------------------------------------------------
+	See [`glsp::set_fuel`](fn.set_fuel.html).
+	*/
 
-//all arrs are tagged with a Span, indicating where they were created. we use this information 
-//for reporting line numbers when an error occurs.
-#[derive(Copy, Clone, PartialEq, Eq, Hash)]
-pub(crate) enum SpanStorage {
+	pub fn remaining_fuel() -> Option<u64> {
+		with_engine(|engine| {
+			engine.vm.fuel()
+		})
+	}
 
-	//this arr was parsed from the text of a file which was passed to (load) or glsp.load(). 
-	//the Filename identifies the file, and the usize is the 1-indexed line number.
-	Loaded(Filename, usize),
+	/**
+	Sets a deadline for the GameLisp interpreter.
 
-	//this arr was allocated within a macro expander. the Sym is the macro's name, the first Span 
-	//identifies the macro's callsite (i.e. the Span of the arr from which it was expanded), and
-	//the second Span identifies the form's construction site (i.e. the Span of the (arr) or
-	//(clone) call, within a macro expander, which created the form).
-	Expanded(Option<Sym>, Span, Span),
+	Once the deadline has passed, the interpreter fails with an error whose payload is the 
+	symbol `deadline-exceeded`. Like [`glsp::set_fuel`](fn.set_fuel.html), the error can't be 
+	usefully caught by a script. Pass `None`, the default, to remove the deadline.
 
-	//a broad category for any other arr. in practice, arrs which aren't loaded from a file or
-	//created within a macro expander are very unlikely to be used as syntax. when they are,
-	//the user is unlikely to care exactly where they were generated.
-	Generated
-}
+	The clock is tested at the same points where fuel would be consumed, once every thousand or
+	so instructions. It's never tested while a Rust function is executing, so a Rust function
+	which loops for a long time can overrun the deadline.
 
-#[doc(hidden)]
-#[derive(Default, PartialEq, Eq, Hash, Copy, Clone)]
-pub struct Span(u32);
+	This function is not available on the `wasm32` target.
+	*/
 
+	#[cfg(not(target_arch = "wasm32"))]
+	pub fn set_deadline(deadline: Option<Instant>) {
+		with_engine(|engine| {
+			engine.vm.set_deadline(deadline)
+		})
+	}
 
-//-------------------------------------------------------------------------------------------------
-// glsp:: functions
-//-------------------------------------------------------------------------------------------------
+	/**
+	Registers a callback which the GameLisp interpreter invokes regularly while it's executing.
 
-pub mod glsp {
-	use super::*;
+	The callback is invoked once roughly every `interval_instrs` instructions, at the same points
+	where [fuel](fn.set_fuel.html) would be consumed. Its return value can cancel the script,
+	or pause the running coroutine so that control returns to the host's main loop:
 
-	//---------------------------------------------------------------------------------------------
-	// syms
-	//---------------------------------------------------------------------------------------------
+		let cancelled = editor.cancel_flag();
+		glsp::set_safepoint_callback(10_000, Box::new(move || {
+			if cancelled.get() {
+				SafepointAction::RaiseError(error!("cancelled by the user"))
+			} else {
+				SafepointAction::Continue
+			}
+		}));
 
-	/** Equivalent to [`(sym name)`](https://gamelisp.rs/std/sym). */
+	When the callback is invoked, the `Runtime` is in a consistent state, but the interpreter 
+	is suspended partway through a function. The callback may construct a `GError` and inspect
+	plain Rust data, but it must not call back into GameLisp: for example, it mustn't call a
+	function, load a file, or change the safepoint callback. Debug builds enforce this with an
+	assertion.
 
-	pub fn sym(name: &str) -> GResult<Sym> {
-		ensure!(glsp::is_valid_sym_str(name), "invalid sym '{}'", name);
-		glsp::sym_impl(name, SymKind::Normal)
+	Only one callback can be registered at a time; this function replaces any existing callback.
+	See also [`glsp::clear_safepoint_callback`](fn.clear_safepoint_callback.html).
+	*/
+
+	pub fn set_safepoint_callback(
+		interval_instrs: u64,
+		callback: Box<dyn FnMut() -> SafepointAction>
+	) {
+		with_engine(|engine| {
+			engine.vm.set_safepoint_callback(Some((interval_instrs, callback)))
+		})
 	}
 
-	fn sym_impl(name: &str, kind: SymKind) -> GResult<Sym> {
+	///Removes the callback registered by
+	///[`glsp::set_safepoint_callback`](fn.set_safepoint_callback.html).
+	pub fn clear_safepoint_callback() {
 		with_engine(|engine| {
-			let mut syms_map =  engine.syms_map.borrow_mut();
-			if let Some(sym) = syms_map.get(name) {
-				Ok(*sym)
-			} else {
-				let name = Rc::<str>::from(name);
-
-				let mut syms = engine.syms.borrow_mut();
-				syms.push(SymEntry {
-					name: name.clone(),
-					kind,
-					bound_global: None,
-					bound_macro: None
-				});
-				
-				//we panic rather than returning an Err here, becuase we consider running out of
-				//Syms to be an unrecoverable error, similar to out-of-memory in a Rust program
-				assert!(syms.len() - 1 <= MAX_SYM as usize,
-				        "program requires more than {} unique symbols", MAX_SYM + 1);
-				
-				let sym = Sym((syms.len() - 1) as u32);
-				syms_map.insert(name, sym);
-				
-				Ok(sym)
-			}
+			engine.vm.set_safepoint_callback(None)
 		})
 	}
 
-	/** Equivalent to [`(valid-sym-str? st)`](https://gamelisp.rs/std/valid-sym-str-p). */
+	/**
+	Formerly set the optimization level which is used when compiling GameLisp code.
 
-	pub fn is_valid_sym_str(st: &str) -> bool {
-		//one or more of the valid sym chars, optionally with a '#' suffix
-		let mut rev_iter = st.chars().rev();
-		match rev_iter.next() {
-			None => false,
-			Some('#') => st.len() > 1 && rev_iter.all(|ch| lex::is_valid_sym_char(ch)),
-			Some(last_char) => {
-				lex::is_valid_sym_char(last_char) && rev_iter.all(|ch| {
-					lex::is_valid_sym_char(ch)
-				})
-			}
-		}
-	}
+	Changing the optimization level after some code had been compiled would leave a `Runtime`
+	in a mixed state, so it can only be configured using 
+	[`RuntimeBuilder::opt_level`](struct.RuntimeBuilder.html#method.opt_level). This function
+	always returns an `Err`.
+	*/
 
-	/** Equivalent to [`(valid-sym-char? ch)`](https://gamelisp.rs/std/valid-sym-char-p). */
+	#[deprecated(note = "use RuntimeBuilder::opt_level instead")]
+	pub fn set_opt_level(_level: u8) -> GResult<()> {
+		bail!("the optimization level can't be changed after a Runtime is built: \
+		       use RuntimeBuilder::opt_level instead")
+	}
 
-	pub fn is_valid_sym_char(ch: char) -> bool {
-		lex::is_valid_sym_char(ch)
+	//temporarily overrides the optimization level, for dump_form_unoptimized
+	pub(crate) fn swap_opt_level(level: u8) -> u8 {
+		with_engine(|engine| {
+			engine.opt_level.replace(level)
+		})
 	}
 
-	/** Equivalent to [`(gensym)`](https://gamelisp.rs/std/gensym). */
+	/**
+	Returns the current optimization level.
 
-	pub fn gensym() -> Sym {
-		glsp::gensym_impl(None)
+	See [`RuntimeBuilder::opt_level`](struct.RuntimeBuilder.html#method.opt_level).
+	*/
+
+	pub fn opt_level() -> u8 {
+		with_engine(|engine| {
+			engine.opt_level.get()
+		})
 	}
 
-	/** Equivalent to [`(gensym tag)`](https://gamelisp.rs/std/gensym). */
+	/**
+	Formerly set whether the compiler records the name of each GameLisp function's parameters.
 
-	pub fn gensym_with_tag(tag: &str) -> GResult<Sym> {
-		ensure!(glsp::is_valid_sym_str(tag) && !tag.ends_with("#"),
-		        "invalid gensym tag '{}': tags should be a valid sym without a trailing '#'",
-		        tag);
-		Ok(glsp::gensym_impl(Some(tag)))
+	This setting can only be configured using 
+	[`RuntimeBuilder::retain_param_names`](struct.RuntimeBuilder.html#method.retain_param_names).
+	This function always returns an `Err`.
+	*/
+
+	#[deprecated(note = "use RuntimeBuilder::retain_param_names instead")]
+	pub fn set_retain_param_names(_enabled: bool) -> GResult<()> {
+		bail!("retain_param_names can't be changed after a Runtime is built: \
+		       use RuntimeBuilder::retain_param_names instead")
 	}
 
-	fn gensym_impl(tag: Option<&str>) -> Sym {
+	/**
+	Returns `true` if the compiler records parameter names.
+
+	See [`RuntimeBuilder::retain_param_names`](struct.RuntimeBuilder.html#method.retain_param_names).
+	*/
+
+	pub fn retain_param_names() -> bool {
 		with_engine(|engine| {
-			let counter = engine.gensym_counter.get();
-			let seed = engine.gensym_seed.borrow();
+			engine.retain_param_names.get()
+		})
+	}
 
-			let mut bytes = SmallVec::<[u8; 64]>::new();
-			write!(&mut bytes, "#<gs").unwrap();
-			if let Some(tag) = tag {
-				write!(&mut bytes, ":{}", tag).unwrap();
-			}
-			write!(&mut bytes, ":{}", counter).unwrap();
-			if let Some(ref seed) = *seed {
-				write!(&mut bytes, ":{}", seed).unwrap();
-			}
-			write!(&mut bytes, ">").unwrap();
+	/**
+	Formerly set whether the compiler checks the types of annotated parameters and return values.
 
-			engine.gensym_counter.set(counter + 1);
-			drop(seed);
+	This setting can only be configured using 
+	[`RuntimeBuilder::type_checks`](struct.RuntimeBuilder.html#method.type_checks). This 
+	function always returns an `Err`.
+	*/
 
-			let name = str::from_utf8(&bytes[..]).unwrap();
-			glsp::sym_impl(name, SymKind::Gensym).unwrap()
+	#[deprecated(note = "use RuntimeBuilder::type_checks instead")]
+	pub fn set_type_checks(_enabled: bool) -> GResult<()> {
+		bail!("type checks can't be enabled or disabled after a Runtime is built: \
+		       use RuntimeBuilder::type_checks instead")
+	}
+
+	/**
+	Returns `true` if the compiler checks the types of annotated parameters.
+
+	See [`RuntimeBuilder::type_checks`](struct.RuntimeBuilder.html#method.type_checks).
+	*/
+
+	pub fn type_checks() -> bool {
+		with_engine(|engine| {
+			engine.type_checks.get()
 		})
 	}
 
 	/**
-	Makes future gensyms globally unique.
+	Registers a function which transforms each toplevel form before it's compiled.
 
-	When code is expanded in one Runtime and then evaluated in another (as for the 
-	[`compile!`](macro.compile.html) and [`eval!`](macro.eval.html) macros, and the
-	[`glsp::load_and_compile`](fn.load_and_compile.html) function), gensym collisions may occur,
-	because the gensym counter will have been reset.
+	Whenever a toplevel form is about to be compiled by [`glsp::load`](fn.load.html), 
+	[`glsp::eval`](fn.eval.html), or any similar function, it's first fully macro-expanded, and
+	then passed through each expansion pass in the order they were registered. Each pass 
+	receives the previous pass's result, and returns the form which should be compiled in its
+	place. The final result is compiled without any further macro-expansion.
 
-	`glsp::seed_gensym` mitigates this by appending a 128-bit ID (derived from the current
-	wall-clock time) to all future gensyms produced by this `Runtime`. We don't switch it on by
-	default, because it makes gensyms' printed representation harder to read. However, it's
-	automatically switched on within [`glsp::load_and_compile`](fn.load_and_compile.html).
+	This is useful for project-wide instrumentation or linting. For example, this pass counts
+	global definitions, like `(def)` or `(defn)` forms, and injects a call to a global function
+	`on-def` after each one:
+
+		thread_local! {
+			static DEFS: Cell<usize> = Cell::new(0);
+		}
+
+		fn count_defs(form: Val) -> GResult<Val> {
+			let def_sym = glsp::sym("bind-global!")?;
+			match form {
+				Val::Arr(ref arr) if arr.len() > 0 && arr.get::<Val>(0)? == Val::Sym(def_sym) => {
+					DEFS.with(|defs| defs.set(defs.get() + 1));
+
+					let quoted_name: Val = arr.get(1)?;
+					Ok(backquote!("(do ~form (on-def ~quoted_name))"))
+				}
+				form => Ok(form)
+			}
+		}
+
+		glsp::add_expansion_pass(count_defs);
+
+	Arrs which are returned unchanged keep their original source locations. Any arr which was 
+	allocated by a pass outside of a macro expander, like those created by `arr!` or `backquote!`,
+	inherits the source location of its innermost enclosing arr.
+
+	Expansion passes don't see `(splice)` or `(let-macro)` forms, although they do see each of 
+	a `(splice)` form's children. Forms compiled while a pass is running, for example by 
+	calling `glsp::eval` from within a pass, aren't passed through any expansion passes.
+
+	The bytecode produced by [`glsp::load_and_compile`](fn.load_and_compile.html) includes
+	the effects of any expansion passes, so they aren't applied a second time by 
+	[`glsp::load_compiled`](fn.load_compiled.html). The [`compile!`](macro.compile.html) macro 
+	runs in its own `Runtime` at build time, so it's unaffected by expansion passes.
+	*/
+
+	pub fn add_expansion_pass(pass: fn(Val) -> GResult<Val>) {
+		with_engine(|engine| {
+			engine.expansion_passes.borrow_mut().push(pass)
+		})
+	}
 
-		prn!("{}", glsp::gensym()); //prints #<gs:0>
-		glsp::seed_gensym();
-		prn!("{}", glsp::gensym()); //prints #<gs:1:wTz8iriBJYB>
+	/**
+	Unregisters every function which was registered by 
+	[`glsp::add_expansion_pass`](fn.add_expansion_pass.html).
 	*/
 
-	pub fn seed_gensym() {
+	pub fn clear_expansion_passes() {
 		with_engine(|engine| {
-			//this seems to have at least 100ns resolution
-			let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+			engine.expansion_passes.borrow_mut().clear()
+		})
+	}
 
-			//to keep it as concise as possible, we use base64
-			static CHARS: [char; 64] = [
-				'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M', 
-				'N', 'O', 'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z', 
-				'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 
-				'n', 'o', 'p', 'q', 'r', 's', 't', 'u', 'v', 'w', 'x', 'y', 'z',
-				'0', '1', '2', '3', '4', '5', '6', '7', '8', '9', '+', '_'
-			];
+	//passes a fully-expanded toplevel form through each expansion pass
+	pub(crate) fn apply_expansion_passes(form: Val) -> GResult<Val> {
+		let passes = with_engine(|engine| {
+			if engine.in_expansion_pass.get() {
+				Vec::new()
+			} else {
+				engine.expansion_passes.borrow().clone()
+			}
+		});
 
-			//this reverses the order of the characters, but it doesn't matter
-			let mut seed = String::new();
-			let mut bits = nanos;
-			while bits > 0 {
-				seed.push(CHARS[(bits & 0x3f) as usize]);
-				bits >>= 6;
+		if passes.is_empty() {
+			return Ok(form)
+		}
+
+		let span = match form {
+			Val::Arr(ref arr) => arr.span(),
+			_ => Span::default()
+		};
+
+		let mut form = form;
+		{
+			with_engine(|engine| engine.in_expansion_pass.set(true));
+			let _guard = Guard::new(|| {
+				with_engine(|engine| engine.in_expansion_pass.set(false))
+			});
+
+			for pass in passes {
+				form = pass(form)?;
 			}
+		}
 
-			*engine.gensym_seed.borrow_mut() = Some(seed);
-		})
-	}
+		if let Val::Arr(ref arr) = form {
+			inherit_spans(arr, span, 0);
+		}
 
+		Ok(form)
+	}
+	
 	//---------------------------------------------------------------------------------------------
-	// globals
+	// spans and stack-tracing
 	//---------------------------------------------------------------------------------------------
 
-	/** Equivalent to [`(global s)`](https://gamelisp.rs/std/global). */
+	pub(crate) fn push_frame(frame: Frame) {
+		with_engine(|engine| {
+			engine.vm.push_frame(frame);
+		})
+	}
 
-	pub fn global<S, T>(s: S) -> GResult<T> 
-	where
-		S: ToSym, 
-		T: FromVal
-	{
+	pub(crate) fn pop_frame() {
 		with_engine(|engine| {
-			let sym = s.to_sym()?;
+			engine.vm.pop_frame();
+		})
+	}
 
-			let syms = engine.syms.borrow();
-			let entry = &syms[sym.0 as usize];
-			match entry.bound_global {
-				Some(ref global) => T::from_val(&global.val),
-				None => bail!("symbol {} is not bound to a global", entry.name)
-			}
+	pub(crate) fn enter_expander(arr: &Arr, env: Rc<Env>) -> Option<(Option<Sym>, Span, Rc<Env>)> {
+		with_engine(|engine| {
+			let name = if arr.len() >= 1 && arr.get::<Val>(0).unwrap().is_sym() {
+				Some(arr.get::<Sym>(0).unwrap())
+			} else {
+				None
+			};
+			let callsite = arr.span();
+
+			let prev_in_expander = engine.in_expander.borrow().clone();
+			*engine.in_expander.borrow_mut() = Some((name, callsite, env));
+
+			prev_in_expander
 		})
 	}
 
-	pub(crate) fn try_global<S, T>(s: S) -> GResult<Option<T>> 
-	where
-		S: ToSym, 
-		T: FromVal
-	{
+	pub(crate) fn env() -> Option<Rc<Env>> {
 		with_engine(|engine| {
-			let sym = s.to_sym()?;
-			match engine.syms.borrow()[sym.0 as usize].bound_global {
-				Some(ref global) => T::from_val(&global.val).map(|t| Some(t)),
-				None => Ok(None)
+			match *engine.in_expander.borrow() {
+				Some((_, _, ref env)) => Some(Rc::clone(env)),
+				None => None
 			}
 		})
 	}
 
-	/** Equivalent to [`(= (global s) val)`](https://gamelisp.rs/std/set-global). */
-
-	pub fn set_global<S, T>(s: S, val: T) -> GResult<()>
-	where
-		S: ToSym,
-		T: ToVal
-	{
+	pub(crate) fn leave_expander(prev: Option<(Option<Sym>, Span, Rc<Env>)>) {
 		with_engine(|engine| {
-			let sym = s.to_sym()?;
-			let val = val.to_val()?;
+			*engine.in_expander.borrow_mut() = prev;
+		})
+	}
 
-			let mut syms = engine.syms.borrow_mut();
-			let entry = &mut syms[sym.0 as usize];
+	pub(crate) fn span(storage: SpanStorage) -> Span {
+		with_engine(|engine| {
+			if storage == SpanStorage::Generated {
+				Span(0)
+			} else {
+				let mut spans_map = engine.spans_map.borrow_mut();
+				match spans_map.entry(storage) {
+					Occupied(entry) => *entry.get(),
+					Vacant(entry) => {
+						let mut spans = engine.spans.borrow_mut();
+						spans.push(storage);
 
-			match entry.bound_global {
-				Some(ref mut global) => {
-					if global.frozen {
-						let name = entry.name.clone();
-						drop(global);
-						drop(entry);
-						drop(syms);
-						bail!("attempted to mutate frozen global {}", name);
+						//see span_file_location
+						let mut location = storage;
+						let location = loop {
+							match location {
+								SpanStorage::Loaded(file_id, line) => {
+									break Some((file_id.0.get(), line))
+								}
+								SpanStorage::Expanded(_, callsite, _) => {
+									location = spans[callsite.0 as usize];
+								}
+								SpanStorage::Generated => break None
+							}
+						};
+						engine.vm.execution_slot.add_span(location);
+						
+						let span = Span((spans.len() - 1) as u32);
+						entry.insert(span);
+						span
 					}
+				}
+			}
+		})
+	}
 
-					global.val = val;
-					Ok(())
+	pub(crate) fn generated_span() -> Span {
+		Span(0)
+	}	
+
+	/**
+	Equivalent to [`(span-of form)`](https://gamelisp.rs/std/span-of).
+
+	Returns `None` if `form` isn't an arr, or if nothing is known about where it came from.
+	*/
+	pub fn span_of(form: &Val) -> Option<SpanInfo> {
+		match *form {
+			Val::Arr(ref arr) => glsp::span_info(arr.span()),
+			_ => None
+		}
+	}
+
+	pub(crate) fn span_info(mut span: Span) -> Option<SpanInfo> {
+		let mut expansions = Vec::new();
+		let location = loop {
+			match glsp::span_storage(span) {
+				SpanStorage::Loaded(file_id, line) => {
+					break Some((glsp::filename_str(file_id).to_string(), line))
 				}
-				None => {
-					let name = entry.name.clone();
-					drop(entry);
-					drop(syms);
-					bail!("symbol {} is not bound to a global", name)
+				SpanStorage::Expanded(macro_name, callsite, _) => {
+					expansions.push(macro_name);
+					span = callsite;
 				}
+				SpanStorage::Generated => break None
 			}
-		})
+		};
+
+		if location.is_none() && expansions.is_empty() {
+			None
+		} else {
+			Some(SpanInfo { location, expansions })
+		}
 	}
 
-	pub(crate) enum TrySetGlobalOutcome {
-		Success,
-		NotBound,
-		Frozen
+	/**
+	Equivalent to [`(with-span form other)`](https://gamelisp.rs/std/with-span).
+
+	Attaches `other`'s source location to `form`, so that any error raised by the code which 
+	`form` compiles to will be reported at `other`'s location instead. `form` is modified and
+	returned, unless it's frozen (for example, a quoted literal), in which case a shallow copy
+	is returned instead.
+
+	If either `form` or `other` isn't an arr, `form` is returned unchanged.
+	*/
+	pub fn with_span(form: &Val, other: &Val) -> Val {
+		match *other {
+			Val::Arr(ref other) => glsp::respan(form, other.span()),
+			_ => form.clone()
+		}
 	}
 
-	pub(crate) fn try_set_global<S, T>(s: S, t: T) -> GResult<TrySetGlobalOutcome>
-	where
-		S: ToSym, 
-		T: ToVal
-	{
-		with_engine(|engine| {
-			let sym = s.to_sym()?;
-			let val = t.to_val()?;
+	/**
+	Equivalent to [`(spanned filename line form)`](https://gamelisp.rs/std/spanned).
 
-			let mut syms = engine.syms.borrow_mut();
-			let entry = &mut syms[sym.0 as usize];
+	Attributes `form` to the given file and 1-indexed line number, which don't need to refer to
+	a real file. Like [`glsp::with_span`](fn.with_span.html), `form` is modified and returned 
+	unless it's frozen. If `form` isn't an arr, it's returned unchanged.
+	*/
+	pub fn spanned(filename: &str, line: usize, form: &Val) -> GResult<Val> {
+		ensure!(line >= 1, "line numbers start from 1, but received {}", line);
 
-			match entry.bound_global {
-				Some(ref mut global) => {
-					if global.frozen {
-						return Ok(TrySetGlobalOutcome::Frozen)
-					}
+		let span = glsp::span(SpanStorage::Loaded(glsp::filename(filename), line));
+		Ok(glsp::respan(form, span))
+	}
 
-					global.val = val;
-					Ok(TrySetGlobalOutcome::Success)
-				}
-				None => Ok(TrySetGlobalOutcome::NotBound)
+	fn respan(form: &Val, span: Span) -> Val {
+		match *form {
+			Val::Arr(ref arr) => {
+				let arr = if arr.is_frozen() { arr.shallow_clone() } else { arr.clone() };
+				arr.set_span(span);
+				Val::Arr(arr)
 			}
-		})
+			_ => form.clone()
+		}
 	}
 
-	/** Equivalent to [`(freeze-global! s)`](https://gamelisp.rs/std/freeze-global-mut). */
+	pub(crate) fn span_storage(span: Span) -> SpanStorage {
+		with_engine(|engine| {
+			engine.spans.borrow()[span.0 as usize]
+		})
+	}
 
-	pub fn freeze_global<S>(s: S) -> GResult<()>
+	//simplifies a Span into a brief file location, e.g. "scripts/main.glsp:10". only used by (try)
+	//and (file-location). if this span was Loaded from a file, or transitively expanded from a 
+	//macro callsite which was itself Loaded from a file, writes the file location to `f` and
+	//returns Ok(true). otherwise, returns Ok(false).
+	pub(crate) fn span_file_location<F>(f: &mut F, mut span: Span) -> Result<bool, fmt::Error>
 	where
-		S: ToSym
+		F: fmt::Write
 	{
-		with_engine(|engine| {
-			let sym = s.to_sym()?;
-
-			let mut syms = engine.syms.borrow_mut();
-			let entry = &mut syms[sym.0 as usize];
-
-			match entry.bound_global {
-				Some(ref mut global) => {
-					global.frozen = true;
-					Ok(())
+		loop {
+			match glsp::span_storage(span) {
+				SpanStorage::Expanded(_, macro_invocation_span, _) => {
+					span = macro_invocation_span;
 				}
-				None => {
-					let name = entry.name.clone();
-					drop(entry);
-					drop(syms);
-					bail!("attempted to freeze the unbound global {}", name)
+				SpanStorage::Loaded(file_id, line) => {
+					write!(f, "{}:{}", &glsp::filename_str(file_id), line)?;
+					return Ok(true)
 				}
+				SpanStorage::Generated => {
+					return Ok(false)
+				}
+			}
+		}
+	}
+
+	//span_context outputs a description of a single Span to surround a gfn call, rfn call, or
+	//bail_at!() in a stack trace. e.g.:
+	//
+	//	(some-macro) at scripts/main.glsp:10
+	//		expanded to (other-macro) at scripts/main.glsp:20
+	//		expanded to (some-call) at scripts/main.glsp:30
+	//
+	pub(crate) fn span_context<F, C>(
+		f: &mut F,
+		span: Span,
+		callback: &C
+	) -> fmt::Result
+	where
+		F: fmt::Write,
+		C: ?Sized + Fn(&mut F) -> fmt::Result
+	{
+		match glsp::span_storage(span) {
+			SpanStorage::Generated => {
+				callback(f)?;
+			}
+			SpanStorage::Loaded(file_id, line_number) => {
+				callback(f)?;
+				write!(f, " at {}:{}", &glsp::filename_str(file_id), line_number)?;
 			}
-		})
-	}
+			SpanStorage::Expanded(macro_name, expander_callsite, mut constructor_callsite) => {
+				let print_macro_name: &dyn Fn(&mut F) -> fmt::Result = &|f| {
+					match macro_name {
+						Some(macro_name) => write!(f, "({})", macro_name),
+						None => write!(f, "an anonymous macro")
+					}
+				};
 
-	//freezes each global which might be caught by the op-transforms pass, like "+". we can't do
-	//this in the Engine constructor because the stdlib initialization code needs to be able to
-	//bind new rfns to these syms.
-	#[doc(hidden)]
-	pub fn freeze_transform_fns() {
-		with_engine(|engine| {
-			let mut syms = engine.syms.borrow_mut();
+				span_context(f, expander_callsite, print_macro_name)?;
 
-			for sym in STOCK_SYMS_BY_KIND[2] {
-				let entry = &mut syms[sym.0 as usize];
+				write!(f, "\n    expanded to ")?;
+				callback(f)?;
 
-				if entry.kind != SymKind::StockTransform {
-					panic!()
+				//within a macro, it's possible that arr constructors may be themselves expanded
+				//from a macro. we could potentially recurse in that case, but for now we just
+				//report the macro invocation's own constructor site as the construction site.
+				while let SpanStorage::Expanded(_, _, meta) = 
+				          glsp::span_storage(constructor_callsite) {
+					constructor_callsite = meta;
 				}
 
-				if let Some(ref mut global) = entry.bound_global {
-					global.frozen = true;
-				} else {
-					panic!("transform fn {} is not bound", entry.name)
+				match glsp::span_storage(constructor_callsite) {
+					SpanStorage::Generated => (),
+					SpanStorage::Loaded(file_id, line_number) => {
+						write!(f, " at {}:{}", &glsp::filename_str(file_id), line_number)?;
+					}
+					SpanStorage::Expanded(..) => unreachable!()
 				}
 			}
-		})
+		}
+
+		Ok(())
 	}
 
-	/** Equivalent to [`(has-global? s)`](https://gamelisp.rs/std/has-global-p). */
+	/** Equivalent to [`(file-location)`](https://gamelisp.rs/std/file-location). */
 
-	pub fn has_global<S>(s: S) -> GResult<bool>
-	where
-		S: ToSym
-	{
+	//error messages can be stringified in either a short form, or a long form (a stack trace).
+	//the short form is a brief file location, followed by the stringification of the arguments
+	//passed to bail!(), ensure!() or (err). this method generates that brief file location.
+	//the user can also generate this directly using the (file-location) builtin fn.
+	pub fn file_location() -> Option<String> {
 		with_engine(|engine| {
-			let sym = s.to_sym()?;
-			Ok(engine.syms.borrow()[sym.0 as usize].bound_global.is_some())
+			let mut builder = String::new();
+			if engine.vm.file_location(&mut builder).unwrap() {
+				Some(builder)
+			} else {
+				None
+			}
 		})
 	}
 
-	/** Equivalent to [`(bind-global! s)`](https://gamelisp.rs/std/bind-global-mut). */
+	/** Equivalent to [`(stack-trace)`](https://gamelisp.rs/std/stack-trace). */
 
-	pub fn bind_global<S, T>(s: S, t: T) -> GResult<()>
-	where
-		S: ToSym, 
-		T: ToVal
-	{
+	//this method generates the long form: a full stack trace. we don't emit any leading or
+	//trailing linebreaks.
+	pub fn stack_trace() -> String {
 		with_engine(|engine| {
-			let sym = s.to_sym()?;
-			ensure!(sym.is_bindable(), "unable to bind name '{}' to global", sym);
-
-			let val = t.to_val()?;
-
-			let mut syms = engine.syms.borrow_mut();
-			let entry = &mut syms[sym.0 as usize];
-
-			if entry.bound_global.is_none() {
-				entry.bound_global = Some(GlobalEntry {
-					val,
-					frozen: false
-				});
-
-				Ok(())
-			} else {
-				let name = entry.name.clone();
-				drop(entry);
-				drop(syms);
-				bail!("attempted to bind the global '{}', which is already bound", name)
-			}
+			let mut builder = String::new();
+			engine.vm.stack_trace(&mut builder).unwrap();
+			builder
 		})
 	}
 
-	/** Equivalent to [`(del-global! s)`](https://gamelisp.rs/std/del-global-mut). */
+	//---------------------------------------------------------------------------------------------
+	// try
+	//---------------------------------------------------------------------------------------------
 
-	pub fn del_global<S>(s: S) -> GResult<()>
+	/**
+	Calls a function with either verbose or brief error-reporting.
+
+	[`try`](https://gamelisp.rs/std/try) and [`try-verbose`](https://gamelisp.rs/std/try-verbose)
+	work by invoking `glsp::try_call`.
+
+	The default error-reporting style is verbose.
+	*/
+	pub fn try_call<R, A>(is_verbose: bool, receiver: &R, args: &A) -> GResult<Val>
 	where
-		S: ToSym
+		R: CallableOps,
+		A: ToCallArgs + ?Sized
 	{
 		with_engine(|engine| {
-			let sym = s.to_sym()?;
-
-			let mut syms = engine.syms.borrow_mut();
-			let entry = &mut syms[sym.0 as usize];
+			let prev = engine.errors_verbose.get();
+			engine.errors_verbose.set(is_verbose);
+			let _guard = Guard::new(|| engine.errors_verbose.set(prev));
 
-			if let Some(ref mut global) = entry.bound_global {
-				ensure!(!global.frozen, "attempted to unbind '{}', which is frozen", entry.name);
+			glsp::call(receiver, args)
+		})
+	}
 
-				entry.bound_global = None;
-				Ok(())
-			} else {
-				let name = entry.name.clone();
-				drop(entry);
-				drop(syms);
-				bail!("attempted to unbind '{}', which is not bound to a global", name)
-			}
+	pub(crate) fn errors_verbose() -> bool {
+		with_engine(|engine| {
+			engine.errors_verbose.get()
 		})
 	}
 
 	//---------------------------------------------------------------------------------------------
-	// macros
+	// allocation
 	//---------------------------------------------------------------------------------------------
 
-	/** Equivalent to [`(macro s)`](https://gamelisp.rs/std/macro). */
-
-	pub fn get_macro<S: ToSym>(s: S) -> GResult<Expander> {
+	pub(crate) fn alloc<T: Allocate>(t: T) -> Root<T> {
 		with_engine(|engine| {
-			let sym = s.to_sym()?;
-			match engine.syms.borrow()[sym.0 as usize].bound_macro {
-				Some(ref expander) => Ok(expander.clone()),
-				None => bail!("symbol {} is not bound to a macro", sym)
-			}
+			engine.heap.alloc(t)
 		})
 	}
 
-	/** Equivalent to [`(= (macro s) expander)`](https://gamelisp.rs/std/set-macro). */
+	#[doc(hidden)]
+	pub fn alloc_gc<T: Allocate>(t: T) -> Gc<T> {
+		with_engine(|engine| {
+			engine.heap.alloc_gc(t)
+		})
+	}
 
-	pub fn set_macro<S: ToSym>(s: S, expander: Expander) -> GResult<()> {
+	//returns the Span which should be assigned to a newly-allocated arr, allocated at `callsite`
+	pub(crate) fn new_arr_span(callsite: Option<Span>) -> Span {
 		with_engine(|engine| {
-			let sym = s.to_sym()?;
-			let mut syms = engine.syms.borrow_mut();
-			match syms[sym.0 as usize].bound_macro {
-				Some(ref mut storage) => {
-					*storage = expander;
-					Ok(())
+			if let Some(callsite) = callsite {
+				if let Some((expander_name, expander_callsite, _)) = *engine.in_expander.borrow() {
+					glsp::span(SpanStorage::Expanded(expander_name, expander_callsite, callsite))
+				} else {
+					Span::default()
 				}
-				None => {
-					drop(syms);
-					bail!("symbol {} is not bound to a macro", sym)
+			} else {
+				if engine.vm.in_expander() {
+					//when `callsite` is None, that means the arr is being allocated by a call
+					//to glsp::arr(), glsp::call(), etc., rather than an OpArr instr or something 
+					//else which can pass in an explicit "current span". under those circumstances, 
+					//the innermost available Span will be the callsite of the innermost active 
+					//gfn/rfn call, if any, within the innermost expander - or the callsite of
+					//the expander itself, otherwise. 
+
+					//possible future extension (todo) would be to add a type of Span which refers
+					//to a line of rust code, rather than a line of glsp code. couldn't use this
+					//for glsp::call, but we could use it for macros like arr![] and backquote!().
+
+					engine.vm.expander_cur_span()
+				} else {
+					Span::default()
 				}
 			}
 		})
 	}
 
-	/** Equivalent to [`(has-macro? s)`](https://gamelisp.rs/std/has-macro-p). */
+	///Constructs an empty [array](struct.Arr.html).
+	pub fn arr() -> Root<Arr> {
+		let arr = with_heap(|heap| heap.recycler.arr());
+		arr.set_span(glsp::new_arr_span(None));
+		arr
+	}
 
-	pub fn has_macro<S: ToSym>(s: S) -> GResult<bool> {
-		with_engine(|engine| {
-			let sym = s.to_sym()?;
-			Ok(engine.syms.borrow()[sym.0 as usize].bound_macro.is_some())
-		})
+	///Constructs an empty [array](struct.Arr.html) with space for at least `capacity` elements.
+	pub fn arr_with_capacity(capacity: usize) -> Root<Arr> {
+		let arr = with_heap(|heap| heap.recycler.arr_with_capacity(capacity));
+		arr.set_span(glsp::new_arr_span(None));
+		arr
 	}
 
-	/** Equivalent to [`(bind-macro! s)`](https://gamelisp.rs/std/bind-macro-mut). */
+	/**
+	Constructs an [array](struct.Arr.html) which contains `reps` repetitions of `elem`.
 
-	pub fn bind_macro<S: ToSym>(s: S, expander: Expander) -> GResult<()> {
-		with_engine(|engine| {
-			let sym = s.to_sym()?;
-			let mut syms = engine.syms.borrow_mut();
-			match syms[sym.0 as usize].bound_macro {
-				Some(_) => {
-					drop(syms);
-					bail!("attempted to bind the macro {}, which is already bound", sym)
-				}
-				ref mut storage @ None => {
-					*storage = Some(expander);
-					Ok(())
-				}
-			}
-		})
+	Returns an `Err` if [type conversion](trait.ToVal.html) fails.
+	*/
+	pub fn arr_from_elem<T: ToVal>(elem: T, reps: usize) -> GResult<Root<Arr>> {
+		let arr = with_heap(|heap| heap.recycler.arr_from_elem(elem, reps))?;
+		arr.set_span(glsp::new_arr_span(None));
+		Ok(arr)
 	}
 
-	/** Equivalent to [`(del-macro! s)`](https://gamelisp.rs/std/del-macro-mut). */
+	/**
+	Constructs an [array](struct.Arr.html) from the contents of a Rust iterator.
 
-	pub fn del_macro<S: ToSym>(s: S) -> GResult<()> {
-		with_engine(|engine| {
-			let sym = s.to_sym()?;
-			let mut syms = engine.syms.borrow_mut();
-			match syms[sym.0 as usize].bound_macro {
-				ref mut storage @ Some(_) => {
-					*storage = None;
-					Ok(())
-				}
-				None => {
-					drop(syms);
-					bail!("attempted to unbind the macro {}, which is not bound", sym)
-				}
-			}
-		})
+	Returns an `Err` if [type conversion](trait.ToVal.html) fails for any element.
+	*/
+	pub fn arr_from_iter<T>(iter: T) -> GResult<Root<Arr>> 
+	where
+		T: IntoIterator,
+		T::Item: ToVal
+	{
+		let arr = with_heap(|heap| heap.recycler.arr_from_iter(iter))?;
+		arr.set_span(glsp::new_arr_span(None));
+		Ok(arr)
 	}
 
-	//---------------------------------------------------------------------------------------------
-	// filenames
-	//---------------------------------------------------------------------------------------------
-
-	#[doc(hidden)]
-	pub fn filename(st: &str) -> Filename {
-		with_engine(|engine| {
-			let mut filenames_map = engine.filenames_map.borrow_mut();
-			if let Some(filename) = filenames_map.get(st) {
-				*filename
-			} else {
-				let mut filenames = engine.filenames.borrow_mut();
+	///Constructs an empty [string](struct.Str.html).
+	pub fn str() -> Root<Str> {
+		glsp::alloc(Str::new())
+	}
 
-				//see glsp::sym() for the rationale behind panicking here
-				assert!(filenames.len() <= u32::MAX as usize,
-				        "cannot load more than {} unique files", (u32::MAX as u64) + 1);
+	///Constructs an empty [string](struct.Str.html) with the same contents as a Rust string slice.
+	pub fn str_from_rust_str(src: &str) -> Root<Str> {
+		glsp::alloc(Str::from_rust_str(src))
+	}
 
-				let rc = Rc::<str>::from(st);
-				filenames.push(Rc::clone(&rc));
+	/**
+	Constructs a [string](struct.Str.html) from the characters in a Rust iterator.
 
-				let id = u32::try_from(filenames.len() - 1).unwrap();
-				let filename = Filename(NonZeroU32::new(id).unwrap());
-				filenames_map.insert(rc, filename);
-				
-				filename
-			}
-		})
+	Returns an `Err` if [type conversion](trait.IntoElement.html) fails for any element.
+	*/
+	pub fn str_from_iter<T>(iter: T) -> GResult<Root<Str>> 
+	where
+		T: IntoIterator,
+		T::Item: IntoElement<char>
+	{
+		Ok(glsp::alloc(Str::from_iter(iter)?))
 	}
 
-	pub(crate) fn filename_str(filename: Filename) -> Rc<str> {
-		with_engine(|engine| {
-			Rc::clone(&engine.filenames.borrow()[filename.0.get() as usize])
-		})
+	///Constructs an empty [string](struct.Str.html) with space for at least `capacity` characters.
+	pub fn str_with_capacity(capacity: usize) -> Root<Str> {
+		glsp::alloc(Str::with_capacity(capacity))
 	}
 
-	//---------------------------------------------------------------------------------------------
-	// libs, rdata
-	//---------------------------------------------------------------------------------------------
-
 	/**
-	Moves a Rust value onto the garbage-collected heap.
+	Equivalent to [`(intern st)`](https://gamelisp.rs/std/intern).
 
-	Rust types are automatically registered with the `Runtime` when `glsp::rdata` is first called
-	for a value of that type. If two types share the same unprefixed name, like
-	`video::Clip` and `audio::Clip`, it's an error.
+	Returns the canonical [string](struct.Str.html) with the same contents as `st`. When no such
+	string exists yet, `st` itself becomes canonical if it's frozen; otherwise, a frozen copy
+	of `st` is allocated.
+
+	Interned strings are never mutable, and they're never deallocated. Comparing two interned
+	strings for equality only compares their addresses.
 	*/
+	pub fn intern(st: &Root<Str>) -> Root<Str> {
+		if st.is_interned() {
+			return st.clone()
+		}
 
-	pub fn rdata<T: RStore>(rdata: T) -> GResult<Root<RData>> {
+		let contents = st.to_string();
 		with_engine(|engine| {
-			let class_rc = match engine.rclasses.borrow_mut().entry(rdata.type_id()) {
-				Vacant(entry) => {
-					let type_name = T::type_name();
-					ensure!(engine.rclass_names.borrow_mut().insert(&type_name),
-					        "multiple distinct RData types share the same name: {}", type_name);
+			let mut interned = engine.interned.borrow_mut();
+			if let Some(canonical) = interned.get(&contents) {
+				return canonical.clone()
+			}
 
-					let class = Rc::new(T::rclass()?);
-					entry.insert(Rc::clone(&class));
-					class
-				}
-				Occupied(entry) => {
-					Rc::clone(&*entry.get())
-				}
-			};
+			let canonical = if st.is_frozen() { st.clone() } else { st.shallow_clone() };
+			canonical.header().intern();
 
-			let root = glsp::alloc(RData::new(rdata, class_rc));
-			root.gc_self.set(Some(root.to_gc()));
-			Ok(root)
+			interned.insert(contents, canonical.clone());
+			canonical
 		})
 	}
 
+	///Constructs an empty [table](struct.Tab.html).
+	pub fn tab() -> Root<Tab> {
+		glsp::alloc(Tab::new())
+	}
+
 	/**
-	Moves a Rust value onto the garbage-collected heap, returning a typed pointer.
+	Constructs a [table](struct.Tab.html) from the key/value pairs in a Rust iterator.
 
-	This function is a shorthand for
-	[`RRoot::<T>::new(glsp::rdata(rdata)?)`](struct.RRoot.html#method.new).
+	Duplicate keys are permitted.
+
+	Returns an `Err` if [type conversion](trait.ToVal.html) fails for any key or value.
 	*/
-	pub fn rroot<T: RStore>(rdata: T) -> GResult<RRoot<T>> {
-		Ok(RRoot::new(glsp::rdata(rdata)?))
+	pub fn tab_from_iter<T, K, V>(iter: T) -> GResult<Root<Tab>> 
+	where
+		T: IntoIterator<Item = (K, V)>,
+		K: ToVal,
+		V: ToVal
+	{
+		Ok(glsp::alloc(Tab::from_iter(iter)?))
 	}
 
-	/**
-	Registers an instance of a library type.
-
-	Library types are singletons: if the active `Runtime` already contains a library of type
-	`T`, this function will panic.
+	///Constructs an empty [table](struct.Tab.html) with space for at least `capacity` elements.
+	pub fn tab_with_capacity(capacity: usize) -> Root<Tab> {
+		glsp::alloc(Tab::with_capacity(capacity))
+	}
 
-	When the active `Runtime` is dropped, each of its libraries will be dropped in the
-	reverse order that they were registered.
+	/**
+	Constructs an empty [table](struct.Tab.html) which returns `default` when indexed with a
+	missing key.
 
-	Once registered, it's possible to remove a library from the `Runtime` with
-	[`glsp::take_lib`](fn.take_lib.html), or borrow it with [`glsp::lib`](fn.lib.html), 
-	[`glsp::lib_mut`](fn.lib_mut.html), [`glsp::try_lib`](fn.try_lib.html) and
-	[`glsp::try_lib_mut`](fn.try_lib_mut.html).
+	Equivalent to [`(tab-with-default default)`](https://gamelisp.rs/std/tab-with-default).
 
-	It can be more convenient to borrow a library by calling 
-	[`T::borrow()`](trait.Lib.html#method.borrow), and other similar methods on the
-	[`Lib` trait](trait.Lib.html).
+	Returns an `Err` if `default` isn't [deep-frozen](enum.Val.html#method.is_deep_frozen).
 	*/
+	pub fn tab_with_default<V: ToVal>(default: V) -> GResult<Root<Tab>> {
+		Ok(glsp::alloc(Tab::with_default(default.to_slot()?)?))
+	}
 
-	pub fn add_lib<T: Lib>(lib: T) {
-		with_engine(|engine| {
-			let rc = Rc::new(RefCell::new(lib)) as Rc<dyn Any>;
-			if engine.libs.borrow_mut().insert(TypeId::of::<T>(), rc).is_some() {
-				panic!("glsp.add_lib() called twice for the same type");
-			}
+	/**
+	Constructs an empty [table](struct.Tab.html) which holds its keys weakly.
 
-			engine.libs_ordering.borrow_mut().push(TypeId::of::<T>());
-		})
+	Equivalent to [`(weak-key-tab)`](https://gamelisp.rs/std/weak-key-tab).
+
+	When a key is an object, rdata, table, function, class, coroutine or iterator, the table 
+	doesn't keep it alive. Once it's garbage-collected, its entry is removed. Other keys, 
+	and all values, are held as normal.
+	*/
+	pub fn weak_key_tab() -> Root<Tab> {
+		glsp::weak_tab(Weakness::WeakKeys)
 	}
 
 	/**
-	Unregisters a value previously registered using [`glsp::add_lib`](fn.add_lib.html), and
-	returns it.
+	Constructs an empty [table](struct.Tab.html) which holds its values weakly.
 
-	Returns `Err` if no library is registered for the type `T`, or if a library exists but it's
-	currently borrowed.
+	Equivalent to [`(weak-value-tab)`](https://gamelisp.rs/std/weak-value-tab).
+
+	When a value is an object, rdata, table, function, class, coroutine or iterator, the table
+	doesn't keep it alive. Once it's garbage-collected, its entry is removed. Other values,
+	and all keys, are held as normal.
 	*/
+	pub fn weak_value_tab() -> Root<Tab> {
+		glsp::weak_tab(Weakness::WeakValues)
+	}
 
-	pub fn take_lib<T: Lib>() -> GResult<T> {
+	pub(crate) fn weak_tab(weakness: Weakness) -> Root<Tab> {
 		with_engine(|engine| {
-			let rc = match engine.libs.borrow_mut().remove(&TypeId::of::<T>()) {
-				Some(rc) => rc.downcast::<RefCell<T>>().unwrap(),
-				None => bail!("attempted to take nonexistent lib {}", type_name::<T>())
-			};
-
-			match Rc::try_unwrap(rc) {
-				Ok(ref_cell) => Ok(ref_cell.into_inner()),
-				Err(_) => bail!("called take_lib for {}, which is currently borrowed", 
-				                type_name::<T>())
-			}
+			let tab = engine.heap.alloc(Tab::with_weakness(weakness));
+			engine.heap.register_weak_tab(tab.as_gc());
+			tab
 		})
 	}
 
-	///Equivalent to [`Lib::borrow`](trait.Lib.html#method.borrow).
-	pub fn lib<T: Lib>() -> LibRef<T> {
-		match glsp::try_lib::<T>() {
-			Ok(lib_ref) => lib_ref,
-			Err(_) => panic!("attempted to access nonexistent lib {}", type_name::<T>())
-		}
-	}
-
-	///Equivalent to [`Lib::borrow_mut`](trait.Lib.html#method.borrow_mut).
-	pub fn lib_mut<T: Lib>() -> LibRefMut<T> {
-		match glsp::try_lib_mut::<T>() {
-			Ok(lib_ref_mut) => lib_ref_mut,
-			Err(_) => panic!("attempted to access nonexistent lib {}", type_name::<T>())
-		}
+	///Constructs an empty [persistent array](struct.PArr.html).
+	pub fn parr() -> Root<PArr> {
+		glsp::alloc(PArr::new())
 	}
 
-	///Equivalent to [`Lib::try_borrow`](trait.Lib.html#method.try_borrow).
-	pub fn try_lib<T: Lib>() -> GResult<LibRef<T>> {
-		with_engine(|engine| {
-			let libs = engine.libs.borrow();
-
-			let rc = match libs.get(&TypeId::of::<T>()) {
-				Some(rc) => rc.clone(),
-				None => bail!("lib type {} was never registered, or has been \
-				               dropped", type_name::<T>())
-			};
-
-			let rc_ref_cell = rc.downcast::<RefCell<T>>().unwrap();
+	/**
+	Constructs a [persistent array](struct.PArr.html) from the elements of a Rust iterator.
 
-			if rc_ref_cell.try_borrow().is_err() {
-				bail!("attempted to borrow lib {} during a mutable borrow", type_name::<T>())
-			}
+	Returns an `Err` if [type conversion](trait.ToVal.html) fails for any element.
+	*/
+	pub fn parr_from_iter<T>(iter: T) -> GResult<Root<PArr>> 
+	where
+		T: IntoIterator,
+		T::Item: ToVal
+	{
+		let mut slots = Vec::new();
+		for item in iter {
+			slots.push(item.to_slot()?);
+		}
 
-			Ok(LibRef {
-				handle: OwningHandle::new(rc_ref_cell)
-			})
-		})
+		Ok(glsp::alloc(slots.into_iter().collect::<PArr>()))
 	}
 
-	///Equivalent to [`Lib::try_borrow_mut`](trait.Lib.html#method.try_borrow_mut).
-	pub fn try_lib_mut<T: Lib>() -> GResult<LibRefMut<T>> {
-		with_engine(|engine| {
-			let libs = engine.libs.borrow();
+	///Constructs an empty [persistent table](struct.PTab.html).
+	pub fn ptab() -> Root<PTab> {
+		glsp::alloc(PTab::new())
+	}
 
-			let rc = match libs.get(&TypeId::of::<T>()) {
-				Some(rc) => rc.clone(),
-				None => bail!("lib type {} was never registered, or has been \
-				               dropped", type_name::<T>())
-			};
+	/**
+	Constructs a [persistent table](struct.PTab.html) from the key/value pairs in a Rust iterator.
 
-			let rc_ref_cell = rc.downcast::<RefCell<T>>().unwrap();
+	Duplicate keys are permitted. The last value for each key is kept.
 
-			if rc_ref_cell.try_borrow_mut().is_err() {
-				bail!("attempted to mutably borrow {}, which is already borrowed", type_name::<T>())
-			}
+	Returns an `Err` if [type conversion](trait.ToVal.html) fails for any key or value.
+	*/
+	pub fn ptab_from_iter<T, K, V>(iter: T) -> GResult<Root<PTab>> 
+	where
+		T: IntoIterator<Item = (K, V)>,
+		K: ToVal,
+		V: ToVal
+	{
+		let mut entries = Vec::new();
+		for (key, value) in iter {
+			entries.push((key.to_slot()?, value.to_slot()?));
+		}
 
-			Ok(LibRefMut {
-				handle: OwningHandle::new_mut(rc_ref_cell)
-			})
-		})
+		Ok(glsp::alloc(entries.into_iter().collect::<PTab>()))
 	}
 
-	//---------------------------------------------------------------------------------------------
-	// rfns
-	//---------------------------------------------------------------------------------------------
-
 	/**
-	Creates a GameLisp value which represents a Rust function.
+	Constructs a [`vec2`](struct.Vec2.html).
 
-	The `wrapped_fn` parameter should be constructed using the [`rfn!()`](macro.rfn.html) macro.
+	Equivalent to [`(vec2 x y)`](https://gamelisp.rs/std/vec2).
+	*/
+	pub fn vec2(x: Flo, y: Flo) -> Root<Vec2> {
+		glsp::alloc(Vec2::new([x, y]))
+	}
 
-	When binding a Rust function to a global variable, it's usually more convenient to use 
-	[`glsp::bind_rfn`](fn.bind_rfn.html) instead.
+	/**
+	Constructs a [`vec3`](struct.Vec3.html).
 
-	Functions at the same address are deduplicated. For example, repeated calls to 
-	`glsp::rfn("swap-bytes", i32::swap_bytes)` will return the same `RFn` every time.
+	Equivalent to [`(vec3 x y z)`](https://gamelisp.rs/std/vec3).
 	*/
+	pub fn vec3(x: Flo, y: Flo, z: Flo) -> Root<Vec3> {
+		glsp::alloc(Vec3::new([x, y, z]))
+	}
 
-	pub fn rfn(wrapped_fn: WrappedFn) -> RFn {
-		with_engine(|engine| {
-			let address = wrapped_fn.as_usize();
-
-			let mut rfns_map =  engine.rfns_map.borrow_mut();
-			if let Some(rfn) = rfns_map.get(&address) {
-				*rfn
-			} else {
-				let mut rfns = engine.rfns.borrow_mut();
-				rfns.push(RFnEntry {
-					name: None,
-					wrapped_fn
-				});
+	/**
+	Constructs a [`mat3`](struct.Mat3.html) from its three columns.
 
-				let id = u32::try_from(rfns.len() - 1).unwrap();
-				let rfn = RFn(NonZeroU32::new(id).unwrap());
-				rfns_map.insert(address, rfn);
+	Equivalent to [`(mat3 x-axis y-axis z-axis)`](https://gamelisp.rs/std/mat3).
+	*/
+	pub fn mat3(cols: [[Flo; 3]; 3]) -> Root<Mat3> {
+		let mut elements = [0.0; 9];
+		for (i, col) in cols.iter().enumerate() {
+			elements[i * 3 .. i * 3 + 3].copy_from_slice(col);
+		}
 
-				rfn
-			}
-		})
+		glsp::alloc(Mat3::new(elements))
 	}
 
 	/**
-	Creates a GameLisp value which represents a Rust function, with a name.
-
-	This is equivalent to [`glsp::rfn`](fn.rfn.html), but 
-	[`(fn-name rfn)`](https://gamelisp.rs/std/fn-name) will return the given symbol, 
-	rather than returning `#n`.
+	Performs arithmetic on two values, at least one of which should be a `vec2`, `vec3` or `mat3`.
+
+	This is the vector case of [`(+ a b)`](https://gamelisp.rs/std/add),
+	[`(- a b)`](https://gamelisp.rs/std/sub), [`(* a b)`](https://gamelisp.rs/std/mul) and
+	[`(/ a b)`](https://gamelisp.rs/std/div). Two vectors of the same type are combined
+	component-wise. A number is combined with each component of a vector or matrix. A `mat3`
+	multiplied by a `vec3` or `mat3` produces their matrix product. Any other combination of
+	types is an error.
 	*/
+	pub fn vector_arith(op: VectorOp, a: &Val, b: &Val) -> GResult<Val> {
+		vecmath::val_arith(op, a, b)
+	}
 
-	pub fn named_rfn(name: Sym, wrapped_fn: WrappedFn) -> RFn {
-		let rfn = glsp::rfn(wrapped_fn);
-		rfn.set_name(Some(name));
-		rfn
+	#[doc(hidden)]
+	pub fn class(raw_class: &Tab) -> GResult<Root<Class>> {
+		Ok(glsp::alloc(Class::new(raw_class)?))
 	}
 
+	//---------------------------------------------------------------------------------------------
+	// records
+	//---------------------------------------------------------------------------------------------
+
 	/**
-	Binds a Rust function to a global variable.
+	Registers a record type, which can then be instantiated using [`glsp::rec`](fn.rec.html).
 
-	`glsp::bind_rfn(name, rfn!(f))?` is equivalent to:
+	If a record type with the same name already exists and it has the same field names, this
+	is a no-op. If its fields are different, the old type is replaced. Any records which were
+	constructed from the old type are unaffected, but they won't be considered to have the
+	same type as records constructed from the new type.
 
-		let sym = name.to_sym()?
-		let rfn = glsp::named_rfn(sym, rfn!(f));
-		glsp::bind_global(sym, rfn)?;
-		Ok(rfn)
+	Equivalent to the first step of [`(defrecord name ..fields)`](https://gamelisp.rs/std/defrecord).
 	*/
 
-	pub fn bind_rfn<S: ToSym>(name: S, wrapped_fn: WrappedFn) -> GResult<RFn> {
-		let sym = name.to_sym()?;
+	pub fn define_rec(name: Sym, fields: &[Sym]) -> GResult<()> {
+		let rec_type = RecType::new(name, fields)?;
 
-		let rfn = glsp::named_rfn(sym, wrapped_fn);
+		with_engine(|engine| {
+			let mut rec_types = engine.rec_types.borrow_mut();
+			if let Some(prev) = rec_types.get(&name) {
+				if **prev == rec_type {
+					return Ok(())
+				}
+			}
 
-		glsp::bind_global(sym, rfn)?;
-		Ok(rfn)
+			rec_types.insert(name, Rc::new(rec_type));
+			Ok(())
+		})
 	}
 
 	/**
-	Binds a Rust function to a global macro.
+	Returns the field names of the record type which is registered with the given name,
+	if any.
+	*/
 
-	`glsp::bind_rfn_macro(name, rfn!(f))?` is equivalent to:
+	pub fn rec_fields(name: Sym) -> Option<Vec<Sym>> {
+		with_engine(|engine| {
+			engine.rec_types.borrow().get(&name).map(|rec_type| rec_type.fields.to_vec())
+		})
+	}
 
-		let sym = name.to_sym()?
-		let rfn = glsp::named_rfn(sym, rfn!(f));
-		glsp::bind_macro(sym, Expander::RFn(rfn))?;
-		Ok(rfn)
+	/**
+	Constructs a [record](struct.Rec.html) with the given type name. Field values are listed in
+	the same order that the fields were passed to [`glsp::define_rec`](fn.define_rec.html).
 	*/
 
-	pub fn bind_rfn_macro<S: ToSym>(name: S, wrapped_fn: WrappedFn) -> GResult<RFn> {
-		let sym = name.to_sym()?;
+	pub fn rec<T: IntoIterator<Item = V>, V: ToVal>(name: Sym, vals: T) -> GResult<Root<Rec>> {
+		let rec_type = glsp::rec_type(name)?;
 
-		let rfn = glsp::named_rfn(sym, wrapped_fn);
-		
-		glsp::bind_macro(sym, Expander::RFn(rfn))?;
-		Ok(rfn)
+		let mut fields = Vec::with_capacity(rec_type.fields.len());
+		for val in vals {
+			fields.push(val.to_slot()?);
+		}
+
+		ensure!(fields.len() == rec_type.fields.len(),
+		        "the rec type {} has {} fields, but {} values were provided", 
+		        name, rec_type.fields.len(), fields.len());
+
+		Ok(glsp::alloc(Rec::new(rec_type, fields.into_boxed_slice())))
 	}
 
-	pub(crate) fn call_rfn(rfn: RFn, arg_count: usize) -> GResult<Slot> {
+	//used by the parser and the deserializer, which receive each field's name alongside its value
+	pub(crate) fn rec_from_named_fields<I>(name: Sym, named_fields: I) -> GResult<Root<Rec>> 
+	where
+		I: IntoIterator<Item = (Sym, Val)>
+	{
+		let rec_type = glsp::rec_type(name)?;
+
+		let mut fields = vec![None; rec_type.fields.len()];
+		for (field, val) in named_fields {
+			match rec_type.index_of(field) {
+				Some(i) => {
+					ensure!(fields[i].is_none(), "duplicate field '{}' in a {} rec", field, name);
+					fields[i] = Some(val.to_slot()?);
+				}
+				None => bail!("the rec type {} has no field '{}'", name, field)
+			}
+		}
+
+		let mut slots = Vec::with_capacity(fields.len());
+		for (i, field) in fields.into_iter().enumerate() {
+			match field {
+				Some(slot) => slots.push(slot),
+				None => bail!("missing field '{}' in a {} rec", rec_type.fields[i], name)
+			}
+		}
+
+		Ok(glsp::alloc(Rec::new(rec_type, slots.into_boxed_slice())))
+	}
+
+	pub(crate) fn rec_type(name: Sym) -> GResult<Rc<RecType>> {
 		with_engine(|engine| {
+			match engine.rec_types.borrow().get(&name) {
+				Some(rec_type) => Ok(Rc::clone(rec_type)),
+				None => bail!("{} is not a defined rec type", name)
+			}
+		})
+	}
 
-			/*
-			when invoking a wrapped rfn, we borrow the vm's reg stack, copy the useful parts of
-			it to the Rust callstack (as the Temps type), drop the borrow, and then invoke the 
-			rfn. we only pop the regs after the call returns, so that they remain rooted.
-			*/
+	pub(crate) fn call_class(class: &Root<Class>, arg_count: usize) -> GResult<Root<Obj>> {
+		with_engine(|engine| {
 
+			//the simplest way to implement this (but not the most performant - todo fix) is 
+			//to collect the arguments into a SmallVec. we leave them on the stack until the 
+			//constructor returns, so that they stay rooted.
 			let stacks = engine.vm.stacks.borrow();
 			let base_reg = stacks.regs.len() - arg_count;
 
@@ -2405,923 +7508,1030 @@ pub mod glsp {
 				stacks.regs.truncate(base_reg);
 			});
 
-			let regs = Ref::map(stacks, |stacks| &stacks.regs[base_reg..]);
-
-			let wrapped_fn = engine.rfns.borrow()[rfn.0.get() as usize].wrapped_fn;
-
-			let result = panic::catch_unwind(AssertUnwindSafe(|| {
-				wrapped_fn.call(regs)
-			}));
+			let mut args = SmallVec::<[Slot; 16]>::new();
+			for i in base_reg .. stacks.regs.len() {
+				//this particular incantation is much faster than iter().cloned() for some reason
+				args.push(stacks.regs[i].clone());
+			}
 
-			/*
-			for the time being, we don't go through the rigmarole of trying to set a custom panic
-			hook. it's a global resource, and managing that would be annoying. instead, we allow
-			the normal panic hook to print its usual message, and we convert the caught panic
-			into a generic message without any details.
-			*/
-			
-			match result {
-				Ok(glsp_result) => glsp_result,
-				Err(payload) => {
-					let rfn_description = match rfn.name() {
-						Some(sym) => format!("rfn ({})", sym),
-						None => format!("anonymous rfn")
-					};
+			drop(stacks);
 
-					if let Some(msg) = payload.downcast_ref::<&str>() {
-						bail!("{} panicked, '{}'", rfn_description, msg)
-					} else if let Some(msg) = payload.downcast_ref::<String>() {
-						bail!("{} panicked, '{}'", rfn_description, msg)
-					} else {
-						bail!("{} panicked", rfn_description)
-					}
-				}
-			}
+			Obj::new(class, &args[..])
 		})
 	}
 
 	//---------------------------------------------------------------------------------------------
-	// parsing and printing
+	// iterators
 	//---------------------------------------------------------------------------------------------
 
-	/** Equivalent to [`(parse text filename)`](https://gamelisp.rs/std/parse). */
+	pub(crate) fn giter(state: GIterState) -> Root<GIter> {
+		with_heap(|heap| heap.recycler.giter(state))
+	}
 
-	pub fn parse(text: &mut &str, filename: Option<&str>) -> GResult<Option<Val>> {
-		let file_id = filename.map(|path| glsp::filename(path));
+	/** Equivalent to [`(rn start end step-by)`](https://gamelisp.rs/std/rn). */
 
-		let mut parser = Parser::new(file_id);
+	pub fn rn(start: Num, end: Option<Num>, step_by: Num) -> GResult<Root<GIter>> {
+		ensure!(step_by != Num::Int(0), "a range can't be stepped by 0");
+		match (start, end, step_by) {
+			(Num::Int(start), Some(Num::Int(end)), Num::Int(step_by)) => {
+				Ok(glsp::giter(GIterState::RnExclusive(start, end, step_by)))
+			}
+			(Num::Int(start), None, Num::Int(step_by)) => {
+				Ok(glsp::giter(GIterState::RnOpen(start, step_by)))
+			}
+			(start, Some(end), step_by) => {
+				Ok(glsp::giter(GIterState::FRnExclusive(
+					start.into_flo(),
+					end.into_flo(),
+					step_by.into_flo())))
+			}
+			(start, None, step_by) => {
+				Ok(glsp::giter(GIterState::FRnOpen(start.into_flo(), step_by.into_flo())))
+			}
+		}
+	}
 
-		glsp::push_frame(Frame::GlspApi(GlspApiName::Parse, file_id));
-		let _guard = Guard::new(|| glsp::pop_frame());
+	/** Equivalent to [`(rni start end step-by)`](https://gamelisp.rs/std/rni). */
 
-		while text.len() > 0 {
-			if let Some(form) = parser.parse(text)? {
-				return Ok(Some(form))
+	pub fn rni(start: Num, end: Option<Num>, step_by: Num) -> GResult<Root<GIter>> {
+		ensure!(step_by != Num::Int(0), "a range can't be stepped by 0");
+		match (start, end, step_by) {
+			(Num::Int(start), Some(Num::Int(end)), Num::Int(step_by)) => {
+				Ok(glsp::giter(GIterState::RnInclusive(start, end, step_by)))
+			}
+			(Num::Int(start), None, Num::Int(step_by)) => {
+				Ok(glsp::giter(GIterState::RnOpen(start, step_by)))
+			}
+			(start, Some(end), step_by) => {
+				Ok(glsp::giter(GIterState::FRnInclusive(
+					start.into_flo(),
+					end.into_flo(),
+					step_by.into_flo())))
+			}
+			(start, None, step_by) => {
+				Ok(glsp::giter(GIterState::FRnOpen(start.into_flo(), step_by.into_flo())))
 			}
 		}
+	}
 
-		parser.ensure_finished()?;
-		Ok(None)
+	/** Equivalent to [`(once ..args)`](https://gamelisp.rs/std/once). */
+
+	pub fn once(args: &[Val]) -> Root<GIter> {
+		match args.len() {
+			0 => glsp::giter(GIterState::Empty),
+			1 => glsp::giter(GIterState::Once1(Slot::from_val(&args[0]))),
+			_ => {
+				let arr = glsp::arr_from_iter(args.iter()).unwrap();
+				glsp::giter(GIterState::OnceN(arr.to_gc()))
+			}
+		}
 	}
 
-	/** Equivalent to [`(parse-all text filename)`](https://gamelisp.rs/std/parse-all). */
+	/** Equivalent to [`(once-with f)`](https://gamelisp.rs/std/once-with). */
 
-	pub fn parse_all(mut text: &str, filename: Option<&str>) -> GResult<Vec<Val>> {
-		let file_id = filename.map(|path| glsp::filename(path));
+	pub fn once_with(callable: Callable) -> Root<GIter> {
+		glsp::giter(GIterState::OnceWith(GcCallable::from_callable(&callable)))
+	}
 
-		glsp::push_frame(Frame::GlspApi(GlspApiName::ParseAll, file_id));
-		let _guard = Guard::new(|| glsp::pop_frame());
+	/** Equivalent to [`(repeat ..args)`](https://gamelisp.rs/std/repeat). */
 
-		let mut parser = Parser::new(file_id);
-		let mut results = Vec::new();
-		while text.len() > 0 {
-			if let Some(form) = parser.parse(&mut text)? {
-				results.push(form);
+	pub fn repeat(args: &[Val]) -> GResult<Root<GIter>> {
+		match args.len() {
+			0 => bail!("the repeat iterator requires at least one argument"),
+			1 => Ok(glsp::giter(GIterState::Repeat1(Slot::from_val(&args[0])))),
+			_ => {
+				let arr = glsp::arr_from_iter(args.iter()).unwrap();
+				Ok(glsp::giter(GIterState::RepeatN(arr.to_gc(), 0, (arr.len() - 1) as u32)))
 			}
 		}
+	}
 
-		parser.ensure_finished()?;
-		Ok(results)
+	/** Equivalent to [`(repeat-with f)`](https://gamelisp.rs/std/repeat-with). */
+
+	pub fn repeat_with(callable: Callable) -> Root<GIter> {
+		glsp::giter(GIterState::RepeatWith(GcCallable::from_callable(&callable)))
 	}
 
-	/** Equivalent to [`(parse-1 text filename)`](https://gamelisp.rs/std/parse-1). */
+	/** Equivalent to [`(chunks len src-arr)`](https://gamelisp.rs/std/chunks). */
 
-	pub fn parse_1(mut text: &str, filename: Option<&str>) -> GResult<Val> {
-		let file_id = filename.map(|path| glsp::filename(path));
+	pub fn chunks(chunk_len: usize, src_arr: &Root<Arr>) -> GResult<Root<GIter>> {
+		ensure!(chunk_len > 0, "cannot split an array into chunks of length 0");
+		Ok(glsp::giter(GIterState::Chunks(chunk_len as u32, src_arr.shallow_clone().to_gc())))
+	}
 
-		glsp::push_frame(Frame::GlspApi(GlspApiName::Parse1, file_id));
-		let _guard = Guard::new(|| glsp::pop_frame());
+	/** Equivalent to [`(chunks-exact len src-arr)`](https://gamelisp.rs/std/chunks-exact). */
 
-		let mut parser = Parser::new(file_id);
-		while text.len() > 0 {
-			if let Some(form) = parser.parse(&mut text)? {
-				while text.len() > 0 {
-					if let Some(_) = parser.parse(&mut text)? {
-						bail!("parse-1 produced multiple forms")
-					}
-				}
+	pub fn chunks_exact(chunk_len: usize, src_arr: &Root<Arr>) -> GResult<Root<GIter>> {
+		ensure!(chunk_len > 0, "cannot split an array into chunks of length 0");
+		
+		let len = src_arr.len() - (src_arr.len() % chunk_len);
+		let arr = glsp::arr_from_iter(src_arr.iter().take(len)).unwrap();
 
-				return Ok(form)
-			}
-		}
+		Ok(glsp::giter(GIterState::Chunks(chunk_len as u32, arr.to_gc())))
+	}
 
-		bail!("parse-1 did not produce a form")
+	/** Equivalent to [`(rchunks len src-arr)`](https://gamelisp.rs/std/rchunks). */
+
+	pub fn rchunks(chunk_len: usize, src_arr: &Root<Arr>) -> GResult<Root<GIter>> {
+		ensure!(chunk_len > 0, "cannot split an array into chunks of length 0");
+		Ok(glsp::giter(GIterState::RChunks(chunk_len as u32, src_arr.shallow_clone().to_gc())))
 	}
 
-	/**
-	Changes the output writer used by [`pr`](https://gamelisp.rs/std/pr),
-	[`prn`](https://gamelisp.rs/std/prn), [`pr!`](macro.pr.html) and 
-	[`prn!`](macro.prn.html).
+	/** Equivalent to [`(rchunks-exact len src-arr)`](https://gamelisp.rs/std/rchunks-exact). */
 
-	The default writer is [`Stdout`](https://doc.rust-lang.org/std/io/struct.Stdout.html). Note
-	that calling `glsp::set_pr_writer` will not redirect the output of Rust macros like
-	[`println!`](https://doc.rust-lang.org/std/macro.println.html): for that, you would need
-	to use the undocumented feature [`set_print`](https://github.com/rust-lang/rust/issues/31343).
+	pub fn rchunks_exact(chunk_len: usize, src_arr: &Root<Arr>) -> GResult<Root<GIter>> {
+		ensure!(chunk_len > 0, "cannot split an array into chunks of length 0");
+		
+		let to_skip = src_arr.len() % chunk_len;
+		let arr = glsp::arr_from_iter(src_arr.iter().skip(to_skip)).unwrap();
 
-		//silences pr!(), prn!(), pr and prn
-		glsp::set_pr_writer(Box::new(std::io::sink()));
-	*/
+		Ok(glsp::giter(GIterState::RChunks(chunk_len as u32, arr.to_gc())))
+	}
 
-	pub fn set_pr_writer(pr_writer: Box<dyn Write>) {
-		with_engine(|engine| {
-			*engine.pr_writer.borrow_mut() = pr_writer;
-		})
+	/** Equivalent to [`(windows len src-arr)`](https://gamelisp.rs/std/windows). */
+
+	pub fn windows(window_len: usize, src_arr: &Root<Arr>) -> GResult<Root<GIter>> {
+		ensure!(window_len > 0, "cannot split an array into windows of length 0");
+		Ok(glsp::giter(GIterState::Windows(window_len as u32, src_arr.shallow_clone().to_gc())))
+	}
+
+	/** Equivalent to [`(lines st)`](https://gamelisp.rs/std/lines). */
+
+	pub fn lines(st: &Root<Str>) -> Root<GIter> {
+		glsp::giter(GIterState::Lines(st.to_gc(), 0, 0))
 	}
 
 	/**
-	Changes the output writer used by [`epr`](https://gamelisp.rs/std/epr),
-	[`eprn`](https://gamelisp.rs/std/eprn), [`epr!`](macro.epr.html) and 
-	[`eprn!`](macro.eprn.html).
+	Creates an iterator which produces the items of a Rust iterator.
 
-	That same writer is also used by GameLisp to print a stack trace when an error is 
-	not caught.
+	Each item is converted using [`ToVal`](trait.ToVal.html). When an item is an `Err`, the
+	error is returned by the iterator's caller, and iteration may then continue. Because the
+	Rust iterator is stored on the garbage-collected heap, it must implement
+	[`GStore`](trait.GStore.html).
+
+	The Rust iterator is dropped when the `GIter` finishes, when it's 
+	[closed](struct.GIter.html#method.close), or when it's garbage-collected. A 
+	[shallow clone](struct.GIter.html#method.shallow_clone) of the `GIter` shares the same
+	Rust iterator.
+
+		let iter = glsp::rust_iter(records.into_iter().map(|record| Ok(record.name)));
+	*/
+
+	pub fn rust_iter<I, T>(iter: I) -> Root<GIter>
+	where
+		I: Iterator<Item = GResult<T>> + GStore + 'static,
+		T: ToVal
+	{
+		let iter = iter.map(|result| result.and_then(|t| t.to_val()));
+		glsp::giter(GIterState::RustIter(Rc::new(RefCell::new(iter))))
+	}
+
+	/** Equivalent to [`(split st split-at)`](https://gamelisp.rs/std/split). */
 
-	The default writer is [`Stderr`](https://doc.rust-lang.org/std/io/struct.Stderr.html). Note
-	that calling `glsp::set_epr_writer` will not redirect the output of Rust macros like
-	[`panic!`](https://doc.rust-lang.org/std/macro.panic.html) or
-	[`eprintln!`](https://doc.rust-lang.org/std/macro.eprintln.html): for that, you would need
-	to use the undocumented feature [`set_panic`](https://github.com/rust-lang/rust/issues/31343).
+	pub fn split(src: &Root<Str>, split_at: &Root<Str>) -> Root<GIter> {
+		glsp::giter(GIterState::Split(src.shallow_clone().to_gc(),
+		                              split_at.shallow_clone().to_gc()))
+	}
 
-		//silences error-reporting, epr!(), eprn!(), epr and eprn
-		glsp::set_pr_writer(Box::new(std::io::sink()));
-	*/
+	/** Equivalent to [`(rev base)`](https://gamelisp.rs/std/rev). */
 
-	pub fn set_epr_writer(epr_writer: Box<dyn Write>) {
-		with_engine(|engine| {
-			*engine.epr_writer.borrow_mut() = epr_writer;
-		})
+	pub fn rev(base: &Root<GIter>) -> GResult<Root<GIter>> {
+		ensure!(base.is_double_ended(), "{} iterators are not double-ended", base.state_name());
+		Ok(glsp::giter(GIterState::Rev(base.to_gc())))
 	}
-	
-	//---------------------------------------------------------------------------------------------
-	// spans and stack-tracing
-	//---------------------------------------------------------------------------------------------
 
-	pub(crate) fn push_frame(frame: Frame) {
-		with_engine(|engine| {
-			engine.vm.push_frame(frame);
-		})
+	/** Equivalent to [`(enumerate base)`](https://gamelisp.rs/std/enumerate). */
+
+	pub fn enumerate(base: &Root<GIter>) -> Root<GIter> {
+		glsp::giter(GIterState::Enumerate(base.to_gc(), 0))
 	}
 
-	pub(crate) fn pop_frame() {
-		with_engine(|engine| {
-			engine.vm.pop_frame();
-		})
+	/** Equivalent to [`(cloned base)`](https://gamelisp.rs/std/cloned). */
+
+	pub fn cloned(base: &Root<GIter>) -> Root<GIter> {
+		glsp::giter(GIterState::Cloned(base.to_gc()))
 	}
 
-	pub(crate) fn enter_expander(arr: &Arr, env: Rc<Env>) -> Option<(Option<Sym>, Span, Rc<Env>)> {
-		with_engine(|engine| {
-			let name = if arr.len() >= 1 && arr.get::<Val>(0).unwrap().is_sym() {
-				Some(arr.get::<Sym>(0).unwrap())
-			} else {
-				None
-			};
-			let callsite = arr.span();
+	/** Equivalent to [`(deep-cloned base)`](https://gamelisp.rs/std/deep-cloned). */
 
-			let prev_in_expander = engine.in_expander.borrow().clone();
-			*engine.in_expander.borrow_mut() = Some((name, callsite, env));
+	pub fn deep_cloned(base: &Root<GIter>) -> Root<GIter> {
+		glsp::giter(GIterState::DeepCloned(base.to_gc()))
+	}
 
-			prev_in_expander
-		})
+	/** Equivalent to [`(step-by n base)`](https://gamelisp.rs/std/step-by). */
+
+	pub fn step_by(step_by: usize, base: &Root<GIter>) -> GResult<Root<GIter>> {
+		ensure!(step_by > 0, "cannot step an iterator by 0");
+		Ok(glsp::giter(GIterState::StepBy(step_by as u32, base.to_gc())))
 	}
 
-	pub(crate) fn env() -> Option<Rc<Env>> {
-		with_engine(|engine| {
-			match *engine.in_expander.borrow() {
-				Some((_, _, ref env)) => Some(Rc::clone(env)),
-				None => None
-			}
-		})
+	/** Equivalent to [`(map f base)`](https://gamelisp.rs/std/map). */
+
+	pub fn map(callable: &Callable, base: &Root<GIter>) -> Root<GIter> {
+		glsp::giter(GIterState::Map(GcCallable::from_callable(callable), base.to_gc()))
 	}
 
-	pub(crate) fn leave_expander(prev: Option<(Option<Sym>, Span, Rc<Env>)>) {
-		with_engine(|engine| {
-			*engine.in_expander.borrow_mut() = prev;
-		})
+	/** Equivalent to [`(filter f base)`](https://gamelisp.rs/std/filter). */
+
+	pub fn filter(callable: &Callable, base: &Root<GIter>) -> Root<GIter> {
+		glsp::giter(GIterState::Filter(GcCallable::from_callable(callable), base.to_gc()))
 	}
 
-	pub(crate) fn span(storage: SpanStorage) -> Span {
-		with_engine(|engine| {
-			if storage == SpanStorage::Generated {
-				Span(0)
-			} else {
-				let mut spans_map = engine.spans_map.borrow_mut();
-				match spans_map.entry(storage) {
-					Occupied(entry) => *entry.get(),
-					Vacant(entry) => {
-						let mut spans = engine.spans.borrow_mut();
-						spans.push(storage);
-						
-						let span = Span((spans.len() - 1) as u32);
-						entry.insert(span);
-						span
-					}
-				}
-			}
-		})
+	/** Equivalent to [`(zip ..args)`](https://gamelisp.rs/std/zip). */
+
+	pub fn zip(iterables: &[Iterable]) -> Root<GIter> {
+		let arr = glsp::arr_from_iter(iterables.iter().map(|iterable| iterable.giter())).unwrap();
+		glsp::giter(GIterState::Zip(arr.to_gc()))
 	}
 
-	pub(crate) fn generated_span() -> Span {
-		Span(0)
-	}	
+	/** Equivalent to [`(chain ..args)`](https://gamelisp.rs/std/chain). */
 
-	pub(crate) fn span_storage(span: Span) -> SpanStorage {
-		with_engine(|engine| {
-			engine.spans.borrow()[span.0 as usize]
-		})
+	pub fn chain(iterables: &[Iterable]) -> Root<GIter> {
+		let arr = glsp::arr_from_iter(iterables.iter().map(|iterable| iterable.giter())).unwrap();
+		glsp::giter(GIterState::Chain(arr.to_gc()))
 	}
 
-	//simplifies a Span into a brief file location, e.g. "scripts/main.glsp:10". only used by (try)
-	//and (file-location). if this span was Loaded from a file, or transitively expanded from a 
-	//macro callsite which was itself Loaded from a file, writes the file location to `f` and
-	//returns Ok(true). otherwise, returns Ok(false).
-	pub(crate) fn span_file_location<F>(f: &mut F, mut span: Span) -> Result<bool, fmt::Error>
-	where
-		F: fmt::Write
-	{
-		loop {
-			match glsp::span_storage(span) {
-				SpanStorage::Expanded(_, macro_invocation_span, _) => {
-					span = macro_invocation_span;
-				}
-				SpanStorage::Loaded(file_id, line) => {
-					write!(f, "{}:{}", &glsp::filename_str(file_id), line)?;
-					return Ok(true)
-				}
-				SpanStorage::Generated => {
-					return Ok(false)
-				}
-			}
-		}
+	/** Equivalent to [`(flatten base)`](https://gamelisp.rs/std/flatten). */
+
+	pub fn flatten(base: &Root<GIter>) -> Root<GIter> {
+		glsp::giter(GIterState::Flatten(base.to_gc(), None))
 	}
 
-	//span_context outputs a description of a single Span to surround a gfn call, rfn call, or
-	//bail_at!() in a stack trace. e.g.:
-	//
-	//	(some-macro) at scripts/main.glsp:10
-	//		expanded to (other-macro) at scripts/main.glsp:20
-	//		expanded to (some-call) at scripts/main.glsp:30
-	//
-	pub(crate) fn span_context<F, C>(
-		f: &mut F,
-		span: Span,
-		callback: &C
-	) -> fmt::Result
-	where
-		F: fmt::Write,
-		C: ?Sized + Fn(&mut F) -> fmt::Result
-	{
-		match glsp::span_storage(span) {
-			SpanStorage::Generated => {
-				callback(f)?;
-			}
-			SpanStorage::Loaded(file_id, line_number) => {
-				callback(f)?;
-				write!(f, " at {}:{}", &glsp::filename_str(file_id), line_number)?;
-			}
-			SpanStorage::Expanded(macro_name, expander_callsite, mut constructor_callsite) => {
-				let print_macro_name: &dyn Fn(&mut F) -> fmt::Result = &|f| {
-					match macro_name {
-						Some(macro_name) => write!(f, "({})", macro_name),
-						None => write!(f, "an anonymous macro")
-					}
-				};
+	/** Equivalent to [`(cycle base)`](https://gamelisp.rs/std/cycle). */
 
-				span_context(f, expander_callsite, print_macro_name)?;
+	pub fn cycle(base: &Root<GIter>) -> Root<GIter> {
+		glsp::giter(GIterState::Cycle(Some(base.to_gc()), glsp::arr().to_gc(), 0))
+	}
 
-				write!(f, "\n    expanded to ")?;
-				callback(f)?;
+	/** Equivalent to [`(take n base)`](https://gamelisp.rs/std/take). */
 
-				//within a macro, it's possible that arr constructors may be themselves expanded
-				//from a macro. we could potentially recurse in that case, but for now we just
-				//report the macro invocation's own constructor site as the construction site.
-				while let SpanStorage::Expanded(_, _, meta) = 
-				          glsp::span_storage(constructor_callsite) {
-					constructor_callsite = meta;
-				}
+	pub fn take(n: usize, base: &Root<GIter>) -> Root<GIter> {
+		glsp::giter(GIterState::Take(n as u32, base.to_gc()))
+	}
 
-				match glsp::span_storage(constructor_callsite) {
-					SpanStorage::Generated => (),
-					SpanStorage::Loaded(file_id, line_number) => {
-						write!(f, " at {}:{}", &glsp::filename_str(file_id), line_number)?;
-					}
-					SpanStorage::Expanded(..) => unreachable!()
-				}
-			}
-		}
+	/** Equivalent to [`(take-while f base)`](https://gamelisp.rs/std/take-while). */
 
-		Ok(())
+	pub fn take_while(callable: &Callable, base: &Root<GIter>) -> Root<GIter> {
+		glsp::giter(GIterState::TakeWhile(GcCallable::from_callable(callable), base.to_gc()))
 	}
 
-	/** Equivalent to [`(file-location)`](https://gamelisp.rs/std/file-location). */
+	/** Equivalent to [`(skip n base)`](https://gamelisp.rs/std/skip). */
 
-	//error messages can be stringified in either a short form, or a long form (a stack trace).
-	//the short form is a brief file location, followed by the stringification of the arguments
-	//passed to bail!(), ensure!() or (err). this method generates that brief file location.
-	//the user can also generate this directly using the (file-location) builtin fn.
-	pub fn file_location() -> Option<String> {
-		with_engine(|engine| {
-			let mut builder = String::new();
-			if engine.vm.file_location(&mut builder).unwrap() {
-				Some(builder)
-			} else {
-				None
-			}
-		})
+	pub fn skip(n: usize, base: &Root<GIter>) -> Root<GIter> {
+		glsp::giter(GIterState::Skip(n as u32, base.to_gc()))
 	}
 
-	/** Equivalent to [`(stack-trace)`](https://gamelisp.rs/std/stack-trace). */
+	/** Equivalent to [`(skip-while f base)`](https://gamelisp.rs/std/skip-while). */
 
-	//this method generates the long form: a full stack trace. we don't emit any leading or
-	//trailing linebreaks.
-	pub fn stack_trace() -> String {
-		with_engine(|engine| {
-			let mut builder = String::new();
-			engine.vm.stack_trace(&mut builder).unwrap();
-			builder
-		})
+	pub fn skip_while(callable: &Callable, base: &Root<GIter>) -> Root<GIter> {
+		let gc_callable = GcCallable::from_callable(callable);
+		glsp::giter(GIterState::SkipWhile(Some(gc_callable), base.to_gc()))
 	}
 
 	//---------------------------------------------------------------------------------------------
-	// try
+	// garbage collection
 	//---------------------------------------------------------------------------------------------
 
-	/**
-	Calls a function with either verbose or brief error-reporting.
+	/** Equivalent to [`(gc)`](https://gamelisp.rs/std/gc). */
+
+	pub fn gc() {
+		let doomed_coros = with_engine(|engine| {
+			engine.vm.traverse_stacks();
+			engine.heap.step();
+			engine.heap.take_doomed_coros()
+		});
+
+		//when a paused coroutine is unreachable, but it's within the scope of a (defer), the
+		//collector keeps it alive so that its defers can be run here. errors can't be returned
+		//to the caller, so they're reported to the print sink instead. a doomed coroutine may
+		//already have been cancelled by another's defers, e.g. by a (with-children) scope.
+		for coro in doomed_coros {
+			if coro.state() != CoroState::Paused {
+				continue
+			}
+
+			if let Err(error) = glsp::coro_finish(&coro) {
+				glsp::log(PrintLevel::Error, &format!(
+					"error in a (defer) of a collected coroutine: {}", error
+				));
+			}
+		}
+
+		if glsp::log_level() <= PrintLevel::Trace {
+			glsp::log(PrintLevel::Trace, &format!(
+				"gc: {} young bytes, {} old bytes, {} ghost bytes",
+				glsp::gc_young_bytes(),
+				glsp::gc_old_bytes(),
+				glsp::gc_ghost_bytes()
+			));
+		}
+	}
+
+	/** Equivalent to [`(gc-value 'ratio)`](https://gamelisp.rs/std/gc-value). */
+
+	pub fn gc_ratio() -> f32 {
+		with_engine(|engine| {
+			engine.heap.ratio()
+		})
+	}
 
-	[`try`](https://gamelisp.rs/std/try) and [`try-verbose`](https://gamelisp.rs/std/try-verbose)
-	work by invoking `glsp::try_call`.
+	/** 
+	Equivalent to [`(= (gc-value 'ratio) ratio)`](https://gamelisp.rs/std/set-gc-value). 
 
-	The default error-reporting style is verbose.
+	The ratio can be changed at any time. Its initial value can be configured using
+	[`RuntimeBuilder::gc_ratio`](struct.RuntimeBuilder.html#method.gc_ratio).
 	*/
-	pub fn try_call<R, A>(is_verbose: bool, receiver: &R, args: &A) -> GResult<Val>
-	where
-		R: CallableOps,
-		A: ToCallArgs + ?Sized
-	{
-		with_engine(|engine| {
-			let prev = engine.errors_verbose.get();
-			engine.errors_verbose.set(is_verbose);
-			let _guard = Guard::new(|| engine.errors_verbose.set(prev));
 
-			glsp::call(receiver, args)
+	pub fn gc_set_ratio(ratio: f32) {
+		with_engine(|engine| {
+			engine.heap.set_ratio(ratio)
 		})
 	}
 
-	pub(crate) fn errors_verbose() -> bool {
+	/** Equivalent to [`(gc-value 'young-bytes)`](https://gamelisp.rs/std/gc-value). */
+
+	pub fn gc_young_bytes() -> usize {
 		with_engine(|engine| {
-			engine.errors_verbose.get()
+			engine.heap.young_memory_usage()
 		})
 	}
 
-	//---------------------------------------------------------------------------------------------
-	// allocation
-	//---------------------------------------------------------------------------------------------
+	/** Equivalent to [`(gc-value 'old-bytes)`](https://gamelisp.rs/std/gc-value). */
 
-	pub(crate) fn alloc<T: Allocate>(t: T) -> Root<T> {
+	pub fn gc_old_bytes() -> usize {
 		with_engine(|engine| {
-			engine.heap.alloc(t)
+			engine.heap.old_memory_usage()
 		})
 	}
 
-	#[doc(hidden)]
-	pub fn alloc_gc<T: Allocate>(t: T) -> Gc<T> {
+	/** Equivalent to [`(gc-value 'ghost-bytes)`](https://gamelisp.rs/std/gc-value). */
+
+	pub fn gc_ghost_bytes() -> usize {
 		with_engine(|engine| {
-			engine.heap.alloc_gc(t)
+			engine.heap.ghost_memory_usage()
 		})
 	}
 
-	//returns the Span which should be assigned to a newly-allocated arr, allocated at `callsite`
-	pub(crate) fn new_arr_span(callsite: Option<Span>) -> Span {
+	/** Equivalent to [`(gc-value 'steps)`](https://gamelisp.rs/std/gc-value). */
+
+	pub fn gc_steps() -> usize {
 		with_engine(|engine| {
-			if let Some(callsite) = callsite {
-				if let Some((expander_name, expander_callsite, _)) = *engine.in_expander.borrow() {
-					glsp::span(SpanStorage::Expanded(expander_name, expander_callsite, callsite))
-				} else {
-					Span::default()
-				}
-			} else {
-				if engine.vm.in_expander() {
-					//when `callsite` is None, that means the arr is being allocated by a call
-					//to glsp::arr(), glsp::call(), etc., rather than an OpArr instr or something 
-					//else which can pass in an explicit "current span". under those circumstances, 
-					//the innermost available Span will be the callsite of the innermost active 
-					//gfn/rfn call, if any, within the innermost expander - or the callsite of
-					//the expander itself, otherwise. 
+			engine.heap.steps()
+		})
+	}
 
-					//possible future extension (todo) would be to add a type of Span which refers
-					//to a line of rust code, rather than a line of glsp code. couldn't use this
-					//for glsp::call, but we could use it for macros like arr![] and backquote!().
+	/** Equivalent to [`(gc-value 'pool-hits)`](https://gamelisp.rs/std/gc-value). */
 
-					engine.vm.expander_cur_span()
-				} else {
-					Span::default()
-				}
-			}
+	pub fn gc_pool_hits() -> usize {
+		with_engine(|engine| {
+			engine.heap.recycler.hits()
 		})
 	}
 
-	///Constructs an empty [array](struct.Arr.html).
-	pub fn arr() -> Root<Arr> {
-		let arr = with_heap(|heap| heap.recycler.arr());
-		arr.set_span(glsp::new_arr_span(None));
-		arr
-	}
+	/** Equivalent to [`(gc-value 'pool-misses)`](https://gamelisp.rs/std/gc-value). */
 
-	///Constructs an empty [array](struct.Arr.html) with space for at least `capacity` elements.
-	pub fn arr_with_capacity(capacity: usize) -> Root<Arr> {
-		let arr = with_heap(|heap| heap.recycler.arr_with_capacity(capacity));
-		arr.set_span(glsp::new_arr_span(None));
-		arr
+	pub fn gc_pool_misses() -> usize {
+		with_engine(|engine| {
+			engine.heap.recycler.misses()
+		})
 	}
 
-	/**
-	Constructs an [array](struct.Arr.html) which contains `reps` repetitions of `elem`.
+	/** Equivalent to [`(gc-value 'interned-strs)`](https://gamelisp.rs/std/gc-value). */
 
-	Returns an `Err` if [type conversion](trait.ToVal.html) fails.
-	*/
-	pub fn arr_from_elem<T: ToVal>(elem: T, reps: usize) -> GResult<Root<Arr>> {
-		let arr = with_heap(|heap| heap.recycler.arr_from_elem(elem, reps))?;
-		arr.set_span(glsp::new_arr_span(None));
-		Ok(arr)
+	pub fn gc_interned_strs() -> usize {
+		with_engine(|engine| {
+			engine.interned.borrow().len()
+		})
 	}
 
 	/**
-	Constructs an [array](struct.Arr.html) from the contents of a Rust iterator.
-
-	Returns an `Err` if [type conversion](trait.ToVal.html) fails for any element.
-	*/
-	pub fn arr_from_iter<T>(iter: T) -> GResult<Root<Arr>> 
-	where
-		T: IntoIterator,
-		T::Item: ToVal
-	{
-		let arr = with_heap(|heap| heap.recycler.arr_from_iter(iter))?;
-		arr.set_span(glsp::new_arr_span(None));
-		Ok(arr)
-	}
+	Calls `f`, and then immediately frees any objects which it allocated but didn't keep.
 
-	///Constructs an empty [string](struct.Str.html).
-	pub fn str() -> Root<Str> {
-		glsp::alloc(Str::new())
-	}
+	Equivalent to [`(with-scratch ..body)`](https://gamelisp.rs/std/with-scratch).
 
-	///Constructs an empty [string](struct.Str.html) with the same contents as a Rust string slice.
-	pub fn str_from_rust_str(src: &str) -> Root<Str> {
-		glsp::alloc(Str::from_rust_str(src))
-	}
+	Normally, a short-lived object isn't freed until the next call to [`glsp::gc`](fn.gc.html).
+	Within a scratch scope, any object which can't be reached from outside the scope when `f`
+	returns is freed immediately, and its memory is recycled. An object escapes the scope if it's
+	returned (including as part of an error), stored in a `Root`, stored in a global, or stored in 
+	any object which was allocated before the scope began. Escaped objects are left alone, so
+	that the garbage collector can deal with them as usual.
 
-	/**
-	Constructs a [string](struct.Str.html) from the characters in a Rust iterator.
+	The cost of leaving a scratch scope is proportional to the number of objects allocated since 
+	the last call to `glsp::gc`, plus the number of live `Roots`. Scratch scopes are most useful
+	around code which allocates many temporary arrays or tables, and which keeps very few of them.
 
-	Returns an `Err` if [type conversion](trait.IntoElement.html) fails for any element.
+		for entity in &entities {
+			glsp::scratch_scope(|| {
+				glsp::call::<_, _, Val>(&entity.on_step, &())
+			})?;
+		}
 	*/
-	pub fn str_from_iter<T>(iter: T) -> GResult<Root<Str>> 
+
+	pub fn scratch_scope<R, F>(f: F) -> GResult<R>
 	where
-		T: IntoIterator,
-		T::Item: IntoElement<char>
+		F: FnOnce() -> GResult<R>
 	{
-		Ok(glsp::alloc(Str::from_iter(iter)?))
-	}
-
-	///Constructs an empty [string](struct.Str.html) with space for at least `capacity` characters.
-	pub fn str_with_capacity(capacity: usize) -> Root<Str> {
-		glsp::alloc(Str::with_capacity(capacity))
-	}
-
-	///Constructs an empty [table](struct.Tab.html).
-	pub fn tab() -> Root<Tab> {
-		glsp::alloc(Tab::new())
+		scratch_scope_impl(f, false)
 	}
 
 	/**
-	Constructs a [table](struct.Tab.html) from the key/value pairs in a Rust iterator.
+	Equivalent to [`glsp::scratch_scope`](fn.scratch_scope.html), but it's an error for an object
+	to escape.
 
-	Duplicate keys are permitted.
+	Equivalent to [`(with-strict-scratch ..body)`](https://gamelisp.rs/std/with-strict-scratch).
 
-	Returns an `Err` if [type conversion](trait.ToVal.html) fails for any key or value.
+	If `f` succeeds, but at least one of the objects which it allocated is still reachable when it
+	returns, this function returns an error. This includes the return value of `f`. The escaped
+	objects are not freed.
 	*/
-	pub fn tab_from_iter<T, K, V>(iter: T) -> GResult<Root<Tab>> 
+
+	pub fn strict_scratch_scope<R, F>(f: F) -> GResult<R>
 	where
-		T: IntoIterator<Item = (K, V)>,
-		K: ToVal,
-		V: ToVal
+		F: FnOnce() -> GResult<R>
 	{
-		Ok(glsp::alloc(Tab::from_iter(iter)?))
-	}
-
-	///Constructs an empty [table](struct.Tab.html) with space for at least `capacity` elements.
-	pub fn tab_with_capacity(capacity: usize) -> Root<Tab> {
-		glsp::alloc(Tab::with_capacity(capacity))
+		scratch_scope_impl(f, true)
 	}
 
-	#[doc(hidden)]
-	pub fn class(raw_class: &Tab) -> GResult<Root<Class>> {
-		Ok(glsp::alloc(Class::new(raw_class)?))
-	}
+	fn scratch_scope_impl<R, F>(f: F, strict: bool) -> GResult<R>
+	where
+		F: FnOnce() -> GResult<R>
+	{
+		let mark = with_heap(|heap| heap.scratch_mark());
+		let result = f();
 
-	pub(crate) fn call_class(class: &Root<Class>, arg_count: usize) -> GResult<Root<Obj>> {
-		with_engine(|engine| {
+		let (freed_bytes, escaped) = with_engine(|engine| {
+			engine.heap.collect_scratch(mark, |visitor| engine.vm.visit_stacks(visitor))
+		});
 
-			//the simplest way to implement this (but not the most performant - todo fix) is 
-			//to collect the arguments into a SmallVec. we leave them on the stack until the 
-			//constructor returns, so that they stay rooted.
-			let stacks = engine.vm.stacks.borrow();
-			let base_reg = stacks.regs.len() - arg_count;
+		if glsp::log_level() <= PrintLevel::Trace {
+			glsp::log(PrintLevel::Trace, &format!(
+				"scratch: {} bytes freed, {} objects escaped", freed_bytes, escaped
+			));
+		}
 
-			let _guard = Guard::new(|| {
-				let mut stacks = engine.vm.stacks.borrow_mut();
-				stacks.regs.truncate(base_reg);
-			});
+		if strict && escaped > 0 && result.is_ok() {
+			bail!("{} object{} escaped from a strict scratch scope", escaped,
+			      if escaped == 1 { "" } else { "s" })
+		}
 
-			let mut args = SmallVec::<[Slot; 16]>::new();
-			for i in base_reg .. stacks.regs.len() {
-				//this particular incantation is much faster than iter().cloned() for some reason
-				args.push(stacks.regs[i].clone());
-			}
+		result
+	}
 
-			drop(stacks);
+	/** Equivalent to [`(gc-value 'interned-bytes)`](https://gamelisp.rs/std/gc-value). */
 
-			Obj::new(class, &args[..])
+	pub fn gc_interned_bytes() -> usize {
+		with_engine(|engine| {
+			engine.interned.borrow().values().map(|st| {
+				size_of::<Str>() + st.owned_memory_usage()
+			}).sum()
 		})
 	}
 
 	//---------------------------------------------------------------------------------------------
-	// iterators
+	// evaluation and expansion
 	//---------------------------------------------------------------------------------------------
 
-	pub(crate) fn giter(state: GIterState) -> Root<GIter> {
-		with_heap(|heap| heap.recycler.giter(state))
-	}
+	/** Equivalent to [`(eval val env-mode)`](https://gamelisp.rs/std/eval). */
 
-	/** Equivalent to [`(rn start end step-by)`](https://gamelisp.rs/std/rn). */
+	pub fn eval(val: &Val, env_mode: Option<EnvMode>) -> GResult<Val> {
+		glsp::push_frame(Frame::GlspApi(GlspApiName::Eval, None));
+		let _guard = Guard::new(|| glsp::pop_frame());
 
-	pub fn rn(start: Num, end: Option<Num>, step_by: Num) -> GResult<Root<GIter>> {
-		ensure!(step_by != Num::Int(0), "a range can't be stepped by 0");
-		match (start, end, step_by) {
-			(Num::Int(start), Some(Num::Int(end)), Num::Int(step_by)) => {
-				Ok(glsp::giter(GIterState::RnExclusive(start, end, step_by)))
-			}
-			(Num::Int(start), None, Num::Int(step_by)) => {
-				Ok(glsp::giter(GIterState::RnOpen(start, step_by)))
-			}
-			(start, Some(end), step_by) => {
-				Ok(glsp::giter(GIterState::FRnExclusive(
-					start.into_f32(),
-					end.into_f32(),
-					step_by.into_f32())))
-			}
-			(start, None, step_by) => {
-				Ok(glsp::giter(GIterState::FRnOpen(start.into_f32(), step_by.into_f32())))
-			}
-		}
+		eval::eval(&[val.clone()], env_mode, None)
 	}
 
-	/** Equivalent to [`(rni start end step-by)`](https://gamelisp.rs/std/rni). */
+	/** Equivalent to [`(eval-multi vals env-mode)`](https://gamelisp.rs/std/eval-multi). */
 
-	pub fn rni(start: Num, end: Option<Num>, step_by: Num) -> GResult<Root<GIter>> {
-		ensure!(step_by != Num::Int(0), "a range can't be stepped by 0");
-		match (start, end, step_by) {
-			(Num::Int(start), Some(Num::Int(end)), Num::Int(step_by)) => {
-				Ok(glsp::giter(GIterState::RnInclusive(start, end, step_by)))
-			}
-			(Num::Int(start), None, Num::Int(step_by)) => {
-				Ok(glsp::giter(GIterState::RnOpen(start, step_by)))
-			}
-			(start, Some(end), step_by) => {
-				Ok(glsp::giter(GIterState::FRnInclusive(
-					start.into_f32(),
-					end.into_f32(),
-					step_by.into_f32())))
-			}
-			(start, None, step_by) => {
-				Ok(glsp::giter(GIterState::FRnOpen(start.into_f32(), step_by.into_f32())))
-			}
-		}
+	pub fn eval_multi(vals: &[Val], env_mode: Option<EnvMode>) -> GResult<Val> {
+		glsp::push_frame(Frame::GlspApi(GlspApiName::EvalMulti, None));
+		let _guard = Guard::new(|| glsp::pop_frame());
+
+		eval::eval(vals, env_mode, None)
 	}
 
-	/** Equivalent to [`(once ..args)`](https://gamelisp.rs/std/once). */
+	/**
+	Compiles GameLisp source text into a function, without evaluating it.
 
-	pub fn once(args: &[Val]) -> Root<GIter> {
-		match args.len() {
-			0 => glsp::giter(GIterState::Empty),
-			1 => glsp::giter(GIterState::Once1(Slot::from_val(&args[0]))),
-			_ => {
-				let arr = glsp::arr_from_iter(args.iter()).unwrap();
-				glsp::giter(GIterState::OnceN(arr.to_gc()))
-			}
+	Equivalent to [`(compile-fn text filename)`](https://gamelisp.rs/std/compile-fn).
+
+	The text is parsed, macro-expanded and compiled immediately, as the body of a function
+	which accepts no arguments. Calling the function evaluates each form in turn and returns
+	the result of the last form, or `#n` if the text was empty. The function can be called any
+	number of times; its body isn't expanded again.
+
+	Because expansion happens up front, macros which the text itself defines can't be used
+	by the same text. Global variables are looked up when the function is called, as usual.
+
+	Any error encountered while parsing, expanding or compiling the text is returned
+	immediately. Its spans refer to `filename`, which is used as-is, like the filename passed 
+	to [`glsp::load_str`](fn.load_str.html).
+
+		let condition = glsp::compile_fn("(>= (quest-stage 'rescue) 3)", Some("quests.glsp"))?;
+		let met: bool = glsp::call(&condition, &())?;
+	*/
+
+	pub fn compile_fn(text: &str, filename: Option<&str>) -> GResult<Root<GFn>> {
+		let file_id = filename.map(|path| glsp::filename(path));
+
+		glsp::push_frame(Frame::GlspApi(GlspApiName::CompileFn, file_id));
+		let _guard = Guard::new(|| glsp::pop_frame());
+
+		//we evaluate the form (fn () ..forms), which expands and compiles the forms once
+		let fn_arr = glsp::arr();
+		fn_arr.push(FN_SYM)?;
+		fn_arr.push(glsp::arr())?;
+		fn_arr.extend(glsp::parse_all(text, filename)?)?;
+
+		match eval::eval(&[Val::Arr(fn_arr)], None, None)? {
+			Val::GFn(gfn) => Ok(gfn),
+			_ => unreachable!()
 		}
 	}
 
-	/** Equivalent to [`(once-with f)`](https://gamelisp.rs/std/once-with). */
+	/**
+	Replaces the function which is used to obtain the source text for a file.
+
+	The loader is used by [`glsp::load`](fn.load.html), [`glsp::require`](fn.require.html),
+	[`glsp::load_and_compile`](fn.load_and_compile.html) and the 
+	[`include`](https://gamelisp.rs/std/include) macro. It receives a filename which has been 
+	resolved using the current [`FileResolution`](enum.FileResolution.html). By default, the 
+	source text is read from the filesystem using 
+	[`std::fs::read_to_string`](https://doc.rust-lang.org/std/fs/fn.read_to_string.html).
+
+	On the `wasm32` target, there's no filesystem, so loading a file will fail unless a loader
+	has been set. An [`IncludedDir`](struct.IncludedDir.html) can be used as the loader.
+
+		glsp::set_loader(move |filename| {
+			match pak.read_to_string(filename) {
+				Some(text) => Ok(Cow::Owned(text)),
+				None => bail!("{} is not present in the pak file", filename)
+			}
+		});
 
-	pub fn once_with(callable: Callable) -> Root<GIter> {
-		glsp::giter(GIterState::OnceWith(GcCallable::from_callable(&callable)))
+	The loader can be replaced at any time, or configured up front using
+	[`RuntimeBuilder::loader`](struct.RuntimeBuilder.html#method.loader).
+	*/
+
+	pub fn set_loader<F>(loader: F)
+	where
+		F: Fn(&str) -> GResult<Cow<'static, str>> + 'static
+	{
+		with_engine(|engine| {
+			*engine.loader.borrow_mut() = Some(Rc::new(loader));
+		})
 	}
 
-	/** Equivalent to [`(repeat ..args)`](https://gamelisp.rs/std/repeat). */
+	/**
+	Obtains the source text for a file, using the function passed to
+	[`glsp::set_loader`](fn.set_loader.html), or the filesystem by default.
+
+	`filename` is passed to the loader as-is. To resolve it relative to the file which is
+	currently being loaded, use [`glsp::resolve_filename`](fn.resolve_filename.html).
+	*/
 
-	pub fn repeat(args: &[Val]) -> GResult<Root<GIter>> {
-		match args.len() {
-			0 => bail!("the repeat iterator requires at least one argument"),
-			1 => Ok(glsp::giter(GIterState::Repeat1(Slot::from_val(&args[0])))),
-			_ => {
-				let arr = glsp::arr_from_iter(args.iter()).unwrap();
-				Ok(glsp::giter(GIterState::RepeatN(arr.to_gc(), 0, (arr.len() - 1) as u32)))
+	pub fn read_source(filename: &str) -> GResult<Cow<'static, str>> {
+		let text = glsp::load_source(filename)?;
+
+		//a file which is read while another file is being compiled, e.g. by (include), is one of
+		//its compile cache dependencies
+		#[cfg(feature = "compiler")]
+		with_engine(|engine| {
+			let mut cache = engine.compile_cache.borrow_mut();
+			if cache.is_recording() {
+				cache.note_source(filename, &text);
 			}
-		}
+		});
+
+		Ok(text)
 	}
 
-	/** Equivalent to [`(repeat-with f)`](https://gamelisp.rs/std/repeat-with). */
+	fn load_source(filename: &str) -> GResult<Cow<'static, str>> {
+		//the loader may call other glsp functions, so we can't hold a borrow while it runs
+		let loader = with_engine(|engine| engine.loader.borrow().clone());
 
-	pub fn repeat_with(callable: Callable) -> Root<GIter> {
-		glsp::giter(GIterState::RepeatWith(GcCallable::from_callable(&callable)))
-	}
+		match loader {
+			Some(loader) => loader(filename),
 
-	/** Equivalent to [`(chunks len src-arr)`](https://gamelisp.rs/std/chunks). */
+			//the wasm32 target has no filesystem
+			#[cfg(target_arch = "wasm32")]
+			None => {
+				bail!("unable to load file '{}': call glsp::set_loader to provide source files \
+				       on this platform", filename)
+			}
 
-	pub fn chunks(chunk_len: usize, src_arr: &Root<Arr>) -> GResult<Root<GIter>> {
-		ensure!(chunk_len > 0, "cannot split an array into chunks of length 0");
-		Ok(glsp::giter(GIterState::Chunks(chunk_len as u32, src_arr.shallow_clone().to_gc())))
+			#[cfg(not(target_arch = "wasm32"))]
+			None => {
+				match fs::read_to_string(filename) {
+					Ok(text) => Ok(Cow::Owned(text)),
+					Err(err) => {
+						Err(error!("unable to load file '{}'", filename).with_source(err))
+					}
+				}
+			}
+		}
 	}
 
-	/** Equivalent to [`(chunks-exact len src-arr)`](https://gamelisp.rs/std/chunks-exact). */
+	/**
+	Sets the rules used to resolve the filenames passed to [`(load)`](https://gamelisp.rs/std/load),
+	[`(require)`](https://gamelisp.rs/std/require) and 
+	[`(include)`](https://gamelisp.rs/std/include).
 
-	pub fn chunks_exact(chunk_len: usize, src_arr: &Root<Arr>) -> GResult<Root<GIter>> {
-		ensure!(chunk_len > 0, "cannot split an array into chunks of length 0");
-		
-		let len = src_arr.len() - (src_arr.len() % chunk_len);
-		let arr = glsp::arr_from_iter(src_arr.iter().take(len)).unwrap();
+	The default is [`FileResolution::Root`](enum.FileResolution.html#variant.Root). It can be
+	changed at any time, or configured up front using
+	[`RuntimeBuilder::file_resolution`](struct.RuntimeBuilder.html#method.file_resolution).
+	*/
 
-		Ok(glsp::giter(GIterState::Chunks(chunk_len as u32, arr.to_gc())))
+	pub fn set_file_resolution(resolution: FileResolution) {
+		with_engine(|engine| {
+			engine.file_resolution.set(resolution)
+		})
 	}
 
-	/** Equivalent to [`(rchunks len src-arr)`](https://gamelisp.rs/std/rchunks). */
+	/**
+	Returns the current [`FileResolution`](enum.FileResolution.html).
+	*/
 
-	pub fn rchunks(chunk_len: usize, src_arr: &Root<Arr>) -> GResult<Root<GIter>> {
-		ensure!(chunk_len > 0, "cannot split an array into chunks of length 0");
-		Ok(glsp::giter(GIterState::RChunks(chunk_len as u32, src_arr.shallow_clone().to_gc())))
+	pub fn file_resolution() -> FileResolution {
+		with_engine(|engine| {
+			engine.file_resolution.get()
+		})
 	}
 
-	/** Equivalent to [`(rchunks-exact len src-arr)`](https://gamelisp.rs/std/rchunks-exact). */
+	/**
+	Resolves a filename using the current [`FileResolution`](enum.FileResolution.html).
 
-	pub fn rchunks_exact(chunk_len: usize, src_arr: &Root<Arr>) -> GResult<Root<GIter>> {
-		ensure!(chunk_len > 0, "cannot split an array into chunks of length 0");
-		
-		let to_skip = src_arr.len() % chunk_len;
-		let arr = glsp::arr_from_iter(src_arr.iter().skip(to_skip)).unwrap();
+	This is the filename which [`glsp::load`](fn.load.html) would pass to the
+	[loader](fn.set_loader.html), if it were called with `filename` at this point.
+	*/
 
-		Ok(glsp::giter(GIterState::RChunks(chunk_len as u32, arr.to_gc())))
+	pub fn resolve_filename(filename: &str) -> String {
+		with_engine(|engine| {
+			let loading = engine.loading.borrow();
+			let absolute = filename.starts_with(|ch| ch == '/' || ch == '\\') || 
+			               Path::new(filename).is_absolute();
+
+			match (engine.file_resolution.get(), loading.last()) {
+				(FileResolution::RelativeToFile, Some(current)) if !absolute => {
+					let dir_len = current.rfind(|ch| ch == '/' || ch == '\\').map_or(0, |i| i + 1);
+					normalize_filename(&format!("{}{}", &current[..dir_len], filename))
+				}
+				_ => filename.to_string()
+			}
+		})
 	}
 
-	/** Equivalent to [`(windows len src-arr)`](https://gamelisp.rs/std/windows). */
+	/** Equivalent to [`(load filename)`](https://gamelisp.rs/std/load). */
 
-	pub fn windows(window_len: usize, src_arr: &Root<Arr>) -> GResult<Root<GIter>> {
-		ensure!(window_len > 0, "cannot split an array into windows of length 0");
-		Ok(glsp::giter(GIterState::Windows(window_len as u32, src_arr.shallow_clone().to_gc())))
+	pub fn load(filename: &str) -> GResult<Val> {
+		glsp::load_resolved(&glsp::resolve_filename(filename), None)
 	}
 
-	/** Equivalent to [`(lines st)`](https://gamelisp.rs/std/lines). */
+	/**
+	Loads GameLisp source text which didn't come from the [loader](fn.set_loader.html).
 
-	pub fn lines(st: &Root<Str>) -> Root<GIter> {
-		glsp::giter(GIterState::Lines(st.shallow_clone().to_gc()))
-	}
+	This is otherwise equivalent to [`glsp::load`](fn.load.html). `filename` isn't resolved: it's
+	used as-is for spans in error messages, for [`glsp::loaded_files`](fn.loaded_files.html),
+	and as the base directory for any files which the text loads using
+	[`FileResolution::RelativeToFile`](enum.FileResolution.html#variant.RelativeToFile).
 
-	/** Equivalent to [`(split st split-at)`](https://gamelisp.rs/std/split). */
+		glsp::load_str("(prn \"hello, world\")", "scripts/hello.glsp")?;
+	*/
 
-	pub fn split(src: &Root<Str>, split_at: &Root<Str>) -> Root<GIter> {
-		glsp::giter(GIterState::Split(src.shallow_clone().to_gc(),
-		                              split_at.shallow_clone().to_gc()))
+	pub fn load_str(text: &str, filename: &str) -> GResult<Val> {
+		glsp::load_resolved(filename, Some(text))
 	}
 
-	/** Equivalent to [`(rev base)`](https://gamelisp.rs/std/rev). */
+	fn load_resolved(filename: &str, text: Option<&str>) -> GResult<Val> {
+		let normalized = normalize_filename(filename);
+		glsp::run_file_defers(&normalized)?;
+		add_dependency(&normalized);
+		start_loaded_file(&normalized);
 
-	pub fn rev(base: &Root<GIter>) -> GResult<Root<GIter>> {
-		ensure!(base.is_double_ended(), "{} iterators are not double-ended", base.state_name());
-		Ok(glsp::giter(GIterState::Rev(base.to_gc())))
-	}
+		let file_id = glsp::filename(filename);
 
-	/** Equivalent to [`(enumerate base)`](https://gamelisp.rs/std/enumerate). */
+		glsp::push_frame(Frame::GlspApi(GlspApiName::Load, Some(file_id)));
+		let _guard = Guard::new(|| glsp::pop_frame());
 
-	pub fn enumerate(base: &Root<GIter>) -> Root<GIter> {
-		glsp::giter(GIterState::Enumerate(base.to_gc(), 0))
-	}
+		//each file starts out in the root module, so an (in-module) call only lasts until the
+		//end of the file which contains it
+		let prev_module = glsp::current_module();
+		glsp::set_current_module(None);
+		let _module_guard = Guard::new(move || glsp::set_current_module(prev_module));
 
-	/** Equivalent to [`(cloned base)`](https://gamelisp.rs/std/cloned). */
+		let _loading_guard = loading_guard(filename);
 
-	pub fn cloned(base: &Root<GIter>) -> Root<GIter> {
-		glsp::giter(GIterState::Cloned(base.to_gc()))
-	}
+		#[cfg(feature = "compiler")] {
+			if glsp::uses_compile_cache() {
+				return glsp::load_cached(filename, text, &normalized)
+			}
 
-	/** Equivalent to [`(deep-cloned base)`](https://gamelisp.rs/std/deep-cloned). */
+			if is_playing_back() {
+				return glsp::load_playback(filename)
+			} else {
+				glsp::record_action(Action::StartLoad(file_id));
+			}
+		}
 
-	pub fn deep_cloned(base: &Root<GIter>) -> Root<GIter> {
-		glsp::giter(GIterState::DeepCloned(base.to_gc()))
-	}
+		#[cfg(feature = "compiler")]
+		let _guard = Guard::new(|| glsp::record_action(Action::EndLoad));
 
-	/** Equivalent to [`(step-by n base)`](https://gamelisp.rs/std/step-by). */
+		let text = match text {
+			Some(text) => Cow::Borrowed(text),
+			None => glsp::load_source(filename)?
+		};
+		let vals = glsp::parse_all(&text, Some(filename))?;
 
-	pub fn step_by(step_by: usize, base: &Root<GIter>) -> GResult<Root<GIter>> {
-		ensure!(step_by > 0, "cannot step an iterator by 0");
-		Ok(glsp::giter(GIterState::StepBy(step_by as u32, base.to_gc())))
+		eval::eval(&vals, None, Some(&normalized))
 	}
 
-	/** Equivalent to [`(map f base)`](https://gamelisp.rs/std/map). */
-
-	pub fn map(callable: &Callable, base: &Root<GIter>) -> Root<GIter> {
-		glsp::giter(GIterState::Map(GcCallable::from_callable(callable), base.to_gc()))
+	//a file's toplevel (defer) forms are stored when it finishes loading successfully. they're
+	//run when the same file starts loading again, or when the Runtime is dropped.
+	pub(crate) fn push_file_defers(filename: &str, defers: Vec<Root<Bytecode>>) {
+		if defers.len() > 0 {
+			with_engine(|engine| {
+				engine.file_defers.borrow_mut().push((filename.to_string(), defers));
+			})
+		}
 	}
 
-	/** Equivalent to [`(filter f base)`](https://gamelisp.rs/std/filter). */
+	fn run_file_defers(filename: &str) -> GResult<()> {
+		let defers = with_engine(|engine| {
+			let mut file_defers = engine.file_defers.borrow_mut();
+			match file_defers.iter().position(|&(ref name, _)| name == filename) {
+				Some(i) => file_defers.remove(i).1,
+				None => Vec::new()
+			}
+		});
 
-	pub fn filter(callable: &Callable, base: &Root<GIter>) -> Root<GIter> {
-		glsp::giter(GIterState::Filter(GcCallable::from_callable(callable), base.to_gc()))
+		let mut result = Ok(Val::Nil);
+		eval::run_defers(defers, &mut result);
+		result.map(|_| ())
 	}
 
-	/** Equivalent to [`(zip ..args)`](https://gamelisp.rs/std/zip). */
-
-	pub fn zip(iterables: &[Iterable]) -> Root<GIter> {
-		let arr = glsp::arr_from_iter(iterables.iter().map(|iterable| iterable.giter())).unwrap();
-		glsp::giter(GIterState::Zip(arr.to_gc()))
-	}
+	/**
+	Loads a file again, replacing any definitions which it makes.
 
-	/** Equivalent to [`(chain ..args)`](https://gamelisp.rs/std/chain). */
+	Equivalent to [`(reload filename)`](https://gamelisp.rs/std/reload).
 
-	pub fn chain(iterables: &[Iterable]) -> Root<GIter> {
-		let arr = glsp::arr_from_iter(iterables.iter().map(|iterable| iterable.giter())).unwrap();
-		glsp::giter(GIterState::Chain(arr.to_gc()))
-	}
+	This is like [`glsp::load`](fn.load.html), except that definitions like `defn`, `defmacro`
+	and `defclass` replace any existing global or macro binding, rather than failing. Frozen
+	globals still can't be rebound.
 
-	/** Equivalent to [`(flatten base)`](https://gamelisp.rs/std/flatten). */
+	When a global which was bound to a class is rebound to a different class, each obj of the 
+	old class is updated to become an instance of the new class. If both classes have the same
+	fields and states, the obj is simply given the new class. Otherwise, it's migrated:
 
-	pub fn flatten(base: &Root<GIter>) -> Root<GIter> {
-		glsp::giter(GIterState::Flatten(base.to_gc(), None))
-	}
+	- If the new class has a `migrate` const which stores a function, that function is called
+	  with the obj, which is still an instance of the old class. It may return a new instance 
+	  of the new class, in which case that instance's fields and states are moved into the obj.
+	  (The new instance is killed, without running its finalizers.)
 
-	/** Equivalent to [`(cycle base)`](https://gamelisp.rs/std/cycle). */
+	- If there's no `migrate` function, or if it returns `#n` or the obj itself, each field is
+	  copied into the new class' field which has the same name and state, and each enabled 
+	  state remains enabled if the new class has a state with the same name. Fields which 
+	  don't exist in the old class are set to `#n`. No initializers or finalizers are run.
 
-	pub fn cycle(base: &Root<GIter>) -> Root<GIter> {
-		glsp::giter(GIterState::Cycle(Some(base.to_gc()), glsp::arr().to_gc(), 0))
-	}
+	If migration fails with an error, the obj keeps its old class and it's flagged as 
+	[stale](struct.Obj.html#method.is_stale), so that calling any of its methods will fail with
+	a `stale-instance` error. Stale objs are listed in the returned 
+	[`ReloadReport`](struct.ReloadReport.html).
 
-	/** Equivalent to [`(take n base)`](https://gamelisp.rs/std/take). */
+	If an error occurs, every global and macro binding, and every module's imports, are restored
+	to their state before the reload, and no objs are updated. Any other side-effects, such as 
+	mutations to arrays and tables, aren't undone.
+	*/
 
-	pub fn take(n: usize, base: &Root<GIter>) -> Root<GIter> {
-		glsp::giter(GIterState::Take(n as u32, base.to_gc()))
-	}
+	pub fn reload(filename: &str) -> GResult<ReloadReport> {
+		let snapshot = glsp::global_snapshot();
+		let prev_modules = with_engine(|engine| engine.modules.borrow().clone());
 
-	/** Equivalent to [`(take-while f base)`](https://gamelisp.rs/std/take-while). */
+		let result = {
+			let prev_reloading = with_engine(|engine| engine.reloading.replace(true));
+			let _guard = Guard::new(|| {
+				with_engine(|engine| engine.reloading.set(prev_reloading))
+			});
 
-	pub fn take_while(callable: &Callable, base: &Root<GIter>) -> Root<GIter> {
-		glsp::giter(GIterState::TakeWhile(GcCallable::from_callable(callable), base.to_gc()))
-	}
+			glsp::load(filename)
+		};
 
-	/** Equivalent to [`(skip n base)`](https://gamelisp.rs/std/skip). */
+		let result = match result {
+			Ok(result) => result,
+			Err(err) => {
+				with_engine(|engine| *engine.modules.borrow_mut() = prev_modules);
+				glsp::restore_globals(&snapshot)?;
+				return Err(err)
+			}
+		};
 
-	pub fn skip(n: usize, base: &Root<GIter>) -> Root<GIter> {
-		glsp::giter(GIterState::Skip(n as u32, base.to_gc()))
-	}
+		//remap or migrate the objs of each redefined class
+		let mut stale_objs = Vec::new();
+		let mut migration_errors = Vec::new();
+		for &(sym, ref prev_val, _) in &snapshot.bindings {
+			if let Some(Val::Class(ref old_class)) = *prev_val {
+				let new_class = match glsp::try_global::<_, Val>(sym)? {
+					Some(Val::Class(new_class)) if !Root::ptr_eq(&new_class, old_class) => {
+						new_class
+					}
+					_ => continue
+				};
 
-	/** Equivalent to [`(skip-while f base)`](https://gamelisp.rs/std/skip-while). */
+				new_class.set_version(old_class.version() + 1);
+				let compatible = old_class.has_same_layout(&new_class);
 
-	pub fn skip_while(callable: &Callable, base: &Root<GIter>) -> Root<GIter> {
-		let gc_callable = GcCallable::from_callable(callable);
-		glsp::giter(GIterState::SkipWhile(Some(gc_callable), base.to_gc()))
-	}
+				//migration runs arbitrary glsp code, so the objs need to be rooted
+				let objs = with_heap(|heap| heap.objs_of_class(&old_class.to_gc()));
+				let objs: Vec<Root<Obj>> = objs.iter().map(|obj| obj.root()).collect();
 
-	//---------------------------------------------------------------------------------------------
-	// garbage collection
-	//---------------------------------------------------------------------------------------------
+				for obj in objs {
+					if compatible {
+						obj.replace_class(&new_class.to_gc());
+					} else if let Err(err) = obj.migrate(&new_class) {
+						obj.mark_stale(new_class.version());
+						stale_objs.push(obj);
+						migration_errors.push(err);
+					}
+				}
+			}
+		}
 
-	/** Equivalent to [`(gc)`](https://gamelisp.rs/std/gc). */
+		//we don't need to invalidate any inline caches here. rebinding a cached global has already
+		//invalidated every global cache, and method caches are keyed by class, so a remapped obj
+		//can't hit a stale method lookup.
 
-	pub fn gc() {
-		with_engine(|engine| {
-			engine.vm.traverse_stacks();
-			engine.heap.step()
+		Ok(ReloadReport {
+			result,
+			stale_objs,
+			migration_errors
 		})
 	}
 
-	/** Equivalent to [`(gc-value 'ratio)`](https://gamelisp.rs/std/gc-value). */
+	/**
+	Registers a callback to be invoked whenever a file is modified.
 
-	pub fn gc_ratio() -> f32 {
-		with_engine(|engine| {
-			engine.heap.ratio()
-		})
-	}
+	Files are checked by [`glsp::poll_watched`](fn.poll_watched.html), which should be called
+	regularly - for example, once per frame. The callback receives the file's path. Typically,
+	it will [reload](fn.reload.html) the file:
 
-	/** Equivalent to [`(= (gc-value 'ratio) ratio)`](https://gamelisp.rs/std/set-gc-value). */
+		glsp::watch("scripts/combat.glsp", |path| {
+			glsp::reload(path)?;
+			Ok(())
+		})?;
 
-	pub fn gc_set_ratio(ratio: f32) {
-		with_engine(|engine| {
-			engine.heap.set_ratio(ratio)
-		})
-	}
+		//...then, in your main loop:
+		glsp::poll_watched()?;
 
-	/** Equivalent to [`(gc-value 'young-bytes)`](https://gamelisp.rs/std/gc-value). */
+	Requires the `"watch"` feature flag.
+	*/
+
+	#[cfg(feature = "watch")]
+	pub fn watch<F>(path: &str, callback: F) -> GResult<()>
+	where
+		F: FnMut(&str) -> GResult<()> + 'static
+	{
+		let modified = match fs::metadata(path).and_then(|metadata| metadata.modified()) {
+			Ok(modified) => modified,
+			Err(err) => return Err(error!("unable to watch file '{}'", path).with_source(err))
+		};
 
-	pub fn gc_young_bytes() -> usize {
 		with_engine(|engine| {
-			engine.heap.young_memory_usage()
-		})
+			engine.watches.borrow_mut().push(Watch {
+				path: path.to_string(),
+				modified: Some(modified),
+				callback: Box::new(callback)
+			});
+		});
+
+		Ok(())
 	}
 
-	/** Equivalent to [`(gc-value 'old-bytes)`](https://gamelisp.rs/std/gc-value). */
+	/**
+	Invokes the callback for each [watched](fn.watch.html) file which has been modified since 
+	the last call.
 
-	pub fn gc_old_bytes() -> usize {
-		with_engine(|engine| {
-			engine.heap.old_memory_usage()
-		})
-	}
+	If a callback returns an error, the remaining files aren't checked until the next call.
+	A file which is temporarily missing, perhaps because an editor is in the middle of saving
+	it, is silently skipped.
 
-	/** Equivalent to [`(gc-value 'ghost-bytes)`](https://gamelisp.rs/std/gc-value). */
+	Requires the `"watch"` feature flag.
+	*/
 
-	pub fn gc_ghost_bytes() -> usize {
-		with_engine(|engine| {
-			engine.heap.ghost_memory_usage()
-		})
-	}
+	#[cfg(feature = "watch")]
+	pub fn poll_watched() -> GResult<()> {
+		//callbacks may call glsp::watch, so we don't hold a borrow while they're running
+		let mut watches = with_engine(|engine| {
+			std::mem::take(&mut *engine.watches.borrow_mut())
+		});
 
-	//---------------------------------------------------------------------------------------------
-	// evaluation and expansion
-	//---------------------------------------------------------------------------------------------
+		let mut result = Ok(());
+		for watch in &mut watches {
+			let modified = fs::metadata(&watch.path).and_then(|metadata| metadata.modified()).ok();
+			if modified.is_some() && modified != watch.modified {
+				watch.modified = modified;
 
-	/** Equivalent to [`(eval val env-mode)`](https://gamelisp.rs/std/eval). */
+				result = (watch.callback)(&watch.path);
+				if result.is_err() {
+					break
+				}
+			}
+		}
 
-	pub fn eval(val: &Val, env_mode: Option<EnvMode>) -> GResult<Val> {
-		glsp::push_frame(Frame::GlspApi(GlspApiName::Eval, None));
-		let _guard = Guard::new(|| glsp::pop_frame());
+		with_engine(|engine| {
+			let mut engine_watches = engine.watches.borrow_mut();
+			watches.extend(engine_watches.drain(..));
+			*engine_watches = watches;
+		});
 
-		eval::eval(&[val.clone()], env_mode, false)
+		result
 	}
 
-	/** Equivalent to [`(eval-multi vals env-mode)`](https://gamelisp.rs/std/eval-multi). */
+	/** Equivalent to [`(require filename)`](https://gamelisp.rs/std/require). */
 
-	pub fn eval_multi(vals: &[Val], env_mode: Option<EnvMode>) -> GResult<Val> {
-		glsp::push_frame(Frame::GlspApi(GlspApiName::EvalMulti, None));
+	pub fn require(filename: &str) -> GResult<Val> {
+		let filename = &glsp::resolve_filename(filename);
+		let file_id = glsp::filename(filename);
+		glsp::push_frame(Frame::GlspApi(GlspApiName::Require, Some(file_id)));
 		let _guard = Guard::new(|| glsp::pop_frame());
 
-		eval::eval(vals, env_mode, false)
-	}
+		//files are identified by their resolved filename, so "a.glsp" and "./a.glsp" are the same
+		//file, but a symbolic link to "a.glsp" would be a different file
+		let path = normalize_filename(filename);
 
-	/** Equivalent to [`(load filename)`](https://gamelisp.rs/std/load). */
+		let cycle = with_engine(|engine| {
+			let loading = engine.loading.borrow();
+			let start = loading.iter().position(|loading| normalize_filename(loading) == path)?;
+			let mut cycle: Vec<String> = loading[start..].iter().map(|f| f.to_string()).collect();
+			cycle.push(filename.to_string());
+			Some(cycle.join(" -> "))
+		});
 
-	pub fn load(filename: &str) -> GResult<Val> {
-		let file_id = glsp::filename(filename);
+		if let Some(cycle) = cycle {
+			bail!("circular require: {}", cycle)
+		}
 
-		glsp::push_frame(Frame::GlspApi(GlspApiName::Load, Some(file_id)));
-		let _guard = Guard::new(|| glsp::pop_frame());
+		let already_seen = with_engine(|engine| engine.required.borrow().contains(&path));
 
+		//when this file was compiled, the required file might have been loaded in full, or it
+		//might have been skipped because it was already required. when the Runtime which is 
+		//playing back the Recording disagrees, we skip the recorded load, or fail
 		#[cfg(feature = "compiler")] {
-			if is_playing_back() {
-				return glsp::load_playback(filename)
-			} else {
-				glsp::record_action(Action::StartLoad(file_id));
+			if is_playing_back() && !glsp::in_cached_load() {
+				let recorded = with_engine(|engine| {
+					match engine.playing_back.borrow().as_ref().unwrap().peek() {
+						Ok(&Action::StartLoad(recorded)) => Some(recorded),
+						_ => None
+					}
+				});
+				let recorded = recorded.map_or(false, |recorded| {
+					&*glsp::filename_str(recorded) == filename.as_str()
+				});
+
+				match (recorded, already_seen) {
+					(true, true) => {
+						glsp::skip_playback_load()?;
+						add_dependency(&path);
+						return Ok(Val::Nil)
+					}
+					(false, false) => {
+						bail!("invalid Recording: {} had already been required when it was \
+						       compiled, so it must be required before calling load_compiled",
+						       filename)
+					}
+					_ => ()
+				}
 			}
 		}
 
-		#[cfg(feature = "compiler")]
-		let _guard = Guard::new(|| glsp::record_action(Action::EndLoad));
+		if already_seen {
+			add_dependency(&path);
+			return Ok(Val::Nil)
+		}
 
-		let text = match fs::read_to_string(&filename) {
-			Ok(text) => text,
-			Err(err) => return Err(error!("unable to load file '{}'", filename).with_source(err))
-		};
+		with_engine(|engine| engine.required.borrow_mut().insert(path.clone()));
 
-		let vals = glsp::parse_all(&text, Some(filename))?;
+		//if the file fails to load, requiring it again should attempt to load it again
+		let result = glsp::load_resolved(filename, None);
+		if result.is_err() {
+			with_engine(|engine| engine.required.borrow_mut().remove(&path));
+		}
 
-		eval::eval(&vals, None, true)
+		result
 	}
 
-	/** Equivalent to [`(require filename)`](https://gamelisp.rs/std/require). */
+	/**
+	Returns every file which has been loaded by the active `Runtime`, in the order that they
+	were first loaded.
 
-	pub fn require(filename: &str) -> GResult<Val> {
-		let file_id = glsp::filename(filename);
-		glsp::push_frame(Frame::GlspApi(GlspApiName::Require, Some(file_id)));
-		let _guard = Guard::new(|| glsp::pop_frame());
+	Each [`LoadedFile`](struct.LoadedFile.html) lists the files which it loaded or required,
+	so this can be used to work out which files need to be [reloaded](fn.reload.html) when
+	one of them changes. The [`include`](https://gamelisp.rs/std/include) macro isn't tracked.
 
-		let path = match PathBuf::from(filename).canonicalize() {
-			Ok(path) => path,
-			Err(err) => {
-				let msg = error!("invalid filename '{}' passed to glsp::require", filename);
-				return Err(msg.with_source(err))
-			}
-		};
-		
-		let already_seen = with_engine(|engine| {
-			let mut required = engine.required.borrow_mut();
-			if required.contains(&path) {
-				return true
-			} else {
-				required.insert(path);
-				return false
-			}
-		});
+	Equivalent to [`(loaded-files)`](https://gamelisp.rs/std/loaded-files).
+	*/
 
-		if already_seen {
-			Ok(Val::Nil)
-		} else {
-			glsp::load(filename)
-		}
+	pub fn loaded_files() -> Vec<LoadedFile> {
+		with_engine(|engine| {
+			engine.loaded_files.borrow().clone()
+		})
 	}
 
 	/**
@@ -3337,11 +8547,7 @@ pub mod glsp {
 		glsp::push_frame(Frame::GlspApi(GlspApiName::LoadAndCompile, Some(file_id)));
 		let _guard = Guard::new(|| glsp::pop_frame());
 
-		let text = match fs::read_to_string(&filename) {
-			Ok(text) => text,
-			Err(err) => return Err(error!("unable to load file '{}'", filename).with_source(err))
-		};
-
+		let text = glsp::load_source(filename)?;
 		glsp::load_and_compile_str(&text, filename)
 	}
 
@@ -3392,7 +8598,13 @@ pub mod glsp {
 		glsp::record_action(Action::StartLoad(file_id));
 		let end_load_guard = Guard::new(|| glsp::record_action(Action::EndLoad));
 
-		let result = eval::eval(vals, None, true)?;
+		let result = {
+			let normalized = normalize_filename(filename);
+			glsp::run_file_defers(&normalized)?;
+			start_loaded_file(&normalized);
+			let _loading_guard = loading_guard(filename);
+			eval::eval(vals, None, Some(&normalized))?
+		};
 
 		forget(recording_guard);
 		drop(end_load_guard);
@@ -3464,7 +8676,14 @@ pub mod glsp {
 			});
 		});
 
-		let result = glsp::load_playback(&glsp::filename_str(root_filename))?;
+		let root_filename = glsp::filename_str(root_filename);
+		let result = {
+			let normalized = normalize_filename(&root_filename);
+			glsp::run_file_defers(&normalized)?;
+			start_loaded_file(&normalized);
+			let _loading_guard = loading_guard(&root_filename);
+			glsp::load_playback(&root_filename)?
+		};
 
 		forget(playing_back_guard);
 
@@ -3477,6 +8696,298 @@ pub mod glsp {
 		Ok(result)
 	}
 
+	/**
+	Enables the compile cache, storing its entries in the directory `dir`.
+
+	While the cache is enabled, each file passed to [`glsp::load`](fn.load.html), 
+	[`glsp::require`](fn.require.html) or [`glsp::load_str`](fn.load_str.html) is compiled as 
+	though by [`glsp::load_and_compile`](fn.load_and_compile.html), and its compiled code is 
+	stored in `dir`. When the same file is loaded again, perhaps by a later run of the program,
+	its compiled code is played back rather than being parsed, expanded and compiled again.
+
+	Each entry is keyed on the file's name and source text, the version of GameLisp, and the
+	[opt level](struct.RuntimeBuilder.html#method.opt_level). An entry also records everything 
+	outside the file which its macro-expansion depended on: the file which bound each global 
+	macro that it used, the value of each constant which was inlined, and any other source file
+	read using [`glsp::read_source`](fn.read_source.html), for example by 
+	[`include`](https://gamelisp.rs/std/include). If any of those have changed, the entry is
+	discarded and the file is compiled again.
+
+	Macros implemented by Rust functions are assumed not to change, so the directory should be
+	cleared when they do. If a file uses a global macro whose origin is unknown, perhaps because 
+	it was bound by a call to [`glsp::eval`](fn.eval.html), that file isn't cached. Macros which
+	have side-effects other than returning their expansion, or which read files without using 
+	`glsp::read_source`, should not be used while the cache is enabled.
+
+	The directory is created if it doesn't already exist. Enabling the cache also calls
+	[`glsp::seed_gensym`](fn.seed_gensym.html).
+
+	The compile cache is intended to speed up development builds. When shipping a game, 
+	`glsp::load_and_compile` and [`glsp::load_compiled`](fn.load_compiled.html) are usually a
+	better fit.
+
+		glsp::set_compile_cache("target/glsp-cache")?;
+		glsp::load("scripts/main.glsp")?;
+		eprn!("{} files were loaded from the cache", glsp::compile_cache_stats().hits());
+	*/
+
+	#[cfg(feature = "compiler")]
+	pub fn set_compile_cache<P: AsRef<Path>>(dir: P) -> GResult<()> {
+		let dir = dir.as_ref();
+		if let Err(err) = std::fs::create_dir_all(dir) {
+			let msg = error!("unable to create the compile cache directory {:?}", dir);
+			return Err(msg.with_source(err))
+		}
+
+		glsp::seed_gensym();
+
+		with_engine(|engine| {
+			engine.compile_cache.borrow_mut().set_dir(Some(dir.to_path_buf()));
+			Ok(())
+		})
+	}
+
+	/**
+	Disables the [compile cache](fn.set_compile_cache.html).
+
+	Existing cache entries aren't deleted.
+	*/
+
+	#[cfg(feature = "compiler")]
+	pub fn disable_compile_cache() {
+		with_engine(|engine| {
+			engine.compile_cache.borrow_mut().set_dir(None);
+		})
+	}
+
+	/**
+	Returns the number of cache hits and misses since the [compile cache](fn.set_compile_cache.html)
+	was first enabled.
+	*/
+
+	#[cfg(feature = "compiler")]
+	pub fn compile_cache_stats() -> CompileCacheStats {
+		with_engine(|engine| {
+			engine.compile_cache.borrow().stats()
+		})
+	}
+
+	//a (load) within a cached (load) is always handled by load_cached, even if the cache has been
+	//disabled in the meantime, so that the enclosing file's recording is set aside. otherwise, we
+	//stay out of the way of load_and_compile and load_compiled.
+	#[cfg(feature = "compiler")]
+	fn uses_compile_cache() -> bool {
+		with_engine(|engine| {
+			let cache = engine.compile_cache.borrow();
+			cache.is_loading() || (cache.dir().is_some() && 
+			                       engine.recording.borrow().is_none() &&
+			                       engine.playing_back.borrow().is_none())
+		})
+	}
+
+	#[cfg(feature = "compiler")]
+	pub(crate) fn in_cached_load() -> bool {
+		with_engine(|engine| {
+			engine.compile_cache.borrow().is_loading()
+		})
+	}
+
+	//glsp::load delegates to this function when glsp::uses_compile_cache() is true
+	#[cfg(feature = "compiler")]
+	fn load_cached(filename: &str, text: Option<&str>, normalized: &str) -> GResult<Val> {
+		let text = match text {
+			Some(text) => Cow::Borrowed(text),
+			None => glsp::load_source(filename)?
+		};
+
+		let key = cache::entry_key(filename, &text);
+		let dir = with_engine(|engine| {
+			let hash = cache::hash_source(&text);
+			let mut cache = engine.compile_cache.borrow_mut();
+			cache.note_load(filename, hash);
+			cache.push_frame(filename, hash);
+			cache.dir().cloned()
+		});
+
+		let outer_recording = with_engine(|engine| engine.recording.borrow_mut().take());
+		let outer_playback = with_engine(|engine| engine.playing_back.borrow_mut().take());
+		let _guard = Guard::new(move || {
+			with_engine(|engine| {
+				engine.compile_cache.borrow_mut().pop_frame();
+				*engine.recording.borrow_mut() = outer_recording;
+				*engine.playing_back.borrow_mut() = outer_playback;
+			})
+		});
+
+		//playback
+		let cached = dir.as_ref().and_then(|dir| glsp::lookup_cached(dir, key, filename));
+		if let Some(recording) = cached {
+			with_engine(|engine| *engine.playing_back.borrow_mut() = Some(recording));
+			let result = glsp::load_playback(filename)?;
+
+			with_engine(|engine| {
+				let recording = engine.playing_back.borrow_mut().take().unwrap();
+				ensure!(recording.is_empty(), "invalid Recording: some Actions are unused");
+
+				engine.compile_cache.borrow_mut().record_hit();
+				Ok(())
+			})?;
+
+			return Ok(result)
+		}
+
+		//recording
+		with_engine(|engine| {
+			engine.compile_cache.borrow_mut().start_recording();
+			*engine.recording.borrow_mut() = Some(Recording::new());
+		});
+
+		glsp::record_action(Action::StartLoad(glsp::filename(filename)));
+		let result = glsp::parse_all(&text, Some(filename)).and_then(|vals| {
+			eval::eval(&vals, None, Some(normalized))
+		});
+		glsp::record_action(Action::EndLoad);
+
+		let result = result?;
+
+		if let Some(dir) = dir {
+			let (recording, deps) = with_engine(|engine| {
+				let recording = engine.recording.borrow_mut().take().unwrap();
+				(recording, engine.compile_cache.borrow_mut().take_deps())
+			});
+
+			let stored = match (deps, recording.try_into_bytes()) {
+				(Some(deps), Ok(bytes)) => {
+					cache::write_entry(&dir, key, filename, deps, bytes);
+					true
+				}
+				_ => false
+			};
+
+			with_engine(|engine| engine.compile_cache.borrow_mut().record_miss(stored));
+		}
+
+		Ok(result)
+	}
+
+	//returns a cache entry's Recording, if it exists and each of its Deps is still current
+	#[cfg(feature = "compiler")]
+	fn lookup_cached(dir: &Path, key: u64, filename: &str) -> Option<Recording> {
+		let entry = cache::read_entry(dir, key, filename)?;
+
+		let mut loads = FnvHashSet::default();
+		for dep in &entry.deps {
+			if let Dep::Source(ref filename, hash) = *dep {
+				let text = glsp::load_source(filename).ok()?;
+				if cache::hash_source(&text) != hash {
+					return None
+				}
+
+				loads.insert((&filename[..], hash));
+			}
+		}
+
+		let current = with_engine(|engine| {
+			let cache = engine.compile_cache.borrow();
+			entry.deps.iter().all(|dep| {
+				matches!(*dep, Dep::Source(..)) || cache.is_current(dep, &loads)
+			})
+		});
+
+		if current {
+			Recording::from_bytes(&entry.recording).ok()
+		} else {
+			None
+		}
+	}
+
+	//called by the expander for each global macro lookup
+	#[cfg(feature = "compiler")]
+	pub(crate) fn note_macro_lookup(sym: Sym) {
+		with_engine(|engine| {
+			let mut cache = engine.compile_cache.borrow_mut();
+			if cache.is_recording() {
+				let expander = engine.syms.borrow()[sym.0 as usize].bound_macro.clone();
+				cache.note_macro(sym, expander.as_ref());
+			}
+		})
+	}
+
+	//called by the encoder for each constant which it inlines
+	#[cfg(feature = "compiler")]
+	pub(crate) fn note_inlined_const(sym: Sym, val: &Val) {
+		with_engine(|engine| {
+			let mut cache = engine.compile_cache.borrow_mut();
+			if cache.is_recording() {
+				cache.note_const(sym, val);
+			}
+		})
+	}
+
+	/**
+	Searches the output of [`glsp::load_and_compile`](fn.load_and_compile.html) for definitions
+	which are never used.
+
+	`recordings` should contain the compiled output for every script in the project.
+	`entry_points` names each global function, class or macro which the host accesses directly,
+	for example by calling [`glsp::global`](fn.global.html).
+
+	Each toplevel `defn`, `defmacro`, `defclass` or `defmixin` form which can't be reached from 
+	an entry point is reported, in the order that the forms were compiled. A definition is 
+	reached when it's referred to by a reachable definition, or by any other toplevel form, such 
+	as a `def` or a function call. Any sym literal counts as a reference to the global with 
+	that name, and any macro invocation counts as a reference to that macro.
+
+	When reachable code looks up a global using a name which isn't a literal, as in
+	`(global (sym name))`, the analysis can't tell which definitions it might use. The report 
+	still lists every unreachable definition, but each [`DeadDef`](struct.DeadDef.html) is 
+	marked as [uncertain](struct.DeadDef.html#method.is_certain).
+
+		let (_, bytes) = glsp::load_and_compile("scripts/main.glsp")?;
+		let report = glsp::dead_code_report(&[&bytes[..]], &[glsp::sym("main")?])?;
+		print!("{}", glsp::dead_code_text(&report));
+	*/
+
+	#[cfg(feature = "compiler")]
+	pub fn dead_code_report(recordings: &[&[u8]], entry_points: &[Sym]) -> GResult<Vec<DeadDef>> {
+		let recordings = recordings.iter().map(|bytes| {
+			Recording::from_bytes(bytes)
+		}).collect::<GResult<Vec<_>>>()?;
+
+		Ok(deadcode::dead_code_report(&recordings[..], entry_points))
+	}
+
+	/**
+	Renders the output of [`glsp::dead_code_report`](fn.dead_code_report.html) as text.
+
+	Each definition is listed on its own line, like `scripts/ui.glsp:40: (defn old-menu)`.
+	If the report is uncertain, it ends with a note which lists the lookups responsible.
+	*/
+
+	#[cfg(feature = "compiler")]
+	pub fn dead_code_text(report: &[DeadDef]) -> String {
+		deadcode::render_text(report)
+	}
+
+	//discards the actions for a file which was loaded when the Recording was made, and which
+	//is being skipped during playback. (require) is the only caller.
+	#[cfg(feature = "compiler")]
+	fn skip_playback_load() -> GResult<()> {
+		let mut depth = 0;
+		loop {
+			match glsp::pop_action()? {
+				Action::StartLoad(_) => depth += 1,
+				Action::EndLoad => {
+					depth -= 1;
+					if depth == 0 {
+						return Ok(())
+					}
+				}
+				Action::Execute(_) | Action::ToplevelLet(_) | Action::Defer(_) => ()
+			}
+		}
+	}
+
 	//glsp::load delegates to this function when glsp::is_playing_back() is true.
 	#[cfg(feature = "compiler")]
 	pub(crate) fn load_playback(expected_filename: &str) -> GResult<Val> {
@@ -3490,6 +9001,7 @@ pub mod glsp {
 
 		let mut result = Val::Nil;
 		let mut toplevel_let: Option<Root<Stay>> = None;
+		let mut defers = Vec::new();
 		loop {
 			match glsp::pop_action()? {
 				Action::Execute(bytecode) => {
@@ -3513,8 +9025,10 @@ pub mod glsp {
 				}
 				Action::EndLoad => {
 					ensure!(toplevel_let.is_none(), "invalid Recording: unexpected ToplevelLet");
+					glsp::push_file_defers(&normalize_filename(expected_filename), defers);
 					return Ok(result)
 				}
+				Action::Defer(bytecode) => defers.push(bytecode)
 			}
 		}
 	}
@@ -3535,6 +9049,8 @@ pub mod glsp {
 		A: ToCallArgs + ?Sized,
 		R: FromVal
 	{
+		with_vm(|vm| vm.debug_assert_outside_safepoint());
+
 		glsp::push_frame(Frame::GlspCall(receiver.name()));
 		let _guard = Guard::new(|| glsp::pop_frame());
 
@@ -3551,6 +9067,94 @@ pub mod glsp {
 		})
 	}
 
+	/**
+	Invokes a callable value with its own fuel, allocation and time budgets.
+
+	The `limits` apply for the dynamic extent of the call, including any coroutines which it
+	resumes and any Rust functions which call back into GameLisp. When this function returns, the
+	previous limits are restored. Fuel consumed by the call is also deducted from the previous
+	fuel, if any. When calls to `call_limited` are nested, the inner call is bound by the 
+	strictest of its own limits and the limits which were already in place.
+
+	If the call violates one of its own limits, it fails with an error which names the callee,
+	like `(on-tick) exhausted its fuel limit`. Because the limits have been restored by the time
+	that error is returned, the caller can handle it. If a script catches the error from inside
+	the limited call, the violation persists until the call returns, just like
+	[`glsp::set_fuel`](fn.set_fuel.html).
+
+		let limits = Limits {
+			alloc_bytes: Some(64 * 1024),
+			deadline: Some(Instant::now() + Duration::from_micros(500)),
+			..Limits::default()
+		};
+
+		for (name, on_tick) in &mods {
+			if let Err(err) = glsp::call_limited::<_, _, Val>(on_tick, &(), &limits) {
+				eprn!("unloading {}: {}", name, err);
+			}
+		}
+	*/
+
+	pub fn call_limited<C, A, R>(receiver: &C, args: &A, limits: &Limits) -> GResult<R>
+	where
+		C: CallableOps,
+		A: ToCallArgs + ?Sized,
+		R: FromVal
+	{
+		with_engine(|engine| {
+			engine.vm.with_limits(limits, receiver.name(), || glsp::call(receiver, args))
+		})
+	}
+
+	/**
+	Returns a [`RuntimeHandle`](../struct.RuntimeHandle.html) for the active `Runtime`.
+
+	Equivalent to [`Runtime::handle`](../struct.Runtime.html#method.handle).
+	*/
+
+	pub fn runtime_handle() -> RuntimeHandle {
+		with_engine(|engine| RuntimeHandle(Arc::downgrade(&engine.vm.execution_slot)))
+	}
+
+	/**
+	Reports which code a `Runtime` is executing. Can be called from any thread.
+
+	The `Runtime` publishes its position whenever it enters a GameLisp function, and whenever
+	it jumps within a function's bytecode (for example, on each iteration of a loop). This is
+	cheap enough that it's always enabled. The result is precise enough to identify the loop
+	or the function in which a script has become stuck:
+
+		let handle = runtime.handle();
+
+		thread::spawn(move || {
+			loop {
+				thread::sleep(Duration::from_secs(5));
+				if let Some(point) = glsp::current_execution_point(&handle) {
+					eprintln!("script stuck at {}", point);
+				}
+			}
+		});
+
+	Returns `None` if the `Runtime` isn't executing any GameLisp code, or if it's been dropped.
+	While the `Runtime` is executing a Rust function, the result describes the GameLisp code 
+	which called it.
+
+	Because the `Runtime` keeps running while this function reads its position, the result may
+	already be out of date by the time it's returned.
+	*/
+
+	pub fn current_execution_point(handle: &RuntimeHandle) -> Option<ExecutionPoint> {
+		handle.0.upgrade()?.resolve()
+	}
+
+	pub(crate) fn add_fn_name(name: Option<Sym>) {
+		if let Some(name) = name {
+			with_engine(|engine| {
+				engine.vm.execution_slot.add_fn_name(name, &name.name());
+			})
+		}
+	}
+
 	pub(crate) fn call_gfn(gfn: &Root<GFn>, arg_count: usize) -> GResult<Val> {
 		with_engine(|engine| {
 			Ok(engine.vm.exec_gfn(gfn, arg_count)?)
@@ -3579,6 +9183,125 @@ pub mod glsp {
 		})
 	}
 
+	/** Equivalent to [`(coro-throw co err)`](https://gamelisp.rs/std/coro-throw). */
+
+	pub fn coro_throw(coro: &Root<Coro>, error: GError) -> GResult<()> {
+		glsp::push_frame(Frame::GlspApi(GlspApiName::CoroThrow, None));
+		let _guard = Guard::new(|| glsp::pop_frame());
+
+		with_engine(|engine| {
+			Ok(engine.vm.coro_throw(coro, error)?)
+		})
+	}
+
+	/**
+	Saves a `newborn` or `paused` coroutine as a table of representable data.
+
+	Equivalent to [`(coro-save co)`](https://gamelisp.rs/std/coro-save).
+
+	Only some coroutines can be saved: see [`Coro::check_saveability`][0] for the details.
+	The coroutine itself is left unchanged. The result can be converted to text, or serialized
+	using Serde, and later passed to [`glsp::coro_restore`](fn.coro_restore.html).
+
+	[0]: struct.Coro.html#method.check_saveability
+	*/
+
+	pub fn coro_save(coro: &Root<Coro>) -> GResult<Root<Tab>> {
+		corosave::coro_save(coro)
+	}
+
+	/**
+	Reconstructs a coroutine which was saved by [`glsp::coro_save`](fn.coro_save.html).
+
+	Equivalent to [`(coro-restore data)`](https://gamelisp.rs/std/coro-restore).
+
+	The coroutine's function is looked up by name, so the restored coroutine will run the
+	current definition of that function. It's an error if the function's bytecode has changed
+	since the coroutine was saved.
+	*/
+
+	pub fn coro_restore(data: &Root<Tab>) -> GResult<Root<Coro>> {
+		let coro = corosave::coro_restore(data)?;
+
+		with_vm(|vm| {
+			if let Some(scope) = vm.child_scopes.borrow().last() {
+				scope.push(Slot::Coro(coro.clone()))?;
+			}
+
+			Ok(coro.into_root())
+		})
+	}
+
+	//the internal functions used by the (with-children) macro. see Vm::child_scopes.
+
+	#[doc(hidden)]
+	pub fn open_child_scope() -> Root<Arr> {
+		let scope = glsp::arr();
+		glsp::resume_child_scope(&scope);
+		scope
+	}
+
+	#[doc(hidden)]
+	pub fn suspend_child_scope(scope: &Root<Arr>) {
+		with_vm(|vm| {
+			let mut child_scopes = vm.child_scopes.borrow_mut();
+			if let Some(i) = child_scopes.iter().rposition(|open| Root::ptr_eq(open, scope)) {
+				child_scopes.remove(i);
+			}
+		})
+	}
+
+	#[doc(hidden)]
+	pub fn resume_child_scope(scope: &Root<Arr>) {
+		with_vm(|vm| vm.child_scopes.borrow_mut().push(scope.clone()))
+	}
+
+	//cancels each child which is still newborn or paused, most recent first. the 'cancelled 
+	//error itself is expected, so we only report errors raised by the children's (defer)s.
+	#[doc(hidden)]
+	pub fn close_child_scope(scope: &Root<Arr>) -> GResult<()> {
+		glsp::suspend_child_scope(scope);
+
+		let children = scope.iter_to::<Root<Coro>>().collect::<GResult<Vec<_>>>()?;
+
+		let failures = glsp::arr();
+		for child in children.iter().rev() {
+			match child.state() {
+				CoroState::Newborn | CoroState::Paused => {
+					if let Err(err) = glsp::coro_throw(child, GError::from_val(CANCELLED_SYM)) {
+						let mut defer_error = err.defer_chain();
+						while let Some(error) = defer_error {
+							failures.push(error.val())?;
+							defer_error = error.defer_chain();
+						}
+					}
+				}
+				CoroState::Running => {
+					failures.push(str!("a child coroutine was still running when its \
+					                    (with-children) scope exited"))?;
+				}
+				CoroState::Finished | CoroState::Poisoned => ()
+			}
+		}
+
+		if failures.len() > 0 {
+			failures.push_start(CHILD_ERRORS_SYM)?;
+			Err(GError::from_val(failures))
+		} else {
+			Ok(())
+		}
+	}
+
+	/**
+	Returns a listing of a function's bytecode.
+
+	Equivalent to [`(disassemble f)`](https://gamelisp.rs/std/disassemble).
+	*/
+
+	pub fn disassemble(gfn: &GFn) -> String {
+		print::disassemble_gfn(gfn)
+	}
+
 	/** Equivalent to [`(expand val env-mode)`](https://gamelisp.rs/std/expand) */
 
 	pub fn expand(val: &Val, env_mode: Option<EnvMode>) -> GResult<Val> {
@@ -3669,9 +9392,14 @@ define_stock_syms!(
 
 		("&name", FLAG_NAME_SYM),
 		("&arg-limits", FLAG_ARG_LIMITS_SYM),
+		("&doc", FLAG_DOC_SYM),
+		("&param-names", FLAG_PARAM_NAMES_SYM),
+		("&param-types", FLAG_PARAM_TYPES_SYM),
+		("&return-type", FLAG_RETURN_TYPE_SYM),
 
 		("?", QUESTION_MARK_SYM),
 		(":", COLON_SYM),
+		("->", ARROW_SYM),
 		("_", UNDERSCORE_SYM),
 
 		("at", AT_SYM),
@@ -3680,6 +9408,15 @@ define_stock_syms!(
 
 		("def", DEF_SYM),
 		("with-global", WITH_GLOBAL_SYM),
+		("defn", DEFN_SYM),
+		("defmacro", DEFMACRO_SYM),
+		("defclass", DEFCLASS_SYM),
+		("bind-global!", BIND_GLOBAL_SYM),
+		("bind-macro!", BIND_MACRO_SYM),
+		("%make-class", MAKE_CLASS_SYM),
+		("has-global?", HAS_GLOBALP_SYM),
+		("eval", EVAL_SYM),
+		("eval-multi", EVAL_MULTI_SYM),
 
 		("template-str", TEMPLATE_STR_SYM),
 
@@ -3701,6 +9438,11 @@ define_stock_syms!(
 		("err", ERR_SYM),
 		("brief", BRIEF_SYM),
 		("verbose", VERBOSE_SYM),
+		("chain", CHAIN_SYM),
+		("%error", ERR_REC_SYM),
+		("payload", PAYLOAD_SYM),
+		("source", SOURCE_SYM),
+		("kind", KIND_SYM),
 		("end-of-input", END_OF_INPUT_SYM),
 
 		("else", ELSE_SYM),
@@ -3730,6 +9472,16 @@ define_stock_syms!(
 		("class", CLASS_SYM),
 		("coro", CORO_SYM),
 		("rdata", RDATA_SYM),
+		("rec", REC_SYM),
+		("parr", PARR_SYM),
+		("ptab", PTAB_SYM),
+		("view", VIEW_SYM),
+		("vec2", VEC2_SYM),
+		("vec3", VEC3_SYM),
+		("mat3", MAT3_SYM),
+		("x", X_SYM),
+		("y", Y_SYM),
+		("z", Z_SYM),
 
 		("infinite", INFINITE_SYM),
 		("unknown", UNKNOWN_SYM),
@@ -3755,6 +9507,7 @@ define_stock_syms!(
 		("mixin", MIXIN_SYM),
 		("mixin?", MIXINP_SYM),
 		("name", NAME_SYM),
+		("doc", DOC_SYM),
 		("class-name", CLASS_NAME_SYM),
 		("state-name", STATE_NAME_SYM),
 		("self", SELF_SYM),
@@ -3773,9 +9526,13 @@ define_stock_syms!(
 		("enabled-by-default?", ENABLED_BY_DEFAULTP_SYM),
 		("bindings", BINDINGS_SYM),
 
+		("op-access", OP_ACCESS_SYM),
+		("op-set-access", OP_SET_ACCESS_SYM),
 		("op-clone", OP_CLONE_SYM),
 		("op-deep-clone", OP_DEEP_CLONE_SYM),
 		("op-eq?", OP_EQP_SYM),
+		("op-hash", OP_HASH_SYM),
+		("op-ord", OP_ORD_SYM),
 
 		("ratio", RATIO_SYM),
 		("min-ratio", MIN_RATIO_SYM),
@@ -3783,6 +9540,60 @@ define_stock_syms!(
 		("young-bytes", YOUNG_BYTES_SYM),
 		("old-bytes", OLD_BYTES_SYM),
 		("ghost-bytes", GHOST_BYTES_SYM),
+		("steps", STEPS_SYM),
+		("interned-strs", INTERNED_STRS_SYM),
+		("interned-bytes", INTERNED_BYTES_SYM),
+		("pool-hits", POOL_HITS_SYM),
+		("pool-misses", POOL_MISSES_SYM),
+
+		("fuel-exhausted", FUEL_EXHAUSTED_SYM),
+		("deadline-exceeded", DEADLINE_EXCEEDED_SYM),
+		("alloc-limit-exceeded", ALLOC_LIMIT_EXCEEDED_SYM),
+		("stack-overflow", STACK_OVERFLOW_SYM),
+		("stale-instance", STALE_INSTANCE_SYM),
+		("expired-rdata", EXPIRED_RDATA_SYM),
+		("expired-view", EXPIRED_VIEW_SYM),
+		("cancelled", CANCELLED_SYM),
+		("child-errors", CHILD_ERRORS_SYM),
+		("migrate", MIGRATE_SYM),
+		("type-error", TYPE_ERROR_SYM),
+
+		("trace", TRACE_SYM),
+		("print", PRINT_SYM),
+		("warn", WARN_SYM),
+		("error", ERROR_SYM),
+
+		("type", TYPE_SYM),
+		("file", FILE_SYM),
+		("dir", DIR_SYM),
+		("symlink", SYMLINK_SYM),
+		("other", OTHER_SYM),
+		("lossy", LOSSY_SYM),
+
+		("year", YEAR_SYM),
+		("month", MONTH_SYM),
+		("day", DAY_SYM),
+		("hour", HOUR_SYM),
+		("minute", MINUTE_SYM),
+		("second", SECOND_SYM),
+		("weekday", WEEKDAY_SYM),
+
+		("%lazy", LAZY_SYM),
+		("pending", PENDING_SYM),
+		("forcing", FORCING_SYM),
+		("forced", FORCED_SYM),
+		("thunk", THUNK_SYM),
+		("value", VALUE_SYM),
+
+		("macro?", MACROP_SYM),
+		("library", LIBRARY_SYM),
+		("min-args", MIN_ARGS_SYM),
+		("max-args", MAX_ARGS_SYM),
+		("params", PARAMS_SYM),
+		("param-types", PARAM_TYPES_SYM),
+		("capability", CAPABILITY_SYM),
+		("replacement", REPLACEMENT_SYM),
+		("deprecation-note", DEPRECATION_NOTE_SYM),
 
 	StockTransform:
 
@@ -3796,6 +9607,13 @@ define_stock_syms!(
 		("bitand", BITAND_SYM),
 		("bitor", BITOR_SYM),
 		("bitxor", BITXOR_SYM),
+		("bit-and", BIT_AND_SYM),
+		("bit-or", BIT_OR_SYM),
+		("bit-xor", BIT_XOR_SYM),
+		("bit-not", BIT_NOT_SYM),
+		("shl", SHL_SYM),
+		("shr", SHR_SYM),
+		("sar", SAR_SYM),
 
 		("sign", SIGN_SYM),
 		("min", MIN_SYM),
@@ -3821,6 +9639,7 @@ define_stock_syms!(
 		("rfn?", RFNP_SYM),
 		("coro?", COROP_SYM),
 		("rdata?", RDATAP_SYM),
+		("rec?", RECP_SYM),
 		("callable?", CALLABLEP_SYM),
 		("expander?", EXPANDERP_SYM),
 
@@ -3852,6 +9671,9 @@ define_stock_syms!(
 		("call-meth-opt", CALL_METH_OPT_SYM),
 		("call-base-raw", CALL_BASE_RAW_SYM),
 
+		("apply", APPLY_SYM),
+		("call-with", CALL_WITH_SYM),
+
 		("global", GLOBAL_SYM),
 		("global=", SET_GLOBAL_SYM)
 
@@ -1,6 +1,7 @@
 use std::error::{Error};
 use std::fmt::{self, Debug, Display, Formatter};
-use super::engine::{glsp, Guard, Span, with_vm};
+use super::engine::{glsp, Guard, Span, Sym, with_vm};
+use super::suggest::{describe_suggestions};
 use super::val::{Val};
 use super::vm::{Frame};
 use super::wrap::{ToVal};
@@ -20,6 +21,11 @@ of `GError` which is not caught by [`try`](https://gamelisp.rs/std/try) or
 [`try-verbose`](https://gamelisp.rs/std/try-verbose). This means that in order to trigger a 
 [`macro_no_op!`](macro.macro_no_op.html), the enclosing function must return `GResult<T>`.
 
+Similarly, the [`exit`](https://gamelisp.rs/std/exit) function generates a special `GError` which
+is not caught by `try`. It unwinds the stack like any other error, running `defer` forms along 
+the way, until it reaches Rust code. The host can then detect it using the 
+[`exit_code` method](#method.exit_code), and decide what to do with the exit code.
+
 The [`with_source` method](#method.with_source) can be used to chain together two `GErrors`, 
 or to chain an arbitrary [`Error`](https://doc.rust-lang.org/std/error/trait.Error.html) type
 onto a `GError`.
@@ -38,11 +44,68 @@ pub(crate) enum Payload {
 		stack_trace: Option<String>,
 
 		defer_chain: Option<GError>,
-		source: Option<Box<dyn Error + 'static>>
+		source: Option<Box<dyn Error + 'static>>,
+		exit_code: Option<i32>,
+		arg_context: Option<Box<ArgContext>>,
+		message: Option<Box<(MessageId, MessageArgs)>>
 	},
 	MacroNoOp
 }
 
+//attached to an error which was raised while checking or converting a function's arguments. 
+//each layer of the call machinery fills in the details that it knows about: the argument 
+//conversions know the argument's index, and the caller knows the callee's name, its parameter 
+//names, and the argument's value. once the caller has finished the context, the error's payload 
+//is replaced with a description like "(set-pos!): argument 2 (`y`): expected a flo, received a 
+//str \"10\"". a finished context is never modified again, so an error which passes through 
+//several nested calls is only described by the innermost one.
+pub(crate) struct ArgContext {
+	message: String,
+	arg_index: Option<usize>,
+	finished: bool
+}
+
+impl ArgContext {
+	fn describe(
+		&self,
+		callee: Option<Sym>,
+		param_name: Option<Sym>,
+		received: Option<&Val>
+	) -> MessageArgs {
+		let mut args = MessageArgs::new();
+		if let Some(callee) = callee {
+			args = args.with("callee", callee);
+		}
+
+		if let Some(arg_index) = self.arg_index {
+			args = args.with("arg-index", arg_index);
+			if let Some(param_name) = param_name {
+				args = args.with("param", param_name);
+			}
+		}
+
+		args = args.with("message", &self.message);
+
+		//the FromVal conversions only describe the type of the value they received, so we
+		//append a truncated representation of the value itself
+		if let Some(received) = received {
+			if self.message.ends_with(received.a_type_name()) {
+				const MAX_CHARS: usize = 40;
+
+				let repr = format!("{:?}", received);
+				if repr.chars().count() > MAX_CHARS {
+					let truncated: String = repr.chars().take(MAX_CHARS - 3).collect();
+					args = args.with("received-value", format!("{}...", truncated));
+				} else {
+					args = args.with("received-value", repr);
+				}
+			}
+		}
+
+		args
+	}
+}
+
 impl GError {
 	pub fn new() -> GError {
 		GError::from_str("explicit call to bail!, error!, or GError::new")
@@ -67,11 +130,47 @@ impl GError {
 				file_location,
 				stack_trace,
 				defer_chain: None,
-				source: None
+				source: None,
+				exit_code: None,
+				arg_context: None,
+				message: None
 			})
 		}
 	}
 
+	/**
+	Constructs an error which represents a request to exit the program, with the given exit code.
+
+	This is the error generated by [`(exit)`](https://gamelisp.rs/std/exit). It isn't caught by 
+	[`try`](https://gamelisp.rs/std/try) or [`try-verbose`](https://gamelisp.rs/std/try-verbose).
+	*/
+	pub fn exit(code: i32) -> GError {
+		let mut error = GError::from_str(&format!("(exit {}) was called", code));
+		match &mut *error.payload {
+			Payload::Error { exit_code, .. } => *exit_code = Some(code),
+			Payload::MacroNoOp => unreachable!()
+		}
+
+		error
+	}
+
+	/**
+	Constructs an error from one of the engine's [structured messages](enum.MessageId.html).
+
+	The error's payload is a string, rendered by the current
+	[message formatter](fn.set_message_formatter.html). The message's id and arguments can be
+	retrieved using [`message_id`](#method.message_id) and
+	[`message_args`](#method.message_args).
+	*/
+	pub fn from_message(id: MessageId, args: MessageArgs) -> GError {
+		let mut error = GError::from_str(&glsp::format_message(id, &args));
+		if let Payload::Error { ref mut message, .. } = *error.payload {
+			*message = Some(Box::new((id, args)));
+		}
+
+		error
+	}
+
 	pub fn macro_no_op() -> GError {
 		with_vm(|vm| {
 			if vm.in_expander() {
@@ -95,6 +194,17 @@ impl GError {
 		}
 	}
 
+	/**
+	If this error was generated by [`GError::exit`](#method.exit), returns its exit code.
+	Otherwise, returns `None`.
+	*/
+	pub fn exit_code(&self) -> Option<i32> {
+		match &*self.payload {
+			Payload::MacroNoOp => None,
+			Payload::Error { exit_code, .. } => *exit_code
+		}
+	}
+
 	/**
 	Returns the error's payload. Panics if this error is a macro-no-op.
 	*/
@@ -105,6 +215,35 @@ impl GError {
 		}
 	}
 
+	/**
+	If this error was constructed from a [structured message](enum.MessageId.html), returns 
+	the message's id. Otherwise, returns `None`.
+
+	This makes it possible to display a rich description of an error, rather than parsing its
+	payload string.
+	*/
+	pub fn message_id(&self) -> Option<MessageId> {
+		match &*self.payload {
+			Payload::MacroNoOp => None,
+			Payload::Error { message, .. } => message.as_ref().map(|message| message.0)
+		}
+	}
+
+	/**
+	If this error was constructed from a [structured message](enum.MessageId.html), returns 
+	the message's arguments. Otherwise, returns `None`.
+
+	When the error was raised while converting a function's arguments, the arguments also
+	include `callee`, `arg-index` and `param` where they're known, as described for
+	[`MessageId::ArgContext`](enum.MessageId.html#variant.ArgContext).
+	*/
+	pub fn message_args(&self) -> Option<&MessageArgs> {
+		match &*self.payload {
+			Payload::MacroNoOp => None,
+			Payload::Error { message, .. } => message.as_ref().map(|message| &message.1)
+		}
+	}
+
 	/**
 	Returns the error's saved stack trace.
 
@@ -119,14 +258,77 @@ impl GError {
 		}
 	}
 
-	#[allow(dead_code)]
-	pub(crate) fn defer_chain(&self) -> Option<&GError> {
+	/**
+	Returns the error which was generated by a [`defer` form](https://gamelisp.rs/std/defer)
+	while this error was unwinding, if any.
+
+	When several `defer` forms fail, each error is chained onto the previous one, in the order
+	that the `defer` forms were run.
+	*/
+	pub fn defer_chain(&self) -> Option<&GError> {
 		match &*self.payload {
 			Payload::MacroNoOp => panic!(),
 			Payload::Error { defer_chain, .. } => defer_chain.as_ref()
 		}
 	}
 
+	//marks this error as having been raised while checking or converting the argument at the 
+	//given index (or the argument count, when `arg_index` is None). does nothing if the error 
+	//already has an argument context, or if its payload isn't a string.
+	pub(crate) fn with_arg_context(mut self, arg_index: Option<usize>) -> GError {
+		if let Payload::Error { ref val, ref mut arg_context, .. } = *self.payload {
+			if arg_context.is_none() {
+				if let Val::Str(ref st) = *val {
+					*arg_context = Some(Box::new(ArgContext {
+						message: st.to_string(),
+						arg_index,
+						finished: false
+					}));
+				}
+			}
+		}
+
+		self
+	}
+
+	//if this error has an unfinished argument context, uses it to regenerate the error's 
+	//payload. `param_names` and `arg` are only called when the context refers to an argument.
+	pub(crate) fn finish_arg_context(
+		&mut self,
+		callee: Option<Sym>,
+		param_name: impl FnOnce(usize) -> Option<Sym>,
+		arg: impl FnOnce(usize) -> Option<Val>
+	) {
+		if let Payload::Error { 
+			ref mut val, 
+			arg_context: Some(ref mut context), 
+			ref mut message, 
+			.. 
+		} = *self.payload {
+			if !context.finished {
+				context.finished = true;
+
+				let (param_name, received) = match context.arg_index {
+					Some(arg_index) => (param_name(arg_index), arg(arg_index)),
+					None => (None, None)
+				};
+
+				let context_args = context.describe(callee, param_name, received.as_ref());
+				let description = glsp::format_message(MessageId::ArgContext, &context_args);
+				*val = Val::Str(glsp::str_from_rust_str(&description));
+
+				//a structured message gains the context's details, other than the message text
+				if let Some(ref mut message) = *message {
+					for (name, value) in context_args.iter() {
+						if name != "message" {
+							message.1.insert(name, value.to_string());
+						}
+					}
+				}
+			}
+		}
+	}
+
 	pub(crate) fn chain_defer_error(&mut self, defer_error: GError) {
 		if self.is_macro_no_op() {
 			*self = defer_error;
@@ -212,13 +414,24 @@ impl Display for GError {
 	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
 		match &*self.payload {
 			Payload::MacroNoOp => panic!(),
-			Payload::Error { val, file_location, stack_trace, source, defer_chain } => {
+			Payload::Error { val, file_location, stack_trace, source, defer_chain, .. } => {
 				match (file_location, stack_trace) {
-					(&None, &None) => {
-						write!(f, "{:?}", val)
-					}
-					(&Some(ref file_location), &None) => {
-						write!(f, "{}: {:?}", file_location, val)
+					(&None, &None) | (&Some(_), &None) => {
+						if let Some(ref file_location) = *file_location {
+							write!(f, "{}: ", file_location)?;
+						}
+						write!(f, "{:?}", val)?;
+
+						if let Some(ref source) = *source {
+							write_sources(f, source.as_ref())?;
+						}
+
+						if let Some(ref defer_chain) = defer_chain {
+							write!(f, " (while this error was unwinding, \
+							         a (defer) form also failed: {})", defer_chain)?;
+						}
+
+						Ok(())
 					}
 					(_, &Some(ref stack_trace)) => {
 						write!(f, "stack trace:\n")?;
@@ -229,31 +442,9 @@ impl Display for GError {
 						//we print error values using {} rather than {:?}. this is because most
 						//error messages are strings, and escaping curly braces can be confusing.
 						//"clause must end with }" becomes "clause must end with }}".
+						write!(f, "\nerror: {}", val)?;
 						if let Some(ref source) = *source {
-							write!(f, "\nerrors:")?;
-
-							fn write_source(
-								f: &mut Formatter, 
-								source: &(dyn Error + 'static)
-							) -> fmt::Result {
-								//if the source is a GError, we can't stringify it using {},
-								//because that would print its full stack trace again
-								match source.downcast_ref::<GError>() {
-									Some(error) => write!(f, "\n    {}", error.val())?,
-									None => write!(f, "\n    {}", source)?
-								}
-
-								if let Some(source2) = source.source() {
-									write_source(f, source2)
-								} else {
-									Ok(())
-								}
-							}
-
-							write!(f, "\n    {}", &val)?;
-							write_source(f, source.as_ref())?;
-						} else {
-							write!(f, "\nerror: {}", &val)?;
+							write_sources(f, source.as_ref())?;
 						}
 
 						if let Some(ref defer_chain) = defer_chain {
@@ -270,6 +461,447 @@ impl Display for GError {
 	}
 }
 
+//prints a "caused by:" line for each error in a source chain
+fn write_sources(f: &mut Formatter, source: &(dyn Error + 'static)) -> fmt::Result {
+	let mut next = Some(source);
+	while let Some(source) = next {
+		//if the source is a GError, we can't stringify it using {}, because that would print
+		//its full stack trace again
+		match source.downcast_ref::<GError>() {
+			Some(error) => write!(f, "\ncaused by: {}", error.val())?,
+			None => write!(f, "\ncaused by: {}", source)?
+		}
+
+		next = source.source();
+	}
+
+	Ok(())
+}
+
+/**
+Identifies one of the structured error and warning messages generated by the engine.
+
+The engine's most common player-facing messages are built from a `MessageId` and some
+[`MessageArgs`](struct.MessageArgs.html), rather than from a string. By default, they're
+rendered in English, but the host can substitute its own text using
+[`glsp::set_message_formatter`](fn.set_message_formatter.html). An error which was built from
+a message exposes it via [`GError::message_id`](struct.GError.html#method.message_id) and
+[`GError::message_args`](struct.GError.html#method.message_args).
+
+The documentation for each variant lists the arguments which it receives. Arguments marked as
+optional may be absent. New variants may be added in the future, so a custom formatter should
+fall back to [`default_text`](#method.default_text) for any id that it doesn't recognize.
+*/
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[non_exhaustive]
+pub enum MessageId {
+	/**
+	A value had the wrong type.
+
+	`expected` and `received` describe the types, e.g. `"an int"` and `"a str"`.
+	*/
+	TypeMismatch,
+
+	/**
+	An int was outside the range of a Rust integer type.
+
+	`expected` describes the range, e.g. `"a u8"`. `value` is the int which was received.
+	*/
+	IntOutOfRange,
+
+	/**
+	An `rfn` received the wrong number of arguments.
+
+	`received` is the argument count, `min-args` is the minimum argument count, and `max-args`
+	(optional) is the maximum argument count. When `positional` is present, the `rfn` also
+	accepts keyword arguments, and the counts only include positional arguments.
+	*/
+	ArgCount,
+
+	/**
+	A GameLisp function received too few arguments.
+
+	`received` is the argument count and `min-args` is the minimum argument count. `missing`
+	(optional) lists the names of the missing parameters, e.g. ``"`x`, `y`"``.
+	*/
+	TooFewArgs,
+
+	/**
+	A GameLisp function received too many arguments.
+
+	`received` is the argument count and `max-args` is the maximum argument count.
+	*/
+	TooManyArgs,
+
+	/**
+	Describes which function call and which argument caused another message.
+
+	`message` is the other message's rendered text. `callee` (optional) is the function's
+	name. `arg-index` (optional) is the index of the argument, starting from 0, and `param`
+	(optional) is the name of its parameter. `received-value` (optional) is a printed
+	representation of the argument, which may be truncated.
+
+	This id is only passed to the message formatter. `GError::message_id` returns the id of the
+	underlying message instead, and `GError::message_args` includes the `callee`, `arg-index`
+	and `param` arguments.
+	*/
+	ArgContext,
+
+	/**
+	Code attempted to access or assign to an unbound global variable.
+
+	`name` is the global's name. `hint` (optional) suggests why the global might be unbound,
+	e.g. `" (there is no module named 'ui')"`. `suggestions` (optional) lists the bound names
+	which are most similar, e.g. ``"`update-player` or `update-players`"``.
+	*/
+	UnboundSymbol,
+
+	/**
+	A Rust API attempted to access an unbound global variable.
+
+	`name` is the global's name.
+	*/
+	UnboundGlobal,
+
+	/**
+	Code attempted to mutate a [frozen](fn.freeze_global.html) global variable.
+
+	`name` is the global's name.
+	*/
+	FrozenGlobal,
+
+	/**
+	An arr or str was indexed out of bounds.
+
+	`type` is the name of the collection's type, `len` is its length, and `index` is the index.
+	*/
+	OutOfBounds,
+
+	/**
+	Code attempted to access an obj's field, but the field doesn't exist.
+
+	`name` is the field's name. `suggestions` (optional) lists the obj's most similar fields,
+	as for `UnboundSymbol`.
+	*/
+	NonexistentField,
+
+	/**
+	Code attempted to call a method, but the method doesn't exist.
+
+	`name` is the method's name. `alternatives` (optional) describes the methods which do exist.
+	`suggestions` (optional) lists the most similar methods, as for `UnboundSymbol`.
+	*/
+	NonexistentMeth,
+
+	/**
+	A warning that a [deprecated](fn.bind_deprecated.html) global was called.
+
+	`name` is the deprecated name and `replacement` is the name which replaced it. `note`
+	(optional) is the note passed to `glsp::bind_deprecated`.
+	*/
+	Deprecated,
+
+	/**
+	An `rfn` [panicked](fn.is_poisoned.html).
+
+	`rfn` (optional) is the rfn's name. `payload` (optional) is the panic's message, when it was
+	a string.
+	*/
+	RustPanic
+}
+
+impl MessageId {
+	/**
+	Renders a message in English.
+
+	This is the default message formatter. It produces the same text which the engine generated
+	before structured messages were introduced.
+	*/
+	pub fn default_text(self, args: &MessageArgs) -> String {
+		use MessageId::*;
+
+		let arg = |name: &str| args.get(name).unwrap_or("");
+		let count = |name: &str| arg(name).parse::<usize>().unwrap_or(0);
+		let plural = |name: &str| if count(name) == 1 { "" } else { "s" };
+		let did_you_mean = || match args.get("suggestions") {
+			Some(suggestions) => format!(" (did you mean {}?)", suggestions),
+			None => String::new()
+		};
+
+		match self {
+			TypeMismatch => format!("expected {}, received {}", arg("expected"), arg("received")),
+			IntOutOfRange => {
+				format!("expected {}, received an int with value {}", arg("expected"), arg("value"))
+			}
+			ArgCount => {
+				let expected = match args.get("max-args") {
+					Some(max_args) if max_args == arg("min-args") => {
+						format!("exactly {}", max_args)
+					}
+					Some(max_args) => format!("{} to {}", arg("min-args"), max_args),
+					None => format!("at least {}", arg("min-args"))
+				};
+
+				let description = if count("received") < count("min-args") {
+					"too few"
+				} else {
+					"too many"
+				};
+
+				if args.get("positional").is_some() {
+					format!("{} arguments: received {} positional arguments, expected {}",
+					        description, arg("received"), expected)
+				} else {
+					format!("{} arguments: received {}, expected {}",
+					        description, arg("received"), expected)
+				}
+			}
+			TooFewArgs => {
+				let mut text = format!("received {} argument{}, but expected at least {}",
+				                       arg("received"), plural("received"), arg("min-args"));
+				if let Some(missing) = args.get("missing") {
+					text.push_str(&format!(" (missing {})", missing));
+				}
+
+				text
+			}
+			TooManyArgs => {
+				format!("received {} argument{}, but expected no more than {}",
+				        arg("received"), plural("received"), arg("max-args"))
+			}
+			ArgContext => {
+				let mut text = String::new();
+				if let Some(callee) = args.get("callee") {
+					text.push_str(&format!("({}): ", callee));
+				}
+
+				if args.get("arg-index").is_some() {
+					text.push_str(&format!("argument {}", count("arg-index") + 1));
+					if let Some(param) = args.get("param") {
+						text.push_str(&format!(" (`{}`)", param));
+					}
+					text.push_str(": ");
+				}
+
+				text.push_str(arg("message"));
+				if let Some(received_value) = args.get("received-value") {
+					text.push_str(&format!(" {}", received_value));
+				}
+
+				text
+			}
+			UnboundSymbol => {
+				format!("unbound symbol '{}'{}{}", arg("name"), arg("hint"), did_you_mean())
+			}
+			UnboundGlobal => format!("symbol {} is not bound to a global", arg("name")),
+			FrozenGlobal => format!("attempted to mutate frozen global {}", arg("name")),
+			OutOfBounds => {
+				format!("out-of-bounds {} access: len is {}, index is {}",
+				        arg("type"), arg("len"), arg("index"))
+			}
+			NonexistentField => {
+				format!("attempted to access nonexistent field '{}'{}", arg("name"), did_you_mean())
+			}
+			NonexistentMeth => {
+				let mut text = format!("attempted to call nonexistent method '{}'", arg("name"));
+				if let Some(alternatives) = args.get("alternatives") {
+					text.push_str(&format!(": {}", alternatives));
+				}
+
+				text.push_str(&did_you_mean());
+				text
+			}
+			Deprecated => {
+				let mut text = format!("{} is deprecated, use {} instead",
+				                       arg("name"), arg("replacement"));
+				if let Some(note) = args.get("note") {
+					text.push_str(&format!(": {}", note));
+				}
+
+				text
+			}
+			RustPanic => {
+				let mut text = match args.get("rfn") {
+					Some(rfn) => format!("rfn ({}) panicked", rfn),
+					None => format!("anonymous rfn panicked")
+				};
+
+				if let Some(payload) = args.get("payload") {
+					text.push_str(&format!(", '{}'", payload));
+				}
+
+				text
+			}
+		}
+	}
+
+	/**
+	Returns the id's name in kebab-case, like `"type-mismatch"` or `"rust-panic"`.
+
+	This is the `kind` of an error captured by [`try-chain`](https://gamelisp.rs/std/try-chain).
+	*/
+	pub fn name(self) -> &'static str {
+		use MessageId::*;
+
+		match self {
+			TypeMismatch => "type-mismatch",
+			IntOutOfRange => "int-out-of-range",
+			ArgCount => "arg-count",
+			TooFewArgs => "too-few-args",
+			TooManyArgs => "too-many-args",
+			ArgContext => "arg-context",
+			UnboundSymbol => "unbound-symbol",
+			UnboundGlobal => "unbound-global",
+			FrozenGlobal => "frozen-global",
+			OutOfBounds => "out-of-bounds",
+			NonexistentField => "nonexistent-field",
+			NonexistentMeth => "nonexistent-meth",
+			Deprecated => "deprecated",
+			RustPanic => "rust-panic"
+		}
+	}
+}
+
+/**
+The named arguments to a [structured message](enum.MessageId.html).
+
+Each argument's value is stored as a string, so that it can be substituted into a translated
+template.
+
+	glsp::set_message_formatter(Box::new(|id, args| {
+		match id {
+			MessageId::TypeMismatch => args.substitute("{expected} erwartet, {received} erhalten"),
+			id => id.default_text(args)
+		}
+	}));
+*/
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MessageArgs {
+	args: Vec<(&'static str, String)>
+}
+
+impl MessageArgs {
+	///Constructs an empty argument list.
+	pub fn new() -> MessageArgs {
+		MessageArgs { args: Vec::new() }
+	}
+
+	///Adds an argument, replacing any existing argument with the same name.
+	pub fn with<T: Display>(mut self, name: &'static str, value: T) -> MessageArgs {
+		self.insert(name, value.to_string());
+		self
+	}
+
+	fn insert(&mut self, name: &'static str, value: String) {
+		match self.args.iter_mut().find(|(arg_name, _)| *arg_name == name) {
+			Some(arg) => arg.1 = value,
+			None => self.args.push((name, value))
+		}
+	}
+
+	///Returns the value of the named argument, if it's present.
+	pub fn get(&self, name: &str) -> Option<&str> {
+		self.args.iter().find(|(arg_name, _)| *arg_name == name).map(|(_, value)| &value[..])
+	}
+
+	///Iterates over each argument's name and value, in the order they were added.
+	pub fn iter(&self) -> impl Iterator<Item = (&'static str, &str)> {
+		self.args.iter().map(|(name, value)| (*name, &value[..]))
+	}
+
+	/**
+	Replaces each `{name}` in `template` with the value of the named argument.
+
+	Absent arguments are replaced with an empty string. `{{` and `}}` are replaced with `{`
+	and `}`.
+	*/
+	pub fn substitute(&self, template: &str) -> String {
+		let mut text = String::with_capacity(template.len());
+		let mut rest = template;
+		while let Some(i) = rest.find(['{', '}']) {
+			text.push_str(&rest[..i]);
+			rest = &rest[i..];
+
+			if rest.starts_with("{{") || rest.starts_with("}}") {
+				text.push_str(&rest[..1]);
+				rest = &rest[2..];
+			} else if let (true, Some(end)) = (rest.starts_with('{'), rest.find('}')) {
+				text.push_str(self.get(&rest[1..end]).unwrap_or(""));
+				rest = &rest[end + 1..];
+			} else {
+				text.push_str(&rest[..1]);
+				rest = &rest[1..];
+			}
+		}
+
+		text.push_str(rest);
+		text
+	}
+}
+
+//constructors for the messages which are generated in more than one place
+
+#[cold]
+pub(crate) fn unbound_symbol_error(sym: Sym) -> GError {
+	let mut args = MessageArgs::new().with("name", sym);
+	let hint = glsp::unbound_global_hint(sym);
+	if !hint.is_empty() {
+		args = args.with("hint", hint);
+	}
+
+	let suggestions = glsp::similar_globals(&sym.name());
+	if !suggestions.is_empty() {
+		args = args.with("suggestions", describe_suggestions(&suggestions));
+	}
+
+	GError::from_message(MessageId::UnboundSymbol, args)
+}
+
+#[cold]
+pub(crate) fn unbound_global_error<N: Display>(name: N) -> GError {
+	GError::from_message(MessageId::UnboundGlobal, MessageArgs::new().with("name", name))
+}
+
+#[cold]
+pub(crate) fn frozen_global_error<N: Display>(name: N) -> GError {
+	GError::from_message(MessageId::FrozenGlobal, MessageArgs::new().with("name", name))
+}
+
+#[cold]
+pub(crate) fn nonexistent_field_error(sym: Sym, suggestions: Vec<Sym>) -> GError {
+	let mut args = MessageArgs::new().with("name", sym);
+	if !suggestions.is_empty() {
+		args = args.with("suggestions", describe_suggestions(&suggestions));
+	}
+
+	GError::from_message(MessageId::NonexistentField, args)
+}
+
+#[cold]
+pub(crate) fn nonexistent_meth_error(
+	sym: Sym,
+	alternatives: Option<String>,
+	suggestions: Vec<Sym>
+) -> GError {
+	let mut args = MessageArgs::new().with("name", sym);
+	if let Some(alternatives) = alternatives {
+		args = args.with("alternatives", alternatives);
+	}
+	if !suggestions.is_empty() {
+		args = args.with("suggestions", describe_suggestions(&suggestions));
+	}
+
+	GError::from_message(MessageId::NonexistentMeth, args)
+}
+
+#[cold]
+pub(crate) fn out_of_bounds_error<I: Display>(type_name: &str, len: usize, index: I) -> GError {
+	let args = MessageArgs::new().with("type", type_name).with("len", len).with("index", index);
+	GError::from_message(MessageId::OutOfBounds, args)
+}
+
 /**
 Constructs a [`GError`](struct.GError.html) by formatting a string.
 
@@ -25,13 +25,16 @@ use super::compile::{Action};
 //-------------------------------------------------------------------------------------------------
 
 //successively expand and evaluate a number of toplevel forms, all in the same toplevel scope.
-//the `to_record` flag determines whether or not any executed Bytecodes are recorded: this is
-//true for (load) calls, but false for (eval) calls.
+//`filename` is Some for (load) calls, and None for (eval) calls. it determines whether or not any
+//executed Bytecodes are recorded, and what happens to toplevel (defer) forms: an (eval) runs them
+//after its last form, but a file which loads successfully hands them over to the engine, which 
+//runs them when the file is loaded again or when the Runtime is dropped.
 pub(crate) fn eval(
 	forms: &[Val],
 	env_mode: Option<EnvMode>,
-	to_record: bool
+	filename: Option<&str>
 ) -> GResult<Val> {
+	let to_record = filename.is_some();
 
 	let mut context = Context::new(env_mode);
 
@@ -51,6 +54,22 @@ pub(crate) fn eval(
 			}
 		};
 
+		//every other form is passed through the expansion passes before it's handled
+		let expanded = match expanded {
+			Val::Arr(ref arr) if arr.len() > 0 && 
+			                     matches!(arr.get(0)?, Val::Sym(SPLICE_SYM | LET_MACRO_SYM)) => {
+				expanded
+			}
+			expanded => match glsp::apply_expansion_passes(expanded) {
+				Ok(passed) => passed,
+				Err(error) => {
+					let mut defer_result: GResult<Val> = Err(error);
+					context.pop_defers(&mut defer_result);
+					return defer_result
+				}
+			}
+		};
+
 		if expanded.is_arr() && expanded.clone().unwrap_arr().len() > 0 {
 			let arr = expanded.clone().unwrap_arr();
 			match arr.get(0)? {
@@ -82,7 +101,18 @@ pub(crate) fn eval(
 		}
 	}
 
-	context.pop_defers(&mut result);
+	match filename {
+		Some(filename) => {
+			#[cfg(feature = "compiler")]
+			for bytecode in &context.defers {
+				glsp::record_action(Action::Defer(bytecode.clone()));
+			}
+
+			glsp::push_file_defers(filename, context.defers.drain(..).collect());
+		}
+		None => context.pop_defers(&mut result)
+	}
+
 	result
 }
 
@@ -253,6 +283,7 @@ impl Context {
 		//make a gfn by evaluating (fn params ..body), then bind that gfn to `name` as a macro.
 		let span = form.span();
 		ensure_at!(span, form.len() >= 3, "let-macro form has too few arguments");
+		glsp::ensure_let_macro_permitted()?;
 
 		let name = match form.get(1)? {
 			Val::Sym(name) => name,
@@ -359,21 +390,22 @@ impl Context {
 	}
 
 	fn pop_defers(&mut self, result: &mut GResult<Val>) {
-		//evaluate each toplevel (defer) form in reverse order. if one of them fails, chain its
-		//error onto the GResult and then move on to the next (defer) form
-		for bytecode in self.defers.drain(..).rev() {
-			#[cfg(feature = "compiler")]
-			glsp::record_action(Action::Execute(bytecode.clone()));
-
-			let defer_result = with_vm(|vm| {
-				vm.exec_bytecode(&bytecode)
-			});
+		run_defers(self.defers.drain(..).collect(), result)
+	}
+}
 
-			if let Err(error) = defer_result {
-				match *result {
-					Ok(_) => *result = Err(error),
-					Err(ref mut prev) => prev.chain_defer_error(error)
-				}
+//evaluate each toplevel (defer) form in reverse order. if one of them fails, chain its error onto
+//the GResult and then move on to the next (defer) form
+pub(crate) fn run_defers(defers: Vec<Root<Bytecode>>, result: &mut GResult<Val>) {
+	for bytecode in defers.into_iter().rev() {
+		let defer_result = with_vm(|vm| {
+			vm.exec_bytecode(&bytecode)
+		});
+
+		if let Err(error) = defer_result {
+			match *result {
+				Ok(_) => *result = Err(error),
+				Err(ref mut prev) => prev.chain_defer_error(error)
 			}
 		}
 	}
@@ -416,6 +448,10 @@ fn fully_expand_form(
 	context: &mut Context
 ) -> GResult<Option<Val>> {
 
+	//each level of nesting consumes rust stack, so it's counted against glsp::max_call_depth
+	with_vm(|vm| vm.enter_depth(Some(form)))?;
+	let _depth_guard = Guard::new(|| with_vm(|vm| vm.exit_depth()));
+
 	let mut form = form.clone();
 	let mut form_mutated = false;
 
@@ -644,8 +680,22 @@ fn fully_expand_form(
 			}
 			Val::Sym(FN_SYM) => {
 				//for a (fn) form, we skip expanding the parameter list, and we treat its body 
-				//as an implicit "do"
-				let params_i = match arr.iter().position(|v| v.is_arr()) {
+				//as an implicit "do". the &param-names and &param-types flags are followed by an
+				//arr, which isn't the parameter list.
+				let mut params_i = None;
+				let mut i = 1;
+				while i < arr.len() {
+					match arr.get::<Val>(i)? {
+						Val::Sym(FLAG_PARAM_NAMES_SYM) | Val::Sym(FLAG_PARAM_TYPES_SYM) => i += 2,
+						Val::Arr(_) => {
+							params_i = Some(i);
+							break
+						}
+						_ => i += 1
+					}
+				}
+
+				let params_i = match params_i {
 					Some(i) => i,
 					None => bail_at!(arr.span(), "invalid (fn) form passed to expander")
 				};
@@ -756,9 +806,14 @@ fn maybe_call_expander(
 						return invoke_macro_expander(&arr, false, &expander, context)
 					}
 
-					//global macro bindings
-					if glsp::has_macro(sym).unwrap() {
-						let expander = glsp::get_macro(sym).unwrap();
+					//global macro bindings, resolved relative to the current module
+					let global_name = glsp::resolve_global(sym)?;
+
+					#[cfg(feature = "compiler")]
+					glsp::note_macro_lookup(global_name);
+
+					if glsp::has_macro(global_name).unwrap() {
+						let expander = glsp::get_macro(global_name).unwrap();
 						return invoke_macro_expander(&arr, false, &expander, context)
 					}
 
@@ -1,10 +1,15 @@
+use fnv::{FnvHashMap, FnvHashSet};
 use super::code::{Bytecode, Coro, GFn, Lambda, Stay};
-use super::collections::{Arr, DequeOps, Str, Tab};
+use super::collections::{Arr, DequeOps, Str, Tab, Weakness};
 use super::class::{Class, Obj};
 use super::engine::{ACTIVE_ENGINE_ID, glsp, GStore, RData, RFn, Span, Sym, with_heap};
 use super::error::{GResult};
 use super::iter::{GIter, GIterState};
-use super::val::{Hashable, Val};
+use super::persist::{PArr, PTab};
+use super::record::{Rec};
+use super::view::{View};
+use super::vecmath::{Mat3, Vec2, Vec3};
+use super::val::{Flo, Hashable, Val};
 use super::wrap::{ToVal};
 use std::{f32};
 use std::borrow::{Borrow};
@@ -15,6 +20,7 @@ use std::hash::{Hash, Hasher};
 use std::mem::{size_of};
 use std::ops::{Deref};
 use std::process::{abort};
+use std::rc::{Rc};
 
 //the garbage collector currently uses a hybrid incremental and generational algorithm. see
 //notes/gc.md for the details.
@@ -23,8 +29,6 @@ use std::process::{abort};
 // safe Gc<T> implementation
 //----------------------------------------------------------------------------
 
-#[cfg(not(feature = "unsafe-internals"))]
-use std::rc::Rc;
 
 #[doc(hidden)]
 #[cfg(not(feature = "unsafe-internals"))]
@@ -131,8 +135,8 @@ impl<T: Allocate> Deref for Gc<T> {
 
 impl<T: Allocate> Gc<T> {
 	pub(crate) fn from_root(root: &Root<T>) -> Gc<T> {
-		let engine_id = ACTIVE_ENGINE_ID.with(|id| id.get().unwrap());
-		if engine_id != root.header().engine_id() {
+		let engine_id = ACTIVE_ENGINE_ID.with(|id| id.get());
+		if engine_id != Some(root.header().engine_id()) {
 			eprintln!("attempted to move a Root to another Runtime - aborting process");
 			abort()
 		}
@@ -230,6 +234,13 @@ erased_types!(
 	Stay,
 	Coro,
 	RData,
+	Rec,
+	PArr,
+	PTab,
+	View,
+	Vec2,
+	Vec3,
+	Mat3,
 	Bytecode,
 	Lambda
 );
@@ -252,8 +263,8 @@ impl<T: Allocate> Root<T> {
 	fn new(gc: Gc<T>) -> Root<T> {
 		let header = gc.header();
 
-		let engine_id = ACTIVE_ENGINE_ID.with(|id| id.get().unwrap());
-		if engine_id != header.engine_id() {
+		let engine_id = ACTIVE_ENGINE_ID.with(|id| id.get());
+		if engine_id != Some(header.engine_id()) {
 			eprintln!("attempted to create a Root for an inactive Runtime - aborting process");
 			abort()
 		}
@@ -299,6 +310,16 @@ impl<T: Allocate> Root<T> {
 	pub fn ptr_eq(root0: &Root<T>, root1: &Root<T>) -> bool {
 		Gc::ptr_eq(&root0.gc, &root1.gc)
 	}
+
+	/**
+	Creates a [`WeakRoot`](struct.WeakRoot.html) which points to the same object.
+	*/
+	pub fn downgrade(&self) -> WeakRoot<T> {
+		WeakRoot {
+			gc: self.gc.clone(),
+			alive: with_heap(|heap| heap.weak_flag(&self.gc))
+		}
+	}
 }
 
 impl<T: Allocate> Borrow<T> for Root<T> {
@@ -324,8 +345,8 @@ impl<T: Allocate> Clone for Root<T> {
 		let header = self.gc.header();
 		let root_index = header.root_index();
 
-		let engine_id = ACTIVE_ENGINE_ID.with(|id| id.get().unwrap());
-		if engine_id != header.engine_id() {
+		let engine_id = ACTIVE_ENGINE_ID.with(|id| id.get());
+		if engine_id != Some(header.engine_id()) {
 			eprintln!("attempted to clone a Root for an inactive Runtime - aborting process");
 			abort()
 		}
@@ -342,6 +363,77 @@ impl<T: Allocate> Clone for Root<T> {
 	}
 }
 
+/**
+A pointer onto the garbage-collected heap which doesn't keep its object alive.
+
+A `WeakRoot` is created using [`Root::downgrade`](struct.Root.html#method.downgrade). Once its 
+object has been garbage-collected, [`upgrade`](#method.upgrade) will return `None`.
+
+This is useful for caches and lookup tables which refer to GameLisp objects, but which shouldn't
+prevent those objects from being collected. Note that an object which is unreachable may not be
+collected until several calls to [`glsp::gc`](fn.gc.html) have passed.
+
+`WeakRoots` are compared and hashed by identity, so they can be used as `HashMap` keys.
+*/
+
+pub struct WeakRoot<T: Allocate> {
+	//this Gc isn't traced, and it may have been freed. we only dereference it while `alive` is set
+	gc: Gc<T>,
+	alive: Rc<Cell<bool>>
+}
+
+impl<T: Allocate> WeakRoot<T> {
+	/**
+	Returns a `Root` for the object, or `None` if it has been garbage-collected.
+	*/
+	pub fn upgrade(&self) -> Option<Root<T>> {
+		if !self.alive.get() {
+			return None
+		}
+
+		with_heap(|heap| heap.root_untraced(&self.gc))
+	}
+
+	/**
+	Returns `true` if the object has been garbage-collected.
+	*/
+	pub fn is_dead(&self) -> bool {
+		!self.alive.get()
+	}
+
+	/**
+	Returns `true` if both `WeakRoots` point to the same object.
+
+	This is still meaningful after the object has been garbage-collected.
+	*/
+	pub fn ptr_eq(weak0: &WeakRoot<T>, weak1: &WeakRoot<T>) -> bool {
+		Rc::ptr_eq(&weak0.alive, &weak1.alive)
+	}
+}
+
+impl<T: Allocate> PartialEq<WeakRoot<T>> for WeakRoot<T> {
+	fn eq(&self, other: &WeakRoot<T>) -> bool {
+		WeakRoot::ptr_eq(self, other)
+	}
+}
+
+impl<T: Allocate> Eq for WeakRoot<T> { }
+
+impl<T: Allocate> Hash for WeakRoot<T> {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		Rc::as_ptr(&self.alive).hash(state)
+	}
+}
+
+impl<T: Allocate> Clone for WeakRoot<T> {
+	fn clone(&self) -> WeakRoot<T> {
+		WeakRoot {
+			gc: self.gc.clone(),
+			alive: Rc::clone(&self.alive)
+		}
+	}
+}
+
 impl<T: Allocate> Deref for Root<T> {
 	type Target = T;
 	fn deref(&self) -> &T {
@@ -355,8 +447,8 @@ impl<T: Allocate> Drop for Root<T> {
 		let header = self.gc.header();
 		let root_index = header.root_index();
 
-		let engine_id = ACTIVE_ENGINE_ID.with(|id| id.get().unwrap());
-		if engine_id != header.engine_id() {
+		let engine_id = ACTIVE_ENGINE_ID.with(|id| id.get());
+		if engine_id != Some(header.engine_id()) {
 			eprintln!("attempted to drop a Root for an inactive Runtime - aborting process");
 			abort()
 		}
@@ -386,7 +478,7 @@ impl<T: Allocate> Drop for Root<T> {
 pub enum Slot {
 	Nil,
 	Int(i32),
-	Flo(f32),
+	Flo(Flo),
 	Char(char),
 	Bool(bool),
 	Sym(Sym),
@@ -399,7 +491,14 @@ pub enum Slot {
 	Class(Gc<Class>),
 	GFn(Gc<GFn>),
 	Coro(Gc<Coro>),
-	RData(Gc<RData>)
+	RData(Gc<RData>),
+	Rec(Gc<Rec>),
+	PArr(Gc<PArr>),
+	PTab(Gc<PTab>),
+	View(Gc<View>),
+	Vec2(Gc<Vec2>),
+	Vec3(Gc<Vec3>),
+	Mat3(Gc<Mat3>)
 }
 
 impl Slot {
@@ -420,7 +519,14 @@ impl Slot {
 			Val::Class(ref c) => Slot::Class(Gc::from_root(c)),
 			Val::GFn(ref g) => Slot::GFn(Gc::from_root(g)),
 			Val::Coro(ref c) => Slot::Coro(Gc::from_root(c)),
-			Val::RData(ref r) => Slot::RData(Gc::from_root(r))
+			Val::RData(ref r) => Slot::RData(Gc::from_root(r)),
+			Val::Rec(ref r) => Slot::Rec(Gc::from_root(r)),
+			Val::PArr(ref p) => Slot::PArr(Gc::from_root(p)),
+			Val::PTab(ref p) => Slot::PTab(Gc::from_root(p)),
+			Val::View(ref v) => Slot::View(Gc::from_root(v)),
+			Val::Vec2(ref v) => Slot::Vec2(Gc::from_root(v)),
+			Val::Vec3(ref v) => Slot::Vec3(Gc::from_root(v)),
+			Val::Mat3(ref v) => Slot::Mat3(Gc::from_root(v))
 		}
 	}
 
@@ -442,6 +548,13 @@ impl Slot {
 			Slot::GFn(ref c) => Val::GFn(c.root()),
 			Slot::Coro(ref c) => Val::Coro(c.root()),
 			Slot::RData(ref r) => Val::RData(r.root()),
+			Slot::Rec(ref r) => Val::Rec(r.root()),
+			Slot::PArr(ref p) => Val::PArr(p.root()),
+			Slot::PTab(ref p) => Val::PTab(p.root()),
+			Slot::View(ref v) => Val::View(v.root()),
+			Slot::Vec2(ref v) => Val::Vec2(v.root()),
+			Slot::Vec3(ref v) => Val::Vec3(v.root()),
+			Slot::Mat3(ref v) => Val::Mat3(v.root()),
 		}
 	}
 
@@ -463,6 +576,13 @@ impl Slot {
 			Slot::GFn(c) => Val::GFn(c.into_root()),
 			Slot::Coro(c) => Val::Coro(c.into_root()),
 			Slot::RData(r) => Val::RData(r.into_root()),
+			Slot::Rec(r) => Val::Rec(r.into_root()),
+			Slot::PArr(p) => Val::PArr(p.into_root()),
+			Slot::PTab(p) => Val::PTab(p.into_root()),
+			Slot::View(v) => Val::View(v.into_root()),
+			Slot::Vec2(v) => Val::Vec2(v.into_root()),
+			Slot::Vec3(v) => Val::Vec3(v.into_root()),
+			Slot::Mat3(v) => Val::Mat3(v.into_root()),
 		}
 	}
 
@@ -473,27 +593,57 @@ impl Slot {
 	pub fn a_type_name(&self) -> &'static str {
 		self.root().a_type_name()
 	}
+
+	pub(crate) fn is_num_or_char(&self) -> bool {
+		matches!(*self, Slot::Int(_) | Slot::Flo(_) | Slot::Char(_))
+	}
+
+	//a weak tab only holds a key or value weakly if it's compared by identity. returns the 
+	//referent's header and address, or None if the slot would be held strongly.
+	pub(crate) fn weak_referent(&self) -> Option<(&GcHeader, usize)> {
+		match *self {
+			Slot::Tab(ref gc) => Some((gc.header(), gc.as_usize())),
+			Slot::GIter(ref gc) => Some((gc.header(), gc.as_usize())),
+			Slot::Obj(ref gc) => Some((gc.header(), gc.as_usize())),
+			Slot::Class(ref gc) => Some((gc.header(), gc.as_usize())),
+			Slot::GFn(ref gc) => Some((gc.header(), gc.as_usize())),
+			Slot::Coro(ref gc) => Some((gc.header(), gc.as_usize())),
+			Slot::RData(ref gc) => Some((gc.header(), gc.as_usize())),
+			_ => None
+		}
+	}
 }
 
 //Slot implements Eq and Hash so that it can be used as HashMap key. unlike Val, its PartialEq 
 //implementation has the semantics of keys_eqv, rather than eq.
+//
+//syms and ints are by far the most common tab keys, so we compare them directly. a sym or int is
+//never keys_eqv to a value of any other type, so this is consistent with the slow path.
 impl PartialEq<Slot> for Slot {
+	#[inline]
 	fn eq(&self, other: &Slot) -> bool {
-		self.root().keys_eqv(&other.root())
+		match (self, other) {
+			(&Slot::Sym(s0), &Slot::Sym(s1)) => s0 == s1,
+			(&Slot::Int(i0), &Slot::Int(i1)) => i0 == i1,
+			(&Slot::Sym(_), _) | (_, &Slot::Sym(_)) => false,
+			(&Slot::Int(_), _) | (_, &Slot::Int(_)) => false,
+			_ => self.root().keys_eqv(&other.root())
+		}
 	}
 }
 
 impl Eq for Slot { }
 
 impl Hash for Slot {
+	#[inline]
 	fn hash<H: Hasher>(&self, state: &mut H) {
 		match *self {
 			Slot::Nil => Hashable(Val::Nil).hash(state),
-			Slot::Int(i) => Hashable(Val::Int(i)).hash(state),
+			Slot::Int(i) => state.write_u32(i as u32),
 			Slot::Flo(f) => Hashable(Val::Flo(f)).hash(state),
 			Slot::Char(c) => Hashable(Val::Char(c)).hash(state),
 			Slot::Bool(b) => Hashable(Val::Bool(b)).hash(state),
-			Slot::Sym(s) => Hashable(Val::Sym(s)).hash(state),
+			Slot::Sym(s) => state.write_u32(s.0),
 			Slot::RFn(f) => Hashable(Val::RFn(f)).hash(state),
 			Slot::Arr(ref gc) => (**gc).hash(state),
 			Slot::Str(ref gc) => (**gc).hash(state),
@@ -503,15 +653,23 @@ impl Hash for Slot {
 			Slot::Class(ref gc) => (&**gc as *const _ as usize).hash(state),
 			Slot::GFn(ref gc) => (&**gc as *const _ as usize).hash(state),
 			Slot::Coro(ref gc) => (&**gc as *const _ as usize).hash(state),
-			Slot::RData(ref gc) => (&**gc as *const _ as usize).hash(state)
+			Slot::RData(ref gc) => (&**gc as *const _ as usize).hash(state),
+			Slot::Rec(ref gc) => (**gc).hash(state),
+			Slot::PArr(ref gc) => (**gc).hash(state),
+			Slot::PTab(ref gc) => (**gc).hash(state),
+			Slot::View(ref gc) => (&**gc as *const _ as usize).hash(state),
+			Slot::Vec2(ref gc) => (**gc).hash(state),
+			Slot::Vec3(ref gc) => (**gc).hash(state),
+			Slot::Mat3(ref gc) => (**gc).hash(state)
 		}
 	}
 }
 
 //the GcHeader is made of two 32-bit words.
 
-//the high word dedicates its topmost eight bits to the Engine id, and the next bit to a "frozen"
-//flag. its lower 23 bits are an unsigned index into the roots vec (or 0x_007f_ffff when unrooted). 
+//the high word dedicates its topmost eight bits to the Engine id, the next bit to a "frozen"
+//flag, and the next bit to an "interned" flag (only used by Str). its lower 22 bits are an 
+//unsigned index into the roots vec (or 0x_003f_ffff when unrooted). 
 //the root count is stored in the roots vec, rather than inline, to avoid wasting space in the 
 //header of unrooted objects.
 
@@ -519,14 +677,20 @@ impl Hash for Slot {
 //object. otherwise, the upper two bits are the color index, and the lower 30 bits are an unsigned 
 //index into the old-objects vec for that color.
 
-//the frozen flag isn't actually written or read by the gc at all. we just store it in the gc 
-//header because otherwise there would be several structs with a `frozen: Cell<bool>` field,
-//taking up 64 bits of storage for 1 bit of information.
+//the frozen and interned flags aren't actually written or read by the gc at all. we just store 
+//them in the gc header because otherwise there would be several structs with a `frozen: 
+//Cell<bool>` field, taking up 64 bits of storage for 1 bit of information.
+
+//the weak flag is set when an object has at least one WeakRoot, or when it's a weak tab, so that
+//the heap only needs to search its `weak_flags` and `weak_tabs` maps for those objects when 
+//they're freed.
 
 const ENGINE_ID_SHIFT: u32 = 24;
 const ENGINE_ID_MASK: u32 = 0xff << 24;
 const FROZEN_BIT: u32 = 0x1 << 23;
-const ROOT_INDEX_MASK: u32 = !(ENGINE_ID_MASK | FROZEN_BIT);
+const INTERNED_BIT: u32 = 0x1 << 22;
+const WEAK_BIT: u32 = 0x1 << 21;
+const ROOT_INDEX_MASK: u32 = !(ENGINE_ID_MASK | FROZEN_BIT | INTERNED_BIT | WEAK_BIT);
 const UNROOTED_BITS: u32 = ROOT_INDEX_MASK;
 const MAX_ROOT_INDEX: usize = (ROOT_INDEX_MASK - 1) as usize;
 
@@ -571,6 +735,37 @@ impl GcHeader {
 		self.hi.set(self.hi.get() | FROZEN_BIT);
 	}
 
+	//only used by glsp::restore_runtime. scripts can never unfreeze an object, and an interned
+	//object can never be unfrozen at all.
+	pub(crate) fn set_frozen(&self, frozen: bool) {
+		if frozen || self.interned() {
+			self.freeze();
+		} else {
+			self.hi.set(self.hi.get() & !FROZEN_BIT);
+		}
+	}
+
+	pub(crate) fn interned(&self) -> bool {
+		(self.hi.get() & INTERNED_BIT) != 0
+	}
+
+	//only used by glsp::intern, which also freezes the object
+	pub(crate) fn intern(&self) {
+		self.hi.set(self.hi.get() | FROZEN_BIT | INTERNED_BIT);
+	}
+
+	fn weak(&self) -> bool {
+		(self.hi.get() & WEAK_BIT) != 0
+	}
+
+	fn set_weak(&self, weak: bool) {
+		if weak {
+			self.hi.set(self.hi.get() | WEAK_BIT);
+		} else {
+			self.hi.set(self.hi.get() & !WEAK_BIT);
+		}
+	}
+
 	fn rooted(&self) -> bool {
 		(self.hi.get() & ROOT_INDEX_MASK) != UNROOTED_BITS
 	}
@@ -645,9 +840,22 @@ pub trait Visitor {
 			Slot::Class(ref c) => self.visit_gc(c),
 			Slot::GFn(ref g) => self.visit_gc(g),
 			Slot::Coro(ref c) => self.visit_gc(c),
-			Slot::RData(ref r) => self.visit_gc(r)
+			Slot::RData(ref r) => self.visit_gc(r),
+			Slot::Rec(ref r) => self.visit_gc(r),
+			Slot::PArr(ref p) => self.visit_gc(p),
+			Slot::PTab(ref p) => self.visit_gc(p),
+			Slot::View(ref v) => self.visit_gc(v),
+			Slot::Vec2(ref v) => self.visit_gc(v),
+			Slot::Vec3(ref v) => self.visit_gc(v),
+			Slot::Mat3(ref v) => self.visit_gc(v)
 		}
 	}
+
+	//called for each key or value which a weak tab holds weakly. by default, it's treated as 
+	//a strong reference; the marking and scratch visitors skip it.
+	fn visit_weak_slot(&mut self, slot: &Slot) {
+		self.visit_slot(slot)
+	}
 }
 
 struct MarkingVisitor<'a, 'b> {
@@ -692,6 +900,78 @@ impl<'a, 'b> Visitor for MarkingVisitor<'a, 'b> {
 			}
 		}
 	}
+
+	fn visit_weak_slot(&mut self, slot: &Slot) {
+		if slot.weak_referent().is_none() {
+			self.visit_slot(slot)
+		}
+	}
+}
+
+//write-barriers each of the vm's stacks, just before a call to Heap::step(). see traverse_stacks()
+pub(crate) struct StackVisitor<'a> {
+	heap: &'a Heap
+}
+
+impl<'a> StackVisitor<'a> {
+	pub(crate) fn new(heap: &'a Heap) -> StackVisitor<'a> {
+		StackVisitor { heap }
+	}
+}
+
+impl<'a> Visitor for StackVisitor<'a> {
+	fn visit_gc<T: Allocate>(&mut self, gc: &Gc<T>) {
+		self.heap.traverse_stack_gc(gc);
+	}
+}
+
+//finds the scratch objects which are reachable from outside their scratch scope. see
+//collect_scratch(). objects which aren't scratch objects are never pushed onto the stack.
+pub(crate) struct ScratchVisitor {
+	scratch: FnvHashSet<usize>,
+	reached: FnvHashSet<usize>,
+	stack: Vec<ErasedGc>
+}
+
+impl Visitor for ScratchVisitor {
+	fn visit_gc<T: Allocate>(&mut self, gc: &Gc<T>) {
+		if gc.header().young() {
+			let addr = gc.as_usize();
+			if self.scratch.contains(&addr) && self.reached.insert(addr) {
+				self.stack.push(T::erase_gc(gc.clone()));
+			}
+		}
+	}
+
+	fn visit_weak_slot(&mut self, slot: &Slot) {
+		if slot.weak_referent().is_none() {
+			self.visit_slot(slot)
+		}
+	}
+}
+
+struct ReachableVisitor {
+	visited: FnvHashSet<usize>,
+	stack: Vec<ErasedGc>
+}
+
+impl Visitor for ReachableVisitor {
+	fn visit_gc<T: Allocate>(&mut self, gc: &Gc<T>) {
+		if self.visited.insert(gc.as_usize()) {
+			self.stack.push(T::erase_gc(gc.clone()));
+		}
+	}
+}
+
+struct BarrierVisitor<'a, T: Allocate> {
+	heap: &'a Heap,
+	src: &'a T
+}
+
+impl<'a, T: Allocate> Visitor for BarrierVisitor<'a, T> {
+	fn visit_gc<U: Allocate>(&mut self, gc: &Gc<U>) {
+		self.heap.write_barrier(self.src, gc);
+	}
 }
 
 #[doc(hidden)]
@@ -729,6 +1009,13 @@ pub const GC_MIN_RATIO: f32 = 1.2;
 /** Equivalent to [`(gc-value 'default-ratio)`](https://gamelisp.rs/std/gc-value). */
 pub const GC_DEFAULT_RATIO: f32 = INITIAL_U;
 
+//returned by Heap::scratch_mark()
+#[derive(Copy, Clone)]
+pub(crate) struct ScratchMark {
+	steps: usize,
+	young_len: usize
+}
+
 pub(crate) struct Heap {
 	pub(crate) recycler: Recycler,
 
@@ -751,6 +1038,24 @@ pub(crate) struct Heap {
 	ratio_u: Cell<f32>,
 	ratio_r: Cell<f32>,
 	ratio_w: Cell<Option<f32>>,
+
+	steps: Cell<usize>,
+
+	//the total memory usage of every object which has ever been allocated, measured at the time
+	//of its allocation. used by glsp::call_limited.
+	allocated_bytes: Cell<usize>,
+
+	//paused coros which were unreachable, but which still had pending (defer)s. they're kept
+	//alive for a little longer, so that glsp::gc can run those defers. see resurrect_coro().
+	doomed_coros: RefCell<Vec<Gc<Coro>>>,
+
+	//a flag for each object which has a WeakRoot, keyed by its address, and shared with each of 
+	//its WeakRoots. the flag is cleared when the object is freed.
+	weak_flags: RefCell<FnvHashMap<usize, Rc<Cell<bool>>>>,
+
+	//every weak tab which hasn't been freed, keyed by its address. they're untraced. each step
+	//removes their entries whose weak referents are about to be freed; see purge_weak_tabs().
+	weak_tabs: RefCell<FnvHashMap<usize, Gc<Tab>>>
 }
 
 impl Drop for Heap {
@@ -786,12 +1091,24 @@ impl Heap {
 
 			ratio_u: Cell::new(INITIAL_U),
 			ratio_r: Cell::new(INITIAL_R),
-			ratio_w: Cell::new(INITIAL_W)
+			ratio_w: Cell::new(INITIAL_W),
+
+			steps: Cell::new(0),
+			allocated_bytes: Cell::new(0),
+			doomed_coros: RefCell::new(Vec::new()),
+			weak_flags: RefCell::new(FnvHashMap::default()),
+			weak_tabs: RefCell::new(FnvHashMap::default())
 		}
 	}
 
 	#[allow(dead_code)]
 	pub(crate) fn clear(&self) {
+		for (_, alive) in self.weak_flags.borrow_mut().drain() {
+			alive.set(false);
+		}
+
+		self.weak_tabs.borrow_mut().clear();
+
 		for erased in self.young_objects.borrow_mut().drain(..) {
 			with_erased_gc!(erased, gc, gc.free())
 		}
@@ -819,6 +1136,138 @@ impl Heap {
 		//cause a reference loop, so there's no need to clear them when Runtime is dropped.
 	}
 
+	//used by glsp::reload to find the instances of a redefined class. ghosts are excluded,
+	//since they're unreachable and waiting to be freed.
+	pub(crate) fn objs_of_class(&self, class: &Gc<Class>) -> Vec<Gc<Obj>> {
+		let mut objs = Vec::new();
+		let mut visit = |erased: &ErasedGc| {
+			if let ErasedGc::Obj(ref obj) = *erased {
+				if obj.has_class(class) {
+					objs.push(obj.clone());
+				}
+			}
+		};
+
+		self.young_objects.borrow().iter().for_each(&mut visit);
+
+		for i in 0..4 {
+			if i != self.ghost_index.get() {
+				self.old_objects[i].borrow().iter().for_each(&mut visit);
+			}
+		}
+
+		objs
+	}
+
+	//used by glsp::runtime_snapshot to find every object which is reachable from a Root. garbage
+	//which hasn't been collected yet is excluded, so that the snapshot won't keep it alive. the 
+	//vm's stacks aren't traversed, so this should only be called while they're empty.
+	pub(crate) fn reachable_objects(&self) -> Vec<ErasedGc> {
+		let mut visitor = ReachableVisitor {
+			visited: FnvHashSet::default(),
+			stack: Vec::new()
+		};
+
+		for root_entry in self.roots.borrow().iter() {
+			with_erased_gc!(root_entry.gc, gc, visitor.visit_gc(gc));
+		}
+
+		let mut objects = Vec::new();
+		while let Some(erased) = visitor.stack.pop() {
+			with_erased_gc!(erased, gc, gc.visit_gcs(&mut visitor));
+			objects.push(erased);
+		}
+
+		objects
+	}
+
+	//used by glsp::restore_runtime, which replaces an object's contents wholesale. `replace` may 
+	//change the object's memory usage, and make it point to any number of other objects, so we
+	//update young_bytes or old_bytes and write-barrier every one of its pointees.
+	pub(crate) fn replace_contents<T: Allocate, F: FnOnce()>(&self, src: &T, replace: F) {
+		let prev_usage = src.memory_usage();
+		replace();
+		self.memory_usage_barrier(src, prev_usage, src.memory_usage());
+
+		src.visit_gcs(&mut BarrierVisitor { heap: self, src });
+	}
+
+	//roots a Gc which isn't traced by the gc, like the Gc<GFn> held by a WeakCallback. the caller
+	//must guarantee that the object hasn't been freed. returns None if it belongs to some other 
+	//Runtime, or if it's a ghost: ghosts are unreachable and waiting to be freed, so they mustn't 
+	//be resurrected.
+	pub(crate) fn root_untraced<T: Allocate>(&self, gc: &Gc<T>) -> Option<Root<T>> {
+		let header = gc.header();
+
+		let engine_id = ACTIVE_ENGINE_ID.with(|id| id.get());
+		if engine_id != Some(header.engine_id()) {
+			return None
+		}
+
+		if !header.young() && header.color_index() == self.ghost_index.get() {
+			None
+		} else {
+			Some(gc.root())
+		}
+	}
+
+	fn weak_flag<T: Allocate>(&self, gc: &Gc<T>) -> Rc<Cell<bool>> {
+		let header = gc.header();
+		header.set_weak(true);
+
+		let mut weak_flags = self.weak_flags.borrow_mut();
+		let alive = weak_flags.entry(gc.as_usize()).or_insert_with(|| {
+			Rc::new(Cell::new(true))
+		});
+
+		Rc::clone(alive)
+	}
+
+	//must be called just before an object is freed or recycled
+	fn forget_weak(&self, erased: &ErasedGc) {
+		if erased.header().weak() {
+			erased.header().set_weak(false);
+
+			let addr = with_erased_gc!(*erased, gc, gc.as_usize());
+			if let Some(alive) = self.weak_flags.borrow_mut().remove(&addr) {
+				alive.set(false);
+			}
+
+			if let ErasedGc::Tab(_) = *erased {
+				self.weak_tabs.borrow_mut().remove(&addr);
+			}
+		}
+	}
+
+	pub(crate) fn register_weak_tab(&self, tab: &Gc<Tab>) {
+		debug_assert!(tab.weakness() != Weakness::Strong);
+
+		tab.header().set_weak(true);
+		self.weak_tabs.borrow_mut().insert(tab.as_usize(), tab.clone());
+	}
+
+	//a weak tab which is currently borrowed can't have its entries removed, so instead, its weak
+	//referents are kept alive for as long as the borrow lasts
+	fn visit_borrowed_weak_tabs<V: Visitor>(&self, visitor: &mut V) -> bool {
+		let mut any_borrowed = false;
+		for tab in self.weak_tabs.borrow().values() {
+			if tab.is_borrowed() {
+				tab.visit_weak_referents(visitor);
+				any_borrowed = true;
+			}
+		}
+
+		any_borrowed
+	}
+
+	//removes every weak tab entry whose weak referent satisfies `is_dead`. this must happen 
+	//before those referents are freed, so that weak tabs never hold a dangling pointer.
+	fn purge_weak_tabs<F: Fn(&GcHeader, usize) -> bool>(&self, is_dead: F) {
+		for tab in self.weak_tabs.borrow().values() {
+			tab.purge_weak(&is_dead);
+		}
+	}
+
 	pub(crate) fn ratio(&self) -> f32 {
 		self.ratio_u.get()
 	}
@@ -844,7 +1293,9 @@ impl Heap {
 		let header = gc.header();
 		debug_assert!(header.young() && !header.marked());
 
-		self.young_bytes.set(self.young_bytes.get() + gc.memory_usage());
+		let memory_usage = gc.memory_usage();
+		self.young_bytes.set(self.young_bytes.get() + memory_usage);
+		self.allocated_bytes.set(self.allocated_bytes.get().saturating_add(memory_usage));
 		self.young_objects.borrow_mut().push(T::erase_gc(gc));
 	}
 
@@ -902,6 +1353,7 @@ impl Heap {
 	//the caller is required to to write-barrier anything that's in the grey memory-areas (those 
 	//which aren't write-barriered when mutated) just before calling collect_*.
 	pub(crate) fn step(&self) {
+		self.steps.set(self.steps.get() + 1);
 
 		let mut young_objects = self.young_objects.borrow_mut();
 		let mut old_objects = [
@@ -937,6 +1389,36 @@ impl Heap {
 			})
 		}
 
+		//resurrect any unmarked young coros which have pending defers, and mark the weak referents
+		//of any borrowed weak tabs, then finish marking everything which they point to
+		let mut marked_more = false;
+		for erased in young_objects.iter() {
+			if let ErasedGc::Coro(ref coro) = *erased {
+				if !coro.header().marked() && coro.has_pending_defers() {
+					let mut visitor = MarkingVisitor::new(self, &mut marking_stack,
+					                                      &mut old_objects, false);
+					self.resurrect_coro(coro, &mut visitor);
+					marked_more = true;
+				}
+			}
+		}
+
+		let mut visitor = MarkingVisitor::new(self, &mut marking_stack, &mut old_objects, false);
+		marked_more |= self.visit_borrowed_weak_tabs(&mut visitor);
+
+		if marked_more {
+			while let Some(erased) = marking_stack.pop() {
+				with_erased_gc!(erased, gc, {
+					let mut visitor = MarkingVisitor::new(self, &mut marking_stack,
+					                                      &mut old_objects, false);
+					gc.visit_gcs(&mut visitor);
+				})
+			}
+		}
+
+		//weak tabs forget any unmarked young objects, which are about to be freed
+		self.purge_weak_tabs(|header, _| header.young() && !header.marked());
+
 		//sweep young objects: empty the young generation. if a young object is unmarked, free it
 		//immediately. if it's marked, promote it into an old black object.
 		let mut promoted_bytes: usize = 0;
@@ -947,6 +1429,7 @@ impl Heap {
 				if header.marked() {
 					promoted_bytes += self.promote(gc, &mut old_objects);
 				} else {
+					self.forget_weak(&erased);
 					self.recycler.free(erased);
 				}
 			})
@@ -984,6 +1467,7 @@ impl Heap {
 
 			while self.old_bytes[ghost_index].get() > self.ghost_target.get() {
 				let erased = old_objects[ghost_index].pop().unwrap();
+				self.forget_weak(&erased);
 
 				//note that with "unsafe-internals" disabled, this may cause latency spikes by
 				//suddenly freeing a tree of Rc references all at once. we could solve this by
@@ -1001,6 +1485,31 @@ impl Heap {
 			debug_assert!(old_objects[ghost_index].is_empty());
 		}
 
+		//at the end of the cycle, any old white coros which have pending defers are resurrected
+		//by turning them gray. this postpones the end of the cycle until they've been traversed.
+		if old_objects[gray_index].is_empty() && 
+		   self.old_bytes[black_index].get() >= MIN_SURVIVING_BYTES {
+
+			let doomed: Vec<Gc<Coro>> = old_objects[white_index].iter().filter_map(|erased| {
+				match *erased {
+					ErasedGc::Coro(ref coro) if coro.has_pending_defers() => Some(coro.clone()),
+					_ => None
+				}
+			}).collect();
+
+			for coro in &doomed {
+				let mut visitor = MarkingVisitor::new(self, &mut marking_stack,
+				                                      &mut old_objects, true);
+				self.resurrect_coro(coro, &mut visitor);
+			}
+
+			//borrowed weak tabs turn their white referents gray, rather than letting them 
+			//become ghosts while they can't be purged
+			let mut visitor = MarkingVisitor::new(self, &mut marking_stack,
+			                                      &mut old_objects, true);
+			self.visit_borrowed_weak_tabs(&mut visitor);
+		}
+
 		//if there are no gray objects left, and if we've produced at least MIN_SURVIVING_BYTES
 		//of old black objects, then we've reached the end of the cycle. make all white objects
 		//into ghost objects, update W, and turn all black objects white.
@@ -1034,6 +1543,12 @@ impl Heap {
 			self.black_index.set(prev_ghost_index);
 			self.ghost_index.set(prev_white_index);
 
+			//ghosts can never be reached again, so weak tabs forget them immediately, even 
+			//though they'll be freed incrementally
+			self.purge_weak_tabs(|header, _| {
+				!header.young() && header.color_index() == prev_white_index
+			});
+
 			//reset our targets...
 			self.ghost_target.set(self.old_bytes[self.ghost_index.get()].get());
 			self.black_target.set(0);
@@ -1053,6 +1568,124 @@ impl Heap {
 		}
 	}
 
+	pub(crate) fn steps(&self) -> usize {
+		self.steps.get()
+	}
+
+	pub(crate) fn allocated_bytes(&self) -> usize {
+		self.allocated_bytes.get()
+	}
+
+	//a paused coro's (defer)s must run when it's collected, but running arbitrary code during
+	//step() would be unsafe. instead, we mark the coro as though it were reachable, and queue it
+	//up so that glsp::gc can run its defers after step() returns. the coro will then be finished
+	//or poisoned, so it won't be resurrected again.
+	fn resurrect_coro(&self, coro: &Gc<Coro>, visitor: &mut MarkingVisitor) {
+		visitor.visit_gc(coro);
+		self.doomed_coros.borrow_mut().push(coro.clone());
+	}
+
+	pub(crate) fn take_doomed_coros(&self) -> Vec<Root<Coro>> {
+		self.doomed_coros.borrow_mut().drain(..).map(|coro| coro.into_root()).collect()
+	}
+
+	//records the start of a scratch scope. every young object which is allocated after this
+	//point is a scratch object, until the scope is passed to collect_scratch().
+	pub(crate) fn scratch_mark(&self) -> ScratchMark {
+		ScratchMark {
+			steps: self.steps.get(),
+			young_len: self.young_objects.borrow().len()
+		}
+	}
+
+	//frees every scratch object which can't be reached from outside its scratch scope, without
+	//waiting for the next step(). returns the number of bytes freed and the number of scratch 
+	//objects which escaped. the caller must visit each of the vm's stacks using visit_stacks.
+	//
+	//a scratch object escapes if it's reachable from a Root, from the vm's stacks, or from a young 
+	//object which predates the scope. old objects don't need to be traversed: when a pointer to a 
+	//young object is stored in an old object, write_barrier() marks the young object, so we can 
+	//simply treat marked scratch objects as reachable. escaped objects stay in the young 
+	//generation, so that the next step() can promote or free them as normal.
+	pub(crate) fn collect_scratch<F>(&self, mark: ScratchMark, visit_stacks: F) -> (usize, usize)
+	where
+		F: FnOnce(&mut ScratchVisitor)
+	{
+		let mut young_objects = self.young_objects.borrow_mut();
+
+		//if step() was called within the scope, the young generation was emptied, so all of
+		//the current young objects were allocated within the scope
+		let start = if self.steps.get() == mark.steps {
+			mark.young_len.min(young_objects.len())
+		} else {
+			0
+		};
+
+		if start == young_objects.len() {
+			return (0, 0)
+		}
+
+		let mut visitor = ScratchVisitor {
+			scratch: young_objects[start..].iter().map(|erased| {
+				with_erased_gc!(*erased, gc, gc.as_usize())
+			}).collect(),
+			reached: FnvHashSet::default(),
+			stack: Vec::new()
+		};
+
+		for root_entry in self.roots.borrow().iter() {
+			with_erased_gc!(root_entry.gc, gc, visitor.visit_gc(gc));
+		}
+
+		visit_stacks(&mut visitor);
+
+		for erased in young_objects[.. start].iter() {
+			with_erased_gc!(*erased, gc, gc.visit_gcs(&mut visitor));
+		}
+
+		//a paused coro with pending (defer)s is kept alive, so that step() will resurrect it
+		for erased in young_objects[start ..].iter() {
+			let pending_defers = match *erased {
+				ErasedGc::Coro(ref coro) => coro.has_pending_defers(),
+				_ => false
+			};
+
+			if erased.header().marked() || pending_defers {
+				with_erased_gc!(*erased, gc, visitor.visit_gc(gc));
+			}
+		}
+
+		self.visit_borrowed_weak_tabs(&mut visitor);
+
+		while let Some(erased) = visitor.stack.pop() {
+			with_erased_gc!(erased, gc, gc.visit_gcs(&mut visitor));
+		}
+
+		self.purge_weak_tabs(|_, addr| {
+			visitor.scratch.contains(&addr) && !visitor.reached.contains(&addr)
+		});
+
+		let mut freed_bytes = 0;
+		let mut escaped = 0;
+
+		let scratch_objects: Vec<ErasedGc> = young_objects.drain(start ..).collect();
+		for erased in scratch_objects {
+			let addr = with_erased_gc!(erased, gc, gc.as_usize());
+			if visitor.reached.contains(&addr) {
+				young_objects.push(erased);
+				escaped += 1;
+			} else {
+				freed_bytes += with_erased_gc!(erased, gc, gc.memory_usage());
+				self.forget_weak(&erased);
+				self.recycler.free(erased);
+			}
+		}
+
+		self.young_bytes.set(self.young_bytes.get().saturating_sub(freed_bytes));
+
+		(freed_bytes, escaped)
+	}
+
 	pub(crate) fn young_memory_usage(&self) -> usize {
 		self.young_bytes.get()
 	}
@@ -1067,28 +1700,12 @@ impl Heap {
 		self.old_bytes[self.ghost_index.get()].get()
 	}
 
-	pub(crate) fn traverse_stack_slot(&self, dst: &Slot) {
-		match *dst {
-			Slot::Nil | Slot::Int(_) | Slot::Char(_) | Slot::Flo(_) | 
-			Slot::Bool(_) | Slot::Sym(_) | Slot::RFn(_) => (),
-			Slot::Arr(ref gc) => self.traverse_stack_gc(gc),
-			Slot::Str(ref gc) => self.traverse_stack_gc(gc),
-			Slot::Tab(ref gc) => self.traverse_stack_gc(gc),
-			Slot::GIter(ref gc) => self.traverse_stack_gc(gc),
-			Slot::Obj(ref gc) => self.traverse_stack_gc(gc),
-			Slot::Class(ref gc) => self.traverse_stack_gc(gc),
-			Slot::GFn(ref gc) => self.traverse_stack_gc(gc),
-			Slot::Coro(ref gc) => self.traverse_stack_gc(gc),
-			Slot::RData(ref gc) => self.traverse_stack_gc(gc)
-		}
-	}
-
 	pub(crate) fn traverse_stack_gc<T: Allocate>(&self, dst: &Gc<T>) {
 		let header = dst.header();
 
 		//this should be impossible, but we check it for an extra level of security anyway
-		let engine_id = ACTIVE_ENGINE_ID.with(|id| id.get().unwrap());
-		if engine_id != header.engine_id() {
+		let engine_id = ACTIVE_ENGINE_ID.with(|id| id.get());
+		if engine_id != Some(header.engine_id()) {
 			eprintln!("attempted to move a Root to another Runtime - aborting process");
 			abort()
 		}
@@ -1126,7 +1743,14 @@ impl Heap {
 			Val::Class(ref root) => self.write_barrier(src, &root.to_gc()),
 			Val::GFn(ref root) => self.write_barrier(src, &root.to_gc()),
 			Val::Coro(ref root) => self.write_barrier(src, &root.to_gc()),
-			Val::RData(ref root) => self.write_barrier(src, &root.to_gc())
+			Val::RData(ref root) => self.write_barrier(src, &root.to_gc()),
+			Val::Rec(ref root) => self.write_barrier(src, &root.to_gc()),
+			Val::PArr(ref root) => self.write_barrier(src, &root.to_gc()),
+			Val::PTab(ref root) => self.write_barrier(src, &root.to_gc()),
+			Val::View(ref root) => self.write_barrier(src, &root.to_gc()),
+			Val::Vec2(ref root) => self.write_barrier(src, &root.to_gc()),
+			Val::Vec3(ref root) => self.write_barrier(src, &root.to_gc()),
+			Val::Mat3(ref root) => self.write_barrier(src, &root.to_gc())
 		}
 	}
 
@@ -1142,7 +1766,14 @@ impl Heap {
 			Slot::Class(ref gc) => self.write_barrier(src, gc),
 			Slot::GFn(ref gc) => self.write_barrier(src, gc),
 			Slot::Coro(ref gc) => self.write_barrier(src, gc),
-			Slot::RData(ref gc) => self.write_barrier(src, gc)
+			Slot::RData(ref gc) => self.write_barrier(src, gc),
+			Slot::Rec(ref gc) => self.write_barrier(src, gc),
+			Slot::PArr(ref gc) => self.write_barrier(src, gc),
+			Slot::PTab(ref gc) => self.write_barrier(src, gc),
+			Slot::View(ref gc) => self.write_barrier(src, gc),
+			Slot::Vec2(ref gc) => self.write_barrier(src, gc),
+			Slot::Vec3(ref gc) => self.write_barrier(src, gc),
+			Slot::Mat3(ref gc) => self.write_barrier(src, gc)
 		}
 	}
 
@@ -1231,22 +1862,58 @@ Strs, Tabs, Obj, Coros or GIters.
 
 in order to reduce the pressure on the allocator, we maintain a free list for small instances
 of these types. in tcof, this increases total memory usage only slightly, while more than 
-doubling the gc's performance.
+doubling the gc's performance. the free lists' hit rate is reported by glsp::gc_pool_hits() 
+and glsp::gc_pool_misses().
 */
 
 const MAX_ARR_CAPACITY: usize = 16;
 
 pub(crate) struct Recycler {
 	arrs: Vec<RefCell<Vec<Gc<Arr>>>>,
-	giters: RefCell<Vec<Gc<GIter>>>
+	giters: RefCell<Vec<Gc<GIter>>>,
+
+	//the number of allocations which were served from a free list, and the number which
+	//had to fall back to the system allocator
+	hits: Cell<usize>,
+	misses: Cell<usize>
 }
 
 impl Recycler {
 	fn new() -> Recycler {
 		Recycler {
 			arrs: vec![RefCell::new(Vec::new()); 32],
-			giters: RefCell::new(Vec::new())
+			giters: RefCell::new(Vec::new()),
+			hits: Cell::new(0),
+			misses: Cell::new(0)
+		}
+	}
+
+	pub(crate) fn hits(&self) -> usize {
+		self.hits.get()
+	}
+
+	pub(crate) fn misses(&self) -> usize {
+		self.misses.get()
+	}
+
+	//pops the smallest recycled arr whose capacity is at least `min_capacity`, and registers it
+	//with the young generation
+	fn pop_arr(&self, min_capacity: usize) -> Option<Root<Arr>> {
+		for i in min_capacity .. MAX_ARR_CAPACITY {
+			let mut arrs = self.arrs[i].borrow_mut();
+			if let Some(gc) = arrs.pop() {
+				drop(arrs);
+
+				let arr = gc.into_root();
+				with_heap(|heap| heap.register_young(arr.to_gc()));
+
+				self.hits.set(self.hits.get() + 1);
+				return Some(arr)
+			}
 		}
+
+		self.misses.set(self.misses.get() + 1);
+		None
 	}
 
 	fn free(&self, erased: ErasedGc) {
@@ -1282,39 +1949,30 @@ impl Recycler {
 			ErasedGc::Stay(gc) => gc.free(),
 			ErasedGc::Coro(gc) => gc.free(),
 			ErasedGc::RData(gc) => gc.free(),
+			ErasedGc::Rec(gc) => gc.free(),
+			ErasedGc::PArr(gc) => gc.free(),
+			ErasedGc::PTab(gc) => gc.free(),
+			ErasedGc::View(gc) => gc.free(),
+			ErasedGc::Vec2(gc) => gc.free(),
+			ErasedGc::Vec3(gc) => gc.free(),
+			ErasedGc::Mat3(gc) => gc.free(),
 			ErasedGc::Bytecode(gc) => gc.free(),
 			ErasedGc::Lambda(gc) => gc.free()
 		}
 	}
 
 	pub(crate) fn arr(&self) -> Root<Arr> {
-		for arrs in &self.arrs {
-			let mut arrs = arrs.borrow_mut();
-			if !arrs.is_empty() {
-				let arr = arrs.pop().unwrap().into_root();
-				drop(arrs);
-
-				with_heap(|heap| heap.register_young(arr.to_gc()));
-				return arr
-			}
+		match self.pop_arr(0) {
+			Some(arr) => arr,
+			None => glsp::alloc(Arr::new())
 		}
-
-		glsp::alloc(Arr::new())
 	}
 
 	pub(crate) fn arr_with_capacity(&self, capacity: usize) -> Root<Arr> {
-		for i in capacity .. MAX_ARR_CAPACITY {
-			let mut arrs = self.arrs[i].borrow_mut();
-			if !arrs.is_empty() {
-				let arr = arrs.pop().unwrap().into_root();
-				drop(arrs);
-
-				with_heap(|heap| heap.register_young(arr.to_gc()));
-				return arr
-			}
+		match self.pop_arr(capacity) {
+			Some(arr) => arr,
+			None => glsp::alloc(Arr::with_capacity(capacity))
 		}
-
-		glsp::alloc(Arr::with_capacity(capacity))
 	}
 
 	pub(crate) fn arr_from_elem<V: ToVal>(
@@ -1322,20 +1980,12 @@ impl Recycler {
 		elem: V, 
 		reps: usize
 	) -> GResult<Root<Arr>> {
-		for i in reps .. MAX_ARR_CAPACITY {
-			let mut arrs = self.arrs[i].borrow_mut();
-			if !arrs.is_empty() {
-				let arr = arrs.pop().unwrap().into_root();
-				drop(arrs);
-
-				with_heap(|heap| heap.register_young(arr.to_gc()));
-
-				for _ in 0 .. reps {
-					arr.push(elem.to_slot()?)?;
-				}
-
-				return Ok(arr)
+		if let Some(arr) = self.pop_arr(reps) {
+			for _ in 0 .. reps {
+				arr.push(elem.to_slot()?)?;
 			}
+
+			return Ok(arr)
 		}
 
 		Ok(glsp::alloc(Arr::from_elem(elem, reps)?))
@@ -1350,22 +2000,15 @@ impl Recycler {
 		let (min_size, max_size) = iter.size_hint();
 
 		if max_size.is_some() && min_size == max_size.unwrap() {
-			let capacity = min_size;
-			for i in capacity .. MAX_ARR_CAPACITY {
-				let mut arrs = self.arrs[i].borrow_mut();
-				if !arrs.is_empty() {
-					let arr = arrs.pop().unwrap().into_root();
-					drop(arrs);
-
-					with_heap(|heap| heap.register_young(arr.to_gc()));
-
-					for item in iter {
-						arr.push(item.to_slot()?)?;
-					}
-
-					return Ok(arr)
+			if let Some(arr) = self.pop_arr(min_size) {
+				for item in iter {
+					arr.push(item.to_slot()?)?;
 				}
+
+				return Ok(arr)
 			}
+		} else {
+			self.misses.set(self.misses.get() + 1);
 		}
 
 		Ok(glsp::alloc(Arr::from_iter(iter)?))
@@ -1381,8 +2024,10 @@ impl Recycler {
 			//the future.
 			*giter.state.borrow_mut() = state;
 
+			self.hits.set(self.hits.get() + 1);
 			giter
 		} else {
+			self.misses.set(self.misses.get() + 1);
 			glsp::alloc(GIter::new(state))
 		}
 	}
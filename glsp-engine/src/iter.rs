@@ -1,13 +1,15 @@
 use std::{usize};
 use std::cell::{RefCell};
 use std::cmp::{min};
+use std::rc::{Rc};
 use super::class::{Class, Obj};
 use super::code::{Coro, CoroState, GFn};
-use super::collections::{Arr, DequeAccess, DequeOps, Str, Tab};
-use super::engine::{glsp, RData, RFn, with_heap};
+use super::collections::{Arr, DequeAccess, DequeOps, Str, Tab, Weakness};
+use super::engine::{glsp, GStore, RData, RFn, with_heap};
 use super::error::{GResult};
 use super::gc::{Allocate, Gc, GcHeader, Root, Slot, Visitor};
-use super::val::{Val};
+use super::persist::{PArr, PTab};
+use super::val::{Flo, Val};
 use super::wrap::{Callable, FromVal};
 
 //-------------------------------------------------------------------------------------------------
@@ -47,9 +49,14 @@ impl Allocate for GIter {
 			FRnExclusive(..) | FRnInclusive(..) | FRnOpen(..) => (),
 			ArrElements(arr, ..) => v.visit_gc(arr),
 			StrElements(st, ..) => v.visit_gc(st),
+			PArrElements(parr, ..) => v.visit_gc(parr),
 			TabEntries(arr) => v.visit_gc(arr),
 			TabKeys(arr) => v.visit_gc(arr),
 			TabValues(arr) => v.visit_gc(arr),
+			WeakTab(pending, order, ..) => {
+				v.visit_gc(pending);
+				v.visit_gc(order);
+			}
 			CoroResults(coro) => v.visit_gc(coro),
 			Once1(slot) => v.visit_slot(slot),
 			OnceN(arr) => v.visit_gc(arr),
@@ -80,7 +87,8 @@ impl Allocate for GIter {
 			Chunks(_, arr) => v.visit_gc(arr),
 			RChunks(_, arr) => v.visit_gc(arr),
 			Windows(_, arr) => v.visit_gc(arr),
-			Lines(st) => v.visit_gc(st),
+			Lines(st, ..) => v.visit_gc(st),
+			RustIter(_) => (),
 			Split(src, split_at) => {
 				v.visit_gc(src);
 				v.visit_gc(split_at);
@@ -180,6 +188,18 @@ impl GIter {
 		matches!(*self.state.borrow(), GIterState::Finished)
 	}
 
+	/**
+	Finishes the iterator early, discarding any items which it hasn't yet produced.
+
+	This releases any resources which the iterator holds, such as the file opened by
+	[`glsp::read_lines`](fn.read_lines.html), without waiting for it to be garbage-collected.
+
+	Equivalent to [`(close it)`](https://gamelisp.rs/std/close).
+	*/
+	pub fn close(&self) {
+		*self.state.borrow_mut() = GIterState::Finished;
+	}
+
 	/**
 	Returns the iterator's remaining length.
 
@@ -232,9 +252,13 @@ impl GIter {
 				//Exact((st.len() as u32).saturating_sub(start_offs + back_offs) as usize)
 				Unknown
 			}
+			PArrElements(ref parr, start_offs, back_offs) => {
+				Exact((parr.len() as u32).saturating_sub(start_offs + back_offs) as usize)
+			}
 			TabEntries(ref remaining) => Exact(remaining.len()),
 			TabKeys(ref remaining) => Exact(remaining.len()),
 			TabValues(ref remaining) => Exact(remaining.len()),
+			WeakTab(..) => Unknown,
 			CoroResults(_) => Unknown,
 			Once1(_) => Exact(1),
 			OnceN(ref arr) => Exact(arr.len()),
@@ -254,7 +278,8 @@ impl GIter {
 			Windows(window_len, ref arr) => {
 				Exact(arr.len().saturating_sub(window_len as usize - 1))
 			}
-			Lines(_) => Unknown,
+			Lines(..) => Unknown,
+			RustIter(_) => Unknown,
 			Split(_, _) => Unknown,
 			Rev(ref base) => base.len(),
 			Enumerate(ref base, _) => base.len(),
@@ -355,9 +380,11 @@ impl GIter {
 			FRnOpen(..) => false,
 			ArrElements(..) => true,
 			StrElements(..) => true,
+			PArrElements(..) => true,
 			TabEntries(..) => false,
 			TabKeys(..) => false,
 			TabValues(..) => false,
+			WeakTab(..) => false,
 			CoroResults(..) => false,
 			Once1(_) => true,
 			OnceN(_) => true,
@@ -373,7 +400,8 @@ impl GIter {
 			Chunks(_, _) => true,
 			RChunks(_, _) => true,
 			Windows(_, _) => true,
-			Lines(_) => true,
+			Lines(..) => true,
+			RustIter(_) => false,
 			Split(_, _) => true,
 			Rev(_) => true,
 			Enumerate(_, _) => false,
@@ -501,6 +529,15 @@ impl GIter {
 					None
 				}
 			}
+			PArrElements(ref parr, ref mut start_offs, back_offs) => {
+				if *start_offs + back_offs < parr.len() as u32 {
+					let result = parr.get_slot(*start_offs as usize);
+					*start_offs += 1;
+					Some(Ok(result))
+				} else {
+					None
+				}
+			}
 			TabEntries(ref remaining) | TabKeys(ref remaining) | TabValues(ref remaining) => {
 				if remaining.len() > 0 {
 					Some(remaining.pop())
@@ -508,6 +545,16 @@ impl GIter {
 					None
 				}
 			}
+			WeakTab(ref pending, ref order, ref mut next_i, part) => {
+				match (pending.pop_weak_entry(order, next_i), part) {
+					(Some((key, value)), TabPart::Entries) => {
+						Some(Ok(Slot::Arr(arr![key, value].into_gc())))
+					}
+					(Some((key, _)), TabPart::Keys) => Some(Ok(key)),
+					(Some((_, value)), TabPart::Values) => Some(Ok(value)),
+					(None, _) => None
+				}
+			}
 			CoroResults(ref coro) => {
 				if matches!(coro.state(), CoroState::Finished | CoroState::Poisoned) {
 					None
@@ -645,34 +692,33 @@ impl GIter {
 					Some(Ok(Slot::Arr(window.to_gc())))
 				}
 			}
-			Lines(ref st) => {
-				if st.len() == 0 {
+			Lines(ref st, ref mut start_offs, back_offs) => {
+				//consistent with Rust: an empty string has no lines, and a line ending at the
+				//very end of the string doesn't begin another line
+				let end = (st.len() as u32).saturating_sub(back_offs);
+				if *start_offs >= end {
 					None
 				} else {
-					let accum = glsp::str();
-					loop {
-						let len = st.len();
-
-						if len == 0 {
-							break
-						}
-
-						let first: char = st.get(0).unwrap();
-						if first == '\n' {
-							st.pop_start::<char>().unwrap();
-							break
-						}
-
-						if first == '\r' && len >= 2 && st.get::<char>(1).unwrap() == '\n' {
-							st.pop_start::<char>().unwrap();
-							st.pop_start::<char>().unwrap();
-							break
-						}
+					let mut line_end = *start_offs;
+					while line_end < end && st.get::<char>(line_end).unwrap() != '\n' {
+						line_end += 1;
+					}
 
-						accum.push(st.pop_start::<char>().unwrap()).unwrap();
+					let next_start = min(line_end + 1, end);
+					if line_end < end && line_end > *start_offs && 
+					   st.get::<char>(line_end - 1).unwrap() == '\r' {
+						line_end -= 1;
 					}
 
-					Some(Ok(Slot::Str(accum.to_gc())))
+					let line = str_slice(st, *start_offs, line_end);
+					*start_offs = next_start;
+					Some(Ok(Slot::Str(line.to_gc())))
+				}
+			}
+			RustIter(ref iter) => {
+				match iter.try_borrow_mut() {
+					Ok(mut iter) => iter.next().map(|result| result.map(|val| Slot::from_val(&val))),
+					Err(_) => Some(Err(error!("a Rust iterator was advanced while it was running")))
 				}
 			}
 			Split(ref src, ref split_at) => {
@@ -1000,7 +1046,17 @@ impl GIter {
 					None
 				}
 			}
-			TabEntries(_) | TabKeys(_) | TabValues(_) => {
+			PArrElements(ref parr, start_offs, ref mut back_offs) => {
+				let len = parr.len() as u32;
+				if start_offs + *back_offs < len {
+					let result = parr.get_slot((len - (*back_offs + 1)) as usize);
+					*back_offs += 1;
+					Some(Ok(result))
+				} else {
+					None
+				}
+			}
+			TabEntries(_) | TabKeys(_) | TabValues(_) | WeakTab(..) => {
 				Some(Err(error!("table iterators are not double-ended")))
 			}
 			CoroResults(_) => {
@@ -1133,44 +1189,36 @@ impl GIter {
 					Some(Ok(Slot::Arr(window.to_gc())))
 				}
 			}
-			Lines(ref st) => {
-				if st.len() == 0 {
+			Lines(ref st, start_offs, ref mut back_offs) => {
+				let len = st.len() as u32;
+				let mut end = len.saturating_sub(*back_offs);
+				if start_offs >= end {
 					None
 				} else {
-					//the way that Lines iterates forwards is consistent with Rust: an empty
-					//string has no lines. otherwise, it consumes any number of substrings 
-					//followed by line endings, then consumes all remaining characters.
-
-					//to replicate this when iterating backwards, if the string ends with a
-					//line ending we pop it, then we consume characters until we encounter another
-					//line ending, which we don't yet pop.
-
-					let accum = glsp::str();
-
-					if st.get::<char>(-1).unwrap() == '\n' {
-						st.pop::<char>().unwrap();
-
-						if st.len() >= 1 && st.get::<char>(-1).unwrap() == '\r' {
-							st.pop::<char>().unwrap();
+					//to replicate the forward behaviour, if the remaining text ends with a line
+					//ending we skip over it, then we consume characters until we encounter 
+					//another line ending, which we don't yet consume.
+					if st.get::<char>(end - 1).unwrap() == '\n' {
+						end -= 1;
+
+						if end > start_offs && st.get::<char>(end - 1).unwrap() == '\r' {
+							end -= 1;
 						}
 					}
 
-					loop {
-						if st.len() == 0 {
-							break
-						}
-
-						if st.get::<char>(-1).unwrap() == '\n' {
-							break
-						}
-
-						let last = st.pop::<char>().unwrap();
-						accum.push_start(last).unwrap();
+					let mut line_start = end;
+					while line_start > start_offs && 
+					      st.get::<char>(line_start - 1).unwrap() != '\n' {
+						line_start -= 1;
 					}
 
-					Some(Ok(Slot::Str(accum.to_gc())))
+					*back_offs = len - line_start;
+					Some(Ok(Slot::Str(str_slice(st, line_start, end).to_gc())))
 				}
 			}
+			RustIter(_) => {
+				Some(Err(error!("Rust iterators are not double-ended")))
+			}
 			Split(ref src, ref split_at) => {
 				while src.len() > 0 {
 					let first = src.get::<char>(-1).unwrap();
@@ -1306,9 +1354,13 @@ impl GIter {
 			RnInclusive(..) | FRnInclusive(..) => "rni",
 			ArrElements(..) => "arr",
 			StrElements(..) => "str",
+			PArrElements(..) => "parr",
 			TabEntries(..) => "tab",
 			TabKeys(..) => "keys",
 			TabValues(..) => "values",
+			WeakTab(_, _, _, TabPart::Entries) => "tab",
+			WeakTab(_, _, _, TabPart::Keys) => "keys",
+			WeakTab(_, _, _, TabPart::Values) => "values",
 			CoroResults(..) => "coro",
 			Once1(_) | OnceN(_) => "once",
 			OnceWith(_) => "once-with",
@@ -1322,7 +1374,8 @@ impl GIter {
 			Chunks(_, _) => "chunks",
 			RChunks(_, _) => "rchunks",
 			Windows(_, _) => "windows",
-			Lines(_) => "lines",
+			Lines(..) => "lines",
+			RustIter(_) => "rust",
 			Split(_, _) => "split",
 			Rev(_) => "rev",
 			Enumerate(_, _) => "enumerate",
@@ -1357,6 +1410,14 @@ pub enum GIterLen {
 //so that we don't have to track owned_memory_usage, we prefer Gc<Arr> over Vec<Slot> for storing
 //owned data. this has the added benefits of keeping the GIter struct small and making the
 //recycler more effective.
+//which part of each entry a weak tab's iterator produces. see Tab::weak_giter()
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum TabPart {
+	Entries,
+	Keys,
+	Values
+}
+
 #[derive(Clone)]
 pub(crate) enum GIterState {
 
@@ -1375,15 +1436,17 @@ pub(crate) enum GIterState {
 	RnInclusive(i32, i32, i32), //start, end, step_by
 	RnOpen(i32, i32), //start, step_by
 
-	FRnExclusive(f32, f32, f32),
-	FRnInclusive(f32, f32, f32),
-	FRnOpen(f32, f32),
+	FRnExclusive(Flo, Flo, Flo),
+	FRnInclusive(Flo, Flo, Flo),
+	FRnOpen(Flo, Flo),
 
 	ArrElements(Gc<Arr>, u32, u32), //arr, start_offs, back_offs
 	StrElements(Gc<Str>, u32, u32), //str, start_offs, back_offs
+	PArrElements(Gc<PArr>, u32, u32), //parr, start_offs, back_offs
 	TabEntries(Gc<Arr>),
 	TabKeys(Gc<Arr>),
 	TabValues(Gc<Arr>),
+	WeakTab(Gc<Tab>, Gc<Tab>, u32, TabPart), //pending, order, next_i, part
 	CoroResults(Gc<Coro>),
 
 	Once1(Slot),
@@ -1399,13 +1462,16 @@ pub(crate) enum GIterState {
 	AccessRData(Gc<RData>, Gc<GIter>),
 	AccessClass(Gc<Class>, Gc<GIter>),
 
-	//todo: have Chunks, RChunks, Windows, Lines and Split stream the deque's contents 
-	//in like ArrElements, rather than shallow-cloning the source when they're constructed
+	//todo: have Chunks, RChunks, Windows and Split stream the deque's contents in like
+	//ArrElements, rather than shallow-cloning the source when they're constructed
 	Chunks(u32, Gc<Arr>),
 	RChunks(u32, Gc<Arr>),
 	Windows(u32, Gc<Arr>),
 
-	Lines(Gc<Str>),
+	Lines(Gc<Str>, u32, u32), //str, start_offs, back_offs
+
+	//produced by glsp::rust_iter. like CoroResults, it's shared between shallow clones
+	RustIter(Rc<RefCell<dyn Iterator<Item = GResult<Val>> + GStore>>),
 	Split(Gc<Str>, Gc<Str>), //src, split_at
 
 	Rev(Gc<GIter>),
@@ -1435,6 +1501,10 @@ impl GIterState {
 			TabEntries(arr) => TabEntries(arr.shallow_clone().to_gc()),
 			TabKeys(arr) => TabKeys(arr.shallow_clone().to_gc()),
 			TabValues(arr) => TabValues(arr.shallow_clone().to_gc()),
+			WeakTab(pending, order, next_i, part) => {
+				let (pending, order) = (pending.shallow_clone(), order.shallow_clone());
+				WeakTab(pending.to_gc(), order.to_gc(), *next_i, *part)
+			}
 
 			//can't do anything for CoroResults as yet (todo?), because coros can't be
 			//shallow-cloned. 
@@ -1449,7 +1519,6 @@ impl GIterState {
 			RChunks(len, arr) => RChunks(*len, arr.shallow_clone().to_gc()),
 			Windows(len, arr) => Windows(*len, arr.shallow_clone().to_gc()),
 
-			Lines(st) => Lines(st.shallow_clone().to_gc()),
 			Split(src, split_at) => Split(src.shallow_clone().to_gc(), split_at.clone()),
 
 			Rev(base) => Rev(base.shallow_clone().to_gc()),
@@ -1485,6 +1554,15 @@ impl GIterState {
 	}
 }
 
+fn str_slice(st: &Str, start: u32, end: u32) -> Root<Str> {
+	let result = glsp::str();
+	for i in start .. end {
+		result.push(st.get::<char>(i).unwrap()).unwrap();
+	}
+
+	result
+}
+
 #[derive(Clone)]
 pub(crate) enum GcCallable {
 	RFn(RFn),
@@ -1537,7 +1615,9 @@ pub enum Iterable {
 	Str(Root<Str>),
 	Tab(Root<Tab>),
 	Coro(Root<Coro>),
-	GIter(Root<GIter>)
+	GIter(Root<GIter>),
+	PArr(Root<PArr>),
+	PTab(Root<PTab>)
 }
 
 impl PartialEq<Iterable> for Iterable {
@@ -1548,6 +1628,8 @@ impl PartialEq<Iterable> for Iterable {
 			(Iterable::Tab(t0), Iterable::Tab(t1)) => Root::ptr_eq(t0, t1),
 			(Iterable::Coro(c0), Iterable::Coro(c1)) => Root::ptr_eq(c0, c1),
 			(Iterable::GIter(i0), Iterable::GIter(i1)) => Root::ptr_eq(i0, i1),
+			(Iterable::PArr(p0), Iterable::PArr(p1)) => Root::ptr_eq(p0, p1),
+			(Iterable::PTab(p0), Iterable::PTab(p1)) => Root::ptr_eq(p0, p1),
 			_ => false
 		}
 	}
@@ -1568,7 +1650,9 @@ impl IterableOps for Iterable {
 			Iterable::Str(st) => st.giter(),
 			Iterable::Tab(tab) => tab.giter(),
 			Iterable::Coro(coro) => coro.giter(),
-			Iterable::GIter(iter) => iter.giter()
+			Iterable::GIter(iter) => iter.giter(),
+			Iterable::PArr(parr) => parr.giter(),
+			Iterable::PTab(ptab) => ptab.giter()
 		}
 	}
 }
@@ -1587,6 +1671,10 @@ impl IterableOps for Root<Str> {
 
 impl IterableOps for Root<Tab> {
 	fn giter(&self) -> Root<GIter> {
+		if self.weakness() != Weakness::Strong {
+			return self.weak_giter(TabPart::Entries)
+		}
+
 		let arr = glsp::arr_with_capacity(self.len());
 		for pair in self.entries().iter_to::<Slot, Slot>() {
 			let (key, value) = pair.unwrap();
@@ -1603,6 +1691,23 @@ impl IterableOps for Root<Coro> {
 	}
 }
 
+impl IterableOps for Root<PArr> {
+	fn giter(&self) -> Root<GIter> {
+		glsp::giter(GIterState::PArrElements(self.to_gc(), 0, 0))
+	}
+}
+
+impl IterableOps for Root<PTab> {
+	fn giter(&self) -> Root<GIter> {
+		let arr = glsp::arr_with_capacity(self.len());
+		for (key, value) in self.entry_slots() {
+			arr.push(arr![key, value]).unwrap();
+		}
+
+		glsp::giter(GIterState::TabEntries(arr.to_gc()))
+	}
+}
+
 impl IterableOps for Root<GIter> {
 	fn giter(&self) -> Root<GIter> {
 		(*self).clone()
@@ -1633,6 +1738,18 @@ impl IterableOps for Gc<Coro> {
 	}
 }
 
+impl IterableOps for Gc<PArr> {
+	fn giter(&self) -> Root<GIter> {
+		glsp::giter(GIterState::PArrElements(self.clone(), 0, 0))
+	}
+}
+
+impl IterableOps for Gc<PTab> {
+	fn giter(&self) -> Root<GIter> {
+		self.root().giter()
+	}
+}
+
 impl IterableOps for Gc<GIter> {
 	fn giter(&self) -> Root<GIter> {
 		self.root()
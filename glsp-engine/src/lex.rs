@@ -58,6 +58,7 @@ pub(crate) enum TokType {
 
 	ArrOpen,
 	TabOpen,
+	RecOpen, // #rec(
 	ArrClose,
 
 	AccessOpen,
@@ -304,9 +305,10 @@ fn lex<'a>(lexer: &mut Lexer, text: &mut &'a str) -> GResult<Tok<'a>> {
 					(TokType::Whitespace, 2)
 				}
 
-				//arr/tab/access delimiters
+				//arr/tab/rec/access delimiters
 				('(', _) => (TokType::ArrOpen, 1),
 				('#', Some('(')) => (TokType::TabOpen, 2),
+				('#', Some('r')) if chars.as_str().starts_with("rec(") => (TokType::RecOpen, 5),
 				(')', _) => (TokType::ArrClose, 1),
 
 				('[', _) => (TokType::AccessOpen, 1),
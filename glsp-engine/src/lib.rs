@@ -21,23 +21,34 @@ mod collections;
 #[macro_use]
 mod engine;
 
+mod api;
 mod ast;
+mod cache;
 mod code;
 mod compile;
+mod corosave;
 mod class;
+mod deadcode;
 mod encoder;
 mod eval;
 mod gc;
 mod iter;
 mod lex;
+mod library;
 mod parse;
+mod persist;
 mod print;
+mod record;
 mod serde;
+mod suggest;
 mod transform;
+mod vecmath;
+mod view;
 mod vm;
 
 pub use self::{
-	code::{Coro, CoroState, GFn},
+	api::{ApiEntry},
+	code::{Callback, Coro, CoroState, GFn, WeakCallback},
 	collections::{
 		Arr, Deque, DequeAccess, DequeAccessRange, DequeIndex, DequeOps, DequeRange, IntoElement, 
 		FromElement, IterDeque, IterDequeTo, IterTab, IterTabKeys, IterTabKeysTo, IterTabTo, 
@@ -45,20 +56,32 @@ pub use self::{
 	},
 	class::{Class, Obj},
 	engine::{
-		GSend, GStore, PrWriter, EprWriter, Lib, LibRef, LibRefMut, RClass, RData, RFn,
-		RRef, RRefMut, RRoot, RStore, Sym, ToSym, with_lazy_val
+		ExecutionPoint, FileResolution, GlobalSnapshot, GSend, GStore, IncludedDir, IncludedFile, 
+		PrWriter, EprWriter, Lib, LibRef, LibRefMut, Limits, LoadedFile, PrintLevel, RClass, 
+		RData, ReloadReport, RFn, RRef, RRefMut, RRoot, RStore, RuntimeHandle, RuntimeSnapshot, 
+		SafepointAction, SpanInfo, Sym, ToSym, with_lazy_val
 	},
-	error::{GError, GResult},
+	error::{GError, GResult, MessageArgs, MessageId},
 	eval::{EnvMode, Expander, Expansion},
-	gc::{Allocate, GC_DEFAULT_RATIO, GC_MIN_RATIO, Root},
+	gc::{Allocate, GC_DEFAULT_RATIO, GC_MIN_RATIO, Root, WeakRoot},
 	iter::{GIter, GIterLen, Iterable, IterableOps},
-	val::{Hashable, Num, Val},
+	library::{Library},
+	parse::{Definition, ParsedForm},
+	persist::{PArr, PTab},
+	record::{Rec},
+	val::{Flo, Hashable, Num, Val},
+	vecmath::{Mat3, Vec2, Vec3, VectorOp},
+	view::{View, ViewField},
 	wrap::{
-		ArgType, Callable, CallableOps, forwarder, FromVal, IntoResult, MakeArg, MakeTemp,
-		make_temps, OrNil, ToCallArgs, ToVal, WrappedFn, wrapped_arg_limits
+		ArgType, BoxedFn, Callable, CallableOps, forwarder, FromVal, IntoResult, KwArgs, MakeArg,
+		MakeTemp, make_temps, OrNil, ToCallArgs, ToVal, WrappedFn, wrapped_arg_limits,
+		wrapped_param_types
 	}
 };
 
+#[cfg(feature = "compiler")]
+pub use self::{cache::{CompileCacheStats}, deadcode::{DeadDef}};
+
 pub use self::engine::glsp::*;
 
 //undocumented apis required by the glsp-stdlib crate or by macros
@@ -67,5 +90,5 @@ pub use self::{
 	engine::{Engine, EngineBuilder, Span, stock_syms, SymKind},
 	gc::{Slot},
 	parse::{Parser},
-	print::{dump_fn, dump_form, dump_macro}
+	print::{dump_fn, dump_form, dump_form_unoptimized, dump_macro}
 };
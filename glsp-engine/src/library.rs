@@ -0,0 +1,150 @@
+use super::engine::{GSend};
+use super::error::{GResult};
+use super::val::{Val};
+use super::wrap::{ToVal, WrappedFn};
+
+/**
+A collection of global bindings which can be installed into a `Runtime` all at once.
+
+A `Library` is constructed using a builder API. It doesn't refer to any particular `Runtime`,
+so it can be defined once (perhaps in a `static`) and then installed into any number of
+`Runtimes`, on any thread, using [`glsp::install`](glsp/fn.install.html).
+
+	let library = Library::new()
+		.rfn("spawn-enemy", rfn!(spawn_enemy))
+		.rfn("despawn!", rfn!(despawn))
+		.rfn_macro("with-entity", rfn!(with_entity))
+		.constant("tile-size", 16)
+		.source("enemies.glsp", include_str!("enemies.glsp"));
+
+	glsp::install(&library)?;
+
+A long list of Rust functions is more concisely defined using the
+[`library!`](macro.library.html) macro.
+*/
+
+#[derive(Default)]
+pub struct Library {
+	pub(crate) name: Option<String>,
+	pub(crate) items: Vec<(String, LibraryItem)>,
+	pub(crate) sources: Vec<(String, String)>,
+
+	//the name, parameter names and documentation passed to each call to Library::doc
+	pub(crate) docs: Vec<(String, Vec<String>, String)>
+}
+
+pub(crate) enum LibraryItem {
+	RFn(WrappedFn),
+	RFnMacro(WrappedFn),
+
+	//constants are converted to a Val separately for each Runtime
+	Const(Box<dyn Fn() -> GResult<Val> + GSend + Send + Sync>)
+}
+
+impl Library {
+	/** Creates an empty `Library`. */
+	pub fn new() -> Library {
+		Library::default()
+	}
+
+	/**
+	Sets the library's name.
+
+	The name is used to group the library's Rust functions in the 
+	[API manifest](glsp/fn.api_manifest.html). An unnamed library's functions are grouped under
+	the enclosing [`glsp::with_api_library`](glsp/fn.with_api_library.html) call, if any.
+	*/
+	pub fn name(mut self, name: &str) -> Library {
+		self.name = Some(name.to_string());
+		self
+	}
+
+	/**
+	Adds a Rust function, which will be bound to a global variable.
+
+	Equivalent to [`glsp::bind_rfn`](glsp/fn.bind_rfn.html).
+	*/
+	pub fn rfn(mut self, name: &str, wrapped_fn: WrappedFn) -> Library {
+		self.items.push((name.to_string(), LibraryItem::RFn(wrapped_fn)));
+		self
+	}
+
+	/**
+	Adds a Rust function, which will be bound to a global macro.
+
+	Equivalent to [`glsp::bind_rfn_macro`](glsp/fn.bind_rfn_macro.html).
+	*/
+	pub fn rfn_macro(mut self, name: &str, wrapped_fn: WrappedFn) -> Library {
+		self.items.push((name.to_string(), LibraryItem::RFnMacro(wrapped_fn)));
+		self
+	}
+
+	/**
+	Adds a constant, which will be bound to a global variable.
+
+	Equivalent to [`glsp::bind_const`](glsp/fn.bind_const.html). The value is converted using
+	[`ToVal`](trait.ToVal.html) once for each `Runtime` which installs the library.
+	*/
+	pub fn constant<T>(mut self, name: &str, value: T) -> Library
+	where
+		T: ToVal + GSend + Send + Sync + 'static
+	{
+		let item = LibraryItem::Const(Box::new(move || value.to_val()));
+		self.items.push((name.to_string(), item));
+		self
+	}
+
+	/**
+	Adds GameLisp source code, which will be evaluated when the library is installed.
+
+	This is the way to add classes and macros which are written in GameLisp. Sources are
+	evaluated in the order they were added, after all of the library's other bindings have been
+	installed. The toplevel definitions in `text` are reported by
+	[`glsp::definitions`](glsp/fn.definitions.html), and they're checked for conflicts along
+	with everything else.
+	*/
+	pub fn source(mut self, filename: &str, text: &str) -> Library {
+		self.sources.push((filename.to_string(), text.to_string()));
+		self
+	}
+
+	/**
+	Adds parameter names and documentation for one of the library's Rust functions.
+
+	Equivalent to [`glsp::document_api`](glsp/fn.document_api.html). `name` must also be passed
+	to [`rfn`](#method.rfn) or [`rfn_macro`](#method.rfn_macro), or the library will fail to 
+	install.
+	*/
+	pub fn doc(mut self, name: &str, param_names: &[&str], doc: &str) -> Library {
+		let param_names = param_names.iter().map(|param_name| param_name.to_string()).collect();
+		self.docs.push((name.to_string(), param_names, doc.to_string()));
+		self
+	}
+
+	/** Returns `true` if the library contains no bindings and no source code. */
+	pub fn is_empty(&self) -> bool {
+		self.items.is_empty() && self.sources.is_empty()
+	}
+}
+
+/**
+Constructs a [`Library`](struct.Library.html) from a list of Rust functions.
+
+Each function is wrapped using [`rfn!`](macro.rfn.html). Because the result is an ordinary
+`Library`, other bindings can be added to it afterwards.
+
+	let library = library! {
+		"spawn-enemy" => spawn_enemy,
+		"despawn!" => despawn,
+		"enemy-count" => World::enemy_count
+	}.constant("tile-size", 16);
+
+	glsp::install(&library)?;
+*/
+
+#[macro_export]
+macro_rules! library {
+	($($name:expr => $fn_expr:expr),* $(,)?) => (
+		$crate::Library::new()$(.rfn($name, $crate::rfn!($fn_expr)))*
+	);
+}
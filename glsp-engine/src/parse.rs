@@ -1,13 +1,14 @@
 use smallvec::{SmallVec};
 use std::cell::{Cell};
 use std::convert::{TryFrom};
+use std::ops::{Range};
 use std::str::{self, FromStr};
-use super::collections::{Arr, DequeOps, Str, Tab};
+use super::collections::{Arr, DequeAccess, DequeOps, Str, Tab};
 use super::engine::{Filename, glsp, Span, SpanStorage, stock_syms::*, Sym};
 use super::error::{GResult};
 use super::gc::{Root};
 use super::lex::{char_is_whitespace, Lexer, StrStatus, TokType};
-use super::val::{Val};
+use super::val::{Flo, Val};
 
 /*
 
@@ -20,6 +21,10 @@ between one str and the next.)
 in practice, this means that you can either incrementally parse input from a REPL line-by-line, 
 or batch-parse the entire contents of a (load) or (eval) call.
 
+when the parser is constructed with Parser::with_offsets(), it also records the byte range of 
+every form, for use by editor tooling. this is opt-in so that the usual (load) path doesn't need
+to pay for it: arr Spans remain line-granular.
+
 */
 
 #[doc(hidden)]
@@ -27,7 +32,8 @@ pub struct Parser {
 	lexer: Lexer,
 	stack: SmallVec<[Form; 32]>,
 	prev_tok_type: TokType,
-	file: Option<Filename>
+	file: Option<Filename>,
+	offsets: Option<Box<OffsetTracker>>
 }
 
 impl Parser {
@@ -36,10 +42,24 @@ impl Parser {
 			lexer: Lexer::new(),
 			stack: SmallVec::new(),
 			prev_tok_type: TokType::Whitespace,
-			file
+			file,
+			offsets: None
+		}
+	}
+
+	pub(crate) fn with_offsets(file: Option<Filename>) -> Parser {
+		Parser {
+			offsets: Some(Box::new(OffsetTracker::default())),
+			..Parser::new(file)
 		}
 	}
 
+	//after parse() returns a toplevel form, returns the same form with its byte range. only 
+	//available when the Parser was constructed using with_offsets()
+	pub(crate) fn take_parsed_form(&mut self) -> Option<ParsedForm> {
+		self.offsets.as_mut().and_then(|offsets| offsets.finished.take())
+	}
+
 	pub fn parse_all(&mut self, mut text: &str, dst: &mut Vec<Val>) -> GResult<usize> {
 		let starting_len = dst.len();
 
@@ -76,6 +96,8 @@ impl Parser {
 				Form::RawStr(..) => bail!("unterminated raw str"),
 				Form::Tab(..) => bail!("unterminated tab"),
 				Form::TabClause(..) => bail!("unterminated tab clause"),
+				Form::Rec(..) => bail!("unterminated rec"),
+				Form::RecClause(..) => bail!("unterminated rec clause"),
 				Form::Access(..) => bail!("unterminated []"),
 				Form::DiscardNext => bail!("#_ at end of input"),
 				Form::Abbrv(sym) => bail!("{} abbreviation at end of input", sym)
@@ -86,6 +108,113 @@ impl Parser {
 	}
 }
 
+/**
+A form produced by [`glsp::parse_with_offsets`](glsp/fn.parse_with_offsets.html), along with the
+location of its source text.
+
+Ranges are measured in bytes from the start of the parsed text, so they can be used to slice it
+directly.
+*/
+
+#[derive(Clone, Debug)]
+pub struct ParsedForm {
+	val: Val,
+	range: Range<usize>,
+	children: Vec<ParsedForm>
+}
+
+impl ParsedForm {
+	/** Returns the parsed value. */
+	pub fn val(&self) -> &Val {
+		&self.val
+	}
+
+	/** Returns the byte range of the form's source text, including any abbreviation prefix. */
+	pub fn range(&self) -> Range<usize> {
+		self.range.clone()
+	}
+
+	/**
+	Returns each form which was written within this form's source text, in order.
+
+	This is usually one child for each element of an arr. Abbreviations like `'x` and `[a b]`
+	only have children for the forms which appear in the text, so `'x` has the single child 
+	`x`. The children of a `tab` or `rec` literal are its keys and values, and the children of
+	a str with `{}` interpolations are the interpolated forms.
+	*/
+	pub fn children(&self) -> &[ParsedForm] {
+		&self.children[..]
+	}
+}
+
+/**
+A toplevel definition found by [`glsp::definitions`](glsp/fn.definitions.html).
+*/
+
+#[derive(Clone, Debug)]
+pub struct Definition {
+	kind: Sym,
+	name: Sym,
+	range: Range<usize>,
+	name_range: Range<usize>
+}
+
+impl Definition {
+	pub(crate) fn from_parsed_form(form: &ParsedForm) -> GResult<Option<Definition>> {
+		const KINDS: [&str; 9] = [
+			"def", "defn", "defmacro", "defclass", "defmixin", "defstruct", "defrecord",
+			"defonce", "defconst"
+		];
+
+		let arr = match form.val {
+			Val::Arr(ref arr) if arr.len() >= 2 && form.children.len() >= 2 => arr,
+			_ => return Ok(None)
+		};
+
+		match (arr.get::<Val>(0)?, arr.get::<Val>(1)?) {
+			(Val::Sym(kind), Val::Sym(name)) if KINDS.contains(&&*kind.name()) => {
+				Ok(Some(Definition {
+					kind,
+					name,
+					range: form.range(),
+					name_range: form.children[1].range()
+				}))
+			}
+			_ => Ok(None)
+		}
+	}
+
+	/** Returns the defining macro's name, such as `defn` or `defclass`. */
+	pub fn kind(&self) -> Sym {
+		self.kind
+	}
+
+	/** Returns the name being defined. */
+	pub fn name(&self) -> Sym {
+		self.name
+	}
+
+	/** Returns the byte range of the whole definition. */
+	pub fn range(&self) -> Range<usize> {
+		self.range.clone()
+	}
+
+	/** Returns the byte range of the name being defined. */
+	pub fn name_range(&self) -> Range<usize> {
+		self.name_range.clone()
+	}
+}
+
+//the bookkeeping for Parser::with_offsets. `partial` runs parallel to the Parser's stack of 
+//incomplete forms, storing each one's start offset and its children so far.
+#[derive(Default)]
+struct OffsetTracker {
+	offset: usize,
+	partial: Vec<(usize, Vec<ParsedForm>)>,
+	pending: Option<ParsedForm>,
+	finished: Option<ParsedForm>
+}
+
 //an incompletely-parsed form
 enum Form {
 	Arr(Root<Arr>),
@@ -95,6 +224,8 @@ enum Form {
 	ResumedStr(Root<Arr>, Root<Str>, StrEscape),
 	Tab(Root<Tab>),
 	TabClause(Option<Val>, Option<Val>),
+	Rec(Option<Sym>, Vec<(Sym, Val)>), // #rec(Name (field value) ...)
+	RecClause(Option<Val>, Option<Val>),
 	Access(Root<Arr>),
 	DiscardNext,  // #;(a-form)
 	Abbrv(Sym),   // '(a-form), etc.
@@ -126,10 +257,12 @@ fn parse(parser: &mut Parser, text: &mut &str) -> GResult<Option<Val>> {
 	};
 
 	//each call to parse() processes one token
+	let prev_len = text.len();
 	let tok = match parser.lexer.lex(text) {
 		Ok(tok) => tok,
 		Err(err) => return Err(error_at!(span(), "lexing error").with_source(err))
 	};
+	let stack_len = parser.stack.len();
 
 	//many tokens are delimited: they must be followed by ), ], }, or whitespace. this prevents a
 	//number of syntax corner-cases such as (a"b""c"), (prn #t#f\c10), or \retuurn
@@ -140,7 +273,7 @@ fn parse(parser: &mut Parser, text: &mut &str) -> GResult<Option<Val>> {
 		TokType::AccessClose | TokType::StrClose | TokType::RawStrClose => true,
 
 		TokType::Whitespace | TokType::FormComment | TokType::ArrOpen | TokType::TabOpen |
-		TokType::AccessOpen | TokType::StrOpen | TokType::StrPause | TokType::StrResume | 
+		TokType::RecOpen | TokType::AccessOpen | TokType::StrOpen | TokType::StrPause | TokType::StrResume | 
 		TokType::StrChars | TokType::RawStrOpen | TokType::RawStrChars | TokType::Quote | 
 		TokType::Backquote | TokType::Unquote | TokType::Splay | TokType::MethName |
 		TokType::Atsign => false
@@ -188,6 +321,7 @@ fn parse(parser: &mut Parser, text: &mut &str) -> GResult<Option<Val>> {
 		TokType::ArrOpen => {
 			match parser.stack.last() {
 				Some(&Form::Tab(_)) => parser.stack.push(Form::TabClause(None, None)),
+				Some(&Form::Rec(Some(_), _)) => parser.stack.push(Form::RecClause(None, None)),
 				_ => {
 					let arr = glsp::arr();
 					arr.set_span(span());
@@ -200,6 +334,10 @@ fn parse(parser: &mut Parser, text: &mut &str) -> GResult<Option<Val>> {
 			parser.stack.push(Form::Tab(glsp::tab()));
 			None
 		}
+		TokType::RecOpen => {
+			parser.stack.push(Form::Rec(None, Vec::new()));
+			None
+		}
 		TokType::ArrClose => {
 			match parser.stack.pop() {
 				Some(Form::Arr(arr)) => {
@@ -222,6 +360,34 @@ fn parse(parser: &mut Parser, text: &mut &str) -> GResult<Option<Val>> {
 						bail_at!(span(), "invalid clause in tab literal")
 					}
 				}
+				Some(Form::Rec(name, fields)) => {
+					let name = match name {
+						Some(name) => name,
+						None => bail_at!(span(), "rec literal is missing its type name")
+					};
+
+					match glsp::rec_from_named_fields(name, fields) {
+						Ok(rec) => {
+							rec.freeze();
+							Some(Val::Rec(rec))
+						}
+						Err(err) => {
+							return Err(error_at!(span(), "invalid rec literal").with_source(err))
+						}
+					}
+				}
+				Some(Form::RecClause(field, val)) => {
+					if let (Some(Val::Sym(field)), Some(val)) = (field, val) {
+						match parser.stack.last_mut() {
+							Some(Form::Rec(_, ref mut fields)) => fields.push((field, val)),
+							_ => panic!()
+						}
+
+						None
+					} else {
+						bail_at!(span(), "invalid clause in rec literal")
+					}
+				}
 				Some(_) => bail_at!(span(), "unexpected ) token"),
 				None => bail_at!(span(), "unexpected ) token")
 			}
@@ -343,12 +509,40 @@ fn parse(parser: &mut Parser, text: &mut &str) -> GResult<Option<Val>> {
 		}
 	};
 
+	if let Some(ref mut offsets) = parser.offsets {
+		let start = offsets.offset;
+		offsets.offset += prev_len - text.len();
+
+		if parser.stack.len() > stack_len {
+			offsets.partial.push((start, Vec::new()));
+		} else if parser.stack.len() < stack_len {
+			let (start, children) = offsets.partial.pop().unwrap();
+			match parsed_val {
+				Some(ref val) => {
+					let range = start .. offsets.offset;
+					offsets.pending = Some(ParsedForm { val: val.clone(), range, children });
+				}
+				None => {
+					//a tab or rec clause has been closed
+					offsets.partial.last_mut().unwrap().1.extend(children);
+				}
+			}
+		} else if let Some(ref val) = parsed_val {
+			let range = start .. offsets.offset;
+			offsets.pending = Some(ParsedForm { val: val.clone(), range, children: Vec::new() });
+		}
+	}
+
 	//if we haven't produced a value, we're finished. if we have, inspect the stack to decide
 	//what we should do with it. if the stack is empty, we return our val to the caller as a 
 	//toplevel form.
 	while let Some(ref val) = parsed_val {
 		let to_pop = match parser.stack.last_mut() {
 			None => {
+				if let Some(ref mut offsets) = parser.offsets {
+					offsets.finished = offsets.pending.take();
+				}
+
 				return Ok(Some(val.clone()))
 			}
 			Some(&mut Form::Arr(ref arr)) | 
@@ -369,6 +563,24 @@ fn parse(parser: &mut Parser, text: &mut &str) -> GResult<Option<Val>> {
 				parsed_val = None;
 				false
 			}
+			Some(&mut Form::Rec(ref mut name, _)) => {
+				match (&name, val) {
+					(None, &Val::Sym(sym)) => *name = Some(sym),
+					_ => bail_at!(span(), "invalid clause in rec literal")
+				}
+				parsed_val = None;
+				false
+			}
+			Some(&mut Form::RecClause(ref mut field, ref mut value)) => {
+				match (field.is_some(), value.is_some()) {
+					(false, false) => *field = Some(val.clone()),
+					(true, false) => *value = Some(val.clone()),
+					(true, true) => bail_at!(span(), "invalid clause in rec literal"),
+					(false, true) => panic!()
+				}
+				parsed_val = None;
+				false
+			}
 			Some(&mut Form::DiscardNext) => {
 				parsed_val = None;
 				true
@@ -388,6 +600,22 @@ fn parse(parser: &mut Parser, text: &mut &str) -> GResult<Option<Val>> {
 			Some(&mut Form::ResumedStr(_, _, _)) => panic!(),
 		};
 
+		if let Some(ref mut offsets) = parser.offsets {
+			let child = offsets.pending.take().unwrap();
+			match (to_pop, &parsed_val) {
+				(false, _) => offsets.partial.last_mut().unwrap().1.push(child),
+				(true, None) => drop(offsets.partial.pop()),
+				(true, Some(val)) => {
+					let (start, _) = offsets.partial.pop().unwrap();
+					offsets.pending = Some(ParsedForm {
+						val: val.clone(),
+						range: start .. child.range.end,
+						children: vec![child]
+					});
+				}
+			}
+		}
+
 		if to_pop {
 			parser.stack.pop().unwrap();
 		}
@@ -460,8 +688,8 @@ fn parse_int(mut text: &str) -> Option<i32> {
 	}
 }
 
-fn parse_flo(text: &str) -> Option<f32> {
-	//f32::from_str is very close to the rust grammar for f32, except that it doesn't accept
+fn parse_flo(text: &str) -> Option<Flo> {
+	//Flo::from_str is very close to the rust grammar for floats, except that it doesn't accept
 	//an _f32 or _f64 suffix (good!), it accepts floats with no integer part (bad), it doesn't
 	//accept underscores (bad), it accepts strings like 'inf' and '-NaN' (bad), and it accepts 
 	//a leading '+' (bad).
@@ -474,9 +702,9 @@ fn parse_flo(text: &str) -> Option<f32> {
 	}
 
 	match text {
-		"+inf.0" => return Some(f32::INFINITY),
-		"-inf.0" => return Some(f32::NEG_INFINITY),
-		"nan.0" => return Some(f32::NAN),
+		"+inf.0" => return Some(Flo::INFINITY),
+		"-inf.0" => return Some(Flo::NEG_INFINITY),
+		"nan.0" => return Some(Flo::NAN),
 		_ => ()
 	}
 
@@ -489,7 +717,7 @@ fn parse_flo(text: &str) -> Option<f32> {
 	let mut bytes = SmallVec::<[u8; 128]>::with_capacity(text.len());
 	bytes.extend(text.bytes().filter(|byte| *byte != b'_'));
 
-	match f32::from_str(str::from_utf8(&bytes[..]).unwrap()) {
+	match Flo::from_str(str::from_utf8(&bytes[..]).unwrap()) {
 		Ok(f) => Some(f),
 		Err(_) => None
 	}
@@ -609,12 +837,13 @@ fn parse_str_chars(
 			dst.push(escaped_ch)?;
 			text = escape_chars.as_str();
 		} else {
-			dst.push(text.chars().next().unwrap())?;
+			let ch = text.chars().next().unwrap();
+			dst.push(ch)?;
 
 			if text.starts_with("{{") || text.starts_with("}}") {
 				text = &text[2..];
 			} else {
-				text = &text[1..];
+				text = &text[ch.len_utf8()..];
 			}
 		}
 	}
@@ -0,0 +1,873 @@
+use fnv::{FnvHasher};
+use super::collections::{Arr, DequeOps, Tab};
+use super::engine::{glsp};
+use super::error::{GResult, out_of_bounds_error};
+use super::gc::{Allocate, Gc, GcHeader, Root, Slot, Visitor};
+use super::val::{Val};
+use super::wrap::{FromVal, ToVal};
+use std::collections::{VecDeque};
+use std::hash::{Hash, Hasher};
+use std::iter::{FromIterator};
+
+//both persistent collections are tries built from arrs. those arrs are never exposed to scripts
+//and never mutated after they're constructed, so unchanged subtrees can be freely shared between
+//versions. because each node is an ordinary gc object, the gc only traces a shared node once,
+//no matter how many versions refer to it.
+
+const BITS: u32 = 5;
+const WIDTH: usize = 1 << BITS;
+const MASK: usize = WIDTH - 1;
+
+fn new_node<I: IntoIterator<Item = Slot>>(slots: I) -> Gc<Arr> {
+	let node = Arr::from_iter(slots).unwrap();
+	node.freeze();
+	glsp::alloc_gc(node)
+}
+
+fn child(node: &Arr, i: usize) -> Gc<Arr> {
+	match node.borrow()[i] {
+		Slot::Arr(ref child) => child.clone(),
+		_ => unreachable!()
+	}
+}
+
+//returns a copy of `node` with its `i`th slot replaced
+fn with_slot(node: &Arr, i: usize, slot: Slot) -> Gc<Arr> {
+	let mut slots = node.borrow().clone();
+	slots[i] = slot;
+	new_node(slots)
+}
+
+
+//-------------------------------------------------------------------------------------------------
+// PArr
+//-------------------------------------------------------------------------------------------------
+
+/**
+The `parr` primitive type: a persistent array.
+
+A `PArr` can't be mutated. Instead, methods like [`conj`](#method.conj) and
+[`assoc`](#method.assoc) return a new `PArr` which shares almost all of its storage with the
+original, so they only take `O(log n)` time. This makes it cheap to keep many snapshots of a
+large array.
+
+New persistent arrays can be constructed using [`glsp::parr_from_iter`](fn.parr_from_iter.html),
+or from GameLisp code using [`(parr ..elements)`](https://gamelisp.rs/std/parr).
+*/
+
+pub struct PArr {
+	header: GcHeader,
+	len: usize,
+	shift: u32,
+	root: Gc<Arr>,
+	tail: Gc<Arr>
+}
+
+//the layout is the same as clojure's PersistentVector. the last 1 to 32 elements are stored in
+//the `tail` arr. the rest are stored in leaf arrs at the bottom of a 32-way trie, whose branch
+//arrs hold Slot::Arr children. `shift` is the number of index bits consumed below the root.
+
+impl Allocate for PArr {
+	fn header(&self) -> &GcHeader {
+		&self.header
+	}
+
+	fn visit_gcs<V: Visitor>(&self, visitor: &mut V) {
+		visitor.visit_gc(&self.root);
+		visitor.visit_gc(&self.tail);
+	}
+
+	fn clear_gcs(&self) {
+		//the nodes are separately-allocated arrs, so any cycles will be broken by their own
+		//clear_gcs methods
+	}
+
+	fn owned_memory_usage(&self) -> usize {
+		0
+	}
+}
+
+impl PArr {
+	pub(crate) fn new() -> PArr {
+		PArr::from_parts(0, BITS, new_node(None), new_node(None))
+	}
+
+	fn from_parts(len: usize, shift: u32, root: Gc<Arr>, tail: Gc<Arr>) -> PArr {
+		let header = GcHeader::new();
+		header.freeze();
+
+		PArr { header, len, shift, root, tail }
+	}
+
+	/**
+	Returns the number of elements in the array.
+
+	Equivalent to [`(len parr)`](https://gamelisp.rs/std/len).
+	*/
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	/**
+	Returns `true` if the array contains no elements.
+
+	Equivalent to [`(empty? parr)`](https://gamelisp.rs/std/empty-p).
+	*/
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	fn tail_offset(&self) -> usize {
+		if self.len < WIDTH {
+			0
+		} else {
+			((self.len - 1) >> BITS) << BITS
+		}
+	}
+
+	fn resolve_index(&self, index: isize) -> GResult<usize> {
+		let i = if index < 0 { index + self.len as isize } else { index };
+		if i < 0 || i as usize >= self.len {
+			Err(out_of_bounds_error("parr", self.len, index))
+		} else {
+			Ok(i as usize)
+		}
+	}
+
+	fn leaf_for(&self, i: usize) -> Gc<Arr> {
+		if i >= self.tail_offset() {
+			return self.tail.clone()
+		}
+
+		let mut node = self.root.clone();
+		let mut level = self.shift;
+		while level > 0 {
+			node = child(&node, (i >> level) & MASK);
+			level -= BITS;
+		}
+
+		node
+	}
+
+	pub(crate) fn get_slot(&self, i: usize) -> Slot {
+		self.leaf_for(i).borrow()[i & MASK].clone()
+	}
+
+	/**
+	Accesses the element at the given index. A negative index counts backwards from the end
+	of the array.
+
+	Equivalent to [`[parr index]`](https://gamelisp.rs/std/access).
+	*/
+	pub fn get<V: FromVal>(&self, index: isize) -> GResult<V> {
+		V::from_slot(&self.get_slot(self.resolve_index(index)?))
+	}
+
+	/**
+	Returns a new array with an element appended to the end.
+
+	Equivalent to [`(pconj parr val)`](https://gamelisp.rs/std/pconj).
+	*/
+	pub fn conj<V: ToVal>(&self, val: V) -> GResult<Root<PArr>> {
+		Ok(glsp::alloc(self.conj_slot(val.to_slot()?)))
+	}
+
+	pub(crate) fn conj_slot(&self, slot: Slot) -> PArr {
+		let tail_len = self.len - self.tail_offset();
+		if tail_len < WIDTH {
+			let mut tail = self.tail.borrow().clone();
+			tail.push_back(slot);
+
+			return PArr::from_parts(self.len + 1, self.shift, self.root.clone(), new_node(tail))
+		}
+
+		//the tail is full, so it's moved into the trie. if the trie is also full, it gains a
+		//new root with the old root as its first child.
+		let full_tail = self.tail.clone();
+		let (root, shift) = if (self.len >> BITS) > (1 << self.shift) {
+			let path = new_path(self.shift, full_tail);
+			(new_node(vec![Slot::Arr(self.root.clone()), Slot::Arr(path)]), self.shift + BITS)
+		} else {
+			(push_tail(self.len, self.shift, &self.root, full_tail), self.shift)
+		};
+
+		PArr::from_parts(self.len + 1, shift, root, new_node(Some(slot)))
+	}
+
+	/**
+	Returns a new array with the element at the given index replaced. A negative index counts
+	backwards from the end of the array. An index equal to the array's length appends the
+	element to the end.
+
+	Equivalent to [`(passoc parr index val)`](https://gamelisp.rs/std/passoc).
+	*/
+	pub fn assoc<V: ToVal>(&self, index: isize, val: V) -> GResult<Root<PArr>> {
+		let slot = val.to_slot()?;
+		if index >= 0 && index as usize == self.len {
+			return Ok(glsp::alloc(self.conj_slot(slot)))
+		}
+
+		let i = self.resolve_index(index)?;
+		Ok(glsp::alloc(self.assoc_slot(i, slot)))
+	}
+
+	pub(crate) fn assoc_slot(&self, i: usize, slot: Slot) -> PArr {
+		assert!(i < self.len);
+
+		if i >= self.tail_offset() {
+			let tail = with_slot(&self.tail, i & MASK, slot);
+			PArr::from_parts(self.len, self.shift, self.root.clone(), tail)
+		} else {
+			let root = assoc_in(self.shift, &self.root, i, slot);
+			PArr::from_parts(self.len, self.shift, root, self.tail.clone())
+		}
+	}
+
+	/**
+	Returns a new array with its last element removed.
+
+	Equivalent to [`(ppop parr)`](https://gamelisp.rs/std/ppop).
+	*/
+	pub fn pop(&self) -> GResult<Root<PArr>> {
+		ensure!(self.len > 0, "attempted to pop from an empty parr");
+		Ok(glsp::alloc(self.pop_slot()))
+	}
+
+	pub(crate) fn pop_slot(&self) -> PArr {
+		assert!(self.len > 0);
+
+		if self.len == 1 {
+			return PArr::new()
+		}
+
+		if self.len - self.tail_offset() > 1 {
+			let mut tail = self.tail.borrow().clone();
+			tail.pop_back();
+
+			return PArr::from_parts(self.len - 1, self.shift, self.root.clone(), new_node(tail))
+		}
+
+		//the tail is about to become empty, so the trie's last leaf becomes the new tail
+		let tail = self.leaf_for(self.len - 2);
+		let mut root = pop_tail(self.len, self.shift, &self.root).unwrap_or_else(|| new_node(None));
+		let mut shift = self.shift;
+		if shift > BITS && root.borrow().len() == 1 {
+			root = child(&root, 0);
+			shift -= BITS;
+		}
+
+		PArr::from_parts(self.len - 1, shift, root, tail)
+	}
+
+	/**
+	Creates an iterator over the array's elements.
+	*/
+	pub fn iter<'a>(&'a self) -> impl Iterator<Item = Val> + DoubleEndedIterator +
+	                                ExactSizeIterator + 'a {
+		(0 .. self.len).map(move |i| self.get_slot(i).root())
+	}
+
+	/**
+	Copies the array's elements into a new, mutable [`Arr`](struct.Arr.html).
+
+	Equivalent to [`(arr ..parr)`](https://gamelisp.rs/std/arr).
+	*/
+	pub fn to_arr(&self) -> Root<Arr> {
+		glsp::arr_from_iter(self.iter()).unwrap()
+	}
+
+	/**
+	Recursively copies the array and all of its contents.
+
+	Equivalent to [`(deep-clone parr)`](https://gamelisp.rs/std/deep-clone).
+	*/
+	pub fn deep_clone(&self) -> GResult<Root<PArr>> {
+		let mut slots = Vec::with_capacity(self.len);
+		for val in self.iter() {
+			slots.push(val.deep_clone()?.to_slot()?);
+		}
+
+		Ok(glsp::alloc(slots.into_iter().collect()))
+	}
+
+	/**
+	Equivalent to [`(eq? self other)`](https://gamelisp.rs/std/eq-p).
+
+	Two persistent arrays are equal if they have the same length, and each of their elements
+	are equal. As with [`Arr::try_eq`](struct.Arr.html#method.try_eq), this may need to invoke
+	an `op-eq?` method, so it can potentially fail.
+	*/
+	pub fn try_eq(&self, other: &PArr) -> GResult<bool> {
+		if self.len != other.len {
+			return Ok(false)
+		}
+
+		if self.root == other.root && self.tail == other.tail {
+			return Ok(true)
+		}
+
+		for (v0, v1) in self.iter().zip(other.iter()) {
+			if !v0.try_eq(&v1)? {
+				return Ok(false)
+			}
+		}
+
+		Ok(true)
+	}
+}
+
+fn new_path(level: u32, node: Gc<Arr>) -> Gc<Arr> {
+	if level == 0 {
+		node
+	} else {
+		new_node(Some(Slot::Arr(new_path(level - BITS, node))))
+	}
+}
+
+fn push_tail(len: usize, level: u32, parent: &Arr, tail: Gc<Arr>) -> Gc<Arr> {
+	let sub = ((len - 1) >> level) & MASK;
+	let mut slots = parent.borrow().clone();
+
+	let inserted = if level == BITS {
+		tail
+	} else if sub < slots.len() {
+		push_tail(len, level - BITS, &child(parent, sub), tail)
+	} else {
+		new_path(level - BITS, tail)
+	};
+
+	if sub < slots.len() {
+		slots[sub] = Slot::Arr(inserted);
+	} else {
+		slots.push_back(Slot::Arr(inserted));
+	}
+
+	new_node(slots)
+}
+
+fn assoc_in(level: u32, node: &Arr, i: usize, slot: Slot) -> Gc<Arr> {
+	if level == 0 {
+		with_slot(node, i & MASK, slot)
+	} else {
+		let sub = (i >> level) & MASK;
+		let replacement = assoc_in(level - BITS, &child(node, sub), i, slot);
+		with_slot(node, sub, Slot::Arr(replacement))
+	}
+}
+
+//removes the trie's last leaf. returns None if that leaves `node` empty.
+fn pop_tail(len: usize, level: u32, node: &Arr) -> Option<Gc<Arr>> {
+	let sub = ((len - 2) >> level) & MASK;
+
+	if level > BITS {
+		match pop_tail(len, level - BITS, &child(node, sub)) {
+			Some(replacement) => Some(with_slot(node, sub, Slot::Arr(replacement))),
+			None if sub == 0 => None,
+			None => {
+				let mut slots = node.borrow().clone();
+				slots.truncate(sub);
+				Some(new_node(slots))
+			}
+		}
+	} else if sub == 0 {
+		None
+	} else {
+		let mut slots = node.borrow().clone();
+		slots.truncate(sub);
+		Some(new_node(slots))
+	}
+}
+
+impl Hash for PArr {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.len.hash(state);
+		for i in 0 .. self.len {
+			self.get_slot(i).hash(state);
+		}
+	}
+}
+
+impl PartialEq<PArr> for PArr {
+	fn eq(&self, other: &PArr) -> bool {
+		self.try_eq(other).unwrap()
+	}
+}
+
+impl FromIterator<Slot> for PArr {
+	fn from_iter<I: IntoIterator<Item = Slot>>(iter: I) -> PArr {
+		//rather than calling conj_slot repeatedly, we could build the trie bottom-up. this
+		//is simpler, and it's only a constant factor slower.
+		let mut parr = PArr::new();
+		for slot in iter {
+			parr = parr.conj_slot(slot);
+		}
+
+		parr
+	}
+}
+
+
+//-------------------------------------------------------------------------------------------------
+// PTab
+//-------------------------------------------------------------------------------------------------
+
+/**
+The `ptab` primitive type: a persistent table.
+
+A `PTab` can't be mutated. Instead, methods like [`assoc`](#method.assoc) and
+[`dissoc`](#method.dissoc) return a new `PTab` which shares almost all of its storage with
+the original, so they only take `O(log n)` time. This makes it cheap to keep many snapshots of
+a large table.
+
+New persistent tables can be constructed using [`glsp::ptab_from_iter`](fn.ptab_from_iter.html),
+or from GameLisp code using [`(ptab ..entries)`](https://gamelisp.rs/std/ptab).
+*/
+
+pub struct PTab {
+	header: GcHeader,
+	len: usize,
+	root: Gc<Arr>
+}
+
+//a hash array mapped trie, using the CHAMP layout from steindorfer and vinju (2015). each node
+//is an arr laid out as [datamap, nodemap, k0, v0, k1, v1, ..., child0, child1, ...]. the two
+//bitmaps are stored as ints; for each five-bit chunk of a key's hash, the corresponding bit is
+//set in the datamap if the entry is stored inline, or in the nodemap if it's stored in a child
+//node. once all 32 bits of the hash have been consumed, colliding entries are stored in a
+//collision node, laid out as [#n, k0, v0, k1, v1, ...].
+//
+//removing an entry from a child which leaves that child with only one entry will move the
+//entry into the parent node. this keeps the trie as shallow as possible.
+
+const HASH_BITS: u32 = 32;
+
+impl Allocate for PTab {
+	fn header(&self) -> &GcHeader {
+		&self.header
+	}
+
+	fn visit_gcs<V: Visitor>(&self, visitor: &mut V) {
+		visitor.visit_gc(&self.root);
+	}
+
+	fn clear_gcs(&self) {
+		//the nodes are separately-allocated arrs, so any cycles will be broken by their own
+		//clear_gcs methods
+	}
+
+	fn owned_memory_usage(&self) -> usize {
+		0
+	}
+}
+
+fn hash_key(key: &Slot) -> u32 {
+	let mut hasher = FnvHasher::default();
+	key.hash(&mut hasher);
+	let hash = hasher.finish();
+
+	(hash ^ (hash >> 32)) as u32
+}
+
+fn bit_pos(hash: u32, shift: u32) -> u32 {
+	1 << ((hash >> shift) & MASK as u32)
+}
+
+fn bit_index(bitmap: u32, bit: u32) -> usize {
+	(bitmap & (bit - 1)).count_ones() as usize
+}
+
+//returns None for a collision node
+fn bitmaps(slots: &VecDeque<Slot>) -> Option<(u32, u32)> {
+	match (&slots[0], &slots[1]) {
+		(&Slot::Int(datamap), &Slot::Int(nodemap)) => Some((datamap as u32, nodemap as u32)),
+		(&Slot::Nil, _) => None,
+		_ => unreachable!()
+	}
+}
+
+fn lookup(node: &Arr, shift: u32, hash: u32, key: &Slot) -> Option<Slot> {
+	let slots = node.borrow();
+	let (datamap, nodemap) = match bitmaps(&slots) {
+		Some(maps) => maps,
+		None => {
+			let i = (1 .. slots.len()).step_by(2).find(|&i| slots[i] == *key)?;
+			return Some(slots[i + 1].clone())
+		}
+	};
+
+	let bit = bit_pos(hash, shift);
+	if datamap & bit != 0 {
+		let i = 2 + 2 * bit_index(datamap, bit);
+		if slots[i] == *key {
+			Some(slots[i + 1].clone())
+		} else {
+			None
+		}
+	} else if nodemap & bit != 0 {
+		let j = 2 + 2 * datamap.count_ones() as usize + bit_index(nodemap, bit);
+		match slots[j] {
+			Slot::Arr(ref child) => lookup(child, shift + BITS, hash, key),
+			_ => unreachable!()
+		}
+	} else {
+		None
+	}
+}
+
+//returns the new node, and `true` if an entry was added rather than replaced
+fn insert(node: &Arr, shift: u32, hash: u32, key: Slot, value: Slot) -> (Gc<Arr>, bool) {
+	let mut slots = node.borrow().clone();
+	let (datamap, nodemap) = match bitmaps(&slots) {
+		Some(maps) => maps,
+		None => {
+			match (1 .. slots.len()).step_by(2).find(|&i| slots[i] == key) {
+				Some(i) => {
+					slots[i + 1] = value;
+					return (new_node(slots), false)
+				}
+				None => {
+					slots.push_back(key);
+					slots.push_back(value);
+					return (new_node(slots), true)
+				}
+			}
+		}
+	};
+
+	let bit = bit_pos(hash, shift);
+	if datamap & bit != 0 {
+		let i = 2 + 2 * bit_index(datamap, bit);
+		if slots[i] == key {
+			slots[i + 1] = value;
+			return (new_node(slots), false)
+		}
+
+		//two keys share this position, so both entries are pushed down into a new child
+		let existing_value = slots.remove(i + 1).unwrap();
+		let existing_key = slots.remove(i).unwrap();
+		let existing_hash = hash_key(&existing_key);
+		let merged = merge(shift + BITS, (existing_hash, existing_key, existing_value),
+		                   (hash, key, value));
+
+		let datamap = datamap ^ bit;
+		let nodemap = nodemap | bit;
+		let j = 2 + 2 * datamap.count_ones() as usize + bit_index(nodemap, bit);
+		slots.insert(j, Slot::Arr(merged));
+		slots[0] = Slot::Int(datamap as i32);
+		slots[1] = Slot::Int(nodemap as i32);
+
+		(new_node(slots), true)
+	} else if nodemap & bit != 0 {
+		let j = 2 + 2 * datamap.count_ones() as usize + bit_index(nodemap, bit);
+		let (replacement, added) = insert(&child(node, j), shift + BITS, hash, key, value);
+		slots[j] = Slot::Arr(replacement);
+
+		(new_node(slots), added)
+	} else {
+		let i = 2 + 2 * bit_index(datamap, bit);
+		slots.insert(i, value);
+		slots.insert(i, key);
+		slots[0] = Slot::Int((datamap | bit) as i32);
+
+		(new_node(slots), true)
+	}
+}
+
+fn merge(shift: u32, entry0: (u32, Slot, Slot), entry1: (u32, Slot, Slot)) -> Gc<Arr> {
+	if shift >= HASH_BITS {
+		return new_node(vec![Slot::Nil, entry0.1, entry0.2, entry1.1, entry1.2])
+	}
+
+	let (bit0, bit1) = (bit_pos(entry0.0, shift), bit_pos(entry1.0, shift));
+	if bit0 == bit1 {
+		let merged = merge(shift + BITS, entry0, entry1);
+		new_node(vec![Slot::Int(0), Slot::Int(bit0 as i32), Slot::Arr(merged)])
+	} else {
+		let (first, second) = if bit0 < bit1 { (entry0, entry1) } else { (entry1, entry0) };
+		new_node(vec![Slot::Int((bit0 | bit1) as i32), Slot::Int(0),
+		              first.1, first.2, second.1, second.2])
+	}
+}
+
+//returns None if the key was not present
+fn remove(node: &Arr, shift: u32, hash: u32, key: &Slot) -> Option<Gc<Arr>> {
+	let mut slots = node.borrow().clone();
+	let (datamap, nodemap) = match bitmaps(&slots) {
+		Some(maps) => maps,
+		None => {
+			let i = (1 .. slots.len()).step_by(2).find(|&i| slots[i] == *key)?;
+			slots.remove(i + 1);
+			slots.remove(i);
+			return Some(new_node(slots))
+		}
+	};
+
+	let bit = bit_pos(hash, shift);
+	if datamap & bit != 0 {
+		let i = 2 + 2 * bit_index(datamap, bit);
+		if slots[i] != *key {
+			return None
+		}
+
+		slots.remove(i + 1);
+		slots.remove(i);
+		slots[0] = Slot::Int((datamap ^ bit) as i32);
+
+		Some(new_node(slots))
+	} else if nodemap & bit != 0 {
+		let j = 2 + 2 * datamap.count_ones() as usize + bit_index(nodemap, bit);
+		let replacement = remove(&child(node, j), shift + BITS, hash, key)?;
+
+		match singleton(&replacement) {
+			Some((k, v)) => {
+				slots.remove(j);
+
+				let datamap = datamap | bit;
+				let i = 2 + 2 * bit_index(datamap, bit);
+				slots.insert(i, v);
+				slots.insert(i, k);
+				slots[0] = Slot::Int(datamap as i32);
+				slots[1] = Slot::Int((nodemap ^ bit) as i32);
+			}
+			None => slots[j] = Slot::Arr(replacement)
+		}
+
+		Some(new_node(slots))
+	} else {
+		None
+	}
+}
+
+//if a node holds exactly one entry and no children, returns that entry
+fn singleton(node: &Arr) -> Option<(Slot, Slot)> {
+	let slots = node.borrow();
+	let is_singleton = match bitmaps(&slots) {
+		Some((datamap, nodemap)) => datamap.count_ones() == 1 && nodemap == 0,
+		None => slots.len() == 3
+	};
+
+	if is_singleton {
+		Some((slots[slots.len() - 2].clone(), slots[slots.len() - 1].clone()))
+	} else {
+		None
+	}
+}
+
+fn visit_entries<F: FnMut(&Slot, &Slot)>(node: &Arr, f: &mut F) {
+	let slots = node.borrow();
+	let (start, end) = match bitmaps(&slots) {
+		Some((datamap, _)) => (2, 2 + 2 * datamap.count_ones() as usize),
+		None => (1, slots.len())
+	};
+
+	for i in (start .. end).step_by(2) {
+		f(&slots[i], &slots[i + 1]);
+	}
+
+	for slot in slots.iter().skip(end) {
+		match *slot {
+			Slot::Arr(ref child) => visit_entries(child, f),
+			_ => unreachable!()
+		}
+	}
+}
+
+impl PTab {
+	pub(crate) fn new() -> PTab {
+		PTab::from_parts(0, new_node(vec![Slot::Int(0), Slot::Int(0)]))
+	}
+
+	fn from_parts(len: usize, root: Gc<Arr>) -> PTab {
+		let header = GcHeader::new();
+		header.freeze();
+
+		PTab { header, len, root }
+	}
+
+	/**
+	Returns the number of entries in the table.
+
+	Equivalent to [`(len ptab)`](https://gamelisp.rs/std/len).
+	*/
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	/**
+	Returns `true` if the table contains no entries.
+
+	Equivalent to [`(empty? ptab)`](https://gamelisp.rs/std/empty-p).
+	*/
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	pub(crate) fn get_slot(&self, key: &Slot) -> Option<Slot> {
+		lookup(&self.root, 0, hash_key(key), key)
+	}
+
+	/**
+	Accesses the value associated with a key.
+
+	Returns an error if the key is not present in the table.
+
+	Equivalent to [`[ptab key]`](https://gamelisp.rs/std/access).
+	*/
+	pub fn get<K: ToVal, V: FromVal>(&self, key: K) -> GResult<V> {
+		let key = key.to_slot()?;
+		match self.get_slot(&key) {
+			Some(value) => V::from_slot(&value),
+			None => bail!("attempted to access nonexistent key {} in a ptab", key.root())
+		}
+	}
+
+	/**
+	Accesses the value associated with a key, if it's present.
+
+	Equivalent to [`[ptab (? key)]`](https://gamelisp.rs/std/access).
+	*/
+	pub fn get_if_present<K: ToVal, V: FromVal>(&self, key: K) -> GResult<Option<V>> {
+		match self.get_slot(&key.to_slot()?) {
+			Some(value) => Ok(Some(V::from_slot(&value)?)),
+			None => Ok(None)
+		}
+	}
+
+	/**
+	Returns `true` if the given key is present in the table.
+
+	Equivalent to [`(has? ptab key)`](https://gamelisp.rs/std/has-p).
+	*/
+	pub fn has<K: ToVal>(&self, key: K) -> GResult<bool> {
+		Ok(self.get_slot(&key.to_slot()?).is_some())
+	}
+
+	/**
+	Returns a new table with the given entry added or replaced.
+
+	Equivalent to [`(passoc ptab key value)`](https://gamelisp.rs/std/passoc).
+	*/
+	pub fn assoc<K: ToVal, V: ToVal>(&self, key: K, value: V) -> GResult<Root<PTab>> {
+		Ok(glsp::alloc(self.assoc_slot(key.to_slot()?, value.to_slot()?)))
+	}
+
+	pub(crate) fn assoc_slot(&self, key: Slot, value: Slot) -> PTab {
+		let hash = hash_key(&key);
+		let (root, added) = insert(&self.root, 0, hash, key, value);
+
+		PTab::from_parts(if added { self.len + 1 } else { self.len }, root)
+	}
+
+	/**
+	Returns a new table with the given key removed. If the key isn't present, the new table
+	will be identical to this one.
+
+	Equivalent to [`(pdissoc ptab key)`](https://gamelisp.rs/std/pdissoc).
+	*/
+	pub fn dissoc<K: ToVal>(&self, key: K) -> GResult<Root<PTab>> {
+		Ok(glsp::alloc(self.dissoc_slot(&key.to_slot()?)))
+	}
+
+	pub(crate) fn dissoc_slot(&self, key: &Slot) -> PTab {
+		match remove(&self.root, 0, hash_key(key), key) {
+			Some(root) => PTab::from_parts(self.len - 1, root),
+			None => PTab::from_parts(self.len, self.root.clone())
+		}
+	}
+
+	pub(crate) fn entry_slots(&self) -> Vec<(Slot, Slot)> {
+		let mut entries = Vec::with_capacity(self.len);
+		visit_entries(&self.root, &mut |key, value| entries.push((key.clone(), value.clone())));
+		entries
+	}
+
+	/**
+	Returns the table's entries, in an unspecified order.
+	*/
+	pub fn entries(&self) -> Vec<(Val, Val)> {
+		self.entry_slots().into_iter().map(|(key, value)| (key.root(), value.root())).collect()
+	}
+
+	/**
+	Copies the table's entries into a new, mutable [`Tab`](struct.Tab.html).
+
+	Equivalent to [`(tab ..ptab)`](https://gamelisp.rs/std/tab).
+	*/
+	pub fn to_tab(&self) -> Root<Tab> {
+		glsp::tab_from_iter(self.entries()).unwrap()
+	}
+
+	/**
+	Recursively copies the table's keys and values.
+
+	Equivalent to [`(deep-clone ptab)`](https://gamelisp.rs/std/deep-clone).
+	*/
+	pub fn deep_clone(&self) -> GResult<Root<PTab>> {
+		let mut entries = Vec::with_capacity(self.len);
+		for (key, value) in self.entries() {
+			entries.push((key.deep_clone()?.to_slot()?, value.deep_clone()?.to_slot()?));
+		}
+
+		Ok(glsp::alloc(entries.into_iter().collect()))
+	}
+
+	/**
+	Equivalent to [`(eq? self other)`](https://gamelisp.rs/std/eq-p).
+
+	Two persistent tables are equal if they have the same keys, and each of their values are
+	equal. As with [`Tab::try_eq`](struct.Tab.html#method.try_eq), this may need to invoke
+	an `op-eq?` method, so it can potentially fail.
+	*/
+	pub fn try_eq(&self, other: &PTab) -> GResult<bool> {
+		if self.len != other.len {
+			return Ok(false)
+		}
+
+		if self.root == other.root {
+			return Ok(true)
+		}
+
+		for (key, v0) in self.entry_slots() {
+			match other.get_slot(&key) {
+				Some(v1) if v0.root().try_eq(&v1.root())? => (),
+				_ => return Ok(false)
+			}
+		}
+
+		Ok(true)
+	}
+}
+
+impl Hash for PTab {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		//entries are visited in an order which depends on the trie's history, so their hashes
+		//are combined using a commutative operation
+		let mut sum = 0u64;
+		visit_entries(&self.root, &mut |key, value| {
+			let mut hasher = FnvHasher::default();
+			key.hash(&mut hasher);
+			value.hash(&mut hasher);
+			sum = sum.wrapping_add(hasher.finish());
+		});
+
+		self.len.hash(state);
+		sum.hash(state);
+	}
+}
+
+impl PartialEq<PTab> for PTab {
+	fn eq(&self, other: &PTab) -> bool {
+		self.try_eq(other).unwrap()
+	}
+}
+
+impl FromIterator<(Slot, Slot)> for PTab {
+	fn from_iter<I: IntoIterator<Item = (Slot, Slot)>>(iter: I) -> PTab {
+		let mut ptab = PTab::new();
+		for (key, value) in iter {
+			ptab = ptab.assoc_slot(key, value);
+		}
+
+		ptab
+	}
+}
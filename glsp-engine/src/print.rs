@@ -8,14 +8,18 @@ use std::fmt::{self, Debug, Display, Formatter, Pointer};
 use super::{encoder, transform};
 use super::ast::{Ast};
 use super::class::{Class, Obj};
-use super::code::{Bytecode, Coro, GFn, Instr, ParamMap};
+use super::code::{Bytecode, Coro, GFn, Instr, JumpBytes, Lambda, ParamMap, SymBytes};
 use super::collections::{Arr, DequeAccess, DequeOps, Str, Tab};
-use super::engine::{glsp, RData, RFn, Span, Sym, stock_syms::*};
+use super::engine::{glsp, Guard, RData, RFn, Span, Sym, stock_syms::*};
 use super::error::{GResult};
 use super::eval::{Expander};
 use super::gc::{Allocate, Gc, Root, Slot};
 use super::iter::{GIter};
-use super::val::{Val};
+use super::persist::{PArr, PTab};
+use super::record::{Rec};
+use super::vecmath::{Mat3, Vec2, Vec3};
+use super::view::{View};
+use super::val::{Flo, Val};
 use super::wrap::{CallableOps, ToVal};
 
 /*
@@ -89,6 +93,7 @@ impl Val {
 		match self {
 			Val::Arr(arr) => arr.repr_test(stack, lenient_syms),
 			Val::Tab(tab) => tab.repr_test(stack, lenient_syms),
+			Val::Rec(rec) => rec.repr_test(stack, lenient_syms),
 			Val::Sym(sym) => {
 				if lenient_syms {
 					Ok(())
@@ -106,6 +111,12 @@ impl Val {
 			Val::RFn(_) => Err("functions are non-representable"),
 			Val::Coro(_) => Err("coroutines are non-representable"),
 			Val::RData(_) => Err("rust data is non-representable"),
+			Val::PArr(_) => Err("parrs are non-representable"),
+			Val::PTab(_) => Err("ptabs are non-representable"),
+			Val::View(_) => Err("views are non-representable"),
+			Val::Vec2(_) => Err("vec2s are non-representable"),
+			Val::Vec3(_) => Err("vec3s are non-representable"),
+			Val::Mat3(_) => Err("mat3s are non-representable"),
 		}
 	}
 }
@@ -192,6 +203,41 @@ impl Tab {
 	}
 }
 
+impl Rec {
+	///Returns `Ok` if this record can be losslessly converted to text.
+	pub fn check_representability(&self) -> Result<(), &'static str> {
+		let mut stack = SmallVec::new();
+		self.repr_test(&mut stack, false)
+	}
+
+	///Returns `Ok` if this record can be serialized and deserialized using Serde.
+	#[cfg(feature = "serde")]
+	pub fn check_serializability(&self) -> Result<(), &'static str> {
+		let mut stack = SmallVec::new();
+		self.repr_test(&mut stack, true)
+	}
+
+	fn repr_test(
+		&self, 
+		stack: &mut SmallVec<[usize; 32]>,
+		lenient_syms: bool
+	) -> Result<(), &'static str> {
+		
+		let address = self as *const Rec as usize;
+		if stack.contains(&address) {
+			return Err("reference cycles are non-representable")
+		}
+
+		stack.push(address);
+		for val in self.field_vals() {
+			val.repr_test(stack, lenient_syms)?;
+		}
+		stack.pop().unwrap();
+
+		Ok(())
+	}
+}
+
 impl Sym {
 	///Returns `Ok` if this symbol can be losslessly converted to text.
 	pub fn check_representability(&self) -> Result<(), &'static str> {
@@ -236,7 +282,8 @@ macro_rules! impl_forwarding_debug {
 	);
 }
 
-impl_forwarding_debug!(Arr, Tab, GIter, Sym, Obj, Class, GFn, RFn, Coro, RData);
+impl_forwarding_debug!(Arr, Tab, Rec, PArr, PTab, GIter, Sym, Obj, Class, GFn, RFn, Coro, RData, View,
+                       Vec2, Vec3, Mat3);
 
 // Root, Gc
 //------------------------------
@@ -306,8 +353,8 @@ impl Display for Val {
 			Val::Nil => write!(f, "#n"),
 			Val::Int(i) => write!(f, "{}", i),
 			Val::Char(ch) => write!(f, "{}", ch),
-			Val::Flo(flo) if flo == f32::INFINITY => write!(f, "+inf.0"),
-			Val::Flo(flo) if flo == f32::NEG_INFINITY => write!(f, "-inf.0"),
+			Val::Flo(flo) if flo == Flo::INFINITY => write!(f, "+inf.0"),
+			Val::Flo(flo) if flo == Flo::NEG_INFINITY => write!(f, "-inf.0"),
 			Val::Flo(flo) if flo.is_nan() => write!(f, "nan.0"),
 			Val::Flo(flo) => {
 				use std::io::Write;
@@ -346,7 +393,20 @@ impl Display for Val {
 			Val::Class(ref root) => write!(f, "{}", root),
 			Val::GFn(ref root) => write!(f, "{}", root),
 			Val::Coro(ref root) => write!(f, "{}", root),
-			Val::RData(ref root) => write!(f, "{}", root)
+			Val::RData(ref root) => write!(f, "{}", root),
+			Val::Rec(ref root) => {
+				if f.alternate() {
+					write!(f, "{:#}", root)
+				} else {
+					write!(f, "{}", root)
+				}
+			}
+			Val::PArr(ref root) => write!(f, "{}", root),
+			Val::PTab(ref root) => write!(f, "{}", root),
+			Val::View(ref root) => write!(f, "{}", root),
+			Val::Vec2(ref root) => write!(f, "{}", root),
+			Val::Vec3(ref root) => write!(f, "{}", root),
+			Val::Mat3(ref root) => write!(f, "{}", root)
 		}
 	}
 }
@@ -455,7 +515,59 @@ impl Display for RData {
 	}
 }
 
-// Arr, Tab
+impl Display for View {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		match self.index() {
+			Some(index) => write!(f, "#<view:{}:{}>", self.type_name(), index),
+			None => write!(f, "#<view:{}>", self.type_name())
+		}
+	}
+}
+
+// Vec2, Vec3, Mat3
+//------------------------------
+
+//each component is printed in the same way as a flo
+fn write_components(f: &mut Formatter, components: &[Flo]) -> fmt::Result {
+	for (i, &component) in components.iter().enumerate() {
+		if i > 0 {
+			write!(f, " ")?;
+		}
+		write!(f, "{}", Val::Flo(component))?;
+	}
+
+	Ok(())
+}
+
+impl Display for Vec2 {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		write!(f, "#<vec2 ")?;
+		write_components(f, &self.to_array())?;
+		write!(f, ">")
+	}
+}
+
+impl Display for Vec3 {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		write!(f, "#<vec3 ")?;
+		write_components(f, &self.to_array())?;
+		write!(f, ">")
+	}
+}
+
+impl Display for Mat3 {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		write!(f, "#<mat3")?;
+		for col in self.to_cols_array_2d().iter() {
+			write!(f, " (")?;
+			write_components(f, col)?;
+			write!(f, ")")?;
+		}
+		write!(f, ">")
+	}
+}
+
+// Arr, Tab, Rec
 //------------------------------
 
 impl Display for Arr {
@@ -482,6 +594,32 @@ impl Display for Tab {
 	}
 }
 
+impl Display for Rec {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		if f.alternate() {
+			let text = PrettyPrinter::new().print_rec(self);
+			write!(f, "{}", text)
+		} else {
+			let mut parents = SmallVec::<[usize; 64]>::new();
+			ugly_print_rec(self, f, &mut parents)
+		}
+	}
+}
+
+impl Display for PArr {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		let mut parents = SmallVec::<[usize; 64]>::new();
+		ugly_print_parr(self, f, &mut parents)
+	}
+}
+
+impl Display for PTab {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		let mut parents = SmallVec::<[usize; 64]>::new();
+		ugly_print_ptab(self, f, &mut parents)
+	}
+}
+
 
 //-------------------------------------------------------------------------------------------------
 // the ugly-printer
@@ -496,6 +634,9 @@ fn ugly_print_val(
 	match val {
 		Val::Arr(ref arr) => ugly_print_arr(arr, f, parents),
 		Val::Tab(ref tab) => ugly_print_tab(tab, f, parents),
+		Val::Rec(ref rec) => ugly_print_rec(rec, f, parents),
+		Val::PArr(ref parr) => ugly_print_parr(parr, f, parents),
+		Val::PTab(ref ptab) => ugly_print_ptab(ptab, f, parents),
 		_ => write!(f, "{:?}", val)
 	}
 }
@@ -584,6 +725,90 @@ fn ugly_print_tab(
 	Ok(())
 }
 
+fn ugly_print_rec(
+	rec: &Rec, 
+	f: &mut Formatter, 
+	parents: &mut SmallVec<[usize; 64]>
+) -> fmt::Result {
+
+	let address = rec as *const Rec as usize;
+	for (i, parent_address) in parents.iter().rev().enumerate() {
+		if *parent_address == address {
+			return write!(f, "#<cycle:{}>", i)
+		}
+	}
+
+	parents.push(address);
+
+	write!(f, "#rec({}", rec.name())?;
+	for (name, value) in rec.field_names().iter().zip(rec.field_vals()) {
+		write!(f, " ({} ", name)?;
+		ugly_print_val(&value, f, parents)?;
+		write!(f, ")")?;
+	}
+	write!(f, ")")?;
+
+	parents.pop().unwrap();
+	Ok(())
+}
+
+//persistent collections can't be parsed, so they're printed in #<angle-brackets>, but we still
+//print their contents to make debugging easier
+fn ugly_print_parr(
+	parr: &PArr, 
+	f: &mut Formatter, 
+	parents: &mut SmallVec<[usize; 64]>
+) -> fmt::Result {
+
+	let address = parr as *const PArr as usize;
+	for (i, parent_address) in parents.iter().rev().enumerate() {
+		if *parent_address == address {
+			return write!(f, "#<cycle:{}>", i)
+		}
+	}
+
+	parents.push(address);
+
+	write!(f, "#<parr")?;
+	for val in parr.iter() {
+		write!(f, " ")?;
+		ugly_print_val(&val, f, parents)?;
+	}
+	write!(f, ">")?;
+
+	parents.pop().unwrap();
+	Ok(())
+}
+
+fn ugly_print_ptab(
+	ptab: &PTab, 
+	f: &mut Formatter, 
+	parents: &mut SmallVec<[usize; 64]>
+) -> fmt::Result {
+
+	let address = ptab as *const PTab as usize;
+	for (i, parent_address) in parents.iter().rev().enumerate() {
+		if *parent_address == address {
+			return write!(f, "#<cycle:{}>", i)
+		}
+	}
+
+	parents.push(address);
+
+	write!(f, "#<ptab")?;
+	for (key, value) in ptab.entries() {
+		write!(f, " (")?;
+		ugly_print_val(&key, f, parents)?;
+		write!(f, " ")?;
+		ugly_print_val(&value, f, parents)?;
+		write!(f, ")")?;
+	}
+	write!(f, ">")?;
+
+	parents.pop().unwrap();
+	Ok(())
+}
+
 fn detect_abbrv(arr: &Arr) -> Option<(&'static str, Val)> {
 	if arr.len() == 2 {
 		let form = arr.get::<Val>(1).unwrap();
@@ -632,7 +857,7 @@ const INDENT_INCREMENT: usize = 2;
 
 //we could use stock syms here, but it ends up clogging up the stock syms database pretty badly,
 //in exchange for not-very-much performance gain.
-static BREAKING_SYMS: [(&str, usize); 48] = [
+static BREAKING_SYMS: [(&str, usize); 49] = [
 	("do", 1),
 	("do-0", 1),
 	("cond", 1),
@@ -651,6 +876,7 @@ static BREAKING_SYMS: [(&str, usize); 48] = [
 	("fini-mixin", 1),
 	("try", 1),
 	("try-verbose", 1),
+	("try-chain", 1),
 
 	("if", 2),
 	("block", 2),
@@ -721,11 +947,17 @@ impl PrettyPrinter {
 		self.builder
 	}
 
+	fn print_rec(mut self, rec: &Rec) -> String {
+		self.recursively_build_rec(rec);
+		self.builder
+	}
+
 	//returns `true` if this val or any of its children contained at least one breaking form.
 	fn recursively_build_val(&mut self, arg: &Val) -> bool {
 		match *arg {
 			Val::Arr(ref arr) => self.recursively_build_arr(arr),
 			Val::Tab(ref tab) => self.recursively_build_tab(tab),
+			Val::Rec(ref rec) => self.recursively_build_rec(rec),
 			_ => {
 				self.push_str(&format!("{:?}", arg));
 				false
@@ -803,6 +1035,39 @@ impl PrettyPrinter {
 		result
 	}
 
+	fn recursively_build_rec(&mut self, rec: &Rec) -> bool {
+		//check for cycles
+		let address = rec as &Rec as *const Rec as usize;
+
+		for (i, parent) in self.parents.iter().rev().enumerate() {
+			if *parent == address {
+				self.push_str(&format!("#<cycle:{}>", i));
+				return false
+			}
+		}
+
+		//construct the sequence. a rec's fields are printed like a tab's entries
+		let mut sequence = SmallVec::<[Val; 8]>::with_capacity(rec.len() * 2);
+		for (name, value) in rec.field_names().iter().zip(rec.field_vals()) {
+			sequence.push(Val::Sym(*name));
+			sequence.push(value);
+		}
+
+		//delegate to recursively_build_sequence()
+		self.parents.push(address);
+
+		self.push_str(&format!("#rec({}", rec.name()));
+		if !sequence.is_empty() {
+			self.push_str(" ");
+		}
+		let result = self.recursively_build_sequence(&sequence[..], true);
+		self.push_str(")");
+
+		self.parents.pop().unwrap();
+
+		result
+	}
+
 	//we convert both arrs and tabs into a uniform format: an array of vals and an "is a tab" 
 	//flag. tabs stringify two vals at a time, arrs stringify each val individually.
 	fn recursively_build_sequence(&mut self, sequence: &[Val], is_tab: bool) -> bool {
@@ -1065,7 +1330,7 @@ pub(crate) fn instr_to_string(instr: Instr, local_count: u8, scratch_count: u8)
 		CopyRegister(dst, src) => {
 			format!("CopyRegister(dst_reg: {}, src_reg: {})", reg(dst), reg(src))
 		}
-		LoadGlobal(dst, sym_bytes) => {
+		LoadGlobal(dst, sym_bytes, _) => {
 			format!("LoadGlobal(dst_reg: {}, sym: {})", reg(dst), Sym::from(sym_bytes))
 		}
 		SetGlobal(src, sym_bytes) => {
@@ -1098,6 +1363,19 @@ pub(crate) fn instr_to_string(instr: Instr, local_count: u8, scratch_count: u8)
 			format!("CallN(dst_reg: {}, base_reg: {}, arg_count: {})", 
 			        reg(dst), reg(base), arg_count)
 		}
+		TailCall0(callee) => {
+			format!("TailCall0(callee_reg: {})", reg(callee))
+		}
+		TailCall1(callee, arg) => {
+			format!("TailCall1(callee_reg: {}, arg_reg: {})", reg(callee), reg(arg))
+		}
+		TailCall2(callee, arg0, arg1) => {
+			format!("TailCall2(callee_reg: {}, arg0_reg: {}, arg1_reg: {})",
+			        reg(callee), reg(arg0), reg(arg1))
+		}
+		TailCallN(base, arg_count) => {
+			format!("TailCallN(base_reg: {}, arg_count: {})", reg(base), arg_count)
+		}
 		Splay(bits) => {
 			format!("Splay(bits: {:?})", bits)
 		}
@@ -1116,6 +1394,26 @@ pub(crate) fn instr_to_string(instr: Instr, local_count: u8, scratch_count: u8)
 		JumpIfFalse(src, jump_bytes) => {
 			format!("JumpIfFalse(src_reg: {}, jump: {})", reg(src), isize::from(jump_bytes))
 		}
+		NumEqJumpIfFalse(dst, arg0, arg1, jump_bytes) => {
+			format!("NumEqJumpIfFalse(dst_reg: {}, arg0_reg: {}, arg1_reg: {}, jump: {})", 
+			        reg(dst), reg(arg0), reg(arg1), isize::from(jump_bytes))
+		}
+		LtJumpIfFalse(dst, arg0, arg1, jump_bytes) => {
+			format!("LtJumpIfFalse(dst_reg: {}, arg0_reg: {}, arg1_reg: {}, jump: {})", 
+			        reg(dst), reg(arg0), reg(arg1), isize::from(jump_bytes))
+		}
+		LteJumpIfFalse(dst, arg0, arg1, jump_bytes) => {
+			format!("LteJumpIfFalse(dst_reg: {}, arg0_reg: {}, arg1_reg: {}, jump: {})", 
+			        reg(dst), reg(arg0), reg(arg1), isize::from(jump_bytes))
+		}
+		GtJumpIfFalse(dst, arg0, arg1, jump_bytes) => {
+			format!("GtJumpIfFalse(dst_reg: {}, arg0_reg: {}, arg1_reg: {}, jump: {})", 
+			        reg(dst), reg(arg0), reg(arg1), isize::from(jump_bytes))
+		}
+		GteJumpIfFalse(dst, arg0, arg1, jump_bytes) => {
+			format!("GteJumpIfFalse(dst_reg: {}, arg0_reg: {}, arg1_reg: {}, jump: {})", 
+			        reg(dst), reg(arg0), reg(arg1), isize::from(jump_bytes))
+		}
 		PushDefer(defer) => {
 			format!("PushDefer(defer: {})", defer)
 		}
@@ -1157,6 +1455,33 @@ pub(crate) fn instr_to_string(instr: Instr, local_count: u8, scratch_count: u8)
 		OpSign(dst, arg) => {
 			format!("OpSign(dst_reg: {}, arg_reg: {})", reg(dst), reg(arg))
 		}
+		OpBitAnd(dst, arg0, arg1) => {
+			format!("OpBitAnd(dst_reg: {}, arg0_reg: {}, arg1_reg: {})", 
+			        reg(dst), reg(arg0), reg(arg1))
+		}
+		OpBitOr(dst, arg0, arg1) => {
+			format!("OpBitOr(dst_reg: {}, arg0_reg: {}, arg1_reg: {})", 
+			        reg(dst), reg(arg0), reg(arg1))
+		}
+		OpBitXor(dst, arg0, arg1) => {
+			format!("OpBitXor(dst_reg: {}, arg0_reg: {}, arg1_reg: {})", 
+			        reg(dst), reg(arg0), reg(arg1))
+		}
+		OpBitNot(dst, arg) => {
+			format!("OpBitNot(dst_reg: {}, arg_reg: {})", reg(dst), reg(arg))
+		}
+		OpShl(dst, arg0, arg1) => {
+			format!("OpShl(dst_reg: {}, arg0_reg: {}, arg1_reg: {})", 
+			        reg(dst), reg(arg0), reg(arg1))
+		}
+		OpShr(dst, arg0, arg1) => {
+			format!("OpShr(dst_reg: {}, arg0_reg: {}, arg1_reg: {})", 
+			        reg(dst), reg(arg0), reg(arg1))
+		}
+		OpSar(dst, arg0, arg1) => {
+			format!("OpSar(dst_reg: {}, arg0_reg: {}, arg1_reg: {})", 
+			        reg(dst), reg(arg0), reg(arg1))
+		}
 		OpMin(dst, arg0, arg1) => {
 			format!("OpMin(dst_reg: {}, arg0_reg: {}, arg1_reg: {})", 
 			        reg(dst), reg(arg0), reg(arg1))
@@ -1232,11 +1557,11 @@ pub(crate) fn instr_to_string(instr: Instr, local_count: u8, scratch_count: u8)
 			format!("OpArr(dst_reg: {}, arg0_reg: {}, arg_count: {})",
 			        reg(dst), reg(arg0), arg_count)
 		}
-		OpCallMeth(dst, arg0, arg_count) => {
+		OpCallMeth(dst, arg0, arg_count, _) => {
 			format!("OpCallMeth(dst_reg: {}, arg0_reg: {}, arg_count: {})",
 			        reg(dst), reg(arg0), arg_count)
 		}
-		OpCallMethOpt(dst, arg0, arg_count) => {
+		OpCallMethOpt(dst, arg0, arg_count, _) => {
 			format!("OpCallMethOpt(dst_reg: {}, arg0_reg: {}, arg_count: {})",
 			        reg(dst), reg(arg0), arg_count)
 		}
@@ -1254,6 +1579,268 @@ pub(crate) fn instr_to_string(instr: Instr, local_count: u8, scratch_count: u8)
 	}
 }
 
+
+//-------------------------------------------------------------------------------------------------
+// disassembly
+//-------------------------------------------------------------------------------------------------
+
+/*
+the output of glsp::disassemble() needs to stay stable, because it's used for golden tests of
+the optimizer. for example:
+
+	fn count-down
+	  params: 1 basic, 0 optional, no rest
+	  registers: 2 locals, 1 scratch, 3 literals
+	  code:
+	  L0:
+	       0  GtJumpIfFalse    scr0, loc0, lit0=0, L1        ; example.glsp:3
+	       1  OpAdd            loc1, loc1, loc0              ; example.glsp:4
+	       2  OpSub            loc0, loc0, lit1=1            ; example.glsp:5
+	       3  Jump             L0                            ; example.glsp:3
+	  L1:
+	       4  Return           loc1
+
+each instr is printed as its index, its mnemonic (the name of its Instr variant) and its 
+operands. registers are named locN, scrN or litN, and literal registers are annotated with 
+their value. jump targets and defer entry points are replaced with labels, which are numbered
+in instr order. an instr's file location is only printed when it differs from the previous
+instr's. lambdas are printed after the code which contains them, indented by four spaces.
+*/
+
+pub(crate) fn disassemble_gfn(gfn: &GFn) -> String {
+	let mut builder = String::new();
+	disassemble_lambda(&mut builder, &gfn.lambda, "");
+	builder
+}
+
+fn disassemble_lambda(builder: &mut String, lambda: &Lambda, pad: &str) {
+	match lambda.name {
+		Some(name) => builder.push_str(&format!("{}fn {}", pad, name)),
+		None => builder.push_str(&format!("{}fn", pad))
+	}
+
+	if lambda.yields {
+		builder.push_str(" (yields)");
+	}
+
+	let param_map = &lambda.param_map;
+	builder.push_str(&format!("\n{}  params: {} basic, {} optional, {}\n", pad, 
+	                          param_map.basic_param_count, param_map.opt_param_count,
+	                          if param_map.rest_param.is_some() { "rest" } else { "no rest" }));
+
+	if !lambda.captures.is_empty() {
+		let captures: Vec<String> = lambda.captures.iter().map(|id| {
+			format!("stay{}", id)
+		}).collect();
+		builder.push_str(&format!("{}  captures: {}\n", pad, captures.join(", ")));
+	}
+
+	disassemble_bytecode(builder, &lambda.bytecode, pad);
+}
+
+fn disassemble_bytecode(builder: &mut String, code: &Bytecode, pad: &str) {
+	let plural = |count: u8, noun: &str| {
+		format!("{} {}{}", count, noun, if count == 1 { "" } else { "s" })
+	};
+
+	builder.push_str(&format!("{}  registers: {}, {} scratch, {}\n", pad,
+	                          plural(code.local_count, "local"), code.scratch_count,
+	                          plural(code.literal_count, "literal")));
+
+	if !code.start_stays.is_empty() {
+		let stays: Vec<String> = code.start_stays.iter().enumerate().map(|(i, source)| {
+			format!("stay{}={:?}", i, source)
+		}).collect();
+
+		builder.push_str(&format!("{}  stays: {}\n", pad, stays.join(", ")));
+	}
+
+	let mut targets: Vec<usize> = code.instrs.iter().enumerate().filter_map(|(i, instr)| {
+		instr.jump_bytes().map(|jump_bytes| jump_target(i, jump_bytes))
+	}).chain(code.defers.iter().copied()).collect();
+
+	targets.sort_unstable();
+	targets.dedup();
+
+	let label = |target: usize| format!("L{}", targets.binary_search(&target).unwrap());
+
+	builder.push_str(&format!("{}  code:\n", pad));
+
+	let mut prev_location = None;
+	for (i, instr) in code.instrs.iter().enumerate() {
+		if targets.binary_search(&i).is_ok() {
+			builder.push_str(&format!("{}  {}:\n", pad, label(i)));
+		}
+
+		let (mnemonic, operands) = disassemble_instr(*instr, i, code, &label);
+		let mut line = format!("{}  {:>6}  {:<17}{}", pad, i, mnemonic, operands.join(", "));
+
+		let location = code.spans.get(i).and_then(|&span| span_location(span));
+		if location.is_some() && location != prev_location {
+			let width = pad.len() + 56;
+			line = format!("{:<width$} ; {}", line, location.as_ref().unwrap(), width = width);
+		}
+		prev_location = location;
+
+		builder.push_str(line.trim_end());
+		builder.push('\n');
+	}
+
+	//a jump can target the end of the bytecode
+	if targets.last() == Some(&code.instrs.len()) {
+		builder.push_str(&format!("{}  {}:\n", pad, label(code.instrs.len())));
+	}
+
+	let lambda_pad = format!("{}    ", pad);
+	for (i, lambda) in code.lambdas.iter().enumerate() {
+		builder.push_str(&format!("\n{}  lambda{}:\n", pad, i));
+		disassemble_lambda(builder, lambda, &lambda_pad);
+	}
+}
+
+fn jump_target(instr_i: usize, jump_bytes: JumpBytes) -> usize {
+	(instr_i as isize + 1 + isize::from(jump_bytes)) as usize
+}
+
+fn span_location(span: Span) -> Option<String> {
+	let mut location = String::new();
+	if glsp::span_file_location(&mut location, span).unwrap() {
+		Some(location)
+	} else {
+		None
+	}
+}
+
+fn disassemble_instr<L: Fn(usize) -> String>(
+	instr: Instr,
+	instr_i: usize,
+	code: &Bytecode,
+	label: &L
+) -> (&'static str, Vec<String>) {
+	use Instr::*;
+
+	let local_count = code.local_count as usize;
+	let scratch_count = code.scratch_count as usize;
+
+	let reg = |reg_id: u8| -> String {
+		let reg_id = reg_id as usize;
+		if reg_id < local_count {
+			format!("loc{}", reg_id)
+		} else if reg_id < local_count + scratch_count {
+			format!("scr{}", reg_id - local_count)
+		} else {
+			let literal = format!("{:?}", code.start_regs[reg_id].root());
+			let literal = if literal.chars().count() > 40 {
+				format!("{}...", literal.chars().take(37).collect::<String>())
+			} else {
+				literal
+			};
+
+			format!("lit{}={}", reg_id - (local_count + scratch_count), literal)
+		}
+	};
+
+	let jump = |jump_bytes: JumpBytes| label(jump_target(instr_i, jump_bytes));
+	let defer = |defer_id: u8| label(code.defers[defer_id as usize]);
+	let sym = |sym_bytes: SymBytes| Sym::from(sym_bytes).to_string();
+
+	match instr {
+		CopyRegister(dst, src) => ("CopyRegister", vec![reg(dst), reg(src)]),
+		LoadGlobal(dst, sym_bytes, _) => ("LoadGlobal", vec![reg(dst), sym(sym_bytes)]),
+		SetGlobal(src, sym_bytes) => ("SetGlobal", vec![reg(src), sym(sym_bytes)]),
+		LoadStay(dst, stay_id) => ("LoadStay", vec![reg(dst), format!("stay{}", stay_id)]),
+		SetStay(src, stay_id) => ("SetStay", vec![reg(src), format!("stay{}", stay_id)]),
+		MakeStay(src, stay_id) => ("MakeStay", vec![reg(src), format!("stay{}", stay_id)]),
+		MakeGFn(dst, lambda_id) => ("MakeGFn", vec![reg(dst), format!("lambda{}", lambda_id)]),
+		Call0(dst, callee) => ("Call0", vec![reg(dst), reg(callee)]),
+		Call1(dst, callee, arg) => ("Call1", vec![reg(dst), reg(callee), reg(arg)]),
+		Call2(dst, callee, arg0, arg1) => {
+			("Call2", vec![reg(dst), reg(callee), reg(arg0), reg(arg1)])
+		}
+		CallN(dst, base, arg_count) => ("CallN", vec![reg(dst), reg(base), arg_count.to_string()]),
+		TailCall0(callee) => ("TailCall0", vec![reg(callee)]),
+		TailCall1(callee, arg) => ("TailCall1", vec![reg(callee), reg(arg)]),
+		TailCall2(callee, arg0, arg1) => ("TailCall2", vec![reg(callee), reg(arg0), reg(arg1)]),
+		TailCallN(base, arg_count) => ("TailCallN", vec![reg(base), arg_count.to_string()]),
+		Splay(bits) => ("Splay", vec![format!("{:#b}", u32::from_ne_bytes(bits))]),
+		Return(src) => ("Return", vec![reg(src)]),
+		Yield(dst, src) => ("Yield", vec![reg(dst), reg(src)]),
+		Jump(jump_bytes) => ("Jump", vec![jump(jump_bytes)]),
+		JumpIfTrue(src, jump_bytes) => ("JumpIfTrue", vec![reg(src), jump(jump_bytes)]),
+		JumpIfFalse(src, jump_bytes) => ("JumpIfFalse", vec![reg(src), jump(jump_bytes)]),
+		NumEqJumpIfFalse(dst, arg0, arg1, jump_bytes) => {
+			("NumEqJumpIfFalse", vec![reg(dst), reg(arg0), reg(arg1), jump(jump_bytes)])
+		}
+		LtJumpIfFalse(dst, arg0, arg1, jump_bytes) => {
+			("LtJumpIfFalse", vec![reg(dst), reg(arg0), reg(arg1), jump(jump_bytes)])
+		}
+		LteJumpIfFalse(dst, arg0, arg1, jump_bytes) => {
+			("LteJumpIfFalse", vec![reg(dst), reg(arg0), reg(arg1), jump(jump_bytes)])
+		}
+		GtJumpIfFalse(dst, arg0, arg1, jump_bytes) => {
+			("GtJumpIfFalse", vec![reg(dst), reg(arg0), reg(arg1), jump(jump_bytes)])
+		}
+		GteJumpIfFalse(dst, arg0, arg1, jump_bytes) => {
+			("GteJumpIfFalse", vec![reg(dst), reg(arg0), reg(arg1), jump(jump_bytes)])
+		}
+		PushDefer(defer_id) => ("PushDefer", vec![defer(defer_id)]),
+		RunAndPopDefers(defer_count) => ("RunAndPopDefers", vec![defer_count.to_string()]),
+		RunDefer(defer_id) => ("RunDefer", vec![defer(defer_id)]),
+		EndDefer() => ("EndDefer", vec![]),
+		OpAdd(dst, arg0, arg1) => ("OpAdd", vec![reg(dst), reg(arg0), reg(arg1)]),
+		OpSub(dst, arg0, arg1) => ("OpSub", vec![reg(dst), reg(arg0), reg(arg1)]),
+		OpMul(dst, arg0, arg1) => ("OpMul", vec![reg(dst), reg(arg0), reg(arg1)]),
+		OpDiv(dst, arg0, arg1) => ("OpDiv", vec![reg(dst), reg(arg0), reg(arg1)]),
+		OpRem(dst, arg0, arg1) => ("OpRem", vec![reg(dst), reg(arg0), reg(arg1)]),
+		OpAbs(dst, arg) => ("OpAbs", vec![reg(dst), reg(arg)]),
+		OpNeg(dst, arg) => ("OpNeg", vec![reg(dst), reg(arg)]),
+		OpSign(dst, arg) => ("OpSign", vec![reg(dst), reg(arg)]),
+		OpBitAnd(dst, arg0, arg1) => ("OpBitAnd", vec![reg(dst), reg(arg0), reg(arg1)]),
+		OpBitOr(dst, arg0, arg1) => ("OpBitOr", vec![reg(dst), reg(arg0), reg(arg1)]),
+		OpBitXor(dst, arg0, arg1) => ("OpBitXor", vec![reg(dst), reg(arg0), reg(arg1)]),
+		OpBitNot(dst, arg) => ("OpBitNot", vec![reg(dst), reg(arg)]),
+		OpShl(dst, arg0, arg1) => ("OpShl", vec![reg(dst), reg(arg0), reg(arg1)]),
+		OpShr(dst, arg0, arg1) => ("OpShr", vec![reg(dst), reg(arg0), reg(arg1)]),
+		OpSar(dst, arg0, arg1) => ("OpSar", vec![reg(dst), reg(arg0), reg(arg1)]),
+		OpMin(dst, arg0, arg1) => ("OpMin", vec![reg(dst), reg(arg0), reg(arg1)]),
+		OpMax(dst, arg0, arg1) => ("OpMax", vec![reg(dst), reg(arg0), reg(arg1)]),
+		OpPredicate(dst, arg, predicate) => {
+			("OpPredicate", vec![reg(dst), reg(arg), format!("{:?}", predicate)])
+		}
+		OpInt(dst, arg) => ("OpInt", vec![reg(dst), reg(arg)]),
+		OpFlo(dst, arg) => ("OpFlo", vec![reg(dst), reg(arg)]),
+		OpBool(dst, arg) => ("OpBool", vec![reg(dst), reg(arg)]),
+		OpNumEq(dst, arg0, arg1) => ("OpNumEq", vec![reg(dst), reg(arg0), reg(arg1)]),
+		OpLt(dst, arg0, arg1) => ("OpLt", vec![reg(dst), reg(arg0), reg(arg1)]),
+		OpLte(dst, arg0, arg1) => ("OpLte", vec![reg(dst), reg(arg0), reg(arg1)]),
+		OpGt(dst, arg0, arg1) => ("OpGt", vec![reg(dst), reg(arg0), reg(arg1)]),
+		OpGte(dst, arg0, arg1) => ("OpGte", vec![reg(dst), reg(arg0), reg(arg1)]),
+		OpNot(dst, arg) => ("OpNot", vec![reg(dst), reg(arg)]),
+		OpIter(dst, arg) => ("OpIter", vec![reg(dst), reg(arg)]),
+		OpIterNext(dst, arg) => ("OpIterNext", vec![reg(dst), reg(arg)]),
+		OpIterNextBack(dst, arg) => ("OpIterNextBack", vec![reg(dst), reg(arg)]),
+		OpIterFinishedp(dst, arg) => ("OpIterFinishedp", vec![reg(dst), reg(arg)]),
+		OpLen(dst, arg) => ("OpLen", vec![reg(dst), reg(arg)]),
+		OpHasp(dst, arg0, arg1) => ("OpHasp", vec![reg(dst), reg(arg0), reg(arg1)]),
+		OpAccess(dst, arg0, arg1) => ("OpAccess", vec![reg(dst), reg(arg0), reg(arg1)]),
+		OpSetAccess(dst, arg0, arg1, arg2) => {
+			("OpSetAccess", vec![reg(dst), reg(arg0), reg(arg1), reg(arg2)])
+		}
+		OpArr(dst, arg0, arg_count) => ("OpArr", vec![reg(dst), reg(arg0), arg_count.to_string()]),
+		OpCallMeth(dst, arg0, arg_count, _) => {
+			("OpCallMeth", vec![reg(dst), reg(arg0), arg_count.to_string()])
+		}
+		OpCallMethOpt(dst, arg0, arg_count, _) => {
+			("OpCallMethOpt", vec![reg(dst), reg(arg0), arg_count.to_string()])
+		}
+		OpCallBaseRaw(dst, arg0, arg_count) => {
+			("OpCallBaseRaw", vec![reg(dst), reg(arg0), arg_count.to_string()])
+		}
+		OpGlobal(dst, arg) => ("OpGlobal", vec![reg(dst), reg(arg)]),
+		OpSetGlobal(dst, arg0, arg1) => ("OpSetGlobal", vec![reg(dst), reg(arg0), reg(arg1)])
+	}
+}
+
 #[doc(hidden)]
 pub fn dump_form(arg: &Val) -> GResult<String> {
 	let expanded = glsp::expand(arg, None)?;
@@ -1268,6 +1855,15 @@ pub fn dump_form(arg: &Val) -> GResult<String> {
 	Ok(format!("ANONYMOUS FORM:\n\n{}\n", bytecode_to_string(&code)))
 }
 
+//compiles the form at opt level 0, so that it can be compared with dump_form's output
+#[doc(hidden)]
+pub fn dump_form_unoptimized(arg: &Val) -> GResult<String> {
+	let prev_level = glsp::swap_opt_level(0);
+	let _guard = Guard::new(|| { glsp::swap_opt_level(prev_level); });
+
+	dump_form(arg)
+}
+
 #[doc(hidden)]
 pub fn dump_fn(gfn: &GFn) -> GResult<String> {
 	let name = match gfn.lambda.name {
@@ -0,0 +1,320 @@
+use super::engine::{glsp, Sym, ToSym, with_heap};
+use super::error::{GResult};
+use super::gc::{Allocate, GcHeader, Slot, Root, Visitor};
+use super::val::{Val, visit_reachable};
+use super::wrap::{FromVal, ToVal};
+use std::cell::{RefCell};
+use std::hash::{Hash, Hasher};
+use std::mem::{size_of};
+use std::rc::{Rc};
+
+/**
+The `rec` primitive type.
+
+A record is a fixed-size sequence of named fields. Its layout is described by a record type,
+which is registered using [`glsp::define_rec`](fn.define_rec.html) and shared between all of
+the records which have that type. Unlike an [`Obj`](struct.Obj.html), a record has no class,
+no states and no methods, so it's considerably cheaper to allocate and to access.
+
+Records are usually defined using the [`defrecord`](https://gamelisp.rs/std/defrecord) macro.
+*/
+
+pub struct Rec {
+	header: GcHeader,
+	rec_type: Rc<RecType>,
+	fields: RefCell<Box<[Slot]>>
+}
+
+//two records have the same type if their RecTypes are pointer-equal. glsp::define_rec reuses
+//the existing RecType when a record is redefined with an identical field list, so reloading a
+//file which contains a (defrecord) form won't orphan any records which are still alive.
+#[derive(PartialEq, Eq, Debug)]
+pub(crate) struct RecType {
+	pub(crate) name: Sym,
+	pub(crate) fields: Box<[Sym]>
+}
+
+impl RecType {
+	pub(crate) fn new(name: Sym, fields: &[Sym]) -> GResult<RecType> {
+		for (i, &field) in fields.iter().enumerate() {
+			ensure!(!fields[..i].contains(&field),
+			        "duplicate field '{}' in the rec type {}", field, name);
+		}
+
+		Ok(RecType {
+			name,
+			fields: fields.to_vec().into_boxed_slice()
+		})
+	}
+
+	pub(crate) fn index_of(&self, field: Sym) -> Option<usize> {
+		self.fields.iter().position(|&f| f == field)
+	}
+}
+
+impl Rec {
+	pub(crate) fn new(rec_type: Rc<RecType>, fields: Box<[Slot]>) -> Rec {
+		assert!(fields.len() == rec_type.fields.len());
+
+		Rec {
+			header: GcHeader::new(),
+			rec_type,
+			fields: RefCell::new(fields)
+		}
+	}
+
+	/**
+	Returns the name of the record's type.
+
+	Equivalent to [`(rec-name rc)`](https://gamelisp.rs/std/rec-name).
+	*/
+	pub fn name(&self) -> Sym {
+		self.rec_type.name
+	}
+
+	/**
+	Returns the names of the record's fields, in order.
+
+	Equivalent to [`(rec-fields rc)`](https://gamelisp.rs/std/rec-fields).
+	*/
+	pub fn field_names(&self) -> &[Sym] {
+		&self.rec_type.fields
+	}
+
+	/**
+	Returns the number of fields in the record.
+	*/
+	pub fn len(&self) -> usize {
+		self.rec_type.fields.len()
+	}
+
+	/**
+	Returns `true` if the record has no fields.
+	*/
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/**
+	Returns `true` if both records were constructed from the same record type.
+	*/
+	pub fn same_type(&self, other: &Rec) -> bool {
+		Rc::ptr_eq(&self.rec_type, &other.rec_type)
+	}
+
+	/**
+	Returns `true` if the record has a field with the given name.
+
+	Equivalent to [`(has? rc key)`](https://gamelisp.rs/std/has-p).
+	*/
+	pub fn has<S: ToSym>(&self, key: S) -> GResult<bool> {
+		Ok(self.rec_type.index_of(key.to_sym()?).is_some())
+	}
+
+	/**
+	Accesses the value of a field.
+
+	Equivalent to [`[rc key]`](https://gamelisp.rs/std/access).
+	*/
+	pub fn get<S: ToSym, V: FromVal>(&self, key: S) -> GResult<V> {
+		let sym = key.to_sym()?;
+		match self.rec_type.index_of(sym) {
+			Some(i) => V::from_slot(&self.fields.borrow()[i]),
+			None => bail!("attempted to access nonexistent field '{}' in a {} rec",
+			              sym, self.name())
+		}
+	}
+
+	/**
+	Accesses the value of a field, if it exists.
+
+	Equivalent to [`[rc (? key)]`](https://gamelisp.rs/std/access).
+	*/
+	pub fn get_if_present<S: ToSym, V: FromVal>(&self, key: S) -> GResult<Option<V>> {
+		match self.rec_type.index_of(key.to_sym()?) {
+			Some(i) => Ok(Some(V::from_slot(&self.fields.borrow()[i])?)),
+			None => Ok(None)
+		}
+	}
+
+	/**
+	Mutates the value of a field.
+
+	Equivalent to [`(= [rc key] value)`](https://gamelisp.rs/std/set-access).
+	*/
+	pub fn set<S: ToSym, V: ToVal>(&self, key: S, value: V) -> GResult<()> {
+		let sym = key.to_sym()?;
+		match self.rec_type.index_of(sym) {
+			Some(i) => self.set_index(i, value),
+			None => bail!("attempted to assign to nonexistent field '{}' in a {} rec",
+			              sym, self.name())
+		}
+	}
+
+	/**
+	Mutates the value of a field, if it exists. Returns `true` if the field was assigned to.
+
+	Equivalent to [`(= [rc (? key)] value)`](https://gamelisp.rs/std/set-access).
+	*/
+	pub fn set_if_present<S: ToSym, V: ToVal>(&self, key: S, value: V) -> GResult<bool> {
+		match self.rec_type.index_of(key.to_sym()?) {
+			Some(i) => {
+				self.set_index(i, value)?;
+				Ok(true)
+			}
+			None => Ok(false)
+		}
+	}
+
+	/**
+	Accesses a field by its position in the record's field list.
+
+	This is how the accessor functions generated by [`defrecord`](https://gamelisp.rs/std/defrecord)
+	read their field, so it doesn't need to search for the field's name.
+	*/
+	pub fn get_index<V: FromVal>(&self, i: usize) -> GResult<V> {
+		match self.fields.borrow().get(i) {
+			Some(slot) => V::from_slot(slot),
+			None => bail!("field index {} is out of bounds for a {} rec", i, self.name())
+		}
+	}
+
+	/**
+	Mutates a field by its position in the record's field list.
+	*/
+	pub fn set_index<V: ToVal>(&self, i: usize, value: V) -> GResult<()> {
+		ensure!(!self.header.frozen(), "attempted to mutate a frozen {} rec", self.name());
+		ensure!(i < self.len(), "field index {} is out of bounds for a {} rec", i, self.name());
+
+		let slot = value.to_slot()?;
+		with_heap(|heap| heap.write_barrier_slot(self, &slot));
+
+		match self.fields.try_borrow_mut() {
+			Ok(mut fields) => fields[i] = slot,
+			Err(_) => bail!("attempted to mutate a borrowed rec")
+		}
+
+		Ok(())
+	}
+
+	/**
+	Returns the record's field values, in order.
+	*/
+	pub fn field_vals(&self) -> Vec<Val> {
+		self.fields.borrow().iter().map(|slot| slot.root()).collect()
+	}
+
+	pub(crate) fn save_contents(&self) -> Box<[Slot]> {
+		self.fields.borrow().clone()
+	}
+
+	pub(crate) fn restore_contents(&self, saved: &[Slot]) {
+		self.fields.borrow_mut().clone_from_slice(saved);
+	}
+
+	/**
+	Makes the record immutable.
+
+	Equivalent to [`(freeze! rc)`](https://gamelisp.rs/std/freeze-mut).
+	*/
+	pub fn freeze(&self) {
+		self.header.freeze()
+	}
+
+	/**
+	Makes the record and all of its contents immutable.
+
+	Equivalent to [`(deep-freeze! rc)`](https://gamelisp.rs/std/deep-freeze-mut).
+	*/
+	pub fn deep_freeze(&self) {
+		self.freeze();
+		visit_reachable(self.field_vals(), |val| {
+			val.freeze();
+			true
+		});
+	}
+
+	///Returns `true` if the record has been frozen.
+	pub fn is_frozen(&self) -> bool {
+		self.header.frozen()
+	}
+
+	///Returns `true` if the record and all of its contents have been frozen.
+	pub fn is_deep_frozen(&self) -> bool {
+		self.is_frozen() && visit_reachable(self.field_vals(), |val| val.is_frozen())
+	}
+
+	/**
+	Creates a shallow copy of the record.
+
+	Equivalent to [`(clone rc)`](https://gamelisp.rs/std/clone).
+	*/
+	pub fn shallow_clone(&self) -> Root<Rec> {
+		let fields = self.fields.borrow().clone();
+		glsp::alloc(Rec::new(Rc::clone(&self.rec_type), fields))
+	}
+
+	/**
+	Recursively copies the record and all of its contents.
+
+	Equivalent to [`(deep-clone rc)`](https://gamelisp.rs/std/deep-clone).
+	*/
+	pub fn deep_clone(&self) -> GResult<Root<Rec>> {
+		let mut fields = Vec::with_capacity(self.len());
+		for val in self.field_vals() {
+			fields.push(val.deep_clone()?.to_slot()?);
+		}
+
+		Ok(glsp::alloc(Rec::new(Rc::clone(&self.rec_type), fields.into_boxed_slice())))
+	}
+
+	/**
+	Equivalent to [`(eq? self other)`](https://gamelisp.rs/std/eq-p).
+
+	Two records are equal if they have the same record type, and each of their fields are
+	equal. As with [`Arr::try_eq`](struct.Arr.html#method.try_eq), this may need to invoke
+	an `op-eq?` method, so it can potentially fail.
+	*/
+	pub fn try_eq(&self, other: &Rec) -> GResult<bool> {
+		if !self.same_type(other) {
+			return Ok(false)
+		}
+
+		for (v0, v1) in self.field_vals().iter().zip(other.field_vals().iter()) {
+			if !v0.try_eq(v1)? {
+				return Ok(false)
+			}
+		}
+
+		Ok(true)
+	}
+}
+
+impl Allocate for Rec {
+	fn header(&self) -> &GcHeader {
+		&self.header
+	}
+
+	fn visit_gcs<V: Visitor>(&self, visitor: &mut V) {
+		for slot in self.fields.borrow().iter() {
+			visitor.visit_slot(slot);
+		}
+	}
+
+	fn clear_gcs(&self) {
+		for slot in self.fields.borrow_mut().iter_mut() {
+			*slot = Slot::Nil;
+		}
+	}
+
+	fn owned_memory_usage(&self) -> usize {
+		self.fields.borrow().len() * size_of::<Slot>()
+	}
+}
+
+impl Hash for Rec {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.rec_type.name.hash(state);
+		self.fields.borrow().hash(state)
+	}
+}
@@ -4,12 +4,15 @@ use serde::de::{
 	Deserialize, Deserializer, Error as DeError, EnumAccess, MapAccess, 
 	SeqAccess, VariantAccess, Visitor
 };
-use serde::ser::{Error as SerError, Serialize, Serializer, SerializeMap, SerializeSeq};
+use serde::ser::{
+	Error as SerError, Serialize, Serializer, SerializeMap, SerializeSeq, SerializeTuple
+};
 use std::{fmt};
 use std::rc::{Rc};
 use super::collections::{Arr, DequeOps, Str, Tab};
 use super::engine::{glsp, Sym};
 use super::gc::{Allocate, Gc, Slot, Root};
+use super::record::{Rec};
 use super::val::{Val};
 
 /*
@@ -81,13 +84,25 @@ impl Serialize for Tab {
 		}
 
 		let mut map = s.serialize_map(Some(self.len()))?;
-		for (key, value) in self.entries().iter() {
+		for (key, value) in sorted_entries(self) {
 			map.serialize_entry(&key, &value)?;
 		}
 		map.end()
 	}
 }
 
+//a rec is serialized as a tuple of its type name and a map from field names to field values.
+//deserializing it requires the same rec type to have been defined with glsp::define_rec.
+impl Serialize for Rec {
+	fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+		if let Err(err) = self.check_serializability() {
+			return Err(S::Error::custom(format!("serialization error: {}", err)))
+		}
+
+		Unchecked(self).serialize(s)
+	}
+}
+
 impl<T: Allocate + Serialize> Serialize for Root<T> {
 	fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
 		(**self).serialize(s)
@@ -118,6 +133,7 @@ impl<'a> Serialize for Unchecked<&'a Val> {
 			Val::Arr(ref a) => s.serialize_newtype_variant("Val", 6, "Val::Arr", &Unchecked(&**a)),
 			Val::Str(ref st) => s.serialize_newtype_variant("Val", 7, "Val::Str", &**st),
 			Val::Tab(ref t) => s.serialize_newtype_variant("Val", 8, "Val::Tab", &Unchecked(&**t)),
+			Val::Rec(ref r) => s.serialize_newtype_variant("Val", 9, "Val::Rec", &Unchecked(&**r)),
 			_ => unreachable!()
 		}
 	}
@@ -136,13 +152,42 @@ impl<'a> Serialize for Unchecked<&'a Arr> {
 impl<'a> Serialize for Unchecked<&'a Tab> {
 	fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
 		let mut map = s.serialize_map(Some(self.0.len()))?;
-		for (key, value) in self.0.entries().iter() {
+		for (key, value) in sorted_entries(self.0) {
 			map.serialize_entry(&Unchecked(&key), &Unchecked(&value))?;
 		}
 		map.end()
 	}
 }
 
+//a tab's entries are serialized in canonical key order, so that equal tabs always produce the 
+//same output, regardless of the order in which their entries were inserted
+fn sorted_entries(tab: &Tab) -> Vec<(Val, Val)> {
+	let mut entries: Vec<(Val, Val)> = tab.entries().iter().collect();
+	entries.sort_by(|(key0, _), (key1, _)| key0.canonical_cmp(key1));
+	entries
+}
+
+impl<'a> Serialize for Unchecked<&'a Rec> {
+	fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+		let mut tuple = s.serialize_tuple(2)?;
+		tuple.serialize_element(&self.0.name())?;
+		tuple.serialize_element(&UncheckedRecFields(self.0))?;
+		tuple.end()
+	}
+}
+
+struct UncheckedRecFields<'a>(&'a Rec);
+
+impl<'a> Serialize for UncheckedRecFields<'a> {
+	fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+		let mut map = s.serialize_map(Some(self.0.len()))?;
+		for (name, value) in self.0.field_names().iter().zip(self.0.field_vals()) {
+			map.serialize_entry(name, &Unchecked(&value))?;
+		}
+		map.end()
+	}
+}
+
 
 //-------------------------------------------------------------------------------------------------
 // Deserialize
@@ -157,7 +202,8 @@ enum ValVariant {
 	Sym,
 	Arr,
 	Str,
-	Tab
+	Tab,
+	Rec
 }
 
 struct ValVariantVisitor;
@@ -180,6 +226,7 @@ impl<'de> Visitor<'de> for ValVariantVisitor {
 			6 => ValVariant::Arr,
 			7 => ValVariant::Str,
 			8 => ValVariant::Tab,
+			9 => ValVariant::Rec,
 			_ => return Err(E::custom("invalid Val variant"))
 		})
 	}
@@ -195,6 +242,7 @@ impl<'de> Visitor<'de> for ValVariantVisitor {
 			"Val::Arr" => ValVariant::Arr,
 			"Val::Str" => ValVariant::Str,
 			"Val::Tab" => ValVariant::Tab,
+			"Val::Rec" => ValVariant::Rec,
 			_ => return Err(E::custom("invalid Val variant"))
 		})
 	}
@@ -210,6 +258,7 @@ impl<'de> Visitor<'de> for ValVariantVisitor {
 			b"Val::Arr" => ValVariant::Arr,
 			b"Val::Str" => ValVariant::Str,
 			b"Val::Tab" => ValVariant::Tab,
+			b"Val::Rec" => ValVariant::Rec,
 			_ => return Err(E::custom("invalid Val variant"))
 		})
 	}
@@ -240,7 +289,8 @@ impl<'de> Visitor<'de> for ValVisitor {
 			(ValVariant::Sym, v) => Val::Sym(v.newtype_variant()?),
 			(ValVariant::Arr, v) => Val::Arr(v.newtype_variant()?),
 			(ValVariant::Str, v) => Val::Str(v.newtype_variant()?),
-			(ValVariant::Tab, v) => Val::Tab(v.newtype_variant()?)
+			(ValVariant::Tab, v) => Val::Tab(v.newtype_variant()?),
+			(ValVariant::Rec, v) => Val::Rec(v.newtype_variant()?)
 		};
 
 		Ok(val)
@@ -251,7 +301,8 @@ impl<'de> Deserialize<'de> for Val {
 	fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
 		const VARIANTS: &[&str] = &[
 			"Val::Nil", "Val::Int", "Val::Flo", "Val::Char", "Val::Bool", "Val::Sym", "Val::RFn",
-			"Val::Arr", "Val::Str", "Val::Tab", "Val::Obj", "Val::Class", "Val::GFn", "Val::Coro", "Val::RData"
+			"Val::Arr", "Val::Str", "Val::Tab", "Val::Obj", "Val::Class", "Val::GFn", "Val::Coro", "Val::RData",
+			"Val::Rec"
 		];
 
 		d.deserialize_enum("Val", VARIANTS, ValVisitor)
@@ -268,7 +319,13 @@ impl<'de> Visitor<'de> for SymVisitor {
 	}
 
 	fn visit_str<E: DeError>(self, st: &str) -> Result<Self::Value, E> {
-		match glsp::sym(st) {
+		let result = if st.starts_with("#<gs") {
+			glsp::gensym_from_name(st)
+		} else {
+			glsp::sym(st)
+		};
+
+		match result {
 			Ok(sym) => Ok(sym),
 			Err(_) => return Err(E::custom(format!("invalid sym {}", st)))
 		}
@@ -359,3 +416,63 @@ impl<'de> Deserialize<'de> for Root<Tab> {
 	}
 }
 
+
+struct RootRecVisitor;
+
+impl<'de> Visitor<'de> for RootRecVisitor {
+	type Value = Root<Rec>;
+
+	fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "a Rec")
+	}
+
+	fn visit_seq<A: SeqAccess<'de>>(self, mut a: A) -> Result<Self::Value, A::Error> {
+		let name = match a.next_element::<Sym>()? {
+			Some(name) => name,
+			None => return Err(A::Error::invalid_length(0, &self))
+		};
+
+		let fields = match a.next_element::<RecFields>()? {
+			Some(fields) => fields,
+			None => return Err(A::Error::invalid_length(1, &self))
+		};
+
+		match glsp::rec_from_named_fields(name, fields.0) {
+			Ok(rec) => Ok(rec),
+			Err(err) => Err(A::Error::custom(format!("invalid rec: {}", err.val())))
+		}
+	}
+}
+
+impl<'de> Deserialize<'de> for Root<Rec> {
+	fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+		d.deserialize_tuple(2, RootRecVisitor)
+	}
+}
+
+struct RecFields(Vec<(Sym, Val)>);
+
+struct RecFieldsVisitor;
+
+impl<'de> Visitor<'de> for RecFieldsVisitor {
+	type Value = RecFields;
+
+	fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "a map of Rec fields")
+	}
+
+	fn visit_map<A: MapAccess<'de>>(self, mut a: A) -> Result<Self::Value, A::Error> {
+		let mut fields = Vec::with_capacity(a.size_hint().unwrap_or(0));
+		while let Some(entry) = a.next_entry::<Sym, Val>()? {
+			fields.push(entry);
+		}
+
+		Ok(RecFields(fields))
+	}
+}
+
+impl<'de> Deserialize<'de> for RecFields {
+	fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+		d.deserialize_map(RecFieldsVisitor)
+	}
+}
@@ -0,0 +1,137 @@
+use std::cmp::{min};
+use super::engine::{split_qualified, Sym};
+
+/*
+"did you mean" suggestions for misspelled names. when a global, field or method can't be found,
+we compare its name against every name which could have been meant, and suggest the closest.
+
+this only runs on the error path, so we don't bother with anything cleverer than a linear scan
+and a bounded edit distance. the distance is the "optimal string alignment" variant of
+damerau-levenshtein, so transposing two adjacent characters (`updaet`) only costs one edit.
+*/
+
+//the most candidates which we'll suggest at once
+const MAX_SUGGESTIONS: usize = 2;
+
+//short names need to be matched more strictly, otherwise `x` would suggest every one-character
+//name in scope
+fn max_distance(len: usize) -> usize {
+	match len {
+		0 ..= 2 => 0,
+		3 ..= 5 => 1,
+		6 ..= 9 => 2,
+		_ => 3
+	}
+}
+
+//returns None if the distance between `a` and `b` is greater than `max`
+fn edit_distance(a: &[char], b: &[char], max: usize) -> Option<usize> {
+	let len_diff = if a.len() > b.len() { a.len() - b.len() } else { b.len() - a.len() };
+	if len_diff > max {
+		return None
+	}
+
+	//three rows of the dynamic-programming table: two rows back, one row back, and the current
+	//row. the oldest row is only needed for transpositions.
+	let mut prev2: Vec<usize> = vec![0; b.len() + 1];
+	let mut prev: Vec<usize> = (0 ..= b.len()).collect();
+	let mut cur: Vec<usize> = vec![0; b.len() + 1];
+
+	for i in 1 ..= a.len() {
+		cur[0] = i;
+		let mut row_min = cur[0];
+
+		for j in 1 ..= b.len() {
+			let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+			cur[j] = min(min(prev[j] + 1, cur[j - 1] + 1), prev[j - 1] + cost);
+
+			if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+				cur[j] = min(cur[j], prev2[j - 2] + 1);
+			}
+
+			row_min = min(row_min, cur[j]);
+		}
+
+		if row_min > max {
+			return None
+		}
+
+		std::mem::swap(&mut prev2, &mut prev);
+		std::mem::swap(&mut prev, &mut cur);
+	}
+
+	if prev[b.len()] <= max {
+		Some(prev[b.len()])
+	} else {
+		None
+	}
+}
+
+//returns the names in `candidates` which are closest to `name`. `name` itself is never suggested.
+//when one candidate is closer than another, only the closer one is suggested, and names which
+//are too different from `name` are left out, so the result is often empty.
+pub(crate) fn suggest_names<I>(name: &str, candidates: I) -> Vec<Sym>
+where
+	I: IntoIterator<Item = Sym>
+{
+	let target: Vec<char> = name.chars().collect();
+	let max = max_distance(target.len());
+	if max == 0 {
+		return Vec::new()
+	}
+
+	//a qualified name like `ui:draw` is only matched against other qualified names
+	let qualified = split_qualified(name).is_some();
+	let mut matches: Vec<(usize, Sym)> = Vec::new();
+	let mut chars = Vec::new();
+
+	for candidate in candidates {
+		let candidate_name = candidate.name();
+		if &*candidate_name == name || split_qualified(&candidate_name).is_some() != qualified {
+			continue
+		}
+
+		chars.clear();
+		chars.extend(candidate_name.chars());
+
+		if let Some(distance) = edit_distance(&target, &chars, max) {
+			if !matches.iter().any(|&(_, sym)| sym == candidate) {
+				matches.push((distance, candidate));
+			}
+		}
+	}
+
+	let best = match matches.iter().map(|&(distance, _)| distance).min() {
+		Some(best) => best,
+		None => return Vec::new()
+	};
+
+	matches.retain(|&(distance, _)| distance == best);
+	matches.sort_by(|(_, sym0), (_, sym1)| sym0.name().cmp(&sym1.name()));
+	matches.truncate(MAX_SUGGESTIONS);
+
+	matches.into_iter().map(|(_, sym)| sym).collect()
+}
+
+//a suffix for error messages which aren't built from a MessageId, e.g. " (did you mean `x`?)"
+pub(crate) fn did_you_mean(suggestions: &[Sym]) -> String {
+	if suggestions.is_empty() {
+		String::new()
+	} else {
+		format!(" (did you mean {}?)", describe_suggestions(suggestions))
+	}
+}
+
+//renders a list of suggestions for an error message, e.g. "`update-player` or `update-players`"
+pub(crate) fn describe_suggestions(suggestions: &[Sym]) -> String {
+	let mut description = String::new();
+	for (i, sym) in suggestions.iter().enumerate() {
+		if i > 0 {
+			description.push_str(if i == suggestions.len() - 1 { " or " } else { ", " });
+		}
+
+		description.push_str(&format!("`{}`", sym));
+	}
+
+	description
+}
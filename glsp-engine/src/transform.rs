@@ -1,8 +1,10 @@
+use smallvec::{SmallVec};
 use std::collections::{HashMap};
 use std::iter::{FromIterator};
 use super::code::{Instr};
 use super::ast::{Alias, Ast, Binding, Expr, Id, Node, Range};
-use super::engine::{stock_syms::*, Sym, with_known_ops};
+use super::engine::{glsp, stock_syms::*, Sym, with_known_ops};
+use super::val::{Flo, Val};
 
 #[cfg(feature = "compiler")]
 use serde::{Deserialize, Serialize};
@@ -20,7 +22,11 @@ pub(crate) fn standard_passes(
 			yields_node(ast, node, bindings);
 			op_calls_node(ast, node, bindings, known_ops);
 		});
-	})
+	});
+
+	if glsp::opt_level() > 0 {
+		optimize(ast, node);
+	}
 }
 
 
@@ -349,6 +355,13 @@ pub(crate) enum OpId {
 	Abs,
 	Neg,
 	Sign,
+	BitAnd,
+	BitOr,
+	BitXor,
+	BitNot,
+	Shl,
+	Shr,
+	Sar,
 	Min,
 	Max,
 	Int,
@@ -390,6 +403,7 @@ pub(crate) fn op_instr_1_arg(op_id: OpId, dst: u8, arg: u8) -> Instr {
 		OpId::Neg => OpNeg(dst, arg),
 		OpId::Sub => OpNeg(dst, arg),
 		OpId::Sign => OpSign(dst, arg),
+		OpId::BitNot => OpBitNot(dst, arg),
 		OpId::Int => OpInt(dst, arg),
 		OpId::Flo => OpFlo(dst, arg),
 		OpId::Bool => OpBool(dst, arg),
@@ -413,6 +427,12 @@ pub(crate) fn op_instr_2_args(op_id: OpId, dst: u8, arg0: u8, arg1: u8) -> Instr
 		OpId::Mul => OpMul(dst, arg0, arg1),
 		OpId::Div => OpDiv(dst, arg0, arg1),
 		OpId::Rem => OpRem(dst, arg0, arg1),
+		OpId::BitAnd => OpBitAnd(dst, arg0, arg1),
+		OpId::BitOr => OpBitOr(dst, arg0, arg1),
+		OpId::BitXor => OpBitXor(dst, arg0, arg1),
+		OpId::Shl => OpShl(dst, arg0, arg1),
+		OpId::Shr => OpShr(dst, arg0, arg1),
+		OpId::Sar => OpSar(dst, arg0, arg1),
 		OpId::Min => OpMin(dst, arg0, arg1),
 		OpId::Max => OpMax(dst, arg0, arg1),
 		OpId::NumEq => OpNumEq(dst, arg0, arg1),
@@ -441,8 +461,8 @@ pub(crate) fn op_instr_variadic(op_id: OpId, dst: u8, arg0: u8, arg_count: u8) -
 
 	match op_id {
 		OpId::Arr => OpArr(dst, arg0, arg_count),
-		OpId::CallMeth => OpCallMeth(dst, arg0, arg_count),
-		OpId::CallMethOpt => OpCallMethOpt(dst, arg0, arg_count),
+		OpId::CallMeth => OpCallMeth(dst, arg0, arg_count, 0),
+		OpId::CallMethOpt => OpCallMethOpt(dst, arg0, arg_count, 0),
 		OpId::CallBaseRaw => OpCallBaseRaw(dst, arg0, arg_count),
 		_ => panic!()
 	}
@@ -472,11 +492,12 @@ pub(crate) enum Predicate {
 	GFn,
 	Coro,
 	RData,
+	Rec,
 	Callable,
 	Expander
 }
 
-static KNOWN_OPS: [(Sym, KnownOp); 54] = {
+static KNOWN_OPS: [(Sym, KnownOp); 65] = {
 	use KnownOp::*;
 
 	[
@@ -486,6 +507,17 @@ static KNOWN_OPS: [(Sym, KnownOp); 54] = {
 		(DIV_SYM, Fixed(OpId::Div, 2)),
 		(REM_SYM, Fixed(OpId::Rem, 2)),
 		(ABS_SYM, Fixed(OpId::Abs, 1)),
+
+		(BITAND_SYM, Fixed(OpId::BitAnd, 2)),
+		(BITOR_SYM, Fixed(OpId::BitOr, 2)),
+		(BITXOR_SYM, Fixed(OpId::BitXor, 2)),
+		(BIT_AND_SYM, Fixed(OpId::BitAnd, 2)),
+		(BIT_OR_SYM, Fixed(OpId::BitOr, 2)),
+		(BIT_XOR_SYM, Fixed(OpId::BitXor, 2)),
+		(BIT_NOT_SYM, Fixed(OpId::BitNot, 1)),
+		(SHL_SYM, Fixed(OpId::Shl, 2)),
+		(SHR_SYM, Fixed(OpId::Shr, 2)),
+		(SAR_SYM, Fixed(OpId::Sar, 2)),
 		
 		(SIGN_SYM, Fixed(OpId::Sign, 1)),
 		(MIN_SYM, Fixed(OpId::Min, 2)),
@@ -511,6 +543,7 @@ static KNOWN_OPS: [(Sym, KnownOp); 54] = {
 		(RFNP_SYM, TypeCheck(Predicate::RFn)),
 		(COROP_SYM, TypeCheck(Predicate::Coro)),
 		(RDATAP_SYM, TypeCheck(Predicate::RData)),
+		(RECP_SYM, TypeCheck(Predicate::Rec)),
 		(CALLABLEP_SYM, TypeCheck(Predicate::Callable)),
 		(EXPANDERP_SYM, TypeCheck(Predicate::Expander)),
 
@@ -557,8 +590,15 @@ fn op_calls_node(ast: &mut Ast, node: Id<Node>, bindings: &mut Bindings,
 	
 	if let Expr::Call { callee, args, splay_bits } = ast[node].1 {
 		if let Expr::Var(callee_name) = ast[callee].1 {
+			//within a module, the callee's name might resolve to some other global
 			if !bindings.name_has_binding(callee_name) && 
-			   (splay_bits == 0 || callee_name == ARR_SYM) {
+			   (callee_name == APPLY_SYM || callee_name == CALL_WITH_SYM) &&
+			   glsp::resolve_global(callee_name).ok() == Some(callee_name) {
+
+				forward_call_node(ast, node, callee_name, args, splay_bits);
+			} else if !bindings.name_has_binding(callee_name) && 
+			   (splay_bits == 0 || callee_name == ARR_SYM) &&
+			   glsp::resolve_global(callee_name).ok() == Some(callee_name) {
 
 			   	//we special-case `-`, since it's the only case which can accept either one
 			   	//or two arguments, and it needs to behave differently for each
@@ -604,3 +644,400 @@ fn op_calls_node(ast: &mut Ast, node: Id<Node>, bindings: &mut Bindings,
 		}
 	}
 }
+
+//(apply f args) is rewritten to (f ..args), and (call-with f ..rest) is rewritten to (f ..rest).
+//the vm splices a splayed arr directly onto the register stack, so the common wrapper pattern
+//(fn (..args) (apply f args)) doesn't allocate or convert anything beyond the `args` arr itself.
+//(apply f args kwargs) is left alone, because it needs to convert the tab into keyword arguments.
+fn forward_call_node(ast: &mut Ast, node: Id<Node>, callee_name: Sym, args: Range<Node>,
+                     splay_bits: u32) {
+
+	//the first argument would become the callee, which can't be splayed
+	if splay_bits & 0x1 != 0 {
+		return
+	}
+
+	let mut rest = args;
+	let new_callee = match rest.next() {
+		Some(new_callee) => new_callee,
+		None => return
+	};
+
+	let new_splay_bits = if callee_name == APPLY_SYM {
+		if rest.len() != 1 || splay_bits != 0 {
+			return
+		}
+
+		0x1
+	} else {
+		splay_bits >> 1
+	};
+
+	ast[node].1 = Expr::Call {
+		callee: new_callee,
+		args: rest,
+		splay_bits: new_splay_bits
+	};
+}
+
+
+//the "optimize" pass. this runs after the standard passes, and only when glsp::opt_level() is
+//nonzero. it performs a post-order traversal which...
+//- folds Expr::Op and Expr::TypeCheck nodes whose arguments are all literals
+//- replaces an Expr::If which has a literal condition with the branch that would be taken
+//- discards any forms in a (do) which follow an unconditional return, finish-block or 
+//  restart-block
+//- discards any `let` binding of a literal or fn, when that binding is never accessed
+//
+//the yields and stays flags computed by the standard passes are deliberately left alone, so
+//that (for example) a fn which contains an unreachable (yield) is still a coroutine.
+
+fn optimize(ast: &mut Ast, node: Id<Node>) {
+	let mut scope = Scope { entries: Vec::new() };
+	optimize_node(ast, node, &mut scope);
+	assert!(scope.entries.len() == 0);
+}
+
+//each entry is a local name, the Expr::Let node which bound it (if any), and whether it's been
+//accessed or mutated
+struct Scope {
+	entries: Vec<(Sym, Option<Id<Node>>, bool)>
+}
+
+impl Scope {
+	fn mark_used(&mut self, name: Sym) {
+		for entry in self.entries.iter_mut().rev() {
+			if entry.0 == name {
+				entry.2 = true;
+				break
+			}
+		}
+	}
+}
+
+fn optimize_do(ast: &mut Ast, nodes: Range<Node>, scope: &mut Scope) {
+	let base_len = scope.entries.len();
+	let mut unreachable = false;
+
+	for node in nodes {
+		if unreachable {
+			ast[node].1 = Expr::Literal(Val::Nil);
+			continue
+		}
+
+		if let Expr::Let { binding } = ast[node].1 {
+			if let Some(init) = ast[binding].init {
+				optimize_node(ast, init, scope);
+			}
+
+			scope.entries.push((ast[binding].name, Some(node), false));
+		} else {
+			optimize_node(ast, node, scope);
+		}
+
+		if let Expr::Return(_) | Expr::FinishBlock { .. } | Expr::RestartBlock(_) = ast[node].1 {
+			unreachable = true;
+		}
+	}
+
+	//a `let` form evaluates to #n, so an unused binding can be replaced with a #n literal, as 
+	//long as evaluating its initializer has no side-effects
+	for (_, let_node, used) in scope.entries.drain(base_len..) {
+		if let (Some(let_node), false) = (let_node, used) {
+			if let Expr::Let { binding } = ast[let_node].1 {
+				let pure = match ast[binding].init {
+					Some(init) => matches!(ast[init].1, Expr::Literal(_) | Expr::Fn { .. }),
+					None => true
+				};
+
+				if pure {
+					ast[let_node].1 = Expr::Literal(Val::Nil);
+				}
+			}
+		}
+	}
+}
+
+fn optimize_node(ast: &mut Ast, node: Id<Node>, scope: &mut Scope) {
+	match ast[node].1 {
+		Expr::Literal(_) => (),
+		Expr::Var(name) => {
+			scope.mark_used(name);
+		}
+		Expr::Call { callee, args, .. } => {
+			optimize_node(ast, callee, scope);
+			for arg in args {
+				optimize_node(ast, arg, scope);
+			}
+		}
+		Expr::Op { op_id, variadic, args, splay_bits } => {
+			for arg in args {
+				optimize_node(ast, arg, scope);
+			}
+
+			if !variadic && splay_bits == 0 {
+				let mut vals = SmallVec::<[Val; 3]>::new();
+				for arg in args {
+					match ast[arg].1 {
+						Expr::Literal(ref val) => vals.push(val.clone()),
+						_ => return
+					}
+				}
+
+				if let Some(result) = fold_op(op_id, &vals) {
+					ast[node].1 = Expr::Literal(result);
+				}
+			}
+		}
+		Expr::TypeCheck { arg, predicate } => {
+			optimize_node(ast, arg, scope);
+
+			if let Expr::Literal(ref val) = ast[arg].1 {
+				let result = fold_predicate(predicate, val);
+				ast[node].1 = Expr::Literal(Val::Bool(result));
+			}
+		}
+		Expr::Do(nodes) => {
+			optimize_do(ast, nodes, scope);
+		}
+		Expr::If { cond, then_do, else_do } => {
+			optimize_node(ast, cond, scope);
+
+			//a `let`, `defer` or `defer-yield` form can't be moved into the enclosing (do),
+			//because that would change its scope (and it's an error for the encoder anyway)
+			let movable = |expr: &Expr| {
+				!matches!(expr, Expr::Let { .. } | Expr::Defer(_) | Expr::DeferYield { .. })
+			};
+
+			let taken = match ast[cond].1 {
+				Expr::Literal(Val::Nil) | Expr::Literal(Val::Bool(false)) => Some(else_do),
+				Expr::Literal(_) => Some(then_do),
+				_ => None
+			};
+
+			match taken {
+				Some(taken) if movable(&ast[then_do].1) && movable(&ast[else_do].1) => {
+					//the surviving branch keeps its own span
+					ast[node] = std::mem::take(&mut ast[taken]);
+					optimize_node(ast, node, scope);
+				}
+				_ => {
+					optimize_node(ast, then_do, scope);
+					optimize_node(ast, else_do, scope);
+				}
+			}
+		}
+		Expr::Let { binding } => {
+			//this is a misplaced `let` which the encoder will reject
+			if let Some(init) = ast[binding].init {
+				optimize_node(ast, init, scope);
+			}
+		}
+		Expr::Set { target, src_node } => {
+			scope.mark_used(target);
+			optimize_node(ast, src_node, scope);
+		}
+		Expr::Block { body, .. } => {
+			optimize_do(ast, body, scope);
+		}
+		Expr::FinishBlock { result_node, .. } => {
+			optimize_node(ast, result_node, scope);
+		}
+		Expr::RestartBlock(_) => (),
+		Expr::Fn { body, ref param_list, .. } => {
+			let param_list = param_list.clone();
+			let base_len = scope.entries.len();
+
+			for binding in param_list.basic_params {
+				scope.entries.push((ast[binding].name, None, true));
+			}
+
+			for binding in param_list.opt_params {
+				scope.entries.push((ast[binding].name, None, true));
+			}
+
+			if let Some(Some(binding)) = param_list.rest_param {
+				scope.entries.push((ast[binding].name, None, true));
+			}
+
+			optimize_do(ast, body, scope);
+
+			scope.entries.truncate(base_len);
+		}
+		Expr::Return(result_node) | Expr::Yield(result_node) => {
+			optimize_node(ast, result_node, scope);
+		}
+		Expr::Defer(nodes) => {
+			optimize_do(ast, nodes, scope);
+		}
+		Expr::DeferYield { pause_node, resume_node } => {
+			optimize_node(ast, pause_node, scope);
+			optimize_node(ast, resume_node, scope);
+		}
+	}
+}
+
+//returns the result of evaluating an op with literal arguments, or None if the op can't be
+//folded. ops which would fail at runtime, such as integer division by zero, are never folded,
+//so that their errors are still reported when (and if) the op is executed. the arithmetic here
+//must exactly match the corresponding instrs in vm.rs.
+fn fold_op(op_id: OpId, args: &[Val]) -> Option<Val> {
+	fn num(val: &Val) -> Option<Val> {
+		match *val {
+			Val::Int(_) | Val::Flo(_) => Some(val.clone()),
+			_ => None
+		}
+	}
+
+	//numeric comparisons, min and max also accept chars, which are converted to ints
+	fn cmp_num(val: &Val) -> Option<Val> {
+		match *val {
+			Val::Int(_) | Val::Flo(_) => Some(val.clone()),
+			Val::Char(ch) => Some(Val::Int(ch as u32 as i32)),
+			_ => None
+		}
+	}
+
+	fn arith(
+		args: &[Val], 
+		int_op: fn(i32, i32) -> i32, 
+		flo_op: fn(Flo, Flo) -> Flo
+	) -> Option<Val> {
+		Some(match (num(&args[0])?, num(&args[1])?) {
+			(Val::Int(i0), Val::Int(i1)) => Val::Int(int_op(i0, i1)),
+			(Val::Int(i), Val::Flo(f)) => Val::Flo(flo_op(i as Flo, f)),
+			(Val::Flo(f), Val::Int(i)) => Val::Flo(flo_op(f, i as Flo)),
+			(Val::Flo(f0), Val::Flo(f1)) => Val::Flo(flo_op(f0, f1)),
+			_ => unreachable!()
+		})
+	}
+
+	fn int(args: &[Val], int_op: fn(i32, i32) -> i32) -> Option<Val> {
+		match (&args[0], &args[1]) {
+			(&Val::Int(i0), &Val::Int(i1)) => Some(Val::Int(int_op(i0, i1))),
+			_ => None
+		}
+	}
+
+	fn cmp(
+		args: &[Val], 
+		int_op: fn(i32, i32) -> bool, 
+		flo_op: fn(Flo, Flo) -> bool
+	) -> Option<Val> {
+		Some(Val::Bool(match (cmp_num(&args[0])?, cmp_num(&args[1])?) {
+			(Val::Int(i0), Val::Int(i1)) => int_op(i0, i1),
+			(Val::Int(i), Val::Flo(f)) => flo_op(i as Flo, f),
+			(Val::Flo(f), Val::Int(i)) => flo_op(f, i as Flo),
+			(Val::Flo(f0), Val::Flo(f1)) => flo_op(f0, f1),
+			_ => unreachable!()
+		}))
+	}
+
+	fn min_max(args: &[Val], max: bool) -> Option<Val> {
+		let (arg0, arg1) = (cmp_num(&args[0])?, cmp_num(&args[1])?);
+		let first = match (&arg0, &arg1) {
+			(&Val::Int(i0), &Val::Int(i1)) => if max { i0 >= i1 } else { i0 <= i1 },
+			(&Val::Int(i), &Val::Flo(f)) => if max { i as Flo >= f } else { i as Flo <= f },
+			(&Val::Flo(f), &Val::Int(i)) => if max { f >= i as Flo } else { f <= i as Flo },
+			(&Val::Flo(f0), &Val::Flo(f1)) => if max { f0 >= f1 } else { f0 <= f1 },
+			_ => unreachable!()
+		};
+
+		Some(if first { arg0 } else { arg1 })
+	}
+
+	fn truthy(val: &Val) -> bool {
+		!matches!(*val, Val::Nil | Val::Bool(false))
+	}
+
+	match (op_id, args.len()) {
+		(OpId::Add, 2) => arith(args, |i0, i1| i0.wrapping_add(i1), |f0, f1| f0 + f1),
+		(OpId::Sub, 2) => arith(args, |i0, i1| i0.wrapping_sub(i1), |f0, f1| f0 - f1),
+		(OpId::Mul, 2) => arith(args, |i0, i1| i0.wrapping_mul(i1), |f0, f1| f0 * f1),
+		(OpId::Div, 2) | (OpId::Rem, 2) => {
+			if let Val::Int(0) = args[1] {
+				if let Val::Int(_) = args[0] {
+					return None
+				}
+			}
+
+			match op_id {
+				OpId::Div => arith(args, |i0, i1| i0.wrapping_div(i1), |f0, f1| f0 / f1),
+				_ => arith(args, |i0, i1| i0.wrapping_rem(i1), |f0, f1| f0 % f1)
+			}
+		}
+		(OpId::Abs, 1) => match args[0] {
+			Val::Int(i) => Some(Val::Int(i.wrapping_abs())),
+			Val::Flo(f) => Some(Val::Flo(f.abs())),
+			_ => None
+		},
+		(OpId::Neg, 1) => match args[0] {
+			Val::Int(i) => Some(Val::Int(i.wrapping_neg())),
+			Val::Flo(f) => Some(Val::Flo(-f)),
+			_ => None
+		},
+		(OpId::Sign, 1) => match args[0] {
+			Val::Int(i) => Some(Val::Int(i.signum())),
+			Val::Flo(f) if f == 0.0 || f.is_nan() => Some(Val::Int(0)),
+			Val::Flo(f) => Some(Val::Int(f.signum() as i32)),
+			_ => None
+		},
+		(OpId::BitAnd, 2) => int(args, |i0, i1| i0 & i1),
+		(OpId::BitOr, 2) => int(args, |i0, i1| i0 | i1),
+		(OpId::BitXor, 2) => int(args, |i0, i1| i0 ^ i1),
+		(OpId::BitNot, 1) => match args[0] {
+			Val::Int(i) => Some(Val::Int(!i)),
+			_ => None
+		},
+		(OpId::Shl, 2) => int(args, |i0, i1| i0.wrapping_shl(i1 as u32)),
+		(OpId::Shr, 2) => int(args, |i0, i1| (i0 as u32).wrapping_shr(i1 as u32) as i32),
+		(OpId::Sar, 2) => int(args, |i0, i1| i0.wrapping_shr(i1 as u32)),
+		(OpId::Min, 2) => min_max(args, false),
+		(OpId::Max, 2) => min_max(args, true),
+		(OpId::Int, 1) => match args[0] {
+			Val::Flo(f) => Some(Val::Int(f as i32)),
+			Val::Bool(b) => Some(Val::Int(b as i32)),
+			Val::Int(i) => Some(Val::Int(i)),
+			_ => None
+		},
+		(OpId::Flo, 1) => match args[0] {
+			Val::Int(i) => Some(Val::Flo(i as Flo)),
+			Val::Bool(b) => Some(Val::Flo(b as i32 as Flo)),
+			Val::Flo(f) => Some(Val::Flo(f)),
+			_ => None
+		},
+		(OpId::Bool, 1) => Some(Val::Bool(truthy(&args[0]))),
+		(OpId::Not, 1) => Some(Val::Bool(!truthy(&args[0]))),
+		(OpId::NumEq, 2) => cmp(args, |i0, i1| i0 == i1, |f0, f1| f0 == f1),
+		(OpId::Lt, 2) => cmp(args, |i0, i1| i0 < i1, |f0, f1| f0 < f1),
+		(OpId::Lte, 2) => cmp(args, |i0, i1| i0 <= i1, |f0, f1| f0 <= f1),
+		(OpId::Gt, 2) => cmp(args, |i0, i1| i0 > i1, |f0, f1| f0 > f1),
+		(OpId::Gte, 2) => cmp(args, |i0, i1| i0 >= i1, |f0, f1| f0 >= f1),
+		_ => None
+	}
+}
+
+//literals are always nil, numbers, chars, bools, syms, strs, arrs, tabs or recs
+fn fold_predicate(predicate: Predicate, val: &Val) -> bool {
+	match (predicate, val) {
+		(Predicate::Nil, &Val::Nil) => true,
+		(Predicate::Num, &Val::Int(_)) => true,
+		(Predicate::Num, &Val::Flo(_)) => true,
+		(Predicate::Int, &Val::Int(_)) => true,
+		(Predicate::Flo, &Val::Flo(_)) => true,
+		(Predicate::Nan, &Val::Flo(f)) => f.is_nan(),
+		(Predicate::Inf, &Val::Flo(f)) => f.is_infinite(),
+		(Predicate::Bool, &Val::Bool(_)) => true,
+		(Predicate::Sym, &Val::Sym(_)) => true,
+		(Predicate::Deque, &Val::Arr(_)) => true,
+		(Predicate::Deque, &Val::Str(_)) => true,
+		(Predicate::Arr, &Val::Arr(_)) => true,
+		(Predicate::Str, &Val::Str(_)) => true,
+		(Predicate::Tab, &Val::Tab(_)) => true,
+		(Predicate::Iterable, &Val::Arr(_)) => true,
+		(Predicate::Iterable, &Val::Str(_)) => true,
+		(Predicate::Iterable, &Val::Tab(_)) => true,
+		(Predicate::Rec, &Val::Rec(_)) => true,
+		_ => false
+	}
+}
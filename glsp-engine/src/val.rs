@@ -1,3 +1,4 @@
+use fnv::{FnvHashSet};
 use std::char;
 use std::cmp::{Ordering, PartialOrd};
 use std::fmt::{self, Debug, Display, Formatter};
@@ -8,9 +9,13 @@ use super::class::{Class, Obj};
 use super::code::{Coro, GFn};
 use super::collections::{Arr, DequeOps, Str, Tab};
 use super::engine::{RFn, RData, stock_syms::*, Sym};
+use super::record::{Rec};
+use super::view::{View};
+use super::vecmath::{Mat3, Vec2, Vec3};
 use super::error::{GResult};
 use super::gc::Root;
 use super::iter::{GIter};
+use super::persist::{PArr, PTab};
 
 
 //-------------------------------------------------------------------------------------------------
@@ -28,7 +33,7 @@ Many functions in this crate provide automatic conversions to and from `Val`, us
 pub enum Val {
 	Nil,
 	Int(i32),
-	Flo(f32),
+	Flo(Flo),
 	Char(char),
 	Bool(bool),
 	Sym(Sym),
@@ -42,6 +47,13 @@ pub enum Val {
 	GFn(Root<GFn>),
 	Coro(Root<Coro>),
 	RData(Root<RData>),
+	Rec(Root<Rec>),
+	PArr(Root<PArr>),
+	PTab(Root<PTab>),
+	View(Root<View>),
+	Vec2(Root<Vec2>),
+	Vec3(Root<Vec3>),
+	Mat3(Root<Mat3>),
 }
 
 impl Default for Val {
@@ -105,7 +117,7 @@ macro_rules! impl_val {
 
 impl_val!(
 	(Int, i32, "int", "an int", is_int, unwrap_int), 
-	(Flo, f32, "flo", "a flo", is_flo, unwrap_flo), 
+	(Flo, Flo, "flo", "a flo", is_flo, unwrap_flo), 
 	(Char, char, "char", "a char", is_char, unwrap_char), 
 	(Bool, bool, "bool", "a bool", is_bool, unwrap_bool), 
 	(Sym, Sym, "sym", "a sym", is_sym, unwrap_sym), 
@@ -118,7 +130,14 @@ impl_val!(
 	(Class, Root<Class>, "class", "a class", is_class, unwrap_class),
 	(GFn, Root<GFn>, "fn", "a fn", is_gfn, unwrap_gfn),
 	(Coro, Root<Coro>, "coro", "a coro", is_coro, unwrap_coro),
-	(RData, Root<RData>, "rdata", "an rdata", is_rdata, unwrap_rdata)
+	(RData, Root<RData>, "rdata", "an rdata", is_rdata, unwrap_rdata),
+	(Rec, Root<Rec>, "rec", "a rec", is_rec, unwrap_rec),
+	(PArr, Root<PArr>, "parr", "a parr", is_parr, unwrap_parr),
+	(PTab, Root<PTab>, "ptab", "a ptab", is_ptab, unwrap_ptab),
+	(View, Root<View>, "view", "a view", is_view, unwrap_view),
+	(Vec2, Root<Vec2>, "vec2", "a vec2", is_vec2, unwrap_vec2),
+	(Vec3, Root<Vec3>, "vec3", "a vec3", is_vec3, unwrap_vec3),
+	(Mat3, Root<Mat3>, "mat3", "a mat3", is_mat3, unwrap_mat3)
 );
 
 impl Val {
@@ -177,10 +196,11 @@ impl Val {
 
 	/**
 	Returns `true` if the value belongs to the `iterable` abstract type (`arr`, `str`, `tab`,
-	`iter` or `coro`).
+	`iter`, `coro`, `parr` or `ptab`).
 	*/
 	pub fn is_iterable(&self) -> bool {
-		matches!(*self, Val::Arr(_) | Val::Str(_) | Val::Tab(_) | Val::GIter(_) | Val::Coro(_))
+		matches!(*self, Val::Arr(_) | Val::Str(_) | Val::Tab(_) | Val::GIter(_) | Val::Coro(_) |
+		                Val::PArr(_) | Val::PTab(_))
 	}
 
 	/**
@@ -218,6 +238,13 @@ impl Val {
 					None => Val::RData(root.clone())
 				}
 			}
+			Val::Rec(ref rec) => Val::Rec(rec.shallow_clone()),
+			Val::PArr(ref parr) => Val::PArr(parr.clone()),
+			Val::PTab(ref ptab) => Val::PTab(ptab.clone()),
+			Val::View(ref view) => Val::View(view.clone()),
+			Val::Vec2(ref v) => Val::Vec2(v.clone()),
+			Val::Vec3(ref v) => Val::Vec3(v.clone()),
+			Val::Mat3(ref m) => Val::Mat3(m.clone())
 		})
 	}
 
@@ -266,12 +293,23 @@ impl Val {
 					None => Val::RData(root.clone())
 				}
 			}
+			Val::Rec(ref rec) => Val::Rec(rec.deep_clone()?),
+			Val::PArr(ref parr) => Val::PArr(parr.deep_clone()?),
+			Val::PTab(ref ptab) => Val::PTab(ptab.deep_clone()?),
+			Val::View(ref view) => Val::View(view.clone()),
+			Val::Vec2(ref v) => Val::Vec2(v.clone()),
+			Val::Vec3(ref v) => Val::Vec3(v.clone()),
+			Val::Mat3(ref m) => Val::Mat3(m.clone())
 		})
 	}
 
 	/**
 	Makes the value immutable.
 
+	This is a no-op for values which aren't an arr, str, tab, obj or rec. In particular, an `RData`
+	or a view can't be frozen, because the Rust code which owns it could always mutate it. A parr,
+	ptab, vec2, vec3 or mat3 is always frozen.
+
 	Equivalent to [`(freeze! val)`](https://gamelisp.rs/std/freeze-mut).
 	*/
 	pub fn freeze(&self) {
@@ -280,48 +318,118 @@ impl Val {
 			Val::Str(ref st) => st.freeze(),
 			Val::Tab(ref tab) => tab.freeze(),
 			Val::Obj(ref obj) => obj.freeze(),
+			Val::Rec(ref rec) => rec.freeze(),
 			Val::Nil | Val::Int(_) | Val::Flo(_) | Val::Char(_) | Val::Bool(_) | Val::Sym(_) |
-			Val::GIter(_) | Val::RFn(_) | Val::Class(_) | Val::GFn(_) | Val::Coro(_) | Val::RData(_) => ()
+			Val::GIter(_) | Val::RFn(_) | Val::Class(_) | Val::GFn(_) | Val::Coro(_) | Val::RData(_) |
+			Val::PArr(_) | Val::PTab(_) | Val::View(_) | Val::Vec2(_) | Val::Vec3(_) |
+			Val::Mat3(_) => ()
 		}
 	}
 
 	/**
-	Makes the value and all of its contents immutable.
+	Makes the value, and every arr, str, tab, obj and rec which can be reached from it, immutable.
+
+	Reference cycles are permitted. The contents of functions, coroutines, iterators and 
+	rdata are not visited.
 
 	Equivalent to [`(deep-freeze! val)`](https://gamelisp.rs/std/deep-freeze-mut).
 	*/
-
-	//todo: handle reference cycles
 	pub fn deep_freeze(&self) {
-		match *self {
-			Val::Arr(ref arr) => arr.deep_freeze(),
-			Val::Tab(ref tab) => tab.deep_freeze(),
-			Val::Str(ref st) => st.freeze(),
-			Val::Obj(ref obj) => {
-				//todo
-				obj.freeze()
-			}
-			Val::Nil | Val::Int(_) | Val::Flo(_) | Val::Char(_) | Val::Bool(_) | Val::Sym(_) |
-			Val::GIter(_) | Val::RFn(_) | Val::Class(_) | Val::GFn(_) | Val::Coro(_) | Val::RData(_) => ()
-		}
+		visit_reachable(Some(self.clone()), |val| {
+			val.freeze();
+			true
+		});
 	}
 
-	//todo: handle reference cycles
-	pub(crate) fn is_deep_frozen(&self) -> bool {
-		//note that there's currently no way to traverse the fields of objects etc., so it only
-		//makes sense to use this method for representable/evaluable values
+	/**
+	Returns `true` if the value can't be mutated.
+
+	Nil, bools, numbers, chars, symbols, parrs, ptabs, vec2s, vec3s and mat3s are always immutable. An arr, str, tab, 
+	obj or rec is immutable if it's been frozen. Other types can't be frozen, so this method returns `false`
+	for them.
+
+	Equivalent to [`(frozen? val)`](https://gamelisp.rs/std/frozen-p).
+	*/
+	pub fn is_frozen(&self) -> bool {
 		match *self {
-			Val::Arr(ref arr) => arr.is_deep_frozen(),
+			Val::Arr(ref arr) => arr.is_frozen(),
 			Val::Str(ref st) => st.is_frozen(),
-			Val::Tab(ref tab) => tab.is_deep_frozen(),
+			Val::Tab(ref tab) => tab.is_frozen(),
+			Val::Obj(ref obj) => obj.is_frozen(),
+			Val::Rec(ref rec) => rec.is_frozen(),
 
-			Val::Nil | Val::Int(_) | Val::Flo(_) | 
-			Val::Char(_) | Val::Bool(_) | Val::Sym(_) => true,
+			Val::Nil | Val::Int(_) | Val::Flo(_) | Val::Char(_) | 
+			Val::Bool(_) | Val::Sym(_) | Val::PArr(_) | Val::PTab(_) |
+			Val::Vec2(_) | Val::Vec3(_) | Val::Mat3(_) => true,
 
-			Val::Obj(_) | Val::RFn(_) | Val::Class(_) | Val::GIter(_) | 
-			Val::GFn(_) | Val::Coro(_) | Val::RData(_) => unreachable!()
+			Val::RFn(_) | Val::Class(_) | Val::GIter(_) | 
+			Val::GFn(_) | Val::Coro(_) | Val::RData(_) | Val::View(_) => false
 		}
 	}
+
+	/**
+	Returns `true` if the value, and every value which can be reached from it through arrs,
+	tabs, objs and recs, can't be mutated.
+
+	Equivalent to [`(deep-frozen? val)`](https://gamelisp.rs/std/deep-frozen-p).
+	*/
+	pub fn is_deep_frozen(&self) -> bool {
+		visit_reachable(Some(self.clone()), |val| val.is_frozen())
+	}
+}
+
+//calls `f` for each of the `roots`, and for every value which can be reached from them through 
+//arrs, tabs, objs, recs, parrs and ptabs. each collection is visited at most once, so reference cycles terminate. 
+//returns `false` as soon as `f` returns `false`.
+pub(crate) fn visit_reachable<I, F>(roots: I, mut f: F) -> bool
+where
+	I: IntoIterator<Item = Val>,
+	F: FnMut(&Val) -> bool
+{
+	let mut visited = FnvHashSet::<usize>::default();
+	let mut stack: Vec<Val> = roots.into_iter().collect();
+
+	while let Some(val) = stack.pop() {
+		let address = match val {
+			Val::Arr(ref arr) => &**arr as *const Arr as usize,
+			Val::Tab(ref tab) => &**tab as *const Tab as usize,
+			Val::Obj(ref obj) => &**obj as *const Obj as usize,
+			Val::Rec(ref rec) => &**rec as *const Rec as usize,
+			Val::PArr(ref parr) => &**parr as *const PArr as usize,
+			Val::PTab(ref ptab) => &**ptab as *const PTab as usize,
+			_ => 0
+		};
+
+		if address != 0 && !visited.insert(address) {
+			continue
+		}
+
+		if !f(&val) {
+			return false
+		}
+
+		match val {
+			Val::Arr(ref arr) => stack.extend(arr.iter()),
+			Val::Tab(ref tab) => {
+				for (key, value) in tab.entries().iter() {
+					stack.push(key);
+					stack.push(value);
+				}
+			}
+			Val::Obj(ref obj) => stack.extend(obj.field_vals()),
+			Val::Rec(ref rec) => stack.extend(rec.field_vals()),
+			Val::PArr(ref parr) => stack.extend(parr.iter()),
+			Val::PTab(ref ptab) => {
+				for (key, value) in ptab.entries() {
+					stack.push(key);
+					stack.push(value);
+				}
+			}
+			_ => ()
+		}
+	}
+
+	true
 }
 
 
@@ -329,11 +437,25 @@ impl Val {
 // Num
 //-------------------------------------------------------------------------------------------------
 
+/**
+The Rust type which stores a GameLisp `flo`.
+
+This is `f32` by default. When the `"f64-flos"` 
+[feature flag](https://gamelisp.rs/reference/feature-flags.html) is enabled, it's `f64` instead,
+which makes each [`Val`](enum.Val.html) larger on some platforms.
+*/
+
+#[cfg(not(feature = "f64-flos"))]
+pub type Flo = f32;
+
+#[cfg(feature = "f64-flos")]
+pub type Flo = f64;
+
 /**
 A type-erased `num`.
 
 In general, you can manipulate a `Num` in the same way that you would manipulate an `i32`
-or `f32`. `Num` supports familiar methods like [`abs`](#method.abs) and 
+or a [`Flo`](type.Flo.html). `Num` supports familiar methods like [`abs`](#method.abs) and 
 [`div_euclid`](#method.div_euclid), as well as the built-in numeric operators like
 `+` and `%`.
 */
@@ -341,7 +463,7 @@ or `f32`. `Num` supports familiar methods like [`abs`](#method.abs) and
 #[derive(Clone, Copy)]
 pub enum Num {
 	Int(i32),
-	Flo(f32)
+	Flo(Flo)
 }
 
 //todo: significantly more methods, to bring Num on-par with the built-in numeric types where
@@ -361,17 +483,22 @@ impl Num {
 		if let Num::Int(i) = self { i } else { panic!() } 
 	}
 
-	pub fn unwrap_flo(self) -> f32 { 
+	pub fn unwrap_flo(self) -> Flo { 
 		if let Num::Flo(f) = self { f } else { panic!() } 
 	}
 	
-	pub fn into_f32(self) -> f32 {
+	pub fn into_flo(self) -> Flo {
 		match self {
-			Num::Int(i) => i as f32,
+			Num::Int(i) => i as Flo,
 			Num::Flo(f) => f
 		}
 	}
 
+	///Converts the number to an `f32`. If [`Flo`](type.Flo.html) is `f64`, it's rounded.
+	pub fn into_f32(self) -> f32 {
+		self.into_flo() as f32
+	}
+
 	pub fn abs(self) -> Num {
 		match self {
 			Num::Int(i) => Num::Int(i.wrapping_abs()),
@@ -382,8 +509,8 @@ impl Num {
 	pub fn div_euclid(self, other: Num) -> Num {
 		match (self, other) {
 			(Num::Int(left), Num::Int(right)) => Num::Int(left.div_euclid(right)),
-			(Num::Flo(left), Num::Int(right)) => Num::Flo(left.div_euclid(right as f32)),
-			(Num::Int(left), Num::Flo(right)) => Num::Flo((left as f32).div_euclid(right)),
+			(Num::Flo(left), Num::Int(right)) => Num::Flo(left.div_euclid(right as Flo)),
+			(Num::Int(left), Num::Flo(right)) => Num::Flo((left as Flo).div_euclid(right)),
 			(Num::Flo(left), Num::Flo(right)) => Num::Flo(left.div_euclid(right))
 		}
 	}
@@ -391,8 +518,8 @@ impl Num {
 	pub fn wrapping_div_euclid(self, other: Num) -> Num {
 		match (self, other) {
 			(Num::Int(left), Num::Int(right)) => Num::Int(left.wrapping_div_euclid(right)),
-			(Num::Flo(left), Num::Int(right)) => Num::Flo(left.div_euclid(right as f32)),
-			(Num::Int(left), Num::Flo(right)) => Num::Flo((left as f32).div_euclid(right)),
+			(Num::Flo(left), Num::Int(right)) => Num::Flo(left.div_euclid(right as Flo)),
+			(Num::Int(left), Num::Flo(right)) => Num::Flo((left as Flo).div_euclid(right)),
 			(Num::Flo(left), Num::Flo(right)) => Num::Flo(left.div_euclid(right))
 		}
 	}
@@ -400,8 +527,8 @@ impl Num {
 	pub fn rem_euclid(self, other: Num) -> Num {
 		match (self, other) {
 			(Num::Int(left), Num::Int(right)) => Num::Int(left.rem_euclid(right)),
-			(Num::Flo(left), Num::Int(right)) => Num::Flo(left.rem_euclid(right as f32)),
-			(Num::Int(left), Num::Flo(right)) => Num::Flo((left as f32).rem_euclid(right)),
+			(Num::Flo(left), Num::Int(right)) => Num::Flo(left.rem_euclid(right as Flo)),
+			(Num::Int(left), Num::Flo(right)) => Num::Flo((left as Flo).rem_euclid(right)),
 			(Num::Flo(left), Num::Flo(right)) => Num::Flo(left.rem_euclid(right))
 		}
 	}
@@ -409,8 +536,8 @@ impl Num {
 	pub fn wrapping_rem_euclid(self, other: Num) -> Num {
 		match (self, other) {
 			(Num::Int(left), Num::Int(right)) => Num::Int(left.wrapping_rem_euclid(right)),
-			(Num::Flo(left), Num::Int(right)) => Num::Flo(left.rem_euclid(right as f32)),
-			(Num::Int(left), Num::Flo(right)) => Num::Flo((left as f32).rem_euclid(right)),
+			(Num::Flo(left), Num::Int(right)) => Num::Flo(left.rem_euclid(right as Flo)),
+			(Num::Int(left), Num::Flo(right)) => Num::Flo((left as Flo).rem_euclid(right)),
 			(Num::Flo(left), Num::Flo(right)) => Num::Flo(left.rem_euclid(right))
 		}
 	}
@@ -444,8 +571,8 @@ impl PartialEq<Num> for Num {
 	fn eq(&self, other: &Num) -> bool {
 		match (*self, *other) {
 			(Num::Int(i0), Num::Int(i1)) => i0 == i1,
-			(Num::Int(i0), Num::Flo(f1)) => i0 as f32 == f1,
-			(Num::Flo(f0), Num::Int(i1)) => f0 == i1 as f32,
+			(Num::Int(i0), Num::Flo(f1)) => i0 as Flo == f1,
+			(Num::Flo(f0), Num::Int(i1)) => f0 == i1 as Flo,
 			(Num::Flo(f0), Num::Flo(f1)) => f0 == f1
 		}
 	}
@@ -463,13 +590,13 @@ impl PartialEq<Num> for i32 {
 	}
 }
 
-impl PartialEq<f32> for Num {
-	fn eq(&self, other: &f32) -> bool {
+impl PartialEq<Flo> for Num {
+	fn eq(&self, other: &Flo) -> bool {
 		self.eq(&Num::Flo(*other))
 	}
 }
 
-impl PartialEq<Num> for f32 {
+impl PartialEq<Num> for Flo {
 	fn eq(&self, other: &Num) -> bool {
 		Num::Flo(*self).eq(other)
 	}
@@ -479,8 +606,8 @@ impl PartialOrd<Num> for Num {
 	fn partial_cmp(&self, other: &Num) -> Option<Ordering> {
 		match (*self, *other) {
 			(Num::Int(i0), Num::Int(i1)) => i0.partial_cmp(&i1),
-			(Num::Int(i0), Num::Flo(f1)) => (i0 as f32).partial_cmp(&f1),
-			(Num::Flo(f0), Num::Int(i1)) => f0.partial_cmp(&(i1 as f32)),
+			(Num::Int(i0), Num::Flo(f1)) => (i0 as Flo).partial_cmp(&f1),
+			(Num::Flo(f0), Num::Int(i1)) => f0.partial_cmp(&(i1 as Flo)),
 			(Num::Flo(f0), Num::Flo(f1)) => f0.partial_cmp(&f1)
 		}
 	}
@@ -498,13 +625,13 @@ impl PartialOrd<Num> for i32 {
 	}
 }
 
-impl PartialOrd<f32> for Num {
-	fn partial_cmp(&self, other: &f32) -> Option<Ordering> {
+impl PartialOrd<Flo> for Num {
+	fn partial_cmp(&self, other: &Flo) -> Option<Ordering> {
 		self.partial_cmp(&Num::Flo(*other))
 	}
 }
 
-impl PartialOrd<Num> for f32 {
+impl PartialOrd<Num> for Flo {
 	fn partial_cmp(&self, other: &Num) -> Option<Ordering> {
 		Num::Flo(*self).partial_cmp(other)
 	}
@@ -515,8 +642,8 @@ impl Add for Num {
 	fn add(self, rhs: Num) -> Num {
 		match (self, rhs) {
 			(Num::Int(i0), Num::Int(i1)) => Num::Int(i0.wrapping_add(i1)),
-			(Num::Int(i0), Num::Flo(f1)) => Num::Flo(i0 as f32 + f1),
-			(Num::Flo(f0), Num::Int(i1)) => Num::Flo(f0 + i1 as f32),
+			(Num::Int(i0), Num::Flo(f1)) => Num::Flo(i0 as Flo + f1),
+			(Num::Flo(f0), Num::Int(i1)) => Num::Flo(f0 + i1 as Flo),
 			(Num::Flo(f0), Num::Flo(f1)) => Num::Flo(f0 + f1)
 		}
 	}
@@ -527,8 +654,8 @@ impl Sub for Num {
 	fn sub(self, rhs: Num) -> Num {
 		match (self, rhs) {
 			(Num::Int(i0), Num::Int(i1)) => Num::Int(i0.wrapping_sub(i1)),
-			(Num::Int(i0), Num::Flo(f1)) => Num::Flo(i0 as f32 - f1),
-			(Num::Flo(f0), Num::Int(i1)) => Num::Flo(f0 - i1 as f32),
+			(Num::Int(i0), Num::Flo(f1)) => Num::Flo(i0 as Flo - f1),
+			(Num::Flo(f0), Num::Int(i1)) => Num::Flo(f0 - i1 as Flo),
 			(Num::Flo(f0), Num::Flo(f1)) => Num::Flo(f0 - f1)
 		}
 	}
@@ -539,8 +666,8 @@ impl Mul for Num {
 	fn mul(self, rhs: Num) -> Num {
 		match (self, rhs) {
 			(Num::Int(i0), Num::Int(i1)) => Num::Int(i0.wrapping_mul(i1)),
-			(Num::Int(i0), Num::Flo(f1)) => Num::Flo(i0 as f32 * f1),
-			(Num::Flo(f0), Num::Int(i1)) => Num::Flo(f0 * i1 as f32),
+			(Num::Int(i0), Num::Flo(f1)) => Num::Flo(i0 as Flo * f1),
+			(Num::Flo(f0), Num::Int(i1)) => Num::Flo(f0 * i1 as Flo),
 			(Num::Flo(f0), Num::Flo(f1)) => Num::Flo(f0 * f1)
 		}
 	}
@@ -551,8 +678,8 @@ impl Div for Num {
 	fn div(self, rhs: Num) -> Num {
 		match (self, rhs) {
 			(Num::Int(i0), Num::Int(i1)) => Num::Int(i0.wrapping_div(i1)),
-			(Num::Int(i0), Num::Flo(f1)) => Num::Flo(i0 as f32 / f1),
-			(Num::Flo(f0), Num::Int(i1)) => Num::Flo(f0 / i1 as f32),
+			(Num::Int(i0), Num::Flo(f1)) => Num::Flo(i0 as Flo / f1),
+			(Num::Flo(f0), Num::Int(i1)) => Num::Flo(f0 / i1 as Flo),
 			(Num::Flo(f0), Num::Flo(f1)) => Num::Flo(f0 / f1)
 		}
 	}
@@ -563,8 +690,8 @@ impl Rem for Num {
 	fn rem(self, rhs: Num) -> Num {
 		match (self, rhs) {
 			(Num::Int(i0), Num::Int(i1)) => Num::Int(i0.wrapping_rem(i1)),
-			(Num::Int(i0), Num::Flo(f1)) => Num::Flo(i0 as f32 % f1),
-			(Num::Flo(f0), Num::Int(i1)) => Num::Flo(f0 % i1 as f32),
+			(Num::Int(i0), Num::Flo(f1)) => Num::Flo(i0 as Flo % f1),
+			(Num::Flo(f0), Num::Int(i1)) => Num::Flo(f0 % i1 as Flo),
 			(Num::Flo(f0), Num::Flo(f1)) => Num::Flo(f0 % f1)
 		}
 	}
@@ -596,13 +723,13 @@ impl Val {
 	pub fn num_eq(&self, other: &Val) -> Option<bool> {
 		match (self, other) {
 			(&Val::Int(i0), &Val::Int(i1)) => Some(i0 == i1),
-			(&Val::Flo(f0), &Val::Int(i1)) => Some(f0 == i1 as f32),
+			(&Val::Flo(f0), &Val::Int(i1)) => Some(f0 == i1 as Flo),
 			(&Val::Char(c0), &Val::Int(i1)) => Some(c0 as u32 as i32 == i1),
-			(&Val::Int(i0), &Val::Flo(f1)) => Some(i0 as f32 == f1),
+			(&Val::Int(i0), &Val::Flo(f1)) => Some(i0 as Flo == f1),
 			(&Val::Flo(f0), &Val::Flo(f1)) => Some(f0 == f1),
-			(&Val::Char(c0), &Val::Flo(f1)) => Some(c0 as u32 as f32 == f1),
+			(&Val::Char(c0), &Val::Flo(f1)) => Some(c0 as u32 as Flo == f1),
 			(&Val::Int(i0), &Val::Char(c1)) => Some(i0 == c1 as u32 as i32),
-			(&Val::Flo(f0), &Val::Char(c1)) => Some(f0 == c1 as u32 as f32),
+			(&Val::Flo(f0), &Val::Char(c1)) => Some(f0 == c1 as u32 as Flo),
 			(&Val::Char(c0), &Val::Char(c1)) => Some(c0 == c1),
 			_ => None /*bail!("attempted to compare {} and {} using =", 
 			           self.a_type_name(), other.a_type_name())*/
@@ -634,6 +761,13 @@ impl Val {
 			(&Val::GFn(ref root0),   &Val::GFn(ref root1)) => Root::ptr_eq(root0, root1),
 			(&Val::Coro(ref root0),  &Val::Coro(ref root1)) => Root::ptr_eq(root0, root1),
 			(&Val::RData(ref root0),   &Val::RData(ref root1)) => Root::ptr_eq(root0, root1),
+			(&Val::Rec(ref root0),   &Val::Rec(ref root1)) => Root::ptr_eq(root0, root1),
+			(&Val::PArr(ref root0),  &Val::PArr(ref root1)) => Root::ptr_eq(root0, root1),
+			(&Val::PTab(ref root0),  &Val::PTab(ref root1)) => Root::ptr_eq(root0, root1),
+			(&Val::View(ref root0),  &Val::View(ref root1)) => Root::ptr_eq(root0, root1),
+			(&Val::Vec2(ref root0),  &Val::Vec2(ref root1)) => Root::ptr_eq(root0, root1),
+			(&Val::Vec3(ref root0),  &Val::Vec3(ref root1)) => Root::ptr_eq(root0, root1),
+			(&Val::Mat3(ref root0),  &Val::Mat3(ref root1)) => Root::ptr_eq(root0, root1),
 			_ => false
 		}
 	}
@@ -656,6 +790,15 @@ impl Val {
 			(&Val::Tab(ref root0), &Val::Tab(ref root1)) => Root::ptr_eq(root0, root1),
 			(&Val::Obj(ref root0), &Val::Obj(ref root1)) => Root::ptr_eq(root0, root1),
 			(&Val::RData(ref root0), &Val::RData(ref root1)) => Root::ptr_eq(root0, root1),
+			(&Val::Vec2(ref v0), &Val::Vec2(ref v1)) => {
+				components_eqv(v0.components(), v1.components())
+			}
+			(&Val::Vec3(ref v0), &Val::Vec3(ref v1)) => {
+				components_eqv(v0.components(), v1.components())
+			}
+			(&Val::Mat3(ref m0), &Val::Mat3(ref m1)) => {
+				components_eqv(m0.components(), m1.components())
+			}
 			_ => self.eq(other)
 		}
 	}
@@ -688,11 +831,22 @@ impl Val {
 			(&Val::Tab(ref t0), &Val::Tab(ref t1)) => t0.try_eq(t1),
 			(&Val::Obj(ref o0), &Val::Obj(ref o1)) => o0.try_eq(o1),
 			(&Val::RData(ref r0), &Val::RData(ref r1)) => r0.try_eq(r1),
+			(&Val::Rec(ref r0), &Val::Rec(ref r1)) => r0.try_eq(r1),
+			(&Val::PArr(ref p0), &Val::PArr(ref p1)) => p0.try_eq(p1),
+			(&Val::PTab(ref p0), &Val::PTab(ref p1)) => p0.try_eq(p1),
+			(&Val::Vec2(ref v0), &Val::Vec2(ref v1)) => Ok(v0.components() == v1.components()),
+			(&Val::Vec3(ref v0), &Val::Vec3(ref v1)) => Ok(v0.components() == v1.components()),
+			(&Val::Mat3(ref m0), &Val::Mat3(ref m1)) => Ok(m0.components() == m1.components()),
 			_ => Ok(self.same(other))
 		}
 	}
 }
 
+//compares the components of two vec2s, vec3s or mat3s in the same way as two flo keys
+fn components_eqv(c0: &[Flo], c1: &[Flo]) -> bool {
+	c0.iter().zip(c1.iter()).all(|(&f0, &f1)| Val::Flo(f0).keys_eqv(&Val::Flo(f1)))
+}
+
 //`val0 == val` has the same semantics as `eq?`. for symmetry with the other equality methods,
 //it can be called as val0.eq(&val1). errors in `op-eq?` will panic - try_eq() is the alternative.
 impl PartialEq<Val> for Val {
@@ -703,6 +857,12 @@ impl PartialEq<Val> for Val {
 			(&Val::Tab(_), &Val::Tab(_)) => self.try_eq(other).unwrap(),
 			(&Val::Obj(_), &Val::Obj(_)) => self.try_eq(other).unwrap(),
 			(&Val::RData(_), &Val::RData(_)) => self.try_eq(other).unwrap(),
+			(&Val::Rec(_), &Val::Rec(_)) => self.try_eq(other).unwrap(),
+			(&Val::PArr(_), &Val::PArr(_)) => self.try_eq(other).unwrap(),
+			(&Val::PTab(_), &Val::PTab(_)) => self.try_eq(other).unwrap(),
+			(&Val::Vec2(_), &Val::Vec2(_)) => self.try_eq(other).unwrap(),
+			(&Val::Vec3(_), &Val::Vec3(_)) => self.try_eq(other).unwrap(),
+			(&Val::Mat3(_), &Val::Mat3(_)) => self.try_eq(other).unwrap(),
 			_ => self.same(other)
 		}
 	}
@@ -714,14 +874,14 @@ impl Val {
 	pub fn num_cmp(&self, other: &Val) -> Option<Ordering> {
 		match (self, other) {
 			(&Val::Int(i0), &Val::Int(i1)) => Some(i0.cmp(&i1)),
-			(&Val::Flo(f0), &Val::Int(i1)) => f0.partial_cmp(&(i1 as f32)),
-			(&Val::Int(i0), &Val::Flo(f1)) => (i0 as f32).partial_cmp(&f1),
+			(&Val::Flo(f0), &Val::Int(i1)) => f0.partial_cmp(&(i1 as Flo)),
+			(&Val::Int(i0), &Val::Flo(f1)) => (i0 as Flo).partial_cmp(&f1),
 			(&Val::Flo(f0), &Val::Flo(f1)) => f0.partial_cmp(&f1),
 
 			(&Val::Char(c0), &Val::Int(i1)) => Some((c0 as u32 as i32).cmp(&i1)),
-			(&Val::Char(c0), &Val::Flo(f1)) => (c0 as u32 as f32).partial_cmp(&f1),
+			(&Val::Char(c0), &Val::Flo(f1)) => (c0 as u32 as Flo).partial_cmp(&f1),
 			(&Val::Int(i0), &Val::Char(c1)) => Some(i0.cmp(&(c1 as u32 as i32))),
-			(&Val::Flo(f0), &Val::Char(c1)) => f0.partial_cmp(&(c1 as u32 as f32)),
+			(&Val::Flo(f0), &Val::Char(c1)) => f0.partial_cmp(&(c1 as u32 as Flo)),
 
 			(&Val::Char(c0), &Val::Char(c1)) => {
 				Some((c0 as u32 as i32).cmp(&(c1 as u32 as i32)))
@@ -730,6 +890,65 @@ impl Val {
 			_ => None
 		}
 	}
+
+	/**
+	Equivalent to [`(ord self other)`](https://gamelisp.rs/std/ord).
+
+	Numbers and characters are compared numerically. Otherwise, if either argument is an object
+	or `RData` with an `op-ord` method, that method is invoked with the other argument. It 
+	should return a negative number, zero or a positive number.
+
+	Any other combination of types is uncomparable, so this method fails with an error which
+	names both types.
+	*/
+
+	pub fn try_cmp(&self, other: &Val) -> GResult<Ordering> {
+		if let Some(ordering) = self.num_cmp(other) {
+			return Ok(ordering)
+		}
+
+		if let Some(result) = call_op_ord(self, other)? {
+			return ordering_from_op_ord(result)
+		}
+
+		if let Some(result) = call_op_ord(other, self)? {
+			return ordering_from_op_ord(result).map(Ordering::reverse)
+		}
+
+		bail!("uncomparable: {} and {}", describe_ord_type(self), describe_ord_type(other))
+	}
+}
+
+fn call_op_ord(receiver: &Val, arg: &Val) -> GResult<Option<Val>> {
+	match *receiver {
+		Val::Obj(ref obj) => obj.call_if_present(OP_ORD_SYM, &[arg]),
+		Val::RData(ref rdata) => rdata.call_if_present(OP_ORD_SYM, &[arg]),
+		_ => Ok(None)
+	}
+}
+
+fn ordering_from_op_ord(result: Val) -> GResult<Ordering> {
+	match result {
+		Val::Int(i) => Ok(i.cmp(&0)),
+		Val::Flo(f) if !f.is_nan() => Ok(f.partial_cmp(&0.0).unwrap()),
+		Val::Sym(LT_SYM) => Ok(Ordering::Less),
+		Val::Sym(NUM_EQ_SYM) => Ok(Ordering::Equal),
+		Val::Sym(GT_SYM) => Ok(Ordering::Greater),
+		result => bail!("op-ord should return a number, but it returned {}", result.a_type_name())
+	}
+}
+
+fn describe_ord_type(val: &Val) -> String {
+	match *val {
+		Val::Flo(f) if f.is_nan() => "a NaN flo".to_string(),
+		Val::Obj(ref obj) => match obj.class().name() {
+			Some(name) => format!("an obj of class {}", name),
+			None => "an obj".to_string()
+		},
+		Val::RData(ref rdata) => format!("an rdata of type {}", rdata.class_name()),
+		Val::Rec(ref rec) => format!("a rec of type {}", rec.name()),
+		ref val => val.a_type_name().to_string()
+	}
 }
 
 macro_rules! partial_cmp_method (
@@ -739,14 +958,14 @@ macro_rules! partial_cmp_method (
 			pub fn $num_name(&self, other: &Val) -> Option<$return_type> {
 				match (self, other) {
 					(&Val::Int(i0), &Val::Int(i1)) => Some(i0.$name(&i1)),
-					(&Val::Flo(f0), &Val::Int(i1)) => Some(f0.$name(&(i1 as f32))),
-					(&Val::Int(i0), &Val::Flo(f1)) => Some((i0 as f32).$name(&f1)),
+					(&Val::Flo(f0), &Val::Int(i1)) => Some(f0.$name(&(i1 as Flo))),
+					(&Val::Int(i0), &Val::Flo(f1)) => Some((i0 as Flo).$name(&f1)),
 					(&Val::Flo(f0), &Val::Flo(f1)) => Some(f0.$name(&f1)),
 
 					(&Val::Char(c0), &Val::Int(i1)) => Some((c0 as u32 as i32).$name(&i1)),
-					(&Val::Char(c0), &Val::Flo(f1)) => Some((c0 as u32 as f32).$name(&f1)),
+					(&Val::Char(c0), &Val::Flo(f1)) => Some((c0 as u32 as Flo).$name(&f1)),
 					(&Val::Int(i0), &Val::Char(c1)) => Some(i0.$name(&(c1 as u32 as i32))),
-					(&Val::Flo(f0), &Val::Char(c1)) => Some(f0.$name(&(c1 as u32 as f32))),
+					(&Val::Flo(f0), &Val::Char(c1)) => Some(f0.$name(&(c1 as u32 as Flo))),
 
 					(&Val::Char(c0), &Val::Char(c1)) => {
 						Some((c0 as u32 as i32).$name(&(c1 as u32 as i32)))
@@ -764,6 +983,256 @@ partial_cmp_method!(num_le, le, bool);
 partial_cmp_method!(num_gt, gt, bool);
 partial_cmp_method!(num_ge, ge, bool);
 
+//-------------------------------------------------------------------------------------------------
+// Val canonical ordering
+//-------------------------------------------------------------------------------------------------
+
+impl Val {
+	/**
+	Equivalent to [`(canonical-cmp self other)`](https://gamelisp.rs/std/canonical-cmp).
+
+	This is a total order over every value, which is useful for sorting heterogeneous keys into
+	a deterministic order. Unlike [`try_cmp`](#method.try_cmp), it never fails, and it never 
+	invokes any methods.
+
+	Values are ordered by category first, and then by their contents:
+
+	1. `#n`.
+	2. Bools: `#f` before `#t`.
+	3. Ints and flos, numerically, without any loss of precision. An int sorts before an equal
+	   flo, `-0.0` sorts before `0.0`, and NaN sorts after every other number. All NaNs are 
+	   equal to one another.
+	4. Chars, by Unicode scalar value.
+	5. Syms, by name.
+	6. Strs, by contents.
+	7. Arrs, lexicographically. An arr sorts before any longer arr which begins with the same
+	   elements.
+	8. Tabs. Each tab's entries are sorted by key, and then the two entry lists are compared 
+	   lexicographically, comparing each entry's key and then its value.
+	9. Recs, by type name, and then lexicographically by their field values.
+	10. Objs, then classes, then gfns, then rfns, then coros, then iters, then rdata. These 
+	    don't have any canonical contents, so any two values of the same type are equal.
+	11. Parrs, lexicographically, in the same way as arrs.
+	12. Ptabs, in the same way as tabs.
+	13. Views. Any two views are equal.
+	14. Vec2s, then vec3s, then mat3s. Their components are compared lexicographically, in the
+	    same way as flos.
+
+	Names and contents are compared by Unicode scalar value, which matches the order of their 
+	UTF-8 bytes.
+
+	Cyclic values are supported. When the comparison encounters a pair of arrs, tabs or recs 
+	which it's already in the middle of comparing, it treats that pair as equal. Likewise, 
+	arrs, tabs and recs which are nested more than 256 levels deep are treated as equal.
+
+	This ordering is stable: it will only change in a semver-incompatible release.
+	*/
+
+	pub fn canonical_cmp(&self, other: &Val) -> Ordering {
+		canonical_cmp(self, other, &mut Vec::new())
+	}
+}
+
+const CANONICAL_MAX_DEPTH: usize = 256;
+
+fn canonical_rank(val: &Val) -> u8 {
+	match *val {
+		Val::Nil => 0,
+		Val::Bool(_) => 1,
+		Val::Int(_) | Val::Flo(_) => 2,
+		Val::Char(_) => 3,
+		Val::Sym(_) => 4,
+		Val::Str(_) => 5,
+		Val::Arr(_) => 6,
+		Val::Tab(_) => 7,
+		Val::Rec(_) => 8,
+		Val::Obj(_) => 9,
+		Val::Class(_) => 10,
+		Val::GFn(_) => 11,
+		Val::RFn(_) => 12,
+		Val::Coro(_) => 13,
+		Val::GIter(_) => 14,
+		Val::RData(_) => 15,
+		Val::PArr(_) => 16,
+		Val::PTab(_) => 17,
+		Val::View(_) => 18,
+		Val::Vec2(_) => 19,
+		Val::Vec3(_) => 20,
+		Val::Mat3(_) => 21
+	}
+}
+
+//`visiting` holds the addresses of each pair of arrs, tabs or recs which are currently being 
+//compared, so that we can detect cycles
+fn canonical_cmp(val0: &Val, val1: &Val, visiting: &mut Vec<(usize, usize)>) -> Ordering {
+	let rank_ordering = canonical_rank(val0).cmp(&canonical_rank(val1));
+	if rank_ordering != Ordering::Equal {
+		return rank_ordering
+	}
+
+	match (val0, val1) {
+		(&Val::Bool(b0), &Val::Bool(b1)) => b0.cmp(&b1),
+		(&Val::Int(i0), &Val::Int(i1)) => i0.cmp(&i1),
+		(&Val::Int(i0), &Val::Flo(f1)) => canonical_int_flo_cmp(i0, f1),
+		(&Val::Flo(f0), &Val::Int(i1)) => canonical_int_flo_cmp(i1, f0).reverse(),
+		(&Val::Flo(f0), &Val::Flo(f1)) => canonical_flo_cmp(f0, f1),
+		(&Val::Char(c0), &Val::Char(c1)) => c0.cmp(&c1),
+		(&Val::Sym(s0), &Val::Sym(s1)) => s0.name().cmp(&s1.name()),
+		(&Val::Str(ref s0), &Val::Str(ref s1)) => s0.iter().cmp(s1.iter()),
+		(&Val::Arr(ref a0), &Val::Arr(ref a1)) => {
+			let addresses = (&**a0 as *const Arr as usize, &**a1 as *const Arr as usize);
+			canonical_nested_cmp(addresses, visiting, |visiting| {
+				for (item0, item1) in a0.iter().zip(a1.iter()) {
+					let ordering = canonical_cmp(&item0, &item1, visiting);
+					if ordering != Ordering::Equal {
+						return ordering
+					}
+				}
+
+				a0.len().cmp(&a1.len())
+			})
+		}
+		(&Val::Tab(ref t0), &Val::Tab(ref t1)) => {
+			let addresses = (&**t0 as *const Tab as usize, &**t1 as *const Tab as usize);
+			canonical_nested_cmp(addresses, visiting, |visiting| {
+				let entries0 = canonical_entries(t0, visiting);
+				let entries1 = canonical_entries(t1, visiting);
+				for ((key0, value0), (key1, value1)) in entries0.iter().zip(entries1.iter()) {
+					let ordering = canonical_cmp(key0, key1, visiting)
+						.then_with(|| canonical_cmp(value0, value1, visiting));
+					if ordering != Ordering::Equal {
+						return ordering
+					}
+				}
+
+				entries0.len().cmp(&entries1.len())
+			})
+		}
+		(&Val::Rec(ref r0), &Val::Rec(ref r1)) => {
+			let addresses = (&**r0 as *const Rec as usize, &**r1 as *const Rec as usize);
+			canonical_nested_cmp(addresses, visiting, |visiting| {
+				let name_ordering = r0.name().name().cmp(&r1.name().name());
+				if name_ordering != Ordering::Equal {
+					return name_ordering
+				}
+
+				let (fields0, fields1) = (r0.field_vals(), r1.field_vals());
+				for (field0, field1) in fields0.iter().zip(fields1.iter()) {
+					let ordering = canonical_cmp(field0, field1, visiting);
+					if ordering != Ordering::Equal {
+						return ordering
+					}
+				}
+
+				fields0.len().cmp(&fields1.len())
+			})
+		}
+		(&Val::PArr(ref p0), &Val::PArr(ref p1)) => {
+			let addresses = (&**p0 as *const PArr as usize, &**p1 as *const PArr as usize);
+			canonical_nested_cmp(addresses, visiting, |visiting| {
+				for (item0, item1) in p0.iter().zip(p1.iter()) {
+					let ordering = canonical_cmp(&item0, &item1, visiting);
+					if ordering != Ordering::Equal {
+						return ordering
+					}
+				}
+
+				p0.len().cmp(&p1.len())
+			})
+		}
+		(&Val::PTab(ref p0), &Val::PTab(ref p1)) => {
+			let addresses = (&**p0 as *const PTab as usize, &**p1 as *const PTab as usize);
+			canonical_nested_cmp(addresses, visiting, |visiting| {
+				let entries0 = canonical_sort(p0.entries(), visiting);
+				let entries1 = canonical_sort(p1.entries(), visiting);
+				for ((key0, value0), (key1, value1)) in entries0.iter().zip(entries1.iter()) {
+					let ordering = canonical_cmp(key0, key1, visiting)
+						.then_with(|| canonical_cmp(value0, value1, visiting));
+					if ordering != Ordering::Equal {
+						return ordering
+					}
+				}
+
+				entries0.len().cmp(&entries1.len())
+			})
+		}
+		(&Val::Vec2(ref v0), &Val::Vec2(ref v1)) => {
+			canonical_components_cmp(v0.components(), v1.components())
+		}
+		(&Val::Vec3(ref v0), &Val::Vec3(ref v1)) => {
+			canonical_components_cmp(v0.components(), v1.components())
+		}
+		(&Val::Mat3(ref m0), &Val::Mat3(ref m1)) => {
+			canonical_components_cmp(m0.components(), m1.components())
+		}
+		_ => Ordering::Equal
+	}
+}
+
+fn canonical_flo_cmp(f0: Flo, f1: Flo) -> Ordering {
+	match (f0.is_nan(), f1.is_nan()) {
+		(true, true) => Ordering::Equal,
+		(true, false) => Ordering::Greater,
+		(false, true) => Ordering::Less,
+		(false, false) => f0.total_cmp(&f1)
+	}
+}
+
+fn canonical_components_cmp(c0: &[Flo], c1: &[Flo]) -> Ordering {
+	for (&f0, &f1) in c0.iter().zip(c1.iter()) {
+		let ordering = canonical_flo_cmp(f0, f1);
+		if ordering != Ordering::Equal {
+			return ordering
+		}
+	}
+
+	Ordering::Equal
+}
+
+fn canonical_int_flo_cmp(i: i32, f: Flo) -> Ordering {
+	if f.is_nan() {
+		Ordering::Less
+	} else {
+		//both types convert to f64 exactly
+		match (i as f64).partial_cmp(&(f as f64)).unwrap() {
+			Ordering::Equal => Ordering::Less,
+			ordering => ordering
+		}
+	}
+}
+
+fn canonical_nested_cmp<F>(
+	addresses: (usize, usize),
+	visiting: &mut Vec<(usize, usize)>,
+	f: F
+) -> Ordering
+where
+	F: FnOnce(&mut Vec<(usize, usize)>) -> Ordering
+{
+	if addresses.0 == addresses.1 || visiting.len() >= CANONICAL_MAX_DEPTH || 
+	   visiting.contains(&addresses) {
+		return Ordering::Equal
+	}
+
+	visiting.push(addresses);
+	let ordering = f(visiting);
+	visiting.pop();
+
+	ordering
+}
+
+fn canonical_entries(tab: &Tab, visiting: &mut Vec<(usize, usize)>) -> Vec<(Val, Val)> {
+	canonical_sort(tab.entries().iter().collect(), visiting)
+}
+
+fn canonical_sort(
+	mut entries: Vec<(Val, Val)>,
+	visiting: &mut Vec<(usize, usize)>
+) -> Vec<(Val, Val)> {
+	entries.sort_by(|(key0, _), (key1, _)| canonical_cmp(key0, key1, visiting));
+	entries
+}
+
 /**
 A thin wrapper over `Val` which enables it to be used as a key in a `HashTable`.
 
@@ -790,7 +1259,7 @@ impl Hash for Hashable {
 	fn hash<H: Hasher>(&self, state: &mut H) {
 		//the documentation for Hash states that if two values implement Eq and compare equal to
 		//one another, they must generate the same hash value. this means that our hash() method
-		//must deeply inspect arrs, strs and tabs, in the same way that keys_eqv does.
+		//must deeply inspect arrs, strs, tabs and recs, in the same way that keys_eqv does.
 		match self.0 {
 			Val::Nil => 0u8.hash(state),
 			Val::Int(i) => i.hash(state),
@@ -828,7 +1297,14 @@ impl Hash for Hashable {
 			Val::Class(ref root) => (&**root as *const _ as usize).hash(state),
 			Val::GFn(ref root) => (&**root as *const _ as usize).hash(state),
 			Val::Coro(ref root) => (&**root as *const _ as usize).hash(state),
-			Val::RData(ref root) => (&**root as *const _ as usize).hash(state)
+			Val::RData(ref root) => (&**root as *const _ as usize).hash(state),
+			Val::Rec(ref rec) => (**rec).hash(state),
+			Val::PArr(ref parr) => (**parr).hash(state),
+			Val::PTab(ref ptab) => (**ptab).hash(state),
+			Val::View(ref root) => (&**root as *const _ as usize).hash(state),
+			Val::Vec2(ref v) => (**v).hash(state),
+			Val::Vec3(ref v) => (**v).hash(state),
+			Val::Mat3(ref m) => (**m).hash(state)
 		}
 	}
 }
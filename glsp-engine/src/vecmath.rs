@@ -0,0 +1,473 @@
+use smallvec::{SmallVec};
+use std::hash::{Hash, Hasher};
+use super::engine::{glsp, stock_syms::*};
+use super::error::{GResult, out_of_bounds_error};
+use super::gc::{Allocate, GcHeader, Root, Slot, Visitor};
+use super::val::{Flo, Hashable, Val};
+
+//vec2s, vec3s and mat3s are small, immutable gc objects which store their components inline.
+//arithmetic on them is performed in rust, and allocates exactly one new object for its result;
+//there's no class lookup, no field storage and no method call. functions which return a
+//number, like (dot) or (dist), don't allocate at all. because these objects never refer to any
+//other gc object, the collector doesn't need to trace them.
+
+
+//-------------------------------------------------------------------------------------------------
+// Vec2, Vec3, Mat3
+//-------------------------------------------------------------------------------------------------
+
+/**
+The `vec2` primitive type: an immutable two-dimensional vector.
+
+Its components are [`Flo`s](type.Flo.html). New vectors can be constructed using
+[`glsp::vec2`](fn.vec2.html), or from GameLisp code using
+[`(vec2 x y)`](https://gamelisp.rs/std/vec2).
+
+A `vec2` can be converted into a `[f32; 2]` or an `(f32, f32)` using
+[`FromVal`](trait.FromVal.html), which makes it easy to pass to crates like `glam`.
+*/
+
+pub struct Vec2 {
+	header: GcHeader,
+	xy: [Flo; 2]
+}
+
+/**
+The `vec3` primitive type: an immutable three-dimensional vector.
+
+Its components are [`Flo`s](type.Flo.html). New vectors can be constructed using
+[`glsp::vec3`](fn.vec3.html), or from GameLisp code using
+[`(vec3 x y z)`](https://gamelisp.rs/std/vec3).
+
+A `vec3` can be converted into a `[f32; 3]` or an `(f32, f32, f32)` using
+[`FromVal`](trait.FromVal.html).
+*/
+
+pub struct Vec3 {
+	header: GcHeader,
+	xyz: [Flo; 3]
+}
+
+/**
+The `mat3` primitive type: an immutable 3x3 matrix.
+
+Its elements are [`Flo`s](type.Flo.html), stored in column-major order. New matrices can be
+constructed using [`glsp::mat3`](fn.mat3.html), or from GameLisp code using
+[`(mat3 x-axis y-axis z-axis)`](https://gamelisp.rs/std/mat3).
+
+A `mat3` can be converted into a `[[f32; 3]; 3]` of columns using
+[`FromVal`](trait.FromVal.html).
+*/
+
+pub struct Mat3 {
+	header: GcHeader,
+	elements: [Flo; 9]
+}
+
+impl Vec2 {
+	pub(crate) fn new(xy: [Flo; 2]) -> Vec2 {
+		Vec2 {
+			header: GcHeader::new(),
+			xy
+		}
+	}
+
+	///Returns the vector's first component.
+	pub fn x(&self) -> Flo {
+		self.xy[0]
+	}
+
+	///Returns the vector's second component.
+	pub fn y(&self) -> Flo {
+		self.xy[1]
+	}
+
+	///Returns the vector's components.
+	pub fn to_array(&self) -> [Flo; 2] {
+		self.xy
+	}
+
+	///Returns the vector's length. Equivalent to [`(len v)`](https://gamelisp.rs/std/len).
+	pub fn magnitude(&self) -> Flo {
+		self.xy.iter().map(|c| c * c).sum::<Flo>().sqrt()
+	}
+
+	/**
+	Returns one of the vector's components.
+
+	The key can be `x`, `y`, or an index. Equivalent to
+	[`[v key]`](https://gamelisp.rs/std/access).
+	*/
+	pub fn get(&self, key: &Val) -> GResult<Flo> {
+		component_index("vec2", 2, true, &Slot::from_val(key)).map(|i| self.xy[i])
+	}
+
+	///Returns `true` if `key` names one of the vector's components.
+	pub fn has(&self, key: &Val) -> bool {
+		has_component(2, true, &Slot::from_val(key))
+	}
+}
+
+impl Vec3 {
+	pub(crate) fn new(xyz: [Flo; 3]) -> Vec3 {
+		Vec3 {
+			header: GcHeader::new(),
+			xyz
+		}
+	}
+
+	///Returns the vector's first component.
+	pub fn x(&self) -> Flo {
+		self.xyz[0]
+	}
+
+	///Returns the vector's second component.
+	pub fn y(&self) -> Flo {
+		self.xyz[1]
+	}
+
+	///Returns the vector's third component.
+	pub fn z(&self) -> Flo {
+		self.xyz[2]
+	}
+
+	///Returns the vector's components.
+	pub fn to_array(&self) -> [Flo; 3] {
+		self.xyz
+	}
+
+	///Returns the vector's length. Equivalent to [`(len v)`](https://gamelisp.rs/std/len).
+	pub fn magnitude(&self) -> Flo {
+		self.xyz.iter().map(|c| c * c).sum::<Flo>().sqrt()
+	}
+
+	/**
+	Returns one of the vector's components.
+
+	The key can be `x`, `y`, `z`, or an index. Equivalent to
+	[`[v key]`](https://gamelisp.rs/std/access).
+	*/
+	pub fn get(&self, key: &Val) -> GResult<Flo> {
+		component_index("vec3", 3, true, &Slot::from_val(key)).map(|i| self.xyz[i])
+	}
+
+	///Returns `true` if `key` names one of the vector's components.
+	pub fn has(&self, key: &Val) -> bool {
+		has_component(3, true, &Slot::from_val(key))
+	}
+}
+
+impl Mat3 {
+	pub(crate) fn new(elements: [Flo; 9]) -> Mat3 {
+		Mat3 {
+			header: GcHeader::new(),
+			elements
+		}
+	}
+
+	/**
+	Returns one of the matrix's columns.
+
+	Panics if `i` is greater than 2.
+	*/
+	pub fn col(&self, i: usize) -> [Flo; 3] {
+		assert!(i < 3, "attempted to access column {} of a mat3", i);
+		let e = &self.elements;
+		[e[i * 3], e[i * 3 + 1], e[i * 3 + 2]]
+	}
+
+	///Returns the matrix's columns.
+	pub fn to_cols_array_2d(&self) -> [[Flo; 3]; 3] {
+		[self.col(0), self.col(1), self.col(2)]
+	}
+
+	///Returns the matrix's elements, in column-major order.
+	pub fn to_cols_array(&self) -> [Flo; 9] {
+		self.elements
+	}
+
+	/**
+	Returns one of the matrix's columns as a new `vec3`.
+
+	The key must be an index. Equivalent to [`[m key]`](https://gamelisp.rs/std/access).
+	*/
+	pub fn get(&self, key: &Val) -> GResult<Root<Vec3>> {
+		let i = component_index("mat3", 3, false, &Slot::from_val(key))?;
+		Ok(glsp::alloc(Vec3::new(self.col(i))))
+	}
+
+	///Returns `true` if `key` is the index of one of the matrix's columns.
+	pub fn has(&self, key: &Val) -> bool {
+		has_component(3, false, &Slot::from_val(key))
+	}
+}
+
+macro_rules! impl_vector_traits {
+	($($type:ident, $field:ident);+) => (
+		$(
+			impl Allocate for $type {
+				fn header(&self) -> &GcHeader {
+					&self.header
+				}
+
+				fn visit_gcs<V: Visitor>(&self, _visitor: &mut V) { }
+
+				fn clear_gcs(&self) { }
+
+				fn owned_memory_usage(&self) -> usize {
+					0
+				}
+			}
+
+			//consistent with keys_eqv(), which compares each pair of components in the same way
+			//as two flo keys
+			impl Hash for $type {
+				fn hash<H: Hasher>(&self, state: &mut H) {
+					for &component in self.$field.iter() {
+						Hashable(Val::Flo(component)).hash(state);
+					}
+				}
+			}
+
+			impl $type {
+				pub(crate) fn components(&self) -> &[Flo] {
+					&self.$field
+				}
+			}
+		)+
+	);
+}
+
+impl_vector_traits!(Vec2, xy; Vec3, xyz; Mat3, elements);
+
+
+//-------------------------------------------------------------------------------------------------
+// component access
+//-------------------------------------------------------------------------------------------------
+
+//converts the key in [v 'x] or [v 0] into an index into the vector's components. a mat3 is
+//indexed by column, so it only accepts an int.
+fn component_index(type_name: &str, len: usize, named: bool, key: &Slot) -> GResult<usize> {
+	let i = match *key {
+		Slot::Sym(X_SYM) if named => 0,
+		Slot::Sym(Y_SYM) if named => 1,
+		Slot::Sym(Z_SYM) if named && len == 3 => 2,
+		Slot::Sym(sym) if named => bail!("{} has no component named {}", type_name, sym),
+		Slot::Int(i) => {
+			let index = if i < 0 { i as isize + len as isize } else { i as isize };
+			if index < 0 || index as usize >= len {
+				return Err(out_of_bounds_error(type_name, len, i))
+			}
+
+			index as usize
+		}
+		ref key => bail!("attempted to index a {} with {}", type_name, key.a_type_name())
+	};
+
+	Ok(i)
+}
+
+fn has_component(len: usize, named: bool, key: &Slot) -> bool {
+	match *key {
+		Slot::Sym(X_SYM) | Slot::Sym(Y_SYM) => named,
+		Slot::Sym(Z_SYM) => named && len == 3,
+		Slot::Int(i) => i >= -(len as i32) && i < len as i32,
+		_ => false
+	}
+}
+
+//the implementation of [v key] for a vec2, vec3 or mat3. returns None for any other type.
+pub(crate) fn access(coll: &Slot, key: &Slot) -> Option<GResult<Slot>> {
+	Some(match *coll {
+		Slot::Vec2(ref v) => component_index("vec2", 2, true, key).map(|i| Slot::Flo(v.xy[i])),
+		Slot::Vec3(ref v) => component_index("vec3", 3, true, key).map(|i| Slot::Flo(v.xyz[i])),
+		Slot::Mat3(ref m) => {
+			component_index("mat3", 3, false, key).map(|i| Operand::Vec3(m.col(i)).into_slot())
+		}
+		_ => return None
+	})
+}
+
+//the implementation of (has? v key) for a vec2, vec3 or mat3. returns None for any other type.
+pub(crate) fn has(coll: &Slot, key: &Slot) -> Option<bool> {
+	match *coll {
+		Slot::Vec2(_) => Some(has_component(2, true, key)),
+		Slot::Vec3(_) => Some(has_component(3, true, key)),
+		Slot::Mat3(_) => Some(has_component(3, false, key)),
+		_ => None
+	}
+}
+
+pub(crate) fn is_vector(slot: &Slot) -> bool {
+	matches!(*slot, Slot::Vec2(_) | Slot::Vec3(_) | Slot::Mat3(_))
+}
+
+//the values which a vec2, vec3 or mat3 presents to FromVal when it's converted into a Rust array
+//or tuple: two or three flos, or three vec3 columns
+pub(crate) fn elements(val: &Val) -> Option<SmallVec<[Val; 3]>> {
+	match *val {
+		Val::Vec2(ref v) => Some(v.xy.iter().map(|&c| Val::Flo(c)).collect()),
+		Val::Vec3(ref v) => Some(v.xyz.iter().map(|&c| Val::Flo(c)).collect()),
+		Val::Mat3(ref m) => {
+			Some((0 .. 3).map(|i| Val::Vec3(glsp::alloc(Vec3::new(m.col(i))))).collect())
+		}
+		_ => None
+	}
+}
+
+
+//-------------------------------------------------------------------------------------------------
+// arithmetic
+//-------------------------------------------------------------------------------------------------
+
+/**
+An arithmetic operation which can be passed to [`glsp::vector_arith`](fn.vector_arith.html).
+*/
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum VectorOp {
+	Add,
+	Sub,
+	Mul,
+	Div
+}
+
+impl VectorOp {
+	fn verb(self) -> &'static str {
+		match self {
+			VectorOp::Add => "add",
+			VectorOp::Sub => "subtract",
+			VectorOp::Mul => "multiply",
+			VectorOp::Div => "divide"
+		}
+	}
+
+	fn apply(self, a: Flo, b: Flo) -> Flo {
+		match self {
+			VectorOp::Add => a + b,
+			VectorOp::Sub => a - b,
+			VectorOp::Mul => a * b,
+			VectorOp::Div => a / b
+		}
+	}
+}
+
+#[derive(Copy, Clone)]
+enum Operand {
+	Num(Flo),
+	Vec2([Flo; 2]),
+	Vec3([Flo; 3]),
+	Mat3([Flo; 9])
+}
+
+impl Operand {
+	fn from_slot(slot: &Slot) -> Option<Operand> {
+		match *slot {
+			Slot::Int(i) => Some(Operand::Num(i as Flo)),
+			Slot::Flo(f) => Some(Operand::Num(f)),
+			Slot::Vec2(ref v) => Some(Operand::Vec2(v.xy)),
+			Slot::Vec3(ref v) => Some(Operand::Vec3(v.xyz)),
+			Slot::Mat3(ref m) => Some(Operand::Mat3(m.elements)),
+			_ => None
+		}
+	}
+
+	fn into_slot(self) -> Slot {
+		match self {
+			Operand::Num(f) => Slot::Flo(f),
+			Operand::Vec2(xy) => Slot::Vec2(glsp::alloc_gc(Vec2::new(xy))),
+			Operand::Vec3(xyz) => Slot::Vec3(glsp::alloc_gc(Vec3::new(xyz))),
+			Operand::Mat3(elements) => Slot::Mat3(glsp::alloc_gc(Mat3::new(elements)))
+		}
+	}
+
+	fn a_type_name(&self) -> &'static str {
+		match *self {
+			Operand::Num(_) => "a number",
+			Operand::Vec2(_) => "a vec2",
+			Operand::Vec3(_) => "a vec3",
+			Operand::Mat3(_) => "a mat3"
+		}
+	}
+}
+
+fn zip<const N: usize>(a: [Flo; N], b: [Flo; N], f: impl Fn(Flo, Flo) -> Flo) -> [Flo; N] {
+	let mut result = a;
+	for i in 0 .. N {
+		result[i] = f(a[i], b[i]);
+	}
+	result
+}
+
+fn map<const N: usize>(a: [Flo; N], f: impl Fn(Flo) -> Flo) -> [Flo; N] {
+	let mut result = a;
+	for component in result.iter_mut() {
+		*component = f(*component);
+	}
+	result
+}
+
+fn mat_vec_mul(m: &[Flo; 9], v: [Flo; 3]) -> [Flo; 3] {
+	let mut result = [0.0; 3];
+	for (row, component) in result.iter_mut().enumerate() {
+		*component = m[row] * v[0] + m[3 + row] * v[1] + m[6 + row] * v[2];
+	}
+	result
+}
+
+fn mat_mul(m0: &[Flo; 9], m1: &[Flo; 9]) -> [Flo; 9] {
+	let mut result = [0.0; 9];
+	for col in 0 .. 3 {
+		let product = mat_vec_mul(m0, [m1[col * 3], m1[col * 3 + 1], m1[col * 3 + 2]]);
+		result[col * 3 .. col * 3 + 3].copy_from_slice(&product);
+	}
+	result
+}
+
+//vectors of the same type are combined component-wise, and a number is combined with each
+//component of a vector or matrix. a mat3 multiplied by a vec3 or mat3 is a matrix product.
+fn arith(op: VectorOp, a: Operand, b: Operand) -> GResult<Operand> {
+	let f = |a, b| op.apply(a, b);
+
+	Ok(match (a, b) {
+		(Operand::Vec2(a), Operand::Vec2(b)) => Operand::Vec2(zip(a, b, f)),
+		(Operand::Vec2(a), Operand::Num(b)) => Operand::Vec2(map(a, |a| f(a, b))),
+		(Operand::Num(a), Operand::Vec2(b)) => Operand::Vec2(map(b, |b| f(a, b))),
+		(Operand::Vec3(a), Operand::Vec3(b)) => Operand::Vec3(zip(a, b, f)),
+		(Operand::Vec3(a), Operand::Num(b)) => Operand::Vec3(map(a, |a| f(a, b))),
+		(Operand::Num(a), Operand::Vec3(b)) => Operand::Vec3(map(b, |b| f(a, b))),
+		(Operand::Mat3(a), Operand::Vec3(b)) if op == VectorOp::Mul => {
+			Operand::Vec3(mat_vec_mul(&a, b))
+		}
+		(Operand::Mat3(a), Operand::Mat3(b)) if op == VectorOp::Mul => {
+			Operand::Mat3(mat_mul(&a, &b))
+		}
+		(Operand::Mat3(a), Operand::Mat3(b)) if op != VectorOp::Div => Operand::Mat3(zip(a, b, f)),
+		(Operand::Mat3(a), Operand::Num(b)) => Operand::Mat3(map(a, |a| f(a, b))),
+		(Operand::Num(a), Operand::Mat3(b)) => Operand::Mat3(map(b, |b| f(a, b))),
+		(a, b) => bail!("attempted to {} {} and {}", op.verb(), a.a_type_name(), b.a_type_name())
+	})
+}
+
+//the implementation of glsp::vector_arith, used by the +, -, * and / rfns
+pub(crate) fn val_arith(op: VectorOp, arg0: &Val, arg1: &Val) -> GResult<Val> {
+	Ok(slot_arith(op, &Slot::from_val(arg0), &Slot::from_val(arg1))?.into_root())
+}
+
+//called by the OpAdd, OpSub, OpMul and OpDiv instrs when either argument is a vec2, vec3 or mat3
+pub(crate) fn slot_arith(op: VectorOp, arg0: &Slot, arg1: &Slot) -> GResult<Slot> {
+	match (Operand::from_slot(arg0), Operand::from_slot(arg1)) {
+		(Some(a), Some(b)) => Ok(arith(op, a, b)?.into_slot()),
+		_ => bail!("attempted to {} {} and {}", op.verb(), arg0.a_type_name(), arg1.a_type_name())
+	}
+}
+
+//called by the OpNeg instr
+pub(crate) fn slot_neg(arg: &Slot) -> Option<Slot> {
+	match Operand::from_slot(arg)? {
+		Operand::Vec2(xy) => Some(Operand::Vec2(map(xy, |c| -c)).into_slot()),
+		Operand::Vec3(xyz) => Some(Operand::Vec3(map(xyz, |c| -c)).into_slot()),
+		Operand::Mat3(elements) => Some(Operand::Mat3(map(elements, |c| -c)).into_slot()),
+		Operand::Num(_) => None
+	}
+}
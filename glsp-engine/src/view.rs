@@ -0,0 +1,443 @@
+use super::engine::{BorrowSite, glsp, GStore, stock_syms::*, Sym, ToSym};
+use super::error::{GError, GResult, out_of_bounds_error};
+use super::gc::{Allocate, GcHeader, Root, Slot, Visitor};
+use super::val::{Flo};
+use super::wrap::{FromVal, ToVal};
+use std::any::{Any};
+use std::cell::{RefCell};
+use std::convert::{TryFrom};
+use std::rc::{Rc};
+
+/*
+views give scripts direct access to a Vec of Rust structs, without copying each struct into a
+tab or an RData.
+
+the host registers a layout for a Rust type, listing the name and primitive type of each field
+which should be visible to scripts, alongside a projection fn which borrows that field from the
+struct. it then lends a `&mut Vec<T>` using glsp::with_scoped_views. the Vec's buffer is moved
+into a ViewLend (which only moves its pointer, length and capacity), and it's moved back when
+the scope ends.
+
+the lent Vec is type-erased as a `dyn ViewStorage`. indexing the lend's View with an int
+allocates an element View, and indexing an element View with a sym makes a single dynamic call
+into the typed storage, which searches the layout for the field and then reads or writes it
+through its projection fn. the engine forbids unsafe code unless the "unsafe-internals" feature
+is enabled, so we use projections rather than raw byte offsets; a projection like
+`|t| &mut t.x` compiles down to an offset from the struct's address anyway.
+
+every View which is created from the same lend shares the ViewLend. when the lend's scope ends,
+its storage is taken, so that every View which was created from it expires at once.
+*/
+
+/**
+The primitive type of a field in a [`View`](struct.View.html), and a function which borrows
+that field from the Rust struct.
+
+Passed to [`glsp::register_view`](fn.register_view.html). Integer fields are read as an `int`,
+floating-point fields are read as a `flo`, and `Bool` fields are read as a `bool`. The
+function will usually be a closure which doesn't capture anything, such as `|t| &mut t.x`.
+*/
+
+pub enum ViewField<T> {
+	Bool(fn(&mut T) -> &mut bool),
+	I8(fn(&mut T) -> &mut i8),
+	I16(fn(&mut T) -> &mut i16),
+	I32(fn(&mut T) -> &mut i32),
+	U8(fn(&mut T) -> &mut u8),
+	U16(fn(&mut T) -> &mut u16),
+	U32(fn(&mut T) -> &mut u32),
+	F32(fn(&mut T) -> &mut f32),
+	F64(fn(&mut T) -> &mut f64)
+}
+
+impl<T> Clone for ViewField<T> {
+	fn clone(&self) -> ViewField<T> {
+		*self
+	}
+}
+
+impl<T> Copy for ViewField<T> { }
+
+impl<T> ViewField<T> {
+	///Returns the name of the field's Rust type, such as `"f32"`.
+	pub fn type_name(&self) -> &'static str {
+		match *self {
+			ViewField::Bool(_) => "bool",
+			ViewField::I8(_) => "i8",
+			ViewField::I16(_) => "i16",
+			ViewField::I32(_) => "i32",
+			ViewField::U8(_) => "u8",
+			ViewField::U16(_) => "u16",
+			ViewField::U32(_) => "u32",
+			ViewField::F32(_) => "f32",
+			ViewField::F64(_) => "f64"
+		}
+	}
+}
+
+pub(crate) struct ViewLayout<T> {
+	name: Sym,
+	fields: Box<[(Sym, ViewField<T>)]>
+}
+
+impl<T> ViewLayout<T> {
+	pub(crate) fn new(name: Sym, fields: &[(&str, ViewField<T>)]) -> GResult<ViewLayout<T>> {
+		let mut syms = Vec::<(Sym, ViewField<T>)>::with_capacity(fields.len());
+		for &(field_name, field) in fields {
+			let sym = glsp::sym(field_name)?;
+			ensure!(!syms.iter().any(|&(prev, _)| prev == sym),
+			        "duplicate field '{}' in the view type {}", sym, name);
+
+			syms.push((sym, field));
+		}
+
+		Ok(ViewLayout {
+			name,
+			fields: syms.into_boxed_slice()
+		})
+	}
+}
+
+//a lent Vec<T>, with its type erased
+trait ViewStorage: GStore + 'static {
+	fn has(&self, key: Sym) -> bool;
+	fn get(&mut self, i: usize, key: Sym) -> GResult<Slot>;
+	fn set(&mut self, i: usize, key: Sym, value: &Slot) -> GResult<()>;
+	fn into_any(self: Box<Self>) -> Box<dyn Any>;
+}
+
+struct LentVec<T> {
+	vec: Vec<T>,
+	layout: Rc<ViewLayout<T>>
+}
+
+impl<T: GStore + 'static> LentVec<T> {
+	fn field(&self, key: Sym) -> GResult<ViewField<T>> {
+		match self.layout.fields.iter().find(|&&(name, _)| name == key) {
+			Some(&(_, field)) => Ok(field),
+			None => bail!("attempted to access nonexistent field '{}' in a {} view",
+			              key, self.layout.name)
+		}
+	}
+
+	fn type_error(&self, key: Sym, field: ViewField<T>, value: &Slot) -> GError {
+		match (field, value) {
+			(ViewField::Bool(_), _) | (ViewField::F32(_), _) | (ViewField::F64(_), _) => (),
+			(_, &Slot::Int(i)) => {
+				return error!("attempted to assign {} to the {} field '{}' of a {} view, which \
+				               is out of range", i, field.type_name(), key, self.layout.name)
+			}
+			_ => ()
+		}
+
+		error!("attempted to assign {} to the {} field '{}' of a {} view",
+		       value.a_type_name(), field.type_name(), key, self.layout.name)
+	}
+}
+
+impl<T: GStore + 'static> ViewStorage for LentVec<T> {
+	fn has(&self, key: Sym) -> bool {
+		self.layout.fields.iter().any(|&(name, _)| name == key)
+	}
+
+	fn get(&mut self, i: usize, key: Sym) -> GResult<Slot> {
+		let field = self.field(key)?;
+		let elem = &mut self.vec[i];
+
+		Ok(match field {
+			ViewField::Bool(f) => Slot::Bool(*f(elem)),
+			ViewField::I8(f) => Slot::Int(*f(elem) as i32),
+			ViewField::I16(f) => Slot::Int(*f(elem) as i32),
+			ViewField::I32(f) => Slot::Int(*f(elem)),
+			ViewField::U8(f) => Slot::Int(*f(elem) as i32),
+			ViewField::U16(f) => Slot::Int(*f(elem) as i32),
+			ViewField::U32(f) => {
+				let u = *f(elem);
+				match i32::try_from(u) {
+					Ok(i) => Slot::Int(i),
+					Err(_) => bail!("the u32 field '{}' of a {} view holds {}, which is too \
+					                 large for an int", key, self.layout.name, u)
+				}
+			}
+			ViewField::F32(f) => Slot::Flo(*f(elem) as Flo),
+			ViewField::F64(f) => Slot::Flo(*f(elem) as Flo)
+		})
+	}
+
+	fn set(&mut self, i: usize, key: Sym, value: &Slot) -> GResult<()> {
+		let field = self.field(key)?;
+
+		macro_rules! int_field {
+			($f:expr) => ({
+				let converted = match *value {
+					Slot::Int(int) => TryFrom::try_from(int).ok(),
+					_ => None
+				};
+
+				match converted {
+					Some(converted) => *$f(&mut self.vec[i]) = converted,
+					None => return Err(self.type_error(key, field, value))
+				}
+			});
+		}
+
+		macro_rules! flo_field {
+			($f:expr, $t:ty) => ({
+				match *value {
+					Slot::Int(int) => *$f(&mut self.vec[i]) = int as $t,
+					Slot::Flo(flo) => *$f(&mut self.vec[i]) = flo as $t,
+					_ => return Err(self.type_error(key, field, value))
+				}
+			});
+		}
+
+		match field {
+			ViewField::Bool(f) => match *value {
+				Slot::Bool(b) => *f(&mut self.vec[i]) = b,
+				_ => return Err(self.type_error(key, field, value))
+			},
+			ViewField::I8(f) => int_field!(f),
+			ViewField::I16(f) => int_field!(f),
+			ViewField::I32(f) => int_field!(f),
+			ViewField::U8(f) => int_field!(f),
+			ViewField::U16(f) => int_field!(f),
+			ViewField::U32(f) => int_field!(f),
+			ViewField::F32(f) => flo_field!(f, f32),
+			ViewField::F64(f) => flo_field!(f, f64)
+		}
+
+		Ok(())
+	}
+
+	fn into_any(self: Box<Self>) -> Box<dyn Any> {
+		self
+	}
+}
+
+pub(crate) struct ViewLend {
+	name: Sym,
+	storage: RefCell<Option<Box<dyn ViewStorage>>>,
+	len: usize,
+	site: BorrowSite
+}
+
+impl ViewLend {
+	pub(crate) fn new<T>(layout: Rc<ViewLayout<T>>, vec: Vec<T>, site: BorrowSite) -> ViewLend
+	where
+		T: GStore + 'static
+	{
+		ViewLend {
+			name: layout.name,
+			len: vec.len(),
+			storage: RefCell::new(Some(Box::new(LentVec { vec, layout }))),
+			site
+		}
+	}
+
+	//expires the lend, returning the Vec which was lent
+	pub(crate) fn reclaim<T: 'static>(&self) -> Vec<T> {
+		let storage = self.storage.borrow_mut().take().unwrap();
+		match storage.into_any().downcast::<LentVec<T>>() {
+			Ok(lent) => lent.vec,
+			Err(_) => unreachable!()
+		}
+	}
+}
+
+/**
+The `view` primitive type.
+
+A view provides direct access to a `Vec` of Rust structs which has been lent to GameLisp
+using [`glsp::with_scoped_views`](fn.with_scoped_views.html). The struct's layout must have
+been registered using [`glsp::register_view`](fn.register_view.html).
+
+There are two kinds of view. The view which is passed to `with_scoped_views` refers to the
+whole `Vec`: indexing it with an int, as in `[views i]`, returns an element view. Indexing an
+element view with a field name, as in `[v 'x]`, reads that field from the Rust struct, and
+assigning to it, as in `(= [v 'x] 3.0)`, writes the field.
+
+When `with_scoped_views` returns, every view which refers to the `Vec` expires. Any later
+attempt to access an expired view triggers an `expired-view` error.
+*/
+
+pub struct View {
+	header: GcHeader,
+	lend: Rc<ViewLend>,
+	index: Option<usize>
+}
+
+impl Allocate for View {
+	fn header(&self) -> &GcHeader {
+		&self.header
+	}
+
+	fn visit_gcs<V: Visitor>(&self, _visitor: &mut V) { }
+
+	fn clear_gcs(&self) { }
+
+	fn owned_memory_usage(&self) -> usize {
+		0
+	}
+}
+
+impl View {
+	pub(crate) fn new(lend: Rc<ViewLend>, index: Option<usize>) -> View {
+		View {
+			header: GcHeader::new(),
+			lend,
+			index
+		}
+	}
+
+	/**
+	Returns the name of the Rust type which this view refers to.
+	*/
+	pub fn type_name(&self) -> Sym {
+		self.lend.name
+	}
+
+	/**
+	Returns the number of elements in the `Vec` which this view refers to.
+
+	For an element view, this is the length of the `Vec` which the element belongs to.
+
+	Equivalent to [`(len views)`](https://gamelisp.rs/std/len).
+	*/
+	pub fn len(&self) -> usize {
+		self.lend.len
+	}
+
+	/**
+	Returns `true` if the `Vec` which this view refers to is empty.
+	*/
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/**
+	Returns the index of the element which this view refers to, or `None` if it refers to the
+	whole `Vec`.
+	*/
+	pub fn index(&self) -> Option<usize> {
+		self.index
+	}
+
+	/**
+	Returns `true` if the `Vec` which this view refers to has been returned to its lender.
+
+	Equivalent to [`(expired? v)`](https://gamelisp.rs/std/expired-p).
+	*/
+	pub fn is_expired(&self) -> bool {
+		match self.lend.storage.try_borrow() {
+			Ok(storage) => storage.is_none(),
+			Err(_) => false
+		}
+	}
+
+	/**
+	Returns `true` if this is an element view which has a field with the given name.
+
+	Equivalent to [`(has? v key)`](https://gamelisp.rs/std/has-p).
+	*/
+	pub fn has<S: ToSym>(&self, key: S) -> GResult<bool> {
+		let key = key.to_sym()?;
+		match (self.index, &*self.lend.storage.borrow()) {
+			(Some(_), Some(storage)) => Ok(storage.has(key)),
+			_ => Ok(false)
+		}
+	}
+
+	/**
+	Returns an element view which refers to the element at index `i` of this view's `Vec`.
+
+	A negative index counts backwards from the end of the `Vec`. Returns an `Err` if the index
+	is out of bounds, if this is an element view, or if the view has expired.
+
+	Equivalent to [`[views i]`](https://gamelisp.rs/std/access).
+	*/
+	pub fn element(&self, i: isize) -> GResult<Root<View>> {
+		if self.is_expired() {
+			return Err(self.expired_error("element"))
+		}
+
+		ensure!(self.index.is_none(), "attempted to index an element view of {} with an int",
+		        self.type_name());
+
+		let len = self.lend.len;
+		let index = if i < 0 { i + len as isize } else { i };
+		if index < 0 || index as usize >= len {
+			return Err(out_of_bounds_error("view", len, i))
+		}
+
+		Ok(glsp::alloc(View::new(Rc::clone(&self.lend), Some(index as usize))))
+	}
+
+	/**
+	Reads a field from the Rust struct which this element view refers to.
+
+	Equivalent to [`[v key]`](https://gamelisp.rs/std/access).
+	*/
+	pub fn get<S: ToSym, V: FromVal>(&self, key: S) -> GResult<V> {
+		V::from_slot(&self.get_slot(key.to_sym()?)?)
+	}
+
+	/**
+	Writes a field of the Rust struct which this element view refers to.
+
+	Integer fields can only be assigned an int which is within their range. Floating-point
+	fields can be assigned an int or a flo. `Bool` fields can only be assigned a bool.
+
+	Equivalent to [`(= [v key] value)`](https://gamelisp.rs/std/set-access).
+	*/
+	pub fn set<S: ToSym, V: ToVal>(&self, key: S, value: V) -> GResult<()> {
+		self.set_slot(key.to_sym()?, &value.to_slot()?)
+	}
+
+	//the fast path for OpAccess
+	pub(crate) fn access(&self, key: &Slot) -> GResult<Slot> {
+		match *key {
+			Slot::Sym(sym) => self.get_slot(sym),
+			Slot::Int(i) => Ok(Slot::View(self.element(i as isize)?.into_gc())),
+			ref key => bail!("indexed a view with {}", key.a_type_name())
+		}
+	}
+
+	pub(crate) fn get_slot(&self, key: Sym) -> GResult<Slot> {
+		let index = self.field_index(key)?;
+		match self.lend.storage.try_borrow_mut() {
+			Ok(mut storage) => match *storage {
+				Some(ref mut storage) => storage.get(index, key),
+				None => Err(self.expired_error("get"))
+			},
+			Err(_) => bail!("attempted to access a {} view while it was being accessed",
+			                self.type_name())
+		}
+	}
+
+	pub(crate) fn set_slot(&self, key: Sym, value: &Slot) -> GResult<()> {
+		let index = self.field_index(key)?;
+		match self.lend.storage.try_borrow_mut() {
+			Ok(mut storage) => match *storage {
+				Some(ref mut storage) => storage.set(index, key, value),
+				None => Err(self.expired_error("set"))
+			},
+			Err(_) => bail!("attempted to access a {} view while it was being accessed",
+			                self.type_name())
+		}
+	}
+
+	fn field_index(&self, key: Sym) -> GResult<usize> {
+		match self.index {
+			Some(index) => Ok(index),
+			None => bail!("attempted to access the field '{}' of a {} view which refers to \
+			               the whole Vec. index it with an int first", key, self.type_name())
+		}
+	}
+
+	fn expired_error(&self, method: &str) -> GError {
+		let message = format!("View::{} failed: attempted to access a {} view after it \
+		                       expired. it was lent by {}", method, self.type_name(),
+		                       self.lend.site);
+		GError::from_val(EXPIRED_VIEW_SYM).with_source(GError::from_str(&message))
+	}
+}
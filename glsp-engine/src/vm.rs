@@ -1,23 +1,35 @@
 use smallvec::{SmallVec};
-use std::{i32, f32, fmt};
+use std::{i32, fmt};
+use std::cmp::{Ordering};
 use std::cell::{Cell, RefCell, RefMut};
 use std::convert::{From};
 use std::iter::{FromIterator};
 use std::mem::{forget, replace};
+use std::sync::{Arc};
 use super::class::{Class, Obj};
-use super::code::{Bytecode, Coro, GFn, Instr, PrivCoroState, Stay, StaySource};
+use super::code::{
+	Bytecode, Coro, GFn, InlineCache, Instr, PrivCoroState, Stay, StaySource
+};
 use super::collections::{Arr, DequeAccess, DequeOps, Str};
 use super::engine::{
-	Filename, glsp, Guard, RData, Span, SpanStorage::Expanded, 
-	stock_syms::*, Sym, with_heap
+	ExecutionSlot, Filename, glsp, Guard, Limits, RData, RFn, SafepointAction, Span, 
+	SpanStorage::Expanded, stock_syms::*, Sym, with_heap
+};
+use super::error::{
+	frozen_global_error, GError, GResult, nonexistent_field_error, nonexistent_meth_error,
+	out_of_bounds_error, unbound_symbol_error
 };
-use super::error::{GError, GResult};
-use super::gc::{Allocate, Gc, Slot, Root};
+use super::gc::{Allocate, Gc, Slot, StackVisitor, Root, Visitor};
 use super::iter::{GIterLen, IterableOps};
+use super::suggest::{did_you_mean};
 use super::transform::{Predicate};
-use super::val::{Val};
+use super::val::{Flo, Val};
+use super::vecmath::{self, VectorOp};
 use super::wrap::{CallableOps};
 
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Instant};
+
 
 //-------------------------------------------------------------------------------------------------
 // Vm
@@ -26,8 +38,10 @@ use super::wrap::{CallableOps};
 /*
 the rust callstack and the glsp callstack are one and the same - each new gfn/rfn/coro invocation
 is a new call to interpret(). in order to prevent stack overflows (which are safe, but would cause
-the process to abort), we enforce a hard glsp recursion limit, currently 256. the overhead of a 
-single non-inlined rust function call is about 7ns - a fraction of the total cost of a gfn call.
+the process to abort), we enforce a glsp recursion limit, which can be configured using
+glsp::set_max_call_depth. nested macro expansion is counted against the same limit, because the
+expander also recurses on the rust callstack. the overhead of a single non-inlined rust function
+call is about 7ns - a fraction of the total cost of a gfn call.
 
 because rust doesn't support alloca, we also need to maintain parallel stacks (one Vec each) to 
 store registers, stays, and backtrace information. the register stack doubles up as a temporary
@@ -46,7 +60,39 @@ pub(crate) struct Vm {
 	//error-handling code will try to immutably borrow `frames` when it builds a stack trace.
 	frames: RefCell<Vec<Frame>>,
 
-	recursion: Cell<u32>
+	recursion: Cell<u32>,
+	max_call_depth: Cell<u32>,
+	stack_base: Cell<usize>,
+	stack_budget: Cell<Option<usize>>,
+	tail_calls: Cell<bool>,
+
+	//see glsp::set_fuel, glsp::set_deadline and glsp::call_limited. `metered` is true when any
+	//limit is set, so that the interpreter only needs to test a single flag for each instruction.
+	//`alloc_limit` is compared against Heap::allocated_bytes.
+	metered: Cell<bool>,
+	fuel: Cell<Option<u64>>,
+	alloc_limit: Cell<Option<usize>>,
+	#[cfg(not(target_arch = "wasm32"))] deadline: Cell<Option<Instant>>,
+	#[cfg(not(target_arch = "wasm32"))] deadline_countdown: Cell<u64>,
+
+	//see glsp::set_safepoint_callback. the callback is also metered: its countdown is decremented
+	//wherever fuel is consumed. `yield_requested` is set when the callback returns Yield, and 
+	//it's cleared by the interpret_frame which consumed the fuel.
+	safepoint_interval: Cell<Option<u64>>,
+	safepoint_countdown: Cell<u64>,
+	safepoint_callback: RefCell<Option<Box<dyn FnMut() -> SafepointAction>>>,
+	in_safepoint: Cell<bool>,
+	yield_requested: Cell<bool>,
+
+	//see InlineCache in code.rs
+	global_generation: Cell<u64>,
+
+	//the open (with-children) scopes, innermost last. each scope is an arr of the coros which
+	//were created while it was innermost. a scope is removed while its coroutine is paused.
+	pub(crate) child_scopes: RefCell<Vec<Root<Arr>>>,
+
+	//see glsp::current_execution_point
+	pub(crate) execution_slot: Arc<ExecutionSlot>
 }
 
 pub(crate) struct Stacks {
@@ -97,8 +143,10 @@ pub(crate) enum GlspApiName {
 	ParseAll,
 	ParseFile,
 	Parse1,
+	ParseWithOffsets,
 	Eval,
 	EvalMulti,
+	CompileFn,
 	Require,
 	Load,
 	LoadStr,
@@ -109,7 +157,8 @@ pub(crate) enum GlspApiName {
 	Expand,
 	ExpandMulti,
 	Expand1,
-	CoroFinish
+	CoroFinish,
+	CoroThrow
 }
 
 impl fmt::Display for GlspApiName {
@@ -121,8 +170,10 @@ impl fmt::Display for GlspApiName {
 			ParseAll => "parse_all",
 			ParseFile => "parse_file",
 			Parse1 => "parse_1",
+			ParseWithOffsets => "parse_with_offsets",
 			Eval => "eval",
 			EvalMulti => "eval-multi",
+			CompileFn => "compile_fn",
 			Require => "require",
 			Load => "load",
 			LoadStr => "load_str",
@@ -133,7 +184,8 @@ impl fmt::Display for GlspApiName {
 			Expand => "expand",
 			ExpandMulti => "expand-multi",
 			Expand1 => "expand_1",
-			CoroFinish => "coro_finish"
+			CoroFinish => "coro_finish",
+			CoroThrow => "coro_throw"
 		};
 
 		write!(f, "{}", name)
@@ -168,8 +220,373 @@ impl Vm {
 				defers: Vec::with_capacity(16)
 			}),
 			frames: RefCell::new(Vec::with_capacity(32)),
-			recursion: Cell::new(0)
+			recursion: Cell::new(0),
+			max_call_depth: Cell::new(DEFAULT_MAX_CALL_DEPTH),
+			stack_base: Cell::new(0),
+			stack_budget: Cell::new(None),
+			tail_calls: Cell::new(true),
+			metered: Cell::new(false),
+			fuel: Cell::new(None),
+			alloc_limit: Cell::new(None),
+			#[cfg(not(target_arch = "wasm32"))] deadline: Cell::new(None),
+			#[cfg(not(target_arch = "wasm32"))] deadline_countdown: Cell::new(0),
+			safepoint_interval: Cell::new(None),
+			safepoint_countdown: Cell::new(0),
+			safepoint_callback: RefCell::new(None),
+			in_safepoint: Cell::new(false),
+			yield_requested: Cell::new(false),
+			global_generation: Cell::new(0),
+			child_scopes: RefCell::new(Vec::new()),
+			execution_slot: Arc::new(ExecutionSlot::new())
+		}
+	}
+
+	pub(crate) fn max_call_depth(&self) -> u32 {
+		self.max_call_depth.get()
+	}
+
+	pub(crate) fn set_max_call_depth(&self, depth: u32) {
+		self.max_call_depth.set(depth)
+	}
+
+	pub(crate) fn stack_budget(&self) -> Option<usize> {
+		self.stack_budget.get()
+	}
+
+	pub(crate) fn set_stack_budget(&self, bytes: Option<usize>) {
+		self.stack_budget.set(bytes)
+	}
+
+	//every nested interpreter, and every level of nested macro expansion, must call enter_depth()
+	//before it starts consuming rust stack, and exit_depth() when it's finished. `expanding` is
+	//the form which is about to be expanded, if any.
+	//
+	//when glsp::set_stack_budget is used, we also estimate how much native stack has been 
+	//consumed, by comparing the address of a local variable against its address when the 
+	//outermost level was entered. the stack usage per level varies wildly: in a debug build, a
+	//single gfn call can consume tens of kilobytes.
+	#[inline]
+	pub(crate) fn enter_depth(&self, expanding: Option<&Val>) -> GResult<()> {
+		self.debug_assert_outside_safepoint();
+
+		let marker = 0_u8;
+		let address = &marker as *const u8 as usize;
+
+		let recursion = self.recursion.get();
+		if recursion == 0 {
+			self.stack_base.set(address);
+		}
+
+		if recursion >= self.max_call_depth.get() {
+			return Err(self.stack_overflow_error(expanding))
+		}
+
+		if let Some(budget) = self.stack_budget.get() {
+			let base = self.stack_base.get();
+			let used = base.abs_diff(address);
+			if used > budget {
+				return Err(self.stack_overflow_error(expanding))
+			}
+		}
+
+		self.recursion.set(recursion + 1);
+		Ok(())
+	}
+
+	#[inline]
+	pub(crate) fn exit_depth(&self) {
+		self.recursion.set(self.recursion.get() - 1)
+	}
+
+	//the error's payload is the symbol `stack-overflow`, so that it can be recognized by (try).
+	//the full stack trace would be hundreds of lines long, so we attach a brief description of
+	//the innermost calls. for runaway recursion, those calls will usually form a short cycle.
+	#[cold]
+	#[inline(never)]
+	fn stack_overflow_error(&self, expanding: Option<&Val>) -> GError {
+		const MAX_NAMES: usize = 24;
+		const MAX_CYCLE: usize = 8;
+
+		let mut names = Vec::<String>::with_capacity(MAX_NAMES);
+		for frame in self.frames.borrow().iter().rev() {
+			let name = match *frame {
+				Frame::Call(ref callee, _) => {
+					let name = match *callee {
+						Slot::GFn(ref gfn) => gfn.name(),
+						Slot::RFn(rfn) => rfn.name(),
+						Slot::Class(ref class) => class.name(),
+						_ => None
+					};
+
+					match name {
+						Some(name) => format!("({})", name),
+						None => "an anonymous fn".to_string()
+					}
+				}
+				Frame::Expand(ref arr, _) => {
+					match arr.get::<Val>(0) {
+						Ok(Val::Sym(name)) => format!("the macro ({})", name),
+						_ => "an anonymous macro".to_string()
+					}
+				}
+				_ => continue
+			};
+
+			names.push(name);
+			if names.len() == MAX_NAMES {
+				break
+			}
+		}
+
+		//search for the shortest cycle which is repeated at least three times
+		let cycle_len = (1 ..= MAX_CYCLE).find(|&len| {
+			names.len() >= len * 3 && (len .. len * 3).all(|i| names[i] == names[i - len])
+		});
+
+		let description = match cycle_len {
+			Some(len) => {
+				let mut cycle = names[..len].to_vec();
+				cycle.reverse();
+				format!("the innermost calls repeat the cycle {}", cycle.join(" -> "))
+			}
+			None if names.is_empty() => "no calls are in progress".to_string(),
+			None => format!("the innermost calls were {}", names.join(", "))
+		};
+
+		let mut message = if self.recursion.get() >= self.max_call_depth.get() {
+			format!("exceeded the maximum call depth of {}: {}", self.max_call_depth.get(), 
+			        description)
+		} else {
+			format!("exceeded the native stack budget of {} bytes, at a call depth of {}: {}", 
+			        self.stack_budget.get().unwrap(), self.recursion.get(), description)
+		};
+
+		//when a macro's expansion contains a call to the same macro, the expander recurses 
+		//without leaving a frame on the stack, so we describe the form instead
+		if let Some(Val::Arr(arr)) = expanding {
+			match arr.get::<Val>(0) {
+				Ok(Val::Sym(name)) => {
+					message.push_str(&format!(", while expanding a ({} ...) form", name))
+				}
+				_ => message.push_str(", while expanding a form")
+			}
 		}
+
+		GError::from_val(STACK_OVERFLOW_SYM).with_source(GError::from_str(&message))
+	}
+
+	pub(crate) fn tail_calls(&self) -> bool {
+		self.tail_calls.get()
+	}
+
+	pub(crate) fn set_tail_calls(&self, enabled: bool) {
+		self.tail_calls.set(enabled)
+	}
+
+	pub(crate) fn fuel(&self) -> Option<u64> {
+		self.fuel.get()
+	}
+
+	pub(crate) fn set_fuel(&self, fuel: Option<u64>) {
+		self.fuel.set(fuel);
+		self.update_metered();
+	}
+
+	#[cfg(not(target_arch = "wasm32"))]
+	pub(crate) fn set_deadline(&self, deadline: Option<Instant>) {
+		self.deadline.set(deadline);
+		self.deadline_countdown.set(0);
+		self.update_metered();
+	}
+
+	pub(crate) fn set_safepoint_callback(
+		&self,
+		callback: Option<(u64, Box<dyn FnMut() -> SafepointAction>)>
+	) {
+		self.debug_assert_outside_safepoint();
+
+		let (interval, callback) = match callback {
+			Some((interval, callback)) => (Some(interval.max(1)), Some(callback)),
+			None => (None, None)
+		};
+
+		self.safepoint_interval.set(interval);
+		self.safepoint_countdown.set(interval.unwrap_or(0));
+		*self.safepoint_callback.borrow_mut() = callback;
+		self.yield_requested.set(false);
+		self.update_metered();
+	}
+
+	#[inline(always)]
+	pub(crate) fn debug_assert_outside_safepoint(&self) {
+		debug_assert!(!self.in_safepoint.get(), "a safepoint callback attempted to call into glsp");
+	}
+
+	fn update_metered(&self) {
+		#[cfg(not(target_arch = "wasm32"))]
+		let has_deadline = self.deadline.get().is_some();
+
+		#[cfg(target_arch = "wasm32")]
+		let has_deadline = false;
+
+		let has_alloc_limit = self.alloc_limit.get().is_some();
+		let has_safepoint = self.safepoint_interval.get().is_some();
+		self.metered.set(self.fuel.get().is_some() || has_deadline || has_alloc_limit || 
+		                 has_safepoint);
+	}
+
+	//installs the stricter of `limits` and the current limits for the duration of `f`. when `f`
+	//returns, the previous limits are restored, and any fuel consumed by `f` is deducted from
+	//the previous fuel. a violation of one of the new limits is reported as an error which names
+	//the callee, rather than as a bare `fuel-exhausted` symbol.
+	pub(crate) fn with_limits<R>(
+		&self,
+		limits: &Limits,
+		callee: Option<Sym>,
+		f: impl FnOnce() -> GResult<R>
+	) -> GResult<R> {
+		fn stricter<T: Ord>(prev: Option<T>, new: Option<T>) -> Option<T> {
+			match (prev, new) {
+				(Some(prev), Some(new)) => Some(prev.min(new)),
+				(prev, new) => prev.or(new)
+			}
+		}
+
+		fn is_stricter<T: Ord>(prev: Option<T>, new: Option<T>) -> bool {
+			match (prev, new) {
+				(Some(prev), Some(new)) => new <= prev,
+				(None, Some(_)) => true,
+				(_, None) => false
+			}
+		}
+
+		let prev_fuel = self.fuel.get();
+		let prev_alloc_limit = self.alloc_limit.get();
+		#[cfg(not(target_arch = "wasm32"))] let prev_deadline = self.deadline.get();
+
+		let allocated = with_heap(|heap| heap.allocated_bytes());
+		let alloc_limit = limits.alloc_bytes.map(|bytes| allocated.saturating_add(bytes));
+
+		let fuel = stricter(prev_fuel, limits.fuel);
+		self.fuel.set(fuel);
+		self.alloc_limit.set(stricter(prev_alloc_limit, alloc_limit));
+		#[cfg(not(target_arch = "wasm32"))] {
+			self.deadline.set(stricter(prev_deadline, limits.deadline));
+			self.deadline_countdown.set(0);
+		}
+		self.update_metered();
+
+		let _guard = Guard::new(|| {
+			let consumed = match (fuel, self.fuel.get()) {
+				(Some(start), Some(remaining)) => start.saturating_sub(remaining),
+				_ => 0
+			};
+
+			self.fuel.set(prev_fuel.map(|prev| prev.saturating_sub(consumed)));
+			self.alloc_limit.set(prev_alloc_limit);
+			#[cfg(not(target_arch = "wasm32"))] {
+				self.deadline.set(prev_deadline);
+				self.deadline_countdown.set(0);
+			}
+			self.update_metered();
+		});
+
+		let error = match f() {
+			Ok(result) => return Ok(result),
+			Err(error) => error
+		};
+
+		let violation = match error.val() {
+			Val::Sym(sym) if sym == FUEL_EXHAUSTED_SYM && is_stricter(prev_fuel, limits.fuel) => {
+				"exhausted its fuel limit"
+			}
+			Val::Sym(sym) if sym == ALLOC_LIMIT_EXCEEDED_SYM &&
+			                 is_stricter(prev_alloc_limit, alloc_limit) => {
+				"exceeded its allocation limit"
+			}
+			#[cfg(not(target_arch = "wasm32"))]
+			Val::Sym(sym) if sym == DEADLINE_EXCEEDED_SYM && 
+			                 is_stricter(prev_deadline, limits.deadline) => {
+				"exceeded its deadline"
+			}
+			_ => return Err(error)
+		};
+
+		match callee {
+			Some(name) => Err(error!("({}) {}", name, violation).with_source(error)),
+			None => Err(error!("an anonymous fn {}", violation).with_source(error))
+		}
+	}
+
+	//called by interpret_frame while `metered` is set, on entry and on each backward jump. 
+	//reading the clock is relatively slow, so we only test the deadline once DEADLINE_INTERVAL
+	//units of fuel have been consumed - but once it's passed, we test it every time, so that a
+	//script can't (try) its way past the deadline.
+	#[inline(never)]
+	fn consume_fuel(&self, cost: u64, span: Span) -> GResult<()> {
+		if let Some(fuel) = self.fuel.get() {
+			if fuel < cost {
+				self.fuel.set(Some(0));
+				return Err(GError::from_val_at(span, FUEL_EXHAUSTED_SYM))
+			}
+
+			self.fuel.set(Some(fuel - cost));
+		}
+
+		#[cfg(not(target_arch = "wasm32"))]
+		if let Some(deadline) = self.deadline.get() {
+			let countdown = self.deadline_countdown.get();
+			if countdown <= cost {
+				if Instant::now() >= deadline {
+					self.deadline_countdown.set(0);
+					return Err(GError::from_val_at(span, DEADLINE_EXCEEDED_SYM))
+				}
+
+				self.deadline_countdown.set(DEADLINE_INTERVAL);
+			} else {
+				self.deadline_countdown.set(countdown - cost);
+			}
+		}
+
+		if let Some(alloc_limit) = self.alloc_limit.get() {
+			if with_heap(|heap| heap.allocated_bytes()) > alloc_limit {
+				return Err(GError::from_val_at(span, ALLOC_LIMIT_EXCEEDED_SYM))
+			}
+		}
+
+		if let Some(interval) = self.safepoint_interval.get() {
+			let countdown = self.safepoint_countdown.get();
+			if countdown <= cost {
+				self.safepoint_countdown.set(interval);
+				self.run_safepoint()?;
+			} else {
+				self.safepoint_countdown.set(countdown - cost);
+			}
+		}
+
+		Ok(())
+	}
+
+	#[cold]
+	fn run_safepoint(&self) -> GResult<()> {
+		let mut callback = self.safepoint_callback.borrow_mut();
+
+		self.in_safepoint.set(true);
+		let _guard = Guard::new(|| self.in_safepoint.set(false));
+
+		match (callback.as_mut().unwrap())() {
+			SafepointAction::Continue => Ok(()),
+			SafepointAction::RaiseError(error) => Err(error),
+			SafepointAction::Yield => {
+				self.yield_requested.set(true);
+				Ok(())
+			}
+		}
+	}
+
+	//called by engine.rs when a global which might be cached is mutated, rebound or unbound
+	pub(crate) fn invalidate_global_caches(&self) {
+		self.global_generation.set(self.global_generation.get() + 1)
 	}
 
 	pub(crate) fn push_frame(&self, frame: Frame) {
@@ -184,6 +601,41 @@ impl Vm {
 		self.frames.borrow().iter().rev().any(|frame| matches!(frame, Frame::Expand(..)))
 	}
 
+	//every entrypoint into the interpreter pushes a frame, so this is false while any GameLisp
+	//code is executing, including code which has called back into rust
+	pub(crate) fn is_idle(&self) -> bool {
+		self.frames.borrow().is_empty()
+	}
+
+	//the innermost call, if it's a call to an rfn. used to describe where an RData was borrowed
+	//or lent.
+	pub(crate) fn rfn_callsite(&self) -> Option<(RFn, Span)> {
+		for frame in self.frames.borrow().iter().rev() {
+			match *frame {
+				Frame::Call(Slot::RFn(rfn), span) => return Some((rfn, span)),
+				Frame::Instr(..) | Frame::OpInstr(..) | Frame::ErrorAt(..) => (),
+				_ => return None
+			}
+		}
+
+		None
+	}
+
+	//the callsite of the innermost call made by GameLisp code, or the default Span if there's no
+	//such call, e.g. because the innermost frame is a glsp::call() from rust
+	pub(crate) fn callsite(&self) -> Span {
+		for frame in self.frames.borrow().iter().rev() {
+			match *frame {
+				Frame::Call(_, span) => return span,
+				Frame::GlspCall(_) | Frame::Instr(..) | Frame::OpInstr(..) => (),
+				Frame::ErrorAt(..) => (),
+				_ => return Span::default()
+			}
+		}
+
+		Span::default()
+	}
+
 	pub(crate) fn expander_cur_span(&self) -> Span {
 		for frame in self.frames.borrow().iter().rev() {
 			match frame {
@@ -201,35 +653,39 @@ impl Vm {
 	}
 
 	pub(crate) fn traverse_stacks(&self) {
-		with_heap(|heap| {
-			let stacks = self.stacks.borrow();
-			let frames = self.frames.borrow();
+		with_heap(|heap| self.visit_stacks(&mut StackVisitor::new(heap)));
+	}
 
-			for reg in &stacks.regs {
-				heap.traverse_stack_slot(reg);
-			}
+	//visits every object which is directly referenced by the register stack, the stay stack or
+	//the frame stack
+	pub(crate) fn visit_stacks<V: Visitor>(&self, visitor: &mut V) {
+		let stacks = self.stacks.borrow();
+		let frames = self.frames.borrow();
 
-			for stay in &stacks.stays {
-				if let Some(ref stay) = *stay {
-					heap.traverse_stack_gc(stay);
-				}
+		for reg in &stacks.regs {
+			visitor.visit_slot(reg);
+		}
+
+		for stay in &stacks.stays {
+			if let Some(ref stay) = *stay {
+				visitor.visit_gc(stay);
 			}
+		}
 
-			for frame in &*frames {
-				use Frame::*;
+		for frame in &*frames {
+			use Frame::*;
 
-				match frame {
-					GlspApi(_, _) => (),
-					GlspCall(_) => (),
-					GlspCoroRun(coro) => heap.traverse_stack_gc(coro),
-					Expand(arr, _) => heap.traverse_stack_gc(arr),
-					Call(slot, _) => heap.traverse_stack_slot(slot),
-					Instr(_, _) => (),
-					OpInstr(_, _) => (),
-					ErrorAt(_) => ()
-				}
+			match frame {
+				GlspApi(_, _) => (),
+				GlspCall(_) => (),
+				GlspCoroRun(coro) => visitor.visit_gc(coro),
+				Expand(arr, _) => visitor.visit_gc(arr),
+				Call(slot, _) => visitor.visit_slot(slot),
+				Instr(_, _) => (),
+				OpInstr(_, _) => (),
+				ErrorAt(_) => ()
 			}
-		});
+		}
 	}
 
 	pub(crate) fn exec_bytecode(&self, bytecode: &Root<Bytecode>) -> GResult<Val> {
@@ -241,11 +697,16 @@ impl Vm {
 	}
 
 	pub(crate) fn coro_run(&self, coro: &Root<Coro>, resume_arg: Option<Val>) -> GResult<Val> {
-		coro_run(self, coro, resume_arg, false)
+		coro_run(self, coro, resume_arg, false, None)
 	}
 
 	pub(crate) fn coro_finish(&self, coro: &Root<Coro>) -> GResult<()> {
-		coro_run(self, coro, None, true)?;
+		coro_run(self, coro, None, true, None)?;
+		Ok(())
+	}
+
+	pub(crate) fn coro_throw(&self, coro: &Root<Coro>, error: GError) -> GResult<()> {
+		coro_run(self, coro, None, true, Some(error))?;
 		Ok(())
 	}
 
@@ -427,12 +888,13 @@ fn exec_bytecode(
 
 	//invoke the interpreter
 	drop(stacks);
-	match interpret(vm, bytecode.to_gc(), instr_n, base_reg, base_stay) {
+	match interpret(vm, bytecode.to_gc(), None, instr_n, base_reg, base_stay, false) {
 		Ok(InterpretResult::Return(slot)) => Ok(slot.into_root()),
 		Ok(InterpretResult::Yield(_, _, _)) => unreachable!(),
 		Ok(InterpretResult::EndDefer) => unreachable!(),
+		Ok(InterpretResult::TailCall(..)) => unreachable!(),
 		Err(mut error) => {
-			run_defers(vm, bytecode.to_gc(), base_reg, base_stay, base_defer, &mut error);
+			run_defers(vm, bytecode.to_gc(), None, base_reg, base_stay, base_defer, &mut error);
 			Err(error)
 		}
 	}
@@ -460,18 +922,21 @@ fn exec_gfn(
 
 //put a Coro's saved data back onto the stack and resume its execution until it yields, returns
 //or fails. used by glsp::coro_run. if the `defers_only` flag is set, the coro is not resumed;
-//instead, any pending `defers` are run and the coro's state is set to `finished`.
+//instead, any pending `defers` are run and the coro's state is set to `finished`. if a `thrown`
+//error is also passed in, the defers are run as though that error had occurred at the point
+//where the coro is paused, and then the coro is poisoned.
 fn coro_run(
 	vm: &Vm,
 	coro: &Root<Coro>,
 	resume_arg: Option<Val>,
-	defers_only: bool
+	defers_only: bool,
+	thrown: Option<GError>
 ) -> GResult<Val> {
 
 	//check the coro's current state
-	let resume_reg = match coro.state.get() {
-		PrivCoroState::Newborn => None,
-		PrivCoroState::Paused(resume_reg) => Some(resume_reg),
+	let (newborn, resume_reg) = match coro.state.get() {
+		PrivCoroState::Newborn => (true, None),
+		PrivCoroState::Paused(resume_reg) => (false, resume_reg),
 		PrivCoroState::Running => bail!("coro is currently running"),
 		PrivCoroState::Finished => bail!("coro has already finished"),
 		PrivCoroState::Poisoned => bail!("coro has been poisoned by a previous error"),
@@ -518,22 +983,28 @@ fn coro_run(
 	match (resume_arg, resume_reg) {
 		(Some(arg), Some(reg)) => stacks.regs[base_reg + reg as usize] = Slot::from_val(&arg),
 		(None, Some(reg)) => stacks.regs[base_reg + reg as usize] = Slot::Nil,
-		(Some(_), None) => bail!("the first invocation of coro-run cannot receive an argument"),
-		(None, None) => ()
+		(Some(_), None) if newborn => {
+			bail!("the first invocation of coro-run cannot receive an argument")
+		}
+
+		//a coro which was paused by a safepoint callback discards its resume_arg
+		(_, None) => ()
 	}
 
 	//if `defers_only` is set, execute any pending (defer)s, set the state to `finished` or
 	//`poisoned`, then return.
 	if defers_only {
-		let mut error: Option<GError> = None;
+		let mut error: Option<GError> = thrown;
 
 		while stacks.defers.len() > base_defer {
 			let defer_instr = stacks.defers.pop().unwrap();
 
 			drop(stacks);
-			match interpret(vm, gfn.lambda.bytecode.clone(), defer_instr, base_reg, base_stay) {
+			let bytecode = gfn.lambda.bytecode.clone();
+			match interpret(vm, bytecode, gfn.lambda.name, defer_instr, base_reg, base_stay, false) {
 				Ok(InterpretResult::EndDefer) => (),
 				Ok(InterpretResult::Return(..)) => unreachable!(),
+				Ok(InterpretResult::TailCall(..)) => unreachable!(),
 				Ok(InterpretResult::Yield(..)) => unreachable!(),
 				Err(new_error) => {
 					if error.is_some() {
@@ -558,7 +1029,8 @@ fn coro_run(
 	//run the interpreter, then update the coro's state to either Paused or Finished. in the event 
 	//of an error, run any pending (defer)s and set the coro's state to Poisoned.
 	drop(stacks);
-	match interpret(vm, gfn.lambda.bytecode.clone(), instr, base_reg, base_stay) {
+	let name = gfn.lambda.name;
+	match interpret(vm, gfn.lambda.bytecode.clone(), name, instr, base_reg, base_stay, true) {
 		Ok(InterpretResult::Return(slot)) => {
 			coro.state.set(PrivCoroState::Finished);
 			Ok(slot.into_root())
@@ -588,79 +1060,99 @@ fn coro_run(
 			Ok(slot.into_root())
 		}
 		Ok(InterpretResult::EndDefer) => unreachable!(),
+		Ok(InterpretResult::TailCall(..)) => unreachable!(),
 		Err(mut error) => {
 			coro.state.set(PrivCoroState::Poisoned);
-			run_defers(vm, gfn.lambda.bytecode.clone(), base_reg, base_stay, 
-			           base_defer, &mut error);
+			run_defers(vm, gfn.lambda.bytecode.clone(), name, base_reg, base_stay, base_defer, 
+			           &mut error);
 			Err(error)
 		}
 	}
 }
 
-//it feels wrong to mark such a large fn as #[inline(always)], but it saves about 11ns per call
+//splays the last `arg_count` elements of the reg stack in-place, returning the new argument count.
+//the `stacks` lock may be temporarily released, since advancing a GIter can run arbitrary code.
 #[inline(always)]
-fn call<'a>(
+fn splay_args<'a>(
 	vm: &'a Vm,
 	mut stacks: RefMut<'a, Stacks>,
 	mut splay_bits: u32,
-	callee: Slot,
-	mut arg_count: usize,
-	callsite: Option<Span>
-) -> GResult<Slot> {
-	
-	//splay arguments in-place
-	if splay_bits != 0 {
-		let mut index = stacks.regs.len() - arg_count;
-		let base_index = index;
-
-		while splay_bits != 0 {
-			if splay_bits & 0x1 == 0x1 {
-				let giter = match stacks.regs[index].clone() {
-					Slot::Arr(arr) => {
-						stacks.regs.splice(index..index + 1, 
-						                    arr.iter_to::<Slot>().map(|s| s.unwrap()));
-						index += arr.len();
-						splay_bits >>= 1;
-						continue
-					}
-					Slot::Str(st) => {
-						stacks.regs.splice(index..index + 1, st.iter().map(Slot::Char));
-						index += st.len();
-						splay_bits >>= 1;
-						continue
-					}
-					Slot::Tab(tab) => tab.giter(),
-					Slot::Coro(coro) => coro.giter(),
-					Slot::GIter(giter) => giter.root(),
-					slot => bail!("attempted to splay {}", slot.a_type_name())
-				};
-
-				//advancing a GIter may invoke arbitrary GameLisp code, so we can't emit our
-				//results directly to the reg stack
-				drop(stacks);
-				let mut vec = SmallVec::<[Slot; 8]>::new();
-
-				if let GIterLen::Exact(exact_len) = giter.len() {
-					vec.reserve(exact_len);
+	arg_count: usize
+) -> GResult<(RefMut<'a, Stacks>, usize)> {
+
+	let mut index = stacks.regs.len() - arg_count;
+	let base_index = index;
+
+	while splay_bits != 0 {
+		if splay_bits & 0x1 == 0x1 {
+			let giter = match stacks.regs[index].clone() {
+				Slot::Arr(arr) => {
+					stacks.regs.splice(index..index + 1, 
+					                    arr.iter_to::<Slot>().map(|s| s.unwrap()));
+					index += arr.len();
+					splay_bits >>= 1;
+					continue
 				}
-
-				for result in giter {
-					vec.push(Slot::from_val(&result?));
+				Slot::Str(st) => {
+					stacks.regs.splice(index..index + 1, st.iter().map(Slot::Char));
+					index += st.len();
+					splay_bits >>= 1;
+					continue
 				}
+				Slot::Tab(tab) => tab.giter(),
+				Slot::Coro(coro) => coro.giter(),
+				Slot::GIter(giter) => giter.root(),
+				Slot::PArr(parr) => parr.giter(),
+				Slot::PTab(ptab) => ptab.giter(),
+				slot => bail!("attempted to splay {}", slot.a_type_name())
+			};
 
-				stacks = vm.stacks.borrow_mut();
+			//advancing a GIter may invoke arbitrary GameLisp code, so we can't emit our
+			//results directly to the reg stack
+			drop(stacks);
+			let mut vec = SmallVec::<[Slot; 8]>::new();
 
-				let vec_len = vec.len();
-				stacks.regs.splice(index..index + 1, vec);
-				index += vec_len;
-			} else {
-				index += 1;
+			if let GIterLen::Exact(exact_len) = giter.len() {
+				vec.reserve(exact_len);
 			}
 
-			splay_bits >>= 1;
+			for result in giter {
+				vec.push(Slot::from_val(&result?));
+			}
+
+			stacks = vm.stacks.borrow_mut();
+
+			let vec_len = vec.len();
+			stacks.regs.splice(index..index + 1, vec);
+			index += vec_len;
+		} else {
+			index += 1;
 		}
 
-		arg_count = stacks.regs.len() - base_index;
+		splay_bits >>= 1;
+	}
+
+	let arg_count = stacks.regs.len() - base_index;
+
+	Ok((stacks, arg_count))
+}
+
+//it feels wrong to mark such a large fn as #[inline(always)], but it saves about 11ns per call
+#[inline(always)]
+fn call<'a>(
+	vm: &'a Vm,
+	mut stacks: RefMut<'a, Stacks>,
+	splay_bits: u32,
+	callee: Slot,
+	mut arg_count: usize,
+	callsite: Option<Span>
+) -> GResult<Slot> {
+	
+	//splay arguments in-place
+	if splay_bits != 0 {
+		let (splayed_stacks, splayed_arg_count) = splay_args(vm, stacks, splay_bits, arg_count)?;
+		stacks = splayed_stacks;
+		arg_count = splayed_arg_count;
 	}
 
 	//if the argument is an rfn or class, call it and return. if it's a yielding gfn, create and 
@@ -694,6 +1186,10 @@ fn call<'a>(
 			let coro = glsp::alloc_gc(Coro::new(gfn, regs, stays));
 			coro.write_barrier();
 
+			if let Some(scope) = vm.child_scopes.borrow().last() {
+				scope.push(Slot::Coro(coro.clone()))?;
+			}
+
 			Ok(Slot::Coro(coro))
 		}
 		Slot::GFn(gfn) => {
@@ -725,12 +1221,14 @@ fn call<'a>(
 
 			//recurse into the interpreter. if an error bubbles through, run any pending (defer)s.
 			drop(stacks);
-			match interpret(vm, gfn.lambda.bytecode.clone(), 0, base_reg, base_stay) {
+			let name = gfn.lambda.name;
+			match interpret(vm, gfn.lambda.bytecode.clone(), name, 0, base_reg, base_stay, false) {
 				Ok(InterpretResult::Return(slot)) => Ok(slot),
 				Ok(InterpretResult::Yield(_, _, _)) => unreachable!(),
 				Ok(InterpretResult::EndDefer) => unreachable!(),
+				Ok(InterpretResult::TailCall(..)) => unreachable!(),
 				Err(mut error) => {
-					run_defers(vm, gfn.lambda.bytecode.clone(), base_reg, base_stay, 
+					run_defers(vm, gfn.lambda.bytecode.clone(), name, base_reg, base_stay, 
 					           base_defer, &mut error);
 					Err(error)
 				}
@@ -761,11 +1259,10 @@ fn wrangle_args_and_stays(
 	//wrangle them into one register for each parameter.
 	match param_map.wrangle_args(regs, &bytecode.start_regs[..], arg_count, callsite) {
 		Ok(_) => (),
-		Err(msg) => {
-			match gfn.lambda.name {
-				Some(name) => bail!("unable to call the fn '{}': {}", name, msg),
-				None => bail!("{}", msg)
-			}
+		Err((id, args)) => {
+			let mut error = GError::from_message(id, args).with_arg_context(None);
+			error.finish_arg_context(gfn.lambda.name, |_| None, |_| None);
+			return Err(error)
 		}
 	}
 
@@ -797,10 +1294,86 @@ fn wrangle_args_and_stays(
 	Ok(())
 }
 
+//the inline cache for OpCallMeth and OpCallMethOpt. we only cache method lookups, rather than
+//fields, consts or properties, because a method is fully determined by the receiver's class, 
+//its enabled states and the method name. none of those can change without us noticing.
+fn cached_meth(
+	bytecode: &Bytecode,
+	cache_id: u16,
+	obj: &Obj,
+	method_name: Sym
+) -> Option<(Slot, bool, bool, Slot)> {
+	let (class, states_enabled) = obj.meth_cache_key()?;
+
+	match bytecode.inline_caches.borrow().get(cache_id as usize) {
+		Some(&InlineCache::Meth {
+			class: ref cached_class,
+			states_enabled: cached_states, 
+			name, 
+			ref gfn, 
+			requires_next_index, 
+			next_index
+		}) if Gc::ptr_eq(cached_class, &class) && cached_states == states_enabled && 
+		      name == method_name => {
+			let ni = match next_index {
+				Some(ni) => Slot::Int(ni as i32),
+				None => Slot::Nil
+			};
+
+			Some((Slot::GFn(gfn.clone()), true, requires_next_index, ni))
+		}
+		_ => None
+	}
+}
+
+fn cache_meth(
+	bytecode: &Bytecode,
+	cache_id: u16,
+	obj: &Obj,
+	method_name: Sym,
+	tuple: &Option<(Slot, bool, bool, Slot)>
+) {
+	//Obj::get_method only sets the expects_self flag for a method
+	let (gfn, requires_next_index, next_index) = match *tuple {
+		Some((Slot::GFn(ref gfn), true, requires_next_index, ref ni)) => {
+			let next_index = match *ni {
+				Slot::Int(ni) => Some(ni as u16),
+				_ => None
+			};
+
+			(gfn.clone(), requires_next_index, next_index)
+		}
+		_ => return
+	};
+
+	let (class, states_enabled) = match obj.meth_cache_key() {
+		Some(key) => key,
+		None => return
+	};
+
+	let mut caches = bytecode.inline_caches.borrow_mut();
+	if let Some(cache) = caches.get_mut(cache_id as usize) {
+		with_heap(|heap| {
+			heap.write_barrier(bytecode, &class);
+			heap.write_barrier(bytecode, &gfn);
+		});
+
+		*cache = InlineCache::Meth {
+			class: class.clone(),
+			states_enabled,
+			name: method_name,
+			gfn,
+			requires_next_index,
+			next_index
+		};
+	}
+}
+
 //run any pending defers, after a call to interpret() returns Err(_)
 fn run_defers(
 	vm: &Vm,
 	bytecode: Gc<Bytecode>,
+	name: Option<Sym>,
 	base_reg: usize,
 	base_stay: usize,
 	base_defer: usize,
@@ -811,9 +1384,10 @@ fn run_defers(
 		let defer_instr = stacks.defers.pop().unwrap();
 
 		drop(stacks);
-		match interpret(vm, bytecode.clone(), defer_instr, base_reg, base_stay) {
+		match interpret(vm, bytecode.clone(), name, defer_instr, base_reg, base_stay, false) {
 			Ok(InterpretResult::EndDefer) => (),
 			Ok(InterpretResult::Return(_)) | Ok(InterpretResult::Yield(..)) => unreachable!(),
+			Ok(InterpretResult::TailCall(..)) => unreachable!(),
 			Err(defer_error) => {
 				error.chain_defer_error(defer_error);
 			}
@@ -830,29 +1404,91 @@ fn run_defers(
 #[derive(Clone)]
 pub(crate) enum InterpretResult {
 	Return(Slot),
-	Yield(Slot, u8, usize), //(result, resume_reg, resume_instr)
-	EndDefer
+	Yield(Slot, Option<u8>, usize), //(result, resume_reg, resume_instr)
+	EndDefer,
+
+	//only produced by interpret_frame(). the current frame has been replaced with the initial 
+	//regs and stays for this gfn, which should be executed from its first instr.
+	TailCall(Root<GFn>)
 }
 
-//we've chosen quite a conservative recursion limit, because the native stack limit on win32 is
-//1mb, and rustc currently compiles interpret() in a memory-hungry way: 1kb to 2kb per gfn call.
-const RECURSION_LIMIT: u32 = 256;
+//we've chosen quite a conservative default recursion limit, because the native stack limit on 
+//win32 is 1mb, and rustc currently compiles interpret() in a memory-hungry way: 1kb to 2kb per 
+//gfn call.
+pub(crate) const DEFAULT_MAX_CALL_DEPTH: u32 = 256;
 
+#[cfg(not(target_arch = "wasm32"))]
+const DEADLINE_INTERVAL: u64 = 1024;
+
+//`yieldable` is only set when `bytecode` is the body of a coro which is being resumed, in which 
+//case a safepoint callback may suspend it. 
 fn interpret(
 	vm: &Vm,
-	bytecode: Gc<Bytecode>,
+	mut bytecode: Gc<Bytecode>,
+	mut name: Option<Sym>,
 	mut instr_n: usize,
 	base_reg: usize,
-	base_stay: usize
+	base_stay: usize,
+	yieldable: bool
 ) -> GResult<InterpretResult> {
 
 	//check the recursion limit
-	let recursion = vm.recursion.get();
-	if recursion >= RECURSION_LIMIT {
-		bail!("recursion limit exceeded (more than {} nested interpreters)", RECURSION_LIMIT);
+	vm.enter_depth(None)?;
+	let _recursion_guard = Guard::new(|| vm.exit_depth());
+
+	//when we return, the caller's execution point is republished. this isn't precise, but the
+	//caller will publish a new point as soon as it jumps.
+	let caller_point = vm.execution_slot.save();
+	let _execution_guard = Guard::new(|| vm.execution_slot.restore(caller_point));
+
+	//a tail call replaces the frame's bytecode without nesting another interpreter. our caller
+	//only knows about the original bytecode, so if an error bubbles through a tail-called gfn, 
+	//we need to run its pending (defer)s here. (TailCall is never emitted while a (defer) is 
+	//pending, so any defers above base_defer must belong to the current bytecode.)
+	let base_defer = vm.stacks.borrow().defers.len();
+	let mut tail_gfn: Option<Root<GFn>> = None;
+
+	loop {
+		let yieldable = yieldable && tail_gfn.is_none();
+		match interpret_frame(vm, bytecode.clone(), name, instr_n, base_reg, base_stay, yieldable) {
+			Ok(InterpretResult::TailCall(gfn)) => {
+				bytecode = gfn.lambda.bytecode.clone();
+				name = gfn.lambda.name;
+				instr_n = 0;
+				tail_gfn = Some(gfn);
+			}
+			Err(mut error) if tail_gfn.is_some() => {
+				run_defers(vm, bytecode, name, base_reg, base_stay, base_defer, &mut error);
+				return Err(error)
+			}
+			result => return result
+		}
+	}
+}
+
+fn interpret_frame(
+	vm: &Vm,
+	bytecode: Gc<Bytecode>,
+	name: Option<Sym>,
+	mut instr_n: usize,
+	base_reg: usize,
+	base_stay: usize,
+	yieldable: bool
+) -> GResult<InterpretResult> {
+
+	//see glsp::current_execution_point. we publish our position at the start of each basic 
+	//block: here, and after each jump.
+	vm.execution_slot.publish(bytecode.spans[instr_n], name);
+
+	//each call, tail call or coroutine resumption costs one unit of fuel. this means that 
+	//unbounded recursion is metered, even when it's been tail-call-eliminated.
+	if vm.metered.get() {
+		vm.consume_fuel(1, bytecode.spans[instr_n])?;
+
+		if vm.yield_requested.replace(false) && yieldable {
+			return Ok(InterpretResult::Yield(Slot::Nil, None, instr_n))
+		}
 	}
-	vm.recursion.set(recursion + 1);
-	let _recursion_guard = Guard::new(|| vm.recursion.set(recursion));
 
 	//interpreter state
 	let mut stacks = vm.stacks.borrow_mut();
@@ -873,11 +1509,36 @@ fn interpret(
 		($i:expr) => (stacks.regs[base_reg + $i as usize]);
 	);
 
+	//a backward jump is the only way for a frame to execute the same instruction twice, so that's
+	//where loops are charged for fuel. the cost is the loop's length in instructions, which is 
+	//an upper bound for the number of instructions executed on each iteration.
+	macro_rules! jump(
+		($jump_bytes:expr) => ({
+			let offset = isize::from($jump_bytes);
+			instr_n = ((instr_n as isize) + offset) as usize;
+			vm.execution_slot.publish(cur_span, name);
+
+			if offset < 0 && vm.metered.get() {
+				vm.consume_fuel((-offset) as u64, cur_span)?;
+
+				if vm.yield_requested.replace(false) && yieldable {
+					return Ok(InterpretResult::Yield(Slot::Nil, None, instr_n))
+				}
+			}
+		});
+	);
+
 	macro_rules! stay(
 		($i:expr) => (stacks.stays[base_stay + $i as usize]);
 	);
 
 	macro_rules! bail_instr(
+		(@error $instr_name:expr, $error:expr) => ({
+			vm.frames.borrow_mut().push(Frame::Instr($instr_name, cur_span));
+			let _guard = Guard::new(|| { vm.frames.borrow_mut().pop().unwrap(); });
+
+			return Err($error)
+		});
 		($instr_name:expr, $($arg:tt)+) => ({
 			vm.frames.borrow_mut().push(Frame::Instr($instr_name, cur_span));
 			let _guard = Guard::new(|| { vm.frames.borrow_mut().pop().unwrap(); });
@@ -887,6 +1548,12 @@ fn interpret(
 	);
 
 	macro_rules! bail_op(
+		(@error $op_sym:expr, $error:expr) => ({
+			vm.frames.borrow_mut().push(Frame::OpInstr($op_sym, cur_span));
+			let _guard = Guard::new(|| { vm.frames.borrow_mut().pop().unwrap(); });
+
+			return Err($error)
+		});
 		($op_sym:expr, $($arg:tt)+) => ({
 			vm.frames.borrow_mut().push(Frame::OpInstr($op_sym, cur_span));
 			let _guard = Guard::new(|| { vm.frames.borrow_mut().pop().unwrap(); });
@@ -895,16 +1562,49 @@ fn interpret(
 		});
 	);
 
+	//min and max fall back to Val::try_cmp when either argument isn't a number, because it might
+	//be an obj or rdata with an op-ord method. `keep_first` is passed the ordering of arg0 
+	//relative to arg1.
+	macro_rules! ord_op(
+		($op_sym:expr, $dst_reg:expr, $arg0_reg:expr, $arg1_reg:expr, $keep_first:expr) => ({
+			let keep_first: fn(Ordering) -> bool = $keep_first;
+			let arg0 = reg!($arg0_reg).root();
+			let arg1 = reg!($arg1_reg).root();
+
+			vm.frames.borrow_mut().push(Frame::OpInstr($op_sym, cur_span));
+			let _guard = Guard::new(|| {
+				vm.frames.borrow_mut().pop().unwrap();
+			});
+
+			drop(stacks);
+			let ordering = arg0.try_cmp(&arg1);
+			stacks = vm.stacks.borrow_mut();
+
+			let result = if keep_first(ordering?) { arg0 } else { arg1 };
+			reg!($dst_reg) = Slot::from_val(&result);
+		});
+	);
+
 	macro_rules! numeric_op(
 		($op_sym:expr, $dst_reg:expr, $arg0_reg:expr, $arg1_reg:expr, 
-		 $int_op:expr, $flo_op:expr) => ({
+		 $int_op:expr, $flo_op:expr, $vector_op:expr) => ({
 			let int_op = $int_op;
 			let flo_op = $flo_op;
 			let result = match (&reg!($arg0_reg), &reg!($arg1_reg)) {
 				(&Slot::Int(i0), &Slot::Int(i1)) => Slot::Int(int_op(i0, i1)),
-				(&Slot::Int(i), &Slot::Flo(f)) => Slot::Flo(flo_op(i as f32, f)),
-				(&Slot::Flo(f), &Slot::Int(i)) => Slot::Flo(flo_op(f, i as f32)),
+				(&Slot::Int(i), &Slot::Flo(f)) => Slot::Flo(flo_op(i as Flo, f)),
+				(&Slot::Flo(f), &Slot::Int(i)) => Slot::Flo(flo_op(f, i as Flo)),
 				(&Slot::Flo(f0), &Slot::Flo(f1)) => Slot::Flo(flo_op(f0, f1)),
+				(arg0, arg1) if vecmath::is_vector(arg0) || vecmath::is_vector(arg1) => {
+					match vecmath::slot_arith($vector_op, arg0, arg1) {
+						Ok(result) => result,
+						Err(err) => bail_op!(@error $op_sym, err)
+					}
+				}
+				(&Slot::Char(_), _) | (_, &Slot::Char(_)) => {
+					bail_op!($op_sym, "char passed to a numeric op: use char->int and int->char \
+					                   to perform arithmetic on chars")
+				}
 				_ => bail_op!($op_sym, "non-number passed to a numeric op")
 			};
 	
@@ -912,6 +1612,20 @@ fn interpret(
 		});
 	);
 
+	//the shift ops mask their shift count to the range 0..32, just like rust's wrapping_shl and
+	//wrapping_shr. this matches the behaviour of the corresponding rfns in glsp-stdlib.
+	macro_rules! int_op(
+		($op_sym:expr, $dst_reg:expr, $arg0_reg:expr, $arg1_reg:expr, $int_op:expr) => ({
+			let int_op = $int_op;
+			let result = match (&reg!($arg0_reg), &reg!($arg1_reg)) {
+				(&Slot::Int(i0), &Slot::Int(i1)) => Slot::Int(int_op(i0, i1)),
+				_ => bail_op!($op_sym, "non-int passed to a bitwise op")
+			};
+	
+			reg!($dst_reg) = result;
+		});
+	);
+
 	macro_rules! numeric_cmp_op(
 		($op_sym:expr, $dst_reg:expr, $arg0_reg:expr, $arg1_reg:expr, 
 		 $int_op:expr, $flo_op:expr) => ({
@@ -934,8 +1648,8 @@ fn interpret(
 
 			let result = match (arg0, arg1) {
 				(Slot::Int(i0), Slot::Int(i1)) => Slot::Bool(int_op(i0, i1)),
-				(Slot::Int(i), Slot::Flo(f)) => Slot::Bool(flo_op(i as f32, f)),
-				(Slot::Flo(f), Slot::Int(i)) => Slot::Bool(flo_op(f, i as f32)),
+				(Slot::Int(i), Slot::Flo(f)) => Slot::Bool(flo_op(i as Flo, f)),
+				(Slot::Flo(f), Slot::Int(i)) => Slot::Bool(flo_op(f, i as Flo)),
 				(Slot::Flo(f0), Slot::Flo(f1)) => Slot::Bool(flo_op(f0, f1)),
 				_ => unreachable!()
 			};
@@ -944,18 +1658,94 @@ fn interpret(
 		});
 	);
 
+	//when the callee is a non-yielding gfn, discard the current frame and return TailCall to
+	//interpret(), which will execute the callee's bytecode in its place. for any other callee, or 
+	//when tail calls are disabled, this is equivalent to a Call instr followed by a Return.
+	macro_rules! tail_call(
+		($callee_reg:expr, $arg_count:expr) => ({
+			let callee = reg!($callee_reg).clone();
+			let arg_count: usize = $arg_count;
+
+			match callee {
+				Slot::GFn(ref gfn) if !gfn.yields() && vm.tail_calls.get() => {
+					let gfn = gfn.root();
+
+					//the callee takes over the Frame::Call which was pushed for this gfn, if any
+					if let Some(Frame::Call(slot, span)) = vm.frames.borrow_mut().last_mut() {
+						*slot = callee.clone();
+						*span = cur_span;
+					}
+
+					let (mut stacks, arg_count) = splay_args(
+						vm,
+						stacks,
+						replace(&mut splay_bits, 0),
+						arg_count
+					)?;
+
+					//discard this frame's regs and stays, leaving the arguments on top of the
+					//reg stack, then wrangle them just like call()
+					let args_start = stacks.regs.len() - arg_count;
+					stacks.regs.drain(base_reg .. args_start);
+					stacks.stays.truncate(base_stay);
+
+					let Stacks { ref mut regs, ref mut stays, .. } = *stacks;
+					wrangle_args_and_stays(regs, stays, arg_count, &gfn, Some(cur_span))?;
+
+					return Ok(InterpretResult::TailCall(gfn))
+				}
+				_ => {
+					vm.frames.borrow_mut().push(Frame::Call(callee.clone(), cur_span));
+					let _guard = Guard::new(|| {
+						vm.frames.borrow_mut().pop().unwrap();
+					});
+
+					let result = call(
+						vm,
+						stacks,
+						replace(&mut splay_bits, 0),
+						callee,
+						arg_count,
+						Some(cur_span)
+					)?;
+
+					return Ok(InterpretResult::Return(result))
+				}
+			}
+		});
+	);
+
 	//interpret the instruction
 	match instr {
 		Instr::CopyRegister(dst_reg, src_reg) => {
 			reg!(dst_reg) = reg!(src_reg).clone();
 		}
-		Instr::LoadGlobal(dst_reg, sym_bytes) => {
-			let sym = Sym::from(sym_bytes);
+		Instr::LoadGlobal(dst_reg, sym_bytes, cache_id) => {
+			let generation = vm.global_generation.get();
+			let cached = match bytecode.inline_caches.borrow().get(cache_id as usize) {
+				Some(&InlineCache::Global(gen, ref slot)) if gen == generation => Some(slot.clone()),
+				_ => None
+			};
 
-			if let Some(global) = glsp::try_global(sym).unwrap() {
-				reg!(dst_reg) = Slot::from_val(&global);
+			if let Some(slot) = cached {
+				reg!(dst_reg) = slot;
 			} else {
-				bail_instr!(InstrName::LoadGlobal, "unbound symbol '{}'", sym)
+				let sym = Sym::from(sym_bytes);
+
+				match glsp::try_global_for_cache(sym) {
+					Some((slot, cacheable)) => {
+						if cacheable {
+							let mut caches = bytecode.inline_caches.borrow_mut();
+							if let Some(cache) = caches.get_mut(cache_id as usize) {
+								with_heap(|heap| heap.write_barrier_slot(&*bytecode, &slot));
+								*cache = InlineCache::Global(generation, slot.clone());
+							}
+						}
+
+						reg!(dst_reg) = slot;
+					}
+					None => bail_instr!(@error InstrName::LoadGlobal, unbound_symbol_error(sym))
+				}
 			}
 		}
 		Instr::SetGlobal(src_reg, sym_bytes) => {
@@ -965,8 +1755,8 @@ fn interpret(
 			
 			match glsp::try_set_global(sym, &reg!(src_reg)).unwrap() {
 				Success => (),
-				NotBound => bail_instr!(InstrName::SetGlobal, "unbound symbol '{}'", sym),
-				Frozen => bail_instr!(InstrName::SetGlobal, "global '{}' is frozen", sym)
+				NotBound => bail_instr!(@error InstrName::SetGlobal, unbound_symbol_error(sym)),
+				Frozen => bail_instr!(@error InstrName::SetGlobal, frozen_global_error(sym))
 			}
 		}
 		Instr::LoadStay(dst_reg, stay_id) => {
@@ -1079,13 +1869,40 @@ fn interpret(
 			stacks = vm.stacks.borrow_mut();
 			reg!(dst_reg) = result;
 		}
+		Instr::TailCall0(callee_reg) => {
+			tail_call!(callee_reg, 0)
+		}
+		Instr::TailCall1(callee_reg, arg_reg) => {
+			let arg = reg!(arg_reg).clone();
+			stacks.regs.push(arg);
+
+			tail_call!(callee_reg, 1)
+		}
+		Instr::TailCall2(callee_reg, arg0_reg, arg1_reg) => {
+			let arg0 = reg!(arg0_reg).clone();
+			let arg1 = reg!(arg1_reg).clone();
+			stacks.regs.push(arg0);
+			stacks.regs.push(arg1);
+
+			tail_call!(callee_reg, 2)
+		}
+		Instr::TailCallN(base_reg, arg_count) => {
+			for arg_reg in base_reg + 1 .. base_reg + 1 + arg_count {
+				let arg = reg!(arg_reg).clone();
+				stacks.regs.push(arg);
+			}
+
+			tail_call!(base_reg, arg_count as usize)
+		}
 		Instr::Splay(splay_bytes) => {
 			splay_bits = u32::from_ne_bytes(splay_bytes);
 
 			debug_assert!(
 				matches!(
 					bytecode.instrs[instr_n],
-					Instr::Call1(..) | Instr::Call2(..) | Instr::CallN(..) | Instr::OpArr(..)
+					Instr::Call1(..) | Instr::Call2(..) | Instr::CallN(..) | 
+					Instr::TailCall1(..) | Instr::TailCall2(..) | Instr::TailCallN(..) |
+					Instr::OpArr(..)
 				)
 			);
 		}
@@ -1093,10 +1910,10 @@ fn interpret(
 			return Ok(InterpretResult::Return(reg!(src_reg).clone()))
 		}
 		Instr::Yield(dst_reg, src_reg) => {
-			return Ok(InterpretResult::Yield(reg!(src_reg).clone(), dst_reg, instr_n))
+			return Ok(InterpretResult::Yield(reg!(src_reg).clone(), Some(dst_reg), instr_n))
 		}
 		Instr::Jump(jump_bytes) => {
-			instr_n = ((instr_n as isize) + isize::from(jump_bytes)) as usize;
+			jump!(jump_bytes);
 		}
 		Instr::JumpIfTrue(src_reg, jump_bytes) => {
 			let reg_true = match reg!(src_reg) {
@@ -1106,7 +1923,7 @@ fn interpret(
 			};
 			
 			if reg_true {
-				instr_n = ((instr_n as isize) + isize::from(jump_bytes)) as usize;
+				jump!(jump_bytes);
 			}
 		}
 		Instr::JumpIfFalse(src_reg, jump_bytes) => {
@@ -1117,7 +1934,67 @@ fn interpret(
 			};
 			
 			if !reg_true {
-				instr_n = ((instr_n as isize) + isize::from(jump_bytes)) as usize;
+				jump!(jump_bytes);
+			}
+		}
+		Instr::NumEqJumpIfFalse(dst_reg, arg0_reg, arg1_reg, jump_bytes) => {
+			numeric_cmp_op!(
+				NUM_EQ_SYM, 
+				dst_reg, arg0_reg, arg1_reg, 
+				|i0, i1| i0 == i1, 
+				|f0, f1| f0 == f1
+			);
+
+			if let Slot::Bool(false) = reg!(dst_reg) {
+				jump!(jump_bytes);
+			}
+		}
+		Instr::LtJumpIfFalse(dst_reg, arg0_reg, arg1_reg, jump_bytes) => {
+			numeric_cmp_op!(
+				LT_SYM, 
+				dst_reg, arg0_reg, arg1_reg, 
+				|i0, i1| i0 < i1, 
+				|f0, f1| f0 < f1
+			);
+
+			if let Slot::Bool(false) = reg!(dst_reg) {
+				jump!(jump_bytes);
+			}
+		}
+		Instr::LteJumpIfFalse(dst_reg, arg0_reg, arg1_reg, jump_bytes) => {
+			numeric_cmp_op!(
+				LTE_SYM, 
+				dst_reg, arg0_reg, arg1_reg, 
+				|i0, i1| i0 <= i1, 
+				|f0, f1| f0 <= f1
+			);
+
+			if let Slot::Bool(false) = reg!(dst_reg) {
+				jump!(jump_bytes);
+			}
+		}
+		Instr::GtJumpIfFalse(dst_reg, arg0_reg, arg1_reg, jump_bytes) => {
+			numeric_cmp_op!(
+				GT_SYM, 
+				dst_reg, arg0_reg, arg1_reg, 
+				|i0, i1| i0 > i1, 
+				|f0, f1| f0 > f1
+			);
+
+			if let Slot::Bool(false) = reg!(dst_reg) {
+				jump!(jump_bytes);
+			}
+		}
+		Instr::GteJumpIfFalse(dst_reg, arg0_reg, arg1_reg, jump_bytes) => {
+			numeric_cmp_op!(
+				GTE_SYM, 
+				dst_reg, arg0_reg, arg1_reg, 
+				|i0, i1| i0 >= i1, 
+				|f0, f1| f0 >= f1
+			);
+
+			if let Slot::Bool(false) = reg!(dst_reg) {
+				jump!(jump_bytes);
 			}
 		}
 		Instr::PushDefer(defer_id) => {
@@ -1131,9 +2008,12 @@ fn interpret(
 				let defer_instr = stacks.defers.pop().unwrap();
 				drop(stacks);
 
-				match interpret(vm, bytecode.clone(), defer_instr, base_reg, base_stay) {
+				let result = interpret(vm, bytecode.clone(), name, defer_instr,
+				                       base_reg, base_stay, false);
+				match result {
 					Ok(InterpretResult::EndDefer) => (),
 					Ok(InterpretResult::Return(..)) => unreachable!(),
+					Ok(InterpretResult::TailCall(..)) => unreachable!(),
 					Ok(InterpretResult::Yield(..)) => unreachable!(),
 					Err(new_error) => {
 						if error.is_some() {
@@ -1155,9 +2035,10 @@ fn interpret(
 			let defer_instr = bytecode.defers[defer_id as usize];
 
 			drop(stacks);
-			match interpret(vm, bytecode.clone(), defer_instr, base_reg, base_stay)? {
+			match interpret(vm, bytecode.clone(), name, defer_instr, base_reg, base_stay, false)? {
 				InterpretResult::EndDefer => (),
-				InterpretResult::Return(..) | InterpretResult::Yield(..) => unreachable!()
+				InterpretResult::Return(..) | InterpretResult::Yield(..) => unreachable!(),
+				InterpretResult::TailCall(..) => unreachable!()
 			}
 			stacks = vm.stacks.borrow_mut();
 		}
@@ -1169,7 +2050,8 @@ fn interpret(
 				ADD_SYM, 
 				dst_reg, arg0_reg, arg1_reg, 
 				|i0: i32, i1: i32| i0.wrapping_add(i1), 
-				|f0, f1| f0 + f1
+				|f0, f1| f0 + f1,
+				VectorOp::Add
 			)
 		}
 		Instr::OpSub(dst_reg, arg0_reg, arg1_reg) => {
@@ -1177,7 +2059,8 @@ fn interpret(
 				SUB_SYM, 
 				dst_reg, arg0_reg, arg1_reg, 
 				|i0: i32, i1: i32| i0.wrapping_sub(i1), 
-				|f0, f1| f0 - f1
+				|f0, f1| f0 - f1,
+				VectorOp::Sub
 			)
 		}
 		Instr::OpMul(dst_reg, arg0_reg, arg1_reg) => {
@@ -1185,7 +2068,8 @@ fn interpret(
 				MUL_SYM, 
 				dst_reg, arg0_reg, arg1_reg, 
 				|i0: i32, i1: i32| i0.wrapping_mul(i1), 
-				|f0, f1| f0 * f1
+				|f0, f1| f0 * f1,
+				VectorOp::Mul
 			)
 		}
 		Instr::OpDiv(dst_reg, arg0_reg, arg1_reg) => {
@@ -1198,14 +2082,20 @@ fn interpret(
 					reg!(dst_reg) = Slot::Int(i0.wrapping_div(i1));
 				}
 				(&Slot::Int(i), &Slot::Flo(f)) => {
-					reg!(dst_reg) = Slot::Flo((i as f32)/f);
+					reg!(dst_reg) = Slot::Flo((i as Flo)/f);
 				}
 				(&Slot::Flo(f), &Slot::Int(i)) => {
-					reg!(dst_reg) = Slot::Flo(f/(i as f32));
+					reg!(dst_reg) = Slot::Flo(f/(i as Flo));
 				}
 				(&Slot::Flo(f0), &Slot::Flo(f1)) => {
 					reg!(dst_reg) = Slot::Flo(f0/f1);
 				}
+				(arg0, arg1) if vecmath::is_vector(arg0) || vecmath::is_vector(arg1) => {
+					match vecmath::slot_arith(VectorOp::Div, arg0, arg1) {
+						Ok(result) => reg!(dst_reg) = result,
+						Err(err) => bail_op!(@error DIV_SYM, err)
+					}
+				}
 				_ => bail_op!(DIV_SYM, "non-number passed to /")
 			}
 		}
@@ -1220,6 +2110,9 @@ fn interpret(
 			match reg!(arg_reg) {
 				Slot::Int(i) => reg!(dst_reg) = Slot::Int(i.wrapping_neg()),
 				Slot::Flo(f) => reg!(dst_reg) = Slot::Flo(-f),
+				ref arg if vecmath::is_vector(arg) => {
+					reg!(dst_reg) = vecmath::slot_neg(arg).unwrap()
+				}
 				_ => bail_op!(SUB_SYM, "non-number passed to the - function")
 			}
 		}
@@ -1233,10 +2126,10 @@ fn interpret(
 					reg!(dst_reg) = Slot::Int(i0.wrapping_rem(i1));
 				}
 				(&Slot::Int(i), &Slot::Flo(f)) => {
-					reg!(dst_reg) = Slot::Flo((i as f32)%f);
+					reg!(dst_reg) = Slot::Flo((i as Flo)%f);
 				}
 				(&Slot::Flo(f), &Slot::Int(i)) => {
-					reg!(dst_reg) = Slot::Flo(f%(i as f32));
+					reg!(dst_reg) = Slot::Flo(f%(i as Flo));
 				}
 				(&Slot::Flo(f0), &Slot::Flo(f1)) => {
 					reg!(dst_reg) = Slot::Flo(f0%f1);
@@ -1248,7 +2141,7 @@ fn interpret(
 			match reg!(arg_reg) {
 				Slot::Int(i) => reg!(dst_reg) = Slot::Int(i.signum()),
 				Slot::Flo(f) => {
-					let sign = if f == 0.0f32 { 
+					let sign = if f == 0.0 { 
 						0
 					} else if f.is_nan() {
 						0
@@ -1260,6 +2153,44 @@ fn interpret(
 				_ => bail_op!(SIGN_SYM, "non-number passed to the sign function")
 			}
 		}
+		Instr::OpBitAnd(dst_reg, arg0_reg, arg1_reg) => {
+			int_op!(BIT_AND_SYM, dst_reg, arg0_reg, arg1_reg, |i0: i32, i1: i32| i0 & i1)
+		}
+		Instr::OpBitOr(dst_reg, arg0_reg, arg1_reg) => {
+			int_op!(BIT_OR_SYM, dst_reg, arg0_reg, arg1_reg, |i0: i32, i1: i32| i0 | i1)
+		}
+		Instr::OpBitXor(dst_reg, arg0_reg, arg1_reg) => {
+			int_op!(BIT_XOR_SYM, dst_reg, arg0_reg, arg1_reg, |i0: i32, i1: i32| i0 ^ i1)
+		}
+		Instr::OpBitNot(dst_reg, arg_reg) => {
+			match reg!(arg_reg) {
+				Slot::Int(i) => reg!(dst_reg) = Slot::Int(!i),
+				_ => bail_op!(BIT_NOT_SYM, "non-int passed to bit-not")
+			}
+		}
+		Instr::OpShl(dst_reg, arg0_reg, arg1_reg) => {
+			int_op!(SHL_SYM, dst_reg, arg0_reg, arg1_reg, |i0: i32, i1: i32| {
+				i0.wrapping_shl(i1 as u32)
+			})
+		}
+		Instr::OpShr(dst_reg, arg0_reg, arg1_reg) => {
+			int_op!(SHR_SYM, dst_reg, arg0_reg, arg1_reg, |i0: i32, i1: i32| {
+				(i0 as u32).wrapping_shr(i1 as u32) as i32
+			})
+		}
+		Instr::OpSar(dst_reg, arg0_reg, arg1_reg) => {
+			int_op!(SAR_SYM, dst_reg, arg0_reg, arg1_reg, |i0: i32, i1: i32| {
+				i0.wrapping_shr(i1 as u32)
+			})
+		}
+		Instr::OpMin(dst_reg, arg0_reg, arg1_reg) if !reg!(arg0_reg).is_num_or_char() ||
+		                                              !reg!(arg1_reg).is_num_or_char() => {
+			ord_op!(MIN_SYM, dst_reg, arg0_reg, arg1_reg, |ordering| ordering != Ordering::Greater)
+		}
+		Instr::OpMax(dst_reg, arg0_reg, arg1_reg) if !reg!(arg0_reg).is_num_or_char() ||
+		                                              !reg!(arg1_reg).is_num_or_char() => {
+			ord_op!(MAX_SYM, dst_reg, arg0_reg, arg1_reg, |ordering| ordering != Ordering::Less)
+		}
 		Instr::OpMin(dst_reg, arg0_reg, arg1_reg) => {
 			let arg0 = match &reg!(arg0_reg) {
 				&Slot::Int(i0) => Slot::Int(i0),
@@ -1277,8 +2208,8 @@ fn interpret(
 
 			let result = match (arg0, arg1) {
 				(Slot::Int(i0), Slot::Int(i1)) => if i0 <= i1 { Slot::Int(i0) } else { Slot::Int(i1) },
-				(Slot::Int(i), Slot::Flo(f)) => if i as f32 <= f { Slot::Int(i) } else { Slot::Flo(f) },
-				(Slot::Flo(f), Slot::Int(i)) => if f <= i as f32 { Slot::Flo(f) } else { Slot::Int(i) },
+				(Slot::Int(i), Slot::Flo(f)) => if i as Flo <= f { Slot::Int(i) } else { Slot::Flo(f) },
+				(Slot::Flo(f), Slot::Int(i)) => if f <= i as Flo { Slot::Flo(f) } else { Slot::Int(i) },
 				(Slot::Flo(f0), Slot::Flo(f1)) => if f0 <= f1 { Slot::Flo(f0) } else { Slot::Flo(f1) },
 				_ => unreachable!()
 			};
@@ -1302,8 +2233,8 @@ fn interpret(
 
 			let result = match (arg0, arg1) {
 				(Slot::Int(i0), Slot::Int(i1)) => if i0 >= i1 { Slot::Int(i0) } else { Slot::Int(i1) },
-				(Slot::Int(i), Slot::Flo(f)) => if i as f32 >= f { Slot::Int(i) } else { Slot::Flo(f) },
-				(Slot::Flo(f), Slot::Int(i)) => if f >= i as f32 { Slot::Flo(f) } else { Slot::Int(i) },
+				(Slot::Int(i), Slot::Flo(f)) => if i as Flo >= f { Slot::Int(i) } else { Slot::Flo(f) },
+				(Slot::Flo(f), Slot::Int(i)) => if f >= i as Flo { Slot::Flo(f) } else { Slot::Int(i) },
 				(Slot::Flo(f0), Slot::Flo(f1)) => if f0 >= f1 { Slot::Flo(f0) } else { Slot::Flo(f1) },
 				_ => unreachable!()
 			};
@@ -1332,6 +2263,8 @@ fn interpret(
 				(Predicate::Iterable, &Slot::Tab(_)) => true,
 				(Predicate::Iterable, &Slot::GIter(_)) => true,
 				(Predicate::Iterable, &Slot::Coro(_)) => true,
+				(Predicate::Iterable, &Slot::PArr(_)) => true,
+				(Predicate::Iterable, &Slot::PTab(_)) => true,
 				(Predicate::Obj, &Slot::Obj(_)) => true,
 				(Predicate::Class, &Slot::Class(_)) => true,
 				(Predicate::Callable, &Slot::GFn(_)) => true,
@@ -1343,6 +2276,7 @@ fn interpret(
 				(Predicate::RFn, &Slot::RFn(_)) => true,
 				(Predicate::Coro, &Slot::Coro(_)) => true,
 				(Predicate::RData, &Slot::RData(_)) => true,
+				(Predicate::Rec, &Slot::Rec(_)) => true,
 				_ => false
 			};
 
@@ -1358,8 +2292,8 @@ fn interpret(
 		}
 		Instr::OpFlo(dst_reg, arg_reg) => {
 			match reg!(arg_reg) {
-				Slot::Int(i) => reg!(dst_reg) = Slot::Flo(i as f32),
-				Slot::Bool(b) => reg!(dst_reg) = Slot::Flo(b as i32 as f32),
+				Slot::Int(i) => reg!(dst_reg) = Slot::Flo(i as Flo),
+				Slot::Bool(b) => reg!(dst_reg) = Slot::Flo(b as i32 as Flo),
 				Slot::Flo(f) => reg!(dst_reg) = Slot::Flo(f),
 				_ => bail_op!(FLO_SYM, "cannot cast argument to a flo")
 			}
@@ -1429,6 +2363,8 @@ fn interpret(
 				Slot::Tab(tab) => tab.giter().to_gc(),
 				Slot::Coro(coro) => coro.giter().to_gc(),
 				Slot::GIter(giter) => giter.clone(),
+				Slot::PArr(parr) => parr.giter().to_gc(),
+				Slot::PTab(ptab) => ptab.giter().to_gc(),
 				slot => bail_op!(ITER_SYM, "attempted to iterate {}", slot.a_type_name())
 			};
 			
@@ -1501,7 +2437,15 @@ fn interpret(
 						GIterLen::Unknown => reg!(dst_reg) = Slot::Sym(UNKNOWN_SYM)
 					}
 				}
-				_ => bail_op!(LEN_SYM, "non-arr/str/tab/iter passed to the len builtin")
+				Slot::PArr(ref parr) => reg!(dst_reg) = Slot::Int(parr.len() as i32),
+				Slot::PTab(ref ptab) => reg!(dst_reg) = Slot::Int(ptab.len() as i32),
+				Slot::View(ref view) if view.index().is_none() => {
+					reg!(dst_reg) = Slot::Int(view.len() as i32)
+				}
+				Slot::Vec2(ref v) => reg!(dst_reg) = Slot::Flo(v.magnitude()),
+				Slot::Vec3(ref v) => reg!(dst_reg) = Slot::Flo(v.magnitude()),
+				_ => bail_op!(LEN_SYM, "non-arr/str/tab/iter/parr/ptab/view/vector passed to the \
+				                        len builtin")
 			}
 		}
 		Instr::OpHasp(dst_reg, arg0_reg, arg1_reg) => {
@@ -1534,9 +2478,38 @@ fn interpret(
 						false
 					}
 				}
+				Slot::Rec(ref rec) => {
+					if let Slot::Sym(key_name) = key {
+						rec.has(key_name).unwrap()
+					} else {
+						false
+					}
+				}
+				Slot::PArr(ref parr) => {
+					if let Slot::Int(i) = key {
+						let len = parr.len() as i32;
+						i >= -len && i < len
+					} else {
+						false
+					}
+				}
+				Slot::PTab(ref ptab) => {
+					ptab.get_slot(&key).is_some()
+				}
+				Slot::View(ref view) => {
+					match key {
+						Slot::Int(i) if view.index().is_none() => {
+							let len = view.len() as i32;
+							i >= -len && i < len
+						}
+						Slot::Sym(key_name) => view.has(key_name).unwrap(),
+						_ => false
+					}
+				}
+				ref slot if vecmath::is_vector(slot) => vecmath::has(slot, &key).unwrap(),
 				ref slot => {
-					bail_op!(HASP_SYM, "expected an arr, tab, obj or class, but received {}", 
-					         slot.a_type_name())
+					bail_op!(HASP_SYM, "expected an arr, tab, obj, class, rec, parr, ptab, view or \
+					         vector, but received {}", slot.a_type_name())
 				}
 			};
 
@@ -1564,8 +2537,8 @@ fn interpret(
 						};
 
 						if index < 0 || (index as usize) >= len  {
-							bail_op!(ACCESS_SYM, "out-of-bounds {} access: len is {}, index is {}", 
-							         coll.a_type_name(), len, raw_index)
+							bail_op!(@error ACCESS_SYM,
+							         out_of_bounds_error(coll.type_name(), len, raw_index))
 						}
 						
 						match coll {
@@ -1600,6 +2573,8 @@ fn interpret(
 				Slot::Tab(ref tab) => {
 					if let Ok(Some(value)) = tab.get_if_present::<_, Slot>(&index) {
 						reg!(dst_reg) = value;
+					} else if let Some(default) = tab.default_value() {
+						reg!(dst_reg) = Slot::from_val(&default);
 					} else {
 						bail_op!(ACCESS_SYM, "key {:?} is not present", index)
 					}
@@ -1617,7 +2592,9 @@ fn interpret(
 							stacks = vm.stacks.borrow_mut();
 							reg!(dst_reg) = value;
 						} else {
-							bail_op!(ACCESS_SYM, "key {:?} is not present", index)
+							let suggestions = obj.similar_names(key_name, false);
+							let error = nonexistent_field_error(key_name, suggestions);
+							bail_op!(@error ACCESS_SYM, error)
 						}
 					} else {
 						let src_giter = match index {
@@ -1643,7 +2620,19 @@ fn interpret(
 							stacks = vm.stacks.borrow_mut();
 							reg!(dst_reg) = value;
 						} else {
-							bail_op!(ACCESS_SYM, "key {:?} is not present", index)
+							bail_op!(ACCESS_SYM, "key {:?} is not present: {}", index,
+							         rdata.describe_bindings(false))
+						}
+					} else if let Slot::Int(i) = index {
+						//an rdata type supports integer indexing by binding an op-access meth
+						drop(stacks);
+						let value: Option<Slot> = rdata.call_if_present(OP_ACCESS_SYM, &[i])?;
+						if let Some(value) = value {
+							stacks = vm.stacks.borrow_mut();
+							reg!(dst_reg) = value;
+						} else {
+							bail_op!(ACCESS_SYM, "indexed an rdata with an int, but {} has no \
+							         op-access meth", rdata.class_name())
 						}
 					} else {
 						let src_giter = match index {
@@ -1682,6 +2671,55 @@ fn interpret(
 						reg!(dst_reg) = Slot::GIter(giter.into_gc());
 					}	
 				}
+				Slot::Rec(ref rec) => {
+					if let Slot::Sym(key_name) = index {
+						if let Ok(Some(value)) = rec.get_if_present::<_, Slot>(key_name) {
+							reg!(dst_reg) = value;
+						} else {
+							bail_op!(ACCESS_SYM, "key {:?} is not present in a {} rec", 
+							         key_name, rec.name())
+						}
+					} else {
+						bail_op!(ACCESS_SYM, "indexed a rec with {}", index.a_type_name())
+					}
+				}
+				Slot::PArr(ref parr) => {
+					if let Slot::Int(raw_index) = index {
+						let len = parr.len();
+						let index = if raw_index < 0 {
+							(len as isize) + (raw_index as isize)
+						} else {
+							raw_index as isize
+						};
+
+						if index < 0 || (index as usize) >= len  {
+							bail_op!(@error ACCESS_SYM,
+							         out_of_bounds_error("parr", len, raw_index))
+						}
+
+						reg!(dst_reg) = parr.get_slot(index as usize);
+					} else {
+						bail_op!(ACCESS_SYM, "indexed a parr with {}", index.a_type_name())
+					}
+				}
+				Slot::PTab(ref ptab) => {
+					match ptab.get_slot(&index) {
+						Some(value) => reg!(dst_reg) = value,
+						None => bail_op!(ACCESS_SYM, "key {:?} is not present", index)
+					}
+				}
+				Slot::View(ref view) => {
+					match view.access(&index) {
+						Ok(value) => reg!(dst_reg) = value,
+						Err(err) => bail_op!(@error ACCESS_SYM, err)
+					}
+				}
+				ref slot if vecmath::is_vector(slot) => {
+					match vecmath::access(slot, &index).unwrap() {
+						Ok(value) => reg!(dst_reg) = value,
+						Err(err) => bail_op!(@error ACCESS_SYM, err)
+					}
+				}
 				slot => bail_op!(ACCESS_SYM, "attempted to index {}", slot.a_type_name())
 			}		
 		}
@@ -1713,21 +2751,31 @@ fn interpret(
 					};
 
 					if index < 0 || (index as usize) >= len  {
-						bail_op!(SET_ACCESS_SYM, "out-of-bounds {} access: len is {}, index is {}", 
-						         coll.type_name(), len, raw_index)
+						bail_op!(@error SET_ACCESS_SYM,
+						         out_of_bounds_error(coll.type_name(), len, raw_index))
 					}
 					
 					match reg!(arg0_reg) {
 						Slot::Arr(ref arr) => {
+							if arr.is_frozen() {
+								bail_op!(SET_ACCESS_SYM, "attempted to set index {} of a frozen arr",
+								         raw_index)
+							}
+
 							if !arr.can_mutate() {
-								bail_op!(SET_ACCESS_SYM, "attempted to mutate a frozen arr")
+								bail_op!(SET_ACCESS_SYM, "attempted to mutate a borrowed arr")
 							}
 
 							arr.set(index, &reg!(arg2_reg)).unwrap();
 						}
 						Slot::Str(ref st) => {
+							if st.is_frozen() {
+								bail_op!(SET_ACCESS_SYM, "attempted to set index {} of a frozen str",
+								         raw_index)
+							}
+
 							if !st.can_mutate() {
-								bail_op!(SET_ACCESS_SYM, "attempted to mutate a frozen str")
+								bail_op!(SET_ACCESS_SYM, "attempted to mutate a borrowed str")
 							}
 
 							let ch = match reg!(arg2_reg) {
@@ -1741,15 +2789,21 @@ fn interpret(
 					}
 				}
 				Slot::Tab(ref tab) => {
+					if tab.is_frozen() {
+						bail_op!(SET_ACCESS_SYM, "attempted to set the key {} in a frozen tab",
+						         index)
+					}
+
 					if !tab.can_mutate() {
-						bail_op!(SET_ACCESS_SYM, "attempted to mutate a frozen tab")
+						bail_op!(SET_ACCESS_SYM, "attempted to mutate a borrowed tab")
 					}
 					
 					tab.set(index, new_value).unwrap();
 				}
 				Slot::Obj(ref obj) => {
 					if obj.is_frozen() {
-						bail_op!(SET_ACCESS_SYM, "attempted to mutate a frozen obj")
+						bail_op!(SET_ACCESS_SYM, "attempted to set the field '{}' on a frozen obj",
+						         index)
 					}
 
 					if obj.is_killed() {
@@ -1762,8 +2816,9 @@ fn interpret(
 							//(todo: add a Frame variant for a property setter)
 							drop(stacks);
 							if !obj.set_if_present(key_name, new_value)? {
+								let suggestions = obj.similar_names(key_name, false);
 								bail_op!(SET_ACCESS_SYM, "attempted to mutate nonexistent \
-								         field '{}'", key_name)
+								         field '{}'{}", key_name, did_you_mean(&suggestions))
 							}
 							stacks = vm.stacks.borrow_mut();
 						}
@@ -1784,13 +2839,59 @@ fn interpret(
 							}
 							stacks = vm.stacks.borrow_mut();
 						}
+						Slot::Int(i) => {
+							drop(stacks);
+							let args = [Val::Int(i), new_value.root()];
+							if rdata.call_if_present::<_, _, Val>(OP_SET_ACCESS_SYM, &args[..])?
+							        .is_none() {
+								bail_op!(SET_ACCESS_SYM, "indexed an rdata with an int, but {} has \
+								         no op-set-access meth", rdata.class_name())
+							}
+							stacks = vm.stacks.borrow_mut();
+						}
 						_ => bail_op!(SET_ACCESS_SYM, "attempted to mutate non-sym rdata prop")
 					}
 				}
+				Slot::Rec(ref rec) => {
+					if rec.is_frozen() {
+						bail_op!(SET_ACCESS_SYM, "attempted to set the field '{}' on a frozen rec",
+						         index)
+					}
+
+					let key_name = match index {
+						Slot::Sym(key_name) => key_name,
+						_ => bail_op!(SET_ACCESS_SYM, "attempted to mutate non-sym rec field")
+					};
+
+					match rec.set_if_present(key_name, new_value) {
+						Ok(true) => (),
+						Ok(false) => bail_op!(SET_ACCESS_SYM, "attempted to mutate nonexistent \
+						                      field '{}' in a {} rec", key_name, rec.name()),
+						Err(err) => bail_op!(SET_ACCESS_SYM, "{}", err.val())
+					}
+				}
+				Slot::PArr(_) | Slot::PTab(_) => {
+					bail_op!(SET_ACCESS_SYM, "attempted to mutate {}, which is persistent; \
+					         use passoc instead", coll.a_type_name())
+				}
+				Slot::View(ref view) => {
+					let key_name = match index {
+						Slot::Sym(key_name) => key_name,
+						_ => bail_op!(SET_ACCESS_SYM, "attempted to mutate non-sym view field")
+					};
+
+					if let Err(err) = view.set_slot(key_name, &new_value) {
+						bail_op!(@error SET_ACCESS_SYM, err)
+					}
+				}
+				Slot::Vec2(_) | Slot::Vec3(_) | Slot::Mat3(_) => {
+					bail_op!(SET_ACCESS_SYM, "attempted to mutate {}, which is immutable",
+					         coll.a_type_name())
+				}
 				slot => bail_op!(SET_ACCESS_SYM, "attempted to index {}", slot.a_type_name())
 			}	
 
-			reg!(dst_reg) = Slot::Nil;	
+			reg!(dst_reg) = Slot::Nil;
 		}
 		Instr::OpArr(dst_reg, arg0_reg, arg_count) => {
 			let arr = if splay_bits != 0 {
@@ -1806,6 +2907,8 @@ fn interpret(
 							Slot::Arr(ref src) => capacity += src.len(),
 							Slot::Str(ref src) => capacity += src.len(),
 							Slot::Tab(ref src) => capacity += src.len(),
+							Slot::PArr(ref src) => capacity += src.len(),
+							Slot::PTab(ref src) => capacity += src.len(),
 							Slot::Coro(_) => {
 								capacity = 0;
 								break
@@ -1840,9 +2943,14 @@ fn interpret(
 								arr.extend(src.iter().map(Slot::Char)).unwrap();
 								None
 							}
+							Slot::PArr(ref src) => {
+								arr.extend((0 .. src.len()).map(|i| src.get_slot(i))).unwrap();
+								None
+							}
 							Slot::Tab(ref src) => Some(src.giter()),
 							Slot::Coro(ref src) => Some(src.giter()),
 							Slot::GIter(ref src) => Some(src.root()),
+							Slot::PTab(ref src) => Some(src.giter()),
 							_ => unreachable!()
 						};
 
@@ -1883,13 +2991,13 @@ fn interpret(
 			arr.set_span(glsp::new_arr_span(Some(cur_span)));
 			reg!(dst_reg) = Slot::Arr(arr.into_gc());
 		}
-		Instr::OpCallMeth(dst_reg, arg0_reg, arg_count) => {
+		Instr::OpCallMeth(dst_reg, arg0_reg, arg_count, cache_id) => {
 			if arg_count < 2 {
 				bail_op!(CALL_METH_SYM, "expected 2 or more args, but received {}", arg_count)
 			}
 
 			for i in arg0_reg .. arg0_reg + arg_count {
-				let arg = (reg!(i)).clone();
+				let arg = reg!(i).clone();
 				stacks.regs.push(arg);
 			}
 			let base_index = stacks.regs.len() - arg_count as usize;
@@ -1905,12 +3013,19 @@ fn interpret(
 			let receiver = stacks.regs[base_index + 1].clone();
 			let tuple = match receiver {
 				Slot::Obj(ref obj) => {
-					//because get_method could invoke a property getter, it can fail. (todo:
-					//add a Frame variant for a property getter)
-					drop(stacks);
-					let tuple = obj.get_method(method_name)?;
-					stacks = vm.stacks.borrow_mut();
-					tuple
+					match cached_meth(&bytecode, cache_id, obj, method_name) {
+						Some(tuple) => Some(tuple),
+						None => {
+							//because get_method could invoke a property getter, it can fail. 
+							//(todo: add a Frame variant for a property getter)
+							drop(stacks);
+							let tuple = obj.get_method(method_name)?;
+							stacks = vm.stacks.borrow_mut();
+
+							cache_meth(&bytecode, cache_id, obj, method_name, &tuple);
+							tuple
+						}
+					}
 				}
 				Slot::RData(ref rdata) => rdata.get_method(method_name),
 				Slot::Class(ref class) => class.get_method(method_name),
@@ -1920,8 +3035,22 @@ fn interpret(
 
 			let (callee, expects_self, expects_ni, ni) = match tuple {
 				Some(tuple) => tuple,
-				None => bail_op!(CALL_METH_SYM, "attempted to call nonexistent method '{}'", 
-						         method_name)
+				None => {
+					if let Slot::RData(ref rdata) = receiver {
+						let alternatives = Some(rdata.describe_bindings(true));
+						let error = nonexistent_meth_error(method_name, alternatives, Vec::new());
+						bail_op!(@error CALL_METH_SYM, error)
+					}
+
+					let suggestions = match receiver {
+						Slot::Obj(ref obj) => obj.similar_names(method_name, true),
+						Slot::Class(ref class) => class.similar_names(method_name),
+						_ => Vec::new()
+					};
+
+					let error = nonexistent_meth_error(method_name, None, suggestions);
+					bail_op!(@error CALL_METH_SYM, error)
+				}
 			};
 
 			vm.frames.borrow_mut().push(Frame::Call(callee.clone(), cur_span));
@@ -1956,13 +3085,13 @@ fn interpret(
 			stacks.regs.truncate(base_index);
 			reg!(dst_reg) = result;
 		}
-		Instr::OpCallMethOpt(dst_reg, arg0_reg, arg_count) => {
+		Instr::OpCallMethOpt(dst_reg, arg0_reg, arg_count, cache_id) => {
 			if arg_count < 2 {
 				bail_op!(CALL_METH_OPT_SYM, "expected 2 or more args, but received {}", arg_count)
 			}
 
 			for i in arg0_reg .. arg0_reg + arg_count {
-				let arg = (reg!(i)).clone();
+				let arg = reg!(i).clone();
 				stacks.regs.push(arg);
 			}
 			let base_index = stacks.regs.len() - arg_count as usize;
@@ -1976,12 +3105,19 @@ fn interpret(
 			let receiver = stacks.regs[base_index + 1].clone();
 			let tuple = match receiver {
 				Slot::Obj(ref obj) => {
-					//because get_method could invoke a property getter, it can fail. (todo:
-					//add a Frame variant for a property getter)
-					drop(stacks);
-					let tuple = obj.get_method(method_name)?;
-					stacks = vm.stacks.borrow_mut();
-					tuple
+					match cached_meth(&bytecode, cache_id, obj, method_name) {
+						Some(tuple) => Some(tuple),
+						None => {
+							//because get_method could invoke a property getter, it can fail. 
+							//(todo: add a Frame variant for a property getter)
+							drop(stacks);
+							let tuple = obj.get_method(method_name)?;
+							stacks = vm.stacks.borrow_mut();
+
+							cache_meth(&bytecode, cache_id, obj, method_name, &tuple);
+							tuple
+						}
+					}
 				}
 				Slot::RData(ref rdata) => rdata.get_method(method_name),
 				Slot::Class(ref class) => class.get_method(method_name),
@@ -2033,7 +3169,7 @@ fn interpret(
 			}
 
 			for i in arg0_reg .. arg0_reg + arg_count {
-				let arg = (reg!(i)).clone();
+				let arg = reg!(i).clone();
 				stacks.regs.push(arg);
 			}
 			let base_index = stacks.regs.len() - arg_count as usize;
@@ -2094,7 +3230,7 @@ fn interpret(
 
 			reg!(dst_reg) = match glsp::try_global(sym).unwrap() {
 				Some(val) => Slot::from_val(&val),
-				None => bail_op!(GLOBAL_SYM, "unbound symbol '{}'", sym)
+				None => bail_op!(@error GLOBAL_SYM, unbound_symbol_error(sym))
 			}
 		}
 		Instr::OpSetGlobal(dst_reg, arg0_reg, arg1_reg) => {
@@ -2108,8 +3244,8 @@ fn interpret(
 
 			match glsp::try_set_global(sym, &reg!(arg1_reg)).unwrap() {
 				Success => reg!(dst_reg) = Slot::Nil,
-				NotBound => bail_op!(SET_GLOBAL_SYM, "unbound symbol '{}'", sym),
-				Frozen => bail_op!(SET_GLOBAL_SYM, "global '{}' is frozen", sym)
+				NotBound => bail_op!(@error SET_GLOBAL_SYM, unbound_symbol_error(sym)),
+				Frozen => bail_op!(@error SET_GLOBAL_SYM, frozen_global_error(sym))
 			}
 		}
 	}
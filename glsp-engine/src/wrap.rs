@@ -5,20 +5,29 @@ use std::cell::{Ref};
 use std::cmp::{Ordering};
 use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::convert::{TryInto};
+use std::fmt::{Display};
 use std::hash::{BuildHasher, Hash};
 use std::io::{Write};
 use std::iter::{Extend, FromIterator};
 use std::ffi::{CString, CStr, OsString, OsStr};
 use std::path::{Path, PathBuf};
+use std::rc::{Rc};
 use super::code::{Coro, GFn};
 use super::collections::{Arr, DequeAccess, DequeOps, Deque, Str, Tab};
 use super::class::{Class, Obj};
-use super::engine::{glsp, RData, RFn, RRoot, RStore, stock_syms::*, Sym};
-use super::error::{GResult};
+use super::engine::{
+	ACTIVE_ENGINE_ID, glsp, GlobalSnapshot, LoadedFile, RData, ReloadReport, RFn, RRoot, RStore,
+	stock_syms::*, Sym
+};
+use super::error::{GError, GResult, MessageArgs, MessageId};
 use super::eval::{EnvMode, Expander};
 use super::gc::{Gc, Root, Slot};
 use super::iter::{GIter, Iterable, GIterLen};
-use super::val::{Num, Val};
+use super::persist::{PArr, PTab};
+use super::record::{Rec};
+use super::vecmath::{self, Mat3, Vec2, Vec3};
+use super::view::{View};
+use super::val::{Flo, Num, Val};
 
 /*
 rfn!() takes an arbitrary function or non-capturing closure whose arguments all implement
@@ -305,6 +314,13 @@ impl_to_val_root!(Class, Class);
 impl_to_val_root!(GFn, GFn);
 impl_to_val_root!(Coro, Coro);
 impl_to_val_root!(RData, RData);
+impl_to_val_root!(Rec, Rec);
+impl_to_val_root!(PArr, PArr);
+impl_to_val_root!(PTab, PTab);
+impl_to_val_root!(View, View);
+impl_to_val_root!(Vec2, Vec2);
+impl_to_val_root!(Vec3, Vec3);
+impl_to_val_root!(Mat3, Mat3);
 
 impl<T: RStore> ToVal for RRoot<T> {
 	#[inline(always)]
@@ -356,6 +372,54 @@ impl ToVal for Callable {
 	}
 }
 
+//a GlobalSnapshot is represented in GameLisp as a tab with two fields, `globals` and `macros`,
+//each of which maps syms to their bound values
+impl ToVal for GlobalSnapshot {
+	fn to_val(&self) -> GResult<Val> {
+		let globals = glsp::tab();
+		let macros = glsp::tab();
+
+		for &(sym, ref global, ref mac) in &self.bindings {
+			if let Some(ref val) = *global {
+				globals.set(sym, val)?;
+			}
+
+			if let Some(ref expander) = *mac {
+				macros.set(sym, expander)?;
+			}
+		}
+
+		let tab = glsp::tab();
+		tab.set(glsp::sym("globals")?, globals)?;
+		tab.set(glsp::sym("macros")?, macros)?;
+
+		Ok(Val::Tab(tab))
+	}
+}
+
+impl ToVal for ReloadReport {
+	fn to_val(&self) -> GResult<Val> {
+		let tab = glsp::tab();
+		tab.set(glsp::sym("result")?, self.result())?;
+		tab.set(glsp::sym("stale-objs")?, self.stale_objs())?;
+
+		let errors: Vec<Val> = self.migration_errors().iter().map(|err| err.val()).collect();
+		tab.set(glsp::sym("migration-errors")?, errors)?;
+
+		Ok(Val::Tab(tab))
+	}
+}
+
+impl ToVal for LoadedFile {
+	fn to_val(&self) -> GResult<Val> {
+		let tab = glsp::tab();
+		tab.set(glsp::sym("filename")?, self.filename())?;
+		tab.set(glsp::sym("dependencies")?, self.dependencies())?;
+
+		Ok(Val::Tab(tab))
+	}
+}
+
 impl ToVal for Expander {
 	#[inline(always)]
 	fn to_val(&self) -> GResult<Val> {
@@ -382,7 +446,9 @@ impl ToVal for Iterable {
 			Iterable::Str(root) => Ok(Val::Str(root.clone())),
 			Iterable::Tab(root) => Ok(Val::Tab(root.clone())),
 			Iterable::GIter(root) => Ok(Val::GIter(root.clone())),
-			Iterable::Coro(root) => Ok(Val::Coro(root.clone()))
+			Iterable::Coro(root) => Ok(Val::Coro(root.clone())),
+			Iterable::PArr(root) => Ok(Val::PArr(root.clone())),
+			Iterable::PTab(root) => Ok(Val::PTab(root.clone()))
 		}
 	}
 
@@ -393,7 +459,9 @@ impl ToVal for Iterable {
 			Iterable::Str(root) => Ok(Slot::Str(Gc::from_root(root))),
 			Iterable::Tab(root) => Ok(Slot::Tab(Gc::from_root(root))),
 			Iterable::GIter(root) => Ok(Slot::GIter(Gc::from_root(root))),
-			Iterable::Coro(root) => Ok(Slot::Coro(Gc::from_root(root)))
+			Iterable::Coro(root) => Ok(Slot::Coro(Gc::from_root(root))),
+			Iterable::PArr(root) => Ok(Slot::PArr(Gc::from_root(root))),
+			Iterable::PTab(root) => Ok(Slot::PTab(Gc::from_root(root)))
 		}
 	}
 }
@@ -461,15 +529,16 @@ impl_to_val_bounded_int!(u64);
 impl_to_val_bounded_int!(u128);
 impl_to_val_bounded_int!(usize);
 
+//unless the "f64-flos" feature is enabled, this rounds the f64 to the nearest f32
 impl ToVal for f64 {
 	#[inline(always)]
 	fn to_val(&self) -> GResult<Val> {
-		Ok(Val::Flo(*self as f32))
+		Ok(Val::Flo(*self as Flo))
 	}
 
 	#[inline(always)]
 	fn to_slot(&self) -> GResult<Slot> {
-		Ok(Slot::Flo(*self as f32))
+		Ok(Slot::Flo(*self as Flo))
 	}
 }
 
@@ -722,6 +791,64 @@ impl<T> IntoResult for GResult<T> where T: IntoResult {
 // FromVal implementations
 //-------------------------------------------------------------------------------------------------
 
+//the scalar conversions below are on the hot path for every rfn call, so their error messages are
+//constructed out-of-line. this leaves only a tag check (and perhaps a range check) inline.
+
+#[cold]
+#[inline(never)]
+fn type_error(expected: &'static str, received: &'static str) -> GError {
+	type_mismatch(describe_expected(expected), received)
+}
+
+//the non-scalar conversions describe their expected type themselves
+#[cold]
+#[inline(never)]
+fn type_mismatch<E: Display>(expected: E, received: &str) -> GError {
+	let args = MessageArgs::new().with("expected", expected).with("received", received);
+	GError::from_message(MessageId::TypeMismatch, args)
+}
+
+//translates the name of a Rust type into the name of the GameLisp type(s) which it accepts
+fn describe_expected(expected: &'static str) -> &'static str {
+	match expected {
+		"i8" | "i16" | "i32" | "i64" | "i128" | "isize" => "an int",
+		"u8" | "u16" | "u32" | "u64" | "u128" | "usize" => "a non-negative int",
+		"f32" | "f64" => "a flo",
+		"Num" => "a flo or int",
+		"char" => "a char",
+		"bool" => "a bool",
+		"Sym" => "a sym",
+		"RFn" => "an rfn",
+		"Arr" => "an arr",
+		"Str" => "a str",
+		"Tab" => "a tab",
+		"GIter" => "an iter",
+		"Obj" => "an obj",
+		"GFn" => "a fn",
+		"Class" => "a class",
+		"Coro" => "a coro",
+		"RData" => "an rdata",
+		"Rec" => "a rec",
+		"PArr" => "a parr",
+		"PTab" => "a ptab",
+		"View" => "a view",
+		"Vec2" => "a vec2",
+		"Vec3" => "a vec3",
+		"Mat3" => "a mat3",
+		"Deque" => "an arr or str",
+		"Callable" => "a fn, rfn or class",
+		"Iterable" => "an arr, str, tab, iter, coro, parr or ptab",
+		expected => expected
+	}
+}
+
+#[cold]
+#[inline(never)]
+fn int_range_error(expected: &'static str, received: i32) -> GError {
+	let args = MessageArgs::new().with("expected", expected).with("value", received);
+	GError::from_message(MessageId::IntOutOfRange, args)
+}
+
 // Val, Slot
 //-----------------------------------------------------------------------------
 
@@ -760,8 +887,7 @@ macro_rules! impl_from_val_infallible(
 				fn from_val(val: &Val) -> GResult<Self> {
 					match *val {
 						Val::$variant(interior) => Ok(interior as $t),
-						ref val => bail!("expected {}, received {}", 
-						                 stringify!($t), val.a_type_name())
+						ref val => Err(type_error(stringify!($t), val.a_type_name()))
 					}
 				}
 
@@ -769,8 +895,7 @@ macro_rules! impl_from_val_infallible(
 				fn from_slot(val: &Slot) -> GResult<Self> {
 					match *val {
 						Slot::$variant(interior) => Ok(interior as $t),
-						ref val => bail!("expected {}, received {}", 
-						                 stringify!($t), val.a_type_name())
+						ref val => Err(type_error(stringify!($t), val.a_type_name()))
 					}
 				}
 			}
@@ -797,8 +922,7 @@ macro_rules! impl_from_val_root(
 				fn from_val(val: &Val) -> GResult<Self> {
 					match *val {
 						Val::$variant(ref root) => Ok(root.clone()),
-						ref val => bail!("expected {}, received {}", 
-						                 stringify!(Root<$t>), val.a_type_name())
+						ref val => Err(type_error(stringify!($t), val.a_type_name()))
 					}
 				}
 
@@ -806,8 +930,7 @@ macro_rules! impl_from_val_root(
 				fn from_slot(val: &Slot) -> GResult<Self> {
 					match *val {
 						Slot::$variant(ref gc) => Ok(gc.root()),
-						ref val => bail!("expected {}, received {}", 
-						                 stringify!(Root<$t>), val.a_type_name())
+						ref val => Err(type_error(stringify!($t), val.a_type_name()))
 					}
 				}
 			}
@@ -817,8 +940,7 @@ macro_rules! impl_from_val_root(
 				fn from_val(val: &Val) -> GResult<Self> {
 					match *val {
 						Val::$variant(ref root) => Ok(root.as_gc().clone()),
-						ref val => bail!("expected {}, received {}", 
-						                 stringify!(Gc<$t>), val.a_type_name())
+						ref val => Err(type_error(stringify!($t), val.a_type_name()))
 					}
 				}
 
@@ -826,8 +948,7 @@ macro_rules! impl_from_val_root(
 				fn from_slot(val: &Slot) -> GResult<Self> {
 					match *val {
 						Slot::$variant(ref gc) => Ok(gc.clone()),
-						ref val => bail!("expected {}, received {}", 
-						                 stringify!(Gc<$t>), val.a_type_name())
+						ref val => Err(type_error(stringify!($t), val.a_type_name()))
 					}
 				}
 			}
@@ -844,7 +965,14 @@ impl_from_val_root!(
 	(GFn, GFn),
 	(Class, Class),
 	(Coro, Coro),
-	(RData, RData)
+	(RData, RData),
+	(Rec, Rec),
+	(PArr, PArr),
+	(PTab, PTab),
+	(View, View),
+	(Vec2, Vec2),
+	(Vec3, Vec3),
+	(Mat3, Mat3)
 );
 
 impl<T: RStore> FromVal for RRoot<T> {
@@ -852,7 +980,10 @@ impl<T: RStore> FromVal for RRoot<T> {
 	fn from_val(val: &Val) -> GResult<RRoot<T>> {
 		match val {
 			Val::RData(root) => Ok(RRoot::new(root.clone())),
-			val => bail!("expected RRoot<{}>, received {}", type_name::<T>(), val.a_type_name())
+			val => {
+				let expected = format!("RRoot<{}>", type_name::<T>());
+				Err(type_mismatch(expected, val.a_type_name()))
+			}
 		}
 	}
 
@@ -860,7 +991,10 @@ impl<T: RStore> FromVal for RRoot<T> {
 	fn from_slot(slot: &Slot) -> GResult<RRoot<T>> {
 		match slot {
 			Slot::RData(gc) => Ok(RRoot::new(gc.root())),
-			val => bail!("expected RRoot<{}>, received {}", type_name::<T>(), val.a_type_name())
+			val => {
+				let expected = format!("RRoot<{}>", type_name::<T>());
+				Err(type_mismatch(expected, val.a_type_name()))
+			}
 		}
 	}
 }
@@ -875,12 +1009,8 @@ macro_rules! impl_from_val_int_fallible_small(
 						Val::Int(i) if i >= $t::MIN as i32 && i <= $t::MAX as i32 => {
 							Ok(i as $t)
 						}
-						Val::Int(i) => {
-							bail!("expected {}, received an int with value {}",
-							      stringify!($t), i)
-						}
-						ref val => bail!("expected {}, received {}", 
-						                 stringify!($t), val.a_type_name())
+						Val::Int(i) => Err(int_range_error(stringify!($t), i)),
+						ref val => Err(type_error(stringify!($t), val.a_type_name()))
 					}
 				}
 
@@ -890,12 +1020,8 @@ macro_rules! impl_from_val_int_fallible_small(
 						Slot::Int(i) if i >= $t::MIN as i32 && i <= $t::MAX as i32 => {
 							Ok(i as $t)
 						}
-						Slot::Int(i) => {
-							bail!("expected {}, received an int with value {}",
-							      stringify!($t), i)
-						}
-						ref val => bail!("expected {}, received {}", 
-						                 stringify!($t), val.a_type_name())
+						Slot::Int(i) => Err(int_range_error(stringify!($t), i)),
+						ref val => Err(type_error(stringify!($t), val.a_type_name()))
 					}
 				}
 			}
@@ -915,12 +1041,8 @@ macro_rules! impl_from_val_int_fallible_large(
 						Val::Int(i) if i >= 0 => {
 							Ok(i as $t)
 						}
-						Val::Int(i) => {
-							bail!("expected {}, received an int with value {}",
-							      stringify!($t), i)
-						}
-						ref val => bail!("expected {}, received {}", 
-						                 stringify!($t), val.a_type_name())
+						Val::Int(i) => Err(int_range_error(stringify!($t), i)),
+						ref val => Err(type_error(stringify!($t), val.a_type_name()))
 					}
 				}
 
@@ -930,12 +1052,8 @@ macro_rules! impl_from_val_int_fallible_large(
 						Slot::Int(i) if i >= 0 => {
 							Ok(i as $t)
 						}
-						Slot::Int(i) => {
-							bail!("expected {}, received an int with value {}",
-							      stringify!($t), i)
-						}
-						ref val => bail!("expected {}, received {}", 
-						                 stringify!($t), val.a_type_name())
+						Slot::Int(i) => Err(int_range_error(stringify!($t), i)),
+						ref val => Err(type_error(stringify!($t), val.a_type_name()))
 					}
 				}
 			}
@@ -948,20 +1066,22 @@ impl_from_val_int_fallible_large!(u32, u64, u128, usize);
 // f32, f64
 //-----------------------------------------------------------------------------
 
+//when the "f64-flos" feature is enabled, this rounds the flo to the nearest f32, following the
+//usual rules for an `as` cast. values which are too large for an f32 become infinite.
 impl FromVal for f32 {
 	#[inline(always)]
 	fn from_val(val: &Val) -> GResult<Self> {
 		match *val {
-			Val::Flo(f) => Ok(f),
-			ref val => bail!("expected f32, received {}", val.a_type_name())
+			Val::Flo(f) => Ok(f as f32),
+			ref val => Err(type_error("f32", val.a_type_name()))
 		}
 	}
 
 	#[inline(always)]
 	fn from_slot(val: &Slot) -> GResult<Self> {
 		match *val {
-			Slot::Flo(f) => Ok(f),
-			ref val => bail!("expected f32, received {}", val.a_type_name())
+			Slot::Flo(f) => Ok(f as f32),
+			ref val => Err(type_error("f32", val.a_type_name()))
 		}
 	}
 }
@@ -971,7 +1091,7 @@ impl FromVal for f64 {
 	fn from_val(val: &Val) -> GResult<Self> {
 		match *val {
 			Val::Flo(f) => Ok(f as f64),
-			ref val => bail!("expected f64, received {}", val.a_type_name())
+			ref val => Err(type_error("f64", val.a_type_name()))
 		}
 	}
 
@@ -979,7 +1099,7 @@ impl FromVal for f64 {
 	fn from_slot(val: &Slot) -> GResult<Self> {
 		match *val {
 			Slot::Flo(f) => Ok(f as f64),
-			ref val => bail!("expected f64, received {}", val.a_type_name())
+			ref val => Err(type_error("f64", val.a_type_name()))
 		}
 	}
 }
@@ -993,7 +1113,7 @@ impl FromVal for Num {
 		match *val {
 			Val::Int(i) => Ok(Num::Int(i)),
 			Val::Flo(f) => Ok(Num::Flo(f)),
-			ref val => bail!("expected Num, received {}", val.a_type_name())
+			ref val => Err(type_error("Num", val.a_type_name()))
 		}
 	}
 
@@ -1002,7 +1122,7 @@ impl FromVal for Num {
 		match *val {
 			Slot::Int(i) => Ok(Num::Int(i)),
 			Slot::Flo(f) => Ok(Num::Flo(f)),
-			ref val => bail!("expected Num, received {}", val.a_type_name())
+			ref val => Err(type_error("Num", val.a_type_name()))
 		}
 	}
 }
@@ -1016,7 +1136,7 @@ impl FromVal for Deque {
 		match *val {
 			Val::Arr(ref root) => Ok(Deque::Arr(root.clone())),
 			Val::Str(ref root) => Ok(Deque::Str(root.clone())),
-			ref val => bail!("expected Deque, received {}", val.a_type_name())
+			ref val => Err(type_error("Deque", val.a_type_name()))
 		}
 	}
 
@@ -1025,7 +1145,7 @@ impl FromVal for Deque {
 		match *val {
 			Slot::Arr(ref gc) => Ok(Deque::Arr(gc.root())),
 			Slot::Str(ref gc) => Ok(Deque::Str(gc.root())),
-			ref val => bail!("expected Deque, received {}", val.a_type_name())
+			ref val => Err(type_error("Deque", val.a_type_name()))
 		}
 	}
 }
@@ -1040,7 +1160,7 @@ impl FromVal for Callable {
 			Val::GFn(ref root) => Ok(Callable::GFn(root.clone())),
 			Val::RFn(rfn) => Ok(Callable::RFn(rfn)),
 			Val::Class(ref root) => Ok(Callable::Class(root.clone())),
-			ref val => bail!("expected Callable, received {}", val.a_type_name())
+			ref val => Err(type_error("Callable", val.a_type_name()))
 		}
 	}
 
@@ -1050,18 +1170,49 @@ impl FromVal for Callable {
 			Slot::GFn(ref gc) => Ok(Callable::GFn(gc.root())),
 			Slot::RFn(rfn) => Ok(Callable::RFn(rfn)),
 			Slot::Class(ref gc) => Ok(Callable::Class(gc.root())),
-			ref val => bail!("expected Callable, received {}", val.a_type_name())
+			ref val => Err(type_error("Callable", val.a_type_name()))
 		}
 	}
 }
 
+impl FromVal for GlobalSnapshot {
+	fn from_val(val: &Val) -> GResult<Self> {
+		let tab = match *val {
+			Val::Tab(ref tab) => tab,
+			ref val => return Err(type_mismatch("a GlobalSnapshot", val.a_type_name()))
+		};
+
+		let globals = tab.get::<_, Root<Tab>>(glsp::sym("globals")?)?;
+		let macros = tab.get::<_, Root<Tab>>(glsp::sym("macros")?)?;
+
+		let mut map = HashMap::<Sym, (Option<Val>, Option<Expander>)>::new();
+		for (key, val) in &globals.entries() {
+			map.entry(Sym::from_val(&key)?).or_default().0 = Some(val);
+		}
+
+		for (key, val) in &macros.entries() {
+			map.entry(Sym::from_val(&key)?).or_default().1 = Some(Expander::from_val(&val)?);
+		}
+
+		let mut bindings = Vec::from_iter(map.into_iter().map(|(sym, (global, mac))| {
+			(sym, global, mac)
+		}));
+		bindings.sort_by_key(|&(sym, _, _)| sym.0);
+
+		Ok(GlobalSnapshot {
+			engine_id: ACTIVE_ENGINE_ID.with(|cell| cell.get().unwrap()),
+			bindings
+		})
+	}
+}
+
 impl FromVal for Expander {
 	#[inline(always)]
 	fn from_val(val: &Val) -> GResult<Self> {
 		match *val {
 			Val::GFn(ref root) => Ok(Expander::GFn(root.clone())),
 			Val::RFn(rfn) => Ok(Expander::RFn(rfn)),
-			ref val => bail!("expected Expander, received {}", val.a_type_name())
+			ref val => return Err(type_mismatch("Expander", val.a_type_name()))
 		}
 	}
 
@@ -1070,7 +1221,7 @@ impl FromVal for Expander {
 		match *val {
 			Slot::GFn(ref gc) => Ok(Expander::GFn(gc.root())),
 			Slot::RFn(rfn) => Ok(Expander::RFn(rfn)),
-			ref val => bail!("expected Expander, received {}", val.a_type_name())
+			ref val => return Err(type_mismatch("Expander", val.a_type_name()))
 		}
 	}
 }
@@ -1084,7 +1235,9 @@ impl FromVal for Iterable {
 			Val::Tab(root) => Ok(Iterable::Tab(root.clone())),
 			Val::GIter(root) => Ok(Iterable::GIter(root.clone())),
 			Val::Coro(root) => Ok(Iterable::Coro(root.clone())),
-			val => bail!("expected Iterable, received {}", val.a_type_name())
+			Val::PArr(root) => Ok(Iterable::PArr(root.clone())),
+			Val::PTab(root) => Ok(Iterable::PTab(root.clone())),
+			val => Err(type_error("Iterable", val.a_type_name()))
 		}
 	}
 
@@ -1096,7 +1249,9 @@ impl FromVal for Iterable {
 			Slot::Tab(gc) => Ok(Iterable::Tab(gc.root())),
 			Slot::GIter(gc) => Ok(Iterable::GIter(gc.root())),
 			Slot::Coro(gc) => Ok(Iterable::Coro(gc.root())),
-			slot => bail!("expected Iterable, received {}", slot.a_type_name())
+			Slot::PArr(gc) => Ok(Iterable::PArr(gc.root())),
+			Slot::PTab(gc) => Ok(Iterable::PTab(gc.root())),
+			slot => Err(type_error("Iterable", slot.a_type_name()))
 		}
 	}
 }
@@ -1112,7 +1267,7 @@ impl FromVal for EnvMode {
 					_ => bail!("expected an EnvMode, received the symbol {}", sym)
 				}
 			}
-			ref val => bail!("expected an EnvMode, received {}", val.a_type_name())
+			ref val => return Err(type_mismatch("an EnvMode", val.a_type_name()))
 		}
 	}
 }
@@ -1127,7 +1282,7 @@ impl FromVal for Ordering {
 			Val::Sym(LT_SYM) => Ok(Ordering::Less),
 			Val::Sym(NUM_EQ_SYM) => Ok(Ordering::Equal),
 			Val::Sym(GT_SYM) => Ok(Ordering::Greater),
-			ref val => bail!("expected Ordering, received {}", val.a_type_name())
+			ref val => return Err(type_mismatch("Ordering", val.a_type_name()))
 		}
 	}
 
@@ -1137,7 +1292,7 @@ impl FromVal for Ordering {
 			Slot::Sym(LT_SYM) => Ok(Ordering::Less),
 			Slot::Sym(NUM_EQ_SYM) => Ok(Ordering::Equal),
 			Slot::Sym(GT_SYM) => Ok(Ordering::Greater),
-			ref val => bail!("expected Ordering, received {}", val.a_type_name())
+			ref val => return Err(type_mismatch("Ordering", val.a_type_name()))
 		}
 	}
 }
@@ -1158,7 +1313,7 @@ impl<T: FromVal> FromVal for Vec<T> {
 
 				Ok(vec)
 			}
-			ref val => bail!("expected a Vec, received {}", val.a_type_name())
+			ref val => return Err(type_mismatch("a Vec", val.a_type_name()))
 		}
 	}
 }
@@ -1179,7 +1334,7 @@ impl<T: FromVal> FromVal for VecDeque<T> {
 
 				Ok(vec)
 			}
-			ref val => bail!("expected a VecDeque, received {}", val.a_type_name())
+			ref val => return Err(type_mismatch("a VecDeque", val.a_type_name()))
 		}
 	}
 }
@@ -1204,7 +1359,7 @@ where
 
 				Ok(small_vec)
 			}
-			ref val => bail!("expected a SmallVec, received {}", val.a_type_name())
+			ref val => return Err(type_mismatch("a SmallVec", val.a_type_name()))
 		}
 	}
 }
@@ -1228,7 +1383,17 @@ macro_rules! impl_from_val_array {
 							)*])
 						}
 						ref val => {
-							bail!("expected a [T; {}], received {}", $len, val.a_type_name())
+							if let Some(elements) = vecmath::elements(val) {
+								ensure!(elements.len() == $len, "expected a [T; {}], received {}",
+								        $len, val.a_type_name());
+
+								return Ok([$(
+									T::from_val(&elements[$n])?,
+								)*])
+							}
+
+							let expected = format!("a [T; {}]", $len);
+							return Err(type_mismatch(expected, val.a_type_name()))
 						}
 					}
 				}
@@ -1277,7 +1442,19 @@ macro_rules! impl_from_val_tuple {
 							arr.get::<$t>($i)?,
 						)*))
 					}
-					ref val => bail!("expected a tuple, received {}", val.a_type_name())
+					ref val => {
+						if let Some(elements) = vecmath::elements(val) {
+							ensure!(elements.len() == $len,
+							        "expected a {}-element tuple, received {}",
+							        $len, val.a_type_name());
+
+							return Ok(($(
+								$t::from_val(&elements[$i])?,
+							)*))
+						}
+
+						Err(type_mismatch("a tuple", val.a_type_name()))
+					}
 				}
 			}
 		}
@@ -1304,7 +1481,7 @@ impl FromVal for String {
 	fn from_val(val: &Val) -> GResult<Self> {
 		match *val {
 			Val::Str(ref st) => Ok(st.to_string()),
-			ref val => bail!("expected a str, received {}", val.a_type_name())
+			ref val => return Err(type_mismatch("a str", val.a_type_name()))
 		}
 	}
 }
@@ -1323,7 +1500,7 @@ impl FromVal for CString {
 					}
 				}
 			}
-			ref val => bail!("expected a C string, received {}", val.a_type_name())
+			ref val => return Err(type_mismatch("a C string", val.a_type_name()))
 		}
 	}
 }
@@ -1335,7 +1512,7 @@ impl FromVal for PathBuf {
 	fn from_val(val: &Val) -> GResult<Self> {
 		match *val {
 			Val::Str(ref st) => Ok(PathBuf::from(st.to_string())),
-			ref val => bail!("expected a path, received {}", val.a_type_name())
+			ref val => return Err(type_mismatch("a path", val.a_type_name()))
 		}
 	}
 }
@@ -1347,7 +1524,7 @@ impl FromVal for OsString {
 	fn from_val(val: &Val) -> GResult<Self> {
 		match *val {
 			Val::Str(ref st) => Ok(OsString::from(st.to_string())),
-			ref val => bail!("expected an OS string, received {}", val.a_type_name())
+			ref val => return Err(type_mismatch("an OS string", val.a_type_name()))
 		}
 	}
 }
@@ -1379,7 +1556,7 @@ where
 
 				Ok(hash_map)
 			}
-			ref val => bail!("expected a HashMap, received {}", val.a_type_name())
+			ref val => return Err(type_mismatch("a HashMap", val.a_type_name()))
 		}
 	}
 }
@@ -1405,7 +1582,7 @@ impl<K: Ord + FromVal, V: FromVal> FromVal for BTreeMap<K, V> {
 
 				Ok(btree_map)
 			}
-			ref val => bail!("expected a BTreeMap, received {}", val.a_type_name())
+			ref val => return Err(type_mismatch("a BTreeMap", val.a_type_name()))
 		}
 	}
 }
@@ -1420,7 +1597,8 @@ Wrap a function pointer or closure so that it can be passed to [`glsp::rfn`](fn.
 and similar functions.
 
 The macro receives a single argument, which must be the path to a function, the path to a method,
-or an expression which evaluates to a *non-capturing* closure.
+or an expression which evaluates to a *non-capturing* closure. Closures which capture their
+environment should be passed to [`rfn_boxed!`](macro.rfn_boxed.html) instead.
 
 The return value is a [`WrappedFn`](struct.WrappedFn.html). This is an opaque type: it doesn't
 support any operations except being converted to an `RFn`.
@@ -1443,7 +1621,7 @@ macro_rules! rfn {
 					$crate::forwarder($fn_expr, &mut temps)
 				},
 				$crate::wrapped_arg_limits($fn_expr)
-			)
+			).with_param_types($crate::wrapped_param_types($fn_expr))
 		}
 	);
 }
@@ -1458,7 +1636,8 @@ This opaque struct is produced by the [`rfn!` macro](macro.rfn.html), and consum
 #[derive(Copy, Clone)]
 pub struct WrappedFn {
 	wrapper: fn(Ref<[Slot]>) -> GResult<Slot>,
-	pub(crate) arg_limits: (usize, Option<usize>)
+	pub(crate) arg_limits: (usize, Option<usize>),
+	pub(crate) param_types: Option<fn() -> Vec<&'static str>>
 }
 
 impl WrappedFn {
@@ -1467,29 +1646,152 @@ impl WrappedFn {
 	           arg_limits: (usize, Option<usize>)) -> WrappedFn {
 		WrappedFn {
 			wrapper,
-			arg_limits
+			arg_limits,
+			param_types: None
+		}
+	}
+
+	#[doc(hidden)]
+	pub fn with_param_types(self, param_types: fn() -> Vec<&'static str>) -> WrappedFn {
+		WrappedFn {
+			param_types: Some(param_types),
+			..self
 		}
 	}
 
+	//the arg count is checked by make_temps(), within the wrapper itself
 	#[inline(always)]
 	pub(crate) fn call(&self, vals: Ref<[Slot]>) -> GResult<Slot> {
-		if vals.len() < self.arg_limits.0 {
-			bail!("too few arguments: received {}, expected at least {}", 
-			      vals.len(), self.arg_limits.0)
+		(self.wrapper)(vals)
+	}
+
+	pub(crate) fn as_usize(&self) -> usize {
+		self.wrapper as usize
+	}
+}
+
+/**
+Wrap a closure which captures its environment so that it can be passed to
+[`glsp::rfn_boxed`](fn.rfn_boxed.html) or [`glsp::bind_rfn_boxed`](fn.bind_rfn_boxed.html).
+
+This macro performs the same argument conversions as [`rfn!`](macro.rfn.html), but its
+argument can be any `'static` closure. The return value is a [`BoxedFn`](struct.BoxedFn.html).
+
+	let world = Rc::new(RefCell::new(World::new()));
+
+	let world_ref = world.clone();
+	glsp::bind_rfn_boxed("spawn", rfn_boxed!(move |name: Sym, x: i32, y: i32| {
+		world_ref.borrow_mut().spawn(name, x, y)
+	}))?;
+
+Each invocation of the macro allocates a new `BoxedFn`, so unlike `rfn!`, the resulting
+`RFn`s are never deduplicated.
+
+When the argument is a closure expression whose parameters are all plain identifiers with a
+type annotation, like the example above, the parameters' names are recorded in the
+[API manifest](glsp/fn.api_manifest.html). Such a closure is always converted into a `move`
+closure.
+*/
+
+#[macro_export]
+macro_rules! rfn_boxed {
+	(@wrap $closure:expr) => (
+		{
+			let closure = $closure;
+			let arg_limits = $crate::wrapped_arg_limits(&closure);
+			let param_types = $crate::wrapped_param_types(&closure);
+
+			$crate::BoxedFn::new(
+				move |vals: std::cell::Ref<[$crate::Slot]>|
+				 -> $crate::GResult<$crate::Slot> {
+					let mut temps = $crate::make_temps(&closure, &*vals)?;
+					drop(vals);
+					$crate::forwarder(&closure, &mut temps)
+				},
+				arg_limits
+			).with_param_types(param_types)
 		}
+	);
 
-		if let Some(max_args) = self.arg_limits.1 {
-			if vals.len() > max_args {
-				bail!("too many arguments: received {}, expected no more than {}",
-				      vals.len(), max_args)
-			}
+	($(move)? |$($param:ident: $param_ty:ty),*| -> $ret:ty $body:block) => (
+		$crate::rfn_boxed!(@wrap move |$($param: $param_ty),*| -> $ret { $body })
+			.with_param_names(&[$(stringify!($param)),*])
+	);
+
+	($(move)? |$($param:ident: $param_ty:ty),*| $body:expr) => (
+		$crate::rfn_boxed!(@wrap move |$($param: $param_ty),*| $body)
+			.with_param_names(&[$(stringify!($param)),*])
+	);
+
+	($closure:expr) => (
+		$crate::rfn_boxed!(@wrap $closure)
+	);
+}
+
+/**
+Data required to construct an `RFn` from a closure which captures its environment.
+
+This opaque struct is produced by the [`rfn_boxed!` macro](macro.rfn_boxed.html), and consumed
+by [`glsp::rfn_boxed`](fn.rfn_boxed.html) and [`glsp::bind_rfn_boxed`](fn.bind_rfn_boxed.html).
+
+It can also be converted from a `Box<dyn Fn(&[Val]) -> GResult<Val>>`, or any other boxed
+closure with the same signature. Closures of that type receive their arguments without any
+conversion, and they accept any number of arguments.
+*/
+
+#[derive(Clone)]
+pub struct BoxedFn {
+	wrapper: Rc<dyn Fn(Ref<[Slot]>) -> GResult<Slot>>,
+	pub(crate) arg_limits: (usize, Option<usize>),
+	pub(crate) param_types: Option<fn() -> Vec<&'static str>>,
+	pub(crate) param_names: Option<&'static [&'static str]>
+}
+
+impl BoxedFn {
+	#[doc(hidden)]
+	pub fn new<F>(wrapper: F, arg_limits: (usize, Option<usize>)) -> BoxedFn
+	where
+		F: Fn(Ref<[Slot]>) -> GResult<Slot> + 'static
+	{
+		BoxedFn {
+			wrapper: Rc::new(wrapper),
+			arg_limits,
+			param_types: None,
+			param_names: None
+		}
+	}
+
+	#[doc(hidden)]
+	pub fn with_param_types(self, param_types: fn() -> Vec<&'static str>) -> BoxedFn {
+		BoxedFn {
+			param_types: Some(param_types),
+			..self
 		}
+	}
 
+	#[doc(hidden)]
+	pub fn with_param_names(self, param_names: &'static [&'static str]) -> BoxedFn {
+		BoxedFn {
+			param_names: Some(param_names),
+			..self
+		}
+	}
+
+	pub(crate) fn call(&self, vals: Ref<[Slot]>) -> GResult<Slot> {
 		(self.wrapper)(vals)
 	}
+}
 
-	pub(crate) fn as_usize(&self) -> usize {
-		self.wrapper as usize
+impl<F> From<Box<F>> for BoxedFn
+where
+	F: ?Sized + Fn(&[Val]) -> GResult<Val> + 'static
+{
+	fn from(f: Box<F>) -> BoxedFn {
+		BoxedFn::new(move |vals: Ref<[Slot]>| {
+			let args = SmallVec::<[Val; 8]>::from_iter(vals.iter().map(|slot| slot.root()));
+			drop(vals);
+			Ok(Slot::from_val(&f(&args[..])?))
+		}, (0, None))
 	}
 }
 
@@ -1508,10 +1810,17 @@ pub trait MakeTemps {
 	type Temps: 'static;
 
 	fn make_temps(
-		slots: &[Slot]
+		slots: &[Slot],
+		positional: usize
 	) -> GResult<Self::Temps>;
 
+	//the limits on the number of positional arguments, i.e. those before the first keyword
 	fn arg_limits() -> (usize, Option<usize>);
+
+	fn takes_keywords() -> bool;
+
+	//the type name of each parameter, except for Lib parameters
+	fn param_types() -> Vec<&'static str>;
 }
 
 pub trait MakeArgs<'a>: Sized + MakeTemps {
@@ -1520,6 +1829,9 @@ pub trait MakeArgs<'a>: Sized + MakeTemps {
 	) -> GResult<Self>;
 }
 
+//we check the arg count here, rather than in WrappedFn::call, because Args::arg_limits() is
+//constant-folded within each monomorphized wrapper. this means that the optimizer can elide the
+//bounds checks when each argument is read from `vals`.
 #[doc(hidden)]
 #[inline(always)]
 pub fn make_temps<Args, F>(
@@ -1530,7 +1842,65 @@ where
 	Args: MakeTemps,
 	F: TupleCall<Args>
 {
-	Args::make_temps(vals)
+	let (min_args, max_args) = Args::arg_limits();
+	let takes_keywords = Args::takes_keywords();
+
+	let positional = if takes_keywords {
+		keywords_start(vals, min_args)
+	} else {
+		vals.len()
+	};
+
+	if positional < min_args || max_args.map_or(false, |max_args| positional > max_args) {
+		return Err(arg_count_error(positional, min_args, max_args, takes_keywords))
+	}
+
+	Args::make_temps(vals, positional)
+}
+
+//the keyword arguments begin at the first keyword which isn't bound to a required parameter
+fn keywords_start(vals: &[Slot], min_args: usize) -> usize {
+	for (i, val) in vals.iter().enumerate().skip(min_args) {
+		if let Slot::Sym(sym) = *val {
+			if is_keyword(sym) {
+				return i
+			}
+		}
+	}
+
+	vals.len()
+}
+
+fn is_keyword(sym: Sym) -> bool {
+	let name = sym.name();
+	name.len() > 1 && name.starts_with(':')
+}
+
+#[cold]
+#[inline(never)]
+fn arg_count_error(
+	arg_count: usize,
+	min_args: usize,
+	max_args: Option<usize>,
+	takes_keywords: bool
+) -> GError {
+	let mut args = MessageArgs::new().with("received", arg_count).with("min-args", min_args);
+	if let Some(max_args) = max_args {
+		args = args.with("max-args", max_args);
+	}
+
+	if takes_keywords {
+		args = args.with("positional", true);
+	}
+
+	GError::from_message(MessageId::ArgCount, args).with_arg_context(None)
+}
+
+//attaches the index of the argument which failed to convert, so that call_rfn can describe it
+#[cold]
+#[inline(never)]
+fn arg_error(err: GError, arg_index: usize) -> GError {
+	err.with_arg_context(Some(arg_index))
 }
 
 #[doc(hidden)]
@@ -1553,7 +1923,21 @@ where
 	Args: MakeTemps,
 	F: TupleCall<Args>
 {
-	Args::arg_limits()
+	let (min_args, max_args) = Args::arg_limits();
+	if Args::takes_keywords() {
+		(min_args, None)
+	} else {
+		(min_args, max_args)
+	}
+}
+
+#[doc(hidden)]
+pub fn wrapped_param_types<Args, F>(_f: F) -> fn() -> Vec<&'static str>
+where
+	Args: MakeTemps,
+	F: TupleCall<Args>
+{
+	Args::param_types
 }
 
 macro_rules! tuple_impls(
@@ -1587,10 +1971,13 @@ macro_rules! tuple_impls(
 				let mut opt_args = 0;
 				let mut seen_rest = false;
 				let mut seen_opt = false;
+				let mut seen_kw = false;
 				let mut seen_glsp_or_lib = false;
 
 				$(
-					assert!(!seen_rest, "&[T] argument is somewhere other than final position");
+					assert!(!seen_kw, "KwArgs argument is somewhere other than final position");
+					assert!(!seen_rest || $arg_type::ARG_TYPE == ArgType::Kw,
+					        "&[T] argument is somewhere other than final position");
 
 					match $arg_type::ARG_TYPE {
 						ArgType::Lib => {
@@ -1608,22 +1995,51 @@ macro_rules! tuple_impls(
 						ArgType::Rest => {
 							seen_rest = true;
 						}
+						ArgType::Kw => {
+							seen_kw = true;
+						}
 					}
 				)*
 
 				(normal_args, if seen_rest { None } else { Some(normal_args + opt_args) })
 			}
 
+			#[inline(always)]
+			fn takes_keywords() -> bool {
+				false $(|| $arg_type::ARG_TYPE == ArgType::Kw)*
+			}
+
+			#[allow(unused_mut)]
+			fn param_types() -> Vec<&'static str> {
+				let mut param_types = Vec::new();
+				$(
+					if $arg_type::ARG_TYPE != ArgType::Lib {
+						param_types.push(type_name::<$arg_type>());
+					}
+				)*
+				param_types
+			}
+
+			//a KwArgs parameter receives every argument, and the index of the first keyword.
+			//all other parameters only see the positional arguments.
 			#[allow(unused_assignments, unused_mut, unused_variables)]
 			#[inline(always)]
 			fn make_temps(
-				vals: &[Slot]
+				vals: &[Slot],
+				positional: usize
 			) -> GResult<($($arg_type::Temp,)*)> {
 				let mut i = 0;
 				Ok((
 					$(
 						{
-							let temp = $arg_type::make_temp(vals, i)?;
+							let temp = match $arg_type::ARG_TYPE {
+								ArgType::Kw => $arg_type::make_temp(vals, positional)?,
+								ArgType::Lib => $arg_type::make_temp(&vals[..positional], i)?,
+								_ => {
+									$arg_type::make_temp(&vals[..positional], i)
+										.map_err(|err| arg_error(err, i))?
+								}
+							};
 							if $arg_type::ARG_TYPE != ArgType::Lib {
 								i += 1;
 							}
@@ -1649,7 +2065,13 @@ macro_rules! tuple_impls(
 				Ok((
 					$(
 						{
-							let temp = $arg_type::make_arg(&mut temps.$i)?;
+							let temp = match $arg_type::ARG_TYPE {
+								ArgType::Kw | ArgType::Lib => $arg_type::make_arg(&mut temps.$i)?,
+								_ => {
+									$arg_type::make_arg(&mut temps.$i)
+										.map_err(|err| arg_error(err, i))?
+								}
+							};
 							if $arg_type::ARG_TYPE != ArgType::Lib {
 								i += 1;
 							}
@@ -1698,7 +2120,8 @@ pub enum ArgType {
 	Lib,
 	Normal,
 	Opt,
-	Rest
+	Rest,
+	Kw
 }
 
 #[doc(hidden)]
@@ -1720,6 +2143,7 @@ and also for several other types:
 - `Option<T>`, which acts as an optional parameter, storing `None` when an argument isn't
   provided.
 - `&[T]` or `&mut [T]`, which act as a "rest" parameter, capturing any number of arguments.
+- [`KwArgs`](struct.KwArgs.html), which captures any keyword arguments.
 - [`OrNil<T>`](struct.OrNil.html), which accepts either the specified type or `#n`.
 - Shared references to primitive types: [`&Arr`](struct.Arr.html), [`&Tab`](struct.Tab.html), etc.
 - String slices: `&str`, `&Path`, `&CStr`, `&OsStr`.
@@ -1862,7 +2286,7 @@ where
 	) -> GResult<SmallVec<[T; 8]>> {
 		GResult::<SmallVec<[T; 8]>>::from_iter((i .. vals.len()).map(|j| {
 			let mut slot = vals[j].clone();
-			T::make_arg(&mut slot)
+			T::make_arg(&mut slot).map_err(|err| arg_error(err, j))
 		}))
 	}
 }
@@ -1898,7 +2322,7 @@ where
 	) -> GResult<SmallVec<[T; 8]>> {
 		GResult::<SmallVec<[T; 8]>>::from_iter((i .. vals.len()).map(|j| {
 			let mut slot = vals[j].clone();
-			T::make_arg(&mut slot)
+			T::make_arg(&mut slot).map_err(|err| arg_error(err, j))
 		}))
 	}
 }
@@ -1916,6 +2340,135 @@ where
 	}
 }
 
+// KwArgs
+//-----------------------------------------------------------------------------
+
+/**
+An `RFn` parameter which captures keyword arguments.
+
+A keyword is a symbol whose name starts with a colon, like `:count`. When a function's final
+parameter is a `KwArgs`, the keyword arguments begin at the first keyword which isn't bound to
+a required parameter. It must be followed by a value, then any number of other keywords, each
+followed by its own value. No keyword may appear more than once.
+
+	fn spawn(kind: Sym, count: Option<i32>, kwargs: KwArgs) -> GResult<()> {
+		kwargs.ensure_recognized(&["hp", "team"])?;
+		let hp: i32 = kwargs.get("hp")?.unwrap_or(10);
+
+		//...
+	}
+
+	(spawn 'orc ':hp 20)
+	(spawn 'orc 3 ':team 'red ':hp 5)
+
+The [`rfn_params`](attr.rfn_params.html) attribute can bind keyword arguments directly to a
+function's parameters.
+*/
+
+pub struct KwArgs(Root<Tab>);
+
+impl KwArgs {
+	/**
+	Returns a table which maps each keyword's name, without its leading colon, to its argument.
+	*/
+	pub fn tab(&self) -> &Root<Tab> {
+		&self.0
+	}
+
+	/**
+	Returns the argument for a keyword, or `None` if that keyword wasn't passed.
+
+	The `keyword` should be written without its leading colon.
+	*/
+	pub fn get<T: FromVal>(&self, keyword: &str) -> GResult<Option<T>> {
+		match self.0.get_if_present::<_, Val>(glsp::sym(keyword)?)? {
+			Some(val) => match T::from_val(&val) {
+				Ok(t) => Ok(Some(t)),
+				Err(err) => {
+					Err(error!("invalid argument for the keyword :{}", keyword).with_source(err))
+				}
+			},
+			None => Ok(None)
+		}
+	}
+
+	/**
+	Returns an `Err` if any keyword was passed which isn't in the `keywords` list.
+
+	The `keywords` should be written without their leading colons. The error message lists
+	all of the recognized keywords.
+	*/
+	pub fn ensure_recognized(&self, keywords: &[&str]) -> GResult<()> {
+		for key in self.0.entries().keys() {
+			let name = match key {
+				Val::Sym(sym) => sym.name(),
+				_ => continue
+			};
+
+			if !keywords.contains(&&*name) {
+				if keywords.is_empty() {
+					bail!("unrecognized keyword :{}: no keywords are accepted", name)
+				}
+
+				let mut expected = String::new();
+				for (i, keyword) in keywords.iter().enumerate() {
+					if i > 0 {
+						expected.push_str(if i == keywords.len() - 1 { " or " } else { ", " });
+					}
+
+					expected.push(':');
+					expected.push_str(keyword);
+				}
+
+				bail!("unrecognized keyword :{}: expected {}", name, expected)
+			}
+		}
+
+		Ok(())
+	}
+}
+
+impl MakeTemp for KwArgs {
+	const ARG_TYPE: ArgType = ArgType::Kw;
+	type Temp = Root<Tab>;
+
+	#[inline(always)]
+	fn make_temp(
+		vals: &[Slot], 
+		i: usize
+	) -> GResult<Root<Tab>> {
+		let tab = glsp::tab();
+
+		for pair in vals[i..].chunks(2) {
+			let keyword = match pair[0] {
+				Slot::Sym(sym) if is_keyword(sym) => sym,
+				ref slot => {
+					bail!("expected a keyword, received {}: positional arguments can't \
+					       follow keyword arguments", slot.a_type_name())
+				}
+			};
+
+			ensure!(pair.len() == 2, "no argument was provided for the keyword {}", keyword);
+
+			let name = glsp::sym(&keyword.name()[1..])?;
+			ensure!(!tab.has(name)?, "the keyword {} was passed more than once", keyword);
+
+			tab.set(name, pair[1].root())?;
+		}
+
+		Ok(tab)
+	}
+}
+
+impl<'a> MakeArg<'a> for KwArgs {
+	#[inline(always)]
+	fn make_arg(
+		temp: &'a mut Root<Tab>
+	) -> GResult<KwArgs> {
+		Ok(KwArgs(temp.clone()))
+	}
+}
+
 // &Arr, &Str, etc.
 //-----------------------------------------------------------------------------
 
@@ -1941,8 +2494,10 @@ macro_rules! impl_pointee_make_arg {
 				) -> GResult<&'r $pointee> {
 					match *temp {
 						Slot::$variant(ref gc) => Ok(&**gc),
-						ref val => bail!("expected &{}, received {}", 
-						                 stringify!($pointee), (val.type_name()))
+						ref val => {
+							let expected = format!("&{}", stringify!($pointee));
+							return Err(type_mismatch(expected, val.type_name()))
+						}
 					}
 				}
 			}
@@ -1959,7 +2514,14 @@ impl_pointee_make_arg!(
 	(Obj, Obj),
 	(Class, Class),
 	(Coro, Coro),
-	(RData, RData)
+	(RData, RData),
+	(Rec, Rec),
+	(PArr, PArr),
+	(PTab, PTab),
+	(View, View),
+	(Vec2, Vec2),
+	(Vec3, Vec3),
+	(Mat3, Mat3)
 );
 
 // &str, &Path, &CStr, &OsStr
@@ -1978,7 +2540,7 @@ impl<'r> MakeTemp for &'r str {
 
 		match vals[i] {
 			Slot::Str(ref st) => write!(&mut vec, "{}", st).unwrap(),
-			ref val => bail!("expected a &str, received {}", val.a_type_name())
+			ref val => return Err(type_mismatch("a &str", val.a_type_name()))
 		}
 
 		Ok(vec)
@@ -2057,6 +2619,15 @@ for shared and mutable references to the struct's type.
 When a reference to a library struct is bound as an `RFn` parameter, that parameter doesn't 
 consume any input arguments. Instead, it will attempt to [borrow](trait.Lib.html#method.borrow) 
 the library struct from the active `Runtime`.
+
+A struct with curly braces may have type parameters. Each distinct instantiation of the struct,
+like `Assets<Texture>` and `Assets<Sound>`, is registered as a separate library.
+
+	lib! {
+		struct Assets<T: Asset> {
+			by_name: HashMap<String, T>
+		}
+	}
 */
 
 #[macro_export]
@@ -2071,6 +2642,18 @@ macro_rules! lib {
 		$crate::lib_impls! { $lib }
 	);
 
+	(
+		$(#[$struct_attr:meta])*
+		$struct_vis:vis struct $lib:ident < $($param:ident $(: $bound:path)?),+ $(,)? > { 
+			$($struct_token:tt)* 
+		}
+	) => (
+		$(#[$struct_attr])*
+		$struct_vis struct $lib < $($param $(: $bound)?),+ > { $($struct_token)* }
+
+		$crate::lib_impls! { $lib < $($param $(: $bound)?),+ > }
+	);
+
 	(
 		$(#[$struct_attr:meta])*
 		$struct_vis:vis struct $lib:ident;
@@ -2146,6 +2729,62 @@ macro_rules! lib_impls {
 			}
 		}
 	);
+
+	($lib:ident < $($param:ident $(: $bound:path)?),+ >) => (
+		//the unprefixed name alone would be ambiguous, so we name the concrete instantiation
+		impl<$($param: 'static $(+ $bound)?),+> $crate::Lib for $lib<$($param),+> {
+			fn type_name() -> &'static str { 
+				::std::any::type_name::<Self>()
+			}
+		}
+
+		impl<'r, $($param: 'static $(+ $bound)?),+> $crate::MakeTemp for &'r $lib<$($param),+> {
+			const ARG_TYPE: $crate::ArgType = $crate::ArgType::Lib;
+			type Temp = $crate::LibRef<$lib<$($param),+>>;
+
+			#[inline(always)]
+			fn make_temp(
+				_vals: &[$crate::Slot], 
+				_i: usize
+			) -> $crate::GResult<$crate::LibRef<$lib<$($param),+>>> {
+				$crate::try_lib::<$lib<$($param),+>>()
+			}
+		}
+
+		impl<'a: 'r, 'r, $($param: 'static $(+ $bound)?),+> $crate::MakeArg<'a> 
+		for &'r $lib<$($param),+> {
+			#[inline(always)]
+			fn make_arg(
+				temp: &'a mut $crate::LibRef<$lib<$($param),+>>
+			) -> $crate::GResult<&'r $lib<$($param),+>> {
+				Ok(&**temp)
+			}
+		}
+
+		impl<'r, $($param: 'static $(+ $bound)?),+> $crate::MakeTemp 
+		for &'r mut $lib<$($param),+> {
+			const ARG_TYPE: $crate::ArgType = $crate::ArgType::Lib;
+			type Temp = $crate::LibRefMut<$lib<$($param),+>>;
+
+			#[inline(always)]
+			fn make_temp(
+				_vals: &[$crate::Slot], 
+				_i: usize
+			) -> $crate::GResult<$crate::LibRefMut<$lib<$($param),+>>> {
+				$crate::try_lib_mut::<$lib<$($param),+>>()
+			}
+		}
+
+		impl<'a: 'r, 'r, $($param: 'static $(+ $bound)?),+> $crate::MakeArg<'a> 
+		for &'r mut $lib<$($param),+> {
+			#[inline(always)]
+			fn make_arg(
+				temp: &'a mut $crate::LibRefMut<$lib<$($param),+>>
+			) -> $crate::GResult<&'r mut $lib<$($param),+>> {
+				Ok(&mut **temp)
+			}
+		}
+	);
 }
 
 /**
@@ -2236,7 +2875,7 @@ macro_rules! rdata {
 #[doc(hidden)]
 #[macro_export]
 macro_rules! rdata_impls {
-	($rdata:ident;) => (rdata_impls!($rdata; meths { }););
+	($rdata:ident;) => ($crate::rdata_impls!($rdata; meths { }););
 
 	(
 		$rdata:ident;
@@ -2245,7 +2884,7 @@ macro_rules! rdata_impls {
 			$($($meth_kind:ident)? $meth_name:literal : $meth_expr:path,)+
 		}
 	) => (
-		rdata_impls!(
+		$crate::rdata_impls!(
 			$rdata;
 
 			meths {
@@ -2290,9 +2929,13 @@ macro_rules! rdata_impls {
 			) -> $crate::GResult<$crate::RRef<$rdata>> {
 				match vals[i] {
 					$crate::Slot::RData(ref rdata) => rdata.try_borrow(),
-					ref val => bail!("expected {}, received {}", 
-					                 <$rdata as $crate::RStore>::type_name(), 
-					                 val.a_type_name())
+					ref val => {
+						let args = $crate::MessageArgs::new()
+							.with("expected", <$rdata as $crate::RStore>::type_name())
+							.with("received", val.a_type_name());
+						return Err($crate::GError::from_message($crate::MessageId::TypeMismatch, 
+						                                        args))
+					}
 				}
 			}
 		}
@@ -2316,9 +2959,13 @@ macro_rules! rdata_impls {
 			) -> $crate::GResult<$crate::RRefMut<$rdata>> {
 				match vals[i] {
 					$crate::Slot::RData(ref rdata) => rdata.try_borrow_mut(),
-					ref val => bail!("expected {}, received {}", 
-					                 <$rdata as $crate::RStore>::type_name(), 
-					                 val.a_type_name())
+					ref val => {
+						let args = $crate::MessageArgs::new()
+							.with("expected", <$rdata as $crate::RStore>::type_name())
+							.with("received", val.a_type_name());
+						return Err($crate::GError::from_message($crate::MessageId::TypeMismatch, 
+						                                        args))
+					}
 				}
 			}
 		}
@@ -2444,6 +3091,10 @@ so tuples of arguments will need to be passed by reference:
 */
 
 pub trait ToCallArgs: to_call_args_private::Sealed {
+	//the number of arguments, if it's the same for every value of this type. used by Callback
+	#[doc(hidden)]
+	const ARG_COUNT: Option<usize>;
+
 	fn arg_count(&self) -> usize;
 	fn to_call_args<E: Extend<Slot>>(&self, dst: &mut E) -> GResult<()>;
 }
@@ -2459,6 +3110,8 @@ mod to_call_args_private {
 }
 
 impl<T: ToVal> ToCallArgs for [T] {
+	const ARG_COUNT: Option<usize> = None;
+
 	fn arg_count(&self) -> usize {
 		self.len()
 	}
@@ -2479,6 +3132,8 @@ impl<T: ToVal> ToCallArgs for [T] {
 }
 
 impl<T> ToCallArgs for [T; 0] {
+	const ARG_COUNT: Option<usize> = Some(0);
+
 	fn arg_count(&self) -> usize {
 		0
 	}
@@ -2493,6 +3148,8 @@ macro_rules! impl_to_call_args_array {
 		impl<T: ToVal> to_call_args_private::Sealed for [T; $len] { }
 
 		impl<T: ToVal> ToCallArgs for [T; $len] {
+			const ARG_COUNT: Option<usize> = Some($len);
+
 			fn arg_count(&self) -> usize {
 				$len
 			}
@@ -2528,6 +3185,8 @@ impl_to_call_args_array!(11);
 impl_to_call_args_array!(12);
 
 impl ToCallArgs for () {
+	const ARG_COUNT: Option<usize> = Some(0);
+
 	fn arg_count(&self) -> usize {
 		0
 	}
@@ -2545,6 +3204,8 @@ macro_rules! impl_to_call_args_tuple {
 		where 
 			$( $t: ToVal ),+ 
 		{
+			const ARG_COUNT: Option<usize> = Some($len);
+
 			fn arg_count(&self) -> usize {
 				$len
 			}
@@ -0,0 +1,462 @@
+use glsp::{GError, Parser, Val};
+use proc_macro::{Delimiter, Group, Literal, Span, TokenStream, TokenTree};
+use std::error::Error;
+use std::iter::FromIterator;
+use std::str::FromStr;
+
+/*
+
+the string-literal input to our macros, with any {rust-expr} splices extracted.
+
+each splice is replaced by the unquoted placeholder symbol ~__glsp_splice_N. a splice which is
+immediately preceded by .. is replaced by ~..__glsp_splice_N instead, so that it's splayed.
+
+we keep a table which maps each byte in the rewritten text back to a byte in the literal's
+source code. this lets us report parse errors using Literal::subspan, so that rustc underlines
+the offending token rather than the entire macro invocation.
+
+this file is also compiled into glsp-proc-macros2, using a #[path] attribute, because a
+proc-macro crate can't export anything other than macros.
+
+*/
+
+pub(crate) struct Input {
+	lit: Literal,
+	pub(crate) text: String,
+	pub(crate) splices: Vec<Splice>,
+	src_offsets: Vec<usize>
+}
+
+pub(crate) struct Splice {
+	pub(crate) name: String,
+	pub(crate) source: String,
+	pub(crate) expr: TokenStream,
+	pub(crate) span: Span
+}
+
+impl Input {
+	pub(crate) fn parse(input: TokenStream, macro_name: &str) -> Result<Input, TokenStream> {
+		let mut tokens: Vec<TokenTree> = input.into_iter().collect();
+
+		//macro_rules! may wrap a $lit:literal fragment in an invisible group
+		while tokens.len() == 1 {
+			match tokens[0] {
+				TokenTree::Group(ref group) if group.delimiter() == Delimiter::None => {
+					tokens = group.stream().into_iter().collect();
+				}
+				_ => break
+			}
+		}
+
+		let lit = match tokens.as_slice() {
+			[TokenTree::Literal(lit)] => lit.clone(),
+			_ => {
+				let span = tokens.first().map(|tt| tt.span()).unwrap_or_else(Span::call_site);
+				let msg = format!("the input to {}!() must be a single string literal", macro_name);
+				return Err(compile_error(span, &msg))
+			}
+		};
+
+		let (value, value_offsets) = match decode_str_literal(&lit.to_string()) {
+			Some(decoded) => decoded,
+			None => {
+				let msg = format!("the input to {}!() must be a single string literal", macro_name);
+				return Err(compile_error(lit.span(), &msg))
+			}
+		};
+
+		let mut input = Input {
+			lit,
+			text: String::with_capacity(value.len()),
+			splices: Vec::new(),
+			src_offsets: Vec::with_capacity(value.len() + 1)
+		};
+
+		let mut prev_end = 0;
+		for (start, end) in find_splices(&value).map_err(|pos| {
+			let span = input.subspan(value_offsets[pos], value_offsets[pos + 1]);
+			compile_error(span, "unterminated {} splice")
+		})? {
+			let span = input.subspan(value_offsets[start], value_offsets[end]);
+			let source = value[start + 1 .. end - 1].trim().to_string();
+
+			let expr = match TokenStream::from_str(&source) {
+				Ok(expr) if !expr.is_empty() => respan(expr, span),
+				Ok(_) => return Err(compile_error(span, "empty {} splice")),
+				Err(_) => return Err(compile_error(span, "invalid Rust syntax in {} splice"))
+			};
+
+			//splay ..{rust-expr} by rewriting it as ~..__glsp_splice_N
+			let splays = value[prev_end .. start].ends_with("..");
+			let copy_end = if splays { start - 2 } else { start };
+
+			input.push_text(&value[prev_end .. copy_end], &value_offsets[prev_end .. copy_end]);
+
+			let name = format!("__glsp_splice_{}", input.splices.len());
+			let placeholder = format!("~{}{}", if splays { ".." } else { "" }, name);
+			let placeholder_offsets = vec![value_offsets[copy_end]; placeholder.len()];
+			input.push_text(&placeholder, &placeholder_offsets);
+
+			input.splices.push(Splice { name, source, expr, span });
+			prev_end = end;
+		}
+
+		input.push_text(&value[prev_end..], &value_offsets[prev_end .. value.len()]);
+		input.src_offsets.push(value_offsets[value.len()]);
+
+		Ok(input)
+	}
+
+	fn push_text(&mut self, text: &str, offsets: &[usize]) {
+		self.text.push_str(text);
+		self.src_offsets.extend_from_slice(offsets);
+	}
+
+	fn subspan(&self, start: usize, end: usize) -> Span {
+		self.lit.subspan(start .. end.max(start + 1)).unwrap_or_else(|| self.lit.span())
+	}
+
+	//the span of the character which begins at the given byte offset into self.text
+	fn span_at(&self, offset: usize) -> Span {
+		let ch_len = self.text[offset..].chars().next().map(|ch| ch.len_utf8()).unwrap_or(0);
+		let start = self.src_offsets[offset];
+		let end = if ch_len > 0 { self.src_offsets[offset + ch_len] } else { start + 1 };
+
+		self.subspan(start, end)
+	}
+
+	pub(crate) fn parse_forms(&self, filename: Option<&str>) -> Result<Vec<Val>, TokenStream> {
+		parse_tokenwise(&self.text, filename).map_err(|(offset, err)| {
+			compile_error(self.span_at(offset), &describe(&err))
+		})
+	}
+
+	//emits `let __glsp_splice_N = rust_expr;` for each splice, in order
+	pub(crate) fn splice_bindings(&self) -> TokenStream {
+		let mut bindings = TokenStream::new();
+		for splice in &self.splices {
+			let expr = TokenTree::Group(Group::new(Delimiter::None, splice.expr.clone()));
+
+			bindings.extend(TokenStream::from_str(&format!("let {} = ", splice.name)).unwrap());
+			bindings.extend(TokenStream::from(expr));
+			bindings.extend(TokenStream::from_str(";").unwrap());
+		}
+
+		bindings
+	}
+
+	pub(crate) fn span(&self) -> Span {
+		self.lit.span()
+	}
+
+	pub(crate) fn error(&self, msg: &str) -> TokenStream {
+		compile_error(self.span(), msg)
+	}
+}
+
+//parses the text one token at a time, so that any syntax error can be attributed to the byte
+//offset of the token which caused it. "unterminated" errors are attributed to the start of the
+//toplevel form which is unterminated.
+pub(crate) fn parse_tokenwise(
+	src: &str,
+	filename: Option<&str>
+) -> Result<Vec<Val>, (usize, GError)> {
+	let mut parser = Parser::new(filename.map(|path| glsp::filename(path)));
+	let mut forms = Vec::new();
+
+	let mut text = src;
+	let mut form_start = None;
+	let mut inside_block_comment = false;
+
+	while text.len() > 0 {
+		let offset = src.len() - text.len();
+		let prev_text = text;
+
+		match parser.parse(&mut text) {
+			Ok(Some(form)) => {
+				forms.push(form);
+				form_start = None;
+			}
+			Ok(None) => {
+				let tok = &prev_text[.. prev_text.len() - text.len()];
+				if inside_block_comment {
+					inside_block_comment = false;
+				} else if tok == "#|" {
+					inside_block_comment = true;
+				} else if form_start.is_none() && !tok.starts_with(char::is_whitespace) &&
+				          !tok.starts_with(';') {
+					form_start = Some(offset);
+				}
+			}
+			Err(err) => return Err((offset, err))
+		}
+	}
+
+	if let Err(err) = parser.ensure_finished() {
+		return Err((form_start.unwrap_or(src.len()), err))
+	}
+
+	Ok(forms)
+}
+
+pub(crate) fn compile_error(span: Span, msg: &str) -> TokenStream {
+	let tokens = TokenStream::from_str(&format!("::core::compile_error!({:?})", msg)).unwrap();
+	respan(tokens, span)
+}
+
+fn respan(tokens: TokenStream, span: Span) -> TokenStream {
+	TokenStream::from_iter(tokens.into_iter().map(|mut tt| {
+		if let TokenTree::Group(ref group) = tt {
+			tt = TokenTree::Group(Group::new(group.delimiter(), respan(group.stream(), span)));
+		}
+
+		tt.set_span(span);
+		tt
+	}))
+}
+
+//glsp's parse errors are usually a short message with a chained source, like
+//"lexing error" caused by "malformed unicode char escape"
+pub(crate) fn describe(err: &GError) -> String {
+	let mut description = format!("invalid glsp syntax: {}", err.val());
+
+	let mut source = err.source();
+	while let Some(source_err) = source {
+		match source_err.downcast_ref::<GError>() {
+			Some(gerror) => description.push_str(&format!(": {}", gerror.val())),
+			None => description.push_str(&format!(": {}", source_err))
+		}
+		source = source_err.source();
+	}
+
+	description
+}
+
+//decodes the source code of a rust string literal. returns the literal's value, and the offset
+//into the source code of each byte in that value, followed by the offset of the closing quote.
+//
+//the sanctioned way to parse rust tokens is the `syn` crate, but it's a large dependency, and
+//we'd need to track source offsets ourselves regardless. rust's string literals have a
+//straightforward syntax, so we just roll up our sleeves and parse them by hand.
+fn decode_str_literal(src: &str) -> Option<(String, Vec<usize>)> {
+	let mut value = String::new();
+	let mut offsets = Vec::new();
+
+	if src.starts_with('r') {
+		let hashes = src[1..].bytes().take_while(|&b| b == b'#').count();
+		let body_start = 2 + hashes;
+		let closing = format!("\"{}", "#".repeat(hashes));
+
+		if src.len() < body_start + closing.len() || src.as_bytes()[1 + hashes] != b'"' ||
+		   !src.ends_with(&closing) {
+			return None
+		}
+
+		let body_end = src.len() - closing.len();
+		value.push_str(&src[body_start .. body_end]);
+		offsets.extend(body_start ..= body_end);
+
+		return Some((value, offsets))
+	}
+
+	if src.len() < 2 || !src.starts_with('"') || !src.ends_with('"') {
+		return None
+	}
+
+	let body = &src[1 .. src.len() - 1];
+	let mut chars = body.char_indices().peekable();
+	while let Some((i, ch)) = chars.next() {
+		let decoded = if ch == '\\' {
+			match chars.next()?.1 {
+				'n' => '\n',
+				'r' => '\r',
+				't' => '\t',
+				'\\' => '\\',
+				'0' => '\0',
+				'\'' => '\'',
+				'"' => '"',
+				'x' => {
+					let hi = chars.next()?.1.to_digit(8)?;
+					let lo = chars.next()?.1.to_digit(16)?;
+					char::from((hi * 16 + lo) as u8)
+				}
+				'u' => {
+					if chars.next()?.1 != '{' {
+						return None
+					}
+
+					let mut code = 0;
+					loop {
+						match chars.next()?.1 {
+							'}' => break,
+							'_' => (),
+							digit => code = code * 16 + digit.to_digit(16)?
+						}
+					}
+
+					std::char::from_u32(code)?
+				}
+				'\n' | '\r' => {
+					while let Some(&(_, ch)) = chars.peek() {
+						if !ch.is_whitespace() {
+							break
+						}
+						chars.next();
+					}
+					continue
+				}
+				_ => return None
+			}
+		} else {
+			ch
+		};
+
+		value.push(decoded);
+		offsets.extend(std::iter::repeat(i + 1).take(decoded.len_utf8()));
+	}
+
+	offsets.push(src.len() - 1);
+	Some((value, offsets))
+}
+
+//finds each {rust-expr} splice in the given glsp source text, returning the byte range of each
+//splice, including its braces. returns Err(offset) if a splice is unterminated.
+//
+//this is a simplified version of glsp's lexer. braces within a str literal, or within a char
+//literal like \{, belong to glsp. an unescaped { within a str literal begins an interpolation;
+//its matching } returns to the str.
+fn find_splices(text: &str) -> Result<Vec<(usize, usize)>, usize> {
+	enum State {
+		Code,
+		Str,
+		RawStr(usize),
+		BlockComment(usize)
+	}
+
+	let bytes = text.as_bytes();
+	let mut splices = Vec::new();
+	let mut state = State::Code;
+	let mut interpolations = 0;
+	let mut i = 0;
+
+	while i < bytes.len() {
+		if !text.is_char_boundary(i) {
+			i += 1;
+			continue
+		}
+
+		let rest = &text[i..];
+
+		match state {
+			State::Code => {
+				match bytes[i] {
+					b'{' => {
+						let end = i + rust_expr_len(rest).ok_or(i)?;
+						splices.push((i, end));
+						i = end;
+						continue
+					}
+					b'}' if interpolations > 0 => {
+						interpolations -= 1;
+						state = State::Str;
+					}
+					b';' => {
+						i += rest.find('\n').unwrap_or(rest.len());
+					}
+					b'#' if rest.starts_with("#|") => {
+						state = State::BlockComment(0);
+						i += 1;
+					}
+					b'\\' => {
+						if rest.starts_with("\\u{") {
+							i += rest.find('}').unwrap_or(rest.len() - 1);
+						} else {
+							i += rest[1..].chars().next().map(|ch| ch.len_utf8()).unwrap_or(0);
+						}
+					}
+					b'"' => state = State::Str,
+					b'r' if i == 0 || is_delimiter(bytes[i - 1]) => {
+						let hashes = rest[1..].bytes().take_while(|&b| b == b'#').count();
+						if bytes.get(i + 1 + hashes) == Some(&b'"') {
+							state = State::RawStr(hashes);
+							i += 1 + hashes;
+						}
+					}
+					_ => ()
+				}
+			}
+			State::Str => {
+				if rest.starts_with("\\") || rest.starts_with("{{") || rest.starts_with("}}") {
+					i += 1;
+				} else if bytes[i] == b'{' {
+					interpolations += 1;
+					state = State::Code;
+				} else if bytes[i] == b'"' {
+					state = State::Code;
+				}
+			}
+			State::RawStr(hashes) => {
+				let closes = rest[1..].bytes().take_while(|&b| b == b'#').count() >= hashes;
+				if bytes[i] == b'"' && closes {
+					state = State::Code;
+					i += hashes;
+				}
+			}
+			State::BlockComment(nesting) => {
+				if rest.starts_with("#|") {
+					state = State::BlockComment(nesting + 1);
+					i += 1;
+				} else if rest.starts_with("|#") {
+					state = match nesting {
+						0 => State::Code,
+						_ => State::BlockComment(nesting - 1)
+					};
+					i += 1;
+				}
+			}
+		}
+
+		i += 1;
+	}
+
+	Ok(splices)
+}
+
+fn is_delimiter(b: u8) -> bool {
+	b.is_ascii_whitespace() || b"()[]{}'`~@".contains(&b)
+}
+
+//the length of a braced rust expression, including its braces. skips over braces which appear
+//within rust str or char literals, and returns None if the braces are unbalanced.
+fn rust_expr_len(text: &str) -> Option<usize> {
+	let bytes = text.as_bytes();
+	let mut depth = 0;
+	let mut i = 0;
+
+	while i < bytes.len() {
+		match bytes[i] {
+			b'{' => depth += 1,
+			b'}' => {
+				depth -= 1;
+				if depth == 0 {
+					return Some(i + 1)
+				}
+			}
+			b'"' => {
+				i += 1;
+				while i < bytes.len() && bytes[i] != b'"' {
+					i += if bytes[i] == b'\\' { 2 } else { 1 };
+				}
+			}
+			b'\'' if bytes.get(i + 2) == Some(&b'\'') => i += 2,
+			b'\'' if bytes.get(i + 1) == Some(&b'\\') => {
+				i += text[i + 1 ..].find('\'').map(|len| len + 1)?;
+			}
+			_ => ()
+		}
+
+		i += 1;
+	}
+
+	None
+}
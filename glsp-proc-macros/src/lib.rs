@@ -1,11 +1,17 @@
 #![forbid(unsafe_code)]
 
+#![feature(proc_macro_span, proc_macro_tracked_path)]
+
 use glsp::{DequeAccess, DequeOps, Engine, stock_syms::*, SymKind, Val};
-use proc_macro::{TokenStream, TokenTree, TokenTree::Literal};
-use std::{char};
+use proc_macro::{Delimiter, Group, Spacing, TokenStream, TokenTree, tracked};
+use std::{char, env, fs, iter};
 use std::collections::{HashMap, hash_map::Entry};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::fmt::Write;
+use self::input::{compile_error, describe, Input, parse_tokenwise};
+
+mod input;
 
 #[doc(hidden)]
 #[proc_macro]
@@ -38,21 +44,32 @@ The result has a generic return type - it can be any type which implements
 In the unlikely event that conversion from [`Val`](enum.Val.html) to the destination type fails,
 a panic will occur.
 
-Unquoting (`~`) is not supported. If you need to interpolate local variables, use 
-[`backquote!()`](macro.backquote.html) instead.
+Unquoting (`~`) and `{rust_expr}` splices are not supported. If you need to interpolate Rust 
+values, use [`backquote!()`](macro.backquote.html) instead.
 */
 
 #[proc_macro]
 pub fn quote(input: TokenStream) -> TokenStream {
 	//wrangle input
-	let text = parse_single_str_literal(input);
+	let input = match Input::parse(input, "quote") {
+		Ok(input) => input,
+		Err(err) => return err
+	};
+
+	if let Some(splice) = input.splices.first() {
+		return compile_error(splice.span, "quote!() doesn't support {} splices. use \
+		                                   backquote!() instead")
+	}
 
 	//spin up a glsp Engine
 	let engine = Engine::new();
 	engine.run(|| {
 
 		//parse the input string
-		let val = glsp::parse_1(&text, None).expect("quote!() received invalid glsp syntax");
+		let val = match parse_1(&input, "quote") {
+			Ok(val) => val,
+			Err(err) => return Ok(err)
+		};
 
 		//emit rust code to create the input form.
 		let mut builder = String::new();
@@ -103,6 +120,22 @@ interpolated into the output.
 Local variables can be simultaneously unquoted and splayed with `~..`. In that case, the borrowed
 form of the local variable must belong to a type which implements [`Splay`](trait.Splay.html).
 
+Arbitrary Rust expressions can be spliced into the form using curly braces, `{rust_expr}`. The
+expression's result is converted using the [`ToVal` trait](trait.ToVal.html), just like an 
+unquoted local variable. `..{rust_expr}` splays the result. Each expression is evaluated once, 
+in order, before the form is constructed. `{x}` moves or copies `x`, so use `{&x}` to borrow a 
+local variable which you need to use again later.
+
+	let enemy = Enemy { name: "goblin".to_string(), hp: 10 };
+	let extra_args = vec![30, 40];
+	let form: Val = backquote!(r#"
+	  (spawn {&enemy.name} {enemy.hp * 2} ..{&extra_args})
+	"#);
+	println!("{}", form); //prints (spawn "goblin" 20 30 40)
+
+Curly braces within a str literal, such as `"hp: {hp}"`, belong to GameLisp's own string 
+interpolation syntax, so they're not treated as splices.
+
 If any of the [`ToVal`](trait.ToVal.html) conversions fail, the generated code will panic. For a 
 non-panicking version of this macro, use [`try_backquote!()`](macro.try_backquote.html).
 
@@ -135,14 +168,20 @@ Returns a generic [`GResult<T>`](type.GResult.html), where `T` implements
 #[proc_macro]
 pub fn try_backquote(input: TokenStream) -> TokenStream {
 	//wrangle input
-	let text = parse_single_str_literal(input);
+	let input = match Input::parse(input, "backquote") {
+		Ok(input) => input,
+		Err(err) => return err
+	};
 
 	//spin up a glsp Engine
 	let engine = Engine::new();
 	engine.run(|| {
 
 		//parse the input string
-		let val = glsp::parse_1(&text, None).expect("backquote!() received invalid glsp syntax");
+		let val = match parse_1(&input, "backquote") {
+			Ok(val) => val,
+			Err(err) => return Ok(err)
+		};
 
 		//recursively emit rust code to create the input form. this is easier than it sounds:
 		//  - #n, bools, ints, flos and chars are straightforward (e.g. #n emits "Val::Nil")
@@ -163,12 +202,25 @@ pub fn try_backquote(input: TokenStream) -> TokenStream {
 		//in order to resolve to a single GResult<Val>, we nest everything within curly braces,
 		//define a closure which explicitly returns GResult<Val>, and mark any to_val() 
 		//invocations with ?
+		//
+		//{rust-expr} splices have already been replaced with ~__glsp_splice_N, so they're
+		//emitted like any other unquoted local variable. we bind each of those variables
+		//before the closure, so that any ? operator within a splice refers to the caller's fn.
 
 		let mut builder = String::new();
 		let mut gensyms = HashMap::new();
 		let mut gensym_counter = 0_usize;
 		emit_val_for_backquote(&mut builder, &mut gensyms, &mut gensym_counter, &val, 0, false);
 
+		//a splice within a nested backquote would be emitted as a symbol, rather than a value
+		for splice in &input.splices {
+			if builder.contains(&format!("{:?}", splice.name)) {
+				let msg = format!("the splice {{{}}} is nested within an inner backquote, so it \
+				                   can't be interpolated", splice.source);
+				return Ok(compile_error(splice.span, &msg))
+			}
+		}
+
 		let mut gen_vars = String::new();
 		for (sym_name, var_name) in gensyms {
 			write!(&mut gen_vars, "let {} = glsp::gensym_with_tag({:?}).unwrap();",
@@ -176,20 +228,21 @@ pub fn try_backquote(input: TokenStream) -> TokenStream {
 		}
 
 		let output = format!(r#"
-			{{
-				let secret_backquote_closure = || -> ::glsp::GResult<::glsp::Val> {{
-					{}
-					Ok({})
-				}};
-
-				match secret_backquote_closure() {{
-					Ok(val) => ::glsp::FromVal::from_val(&val),
-					Err(err) => ::glsp::GResult::Err(err)
-				}}
+			let secret_backquote_closure = || -> ::glsp::GResult<::glsp::Val> {{
+				{}
+				Ok({})
+			}};
+
+			match secret_backquote_closure() {{
+				Ok(val) => ::glsp::FromVal::from_val(&val),
+				Err(err) => ::glsp::GResult::Err(err)
 			}}
 		"#, gen_vars, builder);
 
-		Ok(TokenStream::from_str(&output).unwrap())
+		let mut block = input.splice_bindings();
+		block.extend(TokenStream::from_str(&output).unwrap());
+
+		Ok(TokenTree::Group(Group::new(Delimiter::Brace, block)).into())
 	}).unwrap()
 }
 
@@ -247,7 +300,8 @@ fn emit_val_for_quote<T: Write>(dst: &mut T, val: &Val) {
 			}
 		}
 		Val::GIter(_) | Val::RFn(_) | Val::Obj(_) | Val::Class(_) | 
-		Val::GFn(_) | Val::Coro(_) | Val::RData(_) => {
+		Val::GFn(_) | Val::Coro(_) | Val::RData(_) | Val::Rec(_) | Val::PArr(_) | Val::PTab(_) |
+		Val::View(_) | Val::Vec2(_) | Val::Vec3(_) | Val::Mat3(_) => {
 			unreachable!()
 		}
 	}
@@ -355,6 +409,16 @@ fn emit_val_for_backquote<T: Write>(
 	}
 }
 
+fn parse_1(input: &Input, macro_name: &str) -> Result<Val, TokenStream> {
+	let mut forms = input.parse_forms(None)?;
+	if forms.len() == 1 {
+		Ok(forms.pop().unwrap())
+	} else {
+		Err(input.error(&format!("the input to {}!() must contain exactly one form, but it \
+		                          contains {}", macro_name, forms.len())))
+	}
+}
+
 fn is_valid_identifier(st: &str) -> bool {
 	if st.len() == 0 {
 		false
@@ -370,105 +434,548 @@ fn is_valid_identifier(st: &str) -> bool {
 	}
 }
 
-/*
-a TokenStream represents the output from rust's lexer. it provides no parsing support - just 
-tokenized utf-8 text.
+/**
+Declares optional, rest and keyword parameters for a function which will be passed to
+[`rfn!`](macro.rfn.html).
+
+Each parameter can be given one of the following attributes:
+
+- `#[opt] name: T` is an optional parameter. When its argument is missing, it's set to
+  `T::default()`.
+- `#[opt(expr)] name: T` is an optional parameter which defaults to `expr`.
+- `#[rest] name: &[T]` is a rest parameter. This is equivalent to leaving the attribute out.
+- `#[kw] name: T` and `#[kw(expr)] name: T` are keyword parameters, with the same defaults as
+  `#[opt]`. The keyword is the parameter's name, with each `_` replaced by `-`. `T` must 
+  implement [`FromVal`](trait.FromVal.html).
+- `#[kw] name: Option<T>` is a keyword parameter which is set to `None` when its keyword is
+  missing.
+
+For example:
+
+	#[rfn_params]
+	fn spawn(
+		kind: Sym,
+		#[opt(1)] count: i32,
+		#[rest] tags: &[Sym],
+		#[kw(100)] max_hp: i32,
+		#[kw] team: Option<Sym>
+	) -> GResult<()> {
+		//...
+	}
 
-the sanctioned way to parse rust tokens is the `syn` crate, but this is a large dependency with
-multiple transitive dependencies - strongly prefer to avoid it, if possible.
+	glsp::bind_rfn("spawn", rfn!(spawn))?;
 
-as it so happens, quote!() and backquote!() only care about parsing rust's string literals, which 
-have a straightforward syntax. to avoid the `syn` dependency, we just roll up our sleeves and
-implement a string-literal parser ourselves.
+	(spawn 'orc)
+	(spawn 'orc 3 'armored 'angry ':max-hp 50 ':team 'red)
+
+Optional parameters are rewritten into `Option<T>` parameters, so they must appear after any
+required parameters and before any rest parameter. Keyword parameters can appear anywhere. 
+They're collected into a single [`KwArgs`](struct.KwArgs.html) parameter, which is appended 
+to the end of the parameter list.
+
+If the caller passes a keyword which doesn't match any keyword parameter, the function returns
+an error which lists the recognized keywords. This means that a function with keyword 
+parameters must return a [`GResult`](type.GResult.html).
 */
 
-fn parse_single_str_literal(input: TokenStream) -> String {
-	//get the literal's text
-	let tokens: Vec<TokenTree> = input.into_iter().collect();
-	assert!(tokens.len() == 1, "expected a single string literal");
+#[proc_macro_attribute]
+pub fn rfn_params(attr: TokenStream, item: TokenStream) -> TokenStream {
+	assert!(attr.is_empty(), "the #[rfn_params] attribute expects no arguments");
 
-	let text = match tokens[..] {
-		[Literal(ref lit)] => lit.to_string(),
-		_ => panic!("expected a single string literal")
-	};
-	let bytes = text.as_bytes();
+	let tokens: Vec<TokenTree> = item.into_iter().collect();
 
-	//inspecting the first two characters is sufficient to differentiate strings and raw strings 
-	//from other literals
-	if text.starts_with("r\"") || text.starts_with("r#") {
+	//locate the parameter list and the function body, skipping over any generic parameters
+	let fn_index = tokens.iter().position(|token| {
+		match *token {
+			TokenTree::Ident(ref ident) => ident.to_string() == "fn",
+			_ => false
+		}
+	}).expect("#[rfn_params] must be applied to a fn");
+
+	let mut params_index = fn_index + 2;
+	if is_punct(tokens.get(params_index), '<') {
+		let mut depth = 0_usize;
+		loop {
+			match tokens.get(params_index) {
+				Some(TokenTree::Punct(ref punct)) if punct.as_char() == '<' => depth += 1,
+				Some(TokenTree::Punct(ref punct)) if punct.as_char() == '>' => depth -= 1,
+				None => panic!("#[rfn_params] received a malformed fn"),
+				_ => ()
+			}
 
-		//a raw string literal
-		let mut hashes = 0_usize;
-		while bytes[hashes + 1] == b'#' {
-			hashes += 1;
+			params_index += 1;
+			if depth == 0 {
+				break
+			}
 		}
-		assert!(bytes[hashes + 1] == b'"' && bytes[bytes.len() - (hashes + 1)] == b'"');
-		assert!(bytes[bytes.len() - hashes..].iter().all(|b| *b == b'#'));
-
-		text[hashes + 2 .. bytes.len() - (hashes + 1)].to_string()
-
-	} else if bytes[0] == b'"' {
-
-		//a non-raw string literal
-		assert!(bytes.len() >= 2 && bytes[0] == b'"' && bytes[bytes.len() - 1] == b'"');
-		
-		let mut dst = String::new();
-		let mut i = 1;
-		while i < bytes.len() - 1 {
-			if bytes[i] == b'\\' {
-				match bytes[i+1] {
-					b'n' | b'r' | b't' | b'\\' | b'0' | b'\'' | b'"' => {
-						dst.push(match bytes[i+1] {
-							b'n' => '\n',
-							b'r' => '\r',
-							b't' => '\t',
-							b'\\' => '\\',
-							b'0' => '\0',
-							b'\'' => '\'',
-							b'"' => '"',
-							_ => unreachable!()
-						});
-						i += 2;
-					}
-					b'x' => {
-						dst.push(u8::from_str_radix(&text[i+2..i+4], 16).unwrap() as char);
-						i += 4;
-					}
-					b'u' => {
-						assert!(bytes[i+2] == b'{');
-						let mut j = i + 3;
-						while bytes[j] != b'}' {
-							j += 1;
-						}
+	}
 
-						let num = u32::from_str_radix(&text[i+3..j], 16).unwrap();
-						dst.push(char::from_u32(num).unwrap());
+	let params_group = match tokens.get(params_index) {
+		Some(TokenTree::Group(ref group)) if group.delimiter() == Delimiter::Parenthesis => {
+			group.clone()
+		}
+		_ => panic!("#[rfn_params] received a malformed fn")
+	};
 
-						i = j + 1;
-					}
-					b'\r' | b'\n' => {
-						let mut j = i + 1;
-						while j < bytes.len() {
-							let ch = text[j..].chars().next().unwrap();
-							if !ch.is_whitespace() {
-								break
-							}
-							j += ch.len_utf8();
+	let body_group = match tokens.last() {
+		Some(TokenTree::Group(ref group)) if group.delimiter() == Delimiter::Brace => {
+			group.clone()
+		}
+		_ => panic!("#[rfn_params] must be applied to a fn with a body")
+	};
+
+	//rewrite each parameter. optional and keyword parameters are unpacked by a prologue which
+	//we insert at the start of the function body.
+	let mut params = TokenStream::new();
+	let mut prologue = TokenStream::new();
+	let mut keywords = Vec::<String>::new();
+
+	for (i, param) in split_params(params_group.stream()).into_iter().enumerate() {
+		let (kind, param) = strip_param_attr(param);
+
+		let colon = (0 .. param.len()).find(|&j| is_single_colon(&param, j));
+		let (pattern, ty) = match colon {
+			Some(colon) => (&param[..colon], &param[colon + 1 ..]),
+			None => {
+				assert!(kind.is_none(), "#[rfn_params] received an attribute on a receiver");
+				params.extend(param.iter().cloned());
+				params.extend(tokens_from_str(","));
+				continue
+			}
+		};
+
+		match kind {
+			None | Some(ParamAttr::Rest) => {
+				params.extend(param.iter().cloned());
+				params.extend(tokens_from_str(","));
+			}
+			Some(ParamAttr::Opt(default)) => {
+				let temp_name = format!("__glsp_opt_{}", i);
+
+				params.extend(tokens_from_str(&format!("{}: Option<", temp_name)));
+				params.extend(ty.iter().cloned());
+				params.extend(tokens_from_str(">,"));
+
+				emit_unpack(&mut prologue, pattern, ty, tokens_from_str(&temp_name), default);
+			}
+			Some(ParamAttr::Kw(default)) => {
+				let mut names = pattern.iter().filter_map(|token| {
+					match *token {
+						TokenTree::Ident(ref ident) => {
+							let name = ident.to_string();
+							if name == "mut" || name == "ref" { None } else { Some(name) }
 						}
-						i = j;
+						_ => None
+					}
+				});
+
+				let name = match (names.next(), names.next()) {
+					(Some(name), None) => name.trim_start_matches("r#").replace('_', "-"),
+					_ => panic!("#[rfn_params] requires each #[kw] parameter to have a name")
+				};
+
+				//an `Option<T>` parameter without a default is set to `None` when its keyword
+				//is missing
+				let is_option = match ty.get(0) {
+					Some(TokenTree::Ident(ref ident)) => {
+						ident.to_string() == "Option" && is_punct(ty.get(1), '<') && 
+						is_punct(ty.last(), '>') && default.is_none()
 					}
-					_ => panic!()
+					_ => false
+				};
+
+				let mut getter = tokens_from_str("__glsp_kwargs.get::<");
+				if is_option {
+					getter.extend(ty[2 .. ty.len() - 1].iter().cloned());
+				} else {
+					getter.extend(ty.iter().cloned());
 				}
-			} else {
-				let ch = text[i..].chars().next().unwrap();
-				dst.push(ch);
-				i += ch.len_utf8();
+				getter.extend(tokens_from_str(&format!(">({:?})?", name)));
+
+				if is_option {
+					prologue.extend(tokens_from_str("let"));
+					prologue.extend(pattern.iter().cloned());
+					prologue.extend(tokens_from_str(":"));
+					prologue.extend(ty.iter().cloned());
+					prologue.extend(tokens_from_str("="));
+					prologue.extend(getter);
+					prologue.extend(tokens_from_str(";"));
+				} else {
+					emit_unpack(&mut prologue, pattern, ty, getter, default);
+				}
+
+				keywords.push(name);
+			}
+		}
+	}
+
+	if !keywords.is_empty() {
+		params.extend(tokens_from_str("__glsp_kwargs: ::glsp::KwArgs"));
+
+		let check = format!("__glsp_kwargs.ensure_recognized(&{:?})?;", keywords);
+		let mut checked_prologue = tokens_from_str(&check);
+		checked_prologue.extend(prologue);
+		prologue = checked_prologue;
+	}
+
+	let mut new_params = Group::new(Delimiter::Parenthesis, params);
+	new_params.set_span(params_group.span());
+
+	prologue.extend(body_group.stream());
+	let mut new_body = Group::new(Delimiter::Brace, prologue);
+	new_body.set_span(body_group.span());
+
+	let mut output = TokenStream::new();
+	output.extend(tokens[..params_index].iter().cloned());
+	output.extend(iter::once(TokenTree::Group(new_params)));
+	output.extend(tokens[params_index + 1 .. tokens.len() - 1].iter().cloned());
+	output.extend(iter::once(TokenTree::Group(new_body)));
+	output
+}
+
+enum ParamAttr {
+	Opt(Option<TokenStream>),
+	Rest,
+	Kw(Option<TokenStream>)
+}
+
+//splits a parameter list at each comma which isn't nested within angle brackets
+fn split_params(stream: TokenStream) -> Vec<Vec<TokenTree>> {
+	let mut params = vec![Vec::new()];
+	let mut depth = 0_usize;
+	let mut prev_was_hyphen = false;
+
+	for token in stream {
+		let mut is_hyphen = false;
+		if let TokenTree::Punct(ref punct) = token {
+			match punct.as_char() {
+				',' if depth == 0 => {
+					params.push(Vec::new());
+					continue
+				}
+				'<' => depth += 1,
+				'>' if !prev_was_hyphen => depth -= 1,
+				'-' => is_hyphen = punct.spacing() == Spacing::Joint,
+				_ => ()
+			}
+		}
+
+		prev_was_hyphen = is_hyphen;
+		params.last_mut().unwrap().push(token);
+	}
+
+	params.retain(|param| !param.is_empty());
+	params
+}
+
+//removes an #[opt], #[rest] or #[kw] attribute from the start of a parameter
+fn strip_param_attr(param: Vec<TokenTree>) -> (Option<ParamAttr>, Vec<TokenTree>) {
+	let mut kind = None;
+	let mut rest = Vec::with_capacity(param.len());
+
+	let mut i = 0;
+	while i < param.len() {
+		if let (true, Some(TokenTree::Group(ref group))) = (is_punct(param.get(i), '#'), 
+		                                                      param.get(i + 1)) {
+			let attr: Vec<TokenTree> = group.stream().into_iter().collect();
+			let name = match attr.get(0) {
+				Some(TokenTree::Ident(ref ident)) => ident.to_string(),
+				_ => String::new()
+			};
+
+			let arg = match attr.get(1) {
+				Some(TokenTree::Group(ref group)) => Some(group.stream()),
+				_ => None
+			};
+
+			let attr_kind = match &name[..] {
+				"opt" => Some(ParamAttr::Opt(arg)),
+				"kw" => Some(ParamAttr::Kw(arg)),
+				"rest" => {
+					assert!(arg.is_none(), "the #[rest] attribute expects no arguments");
+					Some(ParamAttr::Rest)
+				}
+				_ => None
+			};
+
+			if attr_kind.is_some() {
+				assert!(kind.is_none(), "#[rfn_params] received a parameter with two attributes");
+				kind = attr_kind;
+				i += 2;
+				continue
+			}
+		}
+
+		rest.push(param[i].clone());
+		i += 1;
+	}
+
+	(kind, rest)
+}
+
+//emits `let pattern: ty = match src { Some(arg) => arg, None => default };`
+fn emit_unpack(
+	dst: &mut TokenStream,
+	pattern: &[TokenTree],
+	ty: &[TokenTree],
+	src: TokenStream,
+	default: Option<TokenStream>
+) {
+	let default = default.unwrap_or_else(|| tokens_from_str("::std::default::Default::default()"));
+
+	let mut arms = tokens_from_str("Some(arg) => arg, None =>");
+	arms.extend(iter::once(TokenTree::Group(Group::new(Delimiter::Parenthesis, default))));
+
+	dst.extend(tokens_from_str("let"));
+	dst.extend(pattern.iter().cloned());
+	dst.extend(tokens_from_str(":"));
+	dst.extend(ty.iter().cloned());
+	dst.extend(tokens_from_str("= match"));
+	dst.extend(src);
+	dst.extend(iter::once(TokenTree::Group(Group::new(Delimiter::Brace, arms))));
+	dst.extend(tokens_from_str(";"));
+}
+
+fn is_punct(token: Option<&TokenTree>, ch: char) -> bool {
+	match token {
+		Some(TokenTree::Punct(ref punct)) => punct.as_char() == ch,
+		_ => false
+	}
+}
+
+//true if tokens[i] is a `:` which isn't part of a `::`
+fn is_single_colon(tokens: &[TokenTree], i: usize) -> bool {
+	match tokens[i] {
+		TokenTree::Punct(ref punct) if punct.as_char() == ':' => {
+			let joined_prev = match i.checked_sub(1).map(|prev| &tokens[prev]) {
+				Some(TokenTree::Punct(ref prev)) => {
+					prev.as_char() == ':' && prev.spacing() == Spacing::Joint
+				}
+				_ => false
+			};
+
+			punct.spacing() == Spacing::Alone && !joined_prev
+		}
+		_ => false
+	}
+}
+
+fn tokens_from_str(st: &str) -> TokenStream {
+	TokenStream::from_str(st).unwrap()
+}
+
+/**
+Embeds a GameLisp source file into the executable.
+
+The input must be a string literal which contains a filepath, relative to the
+[`CARGO_MANIFEST_DIR`](https://doc.rust-lang.org/cargo/reference/environment-variables.html).
+The macro evaluates to an [`IncludedFile`](struct.IncludedFile.html), which stores the file's
+source text alongside its filename.
+
+	static PRELUDE: IncludedFile = include_glsp!("scripts/prelude.glsp");
+
+	PRELUDE.load()?;
+
+Loading an `IncludedFile` is equivalent to loading the original file from the filesystem, so 
+any spans in error messages will refer to `scripts/prelude.glsp`. The file is parsed while your
+crate is compiling, so invalid syntax is reported as a compiler error, and cargo will rebuild 
+your crate whenever the file changes.
+*/
+
+#[proc_macro]
+pub fn include_glsp(input: TokenStream) -> TokenStream {
+	let input = match Input::parse(input, "include_glsp") {
+		Ok(input) => input,
+		Err(err) => return err
+	};
+
+	if !input.splices.is_empty() {
+		return input.error("the input to include_glsp!() must be a filepath")
+	}
+
+	let filename = normalize_filename(&input.text);
+
+	let engine = Engine::new();
+	engine.run(|| {
+		match included_file(&filename, &manifest_dir().join(&filename)) {
+			Ok(output) => Ok(tokens_from_str(&output)),
+			Err(msg) => Ok(input.error(&msg))
+		}
+	}).unwrap()
+}
+
+/**
+Embeds a directory of GameLisp source files into the executable.
+
+The input must be a string literal which contains a directory path, relative to the
+[`CARGO_MANIFEST_DIR`](https://doc.rust-lang.org/cargo/reference/environment-variables.html).
+The macro evaluates to an [`IncludedDir`](struct.IncludedDir.html), which can load the files
+in order, or act as a [loader](fn.set_loader.html).
+
+	static SCRIPTS: IncludedDir = include_glsp_dir!("scripts/");
+
+	glsp::set_loader(SCRIPTS.loader());
+	SCRIPTS.load()?;
+
+If the directory contains a file named `manifest.txt`, then only the files listed in the 
+manifest are embedded, in the order that they're listed. Each line of the manifest is a path
+relative to the directory. Blank lines are ignored, as are lines which start with `;`.
+
+	; scripts/manifest.txt
+	prelude.glsp
+	ai/goblin.glsp
+	main.glsp
+
+Otherwise, every file with the extension `.glsp` is embedded, including files in 
+subdirectories, sorted by their paths.
+
+Each file's filename is its path relative to the crate root, e.g. `scripts/ai/goblin.glsp`.
+Files are parsed while your crate is compiling, so invalid syntax is reported as a compiler 
+error. Cargo will rebuild your crate when an embedded file changes, when the manifest changes,
+or when a `.glsp` file is added to or removed from a directory without a manifest.
+*/
+
+#[proc_macro]
+pub fn include_glsp_dir(input: TokenStream) -> TokenStream {
+	let input = match Input::parse(input, "include_glsp_dir") {
+		Ok(input) => input,
+		Err(err) => return err
+	};
+
+	if !input.splices.is_empty() {
+		return input.error("the input to include_glsp_dir!() must be a directory path")
+	}
+
+	let dir_name = normalize_filename(&input.text);
+	let dir_path = manifest_dir().join(&dir_name);
+	if !dir_path.is_dir() {
+		return input.error(&format!("{} is not a directory", dir_path.display()))
+	}
+
+	let relative_names = match included_dir_files(&dir_path) {
+		Ok(relative_names) => relative_names,
+		Err(msg) => return input.error(&msg)
+	};
+
+	let engine = Engine::new();
+	engine.run(|| {
+		let mut files = String::new();
+		for relative_name in &relative_names {
+			let filename = normalize_filename(&format!("{}/{}", dir_name, relative_name));
+			match included_file(&filename, &dir_path.join(relative_name)) {
+				Ok(output) => write!(files, "{}, ", output).unwrap(),
+				Err(msg) => return Ok(input.error(&msg))
+			}
+		}
+
+		Ok(tokens_from_str(&format!("::glsp::IncludedDir::new(&[{}])", files)))
+	}).unwrap()
+}
+
+//reads and parses a source file, and returns rust code which constructs an IncludedFile
+fn included_file(filename: &str, path: &Path) -> Result<String, String> {
+	let text = match fs::read_to_string(path) {
+		Ok(text) => text,
+		Err(err) => return Err(format!("unable to read {}: {}", path.display(), err))
+	};
+
+	//rustc will rerun the macro when the file is modified
+	tracked::path(path);
+
+	if let Err((offset, err)) = parse_tokenwise(&text, Some(filename)) {
+		let line = text[..offset].matches('\n').count() + 1;
+		return Err(format!("{}:{}: {}", filename, line, describe(&err)))
+	}
+
+	Ok(format!("::glsp::IncludedFile::new({:?}, {:?})", filename, text))
+}
+
+//returns the path of each file in an include_glsp_dir!() directory, relative to the directory,
+//in load order
+fn included_dir_files(dir_path: &Path) -> Result<Vec<String>, String> {
+	let manifest_path = dir_path.join("manifest.txt");
+	if manifest_path.is_file() {
+		tracked::path(&manifest_path);
+
+		let manifest = match fs::read_to_string(&manifest_path) {
+			Ok(manifest) => manifest,
+			Err(err) => return Err(format!("unable to read {}: {}", manifest_path.display(), err))
+		};
+
+		let mut relative_names = Vec::<String>::new();
+		for line in manifest.lines().map(str::trim) {
+			if line.is_empty() || line.starts_with(';') {
+				continue
+			}
+
+			let relative_name = normalize_filename(line);
+			if !dir_path.join(&relative_name).is_file() {
+				return Err(format!("{} lists {}, which is not a file", manifest_path.display(),
+				                   line))
+			}
+			if relative_names.contains(&relative_name) {
+				return Err(format!("{} lists {} more than once", manifest_path.display(), line))
 			}
+
+			relative_names.push(relative_name);
 		}
 
-		dst
+		Ok(relative_names)
+	} else {
+		let mut relative_names = Vec::new();
+		find_glsp_files(dir_path, "", &mut relative_names)?;
+		relative_names.sort();
+
+		Ok(relative_names)
+	}
+}
+
+fn find_glsp_files(dir_path: &Path, prefix: &str, dst: &mut Vec<String>) -> Result<(), String> {
+	//tracking a directory causes rustc to rerun the macro when an entry is added or removed
+	tracked::path(dir_path);
+
+	let entries = match fs::read_dir(dir_path) {
+		Ok(entries) => entries,
+		Err(err) => return Err(format!("unable to read {}: {}", dir_path.display(), err))
+	};
+
+	for entry in entries {
+		let path = match entry {
+			Ok(entry) => entry.path(),
+			Err(err) => return Err(format!("unable to read {}: {}", dir_path.display(), err))
+		};
+
+		let name = match path.file_name().and_then(|name| name.to_str()) {
+			Some(name) => format!("{}{}", prefix, name),
+			None => return Err(format!("the path {} is not valid utf-8", path.display()))
+		};
+
+		if path.is_dir() {
+			find_glsp_files(&path, &format!("{}/", name), dst)?;
+		} else if path.extension().map_or(false, |extension| extension == "glsp") {
+			dst.push(name);
+		}
+	}
+
+	Ok(())
+}
+
+fn manifest_dir() -> PathBuf {
+	PathBuf::from(env::var_os("CARGO_MANIFEST_DIR").unwrap_or_else(|| ".".into()))
+}
 
+//converts a filepath to the form used for spans: forward slashes, with no `.` components and
+//no trailing slash
+fn normalize_filename(filename: &str) -> String {
+	let absolute = filename.starts_with(|ch| ch == '/' || ch == '\\');
+	let components: Vec<&str> = filename.split(|ch| ch == '/' || ch == '\\').filter(|component| {
+		!component.is_empty() && *component != "."
+	}).collect();
+
+	let joined = components.join("/");
+	if absolute {
+		format!("/{}", joined)
 	} else {
-		panic!("expected a single string literal")
+		joined
 	}
 }
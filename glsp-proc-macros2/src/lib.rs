@@ -1,15 +1,25 @@
 #![forbid(unsafe_code)]
 
-use glsp::{arr, DequeAccess, DequeOps, stock_syms::*, Sym, Val};
+#![feature(proc_macro_diagnostic, proc_macro_span)]
+
+use glsp::{arr, DequeAccess, DequeOps, FromVal, stock_syms::*, Sym, Val};
 use glsp_stdlib::Runtime;
-use proc_macro::{Literal, TokenStream, TokenTree};
+use proc_macro::{Delimiter, Diagnostic, Level, Literal, TokenStream, TokenTree};
 use proc_macro2::{Literal as Literal2, TokenStream as TokenStream2, TokenTree as TokenTree2};
 use quote::quote;
+use self::input::{compile_error, Input};
+use std::collections::{HashSet};
 use std::fmt::Write;
 use std::path::PathBuf;
 use std::str::FromStr;
 use syn::{LitStr, parse::Parser, punctuated::Punctuated, Token};
 
+//describe() and parse_tokenwise() are only used by glsp-proc-macros
+#[allow(dead_code)]
+#[path = "../../glsp-proc-macros/src/input.rs"]
+mod input;
+mod strict;
+
 /**
 Pre-compiles GameLisp code and embeds it into the executable as a byte slice.
 
@@ -88,6 +98,20 @@ the local variables' values are updated when `eval!()` returns, using the
 		(= ~assigned_but_not_read 100.0)
 	"#)?;
 
+Arbitrary Rust expressions can be spliced into the input using curly braces, `{rust_expr}`. 
+The expression's result is converted using the [`ToVal` trait](trait.ToVal.html), and
+`..{rust_expr}` splays it. Each expression is evaluated once, in order, before any of the 
+GameLisp code runs. `{x}` moves or copies `x`, so use `{&x}` to borrow a local variable which you
+need to use again later. Unlike an unquoted local variable, a splice can't be assigned to.
+
+	let player = Player { hp: 40, max_hp: 50 };
+	let regen: i32 = eval!(r#"
+		(min (+ {player.hp} 20) {player.max_hp})
+	"#)?;
+
+Curly braces within a str literal, such as `"hp: {hp}"`, belong to GameLisp's own string 
+interpolation syntax, so they're not treated as splices.
+
 Some Rust collection types, such as tuples, slices, `Strings` and `HashMaps`, will allocate
 a new GameLisp array, string or table when captured as a local variable. This is potentially 
 expensive, especially for large collections. Also, if the resulting collection is mutated, 
@@ -112,25 +136,114 @@ subsequent execution, the overhead is roughly one microsecond per `eval!()` call
 
 The input is macro-expanded using a generic, empty [`Runtime`](struct.Runtime.html) which does not
 have access to any of your own macros. The only macros you can use in `eval!()` are those provided 
-by GameLisp's standard library, or those defined within the `eval!()` form itself
+by GameLisp's standard library, or those defined within the `eval!()` form itself.
+
+When the input contains invalid syntax, the compiler error points to the offending token within 
+the string literal. For some additional compile-time checks, use 
+[`eval_strict!()`](macro.eval_strict.html).
 
 */
 
 #[proc_macro]
 pub fn eval(input: TokenStream) -> TokenStream {
+	eval_impl(input, None)
+}
+
+/**
+A version of [`eval!()`](macro.eval.html) which checks its input for common mistakes.
+
+After the input has been macro-expanded, `eval_strict!()` checks that:
+
+- Each special form, like [`let`](https://gamelisp.rs/std/let) or 
+  [`if`](https://gamelisp.rs/std/if), receives a valid number of arguments.
+- When a function has been bound using [`defn`](https://gamelisp.rs/std/defn),
+  [`let-fn`](https://gamelisp.rs/std/let-fn) or `(let name (fn ...))` earlier in the input, and 
+  it's never reassigned, any calls to it pass a valid number of arguments.
+- Each symbol which isn't quoted refers to a local variable, a global defined earlier in the 
+  input, or a global defined by GameLisp's standard library.
+
+The first two checks trigger a compile error. Unresolved symbols only trigger a compiler warning,
+because they might refer to globals which will be bound at runtime. To silence the warning for
+specific globals, list their names in an `#[allow_globals]` attribute before the string literal.
+Each name can be an identifier, or a string literal for names which aren't valid Rust tokens.
+
+	let _: Val = eval_strict!(#[allow_globals(player, "spawn-enemy")] r#"
+		(defn spawn-horde (n)
+		  (forn (i n)
+		    (spawn-enemy 'goblin (.pos player))))
+
+		(spawn-horde 5 10) ; error: spawn-horde expects exactly 1 argument
+		(spwan-horde 5)    ; warning: unresolved global spwan-horde
+	"#)?;
+
+These checks are heuristic, so they may miss some mistakes. Code which passes them will behave
+exactly as it would under `eval!()`.
+*/
+
+#[proc_macro]
+pub fn eval_strict(input: TokenStream) -> TokenStream {
+	let mut tokens: Vec<TokenTree> = input.into_iter().collect();
+	let mut allowed = HashSet::new();
+
+	//consume any number of #[allow_globals(...)] attributes
+	while let [TokenTree::Punct(punct), TokenTree::Group(group), ..] = &tokens[..] {
+		if punct.as_char() != '#' || group.delimiter() != Delimiter::Bracket {
+			break
+		}
+
+		let attr: Vec<TokenTree> = group.stream().into_iter().collect();
+		let names = match &attr[..] {
+			[TokenTree::Ident(ident), TokenTree::Group(names)]
+				if ident.to_string() == "allow_globals" &&
+				   names.delimiter() == Delimiter::Parenthesis => names.stream(),
+			_ => return compile_error(group.span(), "expected #[allow_globals(...)]")
+		};
+
+		let mut name = String::new();
+		for tt in names.into_iter().chain(Some(TokenTree::from(proc_macro::Punct::new(',', 
+		                                       proc_macro::Spacing::Alone)))) {
+			match tt {
+				TokenTree::Punct(ref punct) if punct.as_char() == ',' => {
+					if !name.is_empty() {
+						allowed.insert(name.split_off(0));
+					}
+				}
+				TokenTree::Literal(ref lit) => {
+					match syn::parse::<LitStr>(TokenTree::Literal(lit.clone()).into()) {
+						Ok(lit_str) => name.push_str(&lit_str.value()),
+						Err(_) => return compile_error(lit.span(), "expected a global's name")
+					}
+				}
+				tt => name.push_str(&tt.to_string())
+			}
+		}
+
+		tokens.drain(..2);
+	}
+
+	eval_impl(tokens.into_iter().collect(), Some(allowed))
+}
+
+fn eval_impl(input: TokenStream, strict: Option<HashSet<String>>) -> TokenStream {
+	let macro_name = if strict.is_some() { "eval_strict" } else { "eval" };
+
 	//wrangle input
-	let lit: LitStr = syn::parse(input).expect("the input to eval!() \
-	                                            must be a single string literal");
-	let text: String = lit.value();
+	let input = match Input::parse(input, macro_name) {
+		Ok(input) => input,
+		Err(err) => return err
+	};
 
 	//spin up a glsp Runtime
 	let runtime = Runtime::new();
 	runtime.run(|| {
 		glsp::seed_gensym();
 
-		//parse the input string
-		let forms = glsp::parse_all(&text, Some("eval-proc-macro"))
-		                 .expect("eval!() received invalid glsp syntax");
+		//parse the input string. any {rust-expr} splices have already been replaced with
+		//~__glsp_splice_N, so from here on they're treated like unquoted local variables.
+		let forms = match input.parse_forms(Some("eval-proc-macro")) {
+			Ok(forms) => forms,
+			Err(err) => return Ok(err)
+		};
 
 		//make it mutable
 		let forms: Vec<Val> = forms.iter().map(|v| v.deep_clone().unwrap()).collect();
@@ -172,31 +285,40 @@ pub fn eval(input: TokenStream) -> TokenStream {
 						()
 					} else if sym == UNQUOTE_SYM {
 						assert!(arr.len() == 2, "invalid unquote form: {}", arr);
-						let identifier = arr.get::<Sym>(1).expect("invalid unquote form");
+
+						//~..name is rewritten to (splay (access arg_gensym index))
+						let (identifier, splays) = match arr.get::<Val>(1).unwrap() {
+							Val::Arr(ref inner) if inner.len() == 2 &&
+							                       inner.get::<Sym>(0).ok() == Some(SPLAY_SYM) => {
+								(inner.get::<Sym>(1).expect("invalid unquote form"), true)
+							}
+							other => (Sym::from_val(&other).expect("invalid unquote form"), false)
+						};
 						assert!(is_valid_identifier(&identifier.name()),
 						        "invalid unquoted identifier: {}", identifier);
 
-						for (i, entry) in names.iter_mut().enumerate() {
-							if entry.name == identifier {
-								arr.clear().unwrap();
-								arr.push(ACCESS_SYM).unwrap();
-								arr.push(arg_gensym).unwrap();
-								arr.push(i).unwrap();
+						let index = match names.iter().position(|entry| entry.name == identifier) {
+							Some(index) => index,
+							None => {
+								names.push(NameEntry {
+									name: identifier,
+									input: false,
+									output: false
+								});
 
-								return
+								names.len() - 1
 							}
-						}
-
-						names.push(NameEntry {
-							name: identifier,
-							input: false,
-							output: false
-						});
+						};
 
 						arr.clear().unwrap();
-						arr.push(ACCESS_SYM).unwrap();
-						arr.push(arg_gensym).unwrap();
-						arr.push(names.len() - 1).unwrap();
+						if splays {
+							arr.push(SPLAY_SYM).unwrap();
+							arr.push(arr![ACCESS_SYM, arg_gensym, index]).unwrap();
+						} else {
+							arr.push(ACCESS_SYM).unwrap();
+							arr.push(arg_gensym).unwrap();
+							arr.push(index).unwrap();
+						}
 
 					} else {
 						for item in arr.iter() {
@@ -241,13 +363,59 @@ pub fn eval(input: TokenStream) -> TokenStream {
 		let mut names = Vec::new();
 		pass0(&fn_form, &mut names, arg_gensym);
 
-		let fn_form = glsp::expand(&fn_form, None).expect("error when expanding eval!()'s input");
+		let fn_form = match glsp::expand(&fn_form, None) {
+			Ok(fn_form) => fn_form,
+			Err(err) => {
+				let msg = format!("error when expanding {}!()'s input: {}", macro_name, err.val());
+				return Ok(input.error(&msg))
+			}
+		};
 		
 		pass1(&fn_form, &mut names, arg_gensym);
 
+		//splices are input-only, and a splice which is never accessed has probably been quoted
+		for splice in &input.splices {
+			let entry = names.iter().find(|entry| *entry.name.name() == *splice.name);
+			let msg = match entry {
+				Some(entry) if entry.output => {
+					format!("the splice {{{}}} can't be assigned to. to mutate a local variable, \
+					         unquote it using ~ instead", splice.source)
+				}
+				Some(entry) if entry.input => continue,
+				_ => format!("the splice {{{}}} is never evaluated. it may be within a quoted \
+				              form", splice.source)
+			};
+
+			return Ok(compile_error(splice.span, &msg))
+		}
+
+		//in strict mode, check the expanded code for likely mistakes
+		if let Some(ref allowed) = strict {
+			match strict::check(&fn_form, allowed) {
+				Ok(unresolved) => {
+					if unresolved.len() > 0 {
+						let names: Vec<String> = unresolved.iter().map(|s| s.to_string()).collect();
+						let msg = format!("eval_strict!() found unresolved globals: {}", 
+						                  names.join(", "));
+
+						Diagnostic::spanned(input.span(), Level::Warning, msg)
+							.help("if these globals are bound at runtime, list them in an \
+							       #[allow_globals(...)] attribute")
+							.emit();
+					}
+				}
+				Err(msg) => return Ok(input.error(&msg))
+			}
+		}
+
 		//compile the fn form
-		let (_, bytes) = glsp::load_and_compile_vals(&[fn_form], "eval-proc-macro")
-			.expect("error when compiling eval!()'s input");
+		let bytes = match glsp::load_and_compile_vals(&[fn_form], "eval-proc-macro") {
+			Ok((_, bytes)) => bytes,
+			Err(err) => {
+				let msg = format!("error when compiling {}!()'s input: {}", macro_name, err.val());
+				return Ok(input.error(&msg))
+			}
+		};
 		let byte_string = TokenTree2::Literal(Literal2::byte_string(&bytes[..]));
 
 		//emit the lazy-initialization code
@@ -271,7 +439,10 @@ pub fn eval(input: TokenStream) -> TokenStream {
 			}
 		}
 
-		Ok(quote! {
+		let splice_bindings: TokenStream2 = input.splice_bindings().into();
+
+		Ok(quote! {{
+			#splice_bindings
 			::glsp::with_lazy_val(::glsp::lazy_key!(),
 				|| ::glsp::load_compiled(#byte_string).unwrap(),
 				|__glsp_eval_val| {
@@ -297,7 +468,7 @@ pub fn eval(input: TokenStream) -> TokenStream {
 					::glsp::FromVal::from_val(&__glsp_eval_result)
 				}
 			)
-		}.into())
+		}}.into())
 	}).unwrap()
 }
 
@@ -0,0 +1,386 @@
+use glsp::{Arr, DequeAccess, DequeOps, FromVal, Root, stock_syms::*, Sym, Val};
+use std::collections::{HashMap, HashSet};
+
+/*
+
+the checks performed by eval_strict!(). they run on the fully-expanded (fn) form, so the only
+forms we need to understand are special forms, function calls, symbols and literals.
+
+we can't see the host's globals, so a bare symbol which isn't a local variable, isn't bound by
+the standard library, and isn't bound by a (bind-global!) call somewhere within the input, is
+reported as an unresolved global. hosts can declare globals which will be bound at runtime
+using an #[allow_globals(...)] attribute.
+
+arity checking is deliberately conservative. we only check calls to fns which are bound by a
+(let) or (bind-global!) form earlier in the input, and which are never reassigned. a call with
+a splayed argument is never checked.
+
+*/
+
+#[derive(Copy, Clone)]
+struct Arity {
+	min: usize,
+	max: Option<usize>
+}
+
+struct Checker<'a> {
+	allowed: &'a HashSet<String>,
+	body_globals: HashSet<Sym>,
+	assignments: HashMap<Sym, usize>,
+	scopes: Vec<HashMap<Sym, Option<Arity>>>,
+	global_arities: HashMap<Sym, Arity>,
+	unresolved: Vec<Sym>
+}
+
+//min and max argument counts for each special form, mirroring glsp-engine's ast.rs
+const SPECIAL_FORMS: [(Sym, usize, Option<usize>); 13] = [
+	(ASSIGNMENT_SYM, 2, Some(2)),
+	(DO_SYM, 0, None),
+	(IF_SYM, 3, Some(3)),
+	(LET_SYM, 2, Some(2)),
+	(BLOCK_SYM, 1, None),
+	(FINISH_BLOCK_SYM, 1, Some(2)),
+	(RESTART_BLOCK_SYM, 1, Some(1)),
+	(FN_SYM, 1, None),
+	(RETURN_SYM, 0, Some(1)),
+	(YIELD_SYM, 0, Some(1)),
+	(QUOTE_SYM, 1, Some(1)),
+	(DEFER_SYM, 0, None),
+	(DEFER_YIELD_SYM, 2, Some(2))
+];
+
+//returns the list of unresolved globals, or an error message if the input is certain to fail
+pub(crate) fn check(fn_form: &Val, allowed: &HashSet<String>) -> Result<Vec<Sym>, String> {
+	let mut checker = Checker {
+		allowed,
+		body_globals: HashSet::new(),
+		assignments: HashMap::new(),
+		scopes: Vec::new(),
+		global_arities: HashMap::new(),
+		unresolved: Vec::new()
+	};
+
+	checker.survey(fn_form);
+	checker.walk(fn_form)?;
+
+	Ok(checker.unresolved)
+}
+
+impl<'a> Checker<'a> {
+	//records every global bound by the input, and counts the assignments to each symbol
+	fn survey(&mut self, val: &Val) {
+		if let Val::Arr(ref arr) = *val {
+			match arr.get::<Val>(0) {
+				Ok(Val::Sym(QUOTE_SYM)) => return,
+				Ok(Val::Sym(ASSIGNMENT_SYM)) => {
+					if let Ok(target) = arr.get::<Sym>(1) {
+						*self.assignments.entry(target).or_insert(0) += 1;
+					}
+				}
+				Ok(Val::Sym(callee)) if &*callee.name() == "bind-global!" ||
+				                        &*callee.name() == "global=" => {
+					if let Some(target) = quoted_sym(arr, 1) {
+						if &*callee.name() == "bind-global!" {
+							self.body_globals.insert(target);
+						}
+						*self.assignments.entry(target).or_insert(0) += 1;
+					}
+				}
+				_ => ()
+			}
+
+			for item in arr.iter() {
+				self.survey(&item);
+			}
+		}
+	}
+
+	fn walk(&mut self, val: &Val) -> Result<(), String> {
+		match *val {
+			Val::Sym(sym) => {
+				self.resolve(sym);
+				Ok(())
+			}
+			Val::Arr(ref arr) if arr.len() > 0 => self.walk_arr(arr),
+			_ => Ok(())
+		}
+	}
+
+	fn walk_arr(&mut self, arr: &Root<Arr>) -> Result<(), String> {
+		let items: Vec<Val> = arr.iter().collect();
+		let args = &items[1..];
+
+		let special = match items[0] {
+			Val::Sym(sym) => SPECIAL_FORMS.iter().find(|&&(name, _, _)| name == sym).copied(),
+			_ => None
+		};
+
+		if let Some((name, min, max)) = special {
+			if args.len() < min || max.map_or(false, |max| args.len() > max) {
+				return Err(format!("the {} special form expects {}, but {} were passed: {}",
+				                   name, describe_arity(Arity { min, max }), args.len(), arr))
+			}
+
+			return self.walk_special(name, arr, args)
+		}
+
+		match items[0] {
+			//(.meth obj) and (.meth? obj) don't refer to any variable named meth
+			Val::Arr(ref callee) if head(callee) == Some(METH_NAME_SYM) ||
+			                        head(callee) == Some(QUESTION_MARK_SYM) => {
+				return self.walk_args(args)
+			}
+
+			//@name and @name? refer to a field, not a variable
+			Val::Sym(ATSIGN_SYM) | Val::Sym(ATSIGN_OPT_SYM) => return Ok(()),
+			Val::Sym(SET_ATSIGN_SYM) | Val::Sym(SET_ATSIGN_OPT_SYM) => {
+				return self.walk_args(&args[1.min(args.len())..])
+			}
+
+			Val::Sym(SPLICE_SYM) => return self.walk_args(args),
+			_ => ()
+		}
+
+		//an ordinary function call
+		self.walk(&items[0])?;
+		self.walk_args(args)?;
+
+		if let Val::Sym(callee) = items[0] {
+			let splayed = args.iter().any(|arg| splayee(arg).is_some());
+			if let (Some(arity), false) = (self.arity_of(callee), splayed) {
+				if args.len() < arity.min || arity.max.map_or(false, |max| args.len() > max) {
+					return Err(format!("{} expects {}, but {} were passed: {}",
+					                   callee, describe_arity(arity), args.len(), arr))
+				}
+			}
+
+			//(bind-global! 'name (fn ...)) records name's arity for any subsequent calls
+			if &*callee.name() == "bind-global!" && args.len() == 2 {
+				if let (Some(name), Some(arity)) = (quoted_sym(arr, 1), fn_arity(&args[1])) {
+					if self.assignments.get(&name) == Some(&1) {
+						self.global_arities.insert(name, arity);
+					}
+				}
+			}
+		}
+
+		Ok(())
+	}
+
+	fn walk_args(&mut self, args: &[Val]) -> Result<(), String> {
+		for arg in args {
+			match splayee(arg) {
+				Some(splayee) => self.walk(&splayee)?,
+				None => self.walk(arg)?
+			}
+		}
+
+		Ok(())
+	}
+
+	fn walk_special(&mut self, name: Sym, arr: &Root<Arr>, args: &[Val]) -> Result<(), String> {
+		match name {
+			QUOTE_SYM | RESTART_BLOCK_SYM => Ok(()),
+			DO_SYM => self.walk_scoped(args),
+			IF_SYM => {
+				for arg in args {
+					self.walk_scoped(&[arg.clone()])?;
+				}
+				Ok(())
+			}
+			LET_SYM => {
+				let name = match args[0] {
+					Val::Sym(name) => name,
+					_ => return Err(format!("the first argument to let must be a sym: {}", arr))
+				};
+
+				self.walk(&args[1])?;
+
+				let arity = match self.assignments.get(&name) {
+					None => fn_arity(&args[1]),
+					Some(_) => None
+				};
+
+				self.scopes.last_mut().unwrap().insert(name, arity);
+				Ok(())
+			}
+			ASSIGNMENT_SYM => {
+				let target = match args[0] {
+					Val::Sym(target) => target,
+					_ => return Err(format!("the first argument to = must be a sym: {}", arr))
+				};
+
+				self.walk(&args[1])?;
+				self.resolve(target);
+
+				//(let name #n) (= name (fn ...)), emitted by let-fn
+				if self.assignments.get(&target) == Some(&1) {
+					if let Some(arity) = fn_arity(&args[1]) {
+						if let Some(scope) = self.scope_of(target) {
+							scope.insert(target, Some(arity));
+						}
+					}
+				}
+
+				Ok(())
+			}
+			BLOCK_SYM | FINISH_BLOCK_SYM => {
+				if !args[0].is_sym() {
+					return Err(format!("the first argument to {} must be a sym: {}", name, arr))
+				}
+
+				self.walk_scoped(&args[1..])
+			}
+			FN_SYM => self.walk_fn(arr, args),
+			_ => self.walk_scoped(args)
+		}
+	}
+
+	fn walk_scoped(&mut self, vals: &[Val]) -> Result<(), String> {
+		self.scopes.push(HashMap::new());
+		let result = vals.iter().try_for_each(|val| self.walk(val));
+		self.scopes.pop();
+
+		result
+	}
+
+	fn walk_fn(&mut self, arr: &Root<Arr>, args: &[Val]) -> Result<(), String> {
+		let params_i = match args.iter().position(|arg| arg.is_arr()) {
+			Some(params_i) => params_i,
+			None => return Err(format!("fn form has no parameter list: {}", arr))
+		};
+
+		self.scopes.push(HashMap::new());
+		let result = (|| {
+			if let Val::Arr(ref params) = args[params_i] {
+				for param in params.iter() {
+					match param {
+						Val::Sym(name) => {
+							self.scopes.last_mut().unwrap().insert(name, None);
+						}
+						Val::Arr(ref param_arr) if param_arr.len() >= 2 => {
+							if param_arr.len() == 3 {
+								self.walk(&param_arr.get::<Val>(2).unwrap())?;
+							}
+
+							if let Ok(name) = param_arr.get::<Sym>(1) {
+								self.scopes.last_mut().unwrap().insert(name, None);
+							}
+						}
+						_ => return Err(format!("invalid parameter {} in fn form: {}", param, arr))
+					}
+				}
+			}
+
+			args[params_i + 1 ..].iter().try_for_each(|val| self.walk(val))
+		})();
+		self.scopes.pop();
+
+		result
+	}
+
+	fn resolve(&mut self, sym: Sym) {
+		let resolved = sym.is_gensym() ||
+		               self.scopes.iter().any(|scope| scope.contains_key(&sym)) ||
+		               self.body_globals.contains(&sym) ||
+		               self.allowed.contains(&*sym.name()) ||
+		               glsp::has_global(sym).unwrap_or(false);
+
+		if !resolved && !self.unresolved.contains(&sym) {
+			self.unresolved.push(sym);
+		}
+	}
+
+	fn scope_of(&mut self, sym: Sym) -> Option<&mut HashMap<Sym, Option<Arity>>> {
+		self.scopes.iter_mut().rev().find(|scope| scope.contains_key(&sym))
+	}
+
+	fn arity_of(&self, sym: Sym) -> Option<Arity> {
+		match self.scopes.iter().rev().find_map(|scope| scope.get(&sym)) {
+			Some(&arity) => arity,
+			None => self.global_arities.get(&sym).copied()
+		}
+	}
+}
+
+//for (name ...), returns name
+fn head(arr: &Root<Arr>) -> Option<Sym> {
+	arr.get::<Sym>(0).ok()
+}
+
+//for (splay x), returns x
+fn splayee(val: &Val) -> Option<Val> {
+	match *val {
+		Val::Arr(ref arr) if arr.len() == 2 && head(arr) == Some(SPLAY_SYM) => {
+			Some(arr.get::<Val>(1).unwrap())
+		}
+		_ => None
+	}
+}
+
+//for an arr whose item at index i is (quote name), returns name
+fn quoted_sym(arr: &Root<Arr>, i: usize) -> Option<Sym> {
+	match arr.get::<Val>(i) {
+		Ok(Val::Arr(ref quoted)) if quoted.len() == 2 && head(quoted) == Some(QUOTE_SYM) => {
+			quoted.get::<Sym>(1).ok()
+		}
+		_ => None
+	}
+}
+
+//if the given val is a (fn) form, returns its arity
+fn fn_arity(val: &Val) -> Option<Arity> {
+	let arr = match *val {
+		Val::Arr(ref arr) if arr.len() >= 2 && head(arr) == Some(FN_SYM) => arr,
+		_ => return None
+	};
+
+	let mut i = 1;
+	loop {
+		match arr.get::<Val>(i).ok()? {
+			Val::Sym(FLAG_NAME_SYM) | Val::Sym(FLAG_DOC_SYM) | Val::Sym(FLAG_PARAM_NAMES_SYM) => {
+				i += 2
+			}
+			Val::Sym(FLAG_ARG_LIMITS_SYM) => {
+				let min = arr.get::<usize>(i + 1).ok()?;
+				let max = match arr.get::<Val>(i + 2).ok()? {
+					Val::Nil => None,
+					max => Some(usize::from_val(&max).ok()?)
+				};
+				return Some(Arity { min, max })
+			}
+			Val::Arr(params) => {
+				let mut arity = Arity { min: 0, max: Some(0) };
+				for param in params.iter() {
+					match param {
+						Val::Sym(_) => {
+							arity.min += 1;
+							arity.max = arity.max.map(|max| max + 1);
+						}
+						Val::Arr(ref param_arr) => {
+							match head(param_arr)? {
+								QUESTION_MARK_SYM => arity.max = arity.max.map(|max| max + 1),
+								SPLAY_SYM => arity.max = None,
+								_ => return None
+							}
+						}
+						_ => return None
+					}
+				}
+
+				return Some(arity)
+			}
+			_ => return None
+		}
+	}
+}
+
+fn describe_arity(arity: Arity) -> String {
+	let plural = |n: usize| if n == 1 { "" } else { "s" };
+
+	match arity.max {
+		Some(max) if max == arity.min => format!("exactly {} argument{}", max, plural(max)),
+		Some(max) => format!("{} to {} arguments", arity.min, max),
+		None => format!("at least {} argument{}", arity.min, plural(arity.min))
+	}
+}
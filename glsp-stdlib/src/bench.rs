@@ -0,0 +1,289 @@
+use glsp::{
+	bail, CallableOps, Callable, ensure, FromVal, GResult, Root, rfn, Tab, ToVal, Val
+};
+use std::fmt::{self, Display, Formatter};
+use std::time::{Duration, Instant};
+
+pub fn init(_sandboxed: bool) -> GResult<()> {
+	glsp::bind_rfn("bench", rfn!(bench_rfn))?;
+	glsp::bind_rfn("bench-table", rfn!(bench_table))?;
+
+	Ok(())
+}
+
+const DEFAULT_ITERS: usize = 100;
+const DEFAULT_WARMUP: usize = 10;
+
+/**
+The result of timing a function using [`glsp::bench`](fn.bench.html).
+
+When converted to a [`Val`](enum.Val.html), a `BenchReport` becomes a table with the fields
+`name`, `iters`, `warmup`, `median`, `mean`, `p95`, `min`, `max` and `gc-steps`. Durations are
+stored as a number of seconds. Converting such a table back into a `BenchReport` is also
+supported.
+
+Its `Display` implementation produces a small aligned table, with a header row.
+*/
+
+#[derive(Clone)]
+pub struct BenchReport {
+	name: String,
+	iters: usize,
+	warmup: usize,
+	median: Duration,
+	mean: Duration,
+	p95: Duration,
+	min: Duration,
+	max: Duration,
+	gc_steps: usize
+}
+
+impl BenchReport {
+	/** Returns the name which was passed to `glsp::bench`. */
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	/** Returns the number of measured calls. */
+	pub fn iters(&self) -> usize {
+		self.iters
+	}
+
+	/** Returns the number of unmeasured calls which preceded the measured calls. */
+	pub fn warmup(&self) -> usize {
+		self.warmup
+	}
+
+	/** Returns the median duration of a single call. */
+	pub fn median(&self) -> Duration {
+		self.median
+	}
+
+	/** Returns the mean duration of a single call. */
+	pub fn mean(&self) -> Duration {
+		self.mean
+	}
+
+	/** Returns the 95th-percentile duration of a single call. */
+	pub fn p95(&self) -> Duration {
+		self.p95
+	}
+
+	/** Returns the duration of the fastest call. */
+	pub fn min(&self) -> Duration {
+		self.min
+	}
+
+	/** Returns the duration of the slowest call. */
+	pub fn max(&self) -> Duration {
+		self.max
+	}
+
+	/**
+	Returns the number of times [`glsp::gc`](fn.gc.html) was called during measurement.
+
+	When this is non-zero, the measurements include some garbage-collection work.
+	*/
+	pub fn gc_steps(&self) -> usize {
+		self.gc_steps
+	}
+
+	/**
+	Formats several reports as a single aligned table, with a header row.
+
+	Equivalent to [`(bench-table ..reports)`](https://gamelisp.rs/std/bench-table).
+	*/
+	pub fn table(reports: &[BenchReport]) -> String {
+		let header = ["name", "iters", "median", "mean", "p95", "min", "max", "gc steps"];
+		let mut rows = vec![header.iter().map(|st| st.to_string()).collect::<Vec<_>>()];
+
+		for report in reports {
+			rows.push(vec![
+				report.name.clone(),
+				report.iters.to_string(),
+				format_duration(report.median),
+				format_duration(report.mean),
+				format_duration(report.p95),
+				format_duration(report.min),
+				format_duration(report.max),
+				report.gc_steps.to_string()
+			]);
+		}
+
+		let mut widths = [0; 8];
+		for row in &rows {
+			for (i, cell) in row.iter().enumerate() {
+				widths[i] = widths[i].max(cell.chars().count());
+			}
+		}
+
+		//the name column is left-aligned, and every other column is right-aligned
+		let mut table = String::new();
+		for (row_i, row) in rows.iter().enumerate() {
+			if row_i > 0 {
+				table.push('\n');
+			}
+
+			let mut line = format!("{:<1$}", row[0], widths[0]);
+			for i in 1 .. row.len() {
+				line.push_str(&format!("  {:>1$}", row[i], widths[i]));
+			}
+
+			table.push_str(line.trim_end());
+		}
+
+		table
+	}
+}
+
+fn format_duration(duration: Duration) -> String {
+	let secs = duration.as_secs_f64();
+	if secs < 1e-6 {
+		format!("{:.1}ns", secs * 1e9)
+	} else if secs < 1e-3 {
+		format!("{:.2}us", secs * 1e6)
+	} else if secs < 1.0 {
+		format!("{:.2}ms", secs * 1e3)
+	} else {
+		format!("{:.2}s", secs)
+	}
+}
+
+impl Display for BenchReport {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		write!(f, "{}", BenchReport::table(&[self.clone()]))
+	}
+}
+
+impl ToVal for BenchReport {
+	fn to_val(&self) -> GResult<Val> {
+		let tab = glsp::tab();
+		tab.set(glsp::sym("name")?, &self.name[..])?;
+		tab.set(glsp::sym("iters")?, self.iters)?;
+		tab.set(glsp::sym("warmup")?, self.warmup)?;
+		tab.set(glsp::sym("median")?, self.median.as_secs_f32())?;
+		tab.set(glsp::sym("mean")?, self.mean.as_secs_f32())?;
+		tab.set(glsp::sym("p95")?, self.p95.as_secs_f32())?;
+		tab.set(glsp::sym("min")?, self.min.as_secs_f32())?;
+		tab.set(glsp::sym("max")?, self.max.as_secs_f32())?;
+		tab.set(glsp::sym("gc-steps")?, self.gc_steps)?;
+
+		Ok(Val::Tab(tab))
+	}
+}
+
+impl FromVal for BenchReport {
+	fn from_val(val: &Val) -> GResult<BenchReport> {
+		let tab = match *val {
+			Val::Tab(ref tab) => tab,
+			ref val => bail!("expected a bench report, received {}", val.a_type_name())
+		};
+
+		let secs = |name: &str| -> GResult<Duration> {
+			let secs: f32 = tab.get(glsp::sym(name)?)?;
+			ensure!(secs >= 0.0 && secs.is_finite(), "invalid bench duration {}", secs);
+			Ok(Duration::from_secs_f32(secs))
+		};
+
+		Ok(BenchReport {
+			name: tab.get::<_, Val>(glsp::sym("name")?)?.to_string(),
+			iters: tab.get(glsp::sym("iters")?)?,
+			warmup: tab.get(glsp::sym("warmup")?)?,
+			median: secs("median")?,
+			mean: secs("mean")?,
+			p95: secs("p95")?,
+			min: secs("min")?,
+			max: secs("max")?,
+			gc_steps: tab.get(glsp::sym("gc-steps")?)?
+		})
+	}
+}
+
+/**
+Equivalent to [`(bench name callee (tab ('iters iters) ('warmup warmup)))`][0].
+
+[0]: https://gamelisp.rs/std/bench
+
+Calls `callee` with no arguments `warmup` times, discarding the timings, and then calls it
+`iters` times, timing each call with a monotonic clock. `iters` must be at least 1.
+
+The garbage collector only runs when [`glsp::gc`](fn.gc.html) is called, so the measurement
+is free of collections unless `callee` triggers them itself. Those collections are reported by
+[`BenchReport::gc_steps`](struct.BenchReport.html#method.gc_steps).
+
+	let callee: Root<GFn> = glsp::global("update-world")?;
+	let report = glsp::bench("update-world", &callee, 1000, 100)?;
+	println!("{}", report);
+*/
+pub fn bench<C>(name: &str, callee: &C, iters: usize, warmup: usize) -> GResult<BenchReport>
+where
+	C: CallableOps
+{
+	ensure!(iters >= 1, "(bench) expected at least one iteration");
+
+	for _ in 0 .. warmup {
+		glsp::call::<_, _, Val>(callee, &())?;
+	}
+
+	let steps_before = glsp::gc_steps();
+
+	let mut samples = Vec::with_capacity(iters);
+	for _ in 0 .. iters {
+		let start = Instant::now();
+		glsp::call::<_, _, Val>(callee, &())?;
+		samples.push(start.elapsed());
+	}
+
+	let gc_steps = glsp::gc_steps() - steps_before;
+
+	samples.sort();
+	let total: Duration = samples.iter().sum();
+
+	let len = samples.len();
+	let median = if len % 2 == 0 {
+		(samples[len / 2 - 1] + samples[len / 2]) / 2
+	} else {
+		samples[len / 2]
+	};
+
+	//nearest-rank percentile
+	let p95_index = ((len * 95 + 99) / 100).max(1) - 1;
+
+	Ok(BenchReport {
+		name: name.to_string(),
+		iters,
+		warmup,
+		median,
+		mean: total / (len as u32),
+		p95: samples[p95_index],
+		min: samples[0],
+		max: samples[len - 1],
+		gc_steps
+	})
+}
+
+fn bench_rfn(name: Val, callee: Callable, options: Option<Root<Tab>>) -> GResult<BenchReport> {
+	let name = match name {
+		Val::Str(_) | Val::Sym(_) => name.to_string(),
+		name => bail!("(bench) expected a str or sym name, received {}", name.a_type_name())
+	};
+
+	let mut iters = DEFAULT_ITERS;
+	let mut warmup = DEFAULT_WARMUP;
+
+	if let Some(options) = options {
+		for (key, value) in options.entries().iter() {
+			match key {
+				Val::Sym(sym) if &*sym.name() == "iters" => iters = usize::from_val(&value)?,
+				Val::Sym(sym) if &*sym.name() == "warmup" => warmup = usize::from_val(&value)?,
+				key => bail!("(bench) received an unknown option {}: expected iters or warmup", key)
+			}
+		}
+	}
+
+	bench(&name, &callee, iters, warmup)
+}
+
+fn bench_table(reports: &[BenchReport]) -> String {
+	BenchReport::table(reports)
+}
@@ -0,0 +1,339 @@
+use glsp::{
+	Arr, bail, DequeOps, error, Flo, FromVal, GResult, rdata, rfn, Str, Tab, ToVal, Val
+};
+use std::collections::{HashMap};
+use std::fmt::{self, Display, Formatter, Write};
+use std::sync::mpsc::{self, Receiver, RecvError, Sender, TryRecvError};
+
+pub fn init(_sandboxed: bool) -> GResult<()> {
+	glsp::bind_rfn("send", rfn!(send))?;
+	glsp::bind_rfn("recv", rfn!(recv))?;
+	glsp::bind_rfn("try-recv", rfn!(try_recv))?;
+
+	Ok(())
+}
+
+//-------------------------------------------------------------------------------------------------
+// Message
+//-------------------------------------------------------------------------------------------------
+
+/*
+a Message is a deep copy of a Val which doesn't refer to any particular Runtime, so it can be
+sent to another thread. arrs, strs and tabs are stored out-of-line in the `nodes` array and
+referred to by index, so that shared references and reference cycles survive the round trip.
+syms are stored by name, because each Runtime has its own symbol table.
+*/
+
+struct Message {
+	val: Transfer,
+	nodes: Vec<Node>
+}
+
+enum Transfer {
+	Nil,
+	Int(i32),
+	Flo(Flo),
+	Char(char),
+	Bool(bool),
+	Sym(Box<str>),
+	Node(usize)
+}
+
+enum Node {
+	Arr(Vec<Transfer>),
+	Str(String),
+	Tab(Vec<(Transfer, Transfer)>)
+}
+
+enum PathSegment {
+	Index(usize),
+	Key(String)
+}
+
+struct Packer {
+	nodes: Vec<Node>,
+	visited: HashMap<usize, usize>,
+	path: Vec<PathSegment>
+}
+
+impl Packer {
+	fn pack(&mut self, val: &Val) -> GResult<Transfer> {
+		Ok(match *val {
+			Val::Nil => Transfer::Nil,
+			Val::Int(i) => Transfer::Int(i),
+			Val::Flo(f) => Transfer::Flo(f),
+			Val::Char(c) => Transfer::Char(c),
+			Val::Bool(b) => Transfer::Bool(b),
+			Val::Sym(sym) => {
+				if sym.is_gensym() {
+					bail!("unable to send a gensym{}: gensyms can't be transferred between \
+					       runtimes", self.location())
+				}
+
+				Transfer::Sym((&*sym.name()).into())
+			}
+			Val::Arr(ref arr) => {
+				let addr = &**arr as *const Arr as usize;
+				if let Some(&index) = self.visited.get(&addr) {
+					return Ok(Transfer::Node(index))
+				}
+
+				let index = self.reserve(addr);
+				let mut elements = Vec::with_capacity(arr.len());
+				for (i, element) in arr.iter().enumerate() {
+					self.path.push(PathSegment::Index(i));
+					elements.push(self.pack(&element)?);
+					self.path.pop();
+				}
+
+				self.nodes[index] = Node::Arr(elements);
+				Transfer::Node(index)
+			}
+			Val::Str(ref st) => {
+				let addr = &**st as *const Str as usize;
+				if let Some(&index) = self.visited.get(&addr) {
+					return Ok(Transfer::Node(index))
+				}
+
+				let index = self.reserve(addr);
+				self.nodes[index] = Node::Str(st.to_string());
+				Transfer::Node(index)
+			}
+			Val::Tab(ref tab) => {
+				let addr = &**tab as *const Tab as usize;
+				if let Some(&index) = self.visited.get(&addr) {
+					return Ok(Transfer::Node(index))
+				}
+
+				let index = self.reserve(addr);
+				let mut entries = Vec::with_capacity(tab.len());
+				for (key, value) in tab.entries().iter() {
+					self.path.push(PathSegment::Key(format!("{:?}", key)));
+					let key = self.pack(&key)?;
+					let value = self.pack(&value)?;
+					self.path.pop();
+
+					entries.push((key, value));
+				}
+
+				self.nodes[index] = Node::Tab(entries);
+				Transfer::Node(index)
+			}
+			ref val => {
+				bail!("unable to send {}{}: only nil, bools, ints, flos, chars, syms, arrs, \
+				       strs and tabs can be transferred between runtimes",
+				       val.a_type_name(), self.location())
+			}
+		})
+	}
+
+	fn reserve(&mut self, addr: usize) -> usize {
+		let index = self.nodes.len();
+		self.nodes.push(Node::Str(String::new()));
+		self.visited.insert(addr, index);
+		index
+	}
+
+	fn location(&self) -> String {
+		if self.path.is_empty() {
+			String::new()
+		} else {
+			let mut location = String::from(" at ");
+			for segment in &self.path {
+				write!(&mut location, "{}", segment).unwrap();
+			}
+			location
+		}
+	}
+}
+
+impl Display for PathSegment {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		match *self {
+			PathSegment::Index(i) => write!(f, "[{}]", i),
+			PathSegment::Key(ref key) => write!(f, "[{}]", key)
+		}
+	}
+}
+
+impl Message {
+	fn pack(val: &Val) -> GResult<Message> {
+		let mut packer = Packer {
+			nodes: Vec::new(),
+			visited: HashMap::new(),
+			path: Vec::new()
+		};
+
+		let val = packer.pack(val)?;
+		Ok(Message { val, nodes: packer.nodes })
+	}
+
+	//we allocate every arr, str and tab before filling any of them in, so that a Transfer::Node
+	//can refer to a node which appears later in the array
+	fn unpack(self) -> GResult<Val> {
+		let vals: Vec<Val> = self.nodes.iter().map(|node| {
+			match *node {
+				Node::Arr(ref elements) => Val::Arr(glsp::arr_with_capacity(elements.len())),
+				Node::Str(ref st) => Val::Str(glsp::str_from_rust_str(st)),
+				Node::Tab(ref entries) => Val::Tab(glsp::tab_with_capacity(entries.len()))
+			}
+		}).collect();
+
+		for (node, val) in self.nodes.iter().zip(vals.iter()) {
+			match (node, val) {
+				(Node::Arr(elements), Val::Arr(arr)) => {
+					for element in elements {
+						arr.push(unpack_transfer(element, &vals)?)?;
+					}
+				}
+				(Node::Tab(entries), Val::Tab(tab)) => {
+					for (key, value) in entries {
+						tab.set(unpack_transfer(key, &vals)?, unpack_transfer(value, &vals)?)?;
+					}
+				}
+				_ => ()
+			}
+		}
+
+		unpack_transfer(&self.val, &vals)
+	}
+}
+
+fn unpack_transfer(transfer: &Transfer, vals: &[Val]) -> GResult<Val> {
+	Ok(match *transfer {
+		Transfer::Nil => Val::Nil,
+		Transfer::Int(i) => Val::Int(i),
+		Transfer::Flo(f) => Val::Flo(f),
+		Transfer::Char(c) => Val::Char(c),
+		Transfer::Bool(b) => Val::Bool(b),
+		Transfer::Sym(ref name) => Val::Sym(glsp::sym(name)?),
+		Transfer::Node(index) => vals[index].clone()
+	})
+}
+
+//-------------------------------------------------------------------------------------------------
+// GSender, GReceiver
+//-------------------------------------------------------------------------------------------------
+
+/**
+Creates a channel for passing values from one [`Runtime`](struct.Runtime.html) to another.
+
+The [`GSender`](struct.GSender.html) and [`GReceiver`](struct.GReceiver.html) can be moved to
+any thread, or into any `Runtime`. They're usually wrapped in an [`RData`](struct.RData.html)
+and bound to a global variable, so that GameLisp code can call
+[`(send)`](https://gamelisp.rs/std/send) and [`(recv)`](https://gamelisp.rs/std/recv).
+
+	let (sender, receiver) = glsp::channel();
+
+	thread::spawn(move || {
+		let worker = Runtime::new();
+		worker.run(|| {
+			glsp::bind_global("results", glsp::rdata(sender)?)?;
+			glsp::load("worker.glsp")?;
+			Ok(())
+		});
+	});
+
+	runtime.run(|| {
+		let val: Root<Tab> = receiver.recv()?;
+		prn!("received {}", val);
+		Ok(())
+	});
+*/
+pub fn channel() -> (GSender, GReceiver) {
+	let (sender, receiver) = mpsc::channel();
+	(GSender(sender), GReceiver(receiver))
+}
+
+rdata! {
+	/**
+	The sending half of a [`glsp::channel`](fn.channel.html).
+
+	A `GSender` can be cloned, and it can be sent between threads.
+	*/
+	#[derive(Clone)]
+	pub struct GSender(Sender<Message>);
+}
+
+impl GSender {
+	/**
+	Copies a value into the channel.
+
+	Nil, bools, ints, flos, chars, symbols, arrays, strings and tables can be sent. Arrays,
+	strings and tables are copied recursively; the copy will share references in the same way
+	as the original, including any reference cycles. Symbols are sent by name. The copy is
+	never [frozen](https://gamelisp.rs/std/freeze-mut), even if the original was.
+
+	Returns an `Err` if the value contains anything else, such as a function or an object. The
+	error message describes the path to the offending element, like `[3][pos]`. Also returns
+	an `Err` if the `GReceiver` has been dropped.
+
+	Equivalent to [`(send sender val)`](https://gamelisp.rs/std/send).
+	*/
+	pub fn send<T: ToVal>(&self, val: T) -> GResult<()> {
+		let message = Message::pack(&val.to_val()?)?;
+		match self.0.send(message) {
+			Ok(()) => Ok(()),
+			Err(_) => bail!("unable to send a value: the channel's receiver has been dropped")
+		}
+	}
+}
+
+rdata! {
+	/**
+	The receiving half of a [`glsp::channel`](fn.channel.html).
+
+	A `GReceiver` can be sent between threads, but it can't be cloned.
+	*/
+	pub struct GReceiver(Receiver<Message>);
+}
+
+impl GReceiver {
+	/**
+	Blocks the current thread until a value is received, then allocates a copy of that value
+	in the active `Runtime` and converts it to `T`.
+
+	Returns an `Err` if every `GSender` for this channel has been dropped, or if the conversion
+	to `T` fails.
+
+	Equivalent to [`(recv receiver)`](https://gamelisp.rs/std/recv).
+	*/
+	pub fn recv<T: FromVal>(&self) -> GResult<T> {
+		match self.0.recv() {
+			Ok(message) => T::from_val(&message.unpack()?),
+			Err(RecvError) => bail!("unable to receive a value: the channel has been closed")
+		}
+	}
+
+	/**
+	Receives a value without blocking. Returns `None` if there are no values waiting in the
+	channel.
+
+	Returns an `Err` if the channel is empty and every `GSender` for this channel has been
+	dropped.
+
+	Equivalent to [`(try-recv receiver)`](https://gamelisp.rs/std/try-recv).
+	*/
+	pub fn try_recv<T: FromVal>(&self) -> GResult<Option<T>> {
+		match self.0.try_recv() {
+			Ok(message) => Ok(Some(T::from_val(&message.unpack()?)?)),
+			Err(TryRecvError::Empty) => Ok(None),
+			Err(TryRecvError::Disconnected) => {
+				Err(error!("unable to receive a value: the channel has been closed"))
+			}
+		}
+	}
+}
+
+fn send(sender: &GSender, val: Val) -> GResult<()> {
+	sender.send(val)
+}
+
+fn recv(receiver: &GReceiver) -> GResult<Val> {
+	receiver.recv()
+}
+
+fn try_recv(receiver: &GReceiver) -> GResult<Option<Val>> {
+	receiver.try_recv()
+}
+
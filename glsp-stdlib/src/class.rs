@@ -1,7 +1,7 @@
 use glsp::{
 	arr, Arr, bail, bail_at, Class, DequeAccess, DequeOps, 
 	ensure, ensure_at, EnvMode, Expander, Expansion, FromVal, GFn,
-	GResult, Obj, OrNil, rfn, Root, Span, Sym, stock_syms::*, Tab, Val
+	GResult, Obj, OrNil, Rec, rfn, Root, Span, Sym, stock_syms::*, Tab, Val
 };
 use glsp_proc_macros::{backquote};
 use smallvec::{SmallVec};
@@ -9,7 +9,8 @@ use std::{str};
 use std::collections::{HashMap, hash_map::Entry::{Occupied, Vacant}, HashSet, VecDeque};
 use std::io::{Write};
 use std::iter::{FromIterator};
-use super::{Std};
+use super::{bind_cap_rfn, bind_cap_rfn_macro, Caps, Std};
+use super::macros::{fn_params_index, split_doc};
 use super::pat::{
 	AssignStrategy, MismatchStrategy, Pat, pat_from_forms, PlaceStrategy, SetStrategy
 };
@@ -23,15 +24,24 @@ pub fn init(_sandboxed: bool) -> GResult<()> {
 	glsp::bind_rfn_macro("let-mixin", rfn!(let_mixin))?;
 	glsp::bind_rfn_macro("mixin", rfn!(mixin))?;
 
-	glsp::bind_rfn_macro("defclassmacro", rfn!(defclassmacro))?;
-	glsp::bind_rfn("bind-classmacro!", rfn!(bind_classmacro))?;
+	bind_cap_rfn_macro("defclassmacro", Caps::MACROS, rfn!(defclassmacro))?;
+	bind_cap_rfn("bind-classmacro!", Caps::MACROS, rfn!(bind_classmacro))?;
 
 	glsp::bind_rfn_macro("defstruct", rfn!(defstruct))?;
+	glsp::bind_rfn("%bind-struct-macro!", rfn!(bind_struct_macro))?;
 	glsp::bind_rfn("%struct-constructor-macro", rfn!(struct_constructor_macro))?;
 
+	glsp::bind_rfn_macro("defrecord", rfn!(defrecord))?;
+	glsp::bind_rfn("%define-rec!", rfn!(define_rec))?;
+	glsp::bind_rfn("%rec", rfn!(rec))?;
+	glsp::bind_rfn("%rec-get", rfn!(rec_get))?;
+	glsp::bind_rfn("%rec-is?", rfn!(rec_isp))?;
+	glsp::bind_rfn("rec-name", rfn!(rec_name))?;
+	glsp::bind_rfn("rec-fields", rfn!(rec_fields))?;
+
 	//todo: get rid of these
-	glsp::bind_rfn("%eval-as-method", rfn!(eval_as_method))?;
-	glsp::bind_rfn("%create-pseudo-method", rfn!(create_pseudo_method))?;
+	bind_cap_rfn("%eval-as-method", Caps::EVAL, rfn!(eval_as_method))?;
+	bind_cap_rfn("%create-pseudo-method", Caps::EVAL, rfn!(create_pseudo_method))?;
 
 	glsp::bind_rfn("call-meth", rfn!(call_meth))?;
 	glsp::bind_rfn("call-meth-opt", rfn!(call_meth_opt))?;
@@ -62,9 +72,16 @@ pub fn init(_sandboxed: bool) -> GResult<()> {
 //-------------------------------------------------------------------------------------------------
 
 fn defclass(name: Sym, clauses: &[Val]) -> Val {
+	//a leading str literal is the class' doc string
+	let (doc_clause, clauses) = match clauses {
+		[Val::Str(doc), rest @ ..] => (vec![backquote!("(doc ~doc)")], rest),
+		_ => (Vec::<Val>::new(), clauses)
+	};
+
 	backquote!(r#"
 		(def ~name (class
 		  (name ~name)
+		  ~..doc_clause
 		  ~..clauses))
 	"#)
 }
@@ -163,11 +180,13 @@ fn class_impl(std: &Std, clauses: &[Val], is_mixin: bool) -> GResult<Val> {
 	//that we perform syntax transformations on @forms within method bodies.
 	let output: Root<Arr> = backquote!("(tab)");
 
-	//if `name` or `mixins` are provided as leading forms, pop them and emit their initializers
+	//if `name`, `doc` or `mixins` are provided as leading forms, pop them and emit their
+	//initializers
 	let mut class_name: Option<Sym> = None;
+	let mut seen_doc = false;
 	let mut seen_mixins = false;
 
-	while expanded.len() > 0 && (class_name.is_none() || !seen_mixins) {
+	while expanded.len() > 0 && (class_name.is_none() || !seen_doc || !seen_mixins) {
 		let clause = expanded.front().unwrap().clone();
 		if let Val::Arr(ref arr) = clause {
 			if arr.len() > 0 && arr.get::<Val>(0)?.is_sym() {
@@ -188,6 +207,18 @@ fn class_impl(std: &Std, clauses: &[Val], is_mixin: bool) -> GResult<Val> {
 						let to_push: Val = backquote!("('name '~name_sym)");
 						output.push(to_push)?;
 					}
+					DOC_SYM => {
+						expanded.pop_front().unwrap();
+
+						ensure_at!(arr.span(), !seen_doc, "duplicate doc clause");
+						ensure_at!(arr.span(), arr.len() == 2 && arr.get::<Val>(1)?.is_str(),
+						           "invalid clause {}", arr);
+						seen_doc = true;
+
+						let doc: Val = arr.get(1)?;
+						let to_push: Val = backquote!("('doc ~doc)");
+						output.push(to_push)?;
+					}
 					MIXIN_SYM => {
 						expanded.pop_front().unwrap();
 
@@ -426,6 +457,8 @@ fn process_class_state(
 			                     "`name` class forms must only appear at the beginning"),
 			MIXIN_SYM => bail_at!(clause.span(),
 			                     "`mixin` class forms must only appear at the beginning"),
+			DOC_SYM => bail_at!(clause.span(),
+			                    "`doc` class forms must only appear at the beginning"),
 
 			FIELD_SYM => {
 				let args = SmallVec::<[Val; 8]>::from_iter(clause.iter().skip(1));
@@ -1051,7 +1084,16 @@ fn method_clause_to_form(
 		None => arr![]
 	};
 
-	let body = glsp::arr_from_iter(clause.iter().skip(body_start_i))?;
+	//a return-type annotation, `-> type`, may appear before the doc string. we pass it through to
+	//(%meth-fn) unchanged.
+	let mut body_forms = Vec::from_iter(clause.iter().skip(body_start_i));
+	let return_arrow = match body_forms.get(0) {
+		Some(&Val::Sym(ARROW_SYM)) => body_forms.drain(..2.min(body_forms.len())).collect(),
+		_ => Vec::new()
+	};
+
+	let (doc_flag, body_forms) = split_doc(&body_forms);
+	let body = glsp::arr_from_iter(body_forms.iter())?;
 
 	//generate a qualified name, to be passed in as the fn's &name flag
 	let qualified_name = if let Some(qualified_name) = qualified_name {
@@ -1135,13 +1177,45 @@ fn method_clause_to_form(
 
 	//macro-expand the method's (fn) form
 	let fn_form: Val = backquote!("
-		(%meth-fn &name ~qualified_name ~params_arr ~..body_init ~..body)
+		(%meth-fn &name ~qualified_name ~params_arr ~..return_arrow ~..body_init ~..body)
 	");
 	let expanded_fn = glsp::expand(&fn_form, Some(EnvMode::Copied))?.unwrap_arr();
-	let expanded_params_i: usize = expanded_fn.iter().position(|v| v.is_arr()).unwrap();
+	let expanded_forms = Vec::from_iter(expanded_fn.iter());
+	let expanded_params_i = fn_params_index(&expanded_forms[1..])? + 1;
 	let expanded_params_arr = expanded_fn.get::<Root<Arr>>(expanded_params_i)?;
 	let expanded_body = glsp::arr_from_iter(expanded_fn.iter().skip(expanded_params_i + 1))?;
 
+	//carry the parameter metadata over to the final (fn) form. the names and types need to 
+	//account for the "invisible" arguments, which are unannotated.
+	let mut meta_flags = Vec::new();
+	let mut flag_i = 1;
+	while flag_i < expanded_params_i {
+		match expanded_forms[flag_i] {
+			Val::Sym(FLAG_ARG_LIMITS_SYM) => flag_i += 3,
+			Val::Sym(flag @ FLAG_PARAM_NAMES_SYM) | Val::Sym(flag @ FLAG_PARAM_TYPES_SYM) => {
+				let original = Root::<Arr>::from_val(&expanded_forms[flag_i + 1])?;
+				let list = glsp::arr_from_iter(original.iter())?;
+				let blank = |name: Sym| {
+					if flag == FLAG_PARAM_NAMES_SYM { Val::Sym(name) } else { Val::Nil }
+				};
+
+				if let Some(base_index_name) = base_index_name {
+					list.push_start(blank(base_index_name))?;
+				}
+				list.push_start(blank(self_name))?;
+
+				meta_flags.push(Val::Sym(flag));
+				meta_flags.push(Val::Arr(list));
+				flag_i += 2;
+			}
+			Val::Sym(FLAG_RETURN_TYPE_SYM) => {
+				meta_flags.extend_from_slice(&expanded_forms[flag_i .. flag_i + 2]);
+				flag_i += 2;
+			}
+			_ => flag_i += 2
+		}
+	}
+
 	//transform any remaining @-forms in the body
 	fn recursively_transform(
 		form: Val, 
@@ -1355,7 +1429,10 @@ fn method_clause_to_form(
 	transformed_params_arr.push_start(self_name)?;
 
 	//finished!
-	Ok(backquote!("(fn &name ~qualified_name ~transformed_params_arr ~..transformed_body)"))
+	Ok(backquote!(r#"
+		(fn &name ~qualified_name ~..doc_flag ~..meta_flags ~transformed_params_arr 
+		    ~..transformed_body)
+	"#))
 }
 
 fn split_wrap_target(span: Span, target_name: Sym) -> GResult<(Sym, Sym)> {
@@ -1497,8 +1574,9 @@ fn bind_classmacro(std: &mut Std, name: Sym, gfn: Root<GFn>) -> GResult<()> {
 fn defstruct(name: Sym, clauses: &[Val]) -> GResult<Val> {
 	//input syntax: any number of bare syms, followed by any number of (meth) clauses, (prop) 
 	//clauses, and (const) clauses. we emit a (field) for each sym; an (init); and implementations
-	//for (meth op-eq? ...) and (meth op-clone ...) if they're not already present. we also
-	//bind Name:new to the class, and bind a constructor macro to the Name.
+	//for (meth op-eq? ...), (meth op-hash ...) and (meth op-clone ...) if they're not already
+	//present. (op-hash) is only generated alongside (op-eq?), so that the two are consistent. we
+	//also bind Name:new to the class, and bind a constructor macro to the Name.
 	let mut clause_stack = Vec::from_iter(clauses.iter().rev().cloned());
 
 	let mut field_names = Vec::<Sym>::new();
@@ -1513,8 +1591,10 @@ fn defstruct(name: Sym, clauses: &[Val]) -> GResult<Val> {
 		init_params.push(backquote!("@~field_name"));
 	}
 
-	//validate the remaining clauses, and check for (meth op-eq?) and (meth op-clone)
+	//validate the remaining clauses, and check for (meth op-eq?), (meth op-hash) and
+	//(meth op-clone)
 	let mut seen_eq = false;
+	let mut seen_hash = false;
 	let mut seen_clone = false;
 	for clause in clause_stack.iter().rev() {
 		match clause {
@@ -1528,6 +1608,7 @@ fn defstruct(name: Sym, clauses: &[Val]) -> GResult<Val> {
 							if arr.len() >= 2 {
 								match arr.get::<Val>(1)? {
 									Val::Sym(OP_EQP_SYM) => seen_eq = true,
+									Val::Sym(OP_HASH_SYM) => seen_hash = true,
 									Val::Sym(OP_CLONE_SYM) => seen_clone = true,
 									_ => ()
 								}
@@ -1550,7 +1631,14 @@ fn defstruct(name: Sym, clauses: &[Val]) -> GResult<Val> {
 	let name_new = glsp::sym(&format!("{}:new", name))?;
 	let namep = glsp::sym(&format!("{}?", name))?;
 
-	//emit op-eq? and op-clone methods
+	//emit op-eq?, op-hash and op-clone methods
+	if !seen_eq && !seen_hash {
+		clause_stack.push(backquote!(r#"
+			(meth op-hash ()
+			  (hash (arr '~name ~..init_params)))
+		"#));
+	}
+
 	if !seen_eq {
 		let other_name = glsp::gensym_with_tag("other")?;
 
@@ -1575,6 +1663,8 @@ fn defstruct(name: Sym, clauses: &[Val]) -> GResult<Val> {
 	//bring it all together
 	clause_stack.reverse();
 
+	let macro_name = glsp::qualify_definition(name)?;
+
 	Ok(backquote!(r#"
 		(do
 		  (def ~name (class
@@ -1588,11 +1678,28 @@ fn defstruct(name: Sym, clauses: &[Val]) -> GResult<Val> {
   		  (defn ~namep (any)
   		    (is? any ~name))
   
-  		  (defmacro ~name (..args)
-		    (%struct-constructor-macro '~name '~name_new '(~..field_names) ..args)))
+  		  (%bind-struct-macro! '~macro_name '~name '~name_new '(~..field_names)))
 	"#))
 }
 
+//defstruct can't simply emit a (defmacro) form, because it would fail when the MACROS capability
+//has been denied. the constructor macro's body is fixed, so it's safe to bind it from here.
+fn bind_struct_macro(
+	macro_name: Sym,
+	name: Sym,
+	name_new: Sym,
+	field_names: Root<Arr>
+) -> GResult<()> {
+
+	let fn_form = backquote!(r#"
+		(fn &name ~macro_name (..args)
+		  (%struct-constructor-macro '~name '~name_new '~field_names ..args))
+	"#);
+
+	let gfn = glsp::eval(&fn_form, None)?.unwrap_gfn();
+	glsp::bind_macro(macro_name, Expander::GFn(gfn))
+}
+
 fn struct_constructor_macro(
 	name: Sym,
 	name_new: Sym,
@@ -1683,6 +1790,97 @@ fn struct_constructor_macro(
 }
 
 
+//-------------------------------------------------------------------------------------------------
+// records
+//-------------------------------------------------------------------------------------------------
+
+fn defrecord(name: Sym, fields: &[Val]) -> GResult<Val> {
+	//we register the rec type, then emit a positional constructor Name:new, a predicate Name?,
+	//an accessor Name:field for each field, and the same constructor macro used by defstruct.
+	//each accessor knows its field's index, so it doesn't need to search for the field by name.
+	let mut field_names = Vec::<Sym>::with_capacity(fields.len());
+	for field in fields {
+		match *field {
+			Val::Sym(field_name) => {
+				ensure!(!field_names.contains(&field_name), 
+				        "duplicate field {} in defrecord {}", field_name, name);
+				field_names.push(field_name);
+			}
+			ref val => bail!("invalid defrecord field: expected a sym, received {}", 
+			                 val.a_type_name())
+		}
+	}
+
+	let name_new = glsp::sym(&format!("{}:new", name))?;
+	let namep = glsp::sym(&format!("{}?", name))?;
+
+	let mut accessors = Vec::<Val>::with_capacity(field_names.len());
+	for (i, &field_name) in field_names.iter().enumerate() {
+		let accessor_name = glsp::sym(&format!("{}:{}", name, field_name))?;
+		accessors.push(backquote!(r#"
+			(defn ~accessor_name (rc)
+			  (%rec-get rc '~name ~i '~field_name))
+		"#));
+	}
+
+	let macro_name = glsp::qualify_definition(name)?;
+
+	Ok(backquote!(r#"
+		(do
+		  (%define-rec! '~name '(~..field_names))
+
+		  (defn ~name_new (~..field_names)
+		    (%rec '~name ~..field_names))
+
+		  (defn ~namep (any)
+		    (%rec-is? any '~name))
+
+		  ~..accessors
+
+		  (%bind-struct-macro! '~macro_name '~name '~name_new '(~..field_names)))
+	"#))
+}
+
+fn define_rec(name: Sym, field_names: Vec<Sym>) -> GResult<()> {
+	glsp::define_rec(name, &field_names)
+}
+
+fn rec(name: Sym, args: &[Val]) -> GResult<Root<Rec>> {
+	glsp::rec(name, args.iter())
+}
+
+fn rec_get(arg: Val, name: Sym, i: usize, field_name: Sym) -> GResult<Val> {
+	match arg {
+		Val::Rec(rec) if rec.name() == name => {
+			//a rec which was constructed before its type was redefined may have a different
+			//layout, in which case we fall back to searching for the field
+			if rec.field_names().get(i) == Some(&field_name) {
+				rec.get_index(i)
+			} else {
+				rec.get(field_name)
+			}
+		}
+		Val::Rec(rec) => bail!("expected a {} rec, received a {} rec", name, rec.name()),
+		val => bail!("expected a {} rec, received {}", name, val.a_type_name())
+	}
+}
+
+fn rec_isp(arg: Val, name: Sym) -> bool {
+	match arg {
+		Val::Rec(rec) => rec.name() == name,
+		_ => false
+	}
+}
+
+fn rec_name(rec: &Rec) -> Sym {
+	rec.name()
+}
+
+fn rec_fields(rec: &Rec) -> GResult<Root<Arr>> {
+	glsp::arr_from_iter(rec.field_names().iter().cloned())
+}
+
+
 //-------------------------------------------------------------------------------------------------
 // class-related rfns
 //-------------------------------------------------------------------------------------------------
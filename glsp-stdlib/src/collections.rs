@@ -1,7 +1,8 @@
 use glsp::{
 	Arr, bail, Callable, Class, Deque, DequeAccess, DequeAccessRange, DequeOps, ensure, 
-	EprWriter, error, FromVal, GError, GIterLen, GResult, Iterable, IterableOps, Obj,
-	OrNil, Parser, PrWriter, rfn, RData, Root, stock_syms::*, Str, Tab, ToVal, Val
+	EprWriter, FromVal, GError, GIter, GIterLen, GResult, Iterable, IterableOps, Obj,
+	OrNil, Parser, PrintLevel, PrWriter, rfn, RData, Root, stock_syms::*, Str, Sym, Tab, ToVal,
+	Val
 };
 use glsp_proc_macros::{backquote};
 use smallvec::{SmallVec};
@@ -23,6 +24,8 @@ pub fn init(_sandboxed: bool) -> GResult<()> {
 	glsp::bind_rfn("access-slice", rfn!(access_slice))?;
 	glsp::bind_rfn("access-slice=", rfn!(set_access_slice))?;
 	glsp::bind_rfn("has?", rfn!(hasp))?;
+	glsp::bind_rfn("get-in", rfn!(get_in))?;
+	glsp::bind_rfn("set-in!", rfn!(set_in))?;
 	glsp::bind_rfn("remove!", rfn!(remove))?;
 	glsp::bind_rfn("remove-opt!", rfn!(remove_opt))?;
 	glsp::bind_rfn("remove-slice!", rfn!(remove_slice))?;
@@ -45,6 +48,7 @@ pub fn init(_sandboxed: bool) -> GResult<()> {
 	glsp::bind_rfn("shrink!", rfn!(shrink))?;
 	glsp::bind_rfn("sort", rfn!(sort))?;
 	glsp::bind_rfn("sort!", rfn!(sort_mut))?;
+	glsp::bind_rfn("bsearch", rfn!(bsearch))?;
 	glsp::bind_rfn("starts-with?", rfn!(starts_withp))?;
 	glsp::bind_rfn("ends-with?", rfn!(ends_withp))?;
 	glsp::bind_rfn("position", rfn!(position))?;
@@ -57,6 +61,8 @@ pub fn init(_sandboxed: bool) -> GResult<()> {
 	//string apis
 	glsp::bind_rfn("str", rfn!(str))?;
 	glsp::bind_rfn("template-str", rfn!(template_str))?;
+	glsp::bind_rfn("intern", rfn!(intern))?;
+	glsp::bind_rfn("interned?", rfn!(internedp))?;
 	glsp::bind_rfn("pretty-str", rfn!(pretty_str))?;
 	glsp::bind_rfn("parse", rfn!(parse))?;
 	glsp::bind_rfn("parse-all", rfn!(parse_all))?;
@@ -69,20 +75,36 @@ pub fn init(_sandboxed: bool) -> GResult<()> {
 	glsp::bind_rfn("epr", rfn!(epr))?;
 	glsp::bind_rfn("eprn", rfn!(eprn))?;
 	glsp::bind_rfn("pretty-eprn", rfn!(pretty_eprn))?;
+	glsp::bind_rfn("log-msg", rfn!(log_msg))?;
+	glsp::bind_rfn("log-level", rfn!(log_level))?;
+	glsp::bind_rfn("log-level=", rfn!(set_log_level))?;
 	glsp::bind_rfn("uppercase", rfn!(uppercase))?;
 	glsp::bind_rfn("lowercase", rfn!(lowercase))?;
+	glsp::bind_rfn("upcase-char", rfn!(upcase_char))?;
+	glsp::bind_rfn("downcase-char", rfn!(downcase_char))?;
+	glsp::bind_rfn("digit-value", rfn!(digit_value))?;
+	glsp::bind_rfn("chars", rfn!(chars))?;
 	glsp::bind_rfn("replace", rfn!(replace))?;
 	glsp::bind_rfn("trim", rfn!(trim))?;
 	glsp::bind_rfn("trim-start", rfn!(trim_start))?;
 	glsp::bind_rfn("trim-end", rfn!(trim_end))?;
+	glsp::bind_rfn("repeat-str", rfn!(repeat_str))?;
 	glsp::bind_rfn("pad", rfn!(pad))?;
 	glsp::bind_rfn("pad-start", rfn!(pad_start))?;
 	glsp::bind_rfn("pad-end", rfn!(pad_end))?;
 	glsp::bind_rfn("whitespace?", rfn!(whitespacep))?;
+	glsp::bind_rfn("alpha?", rfn!(alphap))?;
+	glsp::bind_rfn("digit?", rfn!(digitp))?;
+	glsp::bind_rfn("upper?", rfn!(upperp))?;
+	glsp::bind_rfn("lower?", rfn!(lowerp))?;
 	glsp::bind_rfn("contains?", rfn!(containsp))?;
 
 	//table apis
 	glsp::bind_rfn("tab", rfn!(tab))?;
+	glsp::bind_rfn("tab-with-default", rfn!(tab_with_default))?;
+	glsp::bind_rfn("tab-default", rfn!(tab_default))?;
+	glsp::bind_rfn("weak-key-tab", rfn!(weak_key_tab))?;
+	glsp::bind_rfn("weak-value-tab", rfn!(weak_value_tab))?;
 	glsp::bind_rfn("extend!", rfn!(extend))?;
 
 	Ok(())
@@ -96,6 +118,11 @@ fn len(arg: Val) -> GResult<Val> {
 		Val::GIter(giter) => {
 			return giter.len().to_val()
 		}
+		Val::PArr(parr) => parr.len(),
+		Val::PTab(ptab) => ptab.len(),
+		Val::View(view) if view.index().is_none() => view.len(),
+		Val::Vec2(v) => return Ok(Val::Flo(v.magnitude())),
+		Val::Vec3(v) => return Ok(Val::Flo(v.magnitude())),
 		arg => bail!("argument is {} rather than an arr, str or tab", arg.a_type_name())
 	};
 
@@ -108,7 +135,9 @@ fn emptyp(any: Val) -> GResult<bool> {
 		Val::Str(st) => Ok(st.len() == 0),
 		Val::Tab(tab) => Ok(tab.len() == 0),
 		Val::GIter(giter) => Ok(giter.len() == GIterLen::Exact(0)),
-		_ => bail!("expected an arr, str, tab or iter")
+		Val::PArr(parr) => Ok(parr.is_empty()),
+		Val::PTab(ptab) => Ok(ptab.is_empty()),
+		_ => bail!("expected an arr, str, tab, iter, parr or ptab")
 	}
 }
 
@@ -151,10 +180,49 @@ fn hasp(arg: Val, key: Val) -> GResult<bool> {
 				_ => Ok(false)
 			}
 		}
+		Val::Rec(rec) => {
+			match key {
+				Val::Sym(key_name) => Ok(rec.has(key_name)?),
+				_ => Ok(false)
+			}
+		}
+		Val::PArr(parr) => {
+			if let Val::Int(i) = key {
+				Ok(i < parr.len() as i32 && i >= -(parr.len() as i32))
+			} else {
+				Ok(false)
+			}
+		}
+		Val::PTab(ptab) => Ok(ptab.has(&key)?),
+		Val::View(view) => {
+			match key {
+				Val::Int(i) if view.index().is_none() => {
+					Ok(i < view.len() as i32 && i >= -(view.len() as i32))
+				}
+				Val::Sym(key_name) => Ok(view.has(key_name)?),
+				_ => Ok(false)
+			}
+		}
+		Val::Vec2(v) => Ok(v.has(&key)),
+		Val::Vec3(v) => Ok(v.has(&key)),
+		Val::Mat3(m) => Ok(m.has(&key)),
 		_ => Ok(false)
 	}
 }
 
+fn get_in(coll: Val, path: &Arr, default: Option<Val>) -> GResult<Val> {
+	let path = path.iter().collect::<Vec<Val>>();
+	match glsp::get_in(&coll, &path)? {
+		Some(val) => Ok(val),
+		None => Ok(default.unwrap_or(Val::Nil))
+	}
+}
+
+fn set_in(coll: Val, path: &Arr, val: Val) -> GResult<()> {
+	let path = path.iter().collect::<Vec<Val>>();
+	glsp::set_in(&coll, &path, &val)
+}
+
 fn remove(coll: Val, key: Val) -> GResult<Val> {
 	match coll {
 		Val::Arr(arr) => {
@@ -306,6 +374,13 @@ fn access(coll: Val, index: Val) -> GResult<Val> {
 		Val::RData(rdata) => {
 			match index {
 				Val::Sym(key_name) => rdata.get(key_name),
+				Val::Int(i) => {
+					match rdata.call_if_present(OP_ACCESS_SYM, &[i])? {
+						Some(val) => Ok(val),
+						None => bail!("indexed an rdata with an int, but {} has no op-access \
+						               meth", rdata.class_name())
+					}
+				}
 				Val::Arr(src) => Ok(Val::GIter(RData::access_giter(&rdata, &src.giter()))),
 				Val::Str(src) => Ok(Val::GIter(RData::access_giter(&rdata, &src.giter()))),
 				Val::Tab(src) => Ok(Val::GIter(RData::access_giter(&rdata, &src.giter()))),
@@ -325,6 +400,29 @@ fn access(coll: Val, index: Val) -> GResult<Val> {
 				index => bail!("attempted to index a class with {}", index.a_type_name())
 			}
 		}
+		Val::Rec(rec) => {
+			match index {
+				Val::Sym(key_name) => rec.get(key_name),
+				index => bail!("attempted to index a rec with {}", index.a_type_name())
+			}
+		}
+		Val::PArr(parr) => {
+			match index {
+				Val::Int(i) => parr.get(i as isize),
+				index => bail!("attempted to index a parr with {}", index.a_type_name())
+			}
+		}
+		Val::PTab(ptab) => ptab.get(index),
+		Val::View(view) => {
+			match index {
+				Val::Int(i) => Ok(Val::View(view.element(i as isize)?)),
+				Val::Sym(key_name) => view.get(key_name),
+				index => bail!("attempted to index a view with {}", index.a_type_name())
+			}
+		}
+		Val::Vec2(v) => Ok(Val::Flo(v.get(&index)?)),
+		Val::Vec3(v) => Ok(Val::Flo(v.get(&index)?)),
+		Val::Mat3(m) => Ok(Val::Vec3(m.get(&index)?)),
 		val => bail!("attempted to index {}", val.a_type_name())
 	}
 }
@@ -355,9 +453,32 @@ fn set_access(coll: Val, index: Val, new_value: Val) -> GResult<()> {
 		Val::RData(rdata) => {
 			match index {
 				Val::Sym(key_name) => rdata.set(key_name, new_value),
+				Val::Int(i) => {
+					let args = [Val::Int(i), new_value];
+					match rdata.call_if_present::<_, _, Val>(OP_SET_ACCESS_SYM, &args[..])? {
+						Some(_) => Ok(()),
+						None => bail!("indexed an rdata with an int, but {} has no \
+						               op-set-access meth", rdata.class_name())
+					}
+				}
 				index => bail!("attempted to index an rdata with {}", index.a_type_name())
 			}
 		}
+		Val::Rec(rec) => {
+			match index {
+				Val::Sym(key_name) => rec.set(key_name, new_value),
+				index => bail!("attempted to index a rec with {}", index.a_type_name())
+			}
+		}
+		Val::View(view) => {
+			match index {
+				Val::Sym(key_name) => view.set(key_name, new_value),
+				index => bail!("attempted to index a view with {}", index.a_type_name())
+			}
+		}
+		Val::Vec2(_) | Val::Vec3(_) | Val::Mat3(_) => {
+			bail!("attempted to mutate {}, which is immutable", coll.a_type_name())
+		}
 		val => bail!("attempted to index {} for mutation", val.a_type_name())
 	}
 }
@@ -409,6 +530,40 @@ fn access_opt(coll: Val, index: Val) -> GResult<Option<Val>> {
 				_ => Ok(None)
 			}
 		}
+		Val::Rec(rec) => {
+			match index {
+				Val::Sym(key_name) => rec.get_if_present(key_name),
+				_ => Ok(None)
+			}
+		}
+		Val::PArr(parr) => {
+			match index {
+				Val::Int(i) if i < parr.len() as i32 && i >= -(parr.len() as i32) => {
+					Ok(Some(parr.get(i as isize)?))
+				}
+				_ => Ok(None)
+			}
+		}
+		Val::PTab(ptab) => {
+			ptab.get_if_present(index)
+		}
+		Val::View(view) => {
+			match index {
+				Val::Int(i) if view.index().is_none() => {
+					if i < view.len() as i32 && i >= -(view.len() as i32) {
+						Ok(Some(Val::View(view.element(i as isize)?)))
+					} else {
+						Ok(None)
+					}
+				}
+				Val::Sym(key_name) if view.has(key_name)? => Ok(Some(view.get(key_name)?)),
+				_ => Ok(None)
+			}
+		}
+		Val::Vec2(v) if v.has(&index) => Ok(Some(Val::Flo(v.get(&index)?))),
+		Val::Vec3(v) if v.has(&index) => Ok(Some(Val::Flo(v.get(&index)?))),
+		Val::Mat3(m) if m.has(&index) => Ok(Some(Val::Vec3(m.get(&index)?))),
+		Val::Vec2(_) | Val::Vec3(_) | Val::Mat3(_) => Ok(None),
 		val => bail!("attempted to index {}", val.a_type_name())
 	}
 }
@@ -442,6 +597,21 @@ fn set_access_opt(coll: Val, index: Val, new_value: Val) -> GResult<()> {
 				rdata.set_if_present(key_name, new_value)?;
 			}
 		}
+		Val::Rec(rec) => {
+			if let Val::Sym(key_name) = index {
+				rec.set_if_present(key_name, new_value)?;
+			}
+		}
+		Val::View(view) => {
+			if let Val::Sym(key_name) = index {
+				if view.has(key_name)? {
+					view.set(key_name, new_value)?;
+				}
+			}
+		}
+		Val::Vec2(_) | Val::Vec3(_) | Val::Mat3(_) => {
+			bail!("attempted to mutate {}, which is immutable", coll.a_type_name())
+		}
 		val => bail!("attempted to index {} for mutation", val.a_type_name())
 	}
 
@@ -547,7 +717,9 @@ fn set_access_slice(
 		}
 		Iterable::Tab(tab) => Some(tab.giter()),
 		Iterable::Coro(coro) => Some(coro.giter()),
-		Iterable::GIter(giter) => Some(giter)
+		Iterable::GIter(giter) => Some(giter),
+		Iterable::PArr(parr) => Some(parr.giter()),
+		Iterable::PTab(ptab) => Some(ptab.giter())
 	};
 
 	if let Some(giter) = giter {
@@ -633,13 +805,29 @@ fn map_syntax(callable: Callable, coll: Val) -> GResult<Val> {
 	}
 }
 
-fn sort(deq: Deque, ord: Callable) -> GResult<Deque> {
+//calls an `ordf` passed to sort, sort! or bsearch. when no `ordf` is passed, values are compared
+//using (ord), which respects op-ord methods
+fn call_ordf(ordf: &Option<Callable>, a: &Val, b: &Val) -> GResult<Ordering> {
+	let ordf = match *ordf {
+		Some(ref ordf) => ordf,
+		None => return a.try_cmp(b)
+	};
+
+	match glsp::call(ordf, &[a, b])? {
+		Val::Sym(LT_SYM) => Ok(Ordering::Less),
+		Val::Sym(NUM_EQ_SYM) => Ok(Ordering::Equal),
+		Val::Sym(GT_SYM) => Ok(Ordering::Greater),
+		result => bail!("expected <, == or >, received {}", result)
+	}
+}
+
+fn sort(deq: Deque, ord: Option<Callable>) -> GResult<Deque> {
 	let cloned = deq.shallow_clone();
 	sort_mut(cloned.clone(), ord)?;
 	Ok(cloned)
 }
 
-fn sort_mut(deq: Deque, ord: Callable) -> GResult<()> {
+fn sort_mut(deq: Deque, ord: Option<Callable>) -> GResult<()> {
 	//rust's built-in sort_by() can only sort a slice
 	let mut vec = SmallVec::<[Val; 32]>::from_iter(deq.iter());
 
@@ -653,14 +841,8 @@ fn sort_mut(deq: Deque, ord: Callable) -> GResult<()> {
 			return Ordering::Equal
 		}
 
-		match glsp::call(&ord, &[a.clone(), b.clone()]) {
-			Ok(Val::Sym(LT_SYM)) => Ordering::Less,
-			Ok(Val::Sym(NUM_EQ_SYM)) => Ordering::Equal,
-			Ok(Val::Sym(GT_SYM)) => Ordering::Greater,
-			Ok(result) => {
-				error = Some(error!("expected <, = or >, received {}", result));
-				Ordering::Equal
-			}
+		match call_ordf(&ord, a, b) {
+			Ok(ordering) => ordering,
 			Err(err) => {
 				error = Some(err);
 				Ordering::Equal
@@ -681,6 +863,22 @@ fn sort_mut(deq: Deque, ord: Callable) -> GResult<()> {
 	}
 }
 
+fn bsearch(deq: Deque, target: Val, ord: Option<Callable>) -> GResult<Option<usize>> {
+	let mut low = 0;
+	let mut high = deq.len();
+
+	while low < high {
+		let mid = low + (high - low) / 2;
+		match call_ordf(&ord, &deq.get::<Val>(mid)?, &target)? {
+			Ordering::Less => low = mid + 1,
+			Ordering::Greater => high = mid,
+			Ordering::Equal => return Ok(Some(mid))
+		}
+	}
+
+	Ok(None)
+}
+
 fn starts_withp(deq: Deque, prefix: Deque) -> GResult<bool> {
 	if deq.len() < prefix.len() {
 		return Ok(false)
@@ -775,7 +973,7 @@ fn position_impl(
 					while i != to {
 						let mut matches = false;
 
-						if i <= (st.len() - needle.len()) as isize {
+						if i + needle.len() as isize <= st.len() as isize {
 							matches = true;
 							for j in 0 .. needle.len() as isize {
 								if st.get::<char>(i+j).unwrap() != needle.get::<char>(j).unwrap() {
@@ -989,6 +1187,14 @@ fn template_str(args: &[Val]) -> Root<Str> {
 	st
 }
 
+fn intern(st: Root<Str>) -> Root<Str> {
+	glsp::intern(&st)
+}
+
+fn internedp(st: Root<Str>) -> bool {
+	st.is_interned()
+}
+
 fn pretty_str(arg: Val) -> Root<Str> {
 	use std::fmt::Write;
 
@@ -1110,6 +1316,40 @@ fn pretty_eprn(arg: Val) {
 	writeln!(EprWriter, "{:#}", arg).ok();
 }
 
+fn log_msg(level: Sym, args: &[Val]) -> GResult<()> {
+	let level = sym_to_print_level(level)?;
+
+	let mut builder = String::new();
+	build_msg(&mut builder, args, true).ok();
+	glsp::log(level, &builder);
+
+	Ok(())
+}
+
+fn log_level() -> Sym {
+	match glsp::log_level() {
+		PrintLevel::Trace => TRACE_SYM,
+		PrintLevel::Print => PRINT_SYM,
+		PrintLevel::Warn => WARN_SYM,
+		PrintLevel::Error => ERROR_SYM
+	}
+}
+
+fn set_log_level(level: Sym) -> GResult<()> {
+	glsp::set_log_level(sym_to_print_level(level)?);
+	Ok(())
+}
+
+fn sym_to_print_level(sym: Sym) -> GResult<PrintLevel> {
+	Ok(match sym {
+		TRACE_SYM => PrintLevel::Trace,
+		PRINT_SYM => PrintLevel::Print,
+		WARN_SYM => PrintLevel::Warn,
+		ERROR_SYM => PrintLevel::Error,
+		sym => bail!("expected 'trace, 'print, 'warn or 'error, received {}", sym)
+	})
+}
+
 fn uppercase(st: &Str) -> GResult<Root<Str>> {
 	glsp::str_from_iter(st.iter().map(char::to_uppercase).flatten())
 }
@@ -1118,41 +1358,40 @@ fn lowercase(st: &Str) -> GResult<Root<Str>> {
 	glsp::str_from_iter(st.iter().map(char::to_lowercase).flatten())
 }
 
-fn replace(st: &Str, before: &Str, after: &Str) -> GResult<Root<Str>> {
-	ensure!(before.len() > 0, "'before' string must not be empty");
-
-	if before.len() > st.len() {
-		return Ok(st.shallow_clone())
+//when a char's uppercase or lowercase form has more than one char, like `ß` or `İ`, the char is
+//returned unchanged
+fn convert_case<I>(ch: char, convert: fn(char) -> I) -> char 
+where
+	I: Iterator<Item = char>
+{
+	let mut converted = convert(ch);
+	match (converted.next(), converted.next()) {
+		(Some(single), None) => single,
+		_ => ch
 	}
+}
 
-	let result = glsp::str();
+fn upcase_char(ch: char) -> char {
+	convert_case(ch, char::to_uppercase)
+}
 
-	let mut start = 0;
-	while start < st.len() - before.len() {
-		let mut matches = true;
-		for i in 0 .. before.len() {
-			if st.get::<char>(start + i).unwrap() != before.get::<char>(i).unwrap() {
-				matches = false;
-				break
-			}
-		}
+fn downcase_char(ch: char) -> char {
+	convert_case(ch, char::to_lowercase)
+}
 
-		if !matches {
-			result.push(st.get::<char>(start).unwrap()).unwrap();
-			start += 1;
-		} else {
-			for ch in after.iter() {
-				result.push(ch).unwrap();
-			}
-			start += before.len();
-		}
-	}
+fn digit_value(ch: char, radix: Option<u32>) -> GResult<Option<i32>> {
+	let radix = radix.unwrap_or(10);
+	ensure!(radix >= 2 && radix <= 36, "invalid radix {}", radix);
 
-	for i in start .. st.len() {
-		result.push(st.get::<char>(i).unwrap()).unwrap();
-	}
+	Ok(ch.to_digit(radix).map(|digit| digit as i32))
+}
 
-	Ok(result)
+fn chars(st: Root<Str>) -> Root<GIter> {
+	st.giter()
+}
+
+fn replace(st: &Str, before: &Str, after: &Str) -> GResult<Root<Str>> {
+	st.replace(before, after)
 }
 
 fn trim_impl(st: &Str, start: bool, end: bool, to_trim: Option<&Str>) -> GResult<Root<Str>> {
@@ -1221,37 +1460,51 @@ fn pad_end(st: &Str, len: usize, ch: Option<char>) -> GResult<Root<Str>> {
 	glsp::str_from_iter(st.iter().chain(repeat(ch).take(to_pad)))
 }
 
-fn whitespacep(val: Val) -> GResult<bool> {
+fn char_predicate(val: Val, predicate: fn(char) -> bool) -> GResult<bool> {
 	match val {
-		Val::Char(ch) => Ok(ch.is_whitespace()),
-		Val::Str(st) => Ok(st.iter().all(char::is_whitespace)),
+		Val::Char(ch) => Ok(predicate(ch)),
+		Val::Str(st) => Ok(st.iter().all(predicate)),
 		val => bail!("expected a str or char, received {}", val.a_type_name())
 	}
 }
 
+fn whitespacep(val: Val) -> GResult<bool> {
+	char_predicate(val, char::is_whitespace)
+}
+
+fn alphap(val: Val) -> GResult<bool> {
+	char_predicate(val, char::is_alphabetic)
+}
+
+fn digitp(val: Val) -> GResult<bool> {
+	char_predicate(val, char::is_numeric)
+}
+
+fn upperp(val: Val) -> GResult<bool> {
+	char_predicate(val, char::is_uppercase)
+}
+
+fn lowerp(val: Val) -> GResult<bool> {
+	char_predicate(val, char::is_lowercase)
+}
+
 fn containsp(haystack: &Str, needle: Val) -> GResult<bool> {
-	let mut needle_chars = SmallVec::<[char; 32]>::new();
 	match needle {
-		Val::Char(ch) => needle_chars.push(ch),
-		Val::Str(st) => needle_chars.extend(st.iter()),
+		Val::Char(ch) => Ok(haystack.iter().any(|st_ch| st_ch == ch)),
+		Val::Str(st) => Ok(haystack.contains_str(&st)),
 		val => bail!("expected a char or str, received {}", val.a_type_name())
 	}
+}
 
-	if haystack.len() < needle_chars.len() {
-		return Ok(false)
-	}
-
-	'outer: for start in 0 .. haystack.len() - needle_chars.len() {
-		for i in 0 .. needle_chars.len() {
-			if haystack.get::<char>(start + i).unwrap() != needle_chars[i] {
-				continue 'outer
-			}
+fn repeat_str(st: &Str, count: usize) -> GResult<Root<Str>> {
+	let result = glsp::str_with_capacity(st.len().saturating_mul(count));
+	for _ in 0 .. count {
+		for ch in st.iter() {
+			result.push(ch)?;
 		}
-
-		return Ok(true)
 	}
 
-	Ok(false)
+	Ok(result)
 }
 
 fn tab(entries: &[(Val, Val)]) -> GResult<Root<Tab>> {
@@ -1264,6 +1517,40 @@ fn tab(entries: &[(Val, Val)]) -> GResult<Root<Tab>> {
 	Ok(tab)
 }
 
+fn tab_with_default(default: Val, entries: &[(Val, Val)]) -> GResult<Root<Tab>> {
+	let tab = glsp::tab_with_default(default)?;
+
+	for &(ref key, ref val) in entries {
+		tab.set(key, val)?;
+	}
+
+	Ok(tab)
+}
+
+fn tab_default(tab: &Tab) -> Option<Val> {
+	tab.default_value()
+}
+
+fn weak_key_tab(entries: &[(Val, Val)]) -> GResult<Root<Tab>> {
+	let tab = glsp::weak_key_tab();
+
+	for &(ref key, ref val) in entries {
+		tab.set(key, val)?;
+	}
+
+	Ok(tab)
+}
+
+fn weak_value_tab(entries: &[(Val, Val)]) -> GResult<Root<Tab>> {
+	let tab = glsp::weak_value_tab();
+
+	for &(ref key, ref val) in entries {
+		tab.set(key, val)?;
+	}
+
+	Ok(tab)
+}
+
 fn extend(tab: Root<Tab>, entries: &[(Val, Val)]) -> GResult<()> {
 	ensure!(tab.can_mutate(), "attempted to mutate an immutable tab");
 
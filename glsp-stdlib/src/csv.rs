@@ -0,0 +1,511 @@
+use glsp::{
+	bail, Coro, DequeOps, ensure, error, FromVal, GError, GIter, GResult, Iterable, IterableOps, KwArgs,
+	rdata, rfn, Root, Sym, Tab, Val
+};
+use glsp_proc_macros::{backquote};
+use std::mem;
+
+pub fn init(_sandboxed: bool) -> GResult<()> {
+	glsp::bind_rfn("csv-parse", rfn!(csv_parse_rfn))?;
+	glsp::bind_rfn("csv-parse-lines", rfn!(csv_parse_lines_rfn))?;
+	glsp::bind_rfn("csv-emit", rfn!(csv_emit_rfn))?;
+	glsp::bind_rfn("%csv-next", rfn!(csv_next))?;
+
+	Ok(())
+}
+
+/*
+a minimal parser for the csv dialect described by rfc 4180, which is what spreadsheets export.
+
+fields are separated by a delimiter, usually a comma. a field which contains the delimiter, a
+double-quote or a line break must be wrapped in double-quotes, with any double-quotes inside it
+doubled. we're strict about everything else: a quote in the middle of an unquoted field, or any
+text between a closing quote and the next delimiter, is an error rather than being guessed at.
+
+the parser consumes lines, rather than chars, so that (csv-parse-lines) can be fed by
+(read-lines). a quoted field which spans several lines has its line breaks normalized to "\n".
+blank lines between records are skipped.
+*/
+
+/**
+The options for [`glsp::csv_parse`](fn.csv_parse.html).
+
+	let opts = CsvOpts {
+		delimiter: ';',
+		headers: true
+	};
+*/
+
+#[derive(Copy, Clone, Debug)]
+pub struct CsvOpts {
+	/**
+	The character which separates fields. Defaults to `,`. It can't be a double-quote or a
+	line break.
+	*/
+	pub delimiter: char,
+
+	/**
+	If `true`, the first record is a header row. It isn't produced as a row; instead, each
+	subsequent record is produced as a table which maps each header, converted to a symbol,
+	to the corresponding field.
+	*/
+	pub headers: bool
+}
+
+impl Default for CsvOpts {
+	fn default() -> CsvOpts {
+		CsvOpts {
+			delimiter: ',',
+			headers: false
+		}
+	}
+}
+
+fn check_delimiter(delimiter: char) -> GResult<()> {
+	ensure!(delimiter != '"' && delimiter != '\n' && delimiter != '\r',
+	        "{:?} can't be used as a CSV delimiter", delimiter);
+	Ok(())
+}
+
+//-------------------------------------------------------------------------------------------------
+// parsing
+//-------------------------------------------------------------------------------------------------
+
+struct CsvParser {
+	delimiter: char,
+	record: usize //the number of records parsed so far, including the header row
+}
+
+impl CsvParser {
+	fn new(delimiter: char) -> GResult<CsvParser> {
+		check_delimiter(delimiter)?;
+
+		Ok(CsvParser {
+			delimiter,
+			record: 0
+		})
+	}
+
+	fn error(&self, field: usize, msg: &str) -> GError {
+		error!("invalid CSV in record {}, field {}: {}", self.record, field, msg)
+	}
+
+	fn next_record<I>(&mut self, lines: &mut I) -> Option<GResult<Vec<String>>>
+	where
+		I: Iterator<Item = GResult<String>>
+	{
+		let mut line = loop {
+			match lines.next()? {
+				Ok(line) if line.is_empty() => continue,
+				Ok(line) => break line,
+				Err(err) => return Some(Err(err))
+			}
+		};
+
+		self.record += 1;
+
+		let mut fields = Vec::new();
+		let mut field = String::new();
+		let mut quoted = false; //within a quoted field
+		let mut closed = false; //after the closing quote of a quoted field
+
+		loop {
+			let mut chars = line.chars().peekable();
+			while let Some(ch) = chars.next() {
+				if quoted {
+					if ch == '"' {
+						if chars.peek() == Some(&'"') {
+							chars.next();
+							field.push('"');
+						} else {
+							quoted = false;
+							closed = true;
+						}
+					} else {
+						field.push(ch);
+					}
+				} else if ch == self.delimiter {
+					fields.push(mem::take(&mut field));
+					closed = false;
+				} else if closed {
+					let msg = format!("unexpected {:?} after a closing quote", ch);
+					return Some(Err(self.error(fields.len() + 1, &msg)))
+				} else if ch == '"' {
+					if !field.is_empty() {
+						let msg = "unexpected quote in an unquoted field";
+						return Some(Err(self.error(fields.len() + 1, msg)))
+					}
+
+					quoted = true;
+				} else {
+					field.push(ch);
+				}
+			}
+
+			if !quoted {
+				break
+			}
+
+			//the line ended within a quoted field, so the field continues onto the next line
+			match lines.next() {
+				Some(Ok(next_line)) => {
+					field.push('\n');
+					line = next_line;
+				}
+				Some(Err(err)) => return Some(Err(err)),
+				None => {
+					let msg = "the input ended within a quoted field";
+					return Some(Err(self.error(fields.len() + 1, msg)))
+				}
+			}
+		}
+
+		fields.push(field);
+		Some(Ok(fields))
+	}
+}
+
+/**
+An iterator over the records of a CSV file, returned by
+[`glsp::csv_records`](fn.csv_records.html).
+*/
+
+pub struct CsvRecords<I> {
+	parser: CsvParser,
+	lines: I
+}
+
+impl<I> Iterator for CsvRecords<I>
+where
+	I: Iterator<Item = GResult<String>>
+{
+	type Item = GResult<Vec<String>>;
+
+	fn next(&mut self) -> Option<GResult<Vec<String>>> {
+		self.parser.next_record(&mut self.lines)
+	}
+}
+
+/**
+Parses CSV text, one line at a time, returning an iterator over its records.
+
+This is the parser used by [`(csv-parse)`](https://gamelisp.rs/std/csv-parse), exposed so that
+Rust code can load CSV data without converting it to GameLisp values. Each line should be
+passed to the parser without its line terminator, as produced by
+[`str::lines`](https://doc.rust-lang.org/std/primitive.str.html#method.lines). Each record is
+a `Vec` of its fields, with any quotes removed. Returns an `Err` if the `delimiter` is a
+double-quote or a line break.
+
+When the CSV text is malformed, the iterator produces an error which reports the record
+number and the field number, both counting from 1.
+
+	let text = "name,hp\n\"Orc, Large\",30\n";
+	for record in glsp::csv_records(text.lines().map(|line| Ok(line.to_string())), ',')? {
+		let record = record?;
+		//...
+	}
+*/
+pub fn csv_records<I>(lines: I, delimiter: char) -> GResult<CsvRecords<I>>
+where
+	I: Iterator<Item = GResult<String>>
+{
+	Ok(CsvRecords {
+		parser: CsvParser::new(delimiter)?,
+		lines
+	})
+}
+
+//converts each record into an arr of strs, or into a tab when there's a header row
+struct CsvRows {
+	parser: CsvParser,
+	headers: bool,
+	header_syms: Option<Vec<Sym>>
+}
+
+impl CsvRows {
+	fn new(opts: CsvOpts) -> GResult<CsvRows> {
+		Ok(CsvRows {
+			parser: CsvParser::new(opts.delimiter)?,
+			headers: opts.headers,
+			header_syms: None
+		})
+	}
+
+	fn next_row<I>(&mut self, lines: &mut I) -> Option<GResult<Val>>
+	where
+		I: Iterator<Item = GResult<String>>
+	{
+		let fields = match self.parser.next_record(lines)? {
+			Ok(fields) => fields,
+			Err(err) => return Some(Err(err))
+		};
+
+		if self.headers && self.header_syms.is_none() {
+			match self.header_syms(&fields) {
+				Ok(header_syms) => self.header_syms = Some(header_syms),
+				Err(err) => return Some(Err(err))
+			}
+
+			return self.next_row(lines)
+		}
+
+		Some(self.build_row(fields))
+	}
+
+	fn header_syms(&self, fields: &[String]) -> GResult<Vec<Sym>> {
+		let mut syms = Vec::with_capacity(fields.len());
+		for (i, field) in fields.iter().enumerate() {
+			if !glsp::is_valid_sym_str(field) {
+				let msg = format!("the header {:?} isn't a valid sym", field);
+				return Err(self.parser.error(i + 1, &msg))
+			}
+
+			let sym = glsp::sym(field)?;
+			if syms.contains(&sym) {
+				let msg = format!("the header {:?} appears more than once", field);
+				return Err(self.parser.error(i + 1, &msg))
+			}
+
+			syms.push(sym);
+		}
+
+		Ok(syms)
+	}
+
+	fn build_row(&self, fields: Vec<String>) -> GResult<Val> {
+		match self.header_syms {
+			Some(ref header_syms) => {
+				if fields.len() != header_syms.len() {
+					bail!("invalid CSV in record {}: it has {} fields, but the header row has {}",
+					      self.parser.record, fields.len(), header_syms.len())
+				}
+
+				let tab = glsp::tab();
+				for (&sym, field) in header_syms.iter().zip(fields) {
+					tab.set(sym, field)?;
+				}
+
+				Ok(Val::Tab(tab))
+			}
+			None => Ok(Val::Arr(glsp::arr_from_iter(fields)?))
+		}
+	}
+}
+
+//the lines of a string, with their "\n" or "\r\n" terminators removed
+struct OwnedLines {
+	text: String,
+	pos: usize
+}
+
+impl Iterator for OwnedLines {
+	type Item = GResult<String>;
+
+	fn next(&mut self) -> Option<GResult<String>> {
+		if self.pos >= self.text.len() {
+			return None
+		}
+
+		let rest = &self.text[self.pos..];
+		let (line, consumed) = match rest.find('\n') {
+			Some(i) => (&rest[..i], i + 1),
+			None => (rest, rest.len())
+		};
+
+		let line = line.strip_suffix('\r').unwrap_or(line).to_string();
+		self.pos += consumed;
+
+		Some(Ok(line))
+	}
+}
+
+struct StrRows {
+	rows: CsvRows,
+	lines: OwnedLines
+}
+
+impl Iterator for StrRows {
+	type Item = GResult<Val>;
+
+	fn next(&mut self) -> Option<GResult<Val>> {
+		self.rows.next_row(&mut self.lines)
+	}
+}
+
+/**
+Parses CSV text, returning an iterator over its rows.
+
+Equivalent to [`(csv-parse text)`](https://gamelisp.rs/std/csv-parse), with the
+`:delimiter` and `:headers` keyword arguments taken from `opts`.
+
+Each row is an array of strings; or, when `opts.headers` is `true`, a table which maps each
+header to a string. The text is parsed lazily, as the iterator advances.
+*/
+pub fn csv_parse(text: &str, opts: CsvOpts) -> GResult<Root<GIter>> {
+	Ok(glsp::rust_iter(StrRows {
+		rows: CsvRows::new(opts)?,
+		lines: OwnedLines {
+			text: text.to_string(),
+			pos: 0
+		}
+	}))
+}
+
+fn parse_opts(kwargs: &KwArgs) -> GResult<CsvOpts> {
+	kwargs.ensure_recognized(&["delimiter", "headers"])?;
+
+	let default = CsvOpts::default();
+	Ok(CsvOpts {
+		delimiter: kwargs.get("delimiter")?.unwrap_or(default.delimiter),
+		headers: kwargs.get("headers")?.unwrap_or(default.headers)
+	})
+}
+
+fn csv_parse_rfn(text: &str, kwargs: KwArgs) -> GResult<Root<GIter>> {
+	csv_parse(text, parse_opts(&kwargs)?)
+}
+
+//the lines are produced by a GIter, which can't be stored in a rust iterator. instead, the
+//parser's state is stored in an rdata, and a coroutine passes it to %csv-next along with the
+//GIter, once for each row.
+rdata! {
+	struct LinesParser(CsvRows);
+}
+
+fn csv_parse_lines_rfn(lines: Iterable, kwargs: KwArgs) -> GResult<Root<GIter>> {
+	let parser = glsp::rdata(LinesParser(CsvRows::new(parse_opts(&kwargs)?)?))?;
+
+	let fn_form: Val = backquote!(r#"
+		(fn &name csv-parse-lines (parser lines)
+		  (loop
+		    (let row (%csv-next parser lines))
+		    (when (nil? row)
+		      (break))
+		    (yield row)))
+	"#);
+
+	let gfn = glsp::eval(&fn_form, None)?.unwrap_gfn();
+	let coro: Root<Coro> = glsp::call(&gfn, &(parser, lines.giter()))?;
+
+	Ok(coro.giter())
+}
+
+fn csv_next(parser: &mut LinesParser, lines: Root<GIter>) -> GResult<Option<Val>> {
+	let mut lines = lines.map(|result| {
+		match result? {
+			Val::Str(st) => Ok(st.to_string()),
+			val => bail!("(csv-parse-lines) expected an iterator of strs, but it produced {}",
+			             val.a_type_name())
+		}
+	});
+
+	parser.0.next_row(&mut lines).transpose()
+}
+
+//-------------------------------------------------------------------------------------------------
+// emission
+//-------------------------------------------------------------------------------------------------
+
+/**
+Appends one CSV record to `dst`, followed by `"\n"`.
+
+This is the encoder used by [`(csv-emit)`](https://gamelisp.rs/std/csv-emit). A field is
+wrapped in double-quotes if it contains the delimiter, a double-quote or a line break, with any
+double-quotes inside it doubled. Returns an `Err` if the `delimiter` is a double-quote or a
+line break.
+*/
+pub fn csv_write_record<S: AsRef<str>>(dst: &mut String, fields: &[S], delimiter: char)
+                                       -> GResult<()> {
+	check_delimiter(delimiter)?;
+
+	for (i, field) in fields.iter().enumerate() {
+		if i > 0 {
+			dst.push(delimiter);
+		}
+
+		let field = field.as_ref();
+		let needs_quotes = field.contains(|ch| ch == delimiter || ch == '"' || ch == '\n' ||
+		                                       ch == '\r');
+
+		//a record which consists of a single empty field would otherwise be a blank line,
+		//which the parser skips
+		if needs_quotes || (fields.len() == 1 && field.is_empty()) {
+			dst.push('"');
+			dst.push_str(&field.replace('"', "\"\""));
+			dst.push('"');
+		} else {
+			dst.push_str(field);
+		}
+	}
+
+	dst.push('\n');
+	Ok(())
+}
+
+fn field_text(val: &Val, record: usize, field: usize) -> GResult<String> {
+	match *val {
+		Val::Nil => Ok(String::new()),
+		Val::Str(ref st) => Ok(st.to_string()),
+		Val::Sym(sym) => Ok(sym.name().to_string()),
+		Val::Char(ch) => Ok(ch.to_string()),
+		Val::Int(_) | Val::Flo(_) | Val::Bool(_) => Ok(val.to_string()),
+		ref val => bail!("(csv-emit) received {} in record {}, field {}: expected a str, sym, \
+		                  char, number, bool or nil", val.a_type_name(), record, field)
+	}
+}
+
+fn csv_emit_rfn(rows: Iterable, kwargs: KwArgs) -> GResult<String> {
+	kwargs.ensure_recognized(&["delimiter", "headers"])?;
+	let delimiter: char = kwargs.get("delimiter")?.unwrap_or(',');
+	let headers: Option<Vec<Val>> = kwargs.get("headers")?;
+
+	let mut dst = String::new();
+	let mut record = 0;
+
+	if let Some(ref headers) = headers {
+		record += 1;
+		let fields = headers.iter().enumerate().map(|(i, header)| {
+			field_text(header, record, i + 1)
+		}).collect::<GResult<Vec<String>>>()?;
+
+		csv_write_record(&mut dst, &fields, delimiter)?;
+	}
+
+	for row in rows.giter() {
+		let row = row?;
+		record += 1;
+
+		let fields = match (&row, &headers) {
+			(Val::Tab(tab), Some(headers)) => {
+				tab_fields(tab, headers, record)?
+			}
+			(Val::Tab(_), None) => {
+				bail!("(csv-emit) received a tab in record {}, but no :headers were given",
+				      record)
+			}
+			(row, _) => {
+				let iterable = Iterable::from_val(row)?;
+				let mut fields = Vec::new();
+				for (i, val) in iterable.giter().enumerate() {
+					fields.push(field_text(&val?, record, i + 1)?);
+				}
+
+				fields
+			}
+		};
+
+		csv_write_record(&mut dst, &fields, delimiter)?;
+	}
+
+	Ok(dst)
+}
+
+fn tab_fields(tab: &Root<Tab>, headers: &[Val], record: usize) -> GResult<Vec<String>> {
+	let mut fields = Vec::with_capacity(headers.len());
+	for (i, header) in headers.iter().enumerate() {
+		let val: Val = tab.get_if_present(header)?.unwrap_or(Val::Nil);
+		fields.push(field_text(&val, record, i + 1)?);
+	}
+
+	Ok(fields)
+}
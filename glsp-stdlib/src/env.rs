@@ -0,0 +1,134 @@
+use glsp::{Arr, bail, DequeOps, ensure, GError, GResult, Lib, rfn, Root};
+use std::env::{self, VarError};
+use std::ffi::{OsString};
+use super::{bind_cap_rfn, Caps, Std};
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::fs;
+
+pub fn init(_sandboxed: bool) -> GResult<()> {
+	bind_cap_rfn("getenv", Caps::ENV, rfn!(getenv))?;
+	bind_cap_rfn("args", Caps::ENV, rfn!(args))?;
+	bind_cap_rfn("hostname", Caps::ENV, rfn!(hostname))?;
+	bind_cap_rfn("exit", Caps::ENV, rfn!(exit))?;
+
+	Ok(())
+}
+
+/**
+Permissions for the GameLisp functions which inspect the host environment.
+
+Passed to [`glsp::enable_env`](fn.enable_env.html).
+*/
+
+#[derive(Clone, Debug, Default)]
+pub struct EnvPolicy {
+	/**
+	The array returned by [`(args)`](https://gamelisp.rs/std/args).
+
+	When this is `None`, `(args)` returns the process's command-line arguments.
+	*/
+	pub args: Option<Vec<String>>
+}
+
+/**
+Enables the GameLisp functions which inspect the host environment, such as
+[`getenv`](https://gamelisp.rs/std/getenv) and [`exit`](https://gamelisp.rs/std/exit).
+
+These functions are disabled by default. Until this function is called, any attempt to call
+them from GameLisp code will trigger an error.
+
+Returns an `Err` if the [`Runtime`](struct.Runtime.html) is sandboxed, or if the 
+[`ENV`](struct.Caps.html#associatedconstant.ENV) capability has been denied.
+*/
+pub fn enable_env(policy: EnvPolicy) -> GResult<()> {
+	ensure!(!Std::borrow().sandboxed, "environment access is forbidden in a sandboxed Runtime");
+	ensure!(!Std::borrow().denied.contains(Caps::ENV), "the ENV capability has been denied");
+
+	Std::borrow_mut().env_policy = Some(policy);
+	Ok(())
+}
+
+/**
+Disables the GameLisp functions which inspect the host environment.
+
+This undoes the effect of [`glsp::enable_env`](fn.enable_env.html).
+*/
+pub fn disable_env() {
+	Std::borrow_mut().env_policy = None;
+}
+
+pub(crate) fn check_enabled(fn_name: &str) -> GResult<()> {
+	ensure!(Std::borrow().env_policy.is_some(),
+	        "({}) is disabled: the host has not enabled environment access", fn_name);
+	Ok(())
+}
+
+fn getenv(name: &str) -> GResult<Option<String>> {
+	check_enabled("getenv")?;
+
+	match env::var(name) {
+		Ok(value) => Ok(Some(value)),
+		Err(VarError::NotPresent) => Ok(None),
+		Err(VarError::NotUnicode(_)) => {
+			bail!("the environment variable {} contains non-UTF-8 data", name)
+		}
+	}
+}
+
+fn args() -> GResult<Root<Arr>> {
+	check_enabled("args")?;
+
+	let std = Std::borrow();
+	if let Some(ref args) = std.env_policy.as_ref().unwrap().args {
+		return glsp::arr_from_iter(args.iter())
+	}
+
+	//we use OsString here to avoid failing if some kind of non-UTF string is passed on the
+	//command line, e.g. as the filepath on a non-English system
+	let os_args: Vec<OsString> = env::args_os().collect();
+	let arr = glsp::arr_with_capacity(os_args.len());
+
+	if os_args.len() < 1 {
+		//we guarantee that the first arg is present
+		arr.push(glsp::str())?;
+	} else {
+		//convert each native arg into a str, replacing it with an empty str if it's invalid
+		for os_arg in os_args.into_iter() {
+			arr.push(os_arg.into_string().unwrap_or_default())?;
+		}
+	}
+
+	Ok(arr)
+}
+
+//the standard library doesn't provide a way to query the hostname. rather than pulling in a
+//platform-specific dependency, we check the environment variables which are conventionally set
+//by the os, then fall back to /etc/hostname.
+fn hostname() -> GResult<Option<String>> {
+	check_enabled("hostname")?;
+
+	for var_name in &["COMPUTERNAME", "HOSTNAME"] {
+		if let Ok(name) = env::var(var_name) {
+			if !name.is_empty() {
+				return Ok(Some(name))
+			}
+		}
+	}
+
+	#[cfg(not(target_arch = "wasm32"))] {
+		if let Ok(contents) = fs::read_to_string("/etc/hostname") {
+			let name = contents.trim();
+			if !name.is_empty() {
+				return Ok(Some(name.to_string()))
+			}
+		}
+	}
+
+	Ok(None)
+}
+
+fn exit(code: Option<i32>) -> GResult<()> {
+	check_enabled("exit")?;
+	Err(GError::exit(code.unwrap_or(0)))
+}
@@ -0,0 +1,262 @@
+use glsp::{bail, Callable, error, GError, GResult, Lib, Obj, rfn, Root, Sym, ToSym, Val};
+use std::cell::{Cell};
+use std::collections::{HashMap, HashSet};
+use std::rc::{Rc};
+use super::{Std};
+
+pub fn init(_sandboxed: bool) -> GResult<()> {
+	glsp::bind_rfn("defevent", rfn!(defevent))?;
+	glsp::bind_rfn("listen", rfn!(listen_callable))?;
+	glsp::bind_rfn("unlisten", rfn!(unlisten))?;
+	glsp::bind_rfn("unlisten-all", rfn!(unlisten_all_rfn))?;
+	glsp::bind_rfn("emit", rfn!(emit_rfn))?;
+
+	Ok(())
+}
+
+//-------------------------------------------------------------------------------------------------
+// Events
+//-------------------------------------------------------------------------------------------------
+
+/*
+each listener has an `active` flag which is shared with any in-progress calls to emit(). emit()
+works on a snapshot of the listener list, so that listeners can freely call listen, unlisten or
+emit without invalidating it. a listener which is removed during dispatch has its flag cleared,
+so it won't be called by the remainder of the dispatch. a listener which is added during
+dispatch won't be called until the next emit().
+*/
+
+pub(crate) struct Events {
+	declared: HashSet<Sym>,
+	listeners: HashMap<Sym, Vec<Listener>>,
+	tokens: HashMap<i32, Sym>,
+	next_token: i32
+}
+
+#[derive(Clone)]
+struct Listener {
+	token: i32,
+	callee: Callee,
+	owner: Option<Root<Obj>>,
+	active: Rc<Cell<bool>>
+}
+
+#[derive(Clone)]
+enum Callee {
+	Glsp(Callable),
+	Rust(Rc<dyn Fn(&[Val]) -> GResult<()>>)
+}
+
+impl Events {
+	pub(crate) fn new() -> Events {
+		Events {
+			declared: HashSet::new(),
+			listeners: HashMap::new(),
+			tokens: HashMap::new(),
+			next_token: 1
+		}
+	}
+
+	fn add(&mut self, event: Sym, callee: Callee, owner: Option<Root<Obj>>) -> GResult<i32> {
+		let token = self.next_token;
+		self.next_token = match self.next_token.checked_add(1) {
+			Some(next_token) => next_token,
+			None => bail!("too many event listeners have been registered")
+		};
+
+		self.listeners.entry(event).or_default().push(Listener {
+			token,
+			callee,
+			owner,
+			active: Rc::new(Cell::new(true))
+		});
+		self.tokens.insert(token, event);
+
+		Ok(token)
+	}
+
+	fn remove_where<F: Fn(&Listener) -> bool>(&mut self, event: Sym, f: F) -> usize {
+		let tokens = &mut self.tokens;
+		let listeners = match self.listeners.get_mut(&event) {
+			Some(listeners) => listeners,
+			None => return 0
+		};
+
+		let prev_len = listeners.len();
+		listeners.retain(|listener| {
+			if f(listener) {
+				listener.active.set(false);
+				tokens.remove(&listener.token);
+				false
+			} else {
+				true
+			}
+		});
+
+		let removed = prev_len - listeners.len();
+		if listeners.is_empty() {
+			self.listeners.remove(&event);
+		}
+
+		removed
+	}
+}
+
+fn owner_killed(listener: &Listener) -> bool {
+	match listener.owner {
+		Some(ref owner) => owner.is_killed(),
+		None => false
+	}
+}
+
+/**
+Equivalent to [`(defevent name)`](https://gamelisp.rs/std/defevent).
+
+Declaring events is optional. Once any event has been declared, it's an error to emit an
+event which hasn't been declared, so that misspelled event names are caught early.
+*/
+pub fn declare_event<S: ToSym>(event: S) -> GResult<()> {
+	let event = event.to_sym()?;
+	Std::borrow_mut().events.declared.insert(event);
+	Ok(())
+}
+
+/**
+Registers a Rust function as a listener for the named event.
+
+The function receives the arguments which were passed to [`glsp::emit`](fn.emit.html) or
+[`(emit)`](https://gamelisp.rs/std/emit). Returns a token which can be passed to
+[`glsp::unlisten`](fn.unlisten.html).
+
+	let token = glsp::listen("enemy-died", |args: &[Val]| {
+		prn!("an enemy died at {}", args[0]);
+		Ok(())
+	})?;
+*/
+pub fn listen<S, F>(event: S, f: F) -> GResult<i32>
+where
+	S: ToSym,
+	F: Fn(&[Val]) -> GResult<()> + 'static
+{
+	let event = event.to_sym()?;
+	Std::borrow_mut().events.add(event, Callee::Rust(Rc::new(f)), None)
+}
+
+/**
+Equivalent to [`(unlisten token)`](https://gamelisp.rs/std/unlisten).
+
+Returns `false` if the listener has already been removed.
+*/
+pub fn unlisten(token: i32) -> bool {
+	let mut std = Std::borrow_mut();
+	let events = &mut std.events;
+	match events.tokens.get(&token).copied() {
+		Some(event) => events.remove_where(event, |listener| listener.token == token) > 0,
+		None => false
+	}
+}
+
+/**
+Equivalent to [`(unlisten-all owner)`](https://gamelisp.rs/std/unlisten-all).
+
+Returns the number of listeners which were removed.
+*/
+pub fn unlisten_all(owner: &Root<Obj>) -> usize {
+	let mut std = Std::borrow_mut();
+	let events = &mut std.events;
+
+	let event_names: Vec<Sym> = events.listeners.keys().copied().collect();
+	event_names.into_iter().map(|event| {
+		events.remove_where(event, |listener| {
+			match listener.owner {
+				Some(ref listener_owner) => Root::ptr_eq(listener_owner, owner),
+				None => false
+			}
+		})
+	}).sum()
+}
+
+/**
+Equivalent to [`(emit name ..args)`](https://gamelisp.rs/std/emit).
+
+Calls every listener for the named event, in the order they were registered, and returns the
+number of listeners which were called.
+
+If a listener fails, the remaining listeners are still called. Once they've all finished, the
+errors are combined into a single `Err`. The exception is a call to
+[`(exit)`](https://gamelisp.rs/std/exit), which is propagated immediately.
+*/
+pub fn emit<S: ToSym>(event: S, args: &[Val]) -> GResult<usize> {
+	let event = event.to_sym()?;
+
+	//we mustn't hold a borrow of Std while the listeners are running
+	let snapshot = {
+		let mut std = Std::borrow_mut();
+		let events = &mut std.events;
+
+		if !events.declared.is_empty() && !events.declared.contains(&event) {
+			bail!("attempted to emit the undeclared event {}", event)
+		}
+
+		events.remove_where(event, owner_killed);
+		match events.listeners.get(&event) {
+			Some(listeners) => listeners.clone(),
+			None => return Ok(0)
+		}
+	};
+
+	let mut called = 0;
+	let mut errors = Vec::<GError>::new();
+
+	for listener in &snapshot {
+		if !listener.active.get() || owner_killed(listener) {
+			continue
+		}
+
+		called += 1;
+		let result = match listener.callee {
+			Callee::Glsp(ref callable) => glsp::call::<_, _, Val>(callable, args).map(|_| ()),
+			Callee::Rust(ref f) => f(args)
+		};
+
+		if let Err(err) = result {
+			if err.exit_code().is_some() {
+				return Err(err)
+			}
+
+			errors.push(err);
+		}
+	}
+
+	if errors.is_empty() {
+		return Ok(called)
+	}
+
+	let mut description = String::new();
+	for (i, err) in errors.iter().enumerate() {
+		if i > 0 {
+			description.push_str("; ");
+		}
+		description.push_str(&err.val().to_string());
+	}
+
+	let first_error = errors.remove(0);
+	Err(error!("{} of the {} listeners for {} failed: {}", errors.len() + 1, called, event,
+	           description).with_source(first_error))
+}
+
+fn defevent(event: Sym) -> GResult<()> {
+	declare_event(event)
+}
+
+fn listen_callable(event: Sym, callee: Callable, owner: Option<Root<Obj>>) -> GResult<i32> {
+	Std::borrow_mut().events.add(event, Callee::Glsp(callee), owner)
+}
+
+fn unlisten_all_rfn(owner: Root<Obj>) -> usize {
+	unlisten_all(&owner)
+}
+
+fn emit_rfn(event: Sym, args: &[Val]) -> GResult<usize> {
+	emit(event, args)
+}
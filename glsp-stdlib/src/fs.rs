@@ -0,0 +1,306 @@
+use glsp::{
+	Arr, bail, DequeOps, ensure, error, GError, GIter, GResult, Lib, rfn, Root, stock_syms::*, Sym,
+	tab
+};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::str;
+use super::{bind_cap_rfn, Caps, path::normalize, Std};
+
+pub fn init(_sandboxed: bool) -> GResult<()> {
+	bind_cap_rfn("read-file", Caps::FS, rfn!(read_file))?;
+	bind_cap_rfn("read-lines", Caps::FS, rfn!(read_lines_rfn))?;
+	bind_cap_rfn("write-file", Caps::FS, rfn!(write_file))?;
+	bind_cap_rfn("append-file", Caps::FS, rfn!(append_file))?;
+	bind_cap_rfn("file-exists?", Caps::FS, rfn!(file_existsp))?;
+	bind_cap_rfn("read-dir", Caps::FS, rfn!(read_dir))?;
+	bind_cap_rfn("remove-file", Caps::FS, rfn!(remove_file))?;
+
+	Ok(())
+}
+
+/**
+Permissions for the GameLisp filesystem functions.
+
+Passed to [`glsp::enable_fs`](fn.enable_fs.html).
+*/
+
+#[derive(Clone, Debug)]
+pub struct FsPolicy {
+	/**
+	When this is `Some`, all paths are resolved relative to this directory, and any path which
+	would resolve to a location outside of it (including by following a symbolic link) is
+	rejected with an error.
+	*/
+	pub root: Option<PathBuf>,
+
+	///When this is `false`, only the functions which read from the filesystem are permitted.
+	pub write: bool
+}
+
+/**
+Enables the GameLisp functions which access the filesystem, such as
+[`read-file`](https://gamelisp.rs/std/read-file) and
+[`write-file`](https://gamelisp.rs/std/write-file).
+
+Filesystem access is disabled by default. Until this function is called, any attempt
+to access the filesystem from GameLisp code will trigger an error.
+
+Returns an `Err` if the [`Runtime`](struct.Runtime.html) is sandboxed, if the
+[`FS`](struct.Caps.html#associatedconstant.FS) capability has been denied, or if the `root`
+directory does not exist.
+*/
+pub fn enable_fs(policy: FsPolicy) -> GResult<()> {
+	ensure!(!Std::borrow().sandboxed, "filesystem access is forbidden in a sandboxed Runtime");
+	ensure!(!Std::borrow().denied.contains(Caps::FS), "the FS capability has been denied");
+
+	let root = match policy.root {
+		Some(root) => {
+			match root.canonicalize() {
+				Ok(root) => Some(root),
+				Err(err) => {
+					return Err(error!("invalid filesystem root {}: {}", root.display(), err)
+					           .with_source(err))
+				}
+			}
+		}
+		None => None
+	};
+
+	Std::borrow_mut().fs_policy = Some(FsPolicy { root, ..policy });
+	Ok(())
+}
+
+/**
+Disables the GameLisp functions which access the filesystem.
+
+This undoes the effect of [`glsp::enable_fs`](fn.enable_fs.html).
+*/
+pub fn disable_fs() {
+	Std::borrow_mut().fs_policy = None;
+}
+
+//checks the active FsPolicy, and converts a path received from glsp into a path which can be
+//passed to std::fs. when the policy has a root, we canonicalize the path (following symlinks)
+//and check that it's still within the root. paths which don't exist yet, e.g. the destination
+//of (write-file), are checked by canonicalizing their parent directory instead. canonicalize()
+//also fails for a dangling symlink, so in that case we reject the path: otherwise, (write-file)
+//would follow the link and create a file outside of the root.
+fn resolve(path: &Path, fn_name: &str, writing: bool) -> GResult<PathBuf> {
+	let std = Std::borrow();
+	let policy = match std.fs_policy {
+		Some(ref policy) => policy,
+		None => bail!("({}) is disabled: the host has not enabled filesystem access", fn_name)
+	};
+
+	ensure!(!writing || policy.write,
+	        "({}) is disabled: the host has only enabled read access to the filesystem", fn_name);
+
+	let root = match policy.root {
+		Some(ref root) => root,
+		None => return Ok(path.to_path_buf())
+	};
+
+	let joined = root.join(path);
+	ensure!(normalize(&joined).starts_with(root), "({}) received the path {}, which is outside \
+	        the filesystem root {}", fn_name, path.display(), root.display());
+
+	let resolved = match joined.canonicalize() {
+		Ok(resolved) => resolved,
+		Err(_) => {
+			match (joined.parent(), joined.file_name()) {
+				(Some(parent), Some(file_name)) => {
+					let resolved = match parent.canonicalize() {
+						Ok(parent) => parent.join(file_name),
+						Err(err) => return Err(os_error(fn_name, path, err))
+					};
+
+					if let Ok(metadata) = fs::symlink_metadata(&resolved) {
+						ensure!(!metadata.file_type().is_symlink(), "({}) received the path {}, \
+						        which is a symbolic link to a nonexistent file", fn_name,
+						        path.display());
+					}
+
+					resolved
+				}
+				_ => bail!("({}) received an invalid path {}", fn_name, path.display())
+			}
+		}
+	};
+
+	ensure!(resolved.starts_with(root), "({}) received the path {}, which is outside \
+	        the filesystem root {}", fn_name, path.display(), root.display());
+
+	Ok(resolved)
+}
+
+fn os_error(fn_name: &str, path: &Path, err: io::Error) -> GError {
+	error!("({}) failed for {}: {}", fn_name, path.display(), err).with_source(err)
+}
+
+fn read_file(path: &Path) -> GResult<String> {
+	let resolved = resolve(path, "read-file", false)?;
+	fs::read_to_string(&resolved).map_err(|err| os_error("read-file", path, err))
+}
+
+fn read_lines_rfn(path: &Path, mode: Option<Sym>) -> GResult<Root<GIter>> {
+	let lossy = match mode {
+		None => false,
+		Some(LOSSY_SYM) => true,
+		Some(mode) => bail!("expected 'lossy, received {}", mode)
+	};
+
+	let resolved = resolve(path, "read-lines", false)?;
+	let file = File::open(&resolved).map_err(|err| os_error("read-lines", path, err))?;
+	Ok(glsp::rust_iter(LineReader::new(file, path, lossy)))
+}
+
+/**
+Opens a text file, returning an iterator over its lines.
+
+Equivalent to [`(read-lines path)`](https://gamelisp.rs/std/read-lines), or to
+`(read-lines path 'lossy)` when `lossy` is `true`. However, this function doesn't require
+filesystem access to be [enabled](fn.enable_fs.html), and `path` isn't restricted to the
+filesystem root.
+
+The file is read incrementally, so only one line needs to be held in memory at a time. Lines
+are split in the same way as [`glsp::lines`](fn.lines.html). When a line isn't valid UTF-8,
+the iterator produces an error which reports its line number; when `lossy` is `true`, any 
+invalid sequences are replaced with `U+FFFD` instead.
+
+The file is closed when the iterator finishes, when it's [closed](struct.GIter.html#method.close),
+or when it's garbage-collected.
+*/
+pub fn read_lines<P: AsRef<Path>>(path: P, lossy: bool) -> GResult<Root<GIter>> {
+	let path = path.as_ref();
+	let file = File::open(path).map_err(|err| os_error("read-lines", path, err))?;
+	Ok(glsp::rust_iter(LineReader::new(file, path, lossy)))
+}
+
+struct LineReader {
+	reader: Option<BufReader<File>>, //None after an i/o error
+	path: PathBuf,
+	line: usize,
+	lossy: bool,
+	buf: Vec<u8>
+}
+
+impl LineReader {
+	fn new(file: File, path: &Path, lossy: bool) -> LineReader {
+		LineReader {
+			reader: Some(BufReader::new(file)),
+			path: path.to_path_buf(),
+			line: 0,
+			lossy,
+			buf: Vec::new()
+		}
+	}
+}
+
+impl Iterator for LineReader {
+	type Item = GResult<String>;
+
+	fn next(&mut self) -> Option<GResult<String>> {
+		let reader = self.reader.as_mut()?;
+
+		self.buf.clear();
+		match reader.read_until(b'\n', &mut self.buf) {
+			Ok(0) => return None,
+			Ok(_) => (),
+			Err(err) => {
+				self.reader = None;
+				return Some(Err(os_error("read-lines", &self.path, err)))
+			}
+		}
+
+		self.line += 1;
+
+		if self.buf.ends_with(b"\n") {
+			self.buf.pop();
+			if self.buf.ends_with(b"\r") {
+				self.buf.pop();
+			}
+		}
+
+		if self.lossy {
+			Some(Ok(String::from_utf8_lossy(&self.buf).into_owned()))
+		} else {
+			match str::from_utf8(&self.buf) {
+				Ok(line) => Some(Ok(line.to_string())),
+				Err(err) => {
+					Some(Err(error!("(read-lines) encountered invalid UTF-8 on line {} of {}",
+					                self.line, self.path.display()).with_source(err)))
+				}
+			}
+		}
+	}
+}
+
+fn write_file(path: &Path, contents: &str) -> GResult<()> {
+	let resolved = resolve(path, "write-file", true)?;
+	fs::write(&resolved, contents).map_err(|err| os_error("write-file", path, err))
+}
+
+fn append_file(path: &Path, contents: &str) -> GResult<()> {
+	let resolved = resolve(path, "append-file", true)?;
+
+	let result = OpenOptions::new()
+		.create(true)
+		.append(true)
+		.open(&resolved)
+		.and_then(|mut file| file.write_all(contents.as_bytes()));
+
+	result.map_err(|err| os_error("append-file", path, err))
+}
+
+fn file_existsp(path: &Path) -> GResult<bool> {
+	let resolved = resolve(path, "file-exists?", false)?;
+	Ok(resolved.exists())
+}
+
+fn read_dir(path: &Path) -> GResult<Root<Arr>> {
+	let resolved = resolve(path, "read-dir", false)?;
+	let entries = fs::read_dir(&resolved).map_err(|err| os_error("read-dir", path, err))?;
+
+	let mut results = Vec::new();
+	for entry in entries {
+		let entry = entry.map_err(|err| os_error("read-dir", path, err))?;
+		let file_type = entry.file_type().map_err(|err| os_error("read-dir", path, err))?;
+
+		let type_sym = if file_type.is_symlink() {
+			SYMLINK_SYM
+		} else if file_type.is_dir() {
+			DIR_SYM
+		} else if file_type.is_file() {
+			FILE_SYM
+		} else {
+			OTHER_SYM
+		};
+
+		let name = match entry.file_name().into_string() {
+			Ok(name) => name,
+			Err(name) => bail!("(read-dir) encountered a non-UTF-8 file name {:?}", name)
+		};
+
+		results.push((name, type_sym));
+	}
+
+	//the order of fs::read_dir is platform-dependent, so we sort the results by name
+	results.sort_by(|(name0, _), (name1, _)| name0.cmp(name1));
+
+	let arr = glsp::arr_with_capacity(results.len());
+	for (name, type_sym) in results {
+		arr.push(tab! {
+			(NAME_SYM, name),
+			(TYPE_SYM, type_sym),
+		})?;
+	}
+
+	Ok(arr)
+}
+
+fn remove_file(path: &Path) -> GResult<()> {
+	let resolved = resolve(path, "remove-file", true)?;
+	fs::remove_file(&resolved).map_err(|err| os_error("remove-file", path, err))
+}
@@ -0,0 +1,692 @@
+use glsp::{bail, DequeOps, ensure, Flo, GResult, rfn, stock_syms::*, Val};
+use std::collections::{HashMap};
+use std::f64;
+
+#[cfg(feature = "digest")]
+use glsp::{Sym};
+
+pub fn init(_sandboxed: bool) -> GResult<()> {
+	glsp::bind_rfn("hash", rfn!(hash))?;
+	glsp::bind_rfn("state-hash", rfn!(state_hash_rfn))?;
+
+	#[cfg(feature = "digest")]
+	glsp::bind_rfn("hash-str", rfn!(hash_str))?;
+
+	Ok(())
+}
+
+//(hash x) must produce the same result on every platform, in every Runtime, and in every future
+//version of glsp (so that hashes can be stored on disk), so we can't use std's Hasher. instead,
+//we feed a simple tagged encoding of the value's structure into 64-bit FNV-1a, and then fold the
+//result into an i32:
+//
+//	#n:       0x00
+//	bool:     0x01, followed by 0x00 or 0x01
+//	integer:  0x02, followed by an i32 as four little-endian bytes. this encodes every int and
+//	          char, and every flo with an integer value in the range of an i32, including -0.0.
+//	          this ensures that (eq? 1 1.0 #\x01) is consistent with their hashes, without
+//	          rounding large ints so that they collide with one another.
+//	flo:      0x0e, followed by any other flo converted to an f64, as eight little-endian
+//	          bytes. all nans are converted to f64::NAN. the conversion is exact, so a flo
+//	          which can be represented by an f32 has the same hash whether or not the
+//	          "f64-flos" feature is enabled.
+//	sym:      0x03, followed by its name's length as a u32, followed by its name in UTF-8
+//	str:      0x04, followed by its byte length as a u32, followed by its contents in UTF-8
+//	arr:      0x05, followed by its length as a u32, followed by each element's encoding
+//	tab:      0x06, followed by its length as a u32, followed by the wrapping sum of each entry's
+//	          own 64-bit hash (the encoding of its key, followed by the encoding of its value),
+//	          as a u64. using a commutative operation makes the result independent of the
+//	          table's iteration order, just like (eq?).
+//	obj or
+//	rdata:    0x07, followed by the int returned by its (op-hash) method, as four bytes
+//	rec:      0x08, followed by its type name's length as a u32, followed by its type name in
+//	          UTF-8, followed by each field's encoding
+//	parr:     0x09, followed by its length as a u32, followed by each element's encoding
+//	ptab:     0x0a, followed by its length as a u32, followed by the wrapping sum of each
+//	          entry's hash, as for a tab
+//	vec2:     0x0b, followed by the encoding of each component as a number
+//	vec3:     0x0c, followed by the encoding of each component as a number
+//	mat3:     0x0d, followed by the encoding of each element as a number, in column-major order
+//
+//the encoding deliberately doesn't include any addresses, so everything else is unhashable.
+//
+//without the "f64-flos" feature, an int above 2^24 can be (eq?) to a flo with a different
+//integer value, because the int is rounded when they're compared. (eq?) isn't transitive in that
+//case, so no hash could be consistent with it; we hash each number by its exact value instead.
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+//guards against stack overflow when hashing a self-referential arr or tab
+const MAX_DEPTH: usize = 256;
+
+struct Fnv(u64);
+
+impl Fnv {
+	fn new() -> Fnv {
+		Fnv(FNV_OFFSET_BASIS)
+	}
+
+	fn write(&mut self, bytes: &[u8]) {
+		for &byte in bytes {
+			self.0 ^= byte as u64;
+			self.0 = self.0.wrapping_mul(FNV_PRIME);
+		}
+	}
+
+	fn write_len(&mut self, len: usize) {
+		self.write(&(len as u32).to_le_bytes());
+	}
+}
+
+fn write_int(fnv: &mut Fnv, i: i32) {
+	fnv.write(&[0x02]);
+	fnv.write(&i.to_le_bytes());
+}
+
+fn write_flo(fnv: &mut Fnv, f: Flo) {
+	//i32::MAX can't be represented by an f32, so we compare against 2^31 instead
+	if f.fract() == 0.0 && f >= i32::MIN as Flo && f < -(i32::MIN as Flo) {
+		write_int(fnv, f as i32)
+	} else {
+		let f = if f.is_nan() { f64::NAN } else { f as f64 };
+		fnv.write(&[0x0e]);
+		fnv.write(&f.to_bits().to_le_bytes());
+	}
+}
+
+fn write_components(fnv: &mut Fnv, tag: u8, components: &[Flo]) {
+	fnv.write(&[tag]);
+	for &component in components {
+		write_flo(fnv, component);
+	}
+}
+
+fn write_val(fnv: &mut Fnv, val: &Val, depth: usize) -> GResult<()> {
+	ensure!(depth <= MAX_DEPTH, "(hash) received a value which is too deeply nested, or \
+	        which contains itself");
+
+	match *val {
+		Val::Nil => fnv.write(&[0x00]),
+		Val::Bool(b) => fnv.write(&[0x01, b as u8]),
+		Val::Int(i) => write_int(fnv, i),
+		Val::Flo(f) => write_flo(fnv, f),
+		Val::Char(c) => write_int(fnv, c as u32 as i32),
+		Val::Sym(sym) => {
+			let name = sym.name();
+			fnv.write(&[0x03]);
+			fnv.write_len(name.len());
+			fnv.write(name.as_bytes());
+		}
+		Val::Str(ref st) => {
+			let text = st.to_string();
+			fnv.write(&[0x04]);
+			fnv.write_len(text.len());
+			fnv.write(text.as_bytes());
+		}
+		Val::Arr(ref arr) => {
+			fnv.write(&[0x05]);
+			fnv.write_len(arr.len());
+			for item in arr.iter() {
+				write_val(fnv, &item, depth + 1)?;
+			}
+		}
+		Val::Tab(ref tab) => {
+			let mut sum = 0_u64;
+			for (key, value) in tab.entries().iter() {
+				let mut entry_fnv = Fnv::new();
+				write_val(&mut entry_fnv, &key, depth + 1)?;
+				write_val(&mut entry_fnv, &value, depth + 1)?;
+				sum = sum.wrapping_add(entry_fnv.0);
+			}
+
+			fnv.write(&[0x06]);
+			fnv.write_len(tab.len());
+			fnv.write(&sum.to_le_bytes());
+		}
+		Val::PArr(ref parr) => {
+			fnv.write(&[0x09]);
+			fnv.write_len(parr.len());
+			for item in parr.iter() {
+				write_val(fnv, &item, depth + 1)?;
+			}
+		}
+		Val::PTab(ref ptab) => {
+			let mut sum = 0_u64;
+			for (key, value) in ptab.entries() {
+				let mut entry_fnv = Fnv::new();
+				write_val(&mut entry_fnv, &key, depth + 1)?;
+				write_val(&mut entry_fnv, &value, depth + 1)?;
+				sum = sum.wrapping_add(entry_fnv.0);
+			}
+
+			fnv.write(&[0x0a]);
+			fnv.write_len(ptab.len());
+			fnv.write(&sum.to_le_bytes());
+		}
+		Val::Rec(ref rec) => {
+			let name = rec.name().name();
+			fnv.write(&[0x08]);
+			fnv.write_len(name.len());
+			fnv.write(name.as_bytes());
+			for item in rec.field_vals() {
+				write_val(fnv, &item, depth + 1)?;
+			}
+		}
+		Val::Obj(ref obj) => {
+			match obj.call_if_present(OP_HASH_SYM, &())? {
+				Some(Val::Int(i)) => {
+					fnv.write(&[0x07]);
+					fnv.write(&i.to_le_bytes());
+				}
+				Some(val) => bail!("(op-hash) returned {}, rather than an int", val.a_type_name()),
+				None => {
+					let name = obj.class().name().map(|sym| sym.to_string());
+					bail!("cannot hash an obj of class {}: it has no op-hash method",
+					      name.as_deref().unwrap_or("(anonymous)"))
+				}
+			}
+		}
+		Val::RData(ref rdata) => {
+			match rdata.call_if_present(OP_HASH_SYM, &())? {
+				Some(Val::Int(i)) => {
+					fnv.write(&[0x07]);
+					fnv.write(&i.to_le_bytes());
+				}
+				Some(val) => bail!("(op-hash) returned {}, rather than an int", val.a_type_name()),
+				None => {
+					bail!("cannot hash an rdata of type {}: it has no op-hash method",
+					      rdata.type_name())
+				}
+			}
+		}
+		Val::Vec2(ref v) => write_components(fnv, 0x0b, &v.to_array()),
+		Val::Vec3(ref v) => write_components(fnv, 0x0c, &v.to_array()),
+		Val::Mat3(ref m) => write_components(fnv, 0x0d, &m.to_cols_array()),
+		ref val => bail!("cannot hash {}", val.a_type_name())
+	}
+
+	Ok(())
+}
+
+pub(crate) fn hash_val(val: &Val) -> GResult<i32> {
+	let mut fnv = Fnv::new();
+	write_val(&mut fnv, val, 0)?;
+
+	Ok((fnv.0 ^ (fnv.0 >> 32)) as u32 as i32)
+}
+
+fn hash(val: Val) -> GResult<i32> {
+	hash_val(&val)
+}
+
+//-------------------------------------------------------------------------------------------------
+// state hashing
+//-------------------------------------------------------------------------------------------------
+
+//glsp::state_hash is used to detect the moment when two deterministic simulations diverge, so 
+//it's stricter than (hash): an int is never confused with an equal flo, -0.0 is distinguished
+//from 0.0, and a table's entries are hashed in a fixed order rather than summed. we use the same
+//FNV-1a hasher, fed with a different encoding:
+//
+//	#n:       0x00
+//	bool:     0x01, followed by 0x00 or 0x01
+//	int:      0x02, followed by four little-endian bytes
+//	flo:      0x03, followed by its bits as four little-endian bytes (eight bytes when the
+//	          "f64-flos" feature is enabled). all nans are converted to Flo::NAN, because
+//	          arithmetic produces a differently-signed nan on x86 and on arm
+//	char:     0x04, followed by its code point as four little-endian bytes
+//	sym:      0x05, followed by its name's length as a u32, followed by its name in UTF-8
+//	str:      0x06, followed by its byte length as a u32, followed by its contents in UTF-8
+//	arr:      0x07, followed by its length as a u32, followed by each element's encoding
+//	tab:      0x08, followed by its length as a u32, followed by each entry's key and value.
+//	          the entries are sorted using Val::canonical_cmp on their keys. keys which it
+//	          considers to be equal (e.g. two objs) are sorted by the bytes of their encoding.
+//	rec:      0x09, followed by its type name and its field count (each encoded as for a sym),
+//	          followed by each field's encoding
+//	obj:      0x0a, followed by its class name's length as a u32 and its class name in UTF-8
+//	          (both zero-length for an anonymous class), followed by the int returned by its
+//	          (op-hash) method as four bytes
+//	rdata:    0x0b, followed by its type name (encoded as for a sym), followed by the int 
+//	          returned by its (op-hash) method as four bytes
+//	back-ref: 0x0c, followed by a u32. when an arr, tab, rec, parr or ptab is encountered for
+//	          a second time, we emit the order in which it was first encountered, rather than
+//	          encoding it again. this terminates cycles, and it means that a value which is
+//	          shared between two places is distinguished from two equal copies.
+//	parr:     0x0d, followed by its length as a u32, followed by each element's encoding
+//	ptab:     0x0e, followed by its length as a u32, followed by each entry's key and value,
+//	          sorted as for a tab
+//	vec2:     0x0f, followed by the bits of each component, encoded as for a flo without its tag
+//	vec3:     0x10, followed by the bits of each component, as for a vec2
+//	mat3:     0x11, followed by the bits of each element in column-major order, as for a vec2
+//
+//each root is encoded in turn, and back-refs may point from one root into another.
+
+trait StateSink {
+	fn write(&mut self, bytes: &[u8]);
+
+	fn write_len(&mut self, len: usize) {
+		self.write(&(len as u32).to_le_bytes());
+	}
+
+	fn write_name(&mut self, name: &str) {
+		self.write_len(name.len());
+		self.write(name.as_bytes());
+	}
+}
+
+impl StateSink for Fnv {
+	fn write(&mut self, bytes: &[u8]) {
+		Fnv::write(self, bytes)
+	}
+}
+
+impl StateSink for Vec<u8> {
+	fn write(&mut self, bytes: &[u8]) {
+		self.extend_from_slice(bytes)
+	}
+}
+
+struct StateEncoder<S: StateSink> {
+	sink: S,
+	seen: HashMap<usize, u32>,
+	root: Option<usize>,
+	path: Vec<Val>
+}
+
+impl<S: StateSink> StateEncoder<S> {
+	fn new(sink: S, root: Option<usize>) -> StateEncoder<S> {
+		StateEncoder {
+			sink,
+			seen: HashMap::new(),
+			root,
+			path: Vec::new()
+		}
+	}
+
+	//returns `true` if the value has already been encoded, in which case we emit a back-ref
+	fn back_ref(&mut self, address: usize) -> bool {
+		match self.seen.get(&address) {
+			Some(&index) => {
+				self.sink.write(&[0x0c]);
+				self.sink.write(&index.to_le_bytes());
+				true
+			}
+			None => {
+				let index = self.seen.len() as u32;
+				self.seen.insert(address, index);
+				false
+			}
+		}
+	}
+
+	fn describe_path(&self) -> String {
+		let mut desc = match self.root {
+			Some(root) => format!("root {}", root),
+			None => String::new()
+		};
+
+		for key in &self.path {
+			desc.push_str(&format!("[{:?}]", key));
+		}
+
+		if desc.is_empty() {
+			desc.push_str("the root")
+		}
+
+		desc
+	}
+
+	fn write_child(&mut self, key: Val, val: &Val) -> GResult<()> {
+		self.path.push(key);
+		self.write_val(val)?;
+		self.path.pop();
+
+		Ok(())
+	}
+
+	fn write_entries(&mut self, tag: u8, src: Vec<(Val, Val)>) -> GResult<()> {
+		//each key is encoded separately, without any back-refs into the rest of the
+		//state, so that its position doesn't depend on where the tab was found
+		let mut entries = Vec::with_capacity(src.len());
+		for (key, value) in src {
+			let mut key_encoder = StateEncoder::new(Vec::<u8>::new(), self.root);
+			key_encoder.path = self.path.clone();
+			key_encoder.write_val(&key)?;
+			entries.push((key_encoder.sink, key, value));
+		}
+
+		entries.sort_by(|(bytes0, key0, _), (bytes1, key1, _)| {
+			key0.canonical_cmp(key1).then_with(|| bytes0.cmp(bytes1))
+		});
+
+		self.sink.write(&[tag]);
+		self.sink.write_len(entries.len());
+		for (_, key, value) in entries {
+			self.write_val(&key)?;
+			self.write_child(key, &value)?;
+		}
+
+		Ok(())
+	}
+
+	fn write_flo_bits(&mut self, f: Flo) {
+		let f = if f.is_nan() { Flo::NAN } else { f };
+		self.sink.write(&f.to_bits().to_le_bytes());
+	}
+
+	fn write_components(&mut self, tag: u8, components: &[Flo]) {
+		self.sink.write(&[tag]);
+		for &component in components {
+			self.write_flo_bits(component);
+		}
+	}
+
+	fn write_val(&mut self, val: &Val) -> GResult<()> {
+		ensure!(self.path.len() <= MAX_DEPTH, "unable to compute a state hash: the value at {} \
+		        is too deeply nested", self.describe_path());
+
+		match *val {
+			Val::Nil => self.sink.write(&[0x00]),
+			Val::Bool(b) => self.sink.write(&[0x01, b as u8]),
+			Val::Int(i) => {
+				self.sink.write(&[0x02]);
+				self.sink.write(&i.to_le_bytes());
+			}
+			Val::Flo(f) => {
+				self.sink.write(&[0x03]);
+				self.write_flo_bits(f);
+			}
+			Val::Char(c) => {
+				self.sink.write(&[0x04]);
+				self.sink.write(&(c as u32).to_le_bytes());
+			}
+			Val::Sym(sym) => {
+				self.sink.write(&[0x05]);
+				self.sink.write_name(&sym.name());
+			}
+			Val::Str(ref st) => {
+				self.sink.write(&[0x06]);
+				self.sink.write_name(&st.to_string());
+			}
+			Val::Arr(ref arr) => {
+				if !self.back_ref(&**arr as *const _ as usize) {
+					self.sink.write(&[0x07]);
+					self.sink.write_len(arr.len());
+					for (i, item) in arr.iter().enumerate() {
+						self.write_child(Val::Int(i as i32), &item)?;
+					}
+				}
+			}
+			Val::Tab(ref tab) => {
+				if !self.back_ref(&**tab as *const _ as usize) {
+					self.write_entries(0x08, tab.entries().iter().collect())?;
+				}
+			}
+			Val::PArr(ref parr) => {
+				if !self.back_ref(&**parr as *const _ as usize) {
+					self.sink.write(&[0x0d]);
+					self.sink.write_len(parr.len());
+					for (i, item) in parr.iter().enumerate() {
+						self.write_child(Val::Int(i as i32), &item)?;
+					}
+				}
+			}
+			Val::PTab(ref ptab) => {
+				if !self.back_ref(&**ptab as *const _ as usize) {
+					self.write_entries(0x0e, ptab.entries())?;
+				}
+			}
+			Val::Rec(ref rec) => {
+				if !self.back_ref(&**rec as *const _ as usize) {
+					self.sink.write(&[0x09]);
+					self.sink.write_name(&rec.name().name());
+					self.sink.write_len(rec.len());
+					for (&field, item) in rec.field_names().iter().zip(rec.field_vals()) {
+						self.write_child(Val::Sym(field), &item)?;
+					}
+				}
+			}
+			Val::Obj(ref obj) => {
+				let name = obj.class().name();
+				match obj.call_if_present(OP_HASH_SYM, &())? {
+					Some(Val::Int(i)) => {
+						self.sink.write(&[0x0a]);
+						self.sink.write_name(&name.map(|sym| sym.name()).unwrap_or_default());
+						self.sink.write(&i.to_le_bytes());
+					}
+					Some(val) => {
+						bail!("unable to compute a state hash: (op-hash) returned {} for the \
+						      obj at {}", val.a_type_name(), self.describe_path())
+					}
+					None => {
+						let name = name.map(|sym| sym.to_string());
+						bail!("unable to compute a state hash: the obj at {} has no op-hash \
+						      method (its class is {})", self.describe_path(), 
+						      name.as_deref().unwrap_or("anonymous"))
+					}
+				}
+			}
+			Val::RData(ref rdata) => {
+				match rdata.call_if_present(OP_HASH_SYM, &())? {
+					Some(Val::Int(i)) => {
+						self.sink.write(&[0x0b]);
+						self.sink.write_name(rdata.type_name());
+						self.sink.write(&i.to_le_bytes());
+					}
+					Some(val) => {
+						bail!("unable to compute a state hash: (op-hash) returned {} for the \
+						      rdata at {}", val.a_type_name(), self.describe_path())
+					}
+					None => {
+						bail!("unable to compute a state hash: the rdata at {} has no op-hash \
+						      method (its type is {})", self.describe_path(), rdata.type_name())
+					}
+				}
+			}
+			Val::Vec2(ref v) => self.write_components(0x0f, &v.to_array()),
+			Val::Vec3(ref v) => self.write_components(0x10, &v.to_array()),
+			Val::Mat3(ref m) => self.write_components(0x11, &m.to_cols_array()),
+			ref val => {
+				bail!("unable to compute a state hash: found {} at {}", val.a_type_name(),
+				      self.describe_path())
+			}
+		}
+
+		Ok(())
+	}
+}
+
+/**
+Deterministically hashes one or more values, along with everything they refer to.
+
+Equivalent to [`(state-hash val)`](https://gamelisp.rs/std/state-hash), but it can hash several
+roots at once. Values which are shared between two roots are only hashed once.
+
+This is intended for detecting desyncs in a deterministic lockstep game: compute the state hash
+of your game state once per tick on each client, and compare the results. The algorithm is 
+guaranteed to produce the same result on every platform, in every `Runtime`. Tables are 
+hashed in a fixed order, and self-referential values are supported.
+
+Functions, coroutines, classes, iterators, and objects and `RData` without an `op-hash` method 
+can't be hashed. The error message describes the path to the unhashable value, such as 
+`root 0[players][3][on-hit]`.
+*/
+pub fn state_hash(roots: &[&Val]) -> GResult<u64> {
+	let mut encoder = StateEncoder::new(Fnv::new(), None);
+
+	for (i, root) in roots.iter().enumerate() {
+		if roots.len() > 1 {
+			encoder.root = Some(i);
+		}
+
+		encoder.write_val(root)?;
+	}
+
+	Ok(encoder.sink.0)
+}
+
+fn state_hash_rfn(val: Val) -> GResult<String> {
+	Ok(format!("{:016x}", state_hash(&[&val])?))
+}
+
+//-------------------------------------------------------------------------------------------------
+// cryptographic digests
+//-------------------------------------------------------------------------------------------------
+
+//these are small, dependency-free implementations of sha-1 and sha-256. they're only intended
+//for content hashing (cache keys, file checksums), not for anything security-sensitive. they're
+//behind a feature flag so that games which don't need them don't pay for the extra code.
+
+#[cfg(feature = "digest")]
+fn hash_str(algorithm: Sym, input: Val) -> GResult<String> {
+	let bytes: Vec<u8> = match input {
+		Val::Str(st) => st.to_string().into_bytes(),
+		Val::Arr(arr) => {
+			let mut bytes = Vec::with_capacity(arr.len());
+			for item in arr.iter() {
+				match item {
+					Val::Int(i) if i >= 0 && i <= 255 => bytes.push(i as u8),
+					val => bail!("(hash-str) expected an arr of bytes, but it contained {}", val)
+				}
+			}
+
+			bytes
+		}
+		val => bail!("(hash-str) expected a str or an arr, received {}", val.a_type_name())
+	};
+
+	let digest = match &*algorithm.name() {
+		"sha1" => sha1(&bytes).to_vec(),
+		"sha256" => sha256(&bytes).to_vec(),
+		name => bail!("unknown hash algorithm {}: expected sha1 or sha256", name)
+	};
+
+	let mut hex = String::with_capacity(digest.len() * 2);
+	for byte in digest {
+		hex.push_str(&format!("{:02x}", byte));
+	}
+
+	Ok(hex)
+}
+
+//appends the standard merkle-damgard padding: a 1 bit, zeroes, and the message length in bits as
+//a big-endian u64, so that the total length is a multiple of 64 bytes
+#[cfg(feature = "digest")]
+fn pad_message(bytes: &[u8]) -> Vec<u8> {
+	let mut padded = bytes.to_vec();
+	padded.push(0x80);
+	while padded.len() % 64 != 56 {
+		padded.push(0);
+	}
+
+	padded.extend_from_slice(&((bytes.len() as u64).wrapping_mul(8)).to_be_bytes());
+	padded
+}
+
+#[cfg(feature = "digest")]
+fn sha1(bytes: &[u8]) -> [u8; 20] {
+	let mut h: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+	for block in pad_message(bytes).chunks_exact(64) {
+		let mut w = [0_u32; 80];
+		for i in 0 .. 16 {
+			w[i] = u32::from_be_bytes([block[i*4], block[i*4 + 1], block[i*4 + 2], block[i*4 + 3]]);
+		}
+		for i in 16 .. 80 {
+			w[i] = (w[i-3] ^ w[i-8] ^ w[i-14] ^ w[i-16]).rotate_left(1);
+		}
+
+		let [mut a, mut b, mut c, mut d, mut e] = h;
+		for i in 0 .. 80 {
+			let (f, k) = match i {
+				0 ..= 19 => ((b & c) | (!b & d), 0x5a827999),
+				20 ..= 39 => (b ^ c ^ d, 0x6ed9eba1),
+				40 ..= 59 => ((b & c) | (b & d) | (c & d), 0x8f1bbcdc),
+				_ => (b ^ c ^ d, 0xca62c1d6)
+			};
+
+			let temp = a.rotate_left(5).wrapping_add(f).wrapping_add(e)
+			            .wrapping_add(k).wrapping_add(w[i]);
+			e = d;
+			d = c;
+			c = b.rotate_left(30);
+			b = a;
+			a = temp;
+		}
+
+		for (h, x) in h.iter_mut().zip(&[a, b, c, d, e]) {
+			*h = h.wrapping_add(*x);
+		}
+	}
+
+	let mut digest = [0_u8; 20];
+	for i in 0 .. 5 {
+		digest[i*4 .. i*4 + 4].copy_from_slice(&h[i].to_be_bytes());
+	}
+
+	digest
+}
+
+#[cfg(feature = "digest")]
+fn sha256(bytes: &[u8]) -> [u8; 32] {
+	const K: [u32; 64] = [
+		0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4,
+		0xab1c5ed5, 0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe,
+		0x9bdc06a7, 0xc19bf174, 0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f,
+		0x4a7484aa, 0x5cb0a9dc, 0x76f988da, 0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7,
+		0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967, 0x27b70a85, 0x2e1b2138, 0x4d2c6dfc,
+		0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85, 0xa2bfe8a1, 0xa81a664b,
+		0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070, 0x19a4c116,
+		0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+		0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7,
+		0xc67178f2
+	];
+
+	let mut h: [u32; 8] = [
+		0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a,
+		0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19
+	];
+
+	for block in pad_message(bytes).chunks_exact(64) {
+		let mut w = [0_u32; 64];
+		for i in 0 .. 16 {
+			w[i] = u32::from_be_bytes([block[i*4], block[i*4 + 1], block[i*4 + 2], block[i*4 + 3]]);
+		}
+		for i in 16 .. 64 {
+			let s0 = w[i-15].rotate_right(7) ^ w[i-15].rotate_right(18) ^ (w[i-15] >> 3);
+			let s1 = w[i-2].rotate_right(17) ^ w[i-2].rotate_right(19) ^ (w[i-2] >> 10);
+			w[i] = w[i-16].wrapping_add(s0).wrapping_add(w[i-7]).wrapping_add(s1);
+		}
+
+		let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut hh] = h;
+		for i in 0 .. 64 {
+			let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+			let ch = (e & f) ^ (!e & g);
+			let temp1 = hh.wrapping_add(s1).wrapping_add(ch).wrapping_add(K[i]).wrapping_add(w[i]);
+			let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+			let maj = (a & b) ^ (a & c) ^ (b & c);
+			let temp2 = s0.wrapping_add(maj);
+
+			hh = g;
+			g = f;
+			f = e;
+			e = d.wrapping_add(temp1);
+			d = c;
+			c = b;
+			b = a;
+			a = temp1.wrapping_add(temp2);
+		}
+
+		for (h, x) in h.iter_mut().zip(&[a, b, c, d, e, f, g, hh]) {
+			*h = h.wrapping_add(*x);
+		}
+	}
+
+	let mut digest = [0_u8; 32];
+	for i in 0 .. 8 {
+		digest[i*4 .. i*4 + 4].copy_from_slice(&h[i].to_be_bytes());
+	}
+
+	digest
+}
@@ -1,5 +1,5 @@
 use glsp::{
-	Arr, bail, Callable, DequeOps, GIter, GResult, Iterable, IterableOps,
+	arr, Arr, bail, Callable, DequeOps, GIter, GResult, Iterable, IterableOps,
 	Num, OrNil, rfn, Root, Str, Tab, Val
 };
 
@@ -11,6 +11,7 @@ pub fn init(_sandboxed: bool) -> GResult<()> {
 	glsp::bind_rfn("iter-next-back!", rfn!(iter_next_back))?;
 	glsp::bind_rfn("iter-finished?", rfn!(iter_finishedp))?;
 	glsp::bind_rfn("iter-double-ended?", rfn!(iter_double_endedp))?;
+	glsp::bind_rfn("close", rfn!(close))?;
 
 	//constructors for basic iterators
 	glsp::bind_rfn("rn", rfn!(rn))?;
@@ -56,6 +57,7 @@ pub fn init(_sandboxed: bool) -> GResult<()> {
 	glsp::bind_rfn("rfind", rfn!(rfind))?;
 	glsp::bind_rfn("fold", rfn!(fold))?;
 	glsp::bind_rfn("rfold", rfn!(rfold))?;
+	glsp::bind_rfn("group-by-into-tab", rfn!(group_by_into_tab))?;
 
 	Ok(())
 }
@@ -76,6 +78,10 @@ fn iter_finishedp(giter: &GIter) -> bool {
 	giter.is_finished()
 }
 
+fn close(giter: &GIter) {
+	giter.close()
+}
+
 fn iter_double_endedp(giter: &GIter) -> bool {
 	giter.is_double_ended()
 }
@@ -296,3 +302,18 @@ fn rfold(callable: Callable, mut accum: Val, iterable: Iterable) -> GResult<Val>
 
 	Ok(accum)
 }
+
+fn group_by_into_tab(callable: Callable, iterable: Iterable) -> GResult<Root<Tab>> {
+	let groups = glsp::tab();
+	for result in iterable.giter() {
+		let item = result?;
+		let key: Val = glsp::call(&callable, &[item.clone()])?;
+
+		match groups.get_if_present::<_, Root<Arr>>(&key)? {
+			Some(group) => group.push(item)?,
+			None => groups.set(key, arr![item])?
+		}
+	}
+
+	Ok(groups)
+}
@@ -0,0 +1,118 @@
+use glsp::{
+	bail, Callable, DequeOps, GResult, Rec, rfn, Root, stock_syms::*, Sym, ToSym, ToVal, Val
+};
+use glsp_proc_macros::{backquote};
+
+pub fn init(_sandboxed: bool) -> GResult<()> {
+	glsp::define_rec(LAZY_SYM, &[STATE_SYM, THUNK_SYM, VALUE_SYM])?;
+
+	glsp::bind_rfn("%make-lazy", rfn!(make_lazy))?;
+	glsp::bind_rfn("lazy?", rfn!(lazyp))?;
+	glsp::bind_rfn("force", rfn!(force_rfn))?;
+
+	glsp::bind_rfn_macro("lazy", rfn!(lazy_macro))?;
+	glsp::bind_rfn_macro("defonce", rfn!(defonce))?;
+
+	Ok(())
+}
+
+//a lazy value is a %lazy rec with three fields: its state ('pending, 'forcing or 'forced), its
+//thunk, and its value. we don't force lazy values implicitly: that would add a check to every
+//global access and every argument passed to an rfn, for the benefit of a rarely-used feature.
+
+const STATE: usize = 0;
+const THUNK: usize = 1;
+const VALUE: usize = 2;
+
+fn make_lazy(thunk: Callable) -> GResult<Root<Rec>> {
+	glsp::rec(LAZY_SYM, [Val::Sym(PENDING_SYM), thunk.to_val()?, Val::Nil])
+}
+
+fn is_lazy(val: &Val) -> Option<&Root<Rec>> {
+	match *val {
+		Val::Rec(ref rec) if rec.name() == LAZY_SYM => Some(rec),
+		_ => None
+	}
+}
+
+fn lazyp(val: Val) -> bool {
+	is_lazy(&val).is_some()
+}
+
+fn force_rfn(val: Val) -> GResult<Val> {
+	force(&val)
+}
+
+/**
+Forces a lazy value.
+
+Equivalent to [`(force val)`](https://gamelisp.rs/std/force).
+
+If `val` was produced by [`lazy`](https://gamelisp.rs/std/lazy) or
+[`glsp::bind_global_lazy`](fn.bind_global_lazy.html), this evaluates it (if it hasn't already
+been evaluated) and returns the result. Otherwise, `val` is returned unchanged.
+*/
+pub fn force(val: &Val) -> GResult<Val> {
+	let rec = match is_lazy(val) {
+		Some(rec) => rec,
+		None => return Ok(val.clone())
+	};
+
+	match rec.get_index::<Sym>(STATE)? {
+		FORCED_SYM => return rec.get_index(VALUE),
+		FORCING_SYM => bail!("a lazy value depends on itself"),
+		_ => ()
+	}
+
+	//if the thunk fails, the lazy value goes back to being pending, so it can be retried
+	let thunk: Callable = rec.get_index(THUNK)?;
+	rec.set_index(STATE, FORCING_SYM)?;
+	let result = glsp::call::<_, _, Val>(&thunk, &());
+
+	match result {
+		Ok(value) => {
+			rec.set_index(VALUE, &value)?;
+			rec.set_index(THUNK, Val::Nil)?;
+			rec.set_index(STATE, FORCED_SYM)?;
+			Ok(value)
+		}
+		Err(err) => {
+			rec.set_index(STATE, PENDING_SYM)?;
+			Err(err)
+		}
+	}
+}
+
+/**
+Binds a global variable to a lazy value, which is initialized by calling `init` the first time
+that it's [forced](fn.force.html).
+
+This is useful for expensive host-provided values, like large lookup tables, which many
+scripts never use.
+
+	glsp::bind_global_lazy("terrain-costs", || {
+		Ok(load_terrain_costs()?)
+	})?;
+
+Scripts must access the global using [`(force terrain-costs)`](https://gamelisp.rs/std/force).
+It's an error if `name` is already bound to a global variable.
+*/
+pub fn bind_global_lazy<S, T, F>(name: S, init: F) -> GResult<()>
+where
+	S: ToSym,
+	T: ToVal,
+	F: Fn() -> GResult<T> + 'static
+{
+	let thunk = glsp::rfn_boxed(Box::new(move |_: &[Val]| init()?.to_val()));
+	let lazy = make_lazy(Callable::RFn(thunk))?;
+	glsp::bind_global(name, lazy)
+}
+
+fn lazy_macro(body: &[Val]) -> Val {
+	backquote!("(%make-lazy (fn () ~..body))")
+}
+
+fn defonce(name: Sym, init: Val) -> GResult<Val> {
+	let name = glsp::qualify_definition(name)?;
+	Ok(backquote!("(if (has-global? '~name) #n (bind-global! '~name ~init))"))
+}
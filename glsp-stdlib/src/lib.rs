@@ -2,29 +2,75 @@
 
 #![feature(proc_macro_hygiene)]
 
-use glsp::{bail, Engine, EngineBuilder, Expander, GResult, GSend, lib, Lib, Sym};
-use std::{i32, thread};
-use std::collections::{hash_map::DefaultHasher, HashMap};
-use std::hash::{Hash, Hasher};
-use std::time::{Duration};
+use glsp::{
+	bail, Engine, EngineBuilder, Expander, FileResolution, GError, GResult, GSend, lib, Lib, 
+	PrintLevel, RFn, rfn, RuntimeHandle, Sym, Val, WrappedFn
+};
+use std::{i32};
+use rand::{Rng};
+use std::borrow::{Cow};
+use std::collections::{HashMap};
+use std::error::{Error};
+use std::fmt::{self, Display, Formatter};
+use std::io::{Write};
+use std::ops::{BitOr, BitOrAssign};
 
 #[cfg(not(target_arch = "wasm32"))]
-use std::time::{Instant, SystemTime};
+use std::{thread, time::{Duration, Instant}};
 
+#[cfg(feature = "tools")]
+mod bench;
+mod channel;
 mod class;
 mod collections;
+mod csv;
+mod env;
+mod events;
+mod fs;
+mod hash;
 mod iter;
+mod lazy;
 mod macros;
+mod memo;
 mod misc;
 mod num;
 mod pat;
+mod persist;
+mod path;
+mod rand;
+mod testing;
+mod time;
+mod typed;
+mod vecmath;
+
+#[cfg(feature = "tools")]
+pub use bench::{bench, BenchReport};
+pub use channel::{channel, GReceiver, GSender};
+pub use csv::{csv_parse, csv_records, csv_write_record, CsvOpts, CsvRecords};
+pub use env::{disable_env, enable_env, EnvPolicy};
+pub use events::{declare_event, emit, listen, unlisten, unlisten_all};
+pub use fs::{disable_fs, enable_fs, FsPolicy, read_lines};
+pub use hash::{state_hash};
+pub use lazy::{bind_global_lazy, force};
+pub use memo::{memoize, MemoOpts};
+pub use testing::{run_tests, TestReport, TestResult};
+pub use typed::{FArr, IArr};
 
 lib! {
 	pub(crate) struct Std {
 		setters: HashMap<Sym, (Sym, bool)>,
 		opt_setters: HashMap<Sym, (Sym, bool)>,
 		classmacros: HashMap<Sym, Expander>,
+		loops: Vec<(Sym, usize)>,
 		rng: Rng,
+		sandboxed: bool,
+		denied: Caps,
+		fs_policy: Option<FsPolicy>,
+		env_policy: Option<EnvPolicy>,
+		clock: Option<fn() -> f64>,
+		events: events::Events,
+		memos: memo::Memos,
+		tests: Vec<testing::Test>,
 
 		#[cfg(not(target_arch = "wasm32"))]
 		start_time: Instant
@@ -32,12 +78,21 @@ lib! {
 }
 
 impl Std {
-	fn new() -> GResult<Std> {
+	fn new(sandboxed: bool, denied: Caps) -> GResult<Std> {
 		Ok(Std {
 			setters: HashMap::new(),
 			opt_setters: HashMap::new(),
 			classmacros: HashMap::new(),
+			loops: Vec::new(),
 			rng: Rng::seeded(),
+			sandboxed,
+			denied,
+			fs_policy: None,
+			env_policy: None,
+			clock: None,
+			events: events::Events::new(),
+			memos: memo::Memos::new(),
+			tests: Vec::new(),
 
 			#[cfg(not(target_arch = "wasm32"))]
 			start_time: std::time::Instant::now()
@@ -53,84 +108,48 @@ impl Std {
 
 #[cfg(not(target_arch = "wasm32"))]
 pub fn time() -> f32 {
-	let std = Std::borrow();
-	Instant::now().duration_since(std.start_time).as_secs_f32()
+	//time::now() can only fail on wasm32
+	time::now().unwrap() as f32
 }
 
-/** Equivalent to [`(sleep secs)`](https://gamelisp.rs/std/sleep). */
+/**
+Overrides the clock used by [`(time)`](https://gamelisp.rs/std/time) and
+[`(stopwatch)`](https://gamelisp.rs/std/stopwatch).
 
-pub fn sleep(secs: f32) -> GResult<()> {
-	//the Duration constructor will panic if secs is "not finite, negative, or overflows Duration"
-	if secs.is_infinite() || secs.is_nan() || secs < 0.0 || secs as i32 > i32::MAX {
-		bail!("{} is not an appropriate duration", secs);
-	}
+By default, GameLisp measures real time, in seconds since the `Runtime` was created. When a game
+can be paused or slowed down, you may prefer for scripts to measure game time instead. The
+`clock` function should return a number of seconds which never decreases.
 
-	thread::sleep(Duration::from_secs_f32(secs));
-	Ok(())
-}
+On the `wasm32` target, there's no default clock, so `(time)` will fail unless this function
+has been called.
 
-pub(crate) struct Rng {
-	x: u32,
-	y: u32,
-	z: u32,
-	w: u32
+The clock can be replaced at any time, or configured up front using
+[`RuntimeBuilder::clock`](struct.RuntimeBuilder.html#method.clock).
+*/
+pub fn set_clock(clock: fn() -> f64) {
+	Std::borrow_mut().clock = Some(clock);
 }
 
-impl Rng {
-	fn seeded() -> Rng {
-		let mut rng = Rng {
-			x: 0,
-			y: 0,
-			z: 0,
-			w: 0
-		};
-
-		//generate our initial seed based on the current time
-		let mut hasher = DefaultHasher::default();
-
-		#[cfg(target_arch = "wasm32")] {
-			42u8.hash(&mut hasher);
-		}
+/** 
+Equivalent to [`(sleep secs)`](https://gamelisp.rs/std/sleep). 
 
-		#[cfg(not(target_arch = "wasm32"))] {
-			let time = SystemTime::now();
-			time.hash(&mut hasher);
-		}
-		
-		rng.reseed(hasher.finish() as u32 as i32);
+On the `wasm32` target, the current thread can't be blocked, so this function always fails.
+*/
 
-		rng
+pub fn sleep(secs: f32) -> GResult<()> {
+	//the Duration constructor will panic if secs is "not finite, negative, or overflows Duration"
+	if secs.is_infinite() || secs.is_nan() || secs < 0.0 || secs as i32 > i32::MAX {
+		bail!("{} is not an appropriate duration", secs);
 	}
 
-	fn reseed(&mut self, mut seed: i32) {
-		if seed == 0 {
-			seed = 1
-		}
-		
-		let x = seed as u32;
-		let y = x.wrapping_mul(48271).wrapping_rem(0x7fffffff);
-		let z = y.wrapping_mul(48271).wrapping_rem(0x7fffffff);
-		let w = z.wrapping_mul(48271).wrapping_rem(0x7fffffff);
-
-		*self = Rng { x, y, z, w };
-
-		for _ in 0 .. 8 {
-			self.gen_u32();
-		}
+	#[cfg(not(target_arch = "wasm32"))] {
+		thread::sleep(Duration::from_secs_f32(secs));
+		Ok(())
 	}
-	
-	fn gen_u32(&mut self) -> u32 {
-		let Rng { x, y, z, w } = *self;
 
-		let t = x ^ x.wrapping_shl(11);
-		let new_w = w ^ w.wrapping_shr(19) ^ (t ^ t.wrapping_shr(8));
-
-		self.x = y;
-		self.y = z;
-		self.z = w;
-		self.w = new_w;
-
-		new_w
+	//thread::sleep panics on wasm32
+	#[cfg(target_arch = "wasm32")] {
+		bail!("sleep is not supported on the wasm32 target")
 	}
 }
 
@@ -145,29 +164,34 @@ pub fn rand_i32() -> i32 {
 Uses GameLisp's [random number generator](https://gamelisp.rs/std/rand) to produce an `f32`.
 */
 pub fn rand_f32() -> f32 {
-	//16777215 (0xffffff) is the largest continguous integer which can be exactly represented by 
-	//an f32. seems like as good a choice as any other for our divisor. testing reveals that if 
-	//the rng spits out 16777214u32, rand_float does generate 0.999something, rather than breaking 
-	//the upper limit of our [0,1) range.
-	let rand_u32 = Std::borrow_mut().rng.gen_u32() >> 8;
-	(rand_u32 as f32) / 16777215.0f32
+	Std::borrow_mut().rng.gen_f32()
 }
 
 /** 
 Uses GameLisp's [random number generator](https://gamelisp.rs/std/rand) to produce a `bool`.
 */
 pub fn rand_bool() -> bool {
-	//i don't necessarily trust the lowest bit of this rng, so we select a bit "randomly"
-	let rand_u32 = Std::borrow_mut().rng.gen_u32();
-	let to_shift = (rand_u32 & 0xf) + 4; //select from bits 4 through 20
-	((rand_u32 >> (to_shift as usize)) & 1) == 1
+	//the low bits of xoshiro128** are as good as the high bits, but there's no harm in using
+	//the most significant bit
+	(Std::borrow_mut().rng.gen_u32() >> 31) == 1
 }
 
 /**
 Equivalent to [`(rand-reseed seed)`](https://gamelisp.rs/std/rand-reseed).
 */
 pub fn rand_reseed(seed: i32) {
-	Std::borrow_mut().rng.reseed(seed)
+	Std::borrow_mut().rng = Rng::from_seed(seed as u32 as u64)
+}
+
+/**
+Reseeds the global random number generator used by [`(rand)`](https://gamelisp.rs/std/rand),
+[`(chance)`](https://gamelisp.rs/std/chance) and similar functions.
+
+For a given seed, the generator produces the same sequence of values on every platform.
+When `n` is a non-negative `i32`, `seed_global_rng(n as u64)` is equivalent to `(rand-reseed n)`.
+*/
+pub fn seed_global_rng(seed: u64) {
+	Std::borrow_mut().rng = Rng::from_seed(seed)
 }
 
 /**
@@ -188,6 +212,25 @@ It's possible for multiple `Runtimes` to coexist. Each runtime is strictly isola
 others - they don't share global variables, symbols, and so on. This means that it's possible
 to run GameLisp code in isolated `Runtimes` on multiple threads without needing any 
 synchronization.
+
+# Threading Model
+
+`Runtime` is neither `Send` nor `Sync`. A `Runtime` can be constructed on any thread, but it
+stays on that thread for its entire lifetime. Any number of threads may each own one or more
+`Runtimes` and use them simultaneously; there's no global lock, and no state is shared between
+them.
+
+Each runtime has its own symbol table. Interning the same string in two different runtimes
+produces two unrelated `Sym`s, and a `Sym` which is smuggled from one runtime to another
+will refer to a different symbol (or none at all). Symbols should always be passed between
+runtimes as strings.
+
+The [`GSend`](trait.GSend.html) bound on `run` prevents `Roots`, `Vals`, `Syms` and similar
+types from being moved from one runtime into another. If that check is circumvented (for
+example, by storing a `Root` in a `thread_local!`), then GameLisp will detect the mismatch
+as soon as the `Root` is cloned, dropped, or stored in a collection belonging to another
+runtime. This will print an error message and abort the process, because it's the only way
+to uphold memory safety.
 */
 
 //a Runtime is just a thin wrapper for an Engine which has been initialized with the stdlib. we 
@@ -200,8 +243,8 @@ impl Runtime {
 	/**
 	Construct a `Runtime` with default settings.
 
-	To construct a custom `Runtime`, use [`RuntimeBuilder`](struct.RuntimeBuilder.html)
-	instead.
+	Equivalent to `RuntimeBuilder::new().build()`. To construct a custom `Runtime`, use 
+	[`RuntimeBuilder`](struct.RuntimeBuilder.html) instead.
 	*/
 	pub fn new() -> Runtime {
 
@@ -218,14 +261,6 @@ impl Runtime {
 		RuntimeBuilder::new().build()
 	}
 
-	fn with_settings(sandboxed: bool, engine: Engine) -> Runtime {
-		engine.run(|| {
-			init_stdlib(sandboxed)
-		}).unwrap();
-
-		Runtime(engine)
-	}
-
 	/**
 	Establish this `Runtime` as the active runtime.
 
@@ -241,15 +276,42 @@ impl Runtime {
 	{
 		self.0.run(f)
 	}
+
+	/**
+	Returns a handle to this `Runtime` which can be sent to other threads.
+
+	See [`glsp::current_execution_point`](fn.current_execution_point.html).
+	*/
+	pub fn handle(&self) -> RuntimeHandle {
+		self.0.handle()
+	}
 }
 
 /**
 Configuration options for constructing a [`Runtime`](struct.Runtime.html).
 
-Currently, the only option is [`sandboxed`](#method.sandboxed).
+	let runtime = RuntimeBuilder::new()
+		.deny(Caps::EVAL)
+		.type_checks(true)
+		.log_level(PrintLevel::Warn)
+		.build();
+
+Some options, like [`opt_level`](#method.opt_level) and [`type_checks`](#method.type_checks),
+are fixed for the lifetime of the `Runtime`. The rest only provide an initial value, which can
+be changed later by calling the corresponding global function, such as 
+[`glsp::set_fuel`](fn.set_fuel.html).
+
+Options which contradict one another, like a [`print_sink`](#method.print_sink) alongside a
+[`pr_writer`](#method.pr_writer), are detected by [`try_build`](#method.try_build).
 */
 pub struct RuntimeBuilder {
 	sandboxed: bool,
+	denied: Caps,
+	clock: Option<fn() -> f64>,
+	fs_policy: Option<FsPolicy>,
+	env_policy: Option<EnvPolicy>,
+	rng_seed: Option<u64>,
+	compiled: Option<Vec<u8>>,
 	engine_builder: EngineBuilder
 }
 
@@ -257,6 +319,12 @@ impl RuntimeBuilder {
 	pub fn new() -> RuntimeBuilder {
 		RuntimeBuilder {
 			sandboxed: false,
+			denied: Caps::NONE,
+			clock: None,
+			fs_policy: None,
+			env_policy: None,
+			rng_seed: None,
+			compiled: None,
 			engine_builder: EngineBuilder::new()
 		}
 	}
@@ -268,6 +336,16 @@ impl RuntimeBuilder {
 	[`require`](https://gamelisp.rs/std/require) and [`include`](https://gamelisp.rs/std/include)
 	are not defined in the GameLisp runtime. It's still possible for Rust code to call 
 	[`glsp::load`](fn.load.html) or [`glsp::require`](fn.require.html).
+
+	The filesystem functions, such as [`read-file`](https://gamelisp.rs/std/read-file), and the
+	environment functions, such as [`getenv`](https://gamelisp.rs/std/getenv), are also left 
+	undefined. [`glsp::enable_fs`](fn.enable_fs.html) and 
+	[`glsp::enable_env`](fn.enable_env.html) will return an `Err`.
+
+	This is similar to denying the [`LOAD`](struct.Caps.html#associatedconstant.LOAD), 
+	[`FS`](struct.Caps.html#associatedconstant.FS) and 
+	[`ENV`](struct.Caps.html#associatedconstant.ENV) capabilities, except that the affected
+	functions are left unbound, rather than being bound to stubs.
 	*/
 	pub fn sandboxed(self, sandboxed: bool) -> RuntimeBuilder {
 		RuntimeBuilder {
@@ -276,23 +354,634 @@ impl RuntimeBuilder {
 		}
 	}
 
-	///Construct a `Runtime` with these settings.
+	/**
+	Denies one or more [capabilities](struct.Caps.html) to GameLisp code.
+
+		let runtime = RuntimeBuilder::new()
+			.deny(Caps::LOAD | Caps::FS | Caps::EVAL)
+			.build();
+
+	Each builtin which requires a denied capability is bound to a stub function, or a stub 
+	macro, which fails with the error payload `capability-denied` whenever it's called. The 
+	real builtin is never registered with the `Runtime`, so it can't be reached through any
+	other binding.
+
+	Calling this method more than once denies the union of all of the given capabilities. It's 
+	still possible for Rust code to call functions like [`glsp::load`](fn.load.html) and
+	[`glsp::eval`](fn.eval.html) directly.
+	*/
+	pub fn deny(self, caps: Caps) -> RuntimeBuilder {
+		RuntimeBuilder {
+			denied: self.denied | caps,
+			..self
+		}
+	}
+
+	/**
+	Sets the optimization level used when compiling GameLisp code.
+
+	At level `0`, each fully-expanded form is translated directly into bytecode. At level `1`
+	(the default), the compiler also performs constant folding and dead-code elimination:
+
+	- Arithmetic, bitwise operations and comparisons between literal numbers, and type checks 
+	  on literal values, are evaluated during compilation.
+	- An `if` form whose condition is a literal is replaced with the branch that would be taken.
+	- Forms which follow a `return`, `finish-block` or `restart-block` are discarded.
+	- A local `fn` which is bound using `let`, but never used, is not constructed.
+	- Some common sequences of bytecode instructions, like a numeric comparison followed by a
+	  conditional jump, are fused into a single instruction.
+
+	These optimizations never change a program's observable behaviour. In particular, an 
+	expression which would fail at runtime, like `(/ 1 0)`, is left unfolded so that its error
+	is reported in the usual place. Levels above `1` are currently equivalent to `1`.
+
+	This option is fixed for the lifetime of the `Runtime`.
+	*/
+	pub fn opt_level(self, level: u8) -> RuntimeBuilder {
+		RuntimeBuilder {
+			engine_builder: self.engine_builder.opt_level(level),
+			..self
+		}
+	}
+
+	/**
+	Sets whether the compiler records the name of each GameLisp function's parameters. 
+	Defaults to `true`.
+
+	Parameter names can be queried using [`GFn::param_names`](struct.GFn.html#method.param_names)
+	or [`(params f)`](https://gamelisp.rs/std/params). They're useful for tooling, like argument
+	hints in an in-game console. Each function which retains its parameter names stores an 
+	extra sixteen bytes, plus eight bytes per parameter. When a file is compiled using
+	[`glsp::load_and_compile`](fn.load_and_compile.html), its parameter names are only 
+	preserved if this option was enabled.
+
+	This option is fixed for the lifetime of the `Runtime`.
+	*/
+	pub fn retain_param_names(self, enabled: bool) -> RuntimeBuilder {
+		RuntimeBuilder {
+			engine_builder: self.engine_builder.retain_param_names(enabled),
+			..self
+		}
+	}
+
+	/**
+	Sets whether the compiler checks the types of annotated parameters and return values.
+	Defaults to `false`.
+
+	A parameter annotation like `(amount : int)`, or a return-type annotation like `-> int`, 
+	names a primitive type, a class, a record type or a predicate function. When this option is
+	enabled, each annotated function checks its arguments when it's called, and checks its 
+	result when its body finishes. A failed check raises an error whose payload is the symbol
+	`type-error`.
+
+	When this option is disabled, annotations are only metadata. They can be queried using 
+	[`GFn::params`](struct.GFn.html#method.params) and 
+	[`GFn::return_type`](struct.GFn.html#method.return_type).
+
+	This option is fixed for the lifetime of the `Runtime`.
+	*/
+	pub fn type_checks(self, enabled: bool) -> RuntimeBuilder {
+		RuntimeBuilder {
+			engine_builder: self.engine_builder.type_checks(enabled),
+			..self
+		}
+	}
+
+	/**
+	Enables or disables tail-call elimination. Defaults to `true`.
+
+	Can be changed later using [`glsp::set_tail_calls`](fn.set_tail_calls.html).
+	*/
+	pub fn tail_calls(self, enabled: bool) -> RuntimeBuilder {
+		RuntimeBuilder {
+			engine_builder: self.engine_builder.tail_calls(enabled),
+			..self
+		}
+	}
+
+	/**
+	Limits how deeply GameLisp calls may be nested. Defaults to 256. It must be at least 1.
+
+	Can be changed later using [`glsp::set_max_call_depth`](fn.set_max_call_depth.html).
+	*/
+	pub fn max_call_depth(self, depth: u32) -> RuntimeBuilder {
+		RuntimeBuilder {
+			engine_builder: self.engine_builder.max_call_depth(depth),
+			..self
+		}
+	}
+
+	/**
+	Limits how much native stack may be consumed by nested GameLisp calls. By default, there's
+	no limit.
+
+	Can be changed later using [`glsp::set_stack_budget`](fn.set_stack_budget.html).
+	*/
+	pub fn stack_budget(self, bytes: usize) -> RuntimeBuilder {
+		RuntimeBuilder {
+			engine_builder: self.engine_builder.stack_budget(Some(bytes)),
+			..self
+		}
+	}
+
+	/**
+	Limits the number of instructions which the GameLisp interpreter may execute. By default,
+	there's no limit.
+
+	Can be changed later using [`glsp::set_fuel`](fn.set_fuel.html).
+	*/
+	pub fn fuel(self, fuel: u64) -> RuntimeBuilder {
+		RuntimeBuilder {
+			engine_builder: self.engine_builder.fuel(Some(fuel)),
+			..self
+		}
+	}
+
+	/**
+	Sets the garbage collector's ratio. It must be a finite number.
+
+	Can be changed later using [`glsp::gc_set_ratio`](fn.gc_set_ratio.html).
+	*/
+	pub fn gc_ratio(self, ratio: f32) -> RuntimeBuilder {
+		RuntimeBuilder {
+			engine_builder: self.engine_builder.gc_ratio(ratio),
+			..self
+		}
+	}
+
+	/**
+	Discards any printed text which is less severe than `level`. Defaults to 
+	`PrintLevel::Print`.
+
+	Can be changed later using [`glsp::set_log_level`](fn.set_log_level.html).
+	*/
+	pub fn log_level(self, level: PrintLevel) -> RuntimeBuilder {
+		RuntimeBuilder {
+			engine_builder: self.engine_builder.log_level(level),
+			..self
+		}
+	}
+
+	/**
+	Sets the output writer used by [`pr`](https://gamelisp.rs/std/pr) and related functions.
+	Defaults to `Stdout`. Can't be combined with a [`print_sink`](#method.print_sink).
+
+	Can be changed later using [`glsp::set_pr_writer`](fn.set_pr_writer.html).
+	*/
+	pub fn pr_writer(self, pr_writer: Box<dyn Write>) -> RuntimeBuilder {
+		RuntimeBuilder {
+			engine_builder: self.engine_builder.pr_writer(pr_writer),
+			..self
+		}
+	}
+
+	/**
+	Sets the output writer used by [`epr`](https://gamelisp.rs/std/epr) and related functions,
+	and for reporting uncaught errors. Defaults to `Stderr`. Can't be combined with a 
+	[`print_sink`](#method.print_sink).
+
+	Can be changed later using [`glsp::set_epr_writer`](fn.set_epr_writer.html).
+	*/
+	pub fn epr_writer(self, epr_writer: Box<dyn Write>) -> RuntimeBuilder {
+		RuntimeBuilder {
+			engine_builder: self.engine_builder.epr_writer(epr_writer),
+			..self
+		}
+	}
+
+	/**
+	Redirects all printed text to a callback, rather than the `pr_writer` and `epr_writer`.
+
+	Can be changed later using [`glsp::set_print_sink`](fn.set_print_sink.html).
+	*/
+	pub fn print_sink(self, sink: Box<dyn Fn(PrintLevel, &str)>) -> RuntimeBuilder {
+		RuntimeBuilder {
+			engine_builder: self.engine_builder.print_sink(sink),
+			..self
+		}
+	}
+
+	/**
+	Replaces the function which is used to obtain the source text for a file.
+
+	Can be changed later using [`glsp::set_loader`](fn.set_loader.html).
+	*/
+	pub fn loader<F>(self, loader: F) -> RuntimeBuilder
+	where
+		F: Fn(&str) -> GResult<Cow<'static, str>> + 'static
+	{
+		RuntimeBuilder {
+			engine_builder: self.engine_builder.loader(Box::new(loader)),
+			..self
+		}
+	}
+
+	/**
+	Sets the rules used to resolve filenames. Defaults to 
+	[`FileResolution::Root`](enum.FileResolution.html#variant.Root).
+
+	Can be changed later using [`glsp::set_file_resolution`](fn.set_file_resolution.html).
+	*/
+	pub fn file_resolution(self, resolution: FileResolution) -> RuntimeBuilder {
+		RuntimeBuilder {
+			engine_builder: self.engine_builder.file_resolution(resolution),
+			..self
+		}
+	}
+
+	/**
+	Overrides the clock used by [`(time)`](https://gamelisp.rs/std/time).
+
+	Can be changed later using [`glsp::set_clock`](fn.set_clock.html).
+	*/
+	pub fn clock(self, clock: fn() -> f64) -> RuntimeBuilder {
+		RuntimeBuilder {
+			clock: Some(clock),
+			..self
+		}
+	}
+
+	/**
+	Enables the GameLisp filesystem functions, with the given policy.
+
+	Can't be combined with [`sandboxed`](#method.sandboxed), or with denying the
+	[`FS`](struct.Caps.html#associatedconstant.FS) capability. Can be changed later using 
+	[`glsp::enable_fs`](fn.enable_fs.html) and [`glsp::disable_fs`](fn.disable_fs.html).
+	*/
+	pub fn fs(self, policy: FsPolicy) -> RuntimeBuilder {
+		RuntimeBuilder {
+			fs_policy: Some(policy),
+			..self
+		}
+	}
+
+	/**
+	Enables the GameLisp functions which inspect the host environment, with the given policy.
+
+	Can't be combined with [`sandboxed`](#method.sandboxed), or with denying the
+	[`ENV`](struct.Caps.html#associatedconstant.ENV) capability. Can be changed later using 
+	[`glsp::enable_env`](fn.enable_env.html) and [`glsp::disable_env`](fn.disable_env.html).
+	*/
+	pub fn env(self, policy: EnvPolicy) -> RuntimeBuilder {
+		RuntimeBuilder {
+			env_policy: Some(policy),
+			..self
+		}
+	}
+
+	/**
+	Seeds the random number generator. By default, it's seeded from the system clock.
+
+	Can be changed later using [`glsp::seed_global_rng`](fn.seed_global_rng.html).
+	*/
+	pub fn rng_seed(self, seed: u64) -> RuntimeBuilder {
+		RuntimeBuilder {
+			rng_seed: Some(seed),
+			..self
+		}
+	}
+
+	/**
+	Plays back a compiled file, produced by 
+	[`glsp::load_and_compile`](fn.load_and_compile.html), as the last step of building the
+	`Runtime`.
+
+	This is equivalent to calling [`glsp::load_compiled`](fn.load_compiled.html) immediately
+	after construction. It requires the `compiler` feature flag: without it, `try_build` will
+	return an `Err`.
+	*/
+	pub fn load_compiled(self, bytes: Vec<u8>) -> RuntimeBuilder {
+		RuntimeBuilder {
+			compiled: Some(bytes),
+			..self
+		}
+	}
+
+	/**
+	Construct a `Runtime` with these settings.
+
+	Panics if the settings are invalid. See [`try_build`](#method.try_build).
+	*/
 	pub fn build(self) -> Runtime {
-		Runtime::with_settings(self.sandboxed, self.engine_builder.build())
+		match self.try_build() {
+			Ok(runtime) => runtime,
+			Err(err) => panic!("{}", err)
+		}
+	}
+
+	/**
+	Construct a `Runtime` with these settings, or return an `Err` if they're invalid.
+
+	The settings are invalid if two options contradict one another, or if one of them can't be
+	applied: for example, if the [`fs`](#method.fs) policy's root directory doesn't exist, or
+	if the [compiled file](#method.load_compiled) fails to load.
+	*/
+	pub fn try_build(self) -> Result<Runtime, BuildError> {
+		self.validate()?;
+
+		let RuntimeBuilder {
+			sandboxed,
+			denied,
+			clock,
+			fs_policy,
+			env_policy,
+			rng_seed,
+			compiled,
+			engine_builder
+		} = self;
+
+		let engine = engine_builder.build().map_err(BuildError)?;
+		let result = engine.run(|| {
+			init_stdlib(sandboxed, denied)?;
+
+			//these options are validated above, so a failure here is an environmental problem,
+			//which we report as a BuildError rather than an uncaught GameLisp error
+			let apply = || -> GResult<()> {
+				if let Some(clock) = clock {
+					set_clock(clock);
+				}
+				if let Some(seed) = rng_seed {
+					seed_global_rng(seed);
+				}
+				if let Some(policy) = fs_policy {
+					enable_fs(policy)?;
+				}
+				if let Some(policy) = env_policy {
+					enable_env(policy)?;
+				}
+				if let Some(bytes) = compiled {
+					load_compiled(&bytes)?;
+				}
+
+				Ok(())
+			};
+
+			Ok(apply().map_err(|err| BuildError(err.to_string())))
+		}).unwrap();
+
+		result.map(|_| Runtime(engine))
+	}
+
+	fn validate(&self) -> Result<(), BuildError> {
+		if self.fs_policy.is_some() {
+			if self.sandboxed {
+				return Err(BuildError("the fs option can't be used in a sandboxed Runtime".into()))
+			}
+			if self.denied.contains(Caps::FS) {
+				return Err(BuildError("the fs option can't be used when the FS capability \
+				                       has been denied".into()))
+			}
+		}
+
+		if self.env_policy.is_some() {
+			if self.sandboxed {
+				return Err(BuildError("the env option can't be used in a sandboxed Runtime".into()))
+			}
+			if self.denied.contains(Caps::ENV) {
+				return Err(BuildError("the env option can't be used when the ENV capability \
+				                       has been denied".into()))
+			}
+		}
+
+		if self.compiled.is_some() && !cfg!(feature = "compiler") {
+			return Err(BuildError("the load_compiled option requires the compiler feature \
+			                       flag, which is disabled".into()))
+		}
+
+		self.engine_builder.validate().map_err(BuildError)
+	}
+}
+
+#[cfg(feature = "compiler")]
+fn load_compiled(bytes: &[u8]) -> GResult<()> {
+	glsp::load_compiled(bytes).map(|_| ())
+}
+
+//unreachable, because RuntimeBuilder::validate rejects the load_compiled option
+#[cfg(not(feature = "compiler"))]
+fn load_compiled(_bytes: &[u8]) -> GResult<()> {
+	bail!("the compiler feature flag is disabled")
+}
+
+/**
+The error returned by [`RuntimeBuilder::try_build`](struct.RuntimeBuilder.html#method.try_build).
+
+There's no active `Runtime` when a `BuildError` is produced, so it's a plain error message 
+rather than a [`GError`](struct.GError.html).
+*/
+
+#[derive(Clone, Debug)]
+pub struct BuildError(String);
+
+impl BuildError {
+	///Returns a description of the problem.
+	pub fn message(&self) -> &str {
+		&self.0
+	}
+}
+
+impl Display for BuildError {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		write!(f, "unable to build a Runtime: {}", self.0)
+	}
+}
+
+impl Error for BuildError { }
+
+/**
+A set of capabilities which can be denied to GameLisp code.
+
+Passed to [`RuntimeBuilder::deny`](struct.RuntimeBuilder.html#method.deny). Sets of 
+capabilities can be combined using the `|` operator.
+*/
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Caps(u32);
+
+impl Caps {
+	///The empty set.
+	pub const NONE: Caps = Caps(0);
+
+	/**
+	Loading source files: [`load`](https://gamelisp.rs/std/load), 
+	[`require`](https://gamelisp.rs/std/require), [`reload`](https://gamelisp.rs/std/reload) 
+	and [`include`](https://gamelisp.rs/std/include), as well as 
+	[`snapshot-globals`](https://gamelisp.rs/std/snapshot-globals) and 
+	[`restore-globals!`](https://gamelisp.rs/std/restore-globals-mut).
+	*/
+	pub const LOAD: Caps = Caps(0x1);
+
+	/**
+	The filesystem: [`read-file`](https://gamelisp.rs/std/read-file), 
+	[`read-lines`](https://gamelisp.rs/std/read-lines), 
+	[`write-file`](https://gamelisp.rs/std/write-file), 
+	[`append-file`](https://gamelisp.rs/std/append-file), 
+	[`file-exists?`](https://gamelisp.rs/std/file-exists-p), 
+	[`read-dir`](https://gamelisp.rs/std/read-dir) and 
+	[`remove-file`](https://gamelisp.rs/std/remove-file). When this capability is denied,
+	[`glsp::enable_fs`](fn.enable_fs.html) will return an `Err`.
+	*/
+	pub const FS: Caps = Caps(0x2);
+
+	/**
+	The host environment: [`getenv`](https://gamelisp.rs/std/getenv), 
+	[`args`](https://gamelisp.rs/std/args), [`hostname`](https://gamelisp.rs/std/hostname) and 
+	[`exit`](https://gamelisp.rs/std/exit). When this capability is denied,
+	[`glsp::enable_env`](fn.enable_env.html) will return an `Err`.
+	*/
+	pub const ENV: Caps = Caps(0x4);
+
+	/**
+	Evaluating data as code: [`eval`](https://gamelisp.rs/std/eval),
+	[`eval-multi`](https://gamelisp.rs/std/eval-multi) and
+	[`compile-fn`](https://gamelisp.rs/std/compile-fn), and the internal functions 
+	`%eval-as-method` and `%create-pseudo-method`.
+	*/
+	pub const EVAL: Caps = Caps(0x8);
+
+	/**
+	Defining macros in GameLisp: [`defmacro`](https://gamelisp.rs/std/defmacro), 
+	[`defclassmacro`](https://gamelisp.rs/std/defclassmacro), `bind-classmacro!` and 
+	[`let-macro`](https://gamelisp.rs/std/let-macro). Any call to 
+	[`bind-macro!`](https://gamelisp.rs/std/bind-macro-mut), 
+	[`macro=`](https://gamelisp.rs/std/set-macro) or 
+	[`macro-opt=`](https://gamelisp.rs/std/set-macro-opt) which would bind a GameLisp function
+	as a macro will fail. Macros which are implemented in Rust can still be bound.
+	*/
+	pub const MACROS: Caps = Caps(0x10);
+
+	///Every capability.
+	pub const ALL: Caps = Caps(0x1f);
+
+	///Returns `true` if every capability in `other` is also present in `self`.
+	pub fn contains(self, other: Caps) -> bool {
+		(self.0 & other.0) == other.0
+	}
+}
+
+impl BitOr for Caps {
+	type Output = Caps;
+
+	fn bitor(self, other: Caps) -> Caps {
+		Caps(self.0 | other.0)
 	}
 }
 
-fn init_stdlib(sandboxed: bool) -> GResult<()> {
-	glsp::add_lib(Std::new()?);
+impl BitOrAssign for Caps {
+	fn bitor_assign(&mut self, other: Caps) {
+		self.0 |= other.0
+	}
+}
+
+//binds a builtin which requires the capability `cap`. in a sandboxed Runtime, builtins which 
+//require LOAD, FS or ENV are left unbound. otherwise, if `cap` has been denied, we bind a stub
+//in its place. the real rfn is never registered, so there's no way for a script to reach it.
+pub(crate) fn bind_cap_rfn(name: &str, cap: Caps, wrapped_fn: WrappedFn) -> GResult<()> {
+	match cap_state(cap) {
+		CapState::Granted => {
+			glsp::bind_rfn(name, wrapped_fn)?;
+			glsp::set_api_capability(name, cap_name(cap))
+		}
+		CapState::Denied => glsp::bind_global(name, capability_denied_stub()?),
+		CapState::Sandboxed => Ok(())
+	}
+}
 
-	class::init(sandboxed)?;
-	collections::init(sandboxed)?;
-	iter::init(sandboxed)?;
-	macros::init(sandboxed)?;
-	misc::init(sandboxed)?;
-	num::init(sandboxed)?;
+pub(crate) fn bind_cap_rfn_macro(name: &str, cap: Caps, wrapped_fn: WrappedFn) -> GResult<()> {
+	match cap_state(cap) {
+		CapState::Granted => {
+			glsp::bind_rfn_macro(name, wrapped_fn)?;
+			glsp::set_api_capability(name, cap_name(cap))
+		}
+		CapState::Denied => glsp::bind_macro(name, Expander::RFn(capability_denied_stub()?)),
+		CapState::Sandboxed => Ok(())
+	}
+}
+
+//the name of a single capability, as it's recorded in the api manifest
+fn cap_name(cap: Caps) -> &'static str {
+	match cap {
+		Caps::LOAD => "LOAD",
+		Caps::FS => "FS",
+		Caps::ENV => "ENV",
+		Caps::EVAL => "EVAL",
+		Caps::MACROS => "MACROS",
+		_ => panic!("builtins can't require more than one capability")
+	}
+}
+
+enum CapState {
+	Granted,
+	Denied,
+	Sandboxed
+}
+
+fn cap_state(cap: Caps) -> CapState {
+	let std = Std::borrow();
+	if std.sandboxed && (Caps::LOAD | Caps::FS | Caps::ENV).contains(cap) {
+		CapState::Sandboxed
+	} else if std.denied.contains(cap) {
+		CapState::Denied
+	} else {
+		CapState::Granted
+	}
+}
+
+//every stub shares a single rfn, so it's named after the error rather than the builtin
+fn capability_denied_stub() -> GResult<RFn> {
+	Ok(glsp::named_rfn(glsp::sym("capability-denied")?, rfn!(capability_denied)))
+}
+
+fn capability_denied(_args: &[Val]) -> GResult<Val> {
+	Err(GError::from_val(glsp::sym("capability-denied")?))
+}
+
+//when MACROS has been denied, the functions which bind global macros refuse gfn expanders. 
+//rfn expanders can only be created by Rust code, so they're still permitted.
+pub(crate) fn ensure_macro_permitted(expander: &Expander) -> GResult<()> {
+	match *expander {
+		Expander::GFn(_) if Std::borrow().denied.contains(Caps::MACROS) => {
+			Err(GError::from_val(glsp::sym("capability-denied")?))
+		}
+		_ => Ok(())
+	}
+}
+
+fn init_stdlib(sandboxed: bool, denied: Caps) -> GResult<()> {
+	glsp::add_lib(Std::new(sandboxed, denied)?);
+
+	glsp::with_api_library("std", || -> GResult<()> {
+		#[cfg(feature = "tools")]
+		bench::init(sandboxed)?;
+		channel::init(sandboxed)?;
+		class::init(sandboxed)?;
+		collections::init(sandboxed)?;
+		csv::init(sandboxed)?;
+		env::init(sandboxed)?;
+		events::init(sandboxed)?;
+		fs::init(sandboxed)?;
+		hash::init(sandboxed)?;
+		iter::init(sandboxed)?;
+		lazy::init(sandboxed)?;
+		macros::init(sandboxed)?;
+		memo::init(sandboxed)?;
+		misc::init(sandboxed)?;
+		num::init(sandboxed)?;
+		path::init(sandboxed)?;
+		persist::init(sandboxed)?;
+		rand::init(sandboxed)?;
+		testing::init(sandboxed)?;
+		time::init(sandboxed)?;
+		typed::init(sandboxed)?;
+		vecmath::init(sandboxed)?;
+		Ok(())
+	})?;
 
 	glsp::freeze_transform_fns();
 
+	if denied.contains(Caps::MACROS) {
+		glsp::deny_let_macro();
+	}
+
 	Ok(())
 }
@@ -1,23 +1,20 @@
 use glsp::{
 	arr, Arr, bail, bail_at, Callable, DequeAccess, DequeOps, 
-	ensure, EnvMode, error, FromVal, GResult, Lib, macro_no_op, 
+	ensure, ensure_at, EnvMode, FromVal, GError, GResult, Lib, macro_no_op, OrNil,
 	rfn, Root, Span, Sym, stock_syms::*, str, Val
 };
 use glsp_proc_macros::{backquote};
 use smallvec::{SmallVec};
-use std::{fs};
 use std::collections::{HashMap, hash_map::Entry::{Occupied, Vacant}, HashSet};
 use std::default::{Default};
 use std::iter::FromIterator;
-use super::{Std};
+use super::{bind_cap_rfn_macro, Caps, Std};
 use super::pat::{
 	AssignStrategy, Matcher, MismatchStrategy, Pat, pat_from_forms, PlaceStrategy, SetStrategy
 };
 
-pub fn init(sandboxed: bool) -> GResult<()> {
-	if !sandboxed {
-		glsp::bind_rfn_macro("include", rfn!(include))?;
-	}
+pub fn init(_sandboxed: bool) -> GResult<()> {
+	bind_cap_rfn_macro("include", Caps::LOAD, rfn!(include))?;
 	
 	glsp::bind_rfn_macro("+", rfn!(add))?;
 	glsp::bind_rfn_macro("-", rfn!(sub))?;
@@ -28,11 +25,17 @@ pub fn init(sandboxed: bool) -> GResult<()> {
 	glsp::bind_rfn_macro("bitand", rfn!(bitand))?;
 	glsp::bind_rfn_macro("bitor", rfn!(bitor))?;
 	glsp::bind_rfn_macro("bitxor", rfn!(bitxor))?;
+	glsp::bind_rfn_macro("bit-and", rfn!(bit_and))?;
+	glsp::bind_rfn_macro("bit-or", rfn!(bit_or))?;
+	glsp::bind_rfn_macro("bit-xor", rfn!(bit_xor))?;
 
 	glsp::bind_rfn_macro("def", rfn!(def))?;
+	glsp::bind_rfn_macro("defconst", rfn!(defconst))?;
 	glsp::bind_rfn_macro("defn", rfn!(defn))?;
-	glsp::bind_rfn_macro("defmacro", rfn!(defmacro))?;
+	bind_cap_rfn_macro("defmacro", Caps::MACROS, rfn!(defmacro))?;
 	glsp::bind_rfn_macro("with-global", rfn!(with_global))?;
+	glsp::bind_rfn_macro("with-globals", rfn!(with_globals))?;
+	glsp::bind_rfn_macro("import", rfn!(import))?;
 
 	glsp::bind_rfn_macro("global", rfn!(global))?;
 	glsp::bind_rfn_macro("global=", rfn!(set_global))?;
@@ -51,6 +54,11 @@ pub fn init(sandboxed: bool) -> GResult<()> {
 	glsp::bind_rfn_macro("todo", rfn!(todo))?;
 	glsp::bind_rfn_macro("try", rfn!(try_))?;
 	glsp::bind_rfn_macro("try-verbose", rfn!(try_verbose))?;
+	glsp::bind_rfn_macro("try-chain", rfn!(try_chain))?;
+	glsp::bind_rfn_macro("finally", rfn!(finally))?;
+	glsp::bind_rfn_macro("with-scratch", rfn!(with_scratch))?;
+	glsp::bind_rfn_macro("with-strict-scratch", rfn!(with_strict_scratch))?;
+	glsp::bind_rfn_macro("with-children", rfn!(with_children))?;
 
 	glsp::bind_rfn_macro("when", rfn!(when))?;
 	glsp::bind_rfn_macro("unless", rfn!(unless))?;
@@ -71,6 +79,8 @@ pub fn init(sandboxed: bool) -> GResult<()> {
 	glsp::bind_rfn_macro("let-fn", rfn!(let_fn))?;
 
 	glsp::bind_rfn_macro("match", rfn!(match_))?;
+	glsp::bind_rfn("%match-failed", rfn!(match_failed))?;
+	glsp::bind_rfn("%type-error", rfn!(type_error))?;
 	glsp::bind_rfn_macro("matches?", rfn!(matchesp))?;
 	glsp::bind_rfn_macro("when-let", rfn!(when_let))?;
 
@@ -85,6 +95,7 @@ pub fn init(sandboxed: bool) -> GResult<()> {
 	glsp::bind_rfn_macro("?", rfn!(question_mark))?;
 
 	glsp::bind_rfn_macro("tab", rfn!(tab))?;
+	glsp::bind_rfn_macro("ptab", rfn!(ptab))?;
 
 	glsp::bind_rfn_macro("for", rfn!(for_))?;
 	glsp::bind_rfn_macro("forn", rfn!(forn))?;
@@ -92,6 +103,7 @@ pub fn init(sandboxed: bool) -> GResult<()> {
 	glsp::bind_rfn_macro("break", rfn!(break_))?;
 	glsp::bind_rfn_macro("continue", rfn!(continue_))?;
 	glsp::bind_rfn_macro("loop", rfn!(loop_))?;
+	glsp::bind_rfn_macro("recur", rfn!(recur))?;
 	glsp::bind_rfn_macro("yield-from", rfn!(yield_from))?;
 
 	//setters and in-place mutation
@@ -99,13 +111,14 @@ pub fn init(sandboxed: bool) -> GResult<()> {
 	glsp::bind_rfn("bind-place!", rfn!(bind_place))?;
 	glsp::bind_rfn_macro("=", rfn!(set))?;
 	
-	static SETTERS: [(&str, &str, Option<bool>); 7] = [
+	static SETTERS: [(&str, &str, Option<bool>); 8] = [
 		("access", "access=", None),
 		("access-opt", "access-opt=", None),
 		("access-slice", "access-slice=", None),
 		("global", "global=", None),
 		("macro", "macro=", None),
 		("gc-value", "gc-value=", None),
+		("log-level", "log-level=", None),
 		("atsign", "atsign=", Some(false))
 	];
 
@@ -298,17 +311,15 @@ arithmetic_macro!(max, MAX_SYM, None, false);
 arithmetic_macro!(bitand, BITAND_SYM, Some(Val::Int(-1)), true);
 arithmetic_macro!(bitor, BITOR_SYM, Some(Val::Int(0)), true);
 arithmetic_macro!(bitxor, BITXOR_SYM, Some(Val::Int(0)), true);
+arithmetic_macro!(bit_and, BIT_AND_SYM, Some(Val::Int(-1)), true);
+arithmetic_macro!(bit_or, BIT_OR_SYM, Some(Val::Int(0)), true);
+arithmetic_macro!(bit_xor, BIT_XOR_SYM, Some(Val::Int(0)), true);
 
 fn include(path: &str) -> GResult<Val> {
-	let content_str = match fs::read_to_string(&path) {
-		Ok(content_str) => content_str,
-		Err(err) => {
-			let msg = error!("unable to read file '{}'", path);
-			return Err(msg.with_source(err))
-		}
-	};
+	let path = glsp::resolve_filename(path);
+	let content_str = glsp::read_source(&path)?;
 
-	let mut vals = glsp::parse_all(&content_str, Some(path))?;
+	let mut vals = glsp::parse_all(&content_str, Some(&path))?;
 
 	if vals.len() == 1 {
 		Ok(vals.pop().unwrap())
@@ -318,13 +329,14 @@ fn include(path: &str) -> GResult<Val> {
 }
 
 fn def(args: &[Val]) -> GResult<Val> {
-	match expand_let_like(DEF_SYM, PlaceStrategy::Global, args)? {
+	match expand_let_like(DEF_SYM, PlaceStrategy::DefGlobal, args)? {
 		Some(expanded) => Ok(expanded),
 		None => {
 			match args.len() {
 				0 => Ok(Val::Nil),
 				1 | 2 => {
-					let name = Sym::from_val(&args[0])?;
+					let name = glsp::qualify_definition(Sym::from_val(&args[0])?)?;
+					ensure_not_const(name)?;
 					let init = args.get(1).cloned().unwrap_or(Val::Nil);
 
 					Ok(backquote!("(bind-global! '~name ~init)"))
@@ -335,16 +347,45 @@ fn def(args: &[Val]) -> GResult<Val> {
 	}
 }
 
-fn defn(name: Sym, params: Root<Arr>, body: &[Val]) -> Val {
-	backquote!(r#"
-		(bind-global! '~name (fn &name ~name ~params ~..body))
-	"#)
+//a str literal at the start of a function body is its doc string, unless it's the body's only
+//form (in which case it's the return value). returns the &doc flag, if any, and the rest of
+//the body.
+pub(crate) fn split_doc(body: &[Val]) -> (Vec<Val>, &[Val]) {
+	match body {
+		[Val::Str(doc), rest @ ..] if rest.len() > 0 => {
+			(vec![Val::Sym(FLAG_DOC_SYM), Val::Str(doc.clone())], rest)
+		}
+		_ => (Vec::new(), body)
+	}
 }
 
-fn defmacro(name: Sym, params: Root<Arr>, body: &[Val]) -> Val {
-	backquote!(r#"
-		(bind-macro! '~name (fn &name ~name ~params ~..body))
-	"#)
+//rebinding a constant would fail at runtime anyway, but we can report it before evaluating the
+//definition's initializer
+fn ensure_not_const(name: Sym) -> GResult<()> {
+	ensure!(!glsp::is_const(name)?, "attempted to rebind the constant '{}'", name);
+	Ok(())
+}
+
+fn defconst(name: Sym, init: Val) -> GResult<Val> {
+	let name = glsp::qualify_definition(name)?;
+	Ok(backquote!("(%bind-const! '~name ~init)"))
+}
+
+fn defn(name: Sym, params: Root<Arr>, body: &[Val]) -> GResult<Val> {
+	let name = glsp::qualify_definition(name)?;
+	ensure_not_const(name)?;
+	let (doc_flag, body) = split_doc(body);
+	Ok(backquote!(r#"
+		(bind-global! '~name (fn &name ~name ~..doc_flag ~params ~..body))
+	"#))
+}
+
+fn defmacro(name: Sym, params: Root<Arr>, body: &[Val]) -> GResult<Val> {
+	let name = glsp::qualify_definition(name)?;
+	let (doc_flag, body) = split_doc(body);
+	Ok(backquote!(r#"
+		(bind-macro! '~name (fn &name ~name ~..doc_flag ~params ~..body))
+	"#))
 }
 
 fn with_global(args: &[Val]) -> GResult<Val> {
@@ -354,21 +395,27 @@ fn with_global(args: &[Val]) -> GResult<Val> {
 			match args.len() {
 				0 => Ok(Val::Nil),
 				1 | 2 => {
-					let name = Sym::from_val(&args[0])?;
+					let name = glsp::resolve_global(Sym::from_val(&args[0])?)?;
 					let init = args.get(1).cloned().unwrap_or(Val::Nil);
 
+					//when a paused coro is finished without being resumed, its (defer) runs while 
+					//the global still holds the resumer's value, which we mustn't clobber
 					Ok(backquote!(r#"
 						(splice
 						  (let inner_value# ~init)
 						  (let outer_value# (global '~name))
+						  (let paused# #f)
 						  (global= '~name inner_value#)
 						  (defer
-						    (global= '~name outer_value#))
+						    (unless paused#
+						      (global= '~name outer_value#)))
 						  (defer-yield
 						    (do
+						      (= paused# #t)
 						      (= inner_value# (global '~name))
 						      (global= '~name outer_value#))
 						    (do
+						      (= paused# #f)
 						      (= outer_value# (global '~name))
 						      (global= '~name inner_value#)))
 						  #n)
@@ -380,6 +427,55 @@ fn with_global(args: &[Val]) -> GResult<Val> {
 	}
 }
 
+//(with-globals (a 1 b 2) ..body) evaluates every value before mutating any globals, then emits
+//(with-global) in a fresh lexical scope, so that the globals are restored when `body` finishes
+fn with_globals(bindings: Root<Arr>, body: &[Val]) -> GResult<Val> {
+	ensure!(bindings.len() % 2 == 0, "with-globals expects pairs of names and values, but it \
+	        received {} forms", bindings.len());
+
+	if bindings.len() == 0 {
+		return Ok(backquote!("(do ~..body)"))
+	}
+
+	let mut lets = Vec::<Val>::with_capacity(bindings.len());
+	let mut overrides = Vec::<Val>::with_capacity(bindings.len());
+	for i in (0 .. bindings.len()).step_by(2) {
+		let name: Sym = bindings.get(i)?;
+		let temp = Val::Sym(glsp::gensym());
+
+		lets.push(temp.clone());
+		lets.push(bindings.get(i + 1)?);
+		overrides.push(Val::Sym(name));
+		overrides.push(temp);
+	}
+
+	Ok(backquote!(r#"
+		(do
+		  (let ~..lets)
+		  (with-global ~..overrides)
+		  ~..body)
+	"#))
+}
+
+//the module name may be quoted or unquoted: (import combat) or (import 'combat :only (a b))
+fn import(module: Val, rest: &[Val]) -> GResult<Val> {
+	let module = match module {
+		Val::Sym(module) => module,
+		Val::Arr(ref arr) if arr.len() == 2 && arr.get::<Val>(0)? == Val::Sym(QUOTE_SYM) => {
+			arr.get::<Sym>(1)?
+		}
+		ref val => bail!("expected a module name, received {}", val.a_type_name())
+	};
+
+	match rest {
+		[] => Ok(backquote!("(import-module! '~module)")),
+		[Val::Sym(keyword), Val::Arr(names)] if *keyword == glsp::sym(":only")? => {
+			Ok(backquote!("(import-module! '~module '~names)"))
+		}
+		_ => bail!("expected (import module) or (import module :only (name ...))")
+	}
+}
+
 //we generalise handling of (? x) optional arguments, and `x : y` slice arguments, to several
 //different macro-rfns (global, global=, access, access=, remove!, del!, call-meth). they
 //expand to their something-opt or something-slice variants, respectively.
@@ -578,6 +674,36 @@ fn try_verbose(body: &[Val]) -> Root<Arr> {
 	backquote!("(try-call 'verbose (fn () ~..body))")
 }
 
+fn try_chain(body: &[Val]) -> Root<Arr> {
+	backquote!("(try-call 'chain (fn () ~..body))")
+}
+
+fn finally(body: Val, cleanup: &[Val]) -> Root<Arr> {
+	backquote!("(do (defer ~..cleanup) ~body)")
+}
+
+fn with_scratch(body: &[Val]) -> Root<Arr> {
+	backquote!("(scratch-scope (fn () ~..body))")
+}
+
+fn with_strict_scratch(body: &[Val]) -> Root<Arr> {
+	backquote!("(strict-scratch-scope (fn () ~..body))")
+}
+
+//the scope is taken off the runtime's stack whenever the enclosing coroutine yields, so that
+//coroutines created by the resumer aren't registered as children
+fn with_children(body: &[Val]) -> Root<Arr> {
+	backquote!(r#"
+		(do
+		  (let scope# (%open-child-scope))
+		  (defer (%close-child-scope scope#))
+		  (defer-yield
+		    (%suspend-child-scope scope#)
+		    (%resume-child-scope scope#))
+		  ~..body)
+	"#)
+}
+
 fn when(cond_clause: Val, rest: &[Val]) -> Root<Arr> {
 	let then_clause = match rest.len() {
 		0 => Val::Nil,
@@ -906,10 +1032,11 @@ fn let_(args: &[Val]) -> GResult<Val> {
 }
 
 fn let_fn(name: Sym, params: Root<Arr>, body: &[Val]) -> Val {
+	let (doc_flag, body) = split_doc(body);
 	backquote!(r#"
 		(splice
 		  (let ~name #n)
-		  (= ~name (fn &name ~name ~params ~..body)))
+		  (= ~name (fn &name ~name ~..doc_flag ~params ~..body)))
 	"#)
 }
 
@@ -927,20 +1054,48 @@ fn match_(input_form: Val, clauses: &[Root<Arr>]) -> GResult<Val> {
 
 		let (_, forms_consumed) = pat_from_forms(&clause_forms[..], false, clause.span())?;
 		let clause_pat = &clause_forms[..forms_consumed];
-		let clause_body = &clause_forms[forms_consumed..];
+		let mut clause_body = &clause_forms[forms_consumed..];
+
+		//an optional `:when guard` follows the pattern. the guard can refer to the pattern's
+		//bindings; when it's false, we fall through to the next clause.
+		let mut guard_form = None;
+		if let Some(&Val::Sym(sym)) = clause_body.get(0) {
+			if sym == glsp::sym(":when")? {
+				ensure_at!(clause.span(), clause_body.len() >= 2, 
+				           "a :when keyword in (match) must be followed by a guard form");
+
+				guard_form = Some(clause_body[1].clone());
+				clause_body = &clause_body[2..];
+			}
+		}
 
-		let when_let_form: Val = backquote!("
-			(when-let ~..clause_pat ~input_name
-			  (finish-block ~block_name (do ~..clause_body)))
+		let finish_form: Root<Arr> = backquote!("
+			(finish-block ~block_name (do ~..clause_body))
 		");
+		let success_form: Root<Arr> = match guard_form {
+			Some(guard_form) => backquote!("(when ~guard_form ~finish_form)"),
+			None => finish_form
+		};
+		success_form.set_span(clause.span());
+
+		let when_let_form: Root<Arr> = backquote!("
+			(when-let ~..clause_pat ~input_name ~success_form)
+		");
+		when_let_form.set_span(clause.span());
 		block_form.push(when_let_form)?;
 	}
 
-	block_form.push(Val::Nil)?;
+	//a (match) which falls off the end of its clauses is an error
+	let to_push: Val = backquote!("(%match-failed ~input_name)");
+	block_form.push(to_push)?;
 
 	Ok(Val::Arr(block_form))
 }
 
+fn match_failed(input: Val) -> GResult<()> {
+	bail!("no (match) clause matched the value {:?}", input)
+}
+
 fn matchesp(input_form: Val, pat_forms: &[Val]) -> GResult<Val> {
 	//emit a (bool (block name {pattern-matching code} #t)), where the pattern-matching code 
 	//mismatches with (finish-block name #n).
@@ -1063,19 +1218,13 @@ fn meth_fn(args: &[Val]) -> GResult<Val> {
 
 fn fn_common(args: &[Val], atsign_params: bool) -> GResult<Val> {
 	//detect the params array and any flags
-	let (params_i, params_arr) = {
-		let mut i = 0;
-		loop {
-			ensure!(i < args.len(), "unexpected end of (fn) form");
-
-			match args[i] {
-				Val::Sym(FLAG_NAME_SYM) => i += 2,
-				Val::Sym(FLAG_ARG_LIMITS_SYM) => i += 3,
-				Val::Arr(ref arr) => break (i, arr.clone()),
-				ref arg => bail!("unexpected form {} in (fn)", arg)
-			}
-		}
-	};
+	let params_i = fn_params_index(args)?;
+	let params_arr = args[params_i].clone().unwrap_arr();
+
+	//type annotations are replaced with flags and guards, and then the result is expanded again
+	if let Some(expanded) = expand_type_annotations(args, params_i, atsign_params)? {
+		return Ok(expanded)
+	}
 
 	let flags = &args[..params_i];
 	let body = &args[params_i + 1..];
@@ -1101,6 +1250,14 @@ fn fn_common(args: &[Val], atsign_params: bool) -> GResult<Val> {
 		_ => bail_at!(params_arr.span(), "in (fn), {} is not a valid array pattern", params_arr)
 	};
 
+	//when any parameter is replaced with a gensym, we use the &param-names flag to record the
+	//names which were originally written, so that they can be returned by (params)
+	let mut named_flags = flags.to_vec();
+	if !flags.contains(&Val::Sym(FLAG_PARAM_NAMES_SYM)) {
+		named_flags.push(Val::Sym(FLAG_PARAM_NAMES_SYM));
+		named_flags.push(Val::Arr(declared_param_names(&pats)?));
+	}
+
 	//inspect each pattern to select a FnStrategy
 	let mut strategy = FnStrategy::Trivial;
 
@@ -1226,6 +1383,20 @@ fn fn_common(args: &[Val], atsign_params: bool) -> GResult<Val> {
 				trivial_matcher_to_form(&pat.matcher)
 			}))?;
 
+			let has_underscore = pats.iter().any(|pat| {
+				match &pat.matcher {
+					Matcher::Underscore => true,
+					Matcher::Opt(sub_matcher, _) | Matcher::Rest(sub_matcher) => {
+						match **sub_matcher {
+							Matcher::Underscore => true,
+							_ => false
+						}
+					}
+					_ => false
+				}
+			});
+
+			let flags = if has_underscore { &named_flags[..] } else { flags };
 			Ok(backquote!("(fn ~..flags ~trivial_params ~..body)"))
 		}
 		FnStrategy::Simple => {
@@ -1286,7 +1457,7 @@ fn fn_common(args: &[Val], atsign_params: bool) -> GResult<Val> {
 				}
 			}
 
-			Ok(backquote!("(fn ~..flags ~simple_params ~..pats_body ~..body)"))
+			Ok(backquote!("(fn ~..named_flags ~simple_params ~..pats_body ~..body)"))
 		}
 		FnStrategy::Complex => {
 			//this is relatively straightforward
@@ -1311,12 +1482,326 @@ fn fn_common(args: &[Val], atsign_params: bool) -> GResult<Val> {
 			ensure!(!flags.contains(&Val::Sym(FLAG_ARG_LIMITS_SYM)), "unexpected &arg-limits flag");
 
 			Ok(backquote!("
-				(fn &arg-limits ~min_args ~max_args ~..flags (..~arg_name) ~..pat_body ~..body)
+				(fn &arg-limits ~min_args ~max_args ~..named_flags (..~arg_name) ~..pat_body 
+				    ~..body)
 			"))
 		}
 	}
 }
 
+//the index of the params array in a (fn) form's arguments, skipping over any flags
+pub(crate) fn fn_params_index(args: &[Val]) -> GResult<usize> {
+	let mut i = 0;
+	loop {
+		ensure!(i < args.len(), "unexpected end of (fn) form");
+
+		match args[i] {
+			Val::Sym(FLAG_NAME_SYM) | Val::Sym(FLAG_DOC_SYM) | Val::Sym(FLAG_PARAM_NAMES_SYM) |
+			Val::Sym(FLAG_PARAM_TYPES_SYM) | Val::Sym(FLAG_RETURN_TYPE_SYM) => i += 2,
+			Val::Sym(FLAG_ARG_LIMITS_SYM) => i += 3,
+			Val::Arr(_) => return Ok(i),
+			ref arg => bail!("unexpected form {} in (fn)", arg)
+		}
+	}
+}
+
+/*
+type annotations are written as (name : type) at the toplevel of a (fn) form's params, within
+an optional parameter (? (name : type) init), or as a rest parameter ..(name : type). a return
+type is written as `-> type` immediately after the params.
+
+we replace each annotated parameter with its name, and record the annotations using the
+&param-types and &return-type flags. when glsp::type_checks() is enabled, we also prepend a
+guard for each annotated parameter to the body, and wrap the body in a guard for the return 
+type. for example, with checks enabled,
+
+	(fn &name f ((a : int) (? (b : Obj))) -> str
+	  body)
+
+is transformed into...
+
+	(fn &name f &param-types (int Obj) &return-type str (a (? b))
+	  (unless (int? a) (%type-error 'f 'a 'int a))
+	  (unless (or (nil? b) (is? b Obj)) (%type-error 'f 'b 'Obj b))
+	  (let gs0 (do body))
+	  (unless (str? gs0) (%type-error 'f #n 'str gs0))
+	  gs0)
+
+returns None when there are no annotations to expand.
+*/
+
+#[derive(Copy, Clone, PartialEq)]
+enum AnnotatedKind {
+	Basic,
+	Opt { has_init: bool },
+	Rest
+}
+
+fn expand_type_annotations(
+	args: &[Val],
+	params_i: usize,
+	atsign_params: bool
+) -> GResult<Option<Val>> {
+
+	let flags = &args[..params_i];
+	let params_arr = args[params_i].clone().unwrap_arr();
+	let mut body = &args[params_i + 1..];
+
+	let return_type = match body {
+		[Val::Sym(ARROW_SYM), Val::Sym(return_type), ..] => {
+			body = &body[2..];
+			Some(*return_type)
+		}
+		[Val::Sym(ARROW_SYM), form, ..] => {
+			bail_at!(params_arr.span(), "in (fn), {} is not a valid return type", form)
+		}
+		[Val::Sym(ARROW_SYM)] => bail_at!(params_arr.span(), "in (fn), -> has no return type"),
+		_ => None
+	};
+
+	//strip each annotation from the params
+	let stripped_params = glsp::arr_with_capacity(params_arr.len());
+	stripped_params.set_span(params_arr.span());
+
+	let mut param_types = Vec::with_capacity(params_arr.len());
+	let mut annotated = Vec::new();
+
+	for param in params_arr.iter() {
+		let (kind, form_i) = match param {
+			Val::Arr(ref arr) if arr.len() >= 2 && arr.len() <= 3 && 
+			                     arr.get::<Val>(0)? == Val::Sym(QUESTION_MARK_SYM) => {
+				(AnnotatedKind::Opt { has_init: arr.len() == 3 }, Some(1))
+			}
+			Val::Arr(ref arr) if arr.len() == 2 && arr.get::<Val>(0)? == Val::Sym(SPLAY_SYM) => {
+				(AnnotatedKind::Rest, Some(1))
+			}
+			_ => (AnnotatedKind::Basic, None)
+		};
+
+		let annotation_form = match form_i {
+			Some(form_i) => param.clone().unwrap_arr().get::<Val>(form_i)?,
+			None => param.clone()
+		};
+
+		match annotated_param(&annotation_form)? {
+			Some((name_form, name, type_name, span)) => {
+				match form_i {
+					Some(form_i) => {
+						let wrapper = param.clone().unwrap_arr();
+						let stripped = glsp::arr_from_iter(wrapper.iter())?;
+						stripped.set_span(wrapper.span());
+						stripped.set(form_i, name_form)?;
+						stripped_params.push(stripped)?;
+					}
+					None => stripped_params.push(name_form)?
+				}
+
+				param_types.push(Val::Sym(type_name));
+				annotated.push((kind, name, type_name, span));
+			}
+			None => {
+				stripped_params.push(&param)?;
+				param_types.push(Val::Nil);
+			}
+		}
+	}
+
+	if annotated.is_empty() && return_type.is_none() {
+		return Ok(None)
+	}
+
+	//record the annotations using flags
+	let mut new_flags = flags.to_vec();
+	if !annotated.is_empty() {
+		new_flags.push(Val::Sym(FLAG_PARAM_TYPES_SYM));
+		new_flags.push(Val::Arr(glsp::arr_from_iter(param_types)?));
+	}
+	if let Some(return_type) = return_type {
+		new_flags.push(Val::Sym(FLAG_RETURN_TYPE_SYM));
+		new_flags.push(Val::Sym(return_type));
+	}
+
+	//-> type may appear before the doc string
+	if let [Val::Str(doc), rest @ ..] = body {
+		if rest.len() > 0 && !flags.contains(&Val::Sym(FLAG_DOC_SYM)) {
+			new_flags.push(Val::Sym(FLAG_DOC_SYM));
+			new_flags.push(Val::Str(doc.clone()));
+			body = rest;
+		}
+	}
+
+	//emit the guards
+	let mut fn_name = Val::Nil;
+	for pair in flags.windows(2) {
+		if let [Val::Sym(FLAG_NAME_SYM), Val::Sym(name)] = pair {
+			fn_name = Val::Arr(arr![QUOTE_SYM, *name]);
+		}
+	}
+
+	let guards = glsp::arr();
+	let mut new_body = body.to_vec();
+
+	if glsp::type_checks() {
+		for &(kind, name, type_name, span) in &annotated {
+			match kind {
+				AnnotatedKind::Basic | AnnotatedKind::Opt { .. } => {
+					let test = match type_test(type_name, Val::Sym(name))? {
+						Some(test) => test,
+						None => continue
+					};
+
+					//an optional parameter with no initializer defaults to #n
+					let test = if kind == (AnnotatedKind::Opt { has_init: false }) {
+						backquote!("(or (nil? ~name) ~test)")
+					} else {
+						test
+					};
+
+					let error: Root<Arr> = backquote!("
+						(%type-error ~fn_name '~name '~type_name ~name)
+					");
+					error.set_span(span);
+
+					let guard: Val = backquote!("(unless ~test ~error)");
+					guards.push(guard)?;
+				}
+				AnnotatedKind::Rest => {
+					let elem_name = glsp::gensym();
+					let test = match type_test(type_name, Val::Sym(elem_name))? {
+						Some(test) => test,
+						None => continue
+					};
+
+					let error: Root<Arr> = backquote!("
+						(%type-error ~fn_name '~name '~type_name ~elem_name)
+					");
+					error.set_span(span);
+
+					let guard: Val = backquote!("
+						(for ~elem_name in ~name
+						  (unless ~test ~error))
+					");
+					guards.push(guard)?;
+				}
+			}
+		}
+
+		if let Some(return_type) = return_type {
+			let result_name = glsp::gensym();
+			if let Some(test) = type_test(return_type, Val::Sym(result_name))? {
+				let error: Root<Arr> = backquote!("
+					(%type-error ~fn_name #n '~return_type ~result_name)
+				");
+				error.set_span(params_arr.span());
+
+				let wrapped: Root<Arr> = backquote!("
+					((let ~result_name (do ~..body))
+					 (unless ~test ~error)
+					 ~result_name)
+				");
+				new_body = wrapped.iter().collect();
+			}
+		}
+	}
+
+	let head = if atsign_params { glsp::sym("%meth-fn")? } else { FN_SYM };
+	Ok(Some(backquote!("(~head ~..new_flags ~stripped_params ~..guards ~..new_body)")))
+}
+
+//a (name : type) form, where name is a symbol or an @name form and type is a symbol. returns the
+//name form, the bare name, the type and the form's span.
+fn annotated_param(form: &Val) -> GResult<Option<(Val, Sym, Sym, Span)>> {
+	let arr = match *form {
+		Val::Arr(ref arr) if arr.len() == 3 && arr.get::<Val>(1)? == Val::Sym(COLON_SYM) => arr,
+		_ => return Ok(None)
+	};
+
+	let name_form: Val = arr.get(0)?;
+	let name = match name_form {
+		Val::Sym(name) => name,
+		Val::Arr(ref at) if at.len() == 2 && at.get::<Val>(0)? == Val::Sym(ATSIGN_SYM) => {
+			match at.get::<Val>(1)? {
+				Val::Sym(name) => name,
+				_ => return Ok(None)
+			}
+		}
+		_ => return Ok(None)
+	};
+
+	match arr.get::<Val>(2)? {
+		Val::Sym(type_name) => Ok(Some((name_form, name, type_name, arr.span()))),
+		_ => Ok(None)
+	}
+}
+
+//a form which tests whether `val_form` belongs to the named type, or None for the type `any`.
+//primitive type names are tested using their predicate, a name ending in ? is a predicate, the
+//name of a rec type tests the rec's type, and any other name is a class.
+fn type_test(type_name: Sym, val_form: Val) -> GResult<Option<Val>> {
+	let name = type_name.name();
+	let pred = match &*name {
+		"any" => return Ok(None),
+		"nil" | "bool" | "int" | "flo" | "num" | "char" | "sym" | "str" | "arr" | "tab" | 
+		"deque" | "iter" | "iterable" | "obj" | "class" | "fn" | "rfn" | "coro" | "rdata" |
+		"rec" | "callable" | "expander" => glsp::sym(&format!("{}?", name))?,
+		_ if name.ends_with('?') => type_name,
+		_ if glsp::rec_fields(type_name).is_some() => {
+			return Ok(Some(backquote!("(%rec-is? ~val_form '~type_name)")))
+		}
+		_ => return Ok(Some(backquote!("(is? ~val_form ~type_name)")))
+	};
+
+	Ok(Some(backquote!("(~pred ~val_form)")))
+}
+
+fn type_error(
+	OrNil(fn_name): OrNil<Sym>,
+	OrNil(param_name): OrNil<Sym>,
+	type_name: Sym,
+	val: Val
+) -> GResult<()> {
+	let mut message = String::new();
+	if let Some(fn_name) = fn_name {
+		message.push_str(&format!("({}): ", fn_name));
+	}
+
+	match param_name {
+		Some(param_name) => {
+			message.push_str(&format!("parameter `{}` expected {}, received {}",
+			                          param_name, type_name, val.a_type_name()));
+		}
+		None => {
+			message.push_str(&format!("expected to return {}, but returned {}",
+			                          type_name, val.a_type_name()));
+		}
+	}
+
+	Err(GError::from_val(TYPE_ERROR_SYM).with_source(GError::from_str(&message)))
+}
+
+//the parameter names for a (fn) form's &param-names flag. parameters which aren't bound to a
+//single name, like _ or a destructuring pattern, are named _
+fn declared_param_names(pats: &[Pat]) -> GResult<Root<Arr>> {
+	fn matcher_name(at: Option<Sym>, matcher: &Matcher) -> Sym {
+		match matcher {
+			Matcher::Sym(name) | Matcher::AtsignSym(name) => *name,
+			_ => at.unwrap_or(UNDERSCORE_SYM)
+		}
+	}
+
+	glsp::arr_from_iter(pats.iter().map(|pat| {
+		match &pat.matcher {
+			Matcher::Opt(sub_matcher, _) => {
+				Val::Arr(arr![QUESTION_MARK_SYM, matcher_name(pat.at, sub_matcher)])
+			}
+			Matcher::Rest(sub_matcher) => {
+				Val::Arr(arr![SPLAY_SYM, matcher_name(pat.at, sub_matcher)])
+			}
+			matcher => Val::Sym(matcher_name(pat.at, matcher))
+		}
+	}))
+}
+
 fn fn0(body: &[Val]) -> Root<Arr> {
 	backquote!("(fn () ~..body)")
 }
@@ -1355,7 +1840,7 @@ fn fn1(body: &[Val]) -> GResult<Root<Arr>> {
 		*form = replace_underscores(form.clone(), param_name)?;
 	}
 
-	Ok(backquote!("(fn (~param_name) ~..expanded)"))
+	Ok(backquote!("(fn &param-names (_) (~param_name) ~..expanded)"))
 }
 
 fn arrow_first(first: Val, rest: &[Val]) -> GResult<Val> {
@@ -1473,6 +1958,41 @@ fn tab(clauses: &[Root<Arr>]) -> GResult<Root<Arr>> {
 	"#))
 }
 
+fn ptab(clauses: &[Root<Arr>]) -> GResult<Root<Arr>> {
+	if clauses.len() == 0 {
+		macro_no_op!()
+	}
+
+	let ptab_name = glsp::gensym();
+	let assoc_forms = glsp::arr_with_capacity(clauses.len());
+
+	for clause in clauses.iter() {
+		ensure!(clause.len() == 2, "expected (ptab (k0 v0) (k1 v1) ..base)");
+
+		let (key_form, val_form): (Val, Val) = (clause.get(0)?, clause.get(1)?);
+
+		if key_form == Val::Sym(SPLAY_SYM) {
+			let assoc_form: Root<Arr> = backquote!(r#"
+				(for (k# v#) in ~val_form
+				  (= ~ptab_name (passoc ~ptab_name k# v#)))
+			"#);
+			assoc_forms.push(assoc_form)?;
+		} else {
+			let assoc_form: Root<Arr> = backquote!(r#"
+				(= ~ptab_name (passoc ~ptab_name ~key_form ~val_form))
+			"#);
+			assoc_forms.push(assoc_form)?;
+		}
+	}
+
+	Ok(backquote!(r#"
+		(do
+		  (let ~ptab_name (ptab))
+		  ~..assoc_forms
+		  ~ptab_name)
+	"#))
+}
+
 fn for_(args: &[Val]) -> GResult<Val> {
 	let (_, forms_consumed) = pat_from_forms(args, false, Span::default())?;
 	
@@ -1533,12 +2053,167 @@ fn continue_() -> Val {
 	backquote!("(restart-block LOOP)")
 }
 
-fn loop_(body: &[Val]) -> Val {
-	backquote!(r#"
-		(block LOOP
-		  ~..body
-		  (restart-block LOOP))
-	"#)
+fn loop_(args: &[Val]) -> GResult<Val> {
+	//(loop ((name init) ...) body...) is a loop with bindings. anything else is an endless loop.
+	let bindings = match args.get(0) {
+		Some(Val::Arr(arr)) if arr.iter().all(|binding| {
+			match binding {
+				Val::Arr(pair) => pair.len() == 2 && pair.get::<Val>(0).unwrap().is_sym(),
+				_ => false
+			}
+		}) => arr.clone(),
+		_ => {
+			return Ok(backquote!(r#"
+				(block LOOP
+				  ~..args
+				  (restart-block LOOP))
+			"#))
+		}
+	};
+
+	//each binding is stored in a hidden local, which is copied into a fresh local at the start of
+	//each iteration. (recur) assigns to the hidden locals and then restarts the block.
+	let block_name = glsp::gensym();
+	let outer_names = SmallVec::<[Sym; 8]>::from_iter(bindings.iter().map(|_| glsp::gensym()));
+
+	let outer_form: Root<Arr> = backquote!("(do)");
+	let block_form: Root<Arr> = backquote!("(block ~block_name)");
+	for (binding, &outer_name) in bindings.iter().zip(outer_names.iter()) {
+		let binding = binding.unwrap_arr();
+		let (name, init) = (binding.get::<Sym>(0)?, binding.get::<Val>(1)?);
+
+		let to_push: Val = backquote!("(let ~outer_name ~init)");
+		outer_form.push(to_push)?;
+
+		let to_push: Val = backquote!("(let ~name ~outer_name)");
+		block_form.push(to_push)?;
+	}
+
+	//we fully expand the body while this loop is on top of the stack, so that each (recur) form
+	//expands to (%recur block-name args...). this means that we can find tail positions by
+	//inspecting special forms alone, without needing to understand any other macros.
+	let body_forms = &args[1..];
+	let body: Val = backquote!("(do ~..body_forms)");
+
+	Std::borrow_mut().loops.push((block_name, bindings.len()));
+	let expanded = glsp::expand(&body, Some(EnvMode::Copied));
+	Std::borrow_mut().loops.pop();
+
+	let expanded = expanded?;
+	let mut blocks = Vec::new();
+	let body = loop_rewrite(&expanded, true, &mut blocks, block_name, &outer_names)?
+	           .unwrap_or(expanded);
+
+	block_form.push(body)?;
+	outer_form.push(block_form)?;
+
+	Ok(Val::Arr(outer_form))
+}
+
+fn recur(args: &[Val]) -> GResult<Val> {
+	let (block_name, binding_count) = match Std::borrow().loops.last() {
+		Some(&loop_info) => loop_info,
+		None => bail!("(recur) must appear within a (loop) which has bindings")
+	};
+
+	ensure!(args.len() == binding_count, "(recur) received {} argument{}, but its (loop) has \
+	        {} binding{}", args.len(), if args.len() == 1 { "" } else { "s" },
+	        binding_count, if binding_count == 1 { "" } else { "s" });
+
+	let recur_sym = glsp::sym("%recur")?;
+	Ok(backquote!("(~recur_sym ~block_name ~..args)"))
+}
+
+//walk the fully-expanded body of a (loop), replacing each (%recur block-name args...) form with
+//a jump. returns None if the form is unchanged. bails if a (%recur) is not in tail position.
+//`blocks` is the stack of enclosing (block) names, with a flag for whether each is in tail 
+//position; a (finish-block) form's result is in tail position when its block is.
+fn loop_rewrite(
+	form: &Val,
+	tail: bool,
+	blocks: &mut Vec<(Sym, bool)>,
+	block_name: Sym,
+	outer_names: &[Sym]
+) -> GResult<Option<Val>> {
+
+	let arr = match form {
+		Val::Arr(arr) if arr.len() > 0 => arr,
+		_ => return Ok(None)
+	};
+
+	let head: Val = arr.get(0)?;
+	let finishes_tail_block = match head {
+		Val::Sym(FINISH_BLOCK_SYM) if arr.len() == 3 => {
+			let target = arr.get::<Val>(1)?;
+			blocks.iter().rev().find(|&&(name, _)| Val::Sym(name) == target)
+			      .map(|&(_, is_tail)| is_tail).unwrap_or(false)
+		}
+		_ => false
+	};
+
+	let child_is_tail = |i: usize| {
+		match head {
+			Val::Sym(DO_SYM) => tail && i == arr.len() - 1,
+			Val::Sym(IF_SYM) => tail && (i == 2 || i == 3),
+			Val::Sym(BLOCK_SYM) => tail && i >= 2 && i == arr.len() - 1,
+			Val::Sym(FINISH_BLOCK_SYM) => finishes_tail_block && i == 2,
+			_ => false
+		}
+	};
+
+	match head {
+		Val::Sym(QUOTE_SYM) => return Ok(None),
+		Val::Sym(BLOCK_SYM) if arr.len() >= 2 && arr.get::<Val>(1)?.is_sym() => {
+			blocks.push((arr.get::<Sym>(1)?, tail));
+		}
+		_ => ()
+	}
+
+	let mut replacement: Option<Root<Arr>> = None;
+	for i in 0 .. arr.len() {
+		let child: Val = arr.get(i)?;
+		let child_tail = child_is_tail(i);
+		if let Some(new_child) = loop_rewrite(&child, child_tail, blocks, block_name, outer_names)? {
+			if replacement.is_none() {
+				replacement = Some(arr.shallow_clone());
+			}
+
+			replacement.as_ref().unwrap().set(i, new_child)?;
+		}
+	}
+
+	if head == Val::Sym(BLOCK_SYM) && arr.len() >= 2 && arr.get::<Val>(1)?.is_sym() {
+		blocks.pop();
+	}
+
+	if head == Val::Sym(glsp::sym("%recur")?) && arr.get::<Val>(1)? == Val::Sym(block_name) {
+		ensure_at!(arr.span(), tail, "(recur) must appear in tail position within its (loop)");
+
+		let src = replacement.as_ref().unwrap_or(arr);
+		let do_form: Root<Arr> = backquote!("(do)");
+
+		let temp_names = SmallVec::<[Sym; 8]>::from_iter(
+			outer_names.iter().map(|_| glsp::gensym())
+		);
+		for (i, &temp_name) in temp_names.iter().enumerate() {
+			let arg: Val = src.get(i + 2)?;
+			let to_push: Val = backquote!("(let ~temp_name ~arg)");
+			do_form.push(to_push)?;
+		}
+
+		for (&outer_name, &temp_name) in outer_names.iter().zip(temp_names.iter()) {
+			let to_push: Val = backquote!("(= ~outer_name ~temp_name)");
+			do_form.push(to_push)?;
+		}
+
+		let to_push: Val = backquote!("(restart-block ~block_name)");
+		do_form.push(to_push)?;
+
+		do_form.set_span(arr.span());
+		return Ok(Some(Val::Arr(do_form)))
+	}
+
+	Ok(replacement.map(Val::Arr))
 }
 
 fn yield_from(iterable: Val) -> Val {
@@ -0,0 +1,527 @@
+use glsp::{
+	Arr, bail, Callable, CallableOps, DequeOps, error, FromVal, GFn, GResult, Lib, Obj, RData, rfn,
+	Root, Tab, Val, WeakRoot
+};
+use glsp_proc_macros::{backquote};
+use std::collections::{HashMap};
+use std::collections::hash_map::{DefaultHasher};
+use std::hash::{Hash, Hasher};
+use super::{Std};
+use super::hash::{hash_val};
+
+pub fn init(_sandboxed: bool) -> GResult<()> {
+	glsp::bind_rfn("memoize", rfn!(memoize_rfn))?;
+	glsp::bind_rfn("memo-clear!", rfn!(memo_clear))?;
+	glsp::bind_rfn("memo-stats", rfn!(memo_stats))?;
+	glsp::bind_rfn("%memo-call", rfn!(memo_call))?;
+
+	Ok(())
+}
+
+/**
+The options for [`glsp::memoize`](fn.memoize.html).
+
+By default, the cache is unbounded, and it keeps its arguments alive.
+
+	let opts = MemoOpts {
+		max_entries: Some(1000),
+		weak_keys: true
+	};
+*/
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct MemoOpts {
+	/**
+	The maximum number of results which may be cached at once. When a new result would exceed
+	this limit, the least-recently-used result is evicted.
+	*/
+	pub max_entries: Option<usize>,
+
+	/**
+	If `true`, any `obj` or `rdata` arguments are compared by identity, and they're held by a
+	[`WeakRoot`](struct.WeakRoot.html) rather than being kept alive by the cache. Once one of
+	those arguments has been garbage-collected, its cached results are discarded.
+	*/
+	pub weak_keys: bool
+}
+
+//-------------------------------------------------------------------------------------------------
+// Memos
+//-------------------------------------------------------------------------------------------------
+
+/*
+a memoized function is a small GFn which passes its arguments, and the id of its cache, to
+%memo-call. the caches are stored in the Std library, rather than in an rdata, because they hold
+Vals. a cache is discarded once its memoized function has been garbage-collected.
+
+each cache's entries are stored in a slab, and linked into a list which runs from the
+most-recently-used entry to the least-recently-used entry. the hash of each entry's key is an
+index into `buckets`.
+
+computing and comparing keys can call (op-hash) and (op-eq) methods, which may themselves call
+a memoized function, so the Std library is never borrowed while that's happening. likewise, it's
+never borrowed while the original function is being called.
+*/
+
+pub(crate) struct Memos {
+	memos: HashMap<i32, Memo>,
+	next_id: i32
+}
+
+impl Memos {
+	pub(crate) fn new() -> Memos {
+		Memos {
+			memos: HashMap::new(),
+			next_id: 1
+		}
+	}
+
+	fn get(&mut self, id: i32) -> GResult<&mut Memo> {
+		match self.memos.get_mut(&id) {
+			Some(memo) => Ok(memo),
+			None => bail!("(%memo-call) received an unknown cache id {}", id)
+		}
+	}
+
+	fn find(&mut self, memoized: &Root<GFn>) -> Option<&mut Memo> {
+		let weak = memoized.downgrade();
+		self.memos.values_mut().find(|memo| memo.memoized.as_ref() == Some(&weak))
+	}
+}
+
+struct Memo {
+	memoized: Option<WeakRoot<GFn>>,
+	callee: Callable,
+	opts: MemoOpts,
+
+	slab: Vec<Option<Entry>>,
+	vacant: Vec<usize>,
+	buckets: HashMap<u64, Vec<usize>>,
+	newest: Option<usize>,
+	oldest: Option<usize>,
+	len: usize,
+
+	next_entry_id: u64,
+	inserts_since_sweep: usize,
+
+	hits: usize,
+	misses: usize,
+	evictions: usize
+}
+
+struct Entry {
+	id: u64,
+	hash: u64,
+	key: Vec<KeyArg>,
+	value: Val,
+	newer: Option<usize>,
+	older: Option<usize>
+}
+
+#[derive(Clone)]
+enum KeyArg {
+	Val(Val),
+	Obj(WeakRoot<Obj>),
+	RData(WeakRoot<RData>)
+}
+
+impl KeyArg {
+	fn is_dead(&self) -> bool {
+		match *self {
+			KeyArg::Val(_) => false,
+			KeyArg::Obj(ref weak) => weak.is_dead(),
+			KeyArg::RData(ref weak) => weak.is_dead()
+		}
+	}
+
+	fn try_eq(&self, other: &KeyArg) -> GResult<bool> {
+		Ok(match (self, other) {
+			(KeyArg::Val(val0), KeyArg::Val(val1)) => val0.try_eq(val1)?,
+			(KeyArg::Obj(weak0), KeyArg::Obj(weak1)) => weak0 == weak1,
+			(KeyArg::RData(weak0), KeyArg::RData(weak1)) => weak0 == weak1,
+			_ => false
+		})
+	}
+}
+
+//an entry which might match a call's arguments, copied out of the cache so that it can be
+//compared without borrowing the Std library
+struct Candidate {
+	index: usize,
+	id: u64,
+	key: Vec<KeyArg>,
+	value: Val
+}
+
+impl Memo {
+	fn new(callee: Callable, opts: MemoOpts) -> Memo {
+		Memo {
+			memoized: None,
+			callee,
+			opts,
+
+			slab: Vec::new(),
+			vacant: Vec::new(),
+			buckets: HashMap::new(),
+			newest: None,
+			oldest: None,
+			len: 0,
+
+			next_entry_id: 0,
+			inserts_since_sweep: 0,
+
+			hits: 0,
+			misses: 0,
+			evictions: 0
+		}
+	}
+
+	fn entry(&self, index: usize) -> &Entry {
+		self.slab[index].as_ref().unwrap()
+	}
+
+	fn entry_mut(&mut self, index: usize) -> &mut Entry {
+		self.slab[index].as_mut().unwrap()
+	}
+
+	fn candidates(&self, hash: u64) -> Vec<Candidate> {
+		match self.buckets.get(&hash) {
+			Some(indices) => {
+				indices.iter().map(|&index| {
+					let entry = self.entry(index);
+					Candidate {
+						index,
+						id: entry.id,
+						key: entry.key.clone(),
+						value: entry.value.clone()
+					}
+				}).collect()
+			}
+			None => Vec::new()
+		}
+	}
+
+	fn unlink(&mut self, index: usize) {
+		let (newer, older) = {
+			let entry = self.entry(index);
+			(entry.newer, entry.older)
+		};
+
+		match newer {
+			Some(newer) => self.entry_mut(newer).older = older,
+			None => self.newest = older
+		}
+
+		match older {
+			Some(older) => self.entry_mut(older).newer = newer,
+			None => self.oldest = newer
+		}
+	}
+
+	fn link_newest(&mut self, index: usize) {
+		let prev_newest = self.newest;
+
+		let entry = self.entry_mut(index);
+		entry.newer = None;
+		entry.older = prev_newest;
+
+		match prev_newest {
+			Some(prev_newest) => self.entry_mut(prev_newest).newer = Some(index),
+			None => self.oldest = Some(index)
+		}
+
+		self.newest = Some(index);
+	}
+
+	//records a hit for the entry at `index`, unless it was removed or replaced while its key was
+	//being compared
+	fn touch(&mut self, index: usize, id: u64) {
+		self.hits += 1;
+
+		if self.slab.get(index).and_then(|entry| entry.as_ref()).map(|entry| entry.id) == Some(id) {
+			self.unlink(index);
+			self.link_newest(index);
+		}
+	}
+
+	fn insert(&mut self, hash: u64, key: Vec<KeyArg>, value: Val) {
+		if self.opts.weak_keys {
+			self.inserts_since_sweep += 1;
+			if self.inserts_since_sweep >= self.len.max(32) {
+				self.sweep();
+			}
+		}
+
+		if let Some(max_entries) = self.opts.max_entries {
+			while self.len >= max_entries {
+				let oldest = self.oldest.unwrap();
+				self.remove(oldest);
+				self.evictions += 1;
+			}
+		}
+
+		let entry = Entry {
+			id: self.next_entry_id,
+			hash,
+			key,
+			value,
+			newer: None,
+			older: None
+		};
+
+		self.next_entry_id += 1;
+
+		let index = match self.vacant.pop() {
+			Some(index) => {
+				self.slab[index] = Some(entry);
+				index
+			}
+			None => {
+				self.slab.push(Some(entry));
+				self.slab.len() - 1
+			}
+		};
+
+		self.buckets.entry(hash).or_insert_with(Vec::new).push(index);
+		self.link_newest(index);
+		self.len += 1;
+	}
+
+	fn remove(&mut self, index: usize) {
+		self.unlink(index);
+		let entry = self.slab[index].take().unwrap();
+
+		let bucket = self.buckets.get_mut(&entry.hash).unwrap();
+		bucket.retain(|&i| i != index);
+		if bucket.is_empty() {
+			self.buckets.remove(&entry.hash);
+		}
+
+		self.vacant.push(index);
+		self.len -= 1;
+	}
+
+	//discards every entry which has a garbage-collected argument
+	fn sweep(&mut self) {
+		self.inserts_since_sweep = 0;
+
+		let dead: Vec<usize> = self.slab.iter().enumerate().filter_map(|(index, entry)| {
+			match *entry {
+				Some(ref entry) if entry.key.iter().any(KeyArg::is_dead) => Some(index),
+				_ => None
+			}
+		}).collect();
+
+		for index in dead {
+			self.remove(index);
+		}
+	}
+
+	fn clear(&mut self) {
+		self.slab.clear();
+		self.vacant.clear();
+		self.buckets.clear();
+		self.newest = None;
+		self.oldest = None;
+		self.len = 0;
+		self.inserts_since_sweep = 0;
+	}
+}
+
+//converts a call's arguments into a cache key, and hashes it
+fn make_key(args: &[Val], weak_keys: bool) -> GResult<(u64, Vec<KeyArg>)> {
+	let mut hasher = DefaultHasher::new();
+	args.len().hash(&mut hasher);
+
+	let mut key = Vec::with_capacity(args.len());
+	for (i, arg) in args.iter().enumerate() {
+		let key_arg = match *arg {
+			Val::Obj(ref obj) if weak_keys => KeyArg::Obj(obj.downgrade()),
+			Val::RData(ref rdata) if weak_keys => KeyArg::RData(rdata.downgrade()),
+			ref val => KeyArg::Val(val.clone())
+		};
+
+		match key_arg {
+			KeyArg::Val(ref val) => {
+				let hash = hash_val(val).map_err(|err| {
+					error!("unable to memoize a call: argument {} is unhashable", i).with_source(err)
+				})?;
+
+				(0_u8, hash).hash(&mut hasher);
+			}
+			KeyArg::Obj(ref weak) => (1_u8, weak).hash(&mut hasher),
+			KeyArg::RData(ref weak) => (2_u8, weak).hash(&mut hasher)
+		}
+
+		key.push(key_arg);
+	}
+
+	Ok((hasher.finish(), key))
+}
+
+fn key_eq(key0: &[KeyArg], key1: &[KeyArg]) -> GResult<bool> {
+	if key0.len() != key1.len() {
+		return Ok(false)
+	}
+
+	for (arg0, arg1) in key0.iter().zip(key1.iter()) {
+		if !arg0.try_eq(arg1)? {
+			return Ok(false)
+		}
+	}
+
+	Ok(true)
+}
+
+/**
+Equivalent to [`(memoize f (tab ('max-entries max-entries) ('weak-keys weak-keys)))`][0].
+
+[0]: https://gamelisp.rs/std/memoize
+
+Returns a new function which passes its arguments to `f`, caching the result. When it's called
+again with arguments which are [`eq?`](https://gamelisp.rs/std/eq-p) to a previous call's
+arguments, it returns the cached result without calling `f`. Calls which fail aren't cached.
+
+Each argument must be [hashable](https://gamelisp.rs/std/hash), unless `opts.weak_keys` is set and
+the argument is an `obj` or `rdata`.
+
+	let pathfind: Root<GFn> = glsp::global("pathfind")?;
+	let memoized = glsp::memoize(&pathfind, MemoOpts {
+		max_entries: Some(256),
+		..MemoOpts::default()
+	})?;
+
+	glsp::bind_global("pathfind", memoized)?;
+*/
+pub fn memoize(f: &Root<GFn>, opts: MemoOpts) -> GResult<Root<GFn>> {
+	memoize_callable(Callable::GFn(f.clone()), opts)
+}
+
+fn memoize_callable(callee: Callable, opts: MemoOpts) -> GResult<Root<GFn>> {
+	if opts.max_entries == Some(0) {
+		bail!("(memoize) expected max-entries to be at least 1");
+	}
+
+	let name = callee.name();
+
+	let id = {
+		let mut std = Std::borrow_mut();
+
+		//discard the caches of any memoized functions which have been garbage-collected
+		std.memos.memos.retain(|_, memo| {
+			memo.memoized.as_ref().map_or(true, |memoized| !memoized.is_dead())
+		});
+
+		let id = std.memos.next_id;
+		std.memos.next_id += 1;
+		std.memos.memos.insert(id, Memo::new(callee, opts));
+		id
+	};
+
+	let fn_form: Val = match name {
+		Some(name) => backquote!("(fn &name ~name (..args) (%memo-call ~id args))"),
+		None => backquote!("(fn (..args) (%memo-call ~id args))")
+	};
+
+	let memoized = match glsp::eval(&fn_form, None) {
+		Ok(val) => val.unwrap_gfn(),
+		Err(err) => {
+			Std::borrow_mut().memos.memos.remove(&id);
+			return Err(err)
+		}
+	};
+
+	Std::borrow_mut().memos.get(id)?.memoized = Some(memoized.downgrade());
+	Ok(memoized)
+}
+
+fn memoize_rfn(f: Callable, options: Option<Root<Tab>>) -> GResult<Root<GFn>> {
+	let mut opts = MemoOpts::default();
+
+	if let Some(options) = options {
+		for (key, value) in options.entries().iter() {
+			match key {
+				Val::Sym(sym) if &*sym.name() == "max-entries" => {
+					opts.max_entries = Some(usize::from_val(&value)?);
+				}
+				Val::Sym(sym) if &*sym.name() == "weak-keys" => {
+					opts.weak_keys = value.is_truthy();
+				}
+				key => {
+					bail!("(memoize) received an unknown option {}: expected max-entries or \
+					       weak-keys", key)
+				}
+			}
+		}
+	}
+
+	memoize_callable(f, opts)
+}
+
+fn memo_call(id: i32, args: Root<Arr>) -> GResult<Val> {
+	let (callee, weak_keys) = {
+		let mut std = Std::borrow_mut();
+		let memo = std.memos.get(id)?;
+		(memo.callee.clone(), memo.opts.weak_keys)
+	};
+
+	let args: Vec<Val> = args.iter().collect();
+	let (hash, key) = make_key(&args, weak_keys)?;
+
+	let candidates = Std::borrow().memos.memos.get(&id).map(|memo| memo.candidates(hash));
+	for candidate in candidates.unwrap_or_default() {
+		if key_eq(&candidate.key, &key)? {
+			if let Some(memo) = Std::borrow_mut().memos.memos.get_mut(&id) {
+				memo.touch(candidate.index, candidate.id);
+			}
+
+			return Ok(candidate.value)
+		}
+	}
+
+	if let Some(memo) = Std::borrow_mut().memos.memos.get_mut(&id) {
+		memo.misses += 1;
+	}
+
+	let value: Val = glsp::call(&callee, &args[..])?;
+
+	if let Some(memo) = Std::borrow_mut().memos.memos.get_mut(&id) {
+		memo.insert(hash, key, value.clone());
+	}
+
+	Ok(value)
+}
+
+fn memo_clear(memoized: Root<GFn>) -> GResult<()> {
+	match Std::borrow_mut().memos.find(&memoized) {
+		Some(memo) => {
+			memo.clear();
+			Ok(())
+		}
+		None => bail!("(memo-clear!) expected a memoized fn")
+	}
+}
+
+fn memo_stats(memoized: Root<GFn>) -> GResult<Root<Tab>> {
+	let (hits, misses, entries, evictions) = {
+		let mut std = Std::borrow_mut();
+		let memo = match std.memos.find(&memoized) {
+			Some(memo) => memo,
+			None => bail!("(memo-stats) expected a memoized fn")
+		};
+
+		if memo.opts.weak_keys {
+			memo.sweep();
+		}
+
+		(memo.hits, memo.misses, memo.len, memo.evictions)
+	};
+
+	let tab = glsp::tab();
+	tab.set(glsp::sym("hits")?, hits)?;
+	tab.set(glsp::sym("misses")?, misses)?;
+	tab.set(glsp::sym("entries")?, entries)?;
+	tab.set(glsp::sym("evictions")?, evictions)?;
+
+	Ok(tab)
+}
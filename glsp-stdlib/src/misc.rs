@@ -1,21 +1,27 @@
 use glsp::{
 	arr, Arr, bail, Callable, CallableOps, Coro, CoroState, DequeOps, ensure, 
-	EnvMode, eprn, Expander, Expansion, FromVal, GC_DEFAULT_RATIO, GC_MIN_RATIO, GFn, 
-	GResult, macro_no_op, Num, rfn, RData, Root, stock_syms::*, str, Str, Sym, Val
+	EnvMode, eprn, Expander, Expansion, Flo, FromVal, GC_DEFAULT_RATIO, GC_MIN_RATIO, GFn, 
+	GError, GlobalSnapshot, GResult, Iterable, IterableOps, LoadedFile, macro_no_op, ReloadReport, rfn, RData, Rec, Root, 
+	stock_syms::*, str, Str, Sym, Tab, try_tab, Val
 };
 use smallvec::SmallVec;
 use std::{i32, str};
 use std::char;
 use std::convert::TryFrom;
+use std::error::Error;
 use std::io::Write;
 use std::iter::once;
-use std::time::UNIX_EPOCH;
+use super::{bind_cap_rfn, Caps, ensure_macro_permitted};
 
-pub fn init(sandboxed: bool) -> GResult<()> {
-	if !sandboxed {
-		glsp::bind_rfn("load", rfn!(load))?;
-		glsp::bind_rfn("require", rfn!(require))?;
-	}
+pub fn init(_sandboxed: bool) -> GResult<()> {
+	bind_cap_rfn("load", Caps::LOAD, rfn!(load))?;
+	bind_cap_rfn("require", Caps::LOAD, rfn!(require))?;
+	bind_cap_rfn("loaded-files", Caps::LOAD, rfn!(loaded_files))?;
+	bind_cap_rfn("reload", Caps::LOAD, rfn!(reload))?;
+
+	//intended for development consoles, which need to undo the damage from a bad (eval)
+	bind_cap_rfn("snapshot-globals", Caps::LOAD, rfn!(snapshot_globals))?;
+	bind_cap_rfn("restore-globals!", Caps::LOAD, rfn!(restore_globals))?;
 
 	glsp::bind_rfn("type-of", rfn!(type_of))?;
 	glsp::bind_rfn("nil?", rfn!(nilp))?;
@@ -37,6 +43,14 @@ pub fn init(sandboxed: bool) -> GResult<()> {
 	glsp::bind_rfn("rfn?", rfn!(rfnp))?;
 	glsp::bind_rfn("coro?", rfn!(corop))?;
 	glsp::bind_rfn("rdata?", rfn!(rdatap))?;
+	glsp::bind_rfn("rec?", rfn!(recp))?;
+	glsp::bind_rfn("parr?", rfn!(parrp))?;
+	glsp::bind_rfn("ptab?", rfn!(ptabp))?;
+	glsp::bind_rfn("view?", rfn!(viewp))?;
+	glsp::bind_rfn("vec2?", rfn!(vec2p))?;
+	glsp::bind_rfn("vec3?", rfn!(vec3p))?;
+	glsp::bind_rfn("mat3?", rfn!(mat3p))?;
+	glsp::bind_rfn("rdata-type", rfn!(rdata_type))?;
 	glsp::bind_rfn("callable?", rfn!(callablep))?;
 	glsp::bind_rfn("expander?", rfn!(expanderp))?;
 
@@ -51,8 +65,13 @@ pub fn init(sandboxed: bool) -> GResult<()> {
 	glsp::bind_rfn("int", rfn!(int))?;
 	glsp::bind_rfn("flo", rfn!(flo))?;
 	glsp::bind_rfn("char", rfn!(char))?;
+	glsp::bind_rfn("char->int", rfn!(char_to_int))?;
+	glsp::bind_rfn("int->char", rfn!(int_to_char))?;
 	glsp::bind_rfn("bool", rfn!(bool))?;
 	glsp::bind_rfn("sym", rfn!(sym))?;
+	glsp::bind_rfn("sym-name", rfn!(sym_name))?;
+	glsp::bind_rfn("sym-meta", rfn!(sym_meta))?;
+	glsp::bind_rfn("set-sym-meta!", rfn!(set_sym_meta))?;
 
 	glsp::bind_rfn("int->str", rfn!(int_to_str))?;
 	glsp::bind_rfn("flo->str", rfn!(flo_to_str))?;
@@ -67,6 +86,17 @@ pub fn init(sandboxed: bool) -> GResult<()> {
 	glsp::bind_rfn("has-global?", rfn!(has_global))?;
 	glsp::bind_rfn("bind-global!", rfn!(bind_global))?;
 	glsp::bind_rfn("del-global!", rfn!(del_global))?;
+	glsp::bind_rfn("%bind-const!", rfn!(bind_const))?;
+	glsp::bind_rfn("const?", rfn!(constp))?;
+	glsp::bind_rfn("redefine-const!", rfn!(redefine_const))?;
+	glsp::bind_rfn("deprecate", rfn!(deprecate))?;
+	glsp::bind_rfn("deprecations-hit", rfn!(deprecations_hit))?;
+	glsp::bind_rfn("api-manifest", rfn!(api_manifest))?;
+
+	glsp::bind_rfn("in-module", rfn!(in_module))?;
+	glsp::bind_rfn("current-module", rfn!(current_module))?;
+	glsp::bind_rfn("has-module?", rfn!(has_module))?;
+	glsp::bind_rfn("import-module!", rfn!(import_module))?;
 	
 	glsp::bind_rfn("macro", rfn!(get_macro))?;
 	glsp::bind_rfn("macro=", rfn!(set_macro))?;
@@ -79,47 +109,70 @@ pub fn init(sandboxed: bool) -> GResult<()> {
 	glsp::bind_rfn("expand-multi", rfn!(expand_multi))?;
 	glsp::bind_rfn("expand-1", rfn!(expand_1))?;
 	glsp::bind_rfn("macro-no-op", rfn!(macro_no_op))?;
+	glsp::bind_rfn("span-of", rfn!(span_of))?;
+	glsp::bind_rfn("with-span", rfn!(with_span))?;
+	glsp::bind_rfn("spanned", rfn!(spanned))?;
 	
 	glsp::bind_rfn("fn-name", rfn!(fn_name))?;
+	glsp::bind_rfn("doc", rfn!(doc))?;
 	glsp::bind_rfn("fn-yields?", rfn!(fn_yieldsp))?;
 	glsp::bind_rfn("arg-limits", rfn!(arg_limits))?;
 	glsp::bind_rfn("min-args", rfn!(min_args))?;
 	glsp::bind_rfn("max-args", rfn!(max_args))?;
+	glsp::bind_rfn("arity", rfn!(arg_limits))?;
+	glsp::bind_rfn("params", rfn!(params))?;
+	glsp::bind_rfn("return-type", rfn!(return_type))?;
 	
 	glsp::bind_rfn("coro-state", rfn!(coro_state))?;
 	glsp::bind_rfn("coro-run", rfn!(coro_run))?;
 	glsp::bind_rfn("coro-finish!", rfn!(coro_finish))?;
+	glsp::bind_rfn("coro-throw", rfn!(coro_throw))?;
+	glsp::bind_rfn("coro-saveable?", rfn!(coro_saveablep))?;
+	glsp::bind_rfn("coro-save", rfn!(coro_save))?;
+	glsp::bind_rfn("coro-restore", rfn!(coro_restore))?;
+	glsp::bind_rfn("%open-child-scope", rfn!(open_child_scope))?;
+	glsp::bind_rfn("%suspend-child-scope", rfn!(suspend_child_scope))?;
+	glsp::bind_rfn("%resume-child-scope", rfn!(resume_child_scope))?;
+	glsp::bind_rfn("%close-child-scope", rfn!(close_child_scope))?;
 
 	glsp::bind_rfn("gc", rfn!(gc))?;
 	glsp::bind_rfn("gc-value", rfn!(gc_value))?;
 	glsp::bind_rfn("gc-value=", rfn!(set_gc_value))?;
-
-	#[cfg(not(target_arch = "wasm32"))]
-	glsp::bind_rfn("time", rfn!(time))?;
-	glsp::bind_rfn("unix-time", rfn!(unix_time))?;
-	glsp::bind_rfn("sleep", rfn!(sleep))?;
+	glsp::bind_rfn("scratch-scope", rfn!(scratch_scope))?;
+	glsp::bind_rfn("strict-scratch-scope", rfn!(strict_scratch_scope))?;
 
 	glsp::bind_rfn("bail", rfn!(bail))?;
 	glsp::bind_rfn("try-call", rfn!(try_call))?;
+	glsp::bind_rfn("err-source", rfn!(err_source))?;
+	glsp::bind_rfn("err-chain", rfn!(err_chain))?;
+	glsp::define_rec(ERR_REC_SYM, &[PAYLOAD_SYM, SOURCE_SYM, KIND_SYM])?;
 	glsp::bind_rfn("stack-trace", rfn!(stack_trace))?;
 	glsp::bind_rfn("file-location", rfn!(file_location))?;
 
 	glsp::bind_rfn("dump-form", rfn!(dump_form))?;
 	glsp::bind_rfn("dump-fn", rfn!(dump_fn))?;
 	glsp::bind_rfn("dump-macro", rfn!(dump_macro))?;
+	glsp::bind_rfn("disassemble", rfn!(disassemble))?;
 
 	glsp::bind_rfn("not", rfn!(not))?;
 	glsp::bind_rfn("gensym", rfn!(gensym))?;
+	glsp::bind_rfn("gensym?", rfn!(gensymp))?;
 	glsp::bind_rfn("free!", rfn!(free))?;
 	glsp::bind_rfn("freed?", rfn!(freedp))?;
+	glsp::bind_rfn("expired?", rfn!(expiredp))?;
 	glsp::bind_rfn("clone", rfn!(clone))?;
 	glsp::bind_rfn("deep-clone", rfn!(deep_clone))?;
 	glsp::bind_rfn("freeze!", rfn!(freeze))?;
 	glsp::bind_rfn("deep-freeze!", rfn!(deep_freeze))?;
-	glsp::bind_rfn("eval", rfn!(eval))?;
-	glsp::bind_rfn("eval-multi", rfn!(eval_multi))?;
+	glsp::bind_rfn("frozen?", rfn!(frozenp))?;
+	glsp::bind_rfn("deep-frozen?", rfn!(deep_frozenp))?;
+	bind_cap_rfn("eval", Caps::EVAL, rfn!(eval))?;
+	bind_cap_rfn("eval-multi", Caps::EVAL, rfn!(eval_multi))?;
+	bind_cap_rfn("compile-fn", Caps::EVAL, rfn!(compile_fn))?;
 	glsp::bind_rfn("no-op", rfn!(no_op))?;
 	glsp::bind_rfn("identity", rfn!(identity))?;
+	glsp::bind_rfn("apply", rfn!(apply))?;
+	glsp::bind_rfn("call-with", rfn!(call_with))?;
 
 	Ok(())
 }
@@ -141,7 +194,14 @@ fn type_of(arg: Val) -> Sym {
 		Val::Class(_) => CLASS_SYM,
 		Val::GFn(_) => FN_SYM,
 		Val::Coro(_) => CORO_SYM,
-		Val::RData(_) => RDATA_SYM
+		Val::RData(_) => RDATA_SYM,
+		Val::Rec(_) => REC_SYM,
+		Val::PArr(_) => PARR_SYM,
+		Val::PTab(_) => PTAB_SYM,
+		Val::View(_) => VIEW_SYM,
+		Val::Vec2(_) => VEC2_SYM,
+		Val::Vec3(_) => VEC3_SYM,
+		Val::Mat3(_) => MAT3_SYM
 	}
 }
 
@@ -174,6 +234,17 @@ builtin_typecheck!(fnp, is_gfn);
 builtin_typecheck!(rfnp, is_rfn);
 builtin_typecheck!(corop, is_coro);
 builtin_typecheck!(rdatap, is_rdata);
+builtin_typecheck!(recp, is_rec);
+builtin_typecheck!(parrp, is_parr);
+builtin_typecheck!(ptabp, is_ptab);
+builtin_typecheck!(viewp, is_view);
+builtin_typecheck!(vec2p, is_vec2);
+builtin_typecheck!(vec3p, is_vec3);
+builtin_typecheck!(mat3p, is_mat3);
+
+fn rdata_type(rdata: Root<RData>) -> Sym {
+	rdata.class_name()
+}
 
 fn int(arg: Val) -> GResult<i32> {
 	match arg {
@@ -184,38 +255,45 @@ fn int(arg: Val) -> GResult<i32> {
 			//https://github.com/rust-lang/rust/issues/10184
 			Ok(f as i32)
 		}
-		Val::Char(c) => {
-			//all possible char values are also valid i32 values
-			Ok(c as u32 as i32)
-		}
+		Val::Char(c) => Ok(char_to_int(c)),
 		Val::Bool(b) => Ok(if b { 1 } else { 0 }),
 		arg  => bail!("could not cast {} to an int", arg.a_type_name())
 	}
 }
 
-fn flo(arg: Val) -> GResult<f32> {
+fn flo(arg: Val) -> GResult<Flo> {
 	match arg {
-		Val::Int(i) => Ok(i as f32),
+		Val::Int(i) => Ok(i as Flo),
 		Val::Flo(f) => Ok(f),
-		Val::Char(c) => Ok(c as i32 as f32),
+		Val::Char(c) => Ok(c as i32 as Flo),
 		Val::Bool(b) => Ok(if b { 1.0 } else { 0.0 }),
 		arg  => bail!("could not cast {} to a flo", arg.a_type_name())
 	}
 }
 
 fn char(arg: Val) -> GResult<char> {
-	let i = match arg {
-		Val::Int(i) => i,
-		Val::Flo(f) => f as i32,
-		Val::Char(c) => return Ok(c),
+	match arg {
+		Val::Int(i) => int_to_char(i),
+		Val::Flo(f) => int_to_char(f as i32),
+		Val::Char(c) => Ok(c),
 		_ => bail!("could not cast {} to a char", arg.a_type_name())
-	};
+	}
+}
 
-	ensure!(i >= 0, "{} is not a valid char value", arg);
+//all possible char values are also valid i32 values
+fn char_to_int(ch: char) -> i32 {
+	ch as u32 as i32
+}
+
+//the only ints which can't be converted into a char are negative numbers, numbers above 
+//0x10ffff, and the surrogate code points 0xd800 ..= 0xdfff
+fn int_to_char(i: i32) -> GResult<char> {
+	ensure!(!(0xd800 ..= 0xdfff).contains(&i), "{:#x} is a surrogate, not a valid char value", i);
+	ensure!(i >= 0 && i <= 0x10ffff, "{} is outside the range of valid char values", i);
 
 	match char::try_from(i as u32) {
 		Ok(ch) => Ok(ch),
-		Err(_) => bail!("{} is not a valid char value", arg)
+		Err(_) => bail!("{} is not a valid char value", i)
 	}
 }
 
@@ -245,6 +323,18 @@ fn sym(rest: &[Val]) -> GResult<Sym> {
 	glsp::sym(st)
 }
 
+fn sym_name(sym: Sym) -> String {
+	sym.name().to_string()
+}
+
+fn sym_meta(sym: Sym) -> Val {
+	glsp::sym_meta(sym)
+}
+
+fn set_sym_meta(sym: Sym, val: Val) -> GResult<()> {
+	glsp::set_sym_meta(sym, val)
+}
+
 fn int_to_str(arg: i32, opt_radix: Option<usize>) -> GResult<Root<Str>> {
 	let radix = opt_radix.unwrap_or(10);
 	ensure!(radix >= 2 && radix <= 36, "invalid radix {}", radix);
@@ -270,7 +360,7 @@ fn int_to_str(arg: i32, opt_radix: Option<usize>) -> GResult<Root<Str>> {
 	Ok(st)
 }
 
-fn flo_to_str(arg: f32, decimal_places: Option<usize>) -> Root<Str> {
+fn flo_to_str(arg: Flo, decimal_places: Option<usize>) -> Root<Str> {
 	let mut buf = SmallVec::<[u8; 128]>::new();
 
 	if let Some(decimal_places) = decimal_places {
@@ -372,11 +462,82 @@ fn del_global(name: Sym) -> GResult<()> {
 	glsp::del_global(name)
 }
 
+fn bind_const(name: Sym, init: Val) -> GResult<()> {
+	glsp::bind_const(name, init)
+}
+
+fn constp(name: Sym) -> GResult<bool> {
+	glsp::is_const(name)
+}
+
+fn redefine_const(name: Sym, new_value: Val) -> GResult<()> {
+	glsp::redefine_const(name, new_value)
+}
+
+fn deprecate(old_name: Sym, new_name: Sym, note: Option<&str>) -> GResult<()> {
+	glsp::bind_deprecated(old_name, new_name, note.unwrap_or(""))?;
+	Ok(())
+}
+
+fn deprecations_hit() -> Vec<Sym> {
+	glsp::deprecations_hit()
+}
+
+fn api_manifest() -> GResult<Root<Tab>> {
+	let manifest = glsp::tab();
+	for entry in glsp::api_manifest() {
+		let (min_args, max_args) = entry.arg_limits;
+		manifest.set(entry.name, try_tab! {
+			(MACROP_SYM, entry.is_macro),
+			(LIBRARY_SYM, entry.library),
+			(MIN_ARGS_SYM, min_args),
+			(MAX_ARGS_SYM, max_args),
+			(PARAMS_SYM, entry.param_names),
+			(PARAM_TYPES_SYM, entry.param_types),
+			(DOC_SYM, entry.doc),
+			(CAPABILITY_SYM, entry.capability),
+			(REPLACEMENT_SYM, entry.replacement),
+			(DEPRECATION_NOTE_SYM, entry.deprecation_note),
+		}?)?;
+	}
+
+	Ok(manifest)
+}
+
+fn in_module(name: Val) -> GResult<()> {
+	match name {
+		Val::Nil => glsp::in_module(None),
+		Val::Sym(name) => glsp::in_module(Some(name)),
+		val => bail!("expected a sym or #n, received {}", val.a_type_name())
+	}
+}
+
+fn current_module() -> Option<Sym> {
+	glsp::current_module()
+}
+
+fn has_module(name: Sym) -> bool {
+	glsp::has_module(name)
+}
+
+fn import_module(module: Sym, only: Option<Vec<Sym>>) -> GResult<()> {
+	glsp::import_module(module, only.as_ref().map(|names| &names[..]))
+}
+
+fn snapshot_globals() -> GlobalSnapshot {
+	glsp::global_snapshot()
+}
+
+fn restore_globals(snapshot: GlobalSnapshot) -> GResult<()> {
+	glsp::restore_globals(&snapshot)
+}
+
 fn get_macro(name: Sym) -> GResult<Expander> {
 	glsp::get_macro(name)
 }
 
 fn set_macro(name: Sym, mac: Expander) -> GResult<()> {
+	ensure_macro_permitted(&mac)?;
 	glsp::set_macro(name, mac)
 }
 
@@ -389,6 +550,7 @@ fn macro_opt(name: Sym) -> GResult<Option<Expander>> {
 }
 
 fn set_macro_opt(name: Sym, mac: Expander) -> GResult<()> {
+	ensure_macro_permitted(&mac)?;
 	if glsp::has_macro(name)? {
 		glsp::set_macro(name, mac)?;
 	}
@@ -401,6 +563,7 @@ fn has_macro(name: Sym) -> GResult<bool> {
 }
 
 fn bind_macro(name: Sym, init: Expander) -> GResult<()> {
+	ensure_macro_permitted(&init)?;
 	glsp::bind_macro(name, init)
 }
 
@@ -432,6 +595,30 @@ fn macro_no_op() -> GResult<()> {
 	macro_no_op!()
 }
 
+fn span_of(form: Val) -> GResult<Option<Root<Tab>>> {
+	let info = match glsp::span_of(&form) {
+		Some(info) => info,
+		None => return Ok(None)
+	};
+
+	let tab = glsp::tab();
+	if let (Some(filename), Some(line)) = (info.filename(), info.line()) {
+		tab.set(FILE_SYM, filename)?;
+		tab.set(glsp::sym("line")?, line)?;
+	}
+	tab.set(glsp::sym("expansions")?, glsp::arr_from_iter(info.expansions())?)?;
+
+	Ok(Some(tab))
+}
+
+fn with_span(form: Val, other: Val) -> Val {
+	glsp::with_span(&form, &other)
+}
+
+fn spanned(filename: &str, line: usize, form: Val) -> GResult<Val> {
+	glsp::spanned(filename, line, &form)
+}
+
 fn fn_name(arg: Val) -> GResult<Option<Sym>> {
 	match arg {
 		Val::GFn(ref gfn) => Ok(gfn.name()),
@@ -440,6 +627,45 @@ fn fn_name(arg: Val) -> GResult<Option<Sym>> {
 	}
 }
 
+fn doc(arg: Val, meth_name: Option<Sym>) -> GResult<Option<String>> {
+	if let Some(meth_name) = meth_name {
+		let doc = match arg {
+			Val::Obj(ref obj) => obj.class().meth_doc(meth_name)?,
+			Val::Class(ref class) => class.meth_doc(meth_name)?,
+			_ => bail!("expected an obj or a class, received {}", arg.a_type_name())
+		};
+
+		return Ok(doc.map(|doc| doc.to_string()))
+	}
+
+	//a sym refers to the global or macro which it names
+	let arg = match arg {
+		Val::Sym(name) => {
+			if glsp::has_global(name)? {
+				glsp::global(name)?
+			} else if glsp::has_macro(name)? {
+				match glsp::get_macro(name)? {
+					Expander::GFn(gfn) => Val::GFn(gfn),
+					Expander::RFn(rfn) => Val::RFn(rfn)
+				}
+			} else {
+				bail!("{} is not bound to a global or a macro", name)
+			}
+		}
+		arg => arg
+	};
+
+	let doc = match arg {
+		Val::GFn(ref gfn) => gfn.doc(),
+		Val::Class(ref class) => class.doc(),
+		Val::Obj(ref obj) => obj.class().doc(),
+		Val::RFn(_) => None,
+		_ => bail!("expected a fn, class or obj, received {}", arg.a_type_name())
+	};
+
+	Ok(doc.map(|doc| doc.to_string()))
+}
+
 fn fn_yieldsp(gfn: Root<GFn>) -> bool {
 	gfn.yields()
 }
@@ -456,6 +682,20 @@ fn max_args(callable: Callable) -> Option<usize> {
 	callable.max_args()
 }
 
+fn params(callable: Callable) -> Option<Root<Arr>> {
+	match callable {
+		Callable::GFn(gfn) => gfn.params(),
+		_ => None
+	}
+}
+
+fn return_type(callable: Callable) -> Option<Sym> {
+	match callable {
+		Callable::GFn(gfn) => gfn.return_type(),
+		_ => None
+	}
+}
+
 fn coro_state(coro: Root<Coro>) -> Sym {
 	match coro.state() {
 		CoroState::Newborn => NEWBORN_SYM,
@@ -474,18 +714,63 @@ fn coro_finish(coro: Root<Coro>) -> GResult<()> {
 	glsp::coro_finish(&coro)
 }
 
+fn coro_throw(coro: Root<Coro>, payload: Val) -> GResult<()> {
+	glsp::coro_throw(&coro, GError::from_val(payload))
+}
+
+fn coro_saveablep(coro: Root<Coro>) -> bool {
+	coro.check_saveability().is_ok()
+}
+
+fn coro_save(coro: Root<Coro>) -> GResult<Root<Tab>> {
+	glsp::coro_save(&coro)
+}
+
+fn coro_restore(data: Root<Tab>) -> GResult<Root<Coro>> {
+	glsp::coro_restore(&data)
+}
+
+fn open_child_scope() -> Root<Arr> {
+	glsp::open_child_scope()
+}
+
+fn suspend_child_scope(scope: Root<Arr>) {
+	glsp::suspend_child_scope(&scope)
+}
+
+fn resume_child_scope(scope: Root<Arr>) {
+	glsp::resume_child_scope(&scope)
+}
+
+fn close_child_scope(scope: Root<Arr>) -> GResult<()> {
+	glsp::close_child_scope(&scope)
+}
+
 fn gc() {
 	glsp::gc();
 }
 
+fn scratch_scope(callable: Callable) -> GResult<Val> {
+	glsp::scratch_scope(|| glsp::call(&callable, &()))
+}
+
+fn strict_scratch_scope(callable: Callable) -> GResult<Val> {
+	glsp::strict_scratch_scope(|| glsp::call(&callable, &()))
+}
+
 fn gc_value(name: Sym) -> GResult<Val> {
 	Ok(match name {
-		RATIO_SYM => Val::Flo(glsp::gc_ratio()),
-		MIN_RATIO_SYM => Val::Flo(GC_MIN_RATIO),
-		DEFAULT_RATIO_SYM => Val::Flo(GC_DEFAULT_RATIO),
+		RATIO_SYM => Val::Flo(glsp::gc_ratio() as Flo),
+		MIN_RATIO_SYM => Val::Flo(GC_MIN_RATIO as Flo),
+		DEFAULT_RATIO_SYM => Val::Flo(GC_DEFAULT_RATIO as Flo),
 		YOUNG_BYTES_SYM => Val::Int(glsp::gc_young_bytes() as i32),
 		OLD_BYTES_SYM => Val::Int(glsp::gc_old_bytes() as i32),
 		GHOST_BYTES_SYM => Val::Int(glsp::gc_ghost_bytes() as i32),
+		STEPS_SYM => Val::Int(glsp::gc_steps() as i32),
+		INTERNED_STRS_SYM => Val::Int(glsp::gc_interned_strs() as i32),
+		INTERNED_BYTES_SYM => Val::Int(glsp::gc_interned_bytes() as i32),
+		POOL_HITS_SYM => Val::Int(glsp::gc_pool_hits() as i32),
+		POOL_MISSES_SYM => Val::Int(glsp::gc_pool_misses() as i32),
 		name => bail!("unrecognized gc-value {}", name)
 	})
 }
@@ -494,7 +779,7 @@ fn set_gc_value(name: Sym, to_set: Val) -> GResult<()> {
 	Ok(match name {
 		RATIO_SYM => {
 			match to_set {
-				Val::Flo(ratio) => glsp::gc_set_ratio(ratio),
+				Val::Flo(ratio) => glsp::gc_set_ratio(ratio as f32),
 				to_set => bail!("invalid ratio {}", to_set)
 			}
 		}
@@ -502,23 +787,16 @@ fn set_gc_value(name: Sym, to_set: Val) -> GResult<()> {
 	})
 }
 
-#[cfg(not(target_arch = "wasm32"))]
-fn time() -> f32 {
-	super::time()
-}
-
-fn unix_time() -> String {
-	UNIX_EPOCH.elapsed().unwrap().as_secs().to_string()
-}
-
-fn sleep(secs: Num) -> GResult<()> {
-	super::sleep(secs.into_f32())
-}
-
 fn bail(args: &[Val]) -> GResult<()> {
 	match args.len() {
 		0 => bail!("(bail) was invoked"),
-		1 => bail!(args[0].clone()),
+		1 => {
+			//re-raising an error caught by (try-chain) preserves its source chain
+			match args[0] {
+				Val::Rec(ref rec) if rec.name() == ERR_REC_SYM => Err(error_from_rec(rec)?),
+				_ => bail!(args[0].clone())
+			}
+		}
 		_ => {
 			let mut builder = String::new();
 			super::collections::build_msg(&mut builder, &args[..], true).ok();
@@ -528,10 +806,13 @@ fn bail(args: &[Val]) -> GResult<()> {
 }
 
 fn try_call(mode: Val, callee: Val, args: &[Val]) -> GResult<Root<Arr>> {
-	let is_verbose = match mode {
-		Val::Sym(BRIEF_SYM) => false,
-		Val::Sym(VERBOSE_SYM) => true,
-		_ => return Ok(arr![ERR_SYM, str!("expected 'brief or 'verbose, received {}", mode)])
+	let (is_verbose, is_chain) = match mode {
+		Val::Sym(BRIEF_SYM) => (false, false),
+		Val::Sym(VERBOSE_SYM) => (true, false),
+		Val::Sym(CHAIN_SYM) => (false, true),
+		_ => {
+			return Ok(arr![ERR_SYM, str!("expected 'brief, 'verbose or 'chain, received {}", mode)])
+		}
 	};
 
 	let callable = if callee.is_callable() {
@@ -548,12 +829,23 @@ fn try_call(mode: Val, callee: Val, args: &[Val]) -> GResult<Root<Arr>> {
 	match glsp::try_call(is_verbose, &callable, args) {
 		Ok(result) => Ok(arr![OK_SYM, result]),
 		Err(err) => {
-			//we allow (macro-no-op) errors to bubble through (try) and (try-verbose)
-			if err.is_macro_no_op() {
+			//we allow (macro-no-op) and (exit) errors to bubble through (try) and (try-verbose)
+			if err.is_macro_no_op() || err.exit_code().is_some() {
 				Err(err)
 			} else {
 				if is_verbose {
-					Ok(arr![ERR_SYM, err.val(), err.stack_trace().unwrap()])
+					//an error from a (defer) form which failed while `err` was unwinding would
+					//otherwise be invisible, so we append it to the stack trace
+					let mut stack_trace = err.stack_trace().unwrap().to_string();
+					if let Some(defer_error) = err.defer_chain() {
+						stack_trace.push_str("\n\nwhile this error was unwinding, \
+						                      a (defer) form also failed:\n\n");
+						stack_trace.push_str(&defer_error.to_string());
+					}
+
+					Ok(arr![ERR_SYM, err.val(), stack_trace])
+				} else if is_chain {
+					Ok(arr![ERR_SYM, error_rec(&err)?])
 				} else {
 					Ok(arr![ERR_SYM, err.val()])
 				}
@@ -562,6 +854,66 @@ fn try_call(mode: Val, callee: Val, args: &[Val]) -> GResult<Root<Arr>> {
 	}
 }
 
+//(try-chain) describes each error as a %error rec with the fields `payload`, `source` and `kind`.
+//the source is either another %error rec or #n. the kind is the name of the error's MessageId, 
+//or #n. errors which aren't GErrors, like io::Errors, are described by their Display output.
+fn error_rec(error: &(dyn Error + 'static)) -> GResult<Val> {
+	let (payload, kind) = match error.downcast_ref::<GError>() {
+		Some(gerror) => {
+			let kind = match gerror.message_id() {
+				Some(id) => Val::Sym(glsp::sym(id.name())?),
+				None => Val::Nil
+			};
+
+			(gerror.val(), kind)
+		}
+		None => (Val::Str(glsp::str_from_rust_str(&error.to_string())), Val::Nil)
+	};
+
+	let source = match error.source() {
+		Some(source) => error_rec(source)?,
+		None => Val::Nil
+	};
+
+	Ok(Val::Rec(glsp::rec(ERR_REC_SYM, [payload, source, kind])?))
+}
+
+fn error_from_rec(rec: &Rec) -> GResult<GError> {
+	let error = GError::from_val(rec.get_index::<Val>(0)?);
+	match rec.get_index::<Val>(1)? {
+		Val::Rec(ref source) if source.name() == ERR_REC_SYM => {
+			Ok(error.with_source(error_from_rec(source)?))
+		}
+		_ => Ok(error)
+	}
+}
+
+fn expect_error_rec(val: &Val) -> GResult<&Root<Rec>> {
+	match *val {
+		Val::Rec(ref rec) if rec.name() == ERR_REC_SYM => Ok(rec),
+		ref val => bail!("expected an error caught by (try-chain), received {}", val.a_type_name())
+	}
+}
+
+fn err_source(error: Val) -> GResult<Val> {
+	expect_error_rec(&error)?.get_index(1)
+}
+
+fn err_chain(error: Val) -> GResult<Root<Arr>> {
+	let chain = glsp::arr();
+
+	let mut next = Some(expect_error_rec(&error)?.clone());
+	while let Some(rec) = next {
+		chain.push(rec.get_index::<Val>(0)?)?;
+		next = match rec.get_index::<Val>(1)? {
+			Val::Rec(source) => Some(source),
+			_ => None
+		};
+	}
+
+	Ok(chain)
+}
+
 fn stack_trace() -> String {
 	glsp::stack_trace()
 }
@@ -570,8 +922,17 @@ fn file_location() -> Option<String> {
 	glsp::file_location()
 }
 
-fn dump_form(arg: Val) -> GResult<()> {
-	eprn!("{}", glsp::dump_form(&arg)?);
+fn dump_form(arg: Val, mode: Option<Sym>) -> GResult<()> {
+	match mode.map(|sym| sym.name()) {
+		None => eprn!("{}", glsp::dump_form(&arg)?),
+		Some(name) if &*name == "unoptimized" => eprn!("{}", glsp::dump_form_unoptimized(&arg)?),
+		Some(name) if &*name == "both" => {
+			eprn!("UNOPTIMIZED\n\n{}", glsp::dump_form_unoptimized(&arg)?);
+			eprn!("OPTIMIZED\n\n{}", glsp::dump_form(&arg)?);
+		}
+		Some(name) => bail!("invalid dump-form mode '{}: expected 'unoptimized or 'both", name)
+	}
+
 	Ok(())
 }
 
@@ -585,6 +946,10 @@ fn dump_macro(sym: Sym) -> GResult<()> {
 	Ok(())
 }
 
+fn disassemble(gfn: Root<GFn>) -> String {
+	glsp::disassemble(&gfn)
+}
+
 fn not(arg: Val) -> bool {
 	match arg {
 		Val::Nil => true,
@@ -593,6 +958,13 @@ fn not(arg: Val) -> bool {
 	}
 }
 
+fn gensymp(arg: Val) -> bool {
+	match arg {
+		Val::Sym(sym) => sym.is_gensym(),
+		_ => false
+	}
+}
+
 fn gensym(tag: Option<Val>) -> GResult<Sym> {
 	match tag {
 		Some(Val::Sym(tag)) => glsp::gensym_with_tag(&tag.name()),
@@ -615,6 +987,14 @@ fn freedp(rdata: Root<RData>) -> bool {
 	rdata.is_freed()
 }
 
+fn expiredp(arg: Val) -> GResult<bool> {
+	match arg {
+		Val::RData(rdata) => Ok(rdata.is_expired()),
+		Val::View(view) => Ok(view.is_expired()),
+		arg => bail!("expected an rdata or view, but received {}", arg.a_type_name())
+	}
+}
+
 fn freeze(arg: Val) -> Val {
 	arg.freeze();
 	arg
@@ -625,27 +1005,13 @@ fn deep_freeze(arg: Val) -> Val {
 	arg
 }
 
-/*
-fn command_line_args() -> Root<Arr> {
-	//we use OsString here to avoid panicking if some kind of non-UTF string is passed on the
-	//command line, e.g. as the filepath on a non-English system
-	let os_args: Vec<OsString> = env::args_os().collect();
-	let arr = glsp::arr();
-
-	if os_args.len() < 1 {
-		//we guarantee that the first arg is present
-		arr.push(&Val::Str(glsp::str())).ok();
-	} else {
-		//convert each native arg into a str, replacing it with an empty str if it's invalid
-		for os_arg in os_args.into_iter() {
-			let st = glsp::str_from_rust_str(&os_arg.into_string().unwrap_or("".into()));
-			arr.push(&Val::Str(st)).ok();
-		}
-	}
+fn frozenp(arg: Val) -> bool {
+	arg.is_frozen()
+}
 
-	arr
+fn deep_frozenp(arg: Val) -> bool {
+	arg.is_deep_frozen()
 }
-*/
 
 fn clone(arg: Val) -> GResult<Val> {
 	arg.shallow_clone()
@@ -663,6 +1029,10 @@ fn eval_multi(vals: Vec<Val>, env_mode: Option<EnvMode>) -> GResult<Val> {
 	glsp::eval_multi(&vals[..], env_mode)
 }
 
+fn compile_fn(text: &str, filename: Option<&str>) -> GResult<Root<GFn>> {
+	glsp::compile_fn(text, filename)
+}
+
 fn load(filename: String) -> GResult<Val> {
 	glsp::load(&filename)
 }
@@ -671,6 +1041,14 @@ fn require(filename: String) -> GResult<Val> {
 	glsp::require(&filename)
 }
 
+fn loaded_files() -> Vec<LoadedFile> {
+	glsp::loaded_files()
+}
+
+fn reload(filename: String) -> GResult<ReloadReport> {
+	glsp::reload(&filename)
+}
+
 fn no_op(_: &[Val]) {
 	()
 }
@@ -678,3 +1056,43 @@ fn no_op(_: &[Val]) {
 fn identity(arg: Val) -> Val {
 	arg
 }
+
+//calls to (apply f args) and (call-with f ..rest) are usually rewritten into a direct call by the
+//compiler, so these rfns are only invoked when the function is called indirectly, or when apply
+//receives a kwargs table.
+fn apply(callable: Callable, args: Iterable, kwargs: Option<Root<Tab>>) -> GResult<Val> {
+	let mut vals = SmallVec::<[Val; 8]>::new();
+	match args {
+		Iterable::Arr(ref arr) => vals.extend(arr.iter()),
+		ref args => {
+			for result in args.giter() {
+				vals.push(result?);
+			}
+		}
+	}
+
+	if let Some(kwargs) = kwargs {
+		for (key, value) in kwargs.entries().iter() {
+			let name = match key {
+				Val::Sym(sym) => sym.name(),
+				key => bail!("apply expected a table with symbol keys, but it contained {}", 
+				             key.a_type_name())
+			};
+
+			let keyword = if name.starts_with(':') {
+				glsp::sym(&name)?
+			} else {
+				glsp::sym(&format!(":{}", name))?
+			};
+
+			vals.push(Val::Sym(keyword));
+			vals.push(value);
+		}
+	}
+
+	glsp::call(&callable, &vals[..])
+}
+
+fn call_with(callable: Callable, rest: &[Val]) -> GResult<Val> {
+	glsp::call(&callable, rest)
+}
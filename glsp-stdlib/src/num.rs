@@ -1,7 +1,10 @@
-use glsp::{bail, ensure, GResult, Num, rfn, Val};
+use glsp::{
+	bail, ensure, Flo, FromVal, GError, GResult, Lib, MessageArgs, MessageId, Num, rfn, Val,
+	VectorOp
+};
 use smallvec::SmallVec;
 use std::cmp::Ordering;
-use std::f32;
+use super::{rand::{rand_weighted_index, rand_weighted_with_rng, Rng}, Std};
 
 pub fn init(_sandboxed: bool) -> GResult<()> {
 	glsp::bind_rfn("+", rfn!(add))?;
@@ -11,6 +14,11 @@ pub fn init(_sandboxed: bool) -> GResult<()> {
 	glsp::bind_rfn("%", rfn!(rem))?;
 	glsp::bind_rfn("div-euclid", rfn!(div_euclid))?;
 	glsp::bind_rfn("rem-euclid", rfn!(rem_euclid))?;
+	glsp::bind_rfn("div-exact", rfn!(div_exact))?;
+	glsp::bind_rfn("div-flo", rfn!(div_flo))?;
+	glsp::bind_rfn("quot", rfn!(quot))?;
+	glsp::bind_rfn("rem", rfn!(rem))?;
+	glsp::bind_rfn("mod", rfn!(modulo))?;
 	glsp::bind_rfn("abs", rfn!(abs))?;
 	glsp::bind_rfn("sign", rfn!(sign))?;
 
@@ -28,11 +36,13 @@ pub fn init(_sandboxed: bool) -> GResult<()> {
 	glsp::bind_rfn(">", rfn!(gt))?;
 	glsp::bind_rfn(">=", rfn!(gte))?;
 	glsp::bind_rfn("ord", rfn!(ord))?;
+	glsp::bind_rfn("canonical-cmp", rfn!(canonical_cmp))?;
 	glsp::bind_rfn("min", rfn!(min))?;
 	glsp::bind_rfn("max", rfn!(max))?;
 	glsp::bind_rfn("clamp", rfn!(clamp))?;
 
 	glsp::bind_rfn("round", rfn!(round))?;
+	glsp::bind_rfn("round-even", rfn!(round_even))?;
 	glsp::bind_rfn("floor", rfn!(floor))?;
 	glsp::bind_rfn("ceil", rfn!(ceil))?;
 	glsp::bind_rfn("sqrt", rfn!(sqrt))?;
@@ -48,14 +58,35 @@ pub fn init(_sandboxed: bool) -> GResult<()> {
 	glsp::bind_rfn("asin", rfn!(asin))?;
 	glsp::bind_rfn("acos", rfn!(acos))?;
 	glsp::bind_rfn("atan", rfn!(atan))?;
+	glsp::bind_rfn("atan2", rfn!(atan2))?;
+	glsp::bind_rfn("hypot", rfn!(hypot))?;
+	glsp::bind_rfn("copysign", rfn!(copysign))?;
+	glsp::bind_rfn("remainder", rfn!(remainder))?;
+	glsp::bind_rfn("signum", rfn!(signum))?;
+	glsp::bind_rfn("deg->rad", rfn!(deg_to_rad))?;
+	glsp::bind_rfn("rad->deg", rfn!(rad_to_deg))?;
 
 	glsp::bind_rfn("bitand", rfn!(bitand))?;
 	glsp::bind_rfn("bitor", rfn!(bitor))?;
 	glsp::bind_rfn("bitxor", rfn!(bitxor))?;
 	glsp::bind_rfn("bitnot", rfn!(bitnot))?;
-	glsp::bind_rfn("bitshl", rfn!(bitshl))?;
-	glsp::bind_rfn("bitshr", rfn!(bitshr))?;
-	glsp::bind_rfn("bitsar", rfn!(bitsar))?;
+	glsp::bind_rfn("bitshl", rfn!(shl))?;
+	glsp::bind_rfn("bitshr", rfn!(shr))?;
+	glsp::bind_rfn("bitsar", rfn!(sar))?;
+	glsp::bind_rfn("bit-and", rfn!(bitand))?;
+	glsp::bind_rfn("bit-or", rfn!(bitor))?;
+	glsp::bind_rfn("bit-xor", rfn!(bitxor))?;
+	glsp::bind_rfn("bit-not", rfn!(bitnot))?;
+	glsp::bind_rfn("shl", rfn!(shl))?;
+	glsp::bind_rfn("shr", rfn!(shr))?;
+	glsp::bind_rfn("sar", rfn!(sar))?;
+	glsp::bind_rfn("popcount", rfn!(popcount))?;
+	glsp::bind_rfn("leading-zeros", rfn!(leading_zeros))?;
+	glsp::bind_rfn("trailing-zeros", rfn!(trailing_zeros))?;
+	glsp::bind_rfn("rotate-left", rfn!(rotate_left))?;
+	glsp::bind_rfn("bit-set?", rfn!(bit_setp))?;
+	glsp::bind_rfn("set-bit", rfn!(set_bit))?;
+	glsp::bind_rfn("clear-bit", rfn!(clear_bit))?;
 
 	glsp::bind_rfn("rand", rfn!(rand))?;
 	glsp::bind_rfn("coin-flip", rfn!(coin_flip))?;
@@ -64,65 +95,183 @@ pub fn init(_sandboxed: bool) -> GResult<()> {
 	glsp::bind_rfn("rand-weighted", rfn!(rand_weighted))?;
 	glsp::bind_rfn("rand-reseed", rfn!(rand_reseed))?;
 
+	glsp::bind_rfn("lerp", rfn!(lerp))?;
+	glsp::bind_rfn("inv-lerp", rfn!(inv_lerp))?;
+	glsp::bind_rfn("remap", rfn!(remap))?;
 	glsp::bind_rfn("smoothstep", rfn!(smoothstep))?;
+	glsp::bind_rfn("wrap", rfn!(wrap))?;
+	glsp::bind_rfn("approx=", rfn!(approx_eq))?;
 	glsp::bind_rfn("seek", rfn!(seek))?;
 	glsp::bind_rfn("antiseek", rfn!(antiseek))?;
 
 	Ok(())
 }
 
-fn add(args: &[Num]) -> Num {
-	args.iter().fold(Num::Int(0), |accum, &arg| accum + arg)
+//an argument to +, -, * or /. when both operands are numbers, we use Num's arithmetic; 
+//otherwise, at least one of them is a vec2, vec3 or mat3, so we defer to glsp::vector_arith.
+#[derive(Clone)]
+enum Operand {
+	Num(Num),
+	Vector(Val)
+}
+
+impl FromVal for Operand {
+	fn from_val(val: &Val) -> GResult<Operand> {
+		match *val {
+			Val::Int(i) => Ok(Operand::Num(Num::Int(i))),
+			Val::Flo(f) => Ok(Operand::Num(Num::Flo(f))),
+			Val::Vec2(_) | Val::Vec3(_) | Val::Mat3(_) => Ok(Operand::Vector(val.clone())),
+			ref val => {
+				let args = MessageArgs::new()
+					.with("expected", "a flo, int, vec2, vec3 or mat3")
+					.with("received", val.a_type_name());
+				Err(GError::from_message(MessageId::TypeMismatch, args))
+			}
+		}
+	}
 }
 
-fn sub(first: Num, rest: &[Num]) -> Num {
-	if rest.len() == 0 {
-		-first
-	} else {
-		rest.iter().fold(first, |accum, &arg| accum - arg)
+impl Operand {
+	fn into_val(self) -> Val {
+		match self {
+			Operand::Num(Num::Int(i)) => Val::Int(i),
+			Operand::Num(Num::Flo(f)) => Val::Flo(f),
+			Operand::Vector(val) => val
+		}
+	}
+
+	fn arith<F>(self, op: VectorOp, arg: &Operand, num_op: F) -> GResult<Operand>
+	where
+		F: FnOnce(Num, Num) -> GResult<Num>
+	{
+		match (self, arg) {
+			(Operand::Num(n0), &Operand::Num(n1)) => Ok(Operand::Num(num_op(n0, n1)?)),
+			(accum, arg) => {
+				let result = glsp::vector_arith(op, &accum.into_val(), &arg.clone().into_val())?;
+				Ok(Operand::Vector(result))
+			}
+		}
 	}
 }
 
+fn add(args: &[Operand]) -> GResult<Val> {
+	let sum = args.iter().try_fold(Operand::Num(Num::Int(0)), |accum, arg| {
+		accum.arith(VectorOp::Add, arg, |n0, n1| Ok(n0 + n1))
+	})?;
+
+	Ok(sum.into_val())
+}
+
+fn sub(first: Operand, rest: &[Operand]) -> GResult<Val> {
+	let difference = if rest.len() == 0 {
+		Operand::Num(Num::Int(0)).arith(VectorOp::Sub, &first, |_, n| Ok(-n))?
+	} else {
+		rest.iter().try_fold(first, |accum, arg| {
+			accum.arith(VectorOp::Sub, arg, |n0, n1| Ok(n0 - n1))
+		})?
+	};
+
+	Ok(difference.into_val())
+}
+
 //it would give us a more intuitively correct answer if we were to set result's initial value to a 
 //Flo if there are any Flo arguments: for example, that would stop (* #xf0000 #xf0000 3.0) from
 //overflowing when multiplying the first two arguments. however, this would be inconsistent
 //with the arithmetic operators' behaviour in vm.rs and transform.rs.
-fn mul(args: &[Num]) -> Num {
-	args.iter().fold(Num::Int(1), |accum, &arg| accum * arg)
+fn mul(args: &[Operand]) -> GResult<Val> {
+	let product = args.iter().try_fold(Operand::Num(Num::Int(1)), |accum, arg| {
+		accum.arith(VectorOp::Mul, arg, |n0, n1| Ok(n0 * n1))
+	})?;
+
+	Ok(product.into_val())
 }
 
-fn div(first: Num, rest: &[Num]) -> GResult<Num> {
-	if rest.len() == 0 {
-		ensure!(first != Num::Int(0), "divide-by-zero error");
-		Ok(Num::Flo(1.0) / first)
-	} else {
-		rest.iter().try_fold(first, |accum, &arg| {
-			ensure!(!(accum.is_int() && arg == Num::Int(0)), "divide-by-zero error");
-			Ok(accum / arg)
-		})
+//dividing an int by the int 0 is an error, because there's no int result. when either argument
+//is a flo, the result is a flo, so dividing by zero produces an inf or a nan instead. this 
+//matches the OpDiv and OpRem instrs in vm.rs.
+fn ensure_nonzero(numer: Num, denom: Num) -> GResult<()> {
+	match (numer, denom) {
+		(Num::Int(_), Num::Int(0)) => bail!("divide-by-zero error"),
+		_ => Ok(())
 	}
 }
 
+fn div(first: Operand, rest: &[Operand]) -> GResult<Val> {
+	let quotient = if rest.len() == 0 {
+		Operand::Num(Num::Flo(1.0)).arith(VectorOp::Div, &first, |n0, n1| Ok(n0 / n1))?
+	} else {
+		rest.iter().try_fold(first, |accum, arg| {
+			accum.arith(VectorOp::Div, arg, |n0, n1| {
+				ensure_nonzero(n0, n1)?;
+				Ok(n0 / n1)
+			})
+		})?
+	};
+
+	Ok(quotient.into_val())
+}
+
 fn div_euclid(first: Num, rest: &[Num]) -> GResult<Num> {
 	if rest.len() == 0 {
-		ensure!(first != Num::Int(0), "divide-by-zero error");
 		Ok(Num::Flo(1.0).div_euclid(first))
 	} else {
 		rest.iter().try_fold(first, |accum, &arg| {
-			ensure!(!(accum.is_int() && arg == Num::Int(0)), "divide-by-zero error");
+			ensure_nonzero(accum, arg)?;
 			Ok(accum.wrapping_div_euclid(arg))
 		})
 	}
 }
 
 fn rem(numer: Num, denom: Num) -> GResult<Num> {
-	ensure!(denom != Num::Int(0), "divide-by-zero error");
+	ensure_nonzero(numer, denom)?;
 	Ok(numer % denom)
 }
 
 fn rem_euclid(numer: Num, denom: Num) -> GResult<Num> {
-	ensure!(denom != Num::Int(0), "divide-by-zero error");
-	Ok(numer.rem_euclid(denom))
+	ensure_nonzero(numer, denom)?;
+	Ok(numer.wrapping_rem_euclid(denom))
+}
+
+fn div_exact(numer: i32, denom: i32) -> GResult<i32> {
+	ensure!(denom != 0, "divide-by-zero error");
+	ensure!(numer.wrapping_rem(denom) == 0, "{} is not divisible by {}", numer, denom);
+	Ok(numer.wrapping_div(denom))
+}
+
+fn div_flo(numer: Num, denom: Num) -> Flo {
+	numer.into_flo() / denom.into_flo()
+}
+
+fn quot(numer: Num, denom: Num) -> GResult<Num> {
+	ensure_nonzero(numer, denom)?;
+	match (numer, denom) {
+		(Num::Int(numer), Num::Int(denom)) => Ok(Num::Int(numer.wrapping_div(denom))),
+		(numer, denom) => Ok(Num::Flo((numer.into_flo() / denom.into_flo()).trunc()))
+	}
+}
+
+//the remainder of floored division, which has the same sign as the denominator
+fn modulo(numer: Num, denom: Num) -> GResult<Num> {
+	ensure_nonzero(numer, denom)?;
+	match (numer, denom) {
+		(Num::Int(numer), Num::Int(denom)) => {
+			let rem = numer.wrapping_rem(denom);
+			if rem != 0 && (rem < 0) != (denom < 0) {
+				Ok(Num::Int(rem.wrapping_add(denom)))
+			} else {
+				Ok(Num::Int(rem))
+			}
+		}
+		(numer, denom) => {
+			let (numer, denom) = (numer.into_flo(), denom.into_flo());
+			let rem = numer % denom;
+			if rem != 0.0 && (rem < 0.0) != (denom < 0.0) {
+				Ok(Num::Flo(rem + denom))
+			} else {
+				Ok(Num::Flo(rem))
+			}
+		}
+	}
 }
 
 fn abs(num: Num) -> Num {
@@ -133,24 +282,47 @@ fn sign(num: Num) -> i32 {
 	match num {
 		Num::Int(i) => i.signum(),
 		Num::Flo(f) => {
-			if f == 0.0f32 { 0 } 
+			if f == 0.0 { 0 } 
 			else if f.is_nan() { 0 }
 			else { f.signum() as i32 }
 		}
 	}
 }
 
+fn is_num_or_char(val: &Val) -> bool {
+	val.is_int() || val.is_flo() || val.is_char()
+}
+
 fn min(first: Val, rest: &[Val]) -> GResult<Val> {
-	ensure!(rest.iter().all(|val| val.is_int() || val.is_flo() || val.is_char()),
-	        "non-number passed to min");
+	//when any argument isn't a number, fall back to (ord), which respects op-ord methods
+	if !is_num_or_char(&first) || !rest.iter().all(is_num_or_char) {
+		let mut accum = first;
+		for arg in rest {
+			if arg.try_cmp(&accum)? == Ordering::Less {
+				accum = arg.clone();
+			}
+		}
+
+		return Ok(accum)
+	}
+
 	Ok(rest.iter().fold(first, |accum, arg| {
 		if arg.num_lt(&accum).unwrap() { arg.clone() } else { accum }
 	}))
 }
 
 fn max(first: Val, rest: &[Val]) -> GResult<Val> {
-	ensure!(rest.iter().all(|val| val.is_int() || val.is_flo() || val.is_char()),
-	        "non-number passed to max");
+	if !is_num_or_char(&first) || !rest.iter().all(is_num_or_char) {
+		let mut accum = first;
+		for arg in rest {
+			if arg.try_cmp(&accum)? == Ordering::Greater {
+				accum = arg.clone();
+			}
+		}
+
+		return Ok(accum)
+	}
+
 	Ok(rest.iter().fold(first, |accum, arg| {
 		if arg.num_gt(&accum).unwrap() { arg.clone() } else { accum }
 	}))
@@ -226,6 +398,13 @@ fn round(num: Num) -> Num {
 	}
 }
 
+fn round_even(num: Num) -> Num {
+	match num {
+		Num::Int(i) => Num::Int(i),
+		Num::Flo(f) => Num::Flo(f.round_ties_even())
+	}
+}
+
 fn floor(num: Num) -> Num {
 	match num {
 		Num::Int(i) => Num::Int(i),
@@ -242,14 +421,14 @@ fn ceil(num: Num) -> Num {
 
 fn sqrt(num: Num) -> Num {
 	match num {
-		Num::Int(i) => Num::Flo((i as f32).sqrt()),
+		Num::Int(i) => Num::Flo((i as Flo).sqrt()),
 		Num::Flo(f) => Num::Flo(f.sqrt())
 	}
 }
 
 fn cbrt(num: Num) -> Num {
 	match num {
-		Num::Int(i) => Num::Flo((i as f32).cbrt()),
+		Num::Int(i) => Num::Flo((i as Flo).cbrt()),
 		Num::Flo(f) => Num::Flo(f.cbrt())
 	}
 }
@@ -257,57 +436,60 @@ fn cbrt(num: Num) -> Num {
 fn pow(base: Num, exponent: Num) -> Num {
 	match (base, exponent) {
 		(Num::Int(base), Num::Int(exp)) if exp >= 0 => Num::Int(base.wrapping_pow(exp as u32)),
-		_ => Num::Flo(base.into_f32().powf(exponent.into_f32()))
+		_ => Num::Flo(base.into_flo().powf(exponent.into_flo()))
 	}
 }
 
-fn log(x: Num, base: Option<Num>) -> f32 {
+fn log(x: Num, base: Option<Num>) -> Flo {
 	if let Some(base) = base {
-		if base.into_f32() == 2.0 {
-			x.into_f32().log2()
-		} else if base.into_f32() == 10.0 {
-			x.into_f32().log10()
+		if base.into_flo() == 2.0 {
+			x.into_flo().log2()
+		} else if base.into_flo() == 10.0 {
+			x.into_flo().log10()
 		} else {
-			x.into_f32().log(base.into_f32())
+			x.into_flo().log(base.into_flo())
 		}
 	} else {
-		x.into_f32().ln()
+		x.into_flo().ln()
 	}
 }
 
-fn flo_sign(f: f32) -> f32 {
+fn flo_sign(f: Flo) -> Flo {
 	f.signum()
 }
 
-fn trunc(f: f32) -> f32 {
-	f.trunc()
+fn trunc(num: Num) -> Num {
+	match num {
+		Num::Int(i) => Num::Int(i),
+		Num::Flo(f) => Num::Flo(f.trunc())
+	}
 }
 
-fn fract(f: f32) -> f32 {
-	f.fract()
+fn fract(num: Num) -> Flo {
+	num.into_flo().fract()
 }
 
-fn sin(f: f32) -> f32 {
+fn sin(f: Flo) -> Flo {
 	f.sin()
 }
 
-fn asin(f: f32) -> f32 {
+fn asin(f: Flo) -> Flo {
 	f.asin()
 }
 
-fn cos(f: f32) -> f32 {
+fn cos(f: Flo) -> Flo {
 	f.cos()
 }
 
-fn acos(f: f32) -> f32 {
+fn acos(f: Flo) -> Flo {
 	f.acos()
 }
 
-fn tan(f: f32) -> f32 {
+fn tan(f: Flo) -> Flo {
 	f.tan()
 }
 
-fn atan(y: f32, x: Option<f32>) -> f32 {
+fn atan(y: Flo, x: Option<Flo>) -> Flo {
 	if let Some(x) = x {
 		y.atan2(x)
 	} else {
@@ -315,11 +497,86 @@ fn atan(y: f32, x: Option<f32>) -> f32 {
 	}
 }
 
-fn nanp(f: f32) -> bool {
+fn atan2(y: Num, x: Num) -> Flo {
+	y.into_flo().atan2(x.into_flo())
+}
+
+fn hypot(x: Num, y: Num) -> Flo {
+	x.into_flo().hypot(y.into_flo())
+}
+
+fn copysign(x: Num, y: Num) -> Flo {
+	x.into_flo().copysign(y.into_flo())
+}
+
+//the ieee 754 remainder operation, which rounds the quotient to the nearest integer (with ties
+//rounding to even) rather than truncating it. the result is in the range [-y/2, y/2]. computing
+//x - round(x/y)*y would round both the quotient and the product, so instead we follow fdlibm:
+//fmod (which is exact) reduces |x| to the range [0, 2|y|), and then we subtract |y| at most
+//twice. each of those subtractions is exact, so the result is exact for any two flos.
+fn remainder(x: Num, y: Num) -> Flo {
+	let (x, y) = (x.into_flo(), y.into_flo());
+
+	if x.is_nan() || y.is_nan() || x.is_infinite() || y == 0.0 {
+		return Flo::NAN
+	}
+
+	let p = y.abs();
+	let mut r = x.abs();
+
+	//2|y| would overflow, but in that case |x| is already less than 2|y|
+	if p <= Flo::MAX / 2.0 {
+		r %= p + p;
+	}
+
+	//halving a subnormal |y| would round it, and doubling r could overflow for a large |y|
+	if p < Flo::MIN_POSITIVE * 2.0 {
+		if r + r > p {
+			r -= p;
+			if r + r >= p {
+				r -= p;
+			}
+		}
+	} else {
+		let half = 0.5 * p;
+		if r > half {
+			r -= p;
+			if r >= half {
+				r -= p;
+			}
+		}
+	}
+
+	//this also gives a zero result the sign of x, as ieee 754 requires
+	if x.is_sign_negative() {
+		-r
+	} else {
+		r
+	}
+}
+
+//unlike f32::signum, zeroes and nans are passed through unchanged
+fn signum(num: Num) -> Num {
+	match num {
+		Num::Int(i) => Num::Int(i.signum()),
+		Num::Flo(f) if f == 0.0 || f.is_nan() => Num::Flo(f),
+		Num::Flo(f) => Num::Flo(f.signum())
+	}
+}
+
+fn deg_to_rad(degrees: Num) -> Flo {
+	degrees.into_flo().to_radians()
+}
+
+fn rad_to_deg(radians: Num) -> Flo {
+	radians.into_flo().to_degrees()
+}
+
+fn nanp(f: Flo) -> bool {
 	f.is_nan()
 }
 
-fn infp(f: f32) -> bool {
+fn infp(f: Flo) -> bool {
 	f.is_infinite()
 }
 
@@ -340,18 +597,54 @@ fn bitnot(arg: i32) -> i32 {
 }
 
 //just like the integer arithmetic functions, the bitshifting functions are designed to behave in 
-//an identical way to rust-with-overflow-checks-disabled.
-fn bitshl(arg: i32, shift: u32) -> i32 {
-	arg.overflowing_shl(shift).0
+//an identical way to rust-with-overflow-checks-disabled: the shift count is masked to the range
+//0..32, so a negative count like -1 is equivalent to 31. calls with two arguments are usually
+//compiled into OpShl, OpShr and OpSar instead, which behave identically.
+fn shl(arg: i32, shift: i32) -> i32 {
+	arg.wrapping_shl(shift as u32)
+}
+
+fn shr(arg: i32, shift: i32) -> i32 {
+	(arg as u32).wrapping_shr(shift as u32) as i32
+}
+
+fn sar(arg: i32, shift: i32) -> i32 {
+	arg.wrapping_shr(shift as u32)
+}
+
+fn popcount(arg: i32) -> i32 {
+	arg.count_ones() as i32
+}
+
+fn leading_zeros(arg: i32) -> i32 {
+	arg.leading_zeros() as i32
+}
+
+fn trailing_zeros(arg: i32) -> i32 {
+	arg.trailing_zeros() as i32
 }
 
-fn bitshr(arg: i32, shift: u32) -> i32 {
-	//as far as i can tell, rust doesn't currently perform any bounds checks for `my_i32 as u32`
-	(arg as u32).overflowing_shr(shift).0 as i32
+fn rotate_left(arg: i32, n: i32) -> i32 {
+	arg.rotate_left((n as u32) & 31)
 }
 
-fn bitsar(arg: i32, shift: u32) -> i32 {
-	arg.overflowing_shr(shift).0
+//unlike the shift functions, the single-bit functions are strict about their bit index, because
+//an out-of-range index is almost certainly a bug
+fn bit_mask(n: i32) -> GResult<i32> {
+	ensure!(n >= 0 && n < 32, "bit index {} is outside the range 0 to 31", n);
+	Ok(1_i32.wrapping_shl(n as u32))
+}
+
+fn bit_setp(arg: i32, n: i32) -> GResult<bool> {
+	Ok(arg & bit_mask(n)? != 0)
+}
+
+fn set_bit(arg: i32, n: i32) -> GResult<i32> {
+	Ok(arg | bit_mask(n)?)
+}
+
+fn clear_bit(arg: i32, n: i32) -> GResult<i32> {
+	Ok(arg & !bit_mask(n)?)
 }
 
 fn num_eq(args: &[Val]) -> GResult<bool> {
@@ -390,10 +683,11 @@ fn gte(args: &[Val]) -> GResult<bool> {
 }
 
 fn ord(arg0: Val, arg1: Val) -> GResult<Ordering> {
-	ensure!((arg0.is_int() || arg0.is_flo() || arg0.is_char()) &&
-	        (arg1.is_int() || arg1.is_flo() || arg1.is_char()),
-	        "non-number passed to ord");
-	Ok(arg0.num_cmp(&arg1).unwrap())
+	arg0.try_cmp(&arg1)
+}
+
+fn canonical_cmp(arg0: Val, arg1: Val) -> Ordering {
+	arg0.canonical_cmp(&arg1)
 }
 
 fn rand(arg0: Num, arg1: Option<Num>) -> Num {
@@ -413,15 +707,15 @@ fn rand(arg0: Num, arg1: Option<Num>) -> Num {
 				return Num::Int(i0)
 			}
 		}
-		(Num::Int(i0), Num::Flo(f1)) => (i0 as f32, f1),
-		(Num::Flo(f0), Num::Int(i1)) => (f0, i1 as f32),
+		(Num::Int(i0), Num::Flo(f1)) => (i0 as Flo, f1),
+		(Num::Flo(f0), Num::Int(i1)) => (f0, i1 as Flo),
 		(Num::Flo(f0), Num::Flo(f1)) => (f0, f1),
 	};
 
 	if f0 == f1 {
 		Num::Flo(f0)
 	} else {
-		Num::Flo(f0 + super::rand_f32() * (f1 - f0))
+		Num::Flo(f0 + (super::rand_f32() as Flo) * (f1 - f0))
 	}
 }
 
@@ -429,13 +723,13 @@ fn coin_flip() -> bool {
 	super::rand_bool()
 }
 
-fn chance(chance: f32) -> bool {
+fn chance(chance: Flo) -> bool {
 	if chance <= 0.0 {
 		false
 	} else if chance >= 1.0 {
 		true
 	} else {
-		super::rand_f32() < chance
+		(super::rand_f32() as Flo) < chance
 	}
 }
 
@@ -446,55 +740,109 @@ fn rand_select(args: &[Val]) -> GResult<Val> {
 	Ok(args[i].clone())
 }
 
+//(rand-weighted rng choices weights) uses an rng object, while (rand-weighted c0 w0 c1 w1...)
+//uses the global rng
 fn rand_weighted(args: &[Val]) -> GResult<Val> {
+	if let Some(Val::RData(rdata)) = args.first() {
+		if rdata.is::<Rng>() {
+			ensure!(args.len() == 3, "expected an rng, an arr of choices and an arr of weights");
+
+			let (choices, weights) = match (&args[1], &args[2]) {
+				(Val::Arr(choices), Val::Arr(weights)) => (choices, weights),
+				_ => bail!("expected an rng, an arr of choices and an arr of weights")
+			};
+
+			let mut rng = rdata.try_borrow_mut::<Rng>()?;
+			return rand_weighted_with_rng(&mut *rng, choices, weights)
+		}
+	}
+
 	ensure!(args.len() > 0, "expected at least one argument");
 	ensure!(args.len() % 2 == 0, "expected an even number of arguments");
 
 	let mut choices = SmallVec::<[Val; 32]>::with_capacity(args.len() / 2);
-	let mut weights = SmallVec::<[f32; 32]>::with_capacity(args.len() / 2);
-	let mut total_weight = 0.0_f32;
+	let mut weights = SmallVec::<[Flo; 32]>::with_capacity(args.len() / 2);
 	for pair in args.chunks_exact(2) {
 		let weight = match pair[1] {
-			Val::Int(i) if i >= 0 => i as f32,
-			Val::Flo(f) if f >= 0.0 => f,
+			Val::Int(i) => i as Flo,
+			Val::Flo(f) => f,
 			_ => bail!("each weight must be a non-negative int or a non-negative flo")
 		};
 
 		choices.push(pair[0].clone());
 		weights.push(weight);
-		total_weight += weight;
 	}
 
-	ensure!(total_weight != 0.0, "the total weight must not be 0");
+	let i = rand_weighted_index(&mut Std::borrow_mut().rng, &weights)?;
+	Ok(choices[i].clone())
+}
 
-	let selection = super::rand_f32() * total_weight;
+fn rand_reseed(seed: i32) {
+	super::rand_reseed(seed)
+}
 
-	let mut accum = 0.0;
-	for i in 0 .. weights.len() {
-		if accum >= selection && weights[i] > 0.0 {
-			return Ok(choices[i].clone())
-		}
-		accum += weights[i];
-	}
+//we use the form a*(1-t) + b*t, rather than a + (b-a)*t, because it guarantees that the
+//result will be exactly `b` when `t` is 1.0
+fn lerp(a: Num, b: Num, t: Num) -> Flo {
+	let (a, b, t) = (a.into_flo(), b.into_flo(), t.into_flo());
+	a * (1.0 - t) + b * t
+}
 
-	//this should be unreachable, but just in case...
-	Ok(choices.last().unwrap().clone())
+fn inv_lerp(a: Num, b: Num, v: Num) -> Flo {
+	let (a, b, v) = (a.into_flo(), b.into_flo(), v.into_flo());
+	(v - a) / (b - a)
 }
 
-fn rand_reseed(seed: i32) {
-	super::rand_reseed(seed)
+fn remap(v: Num, a: Num, b: Num, c: Num, d: Num) -> Flo {
+	let t = inv_lerp(a, b, v);
+	lerp(c, d, Num::Flo(t))
 }
 
-fn smoothstep(f: f32) -> f32 {
+fn smoothstep(arg0: Num, arg1: Option<Num>, arg2: Option<Num>) -> GResult<Flo> {
+	let f = match (arg1, arg2) {
+		(None, None) => arg0.into_flo(),
+		(Some(b), Some(t)) => inv_lerp(arg0, b, t),
+		_ => bail!("expected one or three arguments, but received two")
+	};
+
 	if f <= 0.0 {
-		0.0
+		Ok(0.0)
 	} else if f >= 1.0 {
-		1.0
+		Ok(1.0)
 	} else {
-		3.0*f*f - 2.0*f*f*f
+		Ok(3.0*f*f - 2.0*f*f*f)
 	}
 }
 
+fn wrap(x: Num, lo: Num, hi: Num) -> GResult<Num> {
+	ensure!(lo < hi, "the lower bound {} is not less than the upper bound {}", lo, hi);
+
+	match (x, lo, hi) {
+		(Num::Int(x), Num::Int(lo), Num::Int(hi)) => {
+			//we use i64 to avoid overflow when the bounds are far apart
+			let range = hi as i64 - lo as i64;
+			Ok(Num::Int((lo as i64 + (x as i64 - lo as i64).rem_euclid(range)) as i32))
+		}
+		(x, lo, hi) => {
+			let (x, lo, hi) = (x.into_flo(), lo.into_flo(), hi.into_flo());
+			let result = lo + (x - lo).rem_euclid(hi - lo);
+
+			//rem_euclid can round up to exactly (hi - lo) for tiny negative inputs
+			Ok(Num::Flo(if result >= hi { lo } else { result }))
+		}
+	}
+}
+
+fn approx_eq(a: Num, b: Num, epsilon: Option<Num>) -> GResult<bool> {
+	let epsilon = epsilon.map(Num::into_flo).unwrap_or(1e-5);
+	ensure!(epsilon >= 0.0, "negative epsilon {}", epsilon);
+
+	let (a, b) = (a.into_flo(), b.into_flo());
+
+	//the equality check handles infinities, which would otherwise produce a nan difference
+	Ok(a == b || (a - b).abs() <= epsilon)
+}
+
 fn seek(orig: Num, target: Num, step_by: Option<Num>) -> GResult<Num> {
 	let step_by = step_by.unwrap_or(Num::Int(1));
 	ensure!(step_by >= Num::Int(0), "negative step {}", step_by);
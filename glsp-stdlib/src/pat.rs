@@ -30,6 +30,7 @@ pub(crate) enum Matcher {
 
 	Arr(Vec<Pat>),
 	Index(Vec<(Val, Pat)>), //(key, pattern)
+	Class(Sym, Vec<(Sym, Pat)>), //(class name, [(field, pattern)])
 	Opt(Box<Matcher>, Option<Val>),
 	Rest(Box<Matcher>),
 }
@@ -228,6 +229,36 @@ fn matcher_from_form(
 					ensure_at!(arr.span(), arr.len() == 2, "invalid ' form in pattern");
 					arr.get(1)?
 				}
+				Val::Sym(class_name) if is_class_pattern(&arr)? => {
+					//(ClassName :field pat :field pat ...)
+					let elements = SmallVec::<[Val; 8]>::from_iter(arr.iter().skip(1));
+					let mut remaining = &elements[..];
+
+					let mut pairs = Vec::<(Sym, Pat)>::new();
+					while remaining.len() > 0 {
+						let field = match &remaining[0] {
+							Val::Sym(sym) if is_field_keyword(*sym) => {
+								glsp::sym(&sym.name()[1..])?
+							}
+							val => bail_at!(arr.span(), "in class patterns, expected a :field \
+							                keyword but received {}", val)
+						};
+
+						ensure_at!(arr.span(), remaining.len() >= 2, "in class patterns, the \
+						           :{} keyword is missing a sub-pattern", field);
+
+						let (pat, forms_consumed) = pat_from_forms(
+							&remaining[1..],
+							atsign_params,
+							arr.span()
+						)?;
+
+						pairs.push((field, pat));
+						remaining = &remaining[1 + forms_consumed..];
+					}
+
+					return Ok(Matcher::Class(class_name, pairs))
+				}
 				_ => {
 					let elements = SmallVec::<[Val; 8]>::from_iter(arr.iter());
 					let mut remaining = &elements[..];
@@ -253,16 +284,30 @@ fn matcher_from_form(
 
 	match literal_form {
 		Val::Nil | Val::Bool(_) | Val::Int(_) | Val::Flo(_) | Val::Char(_) | 
-		Val::Sym(_) | Val::Arr(_) | Val::Tab(_) | Val::Str(_) => {
+		Val::Sym(_) | Val::Arr(_) | Val::Tab(_) | Val::Str(_) | Val::Rec(_) => {
 			Ok(Matcher::Literal(literal_form))
 		}
 		Val::GIter(..) | Val::Obj(..) | Val::Class(..) | Val::GFn(..) | 
-		Val::RFn(..) | Val::Coro(..) | Val::RData(..) => {
+		Val::RFn(..) | Val::Coro(..) | Val::RData(..) | Val::PArr(..) | Val::PTab(..) |
+		Val::View(..) | Val::Vec2(..) | Val::Vec3(..) | Val::Mat3(..) => {
 			bail_at!(span, "non-syntax value {} encountered in pattern", literal_form)
 		}
 	}
 }
 
+//an array pattern is a class pattern if it starts with a symbol, followed by a :field keyword
+fn is_class_pattern(arr: &Arr) -> GResult<bool> {
+	Ok(arr.len() >= 2 && match arr.get(1)? {
+		Val::Sym(sym) => is_field_keyword(sym),
+		_ => false
+	})
+}
+
+fn is_field_keyword(sym: Sym) -> bool {
+	let name = sym.name();
+	name.len() > 1 && name.starts_with(':')
+}
+
 fn pred_from_form(form: Val, span: Span) -> GResult<Pred> {
 	match form {
 		Val::Sym(name) => Ok(Pred::Sym(name)),
@@ -328,9 +373,13 @@ pub(crate) enum PlaceStrategy {
 	//the local variable x
 	Local,
 
-	//the global variable (global 'x)
+	//the global variable (global 'x), where x is resolved relative to the current module
 	Global,
 
+	//the global variable (global 'x), where x is qualified with the current module's name, as
+	//though it were being defined by (def)
+	DefGlobal,
+
 	//the field @SymName:x
 	Atsign(Sym)
 }
@@ -354,7 +403,14 @@ impl SetStrategy {
 	fn codegen(&self, dst: &Arr, binding_name: Sym, to_assign: Val) -> GResult<()> {
 		let place: Val = match self.0 {
 			PlaceStrategy::Local => backquote!("~binding_name"),
-			PlaceStrategy::Global => backquote!("(global '~binding_name)"),
+			PlaceStrategy::Global => {
+				let global_name = glsp::resolve_global(binding_name)?;
+				backquote!("(global '~global_name)")
+			}
+			PlaceStrategy::DefGlobal => {
+				let global_name = glsp::qualify_definition(binding_name)?;
+				backquote!("(global '~global_name)")
+			}
 			PlaceStrategy::Atsign(state_name) => {
 				let qualified_str = format!("{}:{}", state_name, binding_name);
 				let qualified_sym = glsp::sym(&qualified_str)?;
@@ -406,6 +462,11 @@ impl Matcher {
 					pat.names(dst, atsigns_only);
 				}
 			}
+			Matcher::Class(_, vec) => {
+				for (_, pat) in vec {
+					pat.names(dst, atsigns_only);
+				}
+			}
 			Matcher::Opt(matcher, _) => matcher.names(dst, atsigns_only),
 			Matcher::Rest(matcher) => matcher.names(dst, atsigns_only)
 		}
@@ -779,6 +840,17 @@ impl Pat {
 
 					(coll_name, glsp::sym("remove!")?)
 				} else {
+					//(has?) would fail for a value which can't be indexed at all, so we need to
+					//check for that here in order to report a mismatch rather than an error
+					if let MismatchStrategy::FinishBlock(block_name) = mismatch_strategy {
+						let to_push: Val = backquote!(r#"
+							(unless (or (tab? ~src_name) (arr? ~src_name) 
+							            (obj? ~src_name) (class? ~src_name))
+							  (finish-block ~block_name #n))
+						"#);
+						dst.push(to_push)?;
+					}
+
 					(src_name, ACCESS_SYM)
 				};
 
@@ -915,6 +987,98 @@ impl Pat {
 				}
 			}
 
+			Matcher::Class(class_name, pairs) if glsp::rec_fields(*class_name).is_some() => {
+				//the same syntax is used to destructure a rec. we check the field names against
+				//the rec type while expanding the pattern, so no (has?) check is needed
+				let rec_fields = glsp::rec_fields(*class_name).unwrap();
+				for &(field, _) in pairs {
+					ensure!(rec_fields.contains(&field), 
+					        "{} is not a field of the {} rec type", field, class_name);
+				}
+
+				let to_push: Val = match mismatch_strategy {
+					MismatchStrategy::FinishBlock(block_name) => {
+						backquote!(r#"
+							(unless (%rec-is? ~src_name '~class_name)
+							  (finish-block ~block_name #n))
+						"#)
+					}
+					MismatchStrategy::Bail => {
+						let err_msg = format!("rec pattern mismatch: expected a {} rec, \
+						                      received ", class_name);
+						backquote!(r#"
+							(ensure (%rec-is? ~src_name '~class_name)
+							  ~err_msg (type-of ~src_name))
+						"#)
+					}
+				};
+				dst.push(to_push)?;
+
+				for &(field, ref pat) in pairs {
+					let element_name = glsp::gensym();
+					let to_push: Val = backquote!("(let ~element_name [~src_name '~field])");
+					dst.push(to_push)?;
+
+					pat.codegen(
+						element_name,
+						dst,
+						set_strategy,
+						mismatch_strategy
+					)?;
+				}
+			}
+
+			Matcher::Class(class_name, pairs) => {
+				//check that the input is an instance of the class (or one of its mixins), then
+				//match each field's sub-pattern against the field's current value
+				let to_push: Val = match mismatch_strategy {
+					MismatchStrategy::FinishBlock(block_name) => {
+						backquote!(r#"
+							(unless (and (obj? ~src_name) (is? ~src_name ~class_name))
+							  (finish-block ~block_name #n))
+						"#)
+					}
+					MismatchStrategy::Bail => {
+						let err_msg = format!("class pattern mismatch: expected an instance of \
+						                      {}, received ", class_name);
+						backquote!(r#"
+							(ensure (and (obj? ~src_name) (is? ~src_name ~class_name))
+							  ~err_msg (type-of ~src_name))
+						"#)
+					}
+				};
+				dst.push(to_push)?;
+
+				for &(field, ref pat) in pairs {
+					let else_form: Val = match mismatch_strategy {
+						MismatchStrategy::FinishBlock(block_name) => {
+							backquote!("(finish-block ~block_name #n)")
+						}
+						MismatchStrategy::Bail => {
+							let err_msg = format!("class pattern mismatch: missing field {}", 
+							                      field);
+							backquote!("(bail ~err_msg)")
+						}
+					};
+
+					let element_name = glsp::gensym();
+					let to_push: Val = backquote!(r#"
+						(let ~element_name 
+						  (if (has? ~src_name '~field)
+						    [~src_name '~field]
+						    ~else_form))
+					"#);
+					dst.push(to_push)?;
+
+					pat.codegen(
+						element_name,
+						dst,
+						set_strategy,
+						mismatch_strategy
+					)?;
+				}
+			}
+
 			//Opt and Rest are only valid as the immediate child of an Arr or Index.
 			Matcher::Opt(_, _) => bail_at!(self.span, "unexpected (?) pattern"),
 			Matcher::Rest(_) => bail_at!(self.span, "unexpected .. pattern"),
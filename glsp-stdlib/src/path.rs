@@ -0,0 +1,139 @@
+use glsp::{bail, GResult, rfn};
+use std::ffi::{OsString};
+use std::path::{Component, Path, PathBuf};
+
+//these functions are all purely lexical: they manipulate strs using std::path's rules for the
+//host platform, but they never touch the filesystem. this means that they're safe to bind even
+//when the Runtime is sandboxed.
+//
+//paths are received from glsp as strs, so they can't contain non-UTF-8 data. however, a path
+//component returned by std::path could theoretically split a multi-byte character (this can't
+//happen on any platform we support, but it isn't guaranteed). in that case, converting the
+//result back into a str will trigger an error rather than silently losing data.
+
+pub fn init(_sandboxed: bool) -> GResult<()> {
+	glsp::bind_rfn("path-join", rfn!(path_join))?;
+	glsp::bind_rfn("path-parent", rfn!(path_parent))?;
+	glsp::bind_rfn("path-file-name", rfn!(path_file_name))?;
+	glsp::bind_rfn("path-stem", rfn!(path_stem))?;
+	glsp::bind_rfn("path-ext", rfn!(path_ext))?;
+	glsp::bind_rfn("path-with-ext", rfn!(path_with_ext))?;
+	glsp::bind_rfn("path-normalize", rfn!(path_normalize))?;
+	glsp::bind_rfn("path-relative", rfn!(path_relative))?;
+	glsp::bind_rfn("path-absolute?", rfn!(path_absolutep))?;
+
+	Ok(())
+}
+
+fn path_join(first: &Path, rest: &[PathBuf]) -> PathBuf {
+	let mut result = first.to_path_buf();
+	for component in rest {
+		result.push(component);
+	}
+
+	result
+}
+
+fn path_parent(path: &Path) -> Option<PathBuf> {
+	path.parent().map(|parent| parent.to_path_buf())
+}
+
+fn path_file_name(path: &Path) -> Option<OsString> {
+	path.file_name().map(|name| name.to_os_string())
+}
+
+fn path_stem(path: &Path) -> Option<OsString> {
+	path.file_stem().map(|stem| stem.to_os_string())
+}
+
+fn path_ext(path: &Path) -> Option<OsString> {
+	path.extension().map(|ext| ext.to_os_string())
+}
+
+fn path_with_ext(path: &Path, ext: &str) -> PathBuf {
+	path.with_extension(ext.trim_start_matches('.'))
+}
+
+fn path_absolutep(path: &Path) -> bool {
+	path.is_absolute()
+}
+
+//removes `.` components, and resolves `..` components against any preceding normal component.
+//leading `..` components in a relative path are preserved, while `..` components immediately
+//after the root are discarded, matching the behaviour of most operating systems.
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+	let mut result = PathBuf::new();
+	let mut normal_depth = 0_usize;
+
+	for component in path.components() {
+		match component {
+			Component::Prefix(_) | Component::RootDir => {
+				result.push(component.as_os_str());
+			}
+			Component::CurDir => (),
+			Component::ParentDir => {
+				if normal_depth > 0 {
+					result.pop();
+					normal_depth -= 1;
+				} else if !result.has_root() {
+					result.push("..");
+				}
+			}
+			Component::Normal(name) => {
+				result.push(name);
+				normal_depth += 1;
+			}
+		}
+	}
+
+	if result.as_os_str().is_empty() {
+		result.push(".");
+	}
+
+	result
+}
+
+fn path_normalize(path: &Path) -> PathBuf {
+	normalize(path)
+}
+
+fn path_relative(path: &Path, base: &Path) -> GResult<PathBuf> {
+	let path = normalize(path);
+	let base = normalize(base);
+
+	if path.is_absolute() != base.is_absolute() {
+		bail!("cannot make {} relative to {}: one path is absolute and the other is relative",
+		      path.display(), base.display())
+	}
+
+	let mut path_components = path.components().filter(|c| *c != Component::CurDir).peekable();
+	let mut base_components = base.components().filter(|c| *c != Component::CurDir).peekable();
+
+	//skip the common prefix
+	while let (Some(p), Some(b)) = (path_components.peek(), base_components.peek()) {
+		if p != b {
+			break
+		}
+
+		path_components.next();
+		base_components.next();
+	}
+
+	let mut result = PathBuf::new();
+	for component in base_components {
+		match component {
+			Component::Normal(_) => result.push(".."),
+			_ => bail!("cannot make {} relative to {}", path.display(), base.display())
+		}
+	}
+
+	for component in path_components {
+		result.push(component.as_os_str());
+	}
+
+	if result.as_os_str().is_empty() {
+		result.push(".");
+	}
+
+	Ok(result)
+}
@@ -0,0 +1,50 @@
+use glsp::{bail, GResult, PArr, PTab, rfn, Root, ToVal, Val};
+
+pub fn init(_sandboxed: bool) -> GResult<()> {
+	glsp::bind_rfn("parr", rfn!(parr))?;
+	glsp::bind_rfn("ptab", rfn!(ptab))?;
+	glsp::bind_rfn("pconj", rfn!(pconj))?;
+	glsp::bind_rfn("ppop", rfn!(ppop))?;
+	glsp::bind_rfn("passoc", rfn!(passoc))?;
+	glsp::bind_rfn("pdissoc", rfn!(pdissoc))?;
+
+	Ok(())
+}
+
+fn parr(args: &[Val]) -> GResult<Root<PArr>> {
+	glsp::parr_from_iter(args)
+}
+
+fn ptab(entries: &[(Val, Val)]) -> GResult<Root<PTab>> {
+	glsp::ptab_from_iter(entries.iter().cloned())
+}
+
+fn pconj(parr: Root<PArr>, vals: &[Val]) -> GResult<Root<PArr>> {
+	let mut result = parr;
+	for val in vals {
+		result = result.conj(val)?;
+	}
+
+	Ok(result)
+}
+
+fn ppop(parr: &PArr) -> GResult<Root<PArr>> {
+	parr.pop()
+}
+
+fn passoc(coll: Val, key: Val, value: Val) -> GResult<Val> {
+	match coll {
+		Val::PArr(parr) => {
+			match key {
+				Val::Int(i) => parr.assoc(i as isize, value)?.to_val(),
+				key => bail!("attempted to index a parr with {}", key.a_type_name())
+			}
+		}
+		Val::PTab(ptab) => ptab.assoc(key, value)?.to_val(),
+		coll => bail!("expected a parr or ptab, received {}", coll.a_type_name())
+	}
+}
+
+fn pdissoc(ptab: &PTab, key: Val) -> GResult<Root<PTab>> {
+	ptab.dissoc(key)
+}
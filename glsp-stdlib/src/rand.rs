@@ -0,0 +1,321 @@
+use glsp::{
+	Arr, bail, DequeAccess, DequeOps, ensure, Flo, FromVal, GResult, Lib, Num, rdata, rfn, Root, Val
+};
+use std::collections::hash_map::{DefaultHasher};
+use std::f64::consts::LN_2;
+use std::hash::{Hash, Hasher};
+use std::mem::{size_of};
+use super::Std;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{SystemTime};
+
+pub fn init(_sandboxed: bool) -> GResult<()> {
+	glsp::bind_rfn("rng", rfn!(rng))?;
+	glsp::bind_rfn("rand-int", rfn!(rand_int))?;
+	glsp::bind_rfn("rand-flo", rfn!(rand_flo))?;
+	glsp::bind_rfn("rand-pick", rfn!(rand_pick))?;
+	glsp::bind_rfn("shuffle!", rfn!(shuffle))?;
+	glsp::bind_rfn("rand-normal", rfn!(rand_normal))?;
+	glsp::bind_rfn("rng-state", rfn!(rng_state))?;
+	glsp::bind_rfn("rng-restore!", rfn!(rng_restore))?;
+
+	Ok(())
+}
+
+//xoshiro128** (https://prng.di.unimi.it/), seeded using splitmix64. we only use integer
+//arithmetic and basic floating-point operations (which are exactly specified by ieee 754), so
+//the output for a given seed is identical on every platform.
+
+rdata! {
+	#[derive(Clone)]
+	pub(crate) struct Rng {
+		s: [u32; 4]
+	}
+}
+
+impl Rng {
+	pub(crate) fn seeded() -> Rng {
+		//generate our initial seed based on the current time
+		let mut hasher = DefaultHasher::default();
+
+		#[cfg(target_arch = "wasm32")] {
+			42u8.hash(&mut hasher);
+		}
+
+		#[cfg(not(target_arch = "wasm32"))] {
+			let time = SystemTime::now();
+			time.hash(&mut hasher);
+		}
+
+		Rng::from_seed(hasher.finish())
+	}
+
+	pub(crate) fn from_seed(seed: u64) -> Rng {
+		let mut sm = seed;
+		let mut splitmix64 = || {
+			sm = sm.wrapping_add(0x9e3779b97f4a7c15);
+			let mut z = sm;
+			z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+			z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+			z ^ (z >> 31)
+		};
+
+		let a = splitmix64();
+		let b = splitmix64();
+
+		//splitmix64 can't produce an all-zero state from two consecutive outputs, but we don't
+		//want to rely on that
+		let mut s = [a as u32, (a >> 32) as u32, b as u32, (b >> 32) as u32];
+		if s == [0; 4] {
+			s[0] = 1;
+		}
+
+		Rng { s }
+	}
+
+	pub(crate) fn gen_u32(&mut self) -> u32 {
+		let s = &mut self.s;
+		let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+		let t = s[1] << 9;
+
+		s[2] ^= s[0];
+		s[3] ^= s[1];
+		s[1] ^= s[2];
+		s[0] ^= s[3];
+
+		s[2] ^= t;
+		s[3] = s[3].rotate_left(11);
+
+		result
+	}
+
+	//a uniformly-distributed f32 in the range [0, 1). 2^24 is the largest power of two for which
+	//every integer below it is exactly representable as an f32.
+	pub(crate) fn gen_f32(&mut self) -> f32 {
+		((self.gen_u32() >> 8) as f32) / 16777216.0_f32
+	}
+
+	//a uniformly-distributed f64 in the range [0, 1), with 53 bits of precision
+	fn gen_f64(&mut self) -> f64 {
+		let bits = ((self.gen_u32() as u64) << 21) | (self.gen_u32() as u64 >> 11);
+		(bits as f64) / ((1_u64 << 53) as f64)
+	}
+
+	//a uniformly-distributed f64 in the range [-1, 1), with 53 bits of precision
+	fn gen_signed_f64(&mut self) -> f64 {
+		self.gen_f64() * 2.0 - 1.0
+	}
+
+	//a uniformly-distributed Flo in the range [0, 1), with as much precision as the Flo can
+	//hold. when the "f64-flos" feature is enabled, this consumes two u32s rather than one.
+	pub(crate) fn gen_flo(&mut self) -> Flo {
+		if size_of::<Flo>() == size_of::<f64>() {
+			self.gen_f64() as Flo
+		} else {
+			self.gen_f32() as Flo
+		}
+	}
+
+	//an unbiased integer in the range [0, range), using lemire's multiply-and-reject method.
+	//when `range` is 0, the full u32 range is sampled.
+	pub(crate) fn gen_below(&mut self, range: u32) -> u32 {
+		if range == 0 {
+			return self.gen_u32()
+		}
+
+		let mut m = (self.gen_u32() as u64) * (range as u64);
+		if (m as u32) < range {
+			let threshold = range.wrapping_neg() % range;
+			while (m as u32) < threshold {
+				m = (self.gen_u32() as u64) * (range as u64);
+			}
+		}
+
+		(m >> 32) as u32
+	}
+
+	pub(crate) fn gen_i32_in(&mut self, lo: i32, hi: i32) -> i32 {
+		assert!(lo < hi);
+		let range = (hi as i64 - lo as i64) as u32;
+		(lo as i64 + self.gen_below(range) as i64) as i32
+	}
+
+	//marsaglia's polar method. we avoid the platform's libm, because its results for ln() can
+	//differ between platforms.
+	fn gen_normal(&mut self) -> f64 {
+		loop {
+			let u = self.gen_signed_f64();
+			let v = self.gen_signed_f64();
+			let s = u*u + v*v;
+
+			if s > 0.0 && s < 1.0 {
+				return u * (-2.0 * portable_ln(s) / s).sqrt()
+			}
+		}
+	}
+}
+
+//the natural logarithm of an f64 in the range (0, 1), computed using only basic arithmetic.
+//we scale x into the range [0.5, 1), then use the series ln(m) = 2*atanh((m-1)/(m+1)).
+fn portable_ln(mut x: f64) -> f64 {
+	debug_assert!(x > 0.0 && x < 1.0);
+
+	let mut exponent = 0_i32;
+	while x < 0.5 {
+		x *= 2.0;
+		exponent -= 1;
+	}
+
+	let z = (x - 1.0) / (x + 1.0);
+	let z2 = z * z;
+
+	//|z| <= 1/3, so each term shrinks by a factor of at least 9. 20 terms is far more precision
+	//than an f64 can hold.
+	let mut term = z;
+	let mut sum = 0.0;
+	for i in 0 .. 20 {
+		sum += term / ((2 * i + 1) as f64);
+		term *= z2;
+	}
+
+	2.0 * sum + (exponent as f64) * LN_2
+}
+
+pub(crate) fn rand_weighted_index(rng: &mut Rng, weights: &[Flo]) -> GResult<usize> {
+	ensure!(!weights.is_empty(), "expected at least one weight");
+
+	let mut total_weight: Flo = 0.0;
+	for &weight in weights {
+		ensure!(weight >= 0.0, "each weight must be a non-negative int or a non-negative flo");
+		total_weight += weight;
+	}
+
+	ensure!(total_weight != 0.0, "the total weight must not be 0");
+
+	let selection = (rng.gen_f32() as Flo) * total_weight;
+
+	let mut accum = 0.0;
+	for (i, &weight) in weights.iter().enumerate() {
+		accum += weight;
+		if selection < accum && weight > 0.0 {
+			return Ok(i)
+		}
+	}
+
+	//this should only be reachable due to rounding errors. choose the last non-zero weight.
+	Ok(weights.iter().rposition(|&weight| weight > 0.0).unwrap())
+}
+
+//a seed for (rng). an int only covers 2^32 of the possible seeds, so we also accept an arr of two
+//ints, (hi lo), which are combined into a 64-bit seed. (rng n) is equivalent to (rng (arr 0 n)).
+struct Seed(u64);
+
+impl FromVal for Seed {
+	fn from_val(val: &Val) -> GResult<Seed> {
+		match *val {
+			Val::Int(i) => Ok(Seed(i as u32 as u64)),
+			Val::Arr(ref arr) if arr.len() == 2 => {
+				let hi: i32 = arr.get(0)?;
+				let lo: i32 = arr.get(1)?;
+				Ok(Seed(((hi as u32 as u64) << 32) | (lo as u32 as u64)))
+			}
+			ref val => bail!("expected an int or an arr of two ints, received {}", 
+			                 val.a_type_name())
+		}
+	}
+}
+
+fn rng(seed: Option<Seed>) -> Rng {
+	match seed {
+		Some(Seed(seed)) => Rng::from_seed(seed),
+		None => {
+			let mut std = Std::borrow_mut();
+			let seed = ((std.rng.gen_u32() as u64) << 32) | (std.rng.gen_u32() as u64);
+			Rng::from_seed(seed)
+		}
+	}
+}
+
+fn rand_int(rng: &mut Rng, lo: i32, hi: i32) -> GResult<i32> {
+	ensure!(lo < hi, "the lower bound {} is not less than the upper bound {}", lo, hi);
+	Ok(rng.gen_i32_in(lo, hi))
+}
+
+fn rand_flo(rng: &mut Rng, lo: Option<Num>, hi: Option<Num>) -> GResult<Flo> {
+	match (lo, hi) {
+		(Some(lo), Some(hi)) => {
+			let (lo, hi) = (lo.into_flo(), hi.into_flo());
+			Ok(lo + rng.gen_flo() * (hi - lo))
+		}
+		(None, None) => Ok(rng.gen_flo()),
+		(Some(_), None) => bail!("expected either zero or two bounds, but received one"),
+		(None, Some(_)) => unreachable!()
+	}
+}
+
+fn rand_pick(rng: &mut Rng, arr: Root<Arr>) -> GResult<Val> {
+	ensure!(arr.len() > 0, "cannot pick an element from an empty arr");
+	ensure!(arr.len() <= i32::MAX as usize, "arr is too large");
+
+	let i = rng.gen_below(arr.len() as u32) as usize;
+	arr.get(i)
+}
+
+//the weighted form of (rand-weighted) lives here; the variadic form which uses the global rng
+//is in num.rs
+pub(crate) fn rand_weighted_with_rng(
+	rng: &mut Rng,
+	choices: &Root<Arr>,
+	weights: &Root<Arr>
+) -> GResult<Val> {
+	ensure!(choices.len() == weights.len(), "received {} choices but {} weights",
+	        choices.len(), weights.len());
+
+	let mut weights_vec = Vec::with_capacity(weights.len());
+	for weight in weights.iter() {
+		match weight {
+			Val::Int(i) => weights_vec.push(i as Flo),
+			Val::Flo(f) => weights_vec.push(f),
+			_ => bail!("each weight must be a non-negative int or a non-negative flo")
+		}
+	}
+
+	let i = rand_weighted_index(rng, &weights_vec)?;
+	choices.get(i)
+}
+
+fn shuffle(rng: &mut Rng, arr: Root<Arr>) -> GResult<Root<Arr>> {
+	ensure!(arr.len() <= i32::MAX as usize, "arr is too large");
+
+	//fisher-yates
+	for i in (1 .. arr.len()).rev() {
+		let j = rng.gen_below((i + 1) as u32) as usize;
+		arr.swap(i, j)?;
+	}
+
+	Ok(arr)
+}
+
+fn rand_normal(rng: &mut Rng, mean: Option<Num>, sd: Option<Num>) -> GResult<Flo> {
+	let mean = mean.map(Num::into_flo).unwrap_or(0.0) as f64;
+	let sd = sd.map(Num::into_flo).unwrap_or(1.0) as f64;
+	ensure!(sd >= 0.0, "the standard deviation must be non-negative, but received {}", sd);
+
+	Ok((mean + rng.gen_normal() * sd) as Flo)
+}
+
+//the state is exposed as an arr of four ints, so that it can be stored in a save file using the
+//same mechanisms as any other glsp data
+fn rng_state(rng: &Rng) -> [i32; 4] {
+	let s = rng.s;
+	[s[0] as i32, s[1] as i32, s[2] as i32, s[3] as i32]
+}
+
+fn rng_restore(rng: &mut Rng, state: [i32; 4]) -> GResult<()> {
+	let s = [state[0] as u32, state[1] as u32, state[2] as u32, state[3] as u32];
+	ensure!(s != [0; 4], "an rng's state must not be all zeroes");
+
+	rng.s = s;
+	Ok(())
+}
@@ -0,0 +1,402 @@
+use glsp::{
+	Arr, bail, Callable, DequeAccess, DequeOps, GResult, Lib, Num, prn, rfn, Root, Str, Sym, ToVal,
+	Val
+};
+use glsp_proc_macros::{backquote};
+use std::fmt::{self, Display, Formatter};
+use std::time::{Duration};
+use super::{Std};
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Instant};
+
+pub fn init(_sandboxed: bool) -> GResult<()> {
+	glsp::bind_rfn_macro("deftest", rfn!(deftest))?;
+	glsp::bind_rfn("register-test", rfn!(register_test))?;
+	glsp::bind_rfn("run-tests", rfn!(run_tests_rfn))?;
+
+	glsp::bind_rfn("assert-eq", rfn!(assert_eq))?;
+	glsp::bind_rfn("assert-approx", rfn!(assert_approx))?;
+	glsp::bind_rfn_macro("assert-err", rfn!(assert_err))?;
+	glsp::bind_rfn("assert-err-call", rfn!(assert_err_call))?;
+
+	Ok(())
+}
+
+//-------------------------------------------------------------------------------------------------
+// Tests
+//-------------------------------------------------------------------------------------------------
+
+#[derive(Clone)]
+pub(crate) struct Test {
+	name: Sym,
+	callee: Callable,
+	location: Option<String>
+}
+
+/**
+The result of running a set of tests using [`glsp::run_tests`](fn.run_tests.html).
+
+When converted to a [`Val`](enum.Val.html), a `TestReport` becomes a table with three fields:
+`passed` and `failed`, which are integers, and `results`, which is an array of tables with the
+fields `name`, `passed?`, `message`, `location` and `duration`.
+
+Its `Display` implementation produces a human-readable summary, which is printed by
+[`(run-tests)`](https://gamelisp.rs/std/run-tests).
+*/
+
+pub struct TestReport {
+	results: Vec<TestResult>
+}
+
+/**
+The outcome of a single test, belonging to a [`TestReport`](struct.TestReport.html).
+*/
+
+pub struct TestResult {
+	name: String,
+	message: Option<String>,
+	location: Option<String>,
+	duration: Duration
+}
+
+impl TestReport {
+	/** Returns the result of each test which was run, in the order they were registered. */
+	pub fn results(&self) -> &[TestResult] {
+		&self.results
+	}
+
+	/** Returns the number of tests which passed. */
+	pub fn passed(&self) -> usize {
+		self.results.iter().filter(|result| result.passed()).count()
+	}
+
+	/** Returns the number of tests which failed. */
+	pub fn failed(&self) -> usize {
+		self.results.len() - self.passed()
+	}
+
+	/** Returns `true` if no test failed. */
+	pub fn all_passed(&self) -> bool {
+		self.failed() == 0
+	}
+
+	/**
+	Formats the report as a JUnit XML document.
+
+	Most continuous integration services can display test results in this format.
+	*/
+	pub fn to_junit_xml(&self) -> String {
+		let total_secs: f64 = self.results.iter().map(|result| {
+			result.duration.as_secs_f64()
+		}).sum();
+
+		let mut xml = String::new();
+		xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+		xml.push_str(&format!(
+			"<testsuite name=\"glsp\" tests=\"{}\" failures=\"{}\" time=\"{:.6}\">\n",
+			self.results.len(), self.failed(), total_secs
+		));
+
+		for result in &self.results {
+			let classname = match result.location {
+				Some(ref location) => location.rsplitn(2, ':').last().unwrap(),
+				None => "glsp"
+			};
+
+			xml.push_str(&format!(
+				"\t<testcase name=\"{}\" classname=\"{}\" time=\"{:.6}\"",
+				escape_xml(&result.name), escape_xml(classname),
+				result.duration.as_secs_f64()
+			));
+
+			match result.message {
+				None => xml.push_str("/>\n"),
+				Some(ref message) => {
+					xml.push_str(&format!(
+						">\n\t\t<failure message=\"{}\">{}</failure>\n\t</testcase>\n",
+						escape_xml(message.lines().next().unwrap_or("")), escape_xml(message)
+					));
+				}
+			}
+		}
+
+		xml.push_str("</testsuite>\n");
+		xml
+	}
+}
+
+impl TestResult {
+	/** Returns the test's name. */
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	/** Returns `true` if the test finished without an error. */
+	pub fn passed(&self) -> bool {
+		self.message.is_none()
+	}
+
+	/** If the test failed, returns its error message. */
+	pub fn message(&self) -> Option<&str> {
+		self.message.as_ref().map(|s| &**s)
+	}
+
+	/** Returns the file and line number of the test's `deftest` form, if known. */
+	pub fn location(&self) -> Option<&str> {
+		self.location.as_ref().map(|s| &**s)
+	}
+
+	/** Returns the time taken to run the test. */
+	pub fn duration(&self) -> Duration {
+		self.duration
+	}
+}
+
+impl Display for TestReport {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		for result in &self.results {
+			let outcome = if result.passed() { "ok" } else { "FAILED" };
+			writeln!(f, "test {} ... {}", result.name, outcome)?;
+		}
+
+		if !self.all_passed() {
+			writeln!(f, "\nfailures:")?;
+			for result in self.results.iter().filter(|result| !result.passed()) {
+				match result.location {
+					Some(ref location) => writeln!(f, "\n{} ({}):", result.name, location)?,
+					None => writeln!(f, "\n{}:", result.name)?
+				}
+
+				for line in result.message.as_ref().unwrap().lines() {
+					writeln!(f, "    {}", line)?;
+				}
+			}
+		}
+
+		let status = if self.all_passed() { "ok" } else { "FAILED" };
+		write!(f, "\ntest result: {}. {} passed; {} failed", status, self.passed(), 
+		       self.failed())
+	}
+}
+
+impl ToVal for TestReport {
+	fn to_val(&self) -> GResult<Val> {
+		let results = glsp::arr();
+		for result in &self.results {
+			let tab = glsp::tab();
+			tab.set(glsp::sym("name")?, glsp::sym(&result.name)?)?;
+			tab.set(glsp::sym("passed?")?, result.passed())?;
+			tab.set(glsp::sym("message")?, result.message())?;
+			tab.set(glsp::sym("location")?, result.location())?;
+			tab.set(glsp::sym("duration")?, result.duration.as_secs_f32())?;
+			results.push(tab)?;
+		}
+
+		let tab = glsp::tab();
+		tab.set(glsp::sym("passed")?, self.passed())?;
+		tab.set(glsp::sym("failed")?, self.failed())?;
+		tab.set(glsp::sym("results")?, results)?;
+
+		Ok(Val::Tab(tab))
+	}
+}
+
+fn escape_xml(st: &str) -> String {
+	let mut escaped = String::with_capacity(st.len());
+	for ch in st.chars() {
+		match ch {
+			'&' => escaped.push_str("&amp;"),
+			'<' => escaped.push_str("&lt;"),
+			'>' => escaped.push_str("&gt;"),
+			'"' => escaped.push_str("&quot;"),
+			'\'' => escaped.push_str("&apos;"),
+			ch => escaped.push(ch)
+		}
+	}
+
+	escaped
+}
+
+//a minimal glob: `*` matches any sequence of characters, and everything else matches itself
+fn glob_matches(pattern: &str, name: &str) -> bool {
+	let pattern: Vec<char> = pattern.chars().collect();
+	let name: Vec<char> = name.chars().collect();
+
+	let (mut p, mut n) = (0, 0);
+	let mut backtrack: Option<(usize, usize)> = None;
+
+	while n < name.len() {
+		if p < pattern.len() && pattern[p] == '*' {
+			backtrack = Some((p, n));
+			p += 1;
+		} else if p < pattern.len() && pattern[p] == name[n] {
+			p += 1;
+			n += 1;
+		} else if let Some((star_p, star_n)) = backtrack {
+			p = star_p + 1;
+			n = star_n + 1;
+			backtrack = Some((star_p, star_n + 1));
+		} else {
+			return false
+		}
+	}
+
+	pattern[p..].iter().all(|&ch| ch == '*')
+}
+
+/**
+Equivalent to [`(run-tests filter)`](https://gamelisp.rs/std/run-tests), except that the report
+isn't printed.
+
+Runs each test which was registered using [`(deftest)`](https://gamelisp.rs/std/deftest), in
+the order that they were registered. When `filter` is `Some`, only tests whose names match the
+pattern are run. The pattern may contain `*` wildcards, such as `"combat-*"`.
+
+A test which fails doesn't prevent the remaining tests from running. The exception is a call to
+[`(exit)`](https://gamelisp.rs/std/exit), which is propagated immediately.
+
+	let report = glsp::run_tests(None)?;
+	fs::write("test-results.xml", report.to_junit_xml())?;
+	assert!(report.all_passed(), "{}", report);
+*/
+pub fn run_tests(filter: Option<&str>) -> GResult<TestReport> {
+	//we mustn't hold a borrow of Std while the tests are running
+	let tests: Vec<Test> = Std::borrow().tests.iter().filter(|test| {
+		match filter {
+			Some(filter) => glob_matches(filter, &test.name.name()),
+			None => true
+		}
+	}).cloned().collect();
+
+	let mut results = Vec::with_capacity(tests.len());
+	for test in &tests {
+		#[cfg(not(target_arch = "wasm32"))]
+		let start = Instant::now();
+
+		let result = glsp::try_call(false, &test.callee, &());
+
+		#[cfg(not(target_arch = "wasm32"))]
+		let duration = start.elapsed();
+		#[cfg(target_arch = "wasm32")]
+		let duration = Duration::default();
+
+		let message = match result {
+			Ok(_) => None,
+			Err(err) => {
+				if err.is_macro_no_op() || err.exit_code().is_some() {
+					return Err(err)
+				}
+
+				Some(err.val().to_string())
+			}
+		};
+
+		results.push(TestResult {
+			name: test.name.name().to_string(),
+			message,
+			location: test.location.clone(),
+			duration
+		});
+	}
+
+	Ok(TestReport { results })
+}
+
+fn deftest(name: Sym, body: &[Val]) -> Root<Arr> {
+	backquote!("(register-test '~name (fn &name ~name () ~..body) (file-location))")
+}
+
+fn register_test(name: Sym, callee: Callable, location: Option<Root<Str>>) {
+	let test = Test {
+		name,
+		callee,
+		location: location.map(|location| location.to_string())
+	};
+
+	//redefining a test, e.g. when its file is reloaded, replaces it in-place
+	let mut std = Std::borrow_mut();
+	match std.tests.iter_mut().find(|existing| existing.name == name) {
+		Some(existing) => *existing = test,
+		None => std.tests.push(test)
+	}
+}
+
+fn run_tests_rfn(filter: Option<Root<Str>>) -> GResult<TestReport> {
+	let filter = filter.map(|filter| filter.to_string());
+	let report = run_tests(filter.as_ref().map(|s| &**s))?;
+	prn!("{}", report);
+
+	Ok(report)
+}
+
+//-------------------------------------------------------------------------------------------------
+// Assertions
+//-------------------------------------------------------------------------------------------------
+
+fn assertion_prefix() -> String {
+	match glsp::file_location() {
+		Some(location) => format!("assertion failed at {}", location),
+		None => "assertion failed".to_string()
+	}
+}
+
+fn assert_eq(left: Val, right: Val) -> GResult<()> {
+	if !left.try_eq(&right)? {
+		bail!("{}: the values are not eq?\n  left: {:#}\n  right: {:#}",
+		      assertion_prefix(), left, right)
+	}
+
+	Ok(())
+}
+
+fn assert_approx(left: Num, right: Num, epsilon: Num) -> GResult<()> {
+	let (left, right, epsilon) = (left.into_flo(), right.into_flo(), epsilon.into_flo());
+	if !((left - right).abs() <= epsilon) {
+		bail!("{}: the values differ by more than {:#}\n  left: {:#}\n  right: {:#}",
+		      assertion_prefix(), epsilon, left, right)
+	}
+
+	Ok(())
+}
+
+fn assert_err(kind: Val, body: &[Val]) -> Root<Arr> {
+	backquote!("(assert-err-call ~kind (fn () ~..body))")
+}
+
+fn assert_err_call(kind: Val, callee: Callable) -> GResult<()> {
+	let err = match glsp::try_call(false, &callee, &()) {
+		Ok(result) => {
+			bail!("{}: expected an error matching {:#}, but the body returned {:#}",
+			      assertion_prefix(), kind, result)
+		}
+		Err(err) => {
+			if err.is_macro_no_op() || err.exit_code().is_some() {
+				return Err(err)
+			}
+
+			err
+		}
+	};
+
+	let payload = err.val();
+	let matched = match kind {
+		Val::Nil => true,
+		Val::Str(ref st) => payload.to_string().contains(&st.to_string()),
+		ref kind => {
+			match payload {
+				Val::Arr(ref arr) if arr.len() > 0 => {
+					kind.try_eq(&payload)? || kind.try_eq(&arr.get::<Val>(0)?)?
+				}
+				ref payload => kind.try_eq(payload)?
+			}
+		}
+	};
+
+	if !matched {
+		bail!("{}: expected an error matching {:#}, but received {:#}",
+		      assertion_prefix(), kind, payload)
+	}
+
+	Ok(())
+}
@@ -0,0 +1,126 @@
+use glsp::{bail, Flo, GResult, Lib, Num, rdata, rfn, Root, stock_syms::*, tab, Tab};
+use super::{env::check_enabled, Std};
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Instant, UNIX_EPOCH};
+
+pub fn init(sandboxed: bool) -> GResult<()> {
+	glsp::bind_rfn("time", rfn!(time))?;
+	glsp::bind_rfn("unix-time", rfn!(unix_time))?;
+	glsp::bind_rfn("stopwatch", rfn!(stopwatch))?;
+	glsp::bind_rfn("elapsed", rfn!(elapsed))?;
+	glsp::bind_rfn("reset!", rfn!(reset))?;
+
+	if !sandboxed {
+		glsp::bind_rfn("sleep", rfn!(sleep))?;
+		glsp::bind_rfn("datetime", rfn!(datetime))?;
+	}
+
+	Ok(())
+}
+
+//the current reading of the clock used by (time) and (stopwatch), in seconds. when the host
+//hasn't called glsp::set_clock, we measure real time from the moment the Runtime was created.
+//wasm32 has no default clock, because std::time::Instant panics on that platform.
+pub(crate) fn now() -> GResult<f64> {
+	//the host's clock is free to call into glsp, so we mustn't hold a borrow of Std
+	let clock = Std::borrow().clock;
+	if let Some(clock) = clock {
+		return Ok(clock())
+	}
+
+	#[cfg(not(target_arch = "wasm32"))] {
+		let start_time = Std::borrow().start_time;
+		Ok(Instant::now().duration_since(start_time).as_secs_f64())
+	}
+
+	#[cfg(target_arch = "wasm32")] {
+		bail!("no clock is available: the host must call glsp::set_clock on this platform")
+	}
+}
+
+fn time() -> GResult<Flo> {
+	Ok(now()? as Flo)
+}
+
+fn unix_time() -> GResult<String> {
+	Ok(unix_secs()?.to_string())
+}
+
+//std::time::SystemTime panics on wasm32
+fn unix_secs() -> GResult<i64> {
+	#[cfg(not(target_arch = "wasm32"))] {
+		match UNIX_EPOCH.elapsed() {
+			Ok(duration) => Ok(duration.as_secs() as i64),
+			Err(_) => bail!("the system clock is set to a time before 1970")
+		}
+	}
+
+	#[cfg(target_arch = "wasm32")] {
+		bail!("the system clock is not available on the wasm32 target")
+	}
+}
+
+//blocking the current thread would freeze a game's main loop, so (sleep) is reserved for tools
+//and scripts which the host has trusted with environment access
+fn sleep(secs: Num) -> GResult<()> {
+	check_enabled("sleep")?;
+	super::sleep(secs.into_f32())
+}
+
+rdata! {
+	pub(crate) struct Stopwatch {
+		start: f64
+	}
+}
+
+fn stopwatch() -> GResult<Stopwatch> {
+	Ok(Stopwatch { start: now()? })
+}
+
+fn elapsed(stopwatch: &Stopwatch) -> GResult<Flo> {
+	Ok((now()? - stopwatch.start) as Flo)
+}
+
+fn reset(stopwatch: &mut Stopwatch) -> GResult<Flo> {
+	let now = now()?;
+	let elapsed = now - stopwatch.start;
+	stopwatch.start = now;
+
+	Ok(elapsed as Flo)
+}
+
+fn datetime() -> GResult<Root<Tab>> {
+	check_enabled("datetime")?;
+
+	let secs = unix_secs()?;
+
+	let days = secs.div_euclid(86400);
+	let secs_of_day = secs.rem_euclid(86400);
+
+	//howard hinnant's civil_from_days algorithm, which converts a day count into a date in the
+	//proleptic gregorian calendar (http://howardhinnant.github.io/date_algorithms.html)
+	let z = days + 719468;
+	let era = z.div_euclid(146097);
+	let doe = z.rem_euclid(146097);
+	let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+	let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+	let mp = (5 * doy + 2) / 153;
+	let day = doy - (153 * mp + 2) / 5 + 1;
+	let month = if mp < 10 { mp + 3 } else { mp - 9 };
+	let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+	//1970-01-01 was a thursday. we number the days of the week from monday (1) to sunday (7),
+	//following iso 8601.
+	let weekday = (days + 3).rem_euclid(7) + 1;
+
+	Ok(tab! {
+		(YEAR_SYM, year as i32),
+		(MONTH_SYM, month as i32),
+		(DAY_SYM, day as i32),
+		(HOUR_SYM, (secs_of_day / 3600) as i32),
+		(MINUTE_SYM, ((secs_of_day / 60) % 60) as i32),
+		(SECOND_SYM, (secs_of_day % 60) as i32),
+		(WEEKDAY_SYM, weekday as i32),
+	})
+}
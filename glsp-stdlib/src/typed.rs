@@ -0,0 +1,318 @@
+use glsp::{
+	Arr, bail, Callable, DequeOps, ensure, FromVal, GError, GResult, MessageArgs, MessageId, rdata,
+	rfn, Root, RRoot, RStore, ToVal, Val
+};
+
+pub fn init(_sandboxed: bool) -> GResult<()> {
+	glsp::bind_rfn("farr", rfn!(new::<FArr>))?;
+	glsp::bind_rfn("farr-of-len", rfn!(of_len::<FArr>))?;
+	glsp::bind_rfn("farr?", rfn!(is::<FArr>))?;
+	glsp::bind_rfn("farr-fill!", rfn!(fill::<FArr>))?;
+	glsp::bind_rfn("farr-copy!", rfn!(copy::<FArr>))?;
+	glsp::bind_rfn("farr-map!", rfn!(map::<FArr>))?;
+	glsp::bind_rfn("farr->arr", rfn!(to_arr::<FArr>))?;
+
+	glsp::bind_rfn("iarr", rfn!(new::<IArr>))?;
+	glsp::bind_rfn("iarr-of-len", rfn!(of_len::<IArr>))?;
+	glsp::bind_rfn("iarr?", rfn!(is::<IArr>))?;
+	glsp::bind_rfn("iarr-fill!", rfn!(fill::<IArr>))?;
+	glsp::bind_rfn("iarr-copy!", rfn!(copy::<IArr>))?;
+	glsp::bind_rfn("iarr-map!", rfn!(map::<IArr>))?;
+	glsp::bind_rfn("iarr->arr", rfn!(to_arr::<IArr>))?;
+
+	Ok(())
+}
+
+//-------------------------------------------------------------------------------------------------
+// FArr, IArr
+//-------------------------------------------------------------------------------------------------
+
+/*
+a typed array is an rdata which owns a Vec of primitive numbers, so that Rust code can borrow
+its contents as a slice without converting each element. scripts index it using the op-access
+and op-set-access meths. typed arrays have a fixed length: this keeps farr-map! simple (the
+callback can't resize the array out from under us), and it matches the buffers which they're
+usually handed to.
+*/
+
+trait Elem: Copy + Default + ToVal + 'static {
+	fn from_elem_val(val: &Val) -> GResult<Self>;
+}
+
+impl Elem for f32 {
+	fn from_elem_val(val: &Val) -> GResult<f32> {
+		match *val {
+			Val::Flo(f) => Ok(f as f32),
+			Val::Int(i) => Ok(i as f32),
+			ref val => bail!("expected a number, received {}", val.a_type_name())
+		}
+	}
+}
+
+impl Elem for i32 {
+	fn from_elem_val(val: &Val) -> GResult<i32> {
+		match *val {
+			Val::Int(i) => Ok(i),
+			ref val => bail!("expected an int, received {}", val.a_type_name())
+		}
+	}
+}
+
+trait TypedArr: RStore + From<Vec<<Self as TypedArr>::Elem>> {
+	type Elem: Elem;
+	const NAME: &'static str;
+
+	fn as_slice(&self) -> &[Self::Elem];
+	fn as_mut_slice(&mut self) -> &mut [Self::Elem];
+}
+
+rdata! {
+	/**
+	The `farr` type: a fixed-length array of `f32`.
+
+	Unlike an [`Arr`](struct.Arr.html), which stores each element as a [`Val`](enum.Val.html),
+	an `FArr` stores its elements contiguously. An `rfn` which receives `&FArr` or `&mut FArr`
+	can pass its contents straight to a renderer or a mixer, without any per-element
+	conversion, under the usual [`RData`](struct.RData.html) borrowing rules.
+
+		fn upload_vertices(vertices: &FArr, mesh: &mut Mesh) {
+			mesh.set_vertices(vertices.as_slice());
+		}
+
+	Scripts construct an `FArr` using [`(farr)`](https://gamelisp.rs/std/farr) or
+	[`(farr-of-len)`](https://gamelisp.rs/std/farr-of-len), and access its elements by
+	indexing it with an integer.
+
+	When an `FArr` is received by value, as an argument to an `rfn`, it's copied from either an
+	`farr` or an `arr` of numbers.
+	*/
+	#[derive(Clone, Debug, Default, PartialEq)]
+	pub struct FArr(Vec<f32>);
+
+	meths {
+		"len": FArr::len,
+		"op-access": FArr::op_access,
+		"op-set-access": FArr::op_set_access,
+		"op-clone": FArr::clone,
+		"op-eq?": FArr::op_eq,
+	}
+}
+
+rdata! {
+	/**
+	The `iarr` type: a fixed-length array of `i32`.
+
+	An `IArr` stores its elements contiguously, like an [`FArr`](struct.FArr.html). Scripts
+	construct an `IArr` using [`(iarr)`](https://gamelisp.rs/std/iarr) or
+	[`(iarr-of-len)`](https://gamelisp.rs/std/iarr-of-len).
+
+	When an `IArr` is received by value, as an argument to an `rfn`, it's copied from either an
+	`iarr` or an `arr` of integers.
+	*/
+	#[derive(Clone, Debug, Default, PartialEq)]
+	pub struct IArr(Vec<i32>);
+
+	meths {
+		"len": IArr::len,
+		"op-access": IArr::op_access,
+		"op-set-access": IArr::op_set_access,
+		"op-clone": IArr::clone,
+		"op-eq?": IArr::op_eq,
+	}
+}
+
+macro_rules! typed_arr_impls {
+	($typed_arr:ident, $elem:ty, $name:literal) => (
+		impl TypedArr for $typed_arr {
+			type Elem = $elem;
+			const NAME: &'static str = $name;
+
+			fn as_slice(&self) -> &[$elem] {
+				&self.0
+			}
+
+			fn as_mut_slice(&mut self) -> &mut [$elem] {
+				&mut self.0
+			}
+		}
+
+		impl $typed_arr {
+			///Constructs an empty array.
+			pub fn new() -> $typed_arr {
+				$typed_arr(Vec::new())
+			}
+
+			///Constructs an array which contains `len` copies of `elem`.
+			pub fn from_elem(elem: $elem, len: usize) -> $typed_arr {
+				$typed_arr(vec![elem; len])
+			}
+
+			///Returns the number of elements in the array.
+			pub fn len(&self) -> usize {
+				self.0.len()
+			}
+
+			///Returns `true` if the array has no elements.
+			pub fn is_empty(&self) -> bool {
+				self.0.is_empty()
+			}
+
+			///Borrows the array's elements.
+			pub fn as_slice(&self) -> &[$elem] {
+				&self.0
+			}
+
+			///Mutably borrows the array's elements.
+			pub fn as_mut_slice(&mut self) -> &mut [$elem] {
+				&mut self.0
+			}
+
+			///Consumes the array, returning its storage.
+			pub fn into_vec(self) -> Vec<$elem> {
+				self.0
+			}
+
+			fn op_access(&self, i: i32) -> GResult<$elem> {
+				Ok(self.0[index(i, self.0.len(), $name)?])
+			}
+
+			fn op_set_access(&mut self, i: i32, val: Val) -> GResult<()> {
+				let i = index(i, self.0.len(), $name)?;
+				self.0[i] = <$elem as Elem>::from_elem_val(&val)?;
+				Ok(())
+			}
+
+			fn op_eq(&self, other: &$typed_arr) -> bool {
+				self.0 == other.0
+			}
+		}
+
+		impl From<Vec<$elem>> for $typed_arr {
+			fn from(vec: Vec<$elem>) -> $typed_arr {
+				$typed_arr(vec)
+			}
+		}
+
+		impl<'a> From<&'a [$elem]> for $typed_arr {
+			fn from(slice: &'a [$elem]) -> $typed_arr {
+				$typed_arr(slice.to_vec())
+			}
+		}
+
+		impl From<$typed_arr> for Vec<$elem> {
+			fn from(typed_arr: $typed_arr) -> Vec<$elem> {
+				typed_arr.0
+			}
+		}
+
+		impl FromVal for $typed_arr {
+			fn from_val(val: &Val) -> GResult<$typed_arr> {
+				match *val {
+					Val::RData(ref rdata) if rdata.is::<$typed_arr>() => {
+						Ok(rdata.try_borrow::<$typed_arr>()?.clone())
+					}
+					Val::Arr(ref arr) => {
+						let mut vec = Vec::with_capacity(arr.len());
+						for elem in arr.iter() {
+							vec.push(<$elem as Elem>::from_elem_val(&elem)?);
+						}
+
+						Ok($typed_arr(vec))
+					}
+					ref val => bail!("expected an {} or an arr, received {}", $name,
+					                 val.a_type_name())
+				}
+			}
+		}
+	);
+}
+
+typed_arr_impls!(FArr, f32, "farr");
+typed_arr_impls!(IArr, i32, "iarr");
+
+//negative indices count backwards from the end, as they do for arrs
+fn index(i: i32, len: usize, name: &str) -> GResult<usize> {
+	let adjusted = if i < 0 { i as isize + len as isize } else { i as isize };
+	if adjusted < 0 || (adjusted as usize) >= len {
+		let args = MessageArgs::new().with("type", name).with("len", len).with("index", i);
+		return Err(GError::from_message(MessageId::OutOfBounds, args))
+	}
+
+	Ok(adjusted as usize)
+}
+
+//-------------------------------------------------------------------------------------------------
+// rfns
+//-------------------------------------------------------------------------------------------------
+
+fn new<T: TypedArr>(elems: &[Val]) -> GResult<T> {
+	let mut vec = Vec::with_capacity(elems.len());
+	for elem in elems {
+		vec.push(T::Elem::from_elem_val(elem)?);
+	}
+
+	Ok(T::from(vec))
+}
+
+fn of_len<T: TypedArr>(len: usize, fill: Option<Val>) -> GResult<T> {
+	let fill = match fill {
+		Some(fill) => T::Elem::from_elem_val(&fill)?,
+		None => T::Elem::default()
+	};
+
+	Ok(T::from(vec![fill; len]))
+}
+
+fn is<T: TypedArr>(val: Val) -> bool {
+	match val {
+		Val::RData(rdata) => rdata.is::<T>(),
+		_ => false
+	}
+}
+
+fn fill<T: TypedArr>(dst: RRoot<T>, elem: Val) -> GResult<()> {
+	let elem = T::Elem::from_elem_val(&elem)?;
+	for dst_elem in dst.try_borrow_mut()?.as_mut_slice() {
+		*dst_elem = elem;
+	}
+
+	Ok(())
+}
+
+fn copy<T: TypedArr>(dst: RRoot<T>, src: RRoot<T>, offset: Option<usize>) -> GResult<()> {
+	let offset = offset.unwrap_or(0);
+	let dst_len = dst.try_borrow()?.as_slice().len();
+	let src_len = src.try_borrow()?.as_slice().len();
+
+	ensure!(offset <= dst_len && src_len <= dst_len - offset,
+	        "unable to copy {} elements into an {} of length {}, starting at index {}",
+	        src_len, T::NAME, dst_len, offset);
+
+	//copying an array onto itself, starting at index 0, is a no-op
+	if RRoot::ptr_eq(&dst, &src) {
+		return Ok(())
+	}
+
+	let src = src.try_borrow()?;
+	let mut dst = dst.try_borrow_mut()?;
+	dst.as_mut_slice()[offset .. offset + src_len].copy_from_slice(src.as_slice());
+
+	Ok(())
+}
+
+//the array isn't borrowed while the callback is running, so the callback is free to read or
+//write to the array
+fn map<T: TypedArr>(dst: RRoot<T>, f: Callable) -> GResult<()> {
+	let len = dst.try_borrow()?.as_slice().len();
+	for i in 0 .. len {
+		let elem = dst.try_borrow()?.as_slice()[i];
+		let result: Val = glsp::call(&f, &[elem])?;
+		dst.try_borrow_mut()?.as_mut_slice()[i] = T::Elem::from_elem_val(&result)?;
+	}
+
+	Ok(())
+}
+
+fn to_arr<T: TypedArr>(src: &T) -> GResult<Root<Arr>> {
+	glsp::arr_from_iter(src.as_slice().iter().copied())
+}
@@ -0,0 +1,171 @@
+use glsp::{
+	bail, Arr, DequeAccess, DequeOps, Flo, FromVal, GResult, Mat3, Num, rfn, Root, Val, Vec3
+};
+
+pub fn init(_sandboxed: bool) -> GResult<()> {
+	glsp::bind_rfn("vec2", rfn!(vec2))?;
+	glsp::bind_rfn("vec3", rfn!(vec3))?;
+	glsp::bind_rfn("mat3", rfn!(mat3))?;
+	glsp::bind_rfn("vx", rfn!(vx))?;
+	glsp::bind_rfn("vy", rfn!(vy))?;
+	glsp::bind_rfn("vz", rfn!(vz))?;
+	glsp::bind_rfn("dot", rfn!(dot))?;
+	glsp::bind_rfn("cross", rfn!(cross))?;
+	glsp::bind_rfn("normalize", rfn!(normalize))?;
+	glsp::bind_rfn("dist", rfn!(dist))?;
+	glsp::bind_rfn("lerp-vec", rfn!(lerp_vec))?;
+	glsp::bind_rfn("vec->arr", rfn!(vec_to_arr))?;
+	glsp::bind_rfn("arr->vec", rfn!(arr_to_vec))?;
+
+	Ok(())
+}
+
+//the components of a vec2 or vec3. the functions below copy them out of the gc heap, operate on
+//them, and then allocate a single new vector (if any) for their result.
+#[derive(Copy, Clone)]
+enum Vector {
+	Vec2([Flo; 2]),
+	Vec3([Flo; 3])
+}
+
+impl FromVal for Vector {
+	fn from_val(val: &Val) -> GResult<Vector> {
+		match *val {
+			Val::Vec2(ref v) => Ok(Vector::Vec2(v.to_array())),
+			Val::Vec3(ref v) => Ok(Vector::Vec3(v.to_array())),
+			ref val => bail!("expected a vec2 or vec3, received {}", val.a_type_name())
+		}
+	}
+}
+
+impl Vector {
+	fn components(&self) -> &[Flo] {
+		match *self {
+			Vector::Vec2(ref xy) => xy,
+			Vector::Vec3(ref xyz) => xyz
+		}
+	}
+
+	fn a_type_name(&self) -> &'static str {
+		match *self {
+			Vector::Vec2(_) => "a vec2",
+			Vector::Vec3(_) => "a vec3"
+		}
+	}
+
+	fn magnitude(&self) -> Flo {
+		self.components().iter().map(|c| c * c).sum::<Flo>().sqrt()
+	}
+
+	fn map(self, f: impl Fn(Flo) -> Flo) -> Vector {
+		match self {
+			Vector::Vec2([x, y]) => Vector::Vec2([f(x), f(y)]),
+			Vector::Vec3([x, y, z]) => Vector::Vec3([f(x), f(y), f(z)])
+		}
+	}
+
+	//`fn_name` is used in the error message when the two vectors have different types
+	fn zip(self, other: Vector, fn_name: &str, f: impl Fn(Flo, Flo) -> Flo) -> GResult<Vector> {
+		match (self, other) {
+			(Vector::Vec2([x0, y0]), Vector::Vec2([x1, y1])) => {
+				Ok(Vector::Vec2([f(x0, x1), f(y0, y1)]))
+			}
+			(Vector::Vec3([x0, y0, z0]), Vector::Vec3([x1, y1, z1])) => {
+				Ok(Vector::Vec3([f(x0, x1), f(y0, y1), f(z0, z1)]))
+			}
+			(a, b) => {
+				bail!("({}) expected two vectors of the same type, received {} and {}",
+				      fn_name, a.a_type_name(), b.a_type_name())
+			}
+		}
+	}
+
+	fn into_val(self) -> Val {
+		match self {
+			Vector::Vec2([x, y]) => Val::Vec2(glsp::vec2(x, y)),
+			Vector::Vec3([x, y, z]) => Val::Vec3(glsp::vec3(x, y, z))
+		}
+	}
+}
+
+fn vec2(x: Num, y: Num) -> Val {
+	Vector::Vec2([x.into_flo(), y.into_flo()]).into_val()
+}
+
+fn vec3(x: Num, y: Num, z: Num) -> Val {
+	Vector::Vec3([x.into_flo(), y.into_flo(), z.into_flo()]).into_val()
+}
+
+fn mat3(x_axis: &Vec3, y_axis: &Vec3, z_axis: &Vec3) -> Root<Mat3> {
+	glsp::mat3([x_axis.to_array(), y_axis.to_array(), z_axis.to_array()])
+}
+
+fn vx(v: Vector) -> Flo {
+	v.components()[0]
+}
+
+fn vy(v: Vector) -> Flo {
+	v.components()[1]
+}
+
+fn vz(v: &Vec3) -> Flo {
+	v.z()
+}
+
+fn dot(a: Vector, b: Vector) -> GResult<Flo> {
+	Ok(a.zip(b, "dot", |a, b| a * b)?.components().iter().sum())
+}
+
+//the cross product of two vec2s is the z component of the cross product of the corresponding
+//vec3s, which is handy for finding out whether b is clockwise or anticlockwise from a
+fn cross(a: Vector, b: Vector) -> GResult<Val> {
+	match (a, b) {
+		(Vector::Vec2([x0, y0]), Vector::Vec2([x1, y1])) => Ok(Val::Flo(x0 * y1 - y0 * x1)),
+		(Vector::Vec3([x0, y0, z0]), Vector::Vec3([x1, y1, z1])) => {
+			let xyz = [y0 * z1 - z0 * y1, z0 * x1 - x0 * z1, x0 * y1 - y0 * x1];
+			Ok(Vector::Vec3(xyz).into_val())
+		}
+		(a, b) => {
+			bail!("(cross) expected two vectors of the same type, received {} and {}",
+			      a.a_type_name(), b.a_type_name())
+		}
+	}
+}
+
+fn normalize(v: Vector) -> Val {
+	let magnitude = v.magnitude();
+	if magnitude == 0.0 {
+		v.into_val()
+	} else {
+		v.map(|c| c / magnitude).into_val()
+	}
+}
+
+fn dist(a: Vector, b: Vector) -> GResult<Flo> {
+	Ok(a.zip(b, "dist", |a, b| a - b)?.magnitude())
+}
+
+fn lerp_vec(a: Vector, b: Vector, t: Num) -> GResult<Val> {
+	let t = t.into_flo();
+	Ok(a.zip(b, "lerp-vec", |a, b| a + (b - a) * t)?.into_val())
+}
+
+fn vec_to_arr(val: Val) -> GResult<Root<Arr>> {
+	match val {
+		Val::Vec2(v) => glsp::arr_from_iter(v.to_array().iter()),
+		Val::Vec3(v) => glsp::arr_from_iter(v.to_array().iter()),
+		Val::Mat3(m) => {
+			let cols = m.to_cols_array_2d();
+			glsp::arr_from_iter(cols.iter().map(|&[x, y, z]| glsp::vec3(x, y, z)))
+		}
+		val => bail!("expected a vec2, vec3 or mat3, received {}", val.a_type_name())
+	}
+}
+
+fn arr_to_vec(arr: &Arr) -> GResult<Val> {
+	match arr.len() {
+		2 => Ok(vec2(arr.get(0)?, arr.get(1)?)),
+		3 => Ok(vec3(arr.get(0)?, arr.get(1)?, arr.get(2)?)),
+		len => bail!("(arr->vec) expected an arr of length 2 or 3, received length {}", len)
+	}
+}
@@ -30,28 +30,30 @@ pub mod prelude {
 	#[doc(no_inline)]
 	pub use crate::{
 		arr, backquote, bail, bail_at, ensure, ensure_at, 
-		epr, eprn, error, lib, macro_no_op, pr, prn, quote,
-		rdata, rdata_impls, rfn, str, syms, tab, try_arr, 
+		epr, eprn, error, include_glsp, include_glsp_dir, lib, library, macro_no_op, pr, prn, quote,
+		rdata, rdata_impls, rfn, rfn_boxed, rfn_params, str, syms, tab, try_arr, 
 		try_backquote, try_tab,
 
 		Arr, 
-		Callable, CallableOps, Class, Coro, CoroState, 
+		Callable, CallableOps, Callback, Caps, Class, Coro, CoroState, 
 		Deque, DequeAccess, DequeAccessRange, DequeOps, 
 		EnvMode, Expander, Expansion,
-		FromVal, 
-		GError, GFn, GIter, GIterLen, GResult,
+		Flo, FromVal, 
+		GError, GFn, GIter, GIterLen, GReceiver, GResult, GSender,
 		Hashable, 
-		Iterable, IterableOps,
-		Lib, LibRef, LibRefMut, 
+		IncludedDir, IncludedFile, Iterable, IterableOps,
+		KwArgs, 
+		Lib, LibRef, LibRefMut, Library, 
 		Num, 
 		Obj, OrNil,
 		RData, RFn, Root, RRoot, RRef, RRefMut, Runtime, RuntimeBuilder,
 		Splay, Str, Sym,
 		Tab, ToSym, ToVal, 
 		Val,
+		WeakCallback, WeakRoot
 	};
 
 	#[cfg(feature = "compiler")]
 	#[doc(no_inline)]
-	pub use crate::{compile, eval};
+	pub use crate::{compile, eval, eval_strict};
 }
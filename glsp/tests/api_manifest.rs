@@ -0,0 +1,182 @@
+//! Checks that binding a Rust function records it in the API manifest, along with its parameter
+//! types, documentation, library, capability and deprecation, and that the manifest can be
+//! rendered as Markdown.
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+use glsp::{ApiEntry};
+use std::env;
+use std::fs;
+
+fn add(a: i32, b: Option<i32>) -> i32 {
+	a + b.unwrap_or(0)
+}
+
+fn greet(name: &str, titles: &[Sym]) -> String {
+	format!("hello, {} {:?}", name, titles)
+}
+
+fn entry(name: &str) -> GResult<ApiEntry> {
+	let name = glsp::sym(name)?;
+	match glsp::api_manifest().into_iter().find(|entry| entry.name == name) {
+		Some(entry) => Ok(entry),
+		None => bail!("{} is not in the manifest", name)
+	}
+}
+
+fn strings(strs: &[&str]) -> Vec<String> {
+	strs.iter().map(|st| st.to_string()).collect()
+}
+
+#[test]
+fn rust_api() {
+	run(|| {
+		glsp::bind_rfn("add", rfn!(add))?;
+		glsp::document_api("add", &["a", "b"], "Adds two numbers.")?;
+
+		let add_entry = entry("add")?;
+		assert_eq!(add_entry.library, None);
+		assert_eq!(add_entry.arg_limits, (1, Some(2)));
+		assert_eq!(add_entry.param_names, Some(strings(&["a", "b"])));
+		assert_eq!(add_entry.param_types, Some(strings(&["i32", "Option<i32>"])));
+		assert_eq!(add_entry.doc.as_deref(), Some("Adds two numbers."));
+		assert!(!add_entry.is_macro);
+
+		assert!(glsp::document_api("add", &["a"], "").is_err());
+		assert!(glsp::document_api("no-such-rfn", &[], "doc").is_err());
+
+		//rfn_boxed! records the names of a closure's parameters
+		let offset = 10;
+		glsp::with_api_library("combat", || {
+			glsp::bind_rfn_boxed("attack", rfn_boxed!(|target: Sym, damage: i32| -> i32 {
+				let _ = target;
+				damage + offset
+			}))?;
+			glsp::set_api_capability("attack", "WEAPONS")
+		})?;
+
+		let attack_entry = entry("attack")?;
+		assert_eq!(attack_entry.library.as_deref(), Some("combat"));
+		assert_eq!(attack_entry.param_names, Some(strings(&["target", "damage"])));
+		assert_eq!(attack_entry.param_types, Some(strings(&["Sym", "i32"])));
+		assert_eq!(attack_entry.capability.as_deref(), Some("WEAPONS"));
+
+		//a deprecated name inherits its replacement's documentation
+		glsp::bind_deprecated("sum", "add", "it will be removed in 0.4")?;
+		let sum_entry = entry("sum")?;
+		assert_eq!(sum_entry.replacement, Some(glsp::sym("add")?));
+		assert_eq!(sum_entry.deprecation_note.as_deref(), Some("it will be removed in 0.4"));
+		assert_eq!(sum_entry.doc, add_entry.doc);
+		assert_eq!(sum_entry.arg_limits, (1, Some(2)));
+
+		//functions which are unbound or replaced are left out
+		glsp::del_global("sum")?;
+		assert!(entry("sum").is_err());
+
+		//entries are sorted by library, with the unnamed library last
+		let manifest = glsp::api_manifest();
+		let libraries: Vec<_> = manifest.iter().map(|entry| entry.library.as_deref()).collect();
+		let combat = libraries.iter().position(|&library| library == Some("combat")).unwrap();
+		let std = libraries.iter().position(|&library| library == Some("std")).unwrap();
+		assert!(combat < std);
+		assert_eq!(libraries.last(), Some(&None));
+
+		Ok(())
+	});
+}
+
+#[test]
+fn libraries() {
+	run(|| {
+		let library = Library::new()
+			.name("greetings")
+			.rfn("greet", rfn!(greet))
+			.doc("greet", &["name", "titles"], "Greets someone.")
+			.constant("greeting-count", 1);
+
+		glsp::install(&library)?;
+
+		let greet_entry = entry("greet")?;
+		assert_eq!(greet_entry.library.as_deref(), Some("greetings"));
+		assert_eq!(greet_entry.arg_limits, (1, None));
+		assert_eq!(greet_entry.param_types, Some(strings(&["&str", "&[Sym]"])));
+		assert_eq!(greet_entry.doc.as_deref(), Some("Greets someone."));
+		assert!(entry("greeting-count").is_err());
+
+		//documentation for a name which isn't one of the library's rfns is rejected
+		let library = Library::new()
+			.rfn("greet-2", rfn!(greet))
+			.doc("greet-3", &[], "Doesn't exist.");
+		assert!(glsp::install(&library).is_err());
+		assert!(!glsp::has_global("greet-2")?);
+
+		Ok(())
+	});
+}
+
+#[test]
+fn markdown() {
+	run(|| {
+		glsp::with_api_library("arithmetic", || {
+			glsp::bind_rfn("add", rfn!(add))?;
+			glsp::document_api("add", &["a", "b"], "Adds two numbers.")
+		})?;
+
+		glsp::bind_deprecated("sum", "add", "")?;
+
+		let text = glsp::api_markdown();
+		assert!(text.starts_with("# API Reference\n"));
+		assert!(text.contains("\n## Library `arithmetic`\n\n### `add` (fn)\n\n`(add a b)`\n\n\
+		                       - Arguments: 1 to 2\n\
+		                       - Parameters: `a: i32`, `b: Option<i32>`\n\n\
+		                       Adds two numbers.\n"), "{}", text);
+		assert!(text.contains("- **Deprecated:** use `add` instead\n"), "{}", text);
+		assert!(text.contains("\n### `read-file` (fn)\n"), "{}", text);
+		assert!(text.contains("- Requires the `FS` capability\n"), "{}", text);
+		assert!(text.contains("\n## Other Functions\n"), "{}", text);
+
+		let path = env::temp_dir().join("glsp_api_manifest_test.md");
+		glsp::write_api_markdown(&path)?;
+		assert_eq!(fs::read_to_string(&path).unwrap(), text);
+		fs::remove_file(&path).unwrap();
+
+		Ok(())
+	});
+}
+
+#[test]
+fn scripts() {
+	run(|| {
+		glsp::bind_rfn("add", rfn!(add))?;
+
+		glsp::load_str(r#"
+			(let manifest (api-manifest))
+
+			(let add-info [manifest 'add])
+			(ensure (eq? [add-info 'min-args] 1))
+			(ensure (eq? [add-info 'max-args] 2))
+			(ensure (eq? [add-info 'param-types] '("i32" "Option<i32>")))
+			(ensure (not [add-info 'macro?]))
+			(ensure (nil? [add-info 'library]))
+
+			(ensure (eq? [[manifest 'read-file] 'capability] "FS"))
+			(ensure (eq? [[manifest 'read-file] 'library] "std"))
+			(ensure (nil? [[manifest 'len] 'capability]))
+
+			(defn plus (a b) (+ a b))
+			(deprecate 'old-plus 'plus)
+			(ensure (eq? [[(api-manifest) 'old-plus] 'replacement] 'plus))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+
+	//builtins which have been replaced by stubs aren't listed
+	let runtime = RuntimeBuilder::new().deny(Caps::FS).build();
+	runtime.run(|| {
+		glsp::load_str("(ensure (not (has? (api-manifest) 'read-file)))", "test.glsp")?;
+		Ok(())
+	}).expect("the test failed");
+}
@@ -0,0 +1,122 @@
+//! Checks that `(apply)` and `(call-with)` forward their arguments correctly, both when the
+//! compiler rewrites them into a direct call and when they're invoked indirectly.
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+use glsp::{KwArgs};
+
+fn scaled(n: i32, kwargs: KwArgs) -> GResult<i32> {
+	let factor: Option<i32> = kwargs.get("factor")?;
+	let offset: Option<i32> = kwargs.get("offset")?;
+	Ok(n * factor.unwrap_or(1) + offset.unwrap_or(0))
+}
+
+#[test]
+fn apply() {
+	run(|| {
+		glsp::load_str(r#"
+			(ensure (== (apply + (arr 1 2 3)) 6))
+			(ensure (== (apply + (arr)) 0))
+			(ensure (eq? (apply arr "abc") '(\a \b \c)))
+			(ensure (eq? (apply arr (rn 3)) '(0 1 2)))
+
+			;the common wrapper pattern
+			(defn logged (f)
+			  (let calls (arr))
+			  (arr calls (fn (..args)
+			               (push! calls args)
+			               (apply f args))))
+
+			(let (calls add) (logged +))
+			(ensure (== (add 1 2) 3))
+			(ensure (== (add 10 20 30) 60))
+			(ensure (eq? calls '((1 2) (10 20 30))))
+
+			;the argument array isn't mutated or retained
+			(let args (arr 1 2))
+			(ensure (eq? (apply arr args) '(1 2)))
+			(ensure (not (same? (apply arr args) args)))
+
+			;calling apply indirectly bypasses the compiler's rewrite
+			(let indirect apply)
+			(ensure (== (indirect + (arr 1 2 3)) 6))
+			(ensure (eq? (indirect arr "ab") '(\a \b)))
+			(ensure (eq? (arr ..(map (fn1 (apply + _)) '((1 2) (3 4)))) '(3 7)))
+
+			(ensure (eq? [(try (apply 10 (arr))) 0] 'err))
+			(ensure (eq? [(try (apply + 10)) 0] 'err))
+			(ensure (eq? [(try (indirect + 10)) 0] 'err))
+			(ensure (eq? [(try (apply (fn (a) a) (arr 1 2))) 0] 'err))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn apply_kwargs() {
+	run(|| {
+		glsp::bind_rfn("scaled", rfn!(scaled))?;
+
+		glsp::load_str(r#"
+			(ensure (== (apply scaled (arr 5) (tab ('factor 3))) 15))
+			(ensure (== (apply scaled (arr 5) (tab ('factor 3) ('offset 1))) 16))
+			(ensure (== (apply scaled (arr 5) (tab (':offset 2))) 7))
+			(ensure (== (apply scaled (arr 5) (tab)) 5))
+			(ensure (== (scaled 5 ':factor 2) 10))
+
+			;glsp functions receive keyword arguments as ordinary arguments
+			(ensure (eq? (apply arr (arr 1) (tab ('k 2))) '(1 :k 2)))
+
+			(ensure (eq? [(try (apply scaled (arr 5) (tab ("factor" 3)))) 0] 'err))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn call_with() {
+	run(|| {
+		glsp::load_str(r#"
+			(ensure (== (call-with + 1 2) 3))
+			(ensure (== (call-with +) 0))
+			(ensure (eq? (call-with arr 1 ..(arr 2 3) 4) '(1 2 3 4)))
+			(let fns (arr (fn1 (+ _ 1)) (fn1 (- _ 1))))
+			(ensure (eq? (arr ..(map (fn1 (call-with _ 10)) fns)) '(11 9)))
+
+			(let indirect call-with)
+			(ensure (eq? (indirect arr 1 2) '(1 2)))
+			(ensure (eq? [(try (call-with 'x 1)) 0] 'err))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn rewritten_calls() {
+	run(|| {
+		//a direct call to apply or call-with compiles to an ordinary call instruction
+		let listing = glsp::load_str(r#"
+			(defn wrapper (f ..args)
+			  (apply f args)
+			  (call-with f 1 ..args))
+
+			(disassemble wrapper)
+		"#, "test.glsp")?.to_string();
+
+		assert!(!listing.contains("apply"), "unexpected listing:\n{}", listing);
+		assert!(!listing.contains("call-with"), "unexpected listing:\n{}", listing);
+
+		//apply and call-with can't be rebound, so the rewrite is always safe
+		glsp::load_str(r#"
+			(ensure (eq? [(try (= apply 1)) 0] 'err))
+			(ensure (eq? [(try (= call-with 1)) 0] 'err))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
@@ -0,0 +1,69 @@
+//! Checks that shift counts are masked to their low five bits, and that the dedicated bitwise
+//! instructions agree with the rfns which are called when the operators are used indirectly.
+
+mod common;
+
+use common::run;
+
+#[test]
+fn shift_counts() {
+	run(|| {
+		glsp::load_str(r#"
+			(let min-int -2147483648)
+
+			;only the low five bits of the count are used
+			(ensure (== (bitshl 1 31) min-int))
+			(ensure (== (bitshl 1 32) 1))
+			(ensure (== (bitshl 1 33) 2))
+			(ensure (== (bitshl 3 64) 3))
+			(ensure (== (bitshr min-int 32) min-int))
+			(ensure (== (bitshr -1 60) 15))
+			(ensure (== (bitsar -16 34) -4))
+			(ensure (== (bitsar min-int 63) -1))
+
+			;a negative count is masked in the same way, so -1 shifts by 31
+			(ensure (== (bitshl 1 -1) min-int))
+			(ensure (== (bitshl 1 -32) 1))
+			(ensure (== (bitshr -1 -4) 15))
+			(ensure (== (bitshr -1 -1) 1))
+			(ensure (== (bitsar min-int -1) -1))
+			(ensure (== (bitsar 64 -27) 2))
+
+			;bitshr fills with zeroes, and bitsar copies the sign bit
+			(ensure (== (bitshr -8 1) 2147483644))
+			(ensure (== (bitsar -8 1) -4))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn instrs_match_rfns() {
+	run(|| {
+		glsp::load_str(r#"
+			(let values '(0 1 -1 5 -16 255 2147483647 -2147483648 1431655765))
+			(let counts '(-64 -33 -32 -31 -1 0 1 7 31 32 33 63 100))
+
+			;calling a global through a local variable always invokes the rfn, while a direct
+			;call with two arguments is compiled to an instruction
+			(let rfns (arr bitshl bitshr bitsar bitand bitor bitxor))
+
+			(for x in values
+			  (for n in counts
+			    (let by-instr (arr (bitshl x n) (bitshr x n) (bitsar x n)
+			                       (bitand x n) (bitor x n) (bitxor x n)))
+			    (let by-rfn (arr ..(map (fn1 (_ x n)) rfns)))
+			    (unless (eq? by-instr by-rfn)
+			      (bail (str x " " n ": " by-instr " != " by-rfn)))))
+
+			;both report the same error for a non-int argument
+			(let (_ instr-err) (try (bitshl 1 2.0)))
+			(let f bitshl)
+			(let (_ rfn-err) (try (f 1 2.0)))
+			(ensure (eq? instr-err rfn-err))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
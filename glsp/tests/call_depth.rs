@@ -0,0 +1,143 @@
+//! Checks that runaway recursion through function calls, coroutines and macro expansion fails
+//! with a catchable `stack-overflow` error, rather than overflowing the native stack, and that
+//! the `Runtime` remains usable afterwards.
+
+use glsp::prelude::*;
+use glsp::{GSend};
+use std::thread;
+
+//in a debug build, each GameLisp call can consume tens of kilobytes of native stack, so the
+//default call depth needs a much larger stack than the test harness provides
+fn run_with_large_stack<F: FnOnce() -> GResult<()> + GSend + Send + 'static>(f: F) {
+	thread::Builder::new()
+		.stack_size(64 * 1024 * 1024)
+		.spawn(move || {
+			let runtime = Runtime::new();
+			runtime.run(f).expect("the test failed");
+		})
+		.unwrap()
+		.join()
+		.unwrap();
+}
+
+fn is_stack_overflow(result: GResult<Val>) -> bool {
+	match result {
+		Ok(_) => false,
+		Err(err) => err.val() == Val::Sym(glsp::sym("stack-overflow").unwrap())
+	}
+}
+
+#[test]
+fn runaway_recursion() {
+	run_with_large_stack(|| {
+		assert_eq!(glsp::max_call_depth(), 256);
+
+		glsp::load_str(r#"
+			(defn forever (n)
+			  (+ 1 (forever n)))
+
+			(defn count-down (n)
+			  (cond
+			    ((== n 0) 0)
+			    (else (+ 1 (count-down (- n 1))))))
+
+			(ensure (eq? (try (forever 0)) '(err stack-overflow)))
+			(ensure (eq? (try (forever 0)) '(err stack-overflow)))
+
+			;the stack has been unwound, so ordinary calls still succeed
+			(ensure (== (count-down 200) 200))
+
+			;tail calls aren't limited
+			(defn loop-down (n)
+			  (cond
+			    ((== n 0) 'done)
+			    (else (loop-down (- n 1)))))
+			(ensure (eq? (loop-down 10_000) 'done))
+		"#, "test.glsp")?;
+
+		//the limit is configurable
+		glsp::set_max_call_depth(50);
+		let count_down: Root<GFn> = glsp::global("count-down")?;
+		assert!(is_stack_overflow(glsp::call(&count_down, &(100,))));
+		assert_eq!(glsp::call::<_, _, i32>(&count_down, &(40,))?, 40);
+
+		glsp::set_max_call_depth(1000);
+		assert_eq!(glsp::call::<_, _, i32>(&count_down, &(500,))?, 500);
+		glsp::set_max_call_depth(256);
+
+		Ok(())
+	});
+}
+
+#[test]
+fn cycle_description() {
+	run_with_large_stack(|| {
+		glsp::load_str(r#"
+			(defn ping (n) (+ 1 (pong n)))
+			(defn pong (n) (+ 1 (ping n)))
+		"#, "test.glsp")?;
+
+		let ping: Root<GFn> = glsp::global("ping")?;
+		let err = glsp::call::<_, _, Val>(&ping, &(0,)).unwrap_err();
+		assert_eq!(err.val(), Val::Sym(glsp::sym("stack-overflow")?));
+
+		let message = err.to_string();
+		assert!(message.contains("exceeded the maximum call depth of 256"), "{}", message);
+		assert!(message.contains("(ping) -> (pong)") || message.contains("(pong) -> (ping)"),
+		        "{}", message);
+
+		Ok(())
+	});
+}
+
+#[test]
+fn coroutines_and_macros() {
+	run_with_large_stack(|| {
+		glsp::load_str(r#"
+			(defn nested-coro ()
+			  (coro-run (nested-coro))
+			  (yield))
+
+			(ensure (eq? (try (coro-run (nested-coro))) '(err stack-overflow)))
+
+			(defmacro nested-macro (x)
+			  `(arr (nested-macro ~x)))
+
+			(ensure (eq? (try (eval '(nested-macro 1))) '(err stack-overflow)))
+
+			;both recover afterwards
+			(defn gen ()
+			  (yield 1)
+			  (yield 2))
+			(ensure (eq? (arr ..(gen)) '(1 2)))
+
+			(defmacro twice (x) `(arr ~x ~x))
+			(ensure (eq? (twice 1) '(1 1)))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn stack_budget() {
+	//this runs on the test harness's own thread, which has a small native stack
+	let runtime = RuntimeBuilder::new().stack_budget(256 * 1024).build();
+	runtime.run(|| {
+		assert_eq!(glsp::stack_budget(), Some(256 * 1024));
+
+		glsp::load_str(r#"
+			(defn forever (n)
+			  (+ 1 (forever n)))
+
+			(ensure (eq? (try (forever 0)) '(err stack-overflow)))
+			(ensure (== (+ 1 2) 3))
+		"#, "test.glsp")?;
+
+		glsp::set_stack_budget(None);
+		assert_eq!(glsp::stack_budget(), None);
+
+		Ok(())
+	}).unwrap();
+
+}
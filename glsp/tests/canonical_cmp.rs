@@ -0,0 +1,117 @@
+//! Checks that `canonical-cmp` is a total order over heterogeneous values, that it terminates for
+//! cyclic values, and that `glsp::state_hash` orders table entries with it.
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+use std::cmp::{Ordering};
+
+#[test]
+fn ordering() {
+	run(|| {
+		glsp::load_str(r#"
+			(defn sorted (..args)
+			  (sort args canonical-cmp))
+
+			(ensure (eq? (sorted "b" 2 'a 1.5 #n) '(#n 1.5 2 a "b")))
+			(ensure (eq? (sorted #t #f #n) '(#n #f #t)))
+			(ensure (eq? (sorted \b \a 'b "a" 'a) '(\a \b a b "a")))
+
+			;numbers are compared exactly, with defined positions for ties and nan
+			(let sorted-nums (sorted 1.0 1 -0.0 0 0.0 -1 nan.0 -inf.0 +inf.0))
+			(let expected '(-inf.0 -1 0 -0.0 0.0 1 1.0 +inf.0 nan.0))
+			(ensure (eq? (canonical-cmp sorted-nums expected) '==))
+			(ensure (eq? (canonical-cmp sorted-nums '(-inf.0 -1 -0.0 0 0.0 1 1.0 +inf.0)) '<))
+			(ensure (eq? (canonical-cmp 16777217 16777216.0) '>))
+			(ensure (eq? (canonical-cmp nan.0 (/ 0.0 0.0)) '==))
+
+			;collections are compared by contents
+			(ensure (eq? (sorted '(1 2 3) '(1 2) '(0 5) '()) '(() (0 5) (1 2) (1 2 3))))
+			(let t0 (tab ('a 1) ('b 2)))
+			(let t1 (tab ('b 2) ('a 1)))
+			(let t2 (tab ('a 1) ('b 3)))
+			(let t3 (tab ('a 1) (#n 0)))
+			(ensure (eq? (canonical-cmp t0 t1) '==))
+			(ensure (eq? (canonical-cmp t0 t2) '<))
+			(ensure (eq? (canonical-cmp t0 t3) '>))
+
+			(defrecord Point x y)
+			(defrecord Size w h)
+			(ensure (eq? (canonical-cmp (Point:new 1 2) (Point:new 1 3)) '<))
+			(ensure (eq? (canonical-cmp (Size:new 0 0) (Point:new 9 9)) '>))
+
+			;values without canonical contents are only ordered by type
+			(defclass Empty)
+			(ensure (eq? (canonical-cmp (Empty) (Empty)) '==))
+			(ensure (eq? (canonical-cmp (Empty) (fn () 0)) '<))
+			(ensure (eq? (canonical-cmp (Point:new 1 2) (Empty)) '<))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn cycles() {
+	run(|| {
+		glsp::load_str(r#"
+			(let a (arr 1))
+			(push! a a)
+			(push! a a)
+			(let b (arr 1))
+			(push! b b)
+			(push! b b)
+			(ensure (eq? (canonical-cmp a b) '==))
+
+			(push! b 0)
+			(ensure (eq? (canonical-cmp a b) '<))
+
+			(let t (tab ('x 1)))
+			(= [t 'self] t)
+			(let u (tab))
+			(= [u 'x] 1)
+			(= [u 'self] u)
+			(ensure (not (same? t u)))
+			(ensure (eq? (canonical-cmp t u) '==))
+
+			;deeply-nested values are compared up to a fixed depth
+			(let deep-a (arr 0))
+			(let deep-b (arr 1))
+			(forn (_ 1000)
+			  (= deep-a (arr deep-a))
+			  (= deep-b (arr deep-b)))
+			(ensure (eq? (canonical-cmp deep-a deep-b) '==))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
+
+fn load_vals(src: &str) -> GResult<Vec<Val>> {
+	Vec::<Val>::from_val(&glsp::load_str(src, "test.glsp")?)
+}
+
+#[test]
+fn rust_api() {
+	run(|| {
+		let vals = load_vals("(arr 3 'x 1.5 \"s\" #n)")?;
+		let mut sorted = vals.clone();
+		sorted.sort_by(|a, b| a.canonical_cmp(b));
+		assert_eq!(sorted, load_vals("(arr #n 1.5 3 'x \"s\")")?);
+
+		for a in &vals {
+			for b in &vals {
+				assert_eq!(a.canonical_cmp(b), b.canonical_cmp(a).reverse());
+				assert_eq!(a.canonical_cmp(b) == Ordering::Equal, a == b);
+			}
+		}
+
+		//tables with differently-typed keys hash identically, whatever their insertion order
+		let t0 = glsp::load_str(r#"(tab (1 'a) (1.0 'b) (\a 'c) ('k 'd) ("k" 'e))"#, "test.glsp")?;
+		let t1 = glsp::load_str(r#"(tab ("k" 'e) ('k 'd) (\a 'c) (1.0 'b) (1 'a))"#, "test.glsp")?;
+		assert_eq!(glsp::state_hash(&[&t0])?, glsp::state_hash(&[&t1])?);
+
+		Ok(())
+	});
+}
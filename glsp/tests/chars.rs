@@ -0,0 +1,82 @@
+//! Checks the char conversions, predicates and case conversions, and that chars can't be used in
+//! arithmetic.
+
+mod common;
+
+use common::run;
+
+#[test]
+fn conversions() {
+	run(|| {
+		glsp::load_str(r#"
+			(defn err? (result)
+			  (eq? [result 0] 'err))
+
+			(ensure (== (char->int \a) 97))
+			(ensure (eq? (int->char 97) \a))
+			(ensure (eq? (int->char 0x10ffff) (char 0x10ffff)))
+			(ensure (eq? (int->char (+ (char->int \a) 1)) \b))
+			(ensure (err? (try (char->int 97))))
+			(ensure (err? (try (int->char -1))))
+			(ensure (err? (try (int->char 0x110000))))
+			(ensure (err? (try (int->char 0xd800))))
+			(ensure (err? (try (int->char 0xdfff))))
+			(ensure (err? (try (char 0xd800))))
+			(ensure (err? (try (int->char 97.0))))
+
+			;arithmetic on chars is an error, whether or not it's inlined
+			(let c \a)
+			(let add +)
+			(ensure (err? (try (+ c 1))))
+			(ensure (err? (try (add c 1))))
+
+			(ensure (eq? (arr ..(chars "aß")) '(\a \ß)))
+			(ensure (eq? (arr ..(chars "")) '()))
+		"#, "conversions.glsp")?;
+
+		let message = glsp::load_str("(let c \\a) (+ c 1)", "error.glsp").unwrap_err().to_string();
+		assert!(message.contains("char->int"), "{}", message);
+
+		let message = glsp::load_str("(int->char 0xd800)", "error.glsp").unwrap_err().to_string();
+		assert!(message.contains("surrogate"), "{}", message);
+		Ok(())
+	});
+}
+
+#[test]
+fn predicates_and_case() {
+	run(|| {
+		glsp::load_str(r#"
+			(ensure (alpha? \a))
+			(ensure (alpha? \é))
+			(ensure (alpha? "abcΩ"))
+			(ensure (not (alpha? \1)))
+			(ensure (not (alpha? "ab1")))
+
+			(ensure (digit? \7))
+			(ensure (digit? \½))
+			(ensure (not (digit? \x)))
+
+			(ensure (upper? \A))
+			(ensure (not (upper? \a)))
+			(ensure (not (upper? \1)))
+			(ensure (lower? "abc"))
+			(ensure (not (lower? "aBc")))
+			(ensure (whitespace? \space))
+
+			(ensure (eq? (upcase-char \a) \A))
+			(ensure (eq? (upcase-char \é) \É))
+			(ensure (eq? (upcase-char \ß) \ß))
+			(ensure (eq? (downcase-char \Ω) \ω))
+			(ensure (eq? (downcase-char \1) \1))
+
+			(ensure (== (digit-value \7) 7))
+			(ensure (nil? (digit-value \f)))
+			(ensure (== (digit-value \F 16) 15))
+			(ensure (nil? (digit-value \½)))
+			(ensure (eq? [(try (digit-value \1 37)) 0] 'err))
+		"#, "predicates.glsp")?;
+
+		Ok(())
+	});
+}
@@ -0,0 +1,153 @@
+//! Checks that `glsp::reload` migrates the instances of a redefined class, either by copying
+//! fields by name or by calling the new class' `migrate` function, and that instances which
+//! can't be migrated are flagged as stale.
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+use glsp::{ReloadReport};
+use std::fs;
+use std::path::{PathBuf};
+
+//writes `src` to a temporary file, reloads it, then deletes the file
+fn reload(path: &PathBuf, src: &str) -> GResult<ReloadReport> {
+	fs::write(path, src).unwrap();
+	let result = glsp::reload(path.to_str().unwrap());
+	fs::remove_file(path).unwrap();
+	result
+}
+
+fn temp_path(name: &str) -> PathBuf {
+	std::env::temp_dir().join(format!("glsp-{}-{}.glsp", name, std::process::id()))
+}
+
+#[test]
+fn copy_fields() {
+	run(|| {
+		let path = temp_path("migrate-copy");
+		fs::write(&path, r#"
+			(defclass Enemy
+			  (field name "goblin")
+			  (field hp 10)
+			  (meth describe () (str @name " " @hp)))
+		"#).unwrap();
+		glsp::load(path.to_str().unwrap())?;
+
+		glsp::load_str(r#"
+			(def goblin (Enemy))
+			(def killed (Enemy))
+			(obj-kill! killed)
+			(ensure (eq? (.describe goblin) "goblin 10"))
+		"#, "test.glsp")?;
+
+		//the fields are reordered, and a new field is added
+		let report = reload(&path, r#"
+			(defclass Enemy
+			  (field armor 5)
+			  (field hp 10)
+			  (field name "goblin")
+			  (meth describe () (str @name " " @hp " " @armor)))
+		"#)?;
+		assert!(report.stale_objs().is_empty());
+
+		glsp::load_str(r#"
+			(ensure (is? goblin Enemy))
+			(ensure (eq? (.describe goblin) "goblin 10 #n"))
+			(= [goblin 'armor] 3)
+			(ensure (eq? (.describe goblin) "goblin 10 3"))
+			(ensure (is? killed Enemy))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn migrate_fn() {
+	run(|| {
+		let path = temp_path("migrate-fn");
+		fs::write(&path, r#"
+			(defclass Enemy
+			  (field hp 10))
+		"#).unwrap();
+		glsp::load(path.to_str().unwrap())?;
+
+		glsp::load_str(r#"
+			(def enemies (arr (Enemy) (Enemy)))
+			(= [[enemies 1] 'hp] 0)
+		"#, "test.glsp")?;
+
+		//the migrate function constructs a replacement obj
+		let report = reload(&path, r#"
+			(defclass Enemy
+			  (field health)
+			  (field alive?)
+			  (fsm
+			    (state* Idle)
+			    (state Hunting
+			      (field target)))
+
+			  (const migrate (fn (old)
+			    (Enemy [old 'hp])))
+
+			  (init (health)
+			    (= @health health)
+			    (= @alive? (> health 0)))
+
+			  (meth describe ()
+			    (str @health " " @alive?)))
+		"#)?;
+		assert!(report.stale_objs().is_empty());
+
+		glsp::load_str(r#"
+			(let first [enemies 0])
+			(ensure (eq? (.describe first) "10 #t"))
+			(ensure (eq? (.describe [enemies 1]) "0 #f"))
+			(ensure (same? first [enemies 0]))
+
+			(enab! first 'Hunting)
+			(= [first 'target] 'player)
+		"#, "test.glsp")?;
+
+		//the migrate function mutates the old obj, then returns #n. states are matched by name
+		let report = reload(&path, r#"
+			(defclass Enemy
+			  (field health)
+			  (fsm
+			    (state* Idle)
+			    (state Hunting
+			      (field target)))
+
+			  (const migrate (fn (old)
+			    (ensure (> [old 'health] 0))
+			    (= [old 'health] (* [old 'health] 2))
+			    #n))
+
+			  (meth describe ()
+			    (str @health)))
+		"#)?;
+
+		assert_eq!(report.stale_objs().len(), 1);
+		assert_eq!(report.migration_errors().len(), 1);
+		assert!(report.stale_objs()[0].is_stale());
+
+		glsp::load_str(r#"
+			(ensure (eq? (.describe [enemies 0]) "20"))
+			(ensure (enab? [enemies 0] 'Hunting))
+			(ensure (not (enab? [enemies 0] 'Idle)))
+			(ensure (eq? [[enemies 0] 'target] 'player))
+
+			;the stale obj's fields can still be read, but its methods can't be called
+			(ensure (== [[enemies 1] 'health] 0))
+			(ensure (eq? (try (.describe [enemies 1])) '(err stale-instance)))
+		"#, "test.glsp")?;
+
+		let stale = &report.stale_objs()[0];
+		let message = stale.call::<_, _, Val>("describe", &()).unwrap_err().to_string();
+		assert!(message.contains("stale instance of Enemy"), "{}", message);
+		assert!(message.contains("from version 2 to version 3"), "{}", message);
+
+		Ok(())
+	});
+}
@@ -0,0 +1,11 @@
+//! Helpers shared by the integration tests. Each test file includes this module using
+//! `mod common;`.
+
+use glsp::prelude::*;
+use glsp::{GSend};
+
+///Runs `f` in a new default `Runtime`, panicking if it returns an `Err`.
+pub fn run<R: GSend, F: FnOnce() -> GResult<R> + GSend>(f: F) -> R {
+	let runtime = Runtime::new();
+	runtime.run(f).expect("the test failed")
+}
@@ -0,0 +1,256 @@
+//! Checks that `glsp::set_compile_cache` reuses the compiled code for unchanged files across
+//! Runtimes, and that a file is recompiled when any of the macros, constants, included files or
+//! loaded files which it depended on have changed.
+
+#![cfg(feature = "compiler")]
+
+use glsp::prelude::*;
+use glsp::{CompileCacheStats, GSend};
+use std::borrow::{Cow};
+use std::collections::{HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+struct CacheDir(PathBuf);
+
+impl CacheDir {
+	fn new(name: &str) -> CacheDir {
+		let path = std::env::temp_dir().join(format!("glsp-{}-{}", name, std::process::id()));
+		let _ = fs::remove_dir_all(&path);
+		CacheDir(path)
+	}
+}
+
+impl Drop for CacheDir {
+	fn drop(&mut self) {
+		let _ = fs::remove_dir_all(&self.0);
+	}
+}
+
+//runs `f` in a fresh Runtime which loads `files` from memory, with the compile cache enabled
+fn run<R, F>(dir: &Path, files: &[(&str, &str)], f: F) -> (R, CompileCacheStats)
+where
+	R: GSend,
+	F: FnOnce() -> GResult<R> + GSend
+{
+	let files: HashMap<String, String> = files.iter().map(|&(name, text)| {
+		(name.to_string(), text.to_string())
+	}).collect();
+
+	let runtime = RuntimeBuilder::new()
+		.loader(move |filename| {
+			match files.get(filename) {
+				Some(text) => Ok(Cow::Owned(text.clone())),
+				None => bail!("{} doesn't exist", filename)
+			}
+		})
+		.build();
+
+	runtime.run(|| {
+		glsp::set_compile_cache(dir)?;
+		let result = f()?;
+		Ok((result, glsp::compile_cache_stats()))
+	}).expect("the test failed")
+}
+
+fn stats(stats: CompileCacheStats) -> (usize, usize, usize) {
+	(stats.hits(), stats.misses(), stats.uncacheable())
+}
+
+const MACROS: &str = r#"
+	(defmacro swap-sum! (a b)
+	  (let tmp (gensym))
+	  `(do
+	     (let ~tmp ~a)
+	     (= ~a ~b)
+	     (= ~b ~tmp)
+	     (+ ~a ~b)))
+"#;
+
+const UTILS: &str = r#"
+	(defn helper (x)
+	  (* x 10))
+"#;
+
+const MAIN: &str = r#"
+	(require "macros.glsp")
+	(require "utils.glsp")
+
+	(let a 1)
+	(let b 2)
+	(def sum (swap-sum! a b))
+	(def swapped (arr a b))
+
+	(defn total ()
+	  (+ sum (helper 1)))
+
+	(total)
+"#;
+
+#[test]
+fn warm_runs() {
+	let dir = CacheDir::new("warm-runs");
+	let files = [("main.glsp", MAIN), ("macros.glsp", MACROS), ("utils.glsp", UTILS)];
+
+	let check = || -> GResult<i32> {
+		let result: i32 = FromVal::from_val(&glsp::load("main.glsp")?)?;
+		let swapped: String = glsp::global::<_, Root<Arr>>("swapped")?.to_string();
+		assert_eq!(swapped, "(2 1)");
+		Ok(result)
+	};
+
+	let (result, cold) = run(&dir.0, &files, check);
+	assert_eq!(result, 13);
+	assert_eq!(stats(cold), (0, 3, 0));
+
+	let (result, warm) = run(&dir.0, &files, check);
+	assert_eq!(result, 13);
+	assert_eq!(stats(warm), (3, 0, 0));
+
+	//macros from the cached file are still available to code which isn't cached
+	let ((), _) = run(&dir.0, &files, || {
+		glsp::load("main.glsp")?;
+		glsp::disable_compile_cache();
+		glsp::load_str(r#"
+			(let x 5)
+			(let y 6)
+			(ensure (== (swap-sum! x y) 11))
+			(ensure (== x 6))
+		"#, "test.glsp")?;
+		Ok(())
+	});
+}
+
+#[test]
+fn macro_dependencies() {
+	let dir = CacheDir::new("macro-deps");
+
+	//main.glsp's (require) forms are skipped, because the host has already loaded both files
+	let load_all = || {
+		glsp::require("macros.glsp")?;
+		glsp::require("utils.glsp")?;
+		i32::from_val(&glsp::load("main.glsp")?)
+	};
+
+	let files = [("main.glsp", MAIN), ("macros.glsp", MACROS), ("utils.glsp", UTILS)];
+	let (_, cold) = run(&dir.0, &files, load_all);
+	assert_eq!(stats(cold), (0, 3, 0));
+
+	//changing a file which doesn't provide any macros only recompiles that file
+	let changed_utils = UTILS.replace("(* x 10)", "(* x 100)");
+	let files = [("main.glsp", MAIN), ("macros.glsp", MACROS), ("utils.glsp", &changed_utils[..])];
+	let (result, changed) = run(&dir.0, &files, load_all);
+	assert_eq!(result, 103);
+	assert_eq!(stats(changed), (2, 1, 0));
+
+	//changing the file which provides a macro recompiles every file which used it
+	let changed_macros = MACROS.replace("(+ ~a ~b)", "(- ~a ~b)");
+	let files = [("main.glsp", MAIN), ("macros.glsp", &changed_macros[..]),
+	             ("utils.glsp", &changed_utils[..])];
+	let (result, changed) = run(&dir.0, &files, load_all);
+	assert_eq!(result, 101);
+	assert_eq!(stats(changed), (1, 2, 0));
+
+	//when main.glsp loads the other files itself, any change to them recompiles main.glsp,
+	//since they might have started to define a macro which shadows one of its globals
+	let load_main = || i32::from_val(&glsp::load("main.glsp")?);
+	let (_, cold) = run(&dir.0, &files, load_main);
+	assert_eq!(stats(cold), (2, 1, 0));
+
+	let files = [("main.glsp", MAIN), ("macros.glsp", &changed_macros[..]), ("utils.glsp", UTILS)];
+	let (result, changed) = run(&dir.0, &files, load_main);
+	assert_eq!(result, 11);
+	assert_eq!(stats(changed), (2, 1, 0));
+
+	//a global which wasn't a macro when the file was compiled
+	let uses_twice = r#"
+		(defn twice (x) (* x 2))
+		(twice 21)
+	"#;
+	let ((), cold) = run(&dir.0, &[("twice.glsp", uses_twice)], || {
+		glsp::load("twice.glsp").map(|_| ())
+	});
+	assert_eq!(stats(cold), (0, 1, 0));
+
+	let (result, stats_) = run(&dir.0, &[("twice.glsp", uses_twice)], || {
+		glsp::load_str("(defmacro twice (x) `(+ ~x ~x 1))", "macro.glsp")?;
+		i32::from_val(&glsp::load("twice.glsp")?)
+	});
+	assert_eq!(result, 43);
+	assert_eq!(stats(stats_), (0, 2, 0));
+}
+
+#[test]
+fn other_dependencies() {
+	let dir = CacheDir::new("other-deps");
+
+	let consts = "(defconst tile-size 16)";
+	let main = r#"
+		(include "part.glsp")
+		(+ (* 2 tile-size) part)
+	"#;
+
+	let load_main = || {
+		glsp::load("consts.glsp")?;
+		i32::from_val(&glsp::load("main.glsp")?)
+	};
+
+	let files = [("main.glsp", main), ("consts.glsp", consts), ("part.glsp", "(def part 1)")];
+	let (result, cold) = run(&dir.0, &files, load_main);
+	assert_eq!(result, 33);
+	assert_eq!(stats(cold), (0, 2, 0));
+
+	//a constant which was inlined
+	let files = [("main.glsp", main), ("consts.glsp", "(defconst tile-size 8)"),
+	             ("part.glsp", "(def part 1)")];
+	let (result, changed) = run(&dir.0, &files, load_main);
+	assert_eq!(result, 17);
+	assert_eq!(stats(changed), (0, 2, 0));
+
+	//changing the constant's file without changing its value
+	let files = [("main.glsp", main), ("consts.glsp", "(defconst tile-size 8) ; eight"),
+	             ("part.glsp", "(def part 1)")];
+	let (result, changed) = run(&dir.0, &files, load_main);
+	assert_eq!(result, 17);
+	assert_eq!(stats(changed), (1, 1, 0));
+
+	//a file read by (include)
+	let files = [("main.glsp", main), ("consts.glsp", "(defconst tile-size 8) ; eight"),
+	             ("part.glsp", "(def part 2)")];
+	let (result, changed) = run(&dir.0, &files, load_main);
+	assert_eq!(result, 18);
+	assert_eq!(stats(changed), (1, 1, 0));
+
+	//source text which didn't come from the loader
+	let load_inline = || i32::from_val(&glsp::load_str("(+ 1 2)", "inline.glsp")?);
+	let (_, first) = run(&dir.0, &[], load_inline);
+	let (result, second) = run(&dir.0, &[], load_inline);
+	assert_eq!((stats(first), stats(second), result), ((0, 1, 0), (1, 0, 0), 3));
+}
+
+#[test]
+fn uncacheable() {
+	let dir = CacheDir::new("uncacheable");
+	let main = "(def result (unknown-mac 10))";
+
+	for _ in 0 .. 2 {
+		//a macro bound from Rust code might change between runs, unless it's an rfn
+		let (result, stats_) = run(&dir.0, &[("main.glsp", main)], || {
+			glsp::eval(&glsp::parse_1("(defmacro unknown-mac (x) `(+ ~x 1))", None)?, None)?;
+			glsp::load("main.glsp")?;
+			i32::from_val(&glsp::global("result")?)
+		});
+
+		assert_eq!(result, 11);
+		assert_eq!(stats(stats_), (0, 0, 1));
+	}
+
+	//load_and_compile ignores the cache
+	let ((), stats_) = run(&dir.0, &[("main.glsp", "(+ 1 2)")], || {
+		glsp::load_and_compile("main.glsp")?;
+		let (_, bytes) = glsp::load_and_compile("main.glsp")?;
+		glsp::load_compiled(&bytes)?;
+		Ok(())
+	});
+	assert_eq!(stats(stats_), (0, 0, 0));
+}
@@ -0,0 +1,96 @@
+//! Checks that `glsp::compile_fn` and `compile-fn` compile source text into a function without
+//! running it, that the text is only expanded once, and that errors refer to the source text.
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+
+#[test]
+fn rust_api() {
+	run(|| {
+		glsp::load_str(r#"
+			(def stage 0)
+			(def expansions 0)
+
+			(defmacro stage-at-least (n)
+			  (inc! expansions)
+			  `(>= stage ~n))
+		"#, "setup.glsp")?;
+
+		//the text isn't evaluated until the function is called
+		let condition = glsp::compile_fn("(= stage 100) (stage-at-least 3)", Some("quests.glsp"))?;
+		assert_eq!(glsp::global::<_, i32>("stage")?, 0);
+		assert_eq!(glsp::global::<_, i32>("expansions")?, 1);
+
+		let met: bool = glsp::call(&condition, &())?;
+		assert!(met);
+		assert_eq!(glsp::global::<_, i32>("stage")?, 100);
+
+		//each call sees the current globals, without expanding the text again
+		let condition = glsp::compile_fn("(stage-at-least 3)", None)?;
+		for stage in 0 .. 5 {
+			glsp::set_global("stage", stage)?;
+			let met: bool = glsp::call(&condition, &())?;
+			assert_eq!(met, stage >= 3);
+		}
+		assert_eq!(glsp::global::<_, i32>("expansions")?, 2);
+
+		//an empty text compiles to a function which returns #n, and which takes no arguments
+		let empty = glsp::compile_fn("", None)?;
+		assert_eq!(glsp::call::<_, _, Val>(&empty, &())?, Val::Nil);
+		assert_eq!(empty.arg_limits(), (0, Some(0)));
+
+		Ok(())
+	});
+}
+
+#[test]
+fn errors() {
+	run(|| {
+		//parse errors and expansion errors are reported up front, with spans into the text
+		let message = glsp::compile_fn("\n(+ 1 2", Some("console.glsp")).unwrap_err().to_string();
+		assert!(message.contains("console.glsp"), "{}", message);
+
+		glsp::load_str("(defmacro broken () (bail \"broken macro\"))", "setup.glsp")?;
+		let err = glsp::compile_fn("1\n\n(broken)", Some("console.glsp")).unwrap_err();
+		let message = err.to_string();
+		assert!(message.contains("broken macro"), "{}", message);
+		assert!(message.contains("console.glsp:3"), "{}", message);
+
+		let err = glsp::compile_fn("(if)", Some("console.glsp")).unwrap_err();
+		assert!(err.to_string().contains("console.glsp:1"), "{}", err);
+
+		//errors raised by the function report the line where they occurred
+		let fails = glsp::compile_fn("#n\n(bail \"failed\")", Some("console.glsp"))?;
+		let message = glsp::call::<_, _, Val>(&fails, &()).unwrap_err().to_string();
+		assert!(message.contains("console.glsp:2"), "{}", message);
+
+		Ok(())
+	});
+}
+
+#[test]
+fn scripts() {
+	run(|| {
+		glsp::load_str(r#"
+			(def counter 0)
+			(let step (compile-fn "(inc! counter) (* counter 10)"))
+			(ensure (== counter 0))
+			(ensure (fn? step))
+			(ensure (== (step) 10))
+			(ensure (== (step) 20))
+
+			(let result (try (compile-fn "(" "console.glsp")))
+			(ensure (eq? [result 0] 'err))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+
+	let runtime = RuntimeBuilder::new().deny(Caps::EVAL).build();
+	runtime.run(|| {
+		glsp::load_str(r#"(ensure (eq? [(try (compile-fn "1")) 0] 'err))"#, "test.glsp")?;
+		Ok(())
+	}).expect("the test failed");
+}
@@ -0,0 +1,229 @@
+//! Checks that `(with-children)` cancels the coroutines created within its dynamic extent when
+//! it exits normally, by an error or by cancellation, that scopes nest, and that errors raised
+//! while the children are being cancelled are aggregated.
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+
+//each test logs the cancellation of its helpers to the global `defer-log`, an arr of syms
+const PRELUDE: &str = r#"
+	(def defer-log (arr))
+
+	(defn helper (name)
+	  (defer (push! defer-log name))
+	  (loop (yield name)))
+"#;
+
+fn load(text: &str) -> GResult<Val> {
+	glsp::load_str(PRELUDE, "prelude.glsp")?;
+	glsp::load_str(text, "test.glsp")
+}
+
+fn log() -> GResult<String> {
+	let log: Val = glsp::global("defer-log")?;
+	Ok(log.to_string())
+}
+
+#[test]
+fn normal_exit() {
+	run(|| {
+		load(r#"
+			(defn cutscene ()
+			  (with-children
+			    (let camera (helper 'camera))
+			    (let dialogue (helper 'dialogue))
+			    (let unstarted (helper 'unstarted))
+			    (coro-run camera)
+			    (coro-run dialogue)
+			    (yield 'waiting)
+			    (coro-run camera)
+			    (ensure (== (len defer-log) 0)))
+			  'done)
+
+			(let co (cutscene))
+			(ensure (eq? (coro-run co) 'waiting))
+
+			;coroutines created while the scope's coroutine is paused aren't its children
+			(let outsider (helper 'outsider))
+			(coro-run outsider)
+
+			(ensure (eq? (coro-run co) 'done))
+			(ensure (eq? (coro-state outsider) 'paused))
+		"#)?;
+
+		//children are cancelled in the reverse of the order they were created. the unstarted
+		//child has no pending defers.
+		assert_eq!(log()?, "(dialogue camera)");
+		Ok(())
+	});
+}
+
+#[test]
+fn error_exit() {
+	run(|| {
+		load(r#"
+			(defn failing ()
+			  (with-children
+			    (coro-run (helper 'a))
+			    (yield)
+			    (bail "cutscene failed")))
+
+			(let co (failing))
+			(coro-run co)
+			(let (tag payload) (try (coro-run co)))
+			(ensure (eq? tag 'err))
+			(ensure (eq? payload "cutscene failed"))
+		"#)?;
+
+		assert_eq!(log()?, "(a)");
+		Ok(())
+	});
+}
+
+#[test]
+fn cancellation() {
+	run(|| {
+		load(r#"
+			(defn cutscene ()
+			  (with-children
+			    (coro-run (helper 'a))
+			    (coro-run (helper 'b))
+			    (loop (yield))))
+
+			;the parent is cancelled while it's paused
+			(let co (cutscene))
+			(coro-run co)
+			(let (tag payload) (try (coro-throw co 'cancelled)))
+			(ensure (eq? payload 'cancelled))
+			(ensure (eq? defer-log '(b a)))
+
+			;the parent is garbage-collected while it's paused
+			(def collected (cutscene))
+			(coro-run collected)
+		"#)?;
+
+		glsp::load_str("(= collected #n)", "test.glsp")?;
+		glsp::gc();
+		assert_eq!(log()?, "(b a b a)");
+
+		//a child can't refuse to be cancelled. coro-throw doesn't resume it, so it has no
+		//opportunity to keep yielding.
+		glsp::load_str(r#"
+			(clear! defer-log)
+
+			(defn stubborn ()
+			  (defer (push! defer-log 'stubborn))
+			  (loop
+			    (yield)
+			    (push! defer-log 'resumed)))
+
+			(defn parent ()
+			  (let child #n)
+			  (with-children
+			    (= child (stubborn))
+			    (coro-run child))
+			  child)
+
+			(let child (parent))
+			(ensure (eq? (coro-state child) 'poisoned))
+			(ensure (eq? defer-log '(stubborn)))
+		"#, "test.glsp")?;
+
+		//nor can a child which catches 'cancelled errors. (try) wraps its body in a separate fn,
+		//so the child's (yield) is never inside a (try), and only its defers see the error.
+		glsp::load_str(r#"
+			(clear! defer-log)
+
+			(defn catching ()
+			  (defer (push! defer-log 'catching))
+			  (loop
+			    (let (tag payload) (try (bail 'cancelled)))
+			    (push! defer-log (arr tag payload))
+			    (yield)))
+
+			(defn catching-parent ()
+			  (let child #n)
+			  (with-children
+			    (= child (catching))
+			    (coro-run child)
+			    (coro-run child))
+			  child)
+
+			(let child (catching-parent))
+			(ensure (eq? (coro-state child) 'poisoned))
+			(ensure (eq? defer-log '((err cancelled) (err cancelled) catching)))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn nesting() {
+	run(|| {
+		load(r#"
+			(defn with-grandchild (name)
+			  (with-children
+			    (coro-run (helper name))
+			    (loop (yield))))
+
+			(defn parent ()
+			  (with-children
+			    (coro-run (helper 'outer))
+			    (with-children
+			      (coro-run (helper 'inner))
+			      (yield)
+			      (push! defer-log 'inner-exit))
+			    (ensure (eq? defer-log '(inner-exit inner)))
+
+			    ;cancelling a child also closes the child's own scope
+			    (coro-run (with-grandchild 'grandchild))
+			    (yield)
+			    (push! defer-log 'outer-exit)))
+
+			(let co (parent))
+			(coro-run co)
+			(coro-run co)
+			(ensure (eq? (coro-state co) 'paused))
+			(coro-run co)
+			(ensure (eq? (coro-state co) 'finished))
+
+			;scopes can also be used outside of a coroutine
+			(with-children
+			  (coro-run (helper 'toplevel)))
+		"#)?;
+
+		assert_eq!(log()?, "(inner-exit inner outer-exit grandchild outer toplevel)");
+		Ok(())
+	});
+}
+
+#[test]
+fn aggregated_errors() {
+	run(|| {
+		load(r#"
+			(defn failing-helper (message)
+			  (defer (bail message))
+			  (loop (yield)))
+
+			(defn parent ()
+			  (with-children
+			    (coro-run (failing-helper "first failed"))
+			    (coro-run (helper 'quiet))
+			    (coro-run (failing-helper "second failed"))
+			    (yield)))
+
+			(let co (parent))
+			(coro-run co)
+			(let (tag payload) (try (coro-run co)))
+			(ensure (eq? tag 'err))
+			(ensure (eq? payload '(child-errors "second failed" "first failed")))
+			(ensure (eq? defer-log '(quiet)))
+			(ensure (eq? (coro-state co) 'poisoned))
+		"#)?;
+
+		Ok(())
+	});
+}
@@ -0,0 +1,192 @@
+//! Checks when a `(defer)` inside a coroutine is run: when its scope exits normally, when the
+//! coroutine fails, when it's finished by `coro-finish!` or `coro-throw`, and when a paused
+//! coroutine is garbage-collected.
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+use glsp::{PrintLevel};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+//each test logs its defers to the global `defer-log`, an arr of syms
+const PRELUDE: &str = r#"
+	(def defer-log (arr))
+
+	(defn deferring (name)
+	  (defer (push! defer-log name))
+	  (yield 'first)
+	  (yield 'second)
+	  'done)
+"#;
+
+fn load(text: &str) -> GResult<Val> {
+	glsp::load_str(PRELUDE, "prelude.glsp")?;
+	glsp::load_str(text, "test.glsp")
+}
+
+fn log() -> GResult<String> {
+	let log: Val = glsp::global("defer-log")?;
+	Ok(log.to_string())
+}
+
+#[test]
+fn normal_completion() {
+	run(|| {
+		load(r#"
+			(let co (deferring 'a))
+			(coro-run co)
+			(coro-run co)
+			(ensure (== (len defer-log) 0))
+			(ensure (eq? (coro-run co) 'done))
+			(ensure (eq? (coro-state co) 'finished))
+		"#)?;
+
+		assert_eq!(log()?, "(a)");
+		Ok(())
+	});
+}
+
+#[test]
+fn error() {
+	run(|| {
+		load(r#"
+			(defn failing ()
+			  (defer (push! defer-log 'b))
+			  (yield)
+			  (bail "deliberate failure"))
+
+			(let co (failing))
+			(coro-run co)
+			(ensure (eq? [(try (coro-run co)) 0] 'err))
+			(ensure (eq? (coro-state co) 'poisoned))
+		"#)?;
+
+		assert_eq!(log()?, "(b)");
+		Ok(())
+	});
+}
+
+#[test]
+fn finish() {
+	run(|| {
+		load(r#"
+			(let co (deferring 'c))
+			(coro-run co)
+			(coro-finish! co)
+			(ensure (eq? (coro-state co) 'finished))
+		"#)?;
+
+		assert_eq!(log()?, "(c)");
+		Ok(())
+	});
+}
+
+#[test]
+fn throw() {
+	run(|| {
+		load(r#"
+			(let co (deferring 'd))
+			(coro-run co)
+			(let (tag payload) (try (coro-throw co 'cancelled)))
+			(ensure (eq? tag 'err))
+			(ensure (eq? payload 'cancelled))
+			(ensure (eq? (coro-state co) 'poisoned))
+
+			(let newborn (deferring 'never))
+			(try (coro-throw newborn 'cancelled))
+			(ensure (eq? (coro-state newborn) 'poisoned))
+		"#)?;
+
+		assert_eq!(log()?, "(d)");
+		Ok(())
+	});
+}
+
+#[test]
+fn collected_while_young() {
+	run(|| {
+		load(r#"
+			(let co (deferring 'e))
+			(coro-run co)
+		"#)?;
+
+		//the coro is only referenced by a toplevel local, which is unreachable once the file has
+		//finished loading
+		glsp::gc();
+		assert_eq!(log()?, "(e)");
+
+		glsp::gc();
+		assert_eq!(log()?, "(e)");
+		Ok(())
+	});
+}
+
+#[test]
+fn collected_while_old() {
+	run(|| {
+		load(r#"
+			(def co (deferring 'f))
+			(coro-run co)
+		"#)?;
+
+		//promote the coro to the old generation, and then make it unreachable
+		glsp::gc();
+		glsp::del_global("co")?;
+
+		//a collection cycle only ends once a minimum amount of memory has survived it
+		glsp::load_str("(def ballast (arr))", "ballast.glsp")?;
+		for _ in 0 .. 1000 {
+			glsp::load_str("(push! ballast (arr ..(rn 256)))", "ballast.glsp")?;
+			glsp::gc();
+
+			if log()? != "()" {
+				break
+			}
+		}
+
+		assert_eq!(log()?, "(f)");
+		Ok(())
+	});
+}
+
+#[test]
+fn collected_with_an_error() {
+	run(|| {
+		let lines = Rc::new(RefCell::new(Vec::<(PrintLevel, String)>::new()));
+		let sink_lines = lines.clone();
+		glsp::set_print_sink(Box::new(move |level, line| {
+			sink_lines.borrow_mut().push((level, line.to_string()));
+		}));
+
+		load(r#"
+			(let co ((fn ()
+			  (defer (bail "failure in a defer"))
+			  (yield))))
+			(coro-run co)
+		"#)?;
+
+		glsp::gc();
+
+		assert!(lines.borrow().iter().any(|(level, line)| {
+			*level == PrintLevel::Error && line.contains("failure in a defer")
+		}));
+
+		Ok(())
+	});
+}
+
+#[test]
+fn defer_which_yields() {
+	run(|| {
+		let result = load(r#"
+			(defn yielding ()
+			  (defer (yield 1))
+			  (yield 2))
+		"#);
+
+		assert!(result.is_err());
+		Ok(())
+	});
+}
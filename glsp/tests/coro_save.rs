@@ -0,0 +1,207 @@
+//! Checks that `(coro-save)` and `(coro-restore)` round-trip a paused coroutine through text,
+//! including into a fresh Runtime, that unsaveable coroutines are rejected with a reason, and
+//! that restoring fails when the coroutine's function has been modified.
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+
+fn eval<T: FromVal>(src: &str) -> GResult<T> {
+	T::from_val(&glsp::load_str(src, "test.glsp")?)
+}
+
+const QUEST: &str = r#"
+	(defn quest (name)
+	  (defer (push! quest-log 'done))
+	  (let gold 0)
+	  (push! quest-log name)
+	  (push! quest-log (yield 'talk))
+	  (inc! gold 10)
+	  (let items (arr 'sword))
+	  (yield gold)
+	  (push! items 'shield)
+	  (push! quest-log (len items))
+	  (yield items)
+	  (+ gold 1))
+"#;
+
+#[test]
+fn round_trip() {
+	//save the quest in one Runtime, after it's received its first resume argument
+	let saved = run(|| {
+		glsp::load_str("(def quest-log (arr))", "test.glsp")?;
+		glsp::load_str(QUEST, "quest.glsp")?;
+		glsp::load_str("(def co (quest 'mine))", "test.glsp")?;
+
+		let coro: Root<Coro> = glsp::global("co")?;
+		assert_eq!(eval::<Sym>("(coro-run co)")?, glsp::sym("talk")?);
+		assert_eq!(eval::<i32>("(coro-run co 'hello)")?, 10);
+		assert!(coro.check_saveability().is_ok());
+
+		let data = glsp::coro_save(&coro)?;
+		assert!(Val::Tab(data.clone()).check_representability().is_ok());
+
+		Ok(data.to_string())
+	});
+
+	//restore it in a fresh Runtime
+	run(|| {
+		glsp::load_str("(def quest-log (arr))", "test.glsp")?;
+		glsp::load_str(QUEST, "quest.glsp")?;
+
+		let data: Root<Tab> = Root::<Tab>::from_val(&glsp::parse_1(&saved, None)?)?;
+		let coro = glsp::coro_restore(&data)?;
+		glsp::bind_global("co", coro.clone())?;
+
+		assert_eq!(coro.state(), CoroState::Paused);
+		assert_eq!(eval::<String>("(str (coro-run co))")?, "(sword shield)");
+		assert_eq!(eval::<i32>("(coro-run co)")?, 11);
+		assert_eq!(coro.state(), CoroState::Finished);
+
+		//the log only records the events which happened after the coroutine was restored
+		assert_eq!(eval::<String>("(str quest-log)")?, "(2 done)");
+
+		Ok(())
+	});
+}
+
+#[test]
+fn glsp_api() {
+	run(|| {
+		glsp::load_str("(def quest-log (arr))", "test.glsp")?;
+		glsp::load_str(QUEST, "quest.glsp")?;
+
+		let result: String = eval(r#"
+			(let co (quest 'script))
+			(coro-run co)
+			(coro-run co 'hi)
+
+			(let copy (coro-restore (coro-save co)))
+			(let results (arr (coro-run copy) (coro-run copy)))
+
+			; the original coroutine is unaffected by the copy
+			(push! results (coro-run co))
+			(str results quest-log)
+		"#)?;
+
+		assert_eq!(result, "((sword shield) 11 (sword shield)) (script hi 2 done 2)");
+
+		//newborn coroutines can be saved, too
+		let result: i32 = eval(r#"
+			(let co (coro-restore (coro-save (quest 'newborn))))
+			(coro-run co)
+			(coro-run co)
+		"#)?;
+
+		assert_eq!(result, 10);
+
+		Ok(())
+	});
+}
+
+#[test]
+fn unsaveable() {
+	run(|| {
+		glsp::load_str("(def quest-log (arr))", "test.glsp")?;
+		glsp::load_str(QUEST, "quest.glsp")?;
+		glsp::load_str(r#"
+			(defn holds-obj ()
+			  (let ob (Obj))
+			  (yield)
+			  ob)
+
+			(defclass Obj)
+
+			(defn make-closure (n)
+			  (fn ()
+			    (yield n)))
+
+			(defn holds-closure ()
+			  (let f (fn () 1))
+			  (yield (f)))
+		"#, "unsaveable.glsp")?;
+
+		let reason = |src: &str| -> GResult<String> {
+			let coro: Root<Coro> = eval(src)?;
+			Ok(coro.check_saveability().unwrap_err().val().to_string())
+		};
+
+		assert!(reason("(let co (holds-obj)) (coro-run co) co")?.contains("objects are non-representable"));
+		assert!(reason("(let co (holds-closure)) (coro-run co) co")?.contains("functions are non-representable"));
+		assert!(reason("((make-closure 5))")?.contains("anonymous"));
+		assert!(reason("(let co (quest 'a)) (coro-finish! co) co")?.contains("finished"));
+
+		let saveable: bool = eval(r#"
+			(let co (holds-obj))
+			(coro-run co)
+			(coro-saveable? co)
+		"#)?;
+		assert!(!saveable);
+
+		//the global has been rebound to a different fn since the coroutine was created
+		let err = eval::<Val>(r#"
+			(let co (quest 'b))
+			(= quest (fn (name) (yield name)))
+			(coro-save co)
+		"#).unwrap_err();
+		assert!(err.val().to_string().contains("isn't bound to the global quest"));
+
+		Ok(())
+	});
+}
+
+#[test]
+fn modified_fn() {
+	run(|| {
+		glsp::load_str("(def quest-log (arr))", "test.glsp")?;
+		glsp::load_str(QUEST, "quest.glsp")?;
+		glsp::load_str(r#"
+			(def co (quest 'c))
+			(coro-run co)
+			(def data (coro-save co))
+		"#, "test.glsp")?;
+
+		//redefining the function identically is fine
+		glsp::load_str("(del-global! 'quest)", "test.glsp")?;
+		glsp::load_str(QUEST, "quest.glsp")?;
+		assert_eq!(eval::<i32>("(coro-run (coro-restore data) 'x)")?, 10);
+
+		//but changing its body isn't
+		glsp::load_str(r#"
+			(del-global! 'quest)
+			(defn quest (name)
+			  (yield 'talk)
+			  (yield 'bye))
+		"#, "quest.glsp")?;
+
+		let err = eval::<Val>("(coro-restore data)").unwrap_err();
+		assert!(err.val().to_string().contains("has been modified"));
+
+		glsp::load_str(r#"
+			(del-global! 'quest)
+			(defn quest (name)
+			  (defer (push! quest-log 'done))
+			  (let gold 0)
+			  (push! quest-log name)
+			  (push! quest-log (yield 'talk))
+			  (dec! gold 10)
+			  (let items (arr 'sword))
+			  (yield gold)
+			  (push! items 'shield)
+			  (push! quest-log (len items))
+			  (yield items)
+			  (+ gold 1))
+		"#, "quest.glsp")?;
+
+		//the same number of instrs, with different contents
+		let err = eval::<Val>("(coro-restore data)").unwrap_err();
+		assert!(err.val().to_string().contains("no longer match"));
+
+		glsp::load_str("(= quest 10)", "quest.glsp")?;
+		let err = eval::<Val>("(coro-restore data)").unwrap_err();
+		assert!(err.val().to_string().contains("rather than a coroutine fn"));
+
+		Ok(())
+	});
+}
@@ -0,0 +1,243 @@
+//! Checks CSV parsing and emission: RFC 4180 quoting, embedded line breaks, custom delimiters,
+//! header rows, error positions, the lazy `(csv-parse-lines)` iterator, and round trips
+//! through `(csv-emit)`.
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+use glsp::{CsvOpts};
+
+fn eval<T: FromVal>(src: &str) -> GResult<T> {
+	T::from_val(&glsp::load_str(src, "test.glsp")?)
+}
+
+//parses `text` with (csv-parse), passing any extra arguments, and prints the resulting rows
+fn parse(text: &str, args: &str) -> GResult<String> {
+	glsp::bind_global("text", text)?;
+	let result = eval(&format!("(str (arr ..(csv-parse text {})))", args));
+	glsp::del_global("text")?;
+	result
+}
+
+//tabs are printed in an unspecified order, so rows with headers are compared using (eq?)
+fn parse_eq(text: &str, args: &str, expected: &str) -> GResult<bool> {
+	glsp::bind_global("text", text)?;
+	let result = eval(&format!("(eq? (arr ..(csv-parse text {})) '{})", args, expected));
+	glsp::del_global("text")?;
+	result
+}
+
+fn parse_err(text: &str, args: &str) -> String {
+	parse(text, args).unwrap_err().val().to_string()
+}
+
+#[test]
+fn quoting() {
+	run(|| {
+		assert_eq!(parse("a,b,c\n1,2,3\n", "")?, r#"(("a" "b" "c") ("1" "2" "3"))"#);
+
+		//the final line terminator is optional, and \r\n is accepted
+		assert_eq!(parse("a,b\r\n1,2", "")?, r#"(("a" "b") ("1" "2"))"#);
+
+		//quoted delimiters and doubled quotes
+		assert_eq!(parse("\"Orc, Large\",\"say \"\"hi\"\"\",plain", "")?,
+		           r#"(("Orc, Large" "say \"hi\"" "plain"))"#);
+
+		//empty fields, quoted and unquoted
+		assert_eq!(parse("a,,\"\",\n", "")?, r#"(("a" "" "" ""))"#);
+		assert_eq!(parse("\"\"\n", "")?, r#"((""))"#);
+
+		//a quoted field can contain line breaks, which are normalized to \n
+		assert_eq!(parse("\"two\nlines\",x\r\n\"crlf\r\nbreak\",y\n", "")?,
+		           r#"(("two\nlines" "x") ("crlf\nbreak" "y"))"#);
+		assert_eq!(parse("\"\n\n\",z", "")?, r#"(("\n\n" "z"))"#);
+
+		//blank lines between records are skipped
+		assert_eq!(parse("a\n\n\nb\n", "")?, r#"(("a") ("b"))"#);
+		assert_eq!(parse("", "")?, "()");
+
+		//records may have different lengths when there's no header row
+		assert_eq!(parse("a\nb,c\n", "")?, r#"(("a") ("b" "c"))"#);
+
+		Ok(())
+	});
+}
+
+#[test]
+fn options() {
+	run(|| {
+		assert_eq!(parse("a;\"b;c\";d,e\n", r"':delimiter \;")?, r#"(("a" "b;c" "d,e"))"#);
+		assert_eq!(parse("a\tb\n", r"':delimiter \tab")?, r#"(("a" "b"))"#);
+
+		assert!(parse_eq("name,hp\n\"Orc, Large\",30\ngoblin,5\n", "':headers #t",
+		                 r#"(#((name "Orc, Large") (hp "30")) #((name "goblin") (hp "5")))"#)?);
+
+		let sum: i32 = eval(r#"
+			(let total 0)
+			(for row in (csv-parse "name,hp\na,10\nb,20\n" ':headers #t)
+			  (inc! total (int (parse-1 [row 'hp]))))
+			total
+		"#)?;
+		assert_eq!(sum, 30);
+
+		//a header row with no records produces no rows
+		assert_eq!(parse("name,hp\n", "':headers #t")?, "()");
+
+		assert!(parse_err("a,b\n", "':delimiter \\\"").contains("can't be used as a CSV delimiter"));
+		assert!(parse_err("a,b\n", "':separator \\;").contains("unrecognized keyword :separator"));
+
+		Ok(())
+	});
+}
+
+#[test]
+fn errors() {
+	run(|| {
+		assert_eq!(parse_err("a,b\nc,d\"e\n", ""),
+		           "invalid CSV in record 2, field 2: unexpected quote in an unquoted field");
+		assert_eq!(parse_err("a,\"b\"c\n", ""),
+		           "invalid CSV in record 1, field 2: unexpected 'c' after a closing quote");
+		assert_eq!(parse_err("a\n\nb,c,\"unfinished\nstill going\n", ""),
+		           "invalid CSV in record 2, field 3: the input ended within a quoted field");
+
+		//header errors
+		assert_eq!(parse_err("name,hp\na,1,extra\n", "':headers #t"),
+		           "invalid CSV in record 2: it has 3 fields, but the header row has 2");
+		assert_eq!(parse_err("name,max hp\n", "':headers #t"),
+		           "invalid CSV in record 1, field 2: the header \"max hp\" isn't a valid sym");
+		assert_eq!(parse_err("hp,hp\n", "':headers #t"),
+		           "invalid CSV in record 1, field 2: the header \"hp\" appears more than once");
+
+		//rows are parsed lazily, so the rows before an error are still produced
+		let first: String = eval(r#"
+			(let rows (csv-parse "a\nb\n\"c\n"))
+			(let first (iter-next! rows))
+			(let second (iter-next! rows))
+			(let (tag _) (try (iter-next! rows)))
+			(str first second tag)
+		"#)?;
+		assert_eq!(first, r#"("a") ("b") err"#);
+
+		Ok(())
+	});
+}
+
+#[test]
+fn parse_lines() {
+	run(|| {
+		glsp::bind_global("text", "name,notes\nsword,\"sharp,\nshiny\"\r\nshield,\"\"\n")?;
+
+		let rows: bool = eval(r#"
+			(eq? (arr ..(csv-parse-lines (lines text) ':headers #t))
+			     '(#((name "sword") (notes "sharp,\nshiny")) #((name "shield") (notes ""))))
+		"#)?;
+		assert!(rows);
+
+		//any iterable of strs can be parsed
+		let rows: String = eval(r#"(str (arr ..(csv-parse-lines '("a;b" "\"c" "d\";e") ':delimiter \;)))"#)?;
+		assert_eq!(rows, r#"(("a" "b") ("c\nd" "e"))"#);
+
+		//lines are consumed lazily
+		let consumed: String = eval(r#"
+			(let consumed (arr))
+			(let src (map (fn (line) (push! consumed line) line) '("a" "b" "c")))
+			(let rows (csv-parse-lines src))
+			(iter-next! rows)
+			(str consumed)
+		"#)?;
+		assert_eq!(consumed, r#"("a")"#);
+
+		let err = eval::<Val>("(arr ..(csv-parse-lines '(\"a\" 10)))").unwrap_err();
+		assert!(err.val().to_string().contains("expected an iterator of strs, but it produced an int"));
+
+		let err = eval::<Val>("(arr ..(csv-parse-lines '(\"a,\\\"b\")))").unwrap_err();
+		assert!(err.val().to_string().contains("record 1, field 2: the input ended within"));
+
+		Ok(())
+	});
+}
+
+#[test]
+fn emit() {
+	run(|| {
+		let text: String = eval(r#"
+			(csv-emit '(("Orc, Large" "say \"hi\"" plain)
+			            (1 2.5 \c #t #n)
+			            ("two\nlines")
+			            ("")
+			            ()))
+		"#)?;
+		assert_eq!(text, "\"Orc, Large\",\"say \"\"hi\"\"\",plain\n1,2.5,c,#t,\n\"two\nlines\"\n\"\"\n\n");
+
+		let text: String = eval(r#"
+			(csv-emit (arr (tab ('name "goblin") ('hp 5)) (tab ('name "orc")))
+			          ':headers '(name hp))
+		"#)?;
+		assert_eq!(text, "name,hp\ngoblin,5\norc,\n");
+
+		let text: String = eval(r#"(csv-emit '((a "b;c")) ':delimiter \;)"#)?;
+		assert_eq!(text, "a;\"b;c\"\n");
+
+		//emitting and then parsing is lossless for rows of strs
+		let round_trip: bool = eval(r#"
+			(let rows '(("a" "b,c" "\"q\"") ("" "two\nlines" "x") ("") (" padded " "," "\r")))
+			(eq? rows (arr ..(csv-parse (csv-emit rows))))
+		"#)?;
+		assert!(round_trip);
+
+		let round_trip: bool = eval(r#"
+			(let rows (arr (tab ('id "1") ('name "a, b")) (tab ('id "2") ('name ""))))
+			(eq? rows (arr ..(csv-parse (csv-emit rows ':headers '(id name)) ':headers #t)))
+		"#)?;
+		assert!(round_trip);
+
+		let err = eval::<Val>("(csv-emit '((1 2) (3 (4))))").unwrap_err();
+		assert_eq!(err.val().to_string(), "(csv-emit) received an arr in record 2, field 2: \
+		                                   expected a str, sym, char, number, bool or nil");
+
+		let err = eval::<Val>("(csv-emit (arr (tab ('a 1))))").unwrap_err();
+		assert!(err.val().to_string().contains("no :headers were given"));
+
+		Ok(())
+	});
+}
+
+#[test]
+fn rust_api() {
+	run(|| {
+		let text = "name,hp\n\"Orc, Large\",30\n\"multi\nline\",5\n";
+		let lines = text.lines().map(|line| Ok(line.to_string()));
+		let records = glsp::csv_records(lines, ',')?.collect::<GResult<Vec<_>>>()?;
+		assert_eq!(records, vec![
+			vec!["name".to_string(), "hp".to_string()],
+			vec!["Orc, Large".to_string(), "30".to_string()],
+			vec!["multi\nline".to_string(), "5".to_string()]
+		]);
+
+		let mut dst = String::new();
+		for record in &records {
+			glsp::csv_write_record(&mut dst, record, ',')?;
+		}
+		assert_eq!(dst, text);
+
+		assert!(glsp::csv_write_record(&mut dst, &["a"], '"').is_err());
+		assert!(glsp::csv_records(text.lines().map(|line| Ok(line.to_string())), '\n').is_err());
+
+		let opts = CsvOpts { delimiter: ',', headers: true };
+		let mut rows = glsp::csv_parse(text, opts)?;
+		let first: Root<Tab> = Root::<Tab>::from_val(&rows.next().unwrap()?)?;
+		assert_eq!(first.get::<_, String>(glsp::sym("hp")?)?, "30");
+
+		let lines: Vec<&str> = vec!["a,b", "\"c", "\",d"];
+		let records = glsp::csv_records(lines.into_iter().map(|l| Ok(l.to_string())), ',')?
+			.collect::<GResult<Vec<_>>>()?;
+		assert_eq!(records[1], vec!["c\n".to_string(), "d".to_string()]);
+
+		let err = glsp::csv_records(vec![Ok("a,b\"".to_string())].into_iter(), ',')?
+			.next().unwrap().unwrap_err();
+		assert!(err.val().to_string().contains("record 1, field 2"));
+
+		Ok(())
+	});
+}
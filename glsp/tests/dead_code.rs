@@ -0,0 +1,117 @@
+//! Checks that `glsp::dead_code_report` finds the `defn`, `defmacro` and `defclass` forms which
+//! can't be reached from the host's entry points, and that dynamic global lookups make the
+//! report uncertain.
+
+#![cfg(feature = "compiler")]
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+use glsp::{DeadDef};
+
+fn names(report: &[DeadDef]) -> Vec<String> {
+	report.iter().map(|def| format!("{} {}", def.kind(), def.name())).collect()
+}
+
+fn sym(name: &str) -> Sym {
+	glsp::sym(name).unwrap()
+}
+
+const ENEMIES: &str = r#"
+	(defn spawn-enemy ()
+	  (Enemy))
+
+	(defclass Enemy
+	  (meth on-step ()
+	    (wander)))
+
+	(defn wander ())
+
+	(defn spawn-boss ()
+	  (boss-intro))
+
+	(defn boss-intro ())
+
+	(defmacro unused-mac ()
+	  #n)
+
+	(defmacro used-mac ()
+	  `(helper))
+
+	(defn helper ())
+
+	(defn main ()
+	  (spawn-enemy)
+	  (used-mac))
+
+	(def startup (prn "loading"))
+"#;
+
+#[test]
+fn reachability() {
+	run(|| {
+		let (_, bytes) = glsp::load_and_compile_str(ENEMIES, "enemies.glsp")?;
+
+		let report = glsp::dead_code_report(&[&bytes[..]], &[sym("main")])?;
+		assert_eq!(names(&report), ["defn spawn-boss", "defn boss-intro", "defmacro unused-mac"]);
+		assert!(report.iter().all(|def| def.is_certain()));
+
+		let location = report[0].location().unwrap();
+		assert_eq!((location.filename(), location.line()), (Some("enemies.glsp"), Some(11)));
+
+		let text = glsp::dead_code_text(&report);
+		assert!(text.starts_with("enemies.glsp:11: (defn spawn-boss)\n"), "{}", text);
+		assert!(!text.contains("note:"), "{}", text);
+
+		//with no entry points, only the toplevel (def) keeps anything alive
+		let report = glsp::dead_code_report(&[&bytes[..]], &[])?;
+		assert_eq!(report.len(), 9);
+
+		//a toplevel call is a root, and entry points can be shared between several recordings
+		let (_, other) = glsp::load_and_compile_str("(spawn-boss)", "other.glsp")?;
+		let report = glsp::dead_code_report(&[&bytes[..], &other[..]], &[sym("main")])?;
+		assert_eq!(names(&report), ["defmacro unused-mac"]);
+
+		Ok(())
+	});
+}
+
+#[test]
+fn dynamic_access() {
+	run(|| {
+		let src = r#"
+			(defn greet ())
+
+			(defn main (name)
+			  ((global (sym name))))
+		"#;
+		let (_, bytes) = glsp::load_and_compile_str(src, "dynamic.glsp")?;
+
+		let report = glsp::dead_code_report(&[&bytes[..]], &[sym("main")])?;
+		assert_eq!(names(&report), ["defn greet"]);
+		assert!(!report[0].is_certain());
+		assert_eq!(report[0].dynamic_accesses()[0].line(), Some(5));
+
+		let text = glsp::dead_code_text(&report);
+		assert!(text.contains("note:"), "{}", text);
+		assert!(text.contains("dynamic.glsp:5"), "{}", text);
+
+		//a dynamic lookup in unreachable code doesn't make the report uncertain
+		let report = glsp::dead_code_report(&[&bytes[..]], &[])?;
+		assert_eq!(names(&report), ["defn greet", "defn main"]);
+		assert!(report.iter().all(|def| def.is_certain()));
+
+		Ok(())
+	});
+
+	//literal lookups are ordinary references
+	run(|| {
+		let src = "(defn target ()) (defn main () ((global 'target)))";
+		let (_, bytes) = glsp::load_and_compile_str(src, "literal.glsp")?;
+		let report = glsp::dead_code_report(&[&bytes[..]], &[sym("main")])?;
+		assert!(report.is_empty());
+
+		Ok(())
+	});
+}
@@ -0,0 +1,123 @@
+//! Checks that constants bound by `defconst` can't be rebound or mutated, that the compiler 
+//! inlines their values, and that they can still be replaced by reloading or `redefine-const!`.
+
+mod common;
+
+use common::run;
+use std::fs;
+
+#[test]
+fn immutable() {
+	run(|| {
+		glsp::load_str(r#"
+			(defconst gravity 9.81)
+			(ensure (== gravity 9.81))
+			(ensure (const? 'gravity))
+			(ensure (not (const? 'prn)))
+			(ensure (not (const? 'no-such-global)))
+
+			;statically detectable assignments fail when they're compiled
+			(ensure (eq? [(try (eval '(= gravity 1.0))) 0] 'err))
+			(ensure (eq? [(try (eval '(fn () (= gravity 1.0)))) 0] 'err))
+			(ensure (eq? [(try (eval '(def gravity 1.0))) 0] 'err))
+			(ensure (eq? [(try (eval '(defn gravity () 1.0))) 0] 'err))
+
+			;everything else fails at runtime
+			(ensure (eq? [(try (global= 'gravity 1.0)) 0] 'err))
+			(ensure (eq? [(try (bind-global! 'gravity 1.0)) 0] 'err))
+			(ensure (eq? [(try (del-global! 'gravity)) 0] 'err))
+			(ensure (eq? [(try (eval '(defconst gravity 1.0))) 0] 'err))
+			(ensure (== gravity 9.81))
+
+			;a local variable can shadow a constant
+			(let gravity 1.0)
+			(= gravity 2.0)
+			(ensure (== gravity 2.0))
+
+			(in-module 'physics)
+			(defconst drag 0.5)
+			(in-module #n)
+			(ensure (const? 'physics:drag))
+		"#, "test.glsp")?;
+
+		assert!(glsp::is_const("gravity")?);
+		assert!(glsp::set_global("gravity", 1.0).is_err());
+
+		Ok(())
+	});
+}
+
+#[test]
+fn inlined() {
+	run(|| {
+		glsp::load_str(r#"
+			(defconst speed 10)
+			(defn get-speed () speed)
+
+			(defconst names (arr 'a 'b))
+			(defn get-names () names)
+
+			;primitive values are baked into code which was compiled earlier
+			(redefine-const! 'speed 20)
+			(ensure (== speed 20))
+			(ensure (== (get-speed) 10))
+
+			;other values are always looked up
+			(redefine-const! 'names (arr 'c))
+			(ensure (eq? (get-names) '(c)))
+
+			(ensure (eq? [(try (redefine-const! 'prn 1)) 0] 'err))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn reload() {
+	run(|| {
+		let path = std::env::temp_dir().join(format!("glsp-defconst-{}.glsp", std::process::id()));
+		let filename = path.to_str().unwrap().to_string();
+
+		fs::write(&path, "(defconst max-hp 100)").unwrap();
+		let first = glsp::load(&filename);
+
+		fs::write(&path, "(defconst max-hp 150)").unwrap();
+		let second = glsp::reload(&filename);
+		fs::remove_file(&path).unwrap();
+
+		first?;
+		second?;
+		assert_eq!(glsp::global::<_, i32>("max-hp")?, 150);
+		assert!(glsp::is_const("max-hp")?);
+
+		Ok(())
+	});
+}
+
+#[cfg(feature = "compiler")]
+#[test]
+fn compiled() {
+	let src = r#"
+		(defconst tile-size 16)
+		(defn tiles-to-pixels (n)
+		  (* n tile-size))
+	"#;
+
+	let bytes = run(|| {
+		let (_, bytes) = glsp::load_and_compile_str(src, "main.glsp")?;
+		Ok(bytes)
+	});
+
+	run(move || {
+		glsp::load_compiled(&bytes)?;
+
+		glsp::load_str(r#"
+			(ensure (const? 'tile-size))
+			(ensure (== (tiles-to-pixels 3) 48))
+			(ensure (eq? [(try (global= 'tile-size 8)) 0] 'err))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
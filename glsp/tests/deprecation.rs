@@ -0,0 +1,106 @@
+//! Checks that a deprecated global forwards its calls to the global which replaced it, warning
+//! once for each callsite, and that `deprecations_hit` reports the names which were used.
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+use glsp::{PrintLevel};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+//captures every warning printed while `f` is running
+fn warnings<F: FnOnce() -> GResult<()>>(f: F) -> GResult<Vec<String>> {
+	let lines = Rc::new(RefCell::new(Vec::<String>::new()));
+	let sink_lines = lines.clone();
+	glsp::set_print_sink(Box::new(move |level, line| {
+		if level == PrintLevel::Warn {
+			sink_lines.borrow_mut().push(line.to_string());
+		}
+	}));
+
+	let result = f();
+	glsp::set_print_sink(Box::new(|_, line| eprintln!("{}", line)));
+	result?;
+
+	let lines = lines.borrow().clone();
+	Ok(lines)
+}
+
+#[test]
+fn scripts() {
+	run(|| {
+		let lines = warnings(|| {
+			glsp::load_str(r#"
+				(defn sprite-draw (x y ..rest)
+				  (+ x y (len rest)))
+
+				(deprecate 'draw-sprite 'sprite-draw "it will be removed in 0.4")
+				(deprecate 'unused-name 'sprite-draw)
+				(ensure (eq? (deprecations-hit) '()))
+
+				(ensure (eq? (fn-name draw-sprite) 'draw-sprite))
+				(ensure (eq? (arg-limits draw-sprite) (arg-limits sprite-draw)))
+
+				(forn (i 3)
+				  (ensure (== (draw-sprite 1 2) 3)))
+				(ensure (== (apply draw-sprite '(1 2 a b)) 5))
+				(ensure (eq? [(try (draw-sprite 1)) 0] 'err))
+
+				;the old name forwards to the new name's current value
+				(= sprite-draw (fn (x y) (* x y)))
+				(ensure (== (draw-sprite 3 4) 12))
+
+				(ensure (eq? (deprecations-hit) '(draw-sprite)))
+			"#, "test.glsp")?;
+			Ok(())
+		})?;
+
+		assert_eq!(lines.len(), 4, "{:?}", lines);
+		assert_eq!(lines[0], "test.glsp:13: draw-sprite is deprecated, use sprite-draw instead: \
+		                      it will be removed in 0.4");
+		assert!(lines[1].starts_with("test.glsp:14: "), "{:?}", lines);
+		assert!(lines[2].starts_with("test.glsp:15: "), "{:?}", lines);
+		assert!(lines[3].starts_with("test.glsp:19: "), "{:?}", lines);
+
+		//invalid deprecations
+		glsp::load_str(r#"
+			(def not-callable 10)
+			(ensure (eq? [(try (deprecate 'foo 'not-callable)) 0] 'err))
+			(ensure (eq? [(try (deprecate 'foo 'no-such-global)) 0] 'err))
+			(ensure (eq? [(try (deprecate 'sprite-draw 'sprite-draw)) 0] 'err))
+			(ensure (not (has-global? 'foo)))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
+
+fn clamp(x: i32, min: i32, max: i32) -> i32 {
+	x.max(min).min(max)
+}
+
+#[test]
+fn rust_api() {
+	run(|| {
+		glsp::bind_rfn("clamp-i32", rfn!(clamp))?;
+		glsp::bind_global("clip", 0)?;
+
+		let lines = warnings(|| {
+			let rfn = glsp::bind_deprecated("clip", "clamp-i32", "")?;
+			assert_eq!(rfn.arg_limits(), (3, Some(3)));
+
+			let result: i32 = glsp::call(&rfn, &(15, 0, 10))?;
+			assert_eq!(result, 10);
+
+			glsp::load_str("(ensure (== (clip -5 0 10) 0))", "test.glsp")?;
+			Ok(())
+		})?;
+
+		assert_eq!(lines, ["clip is deprecated, use clamp-i32 instead",
+		                   "test.glsp:1: clip is deprecated, use clamp-i32 instead"]);
+		assert_eq!(glsp::deprecations_hit(), [glsp::sym("clip")?]);
+
+		Ok(())
+	});
+}
@@ -0,0 +1,97 @@
+//! Checks that an error's source chain survives being caught and wrapped by Rust code, raised 
+//! back into GameLisp, caught by `try-chain` and re-raised by `bail`.
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+use std::error::{Error};
+use std::io;
+
+//calls back into glsp, and wraps any error rather than replacing it
+fn load_level(loader: Root<GFn>) -> GResult<Val> {
+	match glsp::call(&loader, &()) {
+		Ok(val) => Ok(val),
+		Err(err) => Err(error!("failed to load the level").with_source(err))
+	}
+}
+
+fn read_config() -> GResult<()> {
+	let io_err = io::Error::new(io::ErrorKind::NotFound, "config.txt is missing");
+	Err(error!("unable to read the config").with_source(io_err))
+}
+
+#[test]
+fn round_trip() {
+	run(|| {
+		glsp::bind_rfn("load-level", rfn!(load_level))?;
+		glsp::bind_rfn("read-config", rfn!(read_config))?;
+
+		glsp::load_str(r#"
+			(defn parse-spawns ()
+			  (bail 'bad-spawn-table))
+
+			(let (tag e) (try-chain (load-level parse-spawns)))
+			(ensure (eq? tag 'err))
+			(ensure (eq? [e 'payload] "failed to load the level"))
+			(ensure (eq? [(err-source e) 'payload] 'bad-spawn-table))
+			(ensure (nil? (err-source (err-source e))))
+			(ensure (eq? (err-chain e) '("failed to load the level" bad-spawn-table)))
+
+			;non-GError sources are described by their messages
+			(let (_ e) (try-chain (read-config)))
+			(ensure (eq? (err-chain e) '("unable to read the config" "config.txt is missing")))
+
+			;the ordinary try form still only reports the outermost payload
+			(ensure (eq? (try (load-level parse-spawns)) '(err "failed to load the level")))
+			(ensure (eq? (try-chain 10) '(ok 10)))
+
+			(ensure (eq? [(try (err-source 'bad-spawn-table)) 0] 'err))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn reraised() {
+	run(|| {
+		glsp::bind_rfn("load-level", rfn!(load_level))?;
+
+		//a glsp error, wrapped by Rust, caught by a script, and re-raised into Rust: all three
+		//layers are still present
+		let err = glsp::load_str(r#"
+			(defn parse-spawns ()
+			  (bail 'bad-spawn-table))
+
+			(defn wrapped ()
+			  (load-level parse-spawns))
+
+			(let (_ e) (try-chain (load-level wrapped)))
+			(ensure (eq? (err-chain e) 
+			             '("failed to load the level" "failed to load the level" bad-spawn-table)))
+			(bail e)
+		"#, "test.glsp").unwrap_err();
+
+		assert_eq!(err.val(), Val::Str(glsp::str_from_rust_str("failed to load the level")));
+
+		let mut payloads = vec![err.val().to_string()];
+		let mut next = err.source();
+		while let Some(source) = next {
+			payloads.push(source.downcast_ref::<GError>().unwrap().val().to_string());
+			next = source.source();
+		}
+		assert_eq!(payloads, [
+			"failed to load the level", "failed to load the level", "bad-spawn-table"
+		]);
+
+		//the Display output includes every layer
+		let message = err.to_string();
+		assert!(message.contains(
+			"failed to load the level\ncaused by: failed to load the level\n\
+			 caused by: bad-spawn-table"
+		), "{}", message);
+
+		Ok(())
+	});
+}
@@ -0,0 +1,94 @@
+//! Checks that `glsp::current_execution_point` can report a stuck script's location from another
+//! thread, and that a handle outlives its runtime.
+
+use glsp::prelude::*;
+use glsp::{ExecutionPoint, RuntimeHandle};
+use std::sync::{Arc, mpsc};
+use std::sync::atomic::{AtomicBool, Ordering::SeqCst};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[test]
+fn stuck_script() {
+	let stop = Arc::new(AtomicBool::new(false));
+	let (sender, receiver) = mpsc::channel::<RuntimeHandle>();
+
+	let script_stop = Arc::clone(&stop);
+	let script = thread::spawn(move || {
+		let runtime = Runtime::new();
+		sender.send(runtime.handle()).unwrap();
+
+		runtime.run(move || {
+			glsp::bind_rfn_boxed("stopped?", rfn_boxed!(move || script_stop.load(SeqCst)))?;
+			glsp::load_str(r#"
+				(defn step (n)
+				  (+ n 1))
+
+				(defn flood-fill ()
+				  (let n 0)
+				  (while (not (stopped?))
+				    (= n (step n)))
+				  n)
+			"#, "ai/pathing.glsp")?;
+
+			glsp::load_str("(flood-fill)", "main.glsp")?;
+			Ok(())
+		}).expect("the script failed");
+
+		runtime.handle()
+	});
+
+	let handle = receiver.recv().unwrap();
+
+	//step is so brief that we're unlikely to observe it, but it's a valid answer
+	let deadline = Instant::now() + Duration::from_secs(10);
+	let mut point: Option<ExecutionPoint> = None;
+	while Instant::now() < deadline {
+		match glsp::current_execution_point(&handle) {
+			Some(found) if found.fn_name() == Some("flood-fill") => {
+				point = Some(found);
+				break
+			}
+			_ => thread::sleep(Duration::from_millis(1))
+		}
+	}
+
+	stop.store(true, SeqCst);
+	let point = point.expect("the stuck script was never observed");
+	assert_eq!(point.filename(), Some("ai/pathing.glsp"));
+
+	//the (while) loop is on line 7, and its body is on line 8
+	let line = point.line().unwrap();
+	assert!(line == 7 || line == 8, "{}", line);
+	assert_eq!(point.to_string(), format!("ai/pathing.glsp:{} in fn flood-fill", line));
+
+	let dropped_handle = script.join().unwrap();
+	assert!(glsp::current_execution_point(&dropped_handle).is_none());
+	assert!(glsp::current_execution_point(&handle).is_none());
+}
+
+#[test]
+fn idle() {
+	let runtime = Runtime::new();
+	let handle = runtime.handle();
+	assert!(glsp::current_execution_point(&handle).is_none());
+
+	runtime.run(|| {
+		glsp::load_str("(defn f () 1) (f)", "idle.glsp")?;
+
+		//from inside a rust function, we see the gfn which called it
+		glsp::bind_rfn_boxed("where", rfn_boxed!(|| -> String {
+			let handle = glsp::runtime_handle();
+			glsp::current_execution_point(&handle).unwrap().to_string()
+		}))?;
+
+		let result = glsp::load_str("\n(defn g () (where))\n(g)", "where.glsp")?;
+		assert_eq!(result.to_string(), "where.glsp:2 in fn g");
+
+		let result = glsp::load_str("(where)", "toplevel.glsp")?;
+		assert_eq!(result.to_string(), "toplevel.glsp:1");
+		Ok(())
+	});
+
+	assert!(glsp::current_execution_point(&handle).is_none());
+}
@@ -0,0 +1,134 @@
+//! Checks that `glsp::add_expansion_pass` transforms each toplevel form before it's compiled,
+//! in registration order, and that injected code reports a sensible source location.
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+use std::cell::{Cell};
+
+thread_local! {
+	static DEFNS: Cell<usize> = Cell::new(0);
+}
+
+//(bind-global! 'name (fn ...)) becomes (do (on-defn 'name) (bind-global! 'name (fn ...)))
+fn count_defns(form: Val) -> GResult<Val> {
+	if let Val::Arr(ref arr) = form {
+		if arr.len() == 3 && arr.get::<Val>(0)? == Val::Sym(glsp::sym("bind-global!")?) {
+			if let Val::Arr(init) = arr.get::<Val>(2)? {
+				if init.len() > 0 && init.get::<Val>(0)? == Val::Sym(glsp::sym("fn")?) {
+					DEFNS.with(|defns| defns.set(defns.get() + 1));
+
+					let quoted_name: Val = arr.get(1)?;
+					return Ok(backquote!("(do (on-defn ~quoted_name) ~form)"))
+				}
+			}
+		}
+	}
+
+	Ok(form)
+}
+
+//replaces the sym `answer` with 42, but only among a form's direct children. the input form may 
+//be frozen, so we construct a new arr.
+fn replace_answer(form: Val) -> GResult<Val> {
+	match form {
+		Val::Arr(ref arr) => {
+			let answer = Val::Sym(glsp::sym("answer")?);
+			let replaced = glsp::arr_from_iter(arr.iter().map(|item| {
+				if item == answer { Val::Int(42) } else { item }
+			}))?;
+
+			Ok(Val::Arr(replaced))
+		}
+		form => Ok(form)
+	}
+}
+
+#[test]
+fn count_and_inject() {
+	run(|| {
+		glsp::load_str(r#"
+			(def defined (arr))
+			(defn on-defn (name)
+			  (push! defined name))
+		"#, "prelude.glsp")?;
+
+		glsp::add_expansion_pass(count_defns);
+
+		glsp::load_str(r#"
+			(defn first () 1)
+			(def not-a-defn 2)
+			(splice
+			  (defn second () 2)
+			  (defn third () 3))
+		"#, "test.glsp")?;
+
+		glsp::eval(&glsp::parse_1("(defn fourth () 4)", None)?, None)?;
+
+		let defined: Val = glsp::global("defined")?;
+		assert_eq!(defined.to_string(), "(first second third fourth)");
+		assert_eq!(DEFNS.with(|defns| defns.get()), 4);
+
+		glsp::clear_expansion_passes();
+		glsp::load_str("(defn fifth () 5)", "test.glsp")?;
+		assert_eq!(DEFNS.with(|defns| defns.get()), 4);
+		Ok(())
+	});
+}
+
+//(form) becomes (- form)
+fn negate(form: Val) -> GResult<Val> {
+	Ok(backquote!("(- ~form)"))
+}
+
+#[test]
+fn registration_order() {
+	run(|| {
+		//if negate ran first, `answer` would no longer be one of the toplevel form's children
+		glsp::add_expansion_pass(replace_answer);
+		glsp::add_expansion_pass(negate);
+
+		let result = glsp::load_str("(+ answer 1)", "test.glsp")?;
+		assert_eq!(result, Val::Int(-43));
+		Ok(())
+	});
+}
+
+#[test]
+fn spans() {
+	run(|| {
+		glsp::load_str("(defn on-defn (name) (bail \"on-defn failed\"))", "prelude.glsp")?;
+		glsp::add_expansion_pass(count_defns);
+
+		//the injected call to on-defn fails, and it's reported as part of the defn's expansion
+		let message = glsp::load_str("\n\n(defn foo ())", "spans.glsp").unwrap_err().to_string();
+		assert!(message.contains("(defn) at spans.glsp:3\n        expanded to (on-defn)"), 
+		        "{}", message);
+		Ok(())
+	});
+}
+
+#[cfg(feature = "compiler")]
+#[test]
+fn compiled() {
+	const PRELUDE: &str = "(def defined (arr)) (defn on-defn (name) (push! defined name))";
+
+	let bytes = run(|| {
+		glsp::load_str(PRELUDE, "prelude.glsp")?;
+		glsp::add_expansion_pass(count_defns);
+
+		let (_, bytes) = glsp::load_and_compile_str("(defn compiled ())", "compiled.glsp")?;
+		Ok(bytes)
+	});
+
+	//the injected call was recorded, so it's replayed without any expansion passes
+	run(move || {
+		glsp::load_str(PRELUDE, "prelude.glsp")?;
+		glsp::load_compiled(&bytes)?;
+
+		let defined: Val = glsp::global("defined")?;
+		assert_eq!(defined.to_string(), "(compiled)");
+		Ok(())
+	});
+}
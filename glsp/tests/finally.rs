@@ -0,0 +1,226 @@
+//! Checks the ordering of `(finally)` cleanup relative to `(defer)` and `(try)`, errors raised
+//! by cleanup while another error is unwinding, cleanup when a coroutine is cancelled, and the
+//! `(defer)` forms at the toplevel of a file.
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+use std::cell::{RefCell};
+
+thread_local! {
+	static LOG: RefCell<Vec<String>> = RefCell::new(Vec::new());
+}
+
+//unlike a global, this log survives its Runtime being dropped
+fn note(text: &str) {
+	LOG.with(|log| log.borrow_mut().push(text.to_string()));
+}
+
+fn take_log() -> Vec<String> {
+	LOG.with(|log| log.borrow_mut().drain(..).collect())
+}
+
+#[test]
+fn ordering() {
+	run(|| {
+		glsp::load_str(r#"
+			(def events (arr))
+
+			;cleanup runs after the defers within the body, and before the enclosing defers
+			(do
+			  (defer (push! events 'enclosing))
+			  (finally
+			    (do
+			      (defer (push! events 'inner))
+			      (push! events 'body))
+			    (push! events 'cleanup)))
+			(ensure (eq? events '(body inner cleanup enclosing)))
+
+			;within a try, cleanup runs before try returns
+			(clear! events)
+			(let result (try
+			  (finally
+			    (bail 'failed)
+			    (push! events 'cleanup))))
+			(push! events 'caught)
+			(ensure (eq? result '(err failed)))
+			(ensure (eq? events '(cleanup caught)))
+
+			;around a try, cleanup runs after the error has been caught
+			(clear! events)
+			(let result (finally
+			  (do
+			    (let result (try (bail 'failed)))
+			    (push! events (arr 'caught ..result))
+			    result)
+			  (push! events 'cleanup)))
+			(ensure (eq? result '(err failed)))
+			(ensure (eq? events '((caught err failed) cleanup)))
+
+			;the result is the body's result, even when cleanup returns a value
+			(ensure (== (finally 10 20) 10))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn error_during_error() {
+	run(|| {
+		glsp::load_str(r#"
+			(def events (arr))
+
+			;try reports the body's error, not the cleanup's error
+			(let result (try
+			  (finally
+			    (bail "body failed")
+			    (push! events 'first-cleanup)
+			    (bail "cleanup failed"))))
+			(ensure (eq? result '(err "body failed")))
+			(ensure (eq? events '(first-cleanup)))
+
+			;try-verbose's stack trace includes the cleanup's error
+			(let (tag payload trace) (try-verbose
+			  (finally
+			    (bail "body failed")
+			    (bail "cleanup failed"))))
+			(ensure (eq? tag 'err))
+			(ensure (eq? payload "body failed"))
+			(ensure (contains? trace "a (defer) form also failed"))
+
+			;when several cleanups fail, the remaining cleanups still run
+			(clear! events)
+			(let result (try
+			  (finally
+			    (finally
+			      (bail "body failed")
+			      (push! events 'inner)
+			      (bail "inner cleanup failed"))
+			    (push! events 'outer)
+			    (bail "outer cleanup failed"))))
+			(ensure (eq? result '(err "body failed")))
+			(ensure (eq? events '(inner outer)))
+
+			;when only the cleanup fails, its error is the result
+			(ensure (eq? (try (finally 10 (bail "cleanup failed"))) '(err "cleanup failed")))
+		"#, "test.glsp")?;
+
+		//the chained errors are displayed in the order they were raised
+		let err = glsp::load_str(r#"
+			(finally
+			  (finally
+			    (bail "body failed")
+			    (bail "inner cleanup failed"))
+			  (bail "outer cleanup failed"))
+		"#, "test.glsp").unwrap_err();
+
+		let message = err.to_string();
+		let body = message.find("body failed").unwrap();
+		let inner = message.find("inner cleanup failed").unwrap();
+		let outer = message.find("outer cleanup failed").unwrap();
+		assert!(body < inner && inner < outer, "{}", message);
+
+		Ok(())
+	});
+}
+
+#[test]
+fn coroutine_cancellation() {
+	run(|| {
+		glsp::load_str(r#"
+			(def events (arr))
+
+			(defn worker (name)
+			  (finally
+			    (do
+			      (defer (push! events (arr name 'inner)))
+			      (loop (yield name)))
+			    (push! events (arr name 'cleanup))))
+
+			;yielding out of the body doesn't run the cleanup
+			(let finished (worker 'finished))
+			(coro-run finished)
+			(coro-run finished)
+			(ensure (eq? events '()))
+
+			;coro-finish! runs it, after the body's defers
+			(coro-finish! finished)
+			(ensure (eq? (coro-state finished) 'finished))
+			(ensure (eq? events '((finished inner) (finished cleanup))))
+
+			;so does coro-throw, without resuming the coroutine
+			(clear! events)
+			(let thrown (worker 'thrown))
+			(coro-run thrown)
+			(let result (try (coro-throw thrown 'cancelled)))
+			(ensure (eq? result '(err cancelled)))
+			(ensure (eq? (coro-state thrown) 'poisoned))
+			(ensure (eq? events '((thrown inner) (thrown cleanup))))
+
+			;a cleanup which fails during coro-finish! raises its error from coro-finish!
+			(clear! events)
+			(defn failing-worker ()
+			  (finally
+			    (loop (yield))
+			    (push! events 'cleanup)
+			    (bail "cleanup failed")))
+
+			(let failing (failing-worker))
+			(coro-run failing)
+			(ensure (eq? (try (coro-finish! failing)) '(err "cleanup failed")))
+			(ensure (eq? events '(cleanup)))
+
+			;a paused coroutine which is garbage-collected runs its cleanup during glsp::gc
+			(clear! events)
+			(def collected (worker 'collected))
+			(coro-run collected)
+		"#, "test.glsp")?;
+
+		glsp::load_str("(= collected #n)", "test.glsp")?;
+		glsp::gc();
+
+		let events: Val = glsp::global("events")?;
+		assert_eq!(events.to_string(), "((collected inner) (collected cleanup))");
+		Ok(())
+	});
+}
+
+#[test]
+fn file_defers() {
+	take_log();
+
+	let runtime = Runtime::new();
+	runtime.run(|| {
+		glsp::bind_rfn("note", rfn!(note))?;
+
+		let first = r#"
+			(note "first loaded")
+			(defer (note "first a"))
+			(defer (note "first b"))
+		"#;
+
+		let second = r#"
+			(note "second loaded")
+			(defer (note "second a"))
+			(defer (note "second b"))
+		"#;
+
+		//a file's toplevel defers don't run when it finishes loading
+		glsp::load_str(first, "first.glsp")?;
+		glsp::load_str(second, "second.glsp")?;
+		assert_eq!(take_log(), ["first loaded", "second loaded"]);
+
+		//reloading a file runs its own defers in reverse order, before it's evaluated again,
+		//and leaves the other file's defers pending
+		glsp::load_str(first, "first.glsp")?;
+		assert_eq!(take_log(), ["first b", "first a", "first loaded"]);
+
+		Ok(())
+	}).unwrap();
+
+	//dropping the Runtime runs the defers of the most recently loaded file first
+	drop(runtime);
+	assert_eq!(take_log(), ["first b", "first a", "second b", "second a"]);
+}
@@ -0,0 +1,90 @@
+//! Checks the width of flos in both configurations: f32 by default, or f64 when the "f64-flos"
+//! feature is enabled. Conversions to and from Rust's float types, printing and parsing should
+//! agree with that width, and compiled code should refuse to load under the other width.
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+use glsp::{Flo};
+use std::mem::size_of;
+
+fn eval<T: FromVal>(src: &str) -> GResult<T> {
+	T::from_val(&glsp::load_str(src, "flo_width.glsp")?)
+}
+
+#[test]
+fn width() {
+	if cfg!(feature = "f64-flos") {
+		assert_eq!(size_of::<Flo>(), 8);
+	} else {
+		assert_eq!(size_of::<Flo>(), 4);
+	}
+}
+
+#[test]
+fn conversions() {
+	run(|| {
+		//f32 always round-trips exactly
+		let val = 0.1_f32.to_val()?;
+		assert_eq!(f32::from_val(&val)?, 0.1_f32);
+
+		//f64 is lossless when flos are f64, and rounded to the nearest f32 otherwise
+		let val = 0.1_f64.to_val()?;
+		if cfg!(feature = "f64-flos") {
+			assert_eq!(f64::from_val(&val)?, 0.1_f64);
+		} else {
+			assert_eq!(f64::from_val(&val)?, 0.1_f32 as f64);
+		}
+
+		//converting a flo to an f32 rounds it, rather than failing
+		assert_eq!(f32::from_val(&val)?, 0.1_f32);
+		assert_eq!(Num::Flo(0.1).into_f32(), 0.1_f32);
+
+		let big = 1e300_f64.to_val()?;
+		if cfg!(feature = "f64-flos") {
+			assert_eq!(f32::from_val(&big)?, f32::INFINITY);
+		}
+
+		Ok(())
+	});
+}
+
+#[test]
+fn arithmetic() {
+	run(|| {
+		let sum: Flo = eval("(+ 0.1 0.2)")?;
+		assert_eq!(sum, 0.1 as Flo + 0.2 as Flo);
+
+		//2^24 + 1 is the smallest int which an f32 can't represent
+		let exact: bool = eval("(== (int (flo 16777217)) 16777217)")?;
+		assert_eq!(exact, cfg!(feature = "f64-flos"));
+
+		let printed: String = eval("(str (/ 1.0 3.0))")?;
+		assert_eq!(printed, format!("{:?}", 1.0 as Flo / 3.0 as Flo));
+
+		let parsed: Flo = eval("1.23456789012345")?;
+		assert_eq!(parsed, "1.23456789012345".parse::<Flo>().unwrap());
+
+		Ok(())
+	});
+}
+
+#[cfg(feature = "compiler")]
+#[test]
+fn compiled_width() {
+	run(|| {
+		let (_, mut bytes) = glsp::load_and_compile_str("(+ 1.5 2.5)", "flo_width.glsp")?;
+		let result = glsp::load_compiled(&bytes)?;
+		assert_eq!(Flo::from_val(&result)?, 4.0);
+
+		//the byte which follows the length header records the width of a flo
+		assert_eq!(bytes[8] as usize, size_of::<Flo>());
+		bytes[8] = if size_of::<Flo>() == 4 { 8 } else { 4 };
+
+		let err = glsp::load_compiled(&bytes).unwrap_err();
+		assert!(err.val().to_string().contains("\"f64-flos\" feature must match"));
+
+		Ok(())
+	});
+}
@@ -0,0 +1,62 @@
+//! Checks that a filesystem root confines the filesystem functions to a directory, including
+//! when a path passes through a symbolic link.
+
+mod common;
+
+use common::run;
+use glsp::{FsPolicy};
+use std::fs;
+use std::path::{PathBuf};
+
+fn temp_dir(name: &str) -> PathBuf {
+	let path = std::env::temp_dir().join(format!("glsp-{}-{}", name, std::process::id()));
+	let _ = fs::remove_dir_all(&path);
+	fs::create_dir_all(&path).unwrap();
+	path
+}
+
+#[cfg(unix)]
+#[test]
+fn symlink_escape() {
+	use std::os::unix::fs::symlink;
+
+	let root = temp_dir("fs-root");
+	let outside = temp_dir("fs-outside");
+	fs::write(outside.join("secret.txt"), "secret").unwrap();
+
+	symlink(outside.join("secret.txt"), root.join("live")).unwrap();
+	symlink(outside.join("created.txt"), root.join("dangling")).unwrap();
+	symlink(&outside, root.join("dir")).unwrap();
+
+	let root_clone = root.clone();
+	let result = std::panic::catch_unwind(move || run(move || {
+		glsp::enable_fs(FsPolicy { root: Some(root_clone), write: true })?;
+
+		glsp::load_str(r#"
+			(write-file "inside.txt" "a")
+			(append-file "inside.txt" "b")
+			(ensure (eq? (read-file "inside.txt") "ab"))
+
+			(ensure (eq? [(try (read-file "live")) 0] 'err))
+			(ensure (eq? [(try (write-file "live" "x")) 0] 'err))
+
+			(ensure (eq? [(try (write-file "dangling" "x")) 0] 'err))
+			(ensure (eq? [(try (append-file "dangling" "x")) 0] 'err))
+			(ensure (eq? [(try (file-exists? "dangling")) 0] 'err))
+
+			(ensure (eq? [(try (write-file "dir/created.txt" "x")) 0] 'err))
+			(ensure (eq? [(try (write-file "../created.txt" "x")) 0] 'err))
+		"#, "test.glsp")?;
+
+		Ok(())
+	}));
+
+	let created = outside.join("created.txt").exists();
+	let secret = fs::read_to_string(outside.join("secret.txt")).unwrap();
+	fs::remove_dir_all(&root).unwrap();
+	fs::remove_dir_all(&outside).unwrap();
+
+	result.unwrap();
+	assert!(!created, "a file was created outside of the filesystem root");
+	assert_eq!(secret, "secret");
+}
@@ -0,0 +1,77 @@
+//! Checks that the storage of small arrs and iterators is recycled after they're collected, that
+//! `pool-hits` and `pool-misses` count those allocations, and that recycled arrs start out empty.
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+
+#[test]
+fn recycling() {
+	run(|| {
+		glsp::load_str(r#"
+			(def entries (tab ..(map (fn (i) (arr i (* i 2))) (rn 10))))
+
+			(defn frame ()
+			  (let total 0)
+			  (for (k v) in entries
+			    (let (a b c) (arr k v (+ k v)))
+			    (inc! total (+ a b c)))
+			  total)
+		"#, "test.glsp")?;
+
+		let frame: Root<GFn> = glsp::global("frame")?;
+		for _ in 0 .. 3 {
+			assert_eq!(glsp::call::<_, _, i32>(&frame, &())?, 270);
+			glsp::gc();
+		}
+
+		//once the first frame's garbage has been collected, later frames reuse its storage
+		let (hits, misses) = (glsp::gc_pool_hits(), glsp::gc_pool_misses());
+		for _ in 0 .. 10 {
+			assert_eq!(glsp::call::<_, _, i32>(&frame, &())?, 270);
+			glsp::gc();
+		}
+
+		let new_hits = glsp::gc_pool_hits() - hits;
+		let new_misses = glsp::gc_pool_misses() - misses;
+		assert!(new_hits >= 200, "{} hits", new_hits);
+		assert!(new_misses < new_hits / 10, "{} hits, {} misses", new_hits, new_misses);
+
+		Ok(())
+	});
+}
+
+#[test]
+fn recycled_contents() {
+	run(|| {
+		glsp::load_str(r#"
+			(defn garbage ()
+			  (forn (i 100)
+			    (let ar (arr i i i))
+			    (= [ar 0] (arr 'x))
+			    (push! ar 'y)))
+
+			(garbage)
+			(gc)
+
+			;recycled arrs are empty, with no span or frozen flag
+			(forn (i 100)
+			  (ensure (eq? (arr) ()))
+			  (ensure (eq? (arr i) (arr i)))
+			  (let ar (arr 1 2 3))
+			  (ensure (not (frozen? ar)))
+			  (ensure (nil? (span-of ar)))
+			  (push! ar 4)
+			  (ensure (eq? ar '(1 2 3 4))))
+
+			(let hits (gc-value 'pool-hits))
+			(let misses (gc-value 'pool-misses))
+			(ensure (and (int? hits) (int? misses)))
+			(arr 1 2)
+			(ensure (> (+ (gc-value 'pool-hits) (gc-value 'pool-misses)) (+ hits misses)))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
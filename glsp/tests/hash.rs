@@ -0,0 +1,52 @@
+//! Checks that `(hash)` produces the values fixed by its documented encoding, that equal numbers
+//! hash equally, and that large ints don't collide with one another.
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+
+fn hash(src: &str) -> i32 {
+	run(|| {
+		let val = glsp::load_str(&format!("(hash {})", src), "test.glsp")?;
+		i32::from_val(&val)
+	})
+}
+
+#[test]
+fn stability() {
+	//these values are fixed by the documented encoding, so they must never change. each of these
+	//numbers can be represented by an f32, so they're the same with or without "f64-flos".
+	assert_eq!(hash("1"), -1752536159);
+	assert_eq!(hash("0.5"), -972986857);
+	assert_eq!(hash("1e10"), 786701325);
+	assert_eq!(hash("nan.0"), -972625249);
+	assert_eq!(hash("16777217"), -1752537070);
+	assert_eq!(hash("-2147483648"), -506396229);
+	assert_eq!(hash("(arr 1 'a)"), -332629682);
+}
+
+#[test]
+fn numbers() {
+	//flos with an integer value hash like the equal int or char
+	assert_eq!(hash("1.0"), hash("1"));
+	assert_eq!(hash("\\x01"), hash("1"));
+	assert_eq!(hash("-0.0"), hash("0"));
+	assert_eq!(hash("(- 0.0 nan.0)"), hash("nan.0"));
+	assert_eq!(hash("(flo -2147483648)"), hash("-2147483648"));
+
+	//ints above 2^24 used to be rounded to an f32 before they were hashed
+	assert_ne!(hash("16777217"), hash("16777216"));
+	assert_ne!(hash("2147483647"), hash("2147483646"));
+
+	run(|| {
+		glsp::load_str(r#"
+			(let hashes (tab))
+			(forn (i 100000000 100001000)
+			  (= [hashes (hash i)] i))
+			(ensure (== (len hashes) 1000))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
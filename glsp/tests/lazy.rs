@@ -0,0 +1,152 @@
+//! Checks that lazy values are evaluated at most once, that `defonce` preserves existing globals
+//! across repeated loads and hot reloads, and that both work in compiled code.
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+use std::cell::{Cell};
+use std::fs;
+use std::rc::{Rc};
+
+#[test]
+fn lazy() {
+	run(|| {
+		glsp::load_str(r#"
+			(let evaluated 0)
+			(let counter (lazy
+			  (inc! evaluated)
+			  (* 10 evaluated)))
+
+			(ensure (lazy? counter))
+			(ensure (not (lazy? 10)))
+			(ensure (== evaluated 0))
+			(ensure (== (force counter) 10))
+			(ensure (== (force counter) 10))
+			(ensure (== evaluated 1))
+
+			;non-lazy values are returned unchanged
+			(ensure (== (force 5) 5))
+			(let a (arr))
+			(ensure (same? (force a) a))
+
+			;a failed lazy value can be retried
+			(let ready #f)
+			(let flaky (lazy
+			  (ensure ready)
+			  'ok))
+			(ensure (eq? [(try (force flaky)) 0] 'err))
+			(= ready #t)
+			(ensure (eq? (force flaky) 'ok))
+
+			;a lazy value which depends on itself is an error, rather than a hang
+			(def cyclic (lazy (force cyclic)))
+			(ensure (eq? [(try (force cyclic)) 0] 'err))
+		"#, "test.glsp")?;
+
+		let forced = glsp::force(&glsp::load_str("(lazy (+ 1 2))", "test.glsp")?)?;
+		assert_eq!(forced, Val::Int(3));
+		assert_eq!(glsp::force(&Val::Int(4))?, Val::Int(4));
+
+		Ok(())
+	});
+}
+
+#[test]
+fn defonce() {
+	run(|| {
+		let src = r#"
+			(defonce entities (arr))
+			(push! entities 'player)
+			(defonce evaluated (do (inc! loads) loads))
+		"#;
+
+		glsp::bind_global("loads", 0)?;
+		glsp::load_str(src, "test.glsp")?;
+		glsp::load_str(src, "test.glsp")?;
+
+		glsp::load_str(r#"
+			(ensure (eq? entities '(player player)))
+			(ensure (== evaluated 1))
+			(ensure (== loads 1))
+
+			(in-module 'game)
+			(defonce score 0)
+			(in-module #n)
+			(ensure (== game:score 0))
+		"#, "test.glsp")?;
+
+		//hot reloading doesn't clobber the state, even though it replaces def bindings
+		let path = std::env::temp_dir().join(format!("glsp-defonce-{}.glsp", std::process::id()));
+		fs::write(&path, "(defonce saved (arr)) (def replaced (arr))").unwrap();
+
+		let filename = path.to_str().unwrap();
+		glsp::load(filename)?;
+		let saved: Val = glsp::global("saved")?;
+		let replaced: Val = glsp::global("replaced")?;
+
+		let result = glsp::reload(filename);
+		fs::remove_file(&path).unwrap();
+		result?;
+
+		assert!(saved.same(&glsp::global("saved")?));
+		assert!(!replaced.same(&glsp::global("replaced")?));
+
+		//defonce is reported as a definition
+		let defs = glsp::definitions("(defonce saved (arr))")?;
+		assert_eq!(defs[0].kind().to_string(), "defonce");
+
+		Ok(())
+	});
+}
+
+#[test]
+fn bind_global_lazy() {
+	run(|| {
+		let calls = Rc::new(Cell::new(0));
+		let calls_ref = calls.clone();
+		glsp::bind_global_lazy("costs", move || {
+			calls_ref.set(calls_ref.get() + 1);
+			Ok(vec![1, 2, 3])
+		})?;
+
+		assert_eq!(calls.get(), 0);
+		glsp::load_str(r#"
+			(ensure (lazy? costs))
+			(ensure (eq? (force costs) '(1 2 3)))
+			(ensure (same? (force costs) (force costs)))
+		"#, "test.glsp")?;
+		assert_eq!(calls.get(), 1);
+
+		assert!(glsp::bind_global_lazy("costs", || Ok(0)).is_err());
+
+		Ok(())
+	});
+}
+
+#[cfg(feature = "compiler")]
+#[test]
+fn compiled() {
+	let src = r#"
+		(defonce table (arr))
+		(push! table 'loaded)
+		(defonce squares (lazy (arr ..(map (fn1 (* _ _)) (rn 4)))))
+	"#;
+
+	let bytes = run(|| {
+		let (_, bytes) = glsp::load_and_compile_str(src, "main.glsp")?;
+		Ok(bytes)
+	});
+
+	run(move || {
+		glsp::load_compiled(&bytes)?;
+		glsp::load_compiled(&bytes)?;
+
+		glsp::load_str(r#"
+			(ensure (eq? table '(loaded loaded)))
+			(ensure (eq? (force squares) '(0 1 4 9)))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
@@ -0,0 +1,136 @@
+//! Checks that a `Library` can be installed into several `Runtimes`, that conflicting names are
+//! all reported before anything is bound, and that a failed source file is rolled back.
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+use std::sync::{Arc};
+use std::thread;
+
+fn add(a: i32, b: i32) -> i32 {
+	a + b
+}
+
+fn greet(name: &str) -> String {
+	format!("hello, {}", name)
+}
+
+fn twice(form: Val) -> Val {
+	backquote!("(arr ~form ~form)")
+}
+
+fn make_library() -> Library {
+	library! {
+		"add" => add,
+		"greet" => greet
+	}
+	.rfn_macro("twice", rfn!(twice))
+	.constant("tile-size", 16)
+	.constant("title", "Dungeon")
+	.source("enemies.glsp", r#"
+		(defclass Enemy
+		  (field hp tile-size))
+
+		(defmacro spawn ()
+		  `(Enemy))
+	"#)
+}
+
+#[test]
+fn install() {
+	let library = Arc::new(make_library());
+
+	let threads: Vec<_> = (0 .. 2).map(|_| {
+		let library = Arc::clone(&library);
+		thread::spawn(move || {
+			run(move || {
+				glsp::install(&library)?;
+
+				glsp::load_str(r#"
+					(ensure (== (add 1 2) 3))
+					(ensure (eq? (greet "world") "hello, world"))
+					(ensure (eq? (twice 5) '(5 5)))
+					(ensure (== tile-size 16))
+					(ensure (const? 'title))
+					(ensure (== [(spawn) 'hp] 16))
+					(ensure (eq? (fn-name add) 'add))
+				"#, "test.glsp")?;
+
+				Ok(())
+			})
+		})
+	}).collect();
+
+	for thread in threads {
+		thread.join().unwrap();
+	}
+}
+
+#[test]
+fn conflicts() {
+	run(|| {
+		let library = Library::new()
+			.rfn("add", rfn!(add))
+			.rfn("add", rfn!(greet))
+			.rfn("len", rfn!(add))
+			.rfn_macro("when", rfn!(twice))
+			.constant("tile-size", 16)
+			.source("conflicts.glsp", "(def tile-size 32) (defn unrelated ())");
+
+		let message = glsp::install(&library).unwrap_err().to_string();
+		assert!(message.contains("the global add is defined more than once"), "{}", message);
+		assert!(message.contains("the global len is already bound"), "{}", message);
+		assert!(message.contains("the macro when is already bound"), "{}", message);
+		assert!(message.contains("the global tile-size is defined more than once"), "{}", message);
+
+		//nothing was bound
+		assert!(!glsp::has_global("add")?);
+		assert!(!glsp::has_global("tile-size")?);
+		assert!(!glsp::has_global("unrelated")?);
+
+		//an rfn macro doesn't conflict with a global of the same name
+		let library = Library::new()
+			.rfn("twice", rfn!(add))
+			.rfn_macro("twice", rfn!(twice));
+		glsp::install(&library)?;
+		assert!(glsp::has_global("twice")? && glsp::has_macro("twice")?);
+
+		//installing the same library twice is a conflict
+		assert!(glsp::install(&library).is_err());
+
+		Ok(())
+	});
+}
+
+#[test]
+fn failed_source() {
+	run(|| {
+		let library = library! {
+			"add" => add
+		}
+		.constant("tile-size", 16)
+		.source("broken.glsp", r#"
+			(defmacro spawn () #n)
+			(def level (add 1 2))
+			(no-such-fn)
+		"#);
+
+		assert!(glsp::install(&library).is_err());
+
+		for name in &["add", "tile-size", "level"] {
+			assert!(!glsp::has_global(*name)?, "{} is still bound", name);
+		}
+		assert!(!glsp::has_macro("spawn")?);
+
+		//the runtime is still usable, and the library can't have left anything behind which
+		//would conflict with a successful installation
+		let library = library! {
+			"add" => add
+		}.constant("tile-size", 16);
+		glsp::install(&library)?;
+		assert_eq!(glsp::load_str("(add tile-size 1)", "test.glsp")?, Val::Int(17));
+
+		Ok(())
+	});
+}
@@ -0,0 +1,189 @@
+//! Checks that each instantiation of a generic `lib!` struct is a separate library, and checks
+//! `glsp::has_lib`, `glsp::lib_or_init` and the state of a library after `glsp::take_lib`.
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+use std::cell::{Cell};
+use std::marker::{PhantomData};
+
+trait Asset {
+	const EXTENSION: &'static str;
+}
+
+struct Texture;
+struct Sound;
+
+impl Asset for Texture {
+	const EXTENSION: &'static str = "png";
+}
+
+impl Asset for Sound {
+	const EXTENSION: &'static str = "ogg";
+}
+
+lib! {
+	struct Assets<T: Asset> {
+		names: Vec<String>,
+		phantom: PhantomData<T>
+	}
+}
+
+impl<T: Asset> Assets<T> {
+	fn new() -> Assets<T> {
+		Assets { names: Vec::new(), phantom: PhantomData }
+	}
+
+	fn load(&mut self, name: &str) -> String {
+		let path = format!("{}.{}", name, T::EXTENSION);
+		self.names.push(path.clone());
+		path
+	}
+}
+
+lib! {
+	struct Counter {
+		count: i32
+	}
+}
+
+fn load_texture(assets: &mut Assets<Texture>, name: &str) -> String {
+	assets.load(name)
+}
+
+fn load_sound(assets: &mut Assets<Sound>, name: &str) -> String {
+	assets.load(name)
+}
+
+fn texture_count(assets: &Assets<Texture>) -> usize {
+	assets.names.len()
+}
+
+fn bump(counter: &mut Counter) -> i32 {
+	counter.count += 1;
+	counter.count
+}
+
+#[test]
+fn generic_libs() {
+	run(|| {
+		glsp::add_lib(Assets::<Texture>::new());
+		glsp::add_lib(Assets::<Sound>::new());
+
+		glsp::bind_rfn("load-texture", rfn!(load_texture))?;
+		glsp::bind_rfn("load-sound", rfn!(load_sound))?;
+		glsp::bind_rfn("texture-count", rfn!(texture_count))?;
+
+		glsp::load_str(r#"
+			(ensure (eq? (load-texture "grass") "grass.png"))
+			(ensure (eq? (load-texture "stone") "stone.png"))
+			(ensure (eq? (load-sound "step") "step.ogg"))
+			(ensure (== (texture-count) 2))
+		"#, "test.glsp")?;
+
+		assert_eq!(Assets::<Texture>::borrow().names, ["grass.png", "stone.png"]);
+		assert_eq!(Assets::<Sound>::borrow().names, ["step.ogg"]);
+
+		//taking one instantiation leaves the other in place
+		let textures = glsp::take_lib::<Assets<Texture>>()?;
+		assert_eq!(textures.names.len(), 2);
+		assert!(!glsp::has_lib::<Assets<Texture>>());
+		assert!(glsp::has_lib::<Assets<Sound>>());
+
+		//the error message names the full instantiation
+		let err = glsp::try_lib::<Assets<Texture>>().err().unwrap();
+		assert!(err.to_string().contains("Assets<"), "{}", err);
+		assert!(err.to_string().contains("Texture>"), "{}", err);
+
+		Ok(())
+	});
+}
+
+#[test]
+fn has_lib() {
+	run(|| {
+		assert!(!glsp::has_lib::<Counter>());
+		glsp::add_lib(Counter { count: 0 });
+		assert!(glsp::has_lib::<Counter>());
+
+		//borrowing a library doesn't affect whether it's registered
+		let counter = Counter::borrow_mut();
+		assert!(glsp::has_lib::<Counter>());
+		drop(counter);
+
+		glsp::take_lib::<Counter>()?;
+		assert!(!glsp::has_lib::<Counter>());
+		Ok(())
+	});
+}
+
+#[test]
+fn lib_or_init() {
+	run(|| {
+		let inits = Cell::new(0);
+		let init = || {
+			inits.set(inits.get() + 1);
+			Counter { count: 10 }
+		};
+
+		//the first call registers the library, and later calls borrow it
+		assert_eq!(glsp::lib_or_init(init)?.count, 10);
+		Counter::borrow_mut().count = 20;
+		assert_eq!(glsp::lib_or_init(init)?.count, 20);
+		assert_eq!(inits.get(), 1);
+
+		//it fails if the library is already mutably borrowed
+		let counter = Counter::borrow_mut();
+		assert!(glsp::lib_or_init(init).is_err());
+		drop(counter);
+		assert_eq!(inits.get(), 1);
+
+		//it fails if the initializer registers the library itself, without panicking
+		glsp::take_lib::<Counter>()?;
+		let result = glsp::lib_or_init(|| {
+			glsp::add_lib(Counter { count: 1 });
+			Counter { count: 2 }
+		});
+		assert!(result.is_err());
+		assert_eq!(Counter::borrow().count, 1);
+
+		Ok(())
+	});
+}
+
+#[test]
+fn take_lib() {
+	run(|| {
+		glsp::add_lib(Counter { count: 0 });
+		glsp::bind_rfn("bump", rfn!(bump))?;
+		glsp::load_str("(ensure (== (bump) 1))", "test.glsp")?;
+
+		//taking a library which is borrowed fails, and leaves the library registered
+		let counter = Counter::borrow();
+		assert!(glsp::take_lib::<Counter>().is_err());
+		drop(counter);
+		assert_eq!(Counter::borrow().count, 1);
+
+		//once it's been taken, borrowing it fails with an error rather than a panic
+		let counter = glsp::take_lib::<Counter>()?;
+		assert_eq!(counter.count, 1);
+		assert!(glsp::take_lib::<Counter>().is_err());
+		assert!(Counter::try_borrow().is_err());
+		assert!(Counter::try_borrow_mut().is_err());
+
+		let err = glsp::try_lib::<Counter>().err().unwrap();
+		assert!(err.to_string().contains("taken or dropped"), "{}", err);
+
+		glsp::load_str(r#"
+			(let (tag payload) (try (bump)))
+			(ensure (eq? tag 'err))
+		"#, "test.glsp")?;
+
+		//the library can be registered again, without add_lib reporting a duplicate
+		glsp::add_lib(counter);
+		glsp::load_str("(ensure (== (bump) 2))", "test.glsp")?;
+
+		Ok(())
+	});
+}
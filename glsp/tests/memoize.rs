@@ -0,0 +1,151 @@
+//! Checks that `(memoize)` caches results by argument equality, that it evicts the
+//! least-recently-used entry, and that weak keys don't keep their objects alive.
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+use glsp::{MemoOpts};
+
+#[test]
+fn caching() {
+	run(|| {
+		glsp::load_str(r#"
+			(def calls 0)
+
+			(def slow-add (memoize (fn (a b)
+			  (inc! calls)
+			  (+ a b))))
+
+			(ensure (== (slow-add 1 2) 3))
+			(ensure (== (slow-add 1 2) 3))
+			(ensure (== (slow-add 1.0 2) 3))
+			(ensure (== calls 1))
+
+			(ensure (== (slow-add 2 2) 4))
+			(ensure (== calls 2))
+
+			;structurally-equal arrs share an entry
+			(def measure (memoize (fn (ar) (inc! calls) (len ar))))
+			(measure (arr 1 2 3))
+			(measure (arr 1 2 3))
+			(ensure (== calls 3))
+
+			(let stats (memo-stats slow-add))
+			(ensure (== [stats 'hits] 2))
+			(ensure (== [stats 'misses] 2))
+			(ensure (== [stats 'entries] 2))
+
+			(memo-clear! slow-add)
+			(ensure (== [(memo-stats slow-add) 'entries] 0))
+			(slow-add 1 2)
+			(ensure (== calls 4))
+
+			;errors aren't cached
+			(def fails (memoize (fn (n)
+			  (inc! calls)
+			  (bail "failure"))))
+
+			(try (fails 1))
+			(try (fails 1))
+			(ensure (== calls 6))
+			(ensure (== [(memo-stats fails) 'entries] 0))
+
+			;unhashable arguments are an error
+			(ensure (eq? [(try (slow-add (fn () 1) 2)) 0] 'err))
+			(ensure (eq? [(try (memo-stats (fn () 1))) 0] 'err))
+
+			;recursive calls are cached
+			(def fib (memoize (fn (n)
+			  (if (< n 2)
+			    n
+			    (+ (fib (- n 1)) (fib (- n 2)))))))
+
+			(ensure (== (fib 10) 55))
+			(ensure (== [(memo-stats fib) 'misses] 11))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn eviction() {
+	run(|| {
+		glsp::load_str(r#"
+			(def calls 0)
+			(def square (memoize (fn (n) (inc! calls) (* n n)) (tab ('max-entries 2))))
+
+			(square 1)
+			(square 2)
+			(square 1)
+			(square 3)
+
+			;2 was the least-recently-used entry
+			(ensure (== calls 3))
+			(square 1)
+			(ensure (== calls 3))
+			(square 2)
+			(ensure (== calls 4))
+
+			(let stats (memo-stats square))
+			(ensure (== [stats 'entries] 2))
+			(ensure (== [stats 'evictions] 2))
+
+			(ensure (eq? [(try (memoize square (tab ('max-entries 0)))) 0] 'err))
+			(ensure (eq? [(try (memoize square (tab ('max-size 1)))) 0] 'err))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn weak_keys() {
+	run(|| {
+		glsp::load_str(r#"
+			(defclass Point
+			  (field x)
+			  (field y)
+			  (init (@x @y)))
+
+			(def calls 0)
+			(def describe (memoize (fn (pt)
+			  (inc! calls)
+			  (+ [pt 'x] [pt 'y])) (tab ('weak-keys #t))))
+
+			;objs without op-hash can be used as weak keys, and they're compared by identity
+			(def kept (Point 1 2))
+			(describe kept)
+			(describe kept)
+			(describe (Point 1 2))
+			(ensure (== calls 2))
+		"#, "test.glsp")?;
+
+		let entries = || -> GResult<i32> {
+			let val = glsp::load_str("[(memo-stats describe) 'entries]", "entries.glsp")?;
+			i32::from_val(&val)
+		};
+
+		//the unreachable point is eventually collected, and its entry is discarded
+		for _ in 0 .. 1000 {
+			glsp::gc();
+			if entries()? == 1 {
+				break
+			}
+		}
+
+		assert_eq!(entries()?, 1);
+
+		//the Rust API
+		let double: Root<GFn> = glsp::load_str("(fn (n) (* n 2))", "double.glsp")?.unwrap_gfn();
+		let memoized = glsp::memoize(&double, MemoOpts {
+			max_entries: Some(10),
+			weak_keys: true
+		})?;
+
+		let result: i32 = glsp::call(&memoized, &(21,))?;
+		assert_eq!(result, 42);
+		Ok(())
+	});
+}
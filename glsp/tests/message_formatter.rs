@@ -0,0 +1,95 @@
+//! Checks that the engine's structured messages render as English by default, that a custom
+//! message formatter can replace their text, and that a `GError` exposes its message's id and
+//! arguments.
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+use glsp::{MessageArgs, MessageId};
+
+fn add_one(x: i32) -> i32 {
+	x + 1
+}
+
+fn message(src: &str) -> String {
+	glsp::load_str(src, "test.glsp").unwrap_err().val().to_string()
+}
+
+#[test]
+fn default_text() {
+	run(|| {
+		glsp::bind_rfn("add-one", rfn!(add_one))?;
+
+		let err = glsp::load_str("(add-one 'x)", "test.glsp").unwrap_err();
+		assert_eq!(err.message_id(), Some(MessageId::TypeMismatch));
+
+		let args = err.message_args().unwrap();
+		assert_eq!(args.get("callee"), Some("add-one"));
+		assert_eq!(args.get("arg-index"), Some("0"));
+		assert_eq!(args.get("expected"), Some("an int"));
+		assert_eq!(args.get("received"), Some("a sym"));
+		assert!(err.val().to_string().contains("expected an int, received a sym"));
+
+		assert_eq!(message("(add-one 1 2)"), "(add-one): too many arguments: received 2, \
+		                                      expected exactly 1");
+		assert_eq!(message("no-such-global"), "unbound symbol 'no-such-global'");
+		assert_eq!(message("[(arr 1 2) 5]"), "out-of-bounds arr access: len is 2, index is 5");
+		assert_eq!(message("(defn f (a b) #n) (f 1)"), "(f): received 1 argument, but expected \
+		                                                at least 2 (missing `b`)");
+
+		let err = glsp::global::<_, Val>("no-such-global").unwrap_err();
+		assert_eq!(err.message_id(), Some(MessageId::UnboundGlobal));
+		assert_eq!(err.val().to_string(), "symbol no-such-global is not bound to a global");
+
+		//errors which aren't built from a message don't have an id
+		assert_eq!(glsp::load_str("(bail 'failed)", "test.glsp").unwrap_err().message_id(), None);
+
+		Ok(())
+	});
+}
+
+#[test]
+fn custom_formatter() {
+	run(|| {
+		glsp::bind_rfn("add-one", rfn!(add_one))?;
+
+		glsp::set_message_formatter(Box::new(|id, args| {
+			match id {
+				MessageId::TypeMismatch => {
+					args.substitute("{expected} erwartet, {received} erhalten")
+				}
+				MessageId::ArgContext => {
+					args.substitute("({callee}) Argument {arg-index}: {message}")
+				}
+				MessageId::UnboundSymbol => args.substitute("unbekanntes Symbol '{name}'"),
+				id => id.default_text(args)
+			}
+		}));
+
+		assert_eq!(message("(add-one 'x)"), "(add-one) Argument 0: an int erwartet, \
+		                                     a sym erhalten");
+		assert_eq!(message("no-such-global"), "unbekanntes Symbol 'no-such-global'");
+		assert_eq!(message("[(arr) 0]"), "out-of-bounds arr access: len is 0, index is 0");
+
+		//errors raised by the formatter itself are rendered using the default formatter
+		glsp::set_message_formatter(Box::new(|id, args| {
+			match glsp::global::<_, Val>("no-such-global") {
+				Ok(_) => id.default_text(args),
+				Err(err) => err.val().to_string()
+			}
+		}));
+
+		assert_eq!(message("no-such-global"), "symbol no-such-global is not bound to a global");
+
+		Ok(())
+	});
+}
+
+#[test]
+fn substitute() {
+	let args = MessageArgs::new().with("name", "x").with("len", 3).with("name", "y");
+	assert_eq!(args.iter().collect::<Vec<_>>(), [("name", "y"), ("len", "3")]);
+	assert_eq!(args.substitute("{name}[{len}] {{ok}} {missing}."), "y[3] {ok} .");
+	assert_eq!(args.substitute("unclosed {name"), "unclosed {name");
+}
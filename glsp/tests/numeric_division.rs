@@ -0,0 +1,86 @@
+//! Checks that the division functions agree about when dividing by zero is an error, and that the
+//! rounding functions preserve integers.
+
+mod common;
+
+use common::run;
+
+#[test]
+fn division() {
+	run(|| {
+		glsp::load_str(r#"
+			(defn err? (result)
+			  (eq? [result 0] 'err))
+
+			;the same results whether the call is inlined or made through a fn
+			(let div /)
+			(let rem %)
+			(ensure (err? (try (/ 3 0))))
+			(ensure (err? (try (div 3 0))))
+			(ensure (err? (try (% 3 0))))
+			(ensure (err? (try (rem 3 0))))
+			(ensure (== (/ 3 0.0) (div 3 0.0) +inf.0))
+			(ensure (== (/ -3.0 0) (div -3.0 0) -inf.0))
+			(ensure (nan? (div 0.0 0.0)))
+			(ensure (nan? (rem 3.0 0.0)))
+			(ensure (== (div 0.0) +inf.0))
+			(ensure (== (div 0) +inf.0))
+			(ensure (err? (try (div-euclid 3 0))))
+			(ensure (err? (try (rem-euclid 3 0))))
+			(ensure (nan? (rem-euclid 3.0 0.0)))
+
+			(ensure (== (quot 7 2) 3))
+			(ensure (== (quot -7 2) -3))
+			(ensure (flo? (quot -7.0 2)))
+			(ensure (== (quot -7.0 2) -3.0))
+			(ensure (err? (try (quot 1 0))))
+			(ensure (== (quot 1.0 0) +inf.0))
+
+			(ensure (== (rem -7 3) -1))
+			(ensure (== (mod -7 3) 2))
+			(ensure (== (mod 7 -3) -2))
+			(ensure (== (mod -7 -3) -1))
+			(ensure (== (mod 6 3) 0))
+			(ensure (== (mod -7.5 2) 0.5))
+			(ensure (int? (mod 7 3)))
+			(ensure (err? (try (mod 1 0))))
+			(ensure (nan? (mod 1.0 0)))
+
+			(ensure (== (div-exact 12 4) 3))
+			(ensure (== (div-exact -12 4) -3))
+			(ensure (err? (try (div-exact 12 5))))
+			(ensure (err? (try (div-exact 12 0))))
+			(ensure (err? (try (div-exact 12.0 4))))
+
+			(ensure (== (div-flo 7 2) 3.5))
+			(ensure (== (div-flo 1 0) +inf.0))
+			(ensure (nan? (div-flo 0 0)))
+		"#, "division.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn rounding() {
+	run(|| {
+		glsp::load_str(r#"
+			(ensure (int? (floor 3)))
+			(ensure (int? (ceil 3)))
+			(ensure (int? (round 3)))
+			(ensure (int? (round-even 3)))
+			(ensure (int? (trunc 3)))
+			(ensure (== (trunc -3) -3))
+
+			(ensure (== (round 2.5) 3.0))
+			(ensure (== (round -2.5) -3.0))
+			(ensure (== (round-even 2.5) 2.0))
+			(ensure (== (round-even 3.5) 4.0))
+			(ensure (== (round-even -2.5) -2.0))
+			(ensure (== (trunc -2.7) -2.0))
+			(ensure (flo? (trunc 2.0)))
+		"#, "rounding.glsp")?;
+
+		Ok(())
+	});
+}
@@ -0,0 +1,214 @@
+//! Checks that each opt level produces the same observable results, including the same side
+//! effects in the same order, and the same errors.
+
+use glsp::prelude::*;
+
+const OPT_LEVELS: [u8; 2] = [0, 1];
+
+//each program is loaded into a new Runtime at each opt level. the optimizer should be
+//invisible, so the printed results (or errors) must be identical at every level.
+const PROGRAMS: &[(&str, &str)] = &[
+	("folded arithmetic", r#"
+		(arr (+ 1 2) (- 10 4.5) (* 3 4 5) (/ 7 2) (/ 7.0 2) (% -7 3) (% 7.5 2)
+		     (+ 2147483647 1) (* -2147483648 -1) (- -2147483648) (abs -2147483648)
+		     (/ 1 0.0) (/ -1 0.0) (/ 0.0 0.0) (% 1.0 0) (- 0.0) (* -1 0.0))
+	"#),
+	("folded comparisons", r#"
+		(arr (< 1 2) (< 2 1) (<= 2 2.0) (> \b \a) (>= 1.5 2) (== 1 1.0) (== \a 97)
+		     (== nan.0 nan.0) (< nan.0 1) (>= nan.0 nan.0) (min 1 1.0) (max 1 1.0)
+		     (min \a 98) (max 2 \a) (min nan.0 1) (max 1 nan.0))
+	"#),
+	("folded conversions", r#"
+		(arr (int 3.9) (int -3.9) (int 1e20) (int -1e20) (int nan.0) (int #t) (int #f)
+		     (flo 3) (flo #t) (sign -5) (sign 0.0) (sign -0.0) (sign nan.0) (sign -2.5)
+		     (bool #n) (bool 0) (not #f) (not '()))
+	"#),
+	("folded bitwise ops", r#"
+		(arr (bitand 12 10) (bitor 12 10) (bitxor 12 10) (bitnot 0)
+		     (bitshl 1 31) (bitshl 1 32) (bitshl 1 33) (bitshr -1 28) (bitsar -16 2)
+		     (bitshr 1 -1) (bitsar -1 40))
+	"#),
+	("folded predicates", r#"
+		(arr (int? 1) (int? 1.0) (flo? 1.0) (num? \a) (nil? #n) (nil? #f) (bool? #f)
+		     (sym? 'a) (str? "a") (arr? '(1)) (tab? "a") (nan? nan.0) (nan? 1) (inf? -inf.0)
+		     (deque? "a") (iterable? "a") (iterable? 1))
+	"#),
+	("arithmetic errors", r#"
+		(defn describe (result)
+		  (if (eq? [result 0] 'err) 'err [result 1]))
+
+		(arr (describe (try (+ 1 'a)))
+		     (describe (try (/ 1 0)))
+		     (describe (try (% 1 0)))
+		     (describe (try (bitand 1 1.0)))
+		     (describe (try (< 1 "a")))
+		     (describe (try (int 'a)))
+		     (describe (try (abs #t)))
+		     (describe (try (+ 1 2))))
+	"#),
+	("literal conditions", r#"
+		(def events (arr))
+		(defn event (x) (push! events x) x)
+
+		(arr (if #n (event 'a) (event 'b))
+		     (if 0 (event 'c) (event 'd))
+		     (if '() (event 'e) (event 'f))
+		     (if (< 2 1) (event 'g) (event 'h))
+		     (cond (#f (event 'i)) ((== 1 1) (event 'j)) (else (event 'k)))
+		     (when (> 1 0) (event 'l) (event 'm))
+		     (unless #t (event 'n))
+		     (and 1 (event 'o) #f (event 'p))
+		     (or #f #n (event 'q) (event 'r))
+		     (if #t (do (let x 's) (event x)) (do (let x 't) (event x)))
+		     events)
+	"#),
+	("unreachable forms", r#"
+		(def events (arr))
+		(defn event (x) (push! events x) x)
+
+		(defn early ()
+		  (event 'before)
+		  (return 'returned)
+		  (event 'after)
+		  'fallthrough)
+
+		(defn finished ()
+		  (block outer
+		    (event 'in-block)
+		    (finish-block outer 'finished)
+		    (event 'after-finish)))
+
+		(defn restarted ()
+		  (let n 0)
+		  (block outer
+		    (inc! n)
+		    (when (< n 3)
+		      (restart-block outer)
+		      (event 'after-restart))
+		    n))
+
+		;a fn which contains an unreachable (yield) is still a coroutine
+		(defn coroutine ()
+		  (return 1)
+		  (yield 2))
+
+		(arr (early) (finished) (restarted) (coro? (coroutine)) (coro-run (coroutine)) events)
+	"#),
+	("unused bindings", r#"
+		(def calls 0)
+		(defn side-effect ()
+		  (inc! calls)
+		  calls)
+
+		(defn f ()
+		  (let unused-literal 10)
+		  (let unused-fn (fn () (side-effect)))
+		  (let unused-call (side-effect))
+		  (let mutated 1)
+		  (= mutated 2)
+		  (let captured 0)
+		  (let incr (fn () (inc! captured)))
+		  (incr)
+		  (incr)
+		  (let shadowed 1)
+		  (let shadowed (+ shadowed 1))
+		  (arr mutated captured shadowed calls))
+
+		(arr (f) (f) calls)
+	"#),
+	("defer ordering", r#"
+		(def events (arr))
+
+		(defn f (n)
+		  (defer (push! events (arr 'outer n)))
+		  (do
+		    (defer (push! events (arr 'inner n)))
+		    (push! events (arr 'body n)))
+		  (if (== n 0)
+		    (do
+		      (defer (push! events 'last))
+		      (+ 1 2))
+		    (f (- n 1))))
+
+		(defn g ()
+		  (let result (do
+		    (defer (push! events 'g-defer))
+		    (* 6 7)))
+		  result)
+
+		(arr (f 2) (g) events)
+	"#),
+	("loops", r#"
+		(let total 0)
+		(forn (i 10)
+		  (when (== i 3) (continue))
+		  (when (== i 8) (break))
+		  (inc! total i))
+
+		(let collected (arr))
+		(let n 0)
+		(while (< n 20)
+		  (inc! n)
+		  (cond
+		    ((== (% n 5) 0) (push! collected 'five))
+		    ((and (> n 10) (odd? n)) (push! collected n))
+		    (else #n)))
+
+		(let nested (arr))
+		(forn (i 3)
+		  (forn (j 3)
+		    (if (== i j) (continue) (push! nested (arr i j)))))
+
+		(let found (loop
+		  (inc! n)
+		  (when (> n 25) (break n))))
+
+		(arr total collected nested found
+		     (arr ..(map (fn1 (if (< _ 2) 'small 'large)) (rn 4))))
+	"#),
+	("runtime errors", r#"
+		(defn fails (x)
+		  (let unused 1)
+		  (+ x 'a))
+
+		(fails 1)
+	"#),
+];
+
+fn observe(opt_level: u8, src: &str) -> String {
+	let runtime = RuntimeBuilder::new().opt_level(opt_level).build();
+	runtime.run(|| {
+		assert_eq!(glsp::opt_level(), opt_level);
+
+		Ok(match glsp::load_str(src, "test.glsp") {
+			Ok(val) => format!("{:?}", val),
+			Err(err) => format!("error: {}", err)
+		})
+	}).unwrap()
+}
+
+#[test]
+fn same_results() {
+	for &(name, src) in PROGRAMS {
+		let unoptimized = observe(0, src);
+		for &opt_level in &OPT_LEVELS[1..] {
+			let optimized = observe(opt_level, src);
+			assert_eq!(unoptimized, optimized, "{} differs at opt level {}", name, opt_level);
+		}
+	}
+}
+
+//a sanity check that the programs above are actually being optimized
+#[test]
+fn folding_is_observable_in_bytecode() {
+	let dumps: Vec<String> = OPT_LEVELS.iter().map(|&opt_level| {
+		let runtime = RuntimeBuilder::new().opt_level(opt_level).build();
+		runtime.run(|| {
+			let val = glsp::load_str("(fn (x) (if (< 1 2) x (bail)))", "test.glsp")?;
+			let gfn = <Root<GFn> as FromVal>::from_val(&val)?;
+			Ok(glsp::disassemble(&gfn))
+		}).unwrap()
+	}).collect();
+
+	assert_ne!(dumps[0], dumps[1]);
+}
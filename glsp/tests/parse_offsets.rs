@@ -0,0 +1,105 @@
+//! Checks that `glsp::parse_with_offsets` reports the byte range of every form and atom, and
+//! that `glsp::definitions` finds toplevel definitions without evaluating anything.
+
+mod common;
+
+use common::run;
+use glsp::{ParsedForm};
+
+//each form's source text, in depth-first order
+fn texts<'a>(text: &'a str, form: &ParsedForm, dst: &mut Vec<&'a str>) {
+	dst.push(&text[form.range()]);
+	for child in form.children() {
+		texts(text, child, dst);
+	}
+}
+
+#[test]
+fn offsets() {
+	run(|| {
+		let text = "; comment\n(defn f (x)\n  (+ x 1.5))  \n\n sym \"λ str\"";
+		let forms = glsp::parse_with_offsets(text, Some("test.glsp"))?;
+		assert_eq!(forms.len(), 3);
+
+		let mut found = Vec::new();
+		texts(text, &forms[0], &mut found);
+		assert_eq!(found, [
+			"(defn f (x)\n  (+ x 1.5))", "defn", "f", "(x)", "x", "(+ x 1.5)", "+", "x", "1.5"
+		]);
+
+		//ranges are byte offsets, even after multibyte characters
+		assert_eq!(&text[forms[1].range()], "sym");
+		assert_eq!(&text[forms[2].range()], "\"λ str\"");
+		assert_eq!(forms[2].val().to_string(), "λ str");
+
+		//the vals are identical to those produced by parse_all
+		let plain = glsp::parse_all(text, None)?;
+		for (form, val) in forms.iter().zip(plain.iter()) {
+			assert!(form.val().try_eq(val)?);
+		}
+
+		Ok(())
+	});
+}
+
+#[test]
+fn abbreviations_and_literals() {
+	run(|| {
+		let text = "('a [b 0] #;(skipped) `(c ~d) \"x{y}z\" #((k v)) ..e)";
+		let forms = glsp::parse_with_offsets(text, None)?;
+
+		let mut found = Vec::new();
+		texts(text, &forms[0], &mut found);
+		assert_eq!(found, [
+			text,
+			"'a", "a",
+			"[b 0]", "b", "0",
+			"`(c ~d)", "(c ~d)", "c", "~d", "d",
+			"\"x{y}z\"", "y",
+			"#((k v))", "k", "v",
+			"..e", "e"
+		]);
+
+		Ok(())
+	});
+}
+
+#[test]
+fn definitions() {
+	run(|| {
+		let text = r#"
+			(def counter 0)
+			(defn spawn (kind)
+			  (defn nested ()))
+			(defmacro when-alive (..body) #n)
+			(defclass Enemy (field hp 10))
+			(defstruct Point x y)
+			(prn "not a definition")
+			(def (a b) (arr 1 2))
+		"#;
+
+		let defs = glsp::definitions(text)?;
+		let summary: Vec<(String, String, &str)> = defs.iter().map(|def| {
+			(def.kind().to_string(), def.name().to_string(), &text[def.name_range()])
+		}).collect();
+
+		assert_eq!(summary, [
+			("def".to_string(), "counter".to_string(), "counter"),
+			("defn".to_string(), "spawn".to_string(), "spawn"),
+			("defmacro".to_string(), "when-alive".to_string(), "when-alive"),
+			("defclass".to_string(), "Enemy".to_string(), "Enemy"),
+			("defstruct".to_string(), "Point".to_string(), "Point")
+		]);
+
+		assert!(text[defs[1].range()].starts_with("(defn spawn"));
+		assert!(text[defs[1].range()].ends_with("(defn nested ()))"));
+
+		//nothing was evaluated
+		assert!(!glsp::has_global("counter")?);
+
+		//syntax errors are reported
+		assert!(glsp::definitions("(defn broken").is_err());
+
+		Ok(())
+	});
+}
@@ -0,0 +1,178 @@
+//! Checks that `parr` and `ptab` return updated copies without modifying the original, that they
+//! remain correct as their trees grow and shrink across node boundaries, and that they support
+//! indexing, iteration, splaying, equality and hashing.
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+use glsp::{PArr, PTab};
+
+#[test]
+fn parr_versions() {
+	run(|| {
+		glsp::load_str(r#"
+			(let v0 (parr 1 2 3))
+			(let v1 (passoc v0 0 10))
+			(let v2 (pconj v1 4 5))
+			(let v3 (ppop v2))
+
+			(ensure (parr? v0))
+			(ensure (not (parr? (arr 1 2 3))))
+			(ensure (eq? (type-of v0) 'parr))
+			(ensure (eq? (arr ..v0) '(1 2 3)))
+			(ensure (eq? (arr ..v1) '(10 2 3)))
+			(ensure (eq? (arr ..v2) '(10 2 3 4 5)))
+			(ensure (eq? (arr ..v3) '(10 2 3 4)))
+
+			(ensure (== [v2 0] 10))
+			(ensure (== [v2 -1] 5))
+			(ensure (== (len v2) 5))
+			(ensure (empty? (parr)))
+			(ensure (has? v0 2))
+			(ensure (not (has? v0 3)))
+			(ensure (nil? [v0 (? 3)]))
+			(ensure (eq? [(try [v0 3]) 0] 'err))
+			(ensure (eq? [(try (= [v0 0] 1)) 0] 'err))
+			(ensure (eq? [(try (ppop (parr))) 0] 'err))
+			(ensure (eq? (arr ..(passoc v0 3 4)) '(1 2 3 4)))
+			(ensure (eq? (arr ..(passoc v0 -1 30)) '(1 2 30)))
+
+			(ensure (eq? v0 (parr 1 2 3)))
+			(ensure (not (eq? v0 v1)))
+			(ensure (not (eq? v0 (arr 1 2 3))))
+			(ensure (== (hash v0) (hash (parr 1 2 3))))
+
+			(let total 0)
+			(for x in v2
+				(inc! total x))
+			(ensure (== total 24))
+			(ensure (eq? (arr ..(rev v0)) '(3 2 1)))
+		"#, "persistent.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn parr_growth() {
+	run(|| {
+		//cross the boundaries at which the tail is pushed into the tree, and at which the tree
+		//gains an extra level, then pop back down to empty
+		glsp::load_str(r#"
+			(let versions (arr))
+			(let p (parr))
+			(forn (i 1100)
+				(push! versions p)
+				(= p (pconj p i)))
+
+			(ensure (== (len p) 1100))
+			(forn (i 1100)
+				(ensure (== [p i] i))
+				(ensure (== (len [versions i]) i)))
+
+			(let p2 (passoc p 1050 'x))
+			(ensure (eq? [p2 1050] 'x))
+			(ensure (== [p 1050] 1050))
+
+			(forn (i 1100)
+				(= p (ppop p))
+				(ensure (eq? p [versions (- 1099 i)])))
+			(ensure (empty? p))
+		"#, "persistent.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn ptab_versions() {
+	run(|| {
+		glsp::load_str(r#"
+			(let t0 (ptab ('a 1) ('b 2)))
+			(let t1 (passoc t0 'c 3))
+			(let t2 (pdissoc t1 'a))
+
+			(ensure (ptab? t0))
+			(ensure (eq? (type-of t0) 'ptab))
+			(ensure (== (len t0) 2))
+			(ensure (== (len t1) 3))
+			(ensure (== (len t2) 2))
+			(ensure (== [t1 'c] 3))
+			(ensure (not (has? t0 'c)))
+			(ensure (not (has? t2 'a)))
+			(ensure (nil? [t2 (? 'a)]))
+			(ensure (eq? [(try [t2 'a]) 0] 'err))
+			(ensure (eq? [(try (= [t0 'a] 10)) 0] 'err))
+
+			(ensure (eq? t0 (ptab ('b 2) ('a 1))))
+			(ensure (eq? (pdissoc t0 'z) t0))
+			(ensure (eq? (ptab ..t0 ('c 3)) t1))
+			(ensure (eq? (ptab ..(arr '(a 1) '(b 2))) t0))
+			(ensure (== (hash t0) (hash (ptab ('b 2) ('a 1)))))
+
+			(let sum 0)
+			(for (k v) in t1
+				(inc! sum v))
+			(ensure (== sum 6))
+		"#, "persistent.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn ptab_many_keys() {
+	run(|| {
+		glsp::load_str(r#"
+			(let t (ptab))
+			(let snapshot #n)
+			(forn (i 5000)
+				(= t (passoc t i (* i 2)))
+				(when (== i 2499)
+					(= snapshot t)))
+
+			(ensure (== (len t) 5000))
+			(ensure (== (len snapshot) 2500))
+			(forn (i 5000)
+				(ensure (== [t i] (* i 2))))
+
+			(forn (i 0 5000 2)
+				(= t (pdissoc t i)))
+			(ensure (== (len t) 2500))
+			(forn (i 5000)
+				(ensure (eq? (has? t i) (odd? i))))
+
+			(forn (i 2500)
+				(ensure (== [snapshot i] (* i 2))))
+		"#, "persistent.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn rust_api() {
+	run(|| {
+		let p0: Root<PArr> = glsp::parr_from_iter(0..100)?;
+		let p1 = p0.assoc(50, "fifty")?;
+		assert_eq!(p0.get::<i32>(50)?, 50);
+		assert_eq!(p1.get::<String>(50)?, "fifty");
+		assert_eq!(p1.pop()?.len(), 99);
+		assert_eq!(p0.iter().count(), 100);
+		assert_eq!(p0.to_arr().len(), 100);
+
+		let t0: Root<PTab> = glsp::ptab_from_iter(vec![(1, 10), (2, 20)])?;
+		let t1 = t0.assoc(3, 30)?;
+		assert!(!t0.has(3)?);
+		assert_eq!(t1.get::<_, i32>(3)?, 30);
+		assert_eq!(t1.dissoc(1)?.len(), 2);
+		assert_eq!(t1.entries().len(), 3);
+
+		glsp::bind_global("p", p0)?;
+		let val: Val = glsp::load_str("(passoc p 0 'zero)", "persistent.glsp")?;
+		assert!(val.is_parr());
+
+		Ok(())
+	});
+}
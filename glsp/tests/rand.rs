@@ -0,0 +1,84 @@
+//! Checks the argument validation of the seeded rng functions, and the exact sequences which
+//! they produce for a fixed seed.
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+use std::mem::{size_of};
+
+#[test]
+fn arguments() {
+	run(|| {
+		glsp::load_str(r#"
+			(let r (rng 7))
+
+			(ensure (== (rand-normal r 3.5 0) 3.5))
+			(ensure (eq? [(try (rand-normal r 0 -1)) 0] 'err))
+			(ensure (eq? [(try (rand-normal r 0 nan.0)) 0] 'err))
+
+			(ensure (eq? (rand-weighted r '(a b) '(0 1.5)) 'b))
+			(ensure (eq? (rand-weighted 'a 0 'b 0.5) 'b))
+			(ensure (eq? [(try (rand-weighted r '(a b) '(0 0))) 0] 'err))
+			(ensure (eq? [(try (rand-int r 5 5)) 0] 'err))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn golden_sequences() {
+	//these sequences are fixed by the documented algorithm, so they must never change
+	let (ints, flos) = run(|| {
+		let ints = glsp::load_str(r#"
+			(let r (rng 42))
+			(arr ..(map (fn1 (rand-int r -100 100)) (rn 5)))
+		"#, "test.glsp")?;
+
+		let flos = glsp::load_str(r#"
+			(let r (rng 42))
+			(arr (rand-flo r) (rand-flo r) (rand-flo r))
+		"#, "test.glsp")?;
+
+		Ok((Vec::<i32>::from_val(&ints)?, Vec::<Flo>::from_val(&flos)?))
+	});
+
+	assert_eq!(ints, [-18, 93, -100, 7, 30]);
+
+	//rand-flo uses the full precision of a Flo, so each result is an exact fraction
+	let expected: Vec<Flo> = if size_of::<Flo>() == size_of::<f64>() {
+		[3726293315029119_u64, 35884652119011, 5855433423259794].iter()
+			.map(|&bits| (bits as f64 / (1_u64 << 53) as f64) as Flo)
+			.collect()
+	} else {
+		[6940762_u32, 16270330, 66840].iter()
+			.map(|&bits| (bits as f32 / (1_u32 << 24) as f32) as Flo)
+			.collect()
+	};
+
+	assert_eq!(flos, expected);
+}
+
+#[test]
+fn wide_seeds() {
+	run(|| {
+		glsp::load_str(r#"
+			(ensure (eq? (rng-state (rng (arr 305419896 -1698898192)))
+			             '(1171149032 370746054 -1586922656 -1384772433)))
+
+			;an int seed is the low 32 bits of a 64-bit seed
+			(ensure (eq? (rng-state (rng -1698898192))
+			             '(-1286325078 -913702268 1851377100 1637169225)))
+			(ensure (eq? (rng-state (rng 7)) (rng-state (rng (arr 0 7)))))
+			(ensure (not (eq? (rng-state (rng 7)) (rng-state (rng (arr 1 7))))))
+
+			(ensure (eq? [(try (rng (arr 1))) 0] 'err))
+			(ensure (eq? [(try (rng (arr 1 2 3))) 0] 'err))
+			(ensure (eq? [(try (rng (arr 1 2.5))) 0] 'err))
+			(ensure (eq? [(try (rng 'seed)) 0] 'err))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
@@ -0,0 +1,125 @@
+//! Checks that `lines` iterates over a str in both directions without copying it, and that
+//! `read-lines` streams a file's lines, reports invalid UTF-8 and can be closed early.
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+use glsp::{FsPolicy};
+use std::fs;
+use std::path::{PathBuf};
+
+fn temp_file(name: &str, contents: &[u8]) -> PathBuf {
+	let path = std::env::temp_dir().join(format!("glsp-{}-{}.txt", name, std::process::id()));
+	fs::write(&path, contents).unwrap();
+	path
+}
+
+#[test]
+fn lines() {
+	run(|| {
+		glsp::load_str(r#"
+			(ensure (eq? (arr ..(lines "")) '()))
+			(ensure (eq? (arr ..(lines "a")) '("a")))
+			(ensure (eq? (arr ..(lines "a\n")) '("a")))
+			(ensure (eq? (arr ..(lines "\n")) '("")))
+			(ensure (eq? (arr ..(lines "a\r\n\nb\rc\n")) '("a" "" "b\rc")))
+
+			(ensure (eq? (arr ..(rev (lines "a\r\n\nb\n"))) '("b" "" "a")))
+			(ensure (eq? (arr ..(rev (lines "\n"))) '("")))
+
+			(let it (lines "one\ntwo\nthree\nfour"))
+			(ensure (eq? (iter-next! it) "one"))
+			(ensure (eq? (iter-next-back! it) "four"))
+			(ensure (eq? (iter-next-back! it) "three"))
+			(ensure (eq? (iter-next! it) "two"))
+			(ensure (nil? (iter-next! it)))
+			(ensure (nil? (iter-next-back! it)))
+
+			;the iterator reads from the original str, rather than a copy
+			(let src (clone "a\nb"))
+			(let it (lines src))
+			(ensure (eq? (iter-next! it) "a"))
+			(push! src \c)
+			(ensure (eq? (iter-next! it) "bc"))
+
+			;closing an iterator finishes it early
+			(let it (lines "a\nb"))
+			(close it)
+			(ensure (iter-finished? it))
+			(ensure (nil? (iter-next! it)))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn read_lines() {
+	let path = temp_file("read-lines", b"first\r\nsecond\n\nfourth");
+	let bad_path = temp_file("read-lines-bad", b"ok\nbad \xff byte\nok\n");
+
+	let filename = path.to_str().unwrap().to_string();
+	let bad_filename = bad_path.to_str().unwrap().to_string();
+
+	let result = std::panic::catch_unwind(move || run(move || {
+		glsp::bind_global("path", filename.as_str())?;
+		glsp::bind_global("bad-path", bad_filename.as_str())?;
+
+		//filesystem access must be enabled
+		assert!(glsp::load_str("(read-lines path)", "test.glsp").is_err());
+		glsp::enable_fs(FsPolicy { root: None, write: false })?;
+
+		glsp::load_str(r#"
+			(ensure (eq? (arr ..(read-lines path)) '("first" "second" "" "fourth")))
+			(ensure (not (iter-double-ended? (read-lines path))))
+
+			;stopping early
+			(let it (read-lines path))
+			(ensure (eq? (iter-next! it) "first"))
+			(close it)
+			(ensure (nil? (iter-next! it)))
+
+			(ensure (eq? (arr ..(read-lines bad-path 'lossy)) '("ok" "bad � byte" "ok")))
+			(ensure (eq? [(try (read-lines bad-path 'strict)) 0] 'err))
+		"#, "test.glsp")?;
+
+		let message = glsp::load_str("(arr ..(read-lines bad-path))", "test.glsp")
+			.unwrap_err().to_string();
+		assert!(message.contains("invalid UTF-8 on line 2"), "{}", message);
+
+		//the rust api
+		let lines: Vec<String> = glsp::read_lines(&bad_filename, true)?
+			.map(|line| String::from_val(&line?))
+			.collect::<GResult<_>>()?;
+		assert_eq!(lines, ["ok", "bad \u{FFFD} byte", "ok"]);
+
+		assert!(glsp::read_lines("no/such/file.txt", false).is_err());
+
+		Ok(())
+	}));
+
+	fs::remove_file(&path).unwrap();
+	fs::remove_file(&bad_path).unwrap();
+	result.unwrap();
+}
+
+#[test]
+fn rust_iter() {
+	run(|| {
+		let it = glsp::rust_iter((1 ..= 3).map(|i| {
+			if i == 2 { Err(error!("failed")) } else { Ok(i) }
+		}));
+		glsp::bind_global("it", it)?;
+
+		glsp::load_str(r#"
+			(ensure (== (iter-next! it) 1))
+			(ensure (eq? [(try (iter-next! it)) 0] 'err))
+			(ensure (== (iter-next! it) 3))
+			(ensure (nil? (iter-next! it)))
+			(ensure (iter-finished? it))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
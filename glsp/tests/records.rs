@@ -0,0 +1,186 @@
+//! Checks that `(defrecord)` defines a compact fixed-field type which supports construction,
+//! access, destructuring, structural equality and hashing, and which can be printed and then
+//! parsed back in.
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+use glsp::{Obj, Rec};
+use std::mem::{size_of};
+
+#[test]
+fn construction_and_access() {
+	run(|| {
+		glsp::load_str(r#"
+			(defrecord Hit pos normal dist)
+
+			(let h (Hit:new 1 2 3))
+			(ensure (rec? h))
+			(ensure (Hit? h))
+			(ensure (not (Hit? 10)))
+			(ensure (not (obj? h)))
+			(ensure (eq? (type-of h) 'rec))
+			(ensure (eq? (rec-name h) 'Hit))
+			(ensure (eq? (rec-fields h) '(pos normal dist)))
+
+			(ensure (== (Hit:pos h) 1))
+			(ensure (== (Hit:normal h) 2))
+			(ensure (== (Hit:dist h) 3))
+			(ensure (== [h 'dist] 3))
+			(ensure (has? h 'normal))
+			(ensure (not (has? h 'velocity)))
+			(ensure (nil? [h (? 'velocity)]))
+
+			(= [h 'dist] 10)
+			(ensure (== (Hit:dist h) 10))
+			(ensure (eq? [(try (= [h 'velocity] 1)) 0] 'err))
+			(ensure (eq? [(try [h 'velocity]) 0] 'err))
+
+			;the named constructor macro accepts fields in any order, and a base record
+			(let h2 (Hit (dist 5) (pos 'a) (normal 'b)))
+			(ensure (eq? (Hit:pos h2) 'a))
+			(let h3 (Hit (dist 6) ..h2))
+			(ensure (eq? (Hit:pos h3) 'a))
+			(ensure (== (Hit:dist h3) 6))
+			(ensure (eq? [(try (Hit:new 1 2)) 0] 'err))
+
+			(defrecord Other pos)
+			(ensure (not (Hit? (Other:new 1))))
+			(ensure (eq? [(try (Hit:pos (Other:new 1))) 0] 'err))
+
+			;freezing and cloning
+			(let c (clone h))
+			(= [c 'pos] 100)
+			(ensure (== (Hit:pos h) 1))
+			(freeze! h)
+			(ensure (eq? [(try (= [h 'pos] 5)) 0] 'err))
+
+			(let nested (Hit:new (arr 1 2) 0 0))
+			(let deep (deep-clone nested))
+			(push! (Hit:pos deep) 3)
+			(ensure (== (len (Hit:pos nested)) 2))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn errors() {
+	run(|| {
+		glsp::load_str(r#"
+			(ensure (eq? [(try (eval '(defrecord Bad a a))) 0] 'err))
+			(ensure (eq? [(try (eval '(defrecord Bad 1))) 0] 'err))
+
+			(defrecord Pair a b)
+			(ensure (eq? [(try (eval '(Pair (a 1) (c 2)))) 0] 'err))
+			(ensure (eq? [(try (eval '(match (Pair:new 1 2) ((Pair :c c) c)))) 0] 'err))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn destructuring() {
+	run(|| {
+		glsp::load_str(r#"
+			(defrecord Hit pos normal dist)
+			(defclass Thing (field pos 0))
+
+			(defn describe (val)
+			  (match val
+			    ((Hit :pos p :dist d) (arr 'hit p d))
+			    ((Thing :pos p) (arr 'thing p))
+			    (_ 'other)))
+
+			(ensure (eq? (describe (Hit:new 1 2 3)) '(hit 1 3)))
+			(ensure (eq? (describe (Thing)) '(thing 0)))
+			(ensure (eq? (describe 10) 'other))
+
+			(let (Hit :normal n) (Hit:new 1 2 3))
+			(ensure (== n 2))
+			(ensure (eq? [(try (let (Hit :normal n) 10)) 0] 'err))
+
+			(defn dist-of ((h : Hit)) (Hit:dist h))
+			(ensure (== (dist-of (Hit:new 1 2 3)) 3))
+			(ensure (eq? [(try (dist-of 10)) 0] 'err))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn equality_and_hashing() {
+	run(|| {
+		glsp::load_str(r#"
+			(defrecord Hit pos normal dist)
+			(defrecord Other pos normal dist)
+
+			(ensure (eq? (Hit:new 1 (arr 2) 3) (Hit:new 1 (arr 2) 3)))
+			(ensure (not (same? (Hit:new 1 2 3) (Hit:new 1 2 3))))
+			(ensure (not (eq? (Hit:new 1 2 3) (Hit:new 1 2 4))))
+			(ensure (not (eq? (Hit:new 1 2 3) (Other:new 1 2 3))))
+
+			(ensure (== (hash (Hit:new 1 2 3)) (hash (Hit:new 1 2 3))))
+			(ensure (not (== (hash (Hit:new 1 2 3)) (hash (Other:new 1 2 3)))))
+
+			;records can be used as table keys
+			(let t (tab ((Hit:new 1 2 3) 'found)))
+			(ensure (eq? [t (Hit:new 1 2 3)] 'found))
+		"#, "test.glsp")?;
+
+		//redefining a record type with the same fields preserves its type
+		let hit = glsp::sym("Hit")?;
+		let fields = glsp::rec_fields(hit).unwrap();
+		let before = glsp::rec(hit, &[Val::Int(1), Val::Int(2), Val::Int(3)])?;
+		glsp::define_rec(hit, &fields)?;
+		let after = glsp::rec(hit, &[Val::Int(1), Val::Int(2), Val::Int(3)])?;
+		assert!(before.same_type(&after));
+		assert!(before.try_eq(&after)?);
+
+		Ok(())
+	});
+}
+
+#[test]
+fn printing() {
+	run(|| {
+		glsp::load_str(r#"
+			(defrecord Hit pos normal dist)
+		"#, "test.glsp")?;
+
+		let hit: Root<Rec> = glsp::rec(glsp::sym("Hit")?, &[Val::Int(1), Val::Int(2), Val::Int(3)])?;
+		let printed = hit.to_string();
+		assert_eq!(printed, "#rec(Hit (pos 1) (normal 2) (dist 3))");
+
+		let parsed = glsp::parse_1(&printed, None)?;
+		match parsed {
+			Val::Rec(ref parsed) => {
+				assert!(parsed.is_frozen());
+				assert!(parsed.try_eq(&hit)?);
+			}
+			_ => panic!("expected a rec, parsed {}", parsed)
+		}
+
+		assert!(glsp::parse_1("#rec(Missing (a 1))", None).is_err());
+		assert!(glsp::parse_1("#rec(Hit (pos 1))", None).is_err());
+		assert!(glsp::parse_1("#rec(Hit (pos 1) (normal 2) (dist 3) (x 4))", None).is_err());
+
+		//record literals are self-evaluating
+		glsp::load_str(r#"
+			(let h #rec(Hit (pos 1) (normal 2) (dist 3)))
+			(ensure (Hit? h))
+			(ensure (== (Hit:dist h) 3))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn size() {
+	assert!(size_of::<Rec>() < size_of::<Obj>());
+}
@@ -0,0 +1,210 @@
+//! Checks that each `RuntimeBuilder` option is applied to the `Runtime` it builds, that
+//! contradictory options are rejected by `try_build`, and that the setters for fixed options
+//! return an `Err`.
+
+use glsp::prelude::*;
+use glsp::{BuildError, Caps, EnvPolicy, FileResolution, FsPolicy, PrintLevel};
+use std::borrow::{Cow};
+use std::cell::{RefCell};
+use std::path::{PathBuf};
+use std::rc::{Rc};
+
+fn expect_err(builder: RuntimeBuilder, fragment: &str) {
+	let result: Result<Runtime, BuildError> = builder.try_build();
+	match result {
+		Ok(_) => panic!("expected an Err mentioning {:?}", fragment),
+		Err(err) => {
+			let message = err.to_string();
+			assert!(message.contains(fragment), "unexpected error message {:?}", message);
+		}
+	}
+}
+
+#[test]
+fn defaults() {
+	let runtime = Runtime::new();
+	runtime.run(|| {
+		assert_eq!(glsp::opt_level(), 1);
+		assert!(glsp::retain_param_names());
+		assert!(!glsp::type_checks());
+		assert!(glsp::tail_calls());
+		assert_eq!(glsp::remaining_fuel(), None);
+		assert_eq!(glsp::log_level(), PrintLevel::Print);
+		Ok(())
+	}).unwrap();
+}
+
+#[test]
+fn fixed_options() {
+	let runtime = RuntimeBuilder::new()
+		.opt_level(0)
+		.retain_param_names(false)
+		.type_checks(true)
+		.build();
+
+	runtime.run(|| {
+		assert_eq!(glsp::opt_level(), 0);
+		assert!(!glsp::retain_param_names());
+		assert!(glsp::type_checks());
+
+		#[allow(deprecated)]
+		{
+			assert!(glsp::set_opt_level(1).is_err());
+			assert!(glsp::set_retain_param_names(true).is_err());
+			assert!(glsp::set_type_checks(false).is_err());
+		}
+
+		assert_eq!(glsp::opt_level(), 0);
+		assert!(glsp::type_checks());
+
+		glsp::load_str(r#"
+			(defn f ((n : int)) n)
+			(ensure (eq? (try (f 'x)) '(err type-error)))
+		"#, "test.glsp")?;
+
+		Ok(())
+	}).unwrap();
+}
+
+#[test]
+fn changeable_options() {
+	let lines = Rc::new(RefCell::new(Vec::<(PrintLevel, String)>::new()));
+	let lines2 = Rc::clone(&lines);
+
+	let runtime = RuntimeBuilder::new()
+		.tail_calls(false)
+		.fuel(1_000_000)
+		.gc_ratio(2.0)
+		.log_level(PrintLevel::Warn)
+		.print_sink(Box::new(move |level, line| {
+			lines2.borrow_mut().push((level, line.to_string()))
+		}))
+		.loader(|filename| {
+			match filename {
+				"scripts/main.glsp" => Ok(Cow::Borrowed("(def loaded 'yes)")),
+				_ => bail!("{} doesn't exist", filename)
+			}
+		})
+		.file_resolution(FileResolution::Root)
+		.clock(|| 12.5)
+		.rng_seed(10)
+		.build();
+
+	runtime.run(|| {
+		assert!(!glsp::tail_calls());
+		assert!(glsp::remaining_fuel().unwrap() <= 1_000_000);
+		assert_eq!(glsp::gc_ratio(), 2.0);
+		assert_eq!(glsp::log_level(), PrintLevel::Warn);
+
+		glsp::load("scripts/main.glsp")?;
+		let loaded: Sym = glsp::global("loaded")?;
+		assert_eq!(loaded, glsp::sym("yes")?);
+		assert_eq!(glsp::time(), 12.5);
+
+		glsp::load_str(r#"
+			(prn "discarded")
+			(eprn "kept")
+		"#, "test.glsp")?;
+
+		//each of these options can still be changed after the Runtime is built
+		glsp::set_tail_calls(true);
+		glsp::set_fuel(None);
+		glsp::set_log_level(PrintLevel::Print);
+		assert!(glsp::tail_calls());
+		assert_eq!(glsp::remaining_fuel(), None);
+
+		Ok(())
+	}).unwrap();
+
+	assert_eq!(*lines.borrow(), [(PrintLevel::Error, "kept".to_string())]);
+
+	//the same seed produces the same random numbers
+	let first = RuntimeBuilder::new().rng_seed(10).build();
+	let second = RuntimeBuilder::new().rng_seed(10).build();
+	let a = first.run(|| Ok(glsp::rand_i32())).unwrap();
+	let b = second.run(|| Ok(glsp::rand_i32())).unwrap();
+	assert_eq!(a, b);
+}
+
+#[test]
+fn capability_policies() {
+	let runtime = RuntimeBuilder::new()
+		.env(EnvPolicy { args: Some(vec!["a".to_string(), "b".to_string()]) })
+		.build();
+
+	runtime.run(|| {
+		glsp::load_str(r#"
+			(ensure (eq? (args) '("a" "b")))
+		"#, "test.glsp")?;
+
+		Ok(())
+	}).unwrap();
+}
+
+#[test]
+fn invalid_combinations() {
+	expect_err(
+		RuntimeBuilder::new()
+			.print_sink(Box::new(|_, _| ()))
+			.pr_writer(Box::new(std::io::sink())),
+		"mutually exclusive"
+	);
+
+	expect_err(
+		RuntimeBuilder::new()
+			.print_sink(Box::new(|_, _| ()))
+			.epr_writer(Box::new(std::io::sink())),
+		"mutually exclusive"
+	);
+
+	expect_err(
+		RuntimeBuilder::new().gc_ratio(f32::NAN),
+		"gc_ratio"
+	);
+
+	expect_err(
+		RuntimeBuilder::new().max_call_depth(0),
+		"max_call_depth"
+	);
+
+	let fs_policy = FsPolicy { root: None, write: false };
+	expect_err(
+		RuntimeBuilder::new().sandboxed(true).fs(fs_policy.clone()),
+		"sandboxed"
+	);
+	expect_err(
+		RuntimeBuilder::new().deny(Caps::FS).fs(fs_policy),
+		"FS capability"
+	);
+
+	expect_err(
+		RuntimeBuilder::new().sandboxed(true).env(EnvPolicy::default()),
+		"sandboxed"
+	);
+	expect_err(
+		RuntimeBuilder::new().deny(Caps::ENV).env(EnvPolicy::default()),
+		"ENV capability"
+	);
+
+	//options which can only be checked while the Runtime is being built
+	let missing = FsPolicy { root: Some(PathBuf::from("no/such/directory")), write: false };
+	expect_err(
+		RuntimeBuilder::new().fs(missing),
+		"invalid filesystem root"
+	);
+}
+
+#[cfg(not(feature = "compiler"))]
+#[test]
+fn load_compiled_without_compiler() {
+	expect_err(
+		RuntimeBuilder::new().load_compiled(Vec::new()),
+		"compiler feature"
+	);
+}
+
+#[test]
+#[should_panic(expected = "unable to build a Runtime")]
+fn build_panics() {
+	RuntimeBuilder::new().gc_ratio(f32::INFINITY).build();
+}
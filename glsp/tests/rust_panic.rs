@@ -0,0 +1,238 @@
+//! Checks that a panic within an rfn is converted into a `rust-panic` error which scripts can
+//! catch, that it poisons the Runtime and any rdata which it had mutably borrowed, and that
+//! unrelated scripts keep working afterwards.
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+use glsp::{MessageId};
+use std::cell::{RefCell};
+use std::panic;
+
+fn eval<T: FromVal>(src: &str) -> GResult<T> {
+	T::from_val(&glsp::load_str(src, "test.glsp")?)
+}
+
+fn explode(msg: &str) -> i32 {
+	panic!("{}", msg)
+}
+
+fn explode_with_int() {
+	std::panic::panic_any(10i32)
+}
+
+fn explode_later(callback: Root<GFn>, arg: Val) -> GResult<Val> {
+	//the error from the inner rfn passes through this rfn like any other error
+	glsp::call(&callback, &(arg,))
+}
+
+rdata! {
+	#[derive(Default)]
+	struct Inventory {
+		items: Vec<String>,
+		weight: i32
+	}
+
+	meths {
+		"add": Inventory::add,
+		"weight": Inventory::weight
+	}
+}
+
+impl Inventory {
+	fn add(&mut self, item: &str, weight: i32) {
+		self.items.push(item.to_string());
+		assert!(weight >= 0, "negative weight for {}", item);
+		self.weight += weight;
+	}
+
+	fn weight(&self) -> i32 {
+		self.weight
+	}
+}
+
+fn inventory() -> Inventory {
+	Inventory::default()
+}
+
+fn check_inventory(inventory: &Inventory) {
+	assert!(inventory.items.len() < 10, "too many items");
+}
+
+fn bind_rfns() -> GResult<()> {
+	glsp::bind_rfn("explode", rfn!(explode))?;
+	glsp::bind_rfn("explode-with-int", rfn!(explode_with_int))?;
+	glsp::bind_rfn("explode-later", rfn!(explode_later))?;
+	glsp::bind_rfn("inventory", rfn!(inventory))?;
+	glsp::bind_rfn("check-inventory", rfn!(check_inventory))?;
+	Ok(())
+}
+
+#[test]
+fn caught_by_script() {
+	run(|| {
+		bind_rfns()?;
+		assert!(!glsp::is_poisoned());
+
+		glsp::load_str(r#"
+			(let (tag e) (try-chain (+ 1 (explode "boom"))))
+			(ensure (eq? tag 'err))
+			(ensure (eq? [e 'kind] 'rust-panic))
+			(ensure (eq? [e 'payload] "rfn (explode) panicked, 'boom'"))
+
+			(ensure (eq? (try (explode-with-int)) '(err "rfn (explode-with-int) panicked")))
+
+			; other errors have a different kind, or none
+			(ensure (eq? [[(try-chain no-such-global) 1] 'kind] 'unbound-symbol))
+			(ensure (nil? [[(try-chain (bail "oops")) 1] 'kind]))
+		"#, "test.glsp")?;
+
+		assert!(glsp::is_poisoned());
+
+		//the host has decided to continue, so it clears the flag
+		glsp::clear_poison();
+		assert!(!glsp::is_poisoned());
+
+		//unrelated scripts still work, including scripts which repeatedly catch panics in the
+		//middle of a call with several arguments on the reg stack
+		let result: i32 = eval(r#"
+			(defn fib (n)
+			  (if (< n 2) n (+ (fib (- n 1)) (fib (- n 2)))))
+
+			(let total 0)
+			(forn (i 50)
+			  (let (tag _) (try (+ i (explode "again") (fib 5))))
+			  (ensure (eq? tag 'err))
+			  (inc! total (+ i (fib 5))))
+
+			(+ total (fib 15))
+		"#)?;
+		assert_eq!(result, (0..50).sum::<i32>() + 5 * 50 + 610);
+		assert!(glsp::is_poisoned());
+
+		//the panic unwinds through a glsp fn and an outer rfn before it's caught
+		let result: String = eval(r#"
+			(let (_ e) (try-chain (explode-later (fn (msg) (explode msg)) "nested")))
+			(str [e 'kind] " " [e 'payload])
+		"#)?;
+		assert_eq!(result, "rust-panic rfn (explode) panicked, 'nested'");
+
+		Ok(())
+	});
+}
+
+#[test]
+fn caught_by_host() {
+	run(|| {
+		bind_rfns()?;
+
+		let explode: RFn = glsp::global("explode")?;
+		let err = glsp::call::<_, _, Val>(&explode, &("from rust",)).unwrap_err();
+
+		assert_eq!(err.message_id(), Some(MessageId::RustPanic));
+		let args = err.message_args().unwrap();
+		assert_eq!(args.get("rfn"), Some("explode"));
+		assert_eq!(args.get("payload"), Some("from rust"));
+		assert!(glsp::is_poisoned());
+
+		let anonymous = glsp::rfn(rfn!(explode_with_int));
+		let err = glsp::call::<_, _, Val>(&anonymous, &()).unwrap_err();
+		assert_eq!(err.val().to_string(), "anonymous rfn panicked");
+		assert_eq!(err.message_args().unwrap().get("payload"), None);
+
+		assert_eq!(MessageId::RustPanic.name(), "rust-panic");
+
+		Ok(())
+	});
+}
+
+#[test]
+fn poisoned_rdata() {
+	run(|| {
+		bind_rfns()?;
+
+		glsp::load_str(r#"
+			(def inv (inventory))
+			(.add inv "sword" 10)
+
+			; the panic interrupts (.add) after it has pushed the item, but before it has
+			; updated the weight
+			(let (tag e) (try-chain (.add inv "feather" -1)))
+			(ensure (eq? [e 'kind] 'rust-panic))
+		"#, "test.glsp")?;
+
+		let inv: Root<RData> = glsp::global("inv")?;
+		assert!(inv.is_poisoned());
+
+		let err = inv.try_borrow::<Inventory>().err().unwrap();
+		assert_eq!(err.val().to_string(), "try_borrow<Inventory> failed: value is poisoned: a \
+		           panic occurred while it was mutably borrowed by the rfn (add), called at \
+		           test.glsp:7");
+		assert!(inv.try_borrow_mut::<Inventory>().is_err());
+
+		let err = eval::<Val>("(.weight inv)").unwrap_err();
+		assert!(err.val().to_string().contains("poisoned"), "{}", err);
+
+		//the host can still recover the value, and decide what to do with it
+		let recovered = inv.take::<Inventory>()?;
+		assert_eq!(recovered.items, ["sword", "feather"]);
+		assert_eq!(recovered.weight, 10);
+
+		//a panic during a shared borrow can't leave the value half-updated, so it doesn't
+		//poison the rdata
+		let result: i32 = eval(r#"
+			(let inv (inventory))
+			(forn (_ 10)
+			  (.add inv "arrow" 1))
+
+			(let (tag _) (try (check-inventory inv)))
+			(ensure (eq? tag 'err))
+			(.weight inv)
+		"#)?;
+		assert_eq!(result, 10);
+
+		Ok(())
+	});
+}
+
+thread_local! {
+	static STASHED: RefCell<Option<RRefMut<Inventory>>> = RefCell::new(None);
+}
+
+struct Unstash;
+
+impl Drop for Unstash {
+	fn drop(&mut self) {
+		STASHED.with(|stashed| drop(stashed.borrow_mut().take()));
+	}
+}
+
+#[test]
+fn borrow_dropped_outside_runtime() {
+	let runtime = Runtime::new();
+	runtime.run(|| {
+		let inv = glsp::rdata(Inventory::default())?;
+		glsp::bind_global("inv", &inv)?;
+		STASHED.with(|stashed| *stashed.borrow_mut() = Some(inv.borrow_mut()));
+		Ok(())
+	}).unwrap();
+
+	//the mutable borrow ends while a panic is unwinding, but no Runtime is active. this must
+	//poison the rdata without panicking again
+	let result = panic::catch_unwind(|| {
+		let _unstash = Unstash;
+		panic!("outside of the runtime");
+	});
+	assert!(result.is_err());
+
+	runtime.run(|| {
+		let inv: Root<RData> = glsp::global("inv")?;
+		assert!(inv.is_poisoned());
+
+		let err = inv.try_borrow::<Inventory>().err().unwrap();
+		assert!(err.val().to_string().contains("value is poisoned"), "{}", err);
+
+		Ok(())
+	}).unwrap();
+}
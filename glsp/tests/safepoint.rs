@@ -0,0 +1,130 @@
+//! Checks that a safepoint callback is invoked regularly while a script is running, and that it
+//! can cancel the script or pause the running coroutine.
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+use glsp::{SafepointAction};
+use std::cell::{Cell};
+use std::rc::{Rc};
+
+#[test]
+fn progress() {
+	run(|| {
+		let calls = Rc::new(Cell::new(0_u32));
+		let callback_calls = calls.clone();
+		glsp::set_safepoint_callback(100, Box::new(move || {
+			callback_calls.set(callback_calls.get() + 1);
+			SafepointAction::Continue
+		}));
+
+		let sum = glsp::load_str("(let n 0) (forn (i 1000) (inc! n i)) n", "test.glsp")?;
+		let sum = i32::from_val(&sum)?;
+		assert_eq!(sum, 499500);
+		assert!(calls.get() >= 10, "{}", calls.get());
+
+		glsp::clear_safepoint_callback();
+		calls.set(0);
+		glsp::load_str("(forn (i 1000) #n)", "test.glsp")?;
+		assert_eq!(calls.get(), 0);
+
+		Ok(())
+	});
+}
+
+#[test]
+fn cancellation() {
+	run(|| {
+		let cancelled = Rc::new(Cell::new(false));
+		let callback_cancelled = cancelled.clone();
+		glsp::set_safepoint_callback(50, Box::new(move || {
+			if callback_cancelled.get() {
+				SafepointAction::RaiseError(error!("cancelled"))
+			} else {
+				SafepointAction::Continue
+			}
+		}));
+
+		glsp::load_str("(forn (i 1000) #n)", "test.glsp")?;
+
+		//the error can be caught by scripts
+		cancelled.set(true);
+		glsp::load_str(r#"
+			(let result (try (loop #n)))
+			(ensure (eq? result '(err "cancelled")))
+		"#, "test.glsp")?;
+
+		let err = glsp::load_str("(loop #n)", "test.glsp").unwrap_err();
+		assert_eq!(err.val().to_string(), "cancelled");
+
+		cancelled.set(false);
+		glsp::load_str("(forn (i 1000) #n)", "test.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn yielding() {
+	run(|| {
+		glsp::set_safepoint_callback(10, Box::new(|| SafepointAction::Yield));
+
+		glsp::load_str(r#"
+			(defn worker ()
+			  (let n 0)
+			  (forn (i 100)
+			    (inc! n i))
+			  (yield 'halfway)
+			  (forn (i 100)
+			    (inc! n i))
+			  n)
+
+			;the coroutine is paused repeatedly, yielding #n, until it reaches its own yield
+			(let co (worker))
+			(ensure (nil? (coro-run co)))
+			(while (nil? (coro-run co 'ignored)))
+
+			(let result #n)
+			(while (eq? (coro-state co) 'paused)
+			  (= result (coro-run co)))
+			(ensure (== result 9900))
+
+			;outside of a coroutine, yielding has no effect
+			(let n 0)
+			(forn (i 100)
+			  (inc! n i))
+			(ensure (== n 4950))
+
+			;neither does it affect a nested call within a coroutine
+			(defn sum-to (limit)
+			  (let n 0)
+			  (forn (i limit)
+			    (inc! n i))
+			  n)
+
+			(defn outer ()
+			  (yield (sum-to 100)))
+
+			(let co (outer))
+			(ensure (== (coro-run co) 4950))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "a safepoint callback attempted to call into glsp")]
+fn reentrancy() {
+	run(|| {
+		glsp::set_safepoint_callback(10, Box::new(|| {
+			let _ = glsp::load_str("1", "inner.glsp");
+			SafepointAction::Continue
+		}));
+
+		glsp::load_str("(forn (i 100) #n)", "test.glsp")?;
+		Ok(())
+	});
+}
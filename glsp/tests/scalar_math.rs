@@ -0,0 +1,255 @@
+//! Checks the edge cases of the scalar math functions: endpoints, extrapolation, degenerate
+//! ranges, signed zeroes, infinities and nans.
+
+mod common;
+
+use common::run;
+
+#[test]
+fn interpolation() {
+	run(|| {
+		glsp::load_str(r#"
+			(defn err? (result)
+			  (eq? [result 0] 'err))
+
+			;lerp is exact at both endpoints, and extrapolates outside them
+			(ensure (== (lerp 2 10 0) 2.0))
+			(ensure (== (lerp 2 10 1) 10.0))
+			(ensure (== (lerp 0.1 0.7 1.0) 0.7))
+			(ensure (== (lerp 2 10 0.5) 6.0))
+			(ensure (== (lerp 0 10 2) 20.0))
+			(ensure (== (lerp 0 10 -1) -10.0))
+			(ensure (== (lerp 10 0 0.25) 7.5))
+			(ensure (flo? (lerp 1 1 0)))
+
+			(ensure (== (inv-lerp 2 10 6) 0.5))
+			(ensure (== (inv-lerp 2 10 2) 0.0))
+			(ensure (== (inv-lerp 2 10 10) 1.0))
+			(ensure (== (inv-lerp 2 10 14) 1.5))
+			(ensure (== (inv-lerp 10 2 4) 0.75))
+
+			;an empty range divides by zero
+			(ensure (nan? (inv-lerp 5 5 5)))
+			(ensure (== (inv-lerp 5 5 6) +inf.0))
+			(ensure (== (inv-lerp 5 5 4) -inf.0))
+
+			(ensure (== (remap 5 0 10 100 200) 150.0))
+			(ensure (approx= (remap 2 0 10 10 0) 8.0))
+			(ensure (== (remap 20 0 10 0 1) 2.0))
+			(ensure (nan? (remap 5 5 5 0 1)))
+
+			;smoothstep clamps its result, with either one or three arguments
+			(ensure (== (smoothstep -1) 0.0))
+			(ensure (== (smoothstep 0) 0.0))
+			(ensure (== (smoothstep 0.5) 0.5))
+			(ensure (== (smoothstep 1) 1.0))
+			(ensure (== (smoothstep 2) 1.0))
+			(ensure (== (smoothstep 0.25) 0.15625))
+			(ensure (== (smoothstep 0 10 5) 0.5))
+			(ensure (== (smoothstep 0 10 -5) 0.0))
+			(ensure (== (smoothstep 0 10 15) 1.0))
+			(ensure (== (smoothstep 10 0 2.5) 0.84375))
+			(ensure (err? (try (smoothstep 0 10))))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn wrapping() {
+	run(|| {
+		glsp::load_str(r#"
+			(defn err? (result)
+			  (eq? [result 0] 'err))
+
+			;wrapping ints produces an int in the half-open range [lo, hi)
+			(ensure (== (wrap 12 0 10) 2))
+			(ensure (== (wrap -1 0 10) 9))
+			(ensure (== (wrap -10 0 10) 0))
+			(ensure (== (wrap 10 0 10) 0))
+			(ensure (== (wrap 0 0 10) 0))
+			(ensure (== (wrap 7 5 6) 5))
+			(ensure (== (wrap -7 -5 5) 3))
+			(ensure (int? (wrap 12 0 10)))
+
+			;bounds which are far apart don't overflow
+			(let min-int -2147483648)
+			(let max-int 2147483647)
+			(ensure (== (wrap 0 min-int max-int) 0))
+			(ensure (== (wrap max-int min-int max-int) min-int))
+			(ensure (== (wrap min-int min-int max-int) min-int))
+			(ensure (== (wrap max-int -1 1) -1))
+
+			;if any argument is a flo, so is the result
+			(ensure (== (wrap 370.0 0 360) 10.0))
+			(ensure (== (wrap -90 0 360.0) 270.0))
+			(ensure (== (wrap -0.5 0 1) 0.5))
+			(ensure (== (wrap 1.0 0 1) 0.0))
+			(ensure (flo? (wrap 12 0 10.0)))
+
+			;a tiny negative input never wraps to exactly hi
+			(let tiny (wrap -1e-10 0.0 1.0))
+			(ensure (>= tiny 0.0))
+			(ensure (< tiny 1.0))
+
+			(ensure (nan? (wrap nan.0 0 1)))
+			(ensure (nan? (wrap +inf.0 0 1)))
+
+			;the range must be non-empty
+			(ensure (err? (try (wrap 1 5 5))))
+			(ensure (err? (try (wrap 1 5 0))))
+			(ensure (err? (try (wrap 1.0 1.0 0.5))))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn comparison() {
+	run(|| {
+		glsp::load_str(r#"
+			(defn err? (result)
+			  (eq? [result 0] 'err))
+
+			;the default epsilon is 1e-5
+			(ensure (approx= 1.0 1.000001))
+			(ensure (approx= 1 1.0))
+			(ensure (not (approx= 1.0 1.1)))
+			(ensure (approx= 1.0 1.1 0.2))
+			(ensure (approx= 100 102 2))
+			(ensure (not (approx= 100 103 2)))
+
+			;a zero epsilon requires exact equality
+			(ensure (approx= 0.5 0.5 0))
+			(ensure (not (approx= 0.5 0.500001 0)))
+			(ensure (approx= 0.0 -0.0 0))
+
+			(ensure (approx= +inf.0 +inf.0))
+			(ensure (approx= -inf.0 -inf.0 0))
+			(ensure (not (approx= +inf.0 -inf.0)))
+			(ensure (not (approx= nan.0 nan.0)))
+			(ensure (not (approx= nan.0 1.0 +inf.0)))
+
+			(ensure (err? (try (approx= 1.0 1.0 -0.1))))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn trigonometry_and_signs() {
+	run(|| {
+		glsp::load_str(r#"
+			(defn negative-zero? (f)
+			  (and (== f 0.0) (== (copysign 1 f) -1.0)))
+
+			;atan2 distinguishes all four quadrants, and the sign of a zero y
+			(ensure (approx= (atan2 1 1) 0.7853982))
+			(ensure (approx= (atan2 1 -1) 2.3561945))
+			(ensure (approx= (atan2 -1 -1) -2.3561945))
+			(ensure (approx= (atan2 0 -1) 3.1415927))
+			(ensure (approx= (atan2 -0.0 -1) -3.1415927))
+			(ensure (== (atan2 0 0) 0.0))
+			(ensure (approx= (atan2 1 0) 1.5707964))
+			(ensure (approx= (atan2 +inf.0 +inf.0) 0.7853982))
+
+			(ensure (== (hypot 3 4) 5.0))
+			(ensure (== (hypot -3 -4) 5.0))
+			(ensure (== (hypot 0 0) 0.0))
+			(ensure (approx= (/ (hypot 1e30 1e30) 1e30) 1.4142135))
+			(ensure (== (hypot +inf.0 nan.0) +inf.0))
+			(ensure (nan? (hypot 1 nan.0)))
+
+			(ensure (== (copysign 3 -1) -3.0))
+			(ensure (== (copysign -2 1) 2.0))
+			(ensure (== (copysign 3 -0.0) -3.0))
+			(ensure (== (copysign -3 0.0) 3.0))
+			(ensure (== (copysign +inf.0 -1) -inf.0))
+
+			;signum preserves ints, zeroes and nans
+			(ensure (== (signum -5) -1))
+			(ensure (== (signum 0) 0))
+			(ensure (== (signum 7) 1))
+			(ensure (int? (signum 7)))
+			(ensure (== (signum 2.5) 1.0))
+			(ensure (== (signum -inf.0) -1.0))
+			(ensure (flo? (signum 2.5)))
+			(ensure (negative-zero? (signum -0.0)))
+			(ensure (not (negative-zero? (signum 0.0))))
+			(ensure (nan? (signum nan.0)))
+
+			(ensure (approx= (deg->rad 180) 3.1415927))
+			(ensure (approx= (deg->rad -90) -1.5707964))
+			(ensure (== (deg->rad 0) 0.0))
+			(ensure (approx= (rad->deg 3.1415927) 180.0 1e-3))
+			(ensure (approx= (rad->deg (deg->rad 45)) 45.0 1e-4))
+			(ensure (== (rad->deg +inf.0) +inf.0))
+
+			(ensure (== (fract 2.75) 0.75))
+			(ensure (== (fract -2.75) -0.75))
+			(ensure (== (fract 3) 0.0))
+			(ensure (nan? (fract +inf.0)))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn ieee_remainder() {
+	run(|| {
+		glsp::load_str(r#"
+			(defn negative-zero? (f)
+			  (and (== f 0.0) (== (copysign 1 f) -1.0)))
+
+			;the quotient is rounded to the nearest integer, with ties rounding to even
+			(ensure (== (remainder 5 2) 1.0))
+			(ensure (== (remainder 7 2) -1.0))
+			(ensure (== (remainder 5.5 2) -0.5))
+			(ensure (== (remainder 6.5 2) 0.5))
+			(ensure (== (remainder -5 2) -1.0))
+			(ensure (== (remainder 5 -2) 1.0))
+			(ensure (== (remainder 10 3) 1.0))
+			(ensure (== (remainder 11 3) -1.0))
+
+			;the result is always within half of the divisor
+			(forn (i -20 20)
+			  (let r (remainder (* i 0.75) 2))
+			  (ensure (<= (abs r) 1.0)))
+
+			;the result is exact, even when x/y is too large to be represented precisely. 2^60
+			;and 2^100 are each 1 more than a multiple of 3, and 2^127 is 2 more than a multiple
+			;of 3 or 7.
+			(ensure (== (remainder 1152921504606846976.0 3) 1.0))
+			(ensure (== (remainder -1267650600228229401496703205376.0 3) -1.0))
+			(ensure (== (remainder 170141183460469231731687303715884105728.0 7) 2.0))
+			(ensure (== (remainder 170141183460469231731687303715884105728.0 -3) -1.0))
+
+			;2^-149 is the smallest subnormal f32
+			(let tiny 1.0)
+			(forn (_ 149)
+			  (= tiny (* tiny 0.5)))
+			(ensure (== (remainder (* tiny 3) (* tiny 2)) (- tiny)))
+			(ensure (== (remainder (* tiny 5) (* tiny 2)) tiny))
+
+			;a zero result has the sign of x
+			(ensure (negative-zero? (remainder -4 2)))
+			(ensure (not (negative-zero? (remainder 4 2))))
+			(ensure (negative-zero? (remainder -0.0 1)))
+
+			;an infinite divisor leaves a finite x unchanged
+			(ensure (== (remainder 3 +inf.0) 3.0))
+			(ensure (== (remainder -3 -inf.0) -3.0))
+
+			(ensure (nan? (remainder 1 0)))
+			(ensure (nan? (remainder +inf.0 2)))
+			(ensure (nan? (remainder nan.0 2)))
+			(ensure (nan? (remainder 2 nan.0)))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
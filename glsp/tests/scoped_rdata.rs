@@ -0,0 +1,100 @@
+//! Checks that `glsp::with_scoped_rdata` lends a value to scripts for the duration of a closure,
+//! returns it afterwards (even when the closure fails), and that a script which keeps the rdata
+//! receives an `expired-rdata` error.
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+
+rdata! {
+	#[derive(Default)]
+	struct DebugUi {
+		labels: Vec<String>
+	}
+
+	meths {
+		"label": DebugUi::label
+	}
+}
+
+impl DebugUi {
+	fn label(&mut self, text: &str) {
+		self.labels.push(text.to_string());
+	}
+}
+
+fn label_count(ui: &DebugUi) -> usize {
+	ui.labels.len()
+}
+
+const SCRIPT: &str = r#"
+	(def kept-ui #n)
+
+	(defn draw-debug-ui (ui)
+	  (.label ui "fps")
+	  (.label ui "entities")
+	  (ensure (== (label-count ui) 2))
+	  (= kept-ui ui))
+"#;
+
+#[test]
+fn lending() {
+	run(|| {
+		glsp::bind_rfn("label-count", rfn!(label_count))?;
+		glsp::load_str(SCRIPT, "ui.glsp")?;
+
+		let draw: Root<GFn> = glsp::global("draw-debug-ui")?;
+
+		let mut ui = DebugUi::default();
+		glsp::with_scoped_rdata(&mut ui, |rdata| {
+			assert!(!rdata.is_expired());
+			glsp::call::<_, _, Val>(&draw, &(rdata.clone(),))
+		})??;
+
+		assert_eq!(ui.labels, ["fps", "entities"]);
+
+		//the script kept the rdata past the end of the frame
+		let kept: Root<RData> = glsp::global("kept-ui")?;
+		assert!(kept.is_expired() && kept.is_freed());
+
+		glsp::load_str(r#"
+			(ensure (eq? (try (.label kept-ui "stale")) '(err expired-rdata)))
+			(ensure (expired? kept-ui))
+		"#, "ui.glsp")?;
+
+		let message = glsp::call::<_, _, Val>(&draw, &(kept.clone(),)).unwrap_err().to_string();
+		assert!(message.contains("scoped DebugUi"), "{}", message);
+		assert!(message.contains("scoped_rdata.rs"), "{}", message);
+		Ok(())
+	});
+}
+
+#[test]
+fn failing_closure() {
+	run(|| {
+		glsp::bind_rfn("label-count", rfn!(label_count))?;
+		glsp::load_str(SCRIPT, "ui.glsp")?;
+
+		let mut ui = DebugUi { labels: vec!["title".to_string()] };
+		let result = glsp::with_scoped_rdata(&mut ui, |rdata| {
+			let draw: Root<GFn> = glsp::global("draw-debug-ui")?;
+			glsp::call::<_, _, Val>(&draw, &(rdata.clone(),))
+		})?;
+
+		//the script's (ensure) failed, but the value and its changes were still returned
+		assert!(result.is_err());
+		assert_eq!(ui.labels, ["title", "fps", "entities"]);
+
+		//a lend which ends while the value is borrowed is an error
+		let mut ui = DebugUi::default();
+		let result = glsp::with_scoped_rdata(&mut ui, |rdata| {
+			std::mem::forget(rdata.borrow::<DebugUi>());
+			rdata.clone()
+		});
+
+		assert!(result.is_err());
+		assert!(ui.labels.is_empty());
+		Ok(())
+	});
+}
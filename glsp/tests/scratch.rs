@@ -0,0 +1,132 @@
+//! Checks that `(with-scratch)` and `glsp::scratch_scope` free the objects which don't escape
+//! them, and leave every escaped object intact.
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+
+#[test]
+fn garbage_is_freed() {
+	run(|| {
+		glsp::gc();
+		let before = glsp::gc_young_bytes();
+
+		glsp::load_str(r#"
+			(with-scratch
+			  (forn (i 1000)
+			    (arr i (tab ('i i)) "text")))
+		"#, "test.glsp")?;
+
+		//the only new young objects are the ones allocated while compiling the file
+		assert!(glsp::gc_young_bytes() - before < 16 * 1024);
+		Ok(())
+	});
+}
+
+#[test]
+fn escaped_objects_survive() {
+	run(|| {
+		glsp::load_str(r#"
+			(def old-tab (tab))
+			(def old-arr (arr))
+		"#, "old.glsp")?;
+
+		//promote old-tab and old-arr to the old generation
+		glsp::gc();
+
+		glsp::load_str(r#"
+			(def young-arr (arr))
+
+			(def returned (with-scratch
+			  (forn (i 100) (arr i))
+			  (= [old-tab 'a] (arr 'tab-value))
+			  (push! old-arr (arr 'arr-value))
+			  (push! young-arr (arr 'young-value))
+			  (def new-global (arr 'global-value))
+			  (arr 'returned-value)))
+		"#, "test.glsp")?;
+
+		let check = |name: &str, expected: &str| -> GResult<()> {
+			let val: Val = glsp::global(name)?;
+			assert_eq!(val.to_string(), expected);
+			Ok(())
+		};
+
+		check("old-tab", "#((a (tab-value)))")?;
+		check("old-arr", "((arr-value))")?;
+		check("young-arr", "((young-value))")?;
+		check("new-global", "(global-value)")?;
+		check("returned", "(returned-value)")?;
+
+		//the survivors must still be intact after they've been through the gc
+		for _ in 0 .. 10 {
+			glsp::gc();
+		}
+
+		check("old-tab", "#((a (tab-value)))")?;
+		check("young-arr", "((young-value))")?;
+		Ok(())
+	});
+}
+
+#[test]
+fn rust_scope() {
+	run(|| {
+		let kept = glsp::scratch_scope(|| {
+			for i in 0 .. 100 {
+				glsp::arr_from_iter(0 .. i)?;
+			}
+
+			Ok(arr![1, 2, 3])
+		})?;
+
+		assert_eq!(kept.len(), 3);
+
+		let outer = glsp::arr();
+		glsp::scratch_scope(|| {
+			outer.push(arr![4, 5])?;
+			glsp::gc();
+			outer.push(arr![6])
+		})?;
+
+		assert_eq!(Val::Arr(outer).to_string(), "((4 5) (6))");
+		Ok(())
+	});
+}
+
+#[test]
+fn nested_scopes() {
+	run(|| {
+		let result = glsp::load_str(r#"
+			(with-scratch
+			  (let inner (with-scratch
+			    (forn (i 10) (arr i))
+			    (arr 'inner)))
+			  (forn (i 10) (arr i))
+			  (arr inner 'outer))
+		"#, "test.glsp")?;
+
+		assert_eq!(result.to_string(), "((inner) outer)");
+		Ok(())
+	});
+}
+
+#[test]
+fn strict_scope() {
+	run(|| {
+		glsp::load_str(r#"
+			(ensure (== (with-strict-scratch (forn (i 10) (arr i)) 5) 5))
+
+			(let (tag payload) (try (with-strict-scratch (arr 1))))
+			(ensure (eq? tag 'err))
+			(ensure (eq? payload "1 object escaped from a strict scratch scope"))
+
+			(def stash (arr))
+			(ensure (eq? [(try (with-strict-scratch (push! stash (arr)) #n)) 0] 'err))
+			(ensure (== (len stash) 1))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
@@ -0,0 +1,167 @@
+//! Checks that macros can attach source locations to the forms they generate using `with-span`
+//! and `spanned`, that `span-of` describes those locations, and that errors, disassembly and
+//! compiled code all report them.
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+
+//each (:where pred) clause expands to an (ensure pred) form which carries the clause's own span
+const QUERY: &str = r#"
+	(defmacro query (..clauses)
+	  (let checks (arr))
+	  (for clause in clauses
+	    (push! checks (with-span `(ensure ~[clause 1]) clause)))
+	  `(do ~..checks 'ok))
+"#;
+
+#[test]
+fn with_span() {
+	run(|| {
+		glsp::load_str(QUERY, "query.glsp")?;
+
+		//without with-span, both failures would be reported at the (query) call on line 2
+		let src = "\n(query\n  (:where #t)\n  (:where #f))";
+		let message = glsp::load_str(src, "main.glsp").unwrap_err().to_string();
+		assert!(message.contains("main.glsp:4"), "{}", message);
+		assert!(!message.contains("main.glsp:2"), "{}", message);
+
+		let src = "(query (:where #t))";
+		assert_eq!(glsp::load_str(src, "main.glsp")?, Val::Sym(glsp::sym("ok")?));
+
+		//non-arrs are returned unchanged, and frozen arrs are copied
+		glsp::load_str(r#"
+			(ensure (== (with-span 10 '(a)) 10))
+			(ensure (eq? (with-span '(a) 'b) '(a)))
+
+			(let literal '(a b))
+			(let respanned (with-span literal (spanned "other.glsp" 5 (arr))))
+			(ensure (eq? respanned literal))
+			(ensure (not (same? respanned literal)))
+			(ensure (eq? [(span-of literal) 'file] "test.glsp"))
+			(ensure (eq? [(span-of respanned) 'file] "other.glsp"))
+
+			(let mutable (arr 'a))
+			(ensure (same? (with-span mutable literal) mutable))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn span_of() {
+	run(|| {
+		glsp::load_str(r#"
+			(defmacro capture (form)
+			  `(quote ~(arr form (arr 'generated))))
+
+			(defmacro outer ()
+			  `(capture x))
+		"#, "macros.glsp")?;
+
+		let captured = glsp::load_str("\n\n(capture (a b))", "main.glsp")?;
+		let form: Val = match captured {
+			Val::Arr(ref arr) => arr.get(0)?,
+			_ => panic!()
+		};
+		let generated: Val = match captured {
+			Val::Arr(ref arr) => arr.get(1)?,
+			_ => panic!()
+		};
+
+		let info = glsp::span_of(&form).unwrap();
+		assert_eq!((info.filename(), info.line()), (Some("main.glsp"), Some(3)));
+		assert!(info.expansions().is_empty());
+		assert_eq!(info.to_string(), "main.glsp:3");
+
+		let info = glsp::span_of(&generated).unwrap();
+		assert_eq!((info.filename(), info.line()), (Some("main.glsp"), Some(3)));
+		assert_eq!(info.expansions(), &[Some(glsp::sym("capture")?)]);
+		assert_eq!(info.to_string(), "main.glsp:3, expanded from (capture)");
+
+		let nested = glsp::load_str("(outer)", "main.glsp")?;
+		let info = glsp::span_of(&nested).unwrap();
+		assert_eq!(info.expansions(), &[Some(glsp::sym("capture")?), Some(glsp::sym("outer")?)]);
+
+		assert!(glsp::span_of(&Val::Int(1)).is_none());
+		assert!(glsp::span_of(&Val::Arr(glsp::arr())).is_none());
+
+		glsp::load_str(r#"
+			(let info (span-of '(a)))
+			(ensure (eq? [info 'file] "test.glsp"))
+			(ensure (== [info 'line] 2))
+			(ensure (eq? [info 'expansions] '()))
+
+			(ensure (nil? (span-of 'a)))
+			(ensure (nil? (span-of (arr))))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn spanned() {
+	run(|| {
+		glsp::load_str(r#"
+			(let form (spanned "levels/forest.csv" 12 '(spawn-enemy)))
+			(ensure (eq? [(span-of form) 'file] "levels/forest.csv"))
+			(ensure (== [(span-of form) 'line] 12))
+
+			(ensure (eq? [(try (spanned "a.glsp" 0 (arr))) 0] 'err))
+		"#, "test.glsp")?;
+
+		//errors raised by the form are reported at the synthetic location
+		let form = glsp::spanned("levels/forest.csv", 12, &glsp::parse_1("(no-such-fn)", None)?)?;
+		let message = glsp::eval(&form, None).unwrap_err().to_string();
+		assert!(message.contains("levels/forest.csv:12"), "{}", message);
+
+		//and by the disassembler
+		let listing = glsp::load_str(r#"
+			(let body (spanned "levels/forest.csv" 31 (arr '+ 1 (arr 'nth 2))))
+			(disassemble (eval (arr 'fn '() body)))
+		"#, "test.glsp")?.to_string();
+		assert!(listing.contains("levels/forest.csv:31"), "unexpected listing:\n{}", listing);
+
+		Ok(())
+	});
+}
+
+#[cfg(feature = "compiler")]
+#[test]
+fn compiled() {
+	let src = r#"
+		(defmacro from-csv (line form)
+		  (spanned "levels/forest.csv" line form))
+
+		(defn spawn ()
+		  (from-csv 12 (no-such-fn)))
+
+		(defmacro csv-literal (line form)
+		  `(quote ~(spanned "levels/forest.csv" line form)))
+
+		(def literal (csv-literal 20 (a b)))
+	"#;
+
+	let bytes = run(|| {
+		let (_, bytes) = glsp::load_and_compile_str(src, "main.glsp")?;
+		Ok(bytes)
+	});
+
+	run(move || {
+		glsp::load_compiled(&bytes)?;
+
+		let spawn: Root<GFn> = glsp::global("spawn")?;
+		let message = glsp::call::<_, _, Val>(&spawn, &()).unwrap_err().to_string();
+		assert!(message.contains("levels/forest.csv:12"), "{}", message);
+
+		//literal arrs keep their spans
+		let literal: Val = glsp::global("literal")?;
+		let info = glsp::span_of(&literal).unwrap();
+		assert_eq!((info.filename(), info.line()), (Some("levels/forest.csv"), Some(20)));
+
+		Ok(())
+	});
+}
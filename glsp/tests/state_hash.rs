@@ -0,0 +1,111 @@
+//! Checks that `glsp::state_hash` is deterministic across `Runtime`s and across table
+//! insertion orders, that it terminates for self-referential values, and that it reports the
+//! path to an unhashable value.
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+
+fn hash_src(src: &'static str) -> u64 {
+	run(move || {
+		let val = glsp::load_str(src, "test.glsp")?;
+		glsp::state_hash(&[&val])
+	})
+}
+
+#[test]
+fn stability() {
+	//this value is fixed by the documented encoding, so it must never change
+	assert_eq!(hash_src("(arr 1 'a)"), 0x73e5bdbdd51d2806);
+
+	run(|| {
+		glsp::load_str(r#"
+			(ensure (eq? (state-hash (arr 1 'a)) "73e5bdbdd51d2806"))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn determinism() {
+	let src = r#"
+		(let state (tab ('tick 10) ('players (arr (tab ('hp 5) ('pos (arr 1.5 -2.0)))))))
+		(defrecord Hit pos dist)
+		(= [state 'last-hit] (Hit:new \a "text"))
+		state
+	"#;
+
+	//each Runtime allocates its own symbols and storage, but the hash is unchanged
+	assert_eq!(hash_src(src), hash_src(src));
+
+	//the order in which a table's entries were inserted doesn't matter
+	assert_eq!(
+		hash_src("(tab ('a 1) ('b 2) (\"c\" 3) (4 4))"),
+		hash_src("(tab (4 4) (\"c\" 3) ('b 2) ('a 1))")
+	);
+
+	//values which (hash) considers to be equal are distinguished
+	assert_ne!(hash_src("(arr 1)"), hash_src("(arr 1.0)"));
+	assert_ne!(hash_src("(arr 0.0)"), hash_src("(arr -0.0)"));
+	assert_ne!(hash_src("(arr 97)"), hash_src("(arr \\a)"));
+	assert_ne!(hash_src("(arr 'a)"), hash_src("(arr \"a\")"));
+	assert_ne!(hash_src("(tab ('hp 5))"), hash_src("(tab ('hp 6))"));
+	assert_ne!(hash_src("(arr (arr 1) 2)"), hash_src("(arr (arr 1 2))"));
+}
+
+#[test]
+fn shared_and_cyclic() {
+	//a self-referential table terminates, and hashes consistently
+	let cyclic = r#"
+		(let t (tab ('name 'root)))
+		(= [t 'self] t)
+		(= [t 'children] (arr t t))
+		t
+	"#;
+	assert_eq!(hash_src(cyclic), hash_src(cyclic));
+
+	//sharing is part of the hashed state
+	assert_ne!(
+		hash_src("(let a (arr 1)) (arr a a)"),
+		hash_src("(arr (arr 1) (arr 1))")
+	);
+
+	//several roots can be hashed together
+	run(|| {
+		let a = glsp::load_str("(arr 1 2)", "test.glsp")?;
+		let b = glsp::load_str("(tab ('x 1))", "test.glsp")?;
+
+		let both = glsp::state_hash(&[&a, &b])?;
+		assert_eq!(both, glsp::state_hash(&[&a, &b])?);
+		assert_ne!(both, glsp::state_hash(&[&b, &a])?);
+		assert_ne!(both, glsp::state_hash(&[&a])?);
+
+		Ok(())
+	});
+}
+
+#[test]
+fn unhashable() {
+	run(|| {
+		let state = glsp::load_str(r#"
+			(defstruct Point x y)
+			(defclass Handle (field id 0))
+
+			(ensure (eq? (state-hash (Point:new 1 2)) (state-hash (Point:new 1 2))))
+			(ensure (eq? [(try (state-hash (Handle))) 0] 'err))
+			(ensure (eq? [(try (state-hash (arr 1 (fn () 2)))) 0] 'err))
+
+			(tab ('players (arr (tab ('on-hit (fn () #n))))))
+		"#, "test.glsp")?;
+
+		let message = glsp::state_hash(&[&state]).unwrap_err().to_string();
+		assert!(message.contains("[players][0][on-hit]"), "unexpected message {:?}", message);
+
+		let message = glsp::state_hash(&[&Val::Int(1), &state]).unwrap_err().to_string();
+		assert!(message.contains("root 1[players][0][on-hit]"), "unexpected message {:?}", message);
+
+		Ok(())
+	});
+}
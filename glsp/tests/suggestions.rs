@@ -0,0 +1,99 @@
+//! Checks that unbound-symbol, nonexistent-method and nonexistent-field errors suggest a similar
+//! name when there's a close match, and that unrelated names don't produce any suggestion.
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+use glsp::{MessageId};
+
+fn message(src: &str) -> String {
+	glsp::load_str(src, "test.glsp").unwrap_err().val().to_string()
+}
+
+const SCRIPT: &str = r#"
+	(defn update-player ()
+	  #n)
+
+	(defn update-players ()
+	  #n)
+
+	(defclass Player
+	  (field health 100)
+	  (field stamina 50)
+	  (const max-speed 10)
+
+	  (meth jump ()
+	    #n)
+
+	  (meth take-damage (n)
+	    (dec! @health n)))
+
+	(def player (Player))
+"#;
+
+#[test]
+fn globals() {
+	run(|| {
+		glsp::load_str(SCRIPT, "test.glsp")?;
+
+		assert_eq!(message("updaet-player"), "unbound symbol 'updaet-player' \
+		                                      (did you mean `update-player`?)");
+		assert_eq!(message("update-playerz"), "unbound symbol 'update-playerz' \
+		                                       (did you mean `update-player` or \
+		                                       `update-players`?)");
+		assert!(message("(updaet-player)").contains("did you mean `update-player`?"));
+
+		//macros are suggested, too
+		assert!(message("(defnn 1 2)").contains("did you mean `defn`?"));
+
+		let err = glsp::load_str("(updaet-player)", "test.glsp").unwrap_err();
+		assert_eq!(err.message_id(), Some(MessageId::UnboundSymbol));
+		let args = err.message_args().unwrap();
+		assert_eq!(args.get("suggestions"), Some("`update-player`"));
+
+		//names which aren't close to anything don't produce any suggestion
+		assert_eq!(message("zzyzx-frobnicate"), "unbound symbol 'zzyzx-frobnicate'");
+		assert_eq!(message("qq"), "unbound symbol 'qq'");
+		let err = glsp::load_str("(no-such-function)", "test.glsp").unwrap_err();
+		assert_eq!(err.message_args().unwrap().get("suggestions"), None);
+
+		assert_eq!(glsp::similar_globals("updte-player"), vec![glsp::sym("update-player")?]);
+		assert!(glsp::similar_globals("completely-unrelated-name").is_empty());
+
+		let candidates = [glsp::sym("red")?, glsp::sym("green")?, glsp::sym("blue")?];
+		assert_eq!(glsp::similar_names("gren", &candidates), vec![glsp::sym("green")?]);
+		assert!(glsp::similar_names("purple", &candidates).is_empty());
+
+		Ok(())
+	});
+}
+
+#[test]
+fn meths_and_fields() {
+	run(|| {
+		glsp::load_str(SCRIPT, "test.glsp")?;
+
+		assert!(message("(.jupm player)").ends_with("attempted to call nonexistent method \
+		                                             'jupm' (did you mean `jump`?)"));
+		assert!(message("(.take-damag player 10)").contains("did you mean `take-damage`?"));
+		assert!(message("[player 'helth]").ends_with("attempted to access nonexistent field \
+		                                              'helth' (did you mean `health`?)"));
+		assert!(message("[player 'max-sped]").contains("did you mean `max-speed`?"));
+		assert!(message("(= [player 'stamna] 10)").contains("did you mean `stamina`?"));
+
+		//fields aren't suggested for methods, or vice versa
+		assert!(!message("(.helth player)").contains("did you mean"));
+		assert!(!message("[player 'jupm]").contains("did you mean"));
+
+		//unrelated names produce no suggestion
+		assert!(!message("(.teleport-to-moon player)").contains("did you mean"));
+		assert!(!message("[player 'xyz]").contains("did you mean"));
+
+		let player: Root<Obj> = glsp::global("player")?;
+		let err = player.get::<_, Val>("healht").unwrap_err();
+		assert_eq!(err.message_args().unwrap().get("suggestions"), Some("`health`"));
+
+		Ok(())
+	});
+}
@@ -0,0 +1,70 @@
+//! Checks that tabs with a default value return it for missing keys, without storing it, and
+//! that `group-by-into-tab` collects items into arrays.
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+
+#[test]
+fn tab_with_default() {
+	run(|| {
+		glsp::load_str(r#"
+			(let counts (tab-with-default 0))
+			(for word in '(a b a c a)
+			  (inc! [counts word]))
+
+			(ensure (== [counts 'a] 3))
+			(ensure (== [counts 'c] 1))
+			(ensure (== [counts 'missing] 0))
+			(ensure (== (tab-default counts) 0))
+
+			;the default only applies to keys which are actually stored
+			(ensure (== (len counts) 3))
+			(ensure (not (has? counts 'missing)))
+			(ensure (nil? [counts (? 'missing)]))
+			(ensure (eq? (arr ..(sort (arr ..(values counts)))) '(1 1 3)))
+
+			;entries can be provided up front, and the default survives cloning
+			(let scores (tab-with-default 10 '(a 1)))
+			(ensure (== [scores 'a] 1))
+			(ensure (== [(clone scores) 'b] 10))
+			(ensure (== [(deep-clone scores) 'b] 10))
+			(ensure (eq? (access scores 'b) 10))
+
+			(ensure (nil? (tab-default (tab))))
+			(ensure (eq? [(try [(tab) 'missing]) 0] 'err))
+
+			;mutable defaults would be shared between every missing key
+			(ensure (eq? [(try (tab-with-default (arr))) 0] 'err))
+			(ensure (eq? [(try (tab-with-default (tab))) 0] 'err))
+			(let frozen (deep-freeze! (arr 1 2)))
+			(ensure (same? [(tab-with-default frozen) 'x] frozen))
+		"#, "test.glsp")?;
+
+		let tab = glsp::tab_with_default(5)?;
+		assert_eq!(tab.get::<_, i32>("missing")?, 5);
+		assert_eq!(tab.get_if_present::<_, i32>("missing")?, None);
+		assert_eq!(tab.default_value(), Some(Val::Int(5)));
+		assert!(glsp::tab_with_default(glsp::arr()).is_err());
+
+		Ok(())
+	});
+}
+
+#[test]
+fn group_by_into_tab() {
+	run(|| {
+		glsp::load_str(r#"
+			(let groups (group-by-into-tab len '("a" "bb" "c" "dd" "eee")))
+			(ensure (eq? groups #((1 ("a" "c")) (2 ("bb" "dd")) (3 ("eee")))))
+			(ensure (not (frozen? [groups 1])))
+
+			(ensure (eq? (group-by-into-tab (fn1 (% _ 2)) (rn 5)) #((0 (0 2 4)) (1 (1 3)))))
+			(ensure (eq? (group-by-into-tab len ()) #()))
+			(ensure (eq? [(try (group-by-into-tab len '(1))) 0] 'err))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
@@ -0,0 +1,126 @@
+//! Checks that tabs with keys of several different types keep them distinct, including the
+//! sym and int keys which are compared and hashed without converting them to a `Val`.
+
+mod common;
+
+use common::run;
+
+#[test]
+fn mixed_keys() {
+	run(|| {
+		glsp::load_str(r#"
+			(let t (tab))
+			(= [t 1] 'int)
+			(= [t 1.0] 'flo)
+			(= [t \a] 'char)
+			(= [t 97] 'int-97)
+			(= [t 'a] 'sym)
+			(= [t "a"] 'str)
+			(= [t #t] 'bool)
+
+			(ensure (== (len t) 7))
+			(ensure (eq? [t 1] 'int))
+			(ensure (eq? [t 1.0] 'flo))
+			(ensure (eq? [t \a] 'char))
+			(ensure (eq? [t 97] 'int-97))
+			(ensure (eq? [t 'a] 'sym))
+			(ensure (eq? [t "a"] 'str))
+			(ensure (eq? [t #t] 'bool))
+
+			(ensure (nil? [t (? 97.0)]))
+			(ensure (nil? [t (? \b)]))
+			(ensure (nil? [t (? 'b)]))
+
+			;removing one key leaves the keys which it's == to
+			(del! t 1)
+			(ensure (not (has? t 1)))
+			(ensure (eq? [t 1.0] 'flo))
+			(del! t \a)
+			(ensure (eq? [t 97] 'int-97))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn flo_keys() {
+	run(|| {
+		glsp::load_str(r#"
+			(let t (tab))
+
+			;every nan is the same key
+			(= [t nan.0] 'first-nan)
+			(ensure (eq? [t nan.0] 'first-nan))
+			(ensure (eq? [t (/ 0.0 0.0)] 'first-nan))
+			(= [t (- nan.0)] 'second-nan)
+			(ensure (== (len t) 1))
+			(ensure (eq? [t nan.0] 'second-nan))
+
+			;as are 0.0 and -0.0
+			(= [t 0.0] 'zero)
+			(ensure (eq? [t -0.0] 'zero))
+			(ensure (nil? [t (? 0)]))
+			(ensure (== (len t) 2))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn rehashing() {
+	run(|| {
+		glsp::load_str(r#"
+			(let t (tab))
+			(let syms (arr ..(map (fn1 (sym "key-{_}")) (rn 1000))))
+
+			;the tab is resized several times as these entries are inserted
+			(forn (i 1000)
+			  (= [t i] (* i 2))
+			  (= [t [syms i]] (- i))
+			  (= [t (flo i)] 'flo)
+			  (= [t (str i)] 'str))
+
+			(ensure (== (len t) 4000))
+			(forn (i 1000)
+			  (ensure (== [t i] (* i 2)))
+			  (ensure (== [t [syms i]] (- i)))
+			  (ensure (eq? [t (sym "key-{i}")] [t [syms i]]))
+			  (ensure (eq? [t (flo i)] 'flo))
+			  (ensure (eq? [t (str i)] 'str)))
+
+			(forn (i 0 1000 2)
+			  (del! t i)
+			  (del! t [syms i]))
+
+			(ensure (== (len t) 3000))
+			(forn (i 1000)
+			  (ensure (eq? (has? t i) (odd? i)))
+			  (ensure (eq? (has? t [syms i]) (odd? i)))
+			  (ensure (has? t (flo i))))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn rust_api() {
+	run(|| {
+		let t = glsp::tab();
+		t.set(1, "int")?;
+		t.set(1.0, "flo")?;
+		t.set('\u{1}', "char")?;
+		t.set(glsp::sym("one")?, "sym")?;
+
+		assert_eq!(t.len(), 4);
+		assert_eq!(t.get::<_, String>(1)?, "int");
+		assert_eq!(t.get::<_, String>(1.0)?, "flo");
+		assert_eq!(t.get::<_, String>('\u{1}')?, "char");
+		assert_eq!(t.get::<_, String>(glsp::sym("one")?)?, "sym");
+		assert!(!t.has(2)?);
+
+		Ok(())
+	});
+}
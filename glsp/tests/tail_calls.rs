@@ -0,0 +1,105 @@
+//! Checks that self and mutual tail recursion can run to any depth without growing the stack,
+//! and that `(defer)` forms still run in the correct order when tail calls reuse a frame.
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+
+#[test]
+fn deep_recursion() {
+	run(|| {
+		assert!(glsp::tail_calls());
+
+		glsp::load_str(r#"
+			(defn count-down (n acc)
+			  (cond
+			    ((== n 0) acc)
+			    (else (count-down (- n 1) (+ acc 1)))))
+
+			(ensure (== (count-down 1_000_000 0) 1_000_000))
+
+			(defn even-steps? (n)
+			  (if (== n 0) #t (odd-steps? (- n 1))))
+
+			(defn odd-steps? (n)
+			  (if (== n 0) #f (even-steps? (- n 1))))
+
+			(ensure (even-steps? 1_000_000))
+			(ensure (odd-steps? 1_000_001))
+
+			;calls with three or more arguments are compiled differently
+			(defn rotate (n a b c)
+			  (cond
+			    ((== n 0) (arr a b c))
+			    (else (rotate (- n 1) b c a))))
+			(ensure (eq? (rotate 1_000_000 'a 'b 'c) '(b c a)))
+		"#, "test.glsp")?;
+
+		//without tail-call elimination, the same recursion is limited by the maximum call depth.
+		//(we lower the limit, because each non-tail call consumes native stack.)
+		glsp::set_max_call_depth(20);
+		assert_eq!(glsp::load_str("(count-down 100 0)", "test.glsp")?, Val::Int(100));
+
+		glsp::set_tail_calls(false);
+		let result = glsp::load_str("(count-down 100 0)", "test.glsp");
+		assert_eq!(result.unwrap_err().val(), Val::Sym(glsp::sym("stack-overflow")?));
+		glsp::set_tail_calls(true);
+
+		Ok(())
+	});
+}
+
+#[test]
+fn defers() {
+	run(|| {
+		glsp::load_str(r#"
+			(def events (arr))
+
+			;each (defer) has gone out of scope before the tail call, so the call reuses the
+			;frame, and each defer runs before the next iteration starts
+			(defn closed-scopes (n)
+			  (do
+			    (defer (push! events (arr 'exit n)))
+			    (push! events (arr 'enter n)))
+			  (if (== n 0) 'done (closed-scopes (- n 1))))
+
+			(ensure (eq? (closed-scopes 1_000_000) 'done))
+			(ensure (== (len events) 2_000_002))
+			(ensure (eq? (arr ..(take 4 events)) '((enter 1000000) (exit 1000000)
+			                                   (enter 999999) (exit 999999))))
+			(ensure (eq? (arr ..(take 2 (rev events))) '((exit 0) (enter 0))))
+
+			;while a (defer) is pending, a call can't be a tail call, so the defers run
+			;innermost-first after the recursion bottoms out
+			(= events (arr))
+			(defn open-scopes (n)
+			  (defer (push! events n))
+			  (if (== n 0) 'done (open-scopes (- n 1))))
+
+			(ensure (eq? (open-scopes 5) 'done))
+			(ensure (eq? events '(0 1 2 3 4 5)))
+
+			;when an error passes through a tail-called function, its pending defers run
+			;before those of the functions which are still on the stack
+			(= events (arr))
+			(defn failing ()
+			  (defer (push! events 'failing))
+			  (bail "failed"))
+
+			(defn tail-caller ()
+			  (do (defer (push! events 'tail-caller)))
+			  (failing))
+
+			(defn outer ()
+			  (defer (push! events 'outer))
+			  (tail-caller)
+			  'unreachable)
+
+			(ensure (eq? [(try (outer)) 0] 'err))
+			(ensure (eq? events '(tail-caller failing outer)))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
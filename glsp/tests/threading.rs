@@ -0,0 +1,120 @@
+//! Checks that `Runtime`s on different threads can execute simultaneously, and that each one has
+//! its own heap, globals and symbol table.
+
+use glsp::prelude::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Barrier};
+use std::thread;
+
+const THREADS: usize = 8;
+const RUNTIMES_PER_THREAD: usize = 20;
+
+const GRID_SRC: &str = r#"
+	(defstruct Grid
+	  width height cells
+
+	  (meth get (x y)
+	    [@cells (+ x (* y @width))])
+
+	  (meth set! (x y value)
+	    (= [@cells (+ x (* y @width))] value)))
+
+	(defn make-grid (width height)
+	  (Grid
+	    (width width)
+	    (height height)
+	    (cells (arr ..(take (* width height) (repeat 0))))))
+"#;
+
+const MAIN_SRC: &str = r#"
+	(def grid (make-grid 16 16))
+	(forn (y 16)
+	  (forn (x 16)
+	    (.set! grid x y (* (+ x worker) y))))
+
+	(def checksum (do
+	  (let total 0)
+	  (forn (y 16)
+	    (forn (x 16)
+	      (inc! total (.get grid x y))))
+	  total))
+"#;
+
+fn write_sources() -> PathBuf {
+	let dir = std::env::temp_dir().join(format!("glsp-threading-{}", std::process::id()));
+	fs::create_dir_all(&dir).unwrap();
+	fs::write(dir.join("grid.glsp"), GRID_SRC).unwrap();
+	fs::write(dir.join("main.glsp"), MAIN_SRC).unwrap();
+	dir
+}
+
+//the checksum computed by main.glsp
+fn expected_checksum(worker: i32) -> i32 {
+	let mut total = 0;
+	for y in 0 .. 16 {
+		for x in 0 .. 16 {
+			total += (x + worker) * y;
+		}
+	}
+	total
+}
+
+fn run_worker(worker: usize, dir: &Path) {
+	let grid_path = dir.join("grid.glsp").to_str().unwrap().to_string();
+	let main_path = dir.join("main.glsp").to_str().unwrap().to_string();
+
+	for i in 0 .. RUNTIMES_PER_THREAD {
+		let grid_path = grid_path.clone();
+		let main_path = main_path.clone();
+
+		let runtime = Runtime::new();
+		runtime.run(move || {
+			//symbols which only this runtime has interned
+			let own_name = format!("worker-{}-runtime-{}", worker, i);
+			glsp::bind_global(&own_name[..], worker as i32)?;
+			let sym_count = glsp::sym_count();
+
+			glsp::bind_global("worker", worker as i32)?;
+			glsp::load(&grid_path)?;
+			glsp::load(&main_path)?;
+
+			let checksum: i32 = glsp::global("checksum")?;
+			assert_eq!(checksum, expected_checksum(worker as i32));
+
+			//no other runtime's globals or symbols are visible
+			for sym in glsp::sym_iter() {
+				let name = sym.name();
+				if name.starts_with("worker-") {
+					assert_eq!(&name[..], &own_name[..]);
+				}
+			}
+			assert!(glsp::sym_count() > sym_count);
+
+			Ok(())
+		}).unwrap();
+	}
+}
+
+#[test]
+fn concurrent_runtimes() {
+	let dir = write_sources();
+	let barrier = Arc::new(Barrier::new(THREADS));
+
+	let handles: Vec<_> = (0 .. THREADS).map(|worker| {
+		let dir = dir.clone();
+		let barrier = barrier.clone();
+
+		thread::spawn(move || {
+			barrier.wait();
+			run_worker(worker, &dir)
+		})
+	}).collect();
+
+	let results: Vec<_> = handles.into_iter().map(|handle| handle.join()).collect();
+	fs::remove_dir_all(&dir).unwrap();
+
+	for result in results {
+		result.expect("a worker thread panicked");
+	}
+}
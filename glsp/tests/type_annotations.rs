@@ -0,0 +1,121 @@
+//! Checks that parameter and return-type annotations are recorded as metadata, and that they're
+//! only checked when `RuntimeBuilder::type_checks` is enabled.
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+use glsp::GSend;
+
+fn run_checked<F: FnOnce() -> GResult<()> + GSend>(f: F) {
+	let runtime = RuntimeBuilder::new().type_checks(true).build();
+	runtime.run(f).expect("the test failed");
+}
+
+const PRELUDE: &str = r#"
+	(defclass Obj)
+
+	(defn damage ((target : Obj) (amount : int) (? (crit? : bool) #f)) -> num
+	  "Damages the target."
+	  (if crit? (* amount 2) amount))
+
+	(defn total (label ..(amounts : num))
+	  (+ ..amounts))
+
+	(defn lie () -> int
+	  'not-an-int)
+"#;
+
+#[test]
+fn metadata() {
+	run(|| {
+		glsp::load_str(PRELUDE, "prelude.glsp")?;
+		glsp::load_str(r#"
+			(ensure (eq? (params damage) '((target : Obj) (amount : int) (? (crit? : bool)))))
+			(ensure (eq? (return-type damage) 'num))
+			(ensure (eq? (doc damage) "Damages the target."))
+			(ensure (eq? (params total) '(label ..(amounts : num))))
+			(ensure (nil? (return-type total)))
+
+			;without type checks, annotations have no effect
+			(ensure (eq? (damage 'not-an-obj 'not-an-int) 'not-an-int))
+			(ensure (eq? (lie) 'not-an-int))
+		"#, "test.glsp")?;
+
+		let damage: Root<GFn> = glsp::global("damage")?;
+		let types: Vec<Option<String>> = damage.param_types().unwrap().iter().map(|param_type| {
+			param_type.map(|sym| sym.name().to_string())
+		}).collect();
+
+		assert_eq!(types, [Some("Obj".into()), Some("int".into()), Some("bool".into())]);
+		assert_eq!(damage.return_type(), Some(glsp::sym("num")?));
+
+		let total: Root<GFn> = glsp::global("total")?;
+		assert_eq!(total.param_types().unwrap(), [None, Some(glsp::sym("num")?)]);
+		Ok(())
+	});
+}
+
+#[test]
+fn checks() {
+	run_checked(|| {
+		glsp::load_str(PRELUDE, "prelude.glsp")?;
+		glsp::load_str(r#"
+			(let target (Obj))
+			(ensure (== (damage target 5) 5))
+			(ensure (== (damage target 5 #t) 10))
+			(ensure (== (total 'x) 0))
+			(ensure (== (total 'x 1 2.5) 3.5))
+
+			(defn type-error? (result)
+			  (eq? result '(err type-error)))
+
+			(ensure (type-error? (try (damage 'not-an-obj 5))))
+			(ensure (type-error? (try (damage target 5.0))))
+			(ensure (type-error? (try (damage target 5 'yes))))
+			(ensure (type-error? (try (total 'x 1 'two))))
+			(ensure (type-error? (try (lie))))
+
+			(defn even-only ((n : even?) (? (label : any))) -> sym
+			  'even)
+
+			(ensure (eq? (even-only 2) 'even))
+			(ensure (type-error? (try (even-only 3))))
+		"#, "test.glsp")?;
+
+		let result = glsp::load_str("(damage (Obj) \"five\")", "error.glsp");
+		let message = result.unwrap_err().to_string();
+		assert!(message.contains("(damage): parameter `amount` expected int, received a str"));
+		Ok(())
+	});
+}
+
+#[test]
+fn methods() {
+	run_checked(|| {
+		glsp::load_str(r#"
+			(defclass Counter
+			  (field n)
+
+			  (init ((@n : int)))
+
+			  (meth add! ((amount : int)) -> int
+			    (inc! @n amount)
+			    @n)
+
+			  (meth split ((pair : arr?) (? (scale : num) 1))
+			    (let (a b) pair)
+			    (* (+ a b) scale)))
+
+			(let counter (Counter 10))
+			(ensure (== (.add! counter 5) 15))
+			(ensure (== (.split counter '(1 2) 2) 6))
+
+			(ensure (eq? (try (Counter 'ten)) '(err type-error)))
+			(ensure (eq? (try (.add! counter 'five)) '(err type-error)))
+			(ensure (eq? (try (.split counter 'ab)) '(err type-error)))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
@@ -0,0 +1,107 @@
+//! Checks that `farr` and `iarr` can be constructed, indexed and bulk-modified by scripts, and
+//! that Rust code can borrow their contents as slices.
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+use glsp::{FArr, IArr};
+
+fn scale(samples: &mut FArr, factor: f32) {
+	for sample in samples.as_mut_slice() {
+		*sample *= factor;
+	}
+}
+
+fn sum(values: &IArr) -> i32 {
+	values.as_slice().iter().sum()
+}
+
+fn average(values: FArr) -> f32 {
+	values.as_slice().iter().sum::<f32>() / values.len() as f32
+}
+
+#[test]
+fn scripts() {
+	run(|| {
+		glsp::load_str(r#"
+			(let fa (farr 1 2.5 -3))
+			(ensure (farr? fa))
+			(ensure (not (farr? (arr 1.0))))
+			(ensure (not (iarr? fa)))
+			(ensure (== (.len fa) 3))
+			(ensure (== [fa 0] 1.0))
+			(ensure (flo? [fa 0]))
+			(ensure (== [fa -1] -3.0))
+
+			(= [fa 1] 10)
+			(ensure (== [fa 1] 10.0))
+			(inc! [fa 1] 0.5)
+			(ensure (== [fa 1] 10.5))
+			(ensure (eq? (farr->arr fa) '(1.0 10.5 -3.0)))
+
+			(ensure (eq? [(try [fa 3]) 0] 'err))
+			(ensure (eq? [(try (= [fa 0] 'x)) 0] 'err))
+
+			(let zeroes (farr-of-len 4))
+			(ensure (eq? (farr->arr zeroes) '(0.0 0.0 0.0 0.0)))
+			(farr-fill! zeroes 2)
+			(ensure (eq? (farr->arr zeroes) '(2.0 2.0 2.0 2.0)))
+
+			(farr-copy! zeroes fa 1)
+			(ensure (eq? (farr->arr zeroes) '(2.0 1.0 10.5 -3.0)))
+			(ensure (eq? [(try (farr-copy! zeroes fa 2)) 0] 'err))
+			(farr-copy! fa fa)
+
+			(farr-map! zeroes (fn (x) (* x 2)))
+			(ensure (eq? (farr->arr zeroes) '(4.0 2.0 21.0 -6.0)))
+
+			(let copy (clone zeroes))
+			(ensure (eq? copy zeroes))
+			(ensure (not (same? copy zeroes)))
+			(= [copy 0] 0.0)
+			(ensure (not (eq? copy zeroes)))
+
+			(let ia (iarr-of-len 3 7))
+			(ensure (iarr? ia))
+			(ensure (int? [ia 2]))
+			(ensure (eq? [(try (= [ia 0] 1.5)) 0] 'err))
+
+			;the callback can read from the array while it's being mapped
+			(iarr-map! ia (fn (x) (+ x [ia 0])))
+			(ensure (eq? (iarr->arr ia) '(14 21 21)))
+			(ensure (eq? (iarr->arr (iarr ..(rn 3))) '(0 1 2)))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn rust_access() {
+	run(|| {
+		glsp::bind_rfn("scale!", rfn!(scale))?;
+		glsp::bind_rfn("sum", rfn!(sum))?;
+		glsp::bind_rfn("average", rfn!(average))?;
+		glsp::bind_global("samples", glsp::rdata(FArr::from(vec![0.5, -1.0, 0.25]))?)?;
+
+		glsp::load_str(r#"
+			(scale! samples 2.0)
+			(ensure (eq? (farr->arr samples) '(1.0 -2.0 0.5)))
+			(ensure (== (sum (iarr 1 2 3)) 6))
+
+			;receiving an FArr by value copies from either an farr or an arr
+			(ensure (== (average (farr 1 2 3)) 2.0))
+			(ensure (== (average (arr 2 4)) 3.0))
+			(ensure (eq? [(try (average (arr 'a))) 0] 'err))
+		"#, "test.glsp")?;
+
+		let samples: RRoot<FArr> = glsp::global("samples")?;
+		assert_eq!(samples.borrow().as_slice(), &[1.0, -2.0, 0.5]);
+
+		let copied: FArr = glsp::global("samples")?;
+		assert_eq!(Vec::from(copied), vec![1.0, -2.0, 0.5]);
+
+		Ok(())
+	});
+}
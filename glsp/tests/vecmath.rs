@@ -0,0 +1,207 @@
+//! Checks the vec2, vec3 and mat3 primitive types: construction, component access, arithmetic
+//! through both the numeric instrs and the `+ - * /` rfns, the vector functions, equality and
+//! hashing, and conversion to and from Rust arrays and tuples.
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+use glsp::{Flo, Mat3, Vec2, Vec3, VectorOp};
+
+fn eval<T: FromVal>(src: &str) -> GResult<T> {
+	T::from_val(&glsp::load_str(src, "test.glsp")?)
+}
+
+#[test]
+fn construction_and_access() {
+	run(|| {
+		glsp::load_str(r#"
+			(def v2 (vec2 1 2.5))
+			(def v3 (vec3 -1 0 4))
+		"#, "test.glsp")?;
+
+		assert!(eval::<bool>("(and (vec2? v2) (vec3? v3) (not (vec2? v3)) (not (vec3? '(1 2))))")?);
+		assert_eq!(eval::<Sym>("(type-of v2)")?, glsp::sym("vec2")?);
+		assert_eq!(eval::<Sym>("(type-of v3)")?, glsp::sym("vec3")?);
+
+		//components are always flos, and can be accessed by name, by index, or with vx/vy/vz
+		assert_eq!(eval::<Val>("[v2 'x]")?, Val::Flo(1.0));
+		assert_eq!(eval::<(f32, f32, f32, f32)>("(arr [v2 1] [v2 -1] (vx v2) (vy v2))")?,
+		           (2.5, 2.5, 1.0, 2.5));
+		assert_eq!(eval::<(f32, f32, f32)>("(arr [v3 'z] (vz v3) [v3 0])")?, (4.0, 4.0, -1.0));
+
+		assert!(eval::<bool>("(has? v3 'z)")?);
+		assert!(!eval::<bool>("(has? v2 'z)")?);
+		assert!(!eval::<bool>("(has? v2 2)")?);
+		assert_eq!(eval::<Val>("[v2 (? 'z)]")?, Val::Nil);
+		assert!(eval::<Val>("[v2 'z]").is_err());
+		assert!(eval::<Val>("[v2 2]").is_err());
+		assert!(eval::<Val>("[v2 \"x\"]").is_err());
+		assert!(eval::<Val>("(vz v2)").is_err());
+
+		//they're immutable
+		assert!(eval::<bool>("(frozen? v2)")?);
+		assert!(eval::<Val>("(= [v2 'x] 10)").is_err());
+		assert!(eval::<Val>("(= [v2 (? 'x)] 10)").is_err());
+
+		assert_eq!(eval::<String>("(str v2)")?, "#<vec2 1.0 2.5>");
+		assert_eq!(eval::<String>("(str (mat3 (vec3 1 0 0) (vec3 0 1 0) (vec3 0 0 1)))")?,
+		           "#<mat3 (1.0 0.0 0.0) (0.0 1.0 0.0) (0.0 0.0 1.0)>");
+
+		Ok(())
+	});
+}
+
+#[test]
+fn arithmetic() {
+	run(|| {
+		glsp::load_str(r#"
+			(def a (vec2 1 2))
+			(def b (vec2 3 5))
+			(def rotate (mat3 (vec3 0 1 0) (vec3 -1 0 0) (vec3 0 0 1)))
+		"#, "test.glsp")?;
+
+		//calling the arithmetic functions directly uses the numeric instrs, while (apply) goes
+		//through the rfns. both should produce the same results.
+		for (direct, applied) in &[
+			("(+ a b)", "(apply + (arr a b))"),
+			("(- b a)", "(apply - (arr b a))"),
+			("(* a b)", "(apply * (arr a b))"),
+			("(/ b a)", "(apply / (arr b a))"),
+			("(* a 2)", "(apply * (arr a 2))"),
+			("(* 2 a)", "(apply * (arr 2 a))"),
+			("(- a)", "(apply - (arr a))"),
+			("(+ 1 a b)", "(apply + (arr 1 a b))")
+		] {
+			let direct: Val = eval(direct)?;
+			let applied: Val = eval(applied)?;
+			assert!(direct.is_vec2(), "{} is not a vec2", direct);
+			assert_eq!(direct, applied);
+		}
+
+		assert_eq!(eval::<[f32; 2]>("(+ a b)")?, [4.0, 7.0]);
+		assert_eq!(eval::<[f32; 2]>("(- b a)")?, [2.0, 3.0]);
+		assert_eq!(eval::<[f32; 2]>("(/ b 2)")?, [1.5, 2.5]);
+		assert_eq!(eval::<[f32; 2]>("(- a)")?, [-1.0, -2.0]);
+		assert_eq!(eval::<[f32; 2]>("(/ (vec2 2 4))")?, [0.5, 0.25]);
+
+		//a mat3 multiplies a vec3 or another mat3 as a matrix
+		assert_eq!(eval::<[f32; 3]>("(* rotate (vec3 1 0 0))")?, [0.0, 1.0, 0.0]);
+		assert_eq!(eval::<[f32; 3]>("(* rotate rotate (vec3 1 0 0))")?, [-1.0, 0.0, 0.0]);
+		assert_eq!(eval::<[f32; 3]>("(apply * (arr rotate (vec3 0 2 0)))")?, [-2.0, 0.0, 0.0]);
+
+		//mismatched types are an error, rather than being broadcast
+		assert!(eval::<Val>("(+ a (vec3 1 2 3))").is_err());
+		assert!(eval::<Val>("(apply + (arr a (vec3 1 2 3)))").is_err());
+		assert!(eval::<Val>("(* (vec3 1 0 0) rotate)").is_err());
+		assert!(eval::<Val>("(+ a 'x)").is_err());
+
+		//plain numeric arithmetic is unaffected
+		assert_eq!(eval::<i32>("(apply + (arr 1 2 3))")?, 6);
+		assert!(eval::<Val>("(apply / (arr 1 0))").is_err());
+
+		Ok(())
+	});
+}
+
+#[test]
+fn vector_fns() {
+	run(|| {
+		assert_eq!(eval::<f32>("(dot (vec2 1 2) (vec2 3 4))")?, 11.0);
+		assert_eq!(eval::<f32>("(dot (vec3 1 2 3) (vec3 4 5 6))")?, 32.0);
+		assert_eq!(eval::<[f32; 3]>("(cross (vec3 1 0 0) (vec3 0 1 0))")?, [0.0, 0.0, 1.0]);
+		assert_eq!(eval::<f32>("(cross (vec2 1 0) (vec2 0 1))")?, 1.0);
+		assert_eq!(eval::<f32>("(len (vec2 3 4))")?, 5.0);
+		assert_eq!(eval::<f32>("(let v (vec3 2 3 6)) (len v)")?, 7.0);
+		assert_eq!(eval::<[f32; 2]>("(normalize (vec2 0 -5))")?, [0.0, -1.0]);
+		assert_eq!(eval::<[f32; 2]>("(normalize (vec2 0 0))")?, [0.0, 0.0]);
+		assert_eq!(eval::<f32>("(dist (vec2 1 1) (vec2 4 5))")?, 5.0);
+		assert_eq!(eval::<[f32; 2]>("(lerp-vec (vec2 0 10) (vec2 10 20) 0.25)")?, [2.5, 12.5]);
+
+		assert!(eval::<Val>("(dot (vec2 1 2) (vec3 1 2 3))").is_err());
+		assert!(eval::<Val>("(dist (vec2 1 2) 3)").is_err());
+
+		//conversion to and from arrs
+		assert_eq!(eval::<Vec<f32>>("(vec->arr (vec3 1 2 3))")?, vec![1.0, 2.0, 3.0]);
+		assert!(eval::<bool>("(eq? (arr->vec '(1 2)) (vec2 1 2))")?);
+		assert!(eval::<bool>("(vec3? (arr->vec (vec->arr (vec3 1 2 3))))")?);
+		assert!(eval::<Val>("(arr->vec '(1 2 3 4))").is_err());
+		assert!(eval::<Val>("(arr->vec '(1 x))").is_err());
+		assert_eq!(eval::<[[f32; 3]; 3]>(r#"
+			(let m (mat3 (vec3 1 2 3) (vec3 4 5 6) (vec3 7 8 9)))
+			(vec->arr m)
+		"#)?, [[1.0, 2.0, 3.0], [4.0, 5.0, 6.0], [7.0, 8.0, 9.0]]);
+
+		Ok(())
+	});
+}
+
+#[test]
+fn equality() {
+	run(|| {
+		assert!(eval::<bool>("(eq? (vec2 1 2) (vec2 1.0 2.0))")?);
+		assert!(!eval::<bool>("(eq? (vec2 1 2) (vec2 2 1))")?);
+		assert!(!eval::<bool>("(eq? (vec2 0 0) '(0.0 0.0))")?);
+		assert!(!eval::<bool>("(same? (vec2 1 2) (vec2 1 2))")?);
+		assert!(eval::<bool>("(let v (vec2 1 2)) (same? v v)")?);
+		assert!(!eval::<bool>("(eq? (vec2 nan.0 0) (vec2 nan.0 0))")?);
+		assert!(eval::<bool>("(keys-eqv? (vec2 nan.0 0) (vec2 nan.0 0))")?);
+
+		//they can be used as table keys, and their hashes are consistent with (eq?)
+		assert_eq!(eval::<Sym>(r#"
+			(let t (tab ((vec3 1 2 3) 'found)))
+			[t (+ (vec3 0 1 2) 1)]
+		"#)?, glsp::sym("found")?);
+		assert!(eval::<bool>("(== (hash (vec2 1 -0.0)) (hash (+ (vec2 0 0) (vec2 1 0))))")?);
+		assert!(eval::<bool>("(eq? (state-hash (vec2 1 2)) (state-hash (+ (vec2 0 1) (vec2 1 1))))")?);
+		assert!(eval::<bool>("(not (== (hash (vec2 1 2)) (hash (vec3 1 2 0))))")?);
+
+		//a canonical ordering puts them after every other type, with their components compared
+		//in order
+		assert!(eval::<bool>(r#"
+			(let sorted (sort (arr (vec3 0 0 0) (vec2 1 0) (vec2 0 5) 10) canonical-cmp))
+			(and (== [sorted 0] 10) (eq? [sorted 1] (vec2 0 5)) (vec3? [sorted 3]))
+		"#)?);
+
+		Ok(())
+	});
+}
+
+#[test]
+fn rust_conversions() {
+	run(|| {
+		//a vec2 or vec3 converts into an array or tuple of its components
+		let v2: Root<Vec2> = glsp::vec2(1.0, 2.0);
+		let v3: Root<Vec3> = glsp::vec3(1.0, 2.0, 3.0);
+		let v2 = v2.to_val()?;
+		let v3 = v3.to_val()?;
+
+		assert_eq!(<[f32; 2]>::from_val(&v2)?, [1.0, 2.0]);
+		assert_eq!(<(f32, f32)>::from_val(&v2)?, (1.0, 2.0));
+		assert_eq!(<[f32; 3]>::from_val(&v3)?, [1.0, 2.0, 3.0]);
+		assert_eq!(<(f32, f32, f32)>::from_val(&v3)?, (1.0, 2.0, 3.0));
+		assert_eq!(<[f64; 3]>::from_val(&v3)?, [1.0, 2.0, 3.0]);
+		assert!(<[f32; 3]>::from_val(&v2).is_err());
+		assert!(<(f32, f32)>::from_val(&v3).is_err());
+
+		//a mat3 converts into its columns
+		let m = glsp::mat3([[1.0, 0.0, 0.0], [0.0, 2.0, 0.0], [0.0, 0.0, 3.0]]);
+		assert_eq!(m.col(1), [0.0, 2.0, 0.0]);
+		assert_eq!(<[[Flo; 3]; 3]>::from_val(&m.to_val()?)?, m.to_cols_array_2d());
+
+		//rust code can perform the same arithmetic as (+ a b), etc.
+		let scaled = glsp::vector_arith(VectorOp::Mul, &m.to_val()?, &v3)?;
+		assert_eq!(<[f32; 3]>::from_val(&scaled)?, [1.0, 4.0, 9.0]);
+		assert!(glsp::vector_arith(VectorOp::Add, &v2, &v3).is_err());
+
+		//rfns can receive vectors directly
+		glsp::bind_rfn("mat3-diag", rfn!(|m: &Mat3| -> [Flo; 3] {
+			[m.col(0)[0], m.col(1)[1], m.col(2)[2]]
+		}))?;
+		glsp::bind_global("m", m)?;
+		assert_eq!(eval::<[f32; 3]>("(mat3-diag m)")?, [1.0, 2.0, 3.0]);
+		assert!(eval::<Val>("(mat3-diag (vec3 1 2 3))").is_err());
+
+		Ok(())
+	});
+}
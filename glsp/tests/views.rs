@@ -0,0 +1,271 @@
+//! Checks that a Vec of Rust structs can be lent to scripts with `glsp::with_scoped_views`, that
+//! field reads and writes go directly to the Rust structs with bounds and type checks, and that
+//! every view expires when its scope ends.
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+use glsp::{View, ViewField};
+
+fn eval<T: FromVal>(src: &str) -> GResult<T> {
+	T::from_val(&glsp::load_str(src, "test.glsp")?)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Transform {
+	x: f32,
+	y: f32,
+	layer: u8,
+	visible: bool,
+	id: u32,
+	name: String
+}
+
+impl Transform {
+	fn new(x: f32, y: f32, name: &str) -> Transform {
+		Transform { x, y, layer: 0, visible: true, id: 0, name: name.to_string() }
+	}
+}
+
+fn register() -> GResult<()> {
+	glsp::register_view::<Transform>(&[
+		("x", ViewField::F32(|t| &mut t.x)),
+		("y", ViewField::F32(|t| &mut t.y)),
+		("layer", ViewField::U8(|t| &mut t.layer)),
+		("visible?", ViewField::Bool(|t| &mut t.visible)),
+		("id", ViewField::U32(|t| &mut t.id))
+	])
+}
+
+#[test]
+fn reads_and_writes() {
+	run(|| {
+		register()?;
+		glsp::load_str(r#"
+			(defn update-transforms (views)
+			  (forn (i (len views))
+			    (let v [views i])
+			    (inc! [v 'x] 10)
+			    (= [v 'y] (* [v 'y] 2.0))
+			    (= [v 'layer] (+ i 1)))
+			  (= [[views -1] 'visible?] #f)
+			  (+ [[views 0] 'x] [[views 1] 'y]))
+		"#, "test.glsp")?;
+
+		let mut transforms = vec![Transform::new(1.0, 2.0, "a"), Transform::new(3.0, 4.5, "b")];
+		let update: Root<GFn> = glsp::global("update-transforms")?;
+
+		let result: f32 = glsp::with_scoped_views(&mut transforms, |views| {
+			glsp::call(&update, &(views.clone(),))
+		})??;
+
+		assert_eq!(result, 11.0 + 9.0);
+		assert_eq!(transforms, vec![
+			Transform { x: 11.0, y: 4.0, layer: 1, visible: true, id: 0, name: "a".to_string() },
+			Transform { x: 13.0, y: 9.0, layer: 2, visible: false, id: 0, name: "b".to_string() }
+		]);
+
+		//the Rust api
+		glsp::with_scoped_views(&mut transforms, |views| -> GResult<()> {
+			assert_eq!(views.len(), 2);
+			assert_eq!(views.index(), None);
+			assert_eq!(views.to_string(), "#<view:Transform>");
+
+			let v = views.element(-2)?;
+			assert_eq!(v.index(), Some(0));
+			assert_eq!(v.to_string(), "#<view:Transform:0>");
+			assert!(v.has("layer")? && !v.has("name")? && !views.has("layer")?);
+
+			assert_eq!(v.get::<_, f32>("x")?, 11.0);
+			v.set("id", 123)?;
+			v.set("visible?", false)?;
+			assert!(!v.get::<_, bool>("visible?")?);
+			Ok(())
+		})??;
+
+		assert_eq!((transforms[0].id, transforms[0].visible), (123, false));
+
+		//views are a primitive type
+		glsp::with_scoped_views(&mut transforms, |views| -> GResult<()> {
+			glsp::bind_global("views", views.clone())?;
+			let result: String = eval(r#"
+				(let v [views 1])
+				(ensure (view? views))
+				(ensure (view? v))
+				(ensure (eq? (type-of v) 'view))
+				(ensure (has? views 1))
+				(ensure (not (has? views 2)))
+				(ensure (has? v 'x))
+				(ensure (not (has? v 'name)))
+				(ensure (eq? v v))
+				(ensure (not (eq? v [views 1])))
+				(ensure (not (expired? v)))
+				(ensure (nil? [views (? 5)]))
+				(ensure (nil? [v (? 'name)]))
+				(= [v (? 'name)] 10)
+				(str views " " v " " (len views) " " (get-in views '(1 layer)))
+			"#)?;
+			assert_eq!(result, "#<view:Transform> #<view:Transform:1> 2 2");
+			glsp::del_global("views")
+		})??;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn checks() {
+	run(|| {
+		register()?;
+
+		let mut transforms = vec![Transform::new(0.0, 0.0, "a"); 3];
+		transforms[2].id = u32::MAX;
+
+		glsp::with_scoped_views(&mut transforms, |views| -> GResult<()> {
+			glsp::bind_global("views", views.clone())?;
+
+			let err = |src: &str| -> String {
+				eval::<Val>(src).unwrap_err().val().to_string()
+			};
+
+			assert_eq!(err("[views 3]"), "out-of-bounds view access: len is 3, index is 3");
+			assert!(err("[views -4]").contains("out-of-bounds view access"));
+			assert!(err("[views 'x]").contains("refers to the whole Vec"));
+			assert!(err("[views \"x\"]").contains("indexed a view with a str"));
+			assert!(err("[[views 0] 0]").contains("with an int"));
+			assert_eq!(err("[[views 0] 'name]"),
+			           "attempted to access nonexistent field 'name' in a Transform view");
+			assert_eq!(err("(= [[views 0] 'layer] 256)"),
+			           "attempted to assign 256 to the u8 field 'layer' of a Transform view, which \
+			            is out of range");
+			assert_eq!(err("(= [[views 0] 'layer] -1)"),
+			           "attempted to assign -1 to the u8 field 'layer' of a Transform view, which \
+			            is out of range");
+			assert_eq!(err("(= [[views 0] 'layer] 1.5)"),
+			           "attempted to assign a flo to the u8 field 'layer' of a Transform view");
+			assert_eq!(err("(= [[views 0] 'visible?] 1)"),
+			           "attempted to assign an int to the bool field 'visible?' of a Transform view");
+			assert_eq!(err("(= [[views 0] 'x] 'a)"),
+			           "attempted to assign a sym to the f32 field 'x' of a Transform view");
+			assert!(err("[[views 2] 'id]").contains("too large for an int"));
+			assert!(err("(len [views 0])").contains("passed to the len builtin"));
+
+			//a failed write leaves the field unchanged
+			assert_eq!(eval::<i32>("[[views 0] 'layer]")?, 0);
+
+			//an int can be assigned to a flo field
+			assert_eq!(eval::<f32>("(= [[views 0] 'x] 7) [[views 0] 'x]")?, 7.0);
+
+			glsp::del_global("views")
+		})??;
+
+		assert_eq!(transforms[0].x, 7.0);
+
+		Ok(())
+	});
+}
+
+#[test]
+fn expiry() {
+	run(|| {
+		register()?;
+		glsp::load_str(r#"
+			(def stored-views #n)
+			(def stored-elem #n)
+
+			(defn keep (views)
+			  (= stored-views views)
+			  (= stored-elem [views 0]))
+		"#, "test.glsp")?;
+
+		let mut transforms = vec![Transform::new(1.0, 2.0, "a")];
+		let keep: Root<GFn> = glsp::global("keep")?;
+		glsp::with_scoped_views(&mut transforms, |views| {
+			glsp::call::<_, _, Val>(&keep, &(views.clone(),))
+		})??;
+
+		//the Vec has been returned to its owner
+		assert_eq!(transforms.len(), 1);
+
+		let result: String = eval(r#"
+			(ensure (expired? stored-views))
+			(ensure (expired? stored-elem))
+
+			(let (tag e) (try-chain [stored-elem 'x]))
+			(ensure (eq? tag 'err))
+			(ensure (eq? [e 'payload] 'expired-view))
+
+			(let (_ e) (try-chain (= [stored-elem 'x] 1.0)))
+			(ensure (eq? [e 'payload] 'expired-view))
+
+			(let (_ e) (try-chain [stored-views 0]))
+			(str [e 'payload] " " [[e 'source] 'payload])
+		"#)?;
+
+		assert!(result.starts_with("expired-view View::element failed: attempted to access a \
+		                            Transform view after it expired. it was lent by Rust code at"),
+		        "{}", result);
+
+		//when the views are lent from within an rfn, the error names the rfn's call site
+		fn lend(callback: Root<GFn>) -> GResult<()> {
+			let mut transforms = vec![Transform::new(1.0, 2.0, "b")];
+			glsp::with_scoped_views(&mut transforms, |views| {
+				glsp::call::<_, _, Val>(&callback, &(views.clone(),))
+			})??;
+			Ok(())
+		}
+
+		glsp::bind_rfn("lend", rfn!(lend))?;
+		let result: String = eval(r#"
+			(lend keep)
+			(let (_ e) (try-chain [stored-elem 'y]))
+			(str [e 'payload] ": " [[e 'source] 'payload])
+		"#)?;
+		assert_eq!(result, "expired-view: View::get failed: attempted to access a Transform view \
+		                    after it expired. it was lent by the rfn (lend), called at test.glsp:2");
+
+		let view: Root<View> = glsp::global("stored-elem")?;
+		assert!(view.is_expired());
+		assert!(view.get::<_, f32>("x").is_err());
+
+		//the Vec is also returned when the closure panics
+		let mut transforms = vec![Transform::new(1.0, 2.0, "c")];
+		let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+			glsp::with_scoped_views(&mut transforms, |_| panic!("oops"))
+		}));
+		assert!(result.is_err());
+		assert_eq!(transforms[0].name, "c");
+
+		Ok(())
+	});
+}
+
+#[test]
+fn registration() {
+	run(|| {
+		let mut transforms = vec![Transform::new(1.0, 2.0, "a")];
+		let err = glsp::with_scoped_views(&mut transforms, |_| ()).unwrap_err();
+		assert!(err.val().to_string().contains("has not been registered"));
+		assert_eq!(transforms.len(), 1);
+
+		let err = glsp::register_view::<Transform>(&[
+			("x", ViewField::F32(|t| &mut t.x)),
+			("x", ViewField::F32(|t| &mut t.y))
+		]).unwrap_err();
+		assert_eq!(err.val().to_string(), "duplicate field 'x' in the view type Transform");
+
+		register()?;
+		let err = register().unwrap_err();
+		assert_eq!(err.val().to_string(), "the view type Transform has already been registered");
+
+		assert_eq!(ViewField::<Transform>::U8(|t| &mut t.layer).type_name(), "u8");
+
+		//an empty Vec can be lent
+		let mut empty = Vec::<Transform>::new();
+		let len = glsp::with_scoped_views(&mut empty, |views| views.len())?;
+		assert_eq!(len, 0);
+
+		Ok(())
+	});
+}
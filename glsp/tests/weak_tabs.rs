@@ -0,0 +1,218 @@
+//! Checks that weak-keyed and weak-valued tables lose their entries once the weakly-held key or
+//! value has been garbage-collected, and that iterating over them doesn't keep those entries alive.
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+
+fn eval_len(src: &str) -> GResult<usize> {
+	usize::from_val(&glsp::load_str(src, "len.glsp")?)
+}
+
+//runs the garbage collector until `done` returns true, or until it gives up
+fn collect_until<F: FnMut() -> GResult<bool>>(mut done: F) -> GResult<()> {
+	for _ in 0 .. 1000 {
+		if done()? {
+			return Ok(())
+		}
+
+		glsp::gc();
+	}
+
+	bail!("the garbage collector didn't remove the expected entries")
+}
+
+#[test]
+fn weak_keys() {
+	run(|| {
+		glsp::load_str(r#"
+			(defclass Entity)
+
+			(def kept (Entity))
+			(def cache (weak-key-tab (arr kept 'kept-value) '(sym sym-value)))
+
+			(defn fill-cache ()
+			  (forn (i 10)
+			    (= [cache (Entity)] i)))
+
+			(fill-cache)
+			(ensure (== (len cache) 12))
+			(ensure (eq? [cache kept] 'kept-value))
+		"#, "test.glsp")?;
+
+		//keys which aren't compared by identity are held strongly
+		collect_until(|| Ok(eval_len("(len cache)")? == 2))?;
+
+		glsp::load_str(r#"
+			(ensure (eq? [cache kept] 'kept-value))
+			(ensure (eq? [cache 'sym] 'sym-value))
+
+			;the table's weakness is preserved when it's cloned
+			(def cache-clone (clone cache))
+			(= [cache-clone (Entity)] 'temporary)
+			(ensure (== (len cache-clone) 3))
+		"#, "test.glsp")?;
+
+		collect_until(|| Ok(eval_len("(len cache-clone)")? == 2))?;
+		assert!(glsp::global::<_, Root<Tab>>("cache-clone")?.has_weak_keys());
+
+		Ok(())
+	});
+}
+
+#[test]
+fn weak_values() {
+	run(|| {
+		glsp::load_str(r#"
+			(defclass Entity)
+
+			(def player (Entity))
+			(def by-name (weak-value-tab))
+			(= [by-name 'player] player)
+			(= [by-name 'score] 100)
+
+			(defn spawn-enemies ()
+			  (forn (i 10)
+			    (= [by-name i] (Entity))))
+
+			(spawn-enemies)
+			(ensure (== (len by-name) 12))
+		"#, "test.glsp")?;
+
+		collect_until(|| Ok(eval_len("(len by-name)")? == 2))?;
+
+		glsp::load_str(r#"
+			(ensure (same? [by-name 'player] player))
+			(ensure (== [by-name 'score] 100))
+			(ensure (not (has? by-name 0)))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn iteration() {
+	run(|| {
+		glsp::load_str(r#"
+			(defclass Entity)
+
+			(def kept (Entity))
+			(def roots (arr))
+			(def cache (weak-key-tab (arr kept 'kept)))
+
+			(defn fill-cache ()
+			  (forn (i 10)
+			    (let entity (Entity))
+			    (push! roots entity)
+			    (= [cache entity] i)))
+
+			(fill-cache)
+
+			;each iterator is created while all of the keys are alive
+			(def entries-iter (iter cache))
+			(def keys-iter (keys cache))
+			(def values-iter (values cache))
+			(ensure (eq? (len entries-iter) 'unknown))
+
+			(clear! roots)
+		"#, "test.glsp")?;
+
+		//the iterators don't keep the keys alive, and they skip the entries which were removed
+		collect_until(|| Ok(eval_len("(len cache)")? == 1))?;
+
+		glsp::load_str(r#"
+			(let entries (arr ..entries-iter))
+			(ensure (== (len entries) 1))
+			(ensure (same? [[entries 0] 0] kept))
+			(ensure (eq? [[entries 0] 1] 'kept))
+
+			(let keys (arr ..keys-iter))
+			(ensure (== (len keys) 1))
+			(ensure (same? [keys 0] kept))
+			(ensure (eq? (arr ..values-iter) '(kept)))
+
+			;entries which are still alive are visited normally
+			(let remaining (arr))
+			(for (key value) in cache
+			  (push! remaining value))
+			(ensure (eq? remaining '(kept)))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn old_generation() {
+	run(|| {
+		//a cycle of the incremental collector only ends once enough memory has survived it, and
+		//it only makes progress when objects are promoted. we keep a large arr alive, and
+		//promote a small arr on each step.
+		glsp::load_str(r#"
+			(def ballast (arr ..(rn 100000)))
+			(def churn #n)
+
+			(defclass Entity)
+			(def roots (arr))
+			(def cache (weak-key-tab))
+			(def by-name (weak-value-tab))
+
+			(forn (i 10)
+			  (let entity (Entity))
+			  (push! roots entity)
+			  (= [cache entity] i)
+			  (= [by-name i] entity))
+		"#, "test.glsp")?;
+
+		//the entities are promoted into the old generation before their roots are dropped
+		for _ in 0 .. 10 {
+			glsp::gc();
+		}
+
+		glsp::load_str("(clear! roots)", "test.glsp")?;
+		assert_eq!(eval_len("(+ (len cache) (len by-name))")?, 20);
+
+		collect_until(|| {
+			glsp::load_str("(= churn (arr ..(rn 100)))", "churn.glsp")?;
+			Ok(eval_len("(+ (len cache) (len by-name))")? == 0)
+		})?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn rust_api() {
+	run(|| {
+		let tab = glsp::weak_key_tab();
+		assert!(tab.has_weak_keys() && !tab.has_weak_values());
+		assert!(glsp::weak_value_tab().has_weak_values());
+		assert!(!glsp::tab().has_weak_keys());
+
+		glsp::load_str("(defclass Point)", "point.glsp")?;
+		let make_obj = || -> GResult<Root<Obj>> {
+			Ok(glsp::load_str("(Point)", "point.glsp")?.unwrap_obj())
+		};
+
+		let kept = make_obj()?;
+		tab.set(&kept, 1)?;
+		tab.set(make_obj()?, 2)?;
+		assert_eq!(tab.len(), 2);
+
+		//while the table is borrowed, its entries can't be removed, so they're kept alive
+		{
+			let entries = tab.entries();
+			glsp::gc();
+			assert_eq!(entries.iter().count(), 2);
+		}
+
+		tab.set(make_obj()?, 3)?;
+		assert_eq!(tab.len(), 3);
+		collect_until(|| Ok(tab.len() == 2))?;
+		assert_eq!(tab.get::<_, i32>(&kept)?, 1);
+
+		Ok(())
+	});
+}
@@ -0,0 +1,153 @@
+//! Checks that `with-global` and `with-globals` restore a global's value when their scope exits
+//! normally or with an error, that they nest, and that an override inside a coroutine is only
+//! visible while that coroutine is running.
+
+mod common;
+
+use common::run;
+use glsp::prelude::*;
+
+#[test]
+fn scopes() {
+	run(|| {
+		glsp::load_str(r#"
+			(def *level* 'info)
+			(def *indent* 0)
+
+			(defn level () *level*)
+
+			(ensure (eq? (with-globals (*level* 'debug) (level)) 'debug))
+			(ensure (eq? *level* 'info))
+
+			;values are evaluated before any global is changed
+			(with-globals (*level* *indent* *indent* *level*)
+			  (ensure (== *level* 0))
+			  (ensure (eq? *indent* 'info)))
+			(ensure (eq? *level* 'info))
+			(ensure (== *indent* 0))
+
+			;nested overrides
+			(with-globals (*level* 'debug)
+			  (with-globals (*level* 'trace *indent* 4)
+			    (ensure (eq? (level) 'trace))
+			    (ensure (== *indent* 4)))
+			  (ensure (eq? (level) 'debug))
+			  (ensure (== *indent* 0)))
+			(ensure (eq? *level* 'info))
+
+			;with-global lasts until the end of the enclosing scope
+			(defn splice-scope ()
+			  (with-global *level* 'warn)
+			  (level))
+			(ensure (eq? (splice-scope) 'warn))
+			(ensure (eq? *level* 'info))
+
+			;the global must already be bound
+			(ensure (eq? [(try (with-globals (no-such-global 1) #n)) 0] 'err))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn errors() {
+	run(|| {
+		glsp::load_str(r#"
+			(def *level* 'info)
+
+			(let result (try
+			  (with-globals (*level* 'debug)
+			    (with-globals (*level* 'trace)
+			      (bail 'failed)))))
+			(ensure (eq? result '(err failed)))
+			(ensure (eq? *level* 'info))
+
+			(defn failing ()
+			  (with-global *level* 'warn)
+			  (bail 'failed))
+			(ensure (eq? (try (failing)) '(err failed)))
+			(ensure (eq? *level* 'info))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn coroutines() {
+	run(|| {
+		glsp::load_str(r#"
+			(def *level* 'info)
+
+			(defn worker ()
+			  (with-globals (*level* 'debug)
+			    (yield *level*)
+			    (yield *level*))
+			  *level*)
+
+			;the override travels with the coroutine, rather than leaking into the resumer
+			(let co (worker))
+			(ensure (eq? (coro-run co) 'debug))
+			(ensure (eq? *level* 'info))
+			(= *level* 'warn)
+			(ensure (eq? (coro-run co) 'debug))
+			(ensure (eq? *level* 'warn))
+			(ensure (eq? (coro-run co) 'warn))
+			(ensure (eq? *level* 'warn))
+
+			;overrides in the resumer and the coroutine are independent
+			(let co (worker))
+			(with-globals (*level* 'error)
+			  (ensure (eq? (coro-run co) 'debug))
+			  (ensure (eq? *level* 'error)))
+			(ensure (eq? *level* 'warn))
+			(ensure (eq? (coro-run co) 'debug))
+			(ensure (eq? *level* 'warn))
+
+			;finishing a paused coroutine doesn't clobber the resumer's value
+			(= *level* 'trace)
+			(coro-finish! co)
+			(ensure (eq? *level* 'trace))
+
+			;an error inside the coroutine restores the value it saw when it was resumed
+			(defn failing-worker ()
+			  (with-globals (*level* 'debug)
+			    (yield)
+			    (bail 'failed)))
+
+			(let co (failing-worker))
+			(coro-run co)
+			(= *level* 'info)
+			(ensure (eq? (try (coro-run co)) '(err failed)))
+			(ensure (eq? *level* 'info))
+		"#, "test.glsp")?;
+
+		Ok(())
+	});
+}
+
+#[test]
+fn rust_api() {
+	run(|| {
+		glsp::bind_global("*level*", glsp::sym("info")?)?;
+		glsp::load_str("(defn level () *level*)", "test.glsp")?;
+
+		let level: Sym = glsp::with_global("*level*", glsp::sym("debug")?, || {
+			glsp::call(&glsp::global::<_, Root<GFn>>("level")?, &())
+		})?;
+		assert_eq!(level, glsp::sym("debug")?);
+		assert_eq!(glsp::global::<_, Sym>("*level*")?, glsp::sym("info")?);
+
+		let result: GResult<()> = glsp::with_global("*level*", 10, || {
+			assert_eq!(glsp::global::<_, i32>("*level*")?, 10);
+			Err(error!("failed"))
+		});
+		assert!(result.is_err());
+		assert_eq!(glsp::global::<_, Sym>("*level*")?, glsp::sym("info")?);
+
+		assert!(glsp::with_global("no-such-global", 1, || Ok(())).is_err());
+
+		Ok(())
+	});
+}
@@ -0,0 +1,24 @@
+#!/bin/sh
+
+# runs the test suite in each supported configuration of the interpreter. flos are f32 by
+# default, or f64 with the "f64-flos" feature, and the two widths share most of their code paths,
+# so both need to be tested before each release.
+#
+# usage: scripts/test-all.sh [extra cargo test arguments]
+
+set -eu
+
+cd "$(dirname "$0")/.."
+
+run() {
+	echo
+	echo "==> cargo test $*"
+	cargo test --workspace "$@"
+}
+
+FULL="glsp/compiler glsp/serde glsp/unsafe-internals"
+
+run "$@"
+run --features "glsp/f64-flos" "$@"
+run --features "$FULL" "$@"
+run --features "$FULL glsp/f64-flos" "$@"
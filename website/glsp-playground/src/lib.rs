@@ -98,7 +98,7 @@ fn play_draw_wrapper(sprite: Sym, x: Num, y: Num, mut flags: &[Val]) -> GResult<
 			}
 			[Val::Sym(flag), Val::Flo(frame_f), ..] if &*flag.name() == "frame" => {
 				ensure!(frame.is_none(), "duplicate 'frame flag passed to play:draw");
-				frame = Some(*frame_f);
+				frame = Some(*frame_f as f32);
 				flags = &flags[2..];
 			}
 			_ => bail!("invalid flags passed to play:draw: {:?}", flags)
@@ -0,0 +1,137 @@
+//! Checks that the engine works on `wasm32-unknown-unknown`, where there's no filesystem, no 
+//! clock and no way to block the current thread. To run these tests:
+//!
+//!     cargo install wasm-bindgen-cli --version <the wasm-bindgen version in Cargo.lock>
+//!     CARGO_TARGET_WASM32_UNKNOWN_UNKNOWN_RUNNER=wasm-bindgen-test-runner \
+//!         cargo test --target wasm32-unknown-unknown
+//!
+//! The same tests can be run natively with a plain `cargo test`.
+
+use glsp::GSend;
+use glsp::prelude::*;
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen_test::wasm_bindgen_test as test;
+
+fn run<F: FnOnce() -> GResult<()> + GSend>(f: F) {
+	let runtime = Runtime::new();
+	runtime.run(f).expect("the test failed");
+}
+
+fn eval<T: FromVal>(text: &str) -> GResult<T> {
+	let vals = glsp::parse_all(text, Some("test.glsp"))?;
+	let val = glsp::eval_multi(&vals, None)?;
+	T::from_val(&val)
+}
+
+#[test]
+fn eval_basics() {
+	run(|| {
+		let sum: i32 = eval("(let xs (arr 1 2 3)) (+ ..xs)")?;
+		assert_eq!(sum, 6);
+
+		let text: String = eval(r#"(let n 5) (str "n is {n}")"#)?;
+		assert_eq!(text, "n is 5");
+
+		Ok(())
+	});
+}
+
+#[test]
+fn classes() {
+	run(|| {
+		let hp: i32 = eval(r#"
+			(defclass Goblin
+			  (field hp 10)
+			  (meth hurt (n)
+			    (dec! @hp n)))
+
+			(let goblin (Goblin))
+			(.hurt goblin 3)
+			[goblin 'hp]
+		"#)?;
+		assert_eq!(hp, 7);
+
+		Ok(())
+	});
+}
+
+#[test]
+fn coroutines() {
+	run(|| {
+		let output: String = eval(r#"
+			(defn counter ()
+			  (yield 1)
+			  (yield 2)
+			  3)
+
+			(let coro (counter))
+			(str (coro-run coro) (coro-run coro) (coro-run coro) (coro-state coro))
+		"#)?;
+		assert_eq!(output, "1 2 3 finished");
+
+		Ok(())
+	});
+}
+
+#[test]
+fn compiled_recording() {
+	//load_and_compile seeds the gensym counter, which uses the system clock on other platforms
+	let bytes = Runtime::new().run(|| {
+		let (_, bytes) = glsp::load_and_compile_str(r#"
+			(defmacro twice (form)
+			  `(do ~form ~form))
+
+			(defn triple (x)
+			  (let total 0)
+			  (twice (inc! total x))
+			  (+ total x))
+		"#, "triple.glsp")?;
+
+		Ok(bytes)
+	}).expect("compilation failed");
+
+	run(move || {
+		glsp::load_compiled(&bytes)?;
+		let n: i32 = eval("(triple 5)")?;
+		assert_eq!(n, 15);
+
+		Ok(())
+	});
+}
+
+#[test]
+fn loader() {
+	static SCRIPTS: IncludedDir = IncludedDir::new(&[
+		IncludedFile::new("scripts/prelude.glsp", r#"(def greeting "hello")"#),
+		IncludedFile::new("scripts/main.glsp", r#"(load "prelude.glsp") greeting"#)
+	]);
+
+	run(|| {
+		glsp::set_file_resolution(glsp::FileResolution::RelativeToFile);
+		glsp::set_loader(SCRIPTS.loader());
+
+		let greeting: String = FromVal::from_val(&glsp::load("scripts/main.glsp")?)?;
+		assert_eq!(greeting, "hello");
+		assert!(glsp::load("scripts/missing.glsp").is_err());
+
+		Ok(())
+	});
+}
+
+#[test]
+fn clock_and_rng() {
+	run(|| {
+		glsp::set_clock(|| 12.5);
+		let time: f32 = eval("(time)")?;
+		assert_eq!(time, 12.5);
+
+		let elapsed: f32 = eval("(let sw (stopwatch)) (elapsed sw)")?;
+		assert_eq!(elapsed, 0.0);
+
+		let n: i32 = eval("(rand 10)")?;
+		assert!(n >= 0 && n < 10);
+
+		Ok(())
+	});
+}